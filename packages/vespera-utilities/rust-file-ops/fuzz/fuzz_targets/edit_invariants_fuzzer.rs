@@ -0,0 +1,35 @@
+//! Fuzz target for edit invariants
+//!
+//! Feeds arbitrary content and operation sequences through
+//! `verify_edit_invariants`, so any panic or broken invariant in
+//! `StringMatcher`, `SingleEditor` or `MultiEditor` becomes a crash.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use arbitrary::Arbitrary;
+use vespera_file_ops::*;
+
+/// Structured input for invariant fuzzing
+#[derive(Arbitrary, Debug)]
+struct InvariantInput {
+    /// The text content to edit
+    content: String,
+    /// (pattern, replacement, replace_all) for each operation
+    operations: Vec<(String, String, bool)>,
+}
+
+fuzz_target!(|input: InvariantInput| {
+    // The missed-occurrence check is quadratic; keep inputs small
+    if input.content.len() > 4096 || input.operations.len() > 16 {
+        return;
+    }
+
+    let operations: Vec<EditOperation> = input.operations.into_iter()
+        .map(|(old, new, replace_all)| EditOperation::new(old, new, replace_all))
+        .collect();
+
+    if let Err(violation) = verify_edit_invariants(&input.content, &operations) {
+        panic!("{} for {:?}", violation, operations);
+    }
+});
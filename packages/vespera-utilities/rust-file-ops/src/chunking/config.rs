@@ -19,6 +19,10 @@ pub struct ChunkingConfig {
     
     /// Format of the input document
     pub format: DocumentFormat,
+    
+    /// Cleaners and transformers run on every chunk's content, in order
+    #[serde(default)]
+    pub post_processors: Vec<PostProcessor>,
 }
 
 impl Default for ChunkingConfig {
@@ -29,10 +33,19 @@ impl Default for ChunkingConfig {
             chunk_strategy: ChunkStrategy::SentenceBoundary,
             preserve_metadata: true,
             format: DocumentFormat::PlainText,
+            post_processors: Vec::new(),
         }
     }
 }
 
+impl ChunkingConfig {
+    /// Append a post-processing step
+    pub fn with_post_processor(mut self, processor: PostProcessor) -> Self {
+        self.post_processors.push(processor);
+        self
+    }
+}
+
 /// Strategy for determining chunk boundaries
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChunkStrategy {
@@ -74,6 +87,35 @@ pub enum DocumentFormat {
     Json,
 }
 
+/// Post-processing step applied to chunk content after splitting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PostProcessor {
+    /// Remove HTML tags, scripts and styles, keeping the text
+    StripHtml,
+    
+    /// Collapse runs of spaces and blank lines and trim trailing whitespace
+    NormalizeWhitespace,
+    
+    /// Replace email addresses and phone numbers with placeholders
+    MaskPii,
+    
+    /// Drop lines such as copyright notices, cookie banners and navigation links
+    RemoveBoilerplate,
+}
+
+impl PostProcessor {
+    /// Look up a step by its snake_case name, e.g. "mask_pii"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "strip_html" => Some(Self::StripHtml),
+            "normalize_whitespace" => Some(Self::NormalizeWhitespace),
+            "mask_pii" => Some(Self::MaskPii),
+            "remove_boilerplate" => Some(Self::RemoveBoilerplate),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +138,7 @@ mod tests {
             chunk_strategy: ChunkStrategy::ConversationBreak,
             preserve_metadata: false,
             format: DocumentFormat::DiscordHtml,
+            post_processors: vec![PostProcessor::StripHtml, PostProcessor::MaskPii],
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -104,5 +147,6 @@ mod tests {
         assert_eq!(deserialized.max_chunk_size, config.max_chunk_size);
         assert_eq!(deserialized.chunk_strategy, config.chunk_strategy);
         assert_eq!(deserialized.format, config.format);
+        assert_eq!(deserialized.post_processors, config.post_processors);
     }
 }
\ No newline at end of file
@@ -0,0 +1,414 @@
+//! Language and structure metadata for chunks
+//!
+//! After a strategy has split a document, [`enrich_metadata`] fills in what
+//! RAG indexing filters and boosts on: the natural language of each chunk's
+//! prose, the programming language of code chunks, the Markdown headings a
+//! chunk sits under, and the function it starts in. Everything is a cheap
+//! heuristic over the original text, so a field is left empty rather than
+//! guessed when the signal is weak.
+
+use crate::chunking::DocumentChunk;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Fewest letters worth guessing a natural language from
+const MIN_LETTERS: usize = 20;
+
+/// Fill `language`, `code_language`, `heading_path` and `function_name` for
+/// chunks of `original`, using each chunk's byte range
+pub fn enrich_metadata(chunks: &mut [DocumentChunk], original: &str) {
+    let structure = Structure::scan(original);
+
+    for chunk in chunks {
+        let (start, end) = chunk.metadata.byte_range;
+        let end = end.min(original.len());
+        let anchor = original.as_bytes()[start.min(end)..end]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .map_or(start.min(end), |i| start + i);
+
+        chunk.metadata.heading_path = structure.heading_path(anchor);
+
+        let code = structure.code_at(original, anchor, end);
+        chunk.metadata.code_language = code.as_ref().and_then(|c| c.language.clone());
+        chunk.metadata.function_name = code.as_ref().and_then(|c| {
+            let code = &original[c.region.0..c.region.1];
+            enclosing_function(code, anchor.checked_sub(c.region.0), end.saturating_sub(c.region.0))
+        });
+
+        let prose = match &code {
+            Some(c) if c.whole_chunk => String::new(),
+            _ => strip_fenced_code(&chunk.content),
+        };
+        chunk.metadata.language = detect_natural_language(&prose).map(String::from);
+    }
+}
+
+/// Guess the natural language of `text` as an ISO 639-1 code
+///
+/// Non-Latin scripts are recognised by their characters; Latin-script text
+/// by common function words of English, Spanish, French, German, Italian,
+/// Portuguese and Dutch.
+pub fn detect_natural_language(text: &str) -> Option<&'static str> {
+    let mut counts = [0usize; 11];
+    const LATIN: usize = 0;
+    const HAN: usize = 1;
+    const KANA: usize = 2;
+    const HANGUL: usize = 3;
+    const SCRIPTS: [(&str, usize); 5] = [("ar", 5), ("el", 6), ("he", 7), ("hi", 8), ("th", 9)];
+
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        let slot = match c as u32 {
+            0x3040..=0x30FF => KANA,
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => HAN,
+            0xAC00..=0xD7AF | 0x1100..=0x11FF => HANGUL,
+            // Ukrainian-only letters: є і ї ґ
+            0x0454 | 0x0456 | 0x0457 | 0x0491 | 0x0404 | 0x0406 | 0x0407 | 0x0490 => 10,
+            0x0400..=0x04FF => 4,
+            0x0600..=0x06FF => 5,
+            0x0370..=0x03FF => 6,
+            0x0590..=0x05FF => 7,
+            0x0900..=0x097F => 8,
+            0x0E00..=0x0E7F => 9,
+            _ => LATIN,
+        };
+        counts[slot] += 1;
+    }
+    let total: usize = counts.iter().sum();
+    if total < MIN_LETTERS {
+        return None;
+    }
+
+    if counts[KANA] > 0 && (counts[KANA] + counts[HAN]) * 2 > total {
+        return Some("ja");
+    }
+    if counts[HANGUL] * 2 > total {
+        return Some("ko");
+    }
+    if counts[HAN] * 2 > total {
+        return Some("zh");
+    }
+    if counts[4] + counts[10] > 0 && (counts[4] + counts[10]) * 2 > total {
+        return Some(if counts[10] > 0 { "uk" } else { "ru" });
+    }
+    if let Some(&(code, _)) = SCRIPTS.iter().find(|&&(_, slot)| counts[slot] * 2 > total) {
+        return Some(code);
+    }
+    if counts[LATIN] * 2 <= total {
+        return None;
+    }
+
+    const STOPWORDS: [(&str, &[&str]); 7] = [
+        ("en", &["the", "and", "of", "to", "is", "in", "that", "it", "for", "with", "was", "on", "are", "this", "be", "by", "not", "you", "have", "from", "which"]),
+        ("es", &["el", "la", "de", "que", "y", "en", "los", "las", "del", "se", "por", "un", "una", "es", "con", "para", "su", "al", "lo", "como", "más"]),
+        ("fr", &["le", "la", "les", "de", "des", "et", "est", "un", "une", "du", "en", "que", "qui", "dans", "pour", "pas", "sur", "ce", "avec", "sont", "au", "il"]),
+        ("de", &["der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "den", "von", "mit", "sich", "des", "auf", "für", "im", "dem", "auch", "wird", "ich"]),
+        ("it", &["il", "la", "di", "che", "e", "è", "un", "una", "per", "non", "con", "del", "della", "sono", "gli", "le", "da", "si", "nel", "alla", "questo"]),
+        ("pt", &["o", "a", "os", "as", "de", "que", "e", "do", "da", "em", "um", "uma", "para", "com", "não", "por", "mais", "dos", "das", "é", "ao"]),
+        ("nl", &["de", "het", "een", "en", "van", "is", "dat", "niet", "op", "te", "zijn", "voor", "met", "die", "ook", "als", "aan", "er", "maar", "wordt"]),
+    ];
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mut scores: Vec<(&str, usize)> = STOPWORDS.iter()
+        .map(|(code, list)| (*code, words.iter().filter(|w| list.contains(&w.as_str())).count()))
+        .collect();
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    match scores.as_slice() {
+        [(code, best), (_, second), ..] if *best >= 2 && best > second => Some(code),
+        _ => None,
+    }
+}
+
+/// Guess the programming language of `text`, or `None` if it doesn't
+/// look like code
+pub fn detect_code_language(text: &str) -> Option<&'static str> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    if lines.is_empty() {
+        return None;
+    }
+    // Code has lines that end in punctuation or open/close blocks
+    let code_like = lines.iter()
+        .filter(|l| l.ends_with([';', '{', '}', ':', ')', ',']) || l.starts_with(['#', '/', '}', '@']))
+        .count();
+    if code_like * 2 < lines.len() {
+        return None;
+    }
+
+    const SIGNALS: [(&str, &[&str]); 8] = [
+        ("rust", &["fn ", "let mut ", "impl ", "pub fn ", "use std::", "&self", "#[derive", "-> ", "Some(", "Ok("]),
+        ("python", &["def ", "import ", "self.", "elif ", "None", "__init__", "print(", "lambda "]),
+        ("javascript", &["function ", "const ", "=> ", "console.log", "require(", "export ", "===", "undefined"]),
+        ("typescript", &["interface ", ": string", ": number", "export type ", "implements ", "readonly "]),
+        ("go", &["func ", "package ", ":= ", "fmt.", "err != nil", "chan "]),
+        ("java", &["public class ", "private ", "System.out", "public static void", "import java", "@Override"]),
+        ("c", &["#include", "int main", "printf(", "malloc(", "NULL", "sizeof("]),
+        ("shell", &["#!/bin/", "echo ", "; then", "\nfi", "esac", "$("]),
+    ];
+    let best = SIGNALS.iter()
+        .map(|(language, signals)| (*language, signals.iter().filter(|s| text.contains(**s)).count()))
+        .max_by_key(|&(_, score)| score)?;
+    (best.1 >= 2).then_some(best.0)
+}
+
+/// Normalise a code fence info string to a language name
+fn fence_language(info: &str) -> Option<String> {
+    let tag = info.split(|c: char| c.is_whitespace() || c == ',' || c == '{').next()?.to_lowercase();
+    let language = match tag.as_str() {
+        "" => return None,
+        "rs" => "rust",
+        "py" | "python3" => "python",
+        "js" | "jsx" | "mjs" | "node" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "sh" | "bash" | "zsh" | "console" => "shell",
+        "golang" => "go",
+        "c++" | "cc" | "cxx" => "cpp",
+        "yml" => "yaml",
+        other => other,
+    };
+    Some(language.to_string())
+}
+
+struct Heading {
+    offset: usize,
+    level: usize,
+    text: String,
+}
+
+struct Fence {
+    /// Start of the opening fence line
+    start: usize,
+    /// Byte range of the code between the fences
+    content: (usize, usize),
+    language: Option<String>,
+}
+
+/// Code found at a chunk's position
+struct CodeContext {
+    language: Option<String>,
+    /// Byte range of `original` to look for function definitions in
+    region: (usize, usize),
+    /// Whether the chunk starts inside this code, rather than only containing some
+    whole_chunk: bool,
+}
+
+/// Markdown headings and fenced code blocks of a document
+struct Structure {
+    headings: Vec<Heading>,
+    fences: Vec<Fence>,
+}
+
+impl Structure {
+    fn scan(text: &str) -> Self {
+        let mut headings = Vec::new();
+        let mut fences = Vec::new();
+        let mut open: Option<(usize, usize, String, Option<String>)> = None;
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let trimmed = line.trim_start_matches(' ');
+            let body = trimmed.trim_end();
+
+            if line.len() - trimmed.len() <= 3 {
+                let marker_len = body.chars().take_while(|&c| c == '`' || c == '~').count();
+                if marker_len >= 3 && body[..marker_len].chars().all(|c| c == body.as_bytes()[0] as char) {
+                    let marker = body[..marker_len].to_string();
+                    match &open {
+                        Some((start, content_start, open_marker, language))
+                            if body.len() == marker_len && marker.starts_with(open_marker.as_str()) =>
+                        {
+                            fences.push(Fence {
+                                start: *start,
+                                content: (*content_start, line_start),
+                                language: language.clone(),
+                            });
+                            open = None;
+                        }
+                        Some(_) => {}
+                        None => open = Some((line_start, offset, marker, fence_language(&body[marker_len..]))),
+                    }
+                    continue;
+                }
+            }
+
+            if open.is_none() {
+                let level = body.chars().take_while(|&c| c == '#').count();
+                if (1..=6).contains(&level) && body[level..].starts_with(' ') {
+                    let title = body[level..].trim().trim_end_matches('#').trim_end();
+                    if !title.is_empty() {
+                        headings.push(Heading { offset: line_start, level, text: title.to_string() });
+                    }
+                }
+            }
+        }
+        if let Some((start, content_start, _, language)) = open {
+            fences.push(Fence { start, content: (content_start, text.len()), language });
+        }
+
+        Self { headings, fences }
+    }
+
+    /// Titles of the headings in effect at `offset`, outermost first
+    fn heading_path(&self, offset: usize) -> Vec<String> {
+        let mut path: Vec<&Heading> = Vec::new();
+        for heading in self.headings.iter().take_while(|h| h.offset <= offset) {
+            while path.last().is_some_and(|top| top.level >= heading.level) {
+                path.pop();
+            }
+            path.push(heading);
+        }
+        path.into_iter().map(|h| h.text.clone()).collect()
+    }
+
+    /// The code a chunk starting at `anchor` and ending at `end` starts in
+    /// or contains
+    fn code_at(&self, original: &str, anchor: usize, end: usize) -> Option<CodeContext> {
+        let language_of = |fence: &Fence| fence.language.clone()
+            .or_else(|| detect_code_language(&original[fence.content.0..fence.content.1]).map(String::from));
+
+        if let Some(fence) = self.fences.iter().find(|f| f.start <= anchor && anchor < f.content.1) {
+            return Some(CodeContext {
+                language: language_of(fence),
+                region: fence.content,
+                whole_chunk: end <= fence.content.1,
+            });
+        }
+        if let Some(fence) = self.fences.iter().find(|f| anchor <= f.start && f.start < end) {
+            return Some(CodeContext {
+                language: language_of(fence),
+                region: fence.content,
+                whole_chunk: false,
+            });
+        }
+        if self.fences.is_empty() && self.headings.is_empty() {
+            let language = detect_code_language(&original[anchor..end])?;
+            return Some(CodeContext {
+                language: Some(language.to_string()),
+                region: (0, original.len()),
+                whole_chunk: true,
+            });
+        }
+        None
+    }
+}
+
+/// The last function defined at or before `anchor` in `code`, or else the
+/// first one defined before `end`; `anchor` is `None` when the chunk starts
+/// before the code does
+fn enclosing_function(code: &str, anchor: Option<usize>, end: usize) -> Option<String> {
+    static DEFINITIONS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let definitions = DEFINITIONS.get_or_init(|| {
+        [
+            // fn / def / func / function, with common modifiers
+            r"(?m)^[ \t]*(?:(?:pub(?:\([^)]*\))?|async|unsafe|const|export|default|static|public|private|protected)\s+)*(?:fn|def|func|function\*?)\s+(?:\([^)]*\)\s*)?([A-Za-z_$][\w$]*)",
+            // const name = (...) => / function
+            r"(?m)^[ \t]*(?:export\s+)?(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*=>|[A-Za-z_$][\w$]*\s*=>)",
+            // C-family: type name(args) {
+            r"(?m)^[ \t]*(?:[A-Za-z_][\w<>\[\]:*&,]*\s+)+\**([A-Za-z_]\w*)\s*\([^;{}]*\)\s*(?:const\s*)?(?:throws\s+[\w.,\s]+)?\{",
+            // shell: name() {
+            r"(?m)^[ \t]*(?:function\s+)?([A-Za-z_][\w-]*)\s*\(\)\s*\{",
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("valid definition pattern"))
+        .collect()
+    });
+    const NOT_NAMES: [&str; 7] = ["if", "for", "while", "switch", "catch", "return", "else"];
+
+    let mut found: Vec<(usize, &str)> = definitions.iter()
+        .flat_map(|regex| regex.captures_iter(code))
+        .filter_map(|captures| {
+            let name = captures.get(1)?;
+            let start = captures.get(0)?.start();
+            (!NOT_NAMES.contains(&name.as_str())).then_some((start, name.as_str()))
+        })
+        .collect();
+    found.sort_by_key(|&(offset, _)| offset);
+
+    anchor
+        .and_then(|anchor| found.iter().rev().find(|&&(offset, _)| offset <= anchor))
+        .or_else(|| found.iter().find(|&&(offset, _)| offset < end))
+        .map(|&(_, name)| name.to_string())
+}
+
+/// `text` without the content of fenced code blocks
+fn strip_fenced_code(text: &str) -> String {
+    let structure = Structure::scan(text);
+    let mut prose = String::with_capacity(text.len());
+    let mut last = 0;
+    for fence in &structure.fences {
+        prose.push_str(&text[last..fence.start]);
+        last = fence.content.1;
+    }
+    prose.push_str(&text[last.min(text.len())..]);
+    prose
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::{chunk_document, ChunkStrategy, ChunkingConfig};
+
+    #[test]
+    fn test_natural_language() {
+        assert_eq!(detect_natural_language("The quick brown fox jumps over the lazy dog and runs to the forest."), Some("en"));
+        assert_eq!(detect_natural_language("El perro corre por el parque con los niños de la escuela."), Some("es"));
+        assert_eq!(detect_natural_language("Der Hund läuft mit den Kindern durch den Park und ist nicht müde."), Some("de"));
+        assert_eq!(detect_natural_language("Это предложение написано на русском языке для проверки."), Some("ru"));
+        assert_eq!(detect_natural_language("これは日本語の文章です。ひらがなとカタカナを含みます。"), Some("ja"));
+        assert_eq!(detect_natural_language("ok"), None);
+    }
+
+    #[test]
+    fn test_code_language() {
+        assert_eq!(detect_code_language("fn main() {\n    let mut x = 1;\n    println!(\"{}\", x);\n}\n"), Some("rust"));
+        assert_eq!(detect_code_language("def run(self):\n    if self.ready:\n        print(self.name)\n"), Some("python"));
+        assert_eq!(detect_code_language("This is a plain sentence.\nAnd another one here.\n"), None);
+    }
+
+    #[test]
+    fn test_markdown_enrichment() {
+        let document = "# Guide\n\nIntro text for the guide, which is written in English.\n\n\
+                        ## Install\n\nRun the installer and wait for it to finish.\n\n\
+                        ```rust\nfn install() {\n    run();\n}\n```\n\n\
+                        ## Usage\n\nThe tool is used from the command line.\n";
+        let config = ChunkingConfig {
+            max_chunk_size: 60,
+            overlap_size: 0,
+            chunk_strategy: ChunkStrategy::ParagraphBoundary,
+            ..Default::default()
+        };
+        let chunks = chunk_document(document, &config).unwrap();
+
+        let install = chunks.iter().find(|c| c.content.contains("Run the installer")).unwrap();
+        assert_eq!(install.metadata.heading_path, vec!["Guide", "Install"]);
+        assert_eq!(install.metadata.language.as_deref(), Some("en"));
+
+        let code = chunks.iter().find(|c| c.content.contains("fn install")).unwrap();
+        assert_eq!(code.metadata.code_language.as_deref(), Some("rust"));
+        assert_eq!(code.metadata.function_name.as_deref(), Some("install"));
+
+        let usage = chunks.iter().find(|c| c.content.contains("command line")).unwrap();
+        assert_eq!(usage.metadata.heading_path, vec!["Guide", "Usage"]);
+        assert_eq!(usage.metadata.code_language, None);
+    }
+
+    #[test]
+    fn test_source_code_enrichment() {
+        let source = "import os\n\ndef first(self):\n    return None\n\ndef second(self):\n    print(self.value)\n    return self.value\n";
+        let config = ChunkingConfig {
+            max_chunk_size: 40,
+            overlap_size: 0,
+            chunk_strategy: ChunkStrategy::ParagraphBoundary,
+            ..Default::default()
+        };
+        let chunks = chunk_document(source, &config).unwrap();
+        let second = chunks.iter().find(|c| c.content.contains("def second")).unwrap();
+        assert_eq!(second.metadata.code_language.as_deref(), Some("python"));
+        assert_eq!(second.metadata.function_name.as_deref(), Some("second"));
+        assert_eq!(second.metadata.language, None);
+    }
+}
@@ -0,0 +1,326 @@
+//! Map-reduce over an [`LLMProcessingSession`]
+//!
+//! The caller supplies a map step, run once per pending chunk on its
+//! prepared [`LLMInput`], and a combine step that merges the per-chunk
+//! results. [`map_reduce`] sends chunks in session order, retries failed map
+//! calls, records results and failures in the session so an interrupted run
+//! can be resumed, and hands the combine step every successful result in
+//! document order together with where it came from.
+
+use super::{prepare_for_llm_with, LLMInput, LLMProcessingSession, Tokenizer};
+use crate::chunking::DocumentChunk;
+use crate::error::VesperaError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::time::Duration;
+
+/// Options for [`map_reduce`]
+#[derive(Debug, Clone)]
+pub struct MapReduceOptions {
+    /// Token budget for each map input
+    pub context_window: usize,
+    /// Include selected chunk metadata in map inputs
+    pub include_metadata: bool,
+    /// Extra attempts after a map call fails
+    pub max_retries: u32,
+    /// Pause before each retry
+    pub retry_delay: Duration,
+    /// Keep mapping other chunks after one fails for good
+    pub continue_on_error: bool,
+}
+
+impl Default for MapReduceOptions {
+    fn default() -> Self {
+        Self {
+            context_window: 8000,
+            include_metadata: true,
+            max_retries: 2,
+            retry_delay: Duration::from_millis(500),
+            continue_on_error: true,
+        }
+    }
+}
+
+/// Where a mapped result came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkProvenance {
+    pub chunk_id: String,
+    pub chunk_index: usize,
+    pub source_file: String,
+    pub byte_range: (usize, usize),
+}
+
+/// A map result with its provenance, as passed to the combine step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedChunk<M> {
+    pub provenance: ChunkProvenance,
+    pub result: M,
+}
+
+/// A chunk the map step gave up on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkFailure {
+    pub chunk_id: String,
+    /// Attempts made in this run; 0 for failures carried over from a
+    /// resumed session
+    pub attempts: u32,
+    pub error: String,
+}
+
+/// Outcome of [`map_reduce`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapReduceOutcome<R> {
+    /// Combined result, if any chunk was mapped and combining succeeded
+    pub result: Option<R>,
+    /// Provenance of every chunk that went into `result`, in document order
+    pub provenance: Vec<ChunkProvenance>,
+    /// Chunks left out of `result`
+    pub failures: Vec<ChunkFailure>,
+    /// Why combining failed, if it did
+    pub combine_error: Option<String>,
+    /// Whether every chunk was mapped and combined
+    pub complete: bool,
+}
+
+/// Map every pending chunk of `session` and combine the results
+///
+/// Results from earlier runs of the same session are reused, so a session
+/// saved after an interruption picks up where it stopped. Chunks that keep
+/// failing are moved to [`LLMProcessingSession::chunks_failed`]; with
+/// `continue_on_error` off, the first such chunk stops the map phase and
+/// leaves the rest pending, and nothing is combined.
+///
+/// Errors only if a stored result can't be read back as `M`.
+pub fn map_reduce<M, R, E, F, C>(
+    session: &mut LLMProcessingSession,
+    chunks: &[DocumentChunk],
+    options: &MapReduceOptions,
+    tokenizer: &dyn Tokenizer,
+    mut map: F,
+    combine: C,
+) -> Result<MapReduceOutcome<R>, VesperaError>
+where
+    M: Serialize + DeserializeOwned,
+    E: Display,
+    F: FnMut(&DocumentChunk, &LLMInput) -> Result<M, E>,
+    C: FnOnce(&[MappedChunk<M>]) -> Result<R, E>,
+{
+    let mut failures = Vec::new();
+    let mut stopped = false;
+
+    while let Some(id) = session.next_pending().map(String::from) {
+        let Some(chunk) = chunks.iter().find(|chunk| chunk.id == id) else {
+            session.mark_failed(&id, "chunk not found");
+            failures.push(ChunkFailure { chunk_id: id, attempts: 0, error: "chunk not found".to_string() });
+            continue;
+        };
+
+        let (outcome, attempts) = match prepare_for_llm_with(chunk, options.context_window, options.include_metadata, tokenizer) {
+            Ok(input) => map_with_retries(chunk, &input, options, &mut map),
+            Err(e) => (Err(e.to_string()), 0),
+        };
+        let stored = outcome.and_then(|result| serde_json::to_value(result).map_err(|e| e.to_string()));
+        match stored {
+            Ok(value) => {
+                session.mark_processed(&id, Some(value));
+            }
+            Err(error) => {
+                session.mark_failed(&id, error.clone());
+                failures.push(ChunkFailure { chunk_id: id, attempts, error });
+                if !options.continue_on_error {
+                    stopped = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Failures recorded by earlier runs
+    for (id, error) in &session.chunks_failed {
+        if !failures.iter().any(|failure| &failure.chunk_id == id) {
+            failures.push(ChunkFailure { chunk_id: id.clone(), attempts: 0, error: error.clone() });
+        }
+    }
+
+    let mut mapped = Vec::new();
+    for chunk in chunks {
+        let Some(value) = session.extracted_data.get(&chunk.id) else {
+            continue;
+        };
+        let result = M::deserialize(value).map_err(|e| VesperaError::InvalidInput {
+            message: format!("Stored result does not match the map output type: {}", e),
+            context: Some(chunk.id.clone()),
+        })?;
+        mapped.push(MappedChunk {
+            provenance: ChunkProvenance {
+                chunk_id: chunk.id.clone(),
+                chunk_index: chunk.metadata.chunk_index,
+                source_file: chunk.metadata.source_file.clone(),
+                byte_range: chunk.metadata.byte_range,
+            },
+            result,
+        });
+    }
+
+    let provenance: Vec<ChunkProvenance> = mapped.iter().map(|m| m.provenance.clone()).collect();
+    let (result, combine_error) = if stopped || mapped.is_empty() {
+        (None, None)
+    } else {
+        match combine(&mapped) {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e.to_string())),
+        }
+    };
+
+    Ok(MapReduceOutcome {
+        complete: result.is_some() && failures.is_empty() && session.is_complete(),
+        result,
+        provenance,
+        failures,
+        combine_error,
+    })
+}
+
+/// Run `map` until it succeeds or the retries run out, returning the
+/// outcome and the number of attempts
+fn map_with_retries<M, E, F>(
+    chunk: &DocumentChunk,
+    input: &LLMInput,
+    options: &MapReduceOptions,
+    map: &mut F,
+) -> (Result<M, String>, u32)
+where
+    E: Display,
+    F: FnMut(&DocumentChunk, &LLMInput) -> Result<M, E>,
+{
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match map(chunk, input) {
+            Ok(result) => return (Ok(result), attempts),
+            Err(e) if attempts > options.max_retries => return (Err(e.to_string()), attempts),
+            Err(_) => std::thread::sleep(options.retry_delay),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::llm::CharEstimateTokenizer;
+    use crate::chunking::{chunk_document, ChunkStrategy, ChunkingConfig};
+    use std::collections::HashMap;
+
+    fn chunks() -> Vec<DocumentChunk> {
+        let text = "Alpha paragraph.\n\nBeta paragraph.\n\nGamma paragraph.";
+        let config = ChunkingConfig {
+            max_chunk_size: 20,
+            overlap_size: 0,
+            chunk_strategy: ChunkStrategy::ParagraphBoundary,
+            ..Default::default()
+        };
+        chunk_document(text, &config).unwrap()
+    }
+
+    fn options() -> MapReduceOptions {
+        MapReduceOptions { retry_delay: Duration::ZERO, ..Default::default() }
+    }
+
+    fn first_word(chunk: &DocumentChunk) -> String {
+        chunk.content.split_whitespace().next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_map_reduce_with_retries() {
+        let chunks = chunks();
+        let mut session = LLMProcessingSession::new(&chunks);
+        let mut calls: HashMap<String, u32> = HashMap::new();
+
+        let outcome = map_reduce(
+            &mut session,
+            &chunks,
+            &options(),
+            &CharEstimateTokenizer::default(),
+            |chunk, _input| {
+                let count = calls.entry(chunk.id.clone()).or_default();
+                *count += 1;
+                // The second chunk fails once before succeeding
+                if chunk.metadata.chunk_index == 1 && *count == 1 {
+                    return Err("rate limited".to_string());
+                }
+                Ok(first_word(chunk))
+            },
+            |mapped| Ok::<_, String>(mapped.iter().map(|m| m.result.as_str()).collect::<Vec<_>>().join(" ")),
+        )
+        .unwrap();
+
+        assert_eq!(outcome.result.as_deref(), Some("Alpha Beta Gamma"));
+        assert!(outcome.complete);
+        assert_eq!(outcome.provenance.iter().map(|p| p.chunk_index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(calls[&chunks[1].id], 2);
+    }
+
+    #[test]
+    fn test_partial_failure_and_resume() {
+        let chunks = chunks();
+        let mut session = LLMProcessingSession::new(&chunks);
+        let join = |mapped: &[MappedChunk<String>]| {
+            Ok::<_, String>(mapped.iter().map(|m| m.result.clone()).collect::<Vec<_>>().join(" "))
+        };
+
+        let outcome = map_reduce(
+            &mut session,
+            &chunks,
+            &options(),
+            &CharEstimateTokenizer::default(),
+            |chunk, _| if chunk.metadata.chunk_index == 2 { Err("model error".to_string()) } else { Ok(first_word(chunk)) },
+            join,
+        )
+        .unwrap();
+        assert_eq!(outcome.result.as_deref(), Some("Alpha Beta"));
+        assert!(!outcome.complete);
+        assert_eq!(outcome.failures.len(), 1);
+        assert_eq!(outcome.failures[0].attempts, 3);
+        assert_eq!(outcome.failures[0].error, "model error");
+
+        // Retrying maps only the failed chunk and reuses the others
+        session.retry_failed();
+        let mut mapped_ids = Vec::new();
+        let outcome = map_reduce(
+            &mut session,
+            &chunks,
+            &options(),
+            &CharEstimateTokenizer::default(),
+            |chunk, _| {
+                mapped_ids.push(chunk.id.clone());
+                Ok::<_, String>(first_word(chunk))
+            },
+            join,
+        )
+        .unwrap();
+        assert_eq!(mapped_ids, vec![chunks[2].id.clone()]);
+        assert_eq!(outcome.result.as_deref(), Some("Alpha Beta Gamma"));
+        assert!(outcome.complete);
+    }
+
+    #[test]
+    fn test_stop_on_error() {
+        let chunks = chunks();
+        let mut session = LLMProcessingSession::new(&chunks);
+        let options = MapReduceOptions { continue_on_error: false, max_retries: 0, ..options() };
+
+        let outcome = map_reduce(
+            &mut session,
+            &chunks,
+            &options,
+            &CharEstimateTokenizer::default(),
+            |_, _| Err::<String, _>("unavailable"),
+            |_| Ok::<String, &str>(String::new()),
+        )
+        .unwrap();
+        assert!(outcome.result.is_none());
+        assert_eq!(outcome.failures.len(), 1);
+        assert_eq!(session.chunks_pending.len(), 2);
+    }
+}
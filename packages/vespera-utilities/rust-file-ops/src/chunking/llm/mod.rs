@@ -1,17 +1,217 @@
 //! LLM integration support for document chunking
+//!
+//! [`prepare_for_llm`] renders a chunk as model input that fits a context
+//! window: an optional metadata header, continuation markers when the chunk
+//! is part of a longer document, and the content itself, cut at a word
+//! boundary if it doesn't fit. Token counts come from a [`Tokenizer`]; the
+//! default [`CharEstimateTokenizer`] uses the same four-characters-per-token
+//! estimate as the Discord chunker, and callers can plug in a real tokenizer
+//! with [`prepare_for_llm_with`].
+//!
+//! [`LLMProcessingSession`] tracks which chunks have been sent and what was
+//! extracted from them, and can be saved and loaded to resume a run;
+//! [`map_reduce`] drives a whole session through per-chunk and combine steps.
 
-use crate::error::VesperaError;
+use crate::error::{EditError, VesperaError};
 use crate::chunking::DocumentChunk;
 use std::collections::HashMap;
+use std::path::Path;
 use serde::{Deserialize, Serialize};
 
-/// Prepare a chunk for LLM processing
+mod mapreduce;
+
+pub use mapreduce::{map_reduce, ChunkFailure, ChunkProvenance, MapReduceOptions, MapReduceOutcome, MappedChunk};
+
+/// Marker appended to content cut short to fit the context window
+const TRUNCATION_MARKER: &str = " [...]";
+
+/// Counts tokens the way a model would
+pub trait Tokenizer {
+    /// Number of tokens `text` encodes to
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Estimates tokens as one per `chars_per_token` characters, rounded up
+#[derive(Debug, Clone, Copy)]
+pub struct CharEstimateTokenizer {
+    pub chars_per_token: usize,
+}
+
+impl Default for CharEstimateTokenizer {
+    fn default() -> Self {
+        Self { chars_per_token: 4 }
+    }
+}
+
+impl Tokenizer for CharEstimateTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(self.chars_per_token.max(1))
+    }
+}
+
+impl<F: Fn(&str) -> usize> Tokenizer for F {
+    fn count_tokens(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// Prepare a chunk for LLM processing, estimating tokens from characters
 pub fn prepare_for_llm(
-    _chunk: &DocumentChunk,
-    _context_window: usize,
-    _include_metadata: bool,
+    chunk: &DocumentChunk,
+    context_window: usize,
+    include_metadata: bool,
 ) -> Result<LLMInput, VesperaError> {
-    todo!("LLM preparation implementation")
+    prepare_for_llm_with(chunk, context_window, include_metadata, &CharEstimateTokenizer::default())
+}
+
+/// Prepare a chunk for LLM processing, counting tokens with `tokenizer`
+///
+/// Fails if the header and footer leave no room for content in
+/// `context_window`. Content cut short ends in a `[...]` marker when there
+/// is room for it; either way [`LLMInput::truncated`] is set.
+pub fn prepare_for_llm_with(
+    chunk: &DocumentChunk,
+    context_window: usize,
+    include_metadata: bool,
+    tokenizer: &dyn Tokenizer,
+) -> Result<LLMInput, VesperaError> {
+    let position = &chunk.metadata;
+    let metadata = include_metadata.then(|| selected_metadata(chunk));
+
+    let mut header = String::new();
+    if position.total_chunks > 1 {
+        header.push_str(&format!("[Chunk {} of {}", position.chunk_index + 1, position.total_chunks));
+        if !position.source_file.is_empty() {
+            header.push_str(&format!(" from {}", position.source_file));
+        }
+        header.push_str(if position.chunk_index > 0 { ", continued from the previous chunk]\n" } else { "]\n" });
+    }
+    if let Some(metadata) = &metadata {
+        let mut fields: Vec<_> = metadata.iter().filter(|(key, _)| !POSITION_KEYS.contains(&key.as_str())).collect();
+        fields.sort();
+        for (key, value) in fields {
+            header.push_str(&format!("{}: {}\n", key, value));
+        }
+    }
+    if !header.is_empty() {
+        header.push('\n');
+    }
+    let footer = if position.chunk_index + 1 < position.total_chunks {
+        "\n\n[Continues in the next chunk]"
+    } else {
+        ""
+    };
+
+    // Splitting text can change its token count, so every candidate is
+    // counted as the whole input rather than part by part
+    let assemble = |body: &str| format!("{}{}{}", header, body, footer);
+    let fits = |body: &str| tokenizer.count_tokens(&assemble(body)) <= context_window;
+
+    let overhead = tokenizer.count_tokens(&assemble(""));
+    if overhead >= context_window {
+        return Err(VesperaError::InvalidInput {
+            message: format!(
+                "context window of {} tokens is too small for the {} tokens of chunk headers",
+                context_window, overhead
+            ),
+            context: Some(chunk.id.clone()),
+        });
+    }
+
+    let (body, truncated) = fit_to_budget(&chunk.content, &fits);
+    let content = assemble(&body);
+    let token_count = tokenizer.count_tokens(&content);
+
+    Ok(LLMInput {
+        content,
+        metadata,
+        token_count,
+        truncated,
+    })
+}
+
+/// Metadata keys that describe the chunk's position, shown in the
+/// continuation header rather than as fields
+const POSITION_KEYS: [&str; 3] = ["source_file", "chunk_index", "total_chunks"];
+
+/// The metadata fields worth showing a model, as strings
+fn selected_metadata(chunk: &DocumentChunk) -> HashMap<String, String> {
+    let meta = &chunk.metadata;
+    let mut fields = HashMap::new();
+    fields.insert("chunk_id".to_string(), chunk.id.clone());
+    fields.insert("chunk_index".to_string(), meta.chunk_index.to_string());
+    fields.insert("total_chunks".to_string(), meta.total_chunks.to_string());
+    if !meta.source_file.is_empty() {
+        fields.insert("source_file".to_string(), meta.source_file.clone());
+    }
+    if let Some((start, end)) = &meta.timestamp_range {
+        fields.insert("time_range".to_string(), format!("{} to {}", start, end));
+    }
+    let lists = [
+        ("participants", &meta.participants, ", "),
+        ("topics", &meta.topics, ", "),
+        ("section", &meta.heading_path, " > "),
+    ];
+    for (key, values, separator) in lists {
+        if !values.is_empty() {
+            fields.insert(key.to_string(), values.join(separator));
+        }
+    }
+    let optional = [
+        ("language", &meta.language),
+        ("code_language", &meta.code_language),
+        ("function", &meta.function_name),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            fields.insert(key.to_string(), value.clone());
+        }
+    }
+    fields
+}
+
+/// The longest prefix of `content` that `fits`, cut after whitespace where
+/// possible and marked when shortened
+///
+/// The marker is left off when it doesn't fit by itself. `fits("")` must
+/// hold.
+fn fit_to_budget(content: &str, fits: &dyn Fn(&str) -> bool) -> (String, bool) {
+    if fits(content) {
+        return (content.to_string(), false);
+    }
+
+    let marker = if fits(TRUNCATION_MARKER) { TRUNCATION_MARKER } else { "" };
+    let boundaries: Vec<usize> = content.char_indices().map(|(i, _)| i).collect();
+    let marked = |prefix: &str| format!("{}{}", prefix.trim_end(), marker);
+
+    // Binary search for the longest prefix that fits with the marker
+    let (mut low, mut high) = (0, boundaries.len().saturating_sub(1));
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if fits(&marked(&content[..boundaries[mid]])) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    let end = boundaries.get(low).copied().unwrap_or(0);
+    let mut prefix = &content[..end];
+    if let Some(space) = prefix.rfind(char::is_whitespace).filter(|&space| space > prefix.len() / 2) {
+        prefix = &prefix[..space];
+    }
+
+    // Cutting at whitespace can change how the text tokenizes; shrink
+    // until the final body really fits
+    loop {
+        let body = marked(prefix);
+        if fits(&body) {
+            return (body, true);
+        }
+        match prefix.char_indices().next_back() {
+            Some((last, _)) => prefix = &prefix[..last],
+            None => return (String::new(), true),
+        }
+    }
 }
 
 /// Input prepared for LLM processing
@@ -20,12 +220,218 @@ pub struct LLMInput {
     pub content: String,
     pub metadata: Option<HashMap<String, String>>,
     pub token_count: usize,
+    /// Whether the chunk content was cut short to fit
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 /// Session for tracking LLM processing
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LLMProcessingSession {
     pub chunks_processed: Vec<String>,
     pub chunks_pending: Vec<String>,
     pub extracted_data: HashMap<String, serde_json::Value>,
-}
\ No newline at end of file
+    /// Chunks that gave up, with the last error
+    #[serde(default)]
+    pub chunks_failed: HashMap<String, String>,
+}
+
+impl LLMProcessingSession {
+    /// Start a session over `chunks`, all pending in order
+    pub fn new(chunks: &[DocumentChunk]) -> Self {
+        Self {
+            chunks_pending: chunks.iter().map(|chunk| chunk.id.clone()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// ID of the next chunk to process
+    pub fn next_pending(&self) -> Option<&str> {
+        self.chunks_pending.first().map(String::as_str)
+    }
+
+    /// The next pending chunk from `chunks`, prepared for the model
+    pub fn prepare_next(
+        &self,
+        chunks: &[DocumentChunk],
+        context_window: usize,
+        include_metadata: bool,
+        tokenizer: &dyn Tokenizer,
+    ) -> Option<Result<LLMInput, VesperaError>> {
+        let id = self.next_pending()?;
+        let chunk = chunks.iter().find(|chunk| chunk.id == id)?;
+        Some(prepare_for_llm_with(chunk, context_window, include_metadata, tokenizer))
+    }
+
+    /// Record a chunk as processed, with whatever was extracted from it
+    ///
+    /// Returns false if the chunk wasn't pending.
+    pub fn mark_processed(&mut self, chunk_id: &str, extracted: Option<serde_json::Value>) -> bool {
+        let Some(index) = self.chunks_pending.iter().position(|id| id == chunk_id) else {
+            return false;
+        };
+        let id = self.chunks_pending.remove(index);
+        if let Some(value) = extracted {
+            self.extracted_data.insert(id.clone(), value);
+        }
+        self.chunks_processed.push(id);
+        true
+    }
+
+    /// Record a pending chunk as failed so later runs skip it
+    ///
+    /// Returns false if the chunk wasn't pending.
+    pub fn mark_failed(&mut self, chunk_id: &str, error: impl Into<String>) -> bool {
+        let Some(index) = self.chunks_pending.iter().position(|id| id == chunk_id) else {
+            return false;
+        };
+        let id = self.chunks_pending.remove(index);
+        self.chunks_failed.insert(id, error.into());
+        true
+    }
+
+    /// Make failed chunks pending again, returning how many there were
+    pub fn retry_failed(&mut self) -> usize {
+        let failed: Vec<String> = self.chunks_failed.drain().map(|(id, _)| id).collect();
+        let count = failed.len();
+        self.chunks_pending.extend(failed);
+        count
+    }
+
+    /// Whether no chunk is left pending
+    pub fn is_complete(&self) -> bool {
+        self.chunks_pending.is_empty()
+    }
+
+    /// Processed and total chunk counts
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self.chunks_processed.len();
+        (done, done + self.chunks_pending.len() + self.chunks_failed.len())
+    }
+
+    /// Write the session to `path` as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VesperaError> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self).map_err(|e| VesperaError::InvalidInput {
+            message: format!("Failed to serialize session: {}", e),
+            context: None,
+        })?;
+        std::fs::write(path, json).map_err(|e| EditError::from_io_with_path(e, path.display().to_string()))
+    }
+
+    /// Load a session saved with [`save`](Self::save) to resume it
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VesperaError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| EditError::from_io_with_path(e, path.display().to_string()))?;
+        serde_json::from_str(&json).map_err(|e| VesperaError::InvalidInput {
+            message: format!("Invalid session file: {}", e),
+            context: Some(path.display().to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::{chunk_document, ChunkStrategy, ChunkingConfig};
+
+    fn chunks() -> Vec<DocumentChunk> {
+        let text = "First paragraph about the build system.\n\nSecond paragraph about tests.\n\nThird paragraph.";
+        let config = ChunkingConfig {
+            max_chunk_size: 45,
+            overlap_size: 0,
+            chunk_strategy: ChunkStrategy::ParagraphBoundary,
+            ..Default::default()
+        };
+        chunk_document(text, &config).unwrap()
+    }
+
+    #[test]
+    fn test_continuation_markers() {
+        let chunks = chunks();
+        assert_eq!(chunks.len(), 3);
+
+        let first = prepare_for_llm(&chunks[0], 1000, false).unwrap();
+        assert!(first.content.starts_with("[Chunk 1 of 3]\n\n"));
+        assert!(first.content.ends_with("[Continues in the next chunk]"));
+        assert!(!first.truncated);
+
+        let last = prepare_for_llm(&chunks[2], 1000, true).unwrap();
+        assert!(last.content.contains("continued from the previous chunk"));
+        assert!(last.content.contains(&format!("chunk_id: {}\n", chunks[2].id)));
+        assert!(!last.content.contains("Continues"));
+        assert_eq!(last.metadata.unwrap()["chunk_index"], "2");
+    }
+
+    #[test]
+    fn test_token_budget() {
+        let mut chunk = chunks().remove(0);
+        chunk.content = "word ".repeat(200);
+        let words = |text: &str| text.split_whitespace().count();
+
+        let input = prepare_for_llm_with(&chunk, 50, false, &words).unwrap();
+        assert!(input.truncated);
+        assert!(input.token_count <= 50);
+        assert!(input.content.contains("word [...]"));
+
+        let input = prepare_for_llm(&chunk, 60, true).unwrap();
+        assert!(input.token_count <= 60);
+
+        assert!(prepare_for_llm_with(&chunk, 3, true, &words).is_err());
+
+        // One token to spare: too little for the marker, which is left off
+        let chars = |text: &str| text.chars().count();
+        let overhead = prepare_for_llm_with(&DocumentChunk { content: String::new(), ..chunk.clone() }, 1000, false, &chars)
+            .unwrap()
+            .token_count;
+        let input = prepare_for_llm_with(&chunk, overhead + 1, false, &chars).unwrap();
+        assert!(input.truncated);
+        assert_eq!(input.token_count, overhead + 1);
+        assert!(!input.content.contains("[...]"));
+        assert!(prepare_for_llm_with(&chunk, overhead, false, &chars).is_err());
+    }
+
+    #[test]
+    fn test_token_budget_with_non_additive_tokenizer() {
+        let mut chunk = chunks().remove(0);
+        chunk.content = "word ".repeat(200);
+        // Longer texts cost more per word, so the parts add up to less than the whole
+        let growing = |text: &str| {
+            let words = text.split_whitespace().count();
+            words + words * words / 20
+        };
+        let parts = growing("[Chunk 1 of 3]\n\n") + growing("word ".repeat(16).as_str()) + growing("[Continues in the next chunk]");
+        assert!(parts <= 40);
+
+        let input = prepare_for_llm_with(&chunk, 40, false, &growing).unwrap();
+        assert!(input.truncated);
+        assert!(input.token_count <= 40, "{} tokens", input.token_count);
+        assert_eq!(input.token_count, growing(&input.content));
+        assert!(input.content.contains("word [...]"));
+        assert!(input.content.ends_with("[Continues in the next chunk]"));
+    }
+
+    #[test]
+    fn test_session_resume() {
+        let chunks = chunks();
+        let mut session = LLMProcessingSession::new(&chunks);
+        let first = session.next_pending().unwrap().to_string();
+        assert!(session.prepare_next(&chunks, 1000, true, &CharEstimateTokenizer::default()).unwrap().is_ok());
+        assert!(session.mark_processed(&first, Some(serde_json::json!({"topic": "build"}))));
+        assert!(!session.mark_processed(&first, None));
+
+        let path = std::env::temp_dir().join(format!("vespera-llm-session-{}.json", uuid::Uuid::new_v4()));
+        session.save(&path).unwrap();
+        let mut resumed = LLMProcessingSession::load(&path).unwrap();
+        assert_eq!(resumed.progress(), (1, 3));
+        assert_eq!(resumed.next_pending(), Some(chunks[1].id.as_str()));
+        assert_eq!(resumed.extracted_data[&first]["topic"], "build");
+
+        while let Some(id) = resumed.next_pending().map(String::from) {
+            resumed.mark_processed(&id, None);
+        }
+        assert!(resumed.is_complete());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -8,10 +8,14 @@ pub mod processor;
 pub mod strategies;
 pub mod discord;
 pub mod llm;
+pub mod enrich;
+pub mod postprocess;
 
-pub use config::{ChunkingConfig, ChunkStrategy, DocumentFormat};
+pub use config::{ChunkingConfig, ChunkStrategy, DocumentFormat, PostProcessor};
 pub use processor::{ChunkProcessor, DocumentChunk, ChunkMetadata};
 
 // Re-export common functionality
 pub use strategies::chunk_document;
+pub use postprocess::post_process;
+pub use enrich::{detect_code_language, detect_natural_language, enrich_metadata};
 pub use discord::{chunk_discord_export, parse_discord_html};
\ No newline at end of file
@@ -0,0 +1,219 @@
+//! Post-processing of chunk content
+//!
+//! [`post_process`] runs the [`PostProcessor`] steps listed in
+//! [`ChunkingConfig::post_processors`](crate::chunking::ChunkingConfig) over
+//! every chunk, whichever strategy produced it. Steps run in the configured
+//! order, so e.g. `StripHtml` before `NormalizeWhitespace` tidies the gaps the
+//! removed markup leaves behind. Byte and character ranges keep pointing at
+//! the original document.
+
+use crate::chunking::{DocumentChunk, PostProcessor};
+use regex::Regex;
+use scraper::node::Node;
+use scraper::{ElementRef, Html};
+use std::sync::OnceLock;
+
+/// Elements whose content is never text
+const SKIPPED_ELEMENTS: [&str; 6] = ["script", "style", "noscript", "template", "head", "svg"];
+
+/// Elements that start a new line when flattened to text
+const BLOCK_ELEMENTS: [&str; 22] = [
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "footer", "h1", "h2",
+    "h3", "h4", "h5", "h6", "header", "hr", "li", "p", "pre", "tr",
+];
+
+/// Apply `processors` to every chunk in order
+///
+/// Chunks left empty are dropped and the rest renumbered.
+pub fn post_process(chunks: &mut Vec<DocumentChunk>, processors: &[PostProcessor]) {
+    if processors.is_empty() {
+        return;
+    }
+
+    for chunk in chunks.iter_mut() {
+        chunk.content = processors.iter().fold(std::mem::take(&mut chunk.content), |text, processor| {
+            processor.apply(&text)
+        });
+    }
+
+    chunks.retain(|chunk| !chunk.content.trim().is_empty());
+    let total = chunks.len();
+    for (index, chunk) in chunks.iter_mut().enumerate() {
+        chunk.metadata.chunk_index = index;
+        chunk.metadata.total_chunks = total;
+    }
+}
+
+impl PostProcessor {
+    /// Run this step on `text`
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            PostProcessor::StripHtml => strip_html(text),
+            PostProcessor::NormalizeWhitespace => normalize_whitespace(text),
+            PostProcessor::MaskPii => mask_pii(text),
+            PostProcessor::RemoveBoilerplate => remove_boilerplate(text),
+        }
+    }
+}
+
+fn strip_html(text: &str) -> String {
+    if !text.contains('<') && !text.contains('&') {
+        return text.to_string();
+    }
+
+    fn flatten(element: ElementRef, out: &mut String) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(t) => out.push_str(t),
+                Node::Element(e) if SKIPPED_ELEMENTS.contains(&e.name()) => {}
+                Node::Element(e) => {
+                    let block = BLOCK_ELEMENTS.contains(&e.name());
+                    if block && !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    if let Some(child) = ElementRef::wrap(child) {
+                        flatten(child, out);
+                    }
+                    if block && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let fragment = Html::parse_fragment(text);
+    let mut out = String::with_capacity(text.len());
+    flatten(fragment.root_element(), &mut out);
+    out
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in text.split('\n') {
+        let rest = line.trim_start_matches([' ', '\t']);
+        // Leading indentation is kept so code stays readable
+        let indent = &line[..line.len() - rest.len()];
+        let collapsed = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if collapsed.is_empty() {
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+        } else {
+            lines.push(format!("{}{}", indent, collapsed));
+        }
+    }
+
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Replace email addresses with `[EMAIL]` and phone numbers with `[PHONE]`
+///
+/// Phone numbers need 9 to 15 digits and a `+`, parenthesis or separator;
+/// bare digit runs are left alone since they are more often IDs.
+fn mask_pii(text: &str) -> String {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    static PHONE: OnceLock<Regex> = OnceLock::new();
+    let email = EMAIL.get_or_init(|| {
+        Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").expect("valid email pattern")
+    });
+    let phone = PHONE.get_or_init(|| {
+        Regex::new(r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?)?\b\d{2,4}(?:[ .-]?\d{2,4}){2,4}\b")
+            .expect("valid phone pattern")
+    });
+
+    let masked = email.replace_all(text, "[EMAIL]");
+    phone
+        .replace_all(&masked, |captures: &regex::Captures| {
+            let candidate = &captures[0];
+            let digits = candidate.chars().filter(char::is_ascii_digit).count();
+            let formatted = candidate.contains(['+', '(', ' ', '.', '-']);
+            if (9..=15).contains(&digits) && formatted {
+                "[PHONE]".to_string()
+            } else {
+                candidate.to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Drop lines that are page furniture rather than content
+fn remove_boilerplate(text: &str) -> String {
+    static BOILERPLATE: OnceLock<Regex> = OnceLock::new();
+    let boilerplate = BOILERPLATE.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?i)^(?:",
+            r"(?:copyright|©|\(c\))\s.*|.*\ball rights reserved\b.*",
+            r"|.*\b(?:this (?:web)?site uses cookies|(?:accept|allow|manage) (?:all )?cookies|cookie (?:policy|settings))\b.*",
+            r"|skip to (?:main )?content|back to top|read more\W*|click here\b.*",
+            r"|share (?:this|on)\b.*|.*\bsubscribe to (?:our|the) newsletter\b.*|unsubscribe\b.*",
+            r"|page \d+ of \d+|powered by \S+|(?:privacy policy|terms of (?:service|use))(?:\s*[|·•]\s*.*)?",
+            r")$",
+        ))
+        .expect("valid boilerplate pattern")
+    });
+
+    let kept: Vec<&str> = text.lines().filter(|line| !boilerplate.is_match(line.trim())).collect();
+    kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::{chunk_document, ChunkStrategy, ChunkingConfig};
+
+    #[test]
+    fn test_strip_html() {
+        let html = "<div><h1>Title</h1><p>Fish &amp; chips<br>today</p><script>track()</script></div>";
+        assert_eq!(strip_html(html), "Title\nFish & chips\ntoday\n");
+        assert_eq!(strip_html("no markup here"), "no markup here");
+    }
+
+    #[test]
+    fn test_normalize_whitespace() {
+        let text = "  fn main() {\r\n      a   =  1;\t\n\n\n\n  }   \n\n";
+        assert_eq!(normalize_whitespace(text), "  fn main() {\n      a = 1;\n\n  }");
+    }
+
+    #[test]
+    fn test_mask_pii() {
+        let text = "Mail jane.doe@example.co.uk or call +1 (555) 123-4567. Order 20240115123, date 2024-01-15.";
+        assert_eq!(
+            mask_pii(text),
+            "Mail [EMAIL] or call [PHONE]. Order 20240115123, date 2024-01-15."
+        );
+    }
+
+    #[test]
+    fn test_remove_boilerplate() {
+        let text = "Skip to content\nThe actual article text.\nCopyright 2024 Example Corp. All rights reserved.\nThis site uses cookies to improve your experience.";
+        assert_eq!(remove_boilerplate(text), "The actual article text.");
+    }
+
+    #[test]
+    fn test_post_processors_in_chunk_document() {
+        let html = "<p>Contact  ops@example.com   for access.</p>\n\n<p>© 2024 Example</p>";
+        let config = ChunkingConfig {
+            max_chunk_size: 40,
+            overlap_size: 0,
+            chunk_strategy: ChunkStrategy::ParagraphBoundary,
+            ..Default::default()
+        }
+        .with_post_processor(PostProcessor::StripHtml)
+        .with_post_processor(PostProcessor::RemoveBoilerplate)
+        .with_post_processor(PostProcessor::MaskPii)
+        .with_post_processor(PostProcessor::NormalizeWhitespace);
+
+        let chunks = chunk_document(html, &config).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "Contact [EMAIL] for access.");
+        assert_eq!(chunks[0].metadata.total_chunks, 1);
+    }
+}
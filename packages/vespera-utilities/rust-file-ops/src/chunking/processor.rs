@@ -51,6 +51,22 @@ pub struct ChunkMetadata {
     
     /// IDs of child chunks
     pub child_chunks: Vec<String>,
+    
+    /// Natural language of the chunk's prose (ISO 639-1 code)
+    #[serde(default)]
+    pub language: Option<String>,
+    
+    /// Programming language, for chunks of code
+    #[serde(default)]
+    pub code_language: Option<String>,
+    
+    /// Titles of the enclosing headings, outermost first
+    #[serde(default)]
+    pub heading_path: Vec<String>,
+    
+    /// Function the chunk starts in, for chunks of code
+    #[serde(default)]
+    pub function_name: Option<String>,
 }
 
 /// Interactive chunk processor for managing document processing sessions
@@ -241,6 +257,10 @@ mod tests {
                 topics: vec![],
                 parent_chunk: None,
                 child_chunks: vec![],
+                language: None,
+                code_language: None,
+                heading_path: vec![],
+                function_name: None,
             },
             embeddings: None,
         }
@@ -155,6 +155,10 @@ impl ChunkingStrategy for ConversationBreakChunker {
                         topics: vec![], // Could be extracted with NLP
                         parent_chunk: None,
                         child_chunks: vec![],
+                        language: None,
+                        code_language: None,
+                        heading_path: vec![],
+                        function_name: None,
                     },
                     embeddings: None,
                 });
@@ -195,6 +199,10 @@ impl ChunkingStrategy for ConversationBreakChunker {
                     topics: vec![],
                     parent_chunk: None,
                     child_chunks: vec![],
+                    language: None,
+                    code_language: None,
+                    heading_path: vec![],
+                    function_name: None,
                 },
                 embeddings: None,
             });
@@ -63,6 +63,10 @@ impl ChunkingStrategy for FixedSizeChunker {
                     topics: vec![],
                     parent_chunk: None,
                     child_chunks: vec![],
+                    language: None,
+                    code_language: None,
+                    heading_path: vec![],
+                    function_name: None,
                 },
                 embeddings: None,
             };
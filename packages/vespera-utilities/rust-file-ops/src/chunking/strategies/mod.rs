@@ -11,14 +11,20 @@ pub use paragraph::ParagraphBoundaryChunker;
 pub use conversation::ConversationBreakChunker;
 
 use crate::chunking::{ChunkingConfig, ChunkStrategy, DocumentChunk};
+use crate::chunking::enrich::enrich_metadata;
+use crate::chunking::postprocess::post_process;
 use crate::error::VesperaError;
 
 /// Main entry point for document chunking
+///
+/// The configured post-processors run on every chunk (see
+/// [`post_process`]), then language and structure metadata is filled in
+/// (see [`enrich_metadata`]).
 pub fn chunk_document(
     content: &str,
     config: &ChunkingConfig,
 ) -> Result<Vec<DocumentChunk>, VesperaError> {
-    match config.chunk_strategy {
+    let mut chunks = match config.chunk_strategy {
         ChunkStrategy::FixedSize => {
             FixedSizeChunker::new(config).chunk(content)
         }
@@ -40,7 +46,10 @@ pub fn chunk_document(
             // This requires embeddings, which we'll add later
             todo!("Semantic similarity chunking")
         }
-    }
+    }?;
+    post_process(&mut chunks, &config.post_processors);
+    enrich_metadata(&mut chunks, content);
+    Ok(chunks)
 }
 
 /// Trait for all chunking strategies
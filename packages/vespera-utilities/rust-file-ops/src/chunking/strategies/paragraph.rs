@@ -48,6 +48,10 @@ impl ChunkingStrategy for ParagraphBoundaryChunker {
                         topics: vec![],
                         parent_chunk: None,
                         child_chunks: vec![],
+                        language: None,
+                        code_language: None,
+                        heading_path: vec![],
+                        function_name: None,
                     },
                     embeddings: None,
                 });
@@ -87,6 +91,10 @@ impl ChunkingStrategy for ParagraphBoundaryChunker {
                     topics: vec![],
                     parent_chunk: None,
                     child_chunks: vec![],
+                    language: None,
+                    code_language: None,
+                    heading_path: vec![],
+                    function_name: None,
                 },
                 embeddings: None,
             });
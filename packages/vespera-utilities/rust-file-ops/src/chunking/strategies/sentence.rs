@@ -93,6 +93,10 @@ impl ChunkingStrategy for SentenceBoundaryChunker {
                     topics: vec![],
                     parent_chunk: None,
                     child_chunks: vec![],
+                    language: None,
+                    code_language: None,
+                    heading_path: vec![],
+                    function_name: None,
                 },
                 embeddings: None,
             }]);
@@ -124,6 +128,10 @@ impl ChunkingStrategy for SentenceBoundaryChunker {
                         topics: vec![],
                         parent_chunk: None,
                         child_chunks: vec![],
+                        language: None,
+                        code_language: None,
+                        heading_path: vec![],
+                        function_name: None,
                     },
                     embeddings: None,
                 });
@@ -153,6 +161,10 @@ impl ChunkingStrategy for SentenceBoundaryChunker {
                     topics: vec![],
                     parent_chunk: None,
                     child_chunks: vec![],
+                    language: None,
+                    code_language: None,
+                    heading_path: vec![],
+                    function_name: None,
                 },
                 embeddings: None,
             });
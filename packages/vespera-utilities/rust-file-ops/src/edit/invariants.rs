@@ -0,0 +1,221 @@
+//! Invariant checks for edit operations
+//!
+//! [`verify_edit_invariants`] applies a sequence of operations with
+//! [`MultiEditor`] and checks the result against properties every correct
+//! edit must have. It is deterministic, so property tests, fuzz targets and
+//! downstream test suites can all share it:
+//!
+//! - the editor rejects exactly the invalid operations (empty patterns)
+//! - every reported position is a UTF-8 character boundary where the
+//!   original text holds `old_string`, and positions don't overlap
+//! - the output is the input with those ranges spliced out for `new_string`
+//! - `replace_all` leaves no occurrence of `old_string` that a replacement
+//!   didn't create, so applying it again only touches those
+//! - a first-only edit replaces the leftmost occurrence, an N-th occurrence
+//!   edit the N-th
+//! - the multi-edit result equals the operations applied one at a time
+
+use crate::edit::multi::MultiEditor;
+use crate::edit::single::SingleEditor;
+use crate::types::{EditOperation, EditResult, EditTarget, MultiEditResult};
+
+/// A broken edit invariant
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("edit invariant '{invariant}' violated: {details}")]
+pub struct InvariantViolation {
+    /// Short name of the invariant
+    pub invariant: &'static str,
+    /// What was expected and what happened
+    pub details: String,
+}
+
+impl InvariantViolation {
+    fn new(invariant: &'static str, details: impl Into<String>) -> Self {
+        Self {
+            invariant,
+            details: details.into(),
+        }
+    }
+}
+
+/// Apply `operations` to `content` and check every edit invariant
+///
+/// Returns the multi-edit result, or `None` when the operations were
+/// correctly rejected as invalid.
+pub fn verify_edit_invariants(
+    content: &str,
+    operations: &[EditOperation],
+) -> Result<Option<MultiEditResult>, InvariantViolation> {
+    let expect_rejection = operations.iter().any(|op| op.validate().is_err());
+    let multi = match (MultiEditor::new().apply_edits(content, operations), expect_rejection) {
+        (Ok(result), false) => result,
+        (Err(_), true) => return Ok(None),
+        (Ok(_), true) => {
+            return Err(InvariantViolation::new("rejection", "an invalid operation was accepted"));
+        }
+        (Err(e), false) => {
+            return Err(InvariantViolation::new("rejection", format!("valid operations were rejected: {}", e)));
+        }
+    };
+
+    let editor = SingleEditor::new();
+    let mut current = content.to_string();
+    let mut total_replacements = 0;
+    for (i, operation) in operations.iter().enumerate() {
+        let result = editor.apply_edit(&current, operation).map_err(|e| {
+            InvariantViolation::new("rejection", format!("operation {} failed on its own: {}", i, e))
+        })?;
+        check_single_edit(&current, operation, &result)
+            .map_err(|v| InvariantViolation::new(v.invariant, format!("operation {}: {}", i, v.details)))?;
+
+        let reported = &multi.operation_results[i];
+        if reported.replacement_positions != result.replacement_positions {
+            return Err(InvariantViolation::new(
+                "sequential",
+                format!("operation {} reported positions {:?}, applied alone {:?}",
+                    i, reported.replacement_positions, result.replacement_positions),
+            ));
+        }
+        total_replacements += result.replacements_made;
+        current = result.content;
+    }
+
+    if multi.content != current {
+        return Err(InvariantViolation::new("sequential", "multi-edit output differs from applying each operation in turn"));
+    }
+    if multi.total_replacements != total_replacements {
+        return Err(InvariantViolation::new(
+            "sequential",
+            format!("total_replacements is {}, operations made {}", multi.total_replacements, total_replacements),
+        ));
+    }
+    Ok(Some(multi))
+}
+
+fn check_single_edit(content: &str, operation: &EditOperation, result: &EditResult) -> Result<(), InvariantViolation> {
+    let old = operation.old_string.as_str();
+    let new = operation.new_string.as_str();
+    let positions = &result.replacement_positions;
+
+    if result.replacements_made != positions.len() {
+        return Err(InvariantViolation::new(
+            "count",
+            format!("{} replacements reported at {} positions", result.replacements_made, positions.len()),
+        ));
+    }
+
+    let mut previous_end = 0;
+    for &position in positions {
+        if position < previous_end {
+            return Err(InvariantViolation::new("positions", format!("match at {} overlaps the previous one", position)));
+        }
+        if !content.is_char_boundary(position) || content.get(position..position + old.len()) != Some(old) {
+            return Err(InvariantViolation::new(
+                "positions",
+                format!("no occurrence of {:?} on a character boundary at byte {}", old, position),
+            ));
+        }
+        previous_end = position + old.len();
+    }
+
+    let mut expected = String::with_capacity(content.len());
+    let mut last = 0;
+    for &position in positions {
+        expected.push_str(&content[last..position]);
+        expected.push_str(new);
+        last = position + old.len();
+    }
+    expected.push_str(&content[last..]);
+    if result.content != expected {
+        return Err(InvariantViolation::new("splice", "output is not the input with the matches replaced"));
+    }
+
+    if result.changed != (!positions.is_empty() && old != new) {
+        return Err(InvariantViolation::new("changed", format!("changed is {} for {} replacements", result.changed, positions.len())));
+    }
+
+    if let EditTarget::Nth(n) = operation.target {
+        let nth = content.match_indices(old).nth(n - 1).map(|(i, _)| i);
+        if positions.first().copied() != nth || positions.len() > 1 {
+            return Err(InvariantViolation::new(
+                "nth",
+                format!("replaced at {:?}, occurrence {} is at {:?}", positions, n, nth),
+            ));
+        }
+        Ok(())
+    } else if operation.target != EditTarget::Everywhere {
+        // Lines and anchors only narrow the occurrences; positions and the
+        // splice are already checked
+        Ok(())
+    } else if operation.replace_all {
+        check_no_missed_occurrences(operation, result)
+    } else {
+        let leftmost = content.find(old);
+        if positions.first().copied() != leftmost || positions.len() > 1 {
+            return Err(InvariantViolation::new(
+                "first_only",
+                format!("replaced at {:?}, leftmost occurrence is {:?}", positions, leftmost),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Every occurrence of `old_string` left after `replace_all` must overlap
+/// (or, for an empty `new_string`, straddle) text a replacement inserted
+fn check_no_missed_occurrences(operation: &EditOperation, result: &EditResult) -> Result<(), InvariantViolation> {
+    let old_len = operation.old_string.len();
+    let new_len = operation.new_string.len();
+    let inserted: Vec<usize> = result.replacement_positions.iter().enumerate()
+        .map(|(i, &position)| position + i * new_len - i * old_len)
+        .collect();
+
+    let output = &result.content;
+    let occurrences = (0..output.len())
+        .filter(|&start| output.is_char_boundary(start) && output[start..].starts_with(operation.old_string.as_str()));
+    for start in occurrences {
+        let end = start + old_len;
+        let created = inserted.iter().any(|&at| {
+            if new_len == 0 { start < at && at < end } else { start < at + new_len && at < end }
+        });
+        if !created {
+            return Err(InvariantViolation::new(
+                "replace_all",
+                format!("occurrence of {:?} at byte {} of the output was not replaced", operation.old_string, start),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_satisfy_invariants() {
+        let operations = vec![
+            EditOperation::new("🌍", "world", true),
+            EditOperation::new("aa", "a", true),
+            EditOperation::new("é", "e\u{301}", false),
+        ];
+        let result = verify_edit_invariants("aaaa 🌍 café 🌍", &operations).unwrap().unwrap();
+        assert_eq!(result.content, "aa world cafe\u{301} world");
+
+        // Rejected, as it should be
+        assert!(verify_edit_invariants("text", &[EditOperation::new("", "x", true)]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_detects_missed_occurrences() {
+        let operation = EditOperation::new("ab", "X", true);
+        let missed = EditResult::success(operation.clone(), "X ab".to_string(), 1, vec![0]);
+        let violation = check_single_edit("ab ab", &operation, &missed).unwrap_err();
+        assert_eq!(violation.invariant, "replace_all");
+
+        // Occurrences a replacement created are fine: "abb" -> "ab"
+        let operation = EditOperation::new("ab", "a", true);
+        let created = EditResult::success(operation.clone(), "ab".to_string(), 1, vec![0]);
+        assert!(check_single_edit("abb", &operation, &created).is_ok());
+    }
+}
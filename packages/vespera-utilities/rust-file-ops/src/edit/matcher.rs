@@ -9,9 +9,15 @@
 //! - UTF-8 character boundary safety
 //! - Efficient algorithms optimized for different scenarios
 //! - Position and match information tracking
+//! - Optional NFC/NFD-insensitive matching and grapheme-safe boundaries
+//! - Case-insensitive and smart-case matching with Unicode case folding
+//! - Whole-word and code-identifier match constraints
+//! - Multi-line blocks matched regardless of blank lines and trailing whitespace
 //! - No external diff libraries - pure finding logic
 
 use crate::error::{EditError, Result};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Information about a found string match
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,8 +30,14 @@ pub struct Match {
     pub char_start: usize,
     /// Character position of the match end (exclusive)
     pub char_end: usize,
-    /// The matched text (for verification)
+    /// The matched text as it appears in the haystack (for verification)
     pub matched_text: String,
+    /// Byte position of the match start in the haystack as searched, after
+    /// NFC normalization and case folding (equal to `start` when both are off)
+    pub normalized_start: usize,
+    /// Byte position of the match end in the haystack as searched, after
+    /// NFC normalization and case folding (equal to `end` when both are off)
+    pub normalized_end: usize,
 }
 
 impl Match {
@@ -45,6 +57,21 @@ impl Match {
     }
 }
 
+/// Constraint on the text surrounding a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchBoundary {
+    /// Match anywhere
+    #[default]
+    Any,
+    /// Match whole words: both ends must fall on Unicode (UAX #29) word
+    /// boundaries, so `don` does not match inside `don't`
+    Word,
+    /// Match code identifiers: the match must not be preceded or followed by
+    /// an identifier character (alphanumeric or `_`), so renaming `count`
+    /// leaves `account` and `count_total` alone but hits `self.count`
+    Identifier,
+}
+
 /// Configuration for string matching operations
 #[derive(Debug, Clone)]
 pub struct MatchConfig {
@@ -52,8 +79,32 @@ pub struct MatchConfig {
     pub max_matches: usize,
     /// Whether to find all matches or stop at first
     pub find_all: bool,
-    /// Whether to perform case-sensitive matching
+    /// Whether to perform case-sensitive matching. Case-insensitive matching
+    /// uses full Unicode case folding (`ß` matches `SS`) and is
+    /// grapheme-aligned like normalized matching.
     pub case_sensitive: bool,
+    /// Ripgrep-style smart case: match case-insensitively unless the pattern
+    /// contains an uppercase character. Overrides `case_sensitive`.
+    pub smart_case: bool,
+    /// Adapt each replacement to the casing of the text it replaces
+    /// (`foo`→`bar`, `Foo`→`Bar`, `FOO`→`BAR`); see [`match_case`]
+    pub preserve_case: bool,
+    /// Treat canonically equivalent text as equal, so `é` (U+00E9) matches
+    /// `e` followed by U+0301. Matches are always grapheme-aligned, since a
+    /// position inside a recomposed cluster has no raw equivalent.
+    pub normalization_insensitive: bool,
+    /// Only accept matches that start and end on extended grapheme cluster
+    /// boundaries, so edits never split a combining sequence or ZWJ emoji
+    pub grapheme_boundaries: bool,
+    /// Word or identifier constraint on matches; any constraint other than
+    /// `Any` implies grapheme-aligned matching
+    pub boundary: MatchBoundary,
+    /// Let a multi-line pattern match a block that has extra or missing
+    /// blank lines between its lines
+    pub ignore_blank_lines: bool,
+    /// Let a multi-line pattern match a block whose lines differ in trailing
+    /// whitespace
+    pub ignore_trailing_whitespace: bool,
 }
 
 impl Default for MatchConfig {
@@ -62,6 +113,13 @@ impl Default for MatchConfig {
             max_matches: 0,
             find_all: false,
             case_sensitive: true,
+            smart_case: false,
+            preserve_case: false,
+            normalization_insensitive: false,
+            grapheme_boundaries: false,
+            boundary: MatchBoundary::Any,
+            ignore_blank_lines: false,
+            ignore_trailing_whitespace: false,
         }
     }
 }
@@ -90,6 +148,14 @@ impl StringMatcher {
             return Err(EditError::EmptyPattern);
         }
         
+        if self.needs_extended_search(needle) {
+            let limit = match (self.config.find_all, self.config.max_matches) {
+                (false, _) => 1,
+                (true, max) => max,
+            };
+            return Ok(self.find_extended(haystack, needle, limit));
+        }
+        
         let mut matches = Vec::new();
         let mut search_start = 0;
         
@@ -123,9 +189,182 @@ impl StringMatcher {
             return Err(EditError::EmptyPattern);
         }
         
+        if self.needs_extended_search(needle) {
+            return Ok(self.find_extended(haystack, needle, 1).pop());
+        }
+        
         self.find_next(haystack, needle, 0)
     }
     
+    fn needs_extended_search(&self, needle: &str) -> bool {
+        self.is_relaxed_block(needle) || self.is_unicode_aware(needle)
+    }
+    
+    fn is_relaxed_block(&self, needle: &str) -> bool {
+        (self.config.ignore_blank_lines || self.config.ignore_trailing_whitespace) && needle.contains('\n')
+    }
+    
+    fn find_extended(&self, haystack: &str, needle: &str, limit: usize) -> Vec<Match> {
+        if self.is_relaxed_block(needle) {
+            self.find_blocks(haystack, needle, limit)
+        } else {
+            self.find_unicode(haystack, needle, limit)
+        }
+    }
+    
+    /// Line-by-line search for a multi-line pattern
+    ///
+    /// The first pattern line must end a haystack line, the last must start
+    /// one, and the lines in between must equal whole haystack lines, with
+    /// trailing whitespace and blank lines ignored as configured. Only the
+    /// blank-line and whitespace options apply to block matches.
+    fn find_blocks(&self, haystack: &str, needle: &str, limit: usize) -> Vec<Match> {
+        let strip = |line: &'_ str| -> String {
+            if self.config.ignore_trailing_whitespace { line.trim_end().to_string() } else { line.to_string() }
+        };
+        let is_blank = |line: &str| self.config.ignore_blank_lines && line.trim().is_empty();
+        
+        let pieces: Vec<&str> = needle.split('\n').collect();
+        let first = strip(pieces[0]);
+        let last = pieces[pieces.len() - 1];
+        let middle: Vec<String> = pieces[1..pieces.len() - 1].iter()
+            .filter(|line| !is_blank(line))
+            .map(|line| strip(line))
+            .collect();
+        
+        let mut offset = 0;
+        let lines: Vec<(usize, &str)> = haystack.split('\n').map(|line| {
+            let start = offset;
+            offset += line.len() + 1;
+            (start, line)
+        }).collect();
+        
+        let skip_blank = |j: &mut usize| {
+            while *j < lines.len() && is_blank(lines[*j].1) {
+                *j += 1;
+            }
+        };
+        
+        let mut matches = Vec::new();
+        let mut min_start = 0;
+        for (i, &(line_start, line)) in lines.iter().enumerate() {
+            let head = strip(line);
+            if !head.ends_with(first.as_str()) {
+                continue;
+            }
+            let start = line_start + head.len() - first.len();
+            if start < min_start {
+                continue;
+            }
+            
+            let mut j = i + 1;
+            let mut complete = true;
+            for expected in &middle {
+                skip_blank(&mut j);
+                if j < lines.len() && strip(lines[j].1) == *expected {
+                    j += 1;
+                } else {
+                    complete = false;
+                    break;
+                }
+            }
+            if !last.is_empty() {
+                skip_blank(&mut j);
+            }
+            let Some(&(last_start, last_line)) = lines.get(j).filter(|_| complete) else {
+                continue;
+            };
+            if !last_line.starts_with(last) {
+                continue;
+            }
+            
+            let end = last_start + last.len();
+            let char_start = haystack[..start].chars().count();
+            matches.push(Match {
+                start,
+                end,
+                char_start,
+                char_end: char_start + haystack[start..end].chars().count(),
+                matched_text: haystack[start..end].to_string(),
+                normalized_start: start,
+                normalized_end: end,
+            });
+            if limit > 0 && matches.len() >= limit {
+                break;
+            }
+            min_start = end;
+        }
+        matches
+    }
+    
+    fn is_unicode_aware(&self, needle: &str) -> bool {
+        self.config.normalization_insensitive
+            || self.config.grapheme_boundaries
+            || self.config.boundary != MatchBoundary::Any
+            || self.ignores_case(needle)
+    }
+    
+    /// Whether `needle` is matched case-insensitively under this configuration
+    pub fn ignores_case(&self, needle: &str) -> bool {
+        if self.config.smart_case {
+            !needle.chars().any(char::is_uppercase)
+        } else {
+            !self.config.case_sensitive
+        }
+    }
+    
+    /// Grapheme-aligned search, optionally over the case-folded and/or NFC
+    /// form of both texts
+    ///
+    /// Finds up to `limit` non-overlapping matches (0 = unlimited); a
+    /// candidate that splits a grapheme cluster is skipped and the search
+    /// resumes one character later.
+    fn find_unicode(&self, haystack: &str, needle: &str, limit: usize) -> Vec<Match> {
+        let fold = self.ignores_case(needle);
+        let normalize = self.config.normalization_insensitive;
+        let segmented = SegmentedText::new(haystack, normalize, fold);
+        let needle = transform(needle, normalize, fold);
+        let word_bounds: Vec<usize> = match self.config.boundary {
+            MatchBoundary::Word => haystack
+                .split_word_bound_indices()
+                .map(|(offset, _)| offset)
+                .chain(std::iter::once(haystack.len()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let text = segmented.text.as_str();
+        
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while let Some(pos) = text[from..].find(needle.as_str()) {
+            let normalized_start = from + pos;
+            let normalized_end = normalized_start + needle.len();
+            
+            match (segmented.raw_offset(normalized_start), segmented.raw_offset(normalized_end)) {
+                (Some(start), Some(end)) if self.boundary_allows(haystack, &word_bounds, start, end) => {
+                    let char_start = haystack[..start].chars().count();
+                    matches.push(Match {
+                        start,
+                        end,
+                        char_start,
+                        char_end: char_start + haystack[start..end].chars().count(),
+                        matched_text: haystack[start..end].to_string(),
+                        normalized_start,
+                        normalized_end,
+                    });
+                    if limit > 0 && matches.len() >= limit {
+                        break;
+                    }
+                    from = normalized_end;
+                }
+                _ => {
+                    from = normalized_start + text[normalized_start..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+        }
+        matches
+    }
+    
     /// Find the next occurrence of a pattern starting from the given position
     fn find_next(&self, haystack: &str, needle: &str, start_pos: usize) -> Result<Option<Match>> {
         if start_pos >= haystack.len() {
@@ -147,6 +386,21 @@ impl StringMatcher {
         }
     }
     
+    /// Whether the raw range `start..end` satisfies the boundary constraint
+    fn boundary_allows(&self, haystack: &str, word_bounds: &[usize], start: usize, end: usize) -> bool {
+        match self.config.boundary {
+            MatchBoundary::Any => true,
+            MatchBoundary::Word => {
+                word_bounds.binary_search(&start).is_ok() && word_bounds.binary_search(&end).is_ok()
+            }
+            MatchBoundary::Identifier => {
+                let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+                !haystack[..start].chars().next_back().is_some_and(is_identifier_char)
+                    && !haystack[end..].chars().next().is_some_and(is_identifier_char)
+            }
+        }
+    }
+    
     /// Fast single character search using memchr
     fn find_single_char(&self, haystack: &str, needle: &str, start_pos: usize) -> Result<Option<Match>> {
         let needle_char = needle.chars().next().unwrap();
@@ -166,6 +420,8 @@ impl StringMatcher {
                 char_start,
                 char_end,
                 matched_text: needle.to_string(),
+                normalized_start: byte_start,
+                normalized_end: byte_end,
             }))
         } else {
             Ok(None)
@@ -190,6 +446,8 @@ impl StringMatcher {
                 char_start,
                 char_end,
                 matched_text: needle.to_string(),
+                normalized_start: byte_start,
+                normalized_end: byte_end,
             }))
         } else {
             Ok(None)
@@ -231,6 +489,8 @@ impl StringMatcher {
                 char_start,
                 char_end,
                 matched_text: needle.to_string(),
+                normalized_start: byte_start,
+                normalized_end: byte_end,
             }))
         } else {
             Ok(None)
@@ -243,6 +503,10 @@ impl StringMatcher {
             return Err(EditError::EmptyPattern);
         }
         
+        if self.needs_extended_search(needle) {
+            return Ok(self.find_extended(haystack, needle, self.config.max_matches).len());
+        }
+        
         let mut count = 0;
         let mut search_start = 0;
         
@@ -284,6 +548,86 @@ impl StringMatcher {
     }
 }
 
+/// Full case folding, approximated by uppercasing then lowercasing each
+/// character so that length-changing folds like `ß`/`SS` and `ſ`/`s` compare
+/// equal. Per character, so final sigma folds like any other sigma.
+fn fold_case(text: &str) -> String {
+    text.chars()
+        .flat_map(char::to_uppercase)
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn transform(text: &str, normalize: bool, fold: bool) -> String {
+    let folded = if fold { fold_case(text) } else { text.to_string() };
+    if normalize {
+        folded.nfc().collect()
+    } else {
+        folded
+    }
+}
+
+/// Haystack split at grapheme cluster boundaries, optionally case-folded and
+/// NFC-normalized cluster by cluster, with the offset of each boundary in
+/// both forms
+struct SegmentedText {
+    text: String,
+    /// (normalized offset, raw offset) at every cluster boundary, ascending
+    boundaries: Vec<(usize, usize)>,
+}
+
+impl SegmentedText {
+    fn new(raw: &str, normalize: bool, fold: bool) -> Self {
+        let mut text = String::with_capacity(raw.len());
+        let mut boundaries = vec![(0, 0)];
+        for (offset, cluster) in raw.grapheme_indices(true) {
+            if normalize || fold {
+                text.push_str(&transform(cluster, normalize, fold));
+            } else {
+                text.push_str(cluster);
+            }
+            boundaries.push((text.len(), offset + cluster.len()));
+        }
+        Self { text, boundaries }
+    }
+    
+    /// Raw offset for a normalized offset, if it falls on a cluster boundary
+    fn raw_offset(&self, normalized: usize) -> Option<usize> {
+        self.boundaries
+            .binary_search_by_key(&normalized, |&(n, _)| n)
+            .ok()
+            .map(|i| self.boundaries[i].1)
+    }
+}
+
+/// Recase `replacement` after the casing pattern of `template`
+///
+/// An all-lowercase template lowercases the replacement, an all-uppercase
+/// one uppercases it, and a capitalized one (`Foo`, or a single uppercase
+/// letter) capitalizes its first character and lowercases the rest. Mixed
+/// or uncased templates leave the replacement as given.
+pub fn match_case(template: &str, replacement: &str) -> String {
+    let mut cased = template.chars().filter(|c| c.is_lowercase() || c.is_uppercase());
+    let Some(first) = cased.next() else {
+        return replacement.to_string();
+    };
+    let rest: Vec<char> = cased.collect();
+    
+    if first.is_lowercase() && rest.iter().all(|c| c.is_lowercase()) {
+        replacement.to_lowercase()
+    } else if first.is_uppercase() && !rest.is_empty() && rest.iter().all(|c| c.is_uppercase()) {
+        replacement.to_uppercase()
+    } else if first.is_uppercase() && rest.iter().all(|c| c.is_lowercase()) {
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(head) => head.to_uppercase().chain(chars.as_str().to_lowercase().chars()).collect(),
+            None => String::new(),
+        }
+    } else {
+        replacement.to_string()
+    }
+}
+
 impl Default for StringMatcher {
     fn default() -> Self {
         Self::new()
@@ -308,6 +652,16 @@ impl StringMatcher {
         })
     }
     
+    /// Create a matcher that finds all canonically equivalent occurrences
+    pub fn normalization_insensitive() -> Self {
+        Self::with_config(MatchConfig {
+            find_all: true,
+            normalization_insensitive: true,
+            grapheme_boundaries: true,
+            ..Default::default()
+        })
+    }
+    
     /// Create a case-insensitive matcher
     pub fn case_insensitive() -> Self {
         Self::with_config(MatchConfig {
@@ -315,6 +669,15 @@ impl StringMatcher {
             ..Default::default()
         })
     }
+    
+    /// Create a smart-case matcher that finds all occurrences
+    pub fn smart_case() -> Self {
+        Self::with_config(MatchConfig {
+            find_all: true,
+            smart_case: true,
+            ..Default::default()
+        })
+    }
 }
 
 #[cfg(test)]
@@ -383,4 +746,133 @@ mod tests {
         assert!(StringMatcher::validate_pattern("").is_err());
         assert!(StringMatcher::validate_pattern("🌍").is_ok());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_grapheme_boundaries() {
+        let matcher = StringMatcher::with_config(MatchConfig {
+            find_all: true,
+            grapheme_boundaries: true,
+            ..Default::default()
+        });
+        
+        // The bare "e" is matched, the base of "e\u{301}" is not
+        let matches = matcher.find_all("e\u{301} e", "e").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start, 4);
+        
+        // Never split a ZWJ emoji sequence
+        assert!(matcher.find_all("👨\u{200d}👩", "👨").unwrap().is_empty());
+        assert_eq!(matcher.count_matches("👨\u{200d}👩 👨", "👨").unwrap(), 1);
+    }
+    
+    #[test]
+    fn test_normalization_insensitive() {
+        let matcher = StringMatcher::normalization_insensitive();
+        let haystack = "caf\u{e9} and cafe\u{301}";
+        let matches = matcher.find_all(haystack, "café").unwrap();
+        
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].start, matches[0].end), (0, 5));
+        assert_eq!((matches[1].start, matches[1].end), (10, 16));
+        assert_eq!(matches[1].matched_text, "cafe\u{301}");
+        assert_eq!(matches[1].char_len(), 5);
+        
+        // Normalized positions are in the NFC text, where both are 5 bytes
+        assert_eq!((matches[1].normalized_start, matches[1].normalized_end), (10, 15));
+        
+        // A decomposed pattern finds the precomposed form too
+        let first = matcher.find_first(haystack, "e\u{301}").unwrap().unwrap();
+        assert_eq!((first.start, first.end), (3, 5));
+    }
+    
+    #[test]
+    fn test_case_insensitive_folding() {
+        let matcher = StringMatcher::with_config(MatchConfig {
+            find_all: true,
+            case_sensitive: false,
+            ..Default::default()
+        });
+        let matches = matcher.find_all("Hello HELLO hello", "hello").unwrap();
+        assert_eq!(matches.iter().map(|m| m.start).collect::<Vec<_>>(), vec![0, 6, 12]);
+        assert_eq!(matches[1].matched_text, "HELLO");
+        
+        // Full folding: "ß" is "ss", so "STRASSE" matches "straße"
+        let m = matcher.find_first("Die STRASSE", "straße").unwrap().unwrap();
+        assert_eq!((m.start, m.end), (4, 11));
+        assert_eq!(matcher.count_matches("ΣΊΣΥΦΟΣ σίσυφος", "σίσυφος").unwrap(), 2);
+    }
+    
+    #[test]
+    fn test_smart_case() {
+        let matcher = StringMatcher::smart_case();
+        assert!(matcher.ignores_case("foo"));
+        assert!(!matcher.ignores_case("Foo"));
+        assert_eq!(matcher.count_matches("foo Foo FOO", "foo").unwrap(), 3);
+        assert_eq!(matcher.count_matches("foo Foo FOO", "Foo").unwrap(), 1);
+    }
+    
+    #[test]
+    fn test_match_case() {
+        assert_eq!(match_case("foo", "Bar"), "bar");
+        assert_eq!(match_case("Foo", "bAR"), "Bar");
+        assert_eq!(match_case("FOO", "bar"), "BAR");
+        assert_eq!(match_case("F", "bar"), "Bar");
+        assert_eq!(match_case("fOo", "bar"), "bar");
+        assert_eq!(match_case("123", "Bar"), "Bar");
+        assert_eq!(match_case("Über", "äpfel"), "Äpfel");
+    }
+    
+    #[test]
+    fn test_word_boundary() {
+        let matcher = StringMatcher::with_config(MatchConfig {
+            find_all: true,
+            boundary: MatchBoundary::Word,
+            ..Default::default()
+        });
+        let matches = matcher.find_all("count account count's", "count").unwrap();
+        assert_eq!(matches.iter().map(|m| m.start).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(matcher.count_matches("don't don", "don").unwrap(), 1);
+    }
+    
+    #[test]
+    fn test_identifier_boundary() {
+        let matcher = StringMatcher::with_config(MatchConfig {
+            find_all: true,
+            boundary: MatchBoundary::Identifier,
+            ..Default::default()
+        });
+        let haystack = "count += account + self.count + count_total + count2 + (count)";
+        let matches = matcher.find_all(haystack, "count").unwrap();
+        assert_eq!(matches.iter().map(|m| m.start).collect::<Vec<_>>(), vec![0, 24, 56]);
+        
+        // A rejected candidate doesn't hide a valid match right after it
+        assert_eq!(matcher.count_matches("aaa aa", "aa").unwrap(), 1);
+    }
+    
+    #[test]
+    fn test_relaxed_block_matching() {
+        let matcher = StringMatcher::with_config(MatchConfig {
+            find_all: true,
+            ignore_blank_lines: true,
+            ignore_trailing_whitespace: true,
+            ..Default::default()
+        });
+        let haystack = "fn main() {  \n\n    let x = 1;\t\n    run(x);\n}\n";
+        let m = matcher.find_first(haystack, "main() {\n    let x = 1;\n\n\n    run(").unwrap().unwrap();
+        
+        assert_eq!(m.start, 3);
+        assert_eq!(&haystack[m.end..], "x);\n}\n");
+        assert_eq!(m.matched_text, "main() {  \n\n    let x = 1;\t\n    run(");
+        
+        // A pattern ending in a newline ends at the start of a line
+        let m = matcher.find_first(haystack, "run(x);   \n").unwrap().unwrap();
+        assert_eq!(&haystack[m.end..], "}\n");
+        
+        // Content differences still fail to match
+        assert!(matcher.find_all(haystack, "let x = 2;\n    run(x);").unwrap().is_empty());
+        
+        // Without the options the block must match exactly
+        let strict = StringMatcher::find_all_matches();
+        assert!(strict.find_all(haystack, "main() {\n    let x = 1;").unwrap().is_empty());
+    }
+}
@@ -11,11 +11,13 @@
 pub mod matcher;
 pub mod single;
 pub mod multi;
+pub mod invariants;
 
 // Re-export key types for convenience
-pub use matcher::{Match, MatchConfig, StringMatcher};
+pub use matcher::{match_case, Match, MatchBoundary, MatchConfig, StringMatcher};
 pub use single::{SingleEditor, replace_string, replace_first, replace_all};
 pub use multi::{
     MultiEditor, OperationAnalysis, OperationConflict, ConflictType,
     apply_multiple_edits,
-};
\ No newline at end of file
+};
+pub use invariants::{verify_edit_invariants, InvariantViolation};
\ No newline at end of file
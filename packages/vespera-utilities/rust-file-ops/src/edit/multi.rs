@@ -32,6 +32,24 @@ impl MultiEditor {
         }
     }
 
+    /// Report operations whose result is already present
+    ///
+    /// See [`SingleEditor::detect_already_applied`].
+    pub fn detect_already_applied(mut self, detect: bool) -> Self {
+        self.single_editor = self.single_editor.detect_already_applied(detect);
+        self
+    }
+
+    /// Fail single-occurrence operations that match more than once
+    ///
+    /// See [`SingleEditor::require_unique_match`]; the `AmbiguousMatch`
+    /// error is returned as is rather than wrapped, so its candidates stay
+    /// machine-readable.
+    pub fn require_unique_match(mut self, require: bool) -> Self {
+        self.single_editor = self.single_editor.require_unique_match(require);
+        self
+    }
+
     /// Apply multiple edit operations sequentially
     ///
     /// Each operation is applied to the result of the previous operation.
@@ -54,6 +72,7 @@ impl MultiEditor {
                 }
             })?;
         }
+        check_unique_labels(operations)?;
 
         // Apply operations sequentially
         let mut current_content = content.to_string();
@@ -73,7 +92,8 @@ impl MultiEditor {
                         .with_replacements(
                             edit_result.replacements_made,
                             edit_result.replacement_positions,
-                        );
+                        )
+                        .with_already_applied(edit_result.already_applied);
 
                     // Merge performance metrics
                     cumulative_metrics = self.merge_metrics(&cumulative_metrics, &edit_result.metrics);
@@ -81,6 +101,7 @@ impl MultiEditor {
                     // Add to multi-edit result
                     result = result.add_operation_result(operation_result);
                 }
+                Err(e @ EditError::AmbiguousMatch { .. }) => return Err(e),
                 Err(e) => {
                     // Operation failed - return error with context
                     return Err(EditError::InvalidOperation {
@@ -120,6 +141,8 @@ impl MultiEditor {
             return Ok(MultiEditResult::new(content.to_string(), 0));
         }
 
+        check_unique_labels(operations)?;
+
         let mut current_content = content.to_string();
         let mut result = MultiEditResult::new(current_content.clone(), operations.len());
         let mut cumulative_metrics = PerformanceMetrics::new();
@@ -136,7 +159,8 @@ impl MultiEditor {
                         .with_replacements(
                             edit_result.replacements_made,
                             edit_result.replacement_positions,
-                        );
+                        )
+                        .with_already_applied(edit_result.already_applied);
 
                     // Merge performance metrics
                     cumulative_metrics = self.merge_metrics(&cumulative_metrics, &edit_result.metrics);
@@ -147,7 +171,8 @@ impl MultiEditor {
                 Err(e) => {
                     // Create a failed operation result
                     let operation_result = SingleOperationResult::new(operation.clone())
-                        .with_warning(format!("Operation {} failed: {}", i + 1, e));
+                        .with_warning(format!("Operation {} failed: {}", i + 1, e))
+                        .with_error(e.to_string());
 
                     result = result.add_operation_result(operation_result);
 
@@ -179,7 +204,7 @@ impl MultiEditor {
                 }
             })?;
         }
-        Ok(())
+        check_unique_labels(operations)
     }
 
     /// Preview what the multi-edit would produce without applying changes
@@ -335,6 +360,31 @@ pub enum ConflictType {
     Cancellation,
 }
 
+/// Labels key the per-operation report entries, and unlabelled operations
+/// are keyed by their index, so no two keys may be the same
+fn check_unique_labels(operations: &[EditOperation]) -> Result<()> {
+    let unlabelled: std::collections::HashSet<String> = operations.iter().enumerate()
+        .filter(|(_, op)| op.label.is_none())
+        .map(|(index, _)| index.to_string())
+        .collect();
+    let mut seen = std::collections::HashSet::new();
+    for label in operations.iter().filter_map(|op| op.label.as_deref()) {
+        if !seen.insert(label) {
+            return Err(EditError::InvalidOperation {
+                reason: format!("Duplicate operation label '{}'", label),
+                suggestion: Some("Give each operation a distinct label".to_string()),
+            });
+        }
+        if unlabelled.contains(label) {
+            return Err(EditError::InvalidOperation {
+                reason: format!("Operation label '{}' is the key of the unlabelled operation at that index", label),
+                suggestion: Some("Label every operation, or use labels that aren't operation indices".to_string()),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Convenience function to apply multiple operations sequentially
 pub fn apply_multiple_edits(
     content: &str,
@@ -495,4 +545,64 @@ mod tests {
         assert_eq!(result.total_replacements, 0);
         assert!(!result.changed);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_labelled_entries() {
+        use crate::types::OperationStatus;
+
+        let editor = MultiEditor::new();
+        let operations = vec![
+            EditOperation::new("hello", "hi", false).with_label("greeting"),
+            EditOperation::new("", "x", false).with_label("broken"),
+            EditOperation::new("missing", "x", false),
+        ];
+        let result = editor.apply_edits_best_effort("hello world", &operations).unwrap();
+        let entries = result.entries();
+
+        assert_eq!(entries.iter().map(|e| e.key.as_str()).collect::<Vec<_>>(), vec!["greeting", "broken", "2"]);
+        let greeting = result.entry("greeting").unwrap();
+        assert_eq!(greeting.status, OperationStatus::Applied);
+        assert_eq!((greeting.replacements_made, greeting.replacement_positions), (1, vec![0]));
+        let broken = result.entry("broken").unwrap();
+        assert_eq!(broken.status, OperationStatus::Failed);
+        assert!(broken.error.is_some());
+        assert_eq!(result.entry("2").unwrap().status, OperationStatus::NoMatch);
+
+        // Labels must be unique
+        let duplicate = vec![
+            EditOperation::new("a", "b", false).with_label("x"),
+            EditOperation::new("c", "d", false).with_label("x"),
+        ];
+        assert!(editor.apply_edits("abc", &duplicate).is_err());
+        assert!(editor.validate_operations(&duplicate).is_err());
+
+        // ...and can't collide with the index key of an unlabelled operation
+        let clashing = vec![
+            EditOperation::new("a", "b", false).with_label("1"),
+            EditOperation::new("c", "d", false),
+        ];
+        assert!(editor.apply_edits_best_effort("abc", &clashing).is_err());
+        assert!(editor.validate_operations(&clashing).is_err());
+        let numbered = vec![
+            EditOperation::new("a", "b", false).with_label("1"),
+            EditOperation::new("c", "d", false).with_label("0"),
+        ];
+        let keys: Vec<_> = editor.apply_edits("abc", &numbered).unwrap().entries().into_iter().map(|e| e.key).collect();
+        assert_eq!(keys, vec!["1", "0"]);
+    }
+
+    #[test]
+    fn test_already_applied_entries() {
+        use crate::types::OperationStatus;
+
+        let editor = MultiEditor::new().detect_already_applied(true);
+        let operations = vec![
+            EditOperation::new("a", "b", false).with_label("first"),
+            EditOperation::new("a", "b", false).with_label("retry"),
+        ];
+        let result = editor.apply_edits("a", &operations).unwrap();
+
+        assert_eq!(result.entry("first").unwrap().status, OperationStatus::Applied);
+        assert_eq!(result.entry("retry").unwrap().status, OperationStatus::AlreadyApplied);
+    }
+}
@@ -11,26 +11,94 @@
 //! - Detailed result statistics and performance metrics
 //! - Support for replace_all flag
 
-use crate::edit::matcher::{StringMatcher, MatchConfig};
-use crate::error::{EditError, Result};
-use crate::types::{EditOperation, EditResult, PerformanceMetrics};
+use crate::edit::matcher::{match_case, Match, StringMatcher, MatchConfig};
+use crate::error::{EditError, MatchCandidate, Result};
+use crate::types::{EditOperation, EditResult, EditTarget, PerformanceMetrics};
 use std::time::Instant;
 
 /// Core single string replacement engine
 pub struct SingleEditor {
-    // Note: matcher configuration is created dynamically per operation
+    /// Base matcher configuration; match limits are set per operation
+    matcher_config: MatchConfig,
+    /// Whether single-occurrence edits must match exactly once
+    require_unique: bool,
+    /// Whether to check for `new_string` when nothing matches
+    detect_already_applied: bool,
 }
 
 impl SingleEditor {
     /// Create a new single editor
     pub fn new() -> Self {
-        Self {}
+        Self::with_matcher_config(MatchConfig::default())
     }
 
     /// Create a single editor with custom matcher configuration
-    pub fn with_matcher_config(_config: MatchConfig) -> Self {
-        // Configuration is applied per operation, not stored in the editor
-        Self {}
+    ///
+    /// `max_matches` and `find_all` are overridden by each operation's
+    /// `replace_all` flag; the remaining options apply to every edit.
+    pub fn with_matcher_config(config: MatchConfig) -> Self {
+        Self { matcher_config: config, require_unique: false, detect_already_applied: false }
+    }
+
+    /// Fail single-occurrence edits that match more than once
+    ///
+    /// When set, an operation without `replace_all` (and not addressing an
+    /// N-th occurrence) that finds several matches returns
+    /// `EditError::AmbiguousMatch` listing every candidate, instead of
+    /// editing the first.
+    pub fn require_unique_match(mut self, require: bool) -> Self {
+        self.require_unique = require;
+        self
+    }
+
+    /// Report edits whose result is already present
+    ///
+    /// When set, an operation that matches nothing but whose non-empty
+    /// `new_string` occurs in the content returns an unchanged result with
+    /// `already_applied` set, so a retried edit can be told apart from a
+    /// wrong `old_string`.
+    pub fn detect_already_applied(mut self, detect: bool) -> Self {
+        self.detect_already_applied = detect;
+        self
+    }
+
+    fn matcher_for(&self, operation: &EditOperation) -> StringMatcher {
+        let first_only = !operation.replace_all
+            && operation.target == EditTarget::Everywhere
+            && !self.require_unique;
+        StringMatcher::with_config(MatchConfig {
+            max_matches: if first_only { 1 } else { 0 },
+            find_all: !first_only,
+            ..self.matcher_config.clone()
+        })
+    }
+
+    /// Find the matches an operation addresses
+    fn find_targets(&self, content: &str, operation: &EditOperation) -> Result<Vec<Match>> {
+        let matcher = self.matcher_for(operation);
+        let mut matches = matcher.find_all(content, &operation.old_string)?;
+
+        match &operation.target {
+            EditTarget::Everywhere => {}
+            EditTarget::Nth(n) => {
+                return Ok(if *n <= matches.len() { vec![matches.swap_remove(n - 1)] } else { Vec::new() });
+            }
+            EditTarget::Lines { start, end } => {
+                let (range_start, range_end) = line_range(content, *start, *end);
+                matches.retain(|m| m.start >= range_start && m.end <= range_end);
+            }
+            EditTarget::After(anchor) => {
+                let anchor_end = matcher.find_first(content, anchor)?.map(|m| m.end);
+                matches.retain(|m| anchor_end.is_some_and(|anchor_end| m.start >= anchor_end));
+            }
+        }
+        if !operation.replace_all {
+            if self.require_unique && matches.len() > 1 {
+                return Err(ambiguous_match(content, &operation.old_string, &matches));
+            }
+            matches.truncate(1);
+        }
+        Ok(matches)
     }
 
     /// Perform a single string replacement operation
@@ -41,16 +109,8 @@ impl SingleEditor {
         // Validate the operation
         operation.validate()?;
 
-        // Configure matcher for this operation
-        let matcher_config = MatchConfig {
-            max_matches: if operation.replace_all { 0 } else { 1 },
-            find_all: operation.replace_all,
-            case_sensitive: true,
-        };
-        let matcher = StringMatcher::with_config(matcher_config);
-
-        // Find all matches
-        let matches = matcher.find_all(content, &operation.old_string)?;
+        // Find the matches this operation addresses
+        let matches = self.find_targets(content, operation)?;
 
         if matches.is_empty() {
             // No matches found - return original content unchanged
@@ -64,16 +124,23 @@ impl SingleEditor {
                 bytes_searched: original_size,
             };
 
+            let already_applied = self.detect_already_applied
+                && !operation.new_string.is_empty()
+                && StringMatcher::with_config(self.matcher_config.clone())
+                    .find_first(content, &operation.new_string)?
+                    .is_some();
+
             return Ok(EditResult::no_changes(operation.clone(), content.to_string())
-                .with_metrics(metrics));
+                .with_metrics(metrics)
+                .with_already_applied(already_applied));
         }
 
         // Perform replacements
         let (new_content, replacement_count) = Self::apply_replacements(
             content,
             &matches,
-            &operation.old_string,
             &operation.new_string,
+            self.matcher_config.preserve_case,
         )?;
 
         // Calculate metrics
@@ -88,46 +155,50 @@ impl SingleEditor {
             bytes_searched: original_size,
         };
 
-        // Extract positions
+        // Extract positions and the exact text replaced at each
         let positions: Vec<usize> = matches.iter().map(|m| m.start).collect();
+        let matched_texts: Vec<String> = matches.into_iter().map(|m| m.matched_text).collect();
 
-        Ok(EditResult::success(
+        // Relaxed matching can change content even when old_string == new_string
+        let changed = new_content != content;
+        let mut result = EditResult::success(
             operation.clone(),
             new_content,
             replacement_count,
             positions,
-        ).with_metrics(metrics))
+        ).with_metrics(metrics).with_matched_texts(matched_texts);
+        result.changed = changed;
+        Ok(result)
     }
 
     /// Apply replacements at the found match positions
     fn apply_replacements(
         content: &str,
-        matches: &[crate::edit::matcher::Match],
-        old_string: &str,
+        matches: &[Match],
         new_string: &str,
+        preserve_case: bool,
     ) -> Result<(String, usize)> {
         if matches.is_empty() {
             return Ok((content.to_string(), 0));
         }
 
         // Calculate the final size to pre-allocate string buffer
-        let old_len = old_string.len();
-        let new_len = new_string.len();
-        let size_delta = (new_len as i64 - old_len as i64) * matches.len() as i64;
-        let final_size = (content.len() as i64 + size_delta) as usize;
+        let matched_len: usize = matches.iter().map(|m| m.byte_len()).sum();
+        let final_size = content.len() - matched_len + new_string.len() * matches.len();
 
         let mut result = String::with_capacity(final_size);
         let mut last_end = 0;
 
         // Process matches from left to right
         for m in matches {
-            // Verify the match is what we expect
+            // Verify the match is what we expect; with normalization-insensitive
+            // matching it may differ from old_string in its raw form
             let matched_text = &content[m.start..m.end];
-            if matched_text != old_string {
+            if matched_text != m.matched_text {
                 return Err(EditError::Internal {
                     details: format!(
                         "Match verification failed: expected '{}', found '{}'",
-                        old_string, matched_text
+                        m.matched_text, matched_text
                     ),
                     context: Some(format!("Position {}-{}", m.start, m.end)),
                 });
@@ -137,7 +208,11 @@ impl SingleEditor {
             result.push_str(&content[last_end..m.start]);
 
             // Add the replacement text
-            result.push_str(new_string);
+            if preserve_case {
+                result.push_str(&match_case(matched_text, new_string));
+            } else {
+                result.push_str(new_string);
+            }
 
             // Move past this match
             last_end = m.end;
@@ -153,14 +228,7 @@ impl SingleEditor {
     pub fn count_replacements(&self, content: &str, operation: &EditOperation) -> Result<usize> {
         operation.validate()?;
 
-        let matcher_config = MatchConfig {
-            max_matches: if operation.replace_all { 0 } else { 1 },
-            find_all: operation.replace_all,
-            case_sensitive: true,
-        };
-        let matcher = StringMatcher::with_config(matcher_config);
-
-        matcher.count_matches(content, &operation.old_string)
+        Ok(self.find_targets(content, operation)?.len())
     }
 
     /// Preview what the edit result would be (for debugging/testing)
@@ -171,6 +239,68 @@ impl SingleEditor {
     }
 }
 
+/// Build an `AmbiguousMatch` error describing every candidate
+fn ambiguous_match(content: &str, pattern: &str, matches: &[Match]) -> EditError {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    // Index of the line containing `offset`
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset) - 1;
+    let line_start = |line: usize| line_starts[line];
+    let line_end = |line: usize| line_starts.get(line + 1).map_or(content.len(), |&next| next - 1);
+
+    let candidates = matches.iter().map(|m| {
+        let first = line_of(m.start);
+        let last = line_of(m.end.saturating_sub(1).max(m.start));
+        let last_line = line_starts.len() - 1;
+
+        // Grow whole lines outwards, alternating sides, until the text is unique
+        let (mut from, mut to) = (first, last);
+        let mut unique_old_string = None;
+        for step in 0..=6 {
+            let text = &content[line_start(from)..line_end(to)];
+            if content.matches(text).count() == 1 {
+                unique_old_string = Some(text.to_string());
+                break;
+            }
+            if step % 2 == 0 && from > 0 {
+                from -= 1;
+            } else if to < last_line {
+                to += 1;
+            } else if from > 0 {
+                from -= 1;
+            } else {
+                break;
+            }
+        }
+
+        MatchCandidate {
+            offset: m.start,
+            line: first + 1,
+            column: content[line_start(first)..m.start].chars().count() + 1,
+            context: content[line_start(first.saturating_sub(1))..line_end((last + 1).min(last_line))].to_string(),
+            unique_old_string,
+        }
+    }).collect();
+
+    EditError::AmbiguousMatch {
+        pattern: pattern.to_string(),
+        candidates,
+    }
+}
+
+/// Byte range covering lines `start..=end` (1-based), newlines included;
+/// lines past the end of the content are empty, so `end` may be
+/// `usize::MAX` to mean the last line
+fn line_range(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let past_last = line_starts.len() + 1;
+    let offset = |line: usize| line_starts.get(line.min(past_last) - 1).copied().unwrap_or(content.len());
+    (offset(start), offset(end.saturating_add(1)))
+}
+
 impl Default for SingleEditor {
     fn default() -> Self {
         Self::new()
@@ -322,4 +452,162 @@ mod tests {
         assert_eq!(result.content, "hi world hi");
         assert_eq!(result.replacements_made, 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_grapheme_safe_normalized_edit() {
+        let editor = SingleEditor::with_matcher_config(MatchConfig {
+            normalization_insensitive: true,
+            grapheme_boundaries: true,
+            ..Default::default()
+        });
+        let operation = EditOperation::new("caf\u{e9}", "bar", true);
+        let result = editor.apply_edit("cafe\u{301} caf\u{e9} cafe", &operation).unwrap();
+
+        assert_eq!(result.content, "bar bar cafe");
+        assert_eq!(result.replacement_positions, vec![0, 7]);
+
+        // The "e" inside "e\u{301}" is left alone
+        let operation = EditOperation::new("e", "E", true);
+        let result = editor.apply_edit("cafe\u{301} cafe", &operation).unwrap();
+        assert_eq!(result.content, "cafe\u{301} cafE");
+    }
+
+    #[test]
+    fn test_case_preserving_replacement() {
+        let editor = SingleEditor::with_matcher_config(MatchConfig {
+            case_sensitive: false,
+            preserve_case: true,
+            ..Default::default()
+        });
+        let operation = EditOperation::new("foo", "bar", true);
+        let result = editor.apply_edit("foo Foo FOO fOo", &operation).unwrap();
+
+        assert_eq!(result.content, "bar Bar BAR bar");
+        assert_eq!(result.replacements_made, 4);
+    }
+
+    #[test]
+    fn test_identifier_rename() {
+        use crate::edit::matcher::MatchBoundary;
+
+        let editor = SingleEditor::with_matcher_config(MatchConfig {
+            boundary: MatchBoundary::Identifier,
+            ..Default::default()
+        });
+        let operation = EditOperation::new("count", "total", true);
+        let result = editor.apply_edit("let count = account.count + count_of(count);", &operation).unwrap();
+
+        assert_eq!(result.content, "let total = account.total + count_of(total);");
+        assert_eq!(result.replacements_made, 3);
+    }
+
+    #[test]
+    fn test_nth_occurrence() {
+        let editor = SingleEditor::new();
+        let operation = EditOperation::new("x", "y", false).nth_occurrence(2);
+        let result = editor.apply_edit("x x x", &operation).unwrap();
+        assert_eq!(result.content, "x y x");
+        assert_eq!(result.replacement_positions, vec![2]);
+
+        let operation = EditOperation::new("x", "y", true).nth_occurrence(4);
+        assert!(!editor.apply_edit("x x x", &operation).unwrap().changed);
+
+        let operation = EditOperation::new("x", "y", true).nth_occurrence(0);
+        assert!(matches!(editor.apply_edit("x", &operation), Err(EditError::InvalidOperation { .. })));
+    }
+
+    #[test]
+    fn test_within_lines() {
+        let editor = SingleEditor::new();
+        let content = "a = 1\na = 2\na = 3\na = 4";
+        let operation = EditOperation::new("a", "b", true).within_lines(2, 3);
+        let result = editor.apply_edit(content, &operation).unwrap();
+        assert_eq!(result.content, "a = 1\nb = 2\nb = 3\na = 4");
+
+        let operation = EditOperation::new("a", "b", false).within_lines(3, 10);
+        assert_eq!(editor.count_replacements(content, &operation).unwrap(), 1);
+        let result = editor.apply_edit(content, &operation).unwrap();
+        assert_eq!(result.content, "a = 1\na = 2\nb = 3\na = 4");
+
+        // An open-ended range runs to the end of the content
+        let operation = EditOperation::new("a", "b", true).within_lines(3, usize::MAX);
+        let result = editor.apply_edit(content, &operation).unwrap();
+        assert_eq!(result.content, "a = 1\na = 2\nb = 3\nb = 4");
+        let operation = EditOperation::new("a", "b", true).within_lines(usize::MAX, usize::MAX);
+        assert!(!editor.apply_edit(content, &operation).unwrap().changed);
+    }
+
+    #[test]
+    fn test_after_anchor() {
+        let editor = SingleEditor::new();
+        let content = "fn a() { ok } fn b() { ok }";
+        let operation = EditOperation::new("ok", "done", false).after_anchor("fn b");
+        let result = editor.apply_edit(content, &operation).unwrap();
+        assert_eq!(result.content, "fn a() { ok } fn b() { done }");
+
+        // Missing anchor: nothing is addressed
+        let operation = EditOperation::new("ok", "done", true).after_anchor("fn c");
+        assert_eq!(editor.apply_edit(content, &operation).unwrap().replacements_made, 0);
+    }
+
+    #[test]
+    fn test_ambiguous_match() {
+        let editor = SingleEditor::new().require_unique_match(true);
+        let content = "fn a() {\n    ok()\n}\nfn b() {\n    ok()\n}";
+        let operation = EditOperation::new("ok()", "done()", false);
+
+        let Err(EditError::AmbiguousMatch { pattern, candidates }) = editor.apply_edit(content, &operation) else {
+            panic!("expected an ambiguous match");
+        };
+        assert_eq!(pattern, "ok()");
+        assert_eq!(candidates.len(), 2);
+        assert_eq!((candidates[0].line, candidates[0].column), (2, 5));
+        assert_eq!((candidates[1].line, candidates[1].column), (5, 5));
+        assert_eq!(candidates[1].context, "fn b() {\n    ok()\n}");
+        assert_eq!(candidates[0].unique_old_string.as_deref(), Some("fn a() {\n    ok()"));
+
+        // The suggestion makes the edit unambiguous
+        let retry = EditOperation::new(candidates[1].unique_old_string.clone().unwrap(), "fn b() {\n    done()", false);
+        assert!(editor.apply_edit(content, &retry).unwrap().changed);
+
+        // Unique matches, replace_all and N-th occurrences are unaffected
+        assert!(editor.apply_edit(content, &EditOperation::new("fn a", "fn c", false)).is_ok());
+        assert_eq!(editor.apply_edit(content, &EditOperation::new("ok()", "x", true)).unwrap().replacements_made, 2);
+        assert!(editor.apply_edit(content, &operation.clone().nth_occurrence(2)).is_ok());
+    }
+
+    #[test]
+    fn test_relaxed_block_edit_records_matched_text() {
+        let editor = SingleEditor::with_matcher_config(MatchConfig {
+            ignore_blank_lines: true,
+            ignore_trailing_whitespace: true,
+            ..Default::default()
+        });
+        let content = "if ready {   \n\n    go();\n}\n";
+        let operation = EditOperation::new("if ready {\n    go();\n}", "if ready {\n    go();\n    done();\n}", false);
+        let result = editor.apply_edit(content, &operation).unwrap();
+
+        assert_eq!(result.content, "if ready {\n    go();\n    done();\n}\n");
+        assert_eq!(result.matched_texts, vec!["if ready {   \n\n    go();\n}".to_string()]);
+
+        // Rewriting a block to its own normalized form is still a change
+        let operation = EditOperation::new("if ready {\n    go();\n}", "if ready {\n    go();\n}", false);
+        assert!(editor.apply_edit(content, &operation).unwrap().changed);
+    }
+
+    #[test]
+    fn test_already_applied_detection() {
+        let editor = SingleEditor::new().detect_already_applied(true);
+        let operation = EditOperation::new("retries = 3", "retries = 5", false);
+
+        let result = editor.apply_edit("retries = 5", &operation).unwrap();
+        assert!(result.already_applied);
+        assert!(!result.changed);
+
+        // Neither string present: a plain no-match
+        assert!(!editor.apply_edit("timeout = 1", &operation).unwrap().already_applied);
+
+        // Off by default
+        assert!(!SingleEditor::new().apply_edit("retries = 5", &operation).unwrap().already_applied);
+    }
+}
@@ -38,6 +38,14 @@ pub enum EditError {
         positions: Vec<usize>,
     },
     
+    /// Several matches found for a single-occurrence edit; each candidate
+    /// carries its location, context and a unique old_string to retry with
+    #[error("Ambiguous match for pattern '{pattern}': {} occurrences at lines {}", .candidates.len(), candidate_lines(.candidates))]
+    AmbiguousMatch {
+        pattern: String,
+        candidates: Vec<MatchCandidate>,
+    },
+    
     /// Text encoding errors with detailed position information
     #[error("Invalid UTF-8 encoding at byte position {position}: {details}")]
     EncodingError {
@@ -59,6 +67,21 @@ pub enum EditError {
         path: String,
     },
     
+    /// Write blocked by a configured guardrail
+    #[error("Guardrail '{guardrail}' blocked the write: {details}")]
+    GuardrailViolation {
+        guardrail: String,
+        details: String,
+        path: Option<String>,
+    },
+    
+    /// String edit attempted on a file detected as binary
+    #[error("Refusing to edit binary file '{path}' (detected {mime})")]
+    BinaryFile {
+        path: String,
+        mime: String,
+    },
+    
     /// I/O operation failures with enhanced context
     #[error("I/O error on '{path}': {operation} - {source}")]
     IoError {
@@ -152,6 +175,26 @@ pub enum EditError {
     },
 }
 
+/// One occurrence reported by [`EditError::AmbiguousMatch`]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MatchCandidate {
+    /// Byte offset of the occurrence
+    pub offset: usize,
+    /// Line number of the occurrence (1-based)
+    pub line: usize,
+    /// Column of the occurrence in characters (1-based)
+    pub column: usize,
+    /// The lines containing the occurrence, plus one line on each side
+    pub context: String,
+    /// The smallest run of whole lines around the occurrence that appears
+    /// exactly once, usable as a more specific old_string
+    pub unique_old_string: Option<String>,
+}
+
+fn candidate_lines(candidates: &[MatchCandidate]) -> String {
+    candidates.iter().map(|c| c.line.to_string()).collect::<Vec<_>>().join(", ")
+}
+
 // Implement From traits for common error types
 impl From<std::io::Error> for EditError {
     fn from(error: std::io::Error) -> Self {
@@ -338,9 +381,21 @@ impl EditError {
             EditError::MultipleMatches { pattern, count, .. } => {
                 format!("Found {} matches for '{}', expected only one", count, pattern)
             },
+            EditError::AmbiguousMatch { pattern, candidates } => {
+                format!(
+                    "Found {} matches for '{}' (lines {}); include more surrounding text or set replace_all",
+                    candidates.len(), pattern, candidate_lines(candidates)
+                )
+            },
             EditError::EncodingError { .. } => {
                 "File contains invalid text encoding".to_string()
             },
+            EditError::GuardrailViolation { guardrail, details, .. } => {
+                format!("Write refused by the {} guardrail: {}", guardrail, details)
+            },
+            EditError::BinaryFile { path, mime } => {
+                format!("{} is a binary file ({}) and cannot be edited as text", path, mime)
+            },
             EditError::FileTooLarge { size, max_size, .. } => {
                 format!("File is too large ({} bytes, maximum {} bytes)", size, max_size)
             },
@@ -364,10 +419,12 @@ impl EditError {
             EditError::FileTooLarge { file_path: path, .. } |
             EditError::FileNotFound { path, .. } |
             EditError::PermissionDenied { path, .. } |
+            EditError::BinaryFile { path, .. } |
             EditError::DirectoryNotEmpty { path } |
             EditError::ConcurrencyError { path } |
             EditError::InsufficientSpace { path } => Some(path),
-            EditError::EncodingError { file_path, .. } => file_path.as_deref(),
+            EditError::EncodingError { file_path, .. } |
+            EditError::GuardrailViolation { path: file_path, .. } => file_path.as_deref(),
             _ => None,
         }
     }
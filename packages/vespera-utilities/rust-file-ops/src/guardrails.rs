@@ -0,0 +1,271 @@
+//! Write guardrails for autonomous callers
+//!
+//! [`Guardrails`] caps how much a single operation and the whole process
+//! may write, how many files one bulk operation may modify, and which paths
+//! may never be written. Every writing API checks the guardrails in its
+//! [`EditConfig`](crate::EditConfig) before touching the disk and fails with
+//! [`EditError::GuardrailViolation`] instead of writing part of the change.
+//! All limits are off by default.
+
+use crate::error::{EditError, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bytes written by guarded operations in this process
+static SESSION_BYTES_WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+/// Limits applied before any write
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Guardrails {
+    /// Maximum bytes a single operation may write (all files combined)
+    pub max_bytes_per_operation: Option<u64>,
+    /// Maximum bytes guarded operations may write over the process lifetime
+    pub max_bytes_per_session: Option<u64>,
+    /// Maximum files a single bulk operation may modify
+    pub max_files_per_operation: Option<usize>,
+    /// Glob patterns for paths that must never be written, matched against
+    /// the absolute path (`*` stays within one directory, `**` crosses them)
+    pub protected_paths: Vec<String>,
+}
+
+impl Guardrails {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Conservative limits for agents with write access: 10 MiB per
+    /// operation, 200 MiB per session, 50 files per bulk operation, and
+    /// version-control metadata, environment files and keys protected
+    pub fn for_agents() -> Self {
+        Self {
+            max_bytes_per_operation: Some(10 * 1024 * 1024),
+            max_bytes_per_session: Some(200 * 1024 * 1024),
+            max_files_per_operation: Some(50),
+            protected_paths: [
+                "**/.git/**", "**/.hg/**", "**/.svn/**",
+                "**/.env", "**/.env.*", "**/.ssh/**", "**/*.pem", "**/*.key",
+            ].iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Set the per-operation byte limit
+    pub fn with_max_bytes_per_operation(mut self, bytes: u64) -> Self {
+        self.max_bytes_per_operation = Some(bytes);
+        self
+    }
+
+    /// Set the per-session byte limit
+    pub fn with_max_bytes_per_session(mut self, bytes: u64) -> Self {
+        self.max_bytes_per_session = Some(bytes);
+        self
+    }
+
+    /// Set the per-operation file limit
+    pub fn with_max_files_per_operation(mut self, files: usize) -> Self {
+        self.max_files_per_operation = Some(files);
+        self
+    }
+
+    /// Add a protected path pattern
+    pub fn with_protected_path(mut self, pattern: impl Into<String>) -> Self {
+        self.protected_paths.push(pattern.into());
+        self
+    }
+
+    /// Whether any limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes_per_operation.is_some()
+            || self.max_bytes_per_session.is_some()
+            || self.max_files_per_operation.is_some()
+            || !self.protected_paths.is_empty()
+    }
+
+    /// Check that one operation may write `writes` (path and byte count of
+    /// each file), and count the bytes against the session limit
+    ///
+    /// Either every write is allowed or none is; bytes are counted when
+    /// allowed, even if the write itself later fails.
+    pub fn check_writes(&self, writes: &[(&Path, u64)]) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        if let Some(limit) = self.max_files_per_operation {
+            if writes.len() > limit {
+                return Err(violation(
+                    "max_files_per_operation",
+                    format!("operation would modify {} files, limit is {}", writes.len(), limit),
+                    None,
+                ));
+            }
+        }
+
+        if !self.protected_paths.is_empty() {
+            let protected = self.protected_set()?;
+            for (path, _) in writes {
+                if candidates(path).iter().any(|candidate| protected.is_match(candidate)) {
+                    return Err(violation(
+                        "protected_paths",
+                        "path matches a protected pattern".to_string(),
+                        Some(path),
+                    ));
+                }
+            }
+        }
+
+        let total: u64 = writes.iter().map(|(_, bytes)| bytes).sum();
+        if let Some(limit) = self.max_bytes_per_operation {
+            if total > limit {
+                return Err(violation(
+                    "max_bytes_per_operation",
+                    format!("operation would write {} bytes, limit is {}", total, limit),
+                    (writes.len() == 1).then(|| writes[0].0),
+                ));
+            }
+        }
+
+        match self.max_bytes_per_session {
+            Some(limit) => SESSION_BYTES_WRITTEN
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |written| {
+                    written.checked_add(total).filter(|&after| after <= limit)
+                })
+                .map(|_| ())
+                .map_err(|written| violation(
+                    "max_bytes_per_session",
+                    format!("{} bytes already written this session, {} more would exceed {}", written, total, limit),
+                    (writes.len() == 1).then(|| writes[0].0),
+                )),
+            None => {
+                SESSION_BYTES_WRITTEN.fetch_add(total, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check a single-file write
+    pub fn check_write(&self, path: &Path, bytes: u64) -> Result<()> {
+        self.check_writes(&[(path, bytes)])
+    }
+
+    fn protected_set(&self) -> Result<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.protected_paths {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| EditError::InvalidPattern {
+                    pattern: pattern.clone(),
+                    reason: e.to_string(),
+                })?;
+            builder.add(glob);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// Bytes written by guarded operations in this process so far
+pub fn session_bytes_written() -> u64 {
+    SESSION_BYTES_WRITTEN.load(Ordering::SeqCst)
+}
+
+/// Reset the session byte counter, e.g. when a new agent session starts
+pub fn reset_session_bytes() {
+    SESSION_BYTES_WRITTEN.store(0, Ordering::SeqCst);
+}
+
+/// The absolute path as given and, when it resolves, with symlinks
+/// resolved, so a link can't be used to reach a protected file
+fn candidates(path: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::with_capacity(2);
+    if let Ok(absolute) = std::path::absolute(path) {
+        paths.push(absolute);
+    }
+    let resolved = path.canonicalize().ok().or_else(|| {
+        let parent = path.parent()?.canonicalize().ok()?;
+        Some(parent.join(path.file_name()?))
+    });
+    paths.extend(resolved);
+    paths
+}
+
+fn violation(guardrail: &str, details: String, path: Option<&Path>) -> EditError {
+    EditError::GuardrailViolation {
+        guardrail: guardrail.to_string(),
+        details,
+        path: path.map(|p| p.display().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guardrail_name(result: Result<()>) -> String {
+        match result {
+            Err(EditError::GuardrailViolation { guardrail, .. }) => guardrail,
+            other => panic!("expected a guardrail violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let guardrails = Guardrails::new().with_max_bytes_per_operation(10).with_max_files_per_operation(2);
+        let (a, b, c) = (Path::new("a.txt"), Path::new("b.txt"), Path::new("c.txt"));
+
+        assert!(guardrails.check_writes(&[(a, 5), (b, 5)]).is_ok());
+        assert_eq!(guardrail_name(guardrails.check_writes(&[(a, 6), (b, 5)])), "max_bytes_per_operation");
+        assert_eq!(guardrail_name(guardrails.check_writes(&[(a, 1), (b, 1), (c, 1)])), "max_files_per_operation");
+        assert!(Guardrails::new().check_write(a, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_protected_paths() {
+        let guardrails = Guardrails::for_agents();
+        let result = guardrails.check_write(Path::new("project/.git/config"), 1);
+        assert_eq!(guardrail_name(result), "protected_paths");
+        assert_eq!(guardrail_name(guardrails.check_write(Path::new(".env"), 1)), "protected_paths");
+        assert!(guardrails.check_write(Path::new("project/src/.envrc.md"), 1).is_ok());
+    }
+
+    #[test]
+    fn test_session_limit() {
+        // The counter is process-wide, so measure relative to its current value
+        let start = session_bytes_written();
+        let guardrails = Guardrails::new().with_max_bytes_per_session(start + 100);
+        assert!(guardrails.check_write(Path::new("log.txt"), 60).is_ok());
+        assert!(session_bytes_written() >= start + 60);
+        let result = Guardrails::new().with_max_bytes_per_session(session_bytes_written() + 10)
+            .check_write(Path::new("log.txt"), 11);
+        assert_eq!(guardrail_name(result), "max_bytes_per_session");
+    }
+
+    #[test]
+    fn test_writing_apis_respect_guardrails() {
+        let dir = std::env::temp_dir().join(format!("vespera-guardrails-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = [dir.join("a.txt"), dir.join("b.txt")];
+        for path in &paths {
+            std::fs::write(path, "old").unwrap();
+        }
+        let operations = [crate::EditOperation::new("old", "new", false)];
+
+        let config = crate::EditConfig::new().with_guardrails(Guardrails::new().with_max_files_per_operation(1));
+        let result = crate::multi_edit_files(&paths, &operations, Some(config.clone()));
+        assert_eq!(guardrail_name(result.map(|_| ())), "max_files_per_operation");
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "old");
+
+        // A single file is within the limit
+        crate::multi_edit_files(&paths[..1], &operations, Some(config)).unwrap();
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "new");
+
+        let protected = Guardrails::new().with_protected_path("**/b.txt");
+        let config = crate::EditConfig::new().with_guardrails(protected);
+        let result = crate::edit_file(&paths[1], &operations[0], Some(config));
+        assert_eq!(guardrail_name(result.map(|_| ())), "protected_paths");
+        assert_eq!(std::fs::read_to_string(&paths[1]).unwrap(), "old");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
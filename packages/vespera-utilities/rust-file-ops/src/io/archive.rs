@@ -0,0 +1,624 @@
+//! Reading and editing files inside zip and tar archives
+//!
+//! Documentation bundles and exported projects often arrive as `.zip` or
+//! `.tar.gz` files. This module lists their entries, extracts a selection of
+//! them, and applies string edits to a single entry in place. Edited archives
+//! are rebuilt in memory and written atomically; untouched zip entries are
+//! copied without recompression and tar entries keep their headers.
+//!
+//! Sizes stored in an archive are not trusted: an entry is refused before
+//! anything is allocated for it when it claims more than
+//! [`EditConfig::max_file_size`], and reads stop once an entry passes that
+//! limit or the entries read so far pass [`EditConfig::max_archive_size`].
+
+use crate::edit::multi::MultiEditor;
+use crate::error::{EditError, Result};
+use crate::io::kind::detect_content_kind;
+use crate::io::writer::AtomicFileWriter;
+use crate::security::{validate_path, validate_path_characters};
+use crate::types::{EditConfig, EditOperation, MultiEditResult};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Detect the format from the file extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if name.ends_with(".zip") {
+            Ok(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Ok(Self::Tar)
+        } else {
+            Err(EditError::InvalidInput {
+                message: format!("Unsupported archive type: {}", path.display()),
+                context: Some("expected .zip, .tar, .tar.gz or .tgz".to_string()),
+            })
+        }
+    }
+}
+
+/// One entry in an archive
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveEntry {
+    /// Path inside the archive, without any leading `./`
+    pub path: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Whether the entry is a directory
+    pub is_dir: bool,
+}
+
+/// List the entries of an archive in stored order
+pub fn list_archive(path: impl AsRef<Path>) -> Result<Vec<ArchiveEntry>> {
+    let path = path.as_ref();
+    let mut entries = Vec::new();
+    match ArchiveFormat::from_path(path)? {
+        ArchiveFormat::Zip => {
+            let mut archive = open_zip(path)?;
+            for i in 0..archive.len() {
+                let file = archive.by_index_raw(i).map_err(|e| zip_error(e, path))?;
+                entries.push(ArchiveEntry {
+                    path: entry_name(file.name()),
+                    size: file.size(),
+                    is_dir: file.is_dir(),
+                });
+            }
+        }
+        format => {
+            let mut archive = open_tar(path, format)?;
+            for entry in archive.entries().map_err(|e| io_error(e, path))? {
+                let entry = entry.map_err(|e| io_error(e, path))?;
+                let name = entry_name(&entry.path().map_err(|e| io_error(e, path))?.to_string_lossy());
+                if name.is_empty() {
+                    continue;
+                }
+                entries.push(ArchiveEntry {
+                    path: name,
+                    size: entry.header().size().unwrap_or(0),
+                    is_dir: entry.header().entry_type().is_dir(),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Read the contents of one entry, no larger than `config.max_file_size`
+pub fn read_archive_entry(path: impl AsRef<Path>, entry: &str, config: Option<EditConfig>) -> Result<Vec<u8>> {
+    let config = config.unwrap_or_default();
+    let path = path.as_ref();
+    let wanted = entry_name(entry);
+    match ArchiveFormat::from_path(path)? {
+        ArchiveFormat::Zip => {
+            let mut archive = open_zip(path)?;
+            let index = zip_index(&mut archive, &wanted, path)?;
+            let file = archive.by_index(index).map_err(|e| zip_error(e, path))?;
+            let declared = file.size();
+            read_entry(file, declared, config.max_file_size, &wanted, path)
+        }
+        format => {
+            let mut archive = open_tar(path, format)?;
+            for tar_entry in archive.entries().map_err(|e| io_error(e, path))? {
+                let tar_entry = tar_entry.map_err(|e| io_error(e, path))?;
+                let name = entry_name(&tar_entry.path().map_err(|e| io_error(e, path))?.to_string_lossy());
+                if name == wanted && tar_entry.header().entry_type().is_file() {
+                    let declared = tar_entry.size();
+                    return read_entry(tar_entry, declared, config.max_file_size, &wanted, path);
+                }
+            }
+            Err(entry_not_found(&wanted, path))
+        }
+    }
+}
+
+/// Extract the files matching any of `patterns` (glob syntax; all files
+/// when empty) into `destination`, returning the written paths
+///
+/// Entries that would land outside `destination` (absolute paths or `..`)
+/// are refused, as are tar links. Extraction fails with
+/// [`EditError::FileTooLarge`] at an entry over `config.max_file_size`, or
+/// once the files selected come to more than `config.max_archive_size`.
+/// Every selected entry is read before anything is written, so the whole
+/// extraction is checked against `config.guardrails` up front and nothing
+/// is written when a limit is hit.
+pub fn extract_archive(
+    path: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    patterns: &[String],
+    config: Option<EditConfig>,
+) -> Result<Vec<PathBuf>> {
+    let config = config.unwrap_or_default();
+    let path = path.as_ref();
+    let destination = destination.as_ref();
+    let filter = build_filter(patterns)?;
+    let selected = |name: &str| filter.as_ref().is_none_or(|set| set.is_match(name));
+    let mut budget = ArchiveBudget::new(&config, path);
+
+    let mut files = Vec::new();
+    match ArchiveFormat::from_path(path)? {
+        ArchiveFormat::Zip => {
+            let mut archive = open_zip(path)?;
+            for i in 0..archive.len() {
+                let file = archive.by_index(i).map_err(|e| zip_error(e, path))?;
+                let name = entry_name(file.name());
+                if file.is_dir() || !selected(&name) {
+                    continue;
+                }
+                let target = entry_destination(destination, &name)?;
+                let declared = file.size();
+                files.push((target, budget.read(file, declared, &name)?));
+            }
+        }
+        format => {
+            let mut archive = open_tar(path, format)?;
+            for entry in archive.entries().map_err(|e| io_error(e, path))? {
+                let entry = entry.map_err(|e| io_error(e, path))?;
+                let name = entry_name(&entry.path().map_err(|e| io_error(e, path))?.to_string_lossy());
+                if !entry.header().entry_type().is_file() || !selected(&name) {
+                    continue;
+                }
+                let target = entry_destination(destination, &name)?;
+                let declared = entry.size();
+                files.push((target, budget.read(entry, declared, &name)?));
+            }
+        }
+    }
+
+    let writes: Vec<_> = files.iter().map(|(target, data)| (target.as_path(), data.len() as u64)).collect();
+    config.guardrails.check_writes(&writes)?;
+    let mut written = Vec::with_capacity(files.len());
+    for (target, data) in files {
+        write_extracted(&target, &data)?;
+        written.push(target);
+    }
+    Ok(written)
+}
+
+/// Apply `operations` to the text entry `entry` and rewrite the archive
+///
+/// The archive is only rewritten when the entry changed.
+pub fn edit_archive_entry(
+    path: impl AsRef<Path>,
+    entry: &str,
+    operations: &[EditOperation],
+    config: Option<EditConfig>,
+) -> Result<MultiEditResult> {
+    let config = config.unwrap_or_default();
+    let path = path.as_ref();
+    validate_path_characters(path)?;
+    validate_path(path, config.base_dir.as_deref())?;
+
+    let format = ArchiveFormat::from_path(path)?;
+    let wanted = entry_name(entry);
+    let editor = MultiEditor::new()
+        .require_unique_match(config.require_unique_match)
+        .detect_already_applied(config.detect_already_applied);
+    let edit = |data: Vec<u8>| -> Result<MultiEditResult> {
+        let kind = detect_content_kind(&data, Some(Path::new(&wanted)));
+        if kind.is_binary {
+            return Err(EditError::BinaryFile {
+                path: format!("{}:{}", path.display(), wanted),
+                mime: kind.mime,
+            });
+        }
+        let content = String::from_utf8(data).map_err(|e| EditError::EncodingError {
+            position: e.utf8_error().valid_up_to(),
+            details: e.utf8_error().to_string(),
+            file_path: Some(format!("{}:{}", path.display(), wanted)),
+        })?;
+        editor.apply_edits(&content, operations)
+    };
+
+    let (result, rebuilt) = match format {
+        ArchiveFormat::Zip => edit_zip(path, &wanted, &config, edit)?,
+        format => edit_tar(path, format, &wanted, &config, edit)?,
+    };
+    if let Some(bytes) = rebuilt {
+        config.guardrails.check_write(path, bytes.len() as u64)?;
+        let mut writer = AtomicFileWriter::new(path)?;
+        writer.write(&bytes)?;
+        writer.commit()?;
+    }
+    Ok(result)
+}
+
+fn edit_zip(
+    path: &Path,
+    wanted: &str,
+    config: &EditConfig,
+    edit: impl FnOnce(Vec<u8>) -> Result<MultiEditResult>,
+) -> Result<(MultiEditResult, Option<Vec<u8>>)> {
+    let mut archive = open_zip(path)?;
+    let index = zip_index(&mut archive, wanted, path)?;
+
+    let (result, options, name) = {
+        let mut file = archive.by_index(index).map_err(|e| zip_error(e, path))?;
+        let declared = file.size();
+        let data = read_entry(&mut file, declared, config.max_file_size, wanted, path)?;
+        let mut options = SimpleFileOptions::default().compression_method(file.compression());
+        if let Some(modified) = file.last_modified() {
+            options = options.last_modified_time(modified);
+        }
+        if let Some(mode) = file.unix_mode() {
+            options = options.unix_permissions(mode);
+        }
+        (edit(data)?, options, file.name().to_string())
+    };
+    if !result.changed {
+        return Ok((result, None));
+    }
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..archive.len() {
+        if i == index {
+            writer.start_file(name.as_str(), options).map_err(|e| zip_error(e, path))?;
+            writer.write_all(result.content.as_bytes()).map_err(|e| io_error(e, path))?;
+        } else {
+            let file = archive.by_index_raw(i).map_err(|e| zip_error(e, path))?;
+            writer.raw_copy_file(file).map_err(|e| zip_error(e, path))?;
+        }
+    }
+    let bytes = writer.finish().map_err(|e| zip_error(e, path))?.into_inner();
+    Ok((result, Some(bytes)))
+}
+
+fn edit_tar(
+    path: &Path,
+    format: ArchiveFormat,
+    wanted: &str,
+    config: &EditConfig,
+    edit: impl FnOnce(Vec<u8>) -> Result<MultiEditResult>,
+) -> Result<(MultiEditResult, Option<Vec<u8>>)> {
+    let mut edit = Some(edit);
+    let mut result = None;
+    let mut entries = Vec::new();
+    // Every entry is held in memory to rebuild the archive
+    let mut budget = ArchiveBudget::new(config, path);
+
+    let mut archive = open_tar(path, format)?;
+    for entry in archive.entries().map_err(|e| io_error(e, path))? {
+        let mut entry = entry.map_err(|e| io_error(e, path))?;
+        let stored_path = entry.path().map_err(|e| io_error(e, path))?.into_owned();
+        let declared = entry.size();
+        let mut data = budget.read(&mut entry, declared, &stored_path.to_string_lossy())?;
+
+        let is_target = entry_name(&stored_path.to_string_lossy()) == wanted && entry.header().entry_type().is_file();
+        if let (true, Some(edit)) = (is_target, edit.take()) {
+            let edited = edit(data)?;
+            if !edited.changed {
+                return Ok((edited, None));
+            }
+            data = edited.content.clone().into_bytes();
+            result = Some(edited);
+        }
+        entries.push((entry.header().clone(), stored_path, data));
+    }
+    let result = result.ok_or_else(|| entry_not_found(wanted, path))?;
+
+    let encoded = match format {
+        ArchiveFormat::TarGz => {
+            let builder = build_tar(GzEncoder::new(Vec::new(), Compression::default()), entries, path)?;
+            builder.finish().map_err(|e| io_error(e, path))?
+        }
+        _ => build_tar(Vec::new(), entries, path)?,
+    };
+    Ok((result, Some(encoded)))
+}
+
+fn build_tar<W: Write>(writer: W, entries: Vec<(tar::Header, PathBuf, Vec<u8>)>, path: &Path) -> Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    for (mut header, stored_path, data) in entries {
+        header.set_size(data.len() as u64);
+        builder.append_data(&mut header, &stored_path, data.as_slice()).map_err(|e| io_error(e, path))?;
+    }
+    builder.into_inner().map_err(|e| io_error(e, path))
+}
+
+/// Read an entry that claims to be `declared` bytes long, refusing it
+/// before allocating when the claim is over `limit`, and stopping once more
+/// than `limit` bytes actually come out
+fn read_entry(reader: impl Read, declared: u64, limit: u64, name: &str, path: &Path) -> Result<Vec<u8>> {
+    let too_large = |size| EditError::file_too_large(size, limit, format!("{}:{}", path.display(), name));
+    if declared > limit {
+        return Err(too_large(declared));
+    }
+    let mut data = Vec::with_capacity(declared as usize);
+    reader.take(limit.saturating_add(1)).read_to_end(&mut data).map_err(|e| io_error(e, path))?;
+    if data.len() as u64 > limit {
+        return Err(too_large(data.len() as u64));
+    }
+    Ok(data)
+}
+
+/// Per-entry and cumulative size limits for reading many entries
+struct ArchiveBudget<'a> {
+    entry_limit: u64,
+    remaining: u64,
+    total_limit: u64,
+    path: &'a Path,
+}
+
+impl<'a> ArchiveBudget<'a> {
+    fn new(config: &EditConfig, path: &'a Path) -> Self {
+        Self {
+            entry_limit: config.max_file_size,
+            remaining: config.max_archive_size,
+            total_limit: config.max_archive_size,
+            path,
+        }
+    }
+
+    fn read(&mut self, reader: impl Read, declared: u64, name: &str) -> Result<Vec<u8>> {
+        let total_exceeded = |size| EditError::file_too_large(size, self.total_limit, self.path.display().to_string());
+        let read_so_far = self.total_limit - self.remaining;
+        if declared > self.remaining && declared <= self.entry_limit {
+            return Err(total_exceeded(read_so_far + declared));
+        }
+        let limit = self.entry_limit.min(self.remaining);
+        let data = match read_entry(reader, declared, limit, name, self.path) {
+            Err(EditError::FileTooLarge { size, .. }) if limit < self.entry_limit && size <= self.entry_limit => {
+                return Err(total_exceeded(read_so_far + size));
+            }
+            result => result?,
+        };
+        self.remaining -= data.len() as u64;
+        Ok(data)
+    }
+}
+
+fn open_zip(path: &Path) -> Result<ZipArchive<BufReader<File>>> {
+    let file = File::open(path).map_err(|e| io_error(e, path))?;
+    ZipArchive::new(BufReader::new(file)).map_err(|e| zip_error(e, path))
+}
+
+fn open_tar(path: &Path, format: ArchiveFormat) -> Result<tar::Archive<Box<dyn Read>>> {
+    let file = BufReader::new(File::open(path).map_err(|e| io_error(e, path))?);
+    let reader: Box<dyn Read> = match format {
+        ArchiveFormat::TarGz => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+fn zip_index(archive: &mut ZipArchive<BufReader<File>>, wanted: &str, path: &Path) -> Result<usize> {
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(|e| zip_error(e, path))?;
+        if !file.is_dir() && entry_name(file.name()) == wanted {
+            return Ok(i);
+        }
+    }
+    Err(entry_not_found(wanted, path))
+}
+
+/// Entry names are compared without a leading `./`
+fn entry_name(name: &str) -> String {
+    let mut name = name;
+    while let Some(rest) = name.strip_prefix("./") {
+        name = rest;
+    }
+    name.trim_end_matches('/').to_string()
+}
+
+fn entry_destination(destination: &Path, name: &str) -> Result<PathBuf> {
+    let relative = Path::new(name);
+    if name.is_empty() || !relative.components().all(|part| matches!(part, Component::Normal(_))) {
+        return Err(EditError::SecurityViolation {
+            path: name.to_string(),
+            reason: "Archive entry would be extracted outside the destination".to_string(),
+        });
+    }
+    Ok(destination.join(relative))
+}
+
+fn write_extracted(target: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| io_error(e, parent))?;
+    }
+    fs::write(target, data).map_err(|e| io_error(e, target))
+}
+
+fn build_filter(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| EditError::InvalidPattern {
+            pattern: pattern.clone(),
+            reason: e.to_string(),
+        })?;
+        builder.add(glob);
+    }
+    builder.build().map(Some).map_err(|e| EditError::InvalidPattern {
+        pattern: patterns.join(", "),
+        reason: e.to_string(),
+    })
+}
+
+fn io_error(error: std::io::Error, path: &Path) -> EditError {
+    EditError::from_io_with_path(error, path.display().to_string())
+}
+
+fn zip_error(error: zip::result::ZipError, path: &Path) -> EditError {
+    match error {
+        zip::result::ZipError::Io(e) => io_error(e, path),
+        other => EditError::InvalidInput {
+            message: format!("Invalid zip archive: {}", other),
+            context: Some(path.display().to_string()),
+        },
+    }
+}
+
+fn entry_not_found(entry: &str, path: &Path) -> EditError {
+    EditError::file_not_found(entry, Some(format!("in archive {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vespera-archive-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const FILES: [(&str, &str); 2] = [("docs/guide.md", "Install with pip.\n"), ("README.md", "# Project\n")];
+
+    fn write_zip(path: &Path) {
+        let mut writer = ZipWriter::new(File::create(path).unwrap());
+        writer.add_directory("docs/", SimpleFileOptions::default()).unwrap();
+        for (name, content) in FILES {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn write_tar_gz(path: &Path) {
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(path).unwrap(), Compression::default()));
+        for (name, content) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, format!("./{}", name), content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_list_and_extract() {
+        let dir = scratch_dir();
+        for name in ["bundle.zip", "bundle.tar.gz"] {
+            let archive = dir.join(name);
+            if name.ends_with(".zip") { write_zip(&archive) } else { write_tar_gz(&archive) }
+
+            let files: Vec<_> = list_archive(&archive).unwrap().into_iter().filter(|e| !e.is_dir).map(|e| e.path).collect();
+            assert_eq!(files, vec!["docs/guide.md", "README.md"]);
+            assert_eq!(read_archive_entry(&archive, "README.md", None).unwrap(), b"# Project\n");
+
+            let out = dir.join(format!("{}-out", name));
+            let written = extract_archive(&archive, &out, &["docs/**".to_string()], None).unwrap();
+            assert_eq!(written, vec![out.join("docs/guide.md")]);
+            assert_eq!(fs::read_to_string(out.join("docs/guide.md")).unwrap(), "Install with pip.\n");
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_edit_entry() {
+        let dir = scratch_dir();
+        for name in ["bundle.zip", "bundle.tgz"] {
+            let archive = dir.join(name);
+            if name.ends_with(".zip") { write_zip(&archive) } else { write_tar_gz(&archive) }
+
+            let operations = [EditOperation::new("pip", "uv", false)];
+            let result = edit_archive_entry(&archive, "docs/guide.md", &operations, None).unwrap();
+            assert!(result.changed);
+            assert_eq!(read_archive_entry(&archive, "docs/guide.md", None).unwrap(), b"Install with uv.\n");
+            assert_eq!(read_archive_entry(&archive, "README.md", None).unwrap(), b"# Project\n");
+
+            let missing = edit_archive_entry(&archive, "nope.md", &operations, None);
+            assert!(matches!(missing, Err(EditError::FileNotFound { .. })));
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let dir = scratch_dir();
+        // Each entry is 1 MiB of zeros, a few kilobytes compressed
+        let zeros = vec![0u8; 1024 * 1024];
+        let zip_path = dir.join("bomb.zip");
+        let mut writer = ZipWriter::new(File::create(&zip_path).unwrap());
+        for name in ["a.bin", "b.bin", "c.bin"] {
+            writer.start_file(name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(&zeros).unwrap();
+        }
+        writer.finish().unwrap();
+        let tar_path = dir.join("bomb.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(&tar_path).unwrap(), Compression::default()));
+        for name in ["a.bin", "b.bin", "c.bin"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(zeros.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, zeros.as_slice()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        for archive in [&zip_path, &tar_path] {
+            let small_entries = EditConfig::new().with_max_file_size(64 * 1024);
+            let read = read_archive_entry(archive, "a.bin", Some(small_entries.clone()));
+            assert!(matches!(read, Err(EditError::FileTooLarge { max_size, .. }) if max_size == 64 * 1024));
+            let out = dir.join("entries-out");
+            assert!(matches!(
+                extract_archive(archive, &out, &[], Some(small_entries)),
+                Err(EditError::FileTooLarge { .. })
+            ));
+
+            // Each entry fits, but not all three together
+            let small_total = EditConfig::new().with_max_archive_size(2 * 1024 * 1024 + 1);
+            let out = dir.join("total-out");
+            let extracted = extract_archive(archive, &out, &[], Some(small_total));
+            assert!(matches!(
+                extracted,
+                Err(EditError::FileTooLarge { max_size, .. }) if max_size == 2 * 1024 * 1024 + 1
+            ));
+            assert!(extract_archive(archive, dir.join("all-out"), &[], None).is_ok());
+        }
+
+        // A reader longer than its declared size stops at the limit
+        let long = read_entry(&zeros[..], 10, 100, "x", &zip_path);
+        assert!(matches!(long, Err(EditError::FileTooLarge { size: 101, .. })));
+        let claimed = read_entry(&zeros[..0], u64::MAX, 100, "x", &zip_path);
+        assert!(matches!(claimed, Err(EditError::FileTooLarge { size: u64::MAX, .. })));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_respects_guardrails() {
+        use crate::guardrails::Guardrails;
+
+        let dir = scratch_dir();
+        let archive = dir.join("bundle.zip");
+        write_zip(&archive);
+
+        let out = dir.join("out");
+        let protected = EditConfig::new().with_guardrails(Guardrails::new().with_protected_path("**/docs/**"));
+        let result = extract_archive(&archive, &out, &[], Some(protected));
+        assert!(matches!(result, Err(EditError::GuardrailViolation { ref guardrail, .. }) if guardrail == "protected_paths"));
+        // Not even the unprotected README was written
+        assert!(!out.exists());
+
+        let one_file = EditConfig::new().with_guardrails(Guardrails::new().with_max_files_per_operation(1));
+        let result = extract_archive(&archive, &out, &[], Some(one_file.clone()));
+        assert!(matches!(result, Err(EditError::GuardrailViolation { ref guardrail, .. }) if guardrail == "max_files_per_operation"));
+        assert_eq!(extract_archive(&archive, &out, &["README.md".to_string()], Some(one_file)).unwrap().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_traversal() {
+        assert!(entry_destination(Path::new("/tmp/out"), "../etc/passwd").is_err());
+        assert!(entry_destination(Path::new("/tmp/out"), "/etc/passwd").is_err());
+        assert!(entry_destination(Path::new("/tmp/out"), "a/b.txt").is_ok());
+    }
+}
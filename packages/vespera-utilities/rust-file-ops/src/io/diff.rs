@@ -0,0 +1,270 @@
+//! Recursive directory tree diffing
+//!
+//! `diff_trees` compares two directory trees file by file and reports added,
+//! removed and modified files, with a size-capped unified diff for each text
+//! file. Each tree is walked with its own `.gitignore`/`.ignore` rules plus
+//! any extra patterns, so an agent's working copy can be checked against a
+//! pristine checkout without git.
+
+use crate::error::{EditError, Result};
+use crate::io::kind::looks_binary;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Options for [`diff_trees`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Extra gitignore-style patterns excluded from both trees
+    pub ignore_patterns: Vec<String>,
+    /// Honour `.gitignore`, `.ignore` and `.git/info/exclude` in each tree
+    pub respect_gitignore: bool,
+    /// Include hidden files and directories (`.git` is always skipped)
+    pub include_hidden: bool,
+    /// Lines of context around each change in the unified diffs
+    pub context_lines: usize,
+    /// Maximum size of each file's diff text; longer diffs are truncated
+    pub max_diff_bytes: usize,
+    /// Files larger than this are compared but not diffed
+    pub max_file_size: u64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            ignore_patterns: Vec::new(),
+            respect_gitignore: true,
+            include_hidden: true,
+            context_lines: 3,
+            max_diff_bytes: 64 * 1024,
+            max_file_size: 10 * 1024 * 1024,
+        }
+    }
+}
+
+/// One added, removed or modified file
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileChange {
+    /// Path relative to the tree roots, `/`-separated
+    pub path: String,
+    /// Size in the first tree, if present there
+    pub size_a: Option<u64>,
+    /// Size in the second tree, if present there
+    pub size_b: Option<u64>,
+    /// Whether either side is binary (not UTF-8 or contains NUL bytes)
+    pub binary: bool,
+    /// Unified diff from the first tree to the second, for text files
+    /// within `max_file_size`
+    pub diff: Option<String>,
+    /// Whether `diff` was cut at `max_diff_bytes`
+    pub diff_truncated: bool,
+}
+
+/// Result of comparing two trees; each list is sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TreeDiff {
+    /// Files only in the second tree
+    pub added: Vec<FileChange>,
+    /// Files only in the first tree
+    pub removed: Vec<FileChange>,
+    /// Files in both trees with different content
+    pub modified: Vec<FileChange>,
+    /// Number of files identical in both trees
+    pub unchanged: usize,
+}
+
+impl TreeDiff {
+    /// Whether the trees have the same files with the same content
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Compare the trees at `path_a` and `path_b`
+pub fn diff_trees(path_a: impl AsRef<Path>, path_b: impl AsRef<Path>, options: &DiffOptions) -> Result<TreeDiff> {
+    let files_a = list_files(path_a.as_ref(), &options.ignore_patterns, options.respect_gitignore, options.include_hidden)?;
+    let files_b = list_files(path_b.as_ref(), &options.ignore_patterns, options.respect_gitignore, options.include_hidden)?;
+
+    let mut diff = TreeDiff::default();
+    for (relative, file_a) in &files_a {
+        match files_b.get(relative) {
+            None => diff.removed.push(describe(relative, Some(file_a), None, options)?),
+            Some(file_b) => {
+                let (bytes_a, bytes_b) = (read(file_a)?, read(file_b)?);
+                if bytes_a == bytes_b {
+                    diff.unchanged += 1;
+                } else {
+                    diff.modified.push(change(relative, Some(&bytes_a), Some(&bytes_b), options));
+                }
+            }
+        }
+    }
+    for (relative, file_b) in &files_b {
+        if !files_a.contains_key(relative) {
+            diff.added.push(describe(relative, None, Some(file_b), options)?);
+        }
+    }
+    Ok(diff)
+}
+
+/// Regular files under `root` that survive the tree's ignore files (when
+/// `respect_gitignore`) and `ignore_patterns`, keyed by `/`-separated
+/// relative path; `.git` is always skipped
+pub(crate) fn list_files(
+    root: &Path,
+    ignore_patterns: &[String],
+    respect_gitignore: bool,
+    include_hidden: bool,
+) -> Result<BTreeMap<String, PathBuf>> {
+    if !root.is_dir() {
+        return Err(EditError::InvalidInput {
+            message: format!("Not a directory: {}", root.display()),
+            context: None,
+        });
+    }
+
+    let mut overrides = OverrideBuilder::new(root);
+    for pattern in ignore_patterns {
+        overrides.add(&format!("!{}", pattern)).map_err(|e| EditError::InvalidPattern {
+            pattern: pattern.clone(),
+            reason: e.to_string(),
+        })?;
+    }
+    let overrides = overrides.build().map_err(|e| EditError::InvalidPattern {
+        pattern: ignore_patterns.join(", "),
+        reason: e.to_string(),
+    })?;
+
+    let walker = WalkBuilder::new(root)
+        .hidden(!include_hidden)
+        .git_ignore(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .git_global(false)
+        .ignore(respect_gitignore)
+        .parents(false)
+        .require_git(false)
+        .overrides(overrides)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut files = BTreeMap::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| EditError::InvalidInput {
+            message: e.to_string(),
+            context: Some(format!("walking {}", root.display())),
+        })?;
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let key = relative.components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.insert(key, entry.into_path());
+    }
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| EditError::from_io_with_path(e, path.display().to_string()))
+}
+
+fn describe(relative: &str, a: Option<&PathBuf>, b: Option<&PathBuf>, options: &DiffOptions) -> Result<FileChange> {
+    let bytes_a = a.map(|path| read(path)).transpose()?;
+    let bytes_b = b.map(|path| read(path)).transpose()?;
+    Ok(change(relative, bytes_a.as_deref(), bytes_b.as_deref(), options))
+}
+
+fn change(relative: &str, a: Option<&[u8]>, b: Option<&[u8]>, options: &DiffOptions) -> FileChange {
+    let text_a = a.map(as_text);
+    let text_b = b.map(as_text);
+    let binary = matches!(text_a, Some(None)) || matches!(text_b, Some(None));
+    let too_large = [a, b].iter().flatten().any(|bytes| bytes.len() as u64 > options.max_file_size);
+
+    let mut diff = None;
+    let mut diff_truncated = false;
+    if !binary && !too_large {
+        let old = text_a.flatten().unwrap_or("");
+        let new = text_b.flatten().unwrap_or("");
+        let old_header = if a.is_some() { format!("a/{}", relative) } else { "/dev/null".to_string() };
+        let new_header = if b.is_some() { format!("b/{}", relative) } else { "/dev/null".to_string() };
+        let mut text = TextDiff::from_lines(old, new)
+            .unified_diff()
+            .context_radius(options.context_lines)
+            .header(&old_header, &new_header)
+            .to_string();
+        if text.len() > options.max_diff_bytes {
+            let mut cut = options.max_diff_bytes;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+            diff_truncated = true;
+        }
+        diff = Some(text);
+    }
+
+    FileChange {
+        path: relative.to_string(),
+        size_a: a.map(|bytes| bytes.len() as u64),
+        size_b: b.map(|bytes| bytes.len() as u64),
+        binary,
+        diff,
+        diff_truncated,
+    }
+}
+
+fn as_text(bytes: &[u8]) -> Option<&str> {
+    if looks_binary(bytes) {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_trees() {
+        let root = std::env::temp_dir().join(format!("vespera-diff-{}", uuid::Uuid::new_v4()));
+        let (a, b) = (root.join("a"), root.join("b"));
+        for (tree, files) in [
+            (&a, vec![("same.txt", "same\n"), ("src/lib.rs", "fn a() {}\n"), ("gone.txt", "x\n"), ("build/out.o", "1")]),
+            (&b, vec![("same.txt", "same\n"), ("src/lib.rs", "fn b() {}\n"), ("new.bin", "\0\x01"), ("build/out.o", "2")]),
+        ] {
+            for (name, content) in files {
+                let path = tree.join(name);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, content).unwrap();
+            }
+            fs::write(tree.join(".gitignore"), "build/\n").unwrap();
+        }
+        fs::create_dir_all(b.join(".git")).unwrap();
+        fs::write(b.join(".git/HEAD"), "ref").unwrap();
+
+        let diff = diff_trees(&a, &b, &DiffOptions::default()).unwrap();
+        assert_eq!(diff.unchanged, 2); // same.txt and .gitignore
+        assert_eq!(diff.removed.iter().map(|c| c.path.as_str()).collect::<Vec<_>>(), vec!["gone.txt"]);
+        assert_eq!(diff.added.len(), 1);
+        assert!(diff.added[0].binary && diff.added[0].diff.is_none());
+
+        let modified = &diff.modified[0];
+        assert_eq!(modified.path, "src/lib.rs");
+        let text = modified.diff.as_deref().unwrap();
+        assert!(text.contains("--- a/src/lib.rs") && text.contains("-fn a() {}") && text.contains("+fn b() {}"));
+
+        // Extra patterns and the diff size cap
+        let options = DiffOptions { ignore_patterns: vec!["*.txt".to_string()], max_diff_bytes: 10, ..Default::default() };
+        let diff = diff_trees(&a, &b, &options).unwrap();
+        assert!(diff.removed.is_empty());
+        assert!(diff.modified[0].diff_truncated);
+        assert_eq!(diff.modified[0].diff.as_ref().unwrap().len(), 10);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+//! Idempotent "ensure" operations for whole files and managed blocks
+//!
+//! `ensure_file` makes a file hold exactly the given content and
+//! `ensure_block` maintains a marker-delimited block inside a file, in the
+//! style of Ansible's `blockinfile`. Both create what is missing, write
+//! atomically, and report whether anything changed, so running them twice
+//! is a no-op.
+
+use crate::error::{EditError, Result};
+use crate::io::writer::write_atomic_safe;
+use crate::security::{validate_path, validate_path_characters};
+use crate::types::EditConfig;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// What an ensure operation did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnsureOutcome {
+    /// The file did not exist and was created
+    Created,
+    /// The file existed and was rewritten
+    Updated,
+    /// The file already had the desired content
+    Unchanged,
+}
+
+/// Make `path` contain exactly `content`, creating it (and its parent
+/// directories) if missing
+pub fn ensure_file(path: impl AsRef<Path>, content: &str, config: Option<EditConfig>) -> Result<EnsureOutcome> {
+    let config = config.unwrap_or_default();
+    let path = path.as_ref();
+
+    match read_existing(path, &config)? {
+        Some(existing) if existing == content => Ok(EnsureOutcome::Unchanged),
+        Some(_) => {
+            config.guardrails.check_write(path, content.len() as u64)?;
+            write_atomic_safe(path, content, config.max_file_size)?;
+            Ok(EnsureOutcome::Updated)
+        }
+        None => {
+            config.guardrails.check_write(path, content.len() as u64)?;
+            write_atomic_safe(path, content, config.max_file_size)?;
+            Ok(EnsureOutcome::Created)
+        }
+    }
+}
+
+/// Make `path` contain a block of `content` between the lines
+/// `marker_begin` and `marker_end`
+///
+/// An existing block (the first line equal to `marker_begin` through the
+/// next line equal to `marker_end`) is replaced; otherwise the block is
+/// appended, creating the file if needed. A begin marker without an end
+/// marker is an error rather than a guess at where the block ends.
+pub fn ensure_block(
+    path: impl AsRef<Path>,
+    marker_begin: &str,
+    marker_end: &str,
+    content: &str,
+    config: Option<EditConfig>,
+) -> Result<EnsureOutcome> {
+    let config = config.unwrap_or_default();
+    let path = path.as_ref();
+
+    let existing = read_existing(path, &config)?;
+    let updated = apply_block(existing.as_deref().unwrap_or(""), marker_begin, marker_end, content)?;
+
+    match existing {
+        Some(existing) if existing == updated => Ok(EnsureOutcome::Unchanged),
+        Some(_) => {
+            config.guardrails.check_write(path, updated.len() as u64)?;
+            write_atomic_safe(path, &updated, config.max_file_size)?;
+            Ok(EnsureOutcome::Updated)
+        }
+        None => {
+            config.guardrails.check_write(path, updated.len() as u64)?;
+            write_atomic_safe(path, &updated, config.max_file_size)?;
+            Ok(EnsureOutcome::Created)
+        }
+    }
+}
+
+/// Return `text` with the managed block set to `content`
+pub fn apply_block(text: &str, marker_begin: &str, marker_end: &str, content: &str) -> Result<String> {
+    if marker_begin.is_empty() || marker_end.is_empty() || marker_begin == marker_end {
+        return Err(EditError::InvalidInput {
+            message: "Block markers must be non-empty and distinct".to_string(),
+            context: None,
+        });
+    }
+
+    let mut block = String::with_capacity(marker_begin.len() + content.len() + marker_end.len() + 3);
+    block.push_str(marker_begin);
+    block.push('\n');
+    if !content.is_empty() {
+        block.push_str(content);
+        if !content.ends_with('\n') {
+            block.push('\n');
+        }
+    }
+    block.push_str(marker_end);
+    block.push('\n');
+
+    let mut offset = 0;
+    let lines: Vec<(usize, &str)> = text.split_inclusive('\n').map(|line| {
+        let start = offset;
+        offset += line.len();
+        (start, line)
+    }).collect();
+    let is_marker = |line: &str, marker: &str| line.trim_end_matches(['\n', '\r']).trim_end() == marker;
+
+    let Some(begin) = lines.iter().position(|(_, line)| is_marker(line, marker_begin)) else {
+        let mut result = text.to_string();
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str(&block);
+        return Ok(result);
+    };
+    let Some(end) = lines[begin..].iter().position(|(_, line)| is_marker(line, marker_end)) else {
+        return Err(EditError::InvalidInput {
+            message: format!("Block begins with '{}' but has no '{}' marker", marker_begin, marker_end),
+            context: Some(format!("line {}", begin + 1)),
+        });
+    };
+
+    let (start, _) = lines[begin];
+    let (end_start, end_line) = lines[begin + end];
+    let mut result = String::with_capacity(text.len() + block.len());
+    result.push_str(&text[..start]);
+    result.push_str(&block);
+    result.push_str(&text[end_start + end_line.len()..]);
+    Ok(result)
+}
+
+/// Read the file if it exists, after the same path checks as other writes
+fn read_existing(path: &Path, config: &EditConfig) -> Result<Option<String>> {
+    validate_path_characters(path)?;
+    if path.exists() {
+        validate_path(path, config.base_dir.as_deref())?;
+    } else if let (Some(base_dir), Some(parent)) = (config.base_dir.as_deref(), path.parent()) {
+        if parent.exists() {
+            validate_path(parent, Some(base_dir))?;
+        }
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(EditError::from_io_with_path(e, path.display().to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("vespera-ensure-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_ensure_file() {
+        let dir = scratch_dir();
+        let path = dir.join("nested/config.toml");
+
+        assert_eq!(ensure_file(&path, "a = 1\n", None).unwrap(), EnsureOutcome::Created);
+        assert_eq!(ensure_file(&path, "a = 1\n", None).unwrap(), EnsureOutcome::Unchanged);
+        assert_eq!(ensure_file(&path, "a = 2\n", None).unwrap(), EnsureOutcome::Updated);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a = 2\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_ensure_block() {
+        let dir = scratch_dir();
+        let path = dir.join(".bashrc");
+        fs::write(&path, "export A=1").unwrap();
+
+        let outcome = ensure_block(&path, "# BEGIN managed", "# END managed", "export B=2", None).unwrap();
+        assert_eq!(outcome, EnsureOutcome::Updated);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "export A=1\n# BEGIN managed\nexport B=2\n# END managed\n");
+
+        let outcome = ensure_block(&path, "# BEGIN managed", "# END managed", "export B=2\n", None).unwrap();
+        assert_eq!(outcome, EnsureOutcome::Unchanged);
+
+        fs::write(&path, "# BEGIN managed\nold\n# END managed\ntail\n").unwrap();
+        ensure_block(&path, "# BEGIN managed", "# END managed", "new", None).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# BEGIN managed\nnew\n# END managed\ntail\n");
+
+        let fresh = dir.join("fresh");
+        let outcome = ensure_block(&fresh, "# BEGIN managed", "# END managed", "x", None).unwrap();
+        assert_eq!(outcome, EnsureOutcome::Created);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unterminated_block() {
+        let result = apply_block("# BEGIN\nstuff\n", "# BEGIN", "# END", "x");
+        assert!(matches!(result, Err(EditError::InvalidInput { .. })));
+    }
+}
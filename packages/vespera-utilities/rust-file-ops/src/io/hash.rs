@@ -0,0 +1,227 @@
+//! File and directory hashing
+//!
+//! [`hash_file`] hashes one file with BLAKE3 (multi-threaded, memory-mapped
+//! for large files) or SHA-256. [`hash_tree`] hashes every file under a
+//! directory in parallel, honouring the same ignore rules as
+//! [`diff_trees`](crate::io::diff_trees), and returns a manifest with a
+//! single root hash for cache keys and per-file hashes for change detection.
+
+use crate::error::{EditError, Result};
+use crate::io::diff::list_files;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Supported hash algorithms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Look up an algorithm by name ("blake3" or "sha256")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "blake3" => Some(Self::Blake3),
+            "sha256" => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+}
+
+/// Options for [`hash_tree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashTreeOptions {
+    pub algorithm: HashAlgorithm,
+    /// Extra gitignore-style patterns to skip
+    pub ignore_patterns: Vec<String>,
+    /// Honour `.gitignore`, `.ignore` and `.git/info/exclude`
+    pub respect_gitignore: bool,
+    /// Include hidden files and directories (`.git` is always skipped)
+    pub include_hidden: bool,
+}
+
+impl Default for HashTreeOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: HashAlgorithm::Blake3,
+            ignore_patterns: Vec::new(),
+            respect_gitignore: true,
+            include_hidden: true,
+        }
+    }
+}
+
+/// Hash and size of one file in a [`HashManifest`]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileHash {
+    /// Lowercase hex digest
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Hashes of every file in a tree
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HashManifest {
+    pub algorithm: HashAlgorithm,
+    /// Hash over every path and file hash; equal root hashes mean equal trees
+    pub root_hash: String,
+    /// Files keyed by `/`-separated path relative to the root
+    pub files: BTreeMap<String, FileHash>,
+}
+
+/// Files that differ between two manifests, each list sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ManifestChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl ManifestChanges {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl HashManifest {
+    /// The files added, removed or modified since `previous`
+    pub fn changes_since(&self, previous: &HashManifest) -> ManifestChanges {
+        let mut changes = ManifestChanges::default();
+        for (path, file) in &self.files {
+            match previous.files.get(path) {
+                None => changes.added.push(path.clone()),
+                Some(old) if old.hash != file.hash => changes.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.removed = previous.files.keys()
+            .filter(|path| !self.files.contains_key(*path))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+/// Hash one file, returning the lowercase hex digest
+pub fn hash_file(path: impl AsRef<Path>, algorithm: HashAlgorithm) -> Result<String> {
+    let path = path.as_ref();
+    let io_error = |e| EditError::from_io_with_path(e, path.display().to_string());
+    match algorithm {
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_mmap_rayon(path).map_err(io_error)?;
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+        HashAlgorithm::Sha256 => {
+            let mut file = File::open(path).map_err(io_error)?;
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0; 64 * 1024];
+            loop {
+                let read = file.read(&mut buffer).map_err(io_error)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+            }
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+    }
+}
+
+/// Hash in-memory content, returning the lowercase hex digest
+pub fn hash_bytes(content: &[u8], algorithm: HashAlgorithm) -> String {
+    match algorithm {
+        HashAlgorithm::Blake3 => blake3::hash(content).to_hex().to_string(),
+        HashAlgorithm::Sha256 => format!("{:x}", Sha256::digest(content)),
+    }
+}
+
+/// Hash every file under `root` in parallel
+pub fn hash_tree(root: impl AsRef<Path>, options: &HashTreeOptions) -> Result<HashManifest> {
+    let files = list_files(root.as_ref(), &options.ignore_patterns, options.respect_gitignore, options.include_hidden)?;
+
+    let hashed = files.par_iter()
+        .map(|(relative, path)| {
+            let size = path.metadata()
+                .map_err(|e| EditError::from_io_with_path(e, path.display().to_string()))?
+                .len();
+            let hash = hash_file(path, options.algorithm)?;
+            Ok((relative.clone(), FileHash { hash, size }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let files: BTreeMap<_, _> = hashed.into_iter().collect();
+
+    // One "path NUL hash LF" record per file, in path order
+    let mut listing = Vec::new();
+    for (path, file) in &files {
+        listing.extend_from_slice(path.as_bytes());
+        listing.push(0);
+        listing.extend_from_slice(file.hash.as_bytes());
+        listing.push(b'\n');
+    }
+    Ok(HashManifest {
+        algorithm: options.algorithm,
+        root_hash: hash_bytes(&listing, options.algorithm),
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("vespera-hash-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            hash_file(&path, HashAlgorithm::Sha256).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_file(&path, HashAlgorithm::Blake3).unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(hash_file(&path, HashAlgorithm::Blake3).unwrap(), hash_bytes(b"abc", HashAlgorithm::Blake3));
+        assert_eq!(HashAlgorithm::from_name("SHA-256"), Some(HashAlgorithm::Sha256));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_tree_changes() {
+        let root = std::env::temp_dir().join(format!("vespera-hash-tree-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/a.md"), "a").unwrap();
+        fs::write(root.join("b.md"), "b").unwrap();
+        fs::write(root.join("debug.log"), "noise").unwrap();
+
+        let options = HashTreeOptions { ignore_patterns: vec!["*.log".to_string()], ..Default::default() };
+        let before = hash_tree(&root, &options).unwrap();
+        assert_eq!(before.files.keys().collect::<Vec<_>>(), vec!["b.md", "docs/a.md"]);
+        assert_eq!(before.files["b.md"].size, 1);
+        assert_eq!(hash_tree(&root, &options).unwrap().root_hash, before.root_hash);
+
+        fs::write(root.join("docs/a.md"), "changed").unwrap();
+        fs::write(root.join("c.md"), "c").unwrap();
+        fs::remove_file(root.join("b.md")).unwrap();
+        let after = hash_tree(&root, &options).unwrap();
+        assert_ne!(after.root_hash, before.root_hash);
+
+        let changes = after.changes_since(&before);
+        assert_eq!(changes.added, vec!["c.md"]);
+        assert_eq!(changes.removed, vec!["b.md"]);
+        assert_eq!(changes.modified, vec!["docs/a.md"]);
+        assert!(after.changes_since(&after).is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,251 @@
+//! Content-type sniffing and binary detection
+//!
+//! [`detect_file_kind`] looks at the first few kilobytes of a file and
+//! reports whether it is text or binary, its text encoding (from a BOM or
+//! UTF-8 validation) and a MIME type guessed from magic bytes, then from the
+//! extension. Editing refuses files detected as binary, and tooling can use
+//! the same answer to route files.
+
+use crate::error::{EditError, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Number of leading bytes examined
+pub const SNIFF_LEN: usize = 8192;
+
+/// Text encoding of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+    /// Text in some other 8-bit encoding (Latin-1, Windows-1252, ...)
+    Other,
+}
+
+/// What a file contains, as far as its first bytes tell
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileKind {
+    /// Whether the content is binary rather than text
+    pub is_binary: bool,
+    /// Text encoding, for text files
+    pub encoding: Option<TextEncoding>,
+    /// Whether the file starts with a byte order mark
+    pub has_bom: bool,
+    /// Best-guess MIME type
+    pub mime: String,
+}
+
+/// Sniff the file at `path`
+pub fn detect_file_kind(path: impl AsRef<Path>) -> Result<FileKind> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| EditError::from_io_with_path(e, path.display().to_string()))?;
+    let mut sample = Vec::with_capacity(SNIFF_LEN);
+    file.take(SNIFF_LEN as u64)
+        .read_to_end(&mut sample)
+        .map_err(|e| EditError::from_io_with_path(e, path.display().to_string()))?;
+    Ok(detect_content_kind(&sample, Some(path)))
+}
+
+/// Sniff the start of some content; `path` is only used for its extension
+pub fn detect_content_kind(content: &[u8], path: Option<&Path>) -> FileKind {
+    let sample = &content[..content.len().min(SNIFF_LEN)];
+    let extension = path
+        .and_then(|p| p.extension())
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if let Some(encoding) = bom(sample) {
+        return FileKind {
+            is_binary: false,
+            encoding: Some(encoding),
+            has_bom: true,
+            mime: text_mime(&extension).to_string(),
+        };
+    }
+
+    let encoding = text_encoding(sample);
+    if let Some((mime, strong)) = magic_mime(sample) {
+        // Signatures made of printable ASCII ("MZ", "%PDF-") only count as
+        // binary when the content isn't plausible text anyway
+        let is_binary = !mime.ends_with("xml") && (strong || encoding.is_none());
+        return FileKind {
+            is_binary,
+            encoding: if is_binary { None } else { encoding },
+            has_bom: false,
+            mime: mime.to_string(),
+        };
+    }
+
+    FileKind {
+        is_binary: encoding.is_none(),
+        encoding,
+        has_bom: false,
+        mime: match encoding {
+            Some(_) => text_mime(&extension),
+            None => binary_mime(&extension),
+        }.to_string(),
+    }
+}
+
+/// Whether `content` looks binary (not text in any encoding we recognise)
+pub fn looks_binary(content: &[u8]) -> bool {
+    detect_content_kind(content, None).is_binary
+}
+
+/// The encoding announced by a byte order mark
+fn bom(sample: &[u8]) -> Option<TextEncoding> {
+    // UTF-32 LE must be checked before UTF-16 LE, which it starts with
+    if sample.starts_with(&[0xFF, 0xFE, 0x00, 0x00]) {
+        Some(TextEncoding::Utf32Le)
+    } else if sample.starts_with(&[0x00, 0x00, 0xFE, 0xFF]) {
+        Some(TextEncoding::Utf32Be)
+    } else if sample.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some(TextEncoding::Utf8)
+    } else if sample.starts_with(&[0xFF, 0xFE]) {
+        Some(TextEncoding::Utf16Le)
+    } else if sample.starts_with(&[0xFE, 0xFF]) {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// UTF-8, some other 8-bit text encoding, or `None` for binary
+fn text_encoding(sample: &[u8]) -> Option<TextEncoding> {
+    // Text has no NULs and few control characters
+    let control = sample.iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B)) || b == 0x7F)
+        .count();
+    if sample.contains(&0) || control * 10 > sample.len() {
+        return None;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => Some(TextEncoding::Utf8),
+        // The sample may end in the middle of a character
+        Err(e) if e.error_len().is_none() => Some(TextEncoding::Utf8),
+        Err(_) => Some(TextEncoding::Other),
+    }
+}
+
+/// The MIME type for a known signature, and whether the signature is
+/// strong (contains bytes that never start a text file)
+fn magic_mime(sample: &[u8]) -> Option<(&'static str, bool)> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xFF\xD8\xFF", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"PK\x05\x06", "application/zip"),
+        (b"\x1F\x8B", "application/gzip"),
+        (b"BZh", "application/x-bzip2"),
+        (b"\xFD7zXZ\x00", "application/x-xz"),
+        (b"7z\xBC\xAF\x27\x1C", "application/x-7z-compressed"),
+        (b"\x28\xB5\x2F\xFD", "application/zstd"),
+        (b"\x7FELF", "application/x-elf"),
+        (b"MZ", "application/vnd.microsoft.portable-executable"),
+        (b"\xCF\xFA\xED\xFE", "application/x-mach-binary"),
+        (b"\x00asm", "application/wasm"),
+        (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+        (b"OggS", "audio/ogg"),
+        (b"fLaC", "audio/flac"),
+        (b"ID3", "audio/mpeg"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+        (b"<?xml", "application/xml"),
+    ];
+    if let Some(&(magic, mime)) = SIGNATURES.iter().find(|(magic, _)| sample.starts_with(magic)) {
+        return Some((mime, !magic.iter().all(u8::is_ascii_graphic)));
+    }
+    // Containers with a size field between the tag and the format name
+    match sample {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(("image/webp", false)),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(("audio/wav", false)),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(("video/mp4", false)),
+        _ => None,
+    }
+}
+
+fn text_mime(extension: &str) -> &'static str {
+    match extension {
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "js" | "mjs" | "cjs" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "py" => "text/x-python",
+        "rs" => "text/x-rust",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "sh" | "bash" => "application/x-sh",
+        _ => "text/plain",
+    }
+}
+
+fn binary_mime(extension: &str) -> &'static str {
+    match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_detection() {
+        let kind = detect_content_kind("# Title\n\ncafé ☕\n".as_bytes(), Some(Path::new("notes.md")));
+        assert!(!kind.is_binary);
+        assert_eq!(kind.encoding, Some(TextEncoding::Utf8));
+        assert_eq!(kind.mime, "text/markdown");
+
+        let kind = detect_content_kind(b"\xFF\xFEh\x00i\x00", None);
+        assert_eq!((kind.is_binary, kind.encoding, kind.has_bom), (false, Some(TextEncoding::Utf16Le), true));
+
+        // Latin-1 text is text, just not UTF-8
+        assert_eq!(detect_content_kind(b"caf\xE9 cr\xE8me\n", None).encoding, Some(TextEncoding::Other));
+        // A sample cut in the middle of a character is still UTF-8
+        assert_eq!(detect_content_kind(&"é".as_bytes()[..1], None).encoding, Some(TextEncoding::Utf8));
+    }
+
+    #[test]
+    fn test_binary_detection() {
+        let kind = detect_content_kind(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", Some(Path::new("logo.txt")));
+        assert!(kind.is_binary);
+        assert_eq!(kind.mime, "image/png");
+
+        assert!(looks_binary(b"text\0with nul"));
+        assert!(looks_binary(&[0x01, 0x02, 0x03, 0x80, 0x81, 0x04]));
+        assert_eq!(detect_content_kind(b"\x01\x02\x03\x04", Some(Path::new("x.gif"))).mime, "image/gif");
+        assert!(!looks_binary(b"<?xml version=\"1.0\"?><a/>"));
+    }
+
+    #[test]
+    fn test_editing_refuses_binary() {
+        let path = std::env::temp_dir().join(format!("vespera-kind-{}.dat", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"\x7FELF\x02\x01\x01\0\0\0").unwrap();
+        assert_eq!(detect_file_kind(&path).unwrap().mime, "application/x-elf");
+
+        let operation = crate::types::EditOperation::new("ELF", "FLE", false);
+        let result = crate::edit_file(&path, &operation, None);
+        assert!(matches!(result, Err(EditError::BinaryFile { .. })));
+        std::fs::remove_file(path).unwrap();
+    }
+}
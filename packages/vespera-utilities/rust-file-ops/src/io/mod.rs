@@ -6,9 +6,27 @@
 pub mod reader;
 pub mod writer;
 pub mod strategy;
+pub mod ensure;
+pub mod diff;
+pub mod archive;
+pub mod range;
+pub mod kind;
+pub mod hash;
+pub mod scratch;
 // pub mod watcher; // Disabled due to compatibility issues with EditError
 
 pub use reader::FileReader;
 pub use writer::FileWriter;
 pub use strategy::FileStrategy;
+pub use ensure::{ensure_block, ensure_file, EnsureOutcome};
+pub use diff::{diff_trees, DiffOptions, FileChange, TreeDiff};
+pub use archive::{
+    edit_archive_entry, extract_archive, list_archive, read_archive_entry, ArchiveEntry, ArchiveFormat,
+};
+pub use range::{follow_from, head, read_range, tail, ReadRange, Tail};
+pub use kind::{detect_content_kind, detect_file_kind, looks_binary, FileKind, TextEncoding};
+pub use hash::{
+    hash_bytes, hash_file, hash_tree, FileHash, HashAlgorithm, HashManifest, HashTreeOptions, ManifestChanges,
+};
+pub use scratch::{cleanup_scratch_dirs, temp_path_for, ScratchDir};
 // pub use watcher::FileWatcher; // Disabled
\ No newline at end of file
@@ -0,0 +1,235 @@
+//! Partial reads of large files: byte or line ranges, head and tail
+//!
+//! None of these read more of the file than they return. `tail` scans
+//! backwards from the end in fixed-size blocks, so the last lines of a
+//! multi-gigabyte log cost a few kilobytes of I/O, and [`follow_from`]
+//! picks up lines appended since a previous read, `tail -f` style.
+//! Invalid UTF-8 is replaced rather than rejected, since logs and partial
+//! byte ranges routinely contain it.
+
+use crate::error::{EditError, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Block size for the reverse scan in [`tail`]
+const TAIL_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Part of a file to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadRange {
+    /// Byte offsets, end exclusive; clamped to the file length
+    Bytes { start: u64, end: u64 },
+    /// 1-based line numbers, inclusive; clamped to the last line
+    Lines { start: usize, end: usize },
+}
+
+/// Lines read from the end of a file
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Tail {
+    /// The lines, oldest first, without line terminators
+    pub lines: Vec<String>,
+    /// Byte offset to pass to [`follow_from`] for the lines that follow
+    pub offset: u64,
+}
+
+/// Read the given byte or line range of a file
+///
+/// Line ranges keep their line terminators, so the result is an exact
+/// slice of the file.
+pub fn read_range(path: impl AsRef<Path>, range: ReadRange) -> Result<String> {
+    let path = path.as_ref();
+    let mut file = open(path)?;
+
+    match range {
+        ReadRange::Bytes { start, end } => {
+            if end < start {
+                return Err(invalid_range(format!("byte range {}..{} ends before it starts", start, end)));
+            }
+            file.seek(SeekFrom::Start(start)).map_err(|e| io_error(e, path))?;
+            let mut bytes = Vec::new();
+            file.take(end - start).read_to_end(&mut bytes).map_err(|e| io_error(e, path))?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        ReadRange::Lines { start, end } => {
+            if start == 0 || end < start {
+                return Err(invalid_range(format!("line range {}..={} is not a 1-based range", start, end)));
+            }
+            let mut reader = BufReader::new(file);
+            let mut result = Vec::new();
+            let mut line = Vec::new();
+            for number in 1..=end {
+                line.clear();
+                if reader.read_until(b'\n', &mut line).map_err(|e| io_error(e, path))? == 0 {
+                    break;
+                }
+                if number >= start {
+                    result.extend_from_slice(&line);
+                }
+            }
+            Ok(String::from_utf8_lossy(&result).into_owned())
+        }
+    }
+}
+
+/// Read the first `n_lines` lines, without line terminators
+pub fn head(path: impl AsRef<Path>, n_lines: usize) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let mut reader = BufReader::new(open(path)?);
+    let mut lines = Vec::with_capacity(n_lines.min(1024));
+    let mut line = Vec::new();
+    while lines.len() < n_lines {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).map_err(|e| io_error(e, path))? == 0 {
+            break;
+        }
+        lines.push(strip_terminator(&line));
+    }
+    Ok(lines)
+}
+
+/// Read the last `n_lines` lines by scanning backwards from the end
+pub fn tail(path: impl AsRef<Path>, n_lines: usize) -> Result<Tail> {
+    let path = path.as_ref();
+    let mut file = open(path)?;
+    let len = file.metadata().map_err(|e| io_error(e, path))?.len();
+    if n_lines == 0 {
+        return Ok(Tail { lines: Vec::new(), offset: len });
+    }
+
+    // Find the newline that ends the line before the first one we want; a
+    // newline as the very last byte terminates the final line instead
+    let mut start = 0;
+    let mut position = len;
+    let mut newlines = 0;
+    let mut block = vec![0; TAIL_BLOCK_SIZE];
+    'scan: while position > 0 {
+        let size = (TAIL_BLOCK_SIZE as u64).min(position) as usize;
+        position -= size as u64;
+        file.seek(SeekFrom::Start(position)).map_err(|e| io_error(e, path))?;
+        file.read_exact(&mut block[..size]).map_err(|e| io_error(e, path))?;
+        for i in (0..size).rev() {
+            let at = position + i as u64;
+            if block[i] == b'\n' && at + 1 != len {
+                newlines += 1;
+                if newlines == n_lines {
+                    start = at + 1;
+                    break 'scan;
+                }
+            }
+        }
+    }
+
+    file.seek(SeekFrom::Start(start)).map_err(|e| io_error(e, path))?;
+    let mut bytes = Vec::with_capacity((len - start) as usize);
+    file.take(len - start).read_to_end(&mut bytes).map_err(|e| io_error(e, path))?;
+    Ok(Tail {
+        lines: String::from_utf8_lossy(&bytes).lines().map(String::from).collect(),
+        offset: start + bytes.len() as u64,
+    })
+}
+
+/// Read the complete lines appended since `offset`
+///
+/// The returned offset stops after the last complete line, so a line still
+/// being written is returned whole by a later call. A file shorter than
+/// `offset` is assumed to have been truncated or rotated and is read from
+/// the start.
+pub fn follow_from(path: impl AsRef<Path>, offset: u64) -> Result<Tail> {
+    let path = path.as_ref();
+    let mut file = open(path)?;
+    let len = file.metadata().map_err(|e| io_error(e, path))?.len();
+    let offset = if len < offset { 0 } else { offset };
+
+    file.seek(SeekFrom::Start(offset)).map_err(|e| io_error(e, path))?;
+    let mut bytes = Vec::with_capacity((len - offset) as usize);
+    file.take(len - offset).read_to_end(&mut bytes).map_err(|e| io_error(e, path))?;
+
+    let complete = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    Ok(Tail {
+        lines: String::from_utf8_lossy(&bytes[..complete]).lines().map(String::from).collect(),
+        offset: offset + complete as u64,
+    })
+}
+
+fn open(path: &Path) -> Result<File> {
+    File::open(path).map_err(|e| io_error(e, path))
+}
+
+fn strip_terminator(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+fn io_error(error: std::io::Error, path: &Path) -> EditError {
+    EditError::from_io_with_path(error, path.display().to_string())
+}
+
+fn invalid_range(message: String) -> EditError {
+    EditError::InvalidInput {
+        message,
+        context: Some("read_range".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn scratch_file(content: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("vespera-range-{}.log", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_range() {
+        let path = scratch_file(b"one\ntwo\r\nthree\nfour");
+        assert_eq!(read_range(&path, ReadRange::Lines { start: 2, end: 3 }).unwrap(), "two\r\nthree\n");
+        assert_eq!(read_range(&path, ReadRange::Lines { start: 4, end: 99 }).unwrap(), "four");
+        assert_eq!(read_range(&path, ReadRange::Bytes { start: 4, end: 7 }).unwrap(), "two");
+        assert_eq!(read_range(&path, ReadRange::Bytes { start: 15, end: 100 }).unwrap(), "four");
+        assert!(read_range(&path, ReadRange::Lines { start: 0, end: 1 }).is_err());
+        assert_eq!(head(&path, 2).unwrap(), vec!["one", "two"]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_tail_across_blocks() {
+        let content: String = (1..=20_000).map(|i| format!("line {}\n", i)).collect();
+        let path = scratch_file(content.as_bytes());
+
+        let result = tail(&path, 3).unwrap();
+        assert_eq!(result.lines, vec!["line 19998", "line 19999", "line 20000"]);
+        assert_eq!(result.offset, content.len() as u64);
+        assert_eq!(tail(&path, 50_000).unwrap().lines.len(), 20_000);
+        assert!(tail(&path, 0).unwrap().lines.is_empty());
+        std::fs::remove_file(path).unwrap();
+
+        let path = scratch_file(b"a\nb");
+        assert_eq!(tail(&path, 1).unwrap().lines, vec!["b"]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_from() {
+        let path = scratch_file(b"first\n");
+        let start = tail(&path, 10).unwrap();
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"second\nthi").unwrap();
+        let next = follow_from(&path, start.offset).unwrap();
+        assert_eq!(next.lines, vec!["second"]);
+
+        file.write_all(b"rd\n").unwrap();
+        let next = follow_from(&path, next.offset).unwrap();
+        assert_eq!(next.lines, vec!["third"]);
+
+        // Truncated (e.g. rotated) files are read from the start
+        std::fs::write(&path, b"new\n").unwrap();
+        assert_eq!(follow_from(&path, next.offset).unwrap().lines, vec!["new"]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -4,6 +4,7 @@
 //! based on file size and access patterns.
 
 use crate::error::{EditError, Result};
+use crate::io::kind::detect_file_kind;
 use crate::io::strategy::{FileStrategy, FileSizeClass};
 use crate::types::EditConfig;
 // use std::io::Read; // Not needed for current implementation
@@ -180,7 +181,16 @@ impl FileReader {
     }
 
     /// Read file content optimized for editing operations
+    ///
+    /// Files that look binary are refused with [`EditError::BinaryFile`].
     pub fn read_for_editing(&self) -> FileOpResult<String> {
+        let kind = detect_file_kind(&self.path)?;
+        if kind.is_binary {
+            return Err(EditError::BinaryFile {
+                path: self.path.clone(),
+                mime: kind.mime,
+            });
+        }
         let content = self.read_string()?;
         
         // Check memory usage limit
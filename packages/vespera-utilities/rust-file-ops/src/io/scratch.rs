@@ -0,0 +1,218 @@
+//! Managed scratch space and collision-free temporary names
+//!
+//! [`ScratchDir`] owns a uniquely named directory that is removed when it is
+//! dropped, hands out names that can't collide across threads or processes,
+//! and can enforce a size quota on what is written through it. Live scratch
+//! directories are also tracked process-wide so [`cleanup_scratch_dirs`] can
+//! remove them from an exit hook, since Rust runs no destructors for values
+//! still alive at exit. The Python module registers it with `atexit`.
+//!
+//! [`temp_path_for`] names the sibling temporary file used by atomic writes.
+
+use crate::error::{EditError, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Scratch directories that haven't been dropped or kept
+static LIVE_SCRATCH_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A temporary directory removed on drop
+#[derive(Debug)]
+pub struct ScratchDir {
+    path: PathBuf,
+    quota: Option<u64>,
+    used: AtomicU64,
+    keep: bool,
+}
+
+impl ScratchDir {
+    /// Create a scratch directory named `{prefix}-{pid}-{uuid}` in the
+    /// system temporary directory
+    pub fn new(prefix: &str) -> Result<Self> {
+        Self::new_in(std::env::temp_dir(), prefix)
+    }
+
+    /// Create a scratch directory inside `parent`
+    pub fn new_in(parent: impl AsRef<Path>, prefix: &str) -> Result<Self> {
+        let parent = parent.as_ref();
+        fs::create_dir_all(parent).map_err(|e| io_error(e, parent))?;
+        let path = parent.join(format!("{}-{}-{}", prefix, std::process::id(), uuid::Uuid::new_v4().simple()));
+        fs::create_dir(&path).map_err(|e| io_error(e, &path))?;
+        register(&path);
+        Ok(Self {
+            path,
+            quota: None,
+            used: AtomicU64::new(0),
+            keep: false,
+        })
+    }
+
+    /// Limit the total bytes [`write`](Self::write) may store
+    pub fn with_quota(mut self, bytes: u64) -> Self {
+        self.quota = Some(bytes);
+        self
+    }
+
+    /// The directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bytes stored through [`write`](Self::write) so far
+    pub fn used_bytes(&self) -> u64 {
+        self.used.load(Ordering::SeqCst)
+    }
+
+    /// A path in the directory that no other call returns: `name_hint`'s
+    /// stem and extension around a random suffix
+    pub fn unique_path(&self, name_hint: &str) -> PathBuf {
+        let hint = Path::new(name_hint);
+        let stem = hint.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+        let name = match hint.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, suffix, ext.to_string_lossy()),
+            None => format!("{}-{}", stem, suffix),
+        };
+        self.path.join(name.trim_start_matches('-'))
+    }
+
+    /// Create a new, empty file with a unique name
+    ///
+    /// Bytes written through the returned handle don't count towards the
+    /// quota.
+    pub fn create_file(&self, name_hint: &str) -> Result<(PathBuf, File)> {
+        let path = self.unique_path(name_hint);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| io_error(e, &path))?;
+        Ok((path, file))
+    }
+
+    /// Write `data` to a new file with a unique name, within the quota
+    pub fn write(&self, name_hint: &str, data: &[u8]) -> Result<PathBuf> {
+        let size = data.len() as u64;
+        if let Some(quota) = self.quota {
+            self.used
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+                    used.checked_add(size).filter(|&after| after <= quota)
+                })
+                .map_err(|_| EditError::InsufficientSpace {
+                    path: self.path.display().to_string(),
+                })?;
+        } else {
+            self.used.fetch_add(size, Ordering::SeqCst);
+        }
+
+        let written = self.create_file(name_hint).and_then(|(path, mut file)| {
+            file.write_all(data).map_err(|e| {
+                let _ = fs::remove_file(&path);
+                io_error(e, &path)
+            })?;
+            Ok(path)
+        });
+        if written.is_err() {
+            // Nothing was stored; give the reservation back
+            self.used.fetch_sub(size, Ordering::SeqCst);
+        }
+        written
+    }
+
+    /// Keep the directory after drop and return its path
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        unregister(&self.path);
+        self.path.clone()
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_dir_all(&self.path);
+            unregister(&self.path);
+        }
+    }
+}
+
+/// Remove every live scratch directory, returning how many were removed
+///
+/// Meant for exit hooks; scratch directories dropped normally are already
+/// gone.
+pub fn cleanup_scratch_dirs() -> usize {
+    let dirs = std::mem::take(&mut *LIVE_SCRATCH_DIRS.lock().unwrap_or_else(|e| e.into_inner()));
+    dirs.iter().filter(|dir| fs::remove_dir_all(dir).is_ok()).count()
+}
+
+/// A unique temporary path next to `target`, for writing before an atomic
+/// rename: `.{file name}.{random}.tmp` in the same directory
+pub fn temp_path_for(target: &Path) -> PathBuf {
+    let name = target.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    target.with_file_name(format!(".{}.{}.tmp", name, suffix))
+}
+
+fn register(path: &Path) {
+    LIVE_SCRATCH_DIRS.lock().unwrap_or_else(|e| e.into_inner()).push(path.to_path_buf());
+}
+
+fn unregister(path: &Path) {
+    LIVE_SCRATCH_DIRS.lock().unwrap_or_else(|e| e.into_inner()).retain(|dir| dir != path);
+}
+
+fn io_error(error: std::io::Error, path: &Path) -> EditError {
+    EditError::from_io_with_path(error, path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_dir_lifecycle() {
+        let scratch = ScratchDir::new("vespera-test").unwrap();
+        let dir = scratch.path().to_path_buf();
+        let a = scratch.write("notes.md", b"a").unwrap();
+        let b = scratch.write("notes.md", b"b").unwrap();
+        assert_ne!(a, b);
+        assert!(a.extension().is_some_and(|ext| ext == "md"));
+        assert_eq!(fs::read(&b).unwrap(), b"b");
+
+        drop(scratch);
+        assert!(!dir.exists());
+
+        let kept = ScratchDir::new("vespera-test").unwrap().keep();
+        assert!(kept.exists());
+        fs::remove_dir_all(kept).unwrap();
+    }
+
+    #[test]
+    fn test_quota() {
+        let scratch = ScratchDir::new("vespera-test").unwrap().with_quota(10);
+        scratch.write("a", &[0; 6]).unwrap();
+        assert!(matches!(scratch.write("b", &[0; 5]), Err(EditError::InsufficientSpace { .. })));
+        scratch.write("c", &[0; 4]).unwrap();
+        assert_eq!(scratch.used_bytes(), 10);
+
+        // A failed write doesn't use up quota
+        let scratch = ScratchDir::new("vespera-test").unwrap().with_quota(10);
+        fs::remove_dir(scratch.path()).unwrap();
+        assert!(scratch.write("a", &[0; 6]).is_err());
+        assert_eq!(scratch.used_bytes(), 0);
+        fs::create_dir(scratch.path()).unwrap();
+        scratch.write("b", &[0; 10]).unwrap();
+    }
+
+    #[test]
+    fn test_temp_path_for() {
+        let target = Path::new("/data/report.tmp");
+        let temp = temp_path_for(target);
+        assert_ne!(temp, target);
+        assert_eq!(temp.parent(), target.parent());
+        assert_ne!(temp, temp_path_for(target));
+    }
+}
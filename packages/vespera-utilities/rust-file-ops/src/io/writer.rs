@@ -4,6 +4,7 @@
 //! atomic operations, and error recovery.
 
 use crate::error::{EditError, Result};
+use crate::io::scratch::temp_path_for;
 use crate::io::strategy::FileStrategy;
 use crate::security::validate_path;
 use crate::types::EditConfig;
@@ -171,7 +172,7 @@ impl AtomicFileWriter {
     /// Create new atomic writer
     pub fn new(path: impl AsRef<Path>) -> FileOpResult<Self> {
         let final_path = path.as_ref().to_path_buf();
-        let temp_path = final_path.with_extension("tmp");
+        let temp_path = temp_path_for(&final_path);
         
         // Ensure parent directory exists
         if let Some(parent) = final_path.parent() {
@@ -28,19 +28,30 @@ pub mod edit;
 pub mod io;
 // pub mod search; // TODO: Fix grep API usage
 pub mod security;
+pub mod guardrails;
 pub mod chunking;
 
 // Re-export core types for convenience
-pub use error::{EditError, Result};
+pub use error::{EditError, MatchCandidate, Result};
+pub use guardrails::{Guardrails, session_bytes_written, reset_session_bytes};
 pub use types::{
-    EditOperation, EditResult, MultiEditResult, PerformanceMetrics,
-    SingleOperationResult, EditConfig,
+    EditOperation, EditResult, EditTarget, MultiEditResult, PerformanceMetrics,
+    SingleOperationResult, EditConfig, OperationEntry, OperationStatus,
 };
 pub use edit::{
-    StringMatcher, Match, MatchConfig, SingleEditor, MultiEditor,
+    StringMatcher, Match, MatchConfig, MatchBoundary, SingleEditor, MultiEditor,
     replace_string, replace_first, replace_all, apply_multiple_edits,
+    verify_edit_invariants, InvariantViolation,
+};
+pub use io::{
+    FileReader, FileWriter, ensure_file, ensure_block, EnsureOutcome,
+    diff_trees, DiffOptions, FileChange, TreeDiff,
+    list_archive, read_archive_entry, extract_archive, edit_archive_entry, ArchiveEntry, ArchiveFormat,
+    read_range, head, tail, follow_from, ReadRange, Tail,
+    detect_file_kind, detect_content_kind, looks_binary, FileKind, TextEncoding,
+    hash_file, hash_bytes, hash_tree, FileHash, HashAlgorithm, HashManifest, HashTreeOptions, ManifestChanges,
+    ScratchDir, cleanup_scratch_dirs, temp_path_for,
 };
-pub use io::{FileReader, FileWriter};
 
 // Python bindings (when pyo3 feature is enabled)
 #[cfg(feature = "python-bindings")]
@@ -91,11 +102,14 @@ pub fn edit_file(
     let content = reader.read_for_editing()?;
 
     // Apply edit operation
-    let editor = SingleEditor::new();
+    let editor = SingleEditor::new()
+        .require_unique_match(config.require_unique_match)
+        .detect_already_applied(config.detect_already_applied);
     let result = editor.apply_edit(&content, operation)?;
 
     // Write result back to file if changes were made
     if result.changed {
+        config.guardrails.check_write(path_ref, result.content.len() as u64)?;
         let mut writer = FileWriter::with_config(path_ref, config)?;
         writer.write_edit_result(&result.content)?;
     }
@@ -148,11 +162,14 @@ pub fn multi_edit_file(
     let content = reader.read_for_editing()?;
 
     // Apply multi-edit operations
-    let editor = MultiEditor::new();
+    let editor = MultiEditor::new()
+        .require_unique_match(config.require_unique_match)
+        .detect_already_applied(config.detect_already_applied);
     let result = editor.apply_edits(&content, operations)?;
 
     // Write result back to file if changes were made
     if result.changed {
+        config.guardrails.check_write(path_ref, result.content.len() as u64)?;
         let mut writer = FileWriter::with_config(path_ref, config)?;
         writer.write_edit_result(&result.content)?;
     }
@@ -185,11 +202,14 @@ pub fn edit_file_atomic(
     let content = reader.read_for_editing()?;
 
     // Apply edit operation
-    let editor = SingleEditor::new();
+    let editor = SingleEditor::new()
+        .require_unique_match(config.require_unique_match)
+        .detect_already_applied(config.detect_already_applied);
     let result = editor.apply_edit(&content, operation)?;
 
     // Write result back atomically if changes were made
     if result.changed {
+        config.guardrails.check_write(path_ref, result.content.len() as u64)?;
         io::writer::write_atomic_safe(path_ref, &result.content, config.max_file_size)?;
     }
 
@@ -227,17 +247,90 @@ pub fn multi_edit_file_atomic(
     let content = reader.read_for_editing()?;
 
     // Apply multi-edit operations
-    let editor = MultiEditor::new();
+    let editor = MultiEditor::new()
+        .require_unique_match(config.require_unique_match)
+        .detect_already_applied(config.detect_already_applied);
     let result = editor.apply_edits(&content, operations)?;
 
     // Write result back atomically if changes were made
     if result.changed {
+        config.guardrails.check_write(path_ref, result.content.len() as u64)?;
         io::writer::write_atomic_safe(path_ref, &result.content, config.max_file_size)?;
     }
 
     Ok(result)
 }
 
+/// Apply the same edit operations to several files
+///
+/// Every file is edited in memory first, and the guardrails are checked
+/// for the whole batch, so nothing is written unless all edits succeed
+/// and every write is allowed. The changed files are then written to
+/// temporary files, and only replaced once all of them were written; if
+/// replacing one fails, the files already replaced are restored. Results
+/// are returned in the order of `paths`.
+///
+/// # Arguments
+/// * `paths` - Files to edit
+/// * `operations` - Slice of edit operations to perform in order on each file
+/// * `config` - Optional configuration (uses default if None)
+pub fn multi_edit_files<P: AsRef<StdPath>>(
+    paths: &[P],
+    operations: &[EditOperation],
+    config: Option<EditConfig>,
+) -> Result<Vec<MultiEditResult>> {
+    let config = config.unwrap_or_default();
+    let editor = MultiEditor::new()
+        .require_unique_match(config.require_unique_match)
+        .detect_already_applied(config.detect_already_applied);
+
+    let mut results = Vec::with_capacity(paths.len());
+    for path in paths {
+        let reader = FileReader::with_config(path.as_ref(), config.clone())?;
+        let content = reader.read_for_editing()?;
+        results.push(editor.apply_edits(&content, operations)?);
+    }
+
+    let writes: Vec<(&StdPath, u64)> = paths.iter().zip(&results)
+        .filter(|(_, result)| result.changed)
+        .map(|(path, result)| (path.as_ref(), result.content.len() as u64))
+        .collect();
+    config.guardrails.check_writes(&writes)?;
+
+    // Write every file to a temporary file next to it before replacing any,
+    // so a file that can't be written leaves all of them as they were
+    let mut staged = Vec::with_capacity(writes.len());
+    for (path, result) in paths.iter().zip(&results).filter(|(_, result)| result.changed) {
+        let path = path.as_ref();
+        if result.content.len() as u64 > config.max_file_size {
+            return Err(EditError::file_too_large(
+                result.content.len() as u64,
+                config.max_file_size,
+                path.display().to_string(),
+            ));
+        }
+        let original = std::fs::read(path).map_err(|e| EditError::io_error(path.display().to_string(), "read", e))?;
+        let mut writer = io::writer::AtomicFileWriter::new(path)?;
+        writer.write_str(&result.content)?;
+        staged.push((path, original, writer));
+    }
+
+    // Renames rarely fail, but if one does, put back the files already replaced
+    let mut replaced: Vec<(&StdPath, Vec<u8>)> = Vec::with_capacity(staged.len());
+    for (path, original, writer) in staged {
+        if let Err(e) = writer.commit() {
+            // Best effort: the failed rename is the error to report
+            for (path, original) in replaced {
+                let _ = io::writer::AtomicFileWriter::new(path)
+                    .and_then(|mut writer| writer.write(&original).and_then(|_| writer.commit()));
+            }
+            return Err(e);
+        }
+        replaced.push((path, original));
+    }
+    Ok(results)
+}
+
 /// Preview what an edit operation would do without modifying the file
 ///
 /// Useful for testing edit operations before applying them.
@@ -298,7 +391,7 @@ pub fn preview_multi_edit(
 
 #[cfg(feature = "python-bindings")]
 mod python_bindings {
-    use crate::chunking::{ChunkingConfig, ChunkStrategy, DocumentFormat};
+    use crate::chunking::{ChunkingConfig, ChunkStrategy, DocumentFormat, PostProcessor};
     use crate::chunking::strategies::chunk_document;
     use crate::chunking::discord::{chunk_discord_export, parse_discord_html};
     use std::collections::HashMap;
@@ -306,6 +399,88 @@ mod python_bindings {
     
     use super::*;
     
+    /// Guardrails applied to every write made through the bindings, set
+    /// with [`py_set_guardrails`]
+    static GUARDRAILS: std::sync::RwLock<Guardrails> = std::sync::RwLock::new(Guardrails {
+        max_bytes_per_operation: None,
+        max_bytes_per_session: None,
+        max_files_per_operation: None,
+        protected_paths: Vec::new(),
+    });
+    
+    /// The configuration every binding that writes uses
+    fn binding_config() -> EditConfig {
+        let guardrails = GUARDRAILS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        EditConfig::new().with_guardrails(guardrails)
+    }
+    
+    /// Guardrail refusals surface as `PermissionError`, other failures as
+    /// `fallback`
+    fn to_py_err(error: EditError, fallback: fn(String) -> PyErr) -> PyErr {
+        match error {
+            EditError::GuardrailViolation { .. } => pyo3::exceptions::PyPermissionError::new_err(error.to_string()),
+            other => fallback(other.to_string()),
+        }
+    }
+    
+    fn io_err(message: String) -> PyErr {
+        pyo3::exceptions::PyIOError::new_err(message)
+    }
+    
+    fn runtime_err(message: String) -> PyErr {
+        pyo3::exceptions::PyRuntimeError::new_err(message)
+    }
+    
+    /// Check a write of `bytes` to `path` against the binding guardrails
+    fn check_write(path: &str, bytes: usize) -> PyResult<()> {
+        binding_config().guardrails.check_write(Path::new(path), bytes as u64)
+            .map_err(|e| to_py_err(e, io_err))
+    }
+    
+    /// Set the guardrails applied to every write made through the bindings
+    ///
+    /// Starts from no limits, or from `Guardrails::for_agents()` when
+    /// `for_agents` is true; the other arguments override or extend it.
+    #[pyfunction]
+    #[pyo3(signature = (
+        for_agents = false,
+        max_bytes_per_operation = None,
+        max_bytes_per_session = None,
+        max_files_per_operation = None,
+        protected_paths = Vec::new(),
+    ))]
+    pub fn py_set_guardrails(
+        for_agents: bool,
+        max_bytes_per_operation: Option<u64>,
+        max_bytes_per_session: Option<u64>,
+        max_files_per_operation: Option<usize>,
+        protected_paths: Vec<String>,
+    ) {
+        let mut guardrails = if for_agents { Guardrails::for_agents() } else { Guardrails::new() };
+        if max_bytes_per_operation.is_some() {
+            guardrails.max_bytes_per_operation = max_bytes_per_operation;
+        }
+        if max_bytes_per_session.is_some() {
+            guardrails.max_bytes_per_session = max_bytes_per_session;
+        }
+        if max_files_per_operation.is_some() {
+            guardrails.max_files_per_operation = max_files_per_operation;
+        }
+        guardrails.protected_paths.extend(protected_paths);
+        *GUARDRAILS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = guardrails;
+    }
+    
+    /// Read a file for string editing, refusing binaries
+    fn read_text_for_editing(path: &str) -> PyResult<String> {
+        let kind = crate::io::detect_file_kind(path)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        if kind.is_binary {
+            let error = EditError::BinaryFile { path: path.to_string(), mime: kind.mime };
+            return Err(pyo3::exceptions::PyValueError::new_err(error.to_string()));
+        }
+        fs::read_to_string(path).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+
     /// Read file content as bytes
     #[pyfunction]
     pub fn read_file(path: &str) -> PyResult<Vec<u8>> {
@@ -328,12 +503,14 @@ mod python_bindings {
     /// Write bytes to file
     #[pyfunction]
     pub fn write_file(path: &str, content: &[u8]) -> PyResult<()> {
+        check_write(path, content.len())?;
         fs::write(path, content).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
     }
 
     /// Write string to file
     #[pyfunction]
     pub fn write_file_string(path: &str, content: &str) -> PyResult<()> {
+        check_write(path, content.len())?;
         fs::write(path, content).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
     }
 
@@ -341,6 +518,7 @@ mod python_bindings {
     #[pyfunction]
     pub fn append_file(path: &str, content: &str) -> PyResult<()> {
         use std::io::Write;
+        check_write(path, content.len())?;
         let mut file = fs::OpenOptions::new()
             .create(true)
             .append(true)
@@ -354,8 +532,9 @@ mod python_bindings {
     /// Write file atomically (safe replacement)
     #[pyfunction]
     pub fn write_file_atomic(path: &str, content: &str) -> PyResult<()> {
+        check_write(path, content.len())?;
         let path = Path::new(path);
-        let temp_path = path.with_extension("tmp");
+        let temp_path = crate::io::scratch::temp_path_for(path);
         
         // Write to temp file first
         fs::write(&temp_path, content).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
@@ -399,26 +578,11 @@ mod python_bindings {
         new_string: &str,
         replace_all: bool,
     ) -> PyResult<(String, usize, Vec<usize>)> {
-        use crate::edit::single::SingleEditor;
         use crate::types::EditOperation;
         
-        // Read file content
-        let content = fs::read_to_string(path)
-            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
-        
-        // Create operation and editor
         let operation = EditOperation::new(old_string, new_string, replace_all);
-        let editor = SingleEditor::new();
-        
-        // Apply edit
-        let result = editor.apply_edit(&content, &operation)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-        
-        // Write back if changed
-        if result.changed {
-            fs::write(path, &result.content)
-                .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
-        }
+        let result = crate::edit_file(path, &operation, Some(binding_config()))
+            .map_err(|e| to_py_err(e, runtime_err))?;
         
         Ok((result.content, result.replacements_made, result.replacement_positions))
     }
@@ -429,28 +593,15 @@ mod python_bindings {
         path: &str,
         edits: Vec<(String, String, bool)>,
     ) -> PyResult<(String, usize, usize)> {
-        use crate::edit::multi::MultiEditor;
         use crate::types::EditOperation;
         
-        // Read file content
-        let content = fs::read_to_string(path)
-            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
-        
         // Convert Python tuples to EditOperations
         let operations: Vec<EditOperation> = edits.into_iter()
             .map(|(old, new, all)| EditOperation::new(&old, &new, all))
             .collect();
         
-        // Create editor and apply edits
-        let editor = MultiEditor::new();
-        let result = editor.apply_edits(&content, &operations)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-        
-        // Write back if changed
-        if result.changed {
-            fs::write(path, &result.content)
-                .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
-        }
+        let result = crate::multi_edit_file(path, &operations, Some(binding_config()))
+            .map_err(|e| to_py_err(e, runtime_err))?;
         
         Ok((
             result.content,
@@ -459,6 +610,42 @@ mod python_bindings {
         ))
     }
     
+    /// Apply labelled edits to a file on a best-effort basis
+    ///
+    /// Each edit is `(label, old_string, new_string, replace_all)`. Returns
+    /// the per-operation entries as JSON, keyed by label, so callers can map
+    /// outcomes back to their request items.
+    #[pyfunction]
+    pub fn py_multi_edit_file_report(
+        path: &str,
+        edits: Vec<(String, String, String, bool)>,
+    ) -> PyResult<String> {
+        use crate::edit::multi::MultiEditor;
+        use crate::types::EditOperation;
+        
+        let content = read_text_for_editing(path)?;
+        
+        let operations: Vec<EditOperation> = edits.into_iter()
+            .map(|(label, old, new, all)| EditOperation::new(old, new, all).with_label(label))
+            .collect();
+        
+        let result = MultiEditor::new()
+            .detect_already_applied(true)
+            .apply_edits_best_effort(&content, &operations)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        
+        if result.changed {
+            let config = binding_config();
+            config.guardrails.check_write(Path::new(path), result.content.len() as u64)
+                .and_then(|()| FileWriter::with_config(path, config))
+                .and_then(|mut writer| writer.write_edit_result(&result.content))
+                .map_err(|e| to_py_err(e, io_err))?;
+        }
+        
+        serde_json::to_string(&result.entries())
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    
     /// Count how many replacements would be made without modifying the file
     #[pyfunction]
     pub fn py_count_replacements(
@@ -481,23 +668,220 @@ mod python_bindings {
             .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
     
+    /// Make a file contain exactly `content`; returns "created", "updated"
+    /// or "unchanged"
+    #[pyfunction]
+    pub fn py_ensure_file(path: &str, content: &str) -> PyResult<String> {
+        let outcome = crate::io::ensure_file(path, content, Some(binding_config()))
+            .map_err(|e| to_py_err(e, io_err))?;
+        Ok(outcome_name(outcome))
+    }
+    
+    /// Maintain a marker-delimited block in a file; returns "created",
+    /// "updated" or "unchanged"
+    #[pyfunction]
+    pub fn py_ensure_block(path: &str, marker_begin: &str, marker_end: &str, content: &str) -> PyResult<String> {
+        let outcome = crate::io::ensure_block(path, marker_begin, marker_end, content, Some(binding_config()))
+            .map_err(|e| to_py_err(e, io_err))?;
+        Ok(outcome_name(outcome))
+    }
+    
+    fn outcome_name(outcome: crate::io::EnsureOutcome) -> String {
+        serde_json::to_value(outcome)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+    
+    /// Compare two directory trees; returns the added, removed and modified
+    /// files (with unified diffs) as JSON
+    #[pyfunction]
+    #[pyo3(signature = (path_a, path_b, ignore_patterns = Vec::new(), context_lines = 3, max_diff_bytes = 65536))]
+    pub fn py_diff_trees(
+        path_a: &str,
+        path_b: &str,
+        ignore_patterns: Vec<String>,
+        context_lines: usize,
+        max_diff_bytes: usize,
+    ) -> PyResult<String> {
+        let options = crate::io::DiffOptions {
+            ignore_patterns,
+            context_lines,
+            max_diff_bytes,
+            ..Default::default()
+        };
+        let diff = crate::io::diff_trees(path_a, path_b, &options)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        serde_json::to_string(&diff)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    
+    /// List the entries of a zip or tar archive as JSON
+    #[pyfunction]
+    pub fn py_list_archive(path: &str) -> PyResult<String> {
+        let entries = crate::io::list_archive(path)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        serde_json::to_string(&entries)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    
+    /// Extract the archive entries matching any of the glob patterns (all
+    /// files when empty); returns the written paths
+    #[pyfunction]
+    #[pyo3(signature = (path, destination, patterns = Vec::new()))]
+    pub fn py_extract_archive(path: &str, destination: &str, patterns: Vec<String>) -> PyResult<Vec<String>> {
+        let written = crate::io::extract_archive(path, destination, &patterns, Some(binding_config()))
+            .map_err(|e| to_py_err(e, io_err))?;
+        Ok(written.into_iter().map(|p| p.display().to_string()).collect())
+    }
+    
+    /// Apply (old, new, replace_all) edits to a text file inside an archive;
+    /// returns (total_replacements, successful_operations)
+    #[pyfunction]
+    pub fn py_edit_archive_entry(
+        path: &str,
+        entry: &str,
+        edits: Vec<(String, String, bool)>,
+    ) -> PyResult<(usize, usize)> {
+        use crate::types::EditOperation;
+        
+        let operations: Vec<EditOperation> = edits.into_iter()
+            .map(|(old, new, all)| EditOperation::new(&old, &new, all))
+            .collect();
+        let result = crate::io::edit_archive_entry(path, entry, &operations, Some(binding_config()))
+            .map_err(|e| to_py_err(e, runtime_err))?;
+        Ok((result.total_replacements, result.successful_operations))
+    }
+    
+    /// Read lines `start..=end` (1-based, unit="lines") or bytes
+    /// `start..end` (unit="bytes") of a file
+    #[pyfunction]
+    #[pyo3(signature = (path, start, end, unit = "lines"))]
+    pub fn py_read_range(path: &str, start: u64, end: u64, unit: &str) -> PyResult<String> {
+        let range = match unit {
+            "lines" => crate::io::ReadRange::Lines { start: start as usize, end: end as usize },
+            "bytes" => crate::io::ReadRange::Bytes { start, end },
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown range unit: {}", other)));
+            }
+        };
+        crate::io::read_range(path, range)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+    
+    /// Read the first `n_lines` lines of a file
+    #[pyfunction]
+    #[pyo3(signature = (path, n_lines = 10))]
+    pub fn py_head(path: &str, n_lines: usize) -> PyResult<Vec<String>> {
+        crate::io::head(path, n_lines)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+    
+    /// Read the last `n_lines` lines of a file; returns (lines, offset)
+    ///
+    /// With `follow`, also waits up to `timeout_ms` for lines to be
+    /// appended. Pass the offset to `py_follow_from` to keep following.
+    #[pyfunction]
+    #[pyo3(signature = (path, n_lines = 10, follow = false, timeout_ms = 1000, poll_interval_ms = 100))]
+    pub fn py_tail(
+        py: Python<'_>,
+        path: &str,
+        n_lines: usize,
+        follow: bool,
+        timeout_ms: u64,
+        poll_interval_ms: u64,
+    ) -> PyResult<(Vec<String>, u64)> {
+        let mut result = crate::io::tail(path, n_lines)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        if follow {
+            let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+            let appended = py.allow_threads(|| loop {
+                let next = crate::io::follow_from(path, result.offset)?;
+                if !next.lines.is_empty() || std::time::Instant::now() >= deadline {
+                    return Ok::<_, crate::error::EditError>(next);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(poll_interval_ms));
+            }).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+            result.lines.extend(appended.lines);
+            result.offset = appended.offset;
+        }
+        Ok((result.lines, result.offset))
+    }
+    
+    /// Read the complete lines appended since `offset`; returns
+    /// (lines, new_offset)
+    #[pyfunction]
+    pub fn py_follow_from(path: &str, offset: u64) -> PyResult<(Vec<String>, u64)> {
+        let result = crate::io::follow_from(path, offset)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        Ok((result.lines, result.offset))
+    }
+    
+    /// Sniff a file: whether it is binary, its text encoding and a MIME
+    /// guess, as JSON
+    #[pyfunction]
+    pub fn py_detect_file_kind(path: &str) -> PyResult<String> {
+        let kind = crate::io::detect_file_kind(path)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        serde_json::to_string(&kind)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    
+    fn hash_algorithm(name: &str) -> PyResult<crate::io::HashAlgorithm> {
+        crate::io::HashAlgorithm::from_name(name).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Unknown hash algorithm: {}", name))
+        })
+    }
+    
+    /// Hash a file ("blake3" or "sha256"); returns the hex digest
+    #[pyfunction]
+    #[pyo3(signature = (path, algorithm = "blake3"))]
+    pub fn py_hash_file(path: &str, algorithm: &str) -> PyResult<String> {
+        crate::io::hash_file(path, hash_algorithm(algorithm)?)
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+    
+    /// Hash every file under a directory in parallel; returns the manifest
+    /// (root_hash and per-file hashes) as JSON
+    #[pyfunction]
+    #[pyo3(signature = (root, algorithm = "blake3", ignore_patterns = Vec::new()))]
+    pub fn py_hash_tree(py: Python<'_>, root: &str, algorithm: &str, ignore_patterns: Vec<String>) -> PyResult<String> {
+        let options = crate::io::HashTreeOptions {
+            algorithm: hash_algorithm(algorithm)?,
+            ignore_patterns,
+            ..Default::default()
+        };
+        let manifest = py.allow_threads(|| crate::io::hash_tree(root, &options))
+            .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        serde_json::to_string(&manifest)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    
+    /// Remove the scratch directories still alive; registered with `atexit`
+    /// when the module is imported
+    #[pyfunction]
+    pub fn py_cleanup_scratch_dirs() -> usize {
+        crate::io::cleanup_scratch_dirs()
+    }
+    
     /// Chunk text content using specified strategy
     #[pyfunction]
-    #[pyo3(signature = (content, max_chunk_size=2000, overlap_size=200, strategy="sentence"))]
+    #[pyo3(signature = (content, max_chunk_size=2000, overlap_size=200, strategy="sentence", post_processors=None))]
     pub fn py_chunk_text(
         content: &str,
         max_chunk_size: usize,
         overlap_size: usize,
         strategy: &str,
+        post_processors: Option<Vec<String>>,
     ) -> PyResult<Vec<HashMap<String, PyObject>>> {
+        let post_processors = post_processors.unwrap_or_default().iter()
+            .map(|name| PostProcessor::from_name(name).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!("Unknown post-processor: {}", name))
+            }))
+            .collect::<PyResult<Vec<_>>>()?;
+
         Python::with_gil(|py| {
-            let chunk_strategy = match strategy {
-                "fixed" => ChunkStrategy::FixedSize,
-                "sentence" => ChunkStrategy::SentenceBoundary,
-                "paragraph" => ChunkStrategy::ParagraphBoundary,
-                "conversation" => ChunkStrategy::ConversationBreak,
-                _ => ChunkStrategy::SentenceBoundary,
-            };
+            let chunk_strategy = chunk_strategy_from_name(strategy);
             
             let config = ChunkingConfig {
                 max_chunk_size,
@@ -505,6 +889,7 @@ mod python_bindings {
                 chunk_strategy,
                 preserve_metadata: true,
                 format: DocumentFormat::PlainText,
+                post_processors,
             };
             
             let chunks = chunk_document(content, &config)
@@ -518,6 +903,10 @@ mod python_bindings {
                 map.insert("total_chunks".to_string(), chunk.metadata.total_chunks.to_object(py));
                 map.insert("byte_range".to_string(), 
                     (chunk.metadata.byte_range.0, chunk.metadata.byte_range.1).to_object(py));
+                map.insert("language".to_string(), chunk.metadata.language.to_object(py));
+                map.insert("code_language".to_string(), chunk.metadata.code_language.to_object(py));
+                map.insert("heading_path".to_string(), chunk.metadata.heading_path.to_object(py));
+                map.insert("function_name".to_string(), chunk.metadata.function_name.to_object(py));
                 map
             }).collect();
             
@@ -525,6 +914,93 @@ mod python_bindings {
         })
     }
     
+    /// Map a chunking strategy name, defaulting to sentence boundaries
+    fn chunk_strategy_from_name(name: &str) -> ChunkStrategy {
+        match name {
+            "fixed" => ChunkStrategy::FixedSize,
+            "sentence" => ChunkStrategy::SentenceBoundary,
+            "paragraph" => ChunkStrategy::ParagraphBoundary,
+            "conversation" => ChunkStrategy::ConversationBreak,
+            _ => ChunkStrategy::SentenceBoundary,
+        }
+    }
+    
+    /// Round-trip a Python object through `json.dumps` into a JSON value
+    fn to_json_value(json: &Bound<'_, PyModule>, object: &Bound<'_, PyAny>) -> std::result::Result<serde_json::Value, String> {
+        let text: String = json.call_method1("dumps", (object,))
+            .and_then(|dumped| dumped.extract())
+            .map_err(|e| e.to_string())?;
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    }
+    
+    /// Chunk `content` and map-reduce it with Python callables
+    ///
+    /// `map_fn(text, metadata)` is called per chunk with the prepared model
+    /// input; `combine_fn(results)` gets a list of `{"provenance", "result"}`
+    /// dicts in document order. Both must return JSON-serializable values.
+    /// Returns the outcome as JSON.
+    #[pyfunction]
+    #[pyo3(signature = (
+        content, map_fn, combine_fn, max_chunk_size=2000, overlap_size=200, strategy="sentence",
+        context_window=8000, max_retries=2, retry_delay_ms=500, continue_on_error=true
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn py_map_reduce(
+        py: Python<'_>,
+        content: &str,
+        map_fn: PyObject,
+        combine_fn: PyObject,
+        max_chunk_size: usize,
+        overlap_size: usize,
+        strategy: &str,
+        context_window: usize,
+        max_retries: u32,
+        retry_delay_ms: u64,
+        continue_on_error: bool,
+    ) -> PyResult<String> {
+        use crate::chunking::llm::{map_reduce, CharEstimateTokenizer, LLMProcessingSession, MapReduceOptions};
+        
+        let config = ChunkingConfig {
+            max_chunk_size,
+            overlap_size,
+            chunk_strategy: chunk_strategy_from_name(strategy),
+            ..Default::default()
+        };
+        let chunks = chunk_document(content, &config)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        let options = MapReduceOptions {
+            context_window,
+            include_metadata: true,
+            max_retries,
+            retry_delay: std::time::Duration::from_millis(retry_delay_ms),
+            continue_on_error,
+        };
+        let json = py.import_bound("json")?;
+        let mut session = LLMProcessingSession::new(&chunks);
+        
+        let outcome = map_reduce(
+            &mut session,
+            &chunks,
+            &options,
+            &CharEstimateTokenizer::default(),
+            |_, input| {
+                let metadata = input.metadata.clone().unwrap_or_default();
+                let result = map_fn.call1(py, (input.content.as_str(), metadata)).map_err(|e| e.to_string())?;
+                to_json_value(&json, result.bind(py))
+            },
+            |mapped| {
+                let payload = serde_json::to_string(mapped).map_err(|e| e.to_string())?;
+                let results = json.call_method1("loads", (payload,)).map_err(|e| e.to_string())?;
+                let combined = combine_fn.call1(py, (results,)).map_err(|e| e.to_string())?;
+                to_json_value(&json, combined.bind(py))
+            },
+        )
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        
+        serde_json::to_string(&outcome)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    
     /// Chunk Discord HTML export file
     #[pyfunction]
     #[pyo3(signature = (html_content, preserve_conversations=true, max_tokens_per_chunk=2000))]
@@ -610,6 +1086,8 @@ fn vespera_file_ops(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(append_file, m)?)?;
     m.add_function(wrap_pyfunction!(write_file_atomic, m)?)?;
     
+    m.add_function(wrap_pyfunction!(py_set_guardrails, m)?)?;
+    
     // Utility functions
     m.add_function(wrap_pyfunction!(glob_files, m)?)?;
     m.add_function(wrap_pyfunction!(get_file_info, m)?)?;
@@ -617,12 +1095,61 @@ fn vespera_file_ops(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // EditOperation-based functions
     m.add_function(wrap_pyfunction!(py_edit_file, m)?)?;
     m.add_function(wrap_pyfunction!(py_multi_edit_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_multi_edit_file_report, m)?)?;
     m.add_function(wrap_pyfunction!(py_count_replacements, m)?)?;
+    m.add_function(wrap_pyfunction!(py_ensure_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_ensure_block, m)?)?;
+    m.add_function(wrap_pyfunction!(py_diff_trees, m)?)?;
+    m.add_function(wrap_pyfunction!(py_list_archive, m)?)?;
+    m.add_function(wrap_pyfunction!(py_extract_archive, m)?)?;
+    m.add_function(wrap_pyfunction!(py_edit_archive_entry, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_range, m)?)?;
+    m.add_function(wrap_pyfunction!(py_head, m)?)?;
+    m.add_function(wrap_pyfunction!(py_tail, m)?)?;
+    m.add_function(wrap_pyfunction!(py_follow_from, m)?)?;
+    m.add_function(wrap_pyfunction!(py_detect_file_kind, m)?)?;
+    m.add_function(wrap_pyfunction!(py_hash_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_hash_tree, m)?)?;
+    
+    // Scratch directories outlive the interpreter unless removed at exit
+    let cleanup_scratch = wrap_pyfunction!(py_cleanup_scratch_dirs, m)?;
+    m.add_function(cleanup_scratch.clone())?;
+    m.py().import_bound("atexit")?.call_method1("register", (cleanup_scratch,))?;
     
     // Document chunking functions
     m.add_function(wrap_pyfunction!(python_bindings::py_chunk_text, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::py_map_reduce, m)?)?;
     m.add_function(wrap_pyfunction!(python_bindings::py_chunk_discord_html, m)?)?;
     m.add_function(wrap_pyfunction!(python_bindings::py_parse_discord_html, m)?)?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_edit_files_writes_nothing_when_a_file_fails() {
+        let dir = std::env::temp_dir().join(format!("vespera-multi-edit-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = [dir.join("a.txt"), dir.join("b.txt")];
+        std::fs::write(&paths[0], "old").unwrap();
+        std::fs::write(&paths[1], "old old").unwrap();
+        let operations = [EditOperation::new("old", "0123456789", true)];
+
+        // The first file fits the limit once edited; the second does not
+        let config = EditConfig::new().with_max_file_size(20);
+        let result = multi_edit_files(&paths, &operations, Some(config));
+        assert!(matches!(result, Err(EditError::FileTooLarge { .. })), "{:?}", result);
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "old");
+        assert_eq!(std::fs::read_to_string(&paths[1]).unwrap(), "old old");
+        // No temporary files are left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        multi_edit_files(&paths, &operations, None).unwrap();
+        assert_eq!(std::fs::read_to_string(&paths[1]).unwrap(), "0123456789 0123456789");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -5,6 +5,7 @@
 //! All types are designed for library use with proper visibility modifiers
 //! and derive appropriate traits for debugging, cloning, and comparison.
 
+use crate::guardrails::Guardrails;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -22,6 +23,32 @@ pub struct EditOperation {
     
     /// If true, replace all occurrences; if false, replace only first
     pub replace_all: bool,
+    
+    /// Which occurrences the operation addresses
+    pub target: EditTarget,
+    
+    /// Caller-supplied identifier, used to key this operation's entry in
+    /// multi-edit reports
+    pub label: Option<String>,
+}
+
+/// Addressing for an edit operation beyond first/all
+///
+/// Occurrences are the non-overlapping, leftmost-first matches of
+/// `old_string`, counted from the start of the content.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum EditTarget {
+    /// The first occurrence, or every occurrence with `replace_all`
+    #[default]
+    Everywhere,
+    /// Only the N-th occurrence (1-based); `replace_all` is ignored
+    Nth(usize),
+    /// Occurrences lying entirely within lines `start..=end` (1-based);
+    /// the first one, or all of them with `replace_all`
+    Lines { start: usize, end: usize },
+    /// Occurrences starting after the end of the first occurrence of the
+    /// anchor string; the first one, or all of them with `replace_all`
+    After(String),
 }
 
 impl EditOperation {
@@ -35,9 +62,35 @@ impl EditOperation {
             old_string: old_string.into(),
             new_string: new_string.into(),
             replace_all,
+            target: EditTarget::Everywhere,
+            label: None,
         }
     }
     
+    /// Attach a label identifying this operation in multi-edit reports
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+    
+    /// Address only the N-th occurrence (1-based)
+    pub fn nth_occurrence(mut self, n: usize) -> Self {
+        self.target = EditTarget::Nth(n);
+        self
+    }
+    
+    /// Address occurrences within lines `start..=end` (1-based, inclusive)
+    pub fn within_lines(mut self, start: usize, end: usize) -> Self {
+        self.target = EditTarget::Lines { start, end };
+        self
+    }
+    
+    /// Address occurrences after the first occurrence of `anchor`
+    pub fn after_anchor(mut self, anchor: impl Into<String>) -> Self {
+        self.target = EditTarget::After(anchor.into());
+        self
+    }
+    
     /// Create an operation that replaces only the first occurrence
     pub fn replace_first(old_string: impl Into<String>, new_string: impl Into<String>) -> Self {
         Self::new(old_string, new_string, false)
@@ -66,8 +119,21 @@ impl EditOperation {
             return Err(EditError::EmptyPattern);
         }
         
-        // Additional validations could go here
-        Ok(())
+        match &self.target {
+            EditTarget::Nth(0) => Err(EditError::invalid_operation(
+                "occurrence numbers start at 1",
+                Some("use nth_occurrence(1) for the first occurrence".to_string()),
+            )),
+            EditTarget::Lines { start, end } if *start == 0 || start > end => Err(EditError::invalid_operation(
+                format!("invalid line range {}..={}", start, end),
+                Some("line numbers start at 1 and the range must not be empty".to_string()),
+            )),
+            EditTarget::After(anchor) if anchor.is_empty() => Err(EditError::invalid_operation(
+                "anchor string is empty",
+                None,
+            )),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -148,6 +214,44 @@ pub struct SingleOperationResult {
     
     /// Any warnings or notes about this operation
     pub warnings: Vec<String>,
+    
+    /// Why the operation failed, in best-effort multi-edits
+    pub error: Option<String>,
+    
+    /// Whether the edit was found to be applied already
+    pub already_applied: bool,
+}
+
+/// Outcome of one operation within a multi-edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    /// The operation found and replaced at least one match
+    Applied,
+    /// The operation found nothing to replace
+    NoMatch,
+    /// The operation found nothing to replace, but its `new_string` is
+    /// already present
+    AlreadyApplied,
+    /// The operation failed; see the entry's error
+    Failed,
+}
+
+/// Per-operation entry of a multi-edit report
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct OperationEntry {
+    /// The operation's label, or its index when it has none
+    pub key: String,
+    /// Position of the operation in the request
+    pub index: usize,
+    /// Outcome of the operation
+    pub status: OperationStatus,
+    /// Number of replacements made
+    pub replacements_made: usize,
+    /// Byte positions where replacements occurred
+    pub replacement_positions: Vec<usize>,
+    /// Failure message for failed operations
+    pub error: Option<String>,
 }
 
 impl SingleOperationResult {
@@ -161,6 +265,33 @@ impl SingleOperationResult {
             made_changes: false,
             metrics: PerformanceMetrics::new(),
             warnings: Vec::new(),
+            error: None,
+            already_applied: false,
+        }
+    }
+    
+    /// Mark this operation as already applied
+    pub fn with_already_applied(mut self, already_applied: bool) -> Self {
+        self.already_applied = already_applied;
+        self
+    }
+    
+    /// Mark this operation as failed
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+    
+    /// Outcome of this operation
+    pub fn status(&self) -> OperationStatus {
+        if self.error.is_some() {
+            OperationStatus::Failed
+        } else if self.already_applied {
+            OperationStatus::AlreadyApplied
+        } else if self.found_matches {
+            OperationStatus::Applied
+        } else {
+            OperationStatus::NoMatch
         }
     }
     
@@ -201,6 +332,15 @@ pub struct EditResult {
     /// Byte positions where replacements occurred
     pub replacement_positions: Vec<usize>,
     
+    /// No match was found but `new_string` is already present, so the edit
+    /// appears to have been applied before
+    pub already_applied: bool,
+    
+    /// The exact original text replaced at each position, which differs
+    /// from `old_string` under case-, normalization- or whitespace-relaxed
+    /// matching
+    pub matched_texts: Vec<String>,
+    
     /// The original operation that was performed
     pub operation: EditOperation,
     
@@ -217,6 +357,8 @@ impl EditResult {
             metrics: PerformanceMetrics::new(),
             changed: false,
             replacement_positions: Vec::new(),
+            already_applied: false,
+            matched_texts: Vec::new(),
             operation,
             warnings: Vec::new(),
         }
@@ -236,6 +378,8 @@ impl EditResult {
             metrics: PerformanceMetrics::new(),
             changed,
             replacement_positions: positions,
+            already_applied: false,
+            matched_texts: Vec::new(),
             operation,
             warnings: Vec::new(),
         }
@@ -249,6 +393,8 @@ impl EditResult {
             metrics: PerformanceMetrics::new(),
             changed: false,
             replacement_positions: Vec::new(),
+            already_applied: false,
+            matched_texts: Vec::new(),
             operation,
             warnings: Vec::new(),
         }
@@ -266,6 +412,18 @@ impl EditResult {
         self
     }
     
+    /// Mark a no-match result as already applied
+    pub fn with_already_applied(mut self, already_applied: bool) -> Self {
+        self.already_applied = already_applied;
+        self
+    }
+    
+    /// Record the original text replaced at each position
+    pub fn with_matched_texts(mut self, matched_texts: Vec<String>) -> Self {
+        self.matched_texts = matched_texts;
+        self
+    }
+    
     /// Check if the operation was successful (found matches)
     pub fn was_successful(&self) -> bool {
         self.replacements_made > 0
@@ -349,6 +507,23 @@ impl MultiEditResult {
         self
     }
     
+    /// Per-operation entries in request order, keyed by label (or index)
+    pub fn entries(&self) -> Vec<OperationEntry> {
+        self.operation_results.iter().enumerate().map(|(index, result)| OperationEntry {
+            key: result.operation.label.clone().unwrap_or_else(|| index.to_string()),
+            index,
+            status: result.status(),
+            replacements_made: result.replacements_made,
+            replacement_positions: result.replacement_positions.clone(),
+            error: result.error.clone(),
+        }).collect()
+    }
+    
+    /// The entry for the operation with this label (or index)
+    pub fn entry(&self, key: &str) -> Option<OperationEntry> {
+        self.entries().into_iter().find(|entry| entry.key == key)
+    }
+    
     /// Get the number of operations that made no changes
     pub fn failed_operations(&self) -> usize {
         self.total_operations - self.successful_operations
@@ -377,9 +552,13 @@ impl MultiEditResult {
 /// Configuration options for edit operations
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EditConfig {
-    /// Maximum file size to process (bytes)
+    /// Maximum file size to process (bytes), also the largest archive
+    /// entry read into memory
     pub max_file_size: u64,
     
+    /// Maximum bytes read out of one archive, all entries combined
+    pub max_archive_size: u64,
+    
     /// Timeout for individual operations
     pub operation_timeout: Duration,
     
@@ -397,6 +576,17 @@ pub struct EditConfig {
     
     /// Base directory for path security validation
     pub base_dir: Option<PathBuf>,
+    
+    /// Fail single-occurrence edits that match more than once with
+    /// `EditError::AmbiguousMatch` instead of editing the first match
+    pub require_unique_match: bool,
+    
+    /// Report `already_applied` instead of a plain no-match when
+    /// `old_string` is absent but `new_string` is present; off by default
+    pub detect_already_applied: bool,
+    
+    /// Limits checked before any write
+    pub guardrails: Guardrails,
 }
 
 
@@ -404,12 +594,16 @@ impl Default for EditConfig {
     fn default() -> Self {
         Self {
             max_file_size: 100 * 1024 * 1024, // 100MB
+            max_archive_size: 1024 * 1024 * 1024, // 1GB
             operation_timeout: Duration::from_secs(30),
             validate_utf8: true,
             normalize_unicode: false,
             max_memory_usage: 256 * 1024 * 1024, // 256MB
             track_performance: true,
             base_dir: None,
+            require_unique_match: true,
+            detect_already_applied: false,
+            guardrails: Guardrails::default(),
         }
     }
 }
@@ -426,6 +620,12 @@ impl EditConfig {
         self
     }
     
+    /// Set the most bytes read out of one archive
+    pub fn with_max_archive_size(mut self, size: u64) -> Self {
+        self.max_archive_size = size;
+        self
+    }
+    
     /// Set the operation timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.operation_timeout = timeout;
@@ -455,6 +655,24 @@ impl EditConfig {
         self.track_performance = track;
         self
     }
+    
+    /// Require single-occurrence edits to match exactly once
+    pub fn with_unique_match(mut self, require: bool) -> Self {
+        self.require_unique_match = require;
+        self
+    }
+    
+    /// Enable or disable already-applied detection
+    pub fn with_already_applied_detection(mut self, detect: bool) -> Self {
+        self.detect_already_applied = detect;
+        self
+    }
+    
+    /// Set the write guardrails
+    pub fn with_guardrails(mut self, guardrails: Guardrails) -> Self {
+        self.guardrails = guardrails;
+        self
+    }
 }
 
 #[cfg(test)]
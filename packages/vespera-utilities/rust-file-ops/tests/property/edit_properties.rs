@@ -0,0 +1,48 @@
+//! Properties of `StringMatcher` and `SingleEditor`
+
+use proptest::prelude::*;
+use vespera_file_ops::{replace_all, StringMatcher};
+
+use crate::strategies::{lossy_text, pattern, replacement, unicode_text};
+
+proptest! {
+    #[test]
+    fn replace_all_is_idempotent(content in unicode_text(), old in pattern(), new in replacement()) {
+        let once = replace_all(&content, &old, &new).unwrap();
+        // Replacements can splice a fresh occurrence together ("abb" with
+        // "ab" -> "a"); only output free of the pattern must be stable
+        prop_assume!(!once.content.contains(&old));
+
+        let twice = replace_all(&once.content, &old, &new).unwrap();
+        prop_assert!(!twice.changed);
+        prop_assert_eq!(twice.replacements_made, 0);
+        prop_assert_eq!(twice.content, once.content);
+    }
+
+    #[test]
+    fn match_positions_agree_on_multibyte_text(content in unicode_text(), old in pattern()) {
+        let matches = StringMatcher::find_all_matches().find_all(&content, &old).unwrap();
+        let expected: Vec<usize> = content.match_indices(old.as_str()).map(|(i, _)| i).collect();
+        prop_assert_eq!(matches.iter().map(|m| m.start).collect::<Vec<_>>(), expected);
+
+        for m in &matches {
+            prop_assert!(content.is_char_boundary(m.start) && content.is_char_boundary(m.end));
+            prop_assert_eq!(&content[m.start..m.end], old.as_str());
+            prop_assert_eq!(m.matched_text.as_str(), old.as_str());
+            prop_assert_eq!(m.char_start, content[..m.start].chars().count());
+            prop_assert_eq!(m.char_end, m.char_start + old.chars().count());
+        }
+    }
+
+    #[test]
+    fn matcher_does_not_panic_on_arbitrary_bytes(content in lossy_text(), old in lossy_text()) {
+        let matcher = StringMatcher::find_all_matches();
+        if let Ok(matches) = matcher.find_all(&content, &old) {
+            prop_assert_eq!(matcher.count_matches(&content, &old).unwrap(), matches.len());
+            let first = matcher.find_first(&content, &old).unwrap();
+            prop_assert_eq!(first.map(|m| m.start), matches.first().map(|m| m.start));
+        } else {
+            prop_assert!(old.is_empty());
+        }
+    }
+}
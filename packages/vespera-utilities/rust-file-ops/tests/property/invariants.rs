@@ -0,0 +1,35 @@
+//! `verify_edit_invariants` over generated operation sequences
+
+use proptest::prelude::*;
+use vespera_file_ops::{verify_edit_invariants, EditOperation};
+
+use crate::strategies::{lossy_text, operation, unicode_text};
+
+proptest! {
+    #[test]
+    fn single_edits_hold_invariants(content in unicode_text(), op in operation()) {
+        let result = verify_edit_invariants(&content, &[op]);
+        prop_assert!(matches!(result, Ok(Some(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn multi_edits_hold_invariants(
+        content in unicode_text(),
+        ops in prop::collection::vec(operation(), 0..6),
+    ) {
+        let result = verify_edit_invariants(&content, &ops);
+        prop_assert!(matches!(result, Ok(Some(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn arbitrary_bytes_hold_invariants(
+        content in lossy_text(),
+        old in lossy_text(),
+        new in lossy_text(),
+        replace_all in any::<bool>(),
+    ) {
+        let op = EditOperation::new(old, new, replace_all);
+        let result = verify_edit_invariants(&content, &[op]);
+        prop_assert!(result.is_ok(), "{:?}", result);
+    }
+}
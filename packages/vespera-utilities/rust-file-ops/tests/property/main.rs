@@ -0,0 +1,8 @@
+//! Property tests for the edit engine
+//!
+//! Run with `cargo test --test property`. Raise `PROPTEST_CASES` for a
+//! longer search.
+
+mod strategies;
+mod edit_properties;
+mod invariants;
@@ -0,0 +1,55 @@
+//! Proptest strategies for edit inputs
+//!
+//! Text is drawn from a small alphabet mixing ASCII with multi-byte
+//! characters, combining marks and ZWJ sequences, so generated patterns
+//! actually occur in generated content and matches land next to
+//! multi-byte boundaries.
+
+use proptest::prelude::*;
+use vespera_file_ops::{EditOperation, EditTarget};
+
+const ALPHABET: &[&str] = &[
+    "a", "b", " ", "\n", "é", "e\u{301}", "日", "本", "🌍", "👨\u{200d}👩", "\u{200d}", "Ω",
+];
+
+/// Content of up to 64 alphabet pieces
+pub fn unicode_text() -> impl Strategy<Value = String> {
+    pieces(0..64)
+}
+
+/// A non-empty pattern of one to three alphabet pieces
+pub fn pattern() -> impl Strategy<Value = String> {
+    pieces(1..4)
+}
+
+/// A replacement of up to three alphabet pieces
+pub fn replacement() -> impl Strategy<Value = String> {
+    pieces(0..4)
+}
+
+/// A valid edit target, mostly the default
+pub fn target() -> impl Strategy<Value = EditTarget> {
+    prop_oneof![
+        3 => Just(EditTarget::Everywhere),
+        1 => (1usize..5).prop_map(EditTarget::Nth),
+        1 => (1usize..4, 0usize..3).prop_map(|(start, len)| EditTarget::Lines { start, end: start + len }),
+        1 => pattern().prop_map(EditTarget::After),
+    ]
+}
+
+/// A valid edit operation
+pub fn operation() -> impl Strategy<Value = EditOperation> {
+    (pattern(), replacement(), any::<bool>(), target()).prop_map(|(old, new, replace_all, target)| {
+        EditOperation { target, ..EditOperation::new(old, new, replace_all) }
+    })
+}
+
+/// Arbitrary bytes, decoded lossily
+pub fn lossy_text() -> impl Strategy<Value = String> {
+    prop::collection::vec(any::<u8>(), 0..128)
+        .prop_map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn pieces(count: std::ops::Range<usize>) -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(ALPHABET), count).prop_map(|pieces| pieces.concat())
+}
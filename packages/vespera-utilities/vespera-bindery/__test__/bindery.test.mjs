@@ -0,0 +1,23 @@
+// Smoke tests for the Node.js bindings; run with `npm test`, which builds them first.
+import { test } from 'node:test'
+import assert from 'node:assert/strict'
+import { mkdtempSync } from 'node:fs'
+import { createRequire } from 'node:module'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+
+const require = createRequire(import.meta.url)
+const { Bindery } = require('../dist/index.js')
+
+test('a new Bindery has no Codices', async () => {
+  const bindery = new Bindery()
+  assert.deepEqual(await bindery.listCodices(), [])
+  assert.equal(await bindery.getCodex('00000000-0000-0000-0000-000000000000'), null)
+})
+
+test('a Bindery opens on a storage path', async () => {
+  const bindery = new Bindery(mkdtempSync(join(tmpdir(), 'bindery-')))
+  const page = await bindery.listCodicesPage(null, 10)
+  assert.deepEqual(page.codices, [])
+  assert.equal(page.nextCursor, undefined)
+})
@@ -0,0 +1,128 @@
+//! Parallel bulk Codex operation benchmarks
+//!
+//! Compares garbage collection, merging and snapshotting of 10k Codices done
+//! serially against the rayon-parallel versions in `codex::bulk` that
+//! `CodexManager` uses.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use uuid::Uuid;
+
+use vespera_bindery::{
+    codex::bulk,
+    crdt::{TemplateValue, VesperaCRDT},
+    CodexId, GarbageCollectionConfig,
+};
+
+const CODEX_COUNT: usize = 10_000;
+const EDITS_PER_CODEX: usize = 20;
+
+fn set_field(crdt: &mut VesperaCRDT, user: &str, field: usize, value: usize) {
+    crdt.set_metadata(format!("field_{}", field), TemplateValue::Text {
+        value: value.to_string(),
+        timestamp: Utc::now(),
+        user_id: user.to_string(),
+    }).unwrap();
+}
+
+/// Codices with a few edits each
+fn make_codices() -> Vec<(CodexId, Arc<VesperaCRDT>)> {
+    (0..CODEX_COUNT).map(|_| {
+        let id = Uuid::new_v4();
+        let mut crdt = VesperaCRDT::new(id, "alice".to_string());
+        crdt.set_title("Benchmark Codex").unwrap();
+        for edit in 0..EDITS_PER_CODEX {
+            set_field(&mut crdt, "alice", edit % 5, edit);
+        }
+        (id, Arc::new(crdt))
+    }).collect()
+}
+
+/// A peer replica of each Codex with edits the local copy hasn't seen
+fn make_remotes(codices: &[(CodexId, Arc<VesperaCRDT>)]) -> Vec<(Arc<VesperaCRDT>, VesperaCRDT)> {
+    codices.iter().map(|(_, crdt)| {
+        let mut remote = (**crdt).clone();
+        for edit in 0..EDITS_PER_CODEX {
+            set_field(&mut remote, "bob", edit % 5 + 5, edit);
+        }
+        (Arc::clone(crdt), remote)
+    }).collect()
+}
+
+fn bench_gc(c: &mut Criterion) {
+    let codices = make_codices();
+    let config = GarbageCollectionConfig {
+        memory_threshold_bytes: 0,
+        max_operations_per_codex: 5,
+        ..GarbageCollectionConfig::default()
+    };
+    let cutoff = Utc::now();
+
+    let mut group = c.benchmark_group("codex_gc");
+    group.throughput(Throughput::Elements(CODEX_COUNT as u64));
+    group.bench_function(BenchmarkId::new("serial", CODEX_COUNT), |b| {
+        b.iter(|| {
+            let collected: Vec<_> = codices.iter().map(|(_, crdt)| {
+                let mut copy = (**crdt).clone();
+                copy.gc_all_with_limits(cutoff, config.max_operations_per_codex, config.max_tree_tombstones_per_codex);
+                copy
+            }).collect();
+            black_box(collected)
+        })
+    });
+    group.bench_function(BenchmarkId::new("parallel", CODEX_COUNT), |b| {
+        b.iter(|| black_box(bulk::collect_garbage(&codices, &config, cutoff)))
+    });
+    group.finish();
+}
+
+fn bench_merge(c: &mut Criterion) {
+    let codices = make_codices();
+    let pairs = make_remotes(&codices);
+
+    let mut group = c.benchmark_group("codex_merge");
+    group.throughput(Throughput::Elements(CODEX_COUNT as u64));
+    group.bench_function(BenchmarkId::new("serial", CODEX_COUNT), |b| {
+        b.iter(|| {
+            let merged: Vec<_> = pairs.iter().map(|(local, remote)| {
+                let mut copy = (**local).clone();
+                copy.merge(remote).unwrap();
+                copy
+            }).collect();
+            black_box(merged)
+        })
+    });
+    group.bench_function(BenchmarkId::new("parallel", CODEX_COUNT), |b| {
+        b.iter(|| black_box(bulk::merge_remote(&pairs)))
+    });
+    group.finish();
+}
+
+fn bench_snapshot(c: &mut Criterion) {
+    let codices = make_codices();
+
+    let mut group = c.benchmark_group("codex_snapshot");
+    group.throughput(Throughput::Elements(CODEX_COUNT as u64));
+    group.bench_function(BenchmarkId::new("serial", CODEX_COUNT), |b| {
+        b.iter(|| {
+            let snapshots: Vec<_> = codices.iter().map(|(id, crdt)| (*id, crdt.snapshot())).collect();
+            black_box(snapshots)
+        })
+    });
+    group.bench_function(BenchmarkId::new("parallel", CODEX_COUNT), |b| {
+        b.iter(|| black_box(bulk::snapshot_all(&codices)))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default()
+        .measurement_time(Duration::from_secs(20))
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(2));
+    targets = bench_gc, bench_merge, bench_snapshot
+);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! CRDT merge benchmarks
+//!
+//! Merges a peer's log of 100k operations into a replica that has none of
+//! them, comparing `merge` (which applies the peer's operations by reference)
+//! with applying a clone of each operation.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use std::time::Duration;
+use chrono::Utc;
+use uuid::Uuid;
+
+use vespera_bindery::crdt::{MemoryConfig, TemplateValue, VesperaCRDT};
+
+const OPERATION_COUNT: usize = 100_000;
+const KEY_COUNT: usize = 1_000;
+
+/// Keeps every operation, so logs reach the benchmarked size
+fn unbounded_log() -> MemoryConfig {
+    MemoryConfig {
+        auto_gc_threshold: usize::MAX,
+        ..MemoryConfig::default()
+    }
+}
+
+/// An empty replica and a peer that has written `OPERATION_COUNT` metadata operations
+fn make_replicas() -> (VesperaCRDT, VesperaCRDT) {
+    let codex_id = Uuid::new_v4();
+    let local = VesperaCRDT::new_with_memory_config(codex_id, "alice".to_string(), unbounded_log());
+    let mut peer = VesperaCRDT::new_with_memory_config(codex_id, "bob".to_string(), unbounded_log());
+    for i in 0..OPERATION_COUNT {
+        peer.set_metadata(format!("field_{}", i % KEY_COUNT), TemplateValue::Text {
+            value: format!("value {}", i),
+            timestamp: Utc::now(),
+            user_id: "bob".to_string(),
+        }).unwrap();
+    }
+    assert_eq!(peer.operation_log.len(), OPERATION_COUNT);
+    (local, peer)
+}
+
+fn bench_merge(c: &mut Criterion) {
+    let (local, peer) = make_replicas();
+
+    let mut group = c.benchmark_group("crdt_merge");
+    group.throughput(Throughput::Elements(OPERATION_COUNT as u64));
+    group.bench_function(BenchmarkId::new("merge_by_reference", OPERATION_COUNT), |b| {
+        b.iter_batched(
+            || local.clone(),
+            |mut replica| {
+                replica.merge(&peer).unwrap();
+                black_box(replica)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function(BenchmarkId::new("apply_cloned", OPERATION_COUNT), |b| {
+        b.iter_batched(
+            || local.clone(),
+            |mut replica| {
+                for operation in &peer.operation_log {
+                    replica.apply_operation(operation.clone()).unwrap();
+                }
+                black_box(replica)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default()
+        .measurement_time(Duration::from_secs(30))
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(2));
+    targets = bench_merge
+);
+criterion_main!(benches);
@@ -188,6 +188,7 @@ async fn main() -> Result<()> {
         recovery_threshold: 1,
         auto_reset_circuit_breakers: false,
         verbose_logging: true,
+        remediation: Vec::new(),
     };
 
     // Get circuit breaker health status
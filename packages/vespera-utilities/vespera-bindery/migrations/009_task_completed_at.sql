@@ -0,0 +1,20 @@
+-- Record when tasks are completed, for dashboard burndown and cycle time
+--
+-- completed_at is set when a task moves to a done status ('completed',
+-- 'done', 'finished') and cleared when it is reopened. Tasks already done
+-- are backfilled with their last update time, the best estimate available.
+--
+-- Version: 9
+-- Created: 2026-10-18 00:00:00 UTC
+
+-- +migrate up
+ALTER TABLE tasks ADD COLUMN completed_at TEXT;
+
+UPDATE tasks SET completed_at = updated_at
+WHERE status IN ('completed', 'done', 'finished') AND completed_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed_at);
+
+-- +migrate down
+DROP INDEX IF EXISTS idx_tasks_completed;
+ALTER TABLE tasks DROP COLUMN completed_at;
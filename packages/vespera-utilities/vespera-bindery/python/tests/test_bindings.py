@@ -0,0 +1,43 @@
+"""Smoke tests for the Python bindings; run after `maturin develop`."""
+
+import pytest
+
+from vespera_bindery import BinderyError, CodexManager, RAGService
+
+
+async def test_codex_manager_lists_nothing_when_empty() -> None:
+    manager = CodexManager()
+    assert await manager.list_codices() == []
+
+
+async def test_empty_manager_pages_and_iterates_nothing() -> None:
+    manager = CodexManager()
+    assert await manager.list_codices_page(limit=10) == {"items": [], "next_cursor": None}
+    assert [codex async for codex in manager.iter_codices(10)] == []
+
+
+async def test_unknown_codex_is_none() -> None:
+    manager = CodexManager()
+    assert await manager.get_codex("00000000-0000-0000-0000-000000000000") is None
+
+
+async def test_invalid_id_is_rejected() -> None:
+    manager = CodexManager()
+    with pytest.raises(ValueError):
+        await manager.get_codex("not-a-uuid")
+
+
+async def test_unknown_template_raises_bindery_error() -> None:
+    manager = CodexManager()
+    with pytest.raises(BinderyError):
+        await manager.create_codex("Draft", "no.such.template")
+
+
+async def test_rag_index_and_search(tmp_path) -> None:
+    rag = await RAGService.open(tmp_path)
+    document_id = await rag.index_document(
+        "Notes", "The lighthouse keeper logs every passing ship.", "markdown"
+    )
+    results = await rag.search("lighthouse keeper", limit=5)
+    assert any(result["document_id"] == document_id for result in results)
+    assert await rag.delete_document(document_id) is True
@@ -0,0 +1,28 @@
+"""Python bindings for Vespera Bindery.
+
+Codex storage, task management, roles and RAG search backed by the Rust
+implementation. I/O methods are coroutines and must be awaited from a
+running asyncio event loop.
+"""
+
+from vespera_bindery._internal import (
+    BinderyError,
+    CodexIterator,
+    CodexManager,
+    CodexSubscription,
+    RAGService,
+    RoleManager,
+    TaskService,
+    __version__,
+)
+
+__all__ = [
+    "BinderyError",
+    "CodexIterator",
+    "CodexManager",
+    "CodexSubscription",
+    "RAGService",
+    "RoleManager",
+    "TaskService",
+    "__version__",
+]
@@ -0,0 +1,84 @@
+from os import PathLike
+from typing import Any, Callable, Dict, List, Optional, Union
+
+_Path = Union[str, PathLike[str]]
+_Json = Dict[str, Any]
+
+__version__: str
+
+class BinderyError(Exception): ...
+
+class CodexManager:
+    def __init__(self, storage_path: Optional[_Path] = None) -> None: ...
+    async def create_codex(self, title: str, template_id: str) -> str: ...
+    async def get_codex(self, codex_id: str) -> Optional[_Json]: ...
+    async def list_codices(self) -> List[str]: ...
+    async def list_codices_page(self, cursor: Optional[str] = None, limit: Optional[int] = None) -> _Json: ...
+    def iter_codices(self, page_size: Optional[int] = None) -> CodexIterator: ...
+    async def delete_codex(self, codex_id: str) -> bool: ...
+    async def restore_codex(self, codex_id: str) -> bool: ...
+    def subscribe(
+        self,
+        callback: Callable[[_Json], None],
+        codex_ids: Optional[List[str]] = None,
+        template_ids: Optional[List[str]] = None,
+        kinds: Optional[List[str]] = None,
+        tasks_only: bool = False,
+    ) -> CodexSubscription: ...
+    def task_service(self) -> TaskService: ...
+    def role_manager(self) -> RoleManager: ...
+
+class CodexIterator:
+    def __aiter__(self) -> CodexIterator: ...
+    async def __anext__(self) -> _Json: ...
+
+class CodexSubscription:
+    def cancel(self) -> None: ...
+
+class TaskService:
+    def __init__(self, manager: CodexManager) -> None: ...
+    async def create_task(self, input: _Json) -> str: ...
+    async def get_task(self, task_id: str) -> Optional[_Json]: ...
+    async def update_task(self, input: _Json) -> None: ...
+    async def delete_task(self, task_id: str, delete_subtasks: bool = False) -> None: ...
+    async def list_tasks(
+        self,
+        project_id: Optional[str] = None,
+        status: Optional[str] = None,
+        priority: Optional[str] = None,
+        assignee: Optional[str] = None,
+        parent_id: Optional[str] = None,
+        limit: Optional[int] = None,
+    ) -> List[_Json]: ...
+    async def get_task_tree(self, task_id: str, max_depth: Optional[int] = None) -> Optional[_Json]: ...
+    async def get_task_dashboard(self, project_id: Optional[str] = None) -> _Json: ...
+    async def complete_task(self, task_id: str, output: Optional[str] = None) -> None: ...
+    async def assign_role(self, task_id: str, role_name: str) -> None: ...
+
+class RoleManager:
+    def __init__(self, manager: CodexManager) -> None: ...
+    async def list_roles(self) -> List[_Json]: ...
+    async def get_role(self, name: str) -> Optional[_Json]: ...
+    async def role_exists(self, name: str) -> bool: ...
+    async def add_role(self, role: _Json) -> None: ...
+    async def remove_role(self, name: str) -> bool: ...
+    async def load_roles_from_file(self, path: _Path) -> None: ...
+
+class RAGService:
+    @staticmethod
+    async def open(project_path: _Path) -> RAGService: ...
+    async def index_document(
+        self,
+        title: str,
+        content: str,
+        document_type: str = "text",
+        source_path: Optional[_Path] = None,
+        tags: Optional[List[str]] = None,
+    ) -> str: ...
+    async def index_file(self, path: _Path) -> str: ...
+    async def search(
+        self, query: str, limit: int = 10, document_types: Optional[List[str]] = None
+    ) -> List[_Json]: ...
+    async def get_document(self, document_id: str) -> Optional[_Json]: ...
+    async def delete_document(self, document_id: str) -> bool: ...
+    async def get_stats(self) -> _Json: ...
@@ -23,15 +23,26 @@ use tokio::net::TcpListener;
 use tokio::sync::RwLock;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
-use tracing::{info, warn};
+use tracing::{info, warn, Instrument};
 use uuid::Uuid;
-use vespera_bindery::database::{Database, TaskInput as DbTaskInput};
+use vespera_bindery::database::{Database, TaskInput as DbTaskInput, TimeSeriesQuery};
 use vespera_bindery::migration::{MigrationCommand, MigrationCommandExecutor};
+use vespera_bindery::observability::{AuditCommand, AuditCommandExecutor, AuditLogger, production_audit_config};
 use vespera_bindery::observability::{
-    config::{ObservabilityConfig, LoggingConfig, FileLoggingConfig, LogRotation},
-    init_observability,
+    config::{ObservabilityConfig, LoggingConfig, FileLoggingConfig, LogRotation, OpenTelemetryConfig, OtlpConfig, OtlpProtocol},
+    init_observability, shutdown_observability, reload_logging, current_logging_config,
 };
-use vespera_bindery::providers::ProviderManager;
+use vespera_bindery::mcp::{self, McpServer, Tool, ToolHandler};
+use vespera_bindery::providers::{types::ChatRequest, PromptTemplate, ProviderManager};
+use vespera_bindery::rag::{
+    DocumentType, HealthCheckConfig, HealthMonitor, RAGConfig, RAGService, RemediationAction, RemediationRule,
+    SystemHealthStatus,
+};
+use vespera_bindery::usage::{UsageConfig, UsageQuery, UsageTracker};
+use vespera_bindery::BinderyConfig;
+use vespera_bindery::PageRequest;
+use vespera_bindery::shutdown::{ShutdownCoordinator, ShutdownPhase};
+use vespera_bindery::workspace::Workspace;
 
 // Input types for JSON-RPC
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -148,17 +159,20 @@ struct AppState {
     roles: Arc<RwLock<Vec<Role>>>,
     codices: Arc<RwLock<HashMap<String, Value>>>,
     provider_manager: Arc<ProviderManager>,
+    health_monitor: Arc<tokio::sync::Mutex<HealthMonitor>>,
+    workspace_root: PathBuf,
+    /// RAG index of the workspace, opened on first use
+    rag: tokio::sync::OnceCell<Arc<RAGService>>,
 }
 
 impl AppState {
     async fn new(workspace_root: PathBuf) -> Result<Self> {
-        // Create .vespera folder for data storage
-        let vespera_dir = workspace_root.join(".vespera");
-        eprintln!("Debug: Creating vespera directory: {:?}", vespera_dir);
-        tokio::fs::create_dir_all(&vespera_dir).await?;
+        // Create the .vespera folder for data storage if this is a new workspace
+        let workspace = Workspace::init(&workspace_root)?;
+        eprintln!("Debug: Workspace: {:?}", workspace.vespera_dir());
 
         // Initialize database in .vespera folder with optimized pool configuration
-        let database_path = vespera_dir.join("tasks.db");
+        let database_path = workspace.database_path();
         eprintln!("Debug: Database path: {:?}", database_path);
 
         // Create optimized database pool configuration for high-throughput
@@ -176,6 +190,14 @@ impl AppState {
         // Initialize schema manually since sqlx migrations might not work
         database.init_schema().await?;
 
+        // Settings from VESPERA_* environment variables
+        let bindery_config = BinderyConfig::from_env()
+            .map_err(|e| warn!("Ignoring Bindery settings: {}", e))
+            .ok();
+        if let Some(backup) = bindery_config.as_ref().and_then(|config| config.backup.clone()) {
+            database.start_backup_schedule(backup).await?;
+        }
+
         // Log pool health information for monitoring
         let health_info = database.get_pool_health_info().await;
         eprintln!("Debug: Database pool initialized - Status: {:?}, Connections: {}/{}",
@@ -198,7 +220,12 @@ impl AppState {
         // Create provider manager and load providers
         let database_arc = Arc::new(database);
         eprintln!("Debug: Created database Arc, creating ProviderManager...");
-        let provider_manager = Arc::new(ProviderManager::new(Arc::clone(&database_arc)));
+        let mut provider_manager = ProviderManager::new(Arc::clone(&database_arc));
+        match load_usage_tracker(&workspace_root, Arc::clone(&database_arc), bindery_config.as_ref()).await {
+            Ok(tracker) => provider_manager = provider_manager.with_usage_tracker(Arc::new(tracker)),
+            Err(e) => warn!("Usage tracking disabled: {}", e),
+        }
+        let provider_manager = Arc::new(provider_manager);
         eprintln!("Debug: ProviderManager created successfully");
 
         // Load providers synchronously during startup to ensure they're available
@@ -212,13 +239,94 @@ impl AppState {
             }
         }
 
+        // Health check providers, restart crashed ones with backoff and route around them meanwhile
+        provider_manager.start_supervision().await;
+
+        // Restart providers that stop answering health checks
+        let health_config = HealthCheckConfig {
+            remediation: vec![RemediationRule {
+                service: "provider:*".to_string(),
+                on_status: SystemHealthStatus::Unhealthy,
+                action: RemediationAction::RestartProvider { provider_id: None },
+                cooldown: std::time::Duration::from_secs(300),
+            }],
+            ..HealthCheckConfig::default()
+        };
+        let health_monitor = Arc::new(tokio::sync::Mutex::new(
+            HealthMonitor::new(health_config).with_provider_manager(Arc::clone(&provider_manager)),
+        ));
+        HealthMonitor::spawn_monitoring(Arc::clone(&health_monitor));
+
         Ok(Self {
             database: database_arc,
             roles: Arc::new(RwLock::new(roles)),
             codices: Arc::new(RwLock::new(HashMap::new())),
             provider_manager,
+            health_monitor,
+            workspace_root,
+            rag: tokio::sync::OnceCell::new(),
         })
     }
+
+    /// Stop providers, flush the RAG index and close the database, within the
+    /// default shutdown deadline
+    async fn shutdown(&self) {
+        let providers = Arc::clone(&self.provider_manager);
+        let database = Arc::clone(&self.database);
+        let mut coordinator = ShutdownCoordinator::default()
+            .with_step(ShutdownPhase::StopIntake, "provider_supervision", {
+                let providers = Arc::clone(&providers);
+                move || async move {
+                    providers.stop_supervision().await;
+                    Ok(())
+                }
+            })
+            .with_step(ShutdownPhase::Close, "providers", move || async move {
+                providers.shutdown().await;
+                Ok(())
+            })
+            .with_step(ShutdownPhase::Close, "database", move || async move {
+                database.close().await;
+                Ok(())
+            });
+        if let Some(rag) = self.rag.get().cloned() {
+            coordinator.add_step(ShutdownPhase::Flush, "rag_index", move || async move {
+                rag.stop_watching().await;
+                rag.flush().await
+            });
+        }
+
+        let report = coordinator.shutdown().await;
+        if !report.is_clean() {
+            warn!(failed = ?report.failed, timed_out = ?report.timed_out, "Shutdown incomplete");
+        }
+    }
+}
+
+/// Usage tracker for the workspace, configured from `.vespera/usage.json5` if present
+///
+/// Requests are attributed to a project named after the workspace folder
+/// unless the file sets `project_id`. Token budgets from the quota settings
+/// in `bindery_config` are added to those of the file.
+async fn load_usage_tracker(
+    workspace_root: &std::path::Path,
+    database: Arc<Database>,
+    bindery_config: Option<&BinderyConfig>,
+) -> Result<UsageTracker> {
+    let config_path = workspace_root.join(".vespera").join("usage.json5");
+    let mut config: UsageConfig = if config_path.exists() {
+        let content = tokio::fs::read_to_string(&config_path).await?;
+        json5::from_str(&content).map_err(|e| anyhow::anyhow!("Invalid {}: {}", config_path.display(), e))?
+    } else {
+        UsageConfig::default()
+    };
+    if config.project_id.is_none() {
+        config.project_id = workspace_root.file_name().map(|name| name.to_string_lossy().into_owned());
+    }
+    if let Some(bindery_config) = bindery_config {
+        config.budgets.extend(bindery_config.quotas.token_budget());
+    }
+    UsageTracker::new(database, config).await
 }
 
 /// CLI arguments for the Bindery server
@@ -269,6 +377,32 @@ enum Commands {
     #[command(subcommand)]
     Migrate(MigrationCommand),
 
+    /// Audit log verification, export and retention
+    #[command(subcommand)]
+    Audit(AuditCommand),
+
+    /// Create the .vespera folder for the workspace, or fill in what is missing
+    Init,
+
+    /// Serve Bindery tools over the Model Context Protocol
+    Mcp {
+        /// How MCP clients connect
+        #[arg(long, value_enum, default_value_t = McpTransport::Stdio)]
+        transport: McpTransport,
+
+        /// HTTP port for the SSE transport
+        #[arg(long, default_value = "8091")]
+        port: u16,
+    },
+
+    /// Serve Codex, task and RAG APIs over gRPC to remote clients
+    #[cfg(feature = "grpc")]
+    Grpc {
+        /// Address to listen on; the server has no authentication of its own
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        listen: std::net::SocketAddr,
+    },
+
     /// Start the server (default if no command specified)
     Serve {
         /// Enable JSON-RPC stdio mode
@@ -289,12 +423,21 @@ enum Commands {
     },
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum McpTransport {
+    /// One JSON message per line on stdin/stdout
+    Stdio,
+    /// HTTP with Server-Sent Events (`GET /sse`, `POST /message`)
+    Sse,
+}
+
 #[tokio::main(flavor = "multi_thread", worker_threads = 4)]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Initialize comprehensive observability
     let json_rpc_mode = cli.json_rpc || matches!(cli.command, Some(Commands::Serve { json_rpc: true, .. }));
+    let mcp_stdio_mode = matches!(cli.command, Some(Commands::Mcp { transport: McpTransport::Stdio, .. }));
 
     // Extract logging configuration from CLI args or serve command
     let (log_level, log_to_file, log_dir, json_logs) = match &cli.command {
@@ -313,10 +456,12 @@ async fn main() -> Result<()> {
         with_source_location: true,
         with_thread_names: true,
         with_span_events: true,
+        module_levels: HashMap::new(),
     };
 
-    // Configure file logging if requested
-    if log_to_file {
+    // Configure file logging if requested; MCP over stdio always logs to a
+    // file, since console logs would go to stdout, which belongs to the client
+    if log_to_file || mcp_stdio_mode {
         let log_directory = log_dir.unwrap_or_else(|| {
             let workspace = cli.workspace.clone().unwrap_or_else(|| {
                 std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
@@ -329,6 +474,8 @@ async fn main() -> Result<()> {
             filename: "bindery-server.log".to_string(),
             rotation: LogRotation::Daily,
             max_files: Some(10),
+            max_size_bytes: Some(50 * 1024 * 1024),
+            json: None,
         });
     }
 
@@ -337,9 +484,13 @@ async fn main() -> Result<()> {
         logging_config.console = true; // Will use stderr writer
     }
 
+    if mcp_stdio_mode {
+        logging_config.console = false;
+    }
+
     let observability_config = ObservabilityConfig {
         logging: logging_config,
-        opentelemetry: None, // TODO: Add OpenTelemetry configuration from CLI args
+        opentelemetry: opentelemetry_from_env(),
         metrics: None,       // TODO: Add metrics configuration from CLI args
         alerting: None,      // TODO: Add alerting configuration from CLI args
     };
@@ -348,7 +499,7 @@ async fn main() -> Result<()> {
     init_observability(&observability_config)
         .context("Failed to initialize observability system")?;
 
-    if json_rpc_mode {
+    if json_rpc_mode || mcp_stdio_mode {
         eprintln!("Starting Vespera Bindery Server v{}", vespera_bindery::VERSION);
     } else {
         info!(
@@ -360,10 +511,23 @@ async fn main() -> Result<()> {
         );
     }
 
-    match cli.command {
+    let result = match cli.command {
         Some(Commands::Migrate(migration_cmd)) => {
             run_migration_command(migration_cmd, cli.workspace, cli.database).await
         }
+        Some(Commands::Audit(audit_cmd)) => {
+            run_audit_command(audit_cmd, cli.workspace).await
+        }
+        Some(Commands::Init) => {
+            run_init_command(cli.workspace)
+        }
+        Some(Commands::Mcp { transport, port }) => {
+            run_mcp_server(transport, port, cli.workspace).await
+        }
+        #[cfg(feature = "grpc")]
+        Some(Commands::Grpc { listen }) => {
+            run_grpc_server(listen, cli.workspace).await
+        }
         Some(Commands::Serve { json_rpc, port, .. }) => {
             if json_rpc {
                 run_json_rpc_stdio(cli.workspace).await
@@ -379,7 +543,75 @@ async fn main() -> Result<()> {
                 run_http_server_with_port(cli.port, cli.workspace).await
             }
         }
+    };
+
+    // Flush any spans still queued for export
+    shutdown_observability();
+    result
+}
+
+/// OTLP trace export, enabled by the standard `OTEL_EXPORTER_OTLP_ENDPOINT` variable
+fn opentelemetry_from_env() -> Option<OpenTelemetryConfig> {
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+    let protocol = match std::env::var("OTEL_EXPORTER_OTLP_PROTOCOL").as_deref() {
+        Ok("http/protobuf") | Ok("http") => OtlpProtocol::HttpProtobuf,
+        _ => OtlpProtocol::Grpc,
+    };
+
+    Some(OpenTelemetryConfig {
+        enabled: true,
+        service_name: std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "vespera-bindery".to_string()),
+        service_version: vespera_bindery::VERSION.to_string(),
+        jaeger: None,
+        otlp: Some(OtlpConfig {
+            endpoint,
+            api_key: None,
+            protocol,
+            headers: Default::default(),
+            sample_rate: 1.0,
+        }),
+    })
+}
+
+/// Initialize the workspace and show where its data lives
+fn run_init_command(workspace: Option<PathBuf>) -> Result<()> {
+    let workspace_root = match workspace {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let workspace = Workspace::init(&workspace_root).context("Failed to initialize workspace")?;
+    let config = workspace.config().context("Invalid workspace configuration")?;
+
+    println!("Initialized workspace '{}' in {}", workspace.manifest().name, workspace.vespera_dir().display());
+    println!("  database:  {}", workspace.database_path().display());
+    println!("  codices:   {}", workspace.codices_dir().display());
+    println!("  templates: {}", workspace.templates_dir().display());
+    println!("  roles:     {}", workspace.roles_path().display());
+    println!("  RAG index: {}", workspace.rag_dir().display());
+    println!();
+    println!("Effective configuration:");
+    print!("{}", config.dump_effective_config()?);
+    Ok(())
+}
+
+/// Run an audit command against the workspace audit log
+async fn run_audit_command(audit_cmd: AuditCommand, workspace: Option<PathBuf>) -> Result<()> {
+    let workspace_root = workspace.unwrap_or_else(|| {
+        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+    });
+    let audit_db_path = workspace_root.join(".vespera").join("audit.db");
+
+    if let Some(parent) = audit_db_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
     }
+
+    let logger = AuditLogger::new(production_audit_config(audit_db_path)).await
+        .context("Failed to open audit log")?;
+
+    AuditCommandExecutor::new(logger).execute(audit_cmd).await
+        .context("Audit command failed")?;
+
+    Ok(())
 }
 
 /// Run a migration command
@@ -460,6 +692,7 @@ async fn run_json_rpc_stdio(workspace: Option<PathBuf>) -> Result<()> {
         stdout.flush().await?;
     }
 
+    state.shutdown().await;
     Ok(())
 }
 
@@ -481,6 +714,7 @@ async fn run_http_server_with_port(port: u16, workspace: Option<PathBuf>) -> Res
         // Pool metrics endpoint for monitoring
         .route("/api/pool/metrics", get(api_pool_metrics))
         .route("/api/pool/health", get(api_pool_health))
+        .route("/api/health/report", get(api_health_report))
         // REST API endpoints for MCP server
         .route("/api/tasks", post(api_create_task))
         .route("/api/tasks", get(api_list_tasks))
@@ -498,7 +732,7 @@ async fn run_http_server_with_port(port: u16, workspace: Option<PathBuf>) -> Res
             ServiceBuilder::new()
                 .layer(CorsLayer::permissive())
         )
-        .with_state(state);
+        .with_state(Arc::clone(&state));
 
     let bind_addr = format!("127.0.0.1:{}", port);
     let listener = TcpListener::bind(&bind_addr)
@@ -508,9 +742,14 @@ async fn run_http_server_with_port(port: u16, workspace: Option<PathBuf>) -> Res
     info!("Server listening on http://{}", bind_addr);
 
     axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Interrupted, shutting down");
+        })
         .await
         .context("Server error")?;
 
+    state.shutdown().await;
     Ok(())
 }
 
@@ -538,6 +777,7 @@ async fn handle_health_check(State(state): State<Arc<AppState>>) -> Json<Value>
             "pool_healthy": is_db_healthy,
             "active_connections": pool_metrics.active_connections,
             "max_connections": pool_health.max_connections,
+            "writer_connections": pool_health.writer_connections,
             "utilization_percent": pool_metrics.pool_utilization,
             "success_rate_percent": pool_health.success_rate,
             "avg_acquisition_time_ms": pool_metrics.average_acquisition_time_ms,
@@ -552,9 +792,14 @@ async fn handle_health_check(State(state): State<Arc<AppState>>) -> Json<Value>
 /// Handle HTTP JSON-RPC requests
 async fn handle_http_json_rpc(
     State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
     Json(request): Json<JsonRpcRequest>,
 ) -> Result<Json<JsonRpcResponse>, StatusCode> {
-    let response = handle_json_rpc_method(&state, &request).await;
+    // Continue the caller's trace when it sent W3C trace context headers
+    let span = tracing::info_span!("json_rpc", method = %request.method);
+    vespera_bindery::observability::propagation::set_parent_from_headers(&span, &headers);
+
+    let response = handle_json_rpc_method(&state, &request).instrument(span).await;
     Ok(Json(response))
 }
 
@@ -577,32 +822,7 @@ async fn handle_json_rpc_request(state: &AppState, request_str: &str) -> JsonRpc
 
 /// Handle JSON-RPC method calls
 async fn handle_json_rpc_method(state: &AppState, request: &JsonRpcRequest) -> JsonRpcResponse {
-    let method_result = match request.method.as_str() {
-        "version_info" => handle_version_info().await,
-        "get_task_dashboard" => handle_get_task_dashboard(state, &request.params).await,
-        "list_tasks" => handle_list_tasks(state, &request.params).await,
-        "get_task" => handle_get_task(state, &request.params).await,
-        "create_task" => handle_create_task(state, &request.params).await,
-        "update_task" => handle_update_task(state, &request.params).await,
-        "delete_task" => handle_delete_task(state, &request.params).await,
-        "complete_task" => handle_complete_task(state, &request.params).await,
-        "list_roles" => handle_list_roles(state).await,
-        "assign_role_to_task" => handle_assign_role_to_task(state, &request.params).await,
-        "list_codices" => handle_list_codices(state).await,
-        "list_children" => handle_list_children(state, &request.params).await,
-        "create_codex" => handle_create_codex(state, &request.params).await,
-        "get_codex" => handle_get_codex(state, &request.params).await,
-        "update_codex" => handle_update_codex(state, &request.params).await,
-        "delete_codex" => handle_delete_codex(state, &request.params).await,
-        // Provider endpoints
-        "provider.list" => handle_provider_list(state).await,
-        "provider.get" => handle_provider_get(state, &request.params).await,
-        "provider.test" => handle_provider_test(state, &request.params).await,
-        "provider.reload" => handle_provider_reload(state, &request.params).await,
-        // Chat endpoints
-        "chat.send_message" => handle_chat_send_message(state, &request.params).await,
-        _ => Err(format!("Method '{}' not found", request.method)),
-    };
+    let method_result = dispatch_method(state, &request.method, &request.params).await;
     
     match method_result {
         Ok(result) => JsonRpcResponse {
@@ -624,6 +844,55 @@ async fn handle_json_rpc_method(state: &AppState, request: &JsonRpcRequest) -> J
     }
 }
 
+/// Run the JSON-RPC method `method`
+async fn dispatch_method(state: &AppState, method: &str, params: &Option<Value>) -> Result<Value, String> {
+    match method {
+        "version_info" => handle_version_info().await,
+        "get_task_dashboard" => handle_get_task_dashboard(state, params).await,
+        "list_tasks" => handle_list_tasks(state, params).await,
+        "get_task" => handle_get_task(state, params).await,
+        "create_task" => handle_create_task(state, params).await,
+        "update_task" => handle_update_task(state, params).await,
+        "delete_task" => handle_delete_task(state, params).await,
+        "complete_task" => handle_complete_task(state, params).await,
+        "list_roles" => handle_list_roles(state).await,
+        "assign_role_to_task" => handle_assign_role_to_task(state, params).await,
+        "list_codices" => handle_list_codices(state, params).await,
+        "list_children" => handle_list_children(state, params).await,
+        "create_codex" => handle_create_codex(state, params).await,
+        "get_codex" => handle_get_codex(state, params).await,
+        "update_codex" => handle_update_codex(state, params).await,
+        "delete_codex" => handle_delete_codex(state, params).await,
+        // Provider endpoints
+        "provider.list" => handle_provider_list(state).await,
+        "provider.get" => handle_provider_get(state, params).await,
+        "provider.test" => handle_provider_test(state, params).await,
+        "provider.reload" => handle_provider_reload(state, params).await,
+        "provider.status" => handle_provider_status(state, params).await,
+        "provider.capabilities" => handle_provider_capabilities(state, params).await,
+        "provider.models" => handle_provider_models(state, params).await,
+        // Health endpoints
+        "health.report" => handle_health_report(state).await,
+        // Logging endpoints
+        "logging.reload" => handle_logging_reload(params).await,
+        // Chat endpoints
+        "chat.send_message" => handle_chat_send_message(state, params).await,
+        "chat.send_request" => handle_chat_send_request(state, params).await,
+        // Usage endpoints
+        "usage.report" => handle_usage_report(state, params).await,
+        "usage.budgets" => handle_usage_budgets(state, params).await,
+        // Prompt template endpoints
+        "prompts.list" => handle_prompts_list(state).await,
+        "prompts.get" => handle_prompts_get(state, params).await,
+        "prompts.save" => handle_prompts_save(state, params).await,
+        // RAG endpoints
+        "rag.search" => handle_rag_search(state, params).await,
+        "rag.index_document" => handle_rag_index_document(state, params).await,
+        "rag.stats" => handle_rag_stats(state).await,
+        _ => Err(format!("Method '{}' not found", method)),
+    }
+}
+
 // Method handlers
 
 async fn handle_version_info() -> Result<Value, String> {
@@ -631,13 +900,42 @@ async fn handle_version_info() -> Result<Value, String> {
     Ok(json!(info))
 }
 
-async fn handle_get_task_dashboard(state: &AppState, _params: &Option<Value>) -> Result<Value, String> {
-    let dashboard = state.database.get_task_dashboard(None).await
-        .map_err(|e| format!("Failed to get task dashboard: {}", e))?;
+async fn handle_get_task_dashboard(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let dashboard = match dashboard_series_query(params)? {
+        Some(query) => state.database.get_task_dashboard_with_series(None, &query).await,
+        None => state.database.get_task_dashboard(None).await,
+    }.map_err(|e| format!("Failed to get task dashboard: {}", e))?;
     
     Ok(serde_json::to_value(dashboard).map_err(|e| e.to_string())?)
 }
 
+/// Time series range from `from`/`to` dates and/or a number of `days`, if any is given
+///
+/// `to` defaults to today and `from` to `days` (30 unless given) before `to`.
+fn dashboard_series_query(params: &Option<Value>) -> Result<Option<TimeSeriesQuery>, String> {
+    let Some(params) = params.as_ref() else {
+        return Ok(None);
+    };
+    let date = |key: &str| {
+        params.get(key).and_then(|v| v.as_str())
+            .map(|text| chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map_err(|e| format!("Invalid {} date '{}': {}", key, text, e)))
+            .transpose()
+    };
+    let (from, to) = (date("from")?, date("to")?);
+    let days = params.get("days").and_then(|v| v.as_i64());
+    if from.is_none() && to.is_none() && days.is_none() {
+        return Ok(None);
+    }
+
+    let to = to.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let from = from.unwrap_or_else(|| to - chrono::Duration::days(days.unwrap_or(30).max(1) - 1));
+    let mut query = TimeSeriesQuery::new(from, to);
+    query.project_id = params.get("project_id").and_then(|v| v.as_str()).map(str::to_string);
+    query.label = params.get("label").and_then(|v| v.as_str()).map(str::to_string);
+    Ok(Some(query))
+}
+
 async fn handle_list_tasks(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
     let limit = params
         .as_ref()
@@ -650,12 +948,26 @@ async fn handle_list_tasks(state: &AppState, params: &Option<Value>) -> Result<V
         .and_then(|p| p.get("parent_id"))
         .and_then(|v| v.as_str());
     
+    if let Some(request) = page_request(params) {
+        let page = state.database.list_tasks_page(parent_id, &request).await
+            .map_err(|e| format!("Failed to list tasks: {}", e))?;
+        return serde_json::to_value(page).map_err(|e| e.to_string());
+    }
+
     let tasks = state.database.list_tasks(limit.map(|l| l as i32), parent_id).await
         .map_err(|e| format!("Failed to list tasks: {}", e))?;
     
     Ok(serde_json::to_value(tasks).map_err(|e| e.to_string())?)
 }
 
+/// A page request when `cursor` or `page_size` is given; lists are returned whole otherwise
+fn page_request(params: &Option<Value>) -> Option<PageRequest> {
+    let params = params.as_ref()?;
+    let cursor = params.get("cursor").and_then(|v| v.as_str()).map(str::to_string);
+    let limit = params.get("page_size").and_then(|v| v.as_u64()).map(|v| v as usize);
+    (cursor.is_some() || limit.is_some()).then_some(PageRequest { cursor, limit })
+}
+
 async fn handle_get_task(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
     let task_id = params
         .as_ref()
@@ -781,7 +1093,13 @@ async fn handle_assign_role_to_task(_state: &AppState, params: &Option<Value>) -
     Ok(json!(null))
 }
 
-async fn handle_list_codices(state: &AppState) -> Result<Value, String> {
+async fn handle_list_codices(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    if let Some(request) = page_request(params) {
+        let page = state.database.list_codices_page(&request).await
+            .map_err(|e| format!("Failed to list codices from database: {}", e))?;
+        return Ok(json!(page));
+    }
+
     // Phase 16b: Load from database
     let codices = state.database.list_codices()
         .await
@@ -984,6 +1302,39 @@ async fn handle_provider_get(state: &AppState, params: &Option<Value>) -> Result
     }))
 }
 
+async fn handle_provider_capabilities(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let provider_id = params
+        .as_ref()
+        .and_then(|p| p.get("provider_id"))
+        .and_then(|v| v.as_str())
+        .ok_or("Missing provider_id parameter")?;
+
+    let capabilities = state
+        .provider_manager
+        .capabilities(provider_id)
+        .await
+        .map_err(|e| format!("Failed to get provider capabilities: {}", e))?;
+
+    serde_json::to_value(capabilities).map_err(|e| e.to_string())
+}
+
+/// Models the provider can serve, for model pickers
+async fn handle_provider_models(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let provider_id = params
+        .as_ref()
+        .and_then(|p| p.get("provider_id"))
+        .and_then(|v| v.as_str())
+        .ok_or("Missing provider_id parameter")?;
+
+    let models = state
+        .provider_manager
+        .list_models(provider_id)
+        .await
+        .map_err(|e| format!("Failed to list models: {}", e))?;
+
+    Ok(json!({ "provider_id": provider_id, "models": models }))
+}
+
 async fn handle_provider_test(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
     let provider_id = params
         .as_ref()
@@ -1003,6 +1354,26 @@ async fn handle_provider_test(state: &AppState, params: &Option<Value>) -> Resul
     }))
 }
 
+/// Supervision status of one provider (`provider_id`) or all of them
+async fn handle_provider_status(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let provider_id = params
+        .as_ref()
+        .and_then(|p| p.get("provider_id"))
+        .and_then(|v| v.as_str());
+
+    match provider_id {
+        Some(provider_id) => {
+            let status = state
+                .provider_manager
+                .provider_status(provider_id)
+                .await
+                .map_err(|e| format!("Failed to get provider status: {}", e))?;
+            serde_json::to_value(status).map_err(|e| e.to_string())
+        }
+        None => Ok(json!({ "providers": state.provider_manager.provider_statuses().await })),
+    }
+}
+
 async fn handle_provider_reload(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
     let provider_id = params
         .as_ref()
@@ -1022,6 +1393,32 @@ async fn handle_provider_reload(state: &AppState, params: &Option<Value>) -> Res
     }))
 }
 
+async fn handle_health_report(state: &AppState) -> Result<Value, String> {
+    let report = state.health_monitor.lock().await.generate_health_report().await;
+    serde_json::to_value(report).map_err(|e| format!("Failed to serialize health report: {}", e))
+}
+
+/// Change log verbosity: `{"level": "debug", "module_levels": {"vespera_bindery::crdt": "trace"}}`
+///
+/// Omitted fields keep their current values.
+async fn handle_logging_reload(params: &Option<Value>) -> Result<Value, String> {
+    let mut config = current_logging_config().ok_or("Logging has not been initialized")?;
+
+    if let Some(level) = params.as_ref().and_then(|p| p.get("level")).and_then(|v| v.as_str()) {
+        config.level = level.to_string();
+    }
+    if let Some(module_levels) = params.as_ref().and_then(|p| p.get("module_levels")) {
+        config.module_levels = serde_json::from_value(module_levels.clone())
+            .map_err(|e| format!("Invalid module_levels: {}", e))?;
+    }
+
+    reload_logging(&config).map_err(|e| format!("Failed to reload logging: {}", e))?;
+    Ok(json!({
+        "level": config.level,
+        "module_levels": config.module_levels,
+    }))
+}
+
 async fn handle_chat_send_message(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
     let provider_id = params
         .as_ref()
@@ -1070,6 +1467,396 @@ async fn handle_chat_send_message(state: &AppState, params: &Option<Value>) -> R
     }))
 }
 
+/// Structured chat request: `messages` (with tool calls and tool results), optional
+/// `tools`, `system_prompt`, `max_tokens`, `temperature`, `stop_sequences`, `session_id`,
+/// and `prompt` (`{"name", "version", "variables"}`) to build it from a prompt template.
+/// Tool calls the model makes come back in `tool_calls` for the caller to execute.
+async fn handle_chat_send_request(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let params = params.as_ref().ok_or("Missing parameters")?;
+
+    let provider_id = params
+        .get("provider_id")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing provider_id parameter")?;
+
+    let session_id = params.get("session_id").and_then(|v| v.as_str());
+
+    let chat_request: ChatRequest = serde_json::from_value(params.clone()).map_err(|e| format!("Invalid chat request: {}", e))?;
+
+    let response = state
+        .provider_manager
+        .send_chat_request(provider_id, chat_request, session_id)
+        .await
+        .map_err(|e| format!("Failed to send chat request: {}", e))?;
+
+    serde_json::to_value(response).map_err(|e| e.to_string())
+}
+
+fn usage_tracker(state: &AppState) -> Result<&Arc<UsageTracker>, String> {
+    state.provider_manager.usage_tracker().ok_or_else(|| "Usage tracking is not enabled".to_string())
+}
+
+async fn handle_usage_report(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let query: UsageQuery = match params {
+        Some(params) => serde_json::from_value(params.clone()).map_err(|e| format!("Invalid usage query: {}", e))?,
+        None => UsageQuery::default(),
+    };
+
+    let groups = usage_tracker(state)?
+        .report(&query)
+        .await
+        .map_err(|e| format!("Failed to build usage report: {}", e))?;
+
+    Ok(json!({ "group_by": query.group_by, "groups": groups }))
+}
+
+async fn handle_usage_budgets(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let session_id = params
+        .as_ref()
+        .and_then(|p| p.get("session_id"))
+        .and_then(|v| v.as_str());
+
+    let budgets = usage_tracker(state)?
+        .budget_statuses(session_id)
+        .await
+        .map_err(|e| format!("Failed to get budget status: {}", e))?;
+
+    Ok(json!({ "budgets": budgets }))
+}
+
+async fn handle_prompts_list(state: &AppState) -> Result<Value, String> {
+    let prompts = state
+        .provider_manager
+        .prompts()
+        .list()
+        .await
+        .map_err(|e| format!("Failed to list prompts: {}", e))?;
+
+    Ok(json!({ "prompts": prompts }))
+}
+
+/// `{"name": "reviewer", "version": 2}`; the latest version when `version` is omitted
+async fn handle_prompts_get(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let name = params
+        .as_ref()
+        .and_then(|p| p.get("name"))
+        .and_then(|v| v.as_str())
+        .ok_or("Missing name parameter")?;
+
+    let version = params
+        .as_ref()
+        .and_then(|p| p.get("version"))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+
+    let prompt = state
+        .provider_manager
+        .prompts()
+        .get(name, version)
+        .await
+        .map_err(|e| format!("Failed to get prompt: {}", e))?;
+
+    serde_json::to_value(prompt).map_err(|e| e.to_string())
+}
+
+/// Save a prompt template as the next version of its name
+async fn handle_prompts_save(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let params = params.as_ref().ok_or("Missing parameters")?;
+    let template: PromptTemplate = serde_json::from_value(params.clone()).map_err(|e| format!("Invalid prompt template: {}", e))?;
+
+    let saved = state
+        .provider_manager
+        .prompts()
+        .save(template)
+        .await
+        .map_err(|e| format!("Failed to save prompt: {}", e))?;
+
+    Ok(json!({ "name": saved.name, "version": saved.version }))
+}
+
+// RAG handlers
+
+/// RAG index of the workspace, opened on first use
+async fn rag_service(state: &AppState) -> Result<&Arc<RAGService>, String> {
+    state
+        .rag
+        .get_or_try_init(|| async {
+            RAGService::new(&state.workspace_root, RAGConfig::default()).await.map(Arc::new)
+        })
+        .await
+        .map_err(|e| format!("Failed to open RAG index: {}", e))
+}
+
+/// Search the RAG index: `query`, optional `limit` (default 10) and `document_types`
+async fn handle_rag_search(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let query = params
+        .as_ref()
+        .and_then(|p| p.get("query"))
+        .and_then(|v| v.as_str())
+        .ok_or("Missing query parameter")?;
+
+    let limit = params
+        .as_ref()
+        .and_then(|p| p.get("limit"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(10) as usize;
+
+    let document_types = match params.as_ref().and_then(|p| p.get("document_types")) {
+        Some(types) => {
+            let names: Vec<String> = serde_json::from_value(types.clone())
+                .map_err(|e| format!("Invalid document_types: {}", e))?;
+            let types = names
+                .iter()
+                .map(|name| name.parse::<DocumentType>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Some(types)
+        }
+        None => None,
+    };
+
+    let results = rag_service(state)
+        .await?
+        .search(query, limit, document_types)
+        .await
+        .map_err(|e| format!("Failed to search: {}", e))?;
+
+    Ok(json!({ "query": query, "results": results }))
+}
+
+/// Index a document: `content`, optional `title`, `document_type`, `source_path` and `tags`
+async fn handle_rag_index_document(state: &AppState, params: &Option<Value>) -> Result<Value, String> {
+    let params = params.as_ref().ok_or("Missing parameters")?;
+
+    let content = params
+        .get("content")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing content parameter")?;
+
+    let title = params.get("title").and_then(|v| v.as_str()).unwrap_or("Untitled");
+
+    let document_type = match params.get("document_type").and_then(|v| v.as_str()) {
+        Some(name) => name.parse::<DocumentType>().map_err(|e| e.to_string())?,
+        None => DocumentType::Text,
+    };
+
+    let source_path = params.get("source_path").and_then(|v| v.as_str()).map(PathBuf::from);
+
+    let tags: Vec<String> = match params.get("tags") {
+        Some(tags) => serde_json::from_value(tags.clone()).map_err(|e| format!("Invalid tags: {}", e))?,
+        None => Vec::new(),
+    };
+
+    let document_id = rag_service(state)
+        .await?
+        .index_document(title.to_string(), content.to_string(), document_type, source_path, tags)
+        .await
+        .map_err(|e| format!("Failed to index document: {}", e))?;
+
+    Ok(json!({ "document_id": document_id }))
+}
+
+async fn handle_rag_stats(state: &AppState) -> Result<Value, String> {
+    let stats = rag_service(state)
+        .await?
+        .get_stats()
+        .await
+        .map_err(|e| format!("Failed to get RAG stats: {}", e))?;
+
+    serde_json::to_value(stats).map_err(|e| e.to_string())
+}
+
+// MCP server
+
+/// Serve the workspace's task, Codex, RAG and provider operations as MCP tools
+async fn run_mcp_server(transport: McpTransport, port: u16, workspace: Option<PathBuf>) -> Result<()> {
+    let workspace_root = workspace.unwrap_or_else(|| {
+        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+    });
+    let state = Arc::new(AppState::new(workspace_root).await?);
+
+    let server = Arc::new(
+        McpServer::new(BinderyTools::new(Arc::clone(&state))).with_instructions(
+            "Vespera Bindery workspace: manage tasks and Codices, search indexed documents \
+             with rag_search, and chat through configured LLM providers.",
+        ),
+    );
+
+    let result = match transport {
+        McpTransport::Stdio => mcp::serve_stdio(server).await,
+        McpTransport::Sse => mcp::serve_sse(server, ([127, 0, 0, 1], port).into()).await,
+    };
+    state.shutdown().await;
+    result
+}
+
+// gRPC server
+
+/// Serve the workspace's database and RAG index to remote gRPC clients
+#[cfg(feature = "grpc")]
+async fn run_grpc_server(listen: std::net::SocketAddr, workspace: Option<PathBuf>) -> Result<()> {
+    let workspace_root = workspace.unwrap_or_else(|| {
+        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+    });
+    let state = AppState::new(workspace_root).await?;
+
+    let mut server = vespera_bindery::grpc::GrpcServer::new(Arc::clone(&state.database));
+    match rag_service(&state).await {
+        Ok(rag) => server = server.with_rag(Arc::clone(rag)),
+        Err(e) => warn!("Serving gRPC without RAG: {}", e),
+    }
+    server.serve(listen).await
+}
+
+/// An MCP tool backed by a JSON-RPC method
+struct McpMethod {
+    tool: Tool,
+    method: &'static str,
+    /// Parameter the method expects the tool arguments under, if not at the top level
+    wrap_in: Option<&'static str>,
+}
+
+fn mcp_method(name: &str, method: &'static str, description: &str, properties: Value, required: &[&str]) -> McpMethod {
+    McpMethod {
+        tool: Tool::new(
+            name,
+            description,
+            json!({ "type": "object", "properties": properties, "required": required }),
+        ),
+        method,
+        wrap_in: None,
+    }
+}
+
+/// Tools that call the server's JSON-RPC methods
+struct BinderyTools {
+    state: Arc<AppState>,
+    methods: Vec<McpMethod>,
+}
+
+impl BinderyTools {
+    fn new(state: Arc<AppState>) -> Self {
+        let id = |what: &str| json!({ "type": "string", "description": format!("{} ID", what) });
+        let string = |description: &str| json!({ "type": "string", "description": description });
+        let strings = json!({ "type": "array", "items": { "type": "string" } });
+
+        let methods = vec![
+            // Tasks
+            McpMethod {
+                wrap_in: Some("task_input"),
+                ..mcp_method("create_task", "create_task", "Create a task, with optional subtasks", json!({
+                    "title": string("Task title"),
+                    "description": string("Task description"),
+                    "priority": { "type": "string", "enum": ["low", "normal", "high", "critical"] },
+                    "project_id": id("Project"),
+                    "parent_id": id("Parent task"),
+                    "tags": strings,
+                    "labels": { "type": "object" },
+                    "subtasks": { "type": "array", "items": { "type": "object" } },
+                }), &["title"])
+            },
+            mcp_method("get_task", "get_task", "Get a task by ID", json!({ "task_id": id("Task") }), &["task_id"]),
+            mcp_method("list_tasks", "list_tasks", "List tasks, optionally only the children of a task; with cursor or page_size, one page and its next_cursor", json!({
+                "limit": { "type": "integer", "minimum": 1 },
+                "parent_id": id("Parent task"),
+                "cursor": string("next_cursor of the previous page"),
+                "page_size": { "type": "integer", "minimum": 1, "maximum": 1000 },
+            }), &[]),
+            McpMethod {
+                wrap_in: Some("update_input"),
+                ..mcp_method("update_task", "update_task", "Change a task's title or status", json!({
+                    "task_id": id("Task"),
+                    "title": string("New title"),
+                    "status": { "type": "string", "enum": ["todo", "doing", "review", "done", "cancelled", "blocked"] },
+                }), &["task_id"])
+            },
+            mcp_method("complete_task", "complete_task", "Mark a task done", json!({ "task_id": id("Task") }), &["task_id"]),
+            mcp_method("delete_task", "delete_task", "Delete a task", json!({ "task_id": id("Task") }), &["task_id"]),
+            mcp_method("get_task_dashboard", "get_task_dashboard", "Task counts by status and priority, with recent and overdue tasks; give a date range for daily created/completed counts, burndown and cycle-time percentiles", json!({
+                "from": string("First day of the time series, YYYY-MM-DD"),
+                "to": string("Last day of the time series, YYYY-MM-DD; defaults to today"),
+                "days": { "type": "integer", "minimum": 1, "description": "Days of time series when from is not given" },
+                "project_id": id("Project to chart"),
+                "label": string("Tag or label key to chart"),
+            }), &[]),
+            mcp_method("list_roles", "list_roles", "List the roles tasks can be assigned to", json!({}), &[]),
+            // Codices
+            mcp_method("list_codices", "list_codices", "List all Codices; with cursor or page_size, one page and its next_cursor", json!({
+                "cursor": string("next_cursor of the previous page"),
+                "page_size": { "type": "integer", "minimum": 1, "maximum": 1000 },
+            }), &[]),
+            mcp_method("list_children", "list_children", "List the child Codices of a Codex", json!({ "parent_id": id("Parent Codex") }), &["parent_id"]),
+            mcp_method("get_codex", "get_codex", "Get a Codex by ID", json!({ "codex_id": id("Codex") }), &["codex_id"]),
+            mcp_method("create_codex", "create_codex", "Create a Codex from a template, returning its ID", json!({
+                "title": string("Codex title"),
+                "template_id": string("Template to create the Codex from"),
+                "metadata": { "type": "object", "description": "Metadata such as project_id and parent_id" },
+            }), &["title"]),
+            mcp_method("update_codex", "update_codex", "Update a Codex; fields left out are unchanged", json!({
+                "codex_id": id("Codex"),
+                "title": string("New title"),
+                "content": { "type": "object", "description": "Template field values" },
+                "tags": strings,
+                "metadata": { "type": "object" },
+            }), &["codex_id"]),
+            mcp_method("delete_codex", "delete_codex", "Delete a Codex", json!({ "codex_id": id("Codex") }), &["codex_id"]),
+            // RAG
+            mcp_method("rag_search", "rag.search", "Search indexed documents by meaning and keywords", json!({
+                "query": string("What to search for"),
+                "limit": { "type": "integer", "minimum": 1, "default": 10 },
+                "document_types": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["text", "code", "markdown", "documentation", "configuration", "data"] },
+                },
+            }), &["query"]),
+            mcp_method("rag_index_document", "rag.index_document", "Add a document to the search index", json!({
+                "title": string("Document title"),
+                "content": string("Document text"),
+                "document_type": { "type": "string", "enum": ["text", "code", "markdown", "documentation", "configuration", "data"] },
+                "source_path": string("File the document came from"),
+                "tags": strings,
+            }), &["content"]),
+            mcp_method("rag_stats", "rag.stats", "Size of the search index", json!({}), &[]),
+            // Providers
+            mcp_method("list_providers", "provider.list", "List configured LLM providers", json!({}), &[]),
+            mcp_method("provider_status", "provider.status", "Health of one provider, or all of them", json!({ "provider_id": id("Provider") }), &[]),
+            mcp_method("list_models", "provider.models", "Models a provider can serve", json!({ "provider_id": id("Provider") }), &["provider_id"]),
+            mcp_method("send_message", "chat.send_message", "Send a chat message through a provider", json!({
+                "provider_id": id("Provider"),
+                "message": string("Message text"),
+                "model": string("Model to use instead of the provider's default"),
+                "session_id": string("Chat session to continue"),
+                "system_prompt": string("System prompt"),
+            }), &["provider_id", "message"]),
+        ];
+
+        Self { state, methods }
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for BinderyTools {
+    fn tools(&self) -> Vec<Tool> {
+        self.methods.iter().map(|method| method.tool.clone()).collect()
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        let method = self
+            .methods
+            .iter()
+            .find(|method| method.tool.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown tool: {}", name))?;
+
+        let params = match method.wrap_in {
+            Some(key) => json!({ key: arguments }),
+            None => arguments,
+        };
+        dispatch_method(&self.state, method.method, &Some(params))
+            .await
+            .map_err(anyhow::Error::msg)
+    }
+}
+
 // REST API handlers for MCP server integration
 
 /// Create a new task (POST /api/tasks)
@@ -1332,6 +2119,15 @@ async fn api_pool_health(
     })))
 }
 
+async fn api_health_report(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Value>, StatusCode> {
+    handle_health_report(&state)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 /// Helper function to get a task by ID
 async fn get_task_by_id(state: &AppState, task_id: &str) -> Result<Value, String> {
     let tasks = state.database.list_tasks(Some(1000), None).await
@@ -0,0 +1,11 @@
+//! Language bindings
+//!
+//! Only compiled with the matching feature: `nodejs` builds the NAPI-RS
+//! module loaded by the VS Code extension and the Obsidian plugin, and
+//! `python` the PyO3 extension used by the Python scriptorium components.
+
+#[cfg(feature = "nodejs")]
+pub mod nodejs;
+
+#[cfg(feature = "python")]
+pub mod python;
@@ -0,0 +1,976 @@
+//! Node.js bindings (NAPI-RS)
+//!
+//! Task management, RAG, hook registration and Codex queries for the VS Code
+//! extension and the Obsidian plugin. `napi build` generates `index.d.ts`
+//! from the `#[napi]` items in this file, so the TypeScript types are the
+//! ones declared here: objects mirror the Rust models with camelCase fields,
+//! IDs are strings, and timestamps are RFC 3339 strings.
+
+use crate::hook_system::{self as hooks, HookManager};
+use crate::pagination::{PageRequest, DEFAULT_PAGE_SIZE};
+use crate::rag::{self, RAGConfig, RAGService};
+use crate::task_management::{self as tasks, TaskManager};
+use crate::{BinderyConfig, CodexId, CodexManager};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, JsFunction, Result};
+use napi_derive::napi;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Default number of tasks returned by `searchTasks`
+const DEFAULT_TASK_LIMIT: u32 = 50;
+/// Default number of results returned by `searchDocuments`
+const DEFAULT_SEARCH_LIMIT: u32 = 10;
+
+fn js_error(error: impl Display) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+fn parse_id(id: &str) -> Result<CodexId> {
+    id.parse().map_err(|_| Error::from_reason(format!("Invalid ID: {}", id)))
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| Error::from_reason(format!("Invalid RFC 3339 timestamp: {}", value)))
+}
+
+#[napi(string_enum = "snake_case")]
+pub enum TaskStatus {
+    Todo,
+    Doing,
+    Review,
+    Done,
+    Cancelled,
+    Blocked,
+}
+
+impl From<TaskStatus> for tasks::TaskStatus {
+    fn from(status: TaskStatus) -> Self {
+        match status {
+            TaskStatus::Todo => tasks::TaskStatus::Todo,
+            TaskStatus::Doing => tasks::TaskStatus::Doing,
+            TaskStatus::Review => tasks::TaskStatus::Review,
+            TaskStatus::Done => tasks::TaskStatus::Done,
+            TaskStatus::Cancelled => tasks::TaskStatus::Cancelled,
+            TaskStatus::Blocked => tasks::TaskStatus::Blocked,
+        }
+    }
+}
+
+impl From<tasks::TaskStatus> for TaskStatus {
+    fn from(status: tasks::TaskStatus) -> Self {
+        match status {
+            tasks::TaskStatus::Todo => TaskStatus::Todo,
+            tasks::TaskStatus::Doing => TaskStatus::Doing,
+            tasks::TaskStatus::Review => TaskStatus::Review,
+            tasks::TaskStatus::Done => TaskStatus::Done,
+            tasks::TaskStatus::Cancelled => TaskStatus::Cancelled,
+            tasks::TaskStatus::Blocked => TaskStatus::Blocked,
+        }
+    }
+}
+
+#[napi(string_enum = "snake_case")]
+pub enum TaskPriority {
+    Low,
+    Normal,
+    High,
+    Critical,
+}
+
+impl From<TaskPriority> for tasks::TaskPriority {
+    fn from(priority: TaskPriority) -> Self {
+        match priority {
+            TaskPriority::Low => tasks::TaskPriority::Low,
+            TaskPriority::Normal => tasks::TaskPriority::Normal,
+            TaskPriority::High => tasks::TaskPriority::High,
+            TaskPriority::Critical => tasks::TaskPriority::Critical,
+        }
+    }
+}
+
+impl From<tasks::TaskPriority> for TaskPriority {
+    fn from(priority: tasks::TaskPriority) -> Self {
+        match priority {
+            tasks::TaskPriority::Low => TaskPriority::Low,
+            tasks::TaskPriority::Normal => TaskPriority::Normal,
+            tasks::TaskPriority::High => TaskPriority::High,
+            tasks::TaskPriority::Critical => TaskPriority::Critical,
+        }
+    }
+}
+
+/// Input for `createTask`
+#[napi(object)]
+pub struct TaskInput {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub assignee: Option<String>,
+    /// RFC 3339 timestamp
+    pub due_date: Option<String>,
+    pub role: Option<String>,
+    pub project_id: Option<String>,
+    pub parent_id: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub labels: Option<HashMap<String, String>>,
+    pub subtasks: Option<Vec<TaskInput>>,
+}
+
+impl TryFrom<TaskInput> for tasks::TaskInput {
+    type Error = Error;
+
+    fn try_from(input: TaskInput) -> Result<Self> {
+        Ok(Self {
+            title: input.title,
+            description: input.description,
+            priority: input.priority.map(Into::into),
+            assignee: input.assignee,
+            due_date: input.due_date.as_deref().map(parse_time).transpose()?,
+            role: input.role,
+            project_id: input.project_id,
+            parent_id: input.parent_id.as_deref().map(parse_id).transpose()?,
+            tags: input.tags.unwrap_or_default(),
+            labels: input.labels.unwrap_or_default(),
+            subtasks: input
+                .subtasks
+                .unwrap_or_default()
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Input for `updateTask`; fields left out are unchanged
+#[napi(object)]
+pub struct TaskUpdateInput {
+    pub task_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub assignee: Option<String>,
+    /// RFC 3339 timestamp
+    pub due_date: Option<String>,
+    pub role: Option<String>,
+    pub labels: Option<HashMap<String, String>>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl TryFrom<TaskUpdateInput> for tasks::TaskUpdateInput {
+    type Error = Error;
+
+    fn try_from(input: TaskUpdateInput) -> Result<Self> {
+        Ok(Self {
+            task_id: parse_id(&input.task_id)?,
+            title: input.title,
+            description: input.description,
+            status: input.status.map(Into::into),
+            priority: input.priority.map(Into::into),
+            assignee: input.assignee,
+            due_date: input.due_date.as_deref().map(parse_time).transpose()?,
+            role: input.role,
+            labels: input.labels,
+            tags: input.tags,
+        })
+    }
+}
+
+/// Filters for `searchTasks`; all given filters must match
+#[napi(object)]
+#[derive(Default)]
+pub struct TaskFilter {
+    /// Case-insensitive text matched against titles and tags
+    pub query: Option<String>,
+    pub project_id: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub assignee: Option<String>,
+    pub parent_id: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[napi(object)]
+pub struct TaskSummary {
+    pub id: String,
+    pub title: String,
+    pub status: TaskStatus,
+    pub priority: TaskPriority,
+    pub assignee: Option<String>,
+    pub project_id: Option<String>,
+    pub due_date: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub tags: Vec<String>,
+    pub progress: Option<f64>,
+    pub parent_id: Option<String>,
+    pub child_count: u32,
+}
+
+impl From<tasks::TaskSummary> for TaskSummary {
+    fn from(summary: tasks::TaskSummary) -> Self {
+        Self {
+            id: summary.id.to_string(),
+            title: summary.title,
+            status: summary.status.into(),
+            priority: summary.priority.into(),
+            assignee: summary.assignee,
+            project_id: summary.project_id,
+            due_date: summary.due_date.map(|date| date.to_rfc3339()),
+            created_at: summary.created_at.to_rfc3339(),
+            updated_at: summary.updated_at.to_rfc3339(),
+            tags: summary.tags,
+            progress: summary.progress,
+            parent_id: summary.parent_id.map(|id| id.to_string()),
+            child_count: summary.child_count as u32,
+        }
+    }
+}
+
+impl TaskSummary {
+    fn matches_query(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.title.to_lowercase().contains(&query)
+            || self.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+    }
+}
+
+#[napi(object)]
+pub struct TaskTree {
+    pub task: TaskSummary,
+    pub children: Vec<TaskTree>,
+    pub depth: u32,
+}
+
+impl From<tasks::TaskTree> for TaskTree {
+    fn from(tree: tasks::TaskTree) -> Self {
+        Self {
+            task: tree.task.into(),
+            children: tree.children.into_iter().map(Into::into).collect(),
+            depth: tree.depth as u32,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct TaskDashboard {
+    pub total_tasks: u32,
+    /// Task count by status (`todo`, `doing`, ...)
+    pub status_breakdown: HashMap<String, u32>,
+    /// Task count by priority (`low`, `normal`, ...)
+    pub priority_breakdown: HashMap<String, u32>,
+    pub project_breakdown: HashMap<String, u32>,
+    pub recent_tasks: Vec<TaskSummary>,
+    pub overdue_tasks: Vec<TaskSummary>,
+    pub completion_rate: f64,
+    pub avg_completion_time_hours: Option<f64>,
+}
+
+impl From<tasks::TaskDashboard> for TaskDashboard {
+    fn from(dashboard: tasks::TaskDashboard) -> Self {
+        Self {
+            total_tasks: dashboard.total_tasks as u32,
+            status_breakdown: breakdown(dashboard.status_breakdown),
+            priority_breakdown: breakdown(dashboard.priority_breakdown),
+            project_breakdown: breakdown(dashboard.project_breakdown),
+            recent_tasks: dashboard.recent_tasks.into_iter().map(Into::into).collect(),
+            overdue_tasks: dashboard.overdue_tasks.into_iter().map(Into::into).collect(),
+            completion_rate: dashboard.completion_rate,
+            avg_completion_time_hours: dashboard.avg_completion_time_hours,
+        }
+    }
+}
+
+/// Counts keyed by the serialized (snake_case) form of `K`
+fn breakdown<K: serde::Serialize>(counts: HashMap<K, usize>) -> HashMap<String, u32> {
+    counts
+        .into_iter()
+        .map(|(key, count)| {
+            let key = match serde_json::to_value(&key) {
+                Ok(Value::String(key)) => key,
+                Ok(other) => other.to_string(),
+                Err(_) => String::new(),
+            };
+            (key, count as u32)
+        })
+        .collect()
+}
+
+#[napi(string_enum = "snake_case")]
+pub enum DocumentType {
+    Text,
+    Code,
+    Markdown,
+    Documentation,
+    Configuration,
+    Data,
+}
+
+impl From<DocumentType> for rag::DocumentType {
+    fn from(document_type: DocumentType) -> Self {
+        match document_type {
+            DocumentType::Text => rag::DocumentType::Text,
+            DocumentType::Code => rag::DocumentType::Code,
+            DocumentType::Markdown => rag::DocumentType::Markdown,
+            DocumentType::Documentation => rag::DocumentType::Documentation,
+            DocumentType::Configuration => rag::DocumentType::Configuration,
+            DocumentType::Data => rag::DocumentType::Data,
+        }
+    }
+}
+
+impl From<rag::DocumentType> for DocumentType {
+    fn from(document_type: rag::DocumentType) -> Self {
+        match document_type {
+            rag::DocumentType::Text => DocumentType::Text,
+            rag::DocumentType::Code => DocumentType::Code,
+            rag::DocumentType::Markdown => DocumentType::Markdown,
+            rag::DocumentType::Documentation => DocumentType::Documentation,
+            rag::DocumentType::Configuration => DocumentType::Configuration,
+            rag::DocumentType::Data => DocumentType::Data,
+        }
+    }
+}
+
+/// Input for `indexDocument`
+#[napi(object)]
+pub struct DocumentInput {
+    pub title: String,
+    pub content: String,
+    /// Defaults to `text`
+    pub document_type: Option<DocumentType>,
+    pub source_path: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[napi(object)]
+pub struct TextHighlight {
+    pub start: u32,
+    pub end: u32,
+    pub text: String,
+}
+
+#[napi(object)]
+pub struct SearchResult {
+    pub document_id: String,
+    pub chunk_id: String,
+    pub content: String,
+    pub score: f64,
+    pub title: String,
+    pub document_type: DocumentType,
+    pub source_path: Option<String>,
+    pub tags: Vec<String>,
+    /// Codex the document was indexed from, to open when the result is selected
+    pub codex_id: Option<String>,
+    pub highlights: Vec<TextHighlight>,
+}
+
+impl From<rag::SearchResult> for SearchResult {
+    fn from(result: rag::SearchResult) -> Self {
+        let codex_id = result.metadata.codex_id().map(|id| id.to_string());
+        Self {
+            document_id: result.document_id.to_string(),
+            chunk_id: result.chunk_id,
+            content: result.content,
+            score: result.score as f64,
+            title: result.metadata.title,
+            document_type: result.metadata.document_type.into(),
+            source_path: result.metadata.source_path.map(|path| path.display().to_string()),
+            tags: result.metadata.tags,
+            codex_id,
+            highlights: result
+                .highlights
+                .into_iter()
+                .map(|highlight| TextHighlight {
+                    start: highlight.start as u32,
+                    end: highlight.end as u32,
+                    text: highlight.text,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[napi(object)]
+pub struct RagStats {
+    pub total_documents: u32,
+    pub total_chunks: u32,
+    pub total_embeddings: u32,
+    pub index_size_bytes: i64,
+    pub last_indexed: Option<String>,
+}
+
+impl From<rag::RAGStats> for RagStats {
+    fn from(stats: rag::RAGStats) -> Self {
+        Self {
+            total_documents: stats.total_documents as u32,
+            total_chunks: stats.total_chunks as u32,
+            total_embeddings: stats.total_embeddings as u32,
+            index_size_bytes: stats.index_size_bytes as i64,
+            last_indexed: stats.last_indexed.map(|time| time.to_rfc3339()),
+        }
+    }
+}
+
+/// Hook agent from a template's automation rules, for `registerHook`
+#[napi(object)]
+pub struct HookAgentInput {
+    pub template_id: String,
+    pub template_name: String,
+    pub automation_rule: Value,
+    pub field_schema: Option<HashMap<String, Value>>,
+    pub template_data: Option<HashMap<String, Value>>,
+    pub context: Option<HashMap<String, Value>>,
+}
+
+impl From<HookAgentInput> for hooks::HookAgentInput {
+    fn from(input: HookAgentInput) -> Self {
+        Self {
+            template_id: input.template_id,
+            template_name: input.template_name,
+            automation_rule: input.automation_rule,
+            field_schema: input.field_schema.unwrap_or_default(),
+            template_data: input.template_data.unwrap_or_default(),
+            context: input.context,
+        }
+    }
+}
+
+/// Scheduled agent from a template's automation rules, for `registerTimedAgent`
+#[napi(object)]
+pub struct TimedAgentInput {
+    pub template_id: String,
+    pub template_name: String,
+    pub automation_rule: Value,
+    pub field_schema: Option<HashMap<String, Value>>,
+    pub template_data: Option<HashMap<String, Value>>,
+    pub schedule_config: HashMap<String, Value>,
+}
+
+impl From<TimedAgentInput> for hooks::TimedAgentInput {
+    fn from(input: TimedAgentInput) -> Self {
+        Self {
+            template_id: input.template_id,
+            template_name: input.template_name,
+            automation_rule: input.automation_rule,
+            field_schema: input.field_schema.unwrap_or_default(),
+            template_data: input.template_data.unwrap_or_default(),
+            schedule_config: input.schedule_config,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct HookExecutionResult {
+    pub hook_id: String,
+    pub success: bool,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub execution_time: String,
+    pub duration_ms: f64,
+    /// Event that ran the hook (`custom_event`, `post_task_create`, ...)
+    pub triggered_by: String,
+    pub context_data: HashMap<String, Value>,
+}
+
+impl From<hooks::HookExecutionResult> for HookExecutionResult {
+    fn from(result: hooks::HookExecutionResult) -> Self {
+        Self {
+            hook_id: result.hook_id,
+            success: result.success,
+            output: result.output,
+            error: result.error,
+            execution_time: result.execution_time.to_rfc3339(),
+            duration_ms: result.duration.as_secs_f64() * 1000.0,
+            triggered_by: match serde_json::to_value(&result.triggered_by) {
+                Ok(Value::String(trigger)) => trigger,
+                _ => format!("{:?}", result.triggered_by),
+            },
+            context_data: result.context_data,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct Codex {
+    pub id: String,
+    pub title: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Metadata values by key, as stored in the Codex
+    pub metadata: HashMap<String, Value>,
+}
+
+impl From<&crate::crdt::VesperaCRDT> for Codex {
+    fn from(crdt: &crate::crdt::VesperaCRDT) -> Self {
+        Self {
+            id: crdt.codex_id.to_string(),
+            title: crdt.get_title(),
+            created_by: crdt.created_by.clone(),
+            created_at: crdt.created_at.to_rfc3339(),
+            updated_at: crdt.updated_at.to_rfc3339(),
+            metadata: crdt
+                .metadata_layer
+                .keys()
+                .filter_map(|key| {
+                    let value = serde_json::to_value(crdt.get_metadata(key)?).ok()?;
+                    Some((key.clone(), value))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One page of `listCodicesPage`
+#[napi(object)]
+pub struct CodexPage {
+    pub codices: Vec<Codex>,
+    /// Cursor for the next page; unset on the last page
+    pub next_cursor: Option<String>,
+}
+
+async fn codex_page(codex_manager: &CodexManager, request: &PageRequest) -> Result<CodexPage> {
+    let page = codex_manager.list_codices_page(request).await.map_err(js_error)?;
+    let mut codices = Vec::with_capacity(page.items.len());
+    for id in page.items {
+        if let Some(crdt) = codex_manager.get_codex(&id).await {
+            codices.push(Codex::from(crdt.as_ref()));
+        }
+    }
+    Ok(CodexPage { codices, next_cursor: page.next_cursor })
+}
+
+/// Pages of Codices from `codexPages`, fetched one `next()` at a time
+#[napi]
+pub struct CodexPages {
+    codex_manager: Arc<CodexManager>,
+    page_size: usize,
+    /// Cursor of the next page, or `None` once the last page was returned
+    cursor: tokio::sync::Mutex<Option<Option<String>>>,
+}
+
+#[napi]
+impl CodexPages {
+    /// The next page, or null when all Codices were returned
+    #[napi]
+    pub async fn next(&self) -> Result<Option<Vec<Codex>>> {
+        let mut cursor = self.cursor.lock().await;
+        let Some(after) = cursor.clone() else {
+            return Ok(None);
+        };
+        let request = PageRequest { cursor: after, limit: Some(self.page_size) };
+        let page = codex_page(&self.codex_manager, &request).await?;
+        *cursor = page.next_cursor.map(Some);
+        Ok(Some(page.codices))
+    }
+}
+
+/// A change to a Codex or task, delivered to `onCodexChange` callbacks
+#[napi(object)]
+pub struct CodexEvent {
+    pub codex_id: String,
+    pub template_id: Option<String>,
+    /// `created`, `updated`, `deleted`, `trashed`, `restored`, `reference_added`
+    /// or `reference_removed`
+    pub kind: String,
+    /// The change itself: the title, the updated fields with their old and
+    /// new values, or the reference
+    pub change: Value,
+    pub timestamp: String,
+}
+
+impl From<crate::CodexEvent> for CodexEvent {
+    fn from(event: crate::CodexEvent) -> Self {
+        let kind = serde_json::to_value(event.change.kind())
+            .ok()
+            .and_then(|kind| kind.as_str().map(str::to_string))
+            .unwrap_or_default();
+        Self {
+            codex_id: event.codex_id.to_string(),
+            template_id: event.template_id,
+            kind,
+            change: serde_json::to_value(&event.change).unwrap_or(Value::Null),
+            timestamp: event.timestamp.to_rfc3339(),
+        }
+    }
+}
+
+/// Which changes `onCodexChange` delivers; unset criteria match everything
+#[napi(object)]
+#[derive(Default)]
+pub struct CodexEventFilter {
+    pub codex_ids: Option<Vec<String>>,
+    pub template_ids: Option<Vec<String>>,
+    /// Change kinds, as in `CodexEvent.kind`
+    pub kinds: Option<Vec<String>>,
+    /// Only task changes
+    pub tasks_only: Option<bool>,
+}
+
+impl TryFrom<CodexEventFilter> for crate::CodexEventFilter {
+    type Error = Error;
+
+    fn try_from(filter: CodexEventFilter) -> Result<Self> {
+        let mut result = if filter.tasks_only.unwrap_or(false) {
+            crate::CodexEventFilter::tasks()
+        } else {
+            crate::CodexEventFilter::new()
+        };
+        for id in filter.codex_ids.unwrap_or_default() {
+            result = result.with_codex(parse_id(&id)?);
+        }
+        for template_id in filter.template_ids.unwrap_or_default() {
+            result = result.with_template(template_id);
+        }
+        for kind in filter.kinds.unwrap_or_default() {
+            let parsed = serde_json::from_value(Value::String(kind.clone()))
+                .map_err(|_| Error::from_reason(format!("Invalid change kind: {}", kind)))?;
+            result = result.with_kind(parsed);
+        }
+        Ok(result)
+    }
+}
+
+/// Delivers changes to an `onCodexChange` callback until unsubscribed
+#[napi]
+pub struct CodexSubscription {
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[napi]
+impl CodexSubscription {
+    /// Stop calling the callback; calling this again does nothing
+    #[napi]
+    pub fn unsubscribe(&self) {
+        self.task.abort();
+    }
+}
+
+/// A Bindery workspace: Codices, tasks, hooks and (once opened) a RAG index
+///
+/// ```ts
+/// const bindery = new Bindery('/path/to/vault/.vespera')
+/// const id = await bindery.createTask({ title: 'Draft chapter 3', priority: TaskPriority.High })
+/// const dashboard = await bindery.taskDashboard()
+/// ```
+#[napi]
+pub struct Bindery {
+    codex_manager: Arc<CodexManager>,
+    tasks: Arc<TaskManager>,
+    hooks: Arc<HookManager>,
+    rag: RwLock<Option<Arc<RAGService>>>,
+}
+
+#[napi]
+impl Bindery {
+    /// Open a workspace, storing Codices under `storagePath` when given
+    #[napi(constructor)]
+    pub fn new(storage_path: Option<String>) -> Result<Self> {
+        let codex_manager = match storage_path {
+            Some(path) => {
+                let config = BinderyConfig::new().and_then(|config| config.with_storage_path(path)).map_err(js_error)?;
+                CodexManager::with_config(config)
+            }
+            None => CodexManager::new(),
+        }
+        .map_err(js_error)?;
+
+        Ok(Self {
+            tasks: codex_manager.get_task_manager(),
+            hooks: codex_manager.get_hook_manager(),
+            codex_manager: Arc::new(codex_manager),
+            rag: RwLock::new(None),
+        })
+    }
+
+    // Tasks
+
+    /// Create a task (and its subtasks), returning its ID
+    #[napi]
+    pub async fn create_task(&self, input: TaskInput) -> Result<String> {
+        let id = self.tasks.create_task(input.try_into()?).await.map_err(js_error)?;
+        Ok(id.to_string())
+    }
+
+    /// The task's Codex with its execution history, or null if there is none
+    #[napi]
+    pub async fn get_task(&self, task_id: String) -> Result<Option<Value>> {
+        self.tasks.get_task(&parse_id(&task_id)?).await.map_err(js_error)
+    }
+
+    #[napi]
+    pub async fn update_task(&self, input: TaskUpdateInput) -> Result<()> {
+        self.tasks.update_task(input.try_into()?).await.map_err(js_error)
+    }
+
+    #[napi]
+    pub async fn delete_task(&self, task_id: String, delete_subtasks: Option<bool>) -> Result<()> {
+        self.tasks
+            .delete_task(&parse_id(&task_id)?, delete_subtasks.unwrap_or(false))
+            .await
+            .map_err(js_error)
+    }
+
+    /// Tasks matching `filter`, newest first
+    #[napi]
+    pub async fn search_tasks(&self, filter: Option<TaskFilter>) -> Result<Vec<TaskSummary>> {
+        let filter = filter.unwrap_or_default();
+        let limit = filter.limit.unwrap_or(DEFAULT_TASK_LIMIT) as usize;
+        let parent_id = filter.parent_id.as_deref().map(parse_id).transpose()?;
+
+        // Without a text query the service can stop at the limit itself
+        let summaries = self
+            .tasks
+            .list_tasks(
+                filter.project_id,
+                filter.status.map(Into::into),
+                filter.priority.map(Into::into),
+                filter.assignee,
+                parent_id,
+                Some(if filter.query.is_some() { usize::MAX } else { limit }),
+            )
+            .await
+            .map_err(js_error)?;
+
+        Ok(summaries
+            .into_iter()
+            .map(TaskSummary::from)
+            .filter(|summary| filter.query.as_deref().is_none_or(|query| summary.matches_query(query)))
+            .take(limit)
+            .collect())
+    }
+
+    #[napi]
+    pub async fn get_task_tree(&self, task_id: String, max_depth: Option<u32>) -> Result<Option<TaskTree>> {
+        let tree = self
+            .tasks
+            .get_task_tree(&parse_id(&task_id)?, max_depth.map(|depth| depth as usize))
+            .await
+            .map_err(js_error)?;
+        Ok(tree.map(Into::into))
+    }
+
+    /// Task statistics, across all projects unless `projectId` is given
+    #[napi]
+    pub async fn task_dashboard(&self, project_id: Option<String>) -> Result<TaskDashboard> {
+        let dashboard = self.tasks.get_task_dashboard(project_id).await.map_err(js_error)?;
+        Ok(dashboard.into())
+    }
+
+    // RAG
+
+    /// Open (or create) the RAG index of the project at `projectPath`
+    ///
+    /// Must be called before the other RAG methods.
+    #[napi]
+    pub async fn open_rag(&self, project_path: String) -> Result<()> {
+        let service = RAGService::new(Path::new(&project_path), RAGConfig::default())
+            .await
+            .map_err(js_error)?;
+        *self.rag.write().await = Some(Arc::new(service));
+        Ok(())
+    }
+
+    async fn rag(&self) -> Result<Arc<RAGService>> {
+        self.rag
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| Error::from_reason("RAG index is not open; call openRag first"))
+    }
+
+    /// Index a document, returning its ID
+    #[napi]
+    pub async fn index_document(&self, input: DocumentInput) -> Result<String> {
+        let id = self
+            .rag()
+            .await?
+            .index_document(
+                input.title,
+                input.content,
+                input.document_type.map_or(rag::DocumentType::Text, Into::into),
+                input.source_path.map(PathBuf::from),
+                input.tags.unwrap_or_default(),
+            )
+            .await
+            .map_err(js_error)?;
+        Ok(id.to_string())
+    }
+
+    /// Index a file, detecting its type from the extension
+    #[napi]
+    pub async fn index_file(&self, path: String) -> Result<String> {
+        let id = self.rag().await?.index_file(Path::new(&path)).await.map_err(js_error)?;
+        Ok(id.to_string())
+    }
+
+    #[napi]
+    pub async fn search_documents(
+        &self,
+        query: String,
+        limit: Option<u32>,
+        document_types: Option<Vec<DocumentType>>,
+    ) -> Result<Vec<SearchResult>> {
+        let results = self
+            .rag()
+            .await?
+            .search(
+                &query,
+                limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as usize,
+                document_types.map(|types| types.into_iter().map(Into::into).collect()),
+            )
+            .await
+            .map_err(js_error)?;
+        Ok(results.into_iter().map(Into::into).collect())
+    }
+
+    /// Remove a document from the index; false if it was not indexed
+    #[napi]
+    pub async fn delete_document(&self, document_id: String) -> Result<bool> {
+        let id = document_id
+            .parse()
+            .map_err(|_| Error::from_reason(format!("Invalid ID: {}", document_id)))?;
+        self.rag().await?.delete_document(id).await.map_err(js_error)
+    }
+
+    #[napi]
+    pub async fn rag_stats(&self) -> Result<RagStats> {
+        Ok(self.rag().await?.get_stats().await.map_err(js_error)?.into())
+    }
+
+    // Hooks
+
+    /// Register a hook agent, returning its ID
+    ///
+    /// Hooks on task events run for tasks changed through this workspace.
+    #[napi]
+    pub async fn register_hook(&self, input: HookAgentInput) -> Result<String> {
+        self.hooks.register_hook_agent(input.into()).await.map_err(js_error)
+    }
+
+    /// Register a scheduled agent, returning its ID
+    #[napi]
+    pub async fn register_timed_agent(&self, input: TimedAgentInput) -> Result<String> {
+        self.hooks.register_timed_agent(input.into()).await.map_err(js_error)
+    }
+
+    /// Run a hook now; unless `force` is set its conditions must match `context`
+    #[napi]
+    pub async fn trigger_hook(
+        &self,
+        hook_id: String,
+        context: Option<HashMap<String, Value>>,
+        force: Option<bool>,
+    ) -> Result<HookExecutionResult> {
+        let result = self
+            .hooks
+            .trigger_hook_agent(hooks::HookTriggerInput {
+                hook_id,
+                trigger_context: context.unwrap_or_default(),
+                force_execute: force.unwrap_or(false),
+            })
+            .await
+            .map_err(js_error)?;
+        Ok(result.into())
+    }
+
+    /// Registered hook and timed agents with their recent executions
+    #[napi]
+    pub async fn hook_status(&self) -> Result<Value> {
+        self.hooks.get_hook_agent_status().await.map_err(js_error)
+    }
+
+    #[napi]
+    pub async fn pause_timed_agent(&self, agent_id: String) -> Result<()> {
+        self.hooks.pause_timed_agent(&agent_id).await.map_err(js_error)
+    }
+
+    #[napi]
+    pub async fn resume_timed_agent(&self, agent_id: String) -> Result<()> {
+        self.hooks.resume_timed_agent(&agent_id).await.map_err(js_error)
+    }
+
+    // Codices
+
+    #[napi]
+    pub async fn get_codex(&self, codex_id: String) -> Result<Option<Codex>> {
+        let crdt = self.codex_manager.get_codex(&parse_id(&codex_id)?).await;
+        Ok(crdt.map(|crdt| Codex::from(crdt.as_ref())))
+    }
+
+    /// All Codices, optionally only those whose title contains `query`
+    #[napi]
+    pub async fn list_codices(&self, query: Option<String>) -> Result<Vec<Codex>> {
+        let query = query.map(|query| query.to_lowercase());
+        let mut codices = Vec::new();
+        for id in self.codex_manager.list_codices().await {
+            let Some(crdt) = self.codex_manager.get_codex(&id).await else {
+                continue;
+            };
+            let codex = Codex::from(crdt.as_ref());
+            let matches = match (&query, &codex.title) {
+                (None, _) => true,
+                (Some(query), Some(title)) => title.to_lowercase().contains(query),
+                (Some(_), None) => false,
+            };
+            if matches {
+                codices.push(codex);
+            }
+        }
+        Ok(codices)
+    }
+
+    /// One page of Codices in ID order, starting after `cursor`
+    #[napi]
+    pub async fn list_codices_page(&self, cursor: Option<String>, limit: Option<u32>) -> Result<CodexPage> {
+        let request = PageRequest { cursor, limit: limit.map(|limit| limit as usize) };
+        codex_page(&self.codex_manager, &request).await
+    }
+
+    /// Codices a page at a time, so large workspaces render incrementally
+    ///
+    /// ```ts
+    /// const pages = bindery.codexPages(200)
+    /// for (let page = await pages.next(); page; page = await pages.next()) render(page)
+    /// ```
+    #[napi]
+    pub fn codex_pages(&self, page_size: Option<u32>) -> CodexPages {
+        CodexPages {
+            codex_manager: Arc::clone(&self.codex_manager),
+            page_size: page_size.map(|size| size as usize).unwrap_or(DEFAULT_PAGE_SIZE),
+            cursor: tokio::sync::Mutex::new(Some(None)),
+        }
+    }
+
+    /// Call `callback` with each Codex or task change matching `filter`, so
+    /// views can update without polling
+    ///
+    /// ```ts
+    /// const subscription = bindery.onCodexChange({ tasksOnly: true }, (event) => refresh(event.codexId))
+    /// subscription.unsubscribe()
+    /// ```
+    #[napi(ts_args_type = "filter: CodexEventFilter | undefined | null, callback: (event: CodexEvent) => void")]
+    pub fn on_codex_change(&self, filter: Option<CodexEventFilter>, callback: JsFunction) -> Result<CodexSubscription> {
+        let filter = filter.unwrap_or_default().try_into()?;
+        let callback: ThreadsafeFunction<CodexEvent, ErrorStrategy::Fatal> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<CodexEvent>| Ok(vec![ctx.value]))?;
+        let events = self.codex_manager.subscribe(filter);
+        let task = napi::bindgen_prelude::spawn(async move {
+            futures::pin_mut!(events);
+            while let Some(event) = events.next().await {
+                callback.call(event.into(), ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        });
+        Ok(CodexSubscription { task })
+    }
+}
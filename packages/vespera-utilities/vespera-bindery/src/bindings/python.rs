@@ -0,0 +1,605 @@
+//! Python bindings (PyO3)
+//!
+//! Exposes `CodexManager`, `TaskService`, `RoleManager` and `RAGService` as
+//! the `vespera_bindery._internal` extension module, so the Python
+//! scriptorium components call into Bindery instead of re-implementing it.
+//!
+//! Every I/O method returns an asyncio awaitable: the work runs on a Tokio
+//! runtime owned by the extension, and the result is handed back to the
+//! calling event loop with `call_soon_threadsafe`. Models cross the boundary
+//! as plain dicts and lists with the same (snake_case) shape as their serde
+//! representation; IDs are strings.
+
+// pyo3 0.22's macros expand to `.into()` on PyErr and check its own
+// `gil-refs` feature, which newer toolchains flag in this crate
+#![allow(clippy::useless_conversion, unexpected_cfgs)]
+
+use crate::pagination::{PageRequest, DEFAULT_PAGE_SIZE};
+use crate::rag::{DocumentType, RAGConfig, RAGService};
+use crate::role_management::{Role, RoleManager};
+use crate::task_management::{TaskInput, TaskManager, TaskPriority, TaskStatus, TaskUpdateInput};
+use crate::{BinderyConfig, CodexEventFilter, CodexId, CodexManager};
+use futures::StreamExt;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyStopAsyncIteration, PyValueError};
+use pyo3::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use tokio::runtime::Runtime;
+
+create_exception!(vespera_bindery, BinderyError, PyException, "Error raised by a Bindery operation.");
+
+fn py_error(error: impl Display) -> PyErr {
+    BinderyError::new_err(error.to_string())
+}
+
+/// Runtime for all work started from Python
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("vespera-bindery")
+            .build()
+            .expect("Failed to start Tokio runtime for Python bindings")
+    })
+}
+
+fn parse_id(id: &str) -> PyResult<CodexId> {
+    id.parse().map_err(|_| PyValueError::new_err(format!("Invalid ID: {}", id)))
+}
+
+/// A model from a JSON-compatible Python value (dict, list, str, ...)
+fn from_py<T: DeserializeOwned>(value: &Bound<'_, PyAny>) -> PyResult<T> {
+    let json: String = PyModule::import_bound(value.py(), "json")?
+        .call_method1("dumps", (value,))?
+        .extract()?;
+    serde_json::from_str(&json).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// An enum from its serialized name (`"todo"`, `"high"`, ...)
+fn parse_name<T: DeserializeOwned>(name: String) -> PyResult<T> {
+    serde_json::from_value(Value::String(name.clone()))
+        .map_err(|_| PyValueError::new_err(format!("Unknown value: {}", name)))
+}
+
+fn parse_document_type(name: &str) -> PyResult<DocumentType> {
+    name.parse().map_err(|e: anyhow::Error| PyValueError::new_err(e.to_string()))
+}
+
+/// Result of an awaitable, converted to a Python object once it completes
+trait IntoPython: Send + 'static {
+    fn into_python(self, py: Python<'_>) -> PyResult<PyObject>;
+}
+
+/// A serializable result, handed to Python as dicts, lists and scalars
+struct Json<T>(T);
+
+impl<T: Serialize + Send + 'static> IntoPython for Json<T> {
+    fn into_python(self, py: Python<'_>) -> PyResult<PyObject> {
+        let json = serde_json::to_string(&self.0).map_err(py_error)?;
+        Ok(PyModule::import_bound(py, "json")?.call_method1("loads", (json,))?.unbind())
+    }
+}
+
+impl IntoPython for PyRAGService {
+    fn into_python(self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(Py::new(py, self)?.into_any())
+    }
+}
+
+/// Complete an asyncio future, unless it was cancelled while the work ran
+#[pyfunction]
+fn resolve_future(future: &Bound<'_, PyAny>, value: PyObject, failed: bool) -> PyResult<()> {
+    if future.call_method0("done")?.is_truthy()? {
+        return Ok(());
+    }
+    future.call_method1(if failed { "set_exception" } else { "set_result" }, (value,))?;
+    Ok(())
+}
+
+/// Run `work` on the Bindery runtime and return an awaitable for its result
+///
+/// Must be called from a coroutine, as the awaitable belongs to the running
+/// event loop.
+fn awaitable<'py, F, T>(py: Python<'py>, work: F) -> PyResult<Bound<'py, PyAny>>
+where
+    F: Future<Output = PyResult<T>> + Send + 'static,
+    T: IntoPython,
+{
+    let event_loop = PyModule::import_bound(py, "asyncio")?.call_method0("get_running_loop")?;
+    let future = event_loop.call_method0("create_future")?;
+    let (event_loop_ref, future_ref) = (event_loop.unbind(), future.clone().unbind());
+
+    runtime().spawn(async move {
+        let result = work.await;
+        Python::with_gil(|py| {
+            let (value, failed) = match result.and_then(|value| value.into_python(py)) {
+                Ok(value) => (value, false),
+                Err(error) => (error.into_value(py).into_any(), true),
+            };
+            let scheduled = wrap_pyfunction_bound!(resolve_future, py).and_then(|resolve| {
+                event_loop_ref.call_method1(py, "call_soon_threadsafe", (resolve, future_ref, value, failed))
+            });
+            // The loop may have been closed while the work was running
+            if let Err(error) = scheduled {
+                error.write_unraisable_bound(py, None);
+            }
+        });
+    });
+
+    Ok(future)
+}
+
+/// Codex storage and lookup
+#[pyclass(name = "CodexManager", module = "vespera_bindery")]
+pub struct PyCodexManager {
+    inner: Arc<CodexManager>,
+}
+
+#[pymethods]
+impl PyCodexManager {
+    /// Open a Codex store, under `storage_path` when given
+    #[new]
+    #[pyo3(signature = (storage_path=None))]
+    fn new(storage_path: Option<PathBuf>) -> PyResult<Self> {
+        let _runtime = runtime().enter();
+        let manager = match storage_path {
+            Some(path) => {
+                let config = BinderyConfig::new().and_then(|config| config.with_storage_path(path)).map_err(py_error)?;
+                CodexManager::with_config(config)
+            }
+            None => CodexManager::new(),
+        }
+        .map_err(py_error)?;
+        Ok(Self { inner: Arc::new(manager) })
+    }
+
+    /// Create a Codex from a template, returning its ID
+    fn create_codex<'py>(&self, py: Python<'py>, title: String, template_id: String) -> PyResult<Bound<'py, PyAny>> {
+        let manager = Arc::clone(&self.inner);
+        awaitable(py, async move {
+            let id = manager.create_codex(title, template_id).await.map_err(py_error)?;
+            Ok(Json(id.to_string()))
+        })
+    }
+
+    /// The Codex as a dict, or None
+    fn get_codex<'py>(&self, py: Python<'py>, codex_id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (manager, id) = (Arc::clone(&self.inner), parse_id(codex_id)?);
+        awaitable(py, async move {
+            let codex = manager.get_codex(&id).await;
+            Ok(Json(codex.map(|crdt| codex_json(&crdt))))
+        })
+    }
+
+    /// IDs of all Codices
+    fn list_codices<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let manager = Arc::clone(&self.inner);
+        awaitable(py, async move {
+            let ids = manager.list_codices().await;
+            Ok(Json(ids.iter().map(ToString::to_string).collect::<Vec<_>>()))
+        })
+    }
+
+    /// One page of Codex IDs in ID order, as `{"items": [...], "next_cursor": ...}`
+    #[pyo3(signature = (cursor=None, limit=None))]
+    fn list_codices_page<'py>(&self, py: Python<'py>, cursor: Option<String>, limit: Option<usize>) -> PyResult<Bound<'py, PyAny>> {
+        let manager = Arc::clone(&self.inner);
+        awaitable(py, async move {
+            let page = manager.list_codices_page(&PageRequest { cursor, limit }).await.map_err(py_error)?;
+            Ok(Json(json!({
+                "items": page.items.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "next_cursor": page.next_cursor,
+            })))
+        })
+    }
+
+    /// Async iterator over all Codices, as dicts, fetched `page_size` at a time
+    ///
+    /// ```python
+    /// async for codex in manager.iter_codices(200):
+    ///     render(codex)
+    /// ```
+    #[pyo3(signature = (page_size=None))]
+    fn iter_codices(&self, page_size: Option<usize>) -> PyCodexIterator {
+        PyCodexIterator {
+            manager: Arc::clone(&self.inner),
+            page_size: page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+            state: Arc::new(tokio::sync::Mutex::new(CodexIteratorState {
+                buffered: VecDeque::new(),
+                cursor: None,
+                exhausted: false,
+            })),
+        }
+    }
+
+    /// Move a Codex and the Codices below it to the trash; False if there
+    /// was none or it already was there
+    fn delete_codex<'py>(&self, py: Python<'py>, codex_id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (manager, id) = (Arc::clone(&self.inner), parse_id(codex_id)?);
+        awaitable(py, async move { manager.delete_codex(&id).await.map(Json).map_err(py_error) })
+    }
+
+    /// Take a Codex out of the trash; False if it was not there
+    fn restore_codex<'py>(&self, py: Python<'py>, codex_id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (manager, id) = (Arc::clone(&self.inner), parse_id(codex_id)?);
+        awaitable(py, async move { manager.restore_codex(&id).await.map(Json).map_err(py_error) })
+    }
+
+    /// Call `callback` with each Codex or task change, as a dict, until the
+    /// returned subscription is cancelled
+    ///
+    /// Must be called from a coroutine; the callback runs on that event loop.
+    /// Each criterion given narrows the changes delivered: `kinds` are
+    /// `"created"`, `"updated"`, `"deleted"`, `"trashed"`, `"restored"`,
+    /// `"reference_added"` and `"reference_removed"`.
+    #[pyo3(signature = (callback, codex_ids=None, template_ids=None, kinds=None, tasks_only=false))]
+    fn subscribe(
+        &self,
+        py: Python<'_>,
+        callback: PyObject,
+        codex_ids: Option<Vec<String>>,
+        template_ids: Option<Vec<String>>,
+        kinds: Option<Vec<String>>,
+        tasks_only: bool,
+    ) -> PyResult<PyCodexSubscription> {
+        let mut filter = if tasks_only { CodexEventFilter::tasks() } else { CodexEventFilter::new() };
+        for id in codex_ids.unwrap_or_default() {
+            filter = filter.with_codex(parse_id(&id)?);
+        }
+        for template_id in template_ids.unwrap_or_default() {
+            filter = filter.with_template(template_id);
+        }
+        for kind in kinds.unwrap_or_default() {
+            filter = filter.with_kind(parse_name(kind)?);
+        }
+
+        let event_loop = PyModule::import_bound(py, "asyncio")?.call_method0("get_running_loop")?.unbind();
+        let events = self.inner.subscribe(filter);
+        let task = runtime().spawn(async move {
+            futures::pin_mut!(events);
+            while let Some(event) = events.next().await {
+                Python::with_gil(|py| {
+                    let scheduled = Json(event).into_python(py).and_then(|event| {
+                        event_loop.call_method1(py, "call_soon_threadsafe", (callback.clone_ref(py), event))
+                    });
+                    if let Err(error) = scheduled {
+                        error.write_unraisable_bound(py, None);
+                    }
+                });
+            }
+        });
+        Ok(PyCodexSubscription { task })
+    }
+
+    /// Task operations on this store's Codices
+    fn task_service(&self) -> PyTaskService {
+        PyTaskService { inner: self.inner.get_task_manager() }
+    }
+
+    /// Roles used when assigning and executing tasks
+    fn role_manager(&self) -> PyRoleManager {
+        PyRoleManager { inner: self.inner.get_role_manager() }
+    }
+}
+
+struct CodexIteratorState {
+    buffered: VecDeque<CodexId>,
+    cursor: Option<String>,
+    exhausted: bool,
+}
+
+/// Codices from `CodexManager.iter_codices`, one page fetched at a time
+#[pyclass(name = "CodexIterator", module = "vespera_bindery")]
+pub struct PyCodexIterator {
+    manager: Arc<CodexManager>,
+    page_size: usize,
+    state: Arc<tokio::sync::Mutex<CodexIteratorState>>,
+}
+
+#[pymethods]
+impl PyCodexIterator {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (manager, state, page_size) = (Arc::clone(&self.manager), Arc::clone(&self.state), self.page_size);
+        awaitable(py, async move {
+            let mut state = state.lock().await;
+            loop {
+                while let Some(id) = state.buffered.pop_front() {
+                    // Codices deleted since their page was fetched are skipped
+                    if let Some(crdt) = manager.get_codex(&id).await {
+                        return Ok(Json(codex_json(&crdt)));
+                    }
+                }
+                if state.exhausted {
+                    return Err(PyStopAsyncIteration::new_err(()));
+                }
+                let request = PageRequest { cursor: state.cursor.take(), limit: Some(page_size) };
+                let page = manager.list_codices_page(&request).await.map_err(py_error)?;
+                state.exhausted = page.next_cursor.is_none();
+                state.cursor = page.next_cursor;
+                state.buffered.extend(page.items);
+            }
+        })
+    }
+}
+
+/// Delivers changes to a `CodexManager.subscribe` callback until cancelled
+#[pyclass(name = "CodexSubscription", module = "vespera_bindery")]
+pub struct PyCodexSubscription {
+    task: tokio::task::JoinHandle<()>,
+}
+
+#[pymethods]
+impl PyCodexSubscription {
+    /// Stop calling the callback; cancelling again does nothing
+    fn cancel(&self) {
+        self.task.abort();
+    }
+}
+
+fn codex_json(crdt: &crate::crdt::VesperaCRDT) -> Value {
+    let metadata: serde_json::Map<String, Value> = crdt
+        .metadata_layer
+        .keys()
+        .filter_map(|key| Some((key.clone(), serde_json::to_value(crdt.get_metadata(key)?).ok()?)))
+        .collect();
+    json!({
+        "id": crdt.codex_id.to_string(),
+        "title": crdt.get_title(),
+        "created_by": crdt.created_by,
+        "created_at": crdt.created_at,
+        "updated_at": crdt.updated_at,
+        "metadata": metadata,
+    })
+}
+
+/// Task CRUD, search and dashboard
+///
+/// Goes through `TaskManager`, so hooks registered for task events run.
+#[pyclass(name = "TaskService", module = "vespera_bindery")]
+pub struct PyTaskService {
+    inner: Arc<TaskManager>,
+}
+
+#[pymethods]
+impl PyTaskService {
+    #[new]
+    fn new(manager: &PyCodexManager) -> Self {
+        manager.task_service()
+    }
+
+    /// Create a task from a dict shaped like `TaskInput`, returning its ID
+    fn create_task<'py>(&self, py: Python<'py>, input: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, input) = (Arc::clone(&self.inner), from_py::<TaskInput>(input)?);
+        awaitable(py, async move {
+            let id = tasks.create_task(input).await.map_err(py_error)?;
+            Ok(Json(id.to_string()))
+        })
+    }
+
+    /// The task's Codex with its execution history, or None
+    fn get_task<'py>(&self, py: Python<'py>, task_id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, id) = (Arc::clone(&self.inner), parse_id(task_id)?);
+        awaitable(py, async move { tasks.get_task(&id).await.map(Json).map_err(py_error) })
+    }
+
+    /// Update a task from a dict shaped like `TaskUpdateInput`
+    fn update_task<'py>(&self, py: Python<'py>, input: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, input) = (Arc::clone(&self.inner), from_py::<TaskUpdateInput>(input)?);
+        awaitable(py, async move { tasks.update_task(input).await.map(Json).map_err(py_error) })
+    }
+
+    #[pyo3(signature = (task_id, delete_subtasks=false))]
+    fn delete_task<'py>(&self, py: Python<'py>, task_id: &str, delete_subtasks: bool) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, id) = (Arc::clone(&self.inner), parse_id(task_id)?);
+        awaitable(py, async move { tasks.delete_task(&id, delete_subtasks).await.map(Json).map_err(py_error) })
+    }
+
+    /// Task summaries matching all given filters, newest first
+    #[pyo3(signature = (project_id=None, status=None, priority=None, assignee=None, parent_id=None, limit=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn list_tasks<'py>(
+        &self,
+        py: Python<'py>,
+        project_id: Option<String>,
+        status: Option<String>,
+        priority: Option<String>,
+        assignee: Option<String>,
+        parent_id: Option<String>,
+        limit: Option<usize>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let tasks = Arc::clone(&self.inner);
+        let status = status.map(parse_name::<TaskStatus>).transpose()?;
+        let priority = priority.map(parse_name::<TaskPriority>).transpose()?;
+        let parent_id = parent_id.as_deref().map(parse_id).transpose()?;
+        awaitable(py, async move {
+            tasks
+                .list_tasks(project_id, status, priority, assignee, parent_id, limit)
+                .await
+                .map(Json)
+                .map_err(py_error)
+        })
+    }
+
+    #[pyo3(signature = (task_id, max_depth=None))]
+    fn get_task_tree<'py>(&self, py: Python<'py>, task_id: &str, max_depth: Option<usize>) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, id) = (Arc::clone(&self.inner), parse_id(task_id)?);
+        awaitable(py, async move { tasks.get_task_tree(&id, max_depth).await.map(Json).map_err(py_error) })
+    }
+
+    /// Task statistics, across all projects unless `project_id` is given
+    #[pyo3(signature = (project_id=None))]
+    fn get_task_dashboard<'py>(&self, py: Python<'py>, project_id: Option<String>) -> PyResult<Bound<'py, PyAny>> {
+        let tasks = Arc::clone(&self.inner);
+        awaitable(py, async move { tasks.get_task_dashboard(project_id).await.map(Json).map_err(py_error) })
+    }
+
+    /// Mark a task done, recording `output` in its execution history
+    #[pyo3(signature = (task_id, output=None))]
+    fn complete_task<'py>(&self, py: Python<'py>, task_id: &str, output: Option<String>) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, id) = (Arc::clone(&self.inner), parse_id(task_id)?);
+        awaitable(py, async move { tasks.complete_task(&id, output, None).await.map(Json).map_err(py_error) })
+    }
+
+    fn assign_role<'py>(&self, py: Python<'py>, task_id: &str, role_name: String) -> PyResult<Bound<'py, PyAny>> {
+        let (tasks, id) = (Arc::clone(&self.inner), parse_id(task_id)?);
+        awaitable(py, async move { tasks.assign_role_to_task(&id, role_name).await.map(Json).map_err(py_error) })
+    }
+}
+
+/// Role definitions and lookup
+#[pyclass(name = "RoleManager", module = "vespera_bindery")]
+pub struct PyRoleManager {
+    inner: Arc<RoleManager>,
+}
+
+#[pymethods]
+impl PyRoleManager {
+    #[new]
+    fn new(manager: &PyCodexManager) -> Self {
+        manager.role_manager()
+    }
+
+    fn list_roles<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let roles = Arc::clone(&self.inner);
+        awaitable(py, async move { Ok(Json(roles.list_roles().await)) })
+    }
+
+    /// The role as a dict, or None
+    fn get_role<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        let roles = Arc::clone(&self.inner);
+        awaitable(py, async move { Ok(Json(roles.get_role(&name).await)) })
+    }
+
+    fn role_exists<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        let roles = Arc::clone(&self.inner);
+        awaitable(py, async move { Ok(Json(roles.role_exists(&name).await)) })
+    }
+
+    /// Add a role from a dict shaped like `Role`
+    fn add_role<'py>(&self, py: Python<'py>, role: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let (roles, role) = (Arc::clone(&self.inner), from_py::<Role>(role)?);
+        awaitable(py, async move { roles.add_role(role).await.map(Json).map_err(py_error) })
+    }
+
+    /// Remove a role; False if there was none
+    fn remove_role<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        let roles = Arc::clone(&self.inner);
+        awaitable(py, async move { roles.remove_role(&name).await.map(Json).map_err(py_error) })
+    }
+
+    /// Load roles from a YAML file
+    fn load_roles_from_file<'py>(&self, py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyAny>> {
+        let roles = Arc::clone(&self.inner);
+        awaitable(py, async move { roles.load_roles_from_file(path).await.map(Json).map_err(py_error) })
+    }
+}
+
+/// Document indexing and retrieval for one project
+///
+/// Created with `await RAGService.open(project_path)`.
+#[pyclass(name = "RAGService", module = "vespera_bindery")]
+pub struct PyRAGService {
+    inner: Arc<RAGService>,
+}
+
+#[pymethods]
+impl PyRAGService {
+    /// Open (or create) the RAG index of the project at `project_path`
+    #[staticmethod]
+    fn open(py: Python<'_>, project_path: PathBuf) -> PyResult<Bound<'_, PyAny>> {
+        awaitable(py, async move {
+            let service = RAGService::new(&project_path, RAGConfig::default()).await.map_err(py_error)?;
+            Ok(PyRAGService { inner: Arc::new(service) })
+        })
+    }
+
+    /// Index a document, returning its ID
+    #[pyo3(signature = (title, content, document_type="text", source_path=None, tags=None))]
+    fn index_document<'py>(
+        &self,
+        py: Python<'py>,
+        title: String,
+        content: String,
+        document_type: &str,
+        source_path: Option<PathBuf>,
+        tags: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (rag, document_type) = (Arc::clone(&self.inner), parse_document_type(document_type)?);
+        awaitable(py, async move {
+            let id = rag
+                .index_document(title, content, document_type, source_path, tags.unwrap_or_default())
+                .await
+                .map_err(py_error)?;
+            Ok(Json(id.to_string()))
+        })
+    }
+
+    /// Index a file, detecting its type from the extension
+    fn index_file<'py>(&self, py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyAny>> {
+        let rag = Arc::clone(&self.inner);
+        awaitable(py, async move {
+            let id = rag.index_file(&path).await.map_err(py_error)?;
+            Ok(Json(id.to_string()))
+        })
+    }
+
+    #[pyo3(signature = (query, limit=10, document_types=None))]
+    fn search<'py>(
+        &self,
+        py: Python<'py>,
+        query: String,
+        limit: usize,
+        document_types: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let rag = Arc::clone(&self.inner);
+        let document_types = document_types
+            .map(|types| types.iter().map(|name| parse_document_type(name)).collect::<PyResult<Vec<_>>>())
+            .transpose()?;
+        awaitable(py, async move { rag.search(&query, limit, document_types).await.map(Json).map_err(py_error) })
+    }
+
+    /// `{"metadata": ..., "content": ...}` for the document, or None
+    fn get_document<'py>(&self, py: Python<'py>, document_id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (rag, id) = (Arc::clone(&self.inner), parse_id(document_id)?);
+        awaitable(py, async move {
+            let document = rag.get_document(id).await.map_err(py_error)?;
+            Ok(Json(document.map(|(metadata, content)| json!({ "metadata": metadata, "content": content }))))
+        })
+    }
+
+    /// Remove a document from the index; False if it was not indexed
+    fn delete_document<'py>(&self, py: Python<'py>, document_id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (rag, id) = (Arc::clone(&self.inner), parse_id(document_id)?);
+        awaitable(py, async move { rag.delete_document(id).await.map(Json).map_err(py_error) })
+    }
+
+    fn get_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let rag = Arc::clone(&self.inner);
+        awaitable(py, async move { rag.get_stats().await.map(Json).map_err(py_error) })
+    }
+}
+
+#[pymodule]
+fn _internal(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("BinderyError", m.py().get_type_bound::<BinderyError>())?;
+    m.add_class::<PyCodexManager>()?;
+    m.add_class::<PyCodexSubscription>()?;
+    m.add_class::<PyCodexIterator>()?;
+    m.add_class::<PyTaskService>()?;
+    m.add_class::<PyRoleManager>()?;
+    m.add_class::<PyRAGService>()?;
+    m.add("__version__", crate::VERSION)?;
+    Ok(())
+}
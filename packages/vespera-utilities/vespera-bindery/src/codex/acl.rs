@@ -0,0 +1,347 @@
+//! Access control lists for Codices shared between users
+//!
+//! A Codex's [`CodexAcl`] names its owner, the editors who may change it and
+//! the readers who may only receive it. It is stored in the [`ACL_FIELD`]
+//! metadata field, so it syncs and merges like any other field. A Codex
+//! without one is open to everyone.
+//!
+//! The [`SyncManager`](crate::sync::SyncManager) enforces the list: it drops
+//! incoming operations from users without write access, or not written by
+//! the peer that sent them, recording each in the audit log, and only pushes
+//! a Codex to peers allowed to read it. Only the owner may change or remove
+//! the list.
+
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{CodexChange, CodexEvent, FieldChange};
+#[cfg(feature = "yjs-compat")]
+use crate::crdt::CRDTDelta;
+use crate::crdt::{CRDTOperation, OperationType, TemplateValue, VesperaCRDT};
+use crate::{BinderyError, BinderyResult, CodexId, CodexManager, UserId};
+
+/// Metadata field holding a Codex's access control list
+pub const ACL_FIELD: &str = "acl";
+
+/// What a user may do with a Codex, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLevel {
+    None,
+    /// Receive the Codex
+    Read,
+    /// Change its content
+    Write,
+    /// Also change the access control list
+    Owner,
+}
+
+impl AccessLevel {
+    pub fn can_read(self) -> bool {
+        self >= AccessLevel::Read
+    }
+
+    pub fn can_write(self) -> bool {
+        self >= AccessLevel::Write
+    }
+}
+
+/// Who may read and change a Codex
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodexAcl {
+    pub owner: UserId,
+    #[serde(default)]
+    pub editors: BTreeSet<UserId>,
+    #[serde(default)]
+    pub readers: BTreeSet<UserId>,
+}
+
+impl CodexAcl {
+    pub fn new(owner: impl Into<UserId>) -> Self {
+        Self { owner: owner.into(), editors: BTreeSet::new(), readers: BTreeSet::new() }
+    }
+
+    pub fn with_editor(mut self, user_id: impl Into<UserId>) -> Self {
+        self.editors.insert(user_id.into());
+        self
+    }
+
+    pub fn with_reader(mut self, user_id: impl Into<UserId>) -> Self {
+        self.readers.insert(user_id.into());
+        self
+    }
+
+    /// The access `user_id` has under this list
+    pub fn access(&self, user_id: &str) -> AccessLevel {
+        if self.owner == user_id {
+            AccessLevel::Owner
+        } else if self.editors.contains(user_id) {
+            AccessLevel::Write
+        } else if self.readers.contains(user_id) {
+            AccessLevel::Read
+        } else {
+            AccessLevel::None
+        }
+    }
+
+    /// The metadata value storing this list, written by `user_id`
+    pub fn to_value(&self, user_id: &str) -> BinderyResult<TemplateValue> {
+        Ok(TemplateValue::Structured {
+            value: serde_json::to_value(self)?,
+            timestamp: Utc::now(),
+            user_id: user_id.to_string(),
+        })
+    }
+
+    /// The list stored in `value`, if it holds one
+    pub fn from_value(value: &TemplateValue) -> Option<Self> {
+        match value {
+            TemplateValue::Structured { value, .. } => serde_json::from_value(value.clone()).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// The access control list of `crdt`, if it has one
+pub fn acl_of(crdt: &VesperaCRDT) -> Option<CodexAcl> {
+    crdt.get_metadata(ACL_FIELD).and_then(CodexAcl::from_value)
+}
+
+/// The access `user_id` has to `crdt`; full access when it has no list
+pub fn access_of(crdt: &VesperaCRDT, user_id: &str) -> AccessLevel {
+    acl_of(crdt).map_or(AccessLevel::Owner, |acl| acl.access(user_id))
+}
+
+/// The access needed to apply `operation`: owner for changes to the list
+/// itself, write for anything else
+pub fn required_access(operation: &CRDTOperation) -> AccessLevel {
+    match &operation.operation {
+        OperationType::MetadataSet { key, .. } | OperationType::MetadataDelete { key } if key == ACL_FIELD => {
+            AccessLevel::Owner
+        }
+        _ => AccessLevel::Write,
+    }
+}
+
+/// Whether `crdt`'s list allows its author to apply `operation`
+pub fn may_apply(crdt: &VesperaCRDT, operation: &CRDTOperation) -> bool {
+    access_of(crdt, &operation.user_id) >= required_access(operation)
+}
+
+/// The users `crdt`'s list doesn't allow to make the changes in `delta`,
+/// with the access each needed
+///
+/// A delta carries state rather than operations, so every user whose clock
+/// entry it advances counts as an author and needs write access, and
+/// whoever last wrote the list needs to be the owner if the delta changes it.
+#[cfg(feature = "yjs-compat")]
+pub fn refused_in_delta(crdt: &VesperaCRDT, delta: &CRDTDelta) -> BinderyResult<Vec<(UserId, AccessLevel)>> {
+    let mut refused: Vec<(UserId, AccessLevel)> = delta.vector_clock.iter()
+        .filter(|&(user_id, &counter)| counter > crdt.clock_entry(&crdt.vector_clock, user_id))
+        .filter(|(user_id, _)| !access_of(crdt, user_id).can_write())
+        .map(|(user_id, _)| (user_id.to_string(), AccessLevel::Write))
+        .collect();
+
+    let mut merged = crdt.clone();
+    merged.apply_delta(delta)?;
+    if acl_of(&merged) != acl_of(crdt) {
+        let author = merged.metadata_layer.key_history(&ACL_FIELD.to_string(), 1)
+            .first()
+            .map(|write| write.user_id.to_string())
+            .unwrap_or_default();
+        if access_of(crdt, &author) < AccessLevel::Owner {
+            refused.push((author, AccessLevel::Owner));
+        }
+    }
+    Ok(refused)
+}
+
+impl CodexManager {
+    /// The access control list of a Codex, if it has one
+    pub async fn codex_acl(&self, id: &CodexId) -> BinderyResult<Option<CodexAcl>> {
+        let codices = self.inner.codices.read().await;
+        let crdt = codices.get(id).ok_or_else(|| BinderyError::NotFound(format!("Codex {}", id)))?;
+        Ok(acl_of(crdt))
+    }
+
+    /// Replace a Codex's access control list
+    ///
+    /// Only the current owner may, or anyone if the Codex has no list yet.
+    pub async fn set_codex_acl(&self, id: &CodexId, acl: CodexAcl) -> BinderyResult<()> {
+        let user_id = self.inner.config.user_id.clone().unwrap_or_else(|| "system".to_string());
+        let (change, template_id) = self.modify_codex(id, |crdt| {
+            if access_of(crdt, &user_id) < AccessLevel::Owner {
+                return Err(BinderyError::PermissionDenied(format!(
+                    "Only the owner can change the access control list of Codex {}",
+                    crdt.codex_id
+                )));
+            }
+            let old = crdt.get_metadata(ACL_FIELD).cloned();
+            let new = acl.to_value(&user_id)?;
+            crdt.set_metadata(ACL_FIELD.to_string(), new.clone())?;
+            Ok(FieldChange { field: ACL_FIELD.to_string(), old, new })
+        }).await?;
+
+        self.publish(CodexEvent::new(*id, template_id, CodexChange::Updated { fields: vec![change] }));
+        Ok(())
+    }
+
+    /// Apply operations received from a peer over `connection_id`,
+    /// returning how many were applied
+    ///
+    /// With collaboration enabled the sync manager first drops operations
+    /// the connection's peer didn't write or the Codex's access control list
+    /// doesn't allow.
+    pub async fn apply_remote_operations(
+        &self,
+        connection_id: &str,
+        id: &CodexId,
+        operations: Vec<CRDTOperation>,
+    ) -> BinderyResult<usize> {
+        let operations = match &self.inner.sync_manager {
+            Some(sync_manager) => sync_manager.authorize_operations(connection_id, id, operations).await?,
+            None => operations,
+        };
+        if operations.is_empty() {
+            return Ok(0);
+        }
+
+        let (applied, template_id) = self.modify_codex(id, |crdt| {
+            let mut applied = 0;
+            for operation in operations {
+                // The list may have changed earlier in this batch
+                if !may_apply(crdt, &operation) {
+                    continue;
+                }
+                crdt.apply_operation(operation)?;
+                applied += 1;
+            }
+            Ok(applied)
+        }).await?;
+
+        if applied > 0 {
+            // Field-level changes aren't tracked for remote operations
+            self.publish(CodexEvent::new(*id, template_id, CodexChange::Updated { fields: Vec::new() }));
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn operation_from(crdt: &VesperaCRDT, user_id: &str, key: &str) -> CRDTOperation {
+        let mut peer = crdt.clone();
+        peer.set_metadata(key.to_string(), TemplateValue::Text {
+            value: "changed".to_string(),
+            timestamp: Utc::now(),
+            user_id: user_id.to_string(),
+        }).unwrap();
+        let mut operation = peer.operation_log.last().unwrap().clone();
+        operation.user_id = user_id.into();
+        operation
+    }
+
+    #[test]
+    fn test_access_levels() {
+        let acl = CodexAcl::new("alice").with_editor("bob").with_reader("carol");
+        assert_eq!(acl.access("alice"), AccessLevel::Owner);
+        assert_eq!(acl.access("bob"), AccessLevel::Write);
+        assert_eq!(acl.access("carol"), AccessLevel::Read);
+        assert_eq!(acl.access("mallory"), AccessLevel::None);
+        assert!(AccessLevel::Read.can_read() && !AccessLevel::Read.can_write());
+
+        let value = acl.to_value("alice").unwrap();
+        assert_eq!(CodexAcl::from_value(&value), Some(acl));
+    }
+
+    #[test]
+    fn test_list_is_metadata_and_gates_operations() {
+        let mut crdt = VesperaCRDT::new(Uuid::new_v4(), "alice".to_string());
+        // Open until a list is set
+        assert_eq!(access_of(&crdt, "anyone"), AccessLevel::Owner);
+
+        let acl = CodexAcl::new("alice").with_editor("bob").with_reader("carol");
+        crdt.set_metadata(ACL_FIELD.to_string(), acl.to_value("alice").unwrap()).unwrap();
+        assert_eq!(acl_of(&crdt), Some(acl));
+
+        assert!(may_apply(&crdt, &operation_from(&crdt, "bob", "status")));
+        assert!(!may_apply(&crdt, &operation_from(&crdt, "carol", "status")));
+        assert!(!may_apply(&crdt, &operation_from(&crdt, "mallory", "status")));
+        // Editors can't grant themselves more
+        assert!(!may_apply(&crdt, &operation_from(&crdt, "bob", ACL_FIELD)));
+        assert!(may_apply(&crdt, &operation_from(&crdt, "alice", ACL_FIELD)));
+    }
+
+    #[tokio::test]
+    async fn test_merge_checks_the_list() {
+        use crate::templates::{Template, TemplateId};
+
+        let config = crate::BinderyConfig { user_id: Some("alice".to_string()), ..Default::default() };
+        let manager = CodexManager::with_config(config).unwrap();
+        manager.register_template(Template::new(
+            TemplateId::new("test.note"),
+            "Note".to_string(),
+            "A note".to_string(),
+            "note".to_string(),
+        )).await.unwrap();
+        let id = manager.create_codex("Plan", "test.note").await.unwrap();
+        manager.set_codex_acl(&id, CodexAcl::new("alice").with_editor("bob")).await.unwrap();
+
+        let local = manager.get_codex(&id).await.unwrap();
+        let replica_from = |user_id: &str| {
+            let mut replica = (*local).clone();
+            let operation = operation_from(&replica, user_id, "status");
+            replica.apply_operation(operation).unwrap();
+            replica
+        };
+
+        let stats = manager.merge_codices(vec![replica_from("mallory")]).await.unwrap();
+        assert_eq!((stats.merged, stats.failed.len()), (0, 1));
+        assert!(manager.get_codex(&id).await.unwrap().get_metadata("status").is_none());
+
+        let stats = manager.merge_codices(vec![replica_from("bob")]).await.unwrap();
+        assert_eq!((stats.merged, stats.failed.len()), (1, 0));
+        assert!(manager.get_codex(&id).await.unwrap().get_metadata("status").is_some());
+
+        // In delta mode the replica's state is merged, not its log
+        #[cfg(feature = "yjs-compat")]
+        {
+            use crate::crdt::{MemoryConfig, OperationContext, StorageMode};
+
+            let config = MemoryConfig { storage_mode: StorageMode::Delta { history_window: 4 }, ..MemoryConfig::default() };
+            let mut shared = VesperaCRDT::new_with_memory_config(Uuid::new_v4(), "alice".to_string(), config);
+            shared.set_metadata(ACL_FIELD.to_string(), CodexAcl::new("alice").with_editor("bob").to_value("alice").unwrap()).unwrap();
+            let id = shared.codex_id;
+            manager.merge_codices(vec![shared.clone()]).await.unwrap();
+
+            let edited_by = |user_id: &str, key: &str, value: TemplateValue| {
+                let mut replica = shared.clone();
+                replica.set_operation_context(OperationContext::new(user_id.to_string()));
+                replica.set_metadata(key.to_string(), value).unwrap();
+                // A forged replica need not keep the operations behind its state
+                replica.operation_log.clear();
+                replica
+            };
+            let status = TemplateValue::Text { value: "done".to_string(), timestamp: Utc::now(), user_id: "bob".to_string() };
+            let takeover = CodexAcl::new("bob").to_value("bob").unwrap();
+
+            let stats = manager.merge_codices(vec![
+                edited_by("mallory", "status", status.clone()),
+                edited_by("bob", ACL_FIELD, takeover),
+            ]).await.unwrap();
+            assert_eq!((stats.merged, stats.failed.len()), (0, 2));
+            let local = manager.get_codex(&id).await.unwrap();
+            assert!(local.get_metadata("status").is_none());
+            assert_eq!(acl_of(&local).map(|acl| acl.owner), Some("alice".to_string()));
+
+            let stats = manager.merge_codices(vec![edited_by("bob", "status", status)]).await.unwrap();
+            assert_eq!((stats.merged, stats.failed.len()), (1, 0));
+            assert!(manager.get_codex(&id).await.unwrap().get_metadata("status").is_some());
+        }
+    }
+}
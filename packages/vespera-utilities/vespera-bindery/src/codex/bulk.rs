@@ -0,0 +1,294 @@
+//! Garbage collection, merging and snapshotting across all Codices
+//!
+//! Work that touches every Codex takes the list of Codex `Arc`s under a brief
+//! read lock, does the per-Codex work on the rayon thread pool with no lock
+//! held, and then swaps the results back in under a write lock held only for
+//! the inserts. A Codex changed by someone else in the meantime is not
+//! overwritten: garbage collection leaves it for the next run, and a merge is
+//! applied again on top of the newer copy.
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{acl, trash, CodexChange, CodexEvent};
+#[cfg(feature = "yjs-compat")]
+use crate::crdt::StorageMode;
+use crate::crdt::{CRDTSnapshot, GarbageCollectionStats, VesperaCRDT};
+use crate::{template_of, BinderyError, BinderyResult, CodexId, CodexManager, GarbageCollectionConfig};
+
+/// Garbage collection result for one Codex
+#[derive(Debug)]
+pub struct GcOutcome {
+    pub codex_id: CodexId,
+    /// The collected copy, when collection removed anything
+    pub collected: Option<VesperaCRDT>,
+    /// None when the Codex was under the memory threshold
+    pub stats: Option<GarbageCollectionStats>,
+    pub memory_before: usize,
+    pub memory_after: usize,
+}
+
+/// Collect garbage in each Codex above `config.memory_threshold_bytes`, in parallel
+///
+/// Codices are copied before collection; the originals are left untouched.
+pub fn collect_garbage(
+    codices: &[(CodexId, Arc<VesperaCRDT>)],
+    config: &GarbageCollectionConfig,
+    cutoff: DateTime<Utc>,
+) -> Vec<GcOutcome> {
+    codices.par_iter().map(|(codex_id, crdt)| {
+        let memory_before = crdt.memory_stats().total_size_bytes;
+        if memory_before <= config.memory_threshold_bytes {
+            return GcOutcome { codex_id: *codex_id, collected: None, stats: None, memory_before, memory_after: memory_before };
+        }
+
+        let mut copy = (**crdt).clone();
+        let stats = copy.gc_all_with_limits(cutoff, config.max_operations_per_codex, config.max_tree_tombstones_per_codex);
+        let (collected, memory_after) = if stats.operations_removed > 0 || stats.tree_tombstones_removed > 0 {
+            let memory_after = copy.memory_stats().total_size_bytes;
+            (Some(copy), memory_after)
+        } else {
+            (None, memory_before)
+        };
+        GcOutcome { codex_id: *codex_id, collected, stats: Some(stats), memory_before, memory_after }
+    }).collect()
+}
+
+/// Merge each remote replica into a copy of its local Codex, in parallel
+///
+/// Results are in the order of `pairs`; `Ok(None)` means the remote had
+/// nothing new.
+pub fn merge_remote(pairs: &[(Arc<VesperaCRDT>, VesperaCRDT)]) -> Vec<BinderyResult<Option<VesperaCRDT>>> {
+    pairs.par_iter().map(|(local, remote)| {
+        let mut copy = (**local).clone();
+        let applied = copy.merge(remote)?;
+        // Delta mode joins state and reports no operations
+        let changed = !applied.is_empty() || copy.vector_clock != local.vector_clock;
+        Ok(changed.then_some(copy))
+    }).collect()
+}
+
+/// Snapshots of all `codices`, taken in parallel
+pub fn snapshot_all(codices: &[(CodexId, Arc<VesperaCRDT>)]) -> HashMap<CodexId, CRDTSnapshot> {
+    codices.par_iter().map(|(codex_id, crdt)| (*codex_id, crdt.snapshot())).collect()
+}
+
+/// Outcome of [`CodexManager::merge_codices`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkMergeStats {
+    /// Existing Codices that gained operations
+    pub merged: usize,
+    /// Codices not known locally, added as they were
+    pub created: usize,
+    /// Codices that failed to merge, with the reason
+    pub failed: Vec<(CodexId, String)>,
+}
+
+impl CodexManager {
+    /// The current Codices, taken under a brief read lock
+    pub(crate) async fn codex_list(&self) -> Vec<(CodexId, Arc<VesperaCRDT>)> {
+        let codices = self.inner.codices.read().await;
+        codices.iter().map(|(id, crdt)| (*id, Arc::clone(crdt))).collect()
+    }
+
+    /// Merge replicas received from peers, for example while catching up after being offline
+    ///
+    /// Replicas of Codices not known locally are added as they are. The
+    /// operations a replica brings for a known Codex, or in delta mode the
+    /// changes in its delta, are checked against its access control list,
+    /// and a replica with any the list doesn't allow is not merged. One
+    /// replica failing to merge doesn't stop the others; failures are listed
+    /// in the result.
+    pub async fn merge_codices(&self, remote: Vec<VesperaCRDT>) -> BinderyResult<BulkMergeStats> {
+        let mut stats = BulkMergeStats::default();
+        let (known, new): (Vec<_>, Vec<_>) = {
+            let codices = self.inner.codices.read().await;
+            remote.into_iter()
+                .map(|replica| (codices.get(&replica.codex_id).cloned(), replica))
+                .partition(|(local, _)| local.is_some())
+        };
+        let mut pairs = Vec::new();
+        for (local, replica) in known {
+            let Some(local) = local else { continue };
+            match self.authorize_replica(&local, &replica).await {
+                Ok(()) => pairs.push((local, replica)),
+                Err(e) => stats.failed.push((replica.codex_id, e.to_string())),
+            }
+        }
+
+        let (pairs, results) = tokio::task::spawn_blocking(move || {
+            let results = merge_remote(&pairs);
+            (pairs, results)
+        }).await.map_err(|e| BinderyError::InternalError(e.to_string()))?;
+
+        let mut changed = Vec::new();
+        {
+            let mut codices = self.inner.codices.write().await;
+            for ((original, replica), result) in pairs.iter().zip(results) {
+                let id = replica.codex_id;
+                let merged = match result {
+                    Ok(Some(merged)) => merged,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        stats.failed.push((id, e.to_string()));
+                        continue;
+                    }
+                };
+                match codices.get_mut(&id) {
+                    Some(current) if Arc::ptr_eq(current, original) => *current = Arc::new(merged),
+                    // Changed or deleted while merging; merge again into what is there now
+                    Some(current) => {
+                        if let Err(e) = Arc::make_mut(current).merge(replica) {
+                            stats.failed.push((id, e.to_string()));
+                            continue;
+                        }
+                    }
+                    None => continue,
+                }
+                changed.push((id, Arc::clone(&codices[&id]), false));
+            }
+            for replica in new.into_iter().map(|(_, replica)| replica) {
+                let id = replica.codex_id;
+                // Added since the list was taken, or earlier in this batch
+                if let Some(current) = codices.get_mut(&id) {
+                    if let Err(e) = Arc::make_mut(current).merge(&replica) {
+                        stats.failed.push((id, e.to_string()));
+                        continue;
+                    }
+                    changed.push((id, Arc::clone(current), false));
+                    continue;
+                }
+                let crdt = Arc::new(replica);
+                codices.insert(id, Arc::clone(&crdt));
+                changed.push((id, crdt, true));
+            }
+        }
+
+        {
+            let mut trashed = self.inner.trash.write().await;
+            for (id, crdt, _) in &changed {
+                match trash::trashed_at(crdt) {
+                    Some((trashed_at, _)) => trashed.insert(*id, trashed_at),
+                    None => trashed.remove(id),
+                };
+            }
+        }
+
+        for (id, crdt, created) in changed {
+            if let Some(sync_manager) = &self.inner.sync_manager {
+                sync_manager.register_codex(id, Arc::clone(&crdt)).await?;
+            }
+            // Field-level changes aren't tracked through a merge
+            let change = if created {
+                stats.created += 1;
+                CodexChange::Created { title: crdt.get_title().unwrap_or_default() }
+            } else {
+                stats.merged += 1;
+                CodexChange::Updated { fields: Vec::new() }
+            };
+            self.publish(CodexEvent::new(id, template_of(&crdt), change));
+        }
+        Ok(stats)
+    }
+
+    /// Refuse `replica` if the access control list of `local` doesn't allow
+    /// every operation it has that `local` hasn't seen, or in delta mode
+    /// every change in the delta `local` would take from it
+    async fn authorize_replica(&self, local: &VesperaCRDT, replica: &VesperaCRDT) -> BinderyResult<()> {
+        // Delta mode merges the replica's state, not its operation log
+        #[cfg(feature = "yjs-compat")]
+        if matches!(local.memory_config().storage_mode, StorageMode::Delta { .. }) {
+            let delta = replica.delta_since(&local.vector_clock)?;
+            let refused = acl::refused_in_delta(local, &delta)?;
+            if !refused.is_empty() {
+                let authors: Vec<_> = refused.iter().map(|(user_id, _)| user_id.as_str()).collect();
+                return Err(BinderyError::PermissionDenied(format!(
+                    "Changes by {} in the replica of Codex {} are not allowed by its access control list",
+                    authors.join(", "), replica.codex_id
+                )));
+            }
+            return Ok(());
+        }
+
+        let seen: HashSet<_> = local.operation_log.iter().map(|operation| operation.id).collect();
+        let incoming: Vec<_> = replica.operation_log.iter()
+            .filter(|operation| !seen.contains(&operation.id))
+            .cloned()
+            .collect();
+        let count = incoming.len();
+        let allowed = match &self.inner.sync_manager {
+            Some(sync_manager) => sync_manager.check_codex_acl(&replica.codex_id, incoming).await?,
+            None => incoming,
+        };
+        let refused = count - allowed.iter().filter(|operation| acl::may_apply(local, operation)).count();
+        if refused > 0 {
+            return Err(BinderyError::PermissionDenied(format!(
+                "{} operations in the replica of Codex {} are not allowed by its access control list",
+                refused, replica.codex_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Snapshots of all Codices, including those in the trash
+    pub async fn snapshot_codices(&self) -> BinderyResult<HashMap<CodexId, CRDTSnapshot>> {
+        let codices = self.codex_list().await;
+        tokio::task::spawn_blocking(move || snapshot_all(&codices))
+            .await
+            .map_err(|e| BinderyError::InternalError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crdt::TemplateValue;
+
+    fn codex(title: &str) -> (CodexId, Arc<VesperaCRDT>) {
+        let id = uuid::Uuid::new_v4();
+        let mut crdt = VesperaCRDT::new(id, "alice".to_string());
+        crdt.set_title(title).unwrap();
+        (id, Arc::new(crdt))
+    }
+
+    #[test]
+    fn test_merge_remote_copies_and_skips_unchanged() {
+        let (id, local) = codex("Plan");
+        let mut ahead = (*local).clone();
+        ahead.set_metadata("status".to_string(), TemplateValue::Text {
+            value: "done".to_string(),
+            timestamp: Utc::now(),
+            user_id: "bob".to_string(),
+        }).unwrap();
+        let (_, other) = codex("Other");
+
+        let results = merge_remote(&[
+            (Arc::clone(&local), ahead),
+            (Arc::clone(&local), (*local).clone()),
+            (Arc::clone(&local), (*other).clone()),
+        ]);
+        let merged = results[0].as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(merged.codex_id, id);
+        assert!(merged.get_metadata("status").is_some());
+        assert!(local.get_metadata("status").is_none());
+        assert!(results[1].as_ref().unwrap().is_none());
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_collect_garbage_skips_codices_under_threshold() {
+        let codices = vec![codex("A"), codex("B")];
+        let config = GarbageCollectionConfig { memory_threshold_bytes: usize::MAX, ..Default::default() };
+        let outcomes = collect_garbage(&codices, &config, Utc::now());
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|outcome| outcome.stats.is_none() && outcome.collected.is_none()));
+
+        let snapshots = snapshot_all(&codices);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[&codices[0].0].codex_id, codices[0].0);
+    }
+}
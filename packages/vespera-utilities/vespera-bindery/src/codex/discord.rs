@@ -0,0 +1,206 @@
+//! Discord conversations as Codices
+//!
+//! [`CodexManager::ingest_discord_chunks`] takes the conversation chunks
+//! `vespera-file-ops` cuts from a Discord Chat Exporter HTML export and
+//! creates one Codex per chunk, of the [`DISCORD_CONVERSATION_TEMPLATE_ID`]
+//! template. Participants, time range and topics become metadata fields; the
+//! messages become the [`TRANSCRIPT_FIELD`] text field, one line per message.
+//! Consecutive conversations are linked by [`NEXT_REFERENCE`] and
+//! [`PREVIOUS_REFERENCE`] references so readers can walk the channel in
+//! order.
+
+use chrono::Utc;
+use serde_json::json;
+use vespera_file_ops::chunking::discord::{chunk_discord_export, ConversationChunk, DiscordMessage};
+
+use crate::crdt::{CodexReference, ReferenceType, TemplateValue};
+use crate::templates::{CrdtLayer, FieldDefinition, FieldType, Template, TemplateId};
+use crate::{BinderyResult, CodexId, CodexManager};
+
+/// Template of Codices holding a Discord conversation
+pub const DISCORD_CONVERSATION_TEMPLATE_ID: &str = "vespera.templates.discord_conversation";
+
+/// Text field holding a conversation's messages
+pub const TRANSCRIPT_FIELD: &str = "messages";
+
+/// Reference type from a conversation to the one after it
+pub const NEXT_REFERENCE: &str = "next_conversation";
+
+/// Reference type from a conversation to the one before it
+pub const PREVIOUS_REFERENCE: &str = "previous_conversation";
+
+/// The Discord conversation template
+pub fn conversation_template() -> Template {
+    let mut template = Template::new(
+        TemplateId::new(DISCORD_CONVERSATION_TEMPLATE_ID),
+        "Discord Conversation".to_string(),
+        "A stretch of a Discord channel export".to_string(),
+        "discord_conversation".to_string(),
+    );
+    for (name, field_type, layer, required) in [
+        ("participants", FieldType::Array, CrdtLayer::Metadata, true),
+        ("started_at", FieldType::DateTime, CrdtLayer::Metadata, true),
+        ("ended_at", FieldType::DateTime, CrdtLayer::Metadata, true),
+        ("topics", FieldType::Array, CrdtLayer::Metadata, false),
+        ("message_count", FieldType::Number, CrdtLayer::Metadata, false),
+        ("sequence", FieldType::Number, CrdtLayer::Metadata, false),
+        ("source", FieldType::Text, CrdtLayer::Metadata, false),
+        ("continuation_context", FieldType::LongText, CrdtLayer::Metadata, false),
+        (TRANSCRIPT_FIELD, FieldType::LongText, CrdtLayer::Text, true),
+    ] {
+        template.add_field(name.to_string(), FieldDefinition::structural(field_type, layer, required));
+    }
+    template
+}
+
+/// One transcript line per message: `[time] author: content`, followed by
+/// attachment names
+fn transcript(messages: &[DiscordMessage]) -> String {
+    let mut text = String::new();
+    for message in messages {
+        text.push_str(&format!(
+            "[{}] {}: {}",
+            message.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+            message.author,
+            message.content
+        ));
+        for attachment in &message.attachments {
+            text.push_str(&format!(" [attachment: {}]", attachment.filename));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+impl CodexManager {
+    /// Create a Codex for each conversation chunk, in order, and link
+    /// neighbours; returns the Codex IDs in chunk order
+    ///
+    /// `source` (e.g. the channel name) is stored on each Codex and leads
+    /// its title.
+    pub async fn ingest_discord_chunks(
+        &self,
+        chunks: &[ConversationChunk],
+        source: Option<&str>,
+    ) -> BinderyResult<Vec<CodexId>> {
+        self.register_template(conversation_template()).await?;
+        let user_id = self.inner.config.user_id.clone().unwrap_or_else(|| "system".to_string());
+        let mut ids = Vec::with_capacity(chunks.len());
+
+        for (sequence, chunk) in chunks.iter().enumerate() {
+            let (started_at, ended_at) = chunk.time_range;
+            let title = format!(
+                "{}, {} ({})",
+                source.unwrap_or("Discord conversation"),
+                started_at.format("%Y-%m-%d %H:%M"),
+                chunk.participants.join(", ")
+            );
+            let id = self.create_codex(title, DISCORD_CONVERSATION_TEMPLATE_ID).await?;
+
+            let now = Utc::now();
+            let text = |value: String| TemplateValue::Text { value, timestamp: now, user_id: user_id.clone() };
+            let structured = |value: serde_json::Value| TemplateValue::Structured { value, timestamp: now, user_id: user_id.clone() };
+            let mut fields = vec![
+                ("participants".to_string(), structured(json!(chunk.participants))),
+                ("started_at".to_string(), text(started_at.to_rfc3339())),
+                ("ended_at".to_string(), text(ended_at.to_rfc3339())),
+                ("topics".to_string(), structured(json!(chunk.detected_topics))),
+                ("message_count".to_string(), structured(json!(chunk.messages.len()))),
+                ("sequence".to_string(), structured(json!(sequence))),
+            ];
+            if let Some(source) = source {
+                fields.push(("source".to_string(), text(source.to_string())));
+            }
+            if let Some(context) = &chunk.continuation_context {
+                fields.push(("continuation_context".to_string(), text(context.clone())));
+            }
+            self.set_codex_fields(&id, fields).await?;
+
+            let transcript = transcript(&chunk.messages);
+            self.modify_codex(&id, |crdt| crdt.insert_text(TRANSCRIPT_FIELD.to_string(), 0, transcript)).await?;
+
+            if let Some(&previous) = ids.last() {
+                self.link_conversations(previous, id).await?;
+            }
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /// Chunk a Discord Chat Exporter HTML export and ingest it with
+    /// [`ingest_discord_chunks`](Self::ingest_discord_chunks)
+    pub async fn ingest_discord_export(
+        &self,
+        html: &str,
+        max_tokens_per_chunk: usize,
+        source: Option<&str>,
+    ) -> BinderyResult<Vec<CodexId>> {
+        let chunks = chunk_discord_export(html, true, max_tokens_per_chunk)?;
+        self.ingest_discord_chunks(&chunks, source).await
+    }
+
+    async fn link_conversations(&self, earlier: CodexId, later: CodexId) -> BinderyResult<()> {
+        self.add_reference(CodexReference {
+            from_codex_id: earlier,
+            to_codex_id: later,
+            reference_type: ReferenceType::Custom(NEXT_REFERENCE.to_string()),
+            context: None,
+        }).await?;
+        self.add_reference(CodexReference {
+            from_codex_id: later,
+            to_codex_id: earlier,
+            reference_type: ReferenceType::Custom(PREVIOUS_REFERENCE.to_string()),
+            context: None,
+        }).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use vespera_file_ops::chunking::discord::{Attachment, MessageType};
+
+    fn message(author: &str, minute: u32, content: &str) -> DiscordMessage {
+        let timestamp = Utc.with_ymd_and_hms(2024, 3, 1, 12, minute, 0).unwrap();
+        DiscordMessage {
+            id: format!("{}-{}", author, minute),
+            author: author.to_string(),
+            timestamp,
+            timestamp_raw: timestamp.to_rfc3339(),
+            content: content.to_string(),
+            attachments: Vec::new(),
+            reactions: Vec::new(),
+            reply_to: None,
+            message_type: MessageType::Regular,
+        }
+    }
+
+    #[test]
+    fn test_transcript() {
+        let mut with_file = message("bob", 1, "Here it is");
+        with_file.attachments.push(Attachment {
+            filename: "map.png".to_string(),
+            url: "https://cdn.example/map.png".to_string(),
+            file_type: Some("png".to_string()),
+            size: None,
+        });
+
+        let text = transcript(&[message("alice", 0, "Map is ready?"), with_file]);
+        assert_eq!(
+            text,
+            "[2024-03-01 12:00:00 UTC] alice: Map is ready?\n[2024-03-01 12:01:00 UTC] bob: Here it is [attachment: map.png]\n"
+        );
+    }
+
+    #[test]
+    fn test_conversation_template() {
+        let template = conversation_template();
+        assert_eq!(template.id, TemplateId::new(DISCORD_CONVERSATION_TEMPLATE_ID));
+        let messages = &template.fields[TRANSCRIPT_FIELD];
+        assert_eq!(messages.crdt_layer, CrdtLayer::Text);
+        assert!(messages.required);
+        assert_eq!(template.fields["participants"].crdt_layer, CrdtLayer::Metadata);
+    }
+}
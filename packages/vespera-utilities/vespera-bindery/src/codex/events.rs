@@ -0,0 +1,268 @@
+//! Change events for Codices and tasks
+//!
+//! [`CodexManager::subscribe`](crate::CodexManager::subscribe) streams a
+//! [`CodexEvent`] for every create, field update, delete, trash, restore and
+//! reference change, with the old and new values, so UIs and bindings can
+//! update live instead of polling. Tasks are Codices, so task changes arrive the same way; use
+//! [`CodexEventFilter::tasks`] to receive only those.
+
+use crate::crdt::{CodexReference, TemplateValue};
+use crate::CodexId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Events buffered per subscriber before the slowest starts missing them
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// Template of task Codices
+pub const TASK_TEMPLATE_ID: &str = "vespera.templates.hierarchical_task";
+
+/// A change to one Codex
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodexEvent {
+    pub codex_id: CodexId,
+    /// Template the Codex was created from
+    pub template_id: Option<String>,
+    pub change: CodexChange,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CodexEvent {
+    pub fn new(codex_id: CodexId, template_id: Option<String>, change: CodexChange) -> Self {
+        Self {
+            codex_id,
+            template_id,
+            change,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Whether this is a change to a task
+    pub fn is_task(&self) -> bool {
+        self.template_id.as_deref() == Some(TASK_TEMPLATE_ID)
+    }
+}
+
+/// What changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CodexChange {
+    Created { title: String },
+    /// Fields set in one update
+    Updated { fields: Vec<FieldChange> },
+    Deleted { title: Option<String> },
+    /// Moved to the trash, from where it can still be restored
+    Trashed { title: Option<String> },
+    Restored { title: Option<String> },
+    ReferenceAdded { reference: CodexReference },
+    ReferenceRemoved { reference: CodexReference },
+}
+
+impl CodexChange {
+    pub fn kind(&self) -> CodexChangeKind {
+        match self {
+            CodexChange::Created { .. } => CodexChangeKind::Created,
+            CodexChange::Updated { .. } => CodexChangeKind::Updated,
+            CodexChange::Deleted { .. } => CodexChangeKind::Deleted,
+            CodexChange::Trashed { .. } => CodexChangeKind::Trashed,
+            CodexChange::Restored { .. } => CodexChangeKind::Restored,
+            CodexChange::ReferenceAdded { .. } => CodexChangeKind::ReferenceAdded,
+            CodexChange::ReferenceRemoved { .. } => CodexChangeKind::ReferenceRemoved,
+        }
+    }
+}
+
+/// One field's value before and after an update
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// None when the field was not set before
+    pub old: Option<TemplateValue>,
+    pub new: TemplateValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodexChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    Trashed,
+    Restored,
+    ReferenceAdded,
+    ReferenceRemoved,
+}
+
+/// Which events a subscriber receives
+///
+/// Each criterion left unset matches everything; the default filter passes
+/// every event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodexEventFilter {
+    pub codex_ids: Option<HashSet<CodexId>>,
+    pub template_ids: Option<HashSet<String>>,
+    pub kinds: Option<HashSet<CodexChangeKind>>,
+}
+
+impl CodexEventFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only task changes
+    pub fn tasks() -> Self {
+        Self::new().with_template(TASK_TEMPLATE_ID)
+    }
+
+    /// Also match changes to `codex_id`
+    pub fn with_codex(mut self, codex_id: CodexId) -> Self {
+        self.codex_ids.get_or_insert_with(HashSet::new).insert(codex_id);
+        self
+    }
+
+    /// Also match Codices created from `template_id`
+    pub fn with_template(mut self, template_id: impl Into<String>) -> Self {
+        self.template_ids.get_or_insert_with(HashSet::new).insert(template_id.into());
+        self
+    }
+
+    /// Also match changes of `kind`
+    pub fn with_kind(mut self, kind: CodexChangeKind) -> Self {
+        self.kinds.get_or_insert_with(HashSet::new).insert(kind);
+        self
+    }
+
+    pub fn matches(&self, event: &CodexEvent) -> bool {
+        let template_matches = match (&self.template_ids, &event.template_id) {
+            (None, _) => true,
+            (Some(template_ids), Some(template_id)) => template_ids.contains(template_id),
+            (Some(_), None) => false,
+        };
+        template_matches
+            && self.codex_ids.as_ref().is_none_or(|ids| ids.contains(&event.codex_id))
+            && self.kinds.as_ref().is_none_or(|kinds| kinds.contains(&event.change.kind()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn created(template_id: Option<&str>) -> CodexEvent {
+        CodexEvent::new(
+            Uuid::new_v4(),
+            template_id.map(str::to_string),
+            CodexChange::Created { title: "Chapter 3".to_string() },
+        )
+    }
+
+    #[test]
+    fn test_default_filter_matches_everything() {
+        let filter = CodexEventFilter::new();
+        assert!(filter.matches(&created(None)));
+        assert!(filter.matches(&created(Some(TASK_TEMPLATE_ID))));
+    }
+
+    #[test]
+    fn test_filter_criteria_combine() {
+        let task = created(Some(TASK_TEMPLATE_ID));
+        let note = created(Some("vespera.templates.note"));
+
+        let tasks = CodexEventFilter::tasks();
+        assert!(tasks.matches(&task));
+        assert!(!tasks.matches(&note));
+        assert!(!tasks.matches(&created(None)));
+
+        let deleted_tasks = CodexEventFilter::tasks().with_kind(CodexChangeKind::Deleted);
+        assert!(!deleted_tasks.matches(&task));
+
+        let one = CodexEventFilter::new().with_codex(note.codex_id);
+        assert!(one.matches(&note));
+        assert!(!one.matches(&task));
+    }
+
+    mod manager {
+        use super::*;
+        use crate::crdt::ReferenceType;
+        use crate::templates::{Template, TemplateId};
+        use crate::CodexManager;
+        use futures::{Stream, StreamExt};
+        use std::time::Duration;
+
+        async fn manager() -> CodexManager {
+            let manager = CodexManager::new().unwrap();
+            manager.register_template(Template::new(
+                TemplateId::new("test.note"),
+                "Note".to_string(),
+                "A note".to_string(),
+                "note".to_string(),
+            )).await.unwrap();
+            manager
+        }
+
+        fn text(value: &str) -> TemplateValue {
+            TemplateValue::Text { value: value.to_string(), timestamp: Utc::now(), user_id: "alice".to_string() }
+        }
+
+        async fn next(events: &mut (impl Stream<Item = CodexEvent> + Unpin)) -> CodexEvent {
+            tokio::time::timeout(Duration::from_secs(5), events.next()).await
+                .expect("event should arrive")
+                .expect("stream should be open")
+        }
+
+        #[tokio::test]
+        async fn test_subscriber_sees_changes_in_order() {
+            let manager = manager().await;
+            let mut events = Box::pin(manager.subscribe(CodexEventFilter::new()));
+
+            let note = manager.create_codex("Draft", "test.note").await.unwrap();
+            let other = manager.create_codex("Other", "test.note").await.unwrap();
+            manager.set_codex_fields(&note, [("title".to_string(), text("Final"))]).await.unwrap();
+            let reference = CodexReference {
+                from_codex_id: note,
+                to_codex_id: other,
+                reference_type: ReferenceType::DependsOn,
+                context: None,
+            };
+            manager.add_reference(reference.clone()).await.unwrap();
+            manager.remove_reference(reference.clone()).await.unwrap();
+            manager.purge_codex(&note).await.unwrap();
+
+            let event = next(&mut events).await;
+            assert_eq!((event.codex_id, event.template_id.as_deref()), (note, Some("test.note")));
+            assert_eq!(event.change, CodexChange::Created { title: "Draft".to_string() });
+            assert_eq!(next(&mut events).await.codex_id, other);
+
+            let event = next(&mut events).await;
+            let CodexChange::Updated { fields } = event.change else { panic!("expected an update, got {:?}", event.change) };
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].field, "title");
+            assert!(matches!(&fields[0].old, Some(TemplateValue::Text { value, .. }) if value == "Draft"));
+            assert!(matches!(&fields[0].new, TemplateValue::Text { value, .. } if value == "Final"));
+
+            assert_eq!(next(&mut events).await.change, CodexChange::ReferenceAdded { reference: reference.clone() });
+            assert_eq!(next(&mut events).await.change, CodexChange::ReferenceRemoved { reference });
+            let event = next(&mut events).await;
+            assert_eq!((event.codex_id, event.change), (note, CodexChange::Deleted { title: Some("Final".to_string()) }));
+        }
+
+        #[tokio::test]
+        async fn test_subscriber_only_sees_matching_changes() {
+            let manager = manager().await;
+            let watched = manager.create_codex("Watched", "test.note").await.unwrap();
+            let mut events = Box::pin(manager.subscribe(
+                CodexEventFilter::new().with_codex(watched).with_kind(CodexChangeKind::Deleted),
+            ));
+
+            let other = manager.create_codex("Other", "test.note").await.unwrap();
+            manager.set_codex_fields(&watched, [("title".to_string(), text("Renamed"))]).await.unwrap();
+            manager.purge_codex(&other).await.unwrap();
+            manager.purge_codex(&watched).await.unwrap();
+
+            let event = next(&mut events).await;
+            assert_eq!((event.codex_id, event.change.kind()), (watched, CodexChangeKind::Deleted));
+        }
+    }
+}
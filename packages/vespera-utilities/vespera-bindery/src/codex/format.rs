@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::{BinderyResult, crdt::VesperaCRDT};
+use crate::compression::{self, CompressionCodec, CompressionConfig};
 
 /// Codex serialization format options
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,7 +28,7 @@ pub enum CodexFormat {
 #[derive(Debug)]
 pub struct CodexSerializer {
     format: CodexFormat,
-    compress: bool,
+    compression: CompressionConfig,
 }
 
 impl CodexSerializer {
@@ -35,13 +36,20 @@ impl CodexSerializer {
     pub fn new(format: CodexFormat) -> Self {
         Self {
             format,
-            compress: false,
+            compression: CompressionConfig::none(),
         }
     }
 
-    /// Enable/disable compression
+    /// Enable/disable compression with the default codec (zstd)
     pub fn with_compression(mut self, compress: bool) -> Self {
-        self.compress = compress;
+        self.compression = if compress { CompressionConfig::default() } else { CompressionConfig::none() };
+        self
+    }
+
+    /// Compress with the given codec, for example a workspace's
+    /// [`BinderyConfig::effective_compression`](crate::BinderyConfig::effective_compression)
+    pub fn with_codec(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
         self
     }
 
@@ -74,15 +82,13 @@ impl CodexSerializer {
             }
         };
 
-        if self.compress {
-            // Implement compression using LZ4 (fast) or Zstd (better compression)
-            // Using LZ4 for speed, but Zstd could be used for better compression ratios
-
-            let compressed = lz4_flex::compress_prepend_size(&bytes);
+        if self.is_compressed() {
+            let compressed = self.compression.compress(&bytes, "codex")?;
             tracing::debug!(
-                "Compressed {} bytes to {} bytes ({:.1}% reduction)",
+                "Compressed {} bytes to {} bytes with {} ({:.1}% reduction)",
                 bytes.len(),
                 compressed.len(),
+                self.compression.codec.as_str(),
                 (1.0 - compressed.len() as f64 / bytes.len() as f64) * 100.0
             );
             Ok(compressed)
@@ -92,11 +98,17 @@ impl CodexSerializer {
     }
 
     /// Deserialize bytes to a CRDT
+    ///
+    /// Compressed data is read with the codec it was written with, whatever
+    /// this serializer's codec is.
     pub fn deserialize(&self, bytes: &[u8]) -> BinderyResult<VesperaCRDT> {
         let decompressed_bytes;
-        let bytes = if self.compress {
-            // Implement decompression using LZ4
-            decompressed_bytes = lz4_flex::decompress_size_prepended(bytes)
+        let bytes = if compression::is_compressed(bytes) {
+            decompressed_bytes = compression::decompress(bytes, "codex", compression::MAX_CODEX_BYTES)?;
+            &decompressed_bytes
+        } else if self.is_compressed() {
+            // Written before codecs were tagged, when LZ4 was the only one
+            decompressed_bytes = compression::decompress_lz4(bytes, compression::MAX_CODEX_BYTES)
                 .map_err(|e| crate::BinderyError::DeserializationError(
                     format!("Failed to decompress data: {}", e)
                 ))?;
@@ -163,7 +175,12 @@ impl CodexSerializer {
 
     /// Check if compression is enabled
     pub fn is_compressed(&self) -> bool {
-        self.compress
+        self.compression.codec != CompressionCodec::None
+    }
+
+    /// The codec used when serializing
+    pub fn codec(&self) -> CompressionCodec {
+        self.compression.codec
     }
 
     /// Get the estimated size reduction from using this format
@@ -196,7 +213,11 @@ impl CodexSerializer {
     /// Create a serializer optimized for the given data size
     pub fn optimized_for_size(size_bytes: usize) -> Self {
         let format = Self::recommend_format_for_size(size_bytes);
-        let compress = matches!(format, CodexFormat::CompressedBinary);
-        Self { format, compress }
+        let compression = if matches!(format, CodexFormat::CompressedBinary) {
+            CompressionConfig::default()
+        } else {
+            CompressionConfig::none()
+        };
+        Self { format, compression }
     }
 }
\ No newline at end of file
@@ -0,0 +1,362 @@
+//! Importing documents as Codex trees
+//!
+//! [`CodexManager::import_document`] reads a file with `vespera-file-ops`,
+//! chunks it at paragraph boundaries and creates one Codex for the document
+//! with a child Codex per section, in document order, below it in the tree
+//! layer. Sections are titled by their nearest Markdown heading.
+//! The whole tree is built before any of it is added to the manager, which
+//! publishes a `Created` event per Codex, document first.
+//! [`CodexManager::import_directory`] imports every text document in a
+//! folder the same way.
+//!
+//! With a RAG service attached through
+//! [`CodexManager::attach_rag_service`], the new sections are indexed in the
+//! background; shutdown waits for that indexing like it does for task
+//! executions.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use vespera_file_ops::chunking::{chunk_document, ChunkStrategy, ChunkingConfig, DocumentChunk, DocumentFormat};
+use vespera_file_ops::FileReader;
+
+use crate::crdt::{TemplateValue, VesperaCRDT};
+use crate::rag::{BinderySource, DocumentType};
+use crate::templates::{CrdtLayer, FieldDefinition, FieldType, Template, TemplateId};
+use crate::{BinderyError, BinderyResult, CodexId, CodexManager};
+
+/// Template used when the caller does not name one
+pub const IMPORTED_DOCUMENT_TEMPLATE_ID: &str = "vespera.templates.imported_document";
+
+/// Text field holding a section's content
+pub const CONTENT_FIELD: &str = "content";
+
+/// Extensions [`CodexManager::import_directory`] picks up
+const IMPORTABLE_EXTENSIONS: [&str; 6] = ["md", "markdown", "mdx", "txt", "rst", "adoc"];
+
+/// Codices created by one import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentImport {
+    /// The Codex standing for the whole document
+    pub document: CodexId,
+    /// Its section Codices, in document order
+    pub sections: Vec<CodexId>,
+    /// Whether the sections were queued for RAG indexing
+    pub indexing_queued: bool,
+}
+
+/// An import built but not yet added to the manager
+struct DocumentTree {
+    document: VesperaCRDT,
+    sections: Vec<VesperaCRDT>,
+    /// `(id, title, content)` of each section, for indexing
+    documents: Vec<(CodexId, String, String)>,
+}
+
+/// The default template for imported documents and their sections
+pub fn imported_document_template() -> Template {
+    let mut template = Template::new(
+        TemplateId::new(IMPORTED_DOCUMENT_TEMPLATE_ID),
+        "Imported Document".to_string(),
+        "A document imported from a file, or one of its sections".to_string(),
+        "imported_document".to_string(),
+    );
+    for (name, field_type, layer, required) in [
+        ("source_path", FieldType::Text, CrdtLayer::Metadata, true),
+        ("section_count", FieldType::Number, CrdtLayer::Metadata, false),
+        ("section_index", FieldType::Number, CrdtLayer::Metadata, false),
+        ("heading_path", FieldType::Array, CrdtLayer::Metadata, false),
+        ("language", FieldType::Text, CrdtLayer::Metadata, false),
+        (CONTENT_FIELD, FieldType::LongText, CrdtLayer::Text, false),
+    ] {
+        template.add_field(name.to_string(), FieldDefinition::structural(field_type, layer, required));
+    }
+    template
+}
+
+/// Chunking settings for importing `path`: paragraph sections without
+/// overlap, so no text ends up in two Codices
+pub fn import_chunking_config(path: &Path) -> ChunkingConfig {
+    let format = match extension(path).as_str() {
+        "md" | "markdown" | "mdx" => DocumentFormat::Markdown,
+        _ => DocumentFormat::PlainText,
+    };
+    ChunkingConfig {
+        chunk_strategy: ChunkStrategy::ParagraphBoundary,
+        overlap_size: 0,
+        format,
+        ..Default::default()
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default()
+}
+
+/// The nearest heading, or the document title and section number
+fn section_title(document_title: &str, chunk: &DocumentChunk) -> String {
+    match chunk.metadata.heading_path.last() {
+        Some(heading) => heading.clone(),
+        None => format!(
+            "{} ({}/{})",
+            document_title,
+            chunk.metadata.chunk_index + 1,
+            chunk.metadata.total_chunks
+        ),
+    }
+}
+
+impl CodexManager {
+    /// Import the file at `path` as a document Codex with one child Codex
+    /// per section
+    ///
+    /// `template_id` applies to the document and its sections; it defaults
+    /// to [`IMPORTED_DOCUMENT_TEMPLATE_ID`], registered on first use. The
+    /// document and its sections are added to the manager together once all
+    /// of them are built, so a failed import creates no Codices.
+    pub async fn import_document(&self, path: impl AsRef<Path>, template_id: Option<&str>) -> BinderyResult<DocumentImport> {
+        let path = path.as_ref();
+        self.import_document_with_config(path, template_id, &import_chunking_config(path)).await
+    }
+
+    /// [`import_document`](Self::import_document) with custom chunking
+    pub async fn import_document_with_config(
+        &self,
+        path: impl AsRef<Path>,
+        template_id: Option<&str>,
+        config: &ChunkingConfig,
+    ) -> BinderyResult<DocumentImport> {
+        let path = path.as_ref();
+        let text = FileReader::new(path)?.read_string()?;
+        let chunks = chunk_document(&text, config)?;
+
+        let template_id = match template_id {
+            Some(template_id) => template_id.to_string(),
+            None => {
+                self.register_template(imported_document_template()).await?;
+                IMPORTED_DOCUMENT_TEMPLATE_ID.to_string()
+            }
+        };
+        let registry_id = TemplateId::new(template_id.clone());
+        if self.inner.templates.read().await.get(&registry_id).is_none() {
+            return Err(BinderyError::TemplateNotFound(registry_id));
+        }
+
+        let DocumentTree { document, sections, documents } = self.build_document_tree(path, &template_id, chunks)?;
+        let document_id = document.codex_id;
+        let section_ids: Vec<CodexId> = sections.iter().map(|section| section.codex_id).collect();
+
+        let mut codices = Vec::with_capacity(sections.len() + 1);
+        codices.push(document);
+        codices.extend(sections);
+        self.insert_new_codices(codices).await?;
+
+        let indexing_queued = self.queue_import_indexing(documents, &template_id, DocumentType::from_extension(&extension(path)));
+        Ok(DocumentImport { document: document_id, sections: section_ids, indexing_queued })
+    }
+
+    /// The document Codex and its section Codices, built without touching
+    /// the manager so a failure leaves nothing behind
+    fn build_document_tree(&self, path: &Path, template_id: &str, chunks: Vec<DocumentChunk>) -> BinderyResult<DocumentTree> {
+        let user_id = self.inner.config.user_id.clone().unwrap_or_else(|| "system".to_string());
+        let now = Utc::now();
+        let text_value = |value: String| TemplateValue::Text { value, timestamp: now, user_id: user_id.clone() };
+        let structured = |value: serde_json::Value| TemplateValue::Structured { value, timestamp: now, user_id: user_id.clone() };
+        let source_path = path.display().to_string();
+
+        let title = path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| source_path.clone());
+        let mut document = self.new_codex_crdt(Uuid::new_v4(), &title, template_id)?;
+        document.set_metadata("source_path".to_string(), text_value(source_path.clone()))?;
+        document.set_metadata("section_count".to_string(), structured(json!(chunks.len())))?;
+
+        let mut sections = Vec::with_capacity(chunks.len());
+        let mut documents = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let section_title = section_title(&title, &chunk);
+            let mut section = self.new_codex_crdt(Uuid::new_v4(), &section_title, template_id)?;
+
+            section.set_metadata("source_path".to_string(), text_value(source_path.clone()))?;
+            section.set_metadata("section_index".to_string(), structured(json!(chunk.metadata.chunk_index)))?;
+            section.set_metadata("heading_path".to_string(), structured(json!(chunk.metadata.heading_path)))?;
+            if let Some(language) = &chunk.metadata.language {
+                section.set_metadata("language".to_string(), text_value(language.clone()))?;
+            }
+            section.insert_text(CONTENT_FIELD.to_string(), 0, chunk.content.clone())?;
+
+            document.add_child(document.codex_id, sections.len(), section.codex_id)?;
+            documents.push((section.codex_id, section_title, chunk.content));
+            sections.push(section);
+        }
+
+        Ok(DocumentTree { document, sections, documents })
+    }
+
+    /// Import every document below `dir` with
+    /// [`import_document`](Self::import_document), in path order
+    ///
+    /// Files with other extensions than Markdown, plain text, reStructuredText
+    /// or AsciiDoc are skipped. Stops at the first file that fails: that
+    /// file leaves no Codices behind, but the files before it stay imported.
+    pub async fn import_directory(&self, dir: impl AsRef<Path>, template_id: Option<&str>) -> BinderyResult<Vec<DocumentImport>> {
+        let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(dir.as_ref())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| BinderyError::IoError(format!("Failed to list {}: {}", dir.as_ref().display(), e)))?
+            .into_iter()
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| IMPORTABLE_EXTENSIONS.contains(&extension(path).as_str()))
+            .collect();
+        paths.sort();
+
+        let mut imports = Vec::with_capacity(paths.len());
+        for path in paths {
+            imports.push(self.import_document(&path, template_id).await?);
+        }
+        Ok(imports)
+    }
+
+    /// Index `(id, title, content)` sections in the background, if a RAG
+    /// service is attached
+    fn queue_import_indexing(
+        &self,
+        sections: Vec<(CodexId, String, String)>,
+        template_id: &str,
+        document_type: DocumentType,
+    ) -> bool {
+        let Some(rag) = self.inner.rag_service.get().cloned() else {
+            return false;
+        };
+        let tags = vec!["codex".to_string(), template_id.to_string()];
+        crate::observability::spawn_traced(self.execution_tracker().track(async move {
+            for (id, title, content) in sections {
+                let result = rag
+                    .upsert_bindery_document(BinderySource::Codex(id), title, content, document_type, tags.clone())
+                    .await;
+                if let Err(e) = result {
+                    tracing::warn!(codex_id = %id, error = %e, "Failed to index imported section");
+                }
+            }
+        }));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections_follow_headings() {
+        let markdown = "# Setting\n\nThe city of Vesper.\n\n## Districts\n\nThe docks and the hill.";
+        let config = ChunkingConfig { max_chunk_size: 30, ..import_chunking_config(Path::new("notes.md")) };
+        let chunks = chunk_document(markdown, &config).unwrap();
+        let titles: Vec<String> = chunks.iter().map(|chunk| section_title("notes", chunk)).collect();
+
+        assert_eq!(titles.first().map(String::as_str), Some("Setting"));
+        assert_eq!(titles.last().map(String::as_str), Some("Districts"));
+        // Without overlap each paragraph lands in exactly one section
+        assert_eq!(chunks.iter().filter(|chunk| chunk.content.contains("docks")).count(), 1);
+    }
+
+    #[test]
+    fn test_untitled_sections_are_numbered() {
+        let config = ChunkingConfig { max_chunk_size: 20, ..import_chunking_config(Path::new("log.txt")) };
+        assert_eq!(config.format, DocumentFormat::PlainText);
+        let chunks = chunk_document("First paragraph here.\n\nSecond paragraph here.", &config).unwrap();
+        assert_eq!(section_title("log", &chunks[0]), format!("log (1/{})", chunks.len()));
+    }
+
+    fn text_field(crdt: &VesperaCRDT, field: &str) -> Option<String> {
+        match crdt.get_metadata(field) {
+            Some(TemplateValue::Text { value, .. }) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_document_builds_ordered_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("setting.md");
+        std::fs::write(&path, "# Setting\n\nThe city of Vesper.\n\n## Districts\n\nThe docks and the hill.").unwrap();
+        let manager = CodexManager::new().unwrap();
+
+        let config = ChunkingConfig { max_chunk_size: 30, ..import_chunking_config(&path) };
+        let import = manager.import_document_with_config(&path, None, &config).await.unwrap();
+        assert!(import.sections.len() >= 2);
+        assert!(!import.indexing_queued);
+        assert_eq!(manager.codex_children(&import.document).await.unwrap(), import.sections);
+        assert_eq!(manager.list_codices().await.len(), import.sections.len() + 1);
+
+        let document = manager.get_codex(&import.document).await.unwrap();
+        assert_eq!(document.get_title().as_deref(), Some("setting"));
+        assert_eq!(text_field(&document, "source_path"), Some(path.display().to_string()));
+        assert_eq!(text_field(&document, "template_id").as_deref(), Some(IMPORTED_DOCUMENT_TEMPLATE_ID));
+
+        let first = manager.get_codex(&import.sections[0]).await.unwrap();
+        let last = manager.get_codex(import.sections.last().unwrap()).await.unwrap();
+        assert_eq!(first.get_title().as_deref(), Some("Setting"));
+        assert_eq!(last.get_title().as_deref(), Some("Districts"));
+        assert!(first.get_text(CONTENT_FIELD).unwrap().to_string().contains("Vesper"));
+        assert!(last.get_text(CONTENT_FIELD).unwrap().to_string().contains("docks"));
+        for (index, id) in import.sections.iter().enumerate() {
+            let section = manager.get_codex(id).await.unwrap();
+            assert!(matches!(
+                section.get_metadata("section_index"),
+                Some(TemplateValue::Structured { value, .. }) if *value == json!(index)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_creates_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "Some notes.").unwrap();
+        let manager = CodexManager::new().unwrap();
+
+        let result = manager.import_document(&path, Some("no.such.template")).await;
+        assert!(matches!(result, Err(BinderyError::TemplateNotFound(_))));
+        assert!(manager.import_document(dir.path().join("missing.txt"), None).await.is_err());
+        assert!(manager.list_codices().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_directory_goes_in_path_order_and_stops_at_first_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("b.md"), "Second.").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "First.").unwrap();
+        std::fs::write(dir.path().join("sub/c.md"), "Third.").unwrap();
+        std::fs::write(dir.path().join("image.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let manager = CodexManager::new().unwrap();
+
+        let imports = manager.import_directory(dir.path(), None).await.unwrap();
+        let mut titles = Vec::new();
+        for import in &imports {
+            titles.push(manager.get_codex(&import.document).await.unwrap().get_title().unwrap());
+        }
+        assert_eq!(titles, ["a", "b", "c"]);
+
+        // Sorted between b.md and sub/c.md; not valid UTF-8
+        std::fs::write(dir.path().join("bz.txt"), [0xff, 0xfe, 0xfd]).unwrap();
+        let other = CodexManager::new().unwrap();
+        assert!(other.import_directory(dir.path(), None).await.is_err());
+
+        // a.txt and b.md stay imported, bz.txt left nothing, sub/c.md was not reached
+        let mut titles = Vec::new();
+        for id in other.list_codices().await {
+            titles.push(other.get_codex(&id).await.unwrap().get_title().unwrap());
+        }
+        titles.sort();
+        assert_eq!(titles, ["a", "a (1/1)", "b", "b (1/1)"]);
+    }
+}
@@ -0,0 +1,351 @@
+//! Crash-safe Codex file writes
+//!
+//! [`CodexJournal`] writes files under a root directory so that a crash
+//! mid-save never leaves a half-written Codex behind. Each write goes
+//! through three durable steps:
+//!
+//! 1. **intent**: a record naming the target file and the SHA-256 of the
+//!    new contents is written to `.journal/<id>.intent`,
+//! 2. **write**: the contents are written to `.journal/<id>.data` and
+//!    renamed over the target, which is atomic on the same filesystem,
+//! 3. **commit**: the intent record is removed.
+//!
+//! Every step is fsynced before the next starts. After a crash,
+//! [`CodexJournal::recover`] finishes writes whose data reached the disk
+//! intact and discards the rest, so each target holds either its old or its
+//! new contents. Run it on startup, before reading any Codex files.
+//!
+//! Codices are still kept in memory by `CodexManager`; the journal is the
+//! write path file-backed persistence is meant to go through.
+//!
+//! ```rust,no_run
+//! # use vespera_bindery::codex::{CodexFormat, CodexSerializer, journal::CodexJournal};
+//! # fn example(crdt: &vespera_bindery::crdt::VesperaCRDT) -> vespera_bindery::BinderyResult<()> {
+//! let journal = CodexJournal::open(".vespera/codices")?;
+//! let report = journal.recover()?;
+//! for path in &report.completed {
+//!     println!("finished interrupted save of {}", path.display());
+//! }
+//!
+//! let bytes = CodexSerializer::new(CodexFormat::Binary).serialize(crdt)?;
+//! journal.write(format!("{}.codex", crdt.codex_id), &bytes)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{BinderyError, BinderyResult};
+
+/// Journal directory inside the root
+pub const JOURNAL_DIR: &str = ".journal";
+
+const INTENT_EXTENSION: &str = "intent";
+const DATA_EXTENSION: &str = "data";
+
+/// A write that has started but not committed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Intent {
+    /// Target, relative to the journal root
+    target: PathBuf,
+    /// SHA-256 of the new contents, hex-encoded
+    sha256: String,
+    started_at: DateTime<Utc>,
+}
+
+/// What [`CodexJournal::recover`] did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Files whose interrupted write was finished from the journal
+    pub completed: Vec<PathBuf>,
+    /// Files whose interrupted write was discarded, keeping their previous contents
+    pub discarded: Vec<PathBuf>,
+}
+
+impl RecoveryReport {
+    /// Whether any file was repaired
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty() && self.discarded.is_empty()
+    }
+}
+
+/// Journaled writer for files under one root directory
+#[derive(Debug, Clone)]
+pub struct CodexJournal {
+    root: PathBuf,
+}
+
+impl CodexJournal {
+    /// Journal writes under `root`, creating it and its `.journal` directory
+    /// if needed
+    pub fn open(root: impl Into<PathBuf>) -> BinderyResult<Self> {
+        let root = root.into();
+        let journal_dir = root.join(JOURNAL_DIR);
+        fs::create_dir_all(&journal_dir).map_err(|e| io_error(&journal_dir, e))?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Replace the file at `target` (relative to the root) with `contents`
+    ///
+    /// When this returns the new contents are durable; if the process dies
+    /// first, [`recover`](Self::recover) leaves either the old or the new
+    /// contents in place.
+    pub fn write(&self, target: impl AsRef<Path>, contents: &[u8]) -> BinderyResult<()> {
+        let target = relative_target(target.as_ref())?;
+        let id = self.begin(&target, contents)?;
+        self.stage(id, contents)?;
+        self.install(id, &target)?;
+        self.commit(id)
+    }
+
+    /// Finish or discard writes interrupted by a crash
+    ///
+    /// An interrupted write is finished when its data file matches the
+    /// checksum in its intent record, and discarded otherwise; the target
+    /// is only ever replaced by complete contents.
+    pub fn recover(&self) -> BinderyResult<RecoveryReport> {
+        let journal_dir = self.journal_dir();
+        let entries = fs::read_dir(&journal_dir).map_err(|e| io_error(&journal_dir, e))?;
+        let mut report = RecoveryReport::default();
+        let mut intents = Vec::new();
+        let mut data_files = Vec::new();
+
+        for entry in entries {
+            let path = entry.map_err(|e| io_error(&journal_dir, e))?.path();
+            let id = path.file_stem().and_then(|s| s.to_str()).and_then(|s| Uuid::parse_str(s).ok());
+            match (id, path.extension().and_then(|e| e.to_str())) {
+                (Some(id), Some(INTENT_EXTENSION)) => intents.push(id),
+                (Some(id), Some(DATA_EXTENSION)) => data_files.push(id),
+                _ => warn!("Ignoring unexpected file in Codex journal: {}", path.display()),
+            }
+        }
+
+        for id in &intents {
+            let intent_path = self.intent_path(*id);
+            let intent = match fs::read(&intent_path).map(|bytes| serde_json::from_slice::<Intent>(&bytes)) {
+                Ok(Ok(intent)) if relative_target(&intent.target).is_ok() => intent,
+                // The intent record itself was cut short, so nothing was written yet
+                _ => {
+                    warn!("Removing unreadable Codex journal record {}", intent_path.display());
+                    remove_if_exists(&self.data_path(*id))?;
+                    remove_if_exists(&intent_path)?;
+                    continue;
+                }
+            };
+
+            let target_path = self.root.join(&intent.target);
+            let data_path = self.data_path(*id);
+            if data_path.exists() {
+                let data = fs::read(&data_path).map_err(|e| io_error(&data_path, e))?;
+                if checksum(&data) == intent.sha256 {
+                    self.install(*id, &intent.target)?;
+                    info!("Finished interrupted write of {}", target_path.display());
+                    report.completed.push(intent.target);
+                } else {
+                    remove_if_exists(&data_path)?;
+                    warn!("Discarded incomplete write of {}", target_path.display());
+                    report.discarded.push(intent.target);
+                }
+            } else if !file_matches(&target_path, &intent.sha256)? {
+                // Crashed before the data was staged; the target is untouched
+                warn!("Discarded interrupted write of {}", target_path.display());
+                report.discarded.push(intent.target);
+            }
+            // Otherwise the rename happened and only the commit was lost
+            self.commit(*id)?;
+        }
+
+        // Data files without an intent can't be installed and were never visible
+        for id in data_files.iter().filter(|id| !intents.contains(id)) {
+            remove_if_exists(&self.data_path(*id))?;
+        }
+
+        Ok(report)
+    }
+
+    /// Step 1: durably record the intent to write `contents` to `target`
+    fn begin(&self, target: &Path, contents: &[u8]) -> BinderyResult<Uuid> {
+        let id = Uuid::new_v4();
+        let intent = Intent {
+            target: target.to_path_buf(),
+            sha256: checksum(contents),
+            started_at: Utc::now(),
+        };
+        let record = serde_json::to_vec(&intent).map_err(|e| BinderyError::SerializationError(e.to_string()))?;
+        write_synced(&self.intent_path(id), &record)?;
+        sync_dir(&self.journal_dir())?;
+        Ok(id)
+    }
+
+    /// Step 2a: durably write the new contents beside the journal
+    fn stage(&self, id: Uuid, contents: &[u8]) -> BinderyResult<()> {
+        write_synced(&self.data_path(id), contents)
+    }
+
+    /// Step 2b: move the staged contents over the target
+    fn install(&self, id: Uuid, target: &Path) -> BinderyResult<()> {
+        let target_path = self.root.join(target);
+        let target_dir = target_path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(target_dir).map_err(|e| io_error(target_dir, e))?;
+        fs::rename(self.data_path(id), &target_path).map_err(|e| io_error(&target_path, e))?;
+        sync_dir(target_dir)
+    }
+
+    /// Step 3: forget the write
+    fn commit(&self, id: Uuid) -> BinderyResult<()> {
+        remove_if_exists(&self.intent_path(id))?;
+        sync_dir(&self.journal_dir())
+    }
+
+    fn journal_dir(&self) -> PathBuf {
+        self.root.join(JOURNAL_DIR)
+    }
+
+    fn intent_path(&self, id: Uuid) -> PathBuf {
+        self.journal_dir().join(format!("{}.{}", id, INTENT_EXTENSION))
+    }
+
+    fn data_path(&self, id: Uuid) -> PathBuf {
+        self.journal_dir().join(format!("{}.{}", id, DATA_EXTENSION))
+    }
+}
+
+/// `target` if it is a plain relative path that stays under the root
+fn relative_target(target: &Path) -> BinderyResult<PathBuf> {
+    let plain = target.components().all(|c| matches!(c, Component::Normal(_)));
+    if !plain || target.as_os_str().is_empty() || target.starts_with(JOURNAL_DIR) {
+        return Err(BinderyError::InvalidInput(format!(
+            "Journaled writes need a relative path under the root, got {}",
+            target.display()
+        )));
+    }
+    Ok(target.to_path_buf())
+}
+
+fn checksum(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+fn file_matches(path: &Path, sha256: &str) -> BinderyResult<bool> {
+    match fs::read(path) {
+        Ok(contents) => Ok(checksum(&contents) == sha256),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(io_error(path, e)),
+    }
+}
+
+fn write_synced(path: &Path, contents: &[u8]) -> BinderyResult<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|e| io_error(path, e))?;
+    file.write_all(contents).map_err(|e| io_error(path, e))?;
+    file.sync_all().map_err(|e| io_error(path, e))
+}
+
+/// Make renames and removals in `dir` durable
+fn sync_dir(dir: &Path) -> BinderyResult<()> {
+    // Windows can't open directories as files; its renames are durable once they return
+    #[cfg(unix)]
+    std::fs::File::open(dir).and_then(|d| d.sync_all()).map_err(|e| io_error(dir, e))?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> BinderyResult<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(path, e)),
+        _ => Ok(()),
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> BinderyError {
+    BinderyError::IoError(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn journal() -> (TempDir, CodexJournal) {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = CodexJournal::open(temp_dir.path()).unwrap();
+        (temp_dir, journal)
+    }
+
+    fn journal_is_empty(journal: &CodexJournal) -> bool {
+        fs::read_dir(journal.journal_dir()).unwrap().next().is_none()
+    }
+
+    #[test]
+    fn test_write_replaces_contents_and_commits() {
+        let (_temp_dir, journal) = journal();
+        journal.write("chapters/one.codex", b"first draft").unwrap();
+        journal.write("chapters/one.codex", b"second draft").unwrap();
+
+        assert_eq!(fs::read(journal.root().join("chapters/one.codex")).unwrap(), b"second draft");
+        assert!(journal_is_empty(&journal));
+        assert!(journal.recover().unwrap().is_empty());
+
+        for bad in ["../escape.codex", "/tmp/absolute.codex", ".journal/x.data", ""] {
+            assert!(matches!(journal.write(bad, b"x"), Err(BinderyError::InvalidInput(_))), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_recovery_finishes_staged_writes() {
+        let (_temp_dir, journal) = journal();
+        journal.write("a.codex", b"old").unwrap();
+
+        // Crashed after staging, before the rename
+        let id = journal.begin(Path::new("a.codex"), b"new").unwrap();
+        journal.stage(id, b"new").unwrap();
+
+        let report = journal.recover().unwrap();
+        assert_eq!(report.completed, vec![PathBuf::from("a.codex")]);
+        assert_eq!(fs::read(journal.root().join("a.codex")).unwrap(), b"new");
+        assert!(journal_is_empty(&journal));
+    }
+
+    #[test]
+    fn test_recovery_discards_incomplete_writes() {
+        let (_temp_dir, journal) = journal();
+        journal.write("a.codex", b"old").unwrap();
+        journal.write("b.codex", b"old").unwrap();
+
+        // Crashed while staging: the data file is cut short
+        let torn = journal.begin(Path::new("a.codex"), b"new contents").unwrap();
+        journal.stage(torn, b"new con").unwrap();
+        // Crashed before staging anything
+        journal.begin(Path::new("b.codex"), b"new").unwrap();
+        // Crashed after the rename, before the commit
+        let renamed = journal.begin(Path::new("c.codex"), b"new").unwrap();
+        journal.stage(renamed, b"new").unwrap();
+        journal.install(renamed, Path::new("c.codex")).unwrap();
+
+        let mut report = journal.recover().unwrap();
+        report.discarded.sort();
+        assert!(report.completed.is_empty());
+        assert_eq!(report.discarded, vec![PathBuf::from("a.codex"), PathBuf::from("b.codex")]);
+        assert_eq!(fs::read(journal.root().join("a.codex")).unwrap(), b"old");
+        assert_eq!(fs::read(journal.root().join("b.codex")).unwrap(), b"old");
+        assert_eq!(fs::read(journal.root().join("c.codex")).unwrap(), b"new");
+        assert!(journal_is_empty(&journal));
+    }
+}
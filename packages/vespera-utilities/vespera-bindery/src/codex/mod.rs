@@ -3,16 +3,32 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use crate::{BinderyResult, CodexId, templates::TemplateId, types::CodexContent};
+use crate::crdt::{CodexReference, ReferenceType};
 use chrono::{DateTime, Utc};
 
 // Sub-modules for Codex functionality
+pub mod acl;
+pub mod bulk;
+pub mod discord;
+pub mod events;
 pub mod format;
+pub mod import;
+pub mod journal;
 pub mod template;
+pub mod trash;
+pub mod tree;
 pub mod versioning;
 
 // Re-export commonly used types
+pub use acl::{AccessLevel, CodexAcl, ACL_FIELD};
+pub use bulk::BulkMergeStats;
+pub use discord::{DISCORD_CONVERSATION_TEMPLATE_ID, NEXT_REFERENCE, PREVIOUS_REFERENCE};
+pub use events::{CodexChange, CodexChangeKind, CodexEvent, CodexEventFilter, FieldChange};
 pub use template::{TemplateRegistry, TemplateLoader};
 pub use format::{CodexFormat, CodexSerializer};
+pub use import::{DocumentImport, IMPORTED_DOCUMENT_TEMPLATE_ID};
+pub use journal::{CodexJournal, RecoveryReport};
+pub use trash::{TrashEntry, TRASHED_AT_FIELD};
 pub use versioning::{VersionManager, CodexVersion};
 
 /// Core Codex structure
@@ -27,6 +43,27 @@ pub struct Codex {
     pub content: CodexContent,
 }
 
+/// Options for [`CodexManager::duplicate_codex`](crate::CodexManager::duplicate_codex)
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateOptions {
+    /// Also copy the Codex's tree children, recursively
+    pub include_children: bool,
+    /// Title of the copy; the original's with " (copy)" appended when unset
+    pub title: Option<String>,
+}
+
+impl DuplicateOptions {
+    pub fn with_children(mut self) -> Self {
+        self.include_children = true;
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+}
+
 /// Codex manager extensions for task management
 pub trait CodexManagerExt {
     /// Create a new Codex
@@ -59,14 +96,19 @@ impl CodexManagerExt for crate::CodexManager {
         Ok(None)
     }
 
-    async fn update_codex_fields(&self, _id: &CodexId, _fields: HashMap<String, crate::templates::TemplateValue>) -> BinderyResult<()> {
-        // Placeholder implementation
-        Ok(())
+    async fn update_codex_fields(&self, id: &CodexId, fields: HashMap<String, crate::templates::TemplateValue>) -> BinderyResult<()> {
+        let user_id = self.config().user_id.clone().unwrap_or_else(|| "system".to_string());
+        let fields = fields
+            .into_iter()
+            .map(|(field, value)| (field, crdt_value(value, &user_id)));
+        self.set_codex_fields(id, fields).await
     }
 
-    async fn delete_codex_ext(&self, _id: &CodexId) -> BinderyResult<()> {
-        // Placeholder implementation
-        Ok(())
+    async fn delete_codex_ext(&self, id: &CodexId) -> BinderyResult<()> {
+        self.delete_codex(id)
+            .await
+            .map(|_| ())
+            .map_err(|e| crate::BinderyError::InternalError(e.to_string()))
     }
 
     async fn list_codices_by_template(&self, _template_id: &TemplateId) -> BinderyResult<Vec<Codex>> {
@@ -74,9 +116,43 @@ impl CodexManagerExt for crate::CodexManager {
         Ok(Vec::new())
     }
 
-    async fn add_codex_reference(&self, _from_id: &CodexId, _to_id: &CodexId, _ref_type: &str, _context: Option<String>) -> BinderyResult<()> {
-        // Placeholder implementation
-        Ok(())
+    async fn add_codex_reference(&self, from_id: &CodexId, to_id: &CodexId, ref_type: &str, context: Option<String>) -> BinderyResult<()> {
+        let reference_type = match ref_type {
+            "child" | "parent_child" => ReferenceType::Child,
+            "depends_on" => ReferenceType::DependsOn,
+            "references" => ReferenceType::References,
+            "related" | "relates_to" => ReferenceType::Related,
+            other => ReferenceType::Custom(other.to_string()),
+        };
+        self.add_reference(CodexReference {
+            from_codex_id: *from_id,
+            to_codex_id: *to_id,
+            reference_type,
+            context,
+        })
+        .await
+        .map(|_| ())
+    }
+}
+
+/// A template field value as stored in the CRDT metadata layer
+fn crdt_value(value: crate::templates::TemplateValue, user_id: &str) -> crate::crdt::TemplateValue {
+    use crate::crdt::TemplateValue as CrdtValue;
+    use crate::templates::TemplateValue;
+
+    let timestamp = Utc::now();
+    let user_id = user_id.to_string();
+    match value {
+        TemplateValue::Text(value) => CrdtValue::Text { value, timestamp, user_id },
+        TemplateValue::Reference(id) => match id.parse() {
+            Ok(codex_id) => CrdtValue::Reference { codex_id, timestamp, user_id },
+            Err(_) => CrdtValue::Text { value: id, timestamp, user_id },
+        },
+        other => CrdtValue::Structured {
+            value: serde_json::to_value(&other).unwrap_or(serde_json::Value::Null),
+            timestamp,
+            user_id,
+        },
     }
 }
 
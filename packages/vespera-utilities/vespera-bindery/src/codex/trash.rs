@@ -0,0 +1,329 @@
+//! Trash for Codices and tasks
+//!
+//! [`CodexManager::delete_codex`] and [`CodexManager::trash_codex`] delete a
+//! Codex and the Codices below it in the hierarchy restorably. The
+//! [`TRASHED_AT_FIELD`] metadata field marks each of them, so the mark syncs
+//! to peers like any other field, and the manager indexes trashed Codices so
+//! queries can leave them out. A trashed Codex is still readable by ID and
+//! can be restored, together with the Codices trashed along with it, with
+//! [`CodexManager::restore_codex`] until `BinderyConfig::trash_retention_days`
+//! pass; after that garbage collection deletes it for good.
+//! [`CodexManager::delete_codices`] deletes Codices for good right away,
+//! subject to approval.
+//!
+//! Trashing, restoring and purging are recorded in the audit log once one is
+//! attached with [`CodexManager::attach_audit_logger`].
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{CodexChange, CodexEvent};
+use crate::crdt::{TemplateValue, VesperaCRDT};
+use crate::observability::{create_trash_event, log_security_event, OperationOutcome, UserContext};
+use crate::{BinderyError, BinderyResult, CodexId, CodexManager, UserId};
+
+/// Metadata field holding when a trashed Codex was trashed
+pub const TRASHED_AT_FIELD: &str = "trashed_at";
+
+/// A Codex in the trash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub codex_id: CodexId,
+    pub title: Option<String>,
+    pub template_id: Option<String>,
+    pub trashed_at: DateTime<Utc>,
+    pub trashed_by: UserId,
+    /// When garbage collection may delete it
+    pub expires_at: DateTime<Utc>,
+}
+
+/// When a Codex was trashed and by whom, or None if it is not in the trash
+pub fn trashed_at(crdt: &VesperaCRDT) -> Option<(DateTime<Utc>, UserId)> {
+    match crdt.get_metadata(TRASHED_AT_FIELD)? {
+        TemplateValue::Text { timestamp, user_id, .. } => Some((*timestamp, user_id.clone())),
+        _ => None,
+    }
+}
+
+impl CodexManager {
+    /// Move a Codex and the Codices below it to the trash, returning false
+    /// if it was already there
+    ///
+    /// Codices below it that were already in the trash keep their own
+    /// trashing time, so restoring this one leaves them there.
+    pub async fn trash_codex(&self, id: &CodexId) -> BinderyResult<bool> {
+        let user_id = self.inner.config.user_id.clone().unwrap_or_else(|| "system".to_string());
+        let now = Utc::now();
+        if !self.mark_trashed(id, now, &user_id).await? {
+            return Ok(false);
+        }
+        for descendant in self.codex_subtree(id).await?.into_iter().skip(1) {
+            self.mark_trashed(&descendant, now, &user_id).await?;
+        }
+        Ok(true)
+    }
+
+    /// Take a Codex out of the trash, with the Codices below it that were
+    /// trashed along with it, returning false if it was not there
+    ///
+    /// Fails once its retention window has passed, even if garbage collection
+    /// has not deleted it yet.
+    pub async fn restore_codex(&self, id: &CodexId) -> BinderyResult<bool> {
+        let Some(trashed_at) = self.unmark_trashed(id, None).await? else {
+            return Ok(false);
+        };
+        for descendant in self.codex_subtree(id).await?.into_iter().skip(1) {
+            self.unmark_trashed(&descendant, Some(trashed_at)).await?;
+        }
+        Ok(true)
+    }
+
+    /// Mark one Codex as trashed at `now`, unless it already is
+    async fn mark_trashed(&self, id: &CodexId, now: DateTime<Utc>, user_id: &str) -> BinderyResult<bool> {
+        let (trashed, template_id) = self.modify_codex(id, |crdt| {
+            if trashed_at(crdt).is_some() {
+                return Ok(None);
+            }
+            crdt.set_metadata(TRASHED_AT_FIELD.to_string(), TemplateValue::Text {
+                value: now.to_rfc3339(),
+                timestamp: now,
+                user_id: user_id.to_string(),
+            })?;
+            Ok(Some(crdt.get_title()))
+        }).await?;
+        let Some(title) = trashed else {
+            return Ok(false);
+        };
+
+        self.inner.trash.write().await.insert(*id, now);
+        self.audit_trash(id, title.as_deref(), "trash").await;
+        self.publish(CodexEvent::new(*id, template_id, CodexChange::Trashed { title }));
+        Ok(true)
+    }
+
+    /// Clear the trash mark of one Codex, if it has one (trashed at
+    /// `trashed_with`, when given), returning when it was trashed
+    async fn unmark_trashed(&self, id: &CodexId, trashed_with: Option<DateTime<Utc>>) -> BinderyResult<Option<DateTime<Utc>>> {
+        let retention = self.trash_retention();
+        let (restored, template_id) = self.modify_codex(id, |crdt| {
+            let Some((trashed_at, _)) = trashed_at(crdt) else {
+                return Ok(None);
+            };
+            if trashed_with.is_some_and(|with| with != trashed_at) {
+                return Ok(None);
+            }
+            if trashed_at + retention <= Utc::now() {
+                return Err(BinderyError::InvalidOperation(format!(
+                    "Codex {} has been in the trash longer than its retention window",
+                    crdt.codex_id
+                )));
+            }
+            crdt.delete_metadata(TRASHED_AT_FIELD.to_string())?;
+            Ok(Some((trashed_at, crdt.get_title())))
+        }).await?;
+        let Some((trashed_at, title)) = restored else {
+            return Ok(None);
+        };
+
+        self.inner.trash.write().await.remove(id);
+        self.audit_trash(id, title.as_deref(), "restore").await;
+        self.publish(CodexEvent::new(*id, template_id, CodexChange::Restored { title }));
+        Ok(Some(trashed_at))
+    }
+
+    /// Whether a Codex is in the trash
+    pub async fn is_trashed(&self, id: &CodexId) -> bool {
+        self.inner.trash.read().await.contains_key(id)
+    }
+
+    /// Codices in the trash, most recently trashed first
+    pub async fn list_trash(&self) -> Vec<TrashEntry> {
+        let trashed: Vec<CodexId> = self.inner.trash.read().await.keys().copied().collect();
+        let retention = self.trash_retention();
+        let mut entries = Vec::with_capacity(trashed.len());
+        for id in trashed {
+            let Some(crdt) = self.get_codex(&id).await else { continue };
+            let Some((trashed_at, trashed_by)) = trashed_at(&crdt) else { continue };
+            entries.push(TrashEntry {
+                codex_id: id,
+                title: crdt.get_title(),
+                template_id: crate::template_of(&crdt),
+                trashed_at,
+                trashed_by,
+                expires_at: trashed_at + retention,
+            });
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.trashed_at));
+        entries
+    }
+
+    /// Permanently delete Codices whose retention window has passed,
+    /// returning how many were deleted
+    ///
+    /// Garbage collection calls this.
+    pub async fn purge_trash(&self) -> BinderyResult<usize> {
+        let cutoff = Utc::now() - self.trash_retention();
+        let expired: Vec<CodexId> = self.inner.trash.read().await
+            .iter()
+            .filter(|(_, trashed_at)| **trashed_at <= cutoff)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let mut purged = 0;
+        for id in expired {
+            let title = self.get_codex(&id).await.and_then(|crdt| crdt.get_title());
+            let deleted = self.purge_codex(&id)
+                .await
+                .map_err(|e| BinderyError::InternalError(e.to_string()))?;
+            if deleted {
+                self.audit_trash(&id, title.as_deref(), "purge").await;
+                purged += 1;
+            }
+        }
+        if purged > 0 {
+            tracing::info!(purged, "Purged expired Codices from the trash");
+        }
+        Ok(purged)
+    }
+
+    fn trash_retention(&self) -> Duration {
+        Duration::days(i64::from(self.inner.config.trash_retention_days))
+    }
+
+    async fn audit_trash(&self, id: &CodexId, title: Option<&str>, action: &str) {
+        let Some(logger) = self.inner.audit_logger.get() else {
+            return;
+        };
+        let user_context = UserContext {
+            user_id: self.inner.config.user_id.clone(),
+            session_id: None,
+            source_ip: None,
+            user_agent: None,
+        };
+        let outcome = OperationOutcome {
+            success: true,
+            result_code: None,
+            error_message: None,
+            duration_ms: 0,
+            records_affected: Some(1),
+        };
+        let event = create_trash_event(user_context, &id.to_string(), title, action, outcome);
+        log_security_event(Some(logger), event).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_trash_mark_is_metadata() {
+        let mut crdt = VesperaCRDT::new(Uuid::new_v4(), "alice".to_string());
+        assert_eq!(trashed_at(&crdt), None);
+
+        let now = Utc::now();
+        crdt.set_metadata(TRASHED_AT_FIELD.to_string(), TemplateValue::Text {
+            value: now.to_rfc3339(),
+            timestamp: now,
+            user_id: "alice".to_string(),
+        }).unwrap();
+        assert_eq!(trashed_at(&crdt), Some((now, "alice".to_string())));
+
+        // Restoring deletes the field, and the deletion syncs like a write
+        let mut peer = crdt.clone();
+        crdt.delete_metadata(TRASHED_AT_FIELD.to_string()).unwrap();
+        assert_eq!(trashed_at(&crdt), None);
+        peer.merge(&crdt).unwrap();
+        assert_eq!(trashed_at(&peer), None);
+    }
+
+    async fn manager_with_retention(days: u32) -> CodexManager {
+        let config = crate::BinderyConfig { trash_retention_days: days, ..Default::default() };
+        let manager = CodexManager::with_config(config).unwrap();
+        manager.register_template(crate::templates::Template::new(
+            crate::templates::TemplateId::new("test.page"),
+            "Page".to_string(),
+            "A page".to_string(),
+            "page".to_string(),
+        )).await.unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_restore_brings_back_what_was_trashed_with_it() {
+        let manager = manager_with_retention(30).await;
+        let mut ids = Vec::new();
+        for title in ["book", "chapter", "scene", "note"] {
+            ids.push(manager.create_codex(title, "test.page").await.unwrap());
+        }
+        let [book, chapter, scene, note] = ids[..] else { unreachable!() };
+        manager.reparent_codex(&chapter, Some(book), 0).await.unwrap();
+        manager.reparent_codex(&scene, Some(chapter), 0).await.unwrap();
+        manager.reparent_codex(&note, Some(book), 1).await.unwrap();
+
+        // The note goes first, on its own
+        assert!(manager.trash_codex(&note).await.unwrap());
+        assert!(manager.trash_codex(&book).await.unwrap());
+        assert!(!manager.trash_codex(&book).await.unwrap());
+        assert!(manager.list_codices().await.is_empty());
+        assert_eq!(manager.list_trash().await.len(), 4);
+
+        assert!(manager.restore_codex(&book).await.unwrap());
+        let mut listed = manager.list_codices().await;
+        listed.sort();
+        let mut expected = vec![book, chapter, scene];
+        expected.sort();
+        assert_eq!(listed, expected);
+        assert!(manager.is_trashed(&note).await);
+        assert!(!manager.restore_codex(&chapter).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_delete_moves_to_the_trash_and_restores() {
+        let manager = manager_with_retention(30).await;
+        let page = manager.create_codex("page", "test.page").await.unwrap();
+        manager.set_codex_fields(&page, [("body".to_string(), TemplateValue::Text {
+            value: "Kept across the trash".to_string(),
+            timestamp: Utc::now(),
+            user_id: "tester".to_string(),
+        })]).await.unwrap();
+
+        assert!(manager.delete_codex(&page).await.unwrap());
+        assert!(!manager.delete_codex(&page).await.unwrap());
+        assert!(manager.is_trashed(&page).await);
+        assert!(manager.list_codices().await.is_empty());
+
+        assert!(manager.restore_codex(&page).await.unwrap());
+        assert_eq!(manager.list_codices().await, vec![page]);
+        let restored = manager.get_codex(&page).await.unwrap();
+        assert_eq!(restored.get_title().as_deref(), Some("page"));
+        assert!(matches!(restored.get_metadata("body"), Some(TemplateValue::Text { value, .. }) if value == "Kept across the trash"));
+
+        assert!(!manager.delete_codex(&Uuid::new_v4()).await.unwrap());
+        assert!(manager.purge_codex(&page).await.unwrap());
+        assert!(manager.get_codex(&page).await.is_none());
+        assert!(matches!(manager.restore_codex(&page).await, Err(BinderyError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_gc_purges_the_trash_after_the_retention_window() {
+        let keeping = manager_with_retention(30).await;
+        let kept = keeping.create_codex("kept", "test.page").await.unwrap();
+        keeping.trash_codex(&kept).await.unwrap();
+        assert_eq!(keeping.gc_all_codices().await.unwrap().trash_purged, 0);
+        assert!(keeping.get_codex(&kept).await.is_some());
+        assert!(keeping.restore_codex(&kept).await.unwrap());
+
+        let expiring = manager_with_retention(0).await;
+        let expired = expiring.create_codex("expired", "test.page").await.unwrap();
+        let live = expiring.create_codex("live", "test.page").await.unwrap();
+        expiring.trash_codex(&expired).await.unwrap();
+        assert!(matches!(expiring.restore_codex(&expired).await, Err(BinderyError::InvalidOperation(_))));
+
+        assert_eq!(expiring.gc_all_codices().await.unwrap().trash_purged, 1);
+        assert!(expiring.get_codex(&expired).await.is_none());
+        assert!(expiring.list_trash().await.is_empty());
+        assert_eq!(expiring.list_codices().await, vec![live]);
+    }
+}
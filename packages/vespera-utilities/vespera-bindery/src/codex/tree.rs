@@ -0,0 +1,184 @@
+//! The Codex hierarchy
+//!
+//! A Codex lists its children in its own tree layer, so the hierarchy is
+//! spread over the parents' CRDTs and each list merges like any other
+//! Codex change: children keep one order on every replica, and concurrent
+//! moves within a list resolve as described in
+//! [`tree_layer`](crate::crdt::tree_layer). Moving a Codex to another parent
+//! adds it to the new parent before taking it from the old one, so a failure
+//! in between leaves it in both lists rather than in neither.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{BinderyError, BinderyResult, CodexId, CodexManager};
+
+impl CodexManager {
+    /// Children of a Codex, in order
+    pub async fn codex_children(&self, id: &CodexId) -> BinderyResult<Vec<CodexId>> {
+        let codices = self.inner.codices.read().await;
+        let crdt = codices.get(id)
+            .ok_or_else(|| BinderyError::NotFound(format!("Codex {}", id)))?;
+        Ok(crdt.children(*id))
+    }
+
+    /// The Codex listing `id` as a child, if any
+    pub async fn codex_parent(&self, id: &CodexId) -> Option<CodexId> {
+        self.codex_parents().await.get(id).copied()
+    }
+
+    /// A Codex and all Codices below it, depth-first with children in order
+    pub async fn codex_subtree(&self, id: &CodexId) -> BinderyResult<Vec<CodexId>> {
+        let codices = self.inner.codices.read().await;
+        if !codices.contains_key(id) {
+            return Err(BinderyError::NotFound(format!("Codex {}", id)));
+        }
+
+        let mut subtree = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![*id];
+        while let Some(node) = stack.pop() {
+            if !seen.insert(node) {
+                continue;
+            }
+            subtree.push(node);
+            if let Some(crdt) = codices.get(&node) {
+                stack.extend(crdt.children(node).into_iter().rev());
+            }
+        }
+        Ok(subtree)
+    }
+
+    /// Move a Codex under `new_parent` at `position`, or out of the
+    /// hierarchy with `None`
+    ///
+    /// Fails if either Codex does not exist or `new_parent` is the Codex
+    /// itself or below it.
+    pub async fn reparent_codex(&self, id: &CodexId, new_parent: Option<CodexId>, position: usize) -> BinderyResult<()> {
+        let parents = self.codex_parents().await;
+        {
+            let codices = self.inner.codices.read().await;
+            for codex in std::iter::once(id).chain(new_parent.as_ref()) {
+                if !codices.contains_key(codex) {
+                    return Err(BinderyError::NotFound(format!("Codex {}", codex)));
+                }
+            }
+        }
+
+        let mut ancestor = new_parent;
+        let mut seen = HashSet::new();
+        while let Some(node) = ancestor.filter(|node| seen.insert(*node)) {
+            if node == *id {
+                return Err(BinderyError::CircularReferenceError(format!(
+                    "Codex {} cannot be moved below itself", id
+                )));
+            }
+            ancestor = parents.get(&node).copied();
+        }
+
+        let old_parent = parents.get(id).copied();
+        if let Some(parent) = new_parent {
+            if old_parent == Some(parent) {
+                self.modify_codex(&parent, |crdt| crdt.reorder(*id, position)).await?;
+                return Ok(());
+            }
+            self.modify_codex(&parent, |crdt| crdt.add_child(parent, position, *id)).await?;
+        }
+        if let Some(parent) = old_parent {
+            self.modify_codex(&parent, |crdt| crdt.remove_child(parent, *id)).await?;
+        }
+        Ok(())
+    }
+
+    /// Move a child to `position` among its siblings
+    pub async fn reorder_codex(&self, parent: &CodexId, child: &CodexId, position: usize) -> BinderyResult<()> {
+        self.modify_codex(parent, |crdt| {
+            if !crdt.children(*parent).contains(child) {
+                return Err(BinderyError::NotFound(format!("{} is not a child of {}", child, parent)));
+            }
+            crdt.reorder(*child, position)
+        }).await?;
+        Ok(())
+    }
+
+    /// Parent of every Codex that has one; where two Codices list the same
+    /// child, the smaller ID wins so the answer does not depend on map order
+    async fn codex_parents(&self) -> HashMap<CodexId, CodexId> {
+        let codices = self.inner.codices.read().await;
+        let mut parents: HashMap<CodexId, CodexId> = HashMap::new();
+        for (parent, crdt) in codices.iter() {
+            for child in crdt.children(*parent) {
+                parents.entry(child)
+                    .and_modify(|current| *current = (*current).min(*parent))
+                    .or_insert(*parent);
+            }
+        }
+        parents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{Template, TemplateId};
+
+    async fn manager_with_codices(titles: &[&str]) -> (CodexManager, Vec<CodexId>) {
+        let manager = CodexManager::new().unwrap();
+        manager.register_template(Template::new(
+            TemplateId::new("test.node"),
+            "Node".to_string(),
+            "A node in the hierarchy".to_string(),
+            "node".to_string(),
+        )).await.unwrap();
+        let mut ids = Vec::new();
+        for title in titles {
+            ids.push(manager.create_codex(*title, "test.node").await.unwrap());
+        }
+        (manager, ids)
+    }
+
+    #[tokio::test]
+    async fn test_reparent_and_reorder() {
+        let (manager, ids) = manager_with_codices(&["book", "one", "two", "three"]).await;
+        let [book, one, two, three] = ids[..] else { unreachable!() };
+
+        for (position, chapter) in [one, two, three].into_iter().enumerate() {
+            manager.reparent_codex(&chapter, Some(book), position).await.unwrap();
+        }
+        assert_eq!(manager.codex_children(&book).await.unwrap(), vec![one, two, three]);
+        assert_eq!(manager.codex_parent(&two).await, Some(book));
+
+        manager.reorder_codex(&book, &three, 0).await.unwrap();
+        assert_eq!(manager.codex_children(&book).await.unwrap(), vec![three, one, two]);
+
+        // Reparenting under the same parent is a reorder
+        manager.reparent_codex(&three, Some(book), 2).await.unwrap();
+        assert_eq!(manager.codex_children(&book).await.unwrap(), vec![one, two, three]);
+
+        // Moving to another parent takes it out of the old one
+        manager.reparent_codex(&three, Some(one), 0).await.unwrap();
+        assert_eq!(manager.codex_children(&book).await.unwrap(), vec![one, two]);
+        assert_eq!(manager.codex_subtree(&book).await.unwrap(), vec![book, one, three, two]);
+
+        manager.reparent_codex(&three, None, 0).await.unwrap();
+        assert_eq!(manager.codex_parent(&three).await, None);
+        assert!(manager.codex_children(&one).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reparent_rejects_cycles_and_unknown_codices() {
+        let (manager, ids) = manager_with_codices(&["book", "chapter", "scene"]).await;
+        let [book, chapter, scene] = ids[..] else { unreachable!() };
+        manager.reparent_codex(&chapter, Some(book), 0).await.unwrap();
+        manager.reparent_codex(&scene, Some(chapter), 0).await.unwrap();
+
+        for parent in [book, scene] {
+            let result = manager.reparent_codex(&book, Some(parent), 0).await;
+            assert!(matches!(result, Err(BinderyError::CircularReferenceError(_))));
+        }
+        let missing = uuid::Uuid::new_v4();
+        assert!(matches!(manager.reparent_codex(&scene, Some(missing), 0).await, Err(BinderyError::NotFound(_))));
+        assert!(matches!(manager.reorder_codex(&book, &scene, 0).await, Err(BinderyError::NotFound(_))));
+
+        assert_eq!(manager.codex_subtree(&book).await.unwrap(), vec![book, chapter, scene]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Compression codecs for persisted Codices and sync payloads
+//!
+//! Compressed data starts with a short header naming the codec, so it can be
+//! read back whichever codec the workspace is configured with now. The codec
+//! and level come from [`CompressionConfig`] in the workspace's
+//! `BinderyConfig` (`[compression]` in `.vespera/bindery.toml`); setting
+//! `compression_enabled = false` turns compression off entirely.
+//!
+//! ```toml
+//! [compression]
+//! codec = "lz4"
+//! ```
+//!
+//! Every compression and decompression records its ratio and duration
+//! through [`BinderyMetrics`].
+//!
+//! Decompression takes a cap on the output size and stops at it, so a small
+//! hostile payload cannot make it allocate gigabytes.
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::time::Instant;
+
+use crate::observability::BinderyMetrics;
+use crate::{BinderyError, BinderyResult};
+
+/// Marks the start of compressed data; followed by one codec byte
+const MAGIC: &[u8; 3] = b"VCZ";
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+/// Default zstd level, zstd's own default
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// Largest decompressed Codex read back from storage, in bytes
+pub const MAX_CODEX_BYTES: usize = 256 * 1024 * 1024;
+
+/// Compression algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionCodec {
+    /// Stored as is
+    None,
+    /// Fast, with a moderate ratio
+    Lz4,
+    /// Better ratio at a somewhat higher cost
+    #[default]
+    Zstd,
+}
+
+impl CompressionCodec {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionCodec::None => "none",
+            CompressionCodec::Lz4 => "lz4",
+            CompressionCodec::Zstd => "zstd",
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            CompressionCodec::None => 0,
+            CompressionCodec::Lz4 => 1,
+            CompressionCodec::Zstd => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(CompressionCodec::None),
+            1 => Some(CompressionCodec::Lz4),
+            2 => Some(CompressionCodec::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Which codec to use, and how hard it should try
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    #[serde(default)]
+    pub codec: CompressionCodec,
+
+    /// zstd level, 1 (fastest) to 22 (smallest); lz4 has no levels
+    #[serde(default = "default_level")]
+    pub level: i32,
+}
+
+fn default_level() -> i32 {
+    DEFAULT_ZSTD_LEVEL
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self { codec: CompressionCodec::default(), level: DEFAULT_ZSTD_LEVEL }
+    }
+}
+
+impl CompressionConfig {
+    pub fn new(codec: CompressionCodec) -> Self {
+        Self { codec, ..Self::default() }
+    }
+
+    /// No compression
+    pub fn none() -> Self {
+        Self::new(CompressionCodec::None)
+    }
+
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn validate(&self) -> BinderyResult<()> {
+        if self.codec == CompressionCodec::Zstd && !zstd::compression_level_range().contains(&self.level) {
+            return Err(BinderyError::ConfigurationError(format!(
+                "compression.level must be between {} and {} for zstd",
+                zstd::compression_level_range().start(),
+                zstd::compression_level_range().end()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Compress `data`, recording metrics under `payload`
+    /// (for example `"codex"` or `"sync"`)
+    pub fn compress(&self, data: &[u8], payload: &str) -> BinderyResult<Vec<u8>> {
+        let started = Instant::now();
+        let mut framed = Vec::with_capacity(HEADER_LEN + data.len() / 2);
+        framed.extend_from_slice(MAGIC);
+        framed.push(self.codec.tag());
+        match self.codec {
+            CompressionCodec::None => framed.extend_from_slice(data),
+            CompressionCodec::Lz4 => framed.extend_from_slice(&lz4_flex::compress_prepend_size(data)),
+            CompressionCodec::Zstd => {
+                let compressed = zstd::bulk::compress(data, self.level)
+                    .map_err(|e| BinderyError::CompressionError(format!("zstd: {}", e)))?;
+                framed.extend_from_slice(&compressed);
+            }
+        }
+        BinderyMetrics::record_compression(self.codec.as_str(), payload, data.len(), framed.len(), started.elapsed());
+        Ok(framed)
+    }
+}
+
+/// Whether `data` starts with a compression header
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN && &data[..MAGIC.len()] == MAGIC
+}
+
+/// The codec `data` was compressed with, if it has a header
+pub fn codec_of(data: &[u8]) -> Option<CompressionCodec> {
+    is_compressed(data).then(|| CompressionCodec::from_tag(data[MAGIC.len()])).flatten()
+}
+
+/// Decompress data written by [`CompressionConfig::compress`], with any
+/// codec, refusing output over `max_bytes`
+pub fn decompress(data: &[u8], payload: &str, max_bytes: usize) -> BinderyResult<Vec<u8>> {
+    let started = Instant::now();
+    let codec = codec_of(data).ok_or_else(|| {
+        BinderyError::DecompressionError("Data has no known compression header".to_string())
+    })?;
+    let body = &data[HEADER_LEN..];
+    let bytes = match codec {
+        CompressionCodec::None if body.len() > max_bytes => return Err(too_large(max_bytes)),
+        CompressionCodec::None => body.to_vec(),
+        CompressionCodec::Lz4 => decompress_lz4(body, max_bytes)?,
+        CompressionCodec::Zstd => {
+            let decoder = zstd::Decoder::new(body)
+                .map_err(|e| BinderyError::DecompressionError(format!("zstd: {}", e)))?;
+            let mut bytes = Vec::new();
+            decoder
+                .take((max_bytes as u64).saturating_add(1))
+                .read_to_end(&mut bytes)
+                .map_err(|e| BinderyError::DecompressionError(format!("zstd: {}", e)))?;
+            if bytes.len() > max_bytes {
+                return Err(too_large(max_bytes));
+            }
+            bytes
+        }
+    };
+    BinderyMetrics::record_decompression(codec.as_str(), payload, data.len(), bytes.len(), started.elapsed());
+    Ok(bytes)
+}
+
+/// Decompress lz4 with its size prepended, checking the size before
+/// allocating for it
+pub(crate) fn decompress_lz4(body: &[u8], max_bytes: usize) -> BinderyResult<Vec<u8>> {
+    let prefix: [u8; 4] = body
+        .get(..4)
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or_else(|| BinderyError::DecompressionError("lz4: missing size prefix".to_string()))?;
+    if u32::from_le_bytes(prefix) as usize > max_bytes {
+        return Err(too_large(max_bytes));
+    }
+    lz4_flex::decompress_size_prepended(body).map_err(|e| BinderyError::DecompressionError(format!("lz4: {}", e)))
+}
+
+fn too_large(max_bytes: usize) -> BinderyError {
+    BinderyError::DecompressionError(format!("Decompressed data exceeds the limit of {} bytes", max_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        "the same operation, again and again; ".repeat(200).into_bytes()
+    }
+
+    #[test]
+    fn test_round_trip_with_each_codec() {
+        let data = sample();
+        for codec in [CompressionCodec::None, CompressionCodec::Lz4, CompressionCodec::Zstd] {
+            let compressed = CompressionConfig::new(codec).compress(&data, "test").unwrap();
+            assert_eq!(codec_of(&compressed), Some(codec));
+            if codec != CompressionCodec::None {
+                assert!(compressed.len() < data.len() / 4, "{:?} did not compress", codec);
+            }
+            assert_eq!(decompress(&compressed, "test", data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unframed_data_and_bad_levels() {
+        assert!(decompress(b"plain bytes", "test", 1024).is_err());
+        assert!(decompress(b"VCZ\x09rest", "test", 1024).is_err());
+        assert!(CompressionConfig::default().with_level(99).validate().is_err());
+        assert!(CompressionConfig::new(CompressionCodec::Lz4).with_level(99).validate().is_ok());
+        assert_eq!(CompressionConfig::default().codec, CompressionCodec::Zstd);
+    }
+
+    #[test]
+    fn test_stops_at_the_output_limit() {
+        // 64 MiB of zeros compresses to a few kilobytes
+        let bomb = vec![0u8; 64 * 1024 * 1024];
+        for codec in [CompressionCodec::None, CompressionCodec::Lz4, CompressionCodec::Zstd] {
+            let compressed = CompressionConfig::new(codec).compress(&bomb, "test").unwrap();
+            let err = decompress(&compressed, "test", 1024 * 1024).unwrap_err();
+            assert!(matches!(err, BinderyError::DecompressionError(_)), "{:?}: {:?}", codec, err);
+        }
+
+        // An lz4 size prefix claiming 4 GiB is refused before allocating
+        let mut forged = b"VCZ\x01".to_vec();
+        forged.extend_from_slice(&u32::MAX.to_le_bytes());
+        forged.extend_from_slice(&[0; 16]);
+        assert!(decompress(&forged, "test", 1024 * 1024).is_err());
+
+        let data = sample();
+        let compressed = CompressionConfig::default().compress(&data, "test").unwrap();
+        assert!(decompress(&compressed, "test", data.len() - 1).is_err());
+        assert_eq!(decompress(&compressed, "test", data.len()).unwrap(), data);
+    }
+}
@@ -0,0 +1,226 @@
+//! Loading [`BinderyConfig`] from files and the environment
+//!
+//! Settings are layered, each layer overriding the one before:
+//!
+//! 1. the defaults ([`BinderyConfig::default`]),
+//! 2. the configuration file (TOML, or any format `config` recognises by
+//!    extension), which only needs the keys it changes,
+//! 3. `VESPERA_*` environment variables, with `__` between nested keys:
+//!    `VESPERA_GC_INTERVAL_SECONDS=600`,
+//!    `VESPERA_DATABASE_POOL__MAX_CONNECTIONS=20`.
+//!
+//! ```toml
+//! database_path = "/srv/vespera/bindery.db"
+//! max_operations_in_memory = 5000
+//!
+//! [database_pool]
+//! max_connections = 20
+//! ```
+
+use crate::{BinderyConfig, BinderyError, BinderyResult};
+use config::{Config, ConfigError, Environment, File};
+use serde_json::Value;
+use std::path::Path;
+
+/// Prefix of environment variables that override configuration keys
+pub const ENV_PREFIX: &str = "VESPERA";
+/// Separator between nested keys in environment variable names
+pub const ENV_SEPARATOR: &str = "__";
+
+impl BinderyConfig {
+    /// Load the configuration file at `path`, then apply `VESPERA_*`
+    /// environment overrides and validate the result
+    ///
+    /// Unknown keys in the file are errors, so a misspelt setting does not
+    /// silently fall back to its default.
+    pub fn from_file(path: impl AsRef<Path>) -> BinderyResult<Self> {
+        Self::load(Some(path.as_ref()), environment())
+    }
+
+    /// The defaults with `VESPERA_*` environment overrides, validated
+    pub fn from_env() -> BinderyResult<Self> {
+        Self::load(None, environment())
+    }
+
+    /// The configuration as TOML, with secret values redacted, for checking
+    /// what a deployment actually runs with
+    pub fn dump_effective_config(&self) -> BinderyResult<String> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to serialize configuration: {}", e)))?;
+        crate::secrets::redact_json(&mut value);
+        // TOML has no null; unset options are left out
+        strip_nulls(&mut value);
+        toml::to_string_pretty(&value)
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to serialize configuration: {}", e)))
+    }
+
+    fn load(path: Option<&Path>, environment: Environment) -> BinderyResult<Self> {
+        let defaults = Config::try_from(&Self::default()).map_err(config_error)?;
+        let mut builder = Config::builder().add_source(defaults);
+        if let Some(path) = path {
+            if !path.exists() {
+                return Err(BinderyError::ConfigurationError(format!(
+                    "Configuration file not found: {}",
+                    path.display()
+                )));
+            }
+            check_known_keys(path)?;
+            builder = builder.add_source(File::from(path));
+        }
+
+        let config: Self = builder
+            .add_source(environment)
+            .build()
+            .and_then(Config::try_deserialize)
+            .map_err(config_error)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+fn environment() -> Environment {
+    Environment::with_prefix(ENV_PREFIX)
+        .prefix_separator("_")
+        .separator(ENV_SEPARATOR)
+        .try_parsing(true)
+}
+
+fn config_error(error: ConfigError) -> BinderyError {
+    BinderyError::ConfigurationError(error.to_string())
+}
+
+/// Fail on the first key in the file that `BinderyConfig` does not have
+///
+/// Keys below a setting that is unset by default (`backup`, `audit_config`,
+/// ...) are left to deserialization, which names them if they are wrong.
+fn check_known_keys(path: &Path) -> BinderyResult<()> {
+    let file: Value = Config::builder()
+        .add_source(File::from(path))
+        .build()
+        .and_then(Config::try_deserialize)
+        .map_err(config_error)?;
+    let defaults = serde_json::to_value(BinderyConfig::default())
+        .map_err(|e| BinderyError::ConfigurationError(e.to_string()))?;
+    match find_unknown_key(&file, &defaults, "") {
+        Some(key) => Err(BinderyError::ConfigurationError(format!(
+            "Unknown configuration key `{}` in {}",
+            key,
+            path.display()
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn find_unknown_key(value: &Value, known: &Value, prefix: &str) -> Option<String> {
+    let (Value::Object(values), Value::Object(known)) = (value, known) else {
+        return None;
+    };
+    values.iter().find_map(|(key, value)| {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match known.get(key) {
+            None => Some(path),
+            Some(known) => find_unknown_key(value, known, &path),
+        }
+    })
+}
+
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn write_config(temp_dir: &TempDir, contents: &str) -> std::path::PathBuf {
+        let path = temp_dir.path().join("bindery.toml");
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Environment overrides from `vars` instead of the process environment
+    fn env(vars: &[(&str, &str)]) -> Environment {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        environment().source(Some(vars))
+    }
+
+    #[test]
+    fn test_file_and_environment_layers() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_config(
+            &temp_dir,
+            "max_operations_in_memory = 5000\ngc_interval_seconds = 600\n\n[database_pool]\nmax_connections = 20\n",
+        );
+
+        let config = BinderyConfig::load(
+            Some(&path),
+            env(&[("VESPERA_GC_INTERVAL_SECONDS", "900"), ("VESPERA_DATABASE_POOL__MIN_CONNECTIONS", "2")]),
+        )
+        .unwrap();
+
+        // File over defaults, environment over file
+        assert_eq!(config.max_operations_in_memory, 5000);
+        assert_eq!(config.gc_interval_seconds, 900);
+        assert_eq!(config.database_pool.max_connections, 20);
+        assert_eq!(config.database_pool.min_connections, 2);
+        assert!(config.auto_gc_enabled);
+    }
+
+    #[test]
+    fn test_workspace_compression_codec() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_config(&temp_dir, "[compression]\ncodec = \"lz4\"\n");
+        let config = BinderyConfig::load(Some(&path), env(&[])).unwrap();
+        assert_eq!(config.compression.codec, crate::CompressionCodec::Lz4);
+        assert_eq!(config.effective_compression().codec, crate::CompressionCodec::Lz4);
+
+        let disabled = BinderyConfig::load(Some(&path), env(&[("VESPERA_COMPRESSION_ENABLED", "false")])).unwrap();
+        assert_eq!(disabled.effective_compression().codec, crate::CompressionCodec::None);
+
+        let bad_level = write_config(&temp_dir, "[compression]\nlevel = 40\n");
+        let error = BinderyConfig::load(Some(&bad_level), env(&[])).unwrap_err().to_string();
+        assert!(error.contains("compression.level"), "{}", error);
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let misspelt = write_config(&temp_dir, "[database_pool]\nmax_conections = 20\n");
+        let error = BinderyConfig::load(Some(&misspelt), env(&[])).unwrap_err().to_string();
+        assert!(error.contains("database_pool.max_conections"), "{}", error);
+
+        let wrong_type = write_config(&temp_dir, "gc_interval_seconds = \"often\"\n");
+        let error = BinderyConfig::load(Some(&wrong_type), env(&[])).unwrap_err().to_string();
+        assert!(error.contains("gc_interval_seconds"), "{}", error);
+
+        let invalid = write_config(&temp_dir, "");
+        let error = BinderyConfig::load(Some(&invalid), env(&[("VESPERA_MAX_OPERATIONS_IN_MEMORY", "0")]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("max_operations_in_memory"), "{}", error);
+    }
+
+    #[test]
+    fn test_dump_round_trips() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = BinderyConfig {
+            max_operations_in_memory: 4321,
+            ..BinderyConfig::default()
+        };
+
+        let dump = config.dump_effective_config().unwrap();
+        let path = write_config(&temp_dir, &dump);
+        let loaded = BinderyConfig::load(Some(&path), env(&[])).unwrap();
+        assert_eq!(loaded.max_operations_in_memory, 4321);
+    }
+}
@@ -0,0 +1,299 @@
+//! Delta-state replication
+//!
+//! In [`StorageMode::Delta`](super::StorageMode) a Codex keeps its layer
+//! state and only the last `history_window` operations, so its memory no
+//! longer grows with every edit. Replicas sync by exchanging
+//! [`CRDTDelta`]s instead of operation logs: fragments of state that join in
+//! any order and any number of times to the same result.
+//!
+//! Each change a replica makes or receives leaves a fragment in a buffer of
+//! the same window. [`VesperaCRDT::delta_since`] joins the fragments a peer
+//! lacks, judged by the peer's vector clock, and sends the whole state
+//! instead when the peer is further behind than the buffer reaches.
+//! [`VesperaCRDT::merge`] does both ends of this in delta mode.
+//!
+//! ```rust,ignore
+//! // Replica B asks A for what it lacks
+//! let delta = replica_a.delta_since(&replica_b.vector_clock)?;
+//! replica_b.apply_delta(&delta)?;
+//! ```
+//!
+//! Text fields travel as Yjs updates, hence the `yjs-compat` feature. The
+//! tree travels as its history of [`TreeChange`]s, which replicas replay in
+//! the same order whatever order they receive them in.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{CRDTOperation, CodexReference, LWWMap, ORSet, OperationType, TemplateValue, TreeChange, VesperaCRDT};
+use crate::types::{CodexId, UserId, VectorClock};
+use crate::{BinderyError, BinderyResult};
+
+/// A join-able fragment of a Codex's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CRDTDelta {
+    pub codex_id: CodexId,
+    /// The sender's clock: with the delta applied, the receiver has seen
+    /// everything the sender had
+    pub vector_clock: VectorClock,
+    pub retired_clients: VectorClock,
+    pub metadata: LWWMap<String, TemplateValue>,
+    pub references: ORSet<CodexReference>,
+    /// Yjs update (v1 encoding) for the text fields
+    pub text: Option<Vec<u8>>,
+    /// Tree layer changes, in history order
+    #[serde(default)]
+    pub tree: Vec<TreeChange>,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: UserId,
+}
+
+impl CRDTDelta {
+    pub fn empty(codex_id: CodexId) -> Self {
+        Self {
+            codex_id,
+            vector_clock: VectorClock::new(),
+            retired_clients: VectorClock::new(),
+            metadata: LWWMap::new(),
+            references: ORSet::new(),
+            text: None,
+            tree: Vec::new(),
+            updated_at: DateTime::<Utc>::MIN_UTC,
+            updated_by: UserId::new(),
+        }
+    }
+
+    /// Join `other` into this delta
+    pub fn join(&mut self, other: &CRDTDelta) -> BinderyResult<()> {
+        self.metadata.merge(&other.metadata);
+        self.references.merge(&other.references);
+        self.text = match (self.text.take(), &other.text) {
+            (Some(ours), Some(theirs)) => Some(
+                yrs::merge_updates_v1(&[&ours, theirs])
+                    .map_err(|e| BinderyError::CrdtError(format!("Invalid Yjs update: {}", e)))?,
+            ),
+            (ours, theirs) => ours.or_else(|| theirs.clone()),
+        };
+        self.tree.extend(other.tree.iter().cloned());
+        self.tree.sort_by_key(|change| (change.timestamp, change.id));
+        self.tree.dedup_by_key(|change| change.id);
+        join_clocks(&mut self.vector_clock, &other.vector_clock);
+        join_clocks(&mut self.retired_clients, &other.retired_clients);
+        if other.updated_at > self.updated_at {
+            self.updated_at = other.updated_at;
+            self.updated_by = other.updated_by.clone();
+        }
+        Ok(())
+    }
+}
+
+fn join_clocks(clock: &mut VectorClock, other: &VectorClock) {
+    for (user_id, &counter) in other {
+        let entry = clock.entry(user_id.clone()).or_insert(0);
+        *entry = (*entry).max(counter);
+    }
+}
+
+/// Recent fragments, for peers that are only a little behind
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DeltaBuffer {
+    /// Oldest first, each with the clock entries it advanced
+    fragments: VecDeque<(VectorClock, CRDTDelta)>,
+    /// Highest counter of each client among dropped fragments, or None when
+    /// that is unknown, as for a Codex loaded from storage
+    dropped: Option<VectorClock>,
+}
+
+impl DeltaBuffer {
+    /// A buffer holding every change after `clock`
+    pub(crate) fn starting_at(clock: VectorClock) -> Self {
+        Self { fragments: VecDeque::new(), dropped: Some(clock) }
+    }
+
+    pub(crate) fn is_tracking(&self) -> bool {
+        self.dropped.is_some()
+    }
+
+    pub(crate) fn push(&mut self, advanced: VectorClock, fragment: CRDTDelta, window: usize) {
+        self.fragments.push_back((advanced, fragment));
+        while self.fragments.len() > window {
+            let Some((advanced, _)) = self.fragments.pop_front() else { break };
+            if let Some(dropped) = &mut self.dropped {
+                join_clocks(dropped, &advanced);
+            }
+        }
+    }
+}
+
+impl VesperaCRDT {
+    /// What a peer with `peer_clock` lacks, as one delta
+    ///
+    /// The whole state when this replica no longer buffers everything the
+    /// peer lacks.
+    pub fn delta_since(&self, peer_clock: &VectorClock) -> BinderyResult<CRDTDelta> {
+        let peer_lacks = |clock: &VectorClock| {
+            clock.iter().any(|(user_id, &counter)| counter > self.clock_entry(peer_clock, user_id))
+        };
+        let mut delta = match &self.delta_buffer.dropped {
+            Some(dropped) if !peer_lacks(dropped) => {
+                let mut delta = CRDTDelta::empty(self.codex_id);
+                for (_, fragment) in self.delta_buffer.fragments.iter().filter(|(advanced, _)| peer_lacks(advanced)) {
+                    delta.join(fragment)?;
+                }
+                delta
+            }
+            _ => self.state_delta()?,
+        };
+        delta.vector_clock = self.vector_clock.clone();
+        delta.retired_clients = self.retired_clients.clone();
+        Ok(delta)
+    }
+
+    /// The whole state as a delta
+    pub fn state_delta(&self) -> BinderyResult<CRDTDelta> {
+        Ok(CRDTDelta {
+            codex_id: self.codex_id,
+            vector_clock: self.vector_clock.clone(),
+            retired_clients: self.retired_clients.clone(),
+            metadata: self.metadata_layer.clone(),
+            references: self.reference_layer.clone(),
+            text: Some(self.text_layer.encode_yjs_update(None)?),
+            tree: self.tree_layer.changes(),
+            updated_at: self.updated_at,
+            updated_by: self.updated_by.clone(),
+        })
+    }
+
+    /// Join a delta from a peer into this replica
+    ///
+    /// In delta mode the delta is also buffered, so this replica passes it on
+    /// to peers that lack it.
+    pub fn apply_delta(&mut self, delta: &CRDTDelta) -> BinderyResult<()> {
+        if delta.codex_id != self.codex_id {
+            return Err(BinderyError::CrdtError(
+                "Cannot apply a delta for a different Codex".to_string(),
+            ));
+        }
+        if let Some(update) = &delta.text {
+            self.text_layer.apply_yjs_update(update)?;
+        }
+        self.metadata_layer.merge(&delta.metadata);
+        self.reference_layer.merge(&delta.references);
+        self.tree_layer.apply_changes(&delta.tree);
+
+        self.join_retirements(&delta.retired_clients);
+        let mut advanced = VectorClock::new();
+        for (user_id, &counter) in &delta.vector_clock {
+            if counter > self.clock_entry(&self.vector_clock, user_id) {
+                self.vector_clock.insert(user_id.clone(), counter);
+                advanced.insert(user_id.clone(), counter);
+            }
+        }
+        if delta.updated_at > self.updated_at {
+            self.updated_at = delta.updated_at;
+            self.updated_by = delta.updated_by.clone();
+        }
+
+        if let Some(window) = self.delta_window() {
+            if !advanced.is_empty() {
+                self.delta_buffer.push(advanced, delta.clone(), window);
+            }
+        }
+        Ok(())
+    }
+
+    /// The part of `operation` a peer needs, before it is applied, along with
+    /// the Yjs state vector to diff text edits against once they are
+    pub(super) fn fragment_for(&self, operation: &CRDTOperation) -> (CRDTDelta, Option<Vec<u8>>) {
+        let mut fragment = CRDTDelta::empty(self.codex_id);
+        fragment.updated_at = operation.timestamp;
+        fragment.updated_by = operation.user_id.to_string();
+        let mut text_before = None;
+
+        match &operation.operation {
+            OperationType::TextInsert { .. } | OperationType::TextDelete { .. } | OperationType::TextFormat { .. } => {
+                text_before = Some(self.text_layer.yjs_state_vector());
+            }
+            OperationType::MetadataSet { key, value } => {
+                fragment.metadata.set_with_metadata(
+                    key.clone(),
+                    value.clone(),
+                    operation.timestamp,
+                    operation.user_id.clone(),
+                    operation.id,
+                );
+            }
+            OperationType::MetadataDelete { key } => {
+                fragment.metadata.delete_with_metadata(key, operation.timestamp, operation.user_id.clone(), operation.id);
+            }
+            OperationType::ReferenceAdd { reference } => {
+                fragment.references.add_with_tag(reference.clone(), Self::reference_tag(operation, &operation.user_id, 0));
+            }
+            OperationType::ReferenceAddMany { references } => {
+                for (index, reference) in references.iter().enumerate() {
+                    fragment.references.add_with_tag(reference.clone(), Self::reference_tag(operation, &operation.user_id, index));
+                }
+            }
+            OperationType::ReferenceRemove { reference } => {
+                fragment.references.remove_tags(reference, &self.observed_reference_tags(reference, operation));
+            }
+            OperationType::ReferenceRemoveTarget { to_codex_id } => {
+                for reference in self.references_to(to_codex_id) {
+                    fragment.references.remove_tags(reference, &self.observed_reference_tags(reference, operation));
+                }
+            }
+            OperationType::RetireClients { clients } => {
+                fragment.retired_clients = clients.clone();
+            }
+            OperationType::TreeInsert { .. } | OperationType::TreeDelete { .. } | OperationType::TreeMove { .. } => {
+                fragment.tree.extend(Self::tree_change(operation));
+            }
+        }
+        (fragment, text_before)
+    }
+
+    /// Buffer an applied operation's fragment for peers
+    pub(super) fn buffer_fragment(
+        &mut self,
+        operation: &CRDTOperation,
+        mut fragment: CRDTDelta,
+        text_before: Option<Vec<u8>>,
+    ) -> BinderyResult<()> {
+        let Some(window) = self.delta_window() else {
+            return Ok(());
+        };
+        if let Some(state_vector) = text_before {
+            fragment.text = Some(self.text_layer.encode_yjs_update(Some(&state_vector))?);
+        }
+        let counter = operation.vector_clock.get(&operation.user_id).copied().unwrap_or(0);
+        let advanced = VectorClock::from([(operation.user_id.clone(), counter)]);
+        self.delta_buffer.push(advanced, fragment, window);
+        Ok(())
+    }
+
+    /// Buffer text an editor imported since `state_vector`
+    ///
+    /// Imports are not operations, so this takes the next counter of the
+    /// current user for them; without it peers that had seen every
+    /// operation would never be sent the import.
+    pub(super) fn buffer_text_import(&mut self, state_vector: &[u8]) -> BinderyResult<()> {
+        let Some(window) = self.delta_window() else {
+            return Ok(());
+        };
+        let user_id = self.get_operation_context().user_id;
+        let user_id = self.interner.intern(&user_id);
+        let retired_at = self.retired_clients.get(&user_id).copied().unwrap_or(0);
+        let counter = self.vector_clock.entry(user_id.clone()).or_insert(retired_at);
+        *counter += 1;
+        let advanced = VectorClock::from([(user_id.clone(), *counter)]);
+
+        let mut fragment = CRDTDelta::empty(self.codex_id);
+        fragment.text = Some(self.text_layer.encode_yjs_update(Some(state_vector))?);
+        fragment.updated_at = self.updated_at;
+        fragment.updated_by = user_id.to_string();
+        self.delta_buffer.push(advanced, fragment, window);
+        Ok(())
+    }
+}
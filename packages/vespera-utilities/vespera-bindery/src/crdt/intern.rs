@@ -0,0 +1,232 @@
+//! Shared strings for the user and field IDs repeated across a CRDT
+//!
+//! Every operation names its user, carries a vector clock keyed by user, and
+//! text operations name their field, so the same few IDs are stored once per
+//! operation. A [`Symbol`] is a reference-counted string; each
+//! [`VesperaCRDT`](super::VesperaCRDT) runs the IDs of the operations it
+//! creates and applies through its [`Interner`], so equal IDs share one
+//! allocation across the operation log, vector clocks and LWW entries.
+//! Symbols serialize as plain strings, so the wire and storage formats are
+//! unchanged.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// An immutable, cheaply cloned string
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    pub fn new(value: &str) -> Self {
+        Self(Arc::from(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether both share one allocation
+    pub fn ptr_eq(&self, other: &Symbol) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(value: String) -> Self {
+        Self(Arc::from(value))
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(value: &String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(value: Symbol) -> Self {
+        value.0.to_string()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<Symbol> for String {
+    fn eq(&self, other: &Symbol) -> bool {
+        **self == *other.0
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+/// The distinct symbols of one CRDT
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    symbols: HashSet<Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared symbol equal to `value`, added if new
+    pub fn intern(&mut self, value: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(value) {
+            return symbol.clone();
+        }
+        let symbol = Symbol::new(value);
+        self.symbols.insert(symbol.clone());
+        symbol
+    }
+
+    /// Replace `symbol` with the shared one equal to it
+    pub fn share(&mut self, symbol: &mut Symbol) {
+        match self.symbols.get(symbol.as_str()) {
+            Some(shared) => *symbol = shared.clone(),
+            None => {
+                self.symbols.insert(symbol.clone());
+            }
+        }
+    }
+
+    /// Replace the keys of `map` with shared symbols
+    pub fn share_keys<V>(&mut self, map: &mut HashMap<Symbol, V>) {
+        if map.keys().all(|key| self.is_shared(key)) {
+            return;
+        }
+        *map = map.drain().map(|(mut key, value)| {
+            self.share(&mut key);
+            (key, value)
+        }).collect();
+    }
+
+    /// Whether `symbol` is this interner's shared copy
+    pub fn is_shared(&self, symbol: &Symbol) -> bool {
+        self.symbols.get(symbol.as_str()).is_some_and(|shared| shared.ptr_eq(symbol))
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Heap bytes of the symbols' text
+    pub fn text_bytes(&self) -> usize {
+        self.symbols.iter().map(|symbol| symbol.len()).sum()
+    }
+
+    /// Forget symbols nothing else refers to
+    pub fn prune(&mut self) {
+        self.symbols.retain(|symbol| Arc::strong_count(&symbol.0) > 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_allocations() {
+        let mut interner = Interner::new();
+        let a = interner.intern("alice");
+        let b = interner.intern("alice");
+        assert!(a.ptr_eq(&b));
+        assert_eq!(interner.len(), 1);
+
+        let mut foreign = Symbol::from("alice".to_string());
+        assert!(!foreign.ptr_eq(&a));
+        interner.share(&mut foreign);
+        assert!(foreign.ptr_eq(&a));
+
+        let mut clock = HashMap::from([(Symbol::from("alice"), 3u64), (Symbol::from("bob"), 1)]);
+        interner.share_keys(&mut clock);
+        assert!(clock.keys().all(|key| interner.intern(key).ptr_eq(key)));
+        assert_eq!(clock.get("alice"), Some(&3));
+
+        drop((a, b, foreign, clock));
+        interner.prune();
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn test_symbol_serializes_as_string() {
+        let symbol = Symbol::from("field");
+        assert_eq!(serde_json::to_string(&symbol).unwrap(), "\"field\"");
+        let back: Symbol = serde_json::from_str("\"field\"").unwrap();
+        assert_eq!(back, symbol);
+        assert_eq!(symbol, "field");
+    }
+}
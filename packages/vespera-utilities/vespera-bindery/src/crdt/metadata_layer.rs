@@ -81,11 +81,25 @@
 //! - Delete operations can be replicated correctly
 //! - Concurrent updates to deleted keys are handled properly
 //! - Garbage collection can clean up old tombstones safely
+//!
+//! # History
+//!
+//! Each key also keeps its most recent writes, sets and deletes alike, with
+//! who made them and when ([`LWWMap::key_history`]), so interfaces can show
+//! "edited by Alice, 2 hours ago" and who changed a field before that.
+//! Replicas that have seen the same writes keep the same history.
 
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use crate::types::UserId;
+use super::Symbol;
+
+/// Writes kept per key unless [`LWWMap::with_history_limit`] says otherwise
+pub const DEFAULT_HISTORY_LIMIT: usize = 10;
+
+fn default_history_limit() -> usize {
+    DEFAULT_HISTORY_LIMIT
+}
 
 /// Last-Writer-Wins Map for metadata that needs simple conflict resolution
 ///
@@ -150,6 +164,14 @@ where
     
     /// Tombstones for deleted entries
     tombstones: HashMap<K, LWWEntry<()>>,
+
+    /// Most recent writes per key, newest first; `None` values are deletes
+    #[serde(default = "HashMap::new")]
+    history: HashMap<K, Vec<LWWEntry<Option<V>>>>,
+
+    /// Writes kept per key in `history`
+    #[serde(default = "default_history_limit")]
+    history_limit: usize,
 }
 
 /// Entry in the LWW map with timestamp and author information
@@ -162,7 +184,7 @@ pub struct LWWEntry<V> {
     pub timestamp: DateTime<Utc>,
     
     /// User who set this value
-    pub user_id: UserId,
+    pub user_id: Symbol,
     
     /// Unique identifier for this operation (for tie-breaking)
     pub operation_id: uuid::Uuid,
@@ -178,8 +200,22 @@ where
         Self {
             entries: HashMap::new(),
             tombstones: HashMap::new(),
+            history: HashMap::new(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
         }
     }
+
+    /// Keep the `limit` most recent writes per key; 0 keeps none
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.set_history_limit(limit);
+        self
+    }
+
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        self.history.values_mut().for_each(|writes| writes.truncate(limit));
+        self.history.retain(|_, writes| !writes.is_empty());
+    }
     
     /// Set a value in the map
     ///
@@ -217,10 +253,11 @@ where
         let entry = LWWEntry {
             value: value.clone(),
             timestamp: Utc::now(),
-            user_id: "system".to_string(), // TODO: Get user ID from operation context
+            user_id: Symbol::from("system"), // TODO: Get user ID from operation context
             operation_id: uuid::Uuid::new_v4(),
         };
 
+        self.record(&key, &entry, Some(&entry.value));
         self.entries.insert(key, entry.clone());
         entry
     }
@@ -230,10 +267,11 @@ where
         let entry = LWWEntry {
             value: value.clone(),
             timestamp: Utc::now(),
-            user_id: "system".to_string(), // TODO: Get user ID from operation context
+            user_id: Symbol::from("system"), // TODO: Get user ID from operation context
             operation_id: uuid::Uuid::new_v4(),
         };
 
+        self.record(key, &entry, Some(&entry.value));
         self.entries.insert(key.clone(), entry.clone());
         entry
     }
@@ -295,23 +333,25 @@ where
         key: K,
         value: V,
         timestamp: DateTime<Utc>,
-        user_id: UserId,
+        user_id: impl Into<Symbol>,
         operation_id: uuid::Uuid,
     ) -> bool {
         let new_entry = LWWEntry {
             value,
             timestamp,
-            user_id,
+            user_id: user_id.into(),
             operation_id,
         };
         
         // Check if we should accept this update
+        // Kept in history whether or not it wins
+        self.record(&key, &new_entry, Some(&new_entry.value));
         let should_update = match self.entries.get(&key) {
             Some(existing) => self.should_update(existing, &new_entry),
             None => {
                 // Check if this key is tombstoned
                 match self.tombstones.get(&key) {
-                    Some(tombstone) => self.should_update_tombstone(&new_entry, tombstone),
+                    Some(tombstone) => self.should_update(tombstone, &new_entry),
                     None => true, // No existing value or tombstone
                 }
             }
@@ -331,11 +371,12 @@ where
         let tombstone = LWWEntry {
             value: (),
             timestamp: Utc::now(),
-            user_id: "system".to_string(), // TODO: Get user ID from operation context
+            user_id: Symbol::from("system"), // TODO: Get user ID from operation context
             operation_id: uuid::Uuid::new_v4(),
         };
         
         // Check if we should delete (i.e., our delete is newer than existing value)
+        self.record(key, &tombstone, None);
         let should_delete = match self.entries.get(key) {
             Some(existing) => tombstone.timestamp > existing.timestamp ||
                 (tombstone.timestamp == existing.timestamp && tombstone.operation_id > existing.operation_id),
@@ -355,17 +396,18 @@ where
         &mut self,
         key: &K,
         timestamp: DateTime<Utc>,
-        user_id: UserId,
+        user_id: impl Into<Symbol>,
         operation_id: uuid::Uuid,
     ) -> bool {
         let tombstone = LWWEntry {
             value: (),
             timestamp,
-            user_id,
+            user_id: user_id.into(),
             operation_id,
         };
         
         // Check if we should apply this delete
+        self.record(key, &tombstone, None);
         let should_delete = match self.entries.get(key) {
             Some(existing) => self.should_update_tombstone(existing, &tombstone),
             None => {
@@ -394,6 +436,25 @@ where
     pub fn get_entry(&self, key: &K) -> Option<&LWWEntry<V>> {
         self.entries.get(key)
     }
+
+    /// The current value of `key` with who wrote it and when, or None if it
+    /// is unset or deleted
+    pub fn get_with_metadata(&self, key: &K) -> Option<&LWWEntry<V>> {
+        self.get_entry(key)
+    }
+
+    /// Up to `limit` of the latest writes to `key`, newest first, starting
+    /// with the one in effect; deletes have a `None` value
+    ///
+    /// Writes that lost to a later one are included, so the history shows
+    /// every recent edit rather than only the winners. At most the map's
+    /// history limit of writes are kept.
+    pub fn key_history(&self, key: &K, limit: usize) -> Vec<&LWWEntry<Option<V>>> {
+        self.history
+            .get(key)
+            .map(|writes| writes.iter().take(limit).collect())
+            .unwrap_or_default()
+    }
     
     /// Check if the map contains a key
     pub fn contains_key(&self, key: &K) -> bool {
@@ -428,17 +489,19 @@ where
     /// Clear all entries
     pub fn clear(&mut self) {
         let now = Utc::now();
-        let user_id = "system".to_string(); // TODO: Get user ID from operation context
+        let user_id = Symbol::from("system"); // TODO: Get user ID from operation context
         
         // Create tombstones for all existing entries
-        for key in self.entries.keys() {
+        let keys: Vec<K> = self.entries.keys().cloned().collect();
+        for key in keys {
             let tombstone = LWWEntry {
                 value: (),
                 timestamp: now,
                 user_id: user_id.clone(),
                 operation_id: uuid::Uuid::new_v4(),
             };
-            self.tombstones.insert(key.clone(), tombstone);
+            self.record(&key, &tombstone, None);
+            self.tombstones.insert(key, tombstone);
         }
         
         self.entries.clear();
@@ -510,6 +573,13 @@ where
             }
         }
         
+        // Merge writes only the other map kept in its history
+        for (key, writes) in &other.history {
+            for write in writes {
+                self.record(key, write, write.value.as_ref());
+            }
+        }
+
         // Merge tombstones
         for (key, other_tombstone) in &other.tombstones {
             if self.delete_with_metadata(
@@ -560,15 +630,45 @@ where
         // Clear tombstones
         self.tombstones.clear();
         self.tombstones.shrink_to_fit();
+
+        self.history.clear();
+        self.history.shrink_to_fit();
     }
     
     /// Shrink collections to fit their contents
     pub fn shrink_to_fit(&mut self) {
         self.entries.shrink_to_fit();
         self.tombstones.shrink_to_fit();
+        self.history.shrink_to_fit();
     }
     
     // Private helper methods
+
+    /// Add a write to the key's history, in (timestamp, operation ID) order
+    /// like conflict resolution, keeping only the newest `history_limit`
+    fn record<T>(&mut self, key: &K, write: &LWWEntry<T>, value: Option<&V>) {
+        let limit = self.history_limit;
+        if limit == 0 {
+            return;
+        }
+        let writes = self.history.entry(key.clone()).or_default();
+        if writes.iter().any(|existing| existing.operation_id == write.operation_id) {
+            return;
+        }
+        let newer = |existing: &LWWEntry<Option<V>>| {
+            (existing.timestamp, existing.operation_id) > (write.timestamp, write.operation_id)
+        };
+        let index = writes.partition_point(newer);
+        if index < limit {
+            writes.insert(index, LWWEntry {
+                value: value.cloned(),
+                timestamp: write.timestamp,
+                user_id: write.user_id.clone(),
+                operation_id: write.operation_id,
+            });
+            writes.truncate(limit);
+        }
+    }
     
     fn should_update<T>(&self, existing: &LWWEntry<T>, new: &LWWEntry<V>) -> bool {
         new.timestamp > existing.timestamp ||
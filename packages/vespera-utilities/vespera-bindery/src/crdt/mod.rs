@@ -82,6 +82,7 @@
 //! - Weak reference tracking to prevent memory leaks
 //! - Automatic cleanup of tombstones and inactive data
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::{Arc, Weak};
 use serde::{Deserialize, Serialize};
@@ -144,16 +145,26 @@ impl OperationContext {
 }
 
 // Sub-modules for different CRDT layers
+pub mod intern;
 pub mod text_layer;
 pub mod tree_layer;
 pub mod metadata_layer;
 pub mod reference_layer;
+pub mod text_rope;
+#[cfg(feature = "yjs-compat")]
+pub mod delta;
+#[cfg(any(test, feature = "simulation"))]
+pub mod simulation;
 
 // Re-export CRDT implementations
+pub use intern::{Interner, Symbol};
 pub use text_layer::YTextCRDT;
-pub use tree_layer::VesperaTreeCRDT;
-pub use metadata_layer::{LWWMap, LWWMapStats};
-pub use reference_layer::{ORSet, ORSetStats};
+pub use tree_layer::{TreeChange, TreeChangeKind, VesperaTreeCRDT};
+pub use metadata_layer::{LWWEntry, LWWMap, LWWMapStats};
+pub use reference_layer::{ORSet, ORSetStats, ORTag};
+pub use text_rope::{TextLength, TextRope};
+#[cfg(feature = "yjs-compat")]
+pub use delta::CRDTDelta;
 
 /// Memory pool for reusing operation allocations
 #[derive(Debug, Clone)]
@@ -196,6 +207,21 @@ pub struct MemoryConfig {
     pub auto_gc_threshold: usize,
     pub max_vector_clock_entries: usize,
     pub aggressive_cleanup: bool,
+    pub storage_mode: StorageMode,
+}
+
+/// How a Codex stores its history and syncs with peers
+///
+/// Every replica of a Codex should use the same mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageMode {
+    /// Keep the operation log, and sync by replaying operations peers lack
+    #[default]
+    OperationLog,
+    /// Keep only the last `history_window` operations, and sync by
+    /// exchanging [`CRDTDelta`]s
+    #[cfg(feature = "yjs-compat")]
+    Delta { history_window: usize },
 }
 
 impl Default for MemoryConfig {
@@ -205,6 +231,7 @@ impl Default for MemoryConfig {
             auto_gc_threshold: 1000,
             max_vector_clock_entries: 50,
             aggressive_cleanup: false,
+            storage_mode: StorageMode::OperationLog,
         }
     }
 }
@@ -394,10 +421,24 @@ pub struct VesperaCRDT {
     /// Current vector clock
     pub vector_clock: VectorClock,
 
+    /// Clients retired from the vector clock, with the counter every later
+    /// clock is taken to have seen of them
+    #[serde(default)]
+    pub retired_clients: VectorClock,
+
+    /// Recent changes for peers, in delta mode (not serialized)
+    #[cfg(feature = "yjs-compat")]
+    #[serde(skip)]
+    delta_buffer: delta::DeltaBuffer,
+
     /// Memory pool for operation reuse (not serialized)
     #[serde(skip)]
     operation_pool: Option<OperationPool>,
 
+    /// Shared user and field IDs of the operation log (not serialized)
+    #[serde(skip)]
+    interner: Interner,
+
     /// Memory management configuration
     #[serde(skip)]
     memory_config: MemoryConfig,
@@ -429,7 +470,7 @@ pub struct CRDTOperation {
     pub operation: OperationType,
     
     /// User who performed the operation
-    pub user_id: UserId,
+    pub user_id: Symbol,
     
     /// Timestamp of the operation
     pub timestamp: DateTime<Utc>,
@@ -449,9 +490,9 @@ pub struct CRDTOperation {
 #[serde(tag = "type")]
 pub enum OperationType {
     /// Text operations (text_layer)
-    TextInsert { field_id: String, position: usize, content: String },
-    TextDelete { field_id: String, position: usize, length: usize },
-    TextFormat { field_id: String, position: usize, length: usize, format: TextFormat },
+    TextInsert { field_id: Symbol, position: usize, content: String },
+    TextDelete { field_id: Symbol, position: usize, length: usize },
+    TextFormat { field_id: Symbol, position: usize, length: usize, format: TextFormat },
     
     /// Tree operations (tree_layer)
     TreeInsert { parent_id: Option<CodexId>, position: usize, child_id: CodexId },
@@ -465,6 +506,13 @@ pub enum OperationType {
     /// Reference operations (reference_layer)
     ReferenceAdd { reference: CodexReference },
     ReferenceRemove { reference: CodexReference },
+    /// Several references added as one operation
+    ReferenceAddMany { references: Vec<CodexReference> },
+    /// Every observed reference to `to_codex_id`, whatever its type
+    ReferenceRemoveTarget { to_codex_id: CodexId },
+
+    /// Vector clock operations
+    RetireClients { clients: VectorClock },
 }
 
 /// CRDT layer identifier
@@ -475,6 +523,7 @@ pub enum CRDTLayer {
     Tree,
     Metadata,
     Reference,
+    Clock,
 }
 
 /// Text formatting information
@@ -553,8 +602,9 @@ impl VesperaCRDT {
     /// Create a new CRDT for a Codex
     pub fn new(codex_id: CodexId, created_by: UserId) -> Self {
         let now = Utc::now();
+        let mut interner = Interner::new();
         let mut vector_clock = VectorClock::new();
-        vector_clock.insert(created_by.clone(), 0);
+        vector_clock.insert(interner.intern(&created_by), 0);
 
         let memory_config = MemoryConfig::default();
         let operation_pool = Some(OperationPool::new(memory_config.max_operation_pool_size));
@@ -567,7 +617,11 @@ impl VesperaCRDT {
             reference_layer: ORSet::new(),
             operation_log: Vec::new(),
             vector_clock,
+            retired_clients: VectorClock::new(),
+            #[cfg(feature = "yjs-compat")]
+            delta_buffer: delta::DeltaBuffer::default(),
             operation_pool,
+            interner,
             memory_config,
             weak_self_ref: None,
             current_context: Some(OperationContext::new(created_by.clone())),
@@ -585,10 +639,16 @@ impl VesperaCRDT {
         memory_config: MemoryConfig
     ) -> Self {
         let now = Utc::now();
+        let mut interner = Interner::new();
         let mut vector_clock = VectorClock::new();
-        vector_clock.insert(created_by.clone(), 0);
+        vector_clock.insert(interner.intern(&created_by), 0);
 
         let operation_pool = Some(OperationPool::new(memory_config.max_operation_pool_size));
+        #[cfg(feature = "yjs-compat")]
+        let delta_buffer = match memory_config.storage_mode {
+            StorageMode::Delta { .. } => delta::DeltaBuffer::starting_at(vector_clock.clone()),
+            StorageMode::OperationLog => delta::DeltaBuffer::default(),
+        };
 
         Self {
             codex_id,
@@ -598,7 +658,11 @@ impl VesperaCRDT {
             reference_layer: ORSet::new(),
             operation_log: Vec::new(),
             vector_clock,
+            retired_clients: VectorClock::new(),
+            #[cfg(feature = "yjs-compat")]
+            delta_buffer,
             operation_pool,
+            interner,
             memory_config,
             weak_self_ref: None,
             current_context: Some(OperationContext::new(created_by.clone())),
@@ -720,11 +784,11 @@ impl VesperaCRDT {
     /// let operation = CRDTOperation {
     ///     id: Uuid::new_v4(),
     ///     operation: OperationType::TextInsert {
-    ///         field_id: "content".to_string(),
+    ///         field_id: "content".into(),
     ///         position: 0,
     ///         content: "Hello".to_string(),
     ///     },
-    ///     user_id: "user1".to_string(),
+    ///     user_id: "user1".into(),
     ///     timestamp: Utc::now(),
     ///     vector_clock: std::collections::HashMap::new(),
     ///     parents: vec![],
@@ -733,13 +797,26 @@ impl VesperaCRDT {
     ///
     /// crdt.apply_operation(operation).unwrap();
     /// ```
+    pub fn apply_operation(&mut self, operation: CRDTOperation) -> BinderyResult<()> {
+        self.apply(Cow::Owned(operation))
+    }
+
+    /// Apply an operation owned by someone else, such as a peer's log during
+    /// [`merge`](Self::merge)
+    ///
+    /// The operation is copied only once it has been applied and is kept in
+    /// the log.
+    pub fn apply_operation_ref(&mut self, operation: &CRDTOperation) -> BinderyResult<()> {
+        self.apply(Cow::Borrowed(operation))
+    }
+
     #[instrument(skip(self, operation), fields(
         codex_id = %self.codex_id,
         operation_type = ?operation.operation,
         user_id = %operation.user_id,
         operation_id = %operation.id
     ))]
-    pub fn apply_operation(&mut self, operation: CRDTOperation) -> BinderyResult<()> {
+    fn apply(&mut self, operation: Cow<'_, CRDTOperation>) -> BinderyResult<()> {
         debug!(
             codex_id = %self.codex_id,
             operation_id = %operation.id,
@@ -749,12 +826,19 @@ impl VesperaCRDT {
         );
 
         let start_time = std::time::Instant::now();
+        let author = self.interner.intern(&operation.user_id);
+
+        // Update vector clock; a retired client's old operations leave it
+        // retired, and new ones bring its entry back
+        let counter = operation.vector_clock.get(&author).copied().unwrap_or(0);
+        if self.retired_clients.get(&author).is_none_or(|&floor| counter > floor) {
+            let user_clock = self.vector_clock.entry(author.clone()).or_insert(0);
+            *user_clock = (*user_clock).max(counter);
+        }
 
-        // Update vector clock
-        let user_clock = self.vector_clock.entry(operation.user_id.clone()).or_insert(0);
-        *user_clock = (*user_clock).max(
-            operation.vector_clock.get(&operation.user_id).copied().unwrap_or(0)
-        );
+        // In delta mode, what the operation changes, for peers
+        #[cfg(feature = "yjs-compat")]
+        let fragment = self.delta_window().map(|_| self.fragment_for(&operation));
 
         // Apply operation to appropriate layer
         let result = match &operation.operation {
@@ -768,8 +852,25 @@ impl VesperaCRDT {
             }
             OperationType::MetadataSet { key, value } => {
                 debug!(key = %key, value_type = ?std::mem::discriminant(value), "Applying metadata set");
-                // Use borrowed references to avoid clones in hot paths
-                self.metadata_layer.set_borrowed(key, value);
+                // Resolve by the operation's own timestamp and ID rather than
+                // arrival time, so replicas agree whatever order ops arrive in
+                self.metadata_layer.set_with_metadata(
+                    key.clone(),
+                    value.clone(),
+                    operation.timestamp,
+                    author.clone(),
+                    operation.id,
+                );
+                Ok(())
+            }
+            OperationType::MetadataDelete { key } => {
+                debug!(key = %key, "Applying metadata delete");
+                self.metadata_layer.delete_with_metadata(
+                    key,
+                    operation.timestamp,
+                    author.clone(),
+                    operation.id,
+                );
                 Ok(())
             }
             OperationType::ReferenceAdd { reference } => {
@@ -779,7 +880,7 @@ impl VesperaCRDT {
                     reference_type = ?reference.reference_type,
                     "Adding reference"
                 );
-                self.reference_layer.add_borrowed(reference);
+                self.reference_layer.add_with_tag(reference.clone(), Self::reference_tag(&operation, &author, 0));
                 Ok(())
             }
             OperationType::ReferenceRemove { reference } => {
@@ -789,7 +890,44 @@ impl VesperaCRDT {
                     reference_type = ?reference.reference_type,
                     "Removing reference"
                 );
-                self.reference_layer.remove(reference);
+                let observed = self.observed_reference_tags(reference, &operation);
+                self.reference_layer.remove_tags(reference, &observed);
+                Ok(())
+            }
+            OperationType::ReferenceAddMany { references } => {
+                debug!(count = references.len(), "Adding references");
+                for (index, reference) in references.iter().enumerate() {
+                    self.reference_layer.add_with_tag(reference.clone(), Self::reference_tag(&operation, &author, index));
+                }
+                Ok(())
+            }
+            OperationType::ReferenceRemoveTarget { to_codex_id } => {
+                debug!(to_codex = %to_codex_id, "Removing references to target");
+                let targeting: Vec<CodexReference> = self.references_to(to_codex_id).into_iter().cloned().collect();
+                for reference in &targeting {
+                    let observed = self.observed_reference_tags(reference, &operation);
+                    self.reference_layer.remove_tags(reference, &observed);
+                }
+                Ok(())
+            }
+            OperationType::TreeInsert { parent_id, position, child_id } => {
+                debug!(child_id = %child_id, parent_id = ?parent_id, position = position, "Applying tree insert");
+                self.apply_tree_change(&operation);
+                Ok(())
+            }
+            OperationType::TreeMove { child_id, new_parent_id, position, .. } => {
+                debug!(child_id = %child_id, new_parent_id = ?new_parent_id, position = position, "Applying tree move");
+                self.apply_tree_change(&operation);
+                Ok(())
+            }
+            OperationType::TreeDelete { parent_id, child_id } => {
+                debug!(child_id = %child_id, parent_id = ?parent_id, "Applying tree delete");
+                self.apply_tree_change(&operation);
+                Ok(())
+            }
+            OperationType::RetireClients { clients } => {
+                debug!(clients = clients.len(), "Retiring clients from vector clock");
+                self.join_retirements(clients);
                 Ok(())
             }
             _ => {
@@ -843,11 +981,22 @@ impl VesperaCRDT {
 
         result?;
 
-        // Store timestamp and user_id before moving operation
-        let timestamp = operation.timestamp;
-        let user_id = operation.user_id.clone();
+        #[cfg(feature = "yjs-compat")]
+        if let Some((fragment, text_before)) = fragment {
+            self.buffer_fragment(&operation, fragment, text_before)?;
+        }
 
-        // Add to operation log with bounded growth - move instead of clone
+        // A borrowed operation is copied only now that it is kept
+        self.log_applied(operation.into_owned());
+        Ok(())
+    }
+
+    /// Add an operation whose effect is already applied to the log, with
+    /// bounded growth
+    fn log_applied(&mut self, mut operation: CRDTOperation) {
+        let timestamp = operation.timestamp;
+        self.share_ids(&mut operation);
+        let author = operation.user_id.clone();
         self.operation_log.push(operation);
 
         // Prevent unbounded growth by garbage collecting old operations
@@ -855,11 +1004,36 @@ impl VesperaCRDT {
 
         // Update timestamps using stored values
         self.updated_at = timestamp;
-        self.updated_by = user_id;
-
-        Ok(())
+        if self.updated_by != author {
+            self.updated_by = author.to_string();
+        }
     }
     
+    /// Apply a tree operation
+    fn apply_tree_change(&mut self, operation: &CRDTOperation) {
+        if let Some(change) = Self::tree_change(operation) {
+            self.tree_layer.apply_change(change);
+        }
+    }
+
+    /// The tree layer change a tree operation makes, identified by the
+    /// operation's own ID and timestamp so every replica orders it the same way
+    fn tree_change(operation: &CRDTOperation) -> Option<TreeChange> {
+        let (child_id, kind) = match &operation.operation {
+            OperationType::TreeInsert { parent_id, position, child_id } => {
+                (*child_id, TreeChangeKind::Attach { parent_id: *parent_id, position: *position })
+            }
+            OperationType::TreeMove { child_id, new_parent_id, position, .. } => {
+                (*child_id, TreeChangeKind::Attach { parent_id: *new_parent_id, position: *position })
+            }
+            OperationType::TreeDelete { parent_id, child_id } => {
+                (*child_id, TreeChangeKind::Detach { parent_id: *parent_id })
+            }
+            _ => return None,
+        };
+        Some(TreeChange { id: operation.id, timestamp: operation.timestamp, child_id, kind })
+    }
+
     /// Generate a new operation
     pub fn create_operation(
         &mut self,
@@ -867,10 +1041,15 @@ impl VesperaCRDT {
         user_id: UserId,
     ) -> CRDTOperation {
         let operation_id = Uuid::new_v4();
-        let timestamp = Utc::now();
+        // Later than the last applied operation even if the wall clock is
+        // coarse or behind, so a local write wins over what it overwrites
+        let timestamp = Utc::now().max(self.updated_at + chrono::Duration::nanoseconds(1));
         
-        // Increment user's clock
-        let user_clock = self.vector_clock.entry(user_id.clone()).or_insert(0);
+        let user_id = self.interner.intern(&user_id);
+
+        // Increment user's clock, carrying on from where it retired
+        let retired_at = self.retired_clients.get(&user_id).copied().unwrap_or(0);
+        let user_clock = self.vector_clock.entry(user_id.clone()).or_insert(retired_at);
         *user_clock += 1;
         
         // Determine layer
@@ -887,7 +1066,11 @@ impl VesperaCRDT {
             OperationType::MetadataDelete { .. } => CRDTLayer::Metadata,
             
             OperationType::ReferenceAdd { .. } | 
-            OperationType::ReferenceRemove { .. } => CRDTLayer::Reference,
+            OperationType::ReferenceRemove { .. } |
+            OperationType::ReferenceAddMany { .. } |
+            OperationType::ReferenceRemoveTarget { .. } => CRDTLayer::Reference,
+
+            OperationType::RetireClients { .. } => CRDTLayer::Clock,
         };
         
         CRDTOperation {
@@ -901,6 +1084,87 @@ impl VesperaCRDT {
         }
     }
     
+    /// Point the IDs of `operation` at this CRDT's shared copies
+    fn share_ids(&mut self, operation: &mut CRDTOperation) {
+        self.interner.share(&mut operation.user_id);
+        self.interner.share_keys(&mut operation.vector_clock);
+        match &mut operation.operation {
+            OperationType::TextInsert { field_id, .. }
+            | OperationType::TextDelete { field_id, .. }
+            | OperationType::TextFormat { field_id, .. } => self.interner.share(field_id),
+            OperationType::RetireClients { clients } => self.interner.share_keys(clients),
+            _ => {}
+        }
+    }
+
+    /// Share equal IDs across the whole CRDT, for example after it was deserialized
+    pub fn share_all_ids(&mut self) {
+        let mut operation_log = std::mem::take(&mut self.operation_log);
+        for operation in &mut operation_log {
+            self.share_ids(operation);
+        }
+        self.operation_log = operation_log;
+        self.interner.share_keys(&mut self.vector_clock);
+        self.interner.share_keys(&mut self.retired_clients);
+    }
+
+    /// OR-Set tag for the `index`th reference added by `operation`, derived
+    /// from the operation so every replica tags the same add the same way
+    fn reference_tag(operation: &CRDTOperation, author: &Symbol, index: usize) -> ORTag {
+        ORTag {
+            operation_id: operation.id,
+            user_id: author.clone(),
+            timestamp: operation.timestamp,
+            index: index as u32,
+        }
+    }
+
+    /// Record clients as retired at the given counters
+    fn join_retirements(&mut self, clients: &VectorClock) {
+        for (user_id, &counter) in clients {
+            let floor = self.retired_clients.entry(user_id.clone()).or_insert(0);
+            *floor = (*floor).max(counter);
+            // A replica that has seen less of the client, or more since,
+            // keeps its entry so its clocks stay exact
+            if self.vector_clock.get(user_id) == Some(floor) {
+                self.vector_clock.remove(user_id);
+            }
+        }
+    }
+
+    /// Tags of `reference` that `remove` observed: only the adds its author
+    /// had seen, not concurrent adds that reached this replica first. Adds no
+    /// longer in the log were collected as old, so were seen.
+    fn observed_reference_tags(&self, reference: &CodexReference, remove: &CRDTOperation) -> Vec<ORTag> {
+        self.reference_layer
+            .get_tags(reference)
+            .into_iter()
+            .filter(|tag| {
+                self.operation_log
+                    .iter()
+                    .find(|logged| logged.id == tag.operation_id)
+                    .is_none_or(|add| self.happened_before(add, remove))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `later`'s author had seen `earlier` when creating `later`
+    fn happened_before(&self, earlier: &CRDTOperation, later: &CRDTOperation) -> bool {
+        let seen = self.clock_entry(&later.vector_clock, &earlier.user_id);
+        earlier.id != later.id && seen >= earlier.vector_clock.get(&earlier.user_id).copied().unwrap_or(0)
+    }
+
+    /// `user_id`'s counter in `clock`; clocks made after a client retired
+    /// leave it out, having seen everything up to its retirement
+    pub(crate) fn clock_entry(&self, clock: &VectorClock, user_id: &str) -> u64 {
+        clock
+            .get(user_id)
+            .or_else(|| self.retired_clients.get(user_id))
+            .copied()
+            .unwrap_or(0)
+    }
+
     /// Set the operation context for subsequent operations
     pub fn set_operation_context(&mut self, context: OperationContext) {
         self.current_context = Some(context);
@@ -921,11 +1185,29 @@ impl VesperaCRDT {
         self.apply_operation(operation)
     }
     
+    /// Delete a metadata value
+    pub fn delete_metadata(&mut self, key: String) -> BinderyResult<()> {
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(OperationType::MetadataDelete { key }, user_id);
+        self.apply_operation(operation)
+    }
+
     /// Get metadata value
     pub fn get_metadata(&self, key: &str) -> Option<&TemplateValue> {
         self.metadata_layer.get(&key.to_string())
     }
 
+    /// Metadata value with who last wrote it and when
+    pub fn get_metadata_with_author(&self, key: &str) -> Option<&LWWEntry<TemplateValue>> {
+        self.metadata_layer.get_with_metadata(&key.to_string())
+    }
+
+    /// Up to `limit` of the latest writes to a metadata key, newest first;
+    /// deletes have no value
+    pub fn metadata_history(&self, key: &str, limit: usize) -> Vec<&LWWEntry<Option<TemplateValue>>> {
+        self.metadata_layer.key_history(&key.to_string(), limit)
+    }
+
     /// Set the title of this Codex
     pub fn set_title(&mut self, title: &str) -> BinderyResult<()> {
         let value = TemplateValue::Text {
@@ -948,6 +1230,7 @@ impl VesperaCRDT {
     /// Insert text at position
     pub fn insert_text(&mut self, field_id: String, position: usize, content: String) -> BinderyResult<()> {
         let user_id = self.get_operation_context().user_id;
+        let field_id = self.interner.intern(&field_id);
         let operation = self.create_operation(
             OperationType::TextInsert { field_id, position, content },
             user_id,
@@ -955,9 +1238,100 @@ impl VesperaCRDT {
         self.apply_operation(operation)
     }
 
+    /// Children of `parent_id` in the tree layer, in their replicated order
+    pub fn children(&self, parent_id: CodexId) -> Vec<CodexId> {
+        self.tree_layer.get_children(Some(parent_id))
+    }
+
+    /// `root_id` and everything below it, depth-first
+    pub fn subtree(&self, root_id: CodexId) -> Vec<CodexId> {
+        self.tree_layer.get_subtree(root_id)
+    }
+
+    /// Insert `child_id` under `parent_id` at `position`
+    ///
+    /// Unlike calling the tree layer directly, this records an operation, so
+    /// the change merges into other replicas.
+    pub fn add_child(&mut self, parent_id: CodexId, position: usize, child_id: CodexId) -> BinderyResult<()> {
+        self.check_tree_move(child_id, Some(parent_id))?;
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(
+            OperationType::TreeInsert { parent_id: Some(parent_id), position, child_id },
+            user_id,
+        );
+        self.apply_operation(operation)
+    }
+
+    /// Take `child_id` out from under `parent_id`
+    pub fn remove_child(&mut self, parent_id: CodexId, child_id: CodexId) -> BinderyResult<()> {
+        if self.tree_layer.get_parent(child_id) != Some(parent_id) {
+            return Err(crate::BinderyError::NotFound(format!("{} is not a child of {}", child_id, parent_id)));
+        }
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(
+            OperationType::TreeDelete { parent_id: Some(parent_id), child_id },
+            user_id,
+        );
+        self.apply_operation(operation)
+    }
+
+    /// Move `child_id` under `new_parent_id` (a root when `None`) at `position`
+    ///
+    /// Fails if the node is not in the tree or the move would make it its own
+    /// ancestor. Concurrent moves resolve as described in [`tree_layer`]: the
+    /// later one wins unless it would create a cycle by then, in which case
+    /// it is dropped on every replica.
+    pub fn reparent(&mut self, child_id: CodexId, new_parent_id: Option<CodexId>, position: usize) -> BinderyResult<()> {
+        if !self.tree_layer.contains(child_id) {
+            return Err(crate::BinderyError::NotFound(format!("{} is not in the tree", child_id)));
+        }
+        self.check_tree_move(child_id, new_parent_id)?;
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(
+            OperationType::TreeMove {
+                child_id,
+                old_parent_id: self.tree_layer.get_parent(child_id),
+                new_parent_id,
+                position,
+            },
+            user_id,
+        );
+        self.apply_operation(operation)
+    }
+
+    /// Move `child_id` to `position` among its siblings
+    pub fn reorder(&mut self, child_id: CodexId, position: usize) -> BinderyResult<()> {
+        if !self.tree_layer.contains(child_id) {
+            return Err(crate::BinderyError::NotFound(format!("{} is not in the tree", child_id)));
+        }
+        self.reparent(child_id, self.tree_layer.get_parent(child_id), position)
+    }
+
+    fn check_tree_move(&self, child_id: CodexId, parent_id: Option<CodexId>) -> BinderyResult<()> {
+        if let Some(parent_id) = parent_id {
+            if parent_id == child_id || self.tree_layer.is_descendant(parent_id, child_id) {
+                return Err(crate::BinderyError::CircularReferenceError(format!(
+                    "Moving {} under {} would create a cycle", child_id, parent_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Text of a field, for converting its byte positions to editor offsets
+    pub fn get_text(&self, field_id: &str) -> Option<TextRope> {
+        self.text_layer.get_text(field_id)
+    }
+
+    /// Length of a field's text in bytes, characters and UTF-16 units
+    pub fn get_text_len(&self, field_id: &str) -> Option<TextLength> {
+        self.text_layer.get_text_len(field_id)
+    }
+
     /// Delete text at position
     pub fn delete_text(&mut self, field_id: String, position: usize, length: usize) -> BinderyResult<()> {
         let user_id = self.get_operation_context().user_id;
+        let field_id = self.interner.intern(&field_id);
         let operation = self.create_operation(
             OperationType::TextDelete { field_id, position, length },
             user_id,
@@ -965,6 +1339,74 @@ impl VesperaCRDT {
         self.apply_operation(operation)
     }
 
+    /// Text fields as a Yjs update (v1 encoding), for Yjs editors to apply
+    #[cfg(feature = "yjs-compat")]
+    pub fn export_yjs_update(&self) -> BinderyResult<Vec<u8>> {
+        self.text_layer.encode_yjs_update(None)
+    }
+
+    /// The part of [`export_yjs_update`](Self::export_yjs_update) an editor
+    /// with `state_vector` does not have yet
+    #[cfg(feature = "yjs-compat")]
+    pub fn export_yjs_update_since(&self, state_vector: &[u8]) -> BinderyResult<Vec<u8>> {
+        self.text_layer.encode_yjs_update(Some(state_vector))
+    }
+
+    /// Yjs state vector of the text fields (v1 encoding)
+    #[cfg(feature = "yjs-compat")]
+    pub fn yjs_state_vector(&self) -> Vec<u8> {
+        self.text_layer.yjs_state_vector()
+    }
+
+    /// Apply a Yjs update (v1 encoding) from an editor to the text fields,
+    /// returning the IDs of the fields that changed
+    ///
+    /// Each `Y.Text` at the root of the editor's document is the field of the
+    /// same name. In delta mode peers get the update itself; otherwise each
+    /// changed field is logged as the text delete and insert that turn its
+    /// old text into the new, since peers only see logged operations.
+    #[cfg(feature = "yjs-compat")]
+    pub fn import_yjs_update(&mut self, update: &[u8]) -> BinderyResult<Vec<String>> {
+        let before = self.delta_window().map(|_| self.text_layer.yjs_state_vector());
+        let texts_before = match before {
+            Some(_) => HashMap::new(),
+            None => self.text_layer.snapshot(),
+        };
+        let changed = self.text_layer.apply_yjs_update(update)?;
+        if !changed.is_empty() {
+            self.updated_at = Utc::now();
+            match before {
+                Some(state_vector) => self.buffer_text_import(&state_vector)?,
+                None => self.log_text_import(&changed, &texts_before),
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Log imported text changes as operations, already applied
+    #[cfg(feature = "yjs-compat")]
+    fn log_text_import(&mut self, changed: &[String], texts_before: &HashMap<String, String>) {
+        let user_id = self.get_operation_context().user_id;
+        for field in changed {
+            let old = texts_before.get(field).map(String::as_str).unwrap_or("");
+            let new = self.text_layer.get_content(field).unwrap_or("").to_string();
+            let (position, length, content) = text_layer::text_edit(old, &new);
+            let field_id = self.interner.intern(field);
+
+            let mut edits = Vec::new();
+            if length > 0 {
+                edits.push(OperationType::TextDelete { field_id: field_id.clone(), position, length });
+            }
+            if !content.is_empty() {
+                edits.push(OperationType::TextInsert { field_id, position, content: content.to_string() });
+            }
+            for edit in edits {
+                let operation = self.create_operation(edit, user_id.clone());
+                self.log_applied(operation);
+            }
+        }
+    }
+
     /// Add reference to another Codex
     pub fn add_reference(&mut self, reference: CodexReference) -> BinderyResult<()> {
         let user_id = self.get_operation_context().user_id;
@@ -975,11 +1417,135 @@ impl VesperaCRDT {
         self.apply_operation(operation)
     }
     
+    /// Remove a reference to another Codex
+    pub fn remove_reference(&mut self, reference: CodexReference) -> BinderyResult<()> {
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(
+            OperationType::ReferenceRemove { reference },
+            user_id,
+        );
+        self.apply_operation(operation)
+    }
+    
+    /// Add several references as a single operation
+    ///
+    /// Does nothing, and logs no operation, when `references` is empty.
+    pub fn add_references(&mut self, references: Vec<CodexReference>) -> BinderyResult<()> {
+        if references.is_empty() {
+            return Ok(());
+        }
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(
+            OperationType::ReferenceAddMany { references },
+            user_id,
+        );
+        self.apply_operation(operation)
+    }
+
+    /// Remove every reference to `to_codex_id`, returning those removed
+    ///
+    /// Used when the target Codex is deleted. Logs no operation when there
+    /// are no such references.
+    pub fn remove_references_to(&mut self, to_codex_id: CodexId) -> BinderyResult<Vec<CodexReference>> {
+        let removed: Vec<CodexReference> = self.references_to(&to_codex_id).into_iter().cloned().collect();
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+        let user_id = self.get_operation_context().user_id;
+        let operation = self.create_operation(
+            OperationType::ReferenceRemoveTarget { to_codex_id },
+            user_id,
+        );
+        self.apply_operation(operation)?;
+        Ok(removed)
+    }
+
+    /// Remove references to Codices for which `exists` is false, returning
+    /// those removed
+    pub fn prune_references(&mut self, exists: impl Fn(&CodexId) -> bool) -> BinderyResult<Vec<CodexReference>> {
+        let mut dangling: Vec<CodexId> = self
+            .reference_layer
+            .iter()
+            .map(|reference| reference.to_codex_id)
+            .filter(|target| !exists(target))
+            .collect();
+        dangling.sort();
+        dangling.dedup();
+
+        let mut pruned = Vec::new();
+        for target in dangling {
+            pruned.extend(self.remove_references_to(target)?);
+        }
+        Ok(pruned)
+    }
+
     /// Get all references from this Codex
     pub fn get_references(&self) -> Vec<&CodexReference> {
         self.reference_layer.iter().collect()
     }
-    
+
+    /// References of the given type
+    pub fn references_of_type(&self, reference_type: &ReferenceType) -> Vec<&CodexReference> {
+        self.reference_layer
+            .iter()
+            .filter(|reference| &reference.reference_type == reference_type)
+            .collect()
+    }
+
+    /// References to the given Codex
+    pub fn references_to(&self, to_codex_id: &CodexId) -> Vec<&CodexReference> {
+        self.reference_layer
+            .iter()
+            .filter(|reference| &reference.to_codex_id == to_codex_id)
+            .collect()
+    }
+
+    /// Copy of this Codex's content as a new Codex `codex_id`, with a fresh
+    /// history authored by `created_by`
+    ///
+    /// `id_map` maps each Codex copied alongside this one, this one included,
+    /// to its copy. References to those are rewritten to the copies and other
+    /// references kept as they are; tree children that were not copied are
+    /// dropped, since a Codex has one parent.
+    pub fn duplicate(
+        &self,
+        codex_id: CodexId,
+        created_by: UserId,
+        id_map: &HashMap<CodexId, CodexId>,
+    ) -> BinderyResult<Self> {
+        let mut copy = Self::new_with_memory_config(codex_id, created_by, self.memory_config.clone());
+        let copied = |id: &CodexId| *id_map.get(id).unwrap_or(id);
+
+        let mut metadata: Vec<_> = self.metadata_layer.snapshot().into_iter().collect();
+        metadata.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (key, value) in metadata {
+            copy.set_metadata(key, value)?;
+        }
+
+        let mut text: Vec<_> = self.text_layer.snapshot().into_iter().collect();
+        text.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (field_id, content) in text {
+            copy.insert_text(field_id, 0, content)?;
+        }
+
+        let references = self
+            .reference_layer
+            .iter()
+            .map(|reference| CodexReference {
+                from_codex_id: codex_id,
+                to_codex_id: copied(&reference.to_codex_id),
+                ..reference.clone()
+            })
+            .collect();
+        copy.add_references(references)?;
+
+        let children = self.tree_layer.get_children(Some(self.codex_id));
+        for (position, child) in children.iter().filter_map(|child| id_map.get(child)).enumerate() {
+            copy.tree_layer.insert(Some(codex_id), position, *child)?;
+        }
+        Ok(copy)
+    }
+
     /// Get the current state as a snapshot
     pub fn snapshot(&self) -> CRDTSnapshot {
         CRDTSnapshot {
@@ -1063,6 +1629,14 @@ impl VesperaCRDT {
             ));
         }
 
+        // Delta mode logs only recent history, so exchange state instead
+        #[cfg(feature = "yjs-compat")]
+        if self.delta_window().is_some() {
+            let delta = other.delta_since(&self.vector_clock)?;
+            self.apply_delta(&delta)?;
+            return Ok(Vec::new());
+        }
+
         info!(
             codex_id = %self.codex_id,
             self_operations = self.operation_log.len(),
@@ -1086,7 +1660,7 @@ impl VesperaCRDT {
                     "Applying operation from merge source"
                 );
                 applied_operations.push(operation.id);
-                self.apply_operation(operation.clone())?;
+                self.apply_operation_ref(operation)?;
             }
         }
 
@@ -1119,7 +1693,16 @@ impl VesperaCRDT {
     
     /// Garbage collect operation log if it exceeds the threshold
     fn gc_operation_log_if_needed(&mut self) {
-        let max_operations = self.memory_config.auto_gc_threshold;
+        // Delta mode keeps only the history window; peers sync from deltas
+        let max_operations = match self.delta_window() {
+            Some(window) => {
+                if self.operation_log.len() > window {
+                    self.gc_operation_log(window);
+                }
+                return;
+            }
+            None => self.memory_config.auto_gc_threshold,
+        };
         let compact_to = max_operations / 2;
 
         if self.operation_log.len() > max_operations {
@@ -1138,24 +1721,64 @@ impl VesperaCRDT {
         }
     }
 
-    /// Garbage collect vector clock to prevent unbounded growth
+    /// Retire clients from the vector clock past
+    /// [`MemoryConfig::max_vector_clock_entries`], returning how many were
+    /// retired
+    ///
+    /// Dropping a client's entry would make later operations look concurrent
+    /// with everything it wrote, so a removal could miss adds its author had
+    /// seen. Instead a client is retired only once every other client with
+    /// operations in the log has seen all of its operations, and the
+    /// retirement is itself an operation: replicas apply it in causal order
+    /// through merge, and read the retired counter wherever a later clock
+    /// leaves the client out. Clients with nothing left in the log are
+    /// assumed to sync before writing again, as log compaction already
+    /// assumes. A retired client that writes again gets its entry back.
+    ///
+    /// Clients that are not yet safe to retire stay, even past the limit. In
+    /// delta mode none are retired: a peer's clock decides what it is sent,
+    /// and there a missing entry has to mean nothing seen.
     pub fn gc_vector_clock(&mut self) -> usize {
-        let initial_size = self.vector_clock.len();
-        if initial_size <= self.memory_config.max_vector_clock_entries {
+        let limit = self.memory_config.max_vector_clock_entries;
+        if self.vector_clock.len() <= limit || self.delta_window().is_some() {
             return 0;
         }
 
-        // Keep only the most recent vector clock entries
-        let mut entries: Vec<_> = self.vector_clock.iter().collect();
-        entries.sort_by_key(|(_, &clock)| std::cmp::Reverse(clock));
-
-        let mut new_vector_clock = VectorClock::new();
-        for (user_id, &clock) in entries.into_iter().take(self.memory_config.max_vector_clock_entries) {
-            new_vector_clock.insert(user_id.clone(), clock);
+        // Latest clock of each client, and where it last wrote, in the log
+        let mut latest: HashMap<&Symbol, (usize, &VectorClock)> = HashMap::new();
+        for (position, operation) in self.operation_log.iter().enumerate() {
+            latest.insert(&operation.user_id, (position, &operation.vector_clock));
+        }
+        let local_user = self.get_operation_context().user_id;
+        let mut stable: Vec<(Option<usize>, &Symbol, u64)> = self
+            .vector_clock
+            .iter()
+            .filter(|(user_id, _)| **user_id != local_user)
+            .filter(|(user_id, &counter)| {
+                latest.iter().all(|(author, (_, clock))| {
+                    author == user_id || self.clock_entry(clock, user_id) >= counter
+                })
+            })
+            .map(|(user_id, &counter)| (latest.get(user_id).map(|(position, _)| *position), user_id, counter))
+            .collect();
+        // Least recently active first
+        stable.sort();
+        stable.truncate(self.vector_clock.len() - limit);
+        if stable.is_empty() {
+            return 0;
         }
 
-        self.vector_clock = new_vector_clock;
-        initial_size - self.vector_clock.len()
+        let clients: VectorClock = stable
+            .into_iter()
+            .map(|(_, user_id, counter)| (user_id.clone(), counter))
+            .collect();
+        let retired = clients.len();
+        let operation = self.create_operation(OperationType::RetireClients { clients }, local_user);
+        if let Err(err) = self.apply_operation(operation) {
+            warn!(error = %err, "Failed to retire vector clock entries");
+            return 0;
+        }
+        retired
     }
 
     /// Configure memory management settings
@@ -1169,6 +1792,16 @@ impl VesperaCRDT {
             }
         }
 
+        // Buffer changes for peers from here on, or stop buffering
+        #[cfg(feature = "yjs-compat")]
+        match config.storage_mode {
+            StorageMode::Delta { .. } if !self.delta_buffer.is_tracking() => {
+                self.delta_buffer = delta::DeltaBuffer::starting_at(self.vector_clock.clone());
+            }
+            StorageMode::Delta { .. } => {}
+            StorageMode::OperationLog => self.delta_buffer = delta::DeltaBuffer::default(),
+        }
+
         // Apply immediate cleanup if aggressive mode is enabled
         if config.aggressive_cleanup {
             self.gc_operation_log(config.auto_gc_threshold / 2);
@@ -1176,6 +1809,15 @@ impl VesperaCRDT {
         }
     }
 
+    /// History window, when in delta mode
+    fn delta_window(&self) -> Option<usize> {
+        match self.memory_config.storage_mode {
+            StorageMode::OperationLog => None,
+            #[cfg(feature = "yjs-compat")]
+            StorageMode::Delta { history_window } => Some(history_window),
+        }
+    }
+
     /// Get memory configuration
     pub fn memory_config(&self) -> &MemoryConfig {
         &self.memory_config
@@ -1210,7 +1852,7 @@ impl VesperaCRDT {
                     user_id: String::new(),
                 },
             },
-            user_id: String::new(),
+            user_id: Symbol::from(""),
             timestamp: Utc::now(),
             vector_clock: VectorClock::new(),
             parents: Vec::new(),
@@ -1274,6 +1916,9 @@ impl VesperaCRDT {
         
         // GC tree layer tombstones with configurable limit
         self.tree_layer.gc_tombstones(max_tree_tombstones);
+
+        // IDs only the removed operations used
+        self.interner.prune();
         
         let stats = GarbageCollectionStats {
             operations_removed: old_operations_removed,
@@ -1305,8 +1950,16 @@ impl VesperaCRDT {
         let reference_stats = self.reference_layer.stats();
         let text_field_count = self.text_layer.field_count();
 
+        let (mut operation_log_bytes, mut shared_id_bytes) = (0, 0);
+        for operation in &self.operation_log {
+            let (bytes, shared) = self.operation_footprint(operation);
+            operation_log_bytes += bytes;
+            shared_id_bytes += shared;
+        }
+
         // Estimate total memory usage in bytes
-        let total_size_bytes = (operation_log_size * 200) + // ~200 bytes per operation
+        let total_size_bytes = operation_log_bytes +
+                               self.interner.text_bytes() + // each shared ID once
                                (metadata_stats.active_entries * 100) + // ~100 bytes per metadata entry
                                (reference_stats.total_elements * 150) + // ~150 bytes per reference
                                (text_field_count * 1000); // ~1KB per text field
@@ -1317,9 +1970,46 @@ impl VesperaCRDT {
             reference_stats,
             text_field_count,
             total_size_bytes,
+            operation_log_bytes,
+            interned_ids: self.interner.len(),
+            shared_id_bytes: shared_id_bytes.saturating_sub(self.interner.text_bytes()),
         }
     }
     
+    /// Estimated bytes of one logged operation, and the bytes of ID text it
+    /// refers to through the interner rather than holding itself
+    fn operation_footprint(&self, operation: &CRDTOperation) -> (usize, usize) {
+        let mut bytes = std::mem::size_of::<CRDTOperation>()
+            + operation.vector_clock.len() * std::mem::size_of::<(Symbol, u64)>()
+            + operation.parents.len() * std::mem::size_of::<OperationId>();
+        let mut shared = 0;
+        let mut count = |symbol: &Symbol| {
+            if self.interner.is_shared(symbol) {
+                shared += symbol.len();
+            } else {
+                bytes += symbol.len();
+            }
+        };
+        count(&operation.user_id);
+        operation.vector_clock.keys().for_each(&mut count);
+        match &operation.operation {
+            OperationType::TextInsert { field_id, content, .. } => {
+                count(field_id);
+                bytes += content.len();
+            }
+            OperationType::TextDelete { field_id, .. } | OperationType::TextFormat { field_id, .. } => count(field_id),
+            OperationType::MetadataSet { key, .. } => bytes += key.len() + 100, // ~100 bytes per value, as for entries
+            OperationType::MetadataDelete { key } => bytes += key.len(),
+            OperationType::ReferenceAddMany { references } => bytes += references.len() * std::mem::size_of::<CodexReference>(),
+            OperationType::RetireClients { clients } => {
+                clients.keys().for_each(&mut count);
+                bytes += clients.len() * std::mem::size_of::<(Symbol, u64)>();
+            }
+            _ => {}
+        }
+        (bytes, shared)
+    }
+
     /// Schedule periodic garbage collection based on age and size thresholds
     pub fn schedule_periodic_gc(
         &mut self,
@@ -1417,6 +2107,7 @@ impl VesperaCRDT {
         // Clear vector clock
         self.vector_clock.clear();
         self.vector_clock.shrink_to_fit();
+        self.retired_clients.clear();
 
         // Clear operation pool
         if let Some(ref mut pool) = self.operation_pool {
@@ -1433,10 +2124,10 @@ impl VesperaCRDT {
         let vector_clock_size = self.vector_clock.len();
 
         DetailedMemoryStats {
+            estimated_operation_log_mb: base_stats.operation_log_bytes as f64 / 1024.0 / 1024.0,
             base_stats,
             operation_pool_size,
             vector_clock_size,
-            estimated_operation_log_mb: (self.operation_log.len() * 200) as f64 / 1024.0 / 1024.0,
             estimated_vector_clock_mb: (vector_clock_size * 50) as f64 / 1024.0 / 1024.0,
             memory_config: self.memory_config.clone(),
         }
@@ -1494,6 +2185,12 @@ pub struct MemoryStats {
     pub reference_stats: ORSetStats,
     pub text_field_count: usize,
     pub total_size_bytes: usize,
+    /// Estimated bytes of the operation log, not counting shared IDs
+    pub operation_log_bytes: usize,
+    /// Distinct user and field IDs
+    pub interned_ids: usize,
+    /// ID text the operation log would hold again per operation if IDs weren't shared
+    pub shared_id_bytes: usize,
 }
 
 /// Detailed memory usage statistics with additional metrics
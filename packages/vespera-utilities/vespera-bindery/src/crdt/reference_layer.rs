@@ -104,7 +104,7 @@ use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use crate::types::UserId;
+use super::Symbol;
 
 /// Observed-Remove Set for managing cross-Codex references
 ///
@@ -188,10 +188,15 @@ pub struct ORTag {
     pub operation_id: Uuid,
     
     /// User who performed the operation
-    pub user_id: UserId,
+    pub user_id: Symbol,
     
     /// Timestamp of the operation
     pub timestamp: DateTime<Utc>,
+
+    /// Position of the element among those one operation added, so a bulk
+    /// add gives each element its own tag
+    #[serde(default)]
+    pub index: u32,
 }
 
 impl<T> ORSet<T>
@@ -246,8 +251,9 @@ where
     pub fn add(&mut self, element: T) -> ORTag {
         let tag = ORTag {
             operation_id: Uuid::new_v4(),
-            user_id: "system".to_string(), // TODO: Get user ID from operation context
+            user_id: Symbol::from("system"), // TODO: Get user ID from operation context
             timestamp: Utc::now(),
+            index: 0,
         };
 
         self.elements.entry(element).or_insert_with(HashSet::new).insert(tag.clone());
@@ -258,8 +264,9 @@ where
     pub fn add_borrowed(&mut self, element: &T) -> ORTag {
         let tag = ORTag {
             operation_id: Uuid::new_v4(),
-            user_id: "system".to_string(), // TODO: Get user ID from operation context
+            user_id: Symbol::from("system"), // TODO: Get user ID from operation context
             timestamp: Utc::now(),
+            index: 0,
         };
 
         self.elements.entry(element.clone()).or_insert_with(HashSet::new).insert(tag.clone());
@@ -0,0 +1,479 @@
+//! Deterministic multi-replica simulation of CRDT convergence
+//!
+//! A [`Simulation`] runs N in-memory [`VesperaCRDT`] replicas of one Codex
+//! over a simulated network. Each step a random replica makes a local change
+//! and some replicas send their state to their peers; messages arrive after
+//! a random delay, so they overtake each other, and partitions drop every
+//! message between the two sides until they heal. At the end the network
+//! heals, in-flight messages land, and a final round of anti-entropy runs,
+//! after which every replica must be in the same state.
+//!
+//! All randomness comes from one seeded RNG and simulated time, so a failing
+//! seed replays exactly. Replicas exchange state through
+//! [`VesperaCRDT::merge`], the same path real sync uses.
+//!
+//! Available in tests and with the `simulation` feature, so CRDT extensions
+//! outside this crate can fuzz their own operations by implementing
+//! [`OperationGenerator`]:
+//!
+//! ```rust,ignore
+//! use vespera_bindery::crdt::simulation::{Simulation, SimulationConfig};
+//!
+//! for seed in 0..100 {
+//!     let config = SimulationConfig { seed, ..SimulationConfig::default() };
+//!     Simulation::new(config).run().unwrap().assert_converged();
+//! }
+//! ```
+//!
+//! Retirement operations from `max_vector_clock_entries` get random IDs, so
+//! runs using it replay the same network but not the same operation IDs.
+//!
+//! Operation logs are compacted past
+//! [`MemoryConfig::auto_gc_threshold`](super::MemoryConfig) operations, after
+//! which merges re-send history; keep `operations` below it.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use uuid::Uuid;
+
+use super::{CodexReference, MemoryConfig, OperationType, ReferenceType, StorageMode, TemplateValue, VesperaCRDT};
+use crate::types::{CodexId, OperationId, UserId};
+use crate::BinderyResult;
+
+/// Simulated time at which every simulation starts
+const EPOCH_SECONDS: i64 = 1_700_000_000;
+
+/// Shape of a simulation run
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// Seed for every random choice in the run
+    pub seed: u64,
+    pub replicas: usize,
+    /// Local operations made across all replicas
+    pub operations: usize,
+    /// Chance per step that a replica sends its state to its peers
+    pub sync_probability: f64,
+    /// Most steps a message spends in flight; delays are uniform up to this
+    pub max_delay_steps: usize,
+    /// Chance per step that the network splits, when it is whole
+    pub partition_probability: f64,
+    /// Chance per step that a split network heals
+    pub heal_probability: f64,
+    /// Largest clock skew between replicas, in milliseconds either way
+    pub max_clock_skew_ms: i64,
+    /// Retire clients from each replica's vector clock past this many
+    /// entries after every local operation
+    pub max_vector_clock_entries: Option<usize>,
+    /// How replicas store history and sync
+    pub storage_mode: StorageMode,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            replicas: 4,
+            operations: 200,
+            sync_probability: 0.3,
+            max_delay_steps: 8,
+            partition_probability: 0.05,
+            heal_probability: 0.1,
+            max_clock_skew_ms: 50,
+            max_vector_clock_entries: None,
+            storage_mode: StorageMode::OperationLog,
+        }
+    }
+}
+
+/// Chooses each local operation in a simulation
+///
+/// Implement this to fuzz operations beyond the built-in [`RandomOperations`].
+/// Returning `None` skips the step.
+pub trait OperationGenerator {
+    fn generate(&mut self, rng: &mut StdRng, replica: &VesperaCRDT, user_id: &UserId, timestamp: DateTime<Utc>) -> Option<OperationType>;
+}
+
+/// Metadata sets and deletes and reference adds and removes, single and bulk,
+/// over a few keys and targets so replicas keep writing over each other
+#[derive(Debug, Clone)]
+pub struct RandomOperations {
+    keys: Vec<String>,
+    targets: Vec<CodexId>,
+}
+
+impl RandomOperations {
+    pub fn new(keys: usize, targets: usize) -> Self {
+        Self {
+            keys: (0..keys).map(|i| format!("key_{}", i)).collect(),
+            targets: (0..targets as u128).map(|i| Uuid::from_u128(i + 1)).collect(),
+        }
+    }
+}
+
+impl Default for RandomOperations {
+    fn default() -> Self {
+        Self::new(4, 3)
+    }
+}
+
+impl OperationGenerator for RandomOperations {
+    fn generate(&mut self, rng: &mut StdRng, replica: &VesperaCRDT, user_id: &UserId, timestamp: DateTime<Utc>) -> Option<OperationType> {
+        let key = self.keys.choose(rng)?.clone();
+        let reference = CodexReference {
+            from_codex_id: replica.codex_id,
+            to_codex_id: *self.targets.choose(rng)?,
+            reference_type: ReferenceType::References,
+            context: None,
+        };
+        Some(match rng.gen_range(0..10) {
+            0..=3 => OperationType::MetadataSet {
+                key,
+                value: TemplateValue::Text {
+                    value: format!("{}@{}", user_id, rng.gen::<u16>()),
+                    timestamp,
+                    user_id: user_id.clone(),
+                },
+            },
+            4 => OperationType::MetadataDelete { key },
+            5..=6 => OperationType::ReferenceAdd { reference },
+            7 => OperationType::ReferenceAddMany {
+                references: vec![
+                    reference.clone(),
+                    CodexReference {
+                        to_codex_id: *self.targets.choose(rng)?,
+                        reference_type: ReferenceType::Related,
+                        ..reference
+                    },
+                ],
+            },
+            8 => OperationType::ReferenceRemove { reference },
+            _ => OperationType::ReferenceRemoveTarget { to_codex_id: reference.to_codex_id },
+        })
+    }
+}
+
+/// What a replica ended up with, compared across replicas for convergence
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicaState {
+    pub metadata: HashMap<String, TemplateValue>,
+    pub references: HashSet<CodexReference>,
+    pub text: HashMap<String, String>,
+    /// Operations in the log; empty in delta mode, where each replica logs
+    /// only its own recent history
+    pub operations: HashSet<OperationId>,
+}
+
+impl ReplicaState {
+    pub fn of(replica: &VesperaCRDT) -> Self {
+        Self {
+            metadata: replica.metadata_layer.snapshot(),
+            references: replica.reference_layer.snapshot(),
+            text: replica.text_layer.snapshot(),
+            operations: match replica.memory_config().storage_mode {
+                StorageMode::OperationLog => replica.operation_log.iter().map(|op| op.id).collect(),
+                #[cfg(feature = "yjs-compat")]
+                StorageMode::Delta { .. } => HashSet::new(),
+            },
+        }
+    }
+}
+
+/// Outcome of a simulation run
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub operations_applied: usize,
+    pub messages_sent: usize,
+    pub messages_delivered: usize,
+    /// Messages lost to partitions
+    pub messages_dropped: usize,
+    pub partitions: usize,
+    /// Final state of each replica, by index
+    pub states: Vec<ReplicaState>,
+}
+
+impl SimulationReport {
+    /// Indices of replicas whose final state differs from replica 0's
+    pub fn divergent_replicas(&self) -> Vec<usize> {
+        let Some(first) = self.states.first() else {
+            return Vec::new();
+        };
+        (1..self.states.len()).filter(|&i| self.states[i] != *first).collect()
+    }
+
+    pub fn converged(&self) -> bool {
+        self.divergent_replicas().is_empty()
+    }
+
+    /// Panic, naming the seed and showing the first divergent replica, unless every
+    /// replica converged
+    pub fn assert_converged(&self) {
+        if let Some(&replica) = self.divergent_replicas().first() {
+            panic!(
+                "Replicas diverged with seed {}: replica 0 has {:#?}\nreplica {} has {:#?}",
+                self.seed, self.states[0], replica, self.states[replica]
+            );
+        }
+    }
+}
+
+/// State sent by one replica, in flight to another
+struct Message {
+    deliver_at: usize,
+    from: usize,
+    to: usize,
+    state: VesperaCRDT,
+}
+
+/// N replicas of one Codex on a simulated, unreliable network
+pub struct Simulation {
+    config: SimulationConfig,
+    rng: StdRng,
+    generator: Box<dyn OperationGenerator>,
+    replicas: Vec<VesperaCRDT>,
+    user_ids: Vec<UserId>,
+    clock_skew: Vec<Duration>,
+    in_flight: Vec<Message>,
+    /// Side of the split each replica is on, while the network is split
+    partition: Option<Vec<bool>>,
+    report: SimulationReport,
+}
+
+impl Simulation {
+    /// A simulation generating [`RandomOperations`]
+    pub fn new(config: SimulationConfig) -> Self {
+        Self::with_generator(config, RandomOperations::default())
+    }
+
+    pub fn with_generator(config: SimulationConfig, generator: impl OperationGenerator + 'static) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let codex_id = Uuid::from_u128(rng.gen());
+        let user_ids: Vec<UserId> = (0..config.replicas).map(|i| format!("replica-{}", i)).collect();
+        let mut memory_config = MemoryConfig { storage_mode: config.storage_mode, ..MemoryConfig::default() };
+        if let Some(limit) = config.max_vector_clock_entries {
+            memory_config.max_vector_clock_entries = limit;
+        }
+        let replicas = user_ids
+            .iter()
+            .map(|user_id| VesperaCRDT::new_with_memory_config(codex_id, user_id.clone(), memory_config.clone()))
+            .collect();
+        let clock_skew = (0..config.replicas)
+            .map(|_| Duration::milliseconds(rng.gen_range(-config.max_clock_skew_ms..=config.max_clock_skew_ms)))
+            .collect();
+        let report = SimulationReport {
+            seed: config.seed,
+            operations_applied: 0,
+            messages_sent: 0,
+            messages_delivered: 0,
+            messages_dropped: 0,
+            partitions: 0,
+            states: Vec::new(),
+        };
+
+        Self {
+            config,
+            rng,
+            generator: Box::new(generator),
+            replicas,
+            user_ids,
+            clock_skew,
+            in_flight: Vec::new(),
+            partition: None,
+            report,
+        }
+    }
+
+    /// Run every operation, then heal the network and sync until quiet
+    ///
+    /// The replicas stay available through [`replicas`](Self::replicas) for
+    /// checks of an extension's own state.
+    pub fn run(&mut self) -> BinderyResult<SimulationReport> {
+        for step in 0..self.config.operations {
+            self.update_partition();
+            self.local_operation(step)?;
+            for replica in 0..self.replicas.len() {
+                if self.rng.gen_bool(self.config.sync_probability) {
+                    self.broadcast(replica, step);
+                }
+            }
+            self.deliver(Some(step))?;
+        }
+
+        self.partition = None;
+        self.deliver(None)?;
+        self.anti_entropy()?;
+
+        self.report.states = self.replicas.iter().map(ReplicaState::of).collect();
+        Ok(self.report.clone())
+    }
+
+    /// Replicas in their current state, by index
+    pub fn replicas(&self) -> &[VesperaCRDT] {
+        &self.replicas
+    }
+
+    fn update_partition(&mut self) {
+        match self.partition {
+            Some(_) if self.rng.gen_bool(self.config.heal_probability) => self.partition = None,
+            None if self.replicas.len() > 1 && self.rng.gen_bool(self.config.partition_probability) => {
+                let mut sides: Vec<bool> = (0..self.replicas.len()).map(|_| self.rng.gen()).collect();
+                // Both sides non-empty
+                sides[0] = true;
+                let last = sides.len() - 1;
+                sides[last] = false;
+                self.partition = Some(sides);
+                self.report.partitions += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn local_operation(&mut self, step: usize) -> BinderyResult<()> {
+        let replica = self.rng.gen_range(0..self.replicas.len());
+        let user_id = self.user_ids[replica].clone();
+        let timestamp =
+            Utc.timestamp_opt(EPOCH_SECONDS, 0).unwrap() + Duration::milliseconds(step as i64) + self.clock_skew[replica];
+
+        let Some(kind) = self.generator.generate(&mut self.rng, &self.replicas[replica], &user_id, timestamp) else {
+            return Ok(());
+        };
+        let mut operation = self.replicas[replica].create_operation(kind, user_id);
+        // Deterministic in place of the random ID and wall-clock time
+        operation.id = Uuid::from_u128(self.rng.gen());
+        operation.timestamp = timestamp;
+        self.replicas[replica].apply_operation(operation)?;
+        self.report.operations_applied += 1;
+        if self.config.max_vector_clock_entries.is_some() {
+            self.replicas[replica].gc_vector_clock();
+        }
+        Ok(())
+    }
+
+    fn broadcast(&mut self, from: usize, step: usize) {
+        for to in (0..self.replicas.len()).filter(|&to| to != from) {
+            self.report.messages_sent += 1;
+            if self.partitioned(from, to) {
+                self.report.messages_dropped += 1;
+                continue;
+            }
+            let delay = self.rng.gen_range(0..=self.config.max_delay_steps);
+            self.in_flight.push(Message {
+                deliver_at: step + delay,
+                from,
+                to,
+                state: self.replicas[from].clone(),
+            });
+        }
+    }
+
+    fn partitioned(&self, a: usize, b: usize) -> bool {
+        self.partition.as_ref().is_some_and(|sides| sides[a] != sides[b])
+    }
+
+    /// Deliver messages due by `step`, or every message when `step` is None
+    fn deliver(&mut self, step: Option<usize>) -> BinderyResult<()> {
+        let (due, pending): (Vec<Message>, Vec<Message>) = std::mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition(|message| step.is_none_or(|step| message.deliver_at <= step));
+        self.in_flight = pending;
+
+        for message in due {
+            // A split that happened while the message was in flight loses it
+            if self.partitioned(message.from, message.to) {
+                self.report.messages_dropped += 1;
+                continue;
+            }
+            self.replicas[message.to].merge(&message.state)?;
+            self.report.messages_delivered += 1;
+        }
+        Ok(())
+    }
+
+    /// Merge every replica into the first, then the first into the rest
+    fn anti_entropy(&mut self) -> BinderyResult<()> {
+        let (first, rest) = self.replicas.split_first_mut().expect("simulation has replicas");
+        for replica in rest.iter() {
+            first.merge(replica)?;
+        }
+        for replica in rest.iter_mut() {
+            replica.merge(first)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replicas_converge_across_seeds() {
+        for seed in 0..16 {
+            let config = SimulationConfig {
+                seed,
+                partition_probability: 0.2,
+                ..SimulationConfig::default()
+            };
+            let report = Simulation::new(config).run().unwrap();
+            report.assert_converged();
+            assert_eq!(report.operations_applied, 200);
+            assert!(report.messages_delivered > 0);
+        }
+    }
+
+    #[test]
+    fn test_replicas_converge_with_retired_clients() {
+        let mut retired = 0;
+        for seed in 0..16 {
+            let config = SimulationConfig {
+                seed,
+                replicas: 6,
+                max_vector_clock_entries: Some(2),
+                ..SimulationConfig::default()
+            };
+            let mut simulation = Simulation::new(config);
+            simulation.run().unwrap().assert_converged();
+            retired += simulation.replicas().iter().filter(|replica| !replica.retired_clients.is_empty()).count();
+        }
+        assert!(retired > 0, "Some replica should have retired clients");
+    }
+
+    #[cfg(feature = "yjs-compat")]
+    #[test]
+    fn test_replicas_converge_in_delta_mode() {
+        for seed in 0..16 {
+            let config = SimulationConfig {
+                seed,
+                replicas: 5,
+                partition_probability: 0.2,
+                // Small enough that lagging peers get the whole state
+                storage_mode: StorageMode::Delta { history_window: 8 },
+                ..SimulationConfig::default()
+            };
+            let mut simulation = Simulation::new(config);
+            simulation.run().unwrap().assert_converged();
+            assert!(simulation.replicas().iter().all(|replica| replica.operation_log.len() <= 8));
+        }
+    }
+
+    #[test]
+    fn test_same_seed_replays_exactly() {
+        let config = SimulationConfig {
+            seed: 42,
+            replicas: 5,
+            partition_probability: 0.3,
+            ..SimulationConfig::default()
+        };
+        let first = Simulation::new(config.clone()).run().unwrap();
+        let second = Simulation::new(config).run().unwrap();
+
+        assert!(first.partitions > 0 && first.messages_dropped > 0);
+        assert_eq!(first.messages_sent, second.messages_sent);
+        assert_eq!(first.messages_dropped, second.messages_dropped);
+        assert_eq!(first.states, second.states);
+    }
+}
@@ -0,0 +1,220 @@
+//! Positional access to text-layer content
+//!
+//! Text-layer positions are UTF-8 byte offsets, while editors count
+//! differently: JavaScript editors (CodeMirror, ProseMirror, Monaco) use
+//! UTF-16 code units and Python uses characters. [`TextRope`] holds a field's
+//! text in chunks that each record where they start in all three units, so
+//! converting a position between them is a binary search plus a scan of one
+//! chunk rather than a scan of the whole text.
+//!
+//! ```rust
+//! use vespera_bindery::crdt::TextRope;
+//!
+//! let rope = TextRope::new("naïve 👋");
+//! // The wave starts at byte 7, character 6 and UTF-16 unit 6
+//! assert_eq!(rope.byte_to_utf16(7), Some(6));
+//! assert_eq!(rope.utf16_to_byte(6), Some(7));
+//! // Byte 3 is inside the two-byte "ï"
+//! assert_eq!(rope.byte_to_char(3), None);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Chunks are cut at the first character boundary past this many bytes
+const CHUNK_BYTES: usize = 1024;
+
+/// Length of a text, or an offset into one, in each unit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextLength {
+    /// UTF-8 bytes, the unit of text-layer positions
+    pub bytes: usize,
+    /// Unicode scalar values
+    pub chars: usize,
+    /// UTF-16 code units, the unit of JavaScript string indices
+    pub utf16: usize,
+}
+
+impl TextLength {
+    pub fn of(text: &str) -> Self {
+        text.chars().fold(Self::default(), |length, c| length.plus(c))
+    }
+
+    fn plus(self, c: char) -> Self {
+        Self {
+            bytes: self.bytes + c.len_utf8(),
+            chars: self.chars + 1,
+            utf16: self.utf16 + c.len_utf16(),
+        }
+    }
+}
+
+/// Unit an offset is counted in
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    Bytes,
+    Chars,
+    Utf16,
+}
+
+impl Unit {
+    fn of(self, length: TextLength) -> usize {
+        match self {
+            Unit::Bytes => length.bytes,
+            Unit::Chars => length.chars,
+            Unit::Utf16 => length.utf16,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    text: String,
+    /// Offset of the chunk's first character in the whole text
+    start: TextLength,
+}
+
+/// A field's text, chunked for offset conversion
+#[derive(Debug, Clone, Default)]
+pub struct TextRope {
+    chunks: Vec<Chunk>,
+    len: TextLength,
+}
+
+impl TextRope {
+    pub fn new(text: &str) -> Self {
+        Self::with_chunk_size(text, CHUNK_BYTES)
+    }
+
+    fn with_chunk_size(text: &str, chunk_bytes: usize) -> Self {
+        let mut chunks = Vec::with_capacity(text.len() / chunk_bytes + 1);
+        let mut start = TextLength::default();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut end = chunk_bytes.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            let (head, tail) = rest.split_at(end);
+            chunks.push(Chunk { text: head.to_string(), start });
+            start = head.chars().fold(start, TextLength::plus);
+            rest = tail;
+        }
+        Self { chunks, len: start }
+    }
+
+    /// Length in every unit
+    pub fn len(&self) -> TextLength {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len.bytes == 0
+    }
+
+    /// The text's chunks, in order
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().map(|chunk| chunk.text.as_str())
+    }
+
+    /// Character offset of byte offset `byte`, or None if it is past the end
+    /// or inside a character
+    pub fn byte_to_char(&self, byte: usize) -> Option<usize> {
+        self.convert(byte, Unit::Bytes).map(|offset| offset.chars)
+    }
+
+    pub fn char_to_byte(&self, char: usize) -> Option<usize> {
+        self.convert(char, Unit::Chars).map(|offset| offset.bytes)
+    }
+
+    /// UTF-16 offset of byte offset `byte`, for JavaScript editors
+    pub fn byte_to_utf16(&self, byte: usize) -> Option<usize> {
+        self.convert(byte, Unit::Bytes).map(|offset| offset.utf16)
+    }
+
+    /// Byte offset of UTF-16 offset `utf16`, or None if it is past the end or
+    /// between the halves of a surrogate pair
+    pub fn utf16_to_byte(&self, utf16: usize) -> Option<usize> {
+        self.convert(utf16, Unit::Utf16).map(|offset| offset.bytes)
+    }
+
+    pub fn char_to_utf16(&self, char: usize) -> Option<usize> {
+        self.convert(char, Unit::Chars).map(|offset| offset.utf16)
+    }
+
+    pub fn utf16_to_char(&self, utf16: usize) -> Option<usize> {
+        self.convert(utf16, Unit::Utf16).map(|offset| offset.chars)
+    }
+
+    /// `offset`, counted in `unit`, in every unit
+    fn convert(&self, offset: usize, unit: Unit) -> Option<TextLength> {
+        if offset == unit.of(self.len) {
+            return Some(self.len);
+        }
+        // Last chunk starting at or before the offset
+        let index = self.chunks.partition_point(|chunk| unit.of(chunk.start) <= offset).checked_sub(1)?;
+        let chunk = &self.chunks[index];
+        let mut position = chunk.start;
+        for c in chunk.text.chars() {
+            if unit.of(position) >= offset {
+                break;
+            }
+            position = position.plus(c);
+        }
+        (unit.of(position) == offset).then_some(position)
+    }
+}
+
+impl From<&str> for TextRope {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl fmt::Display for TextRope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Café 👋 naïve 日本語 ok";
+
+    #[test]
+    fn test_conversions_match_std_across_chunks() {
+        // Tiny chunks so every multi-byte character sits near a boundary
+        let rope = TextRope::with_chunk_size(TEXT, 3);
+        assert!(rope.chunks().count() > 5);
+        assert_eq!(rope.to_string(), TEXT);
+        assert_eq!(rope.len(), TextLength::of(TEXT));
+
+        let mut utf16 = 0;
+        for (char_index, (byte, c)) in TEXT.char_indices().enumerate() {
+            assert_eq!(rope.byte_to_char(byte), Some(char_index));
+            assert_eq!(rope.char_to_byte(char_index), Some(byte));
+            assert_eq!(rope.byte_to_utf16(byte), Some(utf16));
+            assert_eq!(rope.utf16_to_byte(utf16), Some(byte));
+            assert_eq!(rope.utf16_to_char(utf16), Some(char_index));
+            utf16 += c.len_utf16();
+        }
+
+        let end = rope.len();
+        assert_eq!(rope.byte_to_utf16(end.bytes), Some(end.utf16));
+        assert_eq!(rope.char_to_byte(end.chars + 1), None);
+    }
+
+    #[test]
+    fn test_offsets_inside_characters_are_rejected() {
+        let rope = TextRope::new(TEXT);
+        let wave = TEXT.find('👋').unwrap();
+        assert_eq!(rope.byte_to_char(wave + 1), None);
+        // Between the surrogate halves of the wave
+        let wave_utf16 = rope.byte_to_utf16(wave).unwrap();
+        assert_eq!(rope.utf16_to_byte(wave_utf16 + 1), None);
+        assert!(TextRope::new("").is_empty());
+        assert_eq!(TextRope::new("").byte_to_char(0), Some(0));
+    }
+}
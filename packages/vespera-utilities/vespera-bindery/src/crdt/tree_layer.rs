@@ -32,25 +32,26 @@
 //!
 //! ## Conflict Resolution Strategy
 //!
-//! The tree CRDT handles conflicts through position-based resolution:
+//! Every attach (insert or move) and detach is kept as a [`TreeChange`]
+//! ordered by timestamp, then change ID. The children and parent maps are
+//! what replaying that history gives, so replicas that have seen the same
+//! changes have the same tree whatever order they arrived in; a change
+//! arriving out of order triggers a replay.
 //!
 //! ### Move Conflicts
-//! When multiple users move the same node:
-//! 1. **Cycle Check**: Ensure no operation creates cycles
-//! 2. **Timestamp Ordering**: Use operation timestamps for ordering
-//! 3. **Position Adjustment**: Resolve position conflicts deterministically
+//! When multiple users move the same node, the later move wins. A move that
+//! would create a cycle when its turn comes in the replay is skipped and the
+//! node stays where it was, so two nodes moved into each other concurrently
+//! end up with the earlier move applied and no node lost.
 //!
 //! ### Insert Conflicts
-//! When multiple users insert at the same position:
-//! 1. **Position Sharing**: Multiple nodes can share the same logical position
-//! 2. **Deterministic Ordering**: Use node IDs for consistent final order
-//! 3. **Intention Preservation**: Maintain user's intended position as much as possible
+//! Each attach inserts at its position in the children list as it stands at
+//! that point of the replay, which gives every replica the same order.
 //!
 //! ### Delete Conflicts
-//! When nodes are deleted concurrently with moves:
-//! 1. **Tombstone Tracking**: Mark relationships as deleted
-//! 2. **Operation Ordering**: Delete wins over concurrent moves
-//! 3. **Subtree Handling**: Handle deletion of non-leaf nodes gracefully
+//! A detach removes the node only if it is still under the parent it names,
+//! so it loses to a later move elsewhere; an attach after a detach restores
+//! the relationship.
 //!
 //! # Performance Characteristics
 //!
@@ -117,9 +118,38 @@
 //! - **Deterministic Ordering**: Child ordering is consistent across replicas
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use crate::{BinderyResult, types::CodexId};
 
+/// One attach or detach, as replicated between peers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeChange {
+    /// Unique ID, the operation's ID for replicated changes
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub child_id: CodexId,
+    pub kind: TreeChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TreeChangeKind {
+    /// Put the child under `parent_id` at `position`, moving it from
+    /// wherever it is
+    Attach { parent_id: Option<CodexId>, position: usize },
+    /// Take the child from `parent_id`, if it is still there
+    Detach { parent_id: Option<CodexId> },
+}
+
+impl TreeChange {
+    /// Where the change sits in the history
+    fn order(&self) -> (DateTime<Utc>, Uuid) {
+        (self.timestamp, self.id)
+    }
+}
+
 /// CRDT for hierarchical tree structure of Codices
 ///
 /// VesperaTreeCRDT manages the hierarchical relationships between documents in a
@@ -195,6 +225,10 @@ pub struct VesperaTreeCRDT {
     
     /// Operation counter for ordering
     operation_counter: u64,
+
+    /// Every change applied, in history order; the maps above are its replay
+    #[serde(default)]
+    history: Vec<TreeChange>,
 }
 
 impl VesperaTreeCRDT {
@@ -205,6 +239,7 @@ impl VesperaTreeCRDT {
             parents: HashMap::new(),
             tombstones: HashSet::new(),
             operation_counter: 0,
+            history: Vec::new(),
         }
     }
     
@@ -285,20 +320,7 @@ impl VesperaTreeCRDT {
             }
         }
         
-        // Remove child from its current parent (if any)
-        if let Some(old_parent) = self.parents.get(&child_id).copied() {
-            self.remove_from_parent(old_parent, child_id);
-        }
-        
-        // Add to new parent
-        let children = self.children.entry(parent_id).or_insert_with(Vec::new);
-        let insert_position = position.min(children.len());
-        children.insert(insert_position, child_id);
-        
-        // Update parent mapping
-        self.parents.insert(child_id, parent_id);
-        
-        self.operation_counter += 1;
+        self.record(child_id, TreeChangeKind::Attach { parent_id, position });
         Ok(())
     }
     
@@ -311,14 +333,7 @@ impl VesperaTreeCRDT {
             ));
         }
         
-        // Add to tombstones
-        self.tombstones.insert((parent_id, child_id));
-        
-        // Remove from data structures
-        self.remove_from_parent(parent_id, child_id);
-        self.parents.remove(&child_id);
-        
-        self.operation_counter += 1;
+        self.record(child_id, TreeChangeKind::Detach { parent_id });
         Ok(())
     }
     
@@ -395,8 +410,6 @@ impl VesperaTreeCRDT {
     /// assert_eq!(tree.get_children(Some(folder2)), vec![document]);
     /// ```
     pub fn move_node(&mut self, child_id: CodexId, new_parent_id: Option<CodexId>, position: usize) -> BinderyResult<()> {
-        let old_parent_id = self.parents.get(&child_id).copied().flatten();
-        
         // Check for cycles
         if let Some(parent) = new_parent_id {
             if self.would_create_cycle(parent, child_id)? {
@@ -406,20 +419,77 @@ impl VesperaTreeCRDT {
             }
         }
         
-        // Remove from old parent
-        self.remove_from_parent(old_parent_id, child_id);
-        
-        // Add to new parent
-        let children = self.children.entry(new_parent_id).or_insert_with(Vec::new);
-        let insert_position = position.min(children.len());
-        children.insert(insert_position, child_id);
-        
-        // Update parent mapping
-        self.parents.insert(child_id, new_parent_id);
-        
-        self.operation_counter += 1;
+        self.record(child_id, TreeChangeKind::Attach { parent_id: new_parent_id, position });
         Ok(())
     }
+
+    /// Apply a change made here or by a peer
+    ///
+    /// Returns false if the change was already applied. A change older than
+    /// the latest one applied replays the history, which is what makes
+    /// replicas agree. An attach that would create a cycle at its point in
+    /// the history is kept but has no effect.
+    pub fn apply_change(&mut self, change: TreeChange) -> bool {
+        self.apply_changes(std::slice::from_ref(&change)) == 1
+    }
+
+    /// Apply a batch of changes, replaying the history at most once
+    ///
+    /// Returns how many were new.
+    pub fn apply_changes(&mut self, changes: &[TreeChange]) -> usize {
+        if self.history.is_empty() && !self.parents.is_empty() {
+            self.history = self.seeded_history();
+        }
+
+        let mut applied = 0;
+        let mut needs_replay = false;
+        for change in changes {
+            let index = match self.history.binary_search_by_key(&change.order(), TreeChange::order) {
+                Ok(_) => continue,
+                Err(index) => index,
+            };
+            self.history.insert(index, change.clone());
+            applied += 1;
+            if index + 1 < self.history.len() {
+                needs_replay = true;
+            } else if !needs_replay {
+                self.step(change);
+            }
+        }
+        if needs_replay {
+            self.replay();
+        }
+
+        self.operation_counter += applied as u64;
+        applied
+    }
+
+    /// Every change that makes up the tree, in history order
+    pub fn changes(&self) -> Vec<TreeChange> {
+        if self.history.is_empty() && !self.parents.is_empty() {
+            self.seeded_history()
+        } else {
+            self.history.clone()
+        }
+    }
+
+    /// Whether `node_id` has a place in the tree
+    pub fn contains(&self, node_id: CodexId) -> bool {
+        self.parents.contains_key(&node_id)
+    }
+
+    /// `root_id` and everything below it, depth-first with children in order
+    pub fn get_subtree(&self, root_id: CodexId) -> Vec<CodexId> {
+        let mut subtree = Vec::new();
+        let mut stack = vec![root_id];
+
+        while let Some(node_id) = stack.pop() {
+            subtree.push(node_id);
+            stack.extend(self.get_children(Some(node_id)).into_iter().rev());
+        }
+
+        subtree
+    }
     
     /// Get children of a node
     pub fn get_children(&self, parent_id: Option<CodexId>) -> Vec<CodexId> {
@@ -695,8 +765,77 @@ impl VesperaTreeCRDT {
     }
     
     fn would_create_cycle(&self, parent_id: CodexId, child_id: CodexId) -> BinderyResult<bool> {
-        // A cycle would be created if parent_id is a descendant of child_id
-        Ok(self.is_descendant(parent_id, child_id))
+        // A cycle would be created if parent_id is child_id or one of its descendants
+        Ok(parent_id == child_id || self.is_descendant(parent_id, child_id))
+    }
+
+    /// Record a local change, later than everything applied so far
+    fn record(&mut self, child_id: CodexId, kind: TreeChangeKind) {
+        let now = Utc::now();
+        let timestamp = match self.history.last() {
+            Some(last) => now.max(last.timestamp + Duration::nanoseconds(1)),
+            None => now,
+        };
+        self.apply_change(TreeChange { id: Uuid::new_v4(), timestamp, child_id, kind });
+    }
+
+    /// Apply one change to the maps
+    fn step(&mut self, change: &TreeChange) {
+        let child_id = change.child_id;
+        match change.kind {
+            TreeChangeKind::Attach { parent_id, position } => {
+                if parent_id.is_some_and(|parent| parent == child_id || self.is_descendant(parent, child_id)) {
+                    return;
+                }
+                if let Some(old_parent) = self.parents.get(&child_id).copied() {
+                    self.remove_from_parent(old_parent, child_id);
+                }
+                let children = self.children.entry(parent_id).or_default();
+                children.insert(position.min(children.len()), child_id);
+                self.parents.insert(child_id, parent_id);
+                self.tombstones.remove(&(parent_id, child_id));
+            }
+            TreeChangeKind::Detach { parent_id } => {
+                if self.has_relationship(parent_id, child_id) {
+                    self.remove_from_parent(parent_id, child_id);
+                    self.parents.remove(&child_id);
+                    self.tombstones.insert((parent_id, child_id));
+                }
+            }
+        }
+    }
+
+    /// Rebuild the maps from the history
+    fn replay(&mut self) {
+        self.children.clear();
+        self.parents.clear();
+        self.tombstones.clear();
+
+        let history = std::mem::take(&mut self.history);
+        for change in &history {
+            self.step(change);
+        }
+        self.history = history;
+    }
+
+    /// Turn the current tree into history, for trees saved before history
+    /// was kept; peers with the same tree derive the same changes
+    fn seeded_history(&self) -> Vec<TreeChange> {
+        let mut history = Vec::new();
+        let mut parents: Vec<Option<CodexId>> = self.children.keys().copied().collect();
+        parents.sort();
+        for parent_id in parents {
+            for (position, child_id) in self.get_children(parent_id).into_iter().enumerate() {
+                let id = Uuid::from_u128(history.len() as u128 + 1);
+                history.push(TreeChange {
+                    id,
+                    timestamp: DateTime::<Utc>::MIN_UTC,
+                    child_id,
+                    kind: TreeChangeKind::Attach { parent_id, position },
+                });
+            }
+        }
+        history
     }
     
     fn has_cycle_from_node(&self, start_node: CodexId) -> bool {
@@ -749,6 +888,9 @@ impl VesperaTreeCRDT {
         self.tombstones.clear();
         self.tombstones.shrink_to_fit();
         
+        self.history.clear();
+        self.history.shrink_to_fit();
+        
         self.operation_counter = 0;
     }
     
@@ -757,6 +899,7 @@ impl VesperaTreeCRDT {
         self.children.shrink_to_fit();
         self.parents.shrink_to_fit();
         self.tombstones.shrink_to_fit();
+        self.history.shrink_to_fit();
         
         for children_list in self.children.values_mut() {
             children_list.shrink_to_fit();
@@ -785,4 +928,113 @@ impl Drop for VesperaTreeCRDT {
     fn drop(&mut self) {
         self.cleanup();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(seconds: i64, child_id: CodexId, kind: TreeChangeKind) -> TreeChange {
+        TreeChange {
+            id: Uuid::new_v4(),
+            timestamp: DateTime::<Utc>::UNIX_EPOCH + Duration::seconds(seconds),
+            child_id,
+            kind,
+        }
+    }
+
+    fn attach(seconds: i64, child_id: CodexId, parent_id: Option<CodexId>, position: usize) -> TreeChange {
+        change(seconds, child_id, TreeChangeKind::Attach { parent_id, position })
+    }
+
+    fn replica(changes: &[TreeChange]) -> VesperaTreeCRDT {
+        let mut tree = VesperaTreeCRDT::new();
+        for change in changes {
+            tree.apply_change(change.clone());
+        }
+        tree
+    }
+
+    #[test]
+    fn test_concurrent_moves_into_each_other_keep_the_earlier() {
+        let (root, a, b) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let base = vec![
+            attach(0, root, None, 0),
+            attach(1, a, Some(root), 0),
+            attach(2, b, Some(root), 1),
+        ];
+        // Each replica makes a move that is fine on its own
+        let a_under_b = attach(10, a, Some(b), 0);
+        let b_under_a = attach(11, b, Some(a), 0);
+
+        let mut first = replica(&base);
+        first.apply_change(a_under_b.clone());
+        let mut second = replica(&base);
+        second.apply_change(b_under_a.clone());
+
+        assert!(first.apply_change(b_under_a));
+        assert!(second.apply_change(a_under_b));
+
+        for tree in [&first, &second] {
+            tree.validate().unwrap();
+            assert_eq!(tree.get_parent(a), Some(b));
+            assert_eq!(tree.get_parent(b), Some(root));
+            assert_eq!(tree.get_subtree(root), vec![root, b, a]);
+        }
+    }
+
+    #[test]
+    fn test_merge_order_does_not_change_the_tree() {
+        let (root, a, b, c, d) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let changes = vec![
+            attach(0, root, None, 0),
+            attach(1, a, Some(root), 0),
+            // Concurrent inserts at the same position
+            attach(2, b, Some(root), 0),
+            attach(2, c, Some(root), 0),
+            attach(3, d, Some(a), 0),
+            // A reorder, a move and a detach that loses to a later move
+            attach(4, a, Some(root), 2),
+            change(5, d, TreeChangeKind::Detach { parent_id: Some(a) }),
+            attach(5, d, Some(c), 0),
+        ];
+
+        let expected = replica(&changes);
+        let mut reversed = changes.clone();
+        reversed.reverse();
+        let mut interleaved: Vec<TreeChange> = changes.iter().step_by(2).cloned().collect();
+        interleaved.extend(changes.iter().skip(1).step_by(2).cloned());
+
+        for order in [reversed, interleaved] {
+            let tree = replica(&order);
+            tree.validate().unwrap();
+            assert_eq!(tree.get_roots(), expected.get_roots());
+            assert_eq!(tree.get_children(Some(root)), expected.get_children(Some(root)));
+            assert_eq!(tree.snapshot(), expected.snapshot());
+        }
+        assert_eq!(expected.get_children(Some(root)).len(), 3);
+        assert_eq!(expected.get_all_nodes().len(), 5);
+    }
+
+    #[test]
+    fn test_applying_a_change_twice_has_no_effect() {
+        let (root, a) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut tree = replica(&[attach(0, root, None, 0)]);
+        let insert = attach(1, a, Some(root), 0);
+
+        assert!(tree.apply_change(insert.clone()));
+        assert!(!tree.apply_change(insert));
+        assert_eq!(tree.get_children(Some(root)), vec![a]);
+    }
+
+    #[test]
+    fn test_local_moves_reject_cycles() {
+        let (root, child) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut tree = VesperaTreeCRDT::new();
+        tree.insert(None, 0, root).unwrap();
+        tree.insert(Some(root), 0, child).unwrap();
+
+        assert!(tree.move_node(root, Some(child), 0).is_err());
+        assert!(tree.move_node(root, Some(root), 0).is_err());
+        assert_eq!(tree.get_path(child), vec![root, child]);
+    }
+}
@@ -1,22 +1,45 @@
 //! Database persistence module for Vespera Bindery
 //! 
-//! Provides SQLite persistence for tasks, roles, and other data structures
+//! Provides SQLite persistence for tasks, roles, and other data structures.
+//! Task and Codex index persistence is also exposed through the
+//! [`storage::StorageBackend`] trait, with Postgres behind the `postgres` feature.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::{SqlitePool, SqlitePoolOptions}, Pool, Sqlite, Row};
-use chrono::{DateTime, Utc};
+use sqlx::{sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions}, Pool, Sqlite, Row};
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 use crate::migration::MigrationManager;
+use crate::pagination::{self, Page, PageRequest};
 use futures::future::try_join_all;
 use tokio::sync::Semaphore;
 
+pub mod slow_queries;
+pub mod storage;
+pub mod tuning;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(feature = "db-encryption")]
+pub mod encryption;
+
+pub use slow_queries::{BindParam, SlowQuerySummary};
+pub use storage::{open_storage, SqlDialect, StorageBackend, StorageConfig};
+pub use tuning::{PoolAutoTuneConfig, PoolSample, PoolTuningReport, TuningAction, TuningPlan};
+
 /// Maximum recursion depth for task creation to prevent stack overflow
 pub const MAX_TASK_DEPTH: usize = 10;
 /// Maximum concurrent subtask operations to prevent connection pool exhaustion
 const MAX_CONCURRENT_SUBTASKS: usize = 5;
+/// Pages copied per backup step, so writers are not locked out for the whole backup
+const BACKUP_PAGES_PER_STEP: i32 = 256;
+/// Maximum number of consecutive busy/locked backup steps before giving up
+const BACKUP_MAX_BUSY_RETRIES: u32 = 100;
+/// Change events buffered per subscriber before it starts lagging
+const CHANGE_EVENT_CAPACITY: usize = 1024;
+/// Longest date range a dashboard time series covers
+pub const MAX_TIME_SERIES_DAYS: i64 = 731;
 // TODO: Add observability when dependencies are resolved
 // use crate::observability::{
 //     instrumentation::DatabaseInstrumentation,
@@ -24,7 +47,8 @@ const MAX_CONCURRENT_SUBTASKS: usize = 5;
 //     instrument,
 // };
 use tracing::{info, warn, error, debug, instrument};
-use std::collections::{VecDeque, HashSet};
+use std::collections::{VecDeque, HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::time::Instant;
@@ -65,15 +89,85 @@ pub struct TaskDashboard {
     pub upcoming_tasks: Vec<TaskSummary>,
     pub project_breakdown: serde_json::Value,
     pub completion_rate: f64,
+    /// Mean hours from creation to completion
     pub average_completion_time: Option<f64>,
+    /// Daily series, when a date range was asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_series: Option<TaskTimeSeries>,
+}
+
+/// Date range and task filter for dashboard time series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeSeriesQuery {
+    /// First day, inclusive (UTC)
+    pub from: NaiveDate,
+    /// Last day, inclusive (UTC)
+    pub to: NaiveDate,
+    /// Only tasks in this project
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Only tasks with this tag or label key
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl TimeSeriesQuery {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Self {
+        Self { from, to, project_id: None, label: None }
+    }
+
+    /// The `days` days up to and including today
+    pub fn last_days(days: u32) -> Self {
+        let to = Utc::now().date_naive();
+        let from = to - chrono::Duration::days(i64::from(days.max(1)) - 1);
+        Self::new(from, to)
+    }
+
+    pub fn with_project(mut self, project_id: impl Into<String>) -> Self {
+        self.project_id = Some(project_id.into());
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+/// Task activity on one day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyTaskCounts {
+    pub day: NaiveDate,
+    pub created: i64,
+    pub completed: i64,
+    /// Tasks still open at the end of the day, for burndown charts
+    pub remaining: i64,
+}
+
+/// Hours from creation to completion of tasks completed in the range
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CycleTimePercentiles {
+    pub samples: i64,
+    pub p50_hours: Option<f64>,
+    pub p75_hours: Option<f64>,
+    pub p90_hours: Option<f64>,
+    pub p95_hours: Option<f64>,
+}
+
+/// Dashboard series ready for charting, one entry per day of the range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskTimeSeries {
+    pub query: TimeSeriesQuery,
+    pub days: Vec<DailyTaskCounts>,
+    pub cycle_time: CycleTimePercentiles,
 }
 
 /// Connection pool configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabasePoolConfig {
-    /// Maximum number of connections in the pool
+    /// Maximum number of connections in the read-only pool
     pub max_connections: u32,
-    /// Minimum number of connections in the pool
+    /// Minimum number of connections in the read-only pool
     pub min_connections: u32,
     /// Maximum lifetime of a connection before it's closed
     pub max_connection_lifetime: Duration,
@@ -83,6 +177,11 @@ pub struct DatabasePoolConfig {
     pub idle_timeout: Duration,
     /// Whether to enable connection testing on acquire
     pub test_before_acquire: bool,
+    /// Number of prepared statements cached per connection
+    pub statement_cache_capacity: usize,
+    /// Adaptive sizing of the read-only pool
+    #[serde(default)]
+    pub auto_tune: PoolAutoTuneConfig,
 }
 
 impl Default for DatabasePoolConfig {
@@ -96,6 +195,8 @@ impl Default for DatabasePoolConfig {
             acquire_timeout: Duration::from_secs(5), // Faster timeout for high-throughput
             idle_timeout: Duration::from_secs(5 * 60), // 5 minutes - more aggressive cleanup
             test_before_acquire: true,
+            statement_cache_capacity: 100,
+            auto_tune: PoolAutoTuneConfig::default(),
         }
     }
 }
@@ -151,6 +252,14 @@ impl DatabasePoolConfig {
             ));
         }
 
+        if self.statement_cache_capacity > 10_000 {
+            return Err(crate::BinderyError::ConfigurationError(
+                "statement_cache_capacity should not exceed 10,000 statements per connection".to_string()
+            ));
+        }
+
+        self.auto_tune.validate()?;
+
         Ok(())
     }
 
@@ -224,6 +333,18 @@ impl DatabasePoolConfig {
         self.test_before_acquire = enabled;
         self
     }
+
+    /// Set the per-connection prepared statement cache capacity (0 disables caching)
+    pub fn with_statement_cache_capacity(mut self, capacity: usize) -> Self {
+        self.statement_cache_capacity = capacity;
+        self
+    }
+
+    /// Let the read-only pool grow and shrink within `auto_tune`'s bounds
+    pub fn with_auto_tune(mut self, auto_tune: PoolAutoTuneConfig) -> Self {
+        self.auto_tune = auto_tune;
+        self
+    }
 }
 
 /// Builder for DatabasePoolConfig with validation
@@ -235,6 +356,8 @@ pub struct DatabasePoolConfigBuilder {
     acquire_timeout: Option<Duration>,
     idle_timeout: Option<Duration>,
     test_before_acquire: Option<bool>,
+    statement_cache_capacity: Option<usize>,
+    auto_tune: Option<PoolAutoTuneConfig>,
 }
 
 impl DatabasePoolConfigBuilder {
@@ -308,6 +431,16 @@ impl DatabasePoolConfigBuilder {
         self
     }
 
+    pub fn statement_cache_capacity(mut self, capacity: usize) -> Self {
+        self.statement_cache_capacity = Some(capacity);
+        self
+    }
+
+    pub fn auto_tune(mut self, auto_tune: PoolAutoTuneConfig) -> Self {
+        self.auto_tune = Some(auto_tune);
+        self
+    }
+
     pub fn build(self) -> Result<DatabasePoolConfig, crate::BinderyError> {
         let max_connections = self.max_connections.unwrap_or(20);
         let min_connections = self.min_connections.unwrap_or(2);
@@ -329,6 +462,8 @@ impl DatabasePoolConfigBuilder {
             acquire_timeout: self.acquire_timeout.unwrap_or(Duration::from_secs(10)),
             idle_timeout: self.idle_timeout.unwrap_or(Duration::from_secs(10 * 60)),
             test_before_acquire: self.test_before_acquire.unwrap_or(true),
+            statement_cache_capacity: self.statement_cache_capacity.unwrap_or(100),
+            auto_tune: self.auto_tune.unwrap_or_default(),
         };
 
         config.validate()?;
@@ -336,6 +471,40 @@ impl DatabasePoolConfigBuilder {
     }
 }
 
+/// Which connection pool a query is routed to
+///
+/// All writes go through a single dedicated writer connection so they never
+/// contend for SQLite's write lock; reads use a separate read-only pool so
+/// long-running reads (dashboards, listings) don't hold up writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PoolRole {
+    Writer,
+    Reader,
+}
+
+/// Connection acquisition counters for a single pool
+#[derive(Debug, Default)]
+struct PoolCounters {
+    total_acquired: AtomicU64,
+    total_acquisition_failures: AtomicU64,
+    acquisition_times: tokio::sync::Mutex<Vec<Duration>>,
+}
+
+impl PoolCounters {
+    async fn record_success(&self, duration: Duration) {
+        self.total_acquired.fetch_add(1, Ordering::Relaxed);
+        let mut times = self.acquisition_times.lock().await;
+        times.push(duration);
+        if times.len() > 1000 {
+            times.remove(0);
+        }
+    }
+
+    fn record_failure(&self) {
+        self.total_acquisition_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 /// Pool metrics for monitoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolMetrics {
@@ -368,10 +537,50 @@ pub struct PoolHealthInfo {
     pub slow_query_count: u64,
     pub deadlock_count: u64,
     pub active_connections: u32,
+    /// Limit of the read pool: the configured `max_connections`, or the
+    /// size auto-tuning has since chosen
     pub max_connections: u32,
+    /// Dedicated writer connections on top of `max_connections`; 0 when
+    /// reads share the writer's pool, as for in-memory databases
+    pub writer_connections: u32,
     pub recommendations: Vec<String>,
 }
 
+/// Upper bounds (in milliseconds) of the per-statement timing histogram buckets;
+/// a final implicit bucket counts everything slower than the last bound
+pub const QUERY_HISTOGRAM_BUCKETS_MS: [u64; 9] = [1, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// Query metrics configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryMetricsConfig {
+    /// Queries slower than this are recorded in the slow query log
+    pub slow_query_threshold_ms: u64,
+    /// Capture `EXPLAIN QUERY PLAN` output for slow queries
+    pub capture_query_plans: bool,
+    /// Maximum number of distinct statements to track timing statistics for
+    pub max_tracked_statements: usize,
+    /// Write slow queries to the `slow_query_log` table
+    pub persist_slow_queries: bool,
+    /// Days a persisted slow query is kept
+    pub slow_query_retention_days: u32,
+    /// Share of slow queries (0.0 to 1.0) logged with their redacted bind parameters
+    pub bind_parameter_sample_rate: f64,
+}
+
+impl Default for QueryMetricsConfig {
+    fn default() -> Self {
+        Self {
+            slow_query_threshold_ms: 100,
+            capture_query_plans: false, // Opt-in: each capture costs an extra query
+            max_tracked_statements: 500,
+            persist_slow_queries: true,
+            slow_query_retention_days: 14,
+            bind_parameter_sample_rate: 0.1,
+        }
+    }
+}
+
 /// Query performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryPerformanceMetrics {
@@ -380,7 +589,62 @@ pub struct QueryPerformanceMetrics {
     pub avg_query_duration_ms: f64,
     pub queries_over_threshold: u64,
     pub deadlocks_detected: u64,
+    /// Share of executions that reused an already-seen statement (approximate statement cache hit rate)
     pub cache_hit_rate: f64,
+    /// Per-statement timing statistics, slowest total time first
+    pub statements: Vec<StatementStats>,
+}
+
+/// Timing statistics for a single SQL statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementStats {
+    /// Normalized SQL text (whitespace collapsed)
+    pub sql: String,
+    pub query_type: QueryType,
+    pub executions: u64,
+    pub errors: u64,
+    pub total_duration_ms: f64,
+    pub max_duration_ms: f64,
+    /// Execution counts per bucket of `QUERY_HISTOGRAM_BUCKETS_MS`, plus one overflow bucket
+    pub histogram: Vec<u64>,
+}
+
+impl StatementStats {
+    fn new(sql: String, query_type: QueryType) -> Self {
+        Self {
+            sql,
+            query_type,
+            executions: 0,
+            errors: 0,
+            total_duration_ms: 0.0,
+            max_duration_ms: 0.0,
+            histogram: vec![0; QUERY_HISTOGRAM_BUCKETS_MS.len() + 1],
+        }
+    }
+
+    fn record(&mut self, duration: Duration, success: bool) {
+        let duration_ms = duration.as_secs_f64() * 1000.0;
+        self.executions += 1;
+        if !success {
+            self.errors += 1;
+        }
+        self.total_duration_ms += duration_ms;
+        self.max_duration_ms = self.max_duration_ms.max(duration_ms);
+
+        let bucket = QUERY_HISTOGRAM_BUCKETS_MS.iter()
+            .position(|&bound| duration_ms <= bound as f64)
+            .unwrap_or(QUERY_HISTOGRAM_BUCKETS_MS.len());
+        self.histogram[bucket] += 1;
+    }
+
+    /// Average execution time in milliseconds
+    pub fn avg_duration_ms(&self) -> f64 {
+        if self.executions == 0 {
+            0.0
+        } else {
+            self.total_duration_ms / self.executions as f64
+        }
+    }
 }
 
 /// Information about slow queries
@@ -391,6 +655,14 @@ pub struct SlowQueryInfo {
     pub timestamp: DateTime<Utc>,
     pub affected_rows: i64,
     pub query_type: QueryType,
+    /// `EXPLAIN QUERY PLAN` detail lines, if plan capture is enabled
+    pub query_plan: Option<Vec<String>>,
+    /// Redacted bind parameters, for the sampled share of slow queries
+    #[serde(default)]
+    pub bind_parameters: Option<Vec<String>>,
+    /// Tracing spans the query ran under, outermost first
+    #[serde(default)]
+    pub span_stack: Vec<String>,
 }
 
 /// Type of database query
@@ -405,8 +677,45 @@ pub enum QueryType {
     Other(String),
 }
 
+/// SQLite WAL checkpoint mode (see `PRAGMA wal_checkpoint`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalCheckpointMode {
+    /// Checkpoint as many frames as possible without waiting for readers or writers
+    Passive,
+    /// Wait for writers, then checkpoint all frames
+    Full,
+    /// Like `Full`, and also wait for readers so the next writer restarts the log
+    Restart,
+    /// Like `Restart`, and also truncate the WAL file to zero bytes
+    Truncate,
+}
+
+impl WalCheckpointMode {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            WalCheckpointMode::Passive => "PASSIVE",
+            WalCheckpointMode::Full => "FULL",
+            WalCheckpointMode::Restart => "RESTART",
+            WalCheckpointMode::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+/// Result of a WAL checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalCheckpointResult {
+    pub mode: WalCheckpointMode,
+    /// Whether the checkpoint was blocked by another connection
+    pub busy: bool,
+    /// Number of frames in the WAL file (-1 when not in WAL mode)
+    pub log_frames: i64,
+    /// Number of frames written back to the database file (-1 when not in WAL mode)
+    pub checkpointed_frames: i64,
+}
+
 /// Database maintenance configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MaintenanceConfig {
     /// Enable automatic VACUUM operations
     pub auto_vacuum_enabled: bool,
@@ -420,6 +729,14 @@ pub struct MaintenanceConfig {
     pub auto_optimize_indices: bool,
     /// Threshold for triggering index rebuilds (fragmentation %)
     pub index_fragmentation_threshold: f64,
+    /// Checkpoint mode used by `Database::maintenance()`
+    pub wal_checkpoint_mode: WalCheckpointMode,
+    /// Maximum pages to reclaim per incremental vacuum (0 = all free pages)
+    pub incremental_vacuum_pages: u32,
+    /// Run `PRAGMA integrity_check` as part of `Database::maintenance()`
+    pub integrity_check_enabled: bool,
+    /// Interval for the background maintenance schedule (in minutes), if enabled
+    pub schedule_interval_minutes: Option<u64>,
 }
 
 impl Default for MaintenanceConfig {
@@ -431,27 +748,247 @@ impl Default for MaintenanceConfig {
             analyze_interval_hours: 6, // Every 6 hours
             auto_optimize_indices: true,
             index_fragmentation_threshold: 30.0, // 30% fragmentation
+            wal_checkpoint_mode: WalCheckpointMode::Truncate, // Keep the WAL file from growing unbounded
+            incremental_vacuum_pages: 0,
+            integrity_check_enabled: true,
+            schedule_interval_minutes: None,
+        }
+    }
+}
+
+/// Scheduled backup configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Directory where backup files are written
+    pub directory: PathBuf,
+    /// Interval between scheduled backups (in seconds)
+    pub interval_seconds: u64,
+    /// Number of backups to retain; older backups are removed
+    pub retain: usize,
+}
+
+impl BackupConfig {
+    /// Create a backup configuration with daily backups, retaining a week of copies
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            interval_seconds: 24 * 60 * 60,
+            retain: 7,
         }
     }
+
+    /// Validate the backup configuration
+    pub fn validate(&self) -> Result<(), crate::BinderyError> {
+        if !self.directory.is_absolute() {
+            return Err(crate::BinderyError::ConfigurationError(
+                "backup directory must be an absolute path".to_string()
+            ));
+        }
+
+        if self.interval_seconds < 60 {
+            return Err(crate::BinderyError::ConfigurationError(
+                "backup interval_seconds must be at least 60 seconds".to_string()
+            ));
+        }
+
+        if self.retain == 0 {
+            return Err(crate::BinderyError::ConfigurationError(
+                "backup retain must be greater than 0".to_string()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Information about a completed backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A Codex or task written through [`Database`]
+///
+/// Published on the channel returned by [`Database::subscribe_changes`] after
+/// the write commits. Subscribers that fall behind receive
+/// `RecvError::Lagged` and should resynchronise from the tables.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangeEvent {
+    CodexUpserted { id: String },
+    CodexDeleted { id: String },
+    TaskUpserted { id: String },
+    TaskDeleted { id: String },
 }
 
 /// Database manager for Vespera Bindery data persistence
 pub struct Database {
+    /// Dedicated single-connection pool for all writes
     pool: Pool<Sqlite>,
+    /// Read-only pool for queries (same as `pool` for in-memory databases)
+    read_pool: Arc<tuning::ReadPool>,
+    /// Auto-tuning state for the read pool
+    tuner: Arc<tuning::PoolTuner>,
     config: DatabasePoolConfig,
+    /// Path of the database file (`None` for in-memory databases)
+    database_path: Option<PathBuf>,
+    /// SQLCipher `PRAGMA key` value, for connections opened outside the pools
+    key: Option<zeroize::Zeroizing<String>>,
     // Metrics tracking
     total_acquired: Arc<AtomicU64>,
     total_acquisition_failures: Arc<AtomicU64>,
     acquisition_times: Arc<tokio::sync::Mutex<Vec<Duration>>>,
+    writer_counters: Arc<PoolCounters>,
+    reader_counters: Arc<PoolCounters>,
     // Query performance tracking
     slow_queries: Arc<tokio::sync::Mutex<VecDeque<SlowQueryInfo>>>,
     total_queries: Arc<AtomicU64>,
     queries_over_threshold: Arc<AtomicU64>,
     deadlocks_detected: Arc<AtomicU64>,
+    statement_stats: Arc<tokio::sync::Mutex<HashMap<String, StatementStats>>>,
+    query_metrics_config: QueryMetricsConfig,
     // Maintenance tracking
     maintenance_config: MaintenanceConfig,
     last_vacuum: Arc<tokio::sync::Mutex<Option<DateTime<Utc>>>>,
     last_analyze: Arc<tokio::sync::Mutex<Option<DateTime<Utc>>>>,
+    maintenance_handle: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    backup_handle: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    change_events: tokio::sync::broadcast::Sender<ChangeEvent>,
+}
+
+/// A codex row as the JSON shape the list methods return
+fn codex_json(row: &sqlx::sqlite::SqliteRow) -> serde_json::Value {
+    let content: serde_json::Value = serde_json::from_str(&row.get::<String, _>("content"))
+        .unwrap_or(serde_json::json!({"fields": {}}));
+    let metadata: serde_json::Value = serde_json::from_str(&row.get::<String, _>("metadata"))
+        .unwrap_or(serde_json::json!({}));
+
+    let mut codex = serde_json::json!({
+        "id": row.get::<String, _>("id"),
+        "title": row.get::<String, _>("title"),
+        "template_id": row.get::<String, _>("template_id"),
+        "content": content,
+        "metadata": metadata,
+        "project_id": row.get::<Option<String>, _>("project_id"),
+        "created_at": row.get::<String, _>("created_at"),
+        "updated_at": row.get::<String, _>("updated_at"),
+    });
+
+    // Add parent_id if it exists
+    if let Some(parent) = row.get::<Option<String>, _>("parent_id") {
+        codex.as_object_mut().unwrap().insert("parent_id".to_string(), serde_json::json!(parent));
+    }
+    codex
+}
+
+/// A task list row as a summary
+fn task_summary(row: &sqlx::sqlite::SqliteRow) -> Result<TaskSummary> {
+    let tags_str: Option<String> = row.get("tags");
+    let tags = if let Some(tags_json) = tags_str {
+        serde_json::from_str(&tags_json).unwrap_or_default()
+    } else {
+        None
+    };
+
+    Ok(TaskSummary {
+        id: row.get("id"),
+        title: row.get("title"),
+        status: row.get("status"),
+        priority: row.get("priority"),
+        created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
+        updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
+        parent_id: row.get("parent_id"),
+        child_count: row.get("child_count"),
+        tags,
+    })
+}
+
+/// Key `options` for SQLCipher; `PRAGMA key` must run before anything else
+/// touches the file
+pub(crate) fn with_key(options: SqliteConnectOptions, key: Option<&str>) -> SqliteConnectOptions {
+    match key {
+        Some(key) => options.pragma("key", key.to_string()),
+        None => options,
+    }
+}
+
+/// Whether `error` is SQLite reporting that the file isn't a database, which
+/// is how a wrong or missing encryption key shows up
+fn is_not_a_database(error: &sqlx::Error) -> bool {
+    // SQLITE_NOTADB
+    error.as_database_error().and_then(|e| e.code()).is_some_and(|code| code == "26")
+}
+
+/// Open one connection with `options` and read the schema, so a wrong or
+/// missing encryption key is reported as such rather than as a pool timeout
+pub(crate) async fn check_readable(
+    options: &SqliteConnectOptions,
+    what: &str,
+    encrypted: bool,
+) -> Result<sqlx::SqliteConnection, crate::BinderyError> {
+    use sqlx::Connection;
+
+    let mut conn = sqlx::SqliteConnection::connect_with(options).await.map_err(|e| {
+        crate::BinderyError::DatabaseError(format!("Failed to open {}: {}", what, e))
+    })?;
+    match sqlx::query("SELECT count(*) FROM sqlite_master").execute(&mut conn).await {
+        Ok(_) => Ok(conn),
+        Err(e) if is_not_a_database(&e) && encrypted => Err(crate::BinderyError::DatabaseError(format!(
+            "Could not decrypt {}: the encryption key is wrong, or the file is not encrypted",
+            what
+        ))),
+        Err(e) if is_not_a_database(&e) => Err(crate::BinderyError::DatabaseError(format!(
+            "{} is not a database, or is encrypted and no key was given",
+            what
+        ))),
+        Err(e) => Err(crate::BinderyError::DatabaseError(format!("Failed to read {}: {}", what, e))),
+    }
+}
+
+/// Open a read-only pool of up to `max_connections` connections
+async fn connect_read_pool(
+    options: SqliteConnectOptions,
+    config: &DatabasePoolConfig,
+    max_connections: u32,
+) -> Result<Pool<Sqlite>, sqlx::Error> {
+    SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .min_connections(config.min_connections.min(max_connections))
+        .max_lifetime(Some(config.max_connection_lifetime))
+        .acquire_timeout(config.acquire_timeout)
+        .idle_timeout(Some(config.idle_timeout))
+        .test_before_acquire(config.test_before_acquire)
+        .after_connect(|conn, _meta| {
+            Box::pin(async move {
+                // Defense in depth on top of mode=ro
+                sqlx::query("PRAGMA query_only = ON")
+                    .execute(&mut *conn)
+                    .await?;
+
+                sqlx::query("PRAGMA cache_size = -65536")
+                    .execute(&mut *conn)
+                    .await?;
+
+                sqlx::query("PRAGMA busy_timeout = 5000")
+                    .execute(&mut *conn)
+                    .await?;
+
+                sqlx::query("PRAGMA mmap_size = 268435456")
+                    .execute(&mut *conn)
+                    .await?;
+
+                sqlx::query("PRAGMA temp_store = MEMORY")
+                    .execute(&mut *conn)
+                    .await?;
+
+                Ok(())
+            })
+        })
+        .connect_with(options)
+        .await
 }
 
 impl Database {
@@ -469,6 +1006,28 @@ impl Database {
     pub async fn new_with_config(
         database_path: impl AsRef<Path>,
         config: DatabasePoolConfig
+    ) -> Result<Self> {
+        Self::open(database_path, config, None).await
+    }
+
+    /// Open the database at `config.database_path` with its pool settings,
+    /// starting scheduled backups if `config.backup` is set
+    pub async fn from_config(config: &crate::BinderyConfig) -> Result<Self> {
+        let path = config.database_path.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No database_path configured"))?;
+        let database = Self::new_with_config(path, config.database_pool.clone()).await?;
+        if let Some(backup) = &config.backup {
+            database.start_backup_schedule(backup.clone()).await?;
+        }
+        Ok(database)
+    }
+
+    /// Open the database, keying each connection with `key` (a SQLCipher
+    /// `PRAGMA key` value) when given
+    pub(crate) async fn open(
+        database_path: impl AsRef<Path>,
+        config: DatabasePoolConfig,
+        key: Option<&str>,
     ) -> Result<Self> {
         // Validate configuration before proceeding
         config.validate().map_err(|e| anyhow::anyhow!("Database pool configuration validation failed: {}", e))?;
@@ -483,10 +1042,18 @@ impl Database {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        info!("Creating connection pool...");
+        // The pools retry failed connections until they time out, so check
+        // the file can be read first
+        let what = database_path.as_ref().display().to_string();
+        let options = with_key(SqliteConnectOptions::from_str(&database_url)?, key);
+        sqlx::Connection::close(check_readable(&options, &what, key.is_some()).await?).await?;
+
+        info!("Creating writer connection...");
+        // A single writer connection: SQLite serializes writes anyway, and keeping
+        // them on one connection avoids busy-lock retries between pool members
         let pool = SqlitePoolOptions::new()
-            .max_connections(config.max_connections)
-            .min_connections(config.min_connections)
+            .max_connections(1)
+            .min_connections(1)
             .max_lifetime(Some(config.max_connection_lifetime))
             .acquire_timeout(config.acquire_timeout)
             .idle_timeout(Some(config.idle_timeout))
@@ -544,39 +1111,82 @@ impl Database {
                     Ok(())
                 })
             })
-            .connect(&database_url)
+            .connect_with(with_key(
+                SqliteConnectOptions::from_str(&database_url)?
+                    .statement_cache_capacity(config.statement_cache_capacity),
+                key,
+            ))
             .await?;
 
-        info!("Database connection pool created successfully with {} max connections!", config.max_connections);
+        // The writer has switched the database to WAL mode, so read-only connections
+        // can now read concurrently with it
+        info!("Creating read-only connection pool...");
+        let read_url = format!("sqlite:{}?mode=ro", database_path.as_ref().display());
+        let read_options = with_key(
+            SqliteConnectOptions::from_str(&read_url)?
+                .statement_cache_capacity(config.statement_cache_capacity),
+            key,
+        );
+        let read_size = config.auto_tune.initial_size(config.max_connections);
+        let read_pool = connect_read_pool(read_options.clone(), &config, read_size).await?;
+
+        info!("Database connection pools created successfully (1 writer, {} max readers)", read_size);
 
         // Initialize with migration system
         let database = Self {
             pool: pool.clone(),
+            read_pool: Arc::new(tuning::ReadPool::new(read_pool, read_size, Some(read_options))),
+            tuner: Arc::new(tuning::PoolTuner::default()),
             config,
+            database_path: Some(database_path.as_ref().to_path_buf()),
+            key: key.map(|key| zeroize::Zeroizing::new(key.to_string())),
             total_acquired: Arc::new(AtomicU64::new(0)),
             total_acquisition_failures: Arc::new(AtomicU64::new(0)),
             acquisition_times: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            writer_counters: Arc::new(PoolCounters::default()),
+            reader_counters: Arc::new(PoolCounters::default()),
             slow_queries: Arc::new(tokio::sync::Mutex::new(VecDeque::new())),
             total_queries: Arc::new(AtomicU64::new(0)),
             queries_over_threshold: Arc::new(AtomicU64::new(0)),
             deadlocks_detected: Arc::new(AtomicU64::new(0)),
+            statement_stats: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            query_metrics_config: QueryMetricsConfig::default(),
             maintenance_config: MaintenanceConfig::default(),
             last_vacuum: Arc::new(tokio::sync::Mutex::new(None)),
             last_analyze: Arc::new(tokio::sync::Mutex::new(None)),
+            maintenance_handle: Arc::new(tokio::sync::Mutex::new(None)),
+            backup_handle: Arc::new(tokio::sync::Mutex::new(None)),
+            change_events: tokio::sync::broadcast::channel(CHANGE_EVENT_CAPACITY).0,
         };
         database.run_migrations().await?;
+        Self::init_slow_query_log(&database.pool).await?;
+        database.start_pool_tuning(None).await?;
 
         Ok(database)
     }
 
+    /// Migrations directory applied on startup, relative to the working directory
+    fn migrations_dir() -> PathBuf {
+        std::env::current_dir()
+            .unwrap_or_else(|_| std::path::PathBuf::from("."))
+            .join("migrations")
+    }
+
+    /// Applied and pending migrations, from the same directory startup migrates from
+    pub async fn migration_status(&self) -> Result<crate::migration::MigrationStatus> {
+        let migrations_dir = Self::migrations_dir();
+        if !migrations_dir.exists() {
+            return Err(anyhow::anyhow!("Migrations directory not found at {}", migrations_dir.display()));
+        }
+        let migration_manager = MigrationManager::new(self.pool.clone(), migrations_dir).await?;
+        Ok(migration_manager.get_status().await?)
+    }
+
     /// Run database migrations automatically on startup
     async fn run_migrations(&self) -> Result<()> {
         info!("Running database migrations...");
 
-        // Determine migrations directory path
-        let migrations_dir = std::env::current_dir()
-            .unwrap_or_else(|_| std::path::PathBuf::from("."))
-            .join("migrations");
+        let migrations_dir = Self::migrations_dir();
 
         if !migrations_dir.exists() {
             warn!("Migrations directory not found at {:?}, using fallback schema initialization", migrations_dir);
@@ -644,20 +1254,33 @@ impl Database {
             .await?;
 
         sqlx::migrate!("./migrations").run(&pool).await?;
+        Self::init_slow_query_log(&pool).await?;
 
         Ok(Self {
+            // In-memory databases are private to a connection, so reads share the writer pool
+            read_pool: Arc::new(tuning::ReadPool::new(pool.clone(), config.max_connections, None)),
+            tuner: Arc::new(tuning::PoolTuner::default()),
             pool,
             config,
+            database_path: None,
+            key: None,
             total_acquired: Arc::new(AtomicU64::new(0)),
             total_acquisition_failures: Arc::new(AtomicU64::new(0)),
             acquisition_times: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            writer_counters: Arc::new(PoolCounters::default()),
+            reader_counters: Arc::new(PoolCounters::default()),
             slow_queries: Arc::new(tokio::sync::Mutex::new(VecDeque::new())),
             total_queries: Arc::new(AtomicU64::new(0)),
             queries_over_threshold: Arc::new(AtomicU64::new(0)),
             deadlocks_detected: Arc::new(AtomicU64::new(0)),
+            statement_stats: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            query_metrics_config: QueryMetricsConfig::default(),
             maintenance_config: MaintenanceConfig::default(),
             last_vacuum: Arc::new(tokio::sync::Mutex::new(None)),
             last_analyze: Arc::new(tokio::sync::Mutex::new(None)),
+            maintenance_handle: Arc::new(tokio::sync::Mutex::new(None)),
+            backup_handle: Arc::new(tokio::sync::Mutex::new(None)),
+            change_events: tokio::sync::broadcast::channel(CHANGE_EVENT_CAPACITY).0,
         })
     }
     
@@ -680,11 +1303,23 @@ impl Database {
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
                 due_date TEXT,
+                completed_at TEXT,
                 FOREIGN KEY(parent_id) REFERENCES tasks(id) ON DELETE CASCADE
             )
             "#
         ).execute(&self.pool).await?;
 
+        // Tables created before completion tracking lack completed_at
+        let has_completed_at: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('tasks') WHERE name = 'completed_at'"
+        ).fetch_one(&self.pool).await?;
+        if !has_completed_at {
+            sqlx::query("ALTER TABLE tasks ADD COLUMN completed_at TEXT")
+                .execute(&self.pool).await?;
+            sqlx::query("UPDATE tasks SET completed_at = updated_at WHERE status IN ('completed', 'done', 'finished')")
+                .execute(&self.pool).await?;
+        }
+
         // Create tasks indexes
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status)")
             .execute(&self.pool).await?;
@@ -694,6 +1329,8 @@ impl Database {
             .execute(&self.pool).await?;
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_created ON tasks(created_at)")
             .execute(&self.pool).await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed_at)")
+            .execute(&self.pool).await?;
 
         // Create codices table
         sqlx::query(
@@ -774,6 +1411,12 @@ impl Database {
                     "Successfully committed task tree creation"
                 );
 
+                if self.has_change_subscribers() {
+                    for task_id in self.task_subtree_ids(&id).await? {
+                        self.publish_change(ChangeEvent::TaskUpserted { id: task_id });
+                    }
+                }
+
                 Ok(id)
             }
             Err(e) => {
@@ -853,7 +1496,7 @@ impl Database {
             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
         "#;
 
-        let _result = self.execute_with_metrics(async {
+        let _result = self.execute_with_metrics(query, async {
             sqlx::query(query)
                 .bind(&id)
                 .bind(&input.title)
@@ -909,6 +1552,7 @@ impl Database {
             );
         }
 
+        self.publish_change(ChangeEvent::TaskUpserted { id: id.clone() });
         Ok(id)
     }
 
@@ -1069,51 +1713,74 @@ impl Database {
             "Executing task list query"
         );
 
-        let rows = self.execute_with_metrics(async {
+        let rows = self.execute_read_with_metrics(&query, async {
             sqlx::query(&query)
-                .fetch_all(&self.pool).await
+                .fetch_all(&self.read_pool.get()).await
         }).await?;
         
-        let mut tasks = Vec::new();
         debug!(row_count = rows.len(), "Processing task query results");
-        for row in rows {
-            let tags_str: Option<String> = row.get("tags");
-            let tags = if let Some(tags_json) = tags_str {
-                serde_json::from_str(&tags_json).unwrap_or_default()
-            } else {
-                None
-            };
-            
-            tasks.push(TaskSummary {
-                id: row.get("id"),
-                title: row.get("title"),
-                status: row.get("status"),
-                priority: row.get("priority"),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?.with_timezone(&Utc),
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?.with_timezone(&Utc),
-                parent_id: row.get("parent_id"),
-                child_count: row.get("child_count"),
-                tags,
-            });
-        }
-        
-        Ok(tasks)
+        rows.iter().map(task_summary).collect()
+    }
+
+    /// One page of tasks under `parent_id`, or of root tasks when `None`, newest first
+    pub async fn list_tasks_page(&self, parent_id: Option<&str>, request: &PageRequest) -> Result<Page<TaskSummary>> {
+        let after = request.cursor.as_deref().map(|cursor| pagination::decode_cursor(cursor, 2)).transpose()?;
+        let (after_created, after_id) = match &after {
+            Some(parts) => (Some(parts[0].as_str()), Some(parts[1].as_str())),
+            None => (None, None),
+        };
+        let page_size = request.page_size();
+
+        let query = r#"
+            SELECT
+                t.id, t.title, t.status, t.priority, t.created_at, t.updated_at, t.parent_id, t.tags,
+                (SELECT COUNT(*) FROM tasks c WHERE c.parent_id = t.id) as child_count
+            FROM tasks t
+            WHERE ((?1 IS NULL AND t.parent_id IS NULL) OR t.parent_id = ?1)
+            AND (?2 IS NULL OR t.created_at < ?2 OR (t.created_at = ?2 AND t.id < ?3))
+            ORDER BY t.created_at DESC, t.id DESC
+            LIMIT ?4
+        "#;
+        let params = [parent_id.into(), after_created.into(), after_id.into(), ((page_size + 1) as i64).into()];
+        let rows = self.execute_read_with_params(query, &params, async {
+            sqlx::query(query)
+                .bind(parent_id)
+                .bind(after_created)
+                .bind(after_id)
+                .bind((page_size + 1) as i64)
+                .fetch_all(&self.read_pool.get()).await
+        }).await?;
+
+        // Cursors keep the stored timestamp text, which the keyset compares against
+        let entries = rows.iter()
+            .map(|row| {
+                let cursor = pagination::encode_cursor(&[&row.get::<String, _>("created_at"), &row.get::<String, _>("id")]);
+                Ok((cursor, task_summary(row)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let page = Page::from_overfetch(entries, page_size, |(cursor, _)| cursor.clone());
+        Ok(Page {
+            items: page.items.into_iter().map(|(_, task)| task).collect(),
+            next_cursor: page.next_cursor,
+        })
     }
     
     /// Get task dashboard data with pool metrics tracking
     // Instrumentation removed for compilation
     pub async fn get_task_dashboard(&self, _project_id: Option<String>) -> Result<TaskDashboard> {
         // Get total tasks with metrics tracking
-        let total_result = self.execute_with_metrics(async {
-            sqlx::query("SELECT COUNT(*) as total FROM tasks")
-                .fetch_one(&self.pool).await
+        let query = "SELECT COUNT(*) as total FROM tasks";
+        let total_result = self.execute_read_with_metrics(query, async {
+            sqlx::query(query)
+                .fetch_one(&self.read_pool.get()).await
         }).await?;
         let total_tasks: i64 = total_result.get("total");
 
         // Get status breakdown with metrics tracking
-        let status_rows = self.execute_with_metrics(async {
-            sqlx::query("SELECT status, COUNT(*) as count FROM tasks GROUP BY status")
-                .fetch_all(&self.pool).await
+        let query = "SELECT status, COUNT(*) as count FROM tasks GROUP BY status";
+        let status_rows = self.execute_read_with_metrics(query, async {
+            sqlx::query(query)
+                .fetch_all(&self.read_pool.get()).await
         }).await?;
         let mut status_breakdown = serde_json::Map::new();
         for row in status_rows {
@@ -1123,9 +1790,10 @@ impl Database {
         }
         
         // Get priority breakdown with metrics tracking
-        let priority_rows = self.execute_with_metrics(async {
-            sqlx::query("SELECT priority, COUNT(*) as count FROM tasks GROUP BY priority")
-                .fetch_all(&self.pool).await
+        let query = "SELECT priority, COUNT(*) as count FROM tasks GROUP BY priority";
+        let priority_rows = self.execute_read_with_metrics(query, async {
+            sqlx::query(query)
+                .fetch_all(&self.read_pool.get()).await
         }).await?;
         let mut priority_breakdown = serde_json::Map::new();
         for row in priority_rows {
@@ -1138,40 +1806,39 @@ impl Database {
         let recent_tasks = self.list_tasks(Some(5), None).await?;
 
         // Get overdue tasks (due_date < now() and status != 'completed')
-        let overdue_tasks = self.execute_with_metrics(async {
-            sqlx::query_as::<_, TaskSummary>(
-                r#"
-                SELECT id, title, status, priority, created_at, updated_at, parent_id, 0 as child_count, tags
-                FROM tasks
-                WHERE due_date < datetime('now')
-                AND status NOT IN ('completed', 'done', 'finished')
-                ORDER BY due_date ASC
-                LIMIT 10
-                "#
-            )
-            .fetch_all(&self.pool).await
+        let query = r#"
+            SELECT id, title, status, priority, created_at, updated_at, parent_id, 0 as child_count, tags
+            FROM tasks
+            WHERE due_date < datetime('now')
+            AND status NOT IN ('completed', 'done', 'finished')
+            ORDER BY due_date ASC
+            LIMIT 10
+        "#;
+        let overdue_tasks = self.execute_read_with_metrics(query, async {
+            sqlx::query_as::<_, TaskSummary>(query)
+                .fetch_all(&self.read_pool.get()).await
         }).await.unwrap_or_default();
 
         // Get upcoming tasks (due in next 7 days)
-        let upcoming_tasks = self.execute_with_metrics(async {
-            sqlx::query_as::<_, TaskSummary>(
-                r#"
-                SELECT id, title, status, priority, created_at, updated_at, parent_id, 0 as child_count, tags
-                FROM tasks
-                WHERE due_date > datetime('now')
-                AND due_date < datetime('now', '+7 days')
-                AND status NOT IN ('completed', 'done', 'finished')
-                ORDER BY due_date ASC
-                LIMIT 10
-                "#
-            )
-            .fetch_all(&self.pool).await
+        let query = r#"
+            SELECT id, title, status, priority, created_at, updated_at, parent_id, 0 as child_count, tags
+            FROM tasks
+            WHERE due_date > datetime('now')
+            AND due_date < datetime('now', '+7 days')
+            AND status NOT IN ('completed', 'done', 'finished')
+            ORDER BY due_date ASC
+            LIMIT 10
+        "#;
+        let upcoming_tasks = self.execute_read_with_metrics(query, async {
+            sqlx::query_as::<_, TaskSummary>(query)
+                .fetch_all(&self.read_pool.get()).await
         }).await.unwrap_or_default();
 
         // Calculate completion rate
-        let completed_count = self.execute_with_metrics(async {
-            sqlx::query("SELECT COUNT(*) as count FROM tasks WHERE status IN ('completed', 'done', 'finished')")
-                .fetch_one(&self.pool).await
+        let query = "SELECT COUNT(*) as count FROM tasks WHERE status IN ('completed', 'done', 'finished')";
+        let completed_count = self.execute_read_with_metrics(query, async {
+            sqlx::query(query)
+                .fetch_one(&self.read_pool.get()).await
         }).await.map(|row| row.get::<i64, _>("count")).unwrap_or(0);
 
         let completion_rate = if total_tasks > 0 {
@@ -1180,6 +1847,13 @@ impl Database {
             0.0
         };
 
+        let query = "SELECT AVG((julianday(completed_at) - julianday(created_at)) * 24.0) AS hours \
+            FROM tasks WHERE completed_at IS NOT NULL";
+        let average_completion_time = self.execute_read_with_metrics(query, async {
+            sqlx::query(query)
+                .fetch_one(&self.read_pool.get()).await
+        }).await.ok().and_then(|row| row.get::<Option<f64>, _>("hours"));
+
         Ok(TaskDashboard {
             total_tasks,
             status_breakdown: serde_json::Value::Object(status_breakdown),
@@ -1189,11 +1863,135 @@ impl Database {
             upcoming_tasks,
             project_breakdown: serde_json::Value::Object(serde_json::Map::new()),
             completion_rate,
-            average_completion_time: None,
+            average_completion_time,
+            time_series: None,
         })
     }
+
+    /// Dashboard data with daily series over `query`'s date range
+    pub async fn get_task_dashboard_with_series(
+        &self,
+        project_id: Option<String>,
+        query: &TimeSeriesQuery,
+    ) -> Result<TaskDashboard> {
+        let mut dashboard = self.get_task_dashboard(project_id).await?;
+        dashboard.time_series = Some(self.get_task_time_series(query).await?);
+        Ok(dashboard)
+    }
+
+    /// Tasks created and completed per day, burndown and cycle-time percentiles
+    ///
+    /// Every day of the range gets an entry, so charts need no gap filling.
+    /// `remaining` counts tasks created before the range too.
+    pub async fn get_task_time_series(&self, query: &TimeSeriesQuery) -> Result<TaskTimeSeries> {
+        let span = (query.to - query.from).num_days() + 1;
+        if !(1..=MAX_TIME_SERIES_DAYS).contains(&span) {
+            return Err(anyhow::anyhow!(crate::BinderyError::InvalidInput(format!(
+                "Time series range must cover 1 to {} days, got {} to {}",
+                MAX_TIME_SERIES_DAYS, query.from, query.to
+            ))));
+        }
+        let from = query.from.to_string();
+        let to = query.to.to_string();
+
+        // ?1 project, ?2 label, ?3 first day, ?4 last day
+        let filtered = r#"
+            filtered AS (
+                SELECT created_at, completed_at FROM tasks
+                WHERE (?1 IS NULL OR project_id = ?1)
+                AND (?2 IS NULL
+                    OR EXISTS (SELECT 1 FROM json_each(COALESCE(tags, '[]')) WHERE value = ?2)
+                    OR (json_type(labels) = 'object' AND EXISTS (SELECT 1 FROM json_each(labels) WHERE key = ?2)))
+            )
+        "#;
+
+        let series_sql = format!(r#"
+            WITH RECURSIVE {filtered},
+            days(day) AS (
+                SELECT date(?3)
+                UNION ALL
+                SELECT date(day, '+1 day') FROM days WHERE day < date(?4)
+            ),
+            events(day, created, completed) AS (
+                SELECT date(created_at), 1, 0 FROM filtered
+                UNION ALL
+                SELECT date(completed_at), 0, 1 FROM filtered WHERE completed_at IS NOT NULL
+            ),
+            daily AS (
+                SELECT day, SUM(created) AS created, SUM(completed) AS completed FROM events GROUP BY day
+            ),
+            carried AS (
+                SELECT COALESCE(SUM(created) - SUM(completed), 0) AS open FROM daily WHERE day < date(?3)
+            )
+            SELECT days.day AS day,
+                COALESCE(daily.created, 0) AS created,
+                COALESCE(daily.completed, 0) AS completed,
+                (SELECT open FROM carried) + SUM(COALESCE(daily.created, 0) - COALESCE(daily.completed, 0))
+                    OVER (ORDER BY days.day ROWS UNBOUNDED PRECEDING) AS remaining
+            FROM days LEFT JOIN daily ON daily.day = days.day
+            ORDER BY days.day
+        "#);
+        let params = [(&query.project_id).into(), (&query.label).into(), (&from).into(), (&to).into()];
+        let rows = self.execute_read_with_params(&series_sql, &params, async {
+            sqlx::query(&series_sql)
+                .bind(&query.project_id)
+                .bind(&query.label)
+                .bind(&from)
+                .bind(&to)
+                .fetch_all(&self.read_pool.get()).await
+        }).await?;
+        let days = rows.into_iter()
+            .map(|row| {
+                let day: String = row.get("day");
+                Ok(DailyTaskCounts {
+                    day: NaiveDate::parse_from_str(&day, "%Y-%m-%d")?,
+                    created: row.get("created"),
+                    completed: row.get("completed"),
+                    remaining: row.get("remaining"),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Nearest-rank percentiles: the smallest cycle time ranked at or above p * samples
+        let cycle_sql = format!(r#"
+            WITH {filtered},
+            cycles AS (
+                SELECT (julianday(completed_at) - julianday(created_at)) * 24.0 AS hours FROM filtered
+                WHERE completed_at IS NOT NULL AND date(completed_at) BETWEEN date(?3) AND date(?4)
+            ),
+            ranked AS (
+                SELECT hours, ROW_NUMBER() OVER (ORDER BY hours) AS rank, COUNT(*) OVER () AS samples FROM cycles
+            )
+            SELECT COUNT(*) AS samples,
+                MIN(CASE WHEN rank >= 0.50 * samples THEN hours END) AS p50,
+                MIN(CASE WHEN rank >= 0.75 * samples THEN hours END) AS p75,
+                MIN(CASE WHEN rank >= 0.90 * samples THEN hours END) AS p90,
+                MIN(CASE WHEN rank >= 0.95 * samples THEN hours END) AS p95
+            FROM ranked
+        "#);
+        let params = [(&query.project_id).into(), (&query.label).into(), (&from).into(), (&to).into()];
+        let row = self.execute_read_with_params(&cycle_sql, &params, async {
+            sqlx::query(&cycle_sql)
+                .bind(&query.project_id)
+                .bind(&query.label)
+                .bind(&from)
+                .bind(&to)
+                .fetch_one(&self.read_pool.get()).await
+        }).await?;
+        let cycle_time = CycleTimePercentiles {
+            samples: row.get("samples"),
+            p50_hours: row.get("p50"),
+            p75_hours: row.get("p75"),
+            p90_hours: row.get("p90"),
+            p95_hours: row.get("p95"),
+        };
+
+        Ok(TaskTimeSeries { query: query.clone(), days, cycle_time })
+    }
     
     /// Update a task with pool metrics tracking
+    ///
+    /// Moving a task to a done status stamps `completed_at`; any other status clears it.
     pub async fn update_task(&self, task_id: &str, title: Option<&str>, status: Option<&str>) -> Result<bool> {
         let now = Utc::now();
         let now_str = now.to_rfc3339();
@@ -1201,11 +1999,15 @@ impl Database {
         // Build query dynamically based on what fields are provided
         let (_query_str, result) = match (title, status) { // TODO: Use _query_str for logging
             (Some(t), Some(s)) => {
-                let query_str = "UPDATE tasks SET title = ?, status = ?, updated_at = ? WHERE id = ?";
-                let result = self.execute_with_metrics(async {
+                let query_str = "UPDATE tasks SET title = ?, status = ?, \
+                    completed_at = CASE WHEN ? IN ('completed', 'done', 'finished') THEN COALESCE(completed_at, ?) END, \
+                    updated_at = ? WHERE id = ?";
+                let result = self.execute_with_metrics(query_str, async {
                     sqlx::query(query_str)
                         .bind(t)
                         .bind(s)
+                        .bind(s)
+                        .bind(&now_str)
                         .bind(&now_str)
                         .bind(task_id)
                         .execute(&self.pool).await
@@ -1214,7 +2016,7 @@ impl Database {
             },
             (Some(t), None) => {
                 let query_str = "UPDATE tasks SET title = ?, updated_at = ? WHERE id = ?";
-                let result = self.execute_with_metrics(async {
+                let result = self.execute_with_metrics(query_str, async {
                     sqlx::query(query_str)
                         .bind(t)
                         .bind(&now_str)
@@ -1224,10 +2026,14 @@ impl Database {
                 (query_str, result)
             },
             (None, Some(s)) => {
-                let query_str = "UPDATE tasks SET status = ?, updated_at = ? WHERE id = ?";
-                let result = self.execute_with_metrics(async {
+                let query_str = "UPDATE tasks SET status = ?, \
+                    completed_at = CASE WHEN ? IN ('completed', 'done', 'finished') THEN COALESCE(completed_at, ?) END, \
+                    updated_at = ? WHERE id = ?";
+                let result = self.execute_with_metrics(query_str, async {
                     sqlx::query(query_str)
                         .bind(s)
+                        .bind(s)
+                        .bind(&now_str)
                         .bind(&now_str)
                         .bind(task_id)
                         .execute(&self.pool).await
@@ -1240,32 +2046,121 @@ impl Database {
             }
         };
         
-        Ok(result.rows_affected() > 0)
+        let updated = result.rows_affected() > 0;
+        if updated {
+            self.publish_change(ChangeEvent::TaskUpserted { id: task_id.to_string() });
+        }
+        Ok(updated)
     }
     
     /// Delete a task with pool metrics tracking
     pub async fn delete_task(&self, task_id: &str) -> Result<bool> {
-        let result = self.execute_with_metrics(async {
-            sqlx::query("DELETE FROM tasks WHERE id = ?")
+        // Subtasks go with their parent (ON DELETE CASCADE), so note them first
+        let removed = if self.has_change_subscribers() {
+            self.task_subtree_ids(task_id).await?
+        } else {
+            Vec::new()
+        };
+
+        let query = "DELETE FROM tasks WHERE id = ?";
+        let result = self.execute_with_params(query, &[task_id.into()], async {
+            sqlx::query(query)
                 .bind(task_id)
                 .execute(&self.pool).await
         }).await?;
-        Ok(result.rows_affected() > 0)
+
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            for id in removed {
+                self.publish_change(ChangeEvent::TaskDeleted { id });
+            }
+        }
+        Ok(deleted)
     }
 
-    /// Check if setting parent_id would create a circular reference
-    ///
-    /// This method traverses the parent chain upward from the proposed parent
-    /// to detect if the task would become its own ancestor.
-    ///
-    /// # Arguments
-    /// * `task_id` - The ID of the task that would have its parent set
-    /// * `proposed_parent_id` - The ID of the proposed parent task
-    ///
-    /// # Returns
-    /// * `Ok(true)` - Setting this parent would create a cycle
-    /// * `Ok(false)` - No cycle would be created
-    /// * `Err(_)` - Database error occurred
+    /// Get a single task, including its description
+    pub async fn get_task(&self, task_id: &str) -> Result<Option<serde_json::Value>> {
+        let query = r#"
+            SELECT id, title, description, status, priority, parent_id, project_id, tags, labels, created_at, updated_at
+            FROM tasks
+            WHERE id = ?
+        "#;
+        let row = self.execute_read_with_params(query, &[task_id.into()], async {
+            sqlx::query(query)
+                .bind(task_id)
+                .fetch_optional(&self.read_pool.get()).await
+        }).await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let parse_json = |column: &str| -> serde_json::Value {
+            row.get::<Option<String>, _>(column)
+                .and_then(|value| serde_json::from_str(&value).ok())
+                .unwrap_or(serde_json::json!([]))
+        };
+
+        Ok(Some(serde_json::json!({
+            "id": row.get::<String, _>("id"),
+            "title": row.get::<String, _>("title"),
+            "description": row.get::<Option<String>, _>("description"),
+            "status": row.get::<String, _>("status"),
+            "priority": row.get::<String, _>("priority"),
+            "parent_id": row.get::<Option<String>, _>("parent_id"),
+            "project_id": row.get::<Option<String>, _>("project_id"),
+            "tags": parse_json("tags"),
+            "labels": parse_json("labels"),
+            "created_at": row.get::<String, _>("created_at"),
+            "updated_at": row.get::<String, _>("updated_at"),
+        })))
+    }
+
+    /// IDs of a task and all of its descendants
+    async fn task_subtree_ids(&self, task_id: &str) -> Result<Vec<String>> {
+        let query = r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?
+                UNION
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+            )
+            SELECT id FROM subtree
+        "#;
+        let rows = self.execute_read_with_params(query, &[task_id.into()], async {
+            sqlx::query(query)
+                .bind(task_id)
+                .fetch_all(&self.read_pool.get()).await
+        }).await?;
+        Ok(rows.iter().map(|row| row.get("id")).collect())
+    }
+
+    /// Subscribe to Codex and task changes made through this database
+    pub fn subscribe_changes(&self) -> tokio::sync::broadcast::Receiver<ChangeEvent> {
+        self.change_events.subscribe()
+    }
+
+    fn has_change_subscribers(&self) -> bool {
+        self.change_events.receiver_count() > 0
+    }
+
+    fn publish_change(&self, event: ChangeEvent) {
+        // No subscribers is not an error
+        let _ = self.change_events.send(event);
+    }
+
+    /// Check if setting parent_id would create a circular reference
+    ///
+    /// This method traverses the parent chain upward from the proposed parent
+    /// to detect if the task would become its own ancestor.
+    ///
+    /// # Arguments
+    /// * `task_id` - The ID of the task that would have its parent set
+    /// * `proposed_parent_id` - The ID of the proposed parent task
+    ///
+    /// # Returns
+    /// * `Ok(true)` - Setting this parent would create a cycle
+    /// * `Ok(false)` - No cycle would be created
+    /// * `Err(_)` - Database error occurred
     pub async fn would_create_cycle(
         &self,
         task_id: &str,
@@ -1308,10 +2203,11 @@ impl Database {
             }
 
             // Get parent_id for current task
-            let parent_result = self.execute_with_metrics(async {
-                sqlx::query("SELECT parent_id FROM tasks WHERE id = ?")
+            let query = "SELECT parent_id FROM tasks WHERE id = ?";
+            let parent_result = self.execute_read_with_params(query, &[(&current_id).into()], async {
+                sqlx::query(query)
                     .bind(&current_id)
-                    .fetch_optional(&self.pool).await
+                    .fetch_optional(&self.read_pool.get()).await
             }).await?;
 
             match parent_result {
@@ -1379,20 +2275,58 @@ impl Database {
         let now = Utc::now();
         let now_str = now.to_rfc3339();
 
-        let result = self.execute_with_metrics(async {
-            sqlx::query("UPDATE tasks SET parent_id = ?, updated_at = ? WHERE id = ?")
+        let query = "UPDATE tasks SET parent_id = ?, updated_at = ? WHERE id = ?";
+        let params = [new_parent_id.into(), (&now_str).into(), task_id.into()];
+        let result = self.execute_with_params(query, &params, async {
+            sqlx::query(query)
                 .bind(new_parent_id)
                 .bind(&now_str)
                 .bind(task_id)
                 .execute(&self.pool).await
         }).await?;
 
-        Ok(result.rows_affected() > 0)
+        let updated = result.rows_affected() > 0;
+        if updated {
+            self.publish_change(ChangeEvent::TaskUpserted { id: task_id.to_string() });
+        }
+        Ok(updated)
+    }
+
+    /// Get metrics for a single pool (writer or read-only)
+    pub async fn get_pool_metrics_for(&self, role: PoolRole) -> PoolMetrics {
+        let read_pool = self.read_pool.get();
+        let (pool, counters, max_connections) = match role {
+            PoolRole::Writer if self.is_shared_pool() => (&self.pool, &self.writer_counters, self.read_pool.size()),
+            PoolRole::Writer => (&self.pool, &self.writer_counters, 1),
+            PoolRole::Reader => (&read_pool, &self.reader_counters, self.read_pool.size()),
+        };
+
+        let total_connections = pool.size();
+        let idle_connections = pool.num_idle() as u32;
+        let active_connections = total_connections.saturating_sub(idle_connections);
+
+        let acquisition_times = counters.acquisition_times.lock().await;
+        let average_acquisition_time_ms = if acquisition_times.is_empty() {
+            0.0
+        } else {
+            acquisition_times.iter().map(|d| d.as_millis() as f64).sum::<f64>() / acquisition_times.len() as f64
+        };
+
+        PoolMetrics {
+            active_connections,
+            idle_connections,
+            total_connections,
+            total_acquired: counters.total_acquired.load(Ordering::Relaxed),
+            total_acquisition_failures: counters.total_acquisition_failures.load(Ordering::Relaxed),
+            average_acquisition_time_ms,
+            pool_utilization: (total_connections as f64) / (max_connections as f64) * 100.0,
+        }
     }
 
-    /// Get comprehensive pool metrics for monitoring
+    /// Get comprehensive pool metrics for monitoring, aggregated across the writer and read pools
     pub async fn get_pool_metrics(&self) -> PoolMetrics {
-        let pool_state = self.pool.size();
+        let pool_state = self.pool.size() + self.read_pool_size();
+        let max_connections = self.max_total_connections();
         let total_acquired = self.total_acquired.load(Ordering::Relaxed);
         let total_failures = self.total_acquisition_failures.load(Ordering::Relaxed);
 
@@ -1408,15 +2342,15 @@ impl Database {
         };
 
         // Calculate pool utilization as percentage
-        let utilization = if self.config.max_connections > 0 {
-            (pool_state as f64) / (self.config.max_connections as f64) * 100.0
+        let utilization = if max_connections > 0 {
+            (pool_state as f64) / (max_connections as f64) * 100.0
         } else {
             0.0
         };
 
         PoolMetrics {
             active_connections: pool_state,
-            idle_connections: self.config.max_connections.saturating_sub(pool_state),
+            idle_connections: max_connections.saturating_sub(pool_state),
             total_connections: pool_state,
             total_acquired,
             total_acquisition_failures: total_failures,
@@ -1461,7 +2395,8 @@ impl Database {
             slow_query_count: slow_queries,
             deadlock_count: deadlocks,
             active_connections: metrics.active_connections,
-            max_connections: self.config.max_connections,
+            max_connections: self.read_pool.size(),
+            writer_connections: self.max_total_connections() - self.read_pool.size(),
             recommendations: self.generate_health_recommendations(&metrics, success_rate).await,
         }
     }
@@ -1496,18 +2431,71 @@ impl Database {
             recommendations.push("Deadlocks detected - review transaction handling and query patterns".to_string());
         }
 
+        if let Some(tuning) = self.last_pool_tuning() {
+            recommendations.extend(tuning.recommendations);
+        }
+
         recommendations
     }
 
+    /// Publish one pool's gauges to the metrics recorder (no-op without one installed)
+    fn export_pool_metrics(&self, role: PoolRole, acquisition_time: Duration) {
+        let read_pool = self.read_pool.get();
+        let (pool, label, max_connections) = match role {
+            PoolRole::Writer if self.is_shared_pool() => (&self.pool, "writer", self.read_pool.size()),
+            PoolRole::Writer => (&self.pool, "writer", 1),
+            PoolRole::Reader => (&read_pool, "reader", self.read_pool.size()),
+        };
+
+        let total_connections = pool.size();
+        let active_connections = total_connections.saturating_sub(pool.num_idle() as u32);
+        crate::observability::BinderyMetrics::record_database_pool_metrics(
+            label,
+            active_connections,
+            max_connections,
+            total_connections as f64 / max_connections.max(1) as f64,
+            acquisition_time,
+        );
+    }
+
+    fn is_shared_pool(&self) -> bool {
+        self.database_path.is_none()
+    }
+
+    /// Read pool connections not already counted as the writer (in-memory databases share one pool)
+    fn read_pool_size(&self) -> u32 {
+        if self.is_shared_pool() { 0 } else { self.read_pool.get().size() }
+    }
+
+    fn max_total_connections(&self) -> u32 {
+        if self.is_shared_pool() { self.read_pool.size() } else { self.read_pool.size() + 1 }
+    }
+
     /// Get pool configuration
     pub fn get_pool_config(&self) -> &DatabasePoolConfig {
         &self.config
     }
 
-    /// Check if pool is healthy with comprehensive testing
+    /// Check if both the writer and read pools are healthy with comprehensive testing
     pub async fn is_pool_healthy(&self) -> bool {
+        let writer_healthy = self.is_role_healthy(PoolRole::Writer).await;
+        if self.is_shared_pool() {
+            return writer_healthy;
+        }
+        // Check the read pool even when the writer is unhealthy so failures are counted
+        let reader_healthy = self.is_role_healthy(PoolRole::Reader).await;
+        writer_healthy && reader_healthy
+    }
+
+    async fn is_role_healthy(&self, role: PoolRole) -> bool {
+        let read_pool = self.read_pool.get();
+        let (pool, counters) = match role {
+            PoolRole::Writer => (&self.pool, &self.writer_counters),
+            PoolRole::Reader => (&read_pool, &self.reader_counters),
+        };
+
         // Test connection acquisition and basic query execution
-        match self.pool.acquire().await {
+        match pool.acquire().await {
             Ok(mut conn) => {
                 // Test with a simple query to ensure the connection actually works
                 match sqlx::query("SELECT 1 as test")
@@ -1515,19 +2503,21 @@ impl Database {
                     .await
                 {
                     Ok(_) => {
-                        debug!("Pool health check passed");
+                        debug!("{:?} pool health check passed", role);
                         true
                     }
                     Err(e) => {
-                        error!("Pool health check query failed: {}", e);
+                        error!("{:?} pool health check query failed: {}", role, e);
                         self.total_acquisition_failures.fetch_add(1, Ordering::Relaxed);
+                        counters.record_failure();
                         false
                     }
                 }
             }
             Err(e) => {
-                error!("Pool health check connection acquisition failed: {}", e);
+                error!("{:?} pool health check connection acquisition failed: {}", role, e);
                 self.total_acquisition_failures.fetch_add(1, Ordering::Relaxed);
+                counters.record_failure();
                 false
             }
         }
@@ -1568,21 +2558,73 @@ impl Database {
 
     /// Close the pool gracefully
     pub async fn close(&self) {
+        self.stop_maintenance_schedule().await;
+        self.stop_backup_schedule().await;
+        self.stop_pool_tuning().await;
         info!("Closing database connection pool...");
+        self.read_pool.get().close().await;
         self.pool.close().await;
         info!("Database connection pool closed");
     }
 
-    /// Execute a database operation with metrics tracking and error handling
-    async fn execute_with_metrics<F, T, E>(&self, operation: F) -> Result<T, E>
+    /// Execute a write operation (on the writer pool) with metrics tracking and error handling
+    async fn execute_with_metrics<F, T, E>(&self, sql: &str, operation: F) -> Result<T, E>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+        E: From<sqlx::Error> + std::fmt::Display,
+    {
+        self.execute_on_with_metrics(PoolRole::Writer, sql, &[], operation).await
+    }
+
+    /// Execute a write operation binding `params`, which the slow query log may record
+    async fn execute_with_params<F, T, E>(&self, sql: &str, params: &[BindParam], operation: F) -> Result<T, E>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+        E: From<sqlx::Error> + std::fmt::Display,
+    {
+        self.execute_on_with_metrics(PoolRole::Writer, sql, params, operation).await
+    }
+
+    /// Execute a read operation (on the read-only pool) with metrics tracking and error handling
+    async fn execute_read_with_metrics<F, T, E>(&self, sql: &str, operation: F) -> Result<T, E>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+        E: From<sqlx::Error> + std::fmt::Display,
+    {
+        self.execute_on_with_metrics(PoolRole::Reader, sql, &[], operation).await
+    }
+
+    /// Execute a read operation binding `params`, which the slow query log may record
+    async fn execute_read_with_params<F, T, E>(&self, sql: &str, params: &[BindParam], operation: F) -> Result<T, E>
+    where
+        F: std::future::Future<Output = Result<T, E>>,
+        E: From<sqlx::Error> + std::fmt::Display,
+    {
+        self.execute_on_with_metrics(PoolRole::Reader, sql, params, operation).await
+    }
+
+    async fn execute_on_with_metrics<F, T, E>(
+        &self,
+        role: PoolRole,
+        sql: &str,
+        params: &[BindParam],
+        operation: F,
+    ) -> Result<T, E>
     where
         F: std::future::Future<Output = Result<T, E>>,
         E: From<sqlx::Error> + std::fmt::Display,
     {
+        let counters = match role {
+            PoolRole::Writer => &self.writer_counters,
+            PoolRole::Reader => &self.reader_counters,
+        };
         let start_time = Instant::now();
         self.total_queries.fetch_add(1, Ordering::Relaxed);
 
-        match operation.await {
+        let result = operation.await;
+        self.record_statement(sql, start_time.elapsed(), result.is_ok()).await;
+
+        match result {
             Ok(result) => {
                 // Track successful acquisition
                 let duration = start_time.elapsed();
@@ -1594,14 +2636,17 @@ impl Database {
                 if times.len() > 1000 {
                     times.remove(0);
                 }
+                drop(times);
+                counters.record_success(duration).await;
+                self.export_pool_metrics(role, duration);
 
-                // Check for slow queries (>100ms threshold)
-                if duration > Duration::from_millis(100) {
+                // Check for slow queries
+                if duration > Duration::from_millis(self.query_metrics_config.slow_query_threshold_ms) {
                     warn!("Slow database operation detected: {:?}", duration);
                     self.queries_over_threshold.fetch_add(1, Ordering::Relaxed);
 
                     // Log slow query for analysis
-                    self.log_slow_query("Unknown".to_string(), duration.as_millis() as u64, 0).await;
+                    self.log_slow_query(sql.to_string(), duration.as_millis() as u64, 0, params).await;
                 }
 
                 Ok(result)
@@ -1609,6 +2654,7 @@ impl Database {
             Err(e) => {
                 // Track failure
                 self.total_acquisition_failures.fetch_add(1, Ordering::Relaxed);
+                counters.record_failure();
 
                 // Check for deadlock
                 if e.to_string().contains("deadlock") || e.to_string().contains("database is locked") {
@@ -1629,9 +2675,57 @@ impl Database {
         }
     }
 
+    /// Record execution timing for a statement, keyed by its normalized SQL
+    async fn record_statement(&self, sql: &str, duration: Duration, success: bool) {
+        let key = normalize_sql(sql);
+        let mut stats = self.statement_stats.lock().await;
+
+        if !stats.contains_key(&key) && stats.len() >= self.query_metrics_config.max_tracked_statements {
+            // Make room by evicting the least-executed statement
+            let evict = stats.iter()
+                .min_by_key(|(_, s)| s.executions)
+                .map(|(sql, _)| sql.clone());
+            match evict {
+                Some(evict) => {
+                    stats.remove(&evict);
+                }
+                None => return, // max_tracked_statements is 0
+            }
+        }
+
+        let query_type = self.classify_query(&key);
+        stats.entry(key.clone())
+            .or_insert_with(|| StatementStats::new(key, query_type))
+            .record(duration, success);
+    }
+
+    /// Capture `EXPLAIN QUERY PLAN` output for a statement
+    ///
+    /// Runs on the read pool; bind parameters are left unbound, which SQLite plans as NULL.
+    pub async fn explain_query_plan(&self, sql: &str) -> Result<Vec<String>> {
+        let rows = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", sql))
+            .fetch_all(&self.read_pool.get())
+            .await?;
+
+        rows.iter()
+            .map(|row| row.try_get::<String, _>("detail").map_err(Into::into))
+            .collect()
+    }
+
     /// Log a slow query for analysis
-    async fn log_slow_query(&self, query: String, duration_ms: u64, affected_rows: i64) {
+    async fn log_slow_query(&self, query: String, duration_ms: u64, affected_rows: i64, params: &[BindParam]) {
         let query_type = self.classify_query(&query);
+        let query_plan = if self.query_metrics_config.capture_query_plans {
+            match self.explain_query_plan(&query).await {
+                Ok(plan) => Some(plan),
+                Err(e) => {
+                    debug!("Could not capture query plan for slow query: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         let slow_query = SlowQueryInfo {
             query: if query.len() > 500 {
                 format!("{}...", &query[..500])
@@ -1642,8 +2736,16 @@ impl Database {
             timestamp: Utc::now(),
             affected_rows,
             query_type,
+            query_plan,
+            bind_parameters: (!params.is_empty() && rand::random::<f64>() < self.query_metrics_config.bind_parameter_sample_rate)
+                .then(|| params.iter().map(BindParam::redacted).collect()),
+            span_stack: slow_queries::span_stack(),
         };
 
+        if self.query_metrics_config.persist_slow_queries {
+            self.persist_slow_query(&slow_query).await;
+        }
+
         let mut slow_queries = self.slow_queries.lock().await;
         slow_queries.push_back(slow_query);
 
@@ -1683,9 +2785,16 @@ impl Database {
             0.0
         };
 
-        // Calculate cache hit rate (simplified - would need actual SQLite stats)
-        let cache_hit_rate = if total_queries > 0 {
-            ((total_queries - queries_over_threshold) as f64 / total_queries as f64) * 100.0
+        let mut statements: Vec<StatementStats> = self.statement_stats.lock().await
+            .values()
+            .cloned()
+            .collect();
+        statements.sort_by(|a, b| b.total_duration_ms.total_cmp(&a.total_duration_ms));
+
+        // Every execution after a statement's first can reuse its cached preparation
+        let executions: u64 = statements.iter().map(|s| s.executions).sum();
+        let cache_hit_rate = if executions > 0 {
+            (executions - statements.len() as u64) as f64 / executions as f64 * 100.0
         } else {
             0.0
         };
@@ -1697,18 +2806,24 @@ impl Database {
             queries_over_threshold,
             deadlocks_detected,
             cache_hit_rate,
+            statements,
         }
     }
 
+    /// Configure slow query threshold, plan capture, and statement tracking
+    pub fn configure_query_metrics(&mut self, config: QueryMetricsConfig) {
+        info!("Updating database query metrics configuration");
+        self.query_metrics_config = config;
+    }
+
+    /// Get query metrics configuration
+    pub fn get_query_metrics_config(&self) -> &QueryMetricsConfig {
+        &self.query_metrics_config
+    }
+
     /// Perform database maintenance operations
     pub async fn perform_maintenance(&self) -> Result<MaintenanceReport> {
-        let mut report = MaintenanceReport {
-            vacuum_performed: false,
-            analyze_performed: false,
-            indices_optimized: 0,
-            maintenance_duration_ms: 0,
-            errors: Vec::new(),
-        };
+        let mut report = MaintenanceReport::default();
 
         let start_time = Instant::now();
 
@@ -1862,6 +2977,363 @@ impl Database {
         Ok(optimized_count)
     }
 
+    /// Run the full maintenance routine: WAL checkpoint, incremental vacuum,
+    /// ANALYZE, and integrity check
+    ///
+    /// Unlike `perform_maintenance`, every step runs unconditionally. Failures of
+    /// individual steps are collected in the report rather than aborting the run.
+    #[instrument(skip(self))]
+    pub async fn maintenance(&self) -> Result<MaintenanceReport> {
+        Ok(Self::run_maintenance_cycle(&self.pool, &self.maintenance_config, &self.last_analyze).await)
+    }
+
+    /// Checkpoint the WAL file using the given mode
+    pub async fn checkpoint_wal(&self, mode: WalCheckpointMode) -> Result<WalCheckpointResult> {
+        Self::checkpoint_wal_on(&self.pool, mode).await
+    }
+
+    /// Run `PRAGMA integrity_check`, returning the reported problems (empty if the database is intact)
+    pub async fn integrity_check(&self) -> Result<Vec<String>> {
+        Self::integrity_check_on(&self.pool).await
+    }
+
+    /// Start the background maintenance schedule
+    ///
+    /// Uses `interval` if given, otherwise `MaintenanceConfig::schedule_interval_minutes`.
+    /// Does nothing if neither is set. Any previously started schedule is replaced.
+    pub async fn start_maintenance_schedule(&self, interval: Option<Duration>) -> Result<()> {
+        let interval = match interval.or_else(|| {
+            self.maintenance_config.schedule_interval_minutes.map(|m| Duration::from_secs(m * 60))
+        }) {
+            Some(interval) if !interval.is_zero() => interval,
+            Some(_) => {
+                return Err(anyhow::anyhow!(crate::BinderyError::ConfigurationError(
+                    "maintenance schedule interval must be greater than 0".to_string()
+                )));
+            }
+            None => {
+                debug!("No maintenance schedule interval configured, not starting schedule");
+                return Ok(());
+            }
+        };
+
+        let pool = self.pool.clone();
+        let config = self.maintenance_config.clone();
+        let last_analyze = self.last_analyze.clone();
+
+        info!("Starting database maintenance schedule every {:?}", interval);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately; skip it so startup isn't slowed down
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                if pool.is_closed() {
+                    break;
+                }
+
+                let report = Self::run_maintenance_cycle(&pool, &config, &last_analyze).await;
+                if report.errors.is_empty() {
+                    debug!("Scheduled maintenance completed in {}ms", report.maintenance_duration_ms);
+                } else {
+                    warn!("Scheduled maintenance completed with errors: {:?}", report.errors);
+                }
+            }
+        });
+
+        if let Some(previous) = self.maintenance_handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the background maintenance schedule if it is running
+    pub async fn stop_maintenance_schedule(&self) {
+        if let Some(handle) = self.maintenance_handle.lock().await.take() {
+            handle.abort();
+            info!("Stopped database maintenance schedule");
+        }
+    }
+
+    async fn run_maintenance_cycle(
+        pool: &Pool<Sqlite>,
+        config: &MaintenanceConfig,
+        last_analyze: &tokio::sync::Mutex<Option<DateTime<Utc>>>,
+    ) -> MaintenanceReport {
+        let mut report = MaintenanceReport::default();
+        let start_time = Instant::now();
+
+        match Self::checkpoint_wal_on(pool, config.wal_checkpoint_mode).await {
+            Ok(result) => {
+                if result.busy {
+                    warn!("WAL checkpoint could not complete because the database was busy");
+                }
+                report.wal_checkpoint = Some(result);
+            }
+            Err(e) => report.errors.push(format!("WAL checkpoint failed: {}", e)),
+        }
+
+        match Self::incremental_vacuum_on(pool, config.incremental_vacuum_pages).await {
+            Ok(pages) => report.pages_reclaimed = pages,
+            Err(e) => report.errors.push(format!("Incremental vacuum failed: {}", e)),
+        }
+
+        match sqlx::query("ANALYZE").execute(pool).await {
+            Ok(_) => {
+                report.analyze_performed = true;
+                *last_analyze.lock().await = Some(Utc::now());
+            }
+            Err(e) => report.errors.push(format!("ANALYZE failed: {}", e)),
+        }
+
+        if config.integrity_check_enabled {
+            match Self::integrity_check_on(pool).await {
+                Ok(problems) => {
+                    if !problems.is_empty() {
+                        error!("Database integrity check reported {} problems", problems.len());
+                    }
+                    report.integrity_ok = Some(problems.is_empty());
+                    report.integrity_errors = problems;
+                }
+                Err(e) => report.errors.push(format!("Integrity check failed: {}", e)),
+            }
+        }
+
+        for error_msg in &report.errors {
+            error!("{}", error_msg);
+        }
+
+        report.maintenance_duration_ms = start_time.elapsed().as_millis() as u64;
+        report
+    }
+
+    async fn checkpoint_wal_on(pool: &Pool<Sqlite>, mode: WalCheckpointMode) -> Result<WalCheckpointResult> {
+        let row = sqlx::query(&format!("PRAGMA wal_checkpoint({})", mode.as_sql()))
+            .fetch_one(pool)
+            .await?;
+
+        let result = WalCheckpointResult {
+            mode,
+            busy: row.try_get::<i64, _>(0)? != 0,
+            log_frames: row.try_get(1)?,
+            checkpointed_frames: row.try_get(2)?,
+        };
+        debug!("WAL checkpoint result: {:?}", result);
+        Ok(result)
+    }
+
+    /// Returns the number of pages reclaimed, or `None` if the database does not use
+    /// `auto_vacuum = INCREMENTAL`
+    async fn incremental_vacuum_on(pool: &Pool<Sqlite>, max_pages: u32) -> Result<Option<i64>> {
+        let mut conn = pool.acquire().await?;
+
+        let auto_vacuum: i64 = sqlx::query_scalar("PRAGMA auto_vacuum")
+            .fetch_one(&mut *conn)
+            .await?;
+        // 2 = INCREMENTAL; NONE and FULL have nothing to reclaim incrementally
+        if auto_vacuum != 2 {
+            return Ok(None);
+        }
+
+        let free_before: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(&mut *conn)
+            .await?;
+        // incremental_vacuum returns no rows but must be stepped to completion
+        sqlx::query(&format!("PRAGMA incremental_vacuum({})", max_pages))
+            .fetch_all(&mut *conn)
+            .await?;
+        let free_after: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(&mut *conn)
+            .await?;
+
+        Ok(Some(free_before - free_after))
+    }
+
+    async fn integrity_check_on(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+        let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.into_iter().filter(|row| row != "ok").collect())
+    }
+
+    /// Back up the database to `destination` while it remains in use
+    ///
+    /// Uses SQLite's online backup API on a dedicated connection, copying a
+    /// bounded number of pages per step so writers are not blocked for the
+    /// whole backup. Any existing file at `destination` is overwritten.
+    #[instrument(skip(self, destination), fields(destination = %destination.as_ref().display()))]
+    pub async fn backup_to(&self, destination: impl AsRef<Path>) -> Result<BackupInfo> {
+        let source = self.file_path()?.to_path_buf();
+        Self::backup_file(source, destination.as_ref().to_path_buf(), self.key.clone()).await
+    }
+
+    /// Restore the database contents from a backup created by `backup_to`
+    ///
+    /// The backup is integrity-checked before any data is overwritten. Writes
+    /// issued while the restore is running will fail with a busy error.
+    #[instrument(skip(self, backup_path), fields(backup_path = %backup_path.as_ref().display()))]
+    pub async fn restore_from(&self, backup_path: impl AsRef<Path>) -> Result<()> {
+        let backup_path = backup_path.as_ref().to_path_buf();
+        let target = self.file_path()?.to_path_buf();
+
+        if !backup_path.is_file() {
+            return Err(anyhow::anyhow!("Backup file not found: {}", backup_path.display()));
+        }
+
+        // Never overwrite the live database with a corrupt copy
+        let backup_options = with_key(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&backup_path)
+                .read_only(true),
+            self.key.as_deref().map(String::as_str),
+        );
+        let backup_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(backup_options)
+            .await?;
+        let problems = Self::integrity_check_on(&backup_pool).await;
+        backup_pool.close().await;
+        let problems = problems?;
+        if !problems.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Backup {} failed integrity check: {}",
+                backup_path.display(),
+                problems.join("; ")
+            ));
+        }
+
+        warn!("Restoring database {} from backup {}", target.display(), backup_path.display());
+        let start_time = Instant::now();
+        let key = self.key.clone();
+        tokio::task::spawn_blocking(move || raw_sqlite::copy_database(&backup_path, &target, key.as_deref().map(String::as_str)))
+            .await
+            .map_err(|e| anyhow::anyhow!("Restore task panicked: {}", e))??;
+
+        info!("Database restore completed in {:?}", start_time.elapsed());
+        Ok(())
+    }
+
+    /// List backups in `directory` created by the backup schedule, oldest first
+    pub async fn list_backups(&self, directory: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let prefix = Self::backup_prefix(self.file_path()?);
+        Self::list_backups_in(directory.as_ref(), &prefix).await
+    }
+
+    /// Start the scheduled backup task, retaining at most `config.retain` backups
+    ///
+    /// Any previously started backup schedule is replaced.
+    pub async fn start_backup_schedule(&self, config: BackupConfig) -> Result<()> {
+        config.validate().map_err(|e| anyhow::anyhow!("Backup configuration validation failed: {}", e))?;
+        let source = self.file_path()?.to_path_buf();
+        tokio::fs::create_dir_all(&config.directory).await?;
+
+        let pool = self.pool.clone();
+        let key = self.key.clone();
+        info!("Starting database backup schedule every {}s into {:?} (retaining {})",
+              config.interval_seconds, config.directory, config.retain);
+        let handle = tokio::spawn(async move {
+            let prefix = Self::backup_prefix(&source);
+            let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_seconds));
+
+            loop {
+                ticker.tick().await;
+                if pool.is_closed() {
+                    break;
+                }
+
+                let file_name = format!("{}{}.db", prefix, Utc::now().format("%Y%m%dT%H%M%S%.3fZ"));
+                match Self::backup_file(source.clone(), config.directory.join(file_name), key.clone()).await {
+                    Ok(backup) => debug!("Scheduled backup written to {:?}", backup.path),
+                    Err(e) => {
+                        error!("Scheduled database backup failed: {}", e);
+                        continue;
+                    }
+                }
+
+                if let Err(e) = Self::prune_backups(&config.directory, &prefix, config.retain).await {
+                    warn!("Failed to prune old database backups: {}", e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.backup_handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the scheduled backup task if it is running
+    pub async fn stop_backup_schedule(&self) {
+        if let Some(handle) = self.backup_handle.lock().await.take() {
+            handle.abort();
+            info!("Stopped database backup schedule");
+        }
+    }
+
+    fn file_path(&self) -> Result<&Path> {
+        self.database_path.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("Backup and restore require a file-backed database")
+        })
+    }
+
+    fn backup_prefix(source: &Path) -> String {
+        let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("database");
+        format!("{}-backup-", stem)
+    }
+
+    async fn backup_file(
+        source: PathBuf,
+        destination: PathBuf,
+        key: Option<zeroize::Zeroizing<String>>,
+    ) -> Result<BackupInfo> {
+        let start_time = Instant::now();
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let backup_path = destination.clone();
+        tokio::task::spawn_blocking(move || raw_sqlite::copy_database(&source, &backup_path, key.as_deref().map(String::as_str)))
+            .await
+            .map_err(|e| anyhow::anyhow!("Backup task panicked: {}", e))??;
+
+        let info = BackupInfo {
+            size_bytes: tokio::fs::metadata(&destination).await?.len(),
+            path: destination,
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            created_at: Utc::now(),
+        };
+        info!("Database backup written to {:?} ({} bytes in {}ms)", info.path, info.size_bytes, info.duration_ms);
+        Ok(info)
+    }
+
+    async fn list_backups_in(directory: &Path, prefix: &str) -> Result<Vec<PathBuf>> {
+        let mut backups = Vec::new();
+        let mut entries = tokio::fs::read_dir(directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(prefix) && name.ends_with(".db") {
+                backups.push(entry.path());
+            }
+        }
+
+        // Timestamps in the file names sort chronologically
+        backups.sort();
+        Ok(backups)
+    }
+
+    async fn prune_backups(directory: &Path, prefix: &str, retain: usize) -> Result<usize> {
+        let backups = Self::list_backups_in(directory, prefix).await?;
+        let excess = backups.len().saturating_sub(retain);
+        for path in &backups[..excess] {
+            tokio::fs::remove_file(path).await?;
+            debug!("Removed old database backup {:?}", path);
+        }
+        Ok(excess)
+    }
+
     /// Configure maintenance settings
     pub fn configure_maintenance(&mut self, config: MaintenanceConfig) -> Result<()> {
         info!("Updating database maintenance configuration");
@@ -1874,11 +3346,19 @@ impl Database {
         &self.maintenance_config
     }
 
-    /// Get the underlying pool for advanced operations (use with caution)
+    /// Get the underlying writer pool for advanced operations (use with caution)
     pub fn get_pool(&self) -> &Pool<Sqlite> {
         &self.pool
     }
 
+    /// Get the underlying read-only pool for advanced read queries
+    ///
+    /// Pool tuning may replace the pool, so fetch it for each query rather
+    /// than holding on to it.
+    pub fn get_read_pool(&self) -> Pool<Sqlite> {
+        self.read_pool.get()
+    }
+
     // ============================================================================
     // Codex Management Methods
     // ============================================================================
@@ -1928,6 +3408,7 @@ impl Database {
         .map_err(|e| anyhow::anyhow!("Failed to create codex: {}", e))?;
 
         info!(codex_id = %id, title = %title, parent_id = ?parent_id, "Created codex in database");
+        self.publish_change(ChangeEvent::CodexUpserted { id: id.to_string() });
         Ok(())
     }
 
@@ -1942,7 +3423,7 @@ impl Database {
             "#,
         )
         .bind(id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.read_pool.get())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to get codex: {}", e))?;
 
@@ -2036,6 +3517,7 @@ impl Database {
         .map_err(|e| anyhow::anyhow!("Failed to update codex: {}", e))?;
 
         info!(codex_id = %id, title = %title, parent_id = ?parent_id, "Updated codex in database");
+        self.publish_change(ChangeEvent::CodexUpserted { id: id.to_string() });
         Ok(())
     }
 
@@ -2051,48 +3533,40 @@ impl Database {
             "#,
         )
         .bind(parent_id)
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool.get())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to list child codices: {}", e))?;
 
         let mut codices = Vec::new();
         for row in rows {
-            let id: String = row.get("id");
-            let title: String = row.get("title");
-            let template_id: String = row.get("template_id");
-            let content_str: String = row.get("content");
-            let metadata_str: String = row.get("metadata");
-            let project_id: Option<String> = row.get("project_id");
-            let parent_id: Option<String> = row.get("parent_id");
-            let created_at: String = row.get("created_at");
-            let updated_at: String = row.get("updated_at");
-
-            let content: serde_json::Value = serde_json::from_str(&content_str)
-                .unwrap_or(serde_json::json!({"fields": {}}));
-
-            let metadata: serde_json::Value = serde_json::from_str(&metadata_str)
-                .unwrap_or(serde_json::json!({}));
+            codices.push(codex_json(&row));
+        }
 
-            let mut codex = serde_json::json!({
-                "id": id,
-                "title": title,
-                "template_id": template_id,
-                "content": content,
-                "metadata": metadata,
-                "project_id": project_id,
-                "created_at": created_at,
-                "updated_at": updated_at,
-            });
+        info!(parent_id = %parent_id, count = codices.len(), "Listed child codices");
+        Ok(codices)
+    }
 
-            // Add parent_id if it exists
-            if let Some(parent) = parent_id {
-                codex.as_object_mut().unwrap().insert("parent_id".to_string(), serde_json::json!(parent));
-            }
+    /// List codices created from a template, oldest first
+    #[instrument(skip(self), fields(template_id = %template_id))]
+    pub async fn list_codices_by_template(&self, template_id: &str) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, title, template_id, content, metadata, project_id, parent_id, created_at, updated_at
+            FROM codices
+            WHERE template_id = ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(template_id)
+        .fetch_all(&self.read_pool.get())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list codices by template: {}", e))?;
 
-            codices.push(codex);
+        let mut codices = Vec::new();
+        for row in rows {
+            codices.push(codex_json(&row));
         }
 
-        info!(parent_id = %parent_id, count = codices.len(), "Listed child codices");
         Ok(codices)
     }
 
@@ -2106,47 +3580,67 @@ impl Database {
             ORDER BY created_at DESC
             "#,
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool.get())
         .await
         .map_err(|e| anyhow::anyhow!("Failed to list codices: {}", e))?;
         let mut codices = Vec::new();
         for row in rows {
-            let id: String = row.get("id");
-            let title: String = row.get("title");
-            let template_id: String = row.get("template_id");
-            let content_str: String = row.get("content");
-            let metadata_str: String = row.get("metadata");
-            let project_id: Option<String> = row.get("project_id");
-            let parent_id: Option<String> = row.get("parent_id");
-            let created_at: String = row.get("created_at");
-            let updated_at: String = row.get("updated_at");
+            codices.push(codex_json(&row));
+        }
 
-            let content: serde_json::Value = serde_json::from_str(&content_str)
-                .unwrap_or(serde_json::json!({"fields": {}}));
+        Ok(codices)
+    }
 
-            let metadata: serde_json::Value = serde_json::from_str(&metadata_str)
-                .unwrap_or(serde_json::json!({}));
+    /// One page of codices, newest first
+    #[instrument(skip(self))]
+    pub async fn list_codices_page(&self, request: &PageRequest) -> Result<Page<serde_json::Value>> {
+        let after = request.cursor.as_deref().map(|cursor| pagination::decode_cursor(cursor, 2)).transpose()?;
+        let (after_created, after_id) = match &after {
+            Some(parts) => (Some(parts[0].as_str()), Some(parts[1].as_str())),
+            None => (None, None),
+        };
+        let page_size = request.page_size();
 
-            let mut codex = serde_json::json!({
-                "id": id,
-                "title": title,
-                "template_id": template_id,
-                "content": content,
-                "metadata": metadata,
-                "project_id": project_id,
-                "created_at": created_at,
-                "updated_at": updated_at,
-            });
+        let rows = sqlx::query(
+            r#"
+            SELECT id, title, template_id, content, metadata, project_id, parent_id, created_at, updated_at
+            FROM codices
+            WHERE ?1 IS NULL OR created_at < ?1 OR (created_at = ?1 AND id < ?2)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(after_created)
+        .bind(after_id)
+        .bind((page_size + 1) as i64)
+        .fetch_all(&self.read_pool.get())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list codices: {}", e))?;
 
-            // Add parent_id if it exists
-            if let Some(parent) = parent_id {
-                codex.as_object_mut().unwrap().insert("parent_id".to_string(), serde_json::json!(parent));
-            }
+        let codices = rows.iter().map(codex_json).collect();
+        Ok(Page::from_overfetch(codices, page_size, |codex| {
+            let field = |name: &str| codex.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            pagination::encode_cursor(&[&field("created_at"), &field("id")])
+        }))
+    }
 
-            codices.push(codex);
+    /// All codices, newest first, fetched `page_size` at a time
+    ///
+    /// Lets callers process large workspaces without holding every codex in memory.
+    pub fn stream_codices(&self, page_size: usize) -> impl futures::Stream<Item = Result<serde_json::Value>> + '_ {
+        async_stream::try_stream! {
+            let mut request = PageRequest::first(page_size);
+            loop {
+                let page = self.list_codices_page(&request).await?;
+                for codex in page.items {
+                    yield codex;
+                }
+                match page.next_cursor {
+                    Some(cursor) => request.cursor = Some(cursor),
+                    None => break,
+                }
+            }
         }
-
-        Ok(codices)
     }
 
     /// Delete a codex from the database
@@ -2166,6 +3660,7 @@ impl Database {
         let deleted = result.rows_affected() > 0;
         if deleted {
             info!(codex_id = %id, "Deleted codex from database");
+            self.publish_change(ChangeEvent::CodexDeleted { id: id.to_string() });
         } else {
             warn!(codex_id = %id, "Codex not found for deletion");
         }
@@ -2174,12 +3669,134 @@ impl Database {
 }
 
 /// Report from database maintenance operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MaintenanceReport {
     pub vacuum_performed: bool,
     pub analyze_performed: bool,
     pub indices_optimized: usize,
     pub maintenance_duration_ms: u64,
     pub errors: Vec<String>,
+    /// WAL checkpoint result, if a checkpoint was run
+    pub wal_checkpoint: Option<WalCheckpointResult>,
+    /// Pages reclaimed by incremental vacuum (`None` if auto_vacuum is not INCREMENTAL)
+    pub pages_reclaimed: Option<i64>,
+    /// Whether the integrity check passed, if one was run
+    pub integrity_ok: Option<bool>,
+    /// Problems reported by the integrity check
+    pub integrity_errors: Vec<String>,
+}
+
+
+/// Collapse whitespace so the same statement formatted differently shares one cache key
+fn normalize_sql(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+/// Thin wrapper over the SQLite C API for the online backup interface, which sqlx does not expose
+mod raw_sqlite {
+    use super::{BACKUP_MAX_BUSY_RETRIES, BACKUP_PAGES_PER_STEP};
+    use anyhow::Result;
+    use libsqlite3_sys as ffi;
+    use std::ffi::{CStr, CString};
+    use std::os::raw::c_int;
+    use std::path::Path;
+    use std::ptr;
+    use std::time::Duration;
+
+    struct Connection(*mut ffi::sqlite3);
+
+    impl Connection {
+        /// Open `path`, keyed with the SQLCipher `PRAGMA key` value `key` if given
+        fn open(path: &Path, flags: c_int, key: Option<&str>) -> Result<Self> {
+            let c_path = CString::new(path.to_str().ok_or_else(|| {
+                anyhow::anyhow!("Database path is not valid UTF-8: {}", path.display())
+            })?)?;
+
+            let mut handle = ptr::null_mut();
+            // SAFETY: c_path is a valid NUL-terminated string and handle is a valid out pointer
+            let rc = unsafe { ffi::sqlite3_open_v2(c_path.as_ptr(), &mut handle, flags, ptr::null()) };
+            // SQLite allocates a handle even when opening fails, so always take ownership of it
+            let connection = Self(handle);
+            if rc != ffi::SQLITE_OK {
+                return Err(anyhow::anyhow!("Failed to open {}: {}", path.display(), connection.error_message()));
+            }
+
+            // SAFETY: the handle was successfully opened above
+            unsafe { ffi::sqlite3_busy_timeout(connection.0, 5000) };
+            if let Some(key) = key {
+                connection.execute(&zeroize::Zeroizing::new(format!("PRAGMA key = {}", key)))?;
+            }
+            Ok(connection)
+        }
+
+        fn execute(&self, sql: &str) -> Result<()> {
+            let c_sql = zeroize::Zeroizing::new(CString::new(sql)?.into_bytes_with_nul());
+            // SAFETY: the handle is open and c_sql is NUL-terminated; no callback is passed
+            let rc = unsafe {
+                ffi::sqlite3_exec(self.0, c_sql.as_ptr().cast(), None, ptr::null_mut(), ptr::null_mut())
+            };
+            if rc != ffi::SQLITE_OK {
+                return Err(anyhow::anyhow!("Failed to configure connection: {}", self.error_message()));
+            }
+            Ok(())
+        }
+
+        fn error_message(&self) -> String {
+            if self.0.is_null() {
+                return "out of memory".to_string();
+            }
+            // SAFETY: sqlite3_errmsg returns a valid NUL-terminated string owned by the connection
+            unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.0)) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            // SAFETY: closing a null handle is a no-op, and every backup using this
+            // connection has been finished before it is dropped
+            unsafe { ffi::sqlite3_close(self.0) };
+        }
+    }
+
+    /// Copy the main database of `source` into `destination` using the online backup API
+    ///
+    /// Both are keyed with `key` when the database is encrypted, so the copy
+    /// is encrypted with the same key.
+    pub(super) fn copy_database(source: &Path, destination: &Path, key: Option<&str>) -> Result<()> {
+        let source = Connection::open(source, ffi::SQLITE_OPEN_READONLY, key)?;
+        let destination = Connection::open(destination, ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE, key)?;
+        let main = c"main";
+
+        // SAFETY: both handles are open and outlive the backup object
+        let backup = unsafe {
+            ffi::sqlite3_backup_init(destination.0, main.as_ptr(), source.0, main.as_ptr())
+        };
+        if backup.is_null() {
+            return Err(anyhow::anyhow!("Failed to start backup: {}", destination.error_message()));
+        }
+
+        let mut busy_retries = 0;
+        loop {
+            // SAFETY: backup is a live backup object until sqlite3_backup_finish below
+            match unsafe { ffi::sqlite3_backup_step(backup, BACKUP_PAGES_PER_STEP) } {
+                ffi::SQLITE_OK => busy_retries = 0,
+                ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED if busy_retries < BACKUP_MAX_BUSY_RETRIES => {
+                    busy_retries += 1;
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                // SQLITE_DONE, or an error that sqlite3_backup_finish reports
+                _ => break,
+            }
+        }
+
+        // SAFETY: backup has not been finished yet; it is not used afterwards
+        let rc = unsafe { ffi::sqlite3_backup_finish(backup) };
+        if rc != ffi::SQLITE_OK {
+            return Err(anyhow::anyhow!("Backup failed: {}", destination.error_message()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+//! Encryption at rest for the task/Codex and audit databases
+//!
+//! With the `db-encryption` feature the bundled SQLite is SQLCipher.
+//! [`Database::new_encrypted`] and [`AuditLogger::new_encrypted`] key every
+//! connection with a [`DatabaseKey`], a random 256-bit key kept in the
+//! [`SecretManager`] (under [`ENCRYPTION_KEY_SECRET`] by default) rather than
+//! in any configuration file. Opening a database with the wrong key, or an
+//! encrypted one without a key, fails with a `DatabaseError` saying so.
+//! Backups, scheduled or not, are encrypted with the database's key, and
+//! restores read them with it.
+//!
+//! [`rotate_key`] re-encrypts a database under a new key. It needs the
+//! database to be closed; [`rotate_secret_key`] rotates several databases
+//! and then replaces the stored key, rolling back if any step fails.
+
+use std::fmt;
+use std::path::Path;
+
+use rand::RngCore;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, SqliteConnection};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use super::{check_readable, with_key, Database, DatabasePoolConfig};
+use crate::observability::{AuditConfig, AuditLogger};
+use crate::secrets::SecretManager;
+use crate::{BinderyError, BinderyResult};
+
+/// Secret holding the database encryption key
+pub const ENCRYPTION_KEY_SECRET: &str = "database/encryption_key";
+
+/// Key length in bytes
+const KEY_LEN: usize = 32;
+
+/// A raw SQLCipher key, held as hex
+#[derive(Clone, PartialEq, Eq)]
+pub struct DatabaseKey(Zeroizing<String>);
+
+impl fmt::Debug for DatabaseKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DatabaseKey(..)")
+    }
+}
+
+impl DatabaseKey {
+    /// A new random key
+    pub fn generate() -> Self {
+        let mut bytes = Zeroizing::new([0u8; KEY_LEN]);
+        rand::rngs::OsRng.fill_bytes(bytes.as_mut());
+        let mut hex = Zeroizing::new(String::with_capacity(KEY_LEN * 2));
+        for byte in bytes.iter() {
+            hex.push_str(&format!("{:02x}", byte));
+        }
+        Self(hex)
+    }
+
+    /// A key from its 64 hex digits
+    pub fn from_hex(hex: &str) -> BinderyResult<Self> {
+        let hex = hex.trim();
+        if hex.len() != KEY_LEN * 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(BinderyError::ConfigurationError(format!(
+                "A database encryption key must be {} hex digits",
+                KEY_LEN * 2
+            )));
+        }
+        Ok(Self(Zeroizing::new(hex.to_ascii_lowercase())))
+    }
+
+    /// The key as stored in the secret manager
+    pub fn to_hex(&self) -> &str {
+        &self.0
+    }
+
+    /// The key stored as `secret_name`
+    pub async fn load(secrets: &SecretManager, secret_name: &str) -> BinderyResult<Self> {
+        let hex = Zeroizing::new(secrets.get_secret(secret_name).await.map_err(|e| {
+            BinderyError::ConfigurationError(format!(
+                "No database encryption key available as secret '{}': {}",
+                secret_name, e
+            ))
+        })?);
+        Self::from_hex(&hex)
+    }
+
+    /// The key stored as `secret_name`, or a new one stored there if none of
+    /// `databases` exist yet
+    ///
+    /// Never replaces the key of existing databases: a secret backend that is
+    /// merely unavailable would otherwise lock them out for good.
+    pub async fn load_or_create(
+        secrets: &SecretManager,
+        secret_name: &str,
+        databases: &[impl AsRef<Path>],
+    ) -> BinderyResult<Self> {
+        match Self::load(secrets, secret_name).await {
+            Ok(key) => Ok(key),
+            Err(e) if databases.iter().any(|path| path.as_ref().exists()) => Err(e),
+            Err(_) => {
+                let key = Self::generate();
+                store(secrets, secret_name, &key).await?;
+                info!("Created database encryption key '{}'", secret_name);
+                Ok(key)
+            }
+        }
+    }
+
+    /// The value for `PRAGMA key` and `PRAGMA rekey`: a raw key, so
+    /// SQLCipher skips its passphrase derivation
+    fn pragma(&self) -> Zeroizing<String> {
+        Zeroizing::new(format!("\"x'{}'\"", self.to_hex()))
+    }
+}
+
+async fn store(secrets: &SecretManager, secret_name: &str, key: &DatabaseKey) -> BinderyResult<()> {
+    secrets.store_secret(secret_name, key.to_hex()).await.map_err(|e| {
+        BinderyError::ConfigurationError(format!(
+            "Failed to store database encryption key '{}': {}",
+            secret_name, e
+        ))
+    })
+}
+
+impl Database {
+    /// Open a database encrypted with `key`, creating it if it doesn't exist
+    pub async fn new_encrypted(
+        database_path: impl AsRef<Path>,
+        config: DatabasePoolConfig,
+        key: &DatabaseKey,
+    ) -> anyhow::Result<Self> {
+        Self::open(database_path, config, Some(&key.pragma())).await
+    }
+}
+
+impl AuditLogger {
+    /// Open an audit log whose database is encrypted with `key`
+    pub async fn new_encrypted(config: AuditConfig, key: &DatabaseKey) -> BinderyResult<Self> {
+        Self::open(config, Some(&key.pragma())).await
+    }
+}
+
+/// A single connection to `path` keyed with `key`, checked to decrypt
+async fn connect(path: &Path, key: &DatabaseKey) -> BinderyResult<SqliteConnection> {
+    let options = with_key(SqliteConnectOptions::new().filename(path), Some(&key.pragma()));
+    check_readable(&options, &path.display().to_string(), true).await
+}
+
+/// Re-encrypt the database at `path` from `old` to `new`
+///
+/// The database must not be open elsewhere: connections keyed with `old`
+/// can't read pages written under `new`.
+pub async fn rotate_key(path: &Path, old: &DatabaseKey, new: &DatabaseKey) -> BinderyResult<()> {
+    if !path.exists() {
+        return Err(BinderyError::NotFound(format!("Database {}", path.display())));
+    }
+    let mut conn = connect(path, old).await?;
+
+    // SQLCipher can't rekey in WAL mode; fold the log in and switch back after
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode").fetch_one(&mut conn).await?;
+    let wal = journal_mode.eq_ignore_ascii_case("wal");
+    if wal {
+        sqlx::query("PRAGMA journal_mode = DELETE").execute(&mut conn).await?;
+    }
+    sqlx::query(&format!("PRAGMA rekey = {}", new.pragma().as_str()))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| BinderyError::DatabaseError(format!("Failed to re-encrypt {}: {}", path.display(), e)))?;
+    if wal {
+        sqlx::query("PRAGMA journal_mode = WAL").execute(&mut conn).await?;
+    }
+    conn.close().await?;
+
+    // Make sure the new key opens it before anyone relies on it
+    connect(path, new).await?.close().await?;
+    info!("Re-encrypted {}", path.display());
+    Ok(())
+}
+
+/// Re-encrypt `databases` under a new key and store it as `secret_name`,
+/// returning the new key
+///
+/// If a database fails to re-encrypt, or the new key can't be stored, the
+/// databases already done are changed back to the old key.
+pub async fn rotate_secret_key(
+    secrets: &SecretManager,
+    secret_name: &str,
+    databases: &[impl AsRef<Path>],
+) -> BinderyResult<DatabaseKey> {
+    let old = DatabaseKey::load(secrets, secret_name).await?;
+    let new = DatabaseKey::generate();
+
+    let mut rotated = Vec::with_capacity(databases.len());
+    let mut result = Ok(());
+    for path in databases {
+        let path = path.as_ref();
+        if let Err(e) = rotate_key(path, &old, &new).await {
+            result = Err(e);
+            break;
+        }
+        rotated.push(path);
+    }
+    if result.is_ok() {
+        result = store(secrets, secret_name, &new).await;
+    }
+
+    if let Err(e) = result {
+        for path in rotated {
+            if let Err(rollback) = rotate_key(path, &new, &old).await {
+                warn!("Failed to restore the previous key of {}: {}", path.display(), rollback);
+            }
+        }
+        return Err(e);
+    }
+    Ok(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{AesGcmBackend, AesGcmConfig, KdfParams};
+    use tempfile::TempDir;
+
+    fn secrets(dir: &TempDir) -> SecretManager {
+        SecretManager::with_backend(Box::new(
+            AesGcmBackend::new(AesGcmConfig {
+                path: dir.path().join("secrets.enc"),
+                passphrase: Zeroizing::new("test passphrase".to_string()),
+                kdf: KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 },
+            })
+            .unwrap(),
+        ))
+    }
+
+    async fn write_marker(path: &Path, key: &DatabaseKey) {
+        let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+        let mut conn = SqliteConnection::connect_with(&with_key(options, Some(&key.pragma()))).await.unwrap();
+        sqlx::query("CREATE TABLE marker (value TEXT)").execute(&mut conn).await.unwrap();
+        sqlx::query("INSERT INTO marker VALUES ('kept')").execute(&mut conn).await.unwrap();
+        conn.close().await.unwrap();
+    }
+
+    async fn read_marker(path: &Path, key: &DatabaseKey) -> BinderyResult<String> {
+        let mut conn = connect(path, key).await?;
+        Ok(sqlx::query_scalar("SELECT value FROM marker").fetch_one(&mut conn).await?)
+    }
+
+    #[test]
+    fn test_keys_are_random_hex() {
+        let key = DatabaseKey::generate();
+        assert_eq!(key.to_hex().len(), 64);
+        assert_ne!(key, DatabaseKey::generate());
+        assert_eq!(DatabaseKey::from_hex(&key.to_hex().to_uppercase()).unwrap(), key);
+        assert!(DatabaseKey::from_hex("not a key").is_err());
+        assert_eq!(format!("{:?}", key), "DatabaseKey(..)");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_key_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tasks.db");
+        let key = DatabaseKey::generate();
+        let database = Database::new_encrypted(&path, DatabasePoolConfig::default(), &key).await.unwrap();
+        database.close().await;
+
+        // Not readable as plain SQLite
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(!bytes.starts_with(b"SQLite format 3"));
+
+        let wrong = Database::new_encrypted(&path, DatabasePoolConfig::default(), &DatabaseKey::generate()).await;
+        let message = wrong.err().unwrap().to_string();
+        assert!(message.contains("encryption key is wrong"), "{}", message);
+        let missing = Database::new(&path).await.err().unwrap().to_string();
+        assert!(missing.contains("no key was given"), "{}", missing);
+
+        let reopened = Database::new_encrypted(&path, DatabasePoolConfig::default(), &key).await.unwrap();
+        assert!(reopened.is_pool_healthy().await);
+        reopened.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_backup_and_restore() {
+        let dir = TempDir::new().unwrap();
+        let key = DatabaseKey::generate();
+        let database = Database::new_encrypted(dir.path().join("tasks.db"), DatabasePoolConfig::default(), &key).await.unwrap();
+        let pool = database.get_pool();
+        sqlx::query("CREATE TABLE marker (value TEXT)").execute(pool).await.unwrap();
+        sqlx::query("INSERT INTO marker VALUES ('kept')").execute(pool).await.unwrap();
+
+        // The copy is encrypted under the same key
+        let backup = dir.path().join("backups").join("tasks-backup.db");
+        database.backup_to(&backup).await.unwrap();
+        assert!(!std::fs::read(&backup).unwrap().starts_with(b"SQLite format 3"));
+        assert_eq!(read_marker(&backup, &key).await.unwrap(), "kept");
+
+        sqlx::query("UPDATE marker SET value = 'changed'").execute(pool).await.unwrap();
+        database.restore_from(&backup).await.unwrap();
+        let value: String = sqlx::query_scalar("SELECT value FROM marker").fetch_one(pool).await.unwrap();
+        assert_eq!(value, "kept");
+        database.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_is_encrypted() {
+        let dir = TempDir::new().unwrap();
+        let config = AuditConfig { audit_db_path: dir.path().join("audit.db"), ..AuditConfig::default() };
+        let key = DatabaseKey::generate();
+        drop(AuditLogger::new_encrypted(config.clone(), &key).await.unwrap());
+
+        let message = AuditLogger::new_encrypted(config.clone(), &DatabaseKey::generate()).await.err().unwrap();
+        assert!(message.to_string().contains("encryption key is wrong"), "{}", message);
+        assert!(AuditLogger::new(config.clone()).await.is_err());
+        assert!(AuditLogger::new_encrypted(config, &key).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_re_encrypts_and_stores_the_key() {
+        let dir = TempDir::new().unwrap();
+        let secrets = secrets(&dir);
+        let tasks = dir.path().join("tasks.db");
+        let audit = dir.path().join("audit.db");
+
+        // A new workspace gets a key; an existing one never gets a fresh one
+        let key = DatabaseKey::load_or_create(&secrets, ENCRYPTION_KEY_SECRET, &[&tasks, &audit]).await.unwrap();
+        assert_eq!(DatabaseKey::load(&secrets, ENCRYPTION_KEY_SECRET).await.unwrap(), key);
+        assert!(DatabaseKey::load_or_create(&secrets, "database/other", &[dir.path()]).await.is_err());
+
+        write_marker(&tasks, &key).await;
+        write_marker(&audit, &key).await;
+
+        let new = rotate_secret_key(&secrets, ENCRYPTION_KEY_SECRET, &[&tasks, &audit]).await.unwrap();
+        assert_ne!(new, key);
+        assert_eq!(DatabaseKey::load(&secrets, ENCRYPTION_KEY_SECRET).await.unwrap(), new);
+        for path in [&tasks, &audit] {
+            assert_eq!(read_marker(path, &new).await.unwrap(), "kept");
+            assert!(read_marker(path, &key).await.is_err());
+        }
+
+        // A failure part way leaves every database under the stored key
+        let missing = dir.path().join("missing.db");
+        assert!(rotate_secret_key(&secrets, ENCRYPTION_KEY_SECRET, &[&tasks, &missing]).await.is_err());
+        assert_eq!(DatabaseKey::load(&secrets, ENCRYPTION_KEY_SECRET).await.unwrap(), new);
+        assert_eq!(read_marker(&tasks, &new).await.unwrap(), "kept");
+    }
+}
@@ -0,0 +1,404 @@
+//! Postgres storage backend for team deployments
+//!
+//! Mirrors the SQLite task/Codex index schema using native Postgres types
+//! (`TIMESTAMPTZ`, `JSONB`). Only compiled with the `postgres` feature.
+
+use std::path::Path;
+use std::time::Duration;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::Row;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::storage::{SqlDialect, StorageBackend};
+use super::{TaskInput, TaskSummary, MAX_TASK_DEPTH};
+use crate::migration::MigrationManager;
+
+/// Default connection limit for the Postgres pool
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+/// Built-in schema, applied before any Postgres-specific migration files
+const SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS tasks (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        description TEXT,
+        status TEXT NOT NULL DEFAULT 'todo',
+        priority TEXT NOT NULL DEFAULT 'normal',
+        parent_id TEXT REFERENCES tasks(id) ON DELETE CASCADE,
+        project_id TEXT,
+        assignee TEXT,
+        tags JSONB NOT NULL DEFAULT '[]',
+        labels JSONB NOT NULL DEFAULT '{}',
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL,
+        due_date TIMESTAMPTZ,
+        completed_at TIMESTAMPTZ
+    )
+    "#,
+    "ALTER TABLE tasks ADD COLUMN IF NOT EXISTS completed_at TIMESTAMPTZ",
+    "CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status)",
+    "CREATE INDEX IF NOT EXISTS idx_tasks_priority ON tasks(priority)",
+    "CREATE INDEX IF NOT EXISTS idx_tasks_parent ON tasks(parent_id)",
+    "CREATE INDEX IF NOT EXISTS idx_tasks_created ON tasks(created_at)",
+    "CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed_at)",
+    r#"
+    CREATE TABLE IF NOT EXISTS codices (
+        id TEXT PRIMARY KEY,
+        template_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        content JSONB NOT NULL,
+        metadata JSONB NOT NULL,
+        crdt_state TEXT,
+        version INTEGER NOT NULL DEFAULT 1,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL,
+        created_by TEXT,
+        project_id TEXT,
+        parent_id TEXT REFERENCES codices(id) ON DELETE SET NULL
+    )
+    "#,
+    "CREATE INDEX IF NOT EXISTS idx_codices_template_id ON codices(template_id)",
+    "CREATE INDEX IF NOT EXISTS idx_codices_project_id ON codices(project_id)",
+    "CREATE INDEX IF NOT EXISTS idx_codices_parent_id ON codices(parent_id)",
+    "CREATE INDEX IF NOT EXISTS idx_codices_created_at ON codices(created_at)",
+    r#"
+    CREATE TABLE IF NOT EXISTS migrations (
+        version BIGINT PRIMARY KEY,
+        name TEXT NOT NULL,
+        checksum TEXT NOT NULL,
+        executed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+        execution_time_ms BIGINT NOT NULL
+    )
+    "#,
+];
+
+/// Columns selected for Codex rows, with JSONB rendered as text
+const CODEX_COLUMNS: &str =
+    "id, title, template_id, content::text AS content, metadata::text AS metadata, project_id, parent_id, created_at, updated_at";
+
+/// Postgres-backed task and Codex index storage
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    /// Connect to Postgres and create the base schema if needed
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(DEFAULT_MAX_CONNECTIONS)
+            .acquire_timeout(Duration::from_secs(30))
+            .connect(url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to Postgres: {}", e))?;
+
+        let storage = Self { pool };
+        storage.init_schema().await?;
+        info!("Connected to Postgres storage backend");
+        Ok(storage)
+    }
+
+    /// Wrap an existing Postgres pool
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create the base tables and indexes
+    pub async fn init_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Apply pending migrations written for Postgres (shared or `.postgres.sql`)
+    pub async fn run_migrations(&self, migrations_dir: &Path) -> Result<()> {
+        let migrations = MigrationManager::load_migrations_for(migrations_dir, SqlDialect::Postgres).await?;
+
+        let applied: Vec<(i64, String)> = sqlx::query("SELECT version, checksum FROM migrations")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| (row.get("version"), row.get("checksum")))
+            .collect();
+
+        let mut pending: Vec<_> = migrations.values()
+            .filter(|m| !applied.iter().any(|(version, _)| *version == m.version))
+            .collect();
+        pending.sort_by_key(|m| m.version);
+
+        for (version, checksum) in &applied {
+            if migrations.get(version).is_some_and(|m| &m.checksum != checksum) {
+                warn!("Postgres migration {} has changed since it was applied", version);
+            }
+        }
+
+        for migration in pending {
+            let start = std::time::Instant::now();
+            let mut tx = self.pool.begin().await?;
+            sqlx::raw_sql(&migration.up_sql).execute(&mut *tx).await
+                .map_err(|e| anyhow::anyhow!("Postgres migration {} failed: {}", migration.version, e))?;
+            sqlx::query("INSERT INTO migrations (version, name, checksum, execution_time_ms) VALUES ($1, $2, $3, $4)")
+                .bind(migration.version)
+                .bind(&migration.name)
+                .bind(&migration.checksum)
+                .bind(start.elapsed().as_millis() as i64)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            info!("Applied Postgres migration {} ({})", migration.version, migration.name);
+        }
+
+        Ok(())
+    }
+
+    /// Get the underlying connection pool
+    pub fn get_pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    fn row_to_codex(row: &PgRow) -> serde_json::Value {
+        let content: String = row.get("content");
+        let metadata: String = row.get("metadata");
+        let created_at: DateTime<Utc> = row.get("created_at");
+        let updated_at: DateTime<Utc> = row.get("updated_at");
+        let parent_id: Option<String> = row.get("parent_id");
+
+        let mut codex = serde_json::json!({
+            "id": row.get::<String, _>("id"),
+            "title": row.get::<String, _>("title"),
+            "template_id": row.get::<String, _>("template_id"),
+            "content": serde_json::from_str::<serde_json::Value>(&content)
+                .unwrap_or(serde_json::json!({"fields": {}})),
+            "metadata": serde_json::from_str::<serde_json::Value>(&metadata)
+                .unwrap_or(serde_json::json!({})),
+            "project_id": row.get::<Option<String>, _>("project_id"),
+            "created_at": created_at.to_rfc3339(),
+            "updated_at": updated_at.to_rfc3339(),
+        });
+
+        if let Some(parent) = parent_id {
+            codex["parent_id"] = serde_json::json!(parent);
+        }
+
+        codex
+    }
+}
+
+#[async_trait]
+impl StorageBackend for PostgresStorage {
+    fn dialect(&self) -> SqlDialect {
+        SqlDialect::Postgres
+    }
+
+    async fn create_task(&self, input: &TaskInput) -> Result<String> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now();
+        let root_id = Uuid::new_v4().to_string();
+
+        // Walk the task tree iteratively; parents are inserted before children
+        let mut stack = vec![(input, root_id.clone(), input.parent_id.clone(), 0usize)];
+        while let Some((task, id, parent_id, depth)) = stack.pop() {
+            if depth > MAX_TASK_DEPTH {
+                return Err(anyhow::anyhow!(crate::BinderyError::ExecutionError(format!(
+                    "Task recursion depth exceeded: maximum depth is {}, found depth {}",
+                    MAX_TASK_DEPTH, depth
+                ))));
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO tasks (id, title, description, priority, parent_id, project_id, tags, labels, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7::jsonb, $8::jsonb, $9, $9)
+                "#,
+            )
+            .bind(&id)
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(task.priority.as_deref().unwrap_or("normal"))
+            .bind(&parent_id)
+            .bind(&task.project_id)
+            .bind(serde_json::to_string(&task.tags)?)
+            .bind(serde_json::to_string(&task.labels)?)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to insert task '{}': {}", task.title, e))?;
+
+            for subtask in task.subtasks.iter().rev() {
+                stack.push((subtask, Uuid::new_v4().to_string(), Some(id.clone()), depth + 1));
+            }
+        }
+
+        tx.commit().await?;
+        Ok(root_id)
+    }
+
+    async fn list_tasks(&self, limit: Option<i32>, parent_id: Option<&str>) -> Result<Vec<TaskSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                t.id, t.title, t.status, t.priority, t.created_at, t.updated_at, t.parent_id, t.tags::text AS tags,
+                (SELECT COUNT(*) FROM tasks c WHERE c.parent_id = t.id) AS child_count
+            FROM tasks t
+            WHERE t.parent_id IS NOT DISTINCT FROM $1
+            ORDER BY t.created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(parent_id)
+        .bind(limit.map(i64::from))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter()
+            .map(|row| TaskSummary {
+                id: row.get("id"),
+                title: row.get("title"),
+                status: row.get("status"),
+                priority: row.get("priority"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+                parent_id: row.get("parent_id"),
+                child_count: row.get("child_count"),
+                tags: row.get("tags"),
+            })
+            .collect())
+    }
+
+    async fn update_task(&self, task_id: &str, title: Option<&str>, status: Option<&str>) -> Result<bool> {
+        if title.is_none() && status.is_none() {
+            return Ok(false);
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks SET title = COALESCE($1, title), status = COALESCE($2, status),
+                completed_at = CASE
+                    WHEN $2 IS NULL THEN completed_at
+                    WHEN $2 IN ('completed', 'done', 'finished') THEN COALESCE(completed_at, $3)
+                END,
+                updated_at = $3
+            WHERE id = $4
+            "#,
+        )
+        .bind(title)
+        .bind(status)
+        .bind(Utc::now())
+        .bind(task_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_task(&self, task_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM tasks WHERE id = $1")
+            .bind(task_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn create_codex(&self, id: &str, title: &str, template_id: &str, metadata: &serde_json::Value) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO codices (id, title, template_id, content, metadata, version, created_at, updated_at, project_id, parent_id)
+            VALUES ($1, $2, $3, $4::jsonb, $5::jsonb, 1, $6, $6, $7, $8)
+            "#,
+        )
+        .bind(id)
+        .bind(title)
+        .bind(template_id)
+        .bind(serde_json::json!({"fields": {}}).to_string())
+        .bind(serde_json::to_string(metadata)?)
+        .bind(now)
+        .bind(metadata.get("project_id").and_then(|v| v.as_str()))
+        .bind(metadata.get("parent_id").and_then(|v| v.as_str()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create codex: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn get_codex(&self, id: &str) -> Result<Option<serde_json::Value>> {
+        let row = sqlx::query(&format!("SELECT {} FROM codices WHERE id = $1", CODEX_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get codex: {}", e))?;
+
+        Ok(row.as_ref().map(Self::row_to_codex))
+    }
+
+    async fn update_codex(&self, id: &str, codex: &serde_json::Value) -> Result<()> {
+        let metadata = codex.get("metadata").cloned().unwrap_or(serde_json::json!({}));
+        let content = codex.get("content").cloned().unwrap_or(serde_json::json!({"fields": {}}));
+
+        sqlx::query(
+            r#"
+            UPDATE codices
+            SET title = $1, template_id = $2, content = $3::jsonb, metadata = $4::jsonb, updated_at = $5, project_id = $6, parent_id = $7
+            WHERE id = $8
+            "#,
+        )
+        .bind(codex.get("title").and_then(|v| v.as_str()).unwrap_or("Untitled"))
+        .bind(codex.get("template_id").and_then(|v| v.as_str()).unwrap_or("default"))
+        .bind(serde_json::to_string(&content)?)
+        .bind(serde_json::to_string(&metadata)?)
+        .bind(Utc::now())
+        .bind(metadata.get("project_id").and_then(|v| v.as_str()))
+        .bind(metadata.get("parent_id").and_then(|v| v.as_str()))
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update codex: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn list_children(&self, parent_id: &str) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM codices WHERE parent_id = $1 ORDER BY created_at ASC",
+            CODEX_COLUMNS
+        ))
+        .bind(parent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list child codices: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_codex).collect())
+    }
+
+    async fn list_codices(&self) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!("SELECT {} FROM codices ORDER BY created_at DESC", CODEX_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list codices: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_codex).collect())
+    }
+
+    async fn delete_codex(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM codices WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete codex: {}", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn health_check(&self) -> bool {
+        sqlx::query("SELECT 1").fetch_one(&self.pool).await.is_ok()
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
@@ -0,0 +1,335 @@
+//! Persistent slow query log for diagnostics
+//!
+//! Slow queries are kept in memory (see
+//! [`Database::get_query_performance_metrics`]) and, unless
+//! `QueryMetricsConfig::persist_slow_queries` is off, written to the
+//! `slow_query_log` table so they survive restarts. Each entry records the
+//! names of the tracing spans it ran under and, for a sample of entries,
+//! its bind parameters. Text parameters are redacted to their length
+//! unless they are ids or timestamps, so the log is safe to hand over.
+//!
+//! Entries older than `slow_query_retention_days` are pruned as new ones
+//! arrive. [`Database::top_slow_queries`] summarises a time window by
+//! statement, for support bundles.
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use tracing::debug;
+
+use super::{normalize_sql, Database, QueryType, SlowQueryInfo};
+
+/// A statement's bind parameter, as recorded in the slow query log
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindParam {
+    Null,
+    Int(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl BindParam {
+    /// The parameter as logged: numbers, ids and timestamps as they are,
+    /// other text only by length
+    pub fn redacted(&self) -> String {
+        match self {
+            BindParam::Null => "NULL".to_string(),
+            BindParam::Int(value) => value.to_string(),
+            BindParam::Real(value) => value.to_string(),
+            BindParam::Text(value) if is_identifier(value) => format!("'{}'", value),
+            BindParam::Text(value) => format!("<text, {} chars>", value.chars().count()),
+        }
+    }
+}
+
+/// UUIDs, dates and timestamps identify rows without revealing content
+fn is_identifier(value: &str) -> bool {
+    uuid::Uuid::parse_str(value).is_ok()
+        || DateTime::parse_from_rfc3339(value).is_ok()
+        || chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+impl From<&str> for BindParam {
+    fn from(value: &str) -> Self {
+        BindParam::Text(value.to_string())
+    }
+}
+
+impl From<&String> for BindParam {
+    fn from(value: &String) -> Self {
+        BindParam::Text(value.clone())
+    }
+}
+
+impl From<i64> for BindParam {
+    fn from(value: i64) -> Self {
+        BindParam::Int(value)
+    }
+}
+
+impl From<f64> for BindParam {
+    fn from(value: f64) -> Self {
+        BindParam::Real(value)
+    }
+}
+
+impl<T: Into<BindParam>> From<Option<T>> for BindParam {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(BindParam::Null, Into::into)
+    }
+}
+
+impl From<&Option<String>> for BindParam {
+    fn from(value: &Option<String>) -> Self {
+        value.as_ref().into()
+    }
+}
+
+/// Names of the spans the current code runs under, outermost first
+pub(crate) fn span_stack() -> Vec<String> {
+    use tracing_subscriber::registry::LookupSpan;
+
+    let current = tracing::Span::current();
+    let from_registry = current.with_subscriber(|(id, dispatch)| {
+        let registry = dispatch.downcast_ref::<tracing_subscriber::Registry>()?;
+        let span = registry.span(id)?;
+        let mut names: Vec<String> = span.scope().map(|span| span.name().to_string()).collect();
+        names.reverse();
+        Some(names)
+    });
+    match from_registry.flatten() {
+        Some(names) => names,
+        // Without a registry only the innermost span is known
+        None => current.metadata().map(|m| vec![m.name().to_string()]).unwrap_or_default(),
+    }
+}
+
+/// Slow executions of one statement over a time window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowQuerySummary {
+    /// The statement with whitespace normalised
+    pub statement: String,
+    pub query_type: QueryType,
+    pub occurrences: u64,
+    pub total_duration_ms: u64,
+    pub max_duration_ms: u64,
+    pub average_duration_ms: f64,
+    pub last_seen: DateTime<Utc>,
+    /// The slowest execution in the window
+    pub slowest: SlowQueryInfo,
+}
+
+/// Timestamps stored with a fixed width, so they compare as text
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(row: &sqlx::sqlite::SqliteRow, column: &str) -> Option<T> {
+    row.get::<Option<String>, _>(column).and_then(|value| serde_json::from_str(&value).ok())
+}
+
+fn slow_query_from_row(row: &sqlx::sqlite::SqliteRow) -> SlowQueryInfo {
+    SlowQueryInfo {
+        query: row.get("query"),
+        duration_ms: row.get::<i64, _>("duration_ms") as u64,
+        timestamp: DateTime::parse_from_rfc3339(&row.get::<String, _>("recorded_at"))
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_default(),
+        affected_rows: row.get("affected_rows"),
+        query_type: parse_json(row, "query_type").unwrap_or(QueryType::Other("UNKNOWN".to_string())),
+        query_plan: parse_json(row, "query_plan"),
+        bind_parameters: parse_json(row, "bind_parameters"),
+        span_stack: parse_json(row, "span_stack").unwrap_or_default(),
+    }
+}
+
+impl Database {
+    /// Create the slow query log table
+    pub(crate) async fn init_slow_query_log(pool: &Pool<Sqlite>) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS slow_query_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recorded_at TEXT NOT NULL,
+                statement TEXT NOT NULL,
+                query TEXT NOT NULL,
+                query_type TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                affected_rows INTEGER NOT NULL,
+                query_plan TEXT,
+                bind_parameters TEXT,
+                span_stack TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_slow_query_log_recorded ON slow_query_log(recorded_at)")
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Write a slow query to the log, pruning entries past retention
+    ///
+    /// Runs on the writer pool directly so it isn't itself measured.
+    pub(crate) async fn persist_slow_query(&self, info: &SlowQueryInfo) {
+        let result = async {
+            sqlx::query(
+                r#"
+                INSERT INTO slow_query_log
+                    (recorded_at, statement, query, query_type, duration_ms, affected_rows, query_plan, bind_parameters, span_stack)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(timestamp(info.timestamp))
+            .bind(normalize_sql(&info.query))
+            .bind(&info.query)
+            .bind(serde_json::to_string(&info.query_type)?)
+            .bind(info.duration_ms as i64)
+            .bind(info.affected_rows)
+            .bind(info.query_plan.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(info.bind_parameters.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(serde_json::to_string(&info.span_stack)?)
+            .execute(&self.pool)
+            .await?;
+            self.prune_slow_query_log().await?;
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            debug!("Could not persist slow query: {}", e);
+        }
+    }
+
+    /// Delete logged slow queries older than the retention period,
+    /// returning how many were removed
+    pub async fn prune_slow_query_log(&self) -> Result<u64> {
+        let days = self.query_metrics_config.slow_query_retention_days;
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(days));
+        let result = sqlx::query("DELETE FROM slow_query_log WHERE recorded_at < ?")
+            .bind(timestamp(cutoff))
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// The statements with the most time spent in slow executions between
+    /// `since` and `until`, with the slowest execution of each
+    pub async fn top_slow_queries(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<SlowQuerySummary>> {
+        let (since, until) = (timestamp(since), timestamp(until));
+        let rows = sqlx::query(
+            r#"
+            SELECT statement,
+                COUNT(*) AS occurrences,
+                SUM(duration_ms) AS total_ms,
+                MAX(duration_ms) AS max_ms,
+                AVG(duration_ms) AS average_ms,
+                MAX(recorded_at) AS last_seen
+            FROM slow_query_log
+            WHERE recorded_at >= ? AND recorded_at < ?
+            GROUP BY statement
+            ORDER BY total_ms DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(&since)
+        .bind(&until)
+        .bind(i64::from(limit))
+        .fetch_all(&self.read_pool.get())
+        .await?;
+
+        let mut summaries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let statement: String = row.get("statement");
+            let slowest = sqlx::query(
+                r#"
+                SELECT * FROM slow_query_log
+                WHERE statement = ? AND recorded_at >= ? AND recorded_at < ?
+                ORDER BY duration_ms DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(&statement)
+            .bind(&since)
+            .bind(&until)
+            .fetch_one(&self.read_pool.get())
+            .await?;
+            let slowest = slow_query_from_row(&slowest);
+
+            summaries.push(SlowQuerySummary {
+                statement,
+                query_type: slowest.query_type.clone(),
+                occurrences: row.get::<i64, _>("occurrences") as u64,
+                total_duration_ms: row.get::<i64, _>("total_ms") as u64,
+                max_duration_ms: row.get::<i64, _>("max_ms") as u64,
+                average_duration_ms: row.get("average_ms"),
+                last_seen: DateTime::parse_from_rfc3339(&row.get::<String, _>("last_seen"))
+                    .map(|at| at.with_timezone(&Utc))
+                    .unwrap_or_default(),
+                slowest,
+            });
+        }
+        Ok(summaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::QueryMetricsConfig;
+    use tracing::Instrument;
+
+    #[test]
+    fn test_text_parameters_are_redacted() {
+        let id = uuid::Uuid::new_v4().to_string();
+        assert_eq!(BindParam::from(id.as_str()).redacted(), format!("'{}'", id));
+        assert_eq!(BindParam::from("2026-10-18").redacted(), "'2026-10-18'");
+        assert_eq!(BindParam::from("my secret plans").redacted(), "<text, 15 chars>");
+        assert_eq!(BindParam::from(None::<&str>).redacted(), "NULL");
+        assert_eq!(BindParam::from(&Some("x".to_string())).redacted(), "<text, 1 chars>");
+        assert_eq!(BindParam::from(42i64).redacted(), "42");
+    }
+
+    #[tokio::test]
+    async fn test_slow_queries_are_persisted_and_summarised() {
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("slow.db");
+        let mut database = Database::new(&path).await.unwrap();
+        database.init_schema().await.unwrap();
+        database.configure_query_metrics(QueryMetricsConfig {
+            slow_query_threshold_ms: 0,
+            bind_parameter_sample_rate: 1.0,
+            ..Default::default()
+        });
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let since = Utc::now() - chrono::Duration::seconds(1);
+        for _ in 0..3 {
+            database.get_task(&id).instrument(tracing::info_span!("support_case")).await.unwrap();
+        }
+        database.close().await;
+
+        // Survives a restart
+        let database = Database::new(&path).await.unwrap();
+        let top = database.top_slow_queries(since, Utc::now(), 10).await.unwrap();
+        let summary = top.iter()
+            .find(|s| s.statement.contains("FROM tasks WHERE id = ?"))
+            .expect("get_task was logged");
+        assert_eq!(summary.occurrences, 3);
+        assert!(matches!(summary.query_type, QueryType::Select));
+        assert_eq!(summary.slowest.bind_parameters, Some(vec![format!("'{}'", id)]));
+        assert_eq!(summary.slowest.span_stack.first().map(String::as_str), Some("support_case"));
+
+        assert!(database.top_slow_queries(Utc::now(), Utc::now(), 10).await.unwrap().is_empty());
+        assert_eq!(database.prune_slow_query_log().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! Storage backend abstraction for task and Codex index persistence
+//!
+//! SQLite (via [`Database`]) is the default backend. A Postgres backend for
+//! team deployments is available behind the `postgres` feature. Callers that
+//! only need task/Codex persistence should depend on [`StorageBackend`] rather
+//! than on a concrete database type.
+
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{Database, TaskInput, TaskSummary};
+
+/// SQL dialect spoken by a storage backend
+///
+/// Also selects which migration files apply: `V{n}__{name}.sql` runs on every
+/// dialect, while `V{n}__{name}.{dialect}.sql` only runs on that dialect and
+/// takes precedence over a shared file with the same version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlDialect {
+    #[default]
+    Sqlite,
+    Postgres,
+}
+
+impl SqlDialect {
+    /// Short name used in configuration and migration file suffixes
+    pub fn name(&self) -> &'static str {
+        match self {
+            SqlDialect::Sqlite => "sqlite",
+            SqlDialect::Postgres => "postgres",
+        }
+    }
+
+    /// Parse a dialect from its short name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sqlite" => Some(SqlDialect::Sqlite),
+            "postgres" | "postgresql" => Some(SqlDialect::Postgres),
+            _ => None,
+        }
+    }
+}
+
+/// Storage backend configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Backend dialect (SQLite unless configured otherwise)
+    pub dialect: SqlDialect,
+
+    /// Connection URL for server backends (e.g. `postgres://user@host/db`);
+    /// SQLite uses `BinderyConfig::database_path` instead
+    pub url: Option<String>,
+}
+
+impl StorageConfig {
+    /// Postgres storage at the given connection URL
+    pub fn postgres(url: impl Into<String>) -> Self {
+        Self {
+            dialect: SqlDialect::Postgres,
+            url: Some(url.into()),
+        }
+    }
+
+    /// Validate the storage configuration
+    pub fn validate(&self) -> Result<(), crate::BinderyError> {
+        match self.dialect {
+            SqlDialect::Sqlite => Ok(()),
+            SqlDialect::Postgres => {
+                if !cfg!(feature = "postgres") {
+                    return Err(crate::BinderyError::ConfigurationError(
+                        "Postgres storage requires building with the `postgres` feature".to_string()
+                    ));
+                }
+
+                match self.url.as_deref() {
+                    Some(url) if url.starts_with("postgres://") || url.starts_with("postgresql://") => Ok(()),
+                    Some(_) => Err(crate::BinderyError::ConfigurationError(
+                        "Postgres storage url must start with postgres:// or postgresql://".to_string()
+                    )),
+                    None => Err(crate::BinderyError::ConfigurationError(
+                        "Postgres storage requires a connection url".to_string()
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Persistence boundary for the task and Codex index
+///
+/// Codices are exchanged as the same JSON shape [`Database::get_codex`]
+/// returns, so callers can switch backends without changing their data model.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Dialect spoken by this backend
+    fn dialect(&self) -> SqlDialect;
+
+    /// Create a task (and its subtasks) and return the root task ID
+    async fn create_task(&self, input: &TaskInput) -> Result<String>;
+
+    /// List tasks under `parent_id`, or root tasks when `None`
+    async fn list_tasks(&self, limit: Option<i32>, parent_id: Option<&str>) -> Result<Vec<TaskSummary>>;
+
+    /// Update a task's title and/or status; returns whether a row changed
+    async fn update_task(&self, task_id: &str, title: Option<&str>, status: Option<&str>) -> Result<bool>;
+
+    /// Delete a task; returns whether it existed
+    async fn delete_task(&self, task_id: &str) -> Result<bool>;
+
+    /// Create a Codex index entry
+    async fn create_codex(&self, id: &str, title: &str, template_id: &str, metadata: &serde_json::Value) -> Result<()>;
+
+    /// Get a Codex by ID
+    async fn get_codex(&self, id: &str) -> Result<Option<serde_json::Value>>;
+
+    /// Replace a Codex's title, template, content and metadata
+    async fn update_codex(&self, id: &str, codex: &serde_json::Value) -> Result<()>;
+
+    /// List Codices nested under `parent_id`
+    async fn list_children(&self, parent_id: &str) -> Result<Vec<serde_json::Value>>;
+
+    /// List all Codices, newest first
+    async fn list_codices(&self) -> Result<Vec<serde_json::Value>>;
+
+    /// Delete a Codex; returns whether it existed
+    async fn delete_codex(&self, id: &str) -> Result<bool>;
+
+    /// Whether the backend can currently serve queries
+    async fn health_check(&self) -> bool;
+
+    /// Close all connections held by the backend
+    async fn close(&self);
+}
+
+#[async_trait]
+impl StorageBackend for Database {
+    fn dialect(&self) -> SqlDialect {
+        SqlDialect::Sqlite
+    }
+
+    async fn create_task(&self, input: &TaskInput) -> Result<String> {
+        Database::create_task(self, input).await
+    }
+
+    async fn list_tasks(&self, limit: Option<i32>, parent_id: Option<&str>) -> Result<Vec<TaskSummary>> {
+        Database::list_tasks(self, limit, parent_id).await
+    }
+
+    async fn update_task(&self, task_id: &str, title: Option<&str>, status: Option<&str>) -> Result<bool> {
+        Database::update_task(self, task_id, title, status).await
+    }
+
+    async fn delete_task(&self, task_id: &str) -> Result<bool> {
+        Database::delete_task(self, task_id).await
+    }
+
+    async fn create_codex(&self, id: &str, title: &str, template_id: &str, metadata: &serde_json::Value) -> Result<()> {
+        Database::create_codex(self, id, title, template_id, metadata).await
+    }
+
+    async fn get_codex(&self, id: &str) -> Result<Option<serde_json::Value>> {
+        Database::get_codex(self, id).await
+    }
+
+    async fn update_codex(&self, id: &str, codex: &serde_json::Value) -> Result<()> {
+        Database::update_codex(self, id, codex).await
+    }
+
+    async fn list_children(&self, parent_id: &str) -> Result<Vec<serde_json::Value>> {
+        Database::list_children(self, parent_id).await
+    }
+
+    async fn list_codices(&self) -> Result<Vec<serde_json::Value>> {
+        Database::list_codices(self).await
+    }
+
+    async fn delete_codex(&self, id: &str) -> Result<bool> {
+        Database::delete_codex(self, id).await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.is_pool_healthy().await
+    }
+
+    async fn close(&self) {
+        Database::close(self).await
+    }
+}
+
+/// Open the storage backend selected by `config`
+///
+/// SQLite opens (and migrates) the database at `sqlite_path`; Postgres
+/// connects to `config.url` and applies the Postgres migrations in
+/// `migrations_dir`.
+pub async fn open_storage(
+    config: &StorageConfig,
+    sqlite_path: &Path,
+    #[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+    migrations_dir: &Path,
+) -> Result<Arc<dyn StorageBackend>> {
+    config.validate()?;
+
+    match config.dialect {
+        SqlDialect::Sqlite => Ok(Arc::new(Database::new(sqlite_path).await?)),
+        #[cfg(feature = "postgres")]
+        SqlDialect::Postgres => {
+            let url = config.url.as_deref().unwrap_or_default();
+            let storage = super::postgres::PostgresStorage::connect(url).await?;
+            storage.run_migrations(migrations_dir).await?;
+            Ok(Arc::new(storage))
+        }
+        #[cfg(not(feature = "postgres"))]
+        SqlDialect::Postgres => unreachable!("rejected by StorageConfig::validate"),
+    }
+}
@@ -0,0 +1,522 @@
+//! Adaptive sizing of the read-only connection pool
+//!
+//! Each tuning pass samples the read pool's metrics since the previous pass.
+//! When the pool is nearly full, acquisitions are slow or some failed, it
+//! grows by half; when it sits mostly unused it shrinks by a quarter, always
+//! within [`PoolAutoTuneConfig`]'s bounds. sqlx pools can't be resized, so a
+//! resize opens a new pool and swaps it in; queries already running on the
+//! old one finish there, and it closes once they're done.
+//!
+//! A pass also looks at the workload and logs a warning when it suggests a
+//! journal mode or pragma change, such as WAL when readers and the writer
+//! keep blocking each other. These, and any bound the tuner runs into, are
+//! returned as recommendations and included in
+//! [`Database::get_pool_health_info`].
+//!
+//! Passes run in the background when `auto_tune.enabled` is set, or on
+//! demand through [`Database::tune_pool`].
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info, warn};
+
+use super::{connect_read_pool, Database, DatabasePoolConfig, PoolCounters};
+
+/// WAL size above which checkpoints are evidently falling behind
+const WAL_WARNING_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Bounds and thresholds for read pool auto-tuning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoolAutoTuneConfig {
+    /// Run tuning passes in the background
+    pub enabled: bool,
+    /// Smallest read pool the tuner shrinks to
+    pub min_connections: u32,
+    /// Largest read pool the tuner grows to
+    pub max_connections: u32,
+    /// Grow when at least this share of the pool is open (percent)
+    pub grow_utilization: f64,
+    /// Shrink when at most this share of the pool is open (percent)
+    pub shrink_utilization: f64,
+    /// Grow when acquisitions average longer than this
+    pub max_acquisition_time_ms: f64,
+    /// Seconds between background tuning passes
+    pub interval_seconds: u64,
+}
+
+impl Default for PoolAutoTuneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_connections: 2,
+            max_connections: 32,
+            grow_utilization: 90.0,
+            shrink_utilization: 30.0,
+            max_acquisition_time_ms: 50.0,
+            interval_seconds: 60,
+        }
+    }
+}
+
+impl PoolAutoTuneConfig {
+    pub fn validate(&self) -> Result<(), crate::BinderyError> {
+        let invalid = |message: &str| Err(crate::BinderyError::ConfigurationError(format!("auto_tune.{}", message)));
+
+        if self.min_connections == 0 {
+            return invalid("min_connections must be greater than 0");
+        }
+        if self.min_connections > self.max_connections {
+            return invalid("min_connections cannot be greater than max_connections");
+        }
+        if self.max_connections > 1000 {
+            return invalid("max_connections should not exceed 1000 for performance reasons");
+        }
+        if !(0.0..=100.0).contains(&self.shrink_utilization) || !(0.0..=100.0).contains(&self.grow_utilization) {
+            return invalid("grow_utilization and shrink_utilization must be percentages");
+        }
+        if self.shrink_utilization >= self.grow_utilization {
+            return invalid("shrink_utilization must be below grow_utilization");
+        }
+        if !(self.max_acquisition_time_ms.is_finite() && self.max_acquisition_time_ms > 0.0) {
+            return invalid("max_acquisition_time_ms must be a positive number");
+        }
+        if self.interval_seconds == 0 {
+            return invalid("interval_seconds must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// The size to open the read pool at, given the configured `max_connections`
+    pub fn initial_size(&self, configured: u32) -> u32 {
+        if self.enabled {
+            configured.clamp(self.min_connections, self.max_connections)
+        } else {
+            configured
+        }
+    }
+
+    /// What the tuner would do with a pool in the state `sample` describes
+    pub fn plan(&self, sample: &PoolSample) -> TuningPlan {
+        let size = sample.size;
+        if size < self.min_connections {
+            return TuningPlan::new(TuningAction::Grow, self.min_connections, "below auto_tune.min_connections");
+        }
+        if size > self.max_connections {
+            return TuningPlan::new(TuningAction::Shrink, self.max_connections, "above auto_tune.max_connections");
+        }
+
+        let slow = sample.acquisitions > 0 && sample.average_acquisition_time_ms > self.max_acquisition_time_ms;
+        if sample.failures > 0 || slow || sample.utilization >= self.grow_utilization {
+            let reason = if sample.failures > 0 {
+                format!("{} acquisitions failed", sample.failures)
+            } else if slow {
+                format!("acquisitions averaged {:.1}ms", sample.average_acquisition_time_ms)
+            } else {
+                format!("the pool is {:.0}% open", sample.utilization)
+            };
+            let target = (size + (size / 2).max(1)).min(self.max_connections);
+            return TuningPlan::new(TuningAction::Grow, target, reason);
+        }
+
+        let busy = sample.acquisitions > 0 && sample.average_acquisition_time_ms > self.max_acquisition_time_ms / 2.0;
+        if sample.utilization <= self.shrink_utilization && !busy {
+            let target = size.saturating_sub((size / 4).max(1)).max(self.min_connections);
+            return TuningPlan::new(TuningAction::Shrink, target, format!("the pool is {:.0}% open", sample.utilization));
+        }
+
+        TuningPlan::new(TuningAction::Hold, size, "within thresholds")
+    }
+}
+
+/// Read pool activity since the previous tuning pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolSample {
+    /// Connections the pool may open
+    pub size: u32,
+    /// Open connections as a percentage of `size`
+    pub utilization: f64,
+    pub average_acquisition_time_ms: f64,
+    pub acquisitions: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TuningAction {
+    Grow,
+    Shrink,
+    Hold,
+}
+
+/// The direction the tuner wants to move in and the size it can reach
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningPlan {
+    pub action: TuningAction,
+    /// Target size, within the configured bounds
+    pub size: u32,
+    pub reason: String,
+}
+
+impl TuningPlan {
+    fn new(action: TuningAction, size: u32, reason: impl Into<String>) -> Self {
+        Self { action, size, reason: reason.into() }
+    }
+}
+
+/// Outcome of one tuning pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolTuningReport {
+    /// What was done; `Hold` when the pool was already at a bound
+    pub action: TuningAction,
+    pub previous_size: u32,
+    pub size: u32,
+    pub reason: String,
+    pub sample: PoolSample,
+    /// Suggested configuration and pragma changes
+    pub recommendations: Vec<String>,
+    pub tuned_at: DateTime<Utc>,
+}
+
+/// The read-only pool, which tuning may replace with a larger or smaller one
+pub(crate) struct ReadPool {
+    pool: RwLock<Pool<Sqlite>>,
+    size: AtomicU32,
+    /// Options new pools connect with; `None` when reads share the writer pool
+    options: Option<SqliteConnectOptions>,
+}
+
+impl ReadPool {
+    pub(crate) fn new(pool: Pool<Sqlite>, size: u32, options: Option<SqliteConnectOptions>) -> Self {
+        Self { pool: RwLock::new(pool), size: AtomicU32::new(size), options }
+    }
+
+    /// The current pool
+    pub(crate) fn get(&self) -> Pool<Sqlite> {
+        self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Connections the current pool may open
+    pub(crate) fn size(&self) -> u32 {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    async fn resize(&self, config: &DatabasePoolConfig, size: u32) -> Result<()> {
+        let Some(options) = &self.options else {
+            return Ok(());
+        };
+        let pool = connect_read_pool(options.clone(), config, size).await?;
+        // The old pool closes when the last query using it lets go
+        let _old = std::mem::replace(&mut *self.pool.write().unwrap_or_else(|e| e.into_inner()), pool);
+        self.size.store(size, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Counter values at the previous tuning pass
+#[derive(Debug, Default)]
+struct Baseline {
+    reader_acquired: u64,
+    reader_failures: u64,
+    writer_acquired: u64,
+    busy_errors: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct PoolTuner {
+    baseline: tokio::sync::Mutex<Baseline>,
+    handle: tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+    last_report: std::sync::Mutex<Option<PoolTuningReport>>,
+}
+
+/// What a tuning pass needs, detached from the `Database` so it can run in
+/// the background
+#[derive(Clone)]
+struct Tuning {
+    writer: Pool<Sqlite>,
+    read_pool: Arc<ReadPool>,
+    tuner: Arc<PoolTuner>,
+    reader_counters: Arc<PoolCounters>,
+    writer_counters: Arc<PoolCounters>,
+    deadlocks_detected: Arc<AtomicU64>,
+    config: DatabasePoolConfig,
+    database_path: Option<PathBuf>,
+}
+
+/// Mean of the last `count` acquisition times
+async fn recent_average_ms(counters: &PoolCounters, count: u64) -> f64 {
+    let times = counters.acquisition_times.lock().await;
+    let window = &times[times.len().saturating_sub(count as usize)..];
+    if window.is_empty() {
+        0.0
+    } else {
+        window.iter().map(|d| d.as_secs_f64() * 1000.0).sum::<f64>() / window.len() as f64
+    }
+}
+
+impl Tuning {
+    async fn run(&self) -> Result<PoolTuningReport> {
+        let mut baseline = self.tuner.baseline.lock().await;
+        let reader_acquired = self.reader_counters.total_acquired.load(Ordering::Relaxed);
+        let reader_failures = self.reader_counters.total_acquisition_failures.load(Ordering::Relaxed);
+        let writer_acquired = self.writer_counters.total_acquired.load(Ordering::Relaxed);
+        let busy_errors = self.deadlocks_detected.load(Ordering::Relaxed);
+
+        let acquisitions = reader_acquired.saturating_sub(baseline.reader_acquired);
+        let size = self.read_pool.size();
+        let sample = PoolSample {
+            size,
+            utilization: self.read_pool.get().size() as f64 / size.max(1) as f64 * 100.0,
+            average_acquisition_time_ms: recent_average_ms(&self.reader_counters, acquisitions).await,
+            acquisitions,
+            failures: reader_failures.saturating_sub(baseline.reader_failures),
+        };
+
+        let auto_tune = &self.config.auto_tune;
+        let plan = if self.read_pool.options.is_some() {
+            auto_tune.plan(&sample)
+        } else {
+            TuningPlan::new(TuningAction::Hold, size, "reads share the writer pool")
+        };
+
+        let mut recommendations = Vec::new();
+        if plan.action == TuningAction::Grow && plan.size == size {
+            recommendations.push(format!(
+                "The read pool is at auto_tune.max_connections ({}) but {}; raise the limit or reduce read load",
+                size, plan.reason
+            ));
+        }
+        let writes = writer_acquired.saturating_sub(baseline.writer_acquired);
+        let busy = busy_errors.saturating_sub(baseline.busy_errors);
+        recommendations.extend(self.workload_recommendations(&sample, writes, busy).await);
+        for recommendation in &recommendations {
+            warn!("{}", recommendation);
+        }
+
+        if plan.size != size {
+            self.read_pool.resize(&self.config, plan.size).await?;
+            info!("Resized the read pool from {} to {} connections: {}", size, plan.size, plan.reason);
+        } else {
+            debug!("Read pool stays at {} connections: {}", size, plan.reason);
+        }
+
+        *baseline = Baseline { reader_acquired, reader_failures, writer_acquired, busy_errors };
+        let report = PoolTuningReport {
+            action: if plan.size == size { TuningAction::Hold } else { plan.action },
+            previous_size: size,
+            size: plan.size,
+            reason: plan.reason,
+            sample,
+            recommendations,
+            tuned_at: Utc::now(),
+        };
+        *self.tuner.last_report.lock().unwrap_or_else(|e| e.into_inner()) = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Journal mode and pragma changes the recent workload calls for
+    async fn workload_recommendations(&self, reads: &PoolSample, writes: u64, busy: u64) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        let Some(path) = &self.database_path else {
+            return recommendations;
+        };
+
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&self.writer)
+            .await
+            .unwrap_or_default();
+        let slow_reads = reads.acquisitions > 0
+            && reads.average_acquisition_time_ms > self.config.auto_tune.max_acquisition_time_ms;
+        if !journal_mode.eq_ignore_ascii_case("wal") && (busy > 0 || slow_reads) {
+            recommendations.push(format!(
+                "The journal mode is {} and reads are waiting on writes; PRAGMA journal_mode = WAL lets them run alongside each other",
+                journal_mode
+            ));
+        } else if busy > 0 {
+            recommendations.push(format!(
+                "{} operations found the database locked; raise PRAGMA busy_timeout or keep write transactions shorter",
+                busy
+            ));
+        }
+
+        let wal_path = PathBuf::from(format!("{}-wal", path.display()));
+        if let Ok(metadata) = tokio::fs::metadata(&wal_path).await {
+            if metadata.len() > WAL_WARNING_BYTES {
+                recommendations.push(format!(
+                    "The WAL file has grown to {} MiB; lower PRAGMA wal_autocheckpoint or schedule TRUNCATE checkpoints",
+                    metadata.len() / (1024 * 1024)
+                ));
+            }
+        }
+
+        let write_time = recent_average_ms(&self.writer_counters, writes).await;
+        if writes > reads.acquisitions && write_time > self.config.auto_tune.max_acquisition_time_ms {
+            let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+                .fetch_one(&self.writer)
+                .await
+                .unwrap_or_default();
+            recommendations.push(if synchronous >= 2 {
+                format!(
+                    "Writes dominate and average {:.1}ms with PRAGMA synchronous = FULL; NORMAL is safe in WAL mode and much faster",
+                    write_time
+                )
+            } else {
+                format!(
+                    "Writes dominate and average {:.1}ms; batch them into fewer transactions",
+                    write_time
+                )
+            });
+        }
+
+        recommendations
+    }
+}
+
+impl Database {
+    fn tuning(&self) -> Tuning {
+        Tuning {
+            writer: self.pool.clone(),
+            read_pool: self.read_pool.clone(),
+            tuner: self.tuner.clone(),
+            reader_counters: self.reader_counters.clone(),
+            writer_counters: self.writer_counters.clone(),
+            deadlocks_detected: self.deadlocks_detected.clone(),
+            config: self.config.clone(),
+            database_path: self.database_path.clone(),
+        }
+    }
+
+    /// Run one tuning pass now, resizing the read pool if the activity since
+    /// the previous pass calls for it
+    pub async fn tune_pool(&self) -> Result<PoolTuningReport> {
+        self.tuning().run().await
+    }
+
+    /// The outcome of the most recent tuning pass
+    pub fn last_pool_tuning(&self) -> Option<PoolTuningReport> {
+        self.tuner.last_report.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Start background tuning passes
+    ///
+    /// Uses `interval` if given, otherwise `auto_tune.interval_seconds` when
+    /// auto-tuning is enabled. Does nothing if neither applies. Any previously
+    /// started tuning is replaced.
+    pub async fn start_pool_tuning(&self, interval: Option<Duration>) -> Result<()> {
+        let auto_tune = &self.config.auto_tune;
+        let Some(interval) = interval.or_else(|| auto_tune.enabled.then(|| Duration::from_secs(auto_tune.interval_seconds))) else {
+            debug!("Pool auto-tuning is disabled, not starting it");
+            return Ok(());
+        };
+        if interval.is_zero() {
+            return Err(anyhow::anyhow!(crate::BinderyError::ConfigurationError(
+                "pool tuning interval must be greater than 0".to_string()
+            )));
+        }
+
+        let tuning = self.tuning();
+        info!("Tuning the read pool every {:?}", interval);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                if tuning.writer.is_closed() {
+                    break;
+                }
+                if let Err(e) = tuning.run().await {
+                    warn!("Read pool tuning failed: {}", e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.tuner.handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop background tuning passes if they are running
+    pub async fn stop_pool_tuning(&self) {
+        if let Some(handle) = self.tuner.handle.lock().await.take() {
+            handle.abort();
+            info!("Stopped read pool tuning");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(size: u32, utilization: f64, average_acquisition_time_ms: f64) -> PoolSample {
+        PoolSample { size, utilization, average_acquisition_time_ms, acquisitions: 100, failures: 0 }
+    }
+
+    #[test]
+    fn test_plan_grows_and_shrinks_within_bounds() {
+        let config = PoolAutoTuneConfig { min_connections: 2, max_connections: 12, ..Default::default() };
+
+        let plan = config.plan(&sample(8, 95.0, 1.0));
+        assert_eq!((plan.action, plan.size), (TuningAction::Grow, 12));
+        let plan = config.plan(&sample(4, 50.0, 80.0));
+        assert_eq!((plan.action, plan.size), (TuningAction::Grow, 6));
+        assert!(plan.reason.contains("80.0ms"));
+        // Wants to grow but can't
+        let plan = config.plan(&sample(12, 100.0, 1.0));
+        assert_eq!((plan.action, plan.size), (TuningAction::Grow, 12));
+
+        let plan = config.plan(&sample(8, 10.0, 1.0));
+        assert_eq!((plan.action, plan.size), (TuningAction::Shrink, 6));
+        assert_eq!(config.plan(&sample(2, 0.0, 0.0)).size, 2);
+        // Idle but slow: not the time to shrink
+        assert_eq!(config.plan(&sample(8, 10.0, 40.0)).action, TuningAction::Hold);
+        assert_eq!(config.plan(&sample(20, 50.0, 1.0)).size, 12);
+
+        assert_eq!(config.initial_size(20), 20);
+        assert_eq!(PoolAutoTuneConfig { enabled: true, ..config.clone() }.initial_size(20), 12);
+        assert!(PoolAutoTuneConfig { shrink_utilization: 95.0, ..config }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tune_pool_resizes_the_read_pool() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = DatabasePoolConfig {
+            max_connections: 8,
+            min_connections: 1,
+            auto_tune: PoolAutoTuneConfig { min_connections: 2, max_connections: 16, ..Default::default() },
+            ..Default::default()
+        };
+        let database = Database::new_with_config(temp_dir.path().join("tuning.db"), config).await.unwrap();
+
+        // One connection of eight open and nothing waiting
+        let report = database.tune_pool().await.unwrap();
+        assert_eq!((report.action, report.previous_size, report.size), (TuningAction::Shrink, 8, 6));
+        assert_eq!(database.get_pool_config().max_connections, 8);
+        assert_eq!(database.get_read_pool().options().get_max_connections(), 6);
+        sqlx::query("SELECT 1").fetch_one(&database.get_read_pool()).await.unwrap();
+
+        // Slow reads since that pass
+        for _ in 0..10 {
+            database.reader_counters.record_success(Duration::from_millis(200)).await;
+        }
+        let report = database.tune_pool().await.unwrap();
+        assert_eq!((report.action, report.size), (TuningAction::Grow, 9));
+        assert!(report.sample.acquisitions >= 10);
+        assert_eq!(database.last_pool_tuning().unwrap().size, 9);
+        let health = database.get_pool_health_info().await;
+        assert_eq!((health.max_connections, health.writer_connections), (9, 1));
+        sqlx::query("SELECT 1").fetch_one(&database.get_read_pool()).await.unwrap();
+
+        database.close().await;
+    }
+}
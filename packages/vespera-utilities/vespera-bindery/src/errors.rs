@@ -1,6 +1,7 @@
 /// Error types for Vespera Bindery
 
 use std::fmt;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
@@ -19,6 +20,12 @@ pub enum BinderyError {
     /// Permission denied for operation
     PermissionDenied(String),
 
+    /// The action was parked until another user approves the named request
+    ApprovalRequired(uuid::Uuid),
+
+    /// A configured quota is used up
+    QuotaExceeded(String),
+
     /// Configuration error
     ConfigurationError(String),
 
@@ -84,6 +91,9 @@ pub enum BinderyError {
     /// Internal system errors
     InternalError(String),
 
+    /// A file operation failed; the `vespera_file_ops` error is the source
+    FileOps(FileOpsError),
+
     /// Not yet implemented functionality
     NotImplemented(String),
 }
@@ -94,6 +104,8 @@ impl fmt::Display for BinderyError {
             BinderyError::NotFound(msg) => write!(f, "Not found: {}", msg),
             BinderyError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
             BinderyError::PermissionDenied(msg) => write!(f, "Permission denied: {}", msg),
+            BinderyError::ApprovalRequired(id) => write!(f, "Approval required: waiting on request {}", id),
+            BinderyError::QuotaExceeded(msg) => write!(f, "Quota exceeded: {}", msg),
             BinderyError::ConfigurationError(msg) => write!(f, "Configuration error: {}", msg),
 
             BinderyError::TemplateNotFound(template_id) => write!(f, "Template not found: {}", template_id),
@@ -147,12 +159,53 @@ impl fmt::Display for BinderyError {
             BinderyError::UnsupportedLanguage(msg) => write!(f, "Unsupported language: {}", msg),
 
             BinderyError::InternalError(msg) => write!(f, "Internal error: {}", msg),
+            BinderyError::FileOps(err) => write!(f, "File operation error: {}", err.message),
             BinderyError::NotImplemented(msg) => write!(f, "Not implemented: {}", msg),
         }
     }
 }
 
-impl std::error::Error for BinderyError {}
+impl std::error::Error for BinderyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinderyError::FileOps(err) => err.edit_error().map(|e| e as _),
+            _ => None,
+        }
+    }
+}
+
+/// How a file operation failure is treated: its metrics category, log
+/// severity and whether it is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileOpsKind {
+    NotFound,
+    PermissionDenied,
+    InvalidInput,
+    Io,
+    Timeout,
+    Internal,
+}
+
+/// A `vespera_file_ops` error carried across the crate boundary
+///
+/// The message keeps the context, path or suggestion the error's own
+/// `Display` leaves out. The original error is only present in the process
+/// that raised it; it does not survive serialization, the kind and message
+/// do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOpsError {
+    pub kind: FileOpsKind,
+    pub message: String,
+    #[serde(skip)]
+    source: Option<Arc<vespera_file_ops::EditError>>,
+}
+
+impl FileOpsError {
+    /// The original error, unless this one was deserialized
+    pub fn edit_error(&self) -> Option<&vespera_file_ops::EditError> {
+        self.source.as_deref()
+    }
+}
 
 impl BinderyError {
     /// Log the error with appropriate level based on error type
@@ -162,6 +215,7 @@ impl BinderyError {
             BinderyError::DatabaseConnectionError(_) |
             BinderyError::DatabaseTransactionError(_) |
             BinderyError::InternalError(_) |
+            BinderyError::FileOps(FileOpsError { kind: FileOpsKind::Internal, .. }) |
             BinderyError::CrdtStateError(_) => {
                 error!(
                     error = %self,
@@ -192,8 +246,14 @@ impl BinderyError {
             BinderyError::NotFound(_) |
             BinderyError::InvalidInput(_) |
             BinderyError::PermissionDenied(_) |
+            BinderyError::ApprovalRequired(_) |
+            BinderyError::QuotaExceeded(_) |
             BinderyError::TemplateNotFound(_) |
             BinderyError::NotImplemented(_) |
+            BinderyError::FileOps(FileOpsError {
+                kind: FileOpsKind::NotFound | FileOpsKind::PermissionDenied | FileOpsKind::InvalidInput,
+                ..
+            }) |
             BinderyError::CircularReferenceError(_) => {
                 warn!(
                     error = %self,
@@ -223,6 +283,8 @@ impl BinderyError {
             BinderyError::NotFound(_) => "not_found",
             BinderyError::InvalidInput(_) => "invalid_input",
             BinderyError::PermissionDenied(_) => "permission_denied",
+            BinderyError::ApprovalRequired(_) => "approval_required",
+            BinderyError::QuotaExceeded(_) => "quota_exceeded",
             BinderyError::ConfigurationError(_) => "configuration",
 
             BinderyError::TemplateNotFound(_) |
@@ -277,6 +339,15 @@ impl BinderyError {
 
             BinderyError::InternalError(_) => "internal",
             BinderyError::NotImplemented(_) => "not_implemented",
+
+            BinderyError::FileOps(err) => match err.kind {
+                FileOpsKind::NotFound => "not_found",
+                FileOpsKind::PermissionDenied => "permission_denied",
+                FileOpsKind::InvalidInput => "invalid_input",
+                FileOpsKind::Io => "io",
+                FileOpsKind::Timeout => "execution",
+                FileOpsKind::Internal => "internal",
+            },
         }
     }
 
@@ -286,7 +357,8 @@ impl BinderyError {
             // Network and temporary database issues can be retried
             BinderyError::NetworkError(_) |
             BinderyError::DatabaseConnectionError(_) |
-            BinderyError::ExecutionTimeout(_) => true,
+            BinderyError::ExecutionTimeout(_) |
+            BinderyError::FileOps(FileOpsError { kind: FileOpsKind::Timeout, .. }) => true,
 
             // Most other errors should not be retried
             _ => false,
@@ -299,7 +371,8 @@ impl BinderyError {
             match self {
                 BinderyError::NetworkError(_) => Some(1000), // 1 second
                 BinderyError::DatabaseConnectionError(_) => Some(5000), // 5 seconds
-                BinderyError::ExecutionTimeout(_) => Some(2000), // 2 seconds
+                BinderyError::ExecutionTimeout(_) |
+                BinderyError::FileOps(_) => Some(2000), // 2 seconds
                 _ => Some(1000),
             }
         } else {
@@ -419,6 +492,61 @@ impl From<anyhow::Error> for BinderyError {
     }
 }
 
+/// File operation errors keep the original error as their source, and the
+/// context, path or suggestion its message would otherwise drop.
+/// `VesperaError` is the same type, so this covers chunking errors too.
+impl From<vespera_file_ops::EditError> for BinderyError {
+    fn from(err: vespera_file_ops::EditError) -> Self {
+        use vespera_file_ops::EditError as E;
+
+        let detail = match &err {
+            E::StringNotFound { context: Some(context), .. } |
+            E::InvalidInput { context: Some(context), .. } |
+            E::FileNotFound { context: Some(context), .. } |
+            E::Internal { context: Some(context), .. } => format!("{} ({})", err, context),
+            E::InvalidOperation { suggestion: Some(suggestion), .. } => format!("{}; {}", err, suggestion),
+            E::EncodingError { file_path: Some(path), .. } |
+            E::GuardrailViolation { path: Some(path), .. } => format!("{} in '{}'", err, path),
+            E::FileTooLarge { file_path, .. } => format!("{} in '{}'", err, file_path),
+            E::OutOfMemory { operation, .. } => format!("{} during {}", err, operation),
+            _ => err.to_string(),
+        };
+
+        let kind = match &err {
+            E::FileNotFound { .. } |
+            E::StringNotFound { .. } => FileOpsKind::NotFound,
+
+            E::PermissionDenied { .. } |
+            E::SecurityViolation { .. } |
+            E::GuardrailViolation { .. } => FileOpsKind::PermissionDenied,
+
+            E::MultipleMatches { .. } |
+            E::AmbiguousMatch { .. } |
+            E::EmptyPattern |
+            E::InvalidPattern { .. } |
+            E::InvalidInput { .. } |
+            E::InvalidOperation { .. } |
+            E::UnicodeNormalization { .. } |
+            E::EncodingError { .. } |
+            E::BinaryFile { .. } |
+            E::NotAFile { .. } |
+            E::FileTooLarge { .. } => FileOpsKind::InvalidInput,
+
+            E::IoError { .. } |
+            E::DirectoryNotEmpty { .. } |
+            E::ConcurrencyError { .. } |
+            E::InsufficientSpace { .. } => FileOpsKind::Io,
+
+            E::Timeout { .. } => FileOpsKind::Timeout,
+
+            E::OutOfMemory { .. } |
+            E::Internal { .. } => FileOpsKind::Internal,
+        };
+
+        BinderyError::FileOps(FileOpsError { kind, message: detail, source: Some(Arc::new(err)) })
+    }
+}
+
 #[cfg(feature = "regex")]
 impl From<regex::Error> for BinderyError {
     fn from(err: regex::Error) -> Self {
@@ -518,3 +646,51 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vespera_file_ops::EditError;
+
+    #[test]
+    fn test_file_op_errors_keep_category_and_context() {
+        let missing = BinderyError::from(EditError::file_not_found("notes/setting.md", Some("importing".to_string())));
+        assert_eq!(missing.error_category(), "not_found");
+        assert!(missing.to_string().contains("notes/setting.md") && missing.to_string().contains("importing"));
+
+        let guarded = BinderyError::from(EditError::GuardrailViolation {
+            guardrail: "max_bytes".to_string(),
+            details: "write too large".to_string(),
+            path: Some("out.txt".to_string()),
+        });
+        assert_eq!(guarded.error_category(), "permission_denied");
+        assert!(guarded.to_string().contains("out.txt"));
+
+        let io = BinderyError::from(EditError::from_io_with_path(
+            std::io::Error::other("disk unplugged"),
+            "data.bin",
+        ));
+        assert_eq!(io.error_category(), "io");
+        assert!(io.to_string().contains("data.bin") && io.to_string().contains("disk unplugged"));
+
+        // Timeouts stay retryable across the boundary
+        assert!(BinderyError::from(EditError::timeout(30, "read")).is_retryable());
+    }
+
+    #[test]
+    fn test_file_op_errors_keep_their_source() {
+        use std::error::Error;
+
+        let err = BinderyError::from(EditError::AmbiguousMatch { pattern: "TODO".to_string(), candidates: Vec::new() });
+        assert_eq!(err.error_category(), "invalid_input");
+        let source = err.source().and_then(|e| e.downcast_ref::<EditError>());
+        assert!(matches!(source, Some(EditError::AmbiguousMatch { pattern, .. }) if pattern == "TODO"));
+
+        // The source stays behind when the error crosses a process boundary
+        let json = serde_json::to_string(&err).unwrap();
+        let received: BinderyError = serde_json::from_str(&json).unwrap();
+        assert_eq!(received.error_category(), "invalid_input");
+        assert_eq!(received.to_string(), err.to_string());
+        assert!(received.source().is_none());
+    }
+}
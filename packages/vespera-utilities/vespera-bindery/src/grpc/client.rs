@@ -0,0 +1,139 @@
+//! Client for a remote Bindery gRPC server
+
+use super::proto::{self, path};
+use anyhow::{Context as _, Result};
+use tonic::client::Grpc;
+use tonic::codec::{ProstCodec, Streaming};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+
+/// Calls the Codex, task and RAG services over one connection
+///
+/// Cloning is cheap; clones share the connection.
+#[derive(Debug, Clone)]
+pub struct BinderyClient {
+    inner: Grpc<Channel>,
+}
+
+impl BinderyClient {
+    /// Connect to a server, e.g. `"http://team-server:50051"`
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self> {
+        let endpoint = endpoint.into();
+        let channel = Endpoint::from_shared(endpoint.clone())
+            .with_context(|| format!("Invalid gRPC endpoint: {}", endpoint))?
+            .connect()
+            .await
+            .with_context(|| format!("Failed to connect to {}", endpoint))?;
+        Ok(Self::new(channel))
+    }
+
+    /// Use an existing channel, e.g. one configured with TLS or timeouts
+    pub fn new(channel: Channel) -> Self {
+        Self { inner: Grpc::new(channel) }
+    }
+
+    // Codices
+
+    pub async fn get_codex(&mut self, request: proto::GetCodexRequest) -> Result<proto::Codex, Status> {
+        self.unary(path::GET_CODEX, request).await
+    }
+
+    pub async fn list_codices(
+        &mut self,
+        request: proto::ListCodicesRequest,
+    ) -> Result<proto::ListCodicesResponse, Status> {
+        self.unary(path::LIST_CODICES, request).await
+    }
+
+    pub async fn create_codex(&mut self, request: proto::CreateCodexRequest) -> Result<proto::Codex, Status> {
+        self.unary(path::CREATE_CODEX, request).await
+    }
+
+    pub async fn update_codex(&mut self, request: proto::UpdateCodexRequest) -> Result<proto::Codex, Status> {
+        self.unary(path::UPDATE_CODEX, request).await
+    }
+
+    pub async fn delete_codex(&mut self, request: proto::DeleteCodexRequest) -> Result<proto::DeleteResponse, Status> {
+        self.unary(path::DELETE_CODEX, request).await
+    }
+
+    /// Stream of changes, until the stream or the server is dropped
+    pub async fn subscribe_changes(
+        &mut self,
+        request: proto::SubscribeChangesRequest,
+    ) -> Result<Streaming<proto::ChangeEvent>, Status> {
+        self.ready().await?;
+        let response = self
+            .inner
+            .server_streaming(Request::new(request), PathAndQuery::from_static(path::SUBSCRIBE_CHANGES), ProstCodec::default())
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    // Tasks
+
+    pub async fn create_task(&mut self, request: proto::CreateTaskRequest) -> Result<proto::Task, Status> {
+        self.unary(path::CREATE_TASK, request).await
+    }
+
+    pub async fn get_task(&mut self, request: proto::GetTaskRequest) -> Result<proto::Task, Status> {
+        self.unary(path::GET_TASK, request).await
+    }
+
+    pub async fn list_tasks(&mut self, request: proto::ListTasksRequest) -> Result<proto::ListTasksResponse, Status> {
+        self.unary(path::LIST_TASKS, request).await
+    }
+
+    pub async fn update_task(&mut self, request: proto::UpdateTaskRequest) -> Result<proto::Task, Status> {
+        self.unary(path::UPDATE_TASK, request).await
+    }
+
+    pub async fn delete_task(&mut self, request: proto::DeleteTaskRequest) -> Result<proto::DeleteResponse, Status> {
+        self.unary(path::DELETE_TASK, request).await
+    }
+
+    // RAG
+
+    pub async fn search(&mut self, request: proto::SearchRequest) -> Result<proto::SearchResponse, Status> {
+        self.unary(path::SEARCH, request).await
+    }
+
+    pub async fn index_document(
+        &mut self,
+        request: proto::IndexDocumentRequest,
+    ) -> Result<proto::IndexDocumentResponse, Status> {
+        self.unary(path::INDEX_DOCUMENT, request).await
+    }
+
+    pub async fn delete_document(
+        &mut self,
+        request: proto::DeleteDocumentRequest,
+    ) -> Result<proto::DeleteResponse, Status> {
+        self.unary(path::DELETE_DOCUMENT, request).await
+    }
+
+    pub async fn get_stats(&mut self) -> Result<proto::RagStats, Status> {
+        self.unary(path::GET_STATS, proto::GetStatsRequest {}).await
+    }
+
+    async fn ready(&mut self) -> Result<(), Status> {
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| Status::unavailable(format!("gRPC server not ready: {}", e)))
+    }
+
+    async fn unary<Req, Resp>(&mut self, path: &'static str, request: Req) -> Result<Resp, Status>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Resp: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.ready().await?;
+        let response = self
+            .inner
+            .unary(Request::new(request), PathAndQuery::from_static(path), ProstCodec::default())
+            .await?;
+        Ok(response.into_inner())
+    }
+}
@@ -0,0 +1,164 @@
+//! gRPC service layer for remote Bindery access
+//!
+//! Lets a thin desktop client work against a shared team server instead of a
+//! local workspace. The server offers three services from
+//! `proto/bindery.proto`: `CodexService` (including a stream of Codex and task
+//! changes), `TaskService` and `RagService`. They run against the workspace
+//! [`Database`](crate::Database) and [`RAGService`](crate::rag::RAGService), so
+//! every client sees the same data and the changes other clients make.
+//!
+//! The server has no authentication of its own. Bind it to a trusted network,
+//! or put it behind a proxy that terminates TLS and checks credentials.
+//!
+//! # Example
+//! ```rust,no_run
+//! use vespera_bindery::grpc::{proto, BinderyClient};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mut client = BinderyClient::connect("http://team-server:50051").await?;
+//! let codex = client
+//!     .create_codex(proto::CreateCodexRequest { title: "Chapter 3".into(), ..Default::default() })
+//!     .await?;
+//!
+//! let mut changes = client.subscribe_changes(proto::SubscribeChangesRequest::default()).await?;
+//! while let Some(event) = changes.message().await? {
+//!     println!("{:?} {}", event.kind(), event.id);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod proto;
+pub mod server;
+
+pub use client::BinderyClient;
+pub use server::{GrpcServer, DEFAULT_PORT};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::Code;
+
+    /// Start a server on a fresh database and connect a client to it
+    async fn connect(temp_dir: &TempDir) -> BinderyClient {
+        let database = Database::new(temp_dir.path().join("bindery.db")).await.unwrap();
+        database.init_schema().await.unwrap();
+        let database = Arc::new(database);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(GrpcServer::new(database).into_router().serve_with_incoming(incoming));
+
+        BinderyClient::connect(format!("http://{}", addr)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_codex_round_trip_and_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut client = connect(&temp_dir).await;
+        let mut changes = client
+            .subscribe_changes(proto::SubscribeChangesRequest::default())
+            .await
+            .unwrap();
+
+        let codex = client
+            .create_codex(proto::CreateCodexRequest {
+                title: "Chapter 3".to_string(),
+                template_id: "chapter".to_string(),
+                metadata_json: r#"{"project_id": "novel"}"#.to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(codex.title, "Chapter 3");
+        assert_eq!(codex.template_id, "chapter");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&codex.metadata_json).unwrap()["project_id"], "novel");
+
+        let updated = client
+            .update_codex(proto::UpdateCodexRequest {
+                id: codex.id.clone(),
+                title: Some("Chapter Three".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.title, "Chapter Three");
+        assert_eq!(updated.metadata_json, codex.metadata_json);
+
+        let listed = client
+            .list_codices(proto::ListCodicesRequest { template_id: Some("chapter".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(listed.codices.len(), 1);
+
+        let deleted = client
+            .delete_codex(proto::DeleteCodexRequest { id: codex.id.clone() })
+            .await
+            .unwrap();
+        assert!(deleted.deleted);
+        let missing = client.get_codex(proto::GetCodexRequest { id: codex.id.clone() }).await.unwrap_err();
+        assert_eq!(missing.code(), Code::NotFound);
+
+        let mut kinds = Vec::new();
+        for _ in 0..3 {
+            let event = changes.message().await.unwrap().unwrap();
+            assert_eq!(event.id, codex.id);
+            kinds.push(event.kind());
+        }
+        assert_eq!(
+            kinds,
+            [proto::ChangeKind::CodexUpserted, proto::ChangeKind::CodexUpserted, proto::ChangeKind::CodexDeleted]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut client = connect(&temp_dir).await;
+
+        let task = client
+            .create_task(proto::CreateTaskRequest {
+                title: "Draft chapter 3".to_string(),
+                priority: Some("high".to_string()),
+                tags: vec!["writing".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.priority, "high");
+        assert_eq!(task.tags, ["writing"]);
+
+        let task = client
+            .update_task(proto::UpdateTaskRequest {
+                id: task.id.clone(),
+                status: Some("done".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.status, "done");
+
+        let listed = client.list_tasks(proto::ListTasksRequest::default()).await.unwrap();
+        assert_eq!(listed.tasks.len(), 1);
+        assert_eq!(listed.tasks[0].status, "done");
+
+        let empty_title = client.create_task(proto::CreateTaskRequest::default()).await.unwrap_err();
+        assert_eq!(empty_title.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_rag_without_index_is_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut client = connect(&temp_dir).await;
+
+        let status = client
+            .search(proto::SearchRequest { query: "outline".to_string(), ..Default::default() })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+    }
+}
@@ -0,0 +1,320 @@
+//! Messages and method paths of `proto/bindery.proto`
+//!
+//! Written by hand in the shape `prost-build` generates, so the build does not
+//! need `protoc`. Field tags must match the `.proto` file.
+
+use std::collections::HashMap;
+
+/// Fully qualified service names
+pub const CODEX_SERVICE: &str = "vespera.bindery.v1.CodexService";
+pub const TASK_SERVICE: &str = "vespera.bindery.v1.TaskService";
+pub const RAG_SERVICE: &str = "vespera.bindery.v1.RagService";
+
+/// HTTP/2 paths of each RPC
+pub mod path {
+    pub const GET_CODEX: &str = "/vespera.bindery.v1.CodexService/GetCodex";
+    pub const LIST_CODICES: &str = "/vespera.bindery.v1.CodexService/ListCodices";
+    pub const CREATE_CODEX: &str = "/vespera.bindery.v1.CodexService/CreateCodex";
+    pub const UPDATE_CODEX: &str = "/vespera.bindery.v1.CodexService/UpdateCodex";
+    pub const DELETE_CODEX: &str = "/vespera.bindery.v1.CodexService/DeleteCodex";
+    pub const SUBSCRIBE_CHANGES: &str = "/vespera.bindery.v1.CodexService/SubscribeChanges";
+
+    pub const CREATE_TASK: &str = "/vespera.bindery.v1.TaskService/CreateTask";
+    pub const GET_TASK: &str = "/vespera.bindery.v1.TaskService/GetTask";
+    pub const LIST_TASKS: &str = "/vespera.bindery.v1.TaskService/ListTasks";
+    pub const UPDATE_TASK: &str = "/vespera.bindery.v1.TaskService/UpdateTask";
+    pub const DELETE_TASK: &str = "/vespera.bindery.v1.TaskService/DeleteTask";
+
+    pub const SEARCH: &str = "/vespera.bindery.v1.RagService/Search";
+    pub const INDEX_DOCUMENT: &str = "/vespera.bindery.v1.RagService/IndexDocument";
+    pub const DELETE_DOCUMENT: &str = "/vespera.bindery.v1.RagService/DeleteDocument";
+    pub const GET_STATS: &str = "/vespera.bindery.v1.RagService/GetStats";
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteResponse {
+    #[prost(bool, tag = "1")]
+    pub deleted: bool,
+}
+
+// Codices
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Codex {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub title: String,
+    #[prost(string, tag = "3")]
+    pub template_id: String,
+    #[prost(string, tag = "4")]
+    pub content_json: String,
+    #[prost(string, tag = "5")]
+    pub metadata_json: String,
+    #[prost(string, tag = "6")]
+    pub created_at: String,
+    #[prost(string, tag = "7")]
+    pub updated_at: String,
+    #[prost(string, optional, tag = "8")]
+    pub parent_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetCodexRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListCodicesRequest {
+    #[prost(string, optional, tag = "1")]
+    pub parent_id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub template_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListCodicesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub codices: Vec<Codex>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CreateCodexRequest {
+    #[prost(string, tag = "1")]
+    pub title: String,
+    #[prost(string, tag = "2")]
+    pub template_id: String,
+    #[prost(string, tag = "3")]
+    pub metadata_json: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UpdateCodexRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, optional, tag = "2")]
+    pub title: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub content_json: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub metadata_json: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteCodexRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeChangesRequest {
+    #[prost(bool, tag = "1")]
+    pub include_tasks: bool,
+    #[prost(string, repeated, tag = "2")]
+    pub ids: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ChangeKind {
+    Unspecified = 0,
+    CodexUpserted = 1,
+    CodexDeleted = 2,
+    TaskUpserted = 3,
+    TaskDeleted = 4,
+    Resync = 5,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ChangeEvent {
+    #[prost(enumeration = "ChangeKind", tag = "1")]
+    pub kind: i32,
+    #[prost(string, tag = "2")]
+    pub id: String,
+}
+
+// Tasks
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Task {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub title: String,
+    #[prost(string, optional, tag = "3")]
+    pub description: Option<String>,
+    #[prost(string, tag = "4")]
+    pub status: String,
+    #[prost(string, tag = "5")]
+    pub priority: String,
+    #[prost(string, optional, tag = "6")]
+    pub parent_id: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub project_id: Option<String>,
+    #[prost(string, repeated, tag = "8")]
+    pub tags: Vec<String>,
+    #[prost(map = "string, string", tag = "9")]
+    pub labels: HashMap<String, String>,
+    #[prost(string, tag = "10")]
+    pub created_at: String,
+    #[prost(string, tag = "11")]
+    pub updated_at: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TaskSummary {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub title: String,
+    #[prost(string, tag = "3")]
+    pub status: String,
+    #[prost(string, tag = "4")]
+    pub priority: String,
+    #[prost(string, optional, tag = "5")]
+    pub parent_id: Option<String>,
+    #[prost(string, repeated, tag = "6")]
+    pub tags: Vec<String>,
+    #[prost(int64, tag = "7")]
+    pub child_count: i64,
+    #[prost(string, tag = "8")]
+    pub created_at: String,
+    #[prost(string, tag = "9")]
+    pub updated_at: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CreateTaskRequest {
+    #[prost(string, tag = "1")]
+    pub title: String,
+    #[prost(string, optional, tag = "2")]
+    pub description: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub priority: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub project_id: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub parent_id: Option<String>,
+    #[prost(string, repeated, tag = "6")]
+    pub tags: Vec<String>,
+    #[prost(map = "string, string", tag = "7")]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetTaskRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListTasksRequest {
+    #[prost(int32, optional, tag = "1")]
+    pub limit: Option<i32>,
+    #[prost(string, optional, tag = "2")]
+    pub parent_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListTasksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub tasks: Vec<TaskSummary>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UpdateTaskRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, optional, tag = "2")]
+    pub title: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub status: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteTaskRequest {
+    #[prost(string, tag = "1")]
+    pub id: String,
+}
+
+// RAG
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchRequest {
+    #[prost(string, tag = "1")]
+    pub query: String,
+    #[prost(uint32, tag = "2")]
+    pub limit: u32,
+    #[prost(string, repeated, tag = "3")]
+    pub document_types: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchResult {
+    #[prost(string, tag = "1")]
+    pub document_id: String,
+    #[prost(string, tag = "2")]
+    pub chunk_id: String,
+    #[prost(string, tag = "3")]
+    pub title: String,
+    #[prost(string, tag = "4")]
+    pub content: String,
+    #[prost(float, tag = "5")]
+    pub score: f32,
+    #[prost(string, tag = "6")]
+    pub document_type: String,
+    #[prost(string, optional, tag = "7")]
+    pub source_path: Option<String>,
+    #[prost(string, repeated, tag = "8")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SearchResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IndexDocumentRequest {
+    #[prost(string, tag = "1")]
+    pub title: String,
+    #[prost(string, tag = "2")]
+    pub content: String,
+    #[prost(string, tag = "3")]
+    pub document_type: String,
+    #[prost(string, optional, tag = "4")]
+    pub source_path: Option<String>,
+    #[prost(string, repeated, tag = "5")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct IndexDocumentResponse {
+    #[prost(string, tag = "1")]
+    pub document_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteDocumentRequest {
+    #[prost(string, tag = "1")]
+    pub document_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetStatsRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RagStats {
+    #[prost(uint64, tag = "1")]
+    pub total_documents: u64,
+    #[prost(uint64, tag = "2")]
+    pub total_chunks: u64,
+    #[prost(uint64, tag = "3")]
+    pub total_embeddings: u64,
+    #[prost(uint64, tag = "4")]
+    pub index_size_bytes: u64,
+    #[prost(string, optional, tag = "5")]
+    pub last_indexed: Option<String>,
+}
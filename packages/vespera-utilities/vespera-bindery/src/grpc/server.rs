@@ -0,0 +1,581 @@
+//! gRPC services over the workspace database and RAG index
+
+// Status is the error of every RPC; boxing it in helpers would only add unboxing
+#![allow(clippy::result_large_err)]
+
+use super::proto::{self, path};
+use crate::database::{self, Database};
+use crate::rag::{DocumentType, RAGService};
+use anyhow::{Context as _, Result};
+use futures::Stream;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::broadcast::error::RecvError;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{http, Body, BoxFuture, Service, StdError};
+use tonic::server::{Grpc, NamedService};
+use tonic::transport::server::Router;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Port `bindery-server grpc` listens on by default
+pub const DEFAULT_PORT: u16 = 50051;
+
+/// Search results returned when a request does not set a limit
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// gRPC server for the Codex, task and RAG services
+///
+/// Without a RAG index, the RAG service answers `UNAVAILABLE`.
+pub struct GrpcServer {
+    database: Arc<Database>,
+    rag: Option<Arc<RAGService>>,
+}
+
+impl GrpcServer {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self { database, rag: None }
+    }
+
+    /// Serve searches from `rag`
+    pub fn with_rag(mut self, rag: Arc<RAGService>) -> Self {
+        self.rag = Some(rag);
+        self
+    }
+
+    /// The three services on a tonic router, for serving or adding services to
+    pub fn into_router(self) -> Router {
+        let handlers = Arc::new(Handlers {
+            database: self.database,
+            rag: self.rag,
+        });
+        Server::builder()
+            .add_service(CodexServiceServer(Arc::clone(&handlers)))
+            .add_service(TaskServiceServer(Arc::clone(&handlers)))
+            .add_service(RagServiceServer(handlers))
+    }
+
+    /// Serve on `addr` until the process ends
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        info!("gRPC server listening on {}", addr);
+        self.into_router()
+            .serve(addr)
+            .await
+            .with_context(|| format!("gRPC server on {} failed", addr))
+    }
+}
+
+/// What the RPCs run against
+struct Handlers {
+    database: Arc<Database>,
+    rag: Option<Arc<RAGService>>,
+}
+
+// Codices
+impl Handlers {
+    async fn get_codex(self: Arc<Self>, request: proto::GetCodexRequest) -> Result<proto::Codex, Status> {
+        let codex = self
+            .database
+            .get_codex(&request.id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::not_found(format!("Codex {} not found", request.id)))?;
+        Ok(codex_message(&codex))
+    }
+
+    async fn list_codices(
+        self: Arc<Self>,
+        request: proto::ListCodicesRequest,
+    ) -> Result<proto::ListCodicesResponse, Status> {
+        let codices = match (&request.parent_id, &request.template_id) {
+            (Some(parent_id), _) => self.database.list_children(parent_id).await,
+            (None, Some(template_id)) => self.database.list_codices_by_template(template_id).await,
+            (None, None) => self.database.list_codices().await,
+        }
+        .map_err(internal)?;
+
+        let codices = codices
+            .iter()
+            .filter(|codex| match &request.template_id {
+                Some(template_id) => codex.get("template_id").and_then(Value::as_str) == Some(template_id),
+                None => true,
+            })
+            .map(codex_message)
+            .collect();
+        Ok(proto::ListCodicesResponse { codices })
+    }
+
+    async fn create_codex(self: Arc<Self>, request: proto::CreateCodexRequest) -> Result<proto::Codex, Status> {
+        if request.title.trim().is_empty() {
+            return Err(Status::invalid_argument("title is required"));
+        }
+        let template_id = match request.template_id.as_str() {
+            "" => "default",
+            template_id => template_id,
+        };
+        let metadata = parse_json("metadata_json", &request.metadata_json)?;
+
+        let id = Uuid::new_v4().to_string();
+        self.database
+            .create_codex(&id, &request.title, template_id, &metadata)
+            .await
+            .map_err(internal)?;
+        self.get_codex(proto::GetCodexRequest { id }).await
+    }
+
+    async fn update_codex(self: Arc<Self>, request: proto::UpdateCodexRequest) -> Result<proto::Codex, Status> {
+        let mut codex = self
+            .database
+            .get_codex(&request.id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::not_found(format!("Codex {} not found", request.id)))?;
+
+        if let Some(title) = request.title {
+            codex["title"] = json!(title);
+        }
+        if let Some(content) = request.content_json {
+            codex["content"] = parse_json("content_json", &content)?;
+        }
+        if let Some(metadata) = request.metadata_json {
+            codex["metadata"] = parse_json("metadata_json", &metadata)?;
+        }
+
+        self.database.update_codex(&request.id, &codex).await.map_err(internal)?;
+        self.get_codex(proto::GetCodexRequest { id: request.id }).await
+    }
+
+    async fn delete_codex(self: Arc<Self>, request: proto::DeleteCodexRequest) -> Result<proto::DeleteResponse, Status> {
+        let deleted = self.database.delete_codex(&request.id).await.map_err(internal)?;
+        Ok(proto::DeleteResponse { deleted })
+    }
+
+    async fn subscribe_changes(
+        self: Arc<Self>,
+        request: proto::SubscribeChangesRequest,
+    ) -> Result<impl Stream<Item = Result<proto::ChangeEvent, Status>>, Status> {
+        let mut changes = self.database.subscribe_changes();
+        let ids: HashSet<String> = request.ids.into_iter().collect();
+        let include_tasks = request.include_tasks;
+
+        Ok(async_stream::stream! {
+            loop {
+                let event = match changes.recv().await {
+                    Ok(change) => {
+                        let (kind, id) = match change {
+                            database::ChangeEvent::CodexUpserted { id } => (proto::ChangeKind::CodexUpserted, id),
+                            database::ChangeEvent::CodexDeleted { id } => (proto::ChangeKind::CodexDeleted, id),
+                            database::ChangeEvent::TaskUpserted { id } if include_tasks => (proto::ChangeKind::TaskUpserted, id),
+                            database::ChangeEvent::TaskDeleted { id } if include_tasks => (proto::ChangeKind::TaskDeleted, id),
+                            _ => continue,
+                        };
+                        if !ids.is_empty() && !ids.contains(&id) {
+                            continue;
+                        }
+                        proto::ChangeEvent { kind: kind as i32, id }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(missed, "gRPC change subscriber fell behind");
+                        proto::ChangeEvent { kind: proto::ChangeKind::Resync as i32, id: String::new() }
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                yield Ok(event);
+            }
+        })
+    }
+}
+
+// Tasks
+impl Handlers {
+    async fn create_task(self: Arc<Self>, request: proto::CreateTaskRequest) -> Result<proto::Task, Status> {
+        if request.title.trim().is_empty() {
+            return Err(Status::invalid_argument("title is required"));
+        }
+        let input = database::TaskInput {
+            title: request.title,
+            description: request.description,
+            priority: request.priority,
+            project_id: request.project_id,
+            parent_id: request.parent_id,
+            tags: request.tags,
+            labels: json!(request.labels),
+            subtasks: Vec::new(),
+        };
+
+        let id = self.database.create_task(&input).await.map_err(internal)?;
+        self.get_task(proto::GetTaskRequest { id }).await
+    }
+
+    async fn get_task(self: Arc<Self>, request: proto::GetTaskRequest) -> Result<proto::Task, Status> {
+        let task = self
+            .database
+            .get_task(&request.id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::not_found(format!("Task {} not found", request.id)))?;
+        Ok(task_message(&task))
+    }
+
+    async fn list_tasks(self: Arc<Self>, request: proto::ListTasksRequest) -> Result<proto::ListTasksResponse, Status> {
+        let tasks = self
+            .database
+            .list_tasks(request.limit, request.parent_id.as_deref())
+            .await
+            .map_err(internal)?;
+
+        let tasks = tasks
+            .into_iter()
+            .map(|task| proto::TaskSummary {
+                tags: task
+                    .tags
+                    .and_then(|tags| serde_json::from_str(&tags).ok())
+                    .unwrap_or_default(),
+                id: task.id,
+                title: task.title,
+                status: task.status,
+                priority: task.priority,
+                parent_id: task.parent_id,
+                child_count: task.child_count,
+                created_at: task.created_at.to_rfc3339(),
+                updated_at: task.updated_at.to_rfc3339(),
+            })
+            .collect();
+        Ok(proto::ListTasksResponse { tasks })
+    }
+
+    async fn update_task(self: Arc<Self>, request: proto::UpdateTaskRequest) -> Result<proto::Task, Status> {
+        if request.title.is_some() || request.status.is_some() {
+            let updated = self
+                .database
+                .update_task(&request.id, request.title.as_deref(), request.status.as_deref())
+                .await
+                .map_err(internal)?;
+            if !updated {
+                return Err(Status::not_found(format!("Task {} not found", request.id)));
+            }
+        }
+        self.get_task(proto::GetTaskRequest { id: request.id }).await
+    }
+
+    async fn delete_task(self: Arc<Self>, request: proto::DeleteTaskRequest) -> Result<proto::DeleteResponse, Status> {
+        let deleted = self.database.delete_task(&request.id).await.map_err(internal)?;
+        Ok(proto::DeleteResponse { deleted })
+    }
+}
+
+// RAG
+impl Handlers {
+    fn rag(&self) -> Result<&RAGService, Status> {
+        self.rag
+            .as_deref()
+            .ok_or_else(|| Status::unavailable("This server has no RAG index"))
+    }
+
+    async fn search(self: Arc<Self>, request: proto::SearchRequest) -> Result<proto::SearchResponse, Status> {
+        if request.query.trim().is_empty() {
+            return Err(Status::invalid_argument("query is required"));
+        }
+        let limit = match request.limit {
+            0 => DEFAULT_SEARCH_LIMIT,
+            limit => limit as usize,
+        };
+        let document_types = if request.document_types.is_empty() {
+            None
+        } else {
+            let types = request
+                .document_types
+                .iter()
+                .map(|name| name.parse::<DocumentType>())
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            Some(types)
+        };
+
+        let results = self
+            .rag()?
+            .search(&request.query, limit, document_types)
+            .await
+            .map_err(internal)?;
+
+        let results = results
+            .into_iter()
+            .map(|result| proto::SearchResult {
+                document_id: result.document_id.to_string(),
+                chunk_id: result.chunk_id,
+                title: result.metadata.title,
+                content: result.content,
+                score: result.score,
+                document_type: result.metadata.document_type.as_str().to_string(),
+                source_path: result.metadata.source_path.map(|path| path.display().to_string()),
+                tags: result.metadata.tags,
+            })
+            .collect();
+        Ok(proto::SearchResponse { results })
+    }
+
+    async fn index_document(
+        self: Arc<Self>,
+        request: proto::IndexDocumentRequest,
+    ) -> Result<proto::IndexDocumentResponse, Status> {
+        if request.content.is_empty() {
+            return Err(Status::invalid_argument("content is required"));
+        }
+        let document_type = match request.document_type.as_str() {
+            "" => DocumentType::Text,
+            name => name.parse().map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?,
+        };
+
+        let document_id = self
+            .rag()?
+            .index_document(
+                request.title,
+                request.content,
+                document_type,
+                request.source_path.map(PathBuf::from),
+                request.tags,
+            )
+            .await
+            .map_err(internal)?;
+        Ok(proto::IndexDocumentResponse {
+            document_id: document_id.to_string(),
+        })
+    }
+
+    async fn delete_document(
+        self: Arc<Self>,
+        request: proto::DeleteDocumentRequest,
+    ) -> Result<proto::DeleteResponse, Status> {
+        let document_id = Uuid::parse_str(&request.document_id)
+            .map_err(|e| Status::invalid_argument(format!("Invalid document_id: {}", e)))?;
+        let deleted = self.rag()?.delete_document(document_id).await.map_err(internal)?;
+        Ok(proto::DeleteResponse { deleted })
+    }
+
+    async fn get_stats(self: Arc<Self>, _request: proto::GetStatsRequest) -> Result<proto::RagStats, Status> {
+        let stats = self.rag()?.get_stats().await.map_err(internal)?;
+        Ok(proto::RagStats {
+            total_documents: stats.total_documents as u64,
+            total_chunks: stats.total_chunks as u64,
+            total_embeddings: stats.total_embeddings as u64,
+            index_size_bytes: stats.index_size_bytes,
+            last_indexed: stats.last_indexed.map(|at| at.to_rfc3339()),
+        })
+    }
+}
+
+fn internal(error: anyhow::Error) -> Status {
+    Status::internal(format!("{:#}", error))
+}
+
+/// Parse a JSON text field; empty means an empty object
+fn parse_json(field: &str, text: &str) -> Result<Value, Status> {
+    if text.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str(text).map_err(|e| Status::invalid_argument(format!("{} is not valid JSON: {}", field, e)))
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value.get(key).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn optional_string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// A Codex as returned by [`Database::get_codex`]
+fn codex_message(codex: &Value) -> proto::Codex {
+    proto::Codex {
+        id: string_field(codex, "id"),
+        title: string_field(codex, "title"),
+        template_id: string_field(codex, "template_id"),
+        content_json: codex.get("content").map(Value::to_string).unwrap_or_default(),
+        metadata_json: codex.get("metadata").map(Value::to_string).unwrap_or_default(),
+        created_at: string_field(codex, "created_at"),
+        updated_at: string_field(codex, "updated_at"),
+        parent_id: optional_string_field(codex, "parent_id"),
+    }
+}
+
+/// A task as returned by [`Database::get_task`]
+fn task_message(task: &Value) -> proto::Task {
+    let tags = task
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    // Labels are free-form JSON; non-string values are sent as JSON text
+    let labels: HashMap<String, String> = task
+        .get("labels")
+        .and_then(Value::as_object)
+        .map(|labels| {
+            labels
+                .iter()
+                .map(|(key, value)| {
+                    let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                    (key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    proto::Task {
+        id: string_field(task, "id"),
+        title: string_field(task, "title"),
+        description: optional_string_field(task, "description"),
+        status: string_field(task, "status"),
+        priority: string_field(task, "priority"),
+        parent_id: optional_string_field(task, "parent_id"),
+        project_id: optional_string_field(task, "project_id"),
+        tags,
+        labels,
+        created_at: string_field(task, "created_at"),
+        updated_at: string_field(task, "updated_at"),
+    }
+}
+
+// Routing
+
+/// Adapts a handler method to the service tonic drives for each call
+struct Handler<F> {
+    handlers: Arc<Handlers>,
+    method: F,
+}
+
+impl<F, Fut, Req, T> Service<Request<Req>> for Handler<F>
+where
+    F: Fn(Arc<Handlers>, Req) -> Fut,
+    Fut: Future<Output = Result<T, Status>> + Send + 'static,
+{
+    type Response = Response<T>;
+    type Error = Status;
+    type Future = BoxFuture<Response<T>, Status>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let call = (self.method)(Arc::clone(&self.handlers), request.into_inner());
+        Box::pin(async move { call.await.map(Response::new) })
+    }
+}
+
+fn unary<B, F, Fut, Req, Resp>(
+    handlers: Arc<Handlers>,
+    request: http::Request<B>,
+    method: F,
+) -> BoxFuture<http::Response<BoxBody>, Infallible>
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+    F: Fn(Arc<Handlers>, Req) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Resp, Status>> + Send + 'static,
+    Req: prost::Message + Default + Send + 'static,
+    Resp: prost::Message + Send + 'static,
+{
+    Box::pin(async move {
+        let mut grpc = Grpc::new(ProstCodec::default());
+        Ok(grpc.unary(Handler { handlers, method }, request).await)
+    })
+}
+
+fn server_streaming<B, F, Fut, Req, S, Resp>(
+    handlers: Arc<Handlers>,
+    request: http::Request<B>,
+    method: F,
+) -> BoxFuture<http::Response<BoxBody>, Infallible>
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+    F: Fn(Arc<Handlers>, Req) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<S, Status>> + Send + 'static,
+    S: Stream<Item = Result<Resp, Status>> + Send + 'static,
+    Req: prost::Message + Default + Send + 'static,
+    Resp: prost::Message + Send + 'static,
+{
+    Box::pin(async move {
+        let mut grpc = Grpc::new(ProstCodec::default());
+        Ok(grpc.server_streaming(Handler { handlers, method }, request).await)
+    })
+}
+
+fn unimplemented(path: &str) -> BoxFuture<http::Response<BoxBody>, Infallible> {
+    let response = Status::unimplemented(format!("Unknown method {}", path)).into_http();
+    Box::pin(async move { Ok(response) })
+}
+
+/// A proto service whose `call` routes request paths to handler methods
+macro_rules! grpc_service {
+    ($server:ident, $name:expr, |$handlers:ident, $request:ident| $route:expr) => {
+        #[derive(Clone)]
+        struct $server(Arc<Handlers>);
+
+        impl NamedService for $server {
+            const NAME: &'static str = $name;
+        }
+
+        impl<B> Service<http::Request<B>> for $server
+        where
+            B: Body + Send + 'static,
+            B::Error: Into<StdError> + Send + 'static,
+        {
+            type Response = http::Response<BoxBody>;
+            type Error = Infallible;
+            type Future = BoxFuture<Self::Response, Infallible>;
+
+            fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, $request: http::Request<B>) -> Self::Future {
+                let $handlers = Arc::clone(&self.0);
+                $route
+            }
+        }
+    };
+}
+
+grpc_service!(CodexServiceServer, proto::CODEX_SERVICE, |handlers, request| {
+    match request.uri().path() {
+        path::GET_CODEX => unary(handlers, request, Handlers::get_codex),
+        path::LIST_CODICES => unary(handlers, request, Handlers::list_codices),
+        path::CREATE_CODEX => unary(handlers, request, Handlers::create_codex),
+        path::UPDATE_CODEX => unary(handlers, request, Handlers::update_codex),
+        path::DELETE_CODEX => unary(handlers, request, Handlers::delete_codex),
+        path::SUBSCRIBE_CHANGES => server_streaming(handlers, request, Handlers::subscribe_changes),
+        path => unimplemented(path),
+    }
+});
+
+grpc_service!(TaskServiceServer, proto::TASK_SERVICE, |handlers, request| {
+    match request.uri().path() {
+        path::CREATE_TASK => unary(handlers, request, Handlers::create_task),
+        path::GET_TASK => unary(handlers, request, Handlers::get_task),
+        path::LIST_TASKS => unary(handlers, request, Handlers::list_tasks),
+        path::UPDATE_TASK => unary(handlers, request, Handlers::update_task),
+        path::DELETE_TASK => unary(handlers, request, Handlers::delete_task),
+        path => unimplemented(path),
+    }
+});
+
+grpc_service!(RagServiceServer, proto::RAG_SERVICE, |handlers, request| {
+    match request.uri().path() {
+        path::SEARCH => unary(handlers, request, Handlers::search),
+        path::INDEX_DOCUMENT => unary(handlers, request, Handlers::index_document),
+        path::DELETE_DOCUMENT => unary(handlers, request, Handlers::delete_document),
+        path::GET_STATS => unary(handlers, request, Handlers::get_stats),
+        path => unimplemented(path),
+    }
+});
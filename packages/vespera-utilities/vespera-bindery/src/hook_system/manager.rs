@@ -7,50 +7,99 @@ use super::{
     HookAgent, TimedAgent, HookTrigger, HookExecutionResult, HookAgentInput,
     TimedAgentInput, HookTriggerInput, HookAction, ActionType
 };
+use super::notifications::{self, EmailSender, NotificationChannel, NotificationPreferences, Notifier};
 use crate::{CodexId, CodexManager, TaskInput, TaskUpdateInput};
-use crate::codex::{Codex, CodexManagerExt};
+use crate::codex::{Codex, CodexChangeKind, CodexEventFilter, CodexManagerExt};
 use crate::errors::{BinderyError, BinderyResult};
+use crate::observability::BinderyMetrics;
+use crate::providers::ProviderManager;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use tokio::sync::{RwLock, Mutex};
 use uuid::Uuid;
 use chrono::Utc;
+use futures::StreamExt;
 use serde_json::Value;
+use crate::secrets::redact_json;
+
+/// ID of the hook agent registered by [`HookManager::enable_assignment_notifications`]
+pub const ASSIGNMENT_NOTIFICATIONS_HOOK_ID: &str = "builtin.task_assignment_notifications";
+
+/// The CodexManager hooks act on
+#[derive(Debug, Clone)]
+enum CodexHandle {
+    Shared(Arc<CodexManager>),
+    /// The manager's own hook manager refers back to it weakly, so that
+    /// neither keeps the other alive
+    Owner(Weak<crate::CodexManagerInner>),
+}
+
+impl CodexHandle {
+    /// Fails once the owning CodexManager has been dropped
+    fn get(&self) -> BinderyResult<Arc<CodexManager>> {
+        match self {
+            Self::Shared(manager) => Ok(manager.clone()),
+            Self::Owner(owner) => owner.upgrade()
+                .map(|inner| Arc::new(CodexManager { inner }))
+                .ok_or_else(|| BinderyError::HookError("The CodexManager of this hook manager has been dropped".to_string())),
+        }
+    }
+}
 
 /// Hook manager for event-driven automation
-#[derive(Debug)]
+///
+/// Clones share their agents and history.
+#[derive(Debug, Clone)]
 pub struct HookManager {
-    codex_manager: Arc<CodexManager>,
+    codex_manager: CodexHandle,
     hook_agents: Arc<RwLock<HashMap<String, HookAgent>>>,
     timed_agents: Arc<RwLock<HashMap<String, TimedAgent>>>,
     execution_history: Arc<RwLock<Vec<HookExecutionResult>>>,
     scheduler_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    assignment_watcher: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    notifier: Notifier,
 }
 
 impl HookManager {
     /// Create a new hook manager
     pub fn new(codex_manager: Arc<CodexManager>) -> Self {
+        Self::with_handle(CodexHandle::Shared(codex_manager))
+    }
+
+    /// The hook manager a CodexManager builds for itself while it is being
+    /// constructed
+    pub(crate) fn for_owner(owner: Weak<crate::CodexManagerInner>) -> Self {
+        Self::with_handle(CodexHandle::Owner(owner))
+    }
+
+    fn with_handle(codex_manager: CodexHandle) -> Self {
         Self {
             codex_manager,
             hook_agents: Arc::new(RwLock::new(HashMap::new())),
             timed_agents: Arc::new(RwLock::new(HashMap::new())),
             execution_history: Arc::new(RwLock::new(Vec::new())),
             scheduler_handle: Arc::new(Mutex::new(None)),
+            assignment_watcher: Arc::new(Mutex::new(None)),
+            notifier: Notifier::default(),
         }
     }
 
     /// Create a default hook manager for testing/development
     pub fn default() -> Self {
-        // Create a stub CodexManager
-        let stub_manager = Arc::new(crate::CodexManager::new().unwrap_or_else(|e| { panic!("Failed to create stub CodexManager: {}", e); }));
-        
-        Self {
-            codex_manager: stub_manager,
-            hook_agents: Arc::new(RwLock::new(HashMap::new())),
-            timed_agents: Arc::new(RwLock::new(HashMap::new())),
-            execution_history: Arc::new(RwLock::new(Vec::new())),
-            scheduler_handle: Arc::new(Mutex::new(None)),
-        }
+        let manager = crate::CodexManager::new().unwrap_or_else(|e| { panic!("Failed to create stub CodexManager: {}", e); });
+        Self::new(Arc::new(manager))
+    }
+
+    /// Write notification summaries with providers from `providers`
+    pub fn with_provider_manager(mut self, providers: Arc<ProviderManager>) -> Self {
+        self.notifier.providers = Some(providers);
+        self
+    }
+
+    /// Deliver the email notification channel through `sender`
+    pub fn with_email_sender(mut self, sender: Arc<dyn EmailSender>) -> Self {
+        self.notifier.email = Some(sender);
+        self
     }
 
     /// Start the background scheduler for timed agents
@@ -64,7 +113,9 @@ impl HookManager {
             
             loop {
                 interval.tick().await;
-                
+                // Stop once the CodexManager is gone
+                let Ok(codex_manager) = codex_manager.get() else { break };
+
                 let now = Utc::now();
                 let agents = timed_agents.read().await;
                 
@@ -94,6 +145,96 @@ impl HookManager {
         Ok(())
     }
 
+    /// Stop the timed agent scheduler, if it is running
+    pub async fn stop_scheduler(&self) {
+        if let Some(handle) = self.scheduler_handle.lock().await.take() {
+            handle.abort();
+        }
+    }
+
+    /// Notify users when tasks are assigned to them
+    ///
+    /// Registers a built-in hook agent, [`ASSIGNMENT_NOTIFICATIONS_HOOK_ID`],
+    /// and starts watching task changes for a new assignee. Does nothing if
+    /// already enabled.
+    pub async fn enable_assignment_notifications(&self) -> BinderyResult<()> {
+        let mut watcher = self.assignment_watcher.lock().await;
+        if watcher.is_some() {
+            return Ok(());
+        }
+        let codex_manager = self.codex_manager.get()?;
+
+        let mut parameters = HashMap::new();
+        parameters.insert("recipient_field".to_string(), Value::String("assignee_new".to_string()));
+        self.hook_agents.write().await.insert(ASSIGNMENT_NOTIFICATIONS_HOOK_ID.to_string(), HookAgent {
+            id: ASSIGNMENT_NOTIFICATIONS_HOOK_ID.to_string(),
+            name: "Task assignment notifications".to_string(),
+            description: "Notify the new assignee of a task".to_string(),
+            trigger: HookTrigger::TaskAssigned,
+            conditions: vec![],
+            actions: vec![HookAction {
+                action_type: ActionType::NotifyUser,
+                parameters,
+                async_execution: true,
+                retry_config: None,
+            }],
+            template_id: None,
+            enabled: true,
+            created_at: Utc::now(),
+            last_executed: None,
+            execution_count: 0,
+        });
+
+        let manager = self.clone();
+        let mut events = Box::pin(codex_manager.subscribe(
+            CodexEventFilter::tasks().with_kind(CodexChangeKind::Updated),
+        ));
+        *watcher = Some(crate::observability::spawn_traced(async move {
+            while let Some(event) = events.next().await {
+                let Some(context) = notifications::assignment_context(&event) else { continue };
+                if let Err(e) = manager.trigger_task_assigned(context).await {
+                    tracing::warn!(task_id = %event.codex_id, "Assignment hooks failed: {}", e);
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// Stop assignment notifications and remove their hook agent
+    pub async fn disable_assignment_notifications(&self) {
+        if let Some(handle) = self.assignment_watcher.lock().await.take() {
+            handle.abort();
+        }
+        self.hook_agents.write().await.remove(ASSIGNMENT_NOTIFICATIONS_HOOK_ID);
+    }
+
+    /// Notification preferences of `user_id`, if they have set any
+    pub async fn notification_preferences(&self, user_id: &str) -> Option<NotificationPreferences> {
+        notifications::find_preferences(&*self.codex_manager.get().ok()?, user_id)
+            .await
+            .map(|(_, preferences)| preferences)
+    }
+
+    /// Store notification preferences, returning the Codex holding them
+    ///
+    /// Updates the user's existing preference Codex if there is one.
+    pub async fn set_notification_preferences(&self, preferences: &NotificationPreferences) -> BinderyResult<CodexId> {
+        let codex_manager = self.codex_manager.get()?;
+        let id = match notifications::find_preferences(&codex_manager, &preferences.user_id).await {
+            Some((id, _)) => id,
+            None => {
+                codex_manager.register_template(notifications::preferences_template()).await?;
+                codex_manager.create_codex(
+                    format!("Notification preferences for {}", preferences.user_id),
+                    notifications::NOTIFICATION_PREFERENCES_TEMPLATE_ID,
+                ).await?
+            }
+        };
+        let user_id = codex_manager.config().user_id.clone().unwrap_or_else(|| "system".to_string());
+        codex_manager.set_codex_fields(&id, preferences.to_fields(&user_id)).await?;
+        Ok(id)
+    }
+
     /// Register a hook agent from template automation rules
     pub async fn register_hook_agent(&self, input: HookAgentInput) -> BinderyResult<String> {
         let hook_id = Uuid::new_v4().to_string();
@@ -245,6 +386,22 @@ impl HookManager {
         self.trigger_hooks_for_event(HookTrigger::TaskCompleted, context).await
     }
 
+    /// Trigger task assignment hooks
+    ///
+    /// `context` holds `task_id`, `assignee_new` and, if it was assigned
+    /// before, `assignee_old`; the task's title is added as `task_title`.
+    pub async fn trigger_task_assigned(&self, mut context: HashMap<String, Value>) -> BinderyResult<()> {
+        let task_id = context.get("task_id")
+            .and_then(|v| v.as_str())
+            .and_then(|id| id.parse::<CodexId>().ok());
+        if let Some(task_id) = task_id {
+            if let Some(title) = self.codex_manager.get()?.get_codex(&task_id).await.and_then(|crdt| crdt.get_title()) {
+                context.insert("task_title".to_string(), Value::String(title));
+            }
+        }
+        self.trigger_hooks_for_event(HookTrigger::TaskAssigned, context).await
+    }
+
     // Private helper methods
 
     async fn trigger_hooks_for_event(
@@ -264,13 +421,13 @@ impl HookManager {
                     // Execute hook asynchronously
                     let hook_clone = hook.clone();
                     let context_clone = context.clone();
-                    let execution_history = self.execution_history.clone();
+                    let manager = self.clone();
                     
-                    tokio::spawn(async move {
-                        let result = Self::execute_hook_actions_static(&hook_clone, &context_clone).await;
+                    crate::observability::spawn_traced(async move {
+                        let result = manager.execute_hook_actions(&hook_clone, &context_clone).await;
                         
                         if let Ok(execution_result) = result {
-                            execution_history.write().await.push(execution_result);
+                            manager.execution_history.write().await.push(execution_result);
                         }
                     });
                 }
@@ -301,7 +458,7 @@ impl HookManager {
         let duration = start_time.elapsed();
         let success = errors.is_empty();
         
-        Ok(HookExecutionResult {
+        let result = HookExecutionResult {
             hook_id: hook.id.clone(),
             success,
             output: if outputs.is_empty() { None } else { Some(outputs.join("; ")) },
@@ -310,7 +467,9 @@ impl HookManager {
             duration,
             triggered_by: hook.trigger.clone(),
             context_data: context.clone(),
-        })
+        };
+        record_execution(&result);
+        Ok(result)
     }
 
     async fn execute_hook_action(
@@ -330,7 +489,7 @@ impl HookManager {
                     let mut updates = HashMap::new();
                     updates.insert(field.to_string(), crate::templates::TemplateValue::from_json(value.clone())?);
                     
-                    self.codex_manager.update_codex_fields(&codex_id, updates).await?;
+                    self.codex_manager.get()?.update_codex_fields(&codex_id, updates).await?;
                     Ok(format!("Updated field '{}' in codex {}", field, codex_id))
                 } else {
                     Err(BinderyError::InvalidInput("Missing required parameters for UpdateField action".to_string()))
@@ -341,7 +500,7 @@ impl HookManager {
                 // Create a new task Codex
                 if let Some(title) = action.parameters.get("title").and_then(|v| v.as_str()) {
                     let template_id = "vespera.templates.hierarchical_task".to_string();
-                    let codex_id = self.codex_manager.create_codex(title.to_string(), template_id).await?;
+                    let codex_id = self.codex_manager.get()?.create_codex(title.to_string(), template_id).await?;
                     Ok(format!("Created new task codex: {}", codex_id))
                 } else {
                     Err(BinderyError::InvalidInput("Missing title parameter for CreateTask action".to_string()))
@@ -349,13 +508,32 @@ impl HookManager {
             },
             
             ActionType::NotifyUser => {
-                // Send notification (placeholder implementation)
+                // Deliver through the recipient's preferred channel
+                self.notifier.notify_user(&*self.codex_manager.get()?, action, context).await
+            },
+
+            ActionType::CallWebhook | ActionType::SendEmail => {
+                let (channel, recipient) = match action.action_type {
+                    ActionType::CallWebhook => {
+                        let url = action.parameters.get("url").and_then(|v| v.as_str()).ok_or_else(|| {
+                            BinderyError::InvalidInput("Missing url parameter for CallWebhook action".to_string())
+                        })?;
+                        let recipient = action.parameters.get("recipient").and_then(|v| v.as_str()).unwrap_or("");
+                        (NotificationChannel::Webhook { url: url.to_string() }, recipient)
+                    },
+                    _ => {
+                        let to = action.parameters.get("to").and_then(|v| v.as_str()).ok_or_else(|| {
+                            BinderyError::InvalidInput("Missing to parameter for SendEmail action".to_string())
+                        })?;
+                        (NotificationChannel::Email { address: to.to_string() }, to)
+                    },
+                };
                 let message = action.parameters.get("message")
                     .and_then(|v| v.as_str())
-                    .unwrap_or("Hook notification");
-                    
-                tracing::info!("Hook notification: {}", message);
-                Ok(format!("Notification sent: {}", message))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| notifications::plain_message(context));
+                self.notifier.deliver(&channel, recipient, &message, context).await?;
+                Ok(format!("Sent {:?} notification through {:?}", action.action_type, channel))
             },
             
             ActionType::LogEvent => {
@@ -364,7 +542,9 @@ impl HookManager {
                     .and_then(|v| v.as_str())
                     .unwrap_or("Hook event logged");
                     
-                tracing::info!("Hook event: {} - Context: {:?}", message, context);
+                let mut context = serde_json::to_value(context).unwrap_or_default();
+                redact_json(&mut context);
+                tracing::info!("Hook event: {} - Context: {}", message, context);
                 Ok(format!("Event logged: {}", message))
             },
             
@@ -375,29 +555,6 @@ impl HookManager {
         }
     }
 
-    // Static method for background execution
-    async fn execute_hook_actions_static(
-        hook: &HookAgent,
-        context: &HashMap<String, Value>,
-    ) -> BinderyResult<HookExecutionResult> {
-        let start_time = std::time::Instant::now();
-        let execution_time = Utc::now();
-        
-        // Simplified implementation for background execution
-        let duration = start_time.elapsed();
-        
-        Ok(HookExecutionResult {
-            hook_id: hook.id.clone(),
-            success: true,
-            output: Some(format!("Background execution of hook '{}'", hook.name)),
-            error: None,
-            execution_time,
-            duration,
-            triggered_by: hook.trigger.clone(),
-            context_data: context.clone(),
-        })
-    }
-
     async fn execute_timed_agent_actions(
         agent: &TimedAgent,
         _codex_manager: &Arc<CodexManager>,
@@ -409,7 +566,7 @@ impl HookManager {
         // Execute timed agent actions (simplified implementation)
         let duration = start_time.elapsed();
         
-        Ok(HookExecutionResult {
+        let result = HookExecutionResult {
             hook_id: agent.id.clone(),
             success: true,
             output: Some(format!("Timed agent '{}' executed", agent.name)),
@@ -418,7 +575,9 @@ impl HookManager {
             duration,
             triggered_by: HookTrigger::TimeScheduled,
             context_data: HashMap::new(),
-        })
+        };
+        record_execution(&result);
+        Ok(result)
     }
 
     fn parse_automation_rule_to_hook(&self, hook_id: &str, input: &HookAgentInput) -> BinderyResult<HookAgent> {
@@ -436,6 +595,7 @@ impl HookManager {
             "pre_task_update" => HookTrigger::PreTaskUpdate,
             "post_task_update" => HookTrigger::PostTaskUpdate,
             "task_completed" => HookTrigger::TaskCompleted,
+            "task_assigned" => HookTrigger::TaskAssigned,
             "field_change" => HookTrigger::FieldChange,
             _ => HookTrigger::CustomEvent,
         };
@@ -561,4 +721,9 @@ impl HookManager {
         
         context
     }
-}
\ No newline at end of file
+}
+
+/// Report a finished hook execution to the metrics recorder
+fn record_execution(result: &HookExecutionResult) {
+    BinderyMetrics::record_hook_execution(&format!("{:?}", result.triggered_by), result.duration, result.success);
+}
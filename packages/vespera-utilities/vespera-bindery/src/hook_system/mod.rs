@@ -6,8 +6,10 @@
 pub mod manager;
 pub mod agents;
 pub mod scheduler;
+pub mod notifications;
 
 pub use manager::HookManager;
+pub use notifications::{EmailSender, NotificationChannel, NotificationPreferences};
 // Note: HookAgent and TimedAgent structs are defined below, so we don't import them from agents
 
 use serde::{Deserialize, Serialize};
@@ -26,6 +28,8 @@ pub enum HookTrigger {
     PostTaskDelete,
     TaskCompleted,
     TaskStatusChange,
+    /// A task's assignee changed
+    TaskAssigned,
     FieldChange,
     TimeScheduled,
     CustomEvent,
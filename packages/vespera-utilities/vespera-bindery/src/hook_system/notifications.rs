@@ -0,0 +1,411 @@
+//! Task assignment notifications
+//!
+//! [`HookManager::enable_assignment_notifications`](super::HookManager::enable_assignment_notifications)
+//! registers a built-in hook agent on [`HookTrigger::TaskAssigned`](super::HookTrigger::TaskAssigned)
+//! and watches task changes for a new assignee. Its `notify_user` action
+//! reaches the assignee through the channel in their
+//! [`NotificationPreferences`], which are stored as Codices of the
+//! [`NOTIFICATION_PREFERENCES_TEMPLATE_ID`] template and so sync like any other
+//! Codex. A user without preferences is notified through the action's own
+//! `channel` parameter, or the log.
+//!
+//! Preferences naming a `summary_provider` get a message written by that LLM
+//! provider instead of the plain one, once the hook manager has a
+//! [`ProviderManager`]. Bindery has no mail client, so the email channel needs
+//! an [`EmailSender`].
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::HookAction;
+use crate::codex::{CodexChange, CodexEvent};
+use crate::crdt::{TemplateValue, VesperaCRDT};
+use crate::providers::ProviderManager;
+use crate::secrets::redact_json;
+use crate::templates::{CrdtLayer, FieldDefinition, FieldType, Template, TemplateId};
+use crate::{BinderyError, BinderyResult, CodexId, CodexManager, UserId};
+
+/// Template of notification preference Codices
+pub const NOTIFICATION_PREFERENCES_TEMPLATE_ID: &str = "vespera.templates.notification_preferences";
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SUMMARY_SYSTEM_PROMPT: &str = "Write a notification of one or two sentences telling the recipient \
+about the event described in JSON. Reply with the notification text only.";
+
+/// Where a user's notifications go
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "channel", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Log,
+    /// POSTed as JSON with the recipient, message and event context
+    Webhook { url: String },
+    Email { address: String },
+}
+
+/// How a user wants to be notified
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub user_id: UserId,
+    pub enabled: bool,
+    pub channel: NotificationChannel,
+    /// Provider that writes each message as a summary of the event
+    pub summary_provider: Option<String>,
+}
+
+impl NotificationPreferences {
+    pub fn new(user_id: impl Into<UserId>, channel: NotificationChannel) -> Self {
+        Self {
+            user_id: user_id.into(),
+            enabled: true,
+            channel,
+            summary_provider: None,
+        }
+    }
+
+    pub fn with_summary_provider(mut self, provider_id: impl Into<String>) -> Self {
+        self.summary_provider = Some(provider_id.into());
+        self
+    }
+
+    /// Read preferences from a Codex of the preferences template
+    ///
+    /// None if it has no user or names a channel without its address.
+    pub fn from_codex(crdt: &VesperaCRDT) -> Option<Self> {
+        let user_id = text_field(crdt, "user_id")?;
+        let channel = match text_field(crdt, "channel").as_deref() {
+            None | Some("log") => NotificationChannel::Log,
+            Some("webhook") => NotificationChannel::Webhook { url: text_field(crdt, "webhook_url")? },
+            Some("email") => NotificationChannel::Email { address: text_field(crdt, "email")? },
+            Some(_) => return None,
+        };
+        let enabled = match crdt.get_metadata("enabled") {
+            Some(TemplateValue::Structured { value, .. }) => value.as_bool().unwrap_or(true),
+            _ => true,
+        };
+        Some(Self {
+            user_id,
+            enabled,
+            channel,
+            summary_provider: text_field(crdt, "summary_provider"),
+        })
+    }
+
+    /// Metadata fields storing these preferences, written by `user_id`
+    pub fn to_fields(&self, user_id: &UserId) -> Vec<(String, TemplateValue)> {
+        let now = chrono::Utc::now();
+        let text = |value: &str| TemplateValue::Text {
+            value: value.to_string(),
+            timestamp: now,
+            user_id: user_id.clone(),
+        };
+        let (channel, address) = match &self.channel {
+            NotificationChannel::Log => ("log", None),
+            NotificationChannel::Webhook { url } => ("webhook", Some(("webhook_url", url))),
+            NotificationChannel::Email { address } => ("email", Some(("email", address))),
+        };
+
+        let mut fields = vec![
+            ("user_id".to_string(), text(&self.user_id)),
+            ("channel".to_string(), text(channel)),
+            ("enabled".to_string(), TemplateValue::Structured {
+                value: json!(self.enabled),
+                timestamp: now,
+                user_id: user_id.clone(),
+            }),
+            // Empty clears a provider set before
+            ("summary_provider".to_string(), text(self.summary_provider.as_deref().unwrap_or(""))),
+        ];
+        if let Some((field, value)) = address {
+            fields.push((field.to_string(), text(value)));
+        }
+        fields
+    }
+}
+
+/// Template registered for preference Codices
+pub fn preferences_template() -> Template {
+    let mut template = Template::new(
+        TemplateId::new(NOTIFICATION_PREFERENCES_TEMPLATE_ID),
+        "Notification Preferences".to_string(),
+        "How a user is notified of hook events".to_string(),
+        "notification_preferences".to_string(),
+    );
+    let channels = vec!["log".to_string(), "webhook".to_string(), "email".to_string()];
+    for (name, field_type, required) in [
+        ("user_id", FieldType::Text, true),
+        ("channel", FieldType::Enum(channels), true),
+        ("enabled", FieldType::Boolean, false),
+        ("webhook_url", FieldType::Text, false),
+        ("email", FieldType::Text, false),
+        ("summary_provider", FieldType::Text, false),
+    ] {
+        template.add_field(name.to_string(), FieldDefinition::structural(field_type, CrdtLayer::Metadata, required));
+    }
+    template
+}
+
+/// The preference Codex of `user_id`, if they have one
+pub async fn find_preferences(
+    codex_manager: &CodexManager,
+    user_id: &str,
+) -> Option<(CodexId, NotificationPreferences)> {
+    for id in codex_manager.list_codices().await {
+        let Some(crdt) = codex_manager.get_codex(&id).await else { continue };
+        if crate::template_of(&crdt).as_deref() != Some(NOTIFICATION_PREFERENCES_TEMPLATE_ID) {
+            continue;
+        }
+        match NotificationPreferences::from_codex(&crdt) {
+            Some(preferences) if preferences.user_id == user_id => return Some((id, preferences)),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Delivers email for the email channel
+#[async_trait]
+pub trait EmailSender: Send + Sync + std::fmt::Debug {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> BinderyResult<()>;
+}
+
+/// Hook event context for a change of assignee, or None if the event has none
+///
+/// Unassigning a task notifies nobody.
+pub fn assignment_context(event: &CodexEvent) -> Option<HashMap<String, Value>> {
+    let CodexChange::Updated { fields } = &event.change else {
+        return None;
+    };
+    let change = fields.iter().find(|change| change.field == "assignee")?;
+    let assignee = text_value(&change.new).filter(|assignee| !assignee.is_empty())?;
+    let previous = change.old.as_ref().and_then(text_value);
+    if previous.as_deref() == Some(assignee.as_str()) {
+        return None;
+    }
+
+    let mut context = HashMap::new();
+    context.insert("task_id".to_string(), Value::String(event.codex_id.to_string()));
+    context.insert("assignee_new".to_string(), Value::String(assignee));
+    if let Some(previous) = previous.filter(|previous| !previous.is_empty()) {
+        context.insert("assignee_old".to_string(), Value::String(previous));
+    }
+    Some(context)
+}
+
+/// Plain message for a hook event, used when no summary is written
+pub fn plain_message(context: &HashMap<String, Value>) -> String {
+    let get = |key: &str| context.get(key).and_then(Value::as_str);
+    let Some(task) = get("task_title").or_else(|| get("task_id")) else {
+        return "Hook notification".to_string();
+    };
+    match get("assignee_old") {
+        Some(previous) => format!("You were assigned \"{}\", previously assigned to {}", task, previous),
+        None => format!("You were assigned \"{}\"", task),
+    }
+}
+
+/// What notification actions need to deliver messages
+#[derive(Clone, Default)]
+pub(crate) struct Notifier {
+    http: reqwest::Client,
+    pub(crate) email: Option<Arc<dyn EmailSender>>,
+    pub(crate) providers: Option<Arc<ProviderManager>>,
+}
+
+impl std::fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notifier")
+            .field("email", &self.email)
+            .field("providers", &self.providers.is_some())
+            .finish()
+    }
+}
+
+impl Notifier {
+    /// Run a `notify_user` action
+    ///
+    /// The recipient is the `recipient` parameter, or the context value named
+    /// by `recipient_field` (default `assignee_new`).
+    pub(crate) async fn notify_user(
+        &self,
+        codex_manager: &CodexManager,
+        action: &HookAction,
+        context: &HashMap<String, Value>,
+    ) -> BinderyResult<String> {
+        let field = action.parameters.get("recipient_field").and_then(Value::as_str).unwrap_or("assignee_new");
+        let recipient = action.parameters.get("recipient")
+            .or_else(|| context.get(field))
+            .and_then(Value::as_str)
+            .ok_or_else(|| BinderyError::InvalidInput("No recipient for NotifyUser action".to_string()))?;
+
+        let (channel, summary_provider) = match find_preferences(codex_manager, recipient).await {
+            Some((_, preferences)) if !preferences.enabled => {
+                return Ok(format!("Notifications are off for {}", recipient));
+            }
+            Some((_, preferences)) => (preferences.channel, preferences.summary_provider),
+            None => (channel_parameter(action)?, None),
+        };
+
+        let mut message = action.parameters.get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| plain_message(context));
+        if let Some(provider_id) = summary_provider {
+            match self.summarize(&provider_id, recipient, context).await {
+                Ok(summary) => message = summary,
+                Err(e) => tracing::warn!(provider_id, "Sending plain notification; summary failed: {}", e),
+            }
+        }
+
+        self.deliver(&channel, recipient, &message, context).await?;
+        Ok(format!("Notified {} by {:?}", recipient, channel))
+    }
+
+    /// Send `message` to `recipient` through `channel`
+    pub(crate) async fn deliver(
+        &self,
+        channel: &NotificationChannel,
+        recipient: &str,
+        message: &str,
+        context: &HashMap<String, Value>,
+    ) -> BinderyResult<()> {
+        match channel {
+            NotificationChannel::Log => {
+                tracing::info!(recipient, "Hook notification: {}", message);
+                Ok(())
+            }
+            NotificationChannel::Webhook { url } => {
+                let mut context = serde_json::to_value(context).unwrap_or_default();
+                redact_json(&mut context);
+                self.http
+                    .post(url)
+                    .timeout(WEBHOOK_TIMEOUT)
+                    .json(&json!({ "recipient": recipient, "message": message, "context": context }))
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| BinderyError::InternalError(format!("Notification webhook failed: {}", e)))?;
+                Ok(())
+            }
+            NotificationChannel::Email { address } => {
+                let sender = self.email.as_ref().ok_or_else(|| {
+                    BinderyError::InvalidOperation("No email sender attached to the hook manager".to_string())
+                })?;
+                sender.send(address, "Vespera notification", message).await
+            }
+        }
+    }
+
+    async fn summarize(
+        &self,
+        provider_id: &str,
+        recipient: &str,
+        context: &HashMap<String, Value>,
+    ) -> BinderyResult<String> {
+        let providers = self.providers.as_ref().ok_or_else(|| {
+            BinderyError::InvalidOperation("No provider manager attached to the hook manager".to_string())
+        })?;
+        let mut event = serde_json::to_value(context).unwrap_or_default();
+        redact_json(&mut event);
+        let prompt = json!({ "recipient": recipient, "event": event }).to_string();
+        let response = providers
+            .send_message(provider_id, &prompt, None, None, Some(SUMMARY_SYSTEM_PROMPT), false)
+            .await
+            .map_err(|e| BinderyError::InternalError(e.to_string()))?;
+        Ok(response.text.trim().to_string())
+    }
+}
+
+/// Channel named by an action's `channel` parameter, the log by default
+fn channel_parameter(action: &HookAction) -> BinderyResult<NotificationChannel> {
+    let parameter = |key: &str| {
+        action.parameters.get(key).and_then(Value::as_str).map(str::to_string).ok_or_else(|| {
+            BinderyError::InvalidInput(format!("Missing {} parameter for notification channel", key))
+        })
+    };
+    match action.parameters.get("channel").and_then(Value::as_str) {
+        None | Some("log") => Ok(NotificationChannel::Log),
+        Some("webhook") => Ok(NotificationChannel::Webhook { url: parameter("url")? }),
+        Some("email") => Ok(NotificationChannel::Email { address: parameter("to")? }),
+        Some(other) => Err(BinderyError::InvalidInput(format!("Unknown notification channel '{}'", other))),
+    }
+}
+
+fn text_field(crdt: &VesperaCRDT, key: &str) -> Option<String> {
+    crdt.get_metadata(key).and_then(text_value).filter(|value| !value.is_empty())
+}
+
+fn text_value(value: &TemplateValue) -> Option<String> {
+    match value {
+        TemplateValue::Text { value, .. } => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::FieldChange;
+    use uuid::Uuid;
+
+    fn text(value: &str) -> TemplateValue {
+        TemplateValue::Text { value: value.to_string(), timestamp: chrono::Utc::now(), user_id: "alice".to_string() }
+    }
+
+    #[test]
+    fn test_preferences_round_trip_through_codex() {
+        let mut crdt = VesperaCRDT::new(Uuid::new_v4(), "alice".to_string());
+        let preferences = NotificationPreferences::new("bob", NotificationChannel::Webhook {
+            url: "https://hooks.example.com/bob".to_string(),
+        }).with_summary_provider("claude");
+        for (field, value) in preferences.to_fields(&"alice".to_string()) {
+            crdt.set_metadata(field, value).unwrap();
+        }
+        assert_eq!(NotificationPreferences::from_codex(&crdt), Some(preferences.clone()));
+
+        // Switching channel and dropping the provider overwrites both
+        let mut changed = NotificationPreferences::new("bob", NotificationChannel::Log);
+        changed.enabled = false;
+        for (field, value) in changed.to_fields(&"alice".to_string()) {
+            crdt.set_metadata(field, value).unwrap();
+        }
+        assert_eq!(NotificationPreferences::from_codex(&crdt), Some(changed));
+
+        // An email channel without an address is not usable
+        crdt.set_metadata("channel".to_string(), text("email")).unwrap();
+        assert_eq!(NotificationPreferences::from_codex(&crdt), None);
+    }
+
+    #[test]
+    fn test_assignment_context() {
+        let task_id = Uuid::new_v4();
+        let event = |old: Option<&str>, new: &str| CodexEvent::new(task_id, None, CodexChange::Updated {
+            fields: vec![FieldChange { field: "assignee".to_string(), old: old.map(text), new: text(new) }],
+        });
+
+        let context = assignment_context(&event(Some("alice"), "bob")).unwrap();
+        assert_eq!(context["task_id"], json!(task_id.to_string()));
+        assert_eq!(context["assignee_new"], json!("bob"));
+        assert_eq!(context["assignee_old"], json!("alice"));
+        assert!(!assignment_context(&event(None, "bob")).unwrap().contains_key("assignee_old"));
+
+        assert_eq!(assignment_context(&event(Some("bob"), "bob")), None);
+        assert_eq!(assignment_context(&event(Some("bob"), "")), None);
+        let created = CodexEvent::new(task_id, None, CodexChange::Created { title: "Task".to_string() });
+        assert_eq!(assignment_context(&created), None);
+    }
+
+    #[test]
+    fn test_plain_message() {
+        let mut context = HashMap::new();
+        assert_eq!(plain_message(&context), "Hook notification");
+        context.insert("task_title".to_string(), json!("Write docs"));
+        assert_eq!(plain_message(&context), "You were assigned \"Write docs\"");
+        context.insert("assignee_old".to_string(), json!("alice"));
+        assert_eq!(plain_message(&context), "You were assigned \"Write docs\", previously assigned to alice");
+    }
+}
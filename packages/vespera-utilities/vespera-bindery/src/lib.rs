@@ -100,13 +100,34 @@ pub mod database;
 // RAG (Retrieval-Augmented Generation) module
 pub mod rag;
 
-// LLM provider integrations - types consolidated into src/providers/types.rs (Phase 17.5 Task 8)
+// LLM provider integrations - types consolidated into src/providers/types.rs (Phase 17.5 Task 8);
+// the old src/llm module is gone and `providers` is the single provider API
 
 // Secret storage system (Phase 17.5)
 pub mod secrets;
 
+// Token usage tracking and cost budgets
+pub mod usage;
+
+// Graceful shutdown across subsystems
+pub mod shutdown;
+
+// Support bundles for issue reports
+pub mod diagnostics;
+
+// Workspace detection and .vespera bootstrapping
+pub mod workspace;
+pub use workspace::Workspace;
+
+// Model Context Protocol server
+pub mod mcp;
+
+// gRPC service layer for remote access
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
 // Conditional binding modules
-#[cfg(feature = "nodejs")]
+#[cfg(any(feature = "nodejs", feature = "python"))]
 pub mod bindings;
 
 // Test modules
@@ -117,6 +138,10 @@ pub mod tests;
 pub mod errors;
 pub use errors::{BinderyError, BinderyResult};
 
+// Loading BinderyConfig from files and VESPERA_* environment variables
+mod config_loader;
+pub use config_loader::{ENV_PREFIX, ENV_SEPARATOR};
+
 // Observability module with audit logging
 pub mod observability;
 
@@ -127,17 +152,27 @@ pub use types::{
     VectorClock, OperationId, ContentHash
 };
 
+// Cursor pagination for large lists
+pub mod pagination;
+
+// Compression codecs for persisted Codices and sync payloads
+pub mod compression;
+pub use compression::{CompressionCodec, CompressionConfig};
+pub use pagination::{Page, PageRequest};
+
 // Re-export template types
 pub use templates::TemplateId;
 
 // Re-export commonly used task management types
 pub use task_management::{
     TaskManager, TaskService, TaskExecutor, TaskStatus, TaskPriority, TaskInput,
-    TaskUpdateInput, TaskSummary, TaskDashboard, TaskExecutionResult, ExecutionContext
+    TaskUpdateInput, TaskSummary, TaskDashboard, TaskExecutionResult, ExecutionContext,
+    ArtifactStore, TaskArtifact, BreakdownConfig, BreakdownSuggestion
 };
 
 // Re-export role management types
 pub use role_management::{RoleManager, RoleExecutor, Role, ToolGroup, RoleExecutionResult};
+pub use role_management::{ApprovalGate, ApprovalRequest, ApprovalStatus, Actor, GatedAction};
 
 // Re-export hook system types
 pub use hook_system::{HookManager, HookAgent, TimedAgent};
@@ -145,20 +180,23 @@ pub use hook_system::{HookManager, HookAgent, TimedAgent};
 // Re-export RAG types
 pub use rag::{RAGService, RAGConfig, DocumentType, SearchResult, RAGStats};
 
+// Re-export Codex change event types
+pub use codex::{CodexChange, CodexChangeKind, CodexEvent, CodexEventFilter, DuplicateOptions, FieldChange, TrashEntry};
+
 // Re-export database types
-pub use database::{Database, DatabasePoolConfig, PoolMetrics};
+pub use database::{Database, ChangeEvent, DatabasePoolConfig, PoolMetrics, PoolRole, BackupConfig, StorageBackend, StorageConfig, SqlDialect};
 
 // Re-export observability and audit logging types
 pub use observability::{
     // Core observability
     MetricsCollector, BinderyMetrics, PerformanceTimer,
-    init_logging, init_observability, init_audit_logging,
+    init_logging, init_observability, init_audit_logging, reload_logging,
 
     // Audit logging
     AuditLogger, AuditEvent, AuditConfig, AuditQueryFilter, AuditStats,
     UserContext, Operation, SecurityContext, OperationOutcome,
     create_role_execution_event, create_migration_event, create_config_change_event,
-    create_auth_failure_event,
+    create_auth_failure_event, create_trash_event, create_approval_event, create_codex_access_event,
 
     // Audit configuration helpers
     default_audit_config, production_audit_config, validate_audit_config,
@@ -166,7 +204,7 @@ pub use observability::{
 };
 
 // Re-export migration types with audit support
-pub use migration::{MigrationManager, MigrationInfo, MigrationResult, MigrationStatus};
+pub use migration::{MigrationManager, MigrationInfo, MigrationResult, MigrationStatus, MigrationPlan};
 
 /// The main entry point for Vespera Bindery functionality.
 ///
@@ -180,12 +218,32 @@ pub struct CodexManager {
 #[derive(Debug)]
 struct CodexManagerInner {
     codices: tokio::sync::RwLock<HashMap<CodexId, Arc<crdt::VesperaCRDT>>>,
-    templates: Arc<templates::TemplateRegistry>,
+    templates: tokio::sync::RwLock<templates::TemplateRegistry>,
     task_manager: Option<Arc<TaskManager>>,
     role_manager: Arc<RoleManager>,
+    approval_gate: Arc<ApprovalGate>,
     hook_manager: Arc<HookManager>,
     sync_manager: Option<Arc<sync::SyncManager>>,
     config: BinderyConfig,
+    events: tokio::sync::broadcast::Sender<codex::CodexEvent>,
+    /// Running task executions, waited for on shutdown
+    executions: shutdown::WorkTracker,
+    /// Cancelled when shutdown begins
+    shutting_down: tokio_util::sync::CancellationToken,
+    /// When each trashed Codex was trashed
+    trash: tokio::sync::RwLock<HashMap<CodexId, DateTime<Utc>>>,
+    /// Receives trash, restore and purge events once attached
+    audit_logger: std::sync::OnceLock<Arc<observability::AuditLogger>>,
+    /// Indexes imported documents once attached
+    rag_service: std::sync::OnceLock<Arc<rag::RAGService>>,
+}
+
+/// Template a Codex was created from, as recorded in its metadata
+fn template_of(crdt: &crdt::VesperaCRDT) -> Option<String> {
+    match crdt.get_metadata("template_id") {
+        Some(crdt::TemplateValue::Text { value, .. }) => Some(value.clone()),
+        _ => None,
+    }
 }
 
 /// Configuration options for Vespera Bindery
@@ -203,6 +261,13 @@ pub struct BinderyConfig {
     /// Database connection pool configuration
     pub database_pool: database::DatabasePoolConfig,
 
+    /// Scheduled database backups (disabled when `None`)
+    pub backup: Option<database::BackupConfig>,
+
+    /// Storage backend for the task/Codex index (SQLite by default)
+    #[serde(default)]
+    pub storage: database::StorageConfig,
+
     /// Audit logging configuration
     pub audit_config: Option<observability::AuditConfig>,
 
@@ -221,6 +286,11 @@ pub struct BinderyConfig {
     /// Enable compression for stored operations
     pub compression_enabled: bool,
 
+    /// Codec used for persisted Codices and sync payloads when compression
+    /// is enabled
+    #[serde(default)]
+    pub compression: compression::CompressionConfig,
+
     /// User ID for this instance (for collaboration)
     pub user_id: Option<UserId>,
 
@@ -229,6 +299,36 @@ pub struct BinderyConfig {
 
     /// Enable security audit logging
     pub audit_logging_enabled: bool,
+
+    /// How long shutdown may take before unfinished work is abandoned (in seconds)
+    #[serde(default = "default_shutdown_timeout_seconds")]
+    pub shutdown_timeout_seconds: u64,
+
+    /// How long trashed Codices can be restored before garbage collection
+    /// deletes them (in days)
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+
+    /// Limits on task creation, task execution and LLM token use
+    #[serde(default)]
+    pub quotas: task_management::QuotaConfig,
+
+    /// Rate and size limits on operations received from collaborators
+    #[serde(default)]
+    pub sync_limits: sync::SyncLimits,
+
+    /// The role each user acts in, by user ID; gated actions look the role
+    /// up here rather than trusting the caller
+    #[serde(default)]
+    pub user_roles: HashMap<UserId, String>,
+}
+
+fn default_shutdown_timeout_seconds() -> u64 {
+    shutdown::DEFAULT_SHUTDOWN_TIMEOUT.as_secs()
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
 }
 
 impl Default for BinderyConfig {
@@ -238,20 +338,37 @@ impl Default for BinderyConfig {
             database_path: None,
             audit_db_path: None,
             database_pool: database::DatabasePoolConfig::default(),
+            backup: None,
+            storage: database::StorageConfig::default(),
             audit_config: None,
             collaboration_enabled: false,
             max_operations_in_memory: 1000,
             auto_gc_enabled: true,
             gc_interval_seconds: 300, // 5 minutes
             compression_enabled: true,
+            compression: compression::CompressionConfig::default(),
             user_id: None,
             project_id: None,
             audit_logging_enabled: false,
+            shutdown_timeout_seconds: default_shutdown_timeout_seconds(),
+            trash_retention_days: default_trash_retention_days(),
+            quotas: task_management::QuotaConfig::default(),
+            sync_limits: sync::SyncLimits::default(),
+            user_roles: HashMap::new(),
         }
     }
 }
 
 impl BinderyConfig {
+    /// The compression to apply; no compression when `compression_enabled` is off
+    pub fn effective_compression(&self) -> compression::CompressionConfig {
+        if self.compression_enabled {
+            self.compression.clone()
+        } else {
+            compression::CompressionConfig::none()
+        }
+    }
+
     /// Validate the configuration and return any errors
     pub fn validate(&self) -> BinderyResult<()> {
         // Validate memory limits
@@ -280,6 +397,15 @@ impl BinderyConfig {
             ));
         }
 
+        if self.shutdown_timeout_seconds == 0 {
+            return Err(BinderyError::ConfigurationError(
+                "shutdown_timeout_seconds must be greater than 0".to_string()
+            ));
+        }
+
+        self.compression.validate()?;
+        self.sync_limits.validate()?;
+
         // Validate storage path if provided
         if let Some(ref path) = self.storage_path {
             if !path.is_absolute() {
@@ -318,6 +444,14 @@ impl BinderyConfig {
         // Validate database pool configuration
         self.database_pool.validate()?;
 
+        // Validate backup configuration if provided
+        if let Some(ref backup) = self.backup {
+            backup.validate()?;
+        }
+
+        // Validate storage backend configuration
+        self.storage.validate()?;
+
         // Validate audit configuration if provided
         if let Some(ref audit_config) = self.audit_config {
             observability::validate_audit_config(audit_config)?;
@@ -398,6 +532,20 @@ impl BinderyConfig {
         Ok(self)
     }
 
+    /// Enable scheduled database backups
+    pub fn with_backup(mut self, backup: database::BackupConfig) -> BinderyResult<Self> {
+        backup.validate()?;
+        self.backup = Some(backup);
+        Ok(self)
+    }
+
+    /// Select the storage backend for the task/Codex index
+    pub fn with_storage(mut self, storage: database::StorageConfig) -> BinderyResult<Self> {
+        storage.validate()?;
+        self.storage = storage;
+        Ok(self)
+    }
+
     /// Enable collaboration with required fields
     pub fn with_collaboration(
         mut self,
@@ -449,15 +597,23 @@ pub struct BinderyConfigBuilder {
     database_path: Option<std::path::PathBuf>,
     audit_db_path: Option<std::path::PathBuf>,
     database_pool: Option<database::DatabasePoolConfig>,
+    backup: Option<database::BackupConfig>,
+    storage: Option<database::StorageConfig>,
     audit_config: Option<observability::AuditConfig>,
     collaboration_enabled: bool,
     max_operations_in_memory: Option<usize>,
     auto_gc_enabled: Option<bool>,
     gc_interval_seconds: Option<u64>,
     compression_enabled: Option<bool>,
+    compression: Option<compression::CompressionConfig>,
     user_id: Option<UserId>,
     project_id: Option<ProjectId>,
     audit_logging_enabled: bool,
+    shutdown_timeout_seconds: Option<u64>,
+    trash_retention_days: Option<u32>,
+    quotas: Option<task_management::QuotaConfig>,
+    sync_limits: Option<sync::SyncLimits>,
+    user_roles: HashMap<UserId, String>,
 }
 
 impl BinderyConfigBuilder {
@@ -504,6 +660,18 @@ impl BinderyConfigBuilder {
         Ok(self)
     }
 
+    pub fn backup(mut self, config: database::BackupConfig) -> BinderyResult<Self> {
+        config.validate()?;
+        self.backup = Some(config);
+        Ok(self)
+    }
+
+    pub fn storage(mut self, config: database::StorageConfig) -> BinderyResult<Self> {
+        config.validate()?;
+        self.storage = Some(config);
+        Ok(self)
+    }
+
     pub fn audit_config(mut self, config: observability::AuditConfig) -> BinderyResult<Self> {
         observability::validate_audit_config(&config)?;
         self.audit_config = Some(config);
@@ -563,26 +731,70 @@ impl BinderyConfigBuilder {
         self
     }
 
+    pub fn compression(mut self, compression: compression::CompressionConfig) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
     pub fn audit_logging_enabled(mut self, enabled: bool) -> Self {
         self.audit_logging_enabled = enabled;
         self
     }
 
+    pub fn shutdown_timeout(mut self, timeout: std::time::Duration) -> BinderyResult<Self> {
+        if timeout.as_secs() == 0 {
+            return Err(BinderyError::ConfigurationError(
+                "shutdown_timeout_seconds must be greater than 0".to_string()
+            ));
+        }
+        self.shutdown_timeout_seconds = Some(timeout.as_secs());
+        Ok(self)
+    }
+
+    pub fn trash_retention_days(mut self, days: u32) -> Self {
+        self.trash_retention_days = Some(days);
+        self
+    }
+
+    pub fn quotas(mut self, quotas: task_management::QuotaConfig) -> Self {
+        self.quotas = Some(quotas);
+        self
+    }
+
+    pub fn sync_limits(mut self, limits: sync::SyncLimits) -> Self {
+        self.sync_limits = Some(limits);
+        self
+    }
+
+    /// Make `user_id` act in the role `role_name`
+    pub fn user_role(mut self, user_id: impl Into<UserId>, role_name: impl Into<String>) -> Self {
+        self.user_roles.insert(user_id.into(), role_name.into());
+        self
+    }
+
     pub fn build(self) -> BinderyResult<BinderyConfig> {
         let config = BinderyConfig {
             storage_path: self.storage_path,
             database_path: self.database_path,
             audit_db_path: self.audit_db_path,
             database_pool: self.database_pool.unwrap_or_default(),
+            backup: self.backup,
+            storage: self.storage.unwrap_or_default(),
             audit_config: self.audit_config,
             collaboration_enabled: self.collaboration_enabled,
             max_operations_in_memory: self.max_operations_in_memory.unwrap_or(1000),
             auto_gc_enabled: self.auto_gc_enabled.unwrap_or(true),
             gc_interval_seconds: self.gc_interval_seconds.unwrap_or(300),
             compression_enabled: self.compression_enabled.unwrap_or(true),
+            compression: self.compression.unwrap_or_default(),
             user_id: self.user_id,
             project_id: self.project_id,
             audit_logging_enabled: self.audit_logging_enabled,
+            shutdown_timeout_seconds: self.shutdown_timeout_seconds.unwrap_or_else(default_shutdown_timeout_seconds),
+            trash_retention_days: self.trash_retention_days.unwrap_or_else(default_trash_retention_days),
+            quotas: self.quotas.unwrap_or_default(),
+            sync_limits: self.sync_limits.unwrap_or_default(),
+            user_roles: self.user_roles,
         };
 
         config.validate()?;
@@ -600,7 +812,7 @@ impl CodexManager {
     pub fn with_config(config: BinderyConfig) -> Result<Self> {
         // Validate configuration before proceeding
         config.validate().map_err(|e| anyhow::anyhow!("Configuration validation failed: {}", e))?;
-        let templates = Arc::new(templates::TemplateRegistry::new());
+        let templates = tokio::sync::RwLock::new(templates::TemplateRegistry::new());
 
         let sync_manager = if config.collaboration_enabled {
             Some(Arc::new(sync::SyncManager::new(config.clone())?))
@@ -608,21 +820,29 @@ impl CodexManager {
             None
         };
 
-        // Initialize role and hook managers first
-        let role_manager = Arc::new(RoleManager::default());
-        let hook_manager = Arc::new(HookManager::default());
+        // Initialize role manager first; the hook manager refers back to
+        // this manager, so it is built along with it
+        let role_manager = Arc::new(RoleManager::default().with_user_roles(config.user_roles.clone()));
 
         let manager = Self {
-            inner: Arc::new(CodexManagerInner {
+            inner: Arc::new_cyclic(|inner| CodexManagerInner {
                 codices: tokio::sync::RwLock::new(HashMap::new()),
                 templates,
                 task_manager: None, // Will be initialized below
                 role_manager: role_manager.clone(),
-                hook_manager: hook_manager.clone(),
+                approval_gate: Arc::new(ApprovalGate::new(role_manager.clone())),
+                hook_manager: Arc::new(HookManager::for_owner(inner.clone())),
                 sync_manager,
                 config,
+                events: tokio::sync::broadcast::channel(codex::events::EVENT_CAPACITY).0,
+                executions: shutdown::WorkTracker::new(),
+                shutting_down: tokio_util::sync::CancellationToken::new(),
+                trash: tokio::sync::RwLock::new(HashMap::new()),
+                audit_logger: std::sync::OnceLock::new(),
+                rag_service: std::sync::OnceLock::new(),
             }),
         };
+        let hook_manager = manager.inner.hook_manager.clone();
 
         // Initialize task manager after CodexManager creation to avoid circular dependency
         // Use a simplified initialization that doesn't require database configuration
@@ -664,30 +884,58 @@ impl CodexManager {
 
         // Verify template exists
         let template_registry_id = templates::TemplateId::new(template_id.to_string());
-        let _template = self.inner.templates.get(&template_registry_id)
-            .ok_or_else(|| BinderyError::TemplateNotFound(template_registry_id))?;
+        if self.inner.templates.read().await.get(&template_registry_id).is_none() {
+            return Err(BinderyError::TemplateNotFound(template_registry_id));
+        }
 
+        let crdt = self.new_codex_crdt(id, &title, &template_id.to_string())?;
+        self.insert_new_codices(vec![crdt]).await?;
+        Ok(id)
+    }
+
+    /// A Codex with its title and template set, not yet added to the manager
+    pub(crate) fn new_codex_crdt(&self, id: CodexId, title: &str, template_id: &str) -> BinderyResult<crdt::VesperaCRDT> {
         let created_by = self.inner.config.user_id.clone().unwrap_or_else(|| "system".to_string());
         let mut crdt = crdt::VesperaCRDT::new(id, created_by);
 
         // Initialize the CRDT with title and template metadata
         // Note: This is a simplified implementation - full template integration would require
         // loading template fields and creating appropriate CRDT structures
-        crdt.set_title(&title);
-
-        let crdt = Arc::new(crdt);
+        crdt.set_title(title);
+        crdt.set_metadata("template_id".to_string(), crdt::TemplateValue::Text {
+            value: template_id.to_string(),
+            timestamp: Utc::now(),
+            user_id: crdt.created_by.clone(),
+        })?;
+        Ok(crdt)
+    }
 
+    /// Add Codices built with [`new_codex_crdt`](Self::new_codex_crdt) under
+    /// one write lock, then publish a `Created` event for each in order
+    pub(crate) async fn insert_new_codices(&self, new: Vec<crdt::VesperaCRDT>) -> BinderyResult<()> {
+        let new: Vec<Arc<crdt::VesperaCRDT>> = new.into_iter().map(Arc::new).collect();
         {
             let mut codices = self.inner.codices.write().await;
-            codices.insert(id, crdt.clone());
+            for crdt in &new {
+                codices.insert(crdt.codex_id, crdt.clone());
+            }
         }
 
         // If collaboration is enabled, register with sync manager
         if let Some(sync_manager) = &self.inner.sync_manager {
-            sync_manager.register_codex(id, crdt).await?;
+            for crdt in &new {
+                sync_manager.register_codex(crdt.codex_id, crdt.clone()).await?;
+            }
         }
 
-        Ok(id)
+        for crdt in new {
+            self.publish(codex::CodexEvent::new(
+                crdt.codex_id,
+                template_of(&crdt),
+                codex::CodexChange::Created { title: crdt.get_title().unwrap_or_default() },
+            ));
+        }
+        Ok(())
     }
 
     /// Get an existing Codex by ID
@@ -696,25 +944,372 @@ impl CodexManager {
         codices.get(id).cloned()
     }
 
-    /// List all Codex IDs
+    /// List all Codex IDs, except those in the trash
     pub async fn list_codices(&self) -> Vec<CodexId> {
         let codices = self.inner.codices.read().await;
-        codices.keys().copied().collect()
+        let trash = self.inner.trash.read().await;
+        codices.keys().filter(|id| !trash.contains_key(id)).copied().collect()
+    }
+
+    /// One page of Codex IDs, except those in the trash, in ID order
+    pub async fn list_codices_page(&self, request: &PageRequest) -> BinderyResult<Page<CodexId>> {
+        let after = match &request.cursor {
+            Some(cursor) => {
+                let parts = pagination::decode_cursor(cursor, 1)?;
+                Some(parts[0].parse::<CodexId>()
+                    .map_err(|_| BinderyError::InvalidInput(format!("Invalid page cursor: {}", cursor)))?)
+            }
+            None => None,
+        };
+        let page_size = request.page_size();
+
+        let mut ids: Vec<CodexId> = {
+            let codices = self.inner.codices.read().await;
+            let trash = self.inner.trash.read().await;
+            codices.keys()
+                .filter(|id| !trash.contains_key(id) && after.is_none_or(|after| **id > after))
+                .copied()
+                .collect()
+        };
+        // Only the head of the order is needed
+        if ids.len() > page_size + 1 {
+            ids.select_nth_unstable(page_size);
+            ids.truncate(page_size + 1);
+        }
+        ids.sort_unstable();
+        Ok(Page::from_overfetch(ids, page_size, |id| pagination::encode_cursor(&[&id.to_string()])))
+    }
+
+    /// Copy a Codex as a new one, returning the copy's ID
+    ///
+    /// With `include_children` its tree children are copied too, recursively,
+    /// and references between the copied Codices point at the copies. Copies
+    /// start with a fresh history.
+    pub async fn duplicate_codex(&self, id: &CodexId, options: codex::DuplicateOptions) -> BinderyResult<CodexId> {
+        let created_by = self.inner.config.user_id.clone().unwrap_or_else(|| "system".to_string());
+        let mut copies = {
+            let codices = self.inner.codices.read().await;
+            let source = codices.get(id)
+                .ok_or_else(|| BinderyError::NotFound(format!("Codex {}", id)))?;
+
+            let mut originals = vec![Arc::clone(source)];
+            let mut seen = std::collections::HashSet::from([*id]);
+            let mut next = 0;
+            while options.include_children && next < originals.len() {
+                let parent = Arc::clone(&originals[next]);
+                next += 1;
+                for child in parent.tree_layer.get_children(Some(parent.codex_id)) {
+                    if let Some(crdt) = codices.get(&child).filter(|_| seen.insert(child)) {
+                        originals.push(Arc::clone(crdt));
+                    }
+                }
+            }
+
+            let id_map: HashMap<CodexId, CodexId> = originals.iter()
+                .map(|original| (original.codex_id, Uuid::new_v4()))
+                .collect();
+            originals.iter()
+                .map(|original| original.duplicate(id_map[&original.codex_id], created_by.clone(), &id_map))
+                .collect::<BinderyResult<Vec<_>>>()?
+        };
+
+        let title = options.title
+            .unwrap_or_else(|| format!("{} (copy)", copies[0].get_title().unwrap_or_default()));
+        copies[0].set_title(&title)?;
+        let copy_id = copies[0].codex_id;
+
+        self.insert_new_codices(copies).await?;
+        Ok(copy_id)
+    }
+
+    /// Register a template with the structure of an existing Codex, as
+    /// described at [`templates::Template::from_codex`]
+    ///
+    /// The template keeps the content type of the Codex's own template.
+    /// Fails if `template_id` is already registered.
+    pub async fn save_as_template(
+        &self,
+        id: &CodexId,
+        template_id: impl Into<TemplateId>,
+        name: impl Into<String>,
+    ) -> BinderyResult<templates::Template> {
+        let crdt = self.get_codex(id).await
+            .ok_or_else(|| BinderyError::NotFound(format!("Codex {}", id)))?;
+        let template_id = template_id.into();
+        let mut template = templates::Template::from_codex(template_id.clone(), name.into(), &crdt);
+
+        let mut registry = self.inner.templates.write().await;
+        if registry.get(&template_id).is_some() {
+            return Err(BinderyError::TemplateRegistrationError(
+                format!("Template {} already exists", template_id)
+            ));
+        }
+        if let Some(source) = template_of(&crdt).and_then(|source| registry.get(&TemplateId::new(source))) {
+            template.content_type = source.content_type.clone();
+            template.metadata.insert("source_template_id".to_string(), serde_json::json!(source.id));
+        }
+        registry.register(template.clone())?;
+        Ok(template)
     }
 
-    /// Delete a Codex
+    /// Register a template Codices can be created from
+    ///
+    /// Returns false, keeping the registered one, if the ID is taken.
+    pub async fn register_template(&self, template: templates::Template) -> BinderyResult<bool> {
+        let mut registry = self.inner.templates.write().await;
+        if registry.get(&template.id).is_some() {
+            return Ok(false);
+        }
+        registry.register(template)?;
+        Ok(true)
+    }
+
+    /// Delete several Codices permanently, returning how many were deleted
+    ///
+    /// This is a [`GatedAction::BulkDelete`]: if the role assigned to `user`
+    /// requires approval for it, the deletion is parked and this fails with
+    /// [`BinderyError::ApprovalRequired`] until approved and retried.
+    pub async fn delete_codices(&self, ids: &[CodexId], user: &observability::UserContext) -> BinderyResult<usize> {
+        let mut sorted: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        sorted.sort();
+        sorted.dedup();
+        self.inner.approval_gate.authorize(
+            user,
+            GatedAction::BulkDelete,
+            format!("codices:{}", sorted.join(",")),
+            format!("Permanently delete {} Codices", sorted.len()),
+        ).await?;
+
+        let mut deleted = 0;
+        for id in ids {
+            if self.purge_codex(id).await.map_err(|e| BinderyError::InternalError(e.to_string()))? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Delete a Codex by moving it, and the Codices below it, to the trash
+    ///
+    /// Returns false if there is no such Codex or it is already in the
+    /// trash. It can be brought back with [`restore_codex`](Self::restore_codex)
+    /// until garbage collection purges it;
+    /// [`delete_codices`](Self::delete_codices) deletes permanently right
+    /// away, subject to approval.
     pub async fn delete_codex(&self, id: &CodexId) -> Result<bool> {
+        match self.trash_codex(id).await {
+            Err(BinderyError::NotFound(_)) => Ok(false),
+            result => Ok(result?),
+        }
+    }
+
+    /// Delete a Codex permanently, whether or not it is in the trash
+    ///
+    /// Not public: outside callers go through the approval gate of
+    /// [`delete_codices`](Self::delete_codices), or delete restorably with
+    /// [`delete_codex`](Self::delete_codex).
+    pub(crate) async fn purge_codex(&self, id: &CodexId) -> Result<bool> {
         let mut codices = self.inner.codices.write().await;
-        let removed = codices.remove(id).is_some();
+        let removed = codices.remove(id);
+        drop(codices);
+        self.inner.trash.write().await.remove(id);
 
         // If collaboration is enabled, unregister from sync manager
         if let Some(sync_manager) = &self.inner.sync_manager {
             sync_manager.unregister_codex(id).await?;
         }
 
+        let Some(removed) = removed else {
+            return Ok(false);
+        };
+        self.publish(codex::CodexEvent::new(
+            *id,
+            template_of(&removed),
+            codex::CodexChange::Deleted { title: removed.get_title() },
+        ));
+
+        // References to a deleted Codex would dangle
+        for from_id in self.codices_referencing(id).await {
+            self.remove_references(&from_id, |crdt| crdt.remove_references_to(*id)).await?;
+        }
+        Ok(true)
+    }
+
+    /// Remove references to Codices that no longer exist, returning how many
+    /// were removed
+    ///
+    /// Deleting a Codex already removes references to it; this catches those
+    /// left by older versions or arriving from peers that had not seen the
+    /// deletion.
+    pub async fn prune_dangling_references(&self) -> BinderyResult<usize> {
+        let codices = self.inner.codices.read().await;
+        let existing: std::collections::HashSet<CodexId> = codices.keys().copied().collect();
+        let dangling: Vec<CodexId> = codices
+            .iter()
+            .filter(|(_, crdt)| crdt.get_references().iter().any(|reference| !existing.contains(&reference.to_codex_id)))
+            .map(|(id, _)| *id)
+            .collect();
+        drop(codices);
+
+        let mut pruned = 0;
+        for from_id in dangling {
+            pruned += self.remove_references(&from_id, |crdt| {
+                crdt.prune_references(|target| existing.contains(target))
+            }).await?;
+        }
+        if pruned > 0 {
+            tracing::info!(pruned, "Pruned references to deleted Codices");
+        }
+        Ok(pruned)
+    }
+
+    /// Codices with a reference to `target`
+    async fn codices_referencing(&self, target: &CodexId) -> Vec<CodexId> {
+        self.inner.codices.read().await
+            .iter()
+            .filter(|(_, crdt)| !crdt.references_to(target).is_empty())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Apply a removal of references to a Codex, publishing a
+    /// `ReferenceRemoved` event for each, and return how many were removed
+    async fn remove_references(
+        &self,
+        id: &CodexId,
+        change: impl FnOnce(&mut crdt::VesperaCRDT) -> BinderyResult<Vec<crdt::CodexReference>>,
+    ) -> BinderyResult<usize> {
+        let (removed, template_id) = match self.modify_codex(id, change).await {
+            Ok(result) => result,
+            // Deleted concurrently; nothing left to clean up
+            Err(BinderyError::NotFound(_)) => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        let count = removed.len();
+        for reference in removed {
+            self.publish(codex::CodexEvent::new(*id, template_id.clone(), codex::CodexChange::ReferenceRemoved { reference }));
+        }
+        Ok(count)
+    }
+
+    /// Set metadata fields of a Codex (its title and template fields)
+    ///
+    /// Subscribers receive one `Updated` event listing each field's old and
+    /// new value.
+    pub async fn set_codex_fields(
+        &self,
+        id: &CodexId,
+        fields: impl IntoIterator<Item = (String, crdt::TemplateValue)>,
+    ) -> BinderyResult<()> {
+        let (changes, template_id) = self.modify_codex(id, |crdt| {
+            fields
+                .into_iter()
+                .map(|(field, new)| {
+                    let old = crdt.get_metadata(&field).cloned();
+                    crdt.set_metadata(field.clone(), new.clone())?;
+                    Ok(codex::FieldChange { field, old, new })
+                })
+                .collect::<BinderyResult<Vec<_>>>()
+        }).await?;
+
+        if !changes.is_empty() {
+            self.publish(codex::CodexEvent::new(*id, template_id, codex::CodexChange::Updated { fields: changes }));
+        }
+        Ok(())
+    }
+
+    /// Add a reference from `reference.from_codex_id` to another Codex
+    ///
+    /// Returns false, without publishing an event, if it was already there.
+    pub async fn add_reference(&self, reference: crdt::CodexReference) -> BinderyResult<bool> {
+        let id = reference.from_codex_id;
+        let (added, template_id) = self.modify_codex(&id, |crdt| {
+            if crdt.reference_layer.contains(&reference) {
+                return Ok(false);
+            }
+            crdt.add_reference(reference.clone())?;
+            Ok(true)
+        }).await?;
+
+        if added {
+            self.publish(codex::CodexEvent::new(id, template_id, codex::CodexChange::ReferenceAdded { reference }));
+        }
+        Ok(added)
+    }
+
+    /// Remove a reference from `reference.from_codex_id`
+    ///
+    /// Returns false, without publishing an event, if there was none.
+    pub async fn remove_reference(&self, reference: crdt::CodexReference) -> BinderyResult<bool> {
+        let id = reference.from_codex_id;
+        let (removed, template_id) = self.modify_codex(&id, |crdt| {
+            if !crdt.reference_layer.contains(&reference) {
+                return Ok(false);
+            }
+            crdt.remove_reference(reference.clone())?;
+            Ok(true)
+        }).await?;
+
+        if removed {
+            self.publish(codex::CodexEvent::new(id, template_id, codex::CodexChange::ReferenceRemoved { reference }));
+        }
         Ok(removed)
     }
 
+    /// Changes matching `filter`, from now until the stream is dropped
+    ///
+    /// A subscriber more than [`codex::events::EVENT_CAPACITY`] events behind
+    /// misses the oldest of them; a warning is logged when that happens.
+    pub fn subscribe(&self, filter: codex::CodexEventFilter) -> impl futures::Stream<Item = codex::CodexEvent> + Send + 'static {
+        let mut events = self.inner.events.subscribe();
+        let shutting_down = self.inner.shutting_down.clone();
+        async_stream::stream! {
+            loop {
+                let received = tokio::select! {
+                    received = events.recv() => received,
+                    _ = shutting_down.cancelled() => break,
+                };
+                match received {
+                    Ok(event) if filter.matches(&event) => yield event,
+                    Ok(_) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "Codex event subscriber fell behind");
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Apply `change` to a Codex, returning its result and the Codex's template
+    ///
+    /// The Codex is copied on write: readers holding the previous `Arc` keep
+    /// the state they saw.
+    async fn modify_codex<T>(
+        &self,
+        id: &CodexId,
+        change: impl FnOnce(&mut crdt::VesperaCRDT) -> BinderyResult<T>,
+    ) -> BinderyResult<(T, Option<String>)> {
+        let mut codices = self.inner.codices.write().await;
+        let crdt = codices.get_mut(id)
+            .ok_or_else(|| BinderyError::NotFound(format!("Codex {}", id)))?;
+        let result = change(Arc::make_mut(crdt))?;
+        let template_id = template_of(crdt);
+        let crdt = Arc::clone(crdt);
+        drop(codices);
+
+        // The sync manager tracks the replaced copy; point it at the new one
+        if let Some(sync_manager) = &self.inner.sync_manager {
+            sync_manager.register_codex(*id, crdt).await?;
+        }
+        Ok((result, template_id))
+    }
+
+    fn publish(&self, event: codex::CodexEvent) {
+        // No subscribers is not an error
+        let _ = self.inner.events.send(event);
+    }
+
     /// Enable collaboration for this CodexManager
     pub async fn enable_collaboration(&mut self) -> Result<()> {
         if self.inner.sync_manager.is_some() {
@@ -744,6 +1339,11 @@ impl CodexManager {
         &self.inner.config
     }
 
+    /// A serializer for `format` that compresses as this workspace is configured to
+    pub fn codex_serializer(&self, format: codex::CodexFormat) -> codex::CodexSerializer {
+        codex::CodexSerializer::new(format).with_codec(self.inner.config.effective_compression())
+    }
+
     /// Get a task manager instance
     ///
     /// This method creates a new TaskManager instance on demand to avoid
@@ -756,66 +1356,92 @@ impl CodexManager {
         ))
     }
 
+    /// Get the hook manager whose hooks run on task events
+    pub fn get_hook_manager(&self) -> Arc<HookManager> {
+        self.inner.hook_manager.clone()
+    }
+
+    /// Get the role manager used when assigning and executing tasks
+    pub fn get_role_manager(&self) -> Arc<RoleManager> {
+        self.inner.role_manager.clone()
+    }
+
+    /// Get the approval gate for destructive operations
+    pub fn get_approval_gate(&self) -> Arc<ApprovalGate> {
+        self.inner.approval_gate.clone()
+    }
+
+    /// Record trash, restore, purge and approval events, and sync access
+    /// refused by Codex access control lists, in `logger`
+    ///
+    /// Returns false, leaving the first in place, if one was already attached.
+    pub fn attach_audit_logger(&self, logger: Arc<observability::AuditLogger>) -> bool {
+        self.inner.approval_gate.attach_audit_logger(logger.clone());
+        if let Some(sync_manager) = &self.inner.sync_manager {
+            sync_manager.attach_audit_logger(logger.clone());
+        }
+        self.inner.audit_logger.set(logger).is_ok()
+    }
+
+    /// Index the sections of imported documents in `rag`
+    ///
+    /// Returns false, leaving the first in place, if one was already attached.
+    pub fn attach_rag_service(&self, rag: Arc<rag::RAGService>) -> bool {
+        self.inner.rag_service.set(rag).is_ok()
+    }
+
     /// Perform garbage collection on all managed Codices
     pub async fn gc_all_codices(&self) -> Result<CodexManagerGCStats> {
         self.gc_all_codices_with_config(GarbageCollectionConfig::default()).await
     }
 
     /// Perform garbage collection on all managed Codices with custom configuration
+    ///
+    /// Also deletes Codices that have been in the trash longer than
+    /// `BinderyConfig::trash_retention_days`. Codices are collected in
+    /// parallel without holding the manager lock; see [`codex::bulk`].
     pub async fn gc_all_codices_with_config(&self, config: GarbageCollectionConfig) -> Result<CodexManagerGCStats> {
-        let mut total_stats = CodexManagerGCStats::default();
+        let mut total_stats = CodexManagerGCStats {
+            trash_purged: self.purge_trash().await?,
+            ..Default::default()
+        };
         let cutoff = chrono::Utc::now() - chrono::Duration::hours(config.cutoff_hours);
-        let mut processed_codices = Vec::new();
-
-        {
-            let codices = self.inner.codices.read().await;
-
-            // First pass: collect IDs and check memory usage
-            for (id, crdt) in codices.iter() {
-                total_stats.codices_processed += 1;
-                let memory_stats = crdt.memory_stats();
-                total_stats.total_memory_before += memory_stats.total_size_bytes;
-
-                if memory_stats.total_size_bytes > config.memory_threshold_bytes {
-                    processed_codices.push(*id);
-                }
+        let codices = self.codex_list().await;
+        let outcomes = tokio::task::spawn_blocking(move || {
+            let outcomes = codex::bulk::collect_garbage(&codices, &config, cutoff);
+            outcomes.into_iter().zip(codices).collect::<Vec<_>>()
+        }).await.map_err(|e| BinderyError::InternalError(e.to_string()))?;
+
+        let mut collected = Vec::new();
+        for (outcome, (id, original)) in outcomes {
+            total_stats.codices_processed += 1;
+            total_stats.total_memory_before += outcome.memory_before;
+            if outcome.collected.is_some() {
+                collected.push((id, original, outcome));
+            } else {
+                total_stats.total_memory_after += outcome.memory_before;
             }
         }
 
-        // Second pass: perform actual GC with write locks
-        for codex_id in processed_codices {
-            if let Some(crdt) = {
-                let codices = self.inner.codices.read().await;
-                codices.get(&codex_id).cloned()
-            } {
-                // Perform GC operations on the individual CRDT
-                let mut crdt_clone = (*crdt).clone();
-                let gc_stats = crdt_clone.gc_all_with_limits(
-                    cutoff,
-                    config.max_operations_per_codex,
-                    config.max_tree_tombstones_per_codex
-                );
-
-                // Update the CRDT in the map if GC made significant changes
-                if gc_stats.operations_removed > 0 || gc_stats.tree_tombstones_removed > 0 {
-                    let mut codices = self.inner.codices.write().await;
-                    codices.insert(codex_id, Arc::new(crdt_clone));
-
-                    total_stats.operations_removed += gc_stats.operations_removed;
-                    total_stats.tree_tombstones_removed += gc_stats.tree_tombstones_removed;
-                    total_stats.memory_freed_bytes += gc_stats.memory_freed_bytes;
+        let mut codices = self.inner.codices.write().await;
+        for (id, original, outcome) in collected {
+            let Some(crdt) = outcome.collected else { continue };
+            // A Codex changed since the list was taken is left for the next run
+            match codices.get_mut(&id) {
+                Some(current) if Arc::ptr_eq(current, &original) => *current = Arc::new(crdt),
+                _ => {
+                    total_stats.total_memory_after += outcome.memory_before;
+                    continue;
                 }
             }
-        }
-
-        // Calculate final memory usage
-        {
-            let codices = self.inner.codices.read().await;
-            for crdt in codices.values() {
-                let memory_stats = crdt.memory_stats();
-                total_stats.total_memory_after += memory_stats.total_size_bytes;
+            total_stats.total_memory_after += outcome.memory_after;
+            if let Some(stats) = outcome.stats {
+                total_stats.operations_removed += stats.operations_removed;
+                total_stats.tree_tombstones_removed += stats.tree_tombstones_removed;
+                total_stats.memory_freed_bytes += stats.memory_freed_bytes;
             }
         }
+        drop(codices);
 
         Ok(total_stats)
     }
@@ -829,20 +1455,68 @@ impl CodexManager {
             .collect()
     }
 
-    /// Clean up all resources and shut down the manager
-    pub async fn shutdown(&mut self) -> Result<()> {
-        // Stop sync manager first
-        if let Some(sync_manager) = &self.inner.sync_manager {
-            sync_manager.stop().await?;
-        }
+    /// Whether shutdown has begun; new task executions are refused from then on
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutting_down.is_cancelled()
+    }
 
-        // Clear all Codices (this will trigger Drop implementations)
-        {
-            let mut codices = self.inner.codices.write().await;
-            codices.clear();
+    /// Tracks task executions so shutdown can wait for them
+    pub(crate) fn execution_tracker(&self) -> &shutdown::WorkTracker {
+        &self.inner.executions
+    }
+
+    /// A shutdown coordinator with this manager's steps, to which embedding
+    /// applications add the subsystems they own (database, audit log, RAG,
+    /// providers) before running it
+    ///
+    /// New task executions are refused, the hook scheduler stops and change
+    /// subscriptions end; running executions are then drained, and sync is
+    /// stopped and the Codices released. The deadline is
+    /// `shutdown_timeout_seconds` from the configuration.
+    pub fn shutdown_coordinator(&self) -> shutdown::ShutdownCoordinator {
+        use shutdown::ShutdownPhase;
+
+        let deadline = std::time::Duration::from_secs(self.inner.config.shutdown_timeout_seconds);
+        let (inner, hooks) = (Arc::clone(&self.inner), Arc::clone(&self.inner.hook_manager));
+        let mut coordinator = shutdown::ShutdownCoordinator::new(deadline)
+            .with_step(ShutdownPhase::StopIntake, "task_executions", {
+                let inner = Arc::clone(&inner);
+                move || async move {
+                    inner.shutting_down.cancel();
+                    Ok(())
+                }
+            })
+            .with_step(ShutdownPhase::StopIntake, "hook_scheduler", move || async move {
+                hooks.stop_scheduler().await;
+                Ok(())
+            })
+            .with_step(ShutdownPhase::Drain, "task_executions", {
+                let inner = Arc::clone(&inner);
+                move || async move {
+                    inner.executions.wait_idle().await;
+                    Ok(())
+                }
+            });
+
+        if let Some(sync_manager) = self.inner.sync_manager.clone() {
+            coordinator.add_step(ShutdownPhase::Close, "sync", move || async move {
+                sync_manager.stop().await.map_err(anyhow::Error::from)
+            });
         }
 
-        Ok(())
+        // Releasing the Codices runs their Drop implementations
+        coordinator.with_step(ShutdownPhase::Close, "codices", move || async move {
+            inner.codices.write().await.clear();
+            Ok(())
+        })
+    }
+
+    /// Shut down this manager within the configured deadline
+    ///
+    /// Fails if a step failed or was abandoned at the deadline; the report
+    /// lists each step either way.
+    pub async fn shutdown(&mut self) -> Result<shutdown::ShutdownReport> {
+        self.shutdown_coordinator().shutdown().await.into_result()
     }
 }
 
@@ -879,6 +1553,8 @@ pub struct CodexManagerGCStats {
     pub memory_freed_bytes: usize,
     pub total_memory_before: usize,
     pub total_memory_after: usize,
+    /// Codices deleted from the trash once their retention window passed
+    pub trash_purged: usize,
 }
 
 // Version information
@@ -914,5 +1590,17 @@ fn get_enabled_features() -> String {
     #[cfg(feature = "relay-sync")]
     features.push("relay-sync");
 
+    #[cfg(feature = "grpc")]
+    features.push("grpc");
+
+    #[cfg(feature = "metrics")]
+    features.push("metrics");
+
+    #[cfg(feature = "postgres")]
+    features.push("postgres");
+
+    #[cfg(feature = "db-encryption")]
+    features.push("db-encryption");
+
     features.join(",")
 }
\ No newline at end of file
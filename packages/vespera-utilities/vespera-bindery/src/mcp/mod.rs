@@ -0,0 +1,348 @@
+//! Model Context Protocol (MCP) server
+//!
+//! Serves Bindery operations as MCP tools, so MCP clients (desktop assistants,
+//! IDE agents, ...) can use the Rust backend directly instead of going through
+//! the Python MCP layer. [`McpServer`] implements the protocol: the
+//! `initialize` handshake, `ping`, `tools/list` and `tools/call`. What the
+//! tools do is up to a [`ToolHandler`], and the [`transport`] module carries
+//! messages over stdio or HTTP with Server-Sent Events.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use async_trait::async_trait;
+//! use serde_json::{json, Value};
+//! use vespera_bindery::mcp::{self, McpServer, Tool, ToolHandler};
+//!
+//! struct Echo;
+//!
+//! #[async_trait]
+//! impl ToolHandler for Echo {
+//!     fn tools(&self) -> Vec<Tool> {
+//!         vec![Tool::new("echo", "Return the arguments", json!({"type": "object"}))]
+//!     }
+//!
+//!     async fn call_tool(&self, _name: &str, arguments: Value) -> anyhow::Result<Value> {
+//!         Ok(arguments)
+//!     }
+//! }
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! mcp::serve_stdio(Arc::new(McpServer::new(Echo))).await
+//! # }
+//! ```
+
+pub mod transport;
+
+pub use transport::{serve_io, serve_sse, serve_stdio, sse_router};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Protocol revisions this server speaks, newest first
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-03-26", "2024-11-05"];
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+
+/// A tool offered to MCP clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema of the tool's arguments
+    pub input_schema: Value,
+}
+
+impl Tool {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, input_schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            input_schema,
+        }
+    }
+}
+
+/// Provides and runs the tools of an [`McpServer`]
+#[async_trait]
+pub trait ToolHandler: Send + Sync {
+    /// Tools offered to clients
+    fn tools(&self) -> Vec<Tool>;
+
+    /// Run the tool `name` with `arguments`
+    ///
+    /// An error is reported to the client as a failed tool call, which the
+    /// model can see and react to, rather than as a protocol error.
+    async fn call_tool(&self, name: &str, arguments: Value) -> anyhow::Result<Value>;
+}
+
+/// MCP protocol handler, independent of transport
+pub struct McpServer {
+    handler: Arc<dyn ToolHandler>,
+    name: String,
+    version: String,
+    instructions: Option<String>,
+}
+
+impl McpServer {
+    pub fn new(handler: impl ToolHandler + 'static) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            name: "vespera-bindery".to_string(),
+            version: crate::VERSION.to_string(),
+            instructions: None,
+        }
+    }
+
+    /// Usage hints sent to clients on initialization
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Handle one serialized message (a request, notification or batch)
+    ///
+    /// Returns the serialized response, or None when there is nothing to send
+    /// back (notifications, and responses from the client).
+    pub async fn handle_text(&self, text: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(text) {
+            Ok(message) => self.handle_message(message).await?,
+            Err(e) => error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e)),
+        };
+        Some(response.to_string())
+    }
+
+    /// Handle one parsed message; see [`McpServer::handle_text`]
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        match message {
+            Value::Array(batch) => {
+                if batch.is_empty() {
+                    return Some(error_response(Value::Null, INVALID_REQUEST, "Empty batch".to_string()));
+                }
+                let mut responses = Vec::new();
+                for message in batch {
+                    if let Some(response) = self.handle_single(message).await {
+                        responses.push(response);
+                    }
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle_single(message).await,
+        }
+    }
+
+    async fn handle_single(&self, message: Value) -> Option<Value> {
+        let request = match serde_json::from_value::<Request>(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, INVALID_REQUEST, format!("Invalid request: {}", e))),
+        };
+        let Some(method) = request.method else {
+            // A response to something we sent; this server sends no requests
+            debug!("Ignoring MCP message without a method");
+            return None;
+        };
+
+        let result = self.dispatch(&method, request.params.unwrap_or(Value::Null)).await;
+        // Notifications get no response, even when they fail
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, message),
+        })
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value, (i32, String)> {
+        match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "notifications/initialized" | "notifications/cancelled" => Ok(Value::Null),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.handler.tools() })),
+            "tools/call" => self.call_tool(params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method '{}' not found", method))),
+        }
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        // Use the client's revision when we speak it, otherwise offer our newest
+        let requested = params.get("protocolVersion").and_then(Value::as_str);
+        let version = requested
+            .and_then(|requested| PROTOCOL_VERSIONS.iter().find(|v| **v == requested))
+            .unwrap_or(&PROTOCOL_VERSIONS[0]);
+
+        let mut result = json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": { "name": self.name, "version": self.version },
+        });
+        if let Some(instructions) = &self.instructions {
+            result["instructions"] = json!(instructions);
+        }
+        result
+    }
+
+    async fn call_tool(&self, params: Value) -> Result<Value, (i32, String)> {
+        let call: ToolCall = serde_json::from_value(params)
+            .map_err(|e| (INVALID_PARAMS, format!("Invalid tools/call params: {}", e)))?;
+        if !self.handler.tools().iter().any(|tool| tool.name == call.name) {
+            return Err((INVALID_PARAMS, format!("Unknown tool: {}", call.name)));
+        }
+
+        let arguments = call.arguments.unwrap_or_else(|| json!({}));
+        Ok(match self.handler.call_tool(&call.name, arguments).await {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": tool_text(&value) }],
+                "isError": false,
+            }),
+            Err(e) => {
+                warn!(tool = %call.name, error = %e, "MCP tool call failed");
+                json!({
+                    "content": [{ "type": "text", "text": e.to_string() }],
+                    "isError": true,
+                })
+            }
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Option<Value>,
+}
+
+fn error_response(id: Value, code: i32, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Tool output as text: strings as they are, anything else as pretty JSON
+fn tool_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Calculator;
+
+    #[async_trait]
+    impl ToolHandler for Calculator {
+        fn tools(&self) -> Vec<Tool> {
+            vec![Tool::new(
+                "add",
+                "Add two numbers",
+                json!({
+                    "type": "object",
+                    "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+                    "required": ["a", "b"],
+                }),
+            )]
+        }
+
+        async fn call_tool(&self, _name: &str, arguments: Value) -> anyhow::Result<Value> {
+            let a = arguments["a"].as_f64().ok_or_else(|| anyhow::anyhow!("a must be a number"))?;
+            let b = arguments["b"].as_f64().ok_or_else(|| anyhow::anyhow!("b must be a number"))?;
+            Ok(json!(a + b))
+        }
+    }
+
+    fn server() -> McpServer {
+        McpServer::new(Calculator).with_instructions("Use add to add numbers")
+    }
+
+    async fn request(server: &McpServer, method: &str, params: Value) -> Value {
+        server
+            .handle_message(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .await
+            .expect("requests get a response")
+    }
+
+    #[tokio::test]
+    async fn test_initialize_negotiates_version() {
+        let server = server();
+
+        let response = request(&server, "initialize", json!({ "protocolVersion": "2024-11-05" })).await;
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "vespera-bindery");
+        assert_eq!(response["result"]["instructions"], "Use add to add numbers");
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+
+        let response = request(&server, "initialize", json!({ "protocolVersion": "1999-01-01" })).await;
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_and_call() {
+        let server = server();
+
+        let response = request(&server, "tools/list", json!({})).await;
+        assert_eq!(response["result"]["tools"][0]["name"], "add");
+        assert!(response["result"]["tools"][0]["inputSchema"].is_object());
+
+        let response = request(&server, "tools/call", json!({ "name": "add", "arguments": { "a": 2, "b": 3 } })).await;
+        assert_eq!(response["result"]["isError"], false);
+        assert_eq!(response["result"]["content"][0]["text"], "5.0");
+    }
+
+    #[tokio::test]
+    async fn test_tool_failure_is_a_tool_result() {
+        let server = server();
+
+        let response = request(&server, "tools/call", json!({ "name": "add", "arguments": { "a": 2 } })).await;
+        assert!(response.get("error").is_none());
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(response["result"]["content"][0]["text"], "b must be a number");
+
+        let response = request(&server, "tools/call", json!({ "name": "subtract" })).await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let server = server();
+
+        let response = request(&server, "resources/list", json!({})).await;
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], 1);
+
+        let response: Value = serde_json::from_str(&server.handle_text("{not json").await.unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_and_batches() {
+        let server = server();
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server.handle_message(notification.clone()).await.is_none());
+
+        let batch = json!([notification, { "jsonrpc": "2.0", "id": 7, "method": "ping" }]);
+        let response = server.handle_message(batch).await.unwrap();
+        assert_eq!(response.as_array().unwrap().len(), 1);
+        assert_eq!(response[0]["id"], 7);
+        assert_eq!(response[0]["result"], json!({}));
+    }
+}
@@ -0,0 +1,193 @@
+//! MCP transports: stdio and HTTP with Server-Sent Events
+//!
+//! Over stdio, each message is one line of JSON. Over HTTP, a client opens
+//! an event stream with `GET /sse`; the first event (`endpoint`) names the
+//! URL to `POST` its messages to, and responses arrive on the stream as
+//! `message` events.
+
+use super::McpServer;
+use anyhow::{Context, Result};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::{get, post};
+use axum::Router;
+use futures::Stream;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// Responses queued per SSE session before new ones wait for the client
+const SESSION_QUEUE: usize = 64;
+
+/// Serve MCP over stdin/stdout until stdin closes
+///
+/// Nothing else may write to stdout meanwhile; send logs to stderr or a file.
+pub async fn serve_stdio(server: Arc<McpServer>) -> Result<()> {
+    serve_io(server, tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+/// Serve MCP over a pair of byte streams, one JSON message per line
+pub async fn serve_io<R, W>(server: Arc<McpServer>, reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_text(&line).await {
+            writer.write_all(response.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            writer.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Serve MCP over HTTP with Server-Sent Events on `addr`
+pub async fn serve_sse(server: Arc<McpServer>, addr: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind MCP server to {}", addr))?;
+    info!("MCP server listening on http://{}/sse", addr);
+    axum::serve(listener, sse_router(server)).await.context("MCP server error")?;
+    Ok(())
+}
+
+/// Routes for the SSE transport (`GET /sse`, `POST /message`)
+///
+/// Mount at the root of the server: the endpoint sent to clients is the
+/// absolute path `/message`.
+pub fn sse_router(server: Arc<McpServer>) -> Router {
+    Router::new()
+        .route("/sse", get(open_stream))
+        .route("/message", post(post_message))
+        .with_state(SseState {
+            server,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        })
+}
+
+type Sessions = Arc<Mutex<HashMap<String, mpsc::Sender<String>>>>;
+
+#[derive(Clone)]
+struct SseState {
+    server: Arc<McpServer>,
+    sessions: Sessions,
+}
+
+/// Removes a session once its event stream is dropped (the client went away)
+struct SessionGuard {
+    sessions: Sessions,
+    id: String,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&self.id);
+        }
+        debug!(session = %self.id, "MCP session closed");
+    }
+}
+
+async fn open_stream(State(state): State<SseState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let id = Uuid::new_v4().to_string();
+    let (sender, mut receiver) = mpsc::channel(SESSION_QUEUE);
+    if let Ok(mut sessions) = state.sessions.lock() {
+        sessions.insert(id.clone(), sender);
+    }
+    debug!(session = %id, "MCP session opened");
+
+    let stream = async_stream::stream! {
+        let _session = SessionGuard { sessions: state.sessions, id: id.clone() };
+        yield Ok(Event::default().event("endpoint").data(format!("/message?sessionId={}", id)));
+        while let Some(message) = receiver.recv().await {
+            yield Ok(Event::default().event("message").data(message));
+        }
+    };
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[derive(Deserialize)]
+struct SessionQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+async fn post_message(
+    State(state): State<SseState>,
+    Query(query): Query<SessionQuery>,
+    body: String,
+) -> StatusCode {
+    let sender = match state.sessions.lock() {
+        Ok(sessions) => sessions.get(&query.session_id).cloned(),
+        Err(_) => None,
+    };
+    let Some(sender) = sender else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    // Answer on the event stream; the POST only acknowledges receipt
+    tokio::spawn(async move {
+        if let Some(response) = state.server.handle_text(&body).await {
+            let _ = sender.send(response).await;
+        }
+    });
+    StatusCode::ACCEPTED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{Tool, ToolHandler};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+
+    struct NoTools;
+
+    #[async_trait]
+    impl ToolHandler for NoTools {
+        fn tools(&self) -> Vec<Tool> {
+            Vec::new()
+        }
+
+        async fn call_tool(&self, name: &str, _arguments: Value) -> anyhow::Result<Value> {
+            anyhow::bail!("No tool {}", name)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stdio_answers_requests_line_by_line() {
+        let server = Arc::new(McpServer::new(NoTools));
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+            "\n\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+
+        serve_io(server, input.as_bytes(), &mut output).await.unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0], json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+        assert_eq!(responses[1]["result"]["tools"], json!([]));
+    }
+}
@@ -9,10 +9,12 @@
 
 pub mod manager;
 pub mod commands;
+pub mod scriptorium;
 
 // Re-export commonly used types
-pub use manager::{MigrationManager, MigrationInfo, MigrationRecord, MigrationStatus, MigrationResult};
+pub use manager::{MigrationManager, MigrationInfo, MigrationRecord, MigrationStatus, MigrationResult, MigrationPlan, ChecksumDrift};
 pub use commands::{MigrationCommand, MigrationCommandExecutor};
+pub use scriptorium::{ScriptoriumImporter, ScriptoriumImportOptions, ScriptoriumImportReport};
 
 use crate::task_management::{TaskManager, TaskInput, TaskPriority};
 use crate::role_management::RoleManager;
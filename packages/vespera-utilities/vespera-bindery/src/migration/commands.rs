@@ -3,6 +3,7 @@
 //! Provides command-line interface for database migration operations
 
 use crate::migration::manager::{MigrationManager, MigrationStatus, MigrationResult};
+use crate::migration::scriptorium::{ScriptoriumImporter, ScriptoriumImportOptions};
 use crate::errors::{BinderyError, BinderyResult};
 use clap::Subcommand;
 use serde_json;
@@ -73,18 +74,34 @@ pub enum MigrationCommand {
         /// Migration version to show info for
         version: i64,
     },
+
+    /// Import tasks, sessions and artifacts from a legacy Python scriptorium database
+    ImportScriptorium {
+        /// Path to the scriptorium SQLite database (e.g. .vespera_v2/tasks.db)
+        source: PathBuf,
+        /// Project ID for records that have none in the legacy data
+        #[arg(long)]
+        project: Option<String>,
+        /// Dry run (print the mapping report without writing anything)
+        #[arg(long)]
+        dry_run: bool,
+        /// Output format (markdown, json)
+        #[arg(long, default_value = "markdown")]
+        format: String,
+    },
 }
 
 /// Migration command executor
 pub struct MigrationCommandExecutor {
     manager: MigrationManager,
+    pool: SqlitePool,
 }
 
 impl MigrationCommandExecutor {
     /// Create a new migration command executor
     pub async fn new(pool: SqlitePool, migrations_dir: PathBuf) -> BinderyResult<Self> {
-        let manager = MigrationManager::new(pool, migrations_dir).await?;
-        Ok(Self { manager })
+        let manager = MigrationManager::new(pool.clone(), migrations_dir).await?;
+        Ok(Self { manager, pool })
     }
 
     /// Execute a migration command
@@ -98,6 +115,9 @@ impl MigrationCommandExecutor {
             MigrationCommand::MarkExecuted { version, force } => self.mark_executed(version, force).await,
             MigrationCommand::Create { name, description } => self.create_migration(name, description).await,
             MigrationCommand::Info { version } => self.show_info(version).await,
+            MigrationCommand::ImportScriptorium { source, project, dry_run, format } => {
+                self.import_scriptorium(source, project, dry_run, format).await
+            }
         }
     }
 
@@ -167,19 +187,20 @@ impl MigrationCommandExecutor {
     /// Run pending migrations
     async fn migrate_up(&self, target_version: Option<i64>, dry_run: bool) -> BinderyResult<()> {
         if dry_run {
-            let status = self.manager.get_status().await?;
-            let current_version = status.current_version;
-            let target = target_version.unwrap_or_else(|| {
-                status.pending_migrations.iter().map(|m| m.version).max().unwrap_or(current_version)
-            });
+            let plan = self.manager.plan(target_version).await?;
 
             println!("Dry run: Migrations that would be executed:");
-            println!("Current version: {}", current_version);
-            println!("Target version: {}", target);
+            println!("Current version: {}", plan.current_version);
+            println!("Target version: {}", plan.target_version);
 
-            for migration in &status.pending_migrations {
-                if migration.version <= target {
-                    println!("  → {} ({})", migration.version, migration.name);
+            for migration in &plan.pending {
+                println!("  → {} ({})", migration.version, migration.name);
+            }
+
+            for drift in &plan.drift {
+                match &drift.current_checksum {
+                    Some(_) => println!("  ⚠ {} ({}) changed since it was applied", drift.version, drift.name),
+                    None => println!("  ⚠ {} ({}) is applied but its file is missing", drift.version, drift.name),
                 }
             }
             return Ok(());
@@ -325,6 +346,39 @@ impl MigrationCommandExecutor {
         Ok(())
     }
 
+    /// Import a legacy scriptorium database
+    async fn import_scriptorium(
+        &self,
+        source: PathBuf,
+        project: Option<String>,
+        dry_run: bool,
+        format: String,
+    ) -> BinderyResult<()> {
+        info!("Importing scriptorium database {:?} (dry run: {})", source, dry_run);
+
+        let options = ScriptoriumImportOptions {
+            dry_run,
+            default_project_id: project,
+        };
+        let report = ScriptoriumImporter::new(self.pool.clone())
+            .import(&source, &options)
+            .await?;
+
+        match format.as_str() {
+            "json" => {
+                println!("{}", serde_json::to_string_pretty(&report)
+                    .map_err(|e| BinderyError::SerializationError(format!("Failed to serialize report: {}", e)))?);
+            }
+            _ => println!("{}", report.to_markdown()),
+        }
+
+        if report.has_errors() {
+            warn!("Scriptorium import finished with conflicts or failures");
+        }
+
+        Ok(())
+    }
+
     /// Show detailed information about a specific migration
     async fn show_info(&self, version: i64) -> BinderyResult<()> {
         let status = self.manager.get_status().await?;
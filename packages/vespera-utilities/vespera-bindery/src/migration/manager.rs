@@ -6,18 +6,21 @@
 //! - Transaction-safe migration execution
 //! - Migration status and history reporting
 //! - Comprehensive audit logging for all migration operations
+//! - Optional approval gating of changes, per role
 
+use crate::database::storage::SqlDialect;
 use crate::errors::{BinderyError, BinderyResult};
 use crate::observability::audit::{
     AuditLogger, UserContext, Operation, SecurityContext, OperationOutcome,
     create_migration_event
 };
 use crate::observability::metrics::BinderyMetrics;
+use crate::role_management::{ApprovalGate, GatedAction};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Sqlite, Row, Transaction};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{info, warn, error, debug};
@@ -80,6 +83,40 @@ pub struct MigrationMetrics {
     pub rollback_frequency: f64, // rollbacks per day
 }
 
+/// Checksum drift detected for an already-applied migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumDrift {
+    pub version: i64,
+    pub name: String,
+    /// Checksum recorded when the migration was applied
+    pub applied_checksum: String,
+    /// Checksum of the migration file on disk, `None` if the file is missing
+    pub current_checksum: Option<String>,
+}
+
+/// Dry-run plan describing what `migrate` would do without touching the schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    pub current_version: i64,
+    pub target_version: i64,
+    /// Migrations that would be applied, in execution order
+    pub pending: Vec<MigrationInfo>,
+    /// Applied migrations whose file changed or disappeared since execution
+    pub drift: Vec<ChecksumDrift>,
+}
+
+impl MigrationPlan {
+    /// Whether there is nothing to apply and no drift to report
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty() && self.drift.is_empty()
+    }
+
+    /// Whether any applied migration no longer matches its file
+    pub fn has_drift(&self) -> bool {
+        !self.drift.is_empty()
+    }
+}
+
 /// Database migration manager with audit logging
 pub struct MigrationManager {
     pool: Pool<Sqlite>,
@@ -87,6 +124,8 @@ pub struct MigrationManager {
     migrations: HashMap<i64, MigrationInfo>,
     /// Audit logger for migration operations
     audit_logger: Option<Arc<AuditLogger>>,
+    /// Gate changes must pass
+    approval: Option<Arc<ApprovalGate>>,
 }
 
 impl MigrationManager {
@@ -97,6 +136,7 @@ impl MigrationManager {
             migrations_dir,
             migrations: HashMap::new(),
             audit_logger: None,
+            approval: None,
         };
 
         // Initialize migration tracking table
@@ -119,6 +159,7 @@ impl MigrationManager {
             migrations_dir,
             migrations: HashMap::new(),
             audit_logger: Some(audit_logger),
+            approval: None,
         };
 
         // Initialize migration tracking table
@@ -130,6 +171,18 @@ impl MigrationManager {
         Ok(manager)
     }
 
+    /// Pass schema changes through `gate`
+    ///
+    /// Each change (applying, rolling back, force marking or resetting) then
+    /// needs the user context of a user with a role assigned. If that role
+    /// requires approval for [`GatedAction::Migration`], the change is parked
+    /// until another user approves it, and fails with
+    /// [`BinderyError::ApprovalRequired`] until then.
+    pub fn with_approval_gate(mut self, gate: Arc<ApprovalGate>) -> Self {
+        self.approval = Some(gate);
+        self
+    }
+
     /// Initialize the migration tracking table
     async fn initialize_migration_table(&self) -> BinderyResult<()> {
         let start_time = std::time::Instant::now();
@@ -165,33 +218,57 @@ impl MigrationManager {
 
     /// Load migrations from the migrations directory
     async fn load_migrations(&mut self) -> BinderyResult<()> {
-        if !self.migrations_dir.exists() {
-            warn!("Migrations directory does not exist: {:?}", self.migrations_dir);
-            return Ok(());
+        self.migrations = Self::load_migrations_for(&self.migrations_dir, SqlDialect::Sqlite).await?;
+        Ok(())
+    }
+
+    /// Load the migrations that apply to `dialect` from a directory
+    ///
+    /// Files named `V{version}__{name}.sql` apply to every dialect. Files named
+    /// `V{version}__{name}.{dialect}.sql` only apply to that dialect and replace
+    /// a shared file with the same version.
+    pub async fn load_migrations_for(migrations_dir: &Path, dialect: SqlDialect) -> BinderyResult<HashMap<i64, MigrationInfo>> {
+        let mut migrations = HashMap::new();
+
+        if !migrations_dir.exists() {
+            warn!("Migrations directory does not exist: {:?}", migrations_dir);
+            return Ok(migrations);
         }
 
-        let mut dir_entries = tokio::fs::read_dir(&self.migrations_dir)
+        let mut dir_entries = tokio::fs::read_dir(migrations_dir)
             .await
             .map_err(|e| BinderyError::IoError(format!("Failed to read migrations directory: {}", e)))?;
 
+        let mut dialect_specific = HashSet::new();
         while let Some(entry) = dir_entries.next_entry()
             .await
             .map_err(|e| BinderyError::IoError(format!("Failed to read directory entry: {}", e)))?
         {
             let path = entry.path();
             if path.extension().map_or(false, |ext| ext == "sql") {
-                if let Some(migration) = self.parse_migration_file(&path).await? {
-                    self.migrations.insert(migration.version, migration);
+                if let Some((migration, file_dialect)) = Self::parse_migration_file(&path).await? {
+                    match file_dialect {
+                        Some(file_dialect) if file_dialect != dialect => continue,
+                        Some(_) => {
+                            dialect_specific.insert(migration.version);
+                            migrations.insert(migration.version, migration);
+                        }
+                        None => {
+                            if !dialect_specific.contains(&migration.version) {
+                                migrations.insert(migration.version, migration);
+                            }
+                        }
+                    }
                 }
             }
         }
 
-        info!("Loaded {} migrations", self.migrations.len());
-        Ok(())
+        info!("Loaded {} {} migrations", migrations.len(), dialect.name());
+        Ok(migrations)
     }
 
-    /// Parse a migration file
-    async fn parse_migration_file(&self, path: &Path) -> BinderyResult<Option<MigrationInfo>> {
+    /// Parse a migration file, returning the dialect it is restricted to (if any)
+    async fn parse_migration_file(path: &Path) -> BinderyResult<Option<(MigrationInfo, Option<SqlDialect>)>> {
         let filename = path.file_name()
             .and_then(|name| name.to_str())
             .ok_or_else(|| BinderyError::InvalidInput(format!("Invalid migration file name: {:?}", path)))?;
@@ -209,7 +286,16 @@ impl MigrationManager {
         }
 
         let version_str = &parts[0][1..]; // Remove 'V' prefix
-        let name = parts[1].trim_end_matches(".sql");
+        let stem = parts[1].trim_end_matches(".sql");
+
+        // Optional dialect suffix: V{version}__{name}.{dialect}.sql
+        let (name, dialect) = match stem.rsplit_once('.') {
+            Some((name, suffix)) => match SqlDialect::from_name(suffix) {
+                Some(dialect) => (name, Some(dialect)),
+                None => (stem, None),
+            },
+            None => (stem, None),
+        };
 
         let version = version_str.parse::<i64>()
             .map_err(|_| BinderyError::InvalidInput(format!("Invalid version in migration file: {}", filename)))?;
@@ -224,9 +310,9 @@ impl MigrationManager {
         let checksum = format!("{:x}", hasher.finalize());
 
         // Parse up and down SQL
-        let (up_sql, down_sql) = self.parse_migration_content(&content)?;
+        let (up_sql, down_sql) = Self::parse_migration_content(&content)?;
 
-        Ok(Some(MigrationInfo {
+        Ok(Some((MigrationInfo {
             version,
             name: name.to_string(),
             description: format!("Migration {}: {}", version, name),
@@ -235,16 +321,25 @@ impl MigrationManager {
             checksum,
             executed_at: None,
             execution_time_ms: None,
-        }))
+        }, dialect)))
     }
 
     /// Parse migration content into up and down SQL
-    fn parse_migration_content(&self, content: &str) -> BinderyResult<(String, Option<String>)> {
+    ///
+    /// Accepts either a bare `-- Down` separator or the `-- +migrate up` /
+    /// `-- +migrate down` marker pair used by the bundled migration files.
+    fn parse_migration_content(content: &str) -> BinderyResult<(String, Option<String>)> {
         let content = content.trim();
 
-        // Look for -- Down marker
-        if let Some(down_pos) = content.find("-- Down") {
-            let up_sql = content[..down_pos].trim().to_string();
+        // Look for -- Down (or -- +migrate down) marker
+        let down_pos = content.find("-- +migrate down").or_else(|| content.find("-- Down"));
+        if let Some(down_pos) = down_pos {
+            let up_sql = content[..down_pos].lines()
+                .filter(|line| line.trim() != "-- +migrate up")
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string();
             let down_sql = content[down_pos..].lines()
                 .skip(1) // Skip the "-- Down" line
                 .collect::<Vec<_>>()
@@ -299,6 +394,49 @@ impl MigrationManager {
         })
     }
 
+    /// Build a dry-run plan for migrating to `to_version` (latest when `None`)
+    ///
+    /// Nothing is executed; the plan lists the migrations that would run and
+    /// any applied migrations whose checksum no longer matches the file on disk.
+    pub async fn plan(&self, to_version: Option<i64>) -> BinderyResult<MigrationPlan> {
+        let current_version = self.get_current_version().await?;
+        let target_version = to_version.unwrap_or_else(|| {
+            self.migrations.keys().max().copied().unwrap_or(current_version).max(current_version)
+        });
+
+        let mut pending: Vec<_> = self.migrations.values()
+            .filter(|m| m.version > current_version && m.version <= target_version)
+            .cloned()
+            .collect();
+        pending.sort_by_key(|m| m.version);
+
+        let mut drift = Vec::new();
+        for record in self.get_applied_migrations().await? {
+            let current_checksum = self.migrations.get(&record.version).map(|m| m.checksum.clone());
+            if current_checksum.as_deref() != Some(record.checksum.as_str()) {
+                warn!("Applied migration {} ({}) has drifted from its file", record.version, record.name);
+                drift.push(ChecksumDrift {
+                    version: record.version,
+                    name: record.name,
+                    applied_checksum: record.checksum,
+                    current_checksum,
+                });
+            }
+        }
+
+        debug!(
+            "Migration plan: {} pending up to version {}, {} drifted",
+            pending.len(), target_version, drift.len()
+        );
+
+        Ok(MigrationPlan {
+            current_version,
+            target_version,
+            pending,
+            drift,
+        })
+    }
+
     /// Get applied migrations
     async fn get_applied_migrations(&self) -> BinderyResult<Vec<MigrationRecord>> {
         let rows = sqlx::query("SELECT version, name, checksum, executed_at, execution_time_ms FROM migrations ORDER BY version")
@@ -329,12 +467,20 @@ impl MigrationManager {
             return Ok(Vec::new());
         }
 
+        self.authorize("migrate".to_string(), "Apply all pending migrations".to_string(), user_context.as_ref()).await?;
+
         info!("Executing {} pending migrations", pending.len());
 
         let mut results = Vec::new();
         for migration in pending {
             let result = self.execute_migration_up(&migration, user_context.as_ref()).await?;
+            let success = result.success;
             results.push(result);
+
+            if !success {
+                error!("Stopping migrations at failed version {}", migration.version);
+                break;
+            }
         }
 
         Ok(results)
@@ -353,6 +499,8 @@ impl MigrationManager {
             return Ok(Vec::new());
         }
 
+        self.authorize(format!("migrate_up:{}", target_version), format!("Migrate up to version {}", target_version), user_context.as_ref()).await?;
+
         let mut pending: Vec<_> = self.migrations.values()
             .filter(|m| m.version > current_version && m.version <= target_version)
             .cloned()
@@ -370,7 +518,13 @@ impl MigrationManager {
         let mut results = Vec::new();
         for migration in pending {
             let result = self.execute_migration_up(&migration, user_context.as_ref()).await?;
+            let success = result.success;
             results.push(result);
+
+            if !success {
+                error!("Stopping migrations at failed version {}", migration.version);
+                break;
+            }
         }
 
         Ok(results)
@@ -395,6 +549,8 @@ impl MigrationManager {
 
         match self.execute_sql_in_transaction(&mut tx, &migration.up_sql).await {
             Ok(_) => {
+                let execution_time = start_time.elapsed().as_millis() as i64;
+
                 // Record the migration
                 sqlx::query(
                     "INSERT INTO migrations (version, name, checksum, executed_at, execution_time_ms) VALUES (?, ?, ?, CURRENT_TIMESTAMP, ?)"
@@ -402,6 +558,7 @@ impl MigrationManager {
                 .bind(migration.version)
                 .bind(&migration.name)
                 .bind(&migration.checksum)
+                .bind(execution_time)
                 .execute(&mut *tx)
                 .await
                 .map_err(|e| BinderyError::DatabaseError(e.to_string()))?;
@@ -409,7 +566,6 @@ impl MigrationManager {
                 tx.commit().await
                     .map_err(|e| BinderyError::DatabaseError(e.to_string()))?;
 
-                let execution_time = start_time.elapsed().as_millis() as i64;
                 info!("Migration {} completed successfully in {}ms", migration.version, execution_time);
 
                 // Record migration success metrics
@@ -568,9 +724,20 @@ impl MigrationManager {
     }
 
     /// Rollback to a specific version with audit logging
+    ///
+    /// Every applied migration above `target_version` must have a file on disk
+    /// with down SQL; this is checked before anything is rolled back. Rollback
+    /// stops at the first failure so the schema is never left with a gap.
     pub async fn rollback_to(&self, target_version: i64, user_context: Option<UserContext>) -> BinderyResult<Vec<MigrationResult>> {
         let current_version = self.get_current_version().await?;
 
+        if target_version < 0 {
+            return Err(BinderyError::InvalidInput(format!(
+                "Target version {} must not be negative",
+                target_version
+            )));
+        }
+
         if target_version >= current_version {
             return Err(BinderyError::InvalidInput(format!(
                 "Target version {} must be less than current version {}",
@@ -580,18 +747,42 @@ impl MigrationManager {
 
         info!("Rolling back from version {} to {}", current_version, target_version);
 
+        self.authorize(format!("rollback_to:{}", target_version), format!("Roll back from version {} to {}", current_version, target_version), user_context.as_ref()).await?;
+
         let mut results = Vec::new();
 
-        // Get migrations to rollback (in reverse order)
-        let mut migrations_to_rollback: Vec<_> = self.migrations.values()
-            .filter(|m| m.version > target_version && m.version <= current_version)
-            .collect();
+        // Resolve applied migrations to rollback (in reverse order)
+        let mut migrations_to_rollback = Vec::new();
+        for record in self.get_applied_migrations().await?.iter().rev() {
+            if record.version <= target_version {
+                continue;
+            }
+
+            let migration = self.migrations.get(&record.version)
+                .ok_or_else(|| BinderyError::InvalidInput(format!(
+                    "Applied migration {} ({}) not found in migration files",
+                    record.version, record.name
+                )))?;
+
+            if migration.down_sql.is_none() {
+                return Err(BinderyError::InvalidInput(format!(
+                    "Migration {} ({}) is not reversible: no down SQL",
+                    migration.version, migration.name
+                )));
+            }
 
-        migrations_to_rollback.sort_by(|a, b| b.version.cmp(&a.version)); // Reverse order
+            migrations_to_rollback.push(migration);
+        }
 
         for migration in migrations_to_rollback {
             let result = self.execute_migration_down(migration, user_context.as_ref()).await?;
+            let success = result.success;
             results.push(result);
+
+            if !success {
+                error!("Stopping rollback at migration {}", migration.version);
+                break;
+            }
         }
 
         Ok(results)
@@ -605,6 +796,8 @@ impl MigrationManager {
             return Err(BinderyError::InvalidInput("No migrations to rollback".to_string()));
         }
 
+        self.authorize(format!("rollback_last:{}", current_version), format!("Roll back migration {}", current_version), user_context.as_ref()).await?;
+
         let migration = self.migrations.get(&current_version)
             .ok_or_else(|| BinderyError::InvalidInput(format!("Migration {} not found", current_version)))?;
 
@@ -619,6 +812,8 @@ impl MigrationManager {
             return Err(BinderyError::InvalidInput("No migrations to redo".to_string()));
         }
 
+        self.authorize(format!("redo_last:{}", current_version), format!("Redo migration {}", current_version), user_context.as_ref()).await?;
+
         let migration = self.migrations.get(&current_version)
             .ok_or_else(|| BinderyError::InvalidInput(format!("Migration {} not found", current_version)))?;
 
@@ -650,6 +845,8 @@ impl MigrationManager {
 
         info!("Force marking migration {} as executed", version);
 
+        self.authorize(format!("mark_as_executed:{}", version), format!("Force mark migration {} as executed", version), user_context.as_ref()).await?;
+
         // Audit: Log force mark operation
         self.audit_migration_force_mark(migration, user_context.as_ref()).await;
 
@@ -744,6 +941,8 @@ impl MigrationManager {
 
     /// Reset all migrations (dangerous operation) with audit logging
     pub async fn reset(&self, user_context: Option<UserContext>) -> BinderyResult<()> {
+
+        self.authorize("reset".to_string(), "Reset all migration history".to_string(), user_context.as_ref()).await?;
         warn!("Resetting all migrations - this will drop all migration history!");
 
         // Audit: Log reset operation
@@ -758,6 +957,18 @@ impl MigrationManager {
         Ok(())
     }
 
+    /// Pass a schema change through the approval gate, if there is one,
+    /// in the role assigned to the acting user
+    async fn authorize(&self, operation: String, description: String, user_context: Option<&UserContext>) -> BinderyResult<()> {
+        let Some(gate) = &self.approval else {
+            return Ok(());
+        };
+        let user_context = user_context.ok_or_else(|| {
+            BinderyError::PermissionDenied("Schema changes through the approval gate need a user".to_string())
+        })?;
+        gate.authorize(user_context, GatedAction::Migration, format!("migration:{}", operation), description).await
+    }
+
     // Audit logging methods
 
     /// Audit migration attempt
@@ -1010,7 +1221,7 @@ mod tests {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
         let db_path = temp_dir.path().join("test.db");
 
-        let pool = SqlitePool::connect(&format!("sqlite:{}", db_path.display()))
+        let pool = SqlitePool::connect(&format!("sqlite:{}?mode=rwc", db_path.display()))
             .await
             .expect("Failed to connect to test database");
 
@@ -1117,4 +1328,153 @@ mod tests {
         let count: i64 = row.try_get("count").unwrap();
         assert_eq!(count, 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_migration_plan_and_checksum_drift() {
+        let (pool, temp_dir) = setup_test_db().await;
+        let migrations_dir = temp_dir.path().join("migrations");
+        fs::create_dir(&migrations_dir).expect("Failed to create migrations dir");
+
+        create_test_migration(&migrations_dir, 1, "create_a", "CREATE TABLE a (id INTEGER);", Some("DROP TABLE a;"));
+        create_test_migration(&migrations_dir, 2, "create_b", "CREATE TABLE b (id INTEGER);", Some("DROP TABLE b;"));
+
+        let manager = MigrationManager::new(pool.clone(), migrations_dir.clone()).await.unwrap();
+
+        let plan = manager.plan(None).await.unwrap();
+        assert_eq!(plan.current_version, 0);
+        assert_eq!(plan.target_version, 2);
+        assert_eq!(plan.pending.iter().map(|m| m.version).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(!plan.has_drift());
+
+        // Planning must not execute anything
+        assert_eq!(manager.get_current_version().await.unwrap(), 0);
+
+        manager.migrate_up(Some(1), None).await.unwrap();
+        let plan = manager.plan(None).await.unwrap();
+        assert_eq!(plan.current_version, 1);
+        assert_eq!(plan.pending.len(), 1);
+
+        // Edit an applied migration on disk and reload
+        create_test_migration(&migrations_dir, 1, "create_a", "CREATE TABLE a (id INTEGER, extra TEXT);", Some("DROP TABLE a;"));
+        let manager = MigrationManager::new(pool, migrations_dir).await.unwrap();
+
+        let plan = manager.plan(None).await.unwrap();
+        assert!(plan.has_drift());
+        assert_eq!(plan.drift.len(), 1);
+        assert_eq!(plan.drift[0].version, 1);
+        assert!(plan.drift[0].current_checksum.is_some());
+        assert!(!plan.is_up_to_date());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_to_version() {
+        let (pool, temp_dir) = setup_test_db().await;
+        let migrations_dir = temp_dir.path().join("migrations");
+        fs::create_dir(&migrations_dir).expect("Failed to create migrations dir");
+
+        create_test_migration(&migrations_dir, 1, "create_a", "CREATE TABLE a (id INTEGER);", Some("DROP TABLE a;"));
+        create_test_migration(&migrations_dir, 2, "create_b", "CREATE TABLE b (id INTEGER);", Some("DROP TABLE b;"));
+        create_test_migration(&migrations_dir, 3, "create_c", "CREATE TABLE c (id INTEGER);", Some("DROP TABLE c;"));
+
+        let manager = MigrationManager::new(pool.clone(), migrations_dir).await.unwrap();
+        manager.migrate(None).await.unwrap();
+        assert_eq!(manager.get_current_version().await.unwrap(), 3);
+
+        let results = manager.rollback_to(1, None).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.version).collect::<Vec<_>>(), vec![3, 2]);
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(manager.get_current_version().await.unwrap(), 1);
+
+        let row = sqlx::query("SELECT COUNT(*) as count FROM sqlite_master WHERE type='table' AND name IN ('a', 'b', 'c')")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let count: i64 = row.try_get("count").unwrap();
+        assert_eq!(count, 1);
+
+        assert!(manager.rollback_to(1, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_to_rejects_irreversible_migration() {
+        let (pool, temp_dir) = setup_test_db().await;
+        let migrations_dir = temp_dir.path().join("migrations");
+        fs::create_dir(&migrations_dir).expect("Failed to create migrations dir");
+
+        create_test_migration(&migrations_dir, 1, "create_a", "CREATE TABLE a (id INTEGER);", None);
+        create_test_migration(&migrations_dir, 2, "create_b", "CREATE TABLE b (id INTEGER);", Some("DROP TABLE b;"));
+
+        let manager = MigrationManager::new(pool, migrations_dir).await.unwrap();
+        manager.migrate(None).await.unwrap();
+
+        // Migration 1 has no down SQL, so nothing should be rolled back
+        assert!(manager.rollback_to(0, None).await.is_err());
+        assert_eq!(manager.get_current_version().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_approval_uses_the_acting_users_role() {
+        use crate::role_management::{Role, RoleManager};
+
+        let (pool, temp_dir) = setup_test_db().await;
+        let migrations_dir = temp_dir.path().join("migrations");
+        fs::create_dir(&migrations_dir).expect("Failed to create migrations dir");
+        create_test_migration(&migrations_dir, 1, "create_a", "CREATE TABLE a (id INTEGER);", None);
+
+        let role_manager = Arc::new(RoleManager::default());
+        let mut operator = Role::new("operator".to_string(), "Runs migrations".to_string(), vec![]);
+        operator.requires_approval = vec![GatedAction::Migration];
+        role_manager.add_role(operator).await.unwrap();
+        role_manager.add_role(Role::new("admin".to_string(), "Trusted".to_string(), vec![])).await.unwrap();
+        role_manager.assign_user_role("alice", "operator").await.unwrap();
+        role_manager.assign_user_role("dave", "admin").await.unwrap();
+        let manager = MigrationManager::new(pool, migrations_dir).await.unwrap()
+            .with_approval_gate(Arc::new(ApprovalGate::new(role_manager)));
+        let user = |user_id: &str| UserContext {
+            user_id: Some(user_id.to_string()),
+            session_id: None,
+            source_ip: None,
+            user_agent: None,
+        };
+
+        // No user, or one with no role, cannot get past the gate
+        assert!(matches!(manager.migrate(None).await, Err(BinderyError::PermissionDenied(_))));
+        assert!(matches!(manager.migrate(Some(user("mallory"))).await, Err(BinderyError::PermissionDenied(_))));
+        assert!(matches!(manager.migrate(Some(user("alice"))).await, Err(BinderyError::ApprovalRequired(_))));
+        assert_eq!(manager.get_current_version().await.unwrap(), 0);
+
+        manager.migrate(Some(user("dave"))).await.unwrap();
+        assert_eq!(manager.get_current_version().await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_parse_migrate_markers() {
+        let (up_sql, down_sql) = MigrationManager::parse_migration_content(
+            "-- +migrate up\nCREATE TABLE a (id INTEGER);\n\n-- +migrate down\nDROP TABLE a;\n"
+        ).unwrap();
+
+        assert_eq!(up_sql, "CREATE TABLE a (id INTEGER);");
+        assert_eq!(down_sql.as_deref(), Some("DROP TABLE a;"));
+    }
+
+    #[tokio::test]
+    async fn test_dialect_specific_migrations() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let migrations_dir = temp_dir.path().join("migrations");
+        fs::create_dir(&migrations_dir).expect("Failed to create migrations dir");
+
+        create_test_migration(&migrations_dir, 1, "shared", "CREATE TABLE a (id INTEGER);", None);
+        create_test_migration(&migrations_dir, 2, "json_column", "ALTER TABLE a ADD COLUMN data TEXT;", None);
+        create_test_migration(&migrations_dir, 2, "json_column.postgres", "ALTER TABLE a ADD COLUMN data JSONB;", None);
+        create_test_migration(&migrations_dir, 3, "pg_only.postgres", "CREATE INDEX a_data ON a USING GIN (data);", None);
+
+        let sqlite = MigrationManager::load_migrations_for(&migrations_dir, SqlDialect::Sqlite).await.unwrap();
+        assert_eq!(sqlite.len(), 2);
+        assert!(sqlite[&2].up_sql.contains("TEXT"));
+
+        let postgres = MigrationManager::load_migrations_for(&migrations_dir, SqlDialect::Postgres).await.unwrap();
+        assert_eq!(postgres.len(), 3);
+        assert!(postgres[&2].up_sql.contains("JSONB"));
+        assert_eq!(postgres[&2].name, "json_column");
+    }
+}
@@ -0,0 +1,1059 @@
+//! Data migration from the legacy Python vespera-scriptorium database
+//!
+//! Reads the scriptorium SQLite schema (`tasks`, `task_relationships`, and the
+//! optional `artifacts` / `sessions` tables) and converts it into Bindery tasks
+//! and Codices:
+//! - Legacy tasks keep their IDs and hierarchy and land in the `tasks` table
+//! - Sessions and artifacts become Codices, artifacts nested under their session
+//! - Every imported record is tracked in `scriptorium_import_map`, so re-runs
+//!   only touch records whose converted content changed
+//! - Dry runs compute the full mapping report without writing anything
+
+use crate::errors::{BinderyError, BinderyResult};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{Column, Pool, Row, Sqlite, Transaction, TypeInfo, ValueRef};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Template used for Codices created from legacy artifacts
+pub const LEGACY_ARTIFACT_TEMPLATE: &str = "vespera.templates.legacy_artifact";
+
+/// Template used for Codices created from legacy sessions
+pub const LEGACY_SESSION_TEMPLATE: &str = "vespera.templates.legacy_session";
+
+/// Import tracking table created in the target database
+const IMPORT_MAP_TABLE: &str = "scriptorium_import_map";
+
+/// Options controlling a scriptorium import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptoriumImportOptions {
+    /// Compute the mapping report without writing to the target database
+    pub dry_run: bool,
+    /// Project ID applied to records that have none in the legacy data
+    pub default_project_id: Option<String>,
+}
+
+/// What the importer did (or would do) with a single legacy record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Unchanged,
+    /// The target already has a record with this ID that was not imported
+    Conflict,
+    Failed,
+}
+
+/// Mapping from a legacy record to its Bindery counterpart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportMapping {
+    pub source_table: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub action: ImportAction,
+    pub error: Option<String>,
+}
+
+/// Per-table import counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportCounts {
+    pub found: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+}
+
+impl ImportCounts {
+    fn record(&mut self, action: ImportAction) {
+        match action {
+            ImportAction::Create => self.created += 1,
+            ImportAction::Update => self.updated += 1,
+            ImportAction::Unchanged => self.unchanged += 1,
+            ImportAction::Conflict => self.conflicts += 1,
+            ImportAction::Failed => self.failed += 1,
+        }
+    }
+}
+
+/// Mapping report produced by an import or dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptoriumImportReport {
+    pub source_path: PathBuf,
+    pub dry_run: bool,
+    pub tasks: ImportCounts,
+    pub sessions: ImportCounts,
+    pub artifacts: ImportCounts,
+    /// Non-hierarchical task relationships carried over as task labels
+    pub relationships_mapped: usize,
+    pub mappings: Vec<ImportMapping>,
+    pub warnings: Vec<String>,
+}
+
+impl ScriptoriumImportReport {
+    fn new(source_path: &Path, dry_run: bool) -> Self {
+        Self {
+            source_path: source_path.to_path_buf(),
+            dry_run,
+            tasks: ImportCounts::default(),
+            sessions: ImportCounts::default(),
+            artifacts: ImportCounts::default(),
+            relationships_mapped: 0,
+            mappings: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Whether any record failed to import or conflicted with existing data
+    pub fn has_errors(&self) -> bool {
+        [&self.tasks, &self.sessions, &self.artifacts]
+            .iter()
+            .any(|c| c.failed > 0 || c.conflicts > 0)
+    }
+
+    /// Render the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Scriptorium Import Report\n\nSource: {}\nMode: {}\n\n",
+            self.source_path.display(),
+            if self.dry_run { "dry run (nothing written)" } else { "import" }
+        );
+
+        out.push_str("| Table | Found | Created | Updated | Unchanged | Conflicts | Failed |\n");
+        out.push_str("|-------|-------|---------|---------|-----------|-----------|--------|\n");
+        for (name, counts) in [("tasks", &self.tasks), ("sessions", &self.sessions), ("artifacts", &self.artifacts)] {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |\n",
+                name, counts.found, counts.created, counts.updated,
+                counts.unchanged, counts.conflicts, counts.failed
+            ));
+        }
+        out.push_str(&format!("\nRelationships mapped: {}\n", self.relationships_mapped));
+
+        let problems: Vec<_> = self.mappings.iter()
+            .filter(|m| matches!(m.action, ImportAction::Conflict | ImportAction::Failed))
+            .collect();
+        if !problems.is_empty() {
+            out.push_str("\n## Problems\n");
+            for mapping in problems {
+                out.push_str(&format!(
+                    "- {} {} → {}: {:?}{}\n",
+                    mapping.source_table,
+                    mapping.source_id,
+                    mapping.target_id,
+                    mapping.action,
+                    mapping.error.as_ref().map(|e| format!(" ({})", e)).unwrap_or_default()
+                ));
+            }
+        }
+
+        if !self.warnings.is_empty() {
+            out.push_str("\n## Warnings\n");
+            for warning in &self.warnings {
+                out.push_str(&format!("- {}\n", warning));
+            }
+        }
+
+        out
+    }
+}
+
+/// Legacy task converted into the Bindery task shape
+#[derive(Debug, Clone, Serialize)]
+struct ConvertedTask {
+    id: String,
+    title: String,
+    description: Option<String>,
+    status: String,
+    priority: String,
+    parent_id: Option<String>,
+    project_id: Option<String>,
+    assignee: Option<String>,
+    tags: Vec<String>,
+    labels: serde_json::Map<String, serde_json::Value>,
+    created_at: String,
+    updated_at: String,
+    due_date: Option<String>,
+}
+
+/// Legacy session or artifact converted into a Codex
+#[derive(Debug, Clone, Serialize)]
+struct ConvertedCodex {
+    source_id: String,
+    title: String,
+    template_id: &'static str,
+    metadata: serde_json::Value,
+    created_at: String,
+}
+
+/// Previously imported record, as stored in the import map
+struct ImportedRecord {
+    target_id: String,
+    content_hash: String,
+}
+
+/// Importer for legacy vespera-scriptorium databases
+pub struct ScriptoriumImporter {
+    target: Pool<Sqlite>,
+}
+
+impl ScriptoriumImporter {
+    /// Create an importer writing into the given Bindery database pool
+    pub fn new(target: Pool<Sqlite>) -> Self {
+        Self { target }
+    }
+
+    /// Import (or dry-run) the scriptorium database at `source_path`
+    pub async fn import(
+        &self,
+        source_path: impl AsRef<Path>,
+        options: &ScriptoriumImportOptions,
+    ) -> BinderyResult<ScriptoriumImportReport> {
+        let source_path = source_path.as_ref();
+        if !source_path.exists() {
+            return Err(BinderyError::NotFound(format!(
+                "Scriptorium database not found: {}",
+                source_path.display()
+            )));
+        }
+
+        info!(source = %source_path.display(), dry_run = options.dry_run, "Importing scriptorium database");
+
+        let source = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(SqliteConnectOptions::new().filename(source_path).read_only(true))
+            .await
+            .map_err(|e| BinderyError::DatabaseConnectionError(format!("Failed to open scriptorium database: {}", e)))?;
+
+        let result = self.import_from(&source, source_path, options).await;
+        source.close().await;
+        result
+    }
+
+    async fn import_from(
+        &self,
+        source: &Pool<Sqlite>,
+        source_path: &Path,
+        options: &ScriptoriumImportOptions,
+    ) -> BinderyResult<ScriptoriumImportReport> {
+        let mut report = ScriptoriumImportReport::new(source_path, options.dry_run);
+
+        let source_tables = table_names(source).await?;
+        if !source_tables.contains("tasks") {
+            return Err(BinderyError::InvalidInput(format!(
+                "{} is not a scriptorium database: missing tasks table",
+                source_path.display()
+            )));
+        }
+
+        let target_tables = table_names(&self.target).await?;
+        if !options.dry_run {
+            for required in ["tasks", "codices"] {
+                if !target_tables.contains(required) {
+                    return Err(BinderyError::DatabaseMigrationError(format!(
+                        "Target database has no {} table; initialize the schema before importing",
+                        required
+                    )));
+                }
+            }
+        }
+
+        let imported = if target_tables.contains(IMPORT_MAP_TABLE) {
+            self.load_import_map().await?
+        } else {
+            HashMap::new()
+        };
+
+        // Convert everything up front so a dry run and a real run see identical plans
+        let relationships = if source_tables.contains("task_relationships") {
+            load_relationships(source).await?
+        } else {
+            HashMap::new()
+        };
+        let tasks = load_tasks(source, &relationships, options, &mut report).await?;
+        report.relationships_mapped = relationships.values().map(|r| r.len()).sum();
+
+        let sessions = if source_tables.contains("sessions") {
+            load_codices(source, "sessions", LEGACY_SESSION_TEMPLATE, &["title", "name"], &mut report).await?
+        } else {
+            Vec::new()
+        };
+        let artifacts = if source_tables.contains("artifacts") {
+            load_codices(source, "artifacts", LEGACY_ARTIFACT_TEMPLATE, &["title", "name", "path", "file_path"], &mut report).await?
+        } else {
+            Vec::new()
+        };
+
+        let mut tx = if options.dry_run {
+            None
+        } else {
+            let mut tx = self.target.begin().await?;
+            sqlx::query(&format!(
+                r#"
+                CREATE TABLE IF NOT EXISTS {} (
+                    source_table TEXT NOT NULL,
+                    source_id TEXT NOT NULL,
+                    target_id TEXT NOT NULL,
+                    content_hash TEXT NOT NULL,
+                    imported_at TEXT NOT NULL,
+                    PRIMARY KEY (source_table, source_id)
+                )
+                "#,
+                IMPORT_MAP_TABLE
+            ))
+            .execute(&mut *tx)
+            .await?;
+            Some(tx)
+        };
+
+        for task in &tasks {
+            let mapping = self.import_task(tx.as_mut(), task, &imported).await;
+            report.tasks.record(mapping.action);
+            report.mappings.push(mapping);
+        }
+
+        let mut session_ids = HashMap::new();
+        for session in &sessions {
+            let mapping = self.import_codex(tx.as_mut(), "sessions", session, &imported).await;
+            report.sessions.record(mapping.action);
+            if mapping.action != ImportAction::Failed && mapping.action != ImportAction::Conflict {
+                session_ids.insert(session.source_id.clone(), mapping.target_id.clone());
+            }
+            report.mappings.push(mapping);
+        }
+
+        for artifact in &artifacts {
+            let mut artifact = artifact.clone();
+            let session_id = artifact.metadata["legacy"]["session_id"].as_str().map(|s| s.to_string())
+                .or_else(|| artifact.metadata["legacy"]["session_id"].as_i64().map(|id| id.to_string()));
+            if let Some(parent) = session_id.and_then(|id| session_ids.get(&id)) {
+                artifact.metadata["parent_id"] = serde_json::Value::String(parent.clone());
+            }
+
+            let mapping = self.import_codex(tx.as_mut(), "artifacts", &artifact, &imported).await;
+            report.artifacts.record(mapping.action);
+            report.mappings.push(mapping);
+        }
+
+        if let Some(tx) = tx {
+            tx.commit().await?;
+        }
+
+        info!(
+            tasks = report.tasks.found,
+            sessions = report.sessions.found,
+            artifacts = report.artifacts.found,
+            dry_run = options.dry_run,
+            "Scriptorium import finished"
+        );
+
+        Ok(report)
+    }
+
+    async fn load_import_map(&self) -> BinderyResult<HashMap<(String, String), ImportedRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT source_table, source_id, target_id, content_hash FROM {}",
+            IMPORT_MAP_TABLE
+        ))
+        .fetch_all(&self.target)
+        .await?;
+
+        let mut map = HashMap::new();
+        for row in rows {
+            map.insert(
+                (row.try_get("source_table")?, row.try_get("source_id")?),
+                ImportedRecord {
+                    target_id: row.try_get("target_id")?,
+                    content_hash: row.try_get("content_hash")?,
+                },
+            );
+        }
+        Ok(map)
+    }
+
+    async fn import_task(
+        &self,
+        tx: Option<&mut Transaction<'static, Sqlite>>,
+        task: &ConvertedTask,
+        imported: &HashMap<(String, String), ImportedRecord>,
+    ) -> ImportMapping {
+        let hash = content_hash(task);
+        let previous = imported.get(&("tasks".to_string(), task.id.clone()));
+
+        let mut mapping = ImportMapping {
+            source_table: "tasks".to_string(),
+            source_id: task.id.clone(),
+            target_id: task.id.clone(),
+            action: match previous {
+                Some(record) if record.content_hash == hash => ImportAction::Unchanged,
+                Some(_) => ImportAction::Update,
+                None => ImportAction::Create,
+            },
+            error: None,
+        };
+
+        if mapping.action == ImportAction::Create && self.target_has("tasks", &task.id).await {
+            mapping.action = ImportAction::Conflict;
+            mapping.error = Some("a task with this ID already exists".to_string());
+            return mapping;
+        }
+
+        let Some(tx) = tx else {
+            return mapping;
+        };
+
+        if mapping.action == ImportAction::Unchanged {
+            return mapping;
+        }
+
+        let outcome = match write_task(tx, task, mapping.action).await {
+            Ok(()) => record_import(tx, "tasks", &task.id, &task.id, &hash).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = outcome {
+            warn!(task_id = %task.id, "Failed to import legacy task: {}", e);
+            mapping.action = ImportAction::Failed;
+            mapping.error = Some(e.to_string());
+        }
+
+        mapping
+    }
+
+    async fn import_codex(
+        &self,
+        tx: Option<&mut Transaction<'static, Sqlite>>,
+        source_table: &str,
+        codex: &ConvertedCodex,
+        imported: &HashMap<(String, String), ImportedRecord>,
+    ) -> ImportMapping {
+        let hash = content_hash(codex);
+        let previous = imported.get(&(source_table.to_string(), codex.source_id.clone()));
+
+        let mut mapping = ImportMapping {
+            source_table: source_table.to_string(),
+            source_id: codex.source_id.clone(),
+            target_id: previous
+                .map(|record| record.target_id.clone())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            action: match previous {
+                Some(record) if record.content_hash == hash => ImportAction::Unchanged,
+                Some(_) => ImportAction::Update,
+                None => ImportAction::Create,
+            },
+            error: None,
+        };
+
+        let Some(tx) = tx else {
+            return mapping;
+        };
+
+        if mapping.action == ImportAction::Unchanged {
+            return mapping;
+        }
+
+        let outcome = match write_codex(tx, &mapping.target_id, codex, mapping.action).await {
+            Ok(()) => record_import(tx, source_table, &codex.source_id, &mapping.target_id, &hash).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = outcome {
+            warn!(source_table, source_id = %codex.source_id, "Failed to import legacy record: {}", e);
+            mapping.action = ImportAction::Failed;
+            mapping.error = Some(e.to_string());
+        }
+
+        mapping
+    }
+
+    async fn target_has(&self, table: &str, id: &str) -> bool {
+        sqlx::query(&format!("SELECT 1 FROM {} WHERE id = ?", table))
+            .bind(id)
+            .fetch_optional(&self.target)
+            .await
+            .map(|row| row.is_some())
+            .unwrap_or(false)
+    }
+}
+
+async fn table_names(pool: &Pool<Sqlite>) -> BinderyResult<HashSet<String>> {
+    let rows = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .fetch_all(pool)
+        .await?;
+    rows.iter()
+        .map(|row| row.try_get::<String, _>("name").map_err(BinderyError::from))
+        .collect()
+}
+
+/// Load non-hierarchical relationships keyed by source task ID
+async fn load_relationships(source: &Pool<Sqlite>) -> BinderyResult<HashMap<String, Vec<(String, String)>>> {
+    let rows = sqlx::query(
+        "SELECT source_task_id, target_task_id, relationship_type FROM task_relationships WHERE relationship_type != 'parent_child'"
+    )
+    .fetch_all(source)
+    .await?;
+
+    let mut relationships: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for row in rows {
+        relationships
+            .entry(row.try_get("source_task_id")?)
+            .or_default()
+            .push((row.try_get("relationship_type")?, row.try_get("target_task_id")?));
+    }
+    Ok(relationships)
+}
+
+/// Load and convert legacy tasks, ordered so parents precede their children
+async fn load_tasks(
+    source: &Pool<Sqlite>,
+    relationships: &HashMap<String, Vec<(String, String)>>,
+    options: &ScriptoriumImportOptions,
+    report: &mut ScriptoriumImportReport,
+) -> BinderyResult<Vec<ConvertedTask>> {
+    let rows = sqlx::query("SELECT * FROM tasks").fetch_all(source).await?;
+    report.tasks.found = rows.len();
+
+    let mut tasks = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let Some(id) = column_text(row, "id") else {
+            report.warnings.push("Skipped legacy task without an id".to_string());
+            continue;
+        };
+        tasks.push(convert_task(row, id, relationships, options, &mut report.warnings));
+    }
+
+    let known: HashSet<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    for task in &mut tasks {
+        if let Some(parent) = &task.parent_id {
+            if !known.contains(parent) {
+                report.warnings.push(format!(
+                    "Task {} references missing parent {}; imported as a root task",
+                    task.id, parent
+                ));
+                task.parent_id = None;
+            }
+        }
+    }
+
+    // Order by depth so every parent row exists before its children are inserted
+    let parents: HashMap<String, Option<String>> = tasks.iter()
+        .map(|t| (t.id.clone(), t.parent_id.clone()))
+        .collect();
+    let depth = |id: &str| {
+        let mut depth = 0;
+        let mut current = parents.get(id).cloned().flatten();
+        while let Some(parent) = current {
+            depth += 1;
+            if depth > parents.len() {
+                break; // cycle in legacy data
+            }
+            current = parents.get(&parent).cloned().flatten();
+        }
+        depth
+    };
+    tasks.sort_by_cached_key(|t| (depth(&t.id), t.created_at.clone()));
+
+    debug!("Converted {} legacy tasks", tasks.len());
+    Ok(tasks)
+}
+
+fn convert_task(
+    row: &SqliteRow,
+    id: String,
+    relationships: &HashMap<String, Vec<(String, String)>>,
+    options: &ScriptoriumImportOptions,
+    warnings: &mut Vec<String>,
+) -> ConvertedTask {
+    let metadata = column_json(row, "metadata_json");
+    let execution = column_json(row, "execution_json");
+
+    let tags = metadata["tags"].as_array()
+        .map(|tags| tags.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+
+    let mut labels: serde_json::Map<String, serde_json::Value> = metadata["labels"].as_object()
+        .cloned()
+        .unwrap_or_default();
+    for column in ["feature", "milestone", "creator"] {
+        if let Some(value) = column_text(row, column) {
+            labels.entry(column.to_string()).or_insert(serde_json::Value::String(value));
+        }
+    }
+    if let Some(role) = execution["assigned_role"].as_str() {
+        labels.insert("assigned_role".to_string(), serde_json::Value::String(role.to_string()));
+    }
+    if let Some(related) = relationships.get(&id) {
+        let mut by_type: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (relation, target) in related {
+            by_type.entry(relation.as_str()).or_default().push(target.as_str());
+        }
+        for (relation, targets) in by_type {
+            labels.insert(relation.to_string(), serde_json::Value::String(targets.join(",")));
+        }
+    }
+    labels.insert("legacy_source".to_string(), serde_json::Value::String("vespera-scriptorium".to_string()));
+
+    let legacy_status = column_text(row, "status").unwrap_or_else(|| "todo".to_string());
+    let status = match legacy_status.as_str() {
+        "todo" | "doing" | "review" | "done" | "blocked" | "cancelled" => legacy_status.clone(),
+        "archived" => "done".to_string(),
+        other => {
+            warnings.push(format!("Task {} has unknown status '{}'; imported as todo", id, other));
+            "todo".to_string()
+        }
+    };
+    if status != legacy_status {
+        labels.insert("legacy_status".to_string(), serde_json::Value::String(legacy_status));
+    }
+
+    let legacy_priority = column_text(row, "priority").unwrap_or_else(|| "normal".to_string());
+    let priority = match legacy_priority.as_str() {
+        "critical" | "high" | "normal" | "low" => legacy_priority.clone(),
+        "someday" => "low".to_string(),
+        other => {
+            warnings.push(format!("Task {} has unknown priority '{}'; imported as normal", id, other));
+            "normal".to_string()
+        }
+    };
+    if priority != legacy_priority {
+        labels.insert("legacy_priority".to_string(), serde_json::Value::String(legacy_priority));
+    }
+
+    let created_at = normalize_timestamp(column_text(row, "created_at"), &id, warnings);
+    let updated_at = column_text(row, "updated_at")
+        .map(|ts| normalize_timestamp(Some(ts), &id, warnings))
+        .unwrap_or_else(|| created_at.clone());
+    let due_date = column_text(row, "due_date").map(|ts| normalize_timestamp(Some(ts), &id, warnings));
+
+    ConvertedTask {
+        title: column_text(row, "title").unwrap_or_else(|| format!("Untitled task {}", id)),
+        description: column_text(row, "description").filter(|d| !d.is_empty()),
+        status,
+        priority,
+        parent_id: column_text(row, "parent_id"),
+        project_id: column_text(row, "project_id").or_else(|| options.default_project_id.clone()),
+        assignee: column_text(row, "assignee"),
+        tags,
+        labels,
+        created_at,
+        updated_at,
+        due_date,
+        id,
+    }
+}
+
+/// Load a generic legacy table as Codices, keeping every column in metadata
+async fn load_codices(
+    source: &Pool<Sqlite>,
+    table: &str,
+    template_id: &'static str,
+    title_columns: &[&str],
+    report: &mut ScriptoriumImportReport,
+) -> BinderyResult<Vec<ConvertedCodex>> {
+    let rows = sqlx::query(&format!("SELECT * FROM {}", table)).fetch_all(source).await?;
+    let counts = if table == "sessions" { &mut report.sessions } else { &mut report.artifacts };
+    counts.found = rows.len();
+
+    let mut codices = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let legacy = row_to_json(row);
+        let source_id = match &legacy["id"] {
+            serde_json::Value::String(id) => id.clone(),
+            serde_json::Value::Number(id) => id.to_string(),
+            _ => {
+                report.warnings.push(format!("Skipped legacy {} row without an id", table));
+                continue;
+            }
+        };
+
+        let title = title_columns.iter()
+            .find_map(|column| legacy[*column].as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| format!("{} {}", table.trim_end_matches('s'), source_id));
+        let created_at = normalize_timestamp(
+            legacy["created_at"].as_str().map(|s| s.to_string()),
+            &source_id,
+            &mut report.warnings,
+        );
+
+        let mut metadata = serde_json::json!({
+            "legacy_source": "vespera-scriptorium",
+            "legacy_table": table,
+            "legacy": legacy,
+        });
+        if let Some(task_id) = legacy["task_id"].as_str() {
+            metadata["task_id"] = serde_json::Value::String(task_id.to_string());
+        }
+        if let Some(project_id) = legacy["project_id"].as_str() {
+            metadata["project_id"] = serde_json::Value::String(project_id.to_string());
+        }
+
+        codices.push(ConvertedCodex {
+            source_id,
+            title,
+            template_id,
+            metadata,
+            created_at,
+        });
+    }
+
+    Ok(codices)
+}
+
+async fn write_task(
+    tx: &mut Transaction<'static, Sqlite>,
+    task: &ConvertedTask,
+    action: ImportAction,
+) -> BinderyResult<()> {
+    let tags = serde_json::to_string(&task.tags)
+        .map_err(|e| BinderyError::SerializationError(e.to_string()))?;
+    let labels = serde_json::to_string(&task.labels)
+        .map_err(|e| BinderyError::SerializationError(e.to_string()))?;
+
+    let query = if action == ImportAction::Create {
+        r#"
+        INSERT INTO tasks (id, title, description, status, priority, parent_id, project_id, assignee, tags, labels, created_at, updated_at, due_date)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        "#
+    } else {
+        r#"
+        UPDATE tasks SET title = ?2, description = ?3, status = ?4, priority = ?5, parent_id = ?6,
+            project_id = ?7, assignee = ?8, tags = ?9, labels = ?10, updated_at = ?12, due_date = ?13
+        WHERE id = ?1
+        "#
+    };
+
+    sqlx::query(query)
+        .bind(&task.id)
+        .bind(&task.title)
+        .bind(&task.description)
+        .bind(&task.status)
+        .bind(&task.priority)
+        .bind(&task.parent_id)
+        .bind(&task.project_id)
+        .bind(&task.assignee)
+        .bind(&tags)
+        .bind(&labels)
+        .bind(&task.created_at)
+        .bind(&task.updated_at)
+        .bind(&task.due_date)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+async fn write_codex(
+    tx: &mut Transaction<'static, Sqlite>,
+    target_id: &str,
+    codex: &ConvertedCodex,
+    action: ImportAction,
+) -> BinderyResult<()> {
+    let metadata = serde_json::to_string(&codex.metadata)
+        .map_err(|e| BinderyError::SerializationError(e.to_string()))?;
+    let project_id = codex.metadata["project_id"].as_str();
+    let parent_id = codex.metadata["parent_id"].as_str();
+    let now = Utc::now().to_rfc3339();
+
+    if action == ImportAction::Create {
+        sqlx::query(
+            r#"
+            INSERT INTO codices (id, title, template_id, content, metadata, version, created_at, updated_at, created_by, project_id, parent_id)
+            VALUES (?, ?, ?, ?, ?, 1, ?, ?, 'scriptorium-import', ?, ?)
+            "#,
+        )
+        .bind(target_id)
+        .bind(&codex.title)
+        .bind(codex.template_id)
+        .bind(serde_json::json!({"fields": {}}).to_string())
+        .bind(&metadata)
+        .bind(&codex.created_at)
+        .bind(&now)
+        .bind(project_id)
+        .bind(parent_id)
+        .execute(&mut **tx)
+        .await?;
+    } else {
+        sqlx::query(
+            r#"
+            UPDATE codices SET title = ?, metadata = ?, updated_at = ?, project_id = ?, parent_id = ?, version = version + 1
+            WHERE id = ?
+            "#,
+        )
+        .bind(&codex.title)
+        .bind(&metadata)
+        .bind(&now)
+        .bind(project_id)
+        .bind(parent_id)
+        .bind(target_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn record_import(
+    tx: &mut Transaction<'static, Sqlite>,
+    source_table: &str,
+    source_id: &str,
+    target_id: &str,
+    content_hash: &str,
+) -> BinderyResult<()> {
+    sqlx::query(&format!(
+        "INSERT OR REPLACE INTO {} (source_table, source_id, target_id, content_hash, imported_at) VALUES (?, ?, ?, ?, ?)",
+        IMPORT_MAP_TABLE
+    ))
+    .bind(source_table)
+    .bind(source_id)
+    .bind(target_id)
+    .bind(content_hash)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+fn content_hash<T: Serialize>(value: &T) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(value).unwrap_or_default());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Read a column as text, treating missing columns and NULLs alike
+fn column_text(row: &SqliteRow, column: &str) -> Option<String> {
+    row.try_get::<Option<String>, _>(column).ok().flatten()
+        .or_else(|| row.try_get::<Option<i64>, _>(column).ok().flatten().map(|v| v.to_string()))
+}
+
+fn column_json(row: &SqliteRow, column: &str) -> serde_json::Value {
+    column_text(row, column)
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Convert every column of a row into a JSON object
+fn row_to_json(row: &SqliteRow) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    for (index, column) in row.columns().iter().enumerate() {
+        let value = match row.try_get_raw(index) {
+            Ok(raw) if raw.is_null() => serde_json::Value::Null,
+            Ok(raw) => match raw.type_info().name() {
+                "INTEGER" => row.try_get::<i64, _>(index).map(serde_json::Value::from).unwrap_or_default(),
+                "REAL" => row.try_get::<f64, _>(index).map(serde_json::Value::from).unwrap_or_default(),
+                "BLOB" => serde_json::Value::Null,
+                _ => row.try_get::<String, _>(index).map(serde_json::Value::from).unwrap_or_default(),
+            },
+            Err(_) => serde_json::Value::Null,
+        };
+        object.insert(column.name().to_string(), value);
+    }
+    serde_json::Value::Object(object)
+}
+
+/// Normalize legacy timestamps (Python `isoformat()`, naive = UTC) to RFC 3339
+fn normalize_timestamp(raw: Option<String>, record_id: &str, warnings: &mut Vec<String>) -> String {
+    let Some(raw) = raw else {
+        return Utc::now().to_rfc3339();
+    };
+
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(&raw) {
+        return parsed.with_timezone(&Utc).to_rfc3339();
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(parsed) = NaiveDateTime::parse_from_str(&raw, format) {
+            return parsed.and_utc().to_rfc3339();
+        }
+    }
+
+    warnings.push(format!("Record {} has unparseable timestamp '{}'; using import time", record_id, raw));
+    Utc::now().to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+    use tempfile::TempDir;
+
+    async fn create_legacy_db(dir: &TempDir) -> PathBuf {
+        let path = dir.path().join("legacy.db");
+        let pool = SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path.display())).await.unwrap();
+
+        for statement in [
+            r#"CREATE TABLE tasks (
+                id TEXT PRIMARY KEY, title TEXT NOT NULL, description TEXT, parent_id TEXT,
+                status TEXT NOT NULL, priority TEXT NOT NULL, task_order INTEGER DEFAULT 0,
+                project_id TEXT, feature TEXT, milestone TEXT, assignee TEXT DEFAULT 'User',
+                creator TEXT DEFAULT 'System', created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+                due_date TEXT, started_at TEXT, completed_at TEXT, metadata_json TEXT, execution_json TEXT
+            )"#,
+            r#"CREATE TABLE task_relationships (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, source_task_id TEXT NOT NULL,
+                target_task_id TEXT NOT NULL, relationship_type TEXT NOT NULL, created_at TEXT NOT NULL
+            )"#,
+            "CREATE TABLE sessions (id TEXT PRIMARY KEY, name TEXT, created_at TEXT)",
+            "CREATE TABLE artifacts (id INTEGER PRIMARY KEY, task_id TEXT, session_id TEXT, path TEXT, created_at TEXT)",
+            r#"INSERT INTO tasks VALUES ('child', 'Child', '', 'root', 'archived', 'someday', 0, 'proj', NULL, NULL, 'User', 'System',
+                '2024-05-01T10:00:00.123456', '2024-05-01T10:00:00', NULL, NULL, NULL,
+                '{"tags": ["legacy"], "labels": {"area": "core"}}', '{"assigned_role": "coder"}')"#,
+            r#"INSERT INTO tasks VALUES ('root', 'Root', 'Top level', NULL, 'doing', 'high', 0, 'proj', 'import', NULL, 'User', 'System',
+                '2024-04-01T09:00:00', '2024-04-01T09:00:00', NULL, NULL, NULL, NULL, NULL)"#,
+            "INSERT INTO task_relationships (source_task_id, target_task_id, relationship_type, created_at) VALUES ('child', 'root', 'depends_on', '2024-05-01T10:00:00')",
+            "INSERT INTO sessions VALUES ('s1', 'Planning session', '2024-05-02T08:00:00')",
+            "INSERT INTO artifacts VALUES (1, 'child', 's1', 'notes/plan.md', '2024-05-02T08:30:00')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool.close().await;
+        path
+    }
+
+    async fn create_target_db(dir: &TempDir) -> SqlitePool {
+        let path = dir.path().join("bindery.db");
+        let pool = SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path.display())).await.unwrap();
+
+        sqlx::query(
+            r#"CREATE TABLE tasks (
+                id TEXT PRIMARY KEY, title TEXT NOT NULL, description TEXT, status TEXT NOT NULL DEFAULT 'todo',
+                priority TEXT NOT NULL DEFAULT 'normal', parent_id TEXT, project_id TEXT, assignee TEXT,
+                tags TEXT, labels TEXT, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, due_date TEXT,
+                FOREIGN KEY(parent_id) REFERENCES tasks(id) ON DELETE CASCADE
+            )"#,
+        ).execute(&pool).await.unwrap();
+        sqlx::query(
+            r#"CREATE TABLE codices (
+                id TEXT PRIMARY KEY, template_id TEXT NOT NULL, title TEXT NOT NULL, content TEXT NOT NULL,
+                metadata TEXT NOT NULL, crdt_state TEXT, version INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL, updated_at TEXT NOT NULL, created_by TEXT, project_id TEXT,
+                parent_id TEXT, FOREIGN KEY(parent_id) REFERENCES codices(id) ON DELETE SET NULL
+            )"#,
+        ).execute(&pool).await.unwrap();
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let dir = TempDir::new().unwrap();
+        let source = create_legacy_db(&dir).await;
+        let target = create_target_db(&dir).await;
+
+        let importer = ScriptoriumImporter::new(target.clone());
+        let report = importer
+            .import(&source, &ScriptoriumImportOptions { dry_run: true, ..Default::default() })
+            .await
+            .unwrap();
+
+        assert_eq!(report.tasks.created, 2);
+        assert_eq!(report.sessions.created, 1);
+        assert_eq!(report.artifacts.created, 1);
+        assert_eq!(report.relationships_mapped, 1);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks").fetch_one(&target).await.unwrap();
+        assert_eq!(count, 0);
+        let map_tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = 'scriptorium_import_map'")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(map_tables, 0);
+    }
+
+    #[tokio::test]
+    async fn test_import_converts_tasks_and_codices() {
+        let dir = TempDir::new().unwrap();
+        let source = create_legacy_db(&dir).await;
+        let target = create_target_db(&dir).await;
+
+        let report = ScriptoriumImporter::new(target.clone())
+            .import(&source, &ScriptoriumImportOptions::default())
+            .await
+            .unwrap();
+        assert!(!report.has_errors(), "{}", report.to_markdown());
+
+        let row = sqlx::query("SELECT status, priority, parent_id, tags, labels, created_at FROM tasks WHERE id = 'child'")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<String, _>("status"), "done");
+        assert_eq!(row.get::<String, _>("priority"), "low");
+        assert_eq!(row.get::<Option<String>, _>("parent_id").as_deref(), Some("root"));
+        assert_eq!(row.get::<String, _>("tags"), r#"["legacy"]"#);
+        let labels: serde_json::Value = serde_json::from_str(&row.get::<String, _>("labels")).unwrap();
+        assert_eq!(labels["legacy_status"], "archived");
+        assert_eq!(labels["assigned_role"], "coder");
+        assert_eq!(labels["depends_on"], "root");
+        assert!(DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at")).is_ok());
+
+        let session_id: String = sqlx::query_scalar("SELECT id FROM codices WHERE template_id = ?")
+            .bind(LEGACY_SESSION_TEMPLATE)
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        let artifact = sqlx::query("SELECT title, parent_id, metadata FROM codices WHERE template_id = ?")
+            .bind(LEGACY_ARTIFACT_TEMPLATE)
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(artifact.get::<String, _>("title"), "notes/plan.md");
+        assert_eq!(artifact.get::<Option<String>, _>("parent_id"), Some(session_id));
+        let metadata: serde_json::Value = serde_json::from_str(&artifact.get::<String, _>("metadata")).unwrap();
+        assert_eq!(metadata["task_id"], "child");
+    }
+
+    #[tokio::test]
+    async fn test_reimport_is_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let source = create_legacy_db(&dir).await;
+        let target = create_target_db(&dir).await;
+        let importer = ScriptoriumImporter::new(target.clone());
+        let options = ScriptoriumImportOptions::default();
+
+        importer.import(&source, &options).await.unwrap();
+        let report = importer.import(&source, &options).await.unwrap();
+        assert_eq!(report.tasks.unchanged, 2);
+        assert_eq!(report.sessions.unchanged, 1);
+        assert_eq!(report.artifacts.unchanged, 1);
+
+        // Change one legacy task and re-run
+        let legacy = SqlitePool::connect(&format!("sqlite:{}", source.display())).await.unwrap();
+        sqlx::query("UPDATE tasks SET title = 'Renamed root' WHERE id = 'root'").execute(&legacy).await.unwrap();
+        legacy.close().await;
+
+        let report = importer.import(&source, &options).await.unwrap();
+        assert_eq!(report.tasks.updated, 1);
+        assert_eq!(report.tasks.unchanged, 1);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM codices").fetch_one(&target).await.unwrap();
+        assert_eq!(count, 2);
+        let title: String = sqlx::query_scalar("SELECT title FROM tasks WHERE id = 'root'").fetch_one(&target).await.unwrap();
+        assert_eq!(title, "Renamed root");
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_task_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let source = create_legacy_db(&dir).await;
+        let target = create_target_db(&dir).await;
+
+        sqlx::query("INSERT INTO tasks (id, title, created_at, updated_at) VALUES ('root', 'Native task', 'now', 'now')")
+            .execute(&target)
+            .await
+            .unwrap();
+
+        let report = ScriptoriumImporter::new(target.clone())
+            .import(&source, &ScriptoriumImportOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(report.tasks.conflicts, 1);
+        assert!(report.has_errors());
+        let title: String = sqlx::query_scalar("SELECT title FROM tasks WHERE id = 'root'").fetch_one(&target).await.unwrap();
+        assert_eq!(title, "Native task");
+    }
+}
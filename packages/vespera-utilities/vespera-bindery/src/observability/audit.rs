@@ -8,16 +8,22 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
+use futures::TryStreamExt;
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{Pool, Sqlite, Row};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
+use super::audit_analytics::{AuditAggregate, AuditAggregationQuery, AuditAnomaly, AuditAnomalyConfig, AuditGroupBy, AuditTimeBucket, FailedAuthSummary};
 use crate::errors::{BinderyError, BinderyResult};
+use crate::secrets::redact_field;
 
 /// Audit event representing a security-sensitive operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +117,9 @@ pub struct AuditConfig {
     pub enable_compression: bool,
     /// Batch size for bulk operations
     pub batch_size: usize,
+    /// Thresholds for anomaly flags
+    #[serde(default)]
+    pub anomaly: AuditAnomalyConfig,
 }
 
 impl Default for AuditConfig {
@@ -122,6 +131,7 @@ impl Default for AuditConfig {
             retention_days: Some(365), // 1 year default retention
             enable_compression: true,
             batch_size: 1000,
+            anomaly: AuditAnomalyConfig::default(),
         }
     }
 }
@@ -141,6 +151,9 @@ pub struct AuditQueryFilter {
     pub end_time: Option<DateTime<Utc>>,
     /// Filter by resource
     pub resource: Option<String>,
+    /// Filter by the OpenTelemetry trace the event was logged under
+    #[serde(default)]
+    pub trace_id: Option<String>,
     /// Maximum number of results to return
     pub limit: Option<i64>,
     /// Offset for pagination
@@ -166,22 +179,87 @@ pub struct AuditStats {
     pub first_event_time: Option<DateTime<Utc>>,
     /// Hash chain integrity status
     pub hash_chain_valid: bool,
+    /// Hourly event counts per operation type over the last day
+    #[serde(default)]
+    pub recent_activity: Vec<AuditAggregate>,
+    /// Accounts with the most failed logins over the last day
+    #[serde(default)]
+    pub top_failed_auth: Vec<FailedAuthSummary>,
+    /// Anomalies flagged as of now
+    #[serde(default)]
+    pub anomalies: Vec<AuditAnomaly>,
+}
+
+/// Number of accounts listed in [`AuditStats::top_failed_auth`]
+const STATS_TOP_FAILED_AUTH: i64 = 10;
+
+/// How often [`init_audit_logging`](super::init_audit_logging) applies the retention policy
+pub const DEFAULT_RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Result of walking the whole hash chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainVerification {
+    /// True when every event hash and link checked out
+    pub valid: bool,
+    /// Number of events examined
+    pub events_checked: u64,
+    /// Whether events were linked at all (`enable_hash_chaining`)
+    pub hash_chaining: bool,
+    /// The first problem found, walking from the oldest retained event
+    pub first_broken_link: Option<BrokenLink>,
+}
+
+/// Where and how the hash chain first breaks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokenLink {
+    /// Event at which the break was detected
+    pub event_id: Option<String>,
+    /// Zero-based position of that event in chain order
+    pub position: u64,
+    /// What was wrong with it
+    pub reason: ChainBreak,
+}
+
+/// Kinds of hash chain breakage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainBreak {
+    /// The event's contents no longer match its stored hash
+    HashMismatch { stored: String, calculated: String },
+    /// The event doesn't point at the event before it
+    LinkMismatch { expected: Option<String>, found: Option<String> },
+    /// Events after the last retained one are missing (the chain head doesn't match)
+    TruncatedTail { expected_head: Option<String>, found_head: Option<String> },
 }
 
 /// Main audit logger implementation
 #[derive(Debug)]
 pub struct AuditLogger {
     config: AuditConfig,
-    pool: Pool<Sqlite>,
+    pub(super) pool: Pool<Sqlite>,
+    /// Chain head; held for writing across each append so concurrent events can't fork the chain
     last_hash: Arc<RwLock<Option<String>>>,
+    retention_handle: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl AuditLogger {
     /// Create a new audit logger with the specified configuration
     pub async fn new(config: AuditConfig) -> BinderyResult<Self> {
+        Self::open(config, None).await
+    }
+
+    /// Open the audit log, keying its database with `key` (a SQLCipher
+    /// `PRAGMA key` value) when given
+    pub(crate) async fn open(config: AuditConfig, key: Option<&str>) -> BinderyResult<Self> {
         // Create audit database connection
-        let database_url = format!("sqlite:{}", config.audit_db_path.display());
-        let pool = sqlx::SqlitePool::connect(&database_url)
+        let options = SqliteConnectOptions::new()
+            .filename(&config.audit_db_path)
+            .create_if_missing(true);
+        let options = crate::database::with_key(options, key);
+        // Report a wrong or missing key as such rather than a schema error
+        let check = crate::database::check_readable(&options, "the audit database", key.is_some()).await?;
+        sqlx::Connection::close(check).await?;
+        let pool = sqlx::SqlitePool::connect_with(options)
             .await
             .map_err(|e| BinderyError::DatabaseError(format!("Failed to connect to audit database: {}", e)))?;
 
@@ -189,6 +267,7 @@ impl AuditLogger {
             config,
             pool,
             last_hash: Arc::new(RwLock::new(None)),
+            retention_handle: Mutex::new(None),
         };
 
         // Initialize audit schema
@@ -201,6 +280,11 @@ impl AuditLogger {
         Ok(logger)
     }
 
+    /// The configuration this logger was created with
+    pub fn config(&self) -> &AuditConfig {
+        &self.config
+    }
+
     /// Initialize the audit database schema
     async fn initialize_schema(&self) -> BinderyResult<()> {
         // Create audit events table
@@ -267,6 +351,21 @@ impl AuditLogger {
             .await
             .map_err(|e| BinderyError::DatabaseError(format!("Failed to initialize chain state: {}", e)))?;
 
+        // The hash the oldest retained event links to; moves forward as retention prunes the chain
+        let columns = sqlx::query("SELECT name FROM pragma_table_info('audit_chain_state')")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to inspect chain state: {}", e)))?;
+        let has_anchor = columns
+            .iter()
+            .any(|row| row.try_get::<String, _>("name").map(|name| name == "anchor_hash").unwrap_or(false));
+        if !has_anchor {
+            sqlx::query("ALTER TABLE audit_chain_state ADD COLUMN anchor_hash TEXT")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| BinderyError::DatabaseError(format!("Failed to add chain anchor: {}", e)))?;
+        }
+
         Ok(())
     }
 
@@ -289,10 +388,22 @@ impl AuditLogger {
     pub async fn log_event(&self, event: AuditEvent) -> BinderyResult<()> {
         let mut event = event;
 
+        // Secret values never reach the audit trail; secret:// references do
+        for (name, value) in event.operation.details.iter_mut().chain(event.metadata.iter_mut()) {
+            redact_field(name, value);
+        }
+
+        // Correlate with the active trace, unless the caller recorded one already
+        if let Some((trace_id, span_id)) = super::propagation::current_trace_ids() {
+            event.metadata.entry("trace_id".to_string()).or_insert_with(|| trace_id.into());
+            event.metadata.entry("span_id".to_string()).or_insert_with(|| span_id.into());
+        }
+
+        let mut last_hash = self.last_hash.write().await;
+
         // Set hash chain if enabled
         if self.config.enable_hash_chaining {
-            let last_hash = self.last_hash.read().await.clone();
-            event.previous_hash = last_hash;
+            event.previous_hash = last_hash.clone();
         }
 
         // Calculate event hash
@@ -303,9 +414,10 @@ impl AuditLogger {
 
         // Update hash chain state
         if self.config.enable_hash_chaining {
-            *self.last_hash.write().await = Some(event.event_hash.clone());
             self.update_chain_state(&event).await?;
+            *last_hash = Some(event.event_hash.clone());
         }
+        drop(last_hash);
 
         debug!("Audit event logged: {} - {}", event.operation.operation_type, event.operation.action);
         Ok(())
@@ -409,6 +521,12 @@ impl AuditLogger {
         if let Some(resource) = &filter.resource {
             conditions.push(format!("resource = '{}'", resource));
         }
+        if let Some(trace_id) = &filter.trace_id {
+            if !trace_id.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(BinderyError::InvalidInput(format!("Invalid trace ID: {}", trace_id)));
+            }
+            conditions.push(format!("JSON_EXTRACT(metadata, '$.trace_id') = '{}'", trace_id.to_lowercase()));
+        }
 
         if !conditions.is_empty() {
             query.push_str(" AND ");
@@ -551,6 +669,14 @@ impl AuditLogger {
         // Validate hash chain
         let hash_chain_valid = self.validate_hash_chain().await?;
 
+        // Summaries for dashboards
+        let day_ago = Utc::now() - chrono::Duration::hours(24);
+        let recent_activity = self
+            .aggregate_events(&AuditAggregationQuery::new(AuditGroupBy::OperationType, AuditTimeBucket::Hour).since(day_ago))
+            .await?;
+        let top_failed_auth = self.top_failed_auth(day_ago, STATS_TOP_FAILED_AUTH).await?;
+        let anomalies = self.detect_anomalies().await?;
+
         Ok(AuditStats {
             total_events,
             successful_operations,
@@ -560,62 +686,110 @@ impl AuditLogger {
             first_event_time,
             last_event_time,
             hash_chain_valid,
+            recent_activity,
+            top_failed_auth,
+            anomalies,
         })
     }
 
     /// Validate the integrity of the hash chain
     pub async fn validate_hash_chain(&self) -> BinderyResult<bool> {
-        if !self.config.enable_hash_chaining {
-            return Ok(true); // No chain to validate
+        let verification = self.verify_chain().await?;
+        if let Some(broken) = &verification.first_broken_link {
+            warn!("Hash chain break detected at event {:?} (position {}): {:?}",
+                  broken.event_id, broken.position, broken.reason);
         }
+        Ok(verification.valid)
+    }
 
-        let events = self.query_events(AuditQueryFilter {
-            limit: None,
-            ..Default::default()
-        }).await?;
+    /// Walk every retained event from oldest to newest and check the hash chain
+    ///
+    /// Each event's hash is recalculated and, with hash chaining enabled, its
+    /// `previous_hash` must match the event before it — or, for the oldest
+    /// retained event, the anchor left behind by retention pruning. The newest
+    /// event must also be the recorded chain head, so deleting events from the
+    /// end is caught too. Stops at the first broken link.
+    pub async fn verify_chain(&self) -> BinderyResult<ChainVerification> {
+        // Block appends and pruning so the chain doesn't move underneath us
+        let _head = self.last_hash.read().await;
+        let chaining = self.config.enable_hash_chaining;
+
+        let state = sqlx::query("SELECT last_hash, last_event_id, anchor_hash FROM audit_chain_state WHERE id = 1")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to load chain state: {}", e)))?;
+        let head: Option<String> = state.try_get("last_hash")?;
+        let head_event_id: Option<String> = state.try_get("last_event_id")?;
+        let mut expected_previous: Option<String> = state.try_get("anchor_hash")?;
 
-        if events.is_empty() {
-            return Ok(true);
-        }
+        let mut events_checked = 0u64;
+        let mut last_event_id = None;
+        let mut rows = sqlx::query("SELECT * FROM audit_events ORDER BY rowid ASC").fetch(&self.pool);
 
-        // Check each event's hash
-        for event in &events {
-            let calculated_hash = self.calculate_event_hash(event)?;
-            if calculated_hash != event.event_hash {
-                warn!("Hash mismatch detected for event {}: expected {}, got {}",
-                      event.id, event.event_hash, calculated_hash);
-                return Ok(false);
+        while let Some(row) = rows
+            .try_next()
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to read audit events: {}", e)))?
+        {
+            let event = self.row_to_audit_event(&row)?;
+            let position = events_checked;
+            events_checked += 1;
+
+            let calculated = self.calculate_event_hash(&event)?;
+            let reason = if calculated != event.event_hash {
+                Some(ChainBreak::HashMismatch { stored: event.event_hash.clone(), calculated })
+            } else if chaining && event.previous_hash != expected_previous {
+                Some(ChainBreak::LinkMismatch { expected: expected_previous.clone(), found: event.previous_hash.clone() })
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                return Ok(ChainVerification::broken(events_checked, chaining, Some(event.id), position, reason));
             }
-        }
 
-        // Check chain linkage
-        for i in 1..events.len() {
-            let current = &events[i];
-            let previous = &events[i - 1];
+            expected_previous = Some(event.event_hash);
+            last_event_id = Some(event.id);
+        }
 
-            if current.previous_hash.as_ref() != Some(&previous.event_hash) {
-                warn!("Hash chain break detected between events {} and {}",
-                      previous.id, current.id);
-                return Ok(false);
-            }
+        if chaining && expected_previous != head {
+            let reason = ChainBreak::TruncatedTail { expected_head: head, found_head: expected_previous };
+            let event_id = head_event_id.or(last_event_id);
+            return Ok(ChainVerification::broken(events_checked, chaining, event_id, events_checked, reason));
         }
 
-        Ok(true)
+        Ok(ChainVerification {
+            valid: true,
+            events_checked,
+            hash_chaining: chaining,
+            first_broken_link: None,
+        })
     }
 
     /// Clean up old audit events based on retention policy
+    ///
+    /// Only ever removes the oldest events, so the remaining ones still form
+    /// one unbroken chain; the hash the new oldest event links to is recorded
+    /// as the chain anchor for [`verify_chain`](Self::verify_chain).
     pub async fn cleanup_old_events(&self) -> BinderyResult<usize> {
+        // Hold off appends until the new anchor is recorded
+        let head = self.last_hash.write().await;
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to start cleanup transaction: {}", e)))?;
         let mut deleted_count = 0;
 
-        // Clean up by retention days
+        // Clean up by retention days, up to the newest event past the cutoff
         if let Some(retention_days) = self.config.retention_days {
             let cutoff_date = Utc::now() - chrono::Duration::days(retention_days as i64);
 
-            let result = sqlx::query("DELETE FROM audit_events WHERE timestamp < ?")
-                .bind(cutoff_date.to_rfc3339())
-                .execute(&self.pool)
-                .await
-                .map_err(|e| BinderyError::DatabaseError(format!("Failed to delete old events: {}", e)))?;
+            let result = sqlx::query(
+                "DELETE FROM audit_events WHERE rowid <= (SELECT MAX(rowid) FROM audit_events WHERE timestamp < ?)"
+            )
+            .bind(cutoff_date.to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to delete old events: {}", e)))?;
 
             deleted_count += result.rows_affected() as usize;
         }
@@ -623,7 +797,7 @@ impl AuditLogger {
         // Clean up by max events count
         if let Some(max_events) = self.config.max_events {
             let count_row = sqlx::query("SELECT COUNT(*) as count FROM audit_events")
-                .fetch_one(&self.pool)
+                .fetch_one(&mut *tx)
                 .await
                 .map_err(|e| BinderyError::DatabaseError(format!("Failed to count events: {}", e)))?;
 
@@ -633,10 +807,10 @@ impl AuditLogger {
                 let excess = total_count - max_events as i64;
 
                 let result = sqlx::query(
-                    "DELETE FROM audit_events WHERE id IN (SELECT id FROM audit_events ORDER BY timestamp ASC LIMIT ?)"
+                    "DELETE FROM audit_events WHERE rowid IN (SELECT rowid FROM audit_events ORDER BY rowid ASC LIMIT ?)"
                 )
                 .bind(excess)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await
                 .map_err(|e| BinderyError::DatabaseError(format!("Failed to delete excess events: {}", e)))?;
 
@@ -644,12 +818,85 @@ impl AuditLogger {
             }
         }
 
+        if deleted_count > 0 && self.config.enable_hash_chaining {
+            let oldest = sqlx::query("SELECT previous_hash FROM audit_events ORDER BY rowid ASC LIMIT 1")
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| BinderyError::DatabaseError(format!("Failed to find oldest event: {}", e)))?;
+            // With everything pruned, the next event will link to the current head
+            let anchor = match oldest {
+                Some(row) => row.try_get::<Option<String>, _>("previous_hash")?,
+                None => head.clone(),
+            };
+
+            sqlx::query("UPDATE audit_chain_state SET anchor_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = 1")
+                .bind(anchor)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| BinderyError::DatabaseError(format!("Failed to update chain anchor: {}", e)))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to commit cleanup: {}", e)))?;
+
         if deleted_count > 0 {
             info!("Cleaned up {} old audit events", deleted_count);
         }
 
         Ok(deleted_count)
     }
+
+    /// Apply the retention policy every `interval` in the background
+    ///
+    /// Replaces any job already running; the job stops on its own once the
+    /// logger is dropped.
+    pub async fn start_retention_job(self: &Arc<Self>, interval: Duration) {
+        let logger = Arc::downgrade(self);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(logger) = logger.upgrade() else { break };
+                if let Err(e) = logger.cleanup_old_events().await {
+                    error!("Audit retention job failed: {}", e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.retention_handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// Stop the background retention job, if one is running
+    pub async fn stop_retention_job(&self) {
+        if let Some(handle) = self.retention_handle.lock().await.take() {
+            handle.abort();
+        }
+    }
+
+    /// Let in-flight events finish writing, then close the audit database
+    ///
+    /// Events logged afterwards fail.
+    pub async fn close(&self) {
+        self.stop_retention_job().await;
+        // Appends hold the chain head for writing until their event is stored
+        let _chain = self.last_hash.write().await;
+        self.pool.close().await;
+        info!("Audit log closed");
+    }
+}
+
+impl ChainVerification {
+    fn broken(events_checked: u64, hash_chaining: bool, event_id: Option<String>, position: u64, reason: ChainBreak) -> Self {
+        Self {
+            valid: false,
+            events_checked,
+            hash_chaining,
+            first_broken_link: Some(BrokenLink { event_id, position, reason }),
+        }
+    }
 }
 
 /// Helper functions for creating common audit events
@@ -724,6 +971,123 @@ pub fn create_migration_event(
     }
 }
 
+/// Create an audit event for moving a Codex to the trash, restoring it or
+/// purging it (`action` is `trash`, `restore` or `purge`)
+pub fn create_trash_event(
+    user_context: UserContext,
+    codex_id: &str,
+    title: Option<&str>,
+    action: &str,
+    outcome: OperationOutcome,
+) -> AuditEvent {
+    let mut details = HashMap::new();
+    if let Some(title) = title {
+        details.insert("title".to_string(), serde_json::Value::String(title.to_string()));
+    }
+
+    AuditEvent {
+        id: Uuid::new_v4().to_string(),
+        timestamp: Utc::now(),
+        user_context,
+        operation: Operation {
+            operation_type: "codex_trash".to_string(),
+            action: action.to_string(),
+            resource: format!("codex:{}", codex_id),
+            details,
+        },
+        security_context: SecurityContext {
+            roles: Vec::new(),
+            permissions: vec!["codex:delete".to_string()],
+            security_level: Some("medium".to_string()),
+            auth_method: None,
+        },
+        outcome,
+        previous_hash: None,
+        event_hash: String::new(),
+        metadata: HashMap::new(),
+    }
+}
+
+/// Create an audit event for sync access refused by a Codex's access
+/// control list (`action` is `reject_operation` or `deny_read`)
+pub fn create_codex_access_event(
+    user_context: UserContext,
+    codex_id: &str,
+    action: &str,
+    details: HashMap<String, serde_json::Value>,
+) -> AuditEvent {
+    AuditEvent {
+        id: Uuid::new_v4().to_string(),
+        timestamp: Utc::now(),
+        user_context,
+        operation: Operation {
+            operation_type: "codex_access".to_string(),
+            action: action.to_string(),
+            resource: format!("codex:{}", codex_id),
+            details,
+        },
+        security_context: SecurityContext {
+            roles: Vec::new(),
+            permissions: Vec::new(),
+            security_level: Some("high".to_string()),
+            auth_method: None,
+        },
+        outcome: OperationOutcome {
+            success: false,
+            result_code: Some(403),
+            error_message: None,
+            duration_ms: 0,
+            records_affected: None,
+        },
+        previous_hash: None,
+        event_hash: String::new(),
+        metadata: HashMap::new(),
+    }
+}
+
+/// Create an audit event for one step of an approval request: `request`,
+/// `approve`, `reject` or `execute`
+#[allow(clippy::too_many_arguments)]
+pub fn create_approval_event(
+    user_context: UserContext,
+    request_id: &str,
+    gated_action: &str,
+    resource: &str,
+    role: &str,
+    step: &str,
+    comment: Option<&str>,
+    outcome: OperationOutcome,
+) -> AuditEvent {
+    let mut details = HashMap::new();
+    details.insert("gated_action".to_string(), serde_json::Value::String(gated_action.to_string()));
+    details.insert("resource".to_string(), serde_json::Value::String(resource.to_string()));
+    if let Some(comment) = comment {
+        details.insert("comment".to_string(), serde_json::Value::String(comment.to_string()));
+    }
+
+    AuditEvent {
+        id: Uuid::new_v4().to_string(),
+        timestamp: Utc::now(),
+        user_context,
+        operation: Operation {
+            operation_type: "approval".to_string(),
+            action: step.to_string(),
+            resource: format!("approval:{}", request_id),
+            details,
+        },
+        security_context: SecurityContext {
+            roles: vec![role.to_string()],
+            permissions: vec![format!("{}:approve", gated_action)],
+            security_level: Some("high".to_string()),
+            auth_method: None,
+        },
+        outcome,
+        previous_hash: None,
+        event_hash: String::new(),
+        metadata: HashMap::new(),
+    }
+}
+
 /// Create a configuration change audit event
 pub fn create_config_change_event(
     user_context: UserContext,
@@ -732,11 +1096,17 @@ pub fn create_config_change_event(
     new_value: &str,
     outcome: OperationOutcome,
 ) -> AuditEvent {
+    // Values of secret settings (API keys, tokens, ...) are not recorded
+    let value = |v: &str| {
+        let mut value = serde_json::Value::String(v.to_string());
+        redact_field(config_key, &mut value);
+        value
+    };
     let mut details = HashMap::new();
     details.insert("config_key".to_string(), serde_json::Value::String(config_key.to_string()));
-    details.insert("new_value".to_string(), serde_json::Value::String(new_value.to_string()));
+    details.insert("new_value".to_string(), value(new_value));
     if let Some(old_val) = old_value {
-        details.insert("old_value".to_string(), serde_json::Value::String(old_val.to_string()));
+        details.insert("old_value".to_string(), value(old_val));
     }
 
     AuditEvent {
@@ -813,6 +1183,7 @@ mod tests {
             retention_days: Some(30),
             enable_compression: false,
             batch_size: 100,
+            anomaly: Default::default(),
         };
 
         let logger = AuditLogger::new(config).await.expect("Failed to create audit logger");
@@ -900,6 +1271,115 @@ mod tests {
         assert!(stats.hash_chain_valid, "Hash chain should be reported as valid in stats");
     }
 
+    async fn log_test_events(logger: &AuditLogger, count: usize) {
+        for i in 0..count {
+            let event = create_role_execution_event(
+                UserContext { user_id: Some(format!("user_{}", i)), session_id: None, source_ip: None, user_agent: None },
+                "test_role",
+                &format!("task_{}", i),
+                OperationOutcome { success: true, result_code: None, error_message: None, duration_ms: 1, records_affected: None },
+                vec![],
+            );
+            logger.log_event(event).await.expect("Failed to log event");
+        }
+    }
+
+    async fn event_id_at(logger: &AuditLogger, position: i64) -> String {
+        sqlx::query("SELECT id FROM audit_events ORDER BY rowid ASC LIMIT 1 OFFSET ?")
+            .bind(position)
+            .fetch_one(&logger.pool)
+            .await
+            .unwrap()
+            .get("id")
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain_reports_first_broken_link() {
+        let (logger, _temp_dir) = setup_test_audit_logger().await;
+        log_test_events(&logger, 5).await;
+
+        let verification = logger.verify_chain().await.unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.events_checked, 5);
+
+        // Rewriting an event's contents breaks its own hash
+        let edited = event_id_at(&logger, 2).await;
+        sqlx::query("UPDATE audit_events SET user_id = 'intruder' WHERE id = ?")
+            .bind(&edited)
+            .execute(&logger.pool)
+            .await
+            .unwrap();
+
+        let verification = logger.verify_chain().await.unwrap();
+        let broken = verification.first_broken_link.unwrap();
+        assert!(!verification.valid);
+        assert_eq!(broken.position, 2);
+        assert_eq!(broken.event_id.as_deref(), Some(edited.as_str()));
+        assert!(matches!(broken.reason, ChainBreak::HashMismatch { .. }));
+        assert!(!logger.validate_hash_chain().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain_detects_deleted_events() {
+        let (logger, _temp_dir) = setup_test_audit_logger().await;
+        log_test_events(&logger, 4).await;
+
+        // Dropping an event from the middle leaves its successor dangling
+        let removed = event_id_at(&logger, 1).await;
+        let successor = event_id_at(&logger, 2).await;
+        sqlx::query("DELETE FROM audit_events WHERE id = ?").bind(&removed).execute(&logger.pool).await.unwrap();
+
+        let broken = logger.verify_chain().await.unwrap().first_broken_link.unwrap();
+        assert_eq!(broken.event_id.as_deref(), Some(successor.as_str()));
+        assert!(matches!(broken.reason, ChainBreak::LinkMismatch { .. }));
+
+        // Dropping the newest events no longer matches the recorded head
+        let (logger, _temp_dir) = setup_test_audit_logger().await;
+        log_test_events(&logger, 3).await;
+        let newest = event_id_at(&logger, 2).await;
+        sqlx::query("DELETE FROM audit_events WHERE id = ?").bind(&newest).execute(&logger.pool).await.unwrap();
+
+        let broken = logger.verify_chain().await.unwrap().first_broken_link.unwrap();
+        assert_eq!(broken.event_id.as_deref(), Some(newest.as_str()));
+        assert!(matches!(broken.reason, ChainBreak::TruncatedTail { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_retention_keeps_chain_verifiable() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = Arc::new(AuditLogger::new(AuditConfig {
+            audit_db_path: temp_dir.path().join("audit.db"),
+            max_events: Some(3),
+            retention_days: Some(30),
+            ..Default::default()
+        }).await.unwrap());
+        log_test_events(&logger, 5).await;
+
+        // Backdate the oldest event past the retention window
+        let oldest = event_id_at(&logger, 0).await;
+        sqlx::query("UPDATE audit_events SET timestamp = ? WHERE id = ?")
+            .bind((Utc::now() - chrono::Duration::days(60)).to_rfc3339())
+            .bind(&oldest)
+            .execute(&logger.pool)
+            .await
+            .unwrap();
+
+        assert_eq!(logger.cleanup_old_events().await.unwrap(), 2);
+        let verification = logger.verify_chain().await.unwrap();
+        assert!(verification.valid, "{:?}", verification.first_broken_link);
+        assert_eq!(verification.events_checked, 3);
+
+        // New events keep extending the pruned chain
+        log_test_events(&logger, 2).await;
+        logger.start_retention_job(Duration::from_millis(10)).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        logger.stop_retention_job().await;
+
+        let verification = logger.verify_chain().await.unwrap();
+        assert!(verification.valid, "{:?}", verification.first_broken_link);
+        assert_eq!(verification.events_checked, 3);
+    }
+
     #[tokio::test]
     async fn test_query_events_with_filters() {
         let (logger, _temp_dir) = setup_test_audit_logger().await;
@@ -958,4 +1438,38 @@ mod tests {
         assert_eq!(successful_events.len(), 1);
         assert!(successful_events[0].outcome.success);
     }
+
+    #[tokio::test]
+    async fn test_secrets_are_redacted() {
+        let (logger, _temp_dir) = setup_test_audit_logger().await;
+
+        let mut event = create_role_execution_event(
+            UserContext { user_id: Some("user".to_string()), session_id: None, source_ip: None, user_agent: None },
+            "test_role",
+            "task",
+            OperationOutcome { success: true, result_code: None, error_message: None, duration_ms: 1, records_affected: None },
+            vec![],
+        );
+        event.metadata.insert("api_key".to_string(), "sk-ant-plaintext".into());
+        event.metadata.insert("webhook_url".to_string(), "secret://hooks/deploy".into());
+        event.metadata.insert("request".to_string(), serde_json::json!({ "headers": { "Authorization": "Bearer abc" } }));
+        logger.log_event(event).await.expect("Failed to log event");
+
+        let stored = &logger.query_events(AuditQueryFilter::default()).await.unwrap()[0];
+        assert_eq!(stored.metadata["api_key"], crate::secrets::REDACTED);
+        assert_eq!(stored.metadata["webhook_url"], "secret://hooks/deploy");
+        assert_eq!(stored.metadata["request"]["headers"]["Authorization"], crate::secrets::REDACTED);
+        assert!(logger.verify_chain().await.unwrap().valid);
+
+        let outcome = OperationOutcome { success: true, result_code: None, error_message: None, duration_ms: 1, records_affected: None };
+        let event = create_config_change_event(
+            UserContext { user_id: None, session_id: None, source_ip: None, user_agent: None },
+            "providers.anthropic.api_key",
+            Some("sk-ant-old"),
+            "secret://anthropic/api_key",
+            outcome,
+        );
+        assert_eq!(event.operation.details["old_value"], crate::secrets::REDACTED);
+        assert_eq!(event.operation.details["new_value"], "secret://anthropic/api_key");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,400 @@
+//! Aggregations, failed-login reports and anomaly flags over the audit log
+//!
+//! Everything is computed inside SQLite so dashboards get summaries without
+//! pulling raw events. [`AuditLogger::get_stats`] includes a recent-activity
+//! summary, the most-targeted accounts and any active anomaly flags; the
+//! methods here answer narrower questions.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use super::audit::AuditLogger;
+use crate::errors::{BinderyError, BinderyResult};
+
+/// Operation types whose failures count as denials
+pub const DENIAL_OPERATION_TYPES: &[&str] = &["authentication", "authorization"];
+
+const FAILED: &str = "NOT JSON_EXTRACT(outcome, '$.success')";
+
+/// Time bucket width for aggregations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTimeBucket {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl AuditTimeBucket {
+    fn strftime_format(self) -> &'static str {
+        match self {
+            AuditTimeBucket::Minute => "%Y-%m-%dT%H:%M:00Z",
+            AuditTimeBucket::Hour => "%Y-%m-%dT%H:00:00Z",
+            AuditTimeBucket::Day => "%Y-%m-%dT00:00:00Z",
+        }
+    }
+}
+
+/// What to count events by within each bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditGroupBy {
+    /// User ID (`<anonymous>` when none was recorded)
+    User,
+    /// Operation type
+    OperationType,
+    /// `success` or `failure`
+    Outcome,
+}
+
+impl AuditGroupBy {
+    fn expression(self) -> &'static str {
+        match self {
+            AuditGroupBy::User => "COALESCE(user_id, '<anonymous>')",
+            AuditGroupBy::OperationType => "operation_type",
+            AuditGroupBy::Outcome => "CASE WHEN JSON_EXTRACT(outcome, '$.success') THEN 'success' ELSE 'failure' END",
+        }
+    }
+}
+
+/// An aggregation over audit events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditAggregationQuery {
+    /// What to count events by
+    pub group_by: AuditGroupBy,
+    /// Bucket width
+    pub bucket: AuditTimeBucket,
+    /// Only events at or after this time
+    pub start_time: Option<DateTime<Utc>>,
+    /// Only events at or before this time
+    pub end_time: Option<DateTime<Utc>>,
+    /// Only events of this operation type
+    pub operation_type: Option<String>,
+}
+
+impl AuditAggregationQuery {
+    /// Count all events by `group_by` per `bucket`
+    pub fn new(group_by: AuditGroupBy, bucket: AuditTimeBucket) -> Self {
+        Self {
+            group_by,
+            bucket,
+            start_time: None,
+            end_time: None,
+            operation_type: None,
+        }
+    }
+
+    /// Restrict to events at or after `start_time`
+    pub fn since(mut self, start_time: DateTime<Utc>) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+}
+
+/// Number of events for one key in one time bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditAggregate {
+    /// Start of the bucket
+    pub bucket_start: DateTime<Utc>,
+    /// Group value (user, operation type or outcome)
+    pub key: String,
+    /// Events in the bucket
+    pub count: i64,
+}
+
+/// Failed logins against one account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedAuthSummary {
+    /// Account the attempts targeted
+    pub attempted_user: String,
+    /// Number of failed attempts
+    pub failures: i64,
+    /// Most recent failed attempt
+    pub last_failure: DateTime<Utc>,
+    /// Distinct source IPs the attempts came from
+    pub source_ips: Vec<String>,
+}
+
+/// Something unusual in recent audit activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditAnomaly {
+    /// Denials in the latest window far exceed the preceding windows' average
+    DenialSpike {
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+        denials: i64,
+        baseline: f64,
+    },
+}
+
+/// Thresholds for anomaly flags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditAnomalyConfig {
+    /// Width of the window compared against the baseline
+    pub window_minutes: u32,
+    /// Number of preceding windows averaged into the baseline
+    pub baseline_windows: u32,
+    /// How many times the baseline a window must reach to be flagged
+    pub spike_factor: f64,
+    /// Fewest denials in a window worth flagging, however low the baseline
+    pub min_denials: i64,
+}
+
+impl Default for AuditAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window_minutes: 60,
+            baseline_windows: 24,
+            spike_factor: 3.0,
+            min_denials: 10,
+        }
+    }
+}
+
+impl AuditLogger {
+    /// Count events per key per time bucket, oldest bucket first
+    pub async fn aggregate_events(&self, query: &AuditAggregationQuery) -> BinderyResult<Vec<AuditAggregate>> {
+        let mut sql = format!(
+            "SELECT strftime('{}', timestamp) AS bucket, {} AS key, COUNT(*) AS count FROM audit_events WHERE 1=1",
+            query.bucket.strftime_format(),
+            query.group_by.expression()
+        );
+        let mut binds = Vec::new();
+        if let Some(start_time) = query.start_time {
+            sql.push_str(" AND timestamp >= ?");
+            binds.push(start_time.to_rfc3339());
+        }
+        if let Some(end_time) = query.end_time {
+            sql.push_str(" AND timestamp <= ?");
+            binds.push(end_time.to_rfc3339());
+        }
+        if let Some(operation_type) = &query.operation_type {
+            sql.push_str(" AND operation_type = ?");
+            binds.push(operation_type.clone());
+        }
+        sql.push_str(" GROUP BY bucket, key ORDER BY bucket ASC, count DESC, key ASC");
+
+        let mut statement = sqlx::query(&sql);
+        for bind in &binds {
+            statement = statement.bind(bind);
+        }
+        let rows = statement
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to aggregate audit events: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(AuditAggregate {
+                    bucket_start: parse_timestamp(&row.try_get::<String, _>("bucket")?)?,
+                    key: row.try_get("key")?,
+                    count: row.try_get("count")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Accounts with the most failed logins since `since`, most-targeted first
+    pub async fn top_failed_auth(&self, since: DateTime<Utc>, limit: i64) -> BinderyResult<Vec<FailedAuthSummary>> {
+        let sql = format!(
+            "SELECT resource, COUNT(*) AS failures, MAX(timestamp) AS last_failure, GROUP_CONCAT(DISTINCT source_ip) AS source_ips \
+             FROM audit_events WHERE operation_type = 'authentication' AND {} AND timestamp >= ? \
+             GROUP BY resource ORDER BY failures DESC, last_failure DESC LIMIT ?",
+            FAILED
+        );
+        let rows = sqlx::query(&sql)
+            .bind(since.to_rfc3339())
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to query failed logins: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                let resource: String = row.try_get("resource")?;
+                let source_ips: Option<String> = row.try_get("source_ips")?;
+                Ok(FailedAuthSummary {
+                    attempted_user: resource.strip_prefix("user:").unwrap_or(&resource).to_string(),
+                    failures: row.try_get("failures")?,
+                    last_failure: parse_timestamp(&row.try_get::<String, _>("last_failure")?)?,
+                    source_ips: source_ips
+                        .map(|ips| ips.split(',').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Flag unusual activity as of now
+    pub async fn detect_anomalies(&self) -> BinderyResult<Vec<AuditAnomaly>> {
+        self.detect_anomalies_at(Utc::now()).await
+    }
+
+    /// Flag unusual activity in the window ending at `now`
+    ///
+    /// A denial spike is reported when the latest window has at least
+    /// `min_denials` denials and `spike_factor` times the average of the
+    /// `baseline_windows` windows before it.
+    pub async fn detect_anomalies_at(&self, now: DateTime<Utc>) -> BinderyResult<Vec<AuditAnomaly>> {
+        let config = &self.config().anomaly;
+        let window = Duration::minutes(config.window_minutes.max(1) as i64);
+        let earliest = now - window * (config.baseline_windows as i32 + 1);
+
+        let denial_types = DENIAL_OPERATION_TYPES
+            .iter()
+            .map(|operation_type| format!("'{}'", operation_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT CAST((julianday(?) - julianday(timestamp)) * 1440 / ? AS INTEGER) AS window, COUNT(*) AS denials \
+             FROM audit_events WHERE operation_type IN ({}) AND {} AND timestamp > ? AND timestamp <= ? \
+             GROUP BY window",
+            denial_types, FAILED
+        );
+        let rows = sqlx::query(&sql)
+            .bind(now.to_rfc3339())
+            .bind(config.window_minutes.max(1) as f64)
+            .bind(earliest.to_rfc3339())
+            .bind(now.to_rfc3339())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| BinderyError::DatabaseError(format!("Failed to count denials: {}", e)))?;
+
+        let mut current = 0;
+        let mut previous = 0;
+        for row in &rows {
+            let window: i64 = row.try_get("window")?;
+            let denials: i64 = row.try_get("denials")?;
+            if window == 0 {
+                current += denials;
+            } else if window <= config.baseline_windows as i64 {
+                previous += denials;
+            }
+        }
+
+        let baseline = previous as f64 / config.baseline_windows.max(1) as f64;
+        let mut anomalies = Vec::new();
+        if current >= config.min_denials && current as f64 >= config.spike_factor * baseline {
+            anomalies.push(AuditAnomaly::DenialSpike {
+                window_start: now - window,
+                window_end: now,
+                denials: current,
+                baseline,
+            });
+        }
+
+        Ok(anomalies)
+    }
+}
+
+fn parse_timestamp(value: &str) -> BinderyResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|e| BinderyError::InvalidInput(format!("Invalid timestamp: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::audit::{
+        create_auth_failure_event, create_role_execution_event, AuditConfig, OperationOutcome, UserContext,
+    };
+    use tempfile::TempDir;
+
+    fn user(id: &str, ip: &str) -> UserContext {
+        UserContext {
+            user_id: Some(id.to_string()),
+            session_id: None,
+            source_ip: Some(ip.to_string()),
+            user_agent: None,
+        }
+    }
+
+    fn outcome(success: bool) -> OperationOutcome {
+        OperationOutcome { success, result_code: None, error_message: None, duration_ms: 1, records_affected: None }
+    }
+
+    async fn setup() -> (AuditLogger, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = AuditLogger::new(AuditConfig {
+            audit_db_path: temp_dir.path().join("audit.db"),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        (logger, temp_dir)
+    }
+
+    async fn log_failed_login(logger: &AuditLogger, target: &str, ip: &str, at: DateTime<Utc>) {
+        let mut event = create_auth_failure_event(user("anonymous", ip), target, "bad password", outcome(false));
+        event.timestamp = at;
+        logger.log_event(event).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_aggregations_and_failed_auth_report() {
+        let (logger, _temp_dir) = setup().await;
+        let now = Utc::now();
+
+        for (user_id, success) in [("alice", true), ("alice", false), ("bob", true)] {
+            let event = create_role_execution_event(user(user_id, "10.0.0.1"), "coder", "task", outcome(success), vec![]);
+            logger.log_event(event).await.unwrap();
+        }
+        for _ in 0..3 {
+            log_failed_login(&logger, "root", "10.0.0.9", now).await;
+        }
+        log_failed_login(&logger, "root", "10.0.0.8", now).await;
+        log_failed_login(&logger, "alice", "10.0.0.9", now).await;
+
+        let by_user = logger
+            .aggregate_events(&AuditAggregationQuery::new(AuditGroupBy::User, AuditTimeBucket::Day))
+            .await
+            .unwrap();
+        let count_for = |key: &str| by_user.iter().filter(|a| a.key == key).map(|a| a.count).sum::<i64>();
+        assert_eq!(count_for("alice"), 2);
+        assert_eq!(count_for("anonymous"), 5);
+
+        let mut by_outcome = AuditAggregationQuery::new(AuditGroupBy::Outcome, AuditTimeBucket::Hour);
+        by_outcome.operation_type = Some("role_execution".to_string());
+        let by_outcome = logger.aggregate_events(&by_outcome).await.unwrap();
+        assert_eq!(by_outcome.iter().filter(|a| a.key == "success").map(|a| a.count).sum::<i64>(), 2);
+        assert_eq!(by_outcome.iter().filter(|a| a.key == "failure").map(|a| a.count).sum::<i64>(), 1);
+        assert!(by_outcome.iter().all(|a| a.bucket_start <= now));
+
+        let top = logger.top_failed_auth(now - Duration::hours(1), 10).await.unwrap();
+        assert_eq!(top[0].attempted_user, "root");
+        assert_eq!(top[0].failures, 4);
+        assert_eq!(top[0].source_ips.len(), 2);
+        assert_eq!(top[1].attempted_user, "alice");
+
+        let stats = logger.get_stats().await.unwrap();
+        assert_eq!(stats.top_failed_auth.len(), 2);
+        assert!(!stats.recent_activity.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_denial_spike_is_flagged() {
+        let (logger, _temp_dir) = setup().await;
+        let now = Utc::now();
+
+        // A steady trickle of one denial an hour is the baseline
+        for hours_ago in 1..=24 {
+            log_failed_login(&logger, "alice", "10.0.0.1", now - Duration::hours(hours_ago) - Duration::minutes(5)).await;
+        }
+        assert!(logger.detect_anomalies_at(now).await.unwrap().is_empty());
+
+        for _ in 0..12 {
+            log_failed_login(&logger, "root", "10.0.0.9", now - Duration::minutes(10)).await;
+        }
+        let anomalies = logger.detect_anomalies_at(now).await.unwrap();
+        match anomalies.as_slice() {
+            [AuditAnomaly::DenialSpike { denials, baseline, .. }] => {
+                assert_eq!(*denials, 12);
+                assert!((baseline - 1.0).abs() < f64::EPSILON);
+            }
+            other => panic!("expected a denial spike, got {:?}", other),
+        }
+    }
+}
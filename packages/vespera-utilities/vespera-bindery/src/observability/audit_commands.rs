@@ -0,0 +1,174 @@
+//! Audit commands for Vespera Bindery CLI
+//!
+//! Provides command-line access to hash chain verification, signed exports
+//! and retention for the audit log
+
+use crate::errors::{BinderyError, BinderyResult};
+use crate::observability::audit::{AuditLogger, AuditQueryFilter, ChainBreak, ChainVerification};
+use crate::observability::audit_export::{load_or_create_signing_key, parse_verifying_key, SignedAuditExport};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use std::path::PathBuf;
+
+/// Audit CLI commands
+#[derive(Debug, Subcommand)]
+pub enum AuditCommand {
+    /// Verify the whole audit hash chain
+    Verify {
+        /// Output format (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Export events as an ed25519-signed bundle for compliance review
+    Export {
+        /// File to write the bundle to
+        #[arg(long)]
+        output: PathBuf,
+        /// Signing key file (base64); generated if it doesn't exist
+        #[arg(long)]
+        signing_key: PathBuf,
+        /// Only events at or after this time (RFC 3339)
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+        /// Only events at or before this time (RFC 3339)
+        #[arg(long)]
+        until: Option<DateTime<Utc>>,
+        /// Only events of this operation type
+        #[arg(long)]
+        operation_type: Option<String>,
+        /// Only events by this user
+        #[arg(long)]
+        user: Option<String>,
+    },
+
+    /// Check the signature of an exported bundle
+    VerifyExport {
+        /// Bundle file produced by `export`
+        bundle: PathBuf,
+        /// Expected signer public key (base64); any key is accepted if omitted
+        #[arg(long)]
+        public_key: Option<String>,
+    },
+
+    /// Apply the retention policy now
+    Prune,
+}
+
+/// Audit command executor
+pub struct AuditCommandExecutor {
+    logger: AuditLogger,
+}
+
+impl AuditCommandExecutor {
+    /// Create a new audit command executor
+    pub fn new(logger: AuditLogger) -> Self {
+        Self { logger }
+    }
+
+    /// Execute an audit command
+    pub async fn execute(&self, command: AuditCommand) -> BinderyResult<()> {
+        match command {
+            AuditCommand::Verify { format } => self.verify(format).await,
+            AuditCommand::Export { output, signing_key, since, until, operation_type, user } => {
+                let filter = AuditQueryFilter {
+                    start_time: since,
+                    end_time: until,
+                    operation_type,
+                    user_id: user,
+                    ..Default::default()
+                };
+                self.export(output, signing_key, filter).await
+            }
+            AuditCommand::VerifyExport { bundle, public_key } => verify_export(bundle, public_key),
+            AuditCommand::Prune => self.prune().await,
+        }
+    }
+
+    /// Verify the hash chain, failing if it is broken
+    async fn verify(&self, format: String) -> BinderyResult<()> {
+        let verification = self.logger.verify_chain().await?;
+
+        match format.as_str() {
+            "json" => {
+                println!("{}", serde_json::to_string_pretty(&verification)
+                    .map_err(|e| BinderyError::SerializationError(format!("Failed to serialize verification: {}", e)))?);
+            }
+            _ => print_verification(&verification),
+        }
+
+        if verification.valid {
+            Ok(())
+        } else {
+            Err(BinderyError::InvalidInput("Audit hash chain is broken".to_string()))
+        }
+    }
+
+    /// Write a signed export bundle
+    async fn export(&self, output: PathBuf, signing_key: PathBuf, filter: AuditQueryFilter) -> BinderyResult<()> {
+        let key = load_or_create_signing_key(&signing_key)?;
+        let export = self.logger.export_signed(filter, &key).await?;
+
+        let json = serde_json::to_string_pretty(&export)
+            .map_err(|e| BinderyError::SerializationError(format!("Failed to serialize export: {}", e)))?;
+        std::fs::write(&output, json)
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to write {:?}: {}", output, e)))?;
+
+        let payload = export.verify(None)?;
+        println!("✓ Exported {} audit events to {}", payload.events.len(), output.display());
+        println!("  Chain valid at export: {}", payload.chain.valid);
+        println!("  Signer public key: {}", export.public_key);
+        Ok(())
+    }
+
+    /// Run retention now
+    async fn prune(&self) -> BinderyResult<()> {
+        let deleted = self.logger.cleanup_old_events().await?;
+        println!("✓ Removed {} audit events past the retention policy", deleted);
+        Ok(())
+    }
+}
+
+/// Check a bundle file's signature and summarize it
+fn verify_export(bundle: PathBuf, public_key: Option<String>) -> BinderyResult<()> {
+    let contents = std::fs::read_to_string(&bundle)
+        .map_err(|e| BinderyError::InvalidInput(format!("Failed to read {:?}: {}", bundle, e)))?;
+    let export: SignedAuditExport = serde_json::from_str(&contents)
+        .map_err(|e| BinderyError::SerializationError(format!("Failed to parse export bundle: {}", e)))?;
+
+    let trusted = public_key.as_deref().map(parse_verifying_key).transpose()?;
+    let payload = export.verify(trusted.as_ref())?;
+
+    println!("✓ Signature valid ({})", if trusted.is_some() { "trusted key" } else { "embedded key only" });
+    println!("  Exported at: {}", payload.exported_at.to_rfc3339());
+    println!("  Events: {}", payload.events.len());
+    print_verification(&payload.chain);
+    Ok(())
+}
+
+fn print_verification(verification: &ChainVerification) {
+    if verification.valid {
+        println!("✓ Audit hash chain intact ({} events checked)", verification.events_checked);
+        if !verification.hash_chaining {
+            println!("  Hash chaining is disabled; only individual event hashes were checked");
+        }
+        return;
+    }
+
+    println!("✗ Audit hash chain broken ({} events checked)", verification.events_checked);
+    if let Some(broken) = &verification.first_broken_link {
+        println!("  Position: {}", broken.position);
+        println!("  Event: {}", broken.event_id.as_deref().unwrap_or("<unknown>"));
+        match &broken.reason {
+            ChainBreak::HashMismatch { stored, calculated } => {
+                println!("  Event contents changed: stored hash {}, calculated {}", stored, calculated);
+            }
+            ChainBreak::LinkMismatch { expected, found } => {
+                println!("  Link broken: expected previous hash {:?}, found {:?}", expected, found);
+            }
+            ChainBreak::TruncatedTail { expected_head, found_head } => {
+                println!("  Newest events missing: chain head {:?}, last event hash {:?}", expected_head, found_head);
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Signed audit exports for compliance reviews
+//!
+//! An export bundles the selected audit events together with the result of
+//! [`AuditLogger::verify_chain`] at export time, serialized once as JSON and
+//! signed with an ed25519 key. Reviewers verify the signature over the exact
+//! payload bytes, so the bundle can be handed around as a plain file without
+//! trusting whoever carried it.
+
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use super::audit::{AuditEvent, AuditLogger, AuditQueryFilter, ChainVerification};
+use crate::errors::{BinderyError, BinderyResult};
+
+/// Version of the export payload layout
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// The signed contents of an audit export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditExportPayload {
+    /// Payload layout version ([`EXPORT_FORMAT_VERSION`])
+    pub format_version: u32,
+    /// When the export was produced
+    pub exported_at: DateTime<Utc>,
+    /// Filter used to select the events
+    pub filter: AuditQueryFilter,
+    /// Integrity of the whole chain at export time
+    pub chain: ChainVerification,
+    /// Selected events, oldest first
+    pub events: Vec<AuditEvent>,
+}
+
+/// An audit export with its ed25519 signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedAuditExport {
+    /// JSON-encoded [`AuditExportPayload`]; the signature covers these exact bytes
+    pub payload: String,
+    /// Base64 ed25519 public key of the signer
+    pub public_key: String,
+    /// Base64 ed25519 signature over `payload`
+    pub signature: String,
+}
+
+impl SignedAuditExport {
+    /// Serialize and sign a payload
+    pub fn sign(payload: &AuditExportPayload, key: &SigningKey) -> BinderyResult<Self> {
+        let payload = serde_json::to_string(payload)
+            .map_err(|e| BinderyError::SerializationError(format!("Failed to serialize audit export: {}", e)))?;
+        let signature = key.sign(payload.as_bytes());
+
+        Ok(Self {
+            public_key: STANDARD.encode(key.verifying_key().as_bytes()),
+            signature: STANDARD.encode(signature.to_bytes()),
+            payload,
+        })
+    }
+
+    /// Check the signature and return the payload
+    ///
+    /// The signature is checked against the embedded public key, which must
+    /// also equal `trusted_key` when one is given. Without a trusted key this
+    /// only proves the bundle wasn't altered after signing, not who signed it.
+    pub fn verify(&self, trusted_key: Option<&VerifyingKey>) -> BinderyResult<AuditExportPayload> {
+        let key_bytes: [u8; 32] = decode_fixed(&self.public_key, "public key")?;
+        let public_key = VerifyingKey::from_bytes(&key_bytes)
+            .map_err(|e| BinderyError::InvalidInput(format!("Invalid export public key: {}", e)))?;
+
+        if let Some(trusted) = trusted_key {
+            if trusted != &public_key {
+                return Err(BinderyError::InvalidInput("Audit export was signed by an untrusted key".to_string()));
+            }
+        }
+
+        let signature = Signature::from_bytes(&decode_fixed(&self.signature, "signature")?);
+        public_key
+            .verify(self.payload.as_bytes(), &signature)
+            .map_err(|_| BinderyError::InvalidInput("Audit export signature does not match its contents".to_string()))?;
+
+        serde_json::from_str(&self.payload)
+            .map_err(|e| BinderyError::SerializationError(format!("Failed to parse audit export payload: {}", e)))
+    }
+}
+
+impl AuditLogger {
+    /// Export the events matching `filter` as a signed bundle
+    ///
+    /// The whole chain is verified first and the result is included in the
+    /// payload, so a reviewer sees whether the log was intact when exported.
+    pub async fn export_signed(&self, filter: AuditQueryFilter, key: &SigningKey) -> BinderyResult<SignedAuditExport> {
+        let chain = self.verify_chain().await?;
+        let mut events = self.query_events(filter.clone()).await?;
+        events.reverse();
+
+        let payload = AuditExportPayload {
+            format_version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            filter,
+            chain,
+            events,
+        };
+        SignedAuditExport::sign(&payload, key)
+    }
+}
+
+/// Parse a base64 ed25519 public key, as printed in an export's `public_key`
+pub fn parse_verifying_key(encoded: &str) -> BinderyResult<VerifyingKey> {
+    VerifyingKey::from_bytes(&decode_fixed(encoded, "public key")?)
+        .map_err(|e| BinderyError::InvalidInput(format!("Invalid public key: {}", e)))
+}
+
+/// Load the base64 signing key at `path`, generating one if the file doesn't exist
+pub fn load_or_create_signing_key(path: &Path) -> BinderyResult<SigningKey> {
+    if path.exists() {
+        let encoded = std::fs::read_to_string(path)
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to read signing key {:?}: {}", path, e)))?;
+        return Ok(SigningKey::from_bytes(&decode_fixed(encoded.trim(), "signing key")?));
+    }
+
+    let key = SigningKey::generate(&mut rand::rngs::OsRng);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to create {:?}: {}", parent, e)))?;
+    }
+    std::fs::write(path, STANDARD.encode(key.to_bytes()))
+        .map_err(|e| BinderyError::ConfigurationError(format!("Failed to write signing key {:?}: {}", path, e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to restrict signing key {:?}: {}", path, e)))?;
+    }
+
+    Ok(key)
+}
+
+fn decode_fixed<const N: usize>(encoded: &str, what: &str) -> BinderyResult<[u8; N]> {
+    STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| BinderyError::InvalidInput(format!("Invalid base64 {}", what)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::audit::{create_config_change_event, AuditConfig, OperationOutcome, UserContext};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_signed_export_verifies_and_detects_tampering() {
+        let temp_dir = TempDir::new().unwrap();
+        let logger = AuditLogger::new(AuditConfig {
+            audit_db_path: temp_dir.path().join("audit.db"),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        for setting in ["a", "b", "c"] {
+            let event = create_config_change_event(
+                UserContext { user_id: Some("admin".to_string()), session_id: None, source_ip: None, user_agent: None },
+                setting,
+                None,
+                "enabled",
+                OperationOutcome { success: true, result_code: None, error_message: None, duration_ms: 1, records_affected: None },
+            );
+            logger.log_event(event).await.unwrap();
+        }
+
+        let key = load_or_create_signing_key(&temp_dir.path().join("keys/audit.key")).unwrap();
+        let export = logger.export_signed(AuditQueryFilter::default(), &key).await.unwrap();
+
+        let trusted = parse_verifying_key(&export.public_key).unwrap();
+        let payload = export.verify(Some(&trusted)).unwrap();
+        assert!(payload.chain.valid);
+        assert_eq!(payload.events.len(), 3);
+        assert_eq!(payload.events[0].operation.resource, "config:a");
+
+        // The key file is reused, not regenerated
+        let reloaded = load_or_create_signing_key(&temp_dir.path().join("keys/audit.key")).unwrap();
+        assert_eq!(reloaded.to_bytes(), key.to_bytes());
+
+        let mut tampered = export.clone();
+        tampered.payload = tampered.payload.replacen("\"admin\"", "\"someone-else\"", 1);
+        assert!(tampered.verify(None).is_err());
+
+        let other = SigningKey::generate(&mut rand::rngs::OsRng);
+        assert!(export.verify(Some(&other.verifying_key())).is_err());
+    }
+}
@@ -25,6 +25,7 @@ mod tests {
             retention_days: Some(30),
             enable_compression: false,
             batch_size: 100,
+            anomaly: Default::default(),
         };
 
         let logger = AuditLogger::new(config).await.expect("Failed to create audit logger");
@@ -300,6 +301,7 @@ DROP TABLE test_table;
             retention_days: None, // Test max_events cleanup only
             enable_compression: false,
             batch_size: 100,
+            anomaly: Default::default(),
         };
 
         let logger = AuditLogger::new(config).await.expect("Failed to create audit logger");
@@ -351,6 +353,7 @@ DROP TABLE test_table;
             retention_days: Some(30),
             enable_compression: true,
             batch_size: 100,
+            anomaly: Default::default(),
         };
         assert!(validate_audit_config(&valid_config).is_ok());
 
@@ -4,11 +4,15 @@
 //! setting up structured logging, OpenTelemetry, and metrics collection.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry, Layer};
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry, Layer};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use super::log_file::RotatingLogFile;
 use anyhow::Result;
 use crate::{BinderyError, BinderyResult};
+use crate::secrets::{is_secret_reference, SecretManager, SecretRef};
 
 /// Configuration for logging behavior
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,8 +31,14 @@ pub struct LoggingConfig {
     pub with_thread_names: bool,
     /// Include span events in logs
     pub with_span_events: bool,
+    /// Per-module level overrides, e.g. `vespera_bindery::crdt` => `debug`
+    #[serde(default)]
+    pub module_levels: HashMap<String, String>,
 }
 
+/// Valid values for `level` and `module_levels`
+const VALID_LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
 impl LoggingConfig {
     /// Validate the logging configuration
     pub fn validate(&self) -> BinderyResult<()> {
@@ -44,6 +54,24 @@ impl LoggingConfig {
             ));
         }
 
+        for (module, level) in &self.module_levels {
+            if module.trim().is_empty() || module.contains(|c: char| c.is_whitespace() || c == ',' || c == '=') {
+                return Err(BinderyError::ConfigurationError(
+                    format!("Invalid module name '{}' in module_levels", module)
+                ));
+            }
+            if !VALID_LOG_LEVELS.contains(&level.to_lowercase().as_str()) {
+                return Err(BinderyError::ConfigurationError(
+                    format!(
+                        "Invalid log level '{}' for module '{}'. Valid levels are: {}",
+                        level,
+                        module,
+                        VALID_LOG_LEVELS.join(", ")
+                    )
+                ));
+            }
+        }
+
         // Validate that at least one output is enabled
         if !self.console && self.file.is_none() {
             return Err(BinderyError::ConfigurationError(
@@ -89,6 +117,23 @@ impl LoggingConfig {
         self.file = Some(file_config);
         Ok(self)
     }
+
+    /// Override the level for one module (a `tracing` target prefix)
+    pub fn with_module_level(mut self, module: impl Into<String>, level: impl Into<String>) -> BinderyResult<Self> {
+        self.module_levels.insert(module.into(), level.into());
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Filter directives for `level` plus the module overrides
+    fn filter_directives(&self) -> String {
+        let mut modules: Vec<_> = self.module_levels.iter().collect();
+        modules.sort();
+        std::iter::once(self.level.to_lowercase())
+            .chain(modules.into_iter().map(|(module, level)| format!("{}={}", module, level.to_lowercase())))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
 }
 
 /// Configuration for file-based logging
@@ -102,6 +147,12 @@ pub struct FileLoggingConfig {
     pub rotation: LogRotation,
     /// Maximum number of log files to keep
     pub max_files: Option<usize>,
+    /// Also rotate once the file would grow past this many bytes
+    #[serde(default)]
+    pub max_size_bytes: Option<u64>,
+    /// Write JSON lines to the file; defaults to `LoggingConfig::json_format`
+    #[serde(default)]
+    pub json: Option<bool>,
 }
 
 impl FileLoggingConfig {
@@ -141,6 +192,12 @@ impl FileLoggingConfig {
             ));
         }
 
+        if self.max_size_bytes == Some(0) {
+            return Err(BinderyError::ConfigurationError(
+                "max_size_bytes must be greater than 0 if specified".to_string()
+            ));
+        }
+
         // Validate max_files if specified
         if let Some(max_files) = self.max_files {
             if max_files == 0 {
@@ -168,10 +225,19 @@ impl FileLoggingConfig {
             filename: filename.into(),
             rotation: LogRotation::Daily,
             max_files: Some(30), // Keep 30 days by default
+            max_size_bytes: None,
+            json: None,
         };
         config.validate()?;
         Ok(config)
     }
+
+    /// Also rotate by size, with validation
+    pub fn with_max_size(mut self, max_size_bytes: u64) -> BinderyResult<Self> {
+        self.max_size_bytes = Some(max_size_bytes);
+        self.validate()?;
+        Ok(self)
+    }
 }
 
 /// Log file rotation strategies
@@ -190,7 +256,7 @@ impl From<LogRotation> for Rotation {
             LogRotation::Never => Rotation::NEVER,
             LogRotation::Hourly => Rotation::HOURLY,
             LogRotation::Daily => Rotation::DAILY,
-            LogRotation::Weekly => Rotation::DAILY, // Approximation
+            LogRotation::Weekly => Rotation::WEEKLY,
         }
     }
 }
@@ -348,12 +414,40 @@ impl JaegerConfig {
 }
 
 /// OTLP (OpenTelemetry Protocol) configuration
+///
+/// Jaeger, Tempo and most hosted backends accept OTLP directly, so this is
+/// the exporter to use for all of them.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OtlpConfig {
-    /// OTLP endpoint
+    /// OTLP endpoint (e.g. `http://localhost:4317` for gRPC, `http://localhost:4318` for HTTP)
     pub endpoint: String,
-    /// API key for authentication
+    /// API key for authentication, sent as `authorization: Bearer <key>`
     pub api_key: Option<String>,
+    /// Transport protocol
+    #[serde(default)]
+    pub protocol: OtlpProtocol,
+    /// Extra headers (gRPC metadata) sent with every export
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Fraction of traces to sample (0.0 to 1.0); child spans follow their parent's decision
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+/// OTLP transport protocol
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OtlpProtocol {
+    /// OTLP over gRPC
+    #[default]
+    #[serde(rename = "grpc")]
+    Grpc,
+    /// OTLP over HTTP with protobuf bodies; `/v1/traces` is appended to the endpoint if missing
+    #[serde(rename = "http/protobuf", alias = "http")]
+    HttpProtobuf,
 }
 
 impl OtlpConfig {
@@ -390,6 +484,13 @@ impl OtlpConfig {
             }
         }
 
+        // Validate sample rate
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            return Err(BinderyError::ConfigurationError(
+                format!("OTLP sample_rate must be between 0.0 and 1.0, got {}", self.sample_rate)
+            ));
+        }
+
         Ok(())
     }
 
@@ -398,6 +499,9 @@ impl OtlpConfig {
         let config = Self {
             endpoint: endpoint.into(),
             api_key: None,
+            protocol: OtlpProtocol::default(),
+            headers: HashMap::new(),
+            sample_rate: default_sample_rate(),
         };
         config.validate()?;
         Ok(config)
@@ -674,7 +778,7 @@ pub struct NotificationConfig {
 /// Webhook notification configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookConfig {
-    /// Webhook URL
+    /// Webhook URL, or a `secret://` reference to one
     pub url: String,
     /// HTTP method (GET, POST, PUT)
     pub method: String,
@@ -817,6 +921,7 @@ impl Default for LoggingConfig {
             with_source_location: true,
             with_thread_names: true,
             with_span_events: true,
+            module_levels: HashMap::new(),
         }
     }
 }
@@ -1130,6 +1235,14 @@ impl NotificationConfig {
 }
 
 impl WebhookConfig {
+    /// URL to call, resolving a `secret://` reference through `secrets`
+    pub async fn resolve_url(&self, secrets: &SecretManager) -> BinderyResult<String> {
+        SecretRef::parse(self.url.as_str())
+            .resolve(secrets)
+            .await
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to resolve webhook URL: {:#}", e)))
+    }
+
     /// Validate webhook configuration
     pub fn validate(&self) -> BinderyResult<()> {
         // Validate URL
@@ -1139,7 +1252,7 @@ impl WebhookConfig {
             ));
         }
 
-        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+        if !is_secret_reference(&self.url) && !self.url.starts_with("http://") && !self.url.starts_with("https://") {
             return Err(BinderyError::ConfigurationError(
                 format!("Webhook URL must be a valid HTTP/HTTPS URL: {}", self.url)
             ));
@@ -1264,41 +1377,42 @@ impl RetryConfig {
 
 /// Initialize logging with the given configuration
 pub fn init_logging(config: &LoggingConfig) -> Result<()> {
+    init_logging_with_layers(config, Vec::new())
+}
+
+/// Subscriber the logging layers are stacked on
+type FilteredRegistry = tracing_subscriber::layer::Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+/// A layer installed alongside the logging layers (e.g. OpenTelemetry export)
+type BoxedLayer = Box<dyn Layer<FilteredRegistry> + Send + Sync>;
+
+/// The running subscriber's level filter, for [`reload_logging`]
+struct LogFilterState {
+    handle: reload::Handle<EnvFilter, Registry>,
+    config: Mutex<LoggingConfig>,
+}
+
+static LOG_FILTER: OnceLock<LogFilterState> = OnceLock::new();
+
+/// Initialize logging with extra layers in the same global subscriber
+///
+/// The global subscriber can only be set once, so everything that observes
+/// spans has to be installed together.
+fn init_logging_with_layers(config: &LoggingConfig, extra_layers: Vec<BoxedLayer>) -> Result<()> {
     // Validate configuration before initialization
     config.validate().map_err(|e| anyhow::anyhow!("Logging configuration validation failed: {}", e))?;
 
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(&config.level));
+    // RUST_LOG still wins at startup
+    let env_filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(config.filter_directives())?,
+    };
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
 
-    let mut layers = Vec::new();
+    let mut layers: Vec<BoxedLayer> = Vec::new();
 
     // Console layer
     if config.console {
-        let console_layer = if config.json_format {
-            tracing_subscriber::fmt::layer()
-                .json()
-                .with_thread_names(config.with_thread_names)
-                .with_span_events(
-                    if config.with_span_events {
-                        tracing_subscriber::fmt::format::FmtSpan::FULL
-                    } else {
-                        tracing_subscriber::fmt::format::FmtSpan::NONE
-                    }
-                )
-                .boxed()
-        } else {
-            tracing_subscriber::fmt::layer()
-                .with_thread_names(config.with_thread_names)
-                .with_span_events(
-                    if config.with_span_events {
-                        tracing_subscriber::fmt::format::FmtSpan::FULL
-                    } else {
-                        tracing_subscriber::fmt::format::FmtSpan::NONE
-                    }
-                )
-                .boxed()
-        };
-        layers.push(console_layer);
+        layers.push(fmt_layer(config, config.json_format, true, std::io::stdout));
     }
 
     // File layer
@@ -1313,69 +1427,141 @@ pub fn init_logging(config: &LoggingConfig) -> Result<()> {
                 ))?;
         }
 
-        let file_appender = RollingFileAppender::new(
-            file_config.rotation.clone().into(),
-            &file_config.directory,
-            &file_config.filename,
-        );
-
-        let file_layer = if config.json_format {
-            tracing_subscriber::fmt::layer()
-                .json()
-                .with_writer(file_appender)
-                .with_thread_names(config.with_thread_names)
-                .with_span_events(
-                    if config.with_span_events {
-                        tracing_subscriber::fmt::format::FmtSpan::FULL
-                    } else {
-                        tracing_subscriber::fmt::format::FmtSpan::NONE
-                    }
+        let json = file_config.json.unwrap_or(config.json_format);
+        let file_layer = match file_config.max_size_bytes {
+            Some(max_size_bytes) => {
+                let file = RotatingLogFile::new(
+                    &file_config.directory,
+                    &file_config.filename,
+                    file_config.rotation.clone(),
+                    max_size_bytes,
+                    file_config.max_files,
                 )
-                .boxed()
-        } else {
-            tracing_subscriber::fmt::layer()
-                .with_writer(file_appender)
-                .with_thread_names(config.with_thread_names)
-                .with_span_events(
-                    if config.with_span_events {
-                        tracing_subscriber::fmt::format::FmtSpan::FULL
-                    } else {
-                        tracing_subscriber::fmt::format::FmtSpan::NONE
-                    }
-                )
-                .boxed()
+                .map_err(|e| anyhow::anyhow!(
+                    "Failed to open log file '{}': {}",
+                    file_config.directory.join(&file_config.filename).display(),
+                    e
+                ))?;
+                fmt_layer(config, json, false, file)
+            }
+            None => {
+                let mut builder = RollingFileAppender::builder()
+                    .rotation(file_config.rotation.clone().into())
+                    .filename_prefix(&file_config.filename);
+                if let Some(max_files) = file_config.max_files {
+                    builder = builder.max_log_files(max_files);
+                }
+                fmt_layer(config, json, false, builder.build(&file_config.directory)?)
+            }
         };
         layers.push(file_layer);
     }
 
+    layers.extend(extra_layers);
+
     // Initialize subscriber
     Registry::default()
         .with(env_filter)
         .with(layers)
         .try_init()?;
 
+    let _ = LOG_FILTER.set(LogFilterState {
+        handle: filter_handle,
+        config: Mutex::new(config.clone()),
+    });
+
     tracing::info!(
         level = %config.level,
         console = config.console,
         json_format = config.json_format,
+        file = ?config.file.as_ref().map(|file| file.directory.join(&file.filename)),
         "Logging initialized"
     );
 
     Ok(())
 }
 
+/// A `fmt` layer writing human-readable or JSON lines to `writer`
+fn fmt_layer<W>(config: &LoggingConfig, json: bool, ansi: bool, writer: W) -> BoxedLayer
+where
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    let span_events = if config.with_span_events {
+        tracing_subscriber::fmt::format::FmtSpan::FULL
+    } else {
+        tracing_subscriber::fmt::format::FmtSpan::NONE
+    };
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        .with_thread_names(config.with_thread_names)
+        .with_span_events(span_events);
+
+    if json {
+        layer.json().boxed()
+    } else {
+        layer.boxed()
+    }
+}
+
+/// Change log verbosity without restarting
+///
+/// Applies `level` and `module_levels` from `config` to the running
+/// subscriber; outputs and formats are fixed when logging is initialized.
+/// Unlike at startup, `RUST_LOG` doesn't take precedence over a reload.
+pub fn reload_logging(config: &LoggingConfig) -> Result<()> {
+    config.validate().map_err(|e| anyhow::anyhow!("Logging configuration validation failed: {}", e))?;
+    let state = LOG_FILTER.get().ok_or_else(|| anyhow::anyhow!("Logging has not been initialized"))?;
+
+    state.handle.reload(EnvFilter::try_new(config.filter_directives())?)?;
+
+    let mut current = state.config.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    current.level = config.level.clone();
+    current.module_levels = config.module_levels.clone();
+    tracing::info!(directives = %current.filter_directives(), "Logging verbosity reloaded");
+    Ok(())
+}
+
+/// The logging configuration in effect, including reloaded levels
+pub fn current_logging_config() -> Option<LoggingConfig> {
+    LOG_FILTER
+        .get()
+        .map(|state| state.config.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone())
+}
+
 /// Initialize full observability stack
 pub fn init_observability(config: &ObservabilityConfig) -> Result<()> {
     // Validate configuration before initialization
     config.validate().map_err(|e| anyhow::anyhow!("Observability configuration validation failed: {}", e))?;
 
+    // OpenTelemetry export is a layer on the logging subscriber
+    let mut extra_layers = Vec::new();
+    if let Some(otel_config) = &config.opentelemetry {
+        extra_layers.extend(init_opentelemetry(otel_config)?);
+    }
+
     // Initialize logging
-    init_logging(&config.logging)?;
+    init_logging_with_layers(&config.logging, extra_layers)?;
 
-    // Initialize OpenTelemetry if configured
     #[cfg(feature = "observability")]
-    if let Some(otel_config) = &config.opentelemetry {
-        init_opentelemetry(otel_config)?;
+    if let Some(otel_config) = config.opentelemetry.as_ref().filter(|c| c.enabled) {
+        match &otel_config.otlp {
+            Some(otlp_config) => tracing::info!(
+                service_name = %otel_config.service_name,
+                service_version = %otel_config.service_version,
+                endpoint = %otlp_config.endpoint,
+                protocol = ?otlp_config.protocol,
+                "OpenTelemetry OTLP trace export initialized"
+            ),
+            None => tracing::warn!(
+                "Jaeger agent export is not supported; point `otlp` at Jaeger's OTLP receiver instead"
+            ),
+        }
+    }
+
+    #[cfg(not(feature = "observability"))]
+    if config.opentelemetry.as_ref().is_some_and(|c| c.enabled) {
+        tracing::warn!("OpenTelemetry support not compiled in");
     }
 
     // Initialize metrics if configured
@@ -1394,49 +1580,78 @@ pub fn init_observability(config: &ObservabilityConfig) -> Result<()> {
 }
 
 #[cfg(feature = "observability")]
-fn init_opentelemetry(config: &OpenTelemetryConfig) -> Result<()> {
-    use opentelemetry_sdk::{runtime::Tokio, trace::TracerProvider};
-    use tracing_opentelemetry::OpenTelemetryLayer;
+fn init_opentelemetry(config: &OpenTelemetryConfig) -> Result<Option<BoxedLayer>> {
+    use opentelemetry::{trace::TracerProvider as _, KeyValue};
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig, WithTonicConfig};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime::Tokio, trace::{Sampler, TracerProvider}, Resource};
 
     if !config.enabled {
-        return Ok(());
+        return Ok(None);
     }
 
     // Validate configuration
     config.validate().map_err(|e| anyhow::anyhow!("OpenTelemetry configuration validation failed: {}", e))?;
 
-    // We need at least one exporter configured
-    if let Some(ref jaeger_config) = config.jaeger {
-        let tracer = opentelemetry_sdk::trace::TracerProvider::builder()
-            .with_batch_exporter(
-                opentelemetry_jaeger::new_agent_pipeline()
-                    .with_service_name(&config.service_name)
-                    .with_endpoint(&jaeger_config.agent_endpoint)
-                    .build_simple()?,
-                Tokio,
-            )
-            .build()
-            .tracer(&config.service_name);
-
-        let telemetry_layer = OpenTelemetryLayer::new(tracer);
-
-        // Register the OpenTelemetry layer with the existing subscriber
-        tracing_subscriber::registry()
-            .with(telemetry_layer)
-            .try_init()?;
-
-        tracing::info!(
-            service_name = %config.service_name,
-            service_version = %config.service_version,
-            jaeger_endpoint = %jaeger_config.agent_endpoint,
-            "OpenTelemetry with Jaeger initialized"
-        );
-    } else if config.otlp.is_some() {
-        // TODO: Implement OTLP initialization
-        tracing::warn!("OTLP exporter not yet implemented");
+    let Some(otlp_config) = &config.otlp else {
+        return Ok(None);
+    };
+
+    let mut headers = otlp_config.headers.clone();
+    if let Some(api_key) = &otlp_config.api_key {
+        headers.insert("authorization".to_string(), format!("Bearer {}", api_key));
     }
 
-    Ok(())
+    let exporter = match otlp_config.protocol {
+        OtlpProtocol::Grpc => {
+            let mut metadata = tonic::metadata::MetadataMap::new();
+            for (key, value) in &headers {
+                let key = tonic::metadata::MetadataKey::from_bytes(key.to_lowercase().as_bytes())?;
+                metadata.insert(key, value.parse()?);
+            }
+            SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(&otlp_config.endpoint)
+                .with_metadata(metadata)
+                .build()?
+        }
+        OtlpProtocol::HttpProtobuf => {
+            let endpoint = otlp_config.endpoint.trim_end_matches('/');
+            let endpoint = if endpoint.ends_with("/v1/traces") {
+                endpoint.to_string()
+            } else {
+                format!("{}/v1/traces", endpoint)
+            };
+            SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .with_headers(headers)
+                .build()?
+        }
+    };
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, Tokio)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(otlp_config.sample_rate))))
+        .with_resource(Resource::new(vec![
+            KeyValue::new("service.name", config.service_name.clone()),
+            KeyValue::new("service.version", config.service_version.clone()),
+        ]))
+        .build();
+    let tracer = provider.tracer(config.service_name.clone());
+
+    // W3C trace context for outgoing/incoming headers (see `propagation`)
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    opentelemetry::global::set_tracer_provider(provider);
+
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer).boxed()))
+}
+
+/// Flush buffered spans and shut down trace export
+///
+/// Call before the process exits; spans still in the batch queue are lost otherwise.
+pub fn shutdown_observability() {
+    #[cfg(feature = "observability")]
+    opentelemetry::global::shutdown_tracer_provider();
 }
 
 #[cfg(feature = "metrics")]
@@ -1449,28 +1664,34 @@ fn init_metrics(config: &MetricsConfig) -> Result<()> {
     config.validate().map_err(|e| anyhow::anyhow!("Metrics configuration validation failed: {}", e))?;
 
     if let Some(prometheus_config) = &config.prometheus {
-        let builder = metrics_exporter_prometheus::PrometheusBuilder::new();
-        let _handle = builder
-            .with_http_listener(([0, 0, 0, 0], prometheus_config.port))
-            .install()?;
-
-        tracing::info!(
-            port = prometheus_config.port,
-            path = %prometheus_config.path,
-            "Prometheus metrics exporter initialized"
-        );
-
-        // Store the handle for later use
-        // TODO: Add proper handle management
+        let exporter = super::prometheus::PrometheusExporter::install("vespera-bindery")?;
+
+        // The scrape endpoint needs a runtime; without one the host can still
+        // serve `render_metrics()` itself
+        match tokio::runtime::Handle::try_current() {
+            Ok(_) => {
+                let listener = std::net::TcpListener::bind(("0.0.0.0", prometheus_config.port))?;
+                listener.set_nonblocking(true)?;
+                exporter.serve_on(tokio::net::TcpListener::from_std(listener)?, prometheus_config.path.clone());
+
+                tracing::info!(
+                    port = prometheus_config.port,
+                    path = %prometheus_config.path,
+                    "Prometheus metrics exporter initialized"
+                );
+            }
+            Err(_) => tracing::warn!(
+                "No Tokio runtime; Prometheus recorder installed without a scrape endpoint"
+            ),
+        }
     }
 
     Ok(())
 }
 
 #[cfg(not(feature = "observability"))]
-fn init_opentelemetry(_config: &OpenTelemetryConfig) -> Result<()> {
-    tracing::warn!("OpenTelemetry support not compiled in");
-    Ok(())
+fn init_opentelemetry(_config: &OpenTelemetryConfig) -> Result<Option<BoxedLayer>> {
+    Ok(None)
 }
 
 #[cfg(not(feature = "metrics"))]
@@ -1543,6 +1764,17 @@ mod tests {
         config.console = false;
         config.file = None;
         assert!(config.validate().is_err());
+
+        // Module overrides
+        let config = LoggingConfig::default()
+            .with_module_level("vespera_bindery::crdt", "DEBUG")
+            .unwrap()
+            .with_module_level("sqlx", "warn")
+            .unwrap();
+        assert_eq!(config.filter_directives(), "info,sqlx=warn,vespera_bindery::crdt=debug");
+        assert!(EnvFilter::try_new(config.filter_directives()).is_ok());
+        assert!(LoggingConfig::default().with_module_level("sqlx", "loud").is_err());
+        assert!(LoggingConfig::default().with_module_level("a=b", "info").is_err());
     }
 
     #[test]
@@ -1553,6 +1785,8 @@ mod tests {
             filename: "app.log".to_string(),
             rotation: LogRotation::Daily,
             max_files: Some(30),
+            max_size_bytes: Some(10 * 1024 * 1024),
+            json: Some(true),
         };
         assert!(config.validate().is_ok());
 
@@ -1575,6 +1809,12 @@ mod tests {
         let mut config = config.clone();
         config.max_files = Some(0);
         assert!(config.validate().is_err());
+
+        // Zero size limit
+        let mut config = config.clone();
+        config.max_files = Some(30);
+        config.max_size_bytes = Some(0);
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -1603,6 +1843,9 @@ mod tests {
         let config = OtlpConfig {
             endpoint: "https://api.honeycomb.io".to_string(),
             api_key: Some("valid_key_123".to_string()),
+            protocol: OtlpProtocol::HttpProtobuf,
+            headers: HashMap::new(),
+            sample_rate: 1.0,
         };
         assert!(config.validate().is_ok());
 
@@ -1615,6 +1858,14 @@ mod tests {
         let mut config = config.clone();
         config.api_key = Some("short".to_string());
         assert!(config.validate().is_err());
+
+        // Protocol and sample rate default when omitted
+        let config: OtlpConfig = serde_json::from_str(r#"{"endpoint": "http://localhost:4317", "api_key": null}"#).unwrap();
+        assert_eq!(config.protocol, OtlpProtocol::Grpc);
+        assert_eq!(config.sample_rate, 1.0);
+        let config: OtlpConfig = serde_json::from_str(r#"{"endpoint": "http://localhost:4318", "api_key": null, "protocol": "http/protobuf", "sample_rate": 2.0}"#).unwrap();
+        assert_eq!(config.protocol, OtlpProtocol::HttpProtobuf);
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -1730,6 +1981,11 @@ mod tests {
         config.url = "invalid-url".to_string();
         assert!(config.validate().is_err());
 
+        // Secret references are resolved when the webhook is called
+        let mut config = config.clone();
+        config.url = "secret://hooks/alerts_url".to_string();
+        assert!(config.validate().is_ok());
+
         // Invalid HTTP method should fail
         let mut config = config.clone();
         config.method = "INVALID".to_string();
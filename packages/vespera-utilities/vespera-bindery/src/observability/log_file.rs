@@ -0,0 +1,216 @@
+//! Log file writer that rotates on size as well as time
+//!
+//! `tracing-appender` only rotates on a schedule. When a size limit is
+//! configured, logs go through [`RotatingLogFile`] instead: the active file
+//! always has the configured name, and once it would exceed the size limit
+//! or its rotation period ends it is renamed with a timestamp suffix
+//! (`bindery.log.2024-05-01T12-00-00`) and a fresh file is started. Only the
+//! newest `max_files` rotated files are kept.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Datelike, Local};
+use tracing_subscriber::fmt::MakeWriter;
+
+use super::config::LogRotation;
+
+/// Size- and time-rotated log file, usable as a `fmt` layer writer
+#[derive(Clone)]
+pub struct RotatingLogFile {
+    inner: Arc<Mutex<ActiveFile>>,
+}
+
+struct ActiveFile {
+    directory: PathBuf,
+    filename: String,
+    rotation: LogRotation,
+    max_size_bytes: u64,
+    max_files: Option<usize>,
+    file: File,
+    size: u64,
+    period: Option<String>,
+}
+
+impl RotatingLogFile {
+    /// Open (or continue) `directory/filename`
+    pub fn new(
+        directory: impl Into<PathBuf>,
+        filename: impl Into<String>,
+        rotation: LogRotation,
+        max_size_bytes: u64,
+        max_files: Option<usize>,
+    ) -> io::Result<Self> {
+        let directory = directory.into();
+        let filename = filename.into();
+        fs::create_dir_all(&directory)?;
+
+        let path = directory.join(&filename);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let opened: DateTime<Local> = metadata.modified().map(DateTime::from).unwrap_or_else(|_| Local::now());
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(ActiveFile {
+                period: period_of(&rotation, opened),
+                directory,
+                filename,
+                rotation,
+                max_size_bytes,
+                max_files,
+                file,
+                size: metadata.len(),
+            })),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ActiveFile> {
+        // A panic mid-write leaves nothing inconsistent worth refusing to log over
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ActiveFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let now = Local::now();
+        let period_ended = period_of(&self.rotation, now) != self.period;
+        let too_big = self.size > 0 && self.size + buf.len() as u64 > self.max_size_bytes;
+        if period_ended || too_big {
+            self.rotate(now)?;
+        }
+
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn rotate(&mut self, now: DateTime<Local>) -> io::Result<()> {
+        self.file.flush()?;
+
+        let active = self.directory.join(&self.filename);
+        let stamp = now.format("%Y-%m-%dT%H-%M-%S").to_string();
+        let mut rotated = self.directory.join(format!("{}.{}", self.filename, stamp));
+        let mut attempt = 1;
+        while rotated.exists() {
+            rotated = self.directory.join(format!("{}.{}.{}", self.filename, stamp, attempt));
+            attempt += 1;
+        }
+        fs::rename(&active, &rotated)?;
+
+        self.file = OpenOptions::new().create(true).append(true).open(&active)?;
+        self.size = 0;
+        self.period = period_of(&self.rotation, now);
+
+        if let Some(max_files) = self.max_files {
+            prune_rotated(&self.directory, &self.filename, max_files)?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies the rotation period `time` falls in; `None` when rotation is size-only
+fn period_of(rotation: &LogRotation, time: DateTime<Local>) -> Option<String> {
+    match rotation {
+        LogRotation::Never => None,
+        LogRotation::Hourly => Some(time.format("%Y-%m-%d-%H").to_string()),
+        LogRotation::Daily => Some(time.format("%Y-%m-%d").to_string()),
+        LogRotation::Weekly => {
+            let week = time.iso_week();
+            Some(format!("{}-W{}", week.year(), week.week()))
+        }
+    }
+}
+
+/// Delete all but the newest `keep` rotated copies of `filename`
+fn prune_rotated(directory: &Path, filename: &str, keep: usize) -> io::Result<()> {
+    let prefix = format!("{}.", filename);
+    let mut rotated = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            rotated.push((entry.metadata()?.modified()?, entry.path()));
+        }
+    }
+
+    // Newest first; names sort by timestamp when modification times tie
+    rotated.sort_by(|a, b| b.cmp(a));
+    for (_, path) in rotated.into_iter().skip(keep) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Writer handed to the `fmt` layer for one event
+pub struct RotatingLogWriter<'a>(MutexGuard<'a, ActiveFile>);
+
+impl Write for RotatingLogWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingLogFile {
+    type Writer = RotatingLogWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RotatingLogWriter(self.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn log_files(directory: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_rotates_on_size_and_keeps_max_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = RotatingLogFile::new(temp_dir.path(), "bindery.log", LogRotation::Never, 64, Some(2)).unwrap();
+
+        for i in 0..10 {
+            let mut writer = log.make_writer();
+            writer.write_all(format!("line {:02} padded to about forty bytes....\n", i).as_bytes()).unwrap();
+        }
+
+        let files = log_files(temp_dir.path());
+        // The active file plus at most two rotated ones
+        assert_eq!(files.len(), 3, "{:?}", files);
+        assert!(files.contains(&"bindery.log".to_string()));
+
+        let active = fs::read_to_string(temp_dir.path().join("bindery.log")).unwrap();
+        assert_eq!(active, "line 09 padded to about forty bytes....\n");
+        for name in files.iter().filter(|name| name.as_str() != "bindery.log") {
+            assert!(fs::metadata(temp_dir.path().join(name)).unwrap().len() <= 64);
+        }
+    }
+
+    #[test]
+    fn test_rotates_when_period_ends() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = RotatingLogFile::new(temp_dir.path(), "bindery.log", LogRotation::Daily, 1024 * 1024, None).unwrap();
+        log.make_writer().write_all(b"yesterday\n").unwrap();
+
+        // Pretend the file was opened on an earlier day
+        log.lock().period = Some("2000-01-01".to_string());
+        log.make_writer().write_all(b"today\n").unwrap();
+
+        let files = log_files(temp_dir.path());
+        assert_eq!(files.len(), 2, "{:?}", files);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("bindery.log")).unwrap(), "today\n");
+    }
+}
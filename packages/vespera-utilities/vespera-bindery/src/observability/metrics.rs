@@ -59,6 +59,7 @@ impl MetricsCollector {
         describe_gauge!("bindery_crdt_memory_usage_bytes", Unit::Bytes, "CRDT memory usage in bytes");
         describe_counter!("bindery_crdt_gc_operations_total", Unit::Count, "Total CRDT garbage collection operations");
         describe_histogram!("bindery_crdt_gc_duration_seconds", Unit::Seconds, "CRDT garbage collection duration");
+        describe_histogram!("bindery_crdt_sync_peers", Unit::Count, "Peers involved in each CRDT sync");
         describe_gauge!("bindery_crdt_vector_clock_size", Unit::Count, "Size of CRDT vector clocks");
         describe_counter!("bindery_crdt_conflicts_total", Unit::Count, "Total CRDT conflicts detected");
         describe_histogram!("bindery_crdt_operation_size_bytes", Unit::Bytes, "Size of CRDT operations in bytes");
@@ -69,12 +70,28 @@ impl MetricsCollector {
         describe_histogram!("bindery_crdt_gc_memory_freed_bytes", Unit::Bytes, "Memory freed during CRDT garbage collection");
         describe_counter!("bindery_crdt_gc_failures_total", Unit::Count, "Total CRDT garbage collection failures");
 
+        // Compression metrics
+        describe_counter!("bindery_compression_operations_total", Unit::Count, "Total compressions and decompressions");
+        describe_histogram!("bindery_compression_ratio", Unit::Count, "Uncompressed size divided by compressed size");
+        describe_histogram!("bindery_compression_duration_seconds", Unit::Seconds, "Compression and decompression duration");
+        describe_counter!("bindery_compression_input_bytes_total", Unit::Bytes, "Total uncompressed bytes compressed or produced by decompression");
+        describe_counter!("bindery_compression_output_bytes_total", Unit::Bytes, "Total compressed bytes written or read");
+
         // RAG system metrics
         describe_counter!("bindery_rag_embeddings_generated_total", Unit::Count, "Total embeddings generated");
         describe_histogram!("bindery_rag_embedding_generation_duration_seconds", Unit::Seconds, "Embedding generation duration");
         describe_counter!("bindery_rag_searches_total", Unit::Count, "Total vector searches");
         describe_histogram!("bindery_rag_search_duration_seconds", Unit::Seconds, "Vector search duration");
         describe_gauge!("bindery_rag_documents_indexed", Unit::Count, "Number of indexed documents");
+        describe_histogram!("bindery_rag_search_results", Unit::Count, "Results returned per vector search");
+
+        // LLM provider metrics
+        describe_counter!("bindery_provider_requests_total", Unit::Count, "Total LLM provider requests");
+        describe_histogram!("bindery_provider_request_duration_seconds", Unit::Seconds, "LLM provider request duration");
+
+        // Hook metrics
+        describe_counter!("bindery_hook_executions_total", Unit::Count, "Total hook executions");
+        describe_histogram!("bindery_hook_execution_duration_seconds", Unit::Seconds, "Hook execution duration");
 
         // Task management metrics
         describe_counter!("bindery_tasks_created_total", Unit::Count, "Total tasks created");
@@ -87,6 +104,7 @@ impl MetricsCollector {
         describe_gauge!("bindery_task_queue_size", Unit::Count, "Number of tasks in execution queue");
         describe_histogram!("bindery_task_queue_wait_duration_seconds", Unit::Seconds, "Time tasks wait in queue before execution");
         describe_histogram!("bindery_task_subtasks_created", Unit::Count, "Number of subtasks created by parent tasks");
+        describe_counter!("bindery_quota_rejections_total", Unit::Count, "Total requests refused by a quota");
 
         // Role management metrics
         describe_counter!("bindery_role_assignments_total", Unit::Count, "Total role assignments");
@@ -159,15 +177,18 @@ impl BinderyMetrics {
 
     /// Record database pool performance metrics
     pub fn record_database_pool_metrics(
+        pool: &str,
         active_connections: u32,
         max_connections: u32,
         pool_utilization: f64,
         acquisition_time: Duration
     ) {
-        gauge!("bindery_database_connections_active").set(active_connections as f64);
-        gauge!("bindery_database_connections_max").set(max_connections as f64);
-        gauge!("bindery_database_connection_pool_utilization_percent").set(pool_utilization * 100.0);
-        histogram!("bindery_database_connection_acquisition_duration_seconds")
+        let labels = [("pool", pool.to_string())];
+
+        gauge!("bindery_database_connections_active", &labels).set(active_connections as f64);
+        gauge!("bindery_database_connections_max", &labels).set(max_connections as f64);
+        gauge!("bindery_database_connection_pool_utilization_percent", &labels).set(pool_utilization * 100.0);
+        histogram!("bindery_database_connection_acquisition_duration_seconds", &labels)
             .record(acquisition_time.as_secs_f64());
     }
 
@@ -309,14 +330,13 @@ impl BinderyMetrics {
 
     /// Record CRDT sync operation
     pub fn record_crdt_sync(operation: &str, peer_count: usize, duration: Duration, success: bool) {
-        let labels = [
-            ("operation", operation.to_string()),
-            ("peer_count", peer_count.to_string()),
-        ];
+        // Peer counts are unbounded, so they go in a histogram rather than a label
+        let labels = [("operation", operation.to_string())];
 
         counter!("bindery_crdt_sync_operations_total", &labels).increment(1);
         histogram!("bindery_crdt_sync_duration_seconds", &labels)
             .record(duration.as_secs_f64());
+        histogram!("bindery_crdt_sync_peers", &labels).record(peer_count as f64);
 
         if !success {
             let error_labels = [("component", "crdt_sync".to_string()), ("operation", operation.to_string())];
@@ -325,6 +345,41 @@ impl BinderyMetrics {
         }
     }
 
+    /// Record compressing `uncompressed` bytes to `compressed` bytes
+    pub fn record_compression(codec: &str, payload: &str, uncompressed: usize, compressed: usize, duration: Duration) {
+        Self::record_compression_step("compress", codec, payload, uncompressed, compressed, duration);
+    }
+
+    /// Record decompressing `compressed` bytes back to `uncompressed` bytes
+    pub fn record_decompression(codec: &str, payload: &str, compressed: usize, uncompressed: usize, duration: Duration) {
+        Self::record_compression_step("decompress", codec, payload, uncompressed, compressed, duration);
+    }
+
+    fn record_compression_step(
+        direction: &str,
+        codec: &str,
+        payload: &str,
+        uncompressed: usize,
+        compressed: usize,
+        duration: Duration
+    ) {
+        let labels = [
+            ("direction", direction.to_string()),
+            ("codec", codec.to_string()),
+            ("payload", payload.to_string()),
+        ];
+
+        counter!("bindery_compression_operations_total", &labels).increment(1);
+        histogram!("bindery_compression_duration_seconds", &labels)
+            .record(duration.as_secs_f64());
+        counter!("bindery_compression_input_bytes_total", &labels).increment(uncompressed as u64);
+        counter!("bindery_compression_output_bytes_total", &labels).increment(compressed as u64);
+        if compressed > 0 {
+            histogram!("bindery_compression_ratio", &labels)
+                .record(uncompressed as f64 / compressed as f64);
+        }
+    }
+
     /// Record RAG embedding generation
     pub fn record_rag_embedding_generation(provider: &str, count: u64, duration: Duration, success: bool) {
         let labels = [("provider", provider.to_string())];
@@ -342,14 +397,12 @@ impl BinderyMetrics {
 
     /// Record RAG search operation
     pub fn record_rag_search(query_type: &str, result_count: usize, duration: Duration, success: bool) {
-        let labels = [
-            ("query_type", query_type.to_string()),
-            ("result_count", result_count.to_string()),
-        ];
+        let labels = [("query_type", query_type.to_string())];
 
         counter!("bindery_rag_searches_total", &labels).increment(1);
         histogram!("bindery_rag_search_duration_seconds", &labels)
             .record(duration.as_secs_f64());
+        histogram!("bindery_rag_search_results", &labels).record(result_count as f64);
 
         if !success {
             let error_labels = [("component", "rag_search".to_string()), ("query_type", query_type.to_string())];
@@ -415,6 +468,12 @@ impl BinderyMetrics {
         }
     }
 
+    /// Record a request refused because `quota` is used up
+    pub fn record_quota_rejection(quota: &str) {
+        let labels = [("quota", quota.to_string())];
+        counter!("bindery_quota_rejections_total", &labels).increment(1);
+    }
+
     /// Record task queue metrics
     pub fn record_task_queue_metrics(queue_size: usize, active_tasks: usize) {
         gauge!("bindery_task_queue_size").set(queue_size as f64);
@@ -512,6 +571,40 @@ impl BinderyMetrics {
         gauge!("bindery_migration_version").set(to_version as f64);
     }
 
+    /// Record an LLM provider request
+    ///
+    /// Labelled by provider type rather than provider ID so that
+    /// reloading providers doesn't mint new series.
+    pub fn record_provider_request(provider_type: &str, operation: &str, duration: Duration, success: bool) {
+        let labels = [
+            ("provider_type", provider_type.to_string()),
+            ("operation", operation.to_string()),
+            ("outcome", if success { "success" } else { "failure" }.to_string()),
+        ];
+
+        counter!("bindery_provider_requests_total", &labels).increment(1);
+        histogram!("bindery_provider_request_duration_seconds", &labels)
+            .record(duration.as_secs_f64());
+
+        if !success {
+            let error_labels = [("component", "provider".to_string()), ("provider_type", provider_type.to_string())];
+            counter!("bindery_errors_total", &error_labels)
+                .increment(1);
+        }
+    }
+
+    /// Record a hook execution, labelled by trigger kind (not hook ID)
+    pub fn record_hook_execution(trigger: &str, duration: Duration, success: bool) {
+        let labels = [
+            ("trigger", trigger.to_string()),
+            ("outcome", if success { "success" } else { "failure" }.to_string()),
+        ];
+
+        counter!("bindery_hook_executions_total", &labels).increment(1);
+        histogram!("bindery_hook_execution_duration_seconds", &labels)
+            .record(duration.as_secs_f64());
+    }
+
     /// Record circuit breaker request
     pub fn record_circuit_breaker_request(
         service_name: &str,
@@ -2,9 +2,10 @@
 //!
 //! This module provides comprehensive logging and monitoring capabilities
 //! for the Vespera Bindery service, including:
-//! - Structured logging with tracing
-//! - OpenTelemetry integration
-//! - Metrics collection
+//! - Structured logging with tracing, to console and size/time-rotated files,
+//!   with per-module levels that can be reloaded at runtime
+//! - OpenTelemetry trace export (OTLP) with context propagation
+//! - Metrics collection, with an optional Prometheus exporter (`metrics` feature)
 //! - Request tracing
 //! - Performance monitoring
 //! - Comprehensive audit logging for security-sensitive operations
@@ -13,18 +14,38 @@ pub mod config;
 pub mod instrumentation;
 pub mod metrics;
 pub mod audit;
+pub mod audit_analytics;
+pub mod audit_export;
+pub mod audit_commands;
+pub mod propagation;
+pub mod log_file;
+#[cfg(feature = "metrics")]
+pub mod prometheus;
 
-pub use config::{LoggingConfig, ObservabilityConfig, init_logging, init_observability};
+pub use config::{
+    LoggingConfig, ObservabilityConfig, init_logging, init_observability, shutdown_observability,
+    reload_logging, current_logging_config
+};
 pub use instrumentation::{instrument_database, instrument_crdt, instrument_rag};
 pub use metrics::{MetricsCollector, BinderyMetrics};
+pub use propagation::{spawn_traced, trace_headers, current_trace_id};
+#[cfg(feature = "metrics")]
+pub use prometheus::{PrometheusExporter, render_metrics};
 
 // Re-export audit logging functionality
 pub use audit::{
     AuditLogger, AuditEvent, AuditConfig, AuditQueryFilter, AuditStats,
     UserContext, Operation, SecurityContext, OperationOutcome,
-    create_role_execution_event, create_migration_event, create_config_change_event,
-    create_auth_failure_event
+    create_role_execution_event, create_migration_event, create_config_change_event, create_trash_event,
+    create_approval_event, create_codex_access_event,
+    create_auth_failure_event, ChainVerification, BrokenLink, ChainBreak, DEFAULT_RETENTION_INTERVAL
+};
+pub use audit_analytics::{
+    AuditAggregationQuery, AuditAggregate, AuditGroupBy, AuditTimeBucket, FailedAuthSummary,
+    AuditAnomaly, AuditAnomalyConfig
 };
+pub use audit_export::{AuditExportPayload, SignedAuditExport, load_or_create_signing_key};
+pub use audit_commands::{AuditCommand, AuditCommandExecutor};
 
 /// Re-export commonly used tracing items
 pub use tracing::{debug, error, info, trace, warn, instrument, Instrument, Span};
@@ -166,12 +187,19 @@ impl Drop for PerformanceTimer {
 ///
 /// This function sets up audit logging with the provided configuration
 /// and returns an AuditLogger instance that can be shared across components.
+/// When `retention_days` or `max_events` is set, the retention policy is
+/// applied every [`DEFAULT_RETENTION_INTERVAL`] in the background.
 pub async fn init_audit_logging(config: AuditConfig) -> Result<std::sync::Arc<AuditLogger>, crate::errors::BinderyError> {
     info!("Initializing audit logging with config: {:?}", config);
 
+    let enforce_retention = config.retention_days.is_some() || config.max_events.is_some();
     let audit_logger = AuditLogger::new(config).await?;
     let audit_logger = std::sync::Arc::new(audit_logger);
 
+    if enforce_retention {
+        audit_logger.start_retention_job(DEFAULT_RETENTION_INTERVAL).await;
+    }
+
     info!("Audit logging initialized successfully");
     Ok(audit_logger)
 }
@@ -187,6 +215,7 @@ pub fn default_audit_config() -> AuditConfig {
         retention_days: Some(30),  // 1 month for development
         enable_compression: false, // Disable for easier debugging
         batch_size: 100,
+        anomaly: Default::default(),
     }
 }
 
@@ -199,6 +228,7 @@ pub fn production_audit_config(audit_db_path: std::path::PathBuf) -> AuditConfig
         retention_days: Some(365),   // 1 year retention
         enable_compression: true,    // Enable compression to save space
         batch_size: 1000,           // Larger batches for better performance
+        anomaly: Default::default(),
     }
 }
 
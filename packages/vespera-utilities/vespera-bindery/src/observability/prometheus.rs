@@ -0,0 +1,191 @@
+//! Prometheus exporter for Bindery metrics (`metrics` feature)
+//!
+//! Installs a process-wide Prometheus recorder so every `BinderyMetrics`
+//! call (CRDT, database pool, RAG, provider and hook metrics) lands in one
+//! registry. Hosts that already run an HTTP server can serve
+//! [`render_metrics`] themselves; otherwise [`PrometheusExporter::serve`]
+//! runs a minimal scrape endpoint on its own port.
+//!
+//! Labels are kept to bounded sets (operation names, provider types, hook
+//! triggers, pool roles) — per-entity IDs and counts never become labels.
+
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::config::PrometheusConfig;
+use super::metrics::MetricsCollector;
+use crate::{BinderyError, BinderyResult};
+
+/// Histogram buckets for `*_seconds` metrics (1ms to 60s)
+const DURATION_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+/// Histogram buckets for `*_bytes` metrics (256B to 64MiB)
+const SIZE_BUCKETS: &[f64] = &[256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 16777216.0, 67108864.0];
+/// Histogram buckets for everything else (counts per operation)
+const COUNT_BUCKETS: &[f64] = &[0.0, 1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 1000.0];
+
+/// Largest scrape request we bother reading
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+static EXPORTER: OnceLock<PrometheusExporter> = OnceLock::new();
+
+/// Handle to the installed Prometheus recorder
+pub struct PrometheusExporter {
+    handle: PrometheusHandle,
+}
+
+impl PrometheusExporter {
+    /// Install the global recorder, or return the one already installed
+    ///
+    /// Fails only if some other `metrics` recorder was installed first.
+    pub fn install(service_name: &str) -> BinderyResult<&'static PrometheusExporter> {
+        if let Some(exporter) = EXPORTER.get() {
+            return Ok(exporter);
+        }
+
+        let handle = PrometheusBuilder::new()
+            .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), DURATION_BUCKETS)
+            .and_then(|builder| builder.set_buckets_for_metric(Matcher::Suffix("_bytes".to_string()), SIZE_BUCKETS))
+            .and_then(|builder| builder.set_buckets(COUNT_BUCKETS))
+            .and_then(|builder| builder.install_recorder())
+            .map_err(|e| BinderyError::ConfigurationError(format!("Failed to install Prometheus recorder: {}", e)))?;
+
+        let exporter = EXPORTER.get_or_init(|| PrometheusExporter { handle });
+
+        // Descriptions only stick once a recorder is installed
+        MetricsCollector::new(service_name);
+        Ok(exporter)
+    }
+
+    /// The installed exporter, if any
+    pub fn get() -> Option<&'static PrometheusExporter> {
+        EXPORTER.get()
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        self.handle.render()
+    }
+
+    /// Bind the configured port and serve scrapes on the configured path
+    pub async fn serve(&'static self, config: &PrometheusConfig) -> BinderyResult<JoinHandle<()>> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+        let listener = TcpListener::bind(addr).await.map_err(|e| {
+            BinderyError::ConfigurationError(format!("Failed to bind metrics endpoint on {}: {}", addr, e))
+        })?;
+
+        Ok(self.serve_on(listener, config.path.clone()))
+    }
+
+    /// Serve scrapes from an already-bound listener
+    ///
+    /// `GET <path>` returns the rendered metrics; anything else is a 404.
+    pub fn serve_on(&'static self, listener: TcpListener, path: String) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!("Metrics endpoint accept failed: {}", e);
+                        continue;
+                    }
+                };
+
+                let path = path.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = self.respond(stream, &path).await {
+                        tracing::debug!("Metrics scrape failed: {}", e);
+                    }
+                });
+            }
+        })
+    }
+
+    async fn respond(&self, mut stream: TcpStream, path: &str) -> std::io::Result<()> {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+            let read = stream.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+
+        let request = String::from_utf8_lossy(&request);
+        let mut parts = request.lines().next().unwrap_or_default().split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or_default();
+        let target = target.split('?').next().unwrap_or_default();
+
+        let (status, content_type, body) = if method == "GET" && target == path {
+            ("200 OK", "text/plain; version=0.0.4", self.render())
+        } else {
+            ("404 Not Found", "text/plain", "not found\n".to_string())
+        };
+
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+/// Render the installed exporter's metrics, for hosts serving `/metrics` themselves
+pub fn render_metrics() -> Option<String> {
+    PrometheusExporter::get().map(PrometheusExporter::render)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::BinderyMetrics;
+    use std::time::Duration;
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_exporter_renders_and_serves_configured_path() {
+        let exporter = PrometheusExporter::install("bindery-test").unwrap();
+        BinderyMetrics::record_provider_request("claude-code-cli", "send_message", Duration::from_millis(20), true);
+        BinderyMetrics::record_rag_search("semantic", 7, Duration::from_millis(3), true);
+
+        let rendered = render_metrics().unwrap();
+        assert!(rendered.contains("bindery_provider_requests_total{provider_type=\"claude-code-cli\""));
+        assert!(rendered.contains("bindery_provider_request_duration_seconds_bucket"));
+        // Result counts are a histogram, never a label
+        assert!(rendered.contains("bindery_rag_search_results_bucket"));
+        assert!(!rendered.contains("result_count="));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = exporter.serve_on(listener, "/custom-metrics".to_string());
+
+        let ok = get(addr, "/custom-metrics").await;
+        assert!(ok.starts_with("HTTP/1.1 200 OK"));
+        assert!(ok.contains("bindery_provider_requests_total"));
+
+        let missing = get(addr, "/metrics").await;
+        assert!(missing.starts_with("HTTP/1.1 404"));
+
+        server.abort();
+    }
+}
@@ -0,0 +1,169 @@
+//! Trace context propagation
+//!
+//! Carries the current trace across the places it would otherwise be lost:
+//! - `tokio::spawn`, which starts the task outside the caller's span
+//!   ([`spawn_traced`])
+//! - outgoing HTTP calls to providers, as W3C `traceparent`/`tracestate`
+//!   headers ([`trace_headers`])
+//! - incoming requests carrying those headers ([`set_parent_from_headers`])
+//! - audit events, which record the trace ID they were logged under
+//!   ([`current_trace_id`])
+//!
+//! Span nesting works with any `tracing` subscriber. Header propagation and
+//! trace IDs need the `observability` feature and an OpenTelemetry layer
+//! installed by [`init_observability`](super::init_observability); without
+//! them those functions return nothing.
+
+use reqwest::header::HeaderMap;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span};
+
+/// Spawn a task that stays inside the caller's current span
+pub fn spawn_traced<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future.in_current_span())
+}
+
+#[cfg(feature = "observability")]
+mod otel {
+    use opentelemetry::propagation::{Extractor, Injector};
+    use opentelemetry::trace::TraceContextExt;
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+    impl Injector for HeaderInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|value| value.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|name| name.as_str()).collect()
+        }
+    }
+
+    pub fn trace_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let context = Span::current().context();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+        });
+        headers
+    }
+
+    pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        if parent.span().span_context().is_valid() {
+            span.set_parent(parent);
+        }
+    }
+
+    pub fn current_ids() -> Option<(String, String)> {
+        let context = Span::current().context();
+        let span = context.span();
+        let span_context = span.span_context();
+        span_context
+            .is_valid()
+            .then(|| (span_context.trace_id().to_string(), span_context.span_id().to_string()))
+    }
+}
+
+/// W3C trace context headers for the current span, to attach to outgoing requests
+pub fn trace_headers() -> HeaderMap {
+    #[cfg(feature = "observability")]
+    return otel::trace_headers();
+
+    #[cfg(not(feature = "observability"))]
+    HeaderMap::new()
+}
+
+/// Make `span` a child of the trace described by incoming request headers
+///
+/// Leaves the span alone when the headers carry no valid trace context.
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    #[cfg(feature = "observability")]
+    otel::set_parent_from_headers(span, headers);
+
+    #[cfg(not(feature = "observability"))]
+    let _ = (span, headers);
+}
+
+/// Hex trace ID of the current span, if it belongs to an OpenTelemetry trace
+pub fn current_trace_id() -> Option<String> {
+    current_trace_ids().map(|(trace_id, _)| trace_id)
+}
+
+/// Hex trace and span IDs of the current span, if it belongs to an OpenTelemetry trace
+pub fn current_trace_ids() -> Option<(String, String)> {
+    #[cfg(feature = "observability")]
+    return otel::current_ids();
+
+    #[cfg(not(feature = "observability"))]
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spawn_traced_keeps_current_span() {
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let span = tracing::info_span!("request");
+
+        let (spawned, plain) = async {
+            (
+                spawn_traced(async { Span::current().id() }).await.unwrap(),
+                tokio::spawn(async { Span::current().id() }).await.unwrap(),
+            )
+        }
+        .instrument(span.clone())
+        .await;
+
+        assert_eq!(spawned, span.id());
+        assert_eq!(plain, None);
+    }
+
+    #[cfg(feature = "observability")]
+    #[test]
+    fn test_trace_context_round_trips_through_headers() {
+        use opentelemetry::trace::TracerProvider as _;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        opentelemetry::global::set_text_map_propagator(
+            opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+        );
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outgoing = tracing::info_span!("provider_call");
+            let (trace_id, headers) = outgoing.in_scope(|| (current_trace_id().unwrap(), trace_headers()));
+            let traceparent = headers.get("traceparent").unwrap().to_str().unwrap();
+            assert!(traceparent.contains(&trace_id));
+
+            // A server receiving those headers continues the same trace
+            let incoming = tracing::info_span!("handle_request");
+            set_parent_from_headers(&incoming, &headers);
+            assert_eq!(incoming.in_scope(current_trace_id), Some(trace_id));
+        });
+    }
+}
@@ -0,0 +1,111 @@
+//! Cursor-based pagination for large Codex and task lists
+//!
+//! List methods ending in `_page` take a [`PageRequest`] and return a
+//! [`Page`] whose `next_cursor` continues where the page stopped. Cursors are
+//! opaque strings that encode the last item's position, so pages stay stable
+//! while items are added or removed elsewhere in the list.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{BinderyError, BinderyResult};
+
+/// Page size used when a request doesn't give one
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page a request may ask for
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Where a page starts and how many items it holds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRequest {
+    /// `next_cursor` of the previous page; the first page when unset
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    /// Items per page, capped at [`MAX_PAGE_SIZE`]; [`DEFAULT_PAGE_SIZE`] when unset
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl PageRequest {
+    pub fn first(limit: usize) -> Self {
+        Self { cursor: None, limit: Some(limit) }
+    }
+
+    pub fn after(cursor: impl Into<String>, limit: usize) -> Self {
+        Self { cursor: Some(cursor.into()), limit: Some(limit) }
+    }
+
+    /// The page size to use, between 1 and [`MAX_PAGE_SIZE`]
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// One page of a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// A page from up to `page_size + 1` fetched items, the extra one showing there is more
+    ///
+    /// `cursor_of` encodes the position of the page's last item.
+    pub(crate) fn from_overfetch(mut items: Vec<T>, page_size: usize, cursor_of: impl Fn(&T) -> String) -> Self {
+        let next_cursor = if items.len() > page_size {
+            items.truncate(page_size);
+            items.last().map(cursor_of)
+        } else {
+            None
+        };
+        Self { items, next_cursor }
+    }
+}
+
+/// An opaque cursor from the sort key parts of an item
+pub(crate) fn encode_cursor(parts: &[&str]) -> String {
+    URL_SAFE_NO_PAD.encode(parts.join("\n"))
+}
+
+/// The `count` sort key parts of a cursor from [`encode_cursor`]
+pub(crate) fn decode_cursor(cursor: &str, count: usize) -> BinderyResult<Vec<String>> {
+    let invalid = || BinderyError::InvalidInput(format!("Invalid page cursor: {}", cursor));
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let parts: Vec<String> = text.split('\n').map(str::to_string).collect();
+    if parts.len() != count {
+        return Err(invalid());
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip_and_rejects_garbage() {
+        let cursor = encode_cursor(&["2026-01-01T00:00:00+00:00", "abc"]);
+        assert_eq!(decode_cursor(&cursor, 2).unwrap(), vec!["2026-01-01T00:00:00+00:00", "abc"]);
+        assert!(matches!(decode_cursor(&cursor, 1), Err(BinderyError::InvalidInput(_))));
+        assert!(matches!(decode_cursor("%%%", 2), Err(BinderyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_page_from_overfetch() {
+        let page = Page::from_overfetch(vec![1, 2, 3], 2, |n| n.to_string());
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.next_cursor.as_deref(), Some("2"));
+
+        let last = Page::from_overfetch(vec![1, 2], 2, |n| n.to_string());
+        assert_eq!(last.next_cursor, None);
+
+        assert_eq!(PageRequest::default().page_size(), DEFAULT_PAGE_SIZE);
+        assert_eq!(PageRequest::first(0).page_size(), 1);
+        assert_eq!(PageRequest::first(1_000_000).page_size(), MAX_PAGE_SIZE);
+    }
+}
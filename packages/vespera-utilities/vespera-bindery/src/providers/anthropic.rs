@@ -0,0 +1,753 @@
+// Anthropic REST Provider
+//
+// Talks to the Anthropic Messages API directly, without the Claude Code CLI.
+//
+// Endpoint: POST {base_url}/v1/messages
+// Protocol: HTTP JSON, `x-api-key` + `anthropic-version` headers
+//
+// Streaming response format: Server-Sent Events
+// message_start       -> {"message":{"usage":{"input_tokens":N}}}
+// content_block_start -> {"index":I,"content_block":{"type":"tool_use","id":"...","name":"..."}}
+// content_block_delta -> {"delta":{"type":"text_delta","text":"..."}}
+//                        {"delta":{"type":"input_json_delta","partial_json":"..."}}
+// content_block_stop  -> {"index":I} (a tool_use block's input is complete)
+// message_delta       -> {"delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":N}}
+// message_stop        -> end of message
+// error               -> {"error":{"type":"...","message":"..."}}
+
+use super::rest::{check_status, data_events};
+use super::types::{
+    chat_chunk_to_stream_chunk, chat_response_to_provider_response, simple_params_to_chat_request, ChatChunk,
+    ChatRequest, ChatResponse, ChatRole, FinishReason, ModelInfo, ProviderCapabilities, ResponseMetadata,
+    StreamingResponse, ToolCall, UsageStats,
+};
+use super::{Provider, ProviderResponse, StreamChunk};
+use crate::observability::trace_headers;
+use crate::secrets::SecretRef;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+const API_NAME: &str = "Anthropic API";
+
+/// Anthropic provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicConfig {
+    /// API base URL
+    pub base_url: String,
+    /// Model name (e.g., claude-sonnet-4-5)
+    pub model: String,
+    /// Secret reference for the API key
+    pub api_key: SecretRef,
+    /// Value of the anthropic-version header
+    pub api_version: String,
+    /// Temperature (0.0-1.0)
+    pub temperature: Option<f32>,
+    /// Max tokens to generate (required by the API)
+    pub max_tokens: usize,
+    /// System prompt
+    pub system_prompt: Option<String>,
+    /// Request timeout in seconds
+    pub timeout: Option<u64>,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.anthropic.com".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            api_key: SecretRef::parse("secret://anthropic/api_key"),
+            api_version: "2023-06-01".to_string(),
+            temperature: None,
+            max_tokens: 4096,
+            system_prompt: None,
+            timeout: Some(300),
+        }
+    }
+}
+
+/// Anthropic provider implementation
+pub struct AnthropicProvider {
+    config: AnthropicConfig,
+    /// Resolved API key (never the vault reference)
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl AnthropicProvider {
+    /// Create a new provider; `api_key` is the already-resolved key
+    pub fn new(config: AnthropicConfig, api_key: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout.unwrap_or(300)))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self { config, api_key, client }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.config.base_url.trim_end_matches('/'), path))
+            .headers(trace_headers())
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", &self.config.api_version)
+    }
+
+    /// Build request payload for the Messages API
+    ///
+    /// The API takes the system prompt as a top-level field, so system messages
+    /// in the conversation are folded into it. Tool calls and results become
+    /// `tool_use` / `tool_result` content blocks, and consecutive messages from
+    /// the same side are merged since the API expects roles to alternate.
+    fn build_request_payload(&self, request: &ChatRequest, model: &str, stream: bool) -> AnthropicRequest {
+        let mut system: Vec<&str> = request
+            .system_prompt
+            .as_deref()
+            .or(self.config.system_prompt.as_deref())
+            .into_iter()
+            .collect();
+        let mut messages: Vec<AnthropicMessage> = Vec::with_capacity(request.messages.len());
+
+        for message in &request.messages {
+            let (role, content) = match message.role {
+                ChatRole::System => {
+                    system.push(&message.content);
+                    continue;
+                }
+                ChatRole::User => ("user", vec![AnthropicContent::Text { text: message.content.clone() }]),
+                ChatRole::Tool => (
+                    "user",
+                    vec![AnthropicContent::ToolResult {
+                        tool_use_id: message.tool_call_id.clone().unwrap_or_default(),
+                        content: message.content.clone(),
+                    }],
+                ),
+                ChatRole::Assistant => {
+                    // Empty text blocks are rejected, but a message needs at least one block
+                    let text = (!message.content.is_empty() || message.tool_calls.is_empty())
+                        .then(|| AnthropicContent::Text { text: message.content.clone() });
+                    let tool_uses = message.tool_calls.iter().map(|call| AnthropicContent::ToolUse {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        input: call.arguments_json(),
+                    });
+                    ("assistant", text.into_iter().chain(tool_uses).collect())
+                }
+            };
+
+            match messages.last_mut() {
+                Some(last) if last.role == role => last.content.extend(content),
+                _ => messages.push(AnthropicMessage { role, content }),
+            }
+        }
+
+        AnthropicRequest {
+            model: model.to_string(),
+            max_tokens: request.max_tokens.unwrap_or(self.config.max_tokens as u32),
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            messages,
+            temperature: request.temperature.or(self.config.temperature),
+            stop_sequences: request.stop_sequences.clone(),
+            tools: request
+                .tools
+                .iter()
+                .map(|tool| AnthropicTool {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    input_schema: tool.parameters.clone(),
+                })
+                .collect(),
+            stream,
+        }
+    }
+
+    /// Send a chat request and wait for the full response
+    async fn chat(&self, request: &ChatRequest, model: &str) -> Result<ChatResponse> {
+        let payload = self.build_request_payload(request, model, false);
+        let response = self
+            .request(reqwest::Method::POST, "/v1/messages")
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send request to Anthropic API")?;
+
+        let message: AnthropicResponse = check_status(response, API_NAME)
+            .await?
+            .json()
+            .await
+            .context("Failed to parse Anthropic response")?;
+
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        for block in message.content {
+            match block {
+                AnthropicResponseBlock::Text { text } => content.push_str(&text),
+                AnthropicResponseBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall::from_json(id, name, input)?),
+                AnthropicResponseBlock::Other => {}
+            }
+        }
+
+        Ok(ChatResponse {
+            content,
+            finish_reason: finish_reason(message.stop_reason.as_deref()),
+            tool_calls,
+            usage: message.usage.into_stats(),
+        })
+    }
+
+    /// Send a chat request and stream the response
+    async fn chat_stream(&self, request: &ChatRequest, model: &str) -> Result<StreamingResponse> {
+        let payload = self.build_request_payload(request, model, true);
+        let response = self
+            .request(reqwest::Method::POST, "/v1/messages")
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send streaming request to Anthropic API")?;
+        let request_id = response
+            .headers()
+            .get("request-id")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let events = data_events(check_status(response, API_NAME).await?);
+
+        let stream = async_stream::stream! {
+            futures::pin_mut!(events);
+            let mut finish: Option<FinishReason> = None;
+            let mut usage = AnthropicUsage::default();
+            // tool_use blocks by content block index, with their input JSON so far
+            let mut tool_uses: HashMap<usize, (String, String, String)> = HashMap::new();
+
+            while let Some(event) = events.next().await {
+                let data = match event {
+                    Ok(data) => data,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+
+                match serde_json::from_str::<AnthropicStreamEvent>(&data) {
+                    Ok(AnthropicStreamEvent::MessageStart { message }) => usage.merge(message.usage),
+                    Ok(AnthropicStreamEvent::ContentBlockStart { index, content_block }) => {
+                        if let AnthropicResponseBlock::ToolUse { id, name, .. } = content_block {
+                            tool_uses.insert(index, (id, name, String::new()));
+                        }
+                    }
+                    Ok(AnthropicStreamEvent::ContentBlockDelta { index, delta }) => {
+                        if let Some(text) = delta.text.filter(|t| !t.is_empty()) {
+                            yield Ok(ChatChunk::text(text));
+                        }
+                        if let (Some(partial), Some((_, _, input))) = (delta.partial_json, tool_uses.get_mut(&index)) {
+                            input.push_str(&partial);
+                        }
+                    }
+                    Ok(AnthropicStreamEvent::ContentBlockStop { index }) => {
+                        if let Some((id, name, input)) = tool_uses.remove(&index) {
+                            match ToolCall::from_json(id, name, serde_json::Value::String(input)) {
+                                Ok(call) => {
+                                    let mut chunk = ChatChunk::text("");
+                                    chunk.tool_calls.push(call);
+                                    yield Ok(chunk);
+                                }
+                                Err(e) => {
+                                    yield Err(e);
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Ok(AnthropicStreamEvent::MessageDelta { delta, usage: delta_usage }) => {
+                        if let Some(reason) = delta.stop_reason {
+                            finish = Some(finish_reason(Some(&reason)));
+                        }
+                        usage.merge(delta_usage);
+                    }
+                    Ok(AnthropicStreamEvent::MessageStop) => break,
+                    Ok(AnthropicStreamEvent::Error { error }) => {
+                        yield Err(anyhow!("Anthropic API stream error ({}): {}", error.kind, error.message));
+                        return;
+                    }
+                    Ok(AnthropicStreamEvent::Other) => {}
+                    Err(e) => {
+                        yield Err(anyhow!("Failed to parse stream event: {}", e));
+                        return;
+                    }
+                }
+            }
+
+            yield Ok(ChatChunk::finish(finish.unwrap_or(FinishReason::Stop), usage.into_stats()));
+        };
+
+        Ok(StreamingResponse {
+            stream: Box::pin(stream),
+            metadata: ResponseMetadata {
+                model: model.to_string(),
+                provider: self.provider_type().to_string(),
+                request_id,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    async fn send_message(
+        &self,
+        message: &str,
+        model: Option<&str>,
+        _session_id: Option<&str>,  // The Messages API is stateless
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> Result<ProviderResponse> {
+        let model_to_use = model.unwrap_or(&self.config.model);
+        info!("Sending message to Anthropic API (model: {})", model_to_use);
+        if stream {
+            warn!("Streaming requested but send_message doesn't support it, use send_message_stream");
+        }
+
+        let request = simple_params_to_chat_request(message, system_prompt, None, None);
+        let response = self.chat(&request, model_to_use).await?;
+
+        info!("Received response from Anthropic API: {} characters", response.content.len());
+
+        let mut response = chat_response_to_provider_response(response, None);
+        response.metadata.insert("model".to_string(), serde_json::json!(model_to_use));
+        Ok(response)
+    }
+
+    async fn send_message_stream(
+        &self,
+        message: &str,
+        model: Option<&str>,
+        _session_id: Option<&str>,  // The Messages API is stateless
+        system_prompt: Option<&str>,
+    ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+        let model_to_use = model.unwrap_or(&self.config.model);
+        info!("Sending message to Anthropic API with streaming (model: {})", model_to_use);
+
+        let request = simple_params_to_chat_request(message, system_prompt, None, None);
+        let response = self.chat_stream(&request, model_to_use).await?;
+        let provider_type = self.provider_type().to_string();
+
+        Ok(Box::new(response.stream.map(move |chunk| {
+            chunk.map(|chunk| chat_chunk_to_stream_chunk(chunk, &provider_type))
+        })))
+    }
+
+    async fn send_chat_request(&self, request: ChatRequest, _session_id: Option<&str>) -> Result<ChatResponse> {
+        self.chat(&request, &self.config.model).await
+    }
+
+    async fn send_chat_request_stream(
+        &self,
+        request: ChatRequest,
+        _session_id: Option<&str>,
+    ) -> Result<StreamingResponse> {
+        self.chat_stream(&request, &self.config.model).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        debug!("Performing health check on Anthropic API");
+
+        // Listing models is free and checks both reachability and the API key
+        match self.request(reqwest::Method::GET, "/v1/models").send().await {
+            Ok(response) if response.status().is_success() => {
+                info!("Anthropic API health check passed");
+                Ok(true)
+            }
+            Ok(response) => {
+                warn!("Anthropic API health check failed: HTTP {}", response.status());
+                Ok(false)
+            }
+            Err(e) => {
+                warn!("Anthropic API health check failed: {}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    fn provider_type(&self) -> &str {
+        "anthropic"
+    }
+
+    fn display_name(&self) -> &str {
+        "Anthropic API"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        Some(&self.config.model)
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = self
+            .request(reqwest::Method::GET, "/v1/models?limit=1000")
+            .send()
+            .await
+            .context("Failed to list models")?;
+        let models: AnthropicModelList = check_status(response, "Anthropic API")
+            .await?
+            .json()
+            .await
+            .context("Failed to parse model list")?;
+        Ok(models
+            .data
+            .into_iter()
+            .map(|model| {
+                let info = ModelInfo::new(model.id).with_context_length(200_000);
+                match model.display_name {
+                    Some(name) => info.with_display_name(name),
+                    None => info,
+                }
+            })
+            .collect())
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supports_streaming: true,
+            supports_tools: true,
+            supports_system_prompt: true,
+            supports_vision: true,
+            max_tokens: self.config.max_tokens as u32,
+            max_context_length: 200_000,
+        }
+    }
+}
+
+fn finish_reason(reason: Option<&str>) -> FinishReason {
+    match reason {
+        Some("max_tokens") => FinishReason::Length,
+        Some("tool_use") => FinishReason::ToolCalls,
+        Some("refusal") => FinishReason::ContentFilter,
+        _ => FinishReason::Stop,
+    }
+}
+
+// Messages API request/response types
+
+#[derive(Debug, Clone, Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<AnthropicTool>,
+    stream: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AnthropicMessage {
+    role: &'static str,
+    content: Vec<AnthropicContent>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicContent {
+    Text { text: String },
+    ToolUse { id: String, name: String, input: serde_json::Value },
+    ToolResult { tool_use_id: String, content: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AnthropicTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicResponse {
+    content: Vec<AnthropicResponseBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: AnthropicUsage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicResponseBlock {
+    Text {
+        #[serde(default)]
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: serde_json::Value,
+    },
+    /// thinking blocks and anything added to the API later
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: Option<u32>,
+    #[serde(default)]
+    output_tokens: Option<u32>,
+}
+
+impl AnthropicUsage {
+    /// Streaming reports input tokens at the start and output tokens at the end
+    fn merge(&mut self, other: AnthropicUsage) {
+        self.input_tokens = other.input_tokens.or(self.input_tokens);
+        self.output_tokens = other.output_tokens.or(self.output_tokens);
+    }
+
+    fn into_stats(self) -> UsageStats {
+        let prompt_tokens = self.input_tokens.unwrap_or(0);
+        let completion_tokens = self.output_tokens.unwrap_or(0);
+        UsageStats {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicStreamEvent {
+    MessageStart {
+        message: AnthropicStreamMessage,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: AnthropicResponseBlock,
+    },
+    ContentBlockDelta {
+        #[serde(default)]
+        index: usize,
+        delta: AnthropicDelta,
+    },
+    ContentBlockStop {
+        index: usize,
+    },
+    MessageDelta {
+        delta: AnthropicMessageDelta,
+        #[serde(default)]
+        usage: AnthropicUsage,
+    },
+    MessageStop,
+    Error {
+        error: AnthropicError,
+    },
+    /// ping and anything added to the API later
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicStreamMessage {
+    #[serde(default)]
+    usage: AnthropicUsage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicDelta {
+    #[serde(default)]
+    text: Option<String>,
+    /// Fragment of a tool_use block's input JSON
+    #[serde(default)]
+    partial_json: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicMessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicModelList {
+    #[serde(default)]
+    data: Vec<AnthropicModel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicModel {
+    id: String,
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnthropicError {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::types::ChatMessage;
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use futures::TryStreamExt;
+    use serde_json::{json, Value};
+
+    async fn messages(headers: HeaderMap, Json(body): Json<Value>) -> axum::response::Response {
+        use axum::response::IntoResponse;
+
+        if headers.get("x-api-key").and_then(|v| v.to_str().ok()) != Some("sk-ant-test") {
+            let error = json!({"type": "error", "error": {"type": "authentication_error", "message": "invalid x-api-key"}});
+            return (axum::http::StatusCode::UNAUTHORIZED, Json(error)).into_response();
+        }
+
+        let has_tools = body["tools"].as_array().is_some_and(|tools| !tools.is_empty());
+        if has_tools && body["stream"] == json!(true) {
+            let events = [
+                json!({"type": "message_start", "message": {"usage": {"input_tokens": 20}}}),
+                json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+                json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Checking."}}),
+                json!({"type": "content_block_stop", "index": 0}),
+                json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {}}}),
+                json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"city\": \"Os"}}),
+                json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "lo\"}"}}),
+                json!({"type": "content_block_stop", "index": 1}),
+                json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 15}}),
+                json!({"type": "message_stop"}),
+            ];
+            let sse: String = events.iter().map(|data| format!("event: {}\ndata: {}\n\n", data["type"].as_str().unwrap(), data)).collect();
+            ([("content-type", "text/event-stream")], sse).into_response()
+        } else if has_tools {
+            // Echo the encoded conversation so the test can check the content blocks
+            Json(json!({
+                "content": [
+                    {"type": "text", "text": body["messages"].to_string()},
+                    {"type": "tool_use", "id": "toolu_2", "name": body["tools"][0]["name"], "input": {"city": "Bergen"}}
+                ],
+                "stop_reason": "tool_use",
+                "usage": {"input_tokens": 30, "output_tokens": 10}
+            }))
+            .into_response()
+        } else if body["stream"] == json!(true) {
+            let events = [
+                ("message_start", json!({"type": "message_start", "message": {"usage": {"input_tokens": 12, "output_tokens": 1}}})),
+                ("content_block_start", json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}})),
+                ("ping", json!({"type": "ping"})),
+                ("content_block_delta", json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hi "}})),
+                ("content_block_delta", json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "there"}})),
+                ("content_block_stop", json!({"type": "content_block_stop", "index": 0})),
+                ("message_delta", json!({"type": "message_delta", "delta": {"stop_reason": "max_tokens"}, "usage": {"output_tokens": 6}})),
+                ("message_stop", json!({"type": "message_stop"})),
+            ];
+            let sse: String = events
+                .iter()
+                .map(|(name, data)| format!("event: {}\ndata: {}\n\n", name, data))
+                .collect();
+            ([("content-type", "text/event-stream")], sse).into_response()
+        } else {
+            // Echo the folded system prompt and the roles that were sent
+            let roles: Vec<&str> = body["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+            Json(json!({
+                "content": [{"type": "text", "text": format!("{}|{}", body["system"].as_str().unwrap_or(""), roles.join(","))}],
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 9, "output_tokens": 3}
+            }))
+            .into_response()
+        }
+    }
+
+    async fn spawn_provider(api_key: &str) -> AnthropicProvider {
+        let app = Router::new().route("/v1/messages", post(messages));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = AnthropicConfig {
+            base_url: format!("http://{}", addr),
+            ..AnthropicConfig::default()
+        };
+        AnthropicProvider::new(config, api_key.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_messages_api_chat_stream_and_errors() {
+        let provider = spawn_provider("sk-ant-test").await;
+
+        let request = ChatRequest {
+            messages: vec![ChatMessage::system("Be terse."), ChatMessage::user("hi"), ChatMessage::assistant("hello"), ChatMessage::user("again")],
+            ..simple_params_to_chat_request("", Some("You are Vespera."), None, None)
+        };
+        let response = provider.send_chat_request(request, None).await.unwrap();
+        assert_eq!(response.content, "You are Vespera.\n\nBe terse.|user,assistant,user");
+        assert_eq!(response.finish_reason, FinishReason::Stop);
+        assert_eq!(response.usage.total_tokens, 12);
+
+        let chunks: Vec<StreamChunk> = provider
+            .send_message_stream("hi", None, None, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let text: String = chunks.iter().filter_map(|c| c.text.as_deref()).collect();
+        assert_eq!(text, "Hi there");
+        let last = chunks.last().unwrap();
+        assert!(last.is_final);
+        let metadata = last.metadata.as_ref().unwrap();
+        assert_eq!(metadata["finish_reason"], "length");
+        assert_eq!(metadata["usage"]["prompt_tokens"], 12);
+        assert_eq!(metadata["usage"]["completion_tokens"], 6);
+
+        let err = spawn_provider("wrong").await.send_message("hi", None, None, None, false).await.unwrap_err();
+        assert!(err.to_string().contains("invalid x-api-key"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_native_tool_use() {
+        let provider = spawn_provider("sk-ant-test").await;
+        let mut request = simple_params_to_chat_request("Weather in Oslo?", None, None, None);
+        request.tools = vec![crate::providers::types::ToolDefinition {
+            name: "get_weather".to_string(),
+            description: "Current weather for a city".to_string(),
+            parameters: json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        }];
+
+        // Streaming: the tool call is surfaced once its input JSON is complete
+        let chunks: Vec<ChatChunk> = provider
+            .send_chat_request_stream(request.clone(), None)
+            .await
+            .unwrap()
+            .stream
+            .try_collect()
+            .await
+            .unwrap();
+        let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+        assert_eq!(text, "Checking.");
+        let calls: Vec<ToolCall> = chunks.iter().flat_map(|c| c.tool_calls.clone()).collect();
+        assert_eq!(calls.len(), 1);
+        assert_eq!((calls[0].id.as_str(), calls[0].name.as_str()), ("toolu_1", "get_weather"));
+        assert_eq!(calls[0].arguments["city"], "Oslo");
+        assert_eq!(chunks.last().unwrap().finish_reason, Some(FinishReason::ToolCalls));
+
+        // Two results for two parallel calls go back in a single user turn
+        let second = ToolCall { id: "toolu_x".to_string(), ..calls[0].clone() };
+        request.messages.push(ChatMessage::assistant_tool_calls("Checking.", vec![calls[0].clone(), second.clone()]));
+        request.messages.push(ChatMessage::tool_result(&calls[0], "12C"));
+        request.messages.push(ChatMessage::tool_result(&second, "13C"));
+        let response = provider.send_chat_request(request, None).await.unwrap();
+        let sent: Value = serde_json::from_str(&response.content).unwrap();
+        assert_eq!(sent.as_array().unwrap().len(), 3);
+        assert_eq!(sent[1]["content"][1], json!({"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Oslo"}}));
+        assert_eq!(sent[2]["role"], "user");
+        assert_eq!(sent[2]["content"][1], json!({"type": "tool_result", "tool_use_id": "toolu_x", "content": "13C"}));
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+        assert_eq!(response.tool_calls[0].arguments["city"], "Bergen");
+    }
+}
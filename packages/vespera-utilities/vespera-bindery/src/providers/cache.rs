@@ -0,0 +1,491 @@
+// Provider Response Cache
+//
+// Optional cache for non-streaming chat requests, so agent loops that keep
+// asking the same thing don't pay for the same answer twice. Entries are keyed
+// by provider, model and a hash of the normalized request: message text is
+// trimmed with line endings unified, and tool call IDs (fresh on every run)
+// are left out. Entries expire after a TTL; when full, expired entries go
+// first, then the least recently used.
+//
+// Semantic mode additionally embeds each prompt and, on an exact miss, reuses
+// the answer to an earlier prompt whose embedding is at least
+// `similarity_threshold` similar. Only prompts with the same provider, model,
+// system prompt, tools and generation parameters are compared.
+
+use super::types::{ChatMessage, ChatRequest, ChatResponse};
+use crate::rag::EmbeddingService;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Response cache settings for [`ProviderManager`](super::ProviderManager)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    /// Serve repeated chat requests from the cache
+    #[serde(default)]
+    pub enabled: bool,
+    /// How long a cached response stays valid
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+    /// Maximum number of cached responses
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    /// Also reuse answers to embedding-similar prompts (needs an embedder)
+    #[serde(default)]
+    pub semantic: bool,
+    /// Cosine similarity a prompt needs to reuse another prompt's answer
+    #[serde(default = "default_similarity_threshold")]
+    pub similarity_threshold: f32,
+}
+
+fn default_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_max_entries() -> usize {
+    1000
+}
+
+fn default_similarity_threshold() -> f32 {
+    0.95
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_ttl_secs(),
+            max_entries: default_max_entries(),
+            semantic: false,
+            similarity_threshold: default_similarity_threshold(),
+        }
+    }
+}
+
+/// Cache effectiveness counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseCacheStats {
+    pub entries: usize,
+    pub capacity: usize,
+    /// Exact hits
+    pub hits: u64,
+    /// Hits on an embedding-similar prompt
+    pub semantic_hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// `(hits + semantic_hits) / lookups`, or 0 before the first lookup
+    pub hit_rate: f64,
+}
+
+/// Embeds prompts for semantic cache lookups
+#[async_trait]
+pub trait PromptEmbedder: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+#[async_trait]
+impl PromptEmbedder for EmbeddingService {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("No embedding generated"))
+    }
+}
+
+/// Cache key of one request
+#[derive(Debug, Clone)]
+pub(crate) struct CacheKey {
+    provider_id: String,
+    /// Provider, model and everything in the request except the messages
+    scope: String,
+    /// `scope` plus the normalized messages
+    exact: String,
+    /// Normalized conversation, embedded in semantic mode
+    prompt: String,
+}
+
+impl CacheKey {
+    pub(crate) fn new(provider_id: &str, model: Option<&str>, request: &ChatRequest) -> Self {
+        let messages: Vec<ChatMessage> = request.messages.iter().map(normalize_message).collect();
+        let params = serde_json::json!({
+            "system_prompt": request.system_prompt.as_deref().map(normalize_text),
+            "tools": request.tools,
+            "max_tokens": request.max_tokens,
+            "temperature": request.temperature,
+            "stop_sequences": request.stop_sequences,
+        });
+
+        let scope = hash(&[provider_id, model.unwrap_or(""), &params.to_string()]);
+        let exact = hash(&[&scope, &serde_json::to_string(&messages).unwrap_or_default()]);
+        let prompt = messages
+            .iter()
+            .map(|m| format!("{:?}: {}", m.role, m.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Self {
+            provider_id: provider_id.to_string(),
+            scope,
+            exact,
+            prompt,
+        }
+    }
+}
+
+fn normalize_text(text: &str) -> String {
+    text.replace("\r\n", "\n").trim().to_string()
+}
+
+fn normalize_message(message: &ChatMessage) -> ChatMessage {
+    let mut message = message.clone();
+    message.content = normalize_text(&message.content);
+    message.tool_call_id = None;
+    for call in &mut message.tool_calls {
+        call.id.clear();
+    }
+    message
+}
+
+fn hash(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+struct CacheEntry {
+    provider_id: String,
+    scope: String,
+    embedding: Option<Vec<f32>>,
+    response: ChatResponse,
+    inserted: Instant,
+    /// Logical clock value of the last hit or insert
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    clock: u64,
+}
+
+/// In-memory TTL + LRU cache of chat responses
+pub struct ResponseCache {
+    config: ResponseCacheConfig,
+    embedder: Option<Arc<dyn PromptEmbedder>>,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    semantic_hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new(config: ResponseCacheConfig) -> Self {
+        Self {
+            config,
+            embedder: None,
+            state: Mutex::new(CacheState::default()),
+            hits: AtomicU64::new(0),
+            semantic_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Embedder for semantic lookups; without one only exact matches are served
+    pub fn with_embedder(mut self, embedder: Arc<dyn PromptEmbedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    pub fn config(&self) -> &ResponseCacheConfig {
+        &self.config
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.config.ttl_secs)
+    }
+
+    fn semantic_embedder(&self) -> Option<&Arc<dyn PromptEmbedder>> {
+        self.embedder.as_ref().filter(|_| self.config.semantic)
+    }
+
+    /// Embedding of the key's prompt, or None outside semantic mode or when embedding fails
+    async fn embed(&self, key: &CacheKey) -> Option<Vec<f32>> {
+        let embedder = self.semantic_embedder()?;
+        match embedder.embed(&key.prompt).await {
+            Ok(vector) => Some(vector),
+            Err(e) => {
+                warn!("Response cache: failed to embed prompt, using exact matching only: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Cached response for `key`
+    ///
+    /// Returns the response and, in semantic mode, the prompt's embedding so
+    /// a miss can be inserted without embedding the prompt again.
+    pub(crate) async fn get(&self, key: &CacheKey) -> (Option<ChatResponse>, Option<Vec<f32>>) {
+        if !self.config.enabled {
+            return (None, None);
+        }
+
+        if let Some(response) = self.lookup(|entries| entries.contains_key(&key.exact).then(|| key.exact.clone())) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return (Some(response), None);
+        }
+
+        let embedding = self.embed(key).await;
+        if let Some(query) = &embedding {
+            let threshold = self.config.similarity_threshold;
+            let similar = self.lookup(|entries| {
+                entries
+                    .iter()
+                    .filter(|(_, entry)| entry.scope == key.scope)
+                    .filter_map(|(id, entry)| Some((id, cosine_similarity(query, entry.embedding.as_ref()?))))
+                    .filter(|(_, similarity)| *similarity >= threshold)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(id, _)| id.clone())
+            });
+            if let Some(response) = similar {
+                self.semantic_hits.fetch_add(1, Ordering::Relaxed);
+                return (Some(response), embedding);
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        (None, embedding)
+    }
+
+    /// Drop expired entries, then return the response of the entry `find` picks
+    fn lookup(&self, find: impl FnOnce(&HashMap<String, CacheEntry>) -> Option<String>) -> Option<ChatResponse> {
+        let ttl = self.ttl();
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|_, entry| entry.inserted.elapsed() < ttl);
+
+        let id = find(&state.entries)?;
+        state.clock += 1;
+        let clock = state.clock;
+        let entry = state.entries.get_mut(&id)?;
+        entry.last_used = clock;
+        Some(entry.response.clone())
+    }
+
+    /// Cache `response` for `key`; `embedding` is what [`get`](Self::get) returned
+    pub(crate) fn insert(&self, key: &CacheKey, embedding: Option<Vec<f32>>, response: ChatResponse) {
+        if !self.config.enabled || self.config.max_entries == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let entry = CacheEntry {
+            provider_id: key.provider_id.clone(),
+            scope: key.scope.clone(),
+            embedding,
+            response,
+            inserted: Instant::now(),
+            last_used: state.clock,
+        };
+        state.entries.insert(key.exact.clone(), entry);
+        self.evict_over_capacity(&mut state);
+    }
+
+    fn evict_over_capacity(&self, state: &mut CacheState) {
+        if state.entries.len() <= self.config.max_entries {
+            return;
+        }
+
+        let ttl = self.ttl();
+        let before = state.entries.len();
+        state.entries.retain(|_, entry| entry.inserted.elapsed() < ttl);
+
+        let excess = state.entries.len().saturating_sub(self.config.max_entries);
+        if excess > 0 {
+            let mut by_age: Vec<(u64, String)> =
+                state.entries.iter().map(|(id, entry)| (entry.last_used, id.clone())).collect();
+            by_age.sort_unstable();
+            for (_, id) in by_age.into_iter().take(excess) {
+                state.entries.remove(&id);
+            }
+        }
+        self.evictions.fetch_add((before - state.entries.len()) as u64, Ordering::Relaxed);
+    }
+
+    /// Drop every cached response from `provider_id`
+    pub fn invalidate_provider(&self, provider_id: &str) {
+        self.state.lock().unwrap().entries.retain(|_, entry| entry.provider_id != provider_id);
+    }
+
+    /// Drop all cached responses
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    pub fn stats(&self) -> ResponseCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let semantic_hits = self.semantic_hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + semantic_hits + misses;
+        ResponseCacheStats {
+            entries: self.state.lock().unwrap().entries.len(),
+            capacity: self.config.max_entries,
+            hits,
+            semantic_hits,
+            misses,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                (hits + semantic_hits) as f64 / lookups as f64
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::types::{FinishReason, UsageStats};
+
+    fn request(text: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![ChatMessage::user(text)],
+            system_prompt: None,
+            tools: Vec::new(),
+            max_tokens: None,
+            temperature: None,
+            stop_sequences: None,
+            prompt: None,
+        }
+    }
+
+    fn response(text: &str) -> ChatResponse {
+        ChatResponse {
+            content: text.to_string(),
+            finish_reason: FinishReason::Stop,
+            tool_calls: Vec::new(),
+            usage: UsageStats {
+                prompt_tokens: 1,
+                completion_tokens: 1,
+                total_tokens: 2,
+            },
+        }
+    }
+
+    /// Embeds text as letter counts, so anagrams are identical
+    struct LetterEmbedder;
+
+    #[async_trait]
+    impl PromptEmbedder for LetterEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let mut counts = vec![0.0; 26];
+            for c in text.to_ascii_lowercase().bytes().filter(u8::is_ascii_lowercase) {
+                counts[(c - b'a') as usize] += 1.0;
+            }
+            Ok(counts)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exact_keying_and_lru_eviction() {
+        let cache = ResponseCache::new(ResponseCacheConfig {
+            enabled: true,
+            max_entries: 2,
+            ..ResponseCacheConfig::default()
+        });
+
+        let key = |provider: &str, model: Option<&str>, text: &str| CacheKey::new(provider, model, &request(text));
+        cache.insert(&key("p1", Some("m"), "hello"), None, response("hi"));
+
+        // Whitespace and line endings are normalized away; provider and model are not
+        let (hit, _) = cache.get(&key("p1", Some("m"), "  hello\r\n")).await;
+        assert_eq!(hit.unwrap().content, "hi");
+        assert!(cache.get(&key("p1", Some("other"), "hello")).await.0.is_none());
+        assert!(cache.get(&key("p2", Some("m"), "hello")).await.0.is_none());
+
+        // Parameters are part of the key
+        let mut hot = request("hello");
+        hot.temperature = Some(1.5);
+        assert!(cache.get(&CacheKey::new("p1", Some("m"), &hot)).await.0.is_none());
+
+        // "hello" was used most recently, so "a" is evicted to make room for "b"
+        cache.insert(&key("p1", Some("m"), "a"), None, response("A"));
+        cache.get(&key("p1", Some("m"), "hello")).await;
+        cache.insert(&key("p1", Some("m"), "b"), None, response("B"));
+        assert!(cache.get(&key("p1", Some("m"), "a")).await.0.is_none());
+        assert!(cache.get(&key("p1", Some("m"), "hello")).await.0.is_some());
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.evictions), (2, 3, 1));
+
+        cache.invalidate_provider("p1");
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expiry() {
+        let cache = ResponseCache::new(ResponseCacheConfig {
+            enabled: true,
+            ttl_secs: 0,
+            ..ResponseCacheConfig::default()
+        });
+        let key = CacheKey::new("p1", None, &request("hello"));
+        cache.insert(&key, None, response("hi"));
+        assert!(cache.get(&key).await.0.is_none());
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_semantic_hits() {
+        let cache = ResponseCache::new(ResponseCacheConfig {
+            enabled: true,
+            semantic: true,
+            similarity_threshold: 0.99,
+            ..ResponseCacheConfig::default()
+        })
+        .with_embedder(Arc::new(LetterEmbedder));
+
+        let key = CacheKey::new("p1", None, &request("listen"));
+        let (hit, embedding) = cache.get(&key).await;
+        assert!(hit.is_none() && embedding.is_some());
+        cache.insert(&key, embedding, response("cached"));
+
+        // An anagram embeds identically; an unrelated prompt doesn't
+        let (hit, _) = cache.get(&CacheKey::new("p1", None, &request("silent"))).await;
+        assert_eq!(hit.unwrap().content, "cached");
+        assert!(cache.get(&CacheKey::new("p1", None, &request("zebra"))).await.0.is_none());
+
+        // Similar prompts under a different system prompt are not reused
+        let mut other_scope = request("silent");
+        other_scope.system_prompt = Some("Be terse".to_string());
+        assert!(cache.get(&CacheKey::new("p1", None, &other_scope)).await.0.is_none());
+
+        assert_eq!(cache.stats().semantic_hits, 1);
+    }
+}
@@ -9,7 +9,15 @@
 // - {"type":"system","subtype":"init"} - Session initialization
 // - {"type":"assistant","message":{...}} - Response content
 // - {"type":"result","subtype":"success"} - Final result with metrics
+//
+// The CLI's tool_use blocks are its own built-in tools, which it runs itself.
+// Tools supplied in a ChatRequest are emulated through the prompt instead
+// (see `tool_emulation`).
+//
+// Cancelled or timed out streams send the CLI SIGINT so it can stop cleanly,
+// and kill it if it hasn't exited shortly after.
 
+use super::control::{self, Interrupted, RequestControl};
 use super::{Provider, ProviderResponse, ProviderUsage, StreamChunk};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -18,12 +26,29 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdout, Command};
 use tracing::{debug, error, info, warn};
 
 // Import tool types for tool calling support
-use crate::providers::types::ToolCall;
+use super::tool_emulation::{extract_tool_calls, render_prompt, system_prompt_with_tools, ToolCallStreamFilter};
+use crate::providers::types::{
+    ChatChunk, ChatRequest, ChatResponse, ChatRole, FinishReason, ModelInfo, ResponseMetadata, StreamingResponse,
+    ToolCall, UsageStats,
+};
+
+/// Models the CLI accepts for `--model`; it has no way to list them
+const CLI_MODELS: &[(&str, &str)] = &[
+    ("claude-sonnet-4-5", "Claude Sonnet 4.5"),
+    ("claude-opus-4-1", "Claude Opus 4.1"),
+    ("claude-haiku-4-5", "Claude Haiku 4.5"),
+    ("claude-sonnet-4", "Claude Sonnet 4"),
+    ("claude-opus-4", "Claude Opus 4"),
+];
+
+/// How long an interrupted CLI gets to exit before it is killed
+const INTERRUPT_GRACE: Duration = Duration::from_secs(2);
 
 /// Claude Code CLI provider configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,10 +119,11 @@ impl ClaudeCodeProvider {
             cmd.arg("--system-prompt").arg(prompt);
         }
 
-        // Configure stdio
+        // Configure stdio; abandoned requests shouldn't leave the CLI running
         cmd.stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
 
         debug!(
             "Spawning Claude Code CLI: {} code --print --output-format stream-json --verbose",
@@ -108,7 +134,7 @@ impl ClaudeCodeProvider {
 
         // Capture and log stderr for debugging
         if let Some(stderr) = child.stderr.take() {
-            tokio::spawn(async move {
+            crate::observability::spawn_traced(async move {
                 let mut reader = BufReader::new(stderr).lines();
                 while let Ok(Some(line)) = reader.next_line().await {
                     eprintln!("Debug: Claude CLI stderr: {}", line);
@@ -120,7 +146,7 @@ impl ClaudeCodeProvider {
         // Write message to stdin
         if let Some(mut stdin) = child.stdin.take() {
             let message = message.to_string();
-            tokio::spawn(async move {
+            crate::observability::spawn_traced(async move {
                 if let Err(e) = stdin.write_all(message.as_bytes()).await {
                     error!("Failed to write message to stdin: {}", e);
                 }
@@ -133,6 +159,34 @@ impl ClaudeCodeProvider {
         Ok(child)
     }
 
+    /// Prompt and system prompt for a structured chat request
+    ///
+    /// System messages join the system prompt, and any tools are described in
+    /// it so the model can call them by prompt convention.
+    fn chat_turn(&self, request: &ChatRequest, resuming: bool) -> (String, Option<String>) {
+        let system: Vec<&str> = request
+            .system_prompt
+            .as_deref()
+            .or(self.config.system_prompt.as_deref())
+            .into_iter()
+            .chain(
+                request
+                    .messages
+                    .iter()
+                    .filter(|m| m.role == ChatRole::System)
+                    .map(|m| m.content.as_str()),
+            )
+            .collect();
+        let system = (!system.is_empty()).then(|| system.join("\n\n"));
+
+        let system = if request.tools.is_empty() {
+            system
+        } else {
+            Some(system_prompt_with_tools(system.as_deref(), &request.tools))
+        };
+        (render_prompt(&request.messages, resuming), system)
+    }
+
     /// Parse stream-json event from Claude Code CLI output
     fn parse_event(line: &str) -> Result<Option<ClaudeCodeEvent>> {
         // Skip the tip message
@@ -305,9 +359,22 @@ impl Provider for ClaudeCodeProvider {
         model: Option<&str>,
         session_id: Option<&str>,
         system_prompt: Option<&str>,
+    ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+        self.send_message_stream_with_control(message, model, session_id, system_prompt, RequestControl::default())
+            .await
+    }
+
+    async fn send_message_stream_with_control(
+        &self,
+        message: &str,
+        model: Option<&str>,
+        session_id: Option<&str>,
+        system_prompt: Option<&str>,
+        control: RequestControl,
     ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
         info!("Sending message to Claude Code CLI (streaming)");
 
+        let deadline = control.start();
         let mut child = self.spawn_process(message, model, session_id, system_prompt)?;
 
         let stdout = child
@@ -319,7 +386,18 @@ impl Provider for ClaudeCodeProvider {
 
         let stream = async_stream::stream! {
             loop {
-                match lines.next_line().await {
+                let line = match control::run(&control, deadline, lines.next_line()).await {
+                    Ok(line) => line,
+                    Err(interrupted) => {
+                        interrupt(&mut child).await;
+                        match interrupted {
+                            Interrupted::Cancelled => yield Ok(control::cancelled_stream_chunk()),
+                            Interrupted::TimedOut => yield Err(deadline.error()),
+                        }
+                        return;
+                    }
+                };
+                match line {
                     Ok(Some(line)) => {
                         match Self::parse_event(&line) {
                             Ok(Some(event)) => {
@@ -343,12 +421,120 @@ impl Provider for ClaudeCodeProvider {
                     }
                 }
             }
+
+            // Surface crashes so the provider manager can supervise the CLI
+            match child.wait().await {
+                Ok(status) if !status.success() => {
+                    yield Err(anyhow!("Claude Code CLI exited with error: {}", status));
+                }
+                Ok(_) => {}
+                Err(e) => yield Err(e.into()),
+            }
         }
         .boxed();
 
         Ok(Box::new(stream))
     }
 
+    async fn send_chat_request(&self, request: ChatRequest, session_id: Option<&str>) -> Result<ChatResponse> {
+        let (prompt, system_prompt) = self.chat_turn(&request, session_id.is_some());
+        let response = self.send_message(&prompt, None, session_id, system_prompt.as_deref(), false).await?;
+
+        let (content, tool_calls) = if request.tools.is_empty() {
+            (response.text, Vec::new())
+        } else {
+            extract_tool_calls(&response.text)
+        };
+        let usage = response.usage.as_ref().map_or_else(
+            || cli_usage(0, 0),
+            |u| cli_usage(u.input_tokens as u64, u.output_tokens as u64),
+        );
+
+        Ok(ChatResponse {
+            content,
+            finish_reason: if tool_calls.is_empty() { FinishReason::Stop } else { FinishReason::ToolCalls },
+            tool_calls,
+            usage,
+        })
+    }
+
+    async fn send_chat_request_stream(
+        &self,
+        request: ChatRequest,
+        session_id: Option<&str>,
+    ) -> Result<StreamingResponse> {
+        self.send_chat_request_stream_with_control(request, session_id, RequestControl::default())
+            .await
+    }
+
+    async fn send_chat_request_stream_with_control(
+        &self,
+        request: ChatRequest,
+        session_id: Option<&str>,
+        control: RequestControl,
+    ) -> Result<StreamingResponse> {
+        let (prompt, system_prompt) = self.chat_turn(&request, session_id.is_some());
+        let mut chunks = self
+            .send_message_stream_with_control(&prompt, None, session_id, system_prompt.as_deref(), control)
+            .await?;
+        let emulate_tools = !request.tools.is_empty();
+
+        let stream = async_stream::stream! {
+            let mut filter = ToolCallStreamFilter::default();
+            let mut usage = cli_usage(0, 0);
+            while let Some(chunk) = chunks.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                match chunk.chunk_type.as_str() {
+                    "assistant" => {
+                        let text = chunk.text.unwrap_or_default();
+                        let text = if emulate_tools { filter.push(&text) } else { text };
+                        if !text.is_empty() {
+                            yield Ok(ChatChunk::text(text));
+                        }
+                    }
+                    // The result repeats the assistant text; only its usage is new
+                    "result" if is_cancelled(&chunk) => {
+                        yield Ok(control::cancelled_chat_chunk());
+                        return;
+                    }
+                    "result" => {
+                        if let Some(result_usage) = chunk.metadata.as_ref().and_then(|m| m.get("usage")) {
+                            usage = cli_usage(
+                                result_usage["input_tokens"].as_u64().unwrap_or(0),
+                                result_usage["output_tokens"].as_u64().unwrap_or(0),
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let (rest, tool_calls) = filter.finish();
+            if !rest.is_empty() {
+                yield Ok(ChatChunk::text(rest));
+            }
+            let reason = if tool_calls.is_empty() { FinishReason::Stop } else { FinishReason::ToolCalls };
+            let mut last = ChatChunk::finish(reason, usage);
+            last.tool_calls = tool_calls;
+            yield Ok(last);
+        };
+
+        Ok(StreamingResponse {
+            stream: Box::pin(stream),
+            metadata: ResponseMetadata {
+                model: self.config.model.clone().unwrap_or_else(|| "default".to_string()),
+                provider: self.provider_type().to_string(),
+                request_id: None,
+            },
+        })
+    }
+
     async fn health_check(&self) -> Result<bool> {
         debug!("Performing health check on Claude Code CLI");
 
@@ -374,11 +560,30 @@ impl Provider for ClaudeCodeProvider {
         "Claude Code CLI"
     }
 
+    fn default_model(&self) -> Option<&str> {
+        self.config.model.as_deref()
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let mut models: Vec<ModelInfo> = CLI_MODELS
+            .iter()
+            .map(|(id, name)| ModelInfo::new(*id).with_display_name(*name).with_context_length(200_000))
+            .collect();
+        // A configured model the list doesn't know about (a dated snapshot, an alias) comes first
+        if let Some(configured) = self.config.model.as_deref() {
+            if !models.iter().any(|m| m.id == configured) {
+                models.insert(0, ModelInfo::new(configured).with_context_length(200_000));
+            }
+        }
+        Ok(models)
+    }
+
     fn capabilities(&self) -> crate::providers::types::ProviderCapabilities {
         crate::providers::types::ProviderCapabilities {
             supports_streaming: true,  // Claude Code CLI supports stream-json format
-            supports_tools: true,       // Tool calling fully implemented
+            supports_tools: true,       // Emulated via the prompt (see tool_emulation)
             supports_system_prompt: true,
+            supports_vision: false,     // Print mode takes a text prompt
             max_tokens: self.config.max_tokens.unwrap_or(4096) as u32,
             max_context_length: 200000, // Claude Sonnet 4.5 context window
         }
@@ -457,16 +662,55 @@ impl ClaudeCodeProvider {
                     metadata: Some(serde_json::to_value(metadata)?),
                 })
             }
-            ClaudeCodeEvent::Result { result, .. } => Ok(StreamChunk {
+            ClaudeCodeEvent::Result { result, usage, total_cost_usd, .. } => Ok(StreamChunk {
                 chunk_type: "result".to_string(),
                 text: Some(result),
                 is_final: true,
-                metadata: None,
+                metadata: Some(serde_json::json!({
+                    "usage": usage,
+                    "cost_usd": total_cost_usd,
+                })),
             }),
         }
     }
 }
 
+/// Ask the CLI to stop with SIGINT, killing it if it doesn't exit within the grace period
+async fn interrupt(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: kill(2) only sends a signal; `pid` is our own child, which
+        // hasn't been reaped yet since `id()` still returns it
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGINT);
+        }
+        if tokio::time::timeout(INTERRUPT_GRACE, child.wait()).await.is_ok() {
+            debug!("Claude Code CLI stopped after SIGINT");
+            return;
+        }
+    }
+    if let Err(e) = child.kill().await {
+        warn!("Failed to kill Claude Code CLI: {}", e);
+    }
+}
+
+/// Whether a stream chunk marks a cancelled request
+fn is_cancelled(chunk: &StreamChunk) -> bool {
+    chunk
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("finish_reason"))
+        .is_some_and(|reason| *reason == serde_json::json!(FinishReason::Cancelled))
+}
+
+fn cli_usage(input_tokens: u64, output_tokens: u64) -> UsageStats {
+    UsageStats {
+        prompt_tokens: input_tokens as u32,
+        completion_tokens: output_tokens as u32,
+        total_tokens: (input_tokens + output_tokens) as u32,
+    }
+}
+
 // Claude Code CLI event types from stream-json output
 
 #[derive(Debug, Clone, Deserialize)]
@@ -521,3 +765,108 @@ struct ResultUsage {
     #[serde(flatten)]
     extra: HashMap<String, Value>,
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::providers::types::{simple_params_to_chat_request, ToolDefinition};
+    use futures::TryStreamExt;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    /// A stand-in CLI that asks for a tool when the tools were described to it
+    fn fake_cli(dir: &TempDir) -> String {
+        let path = dir.path().join("claude");
+        let script = r#"#!/bin/sh
+cat > /dev/null
+case "$*" in
+  *"<tool_call>"*"get_weather"*) TEXT='Checking.\n<tool_call>{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Oslo\"}}</tool_call>' ;;
+  *) TEXT='No tools offered.' ;;
+esac
+echo '{"type":"system","subtype":"init","session_id":"s1"}'
+printf '%s\n' "{\"type\":\"assistant\",\"session_id\":\"s1\",\"message\":{\"content\":[{\"type\":\"text\",\"text\":\"$TEXT\"}]}}"
+printf '%s\n' "{\"type\":\"result\",\"subtype\":\"success\",\"session_id\":\"s1\",\"result\":\"$TEXT\",\"total_cost_usd\":0.01,\"usage\":{\"input_tokens\":10,\"output_tokens\":5}}"
+"#;
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_emulated_through_the_prompt() {
+        let dir = TempDir::new().unwrap();
+        let provider = ClaudeCodeProvider::new(ClaudeCodeConfig {
+            executable_path: fake_cli(&dir),
+            ..ClaudeCodeConfig::default()
+        });
+        let mut request = simple_params_to_chat_request("Weather in Oslo?", None, None, None);
+
+        let response = provider.send_chat_request(request.clone(), None).await.unwrap();
+        assert_eq!(response.content, "No tools offered.");
+        assert!(response.tool_calls.is_empty());
+
+        request.tools = vec![ToolDefinition {
+            name: "get_weather".to_string(),
+            description: "Current weather for a city".to_string(),
+            parameters: serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        }];
+        let response = provider.send_chat_request(request.clone(), None).await.unwrap();
+        assert_eq!(response.content, "Checking.");
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+        assert_eq!(response.tool_calls[0].arguments["city"], "Oslo");
+        assert_eq!(response.usage.total_tokens, 15);
+
+        let chunks: Vec<ChatChunk> = provider
+            .send_chat_request_stream(request, None)
+            .await
+            .unwrap()
+            .stream
+            .try_collect()
+            .await
+            .unwrap();
+        let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+        assert_eq!(text, "Checking.\n");
+        let last = chunks.last().unwrap();
+        assert_eq!(last.tool_calls[0].name, "get_weather");
+        assert_eq!(last.usage.as_ref().unwrap().total_tokens, 15);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_the_cli() {
+        // Answers part way, then hangs until interrupted
+        let dir = TempDir::new().unwrap();
+        let marker = dir.path().join("interrupted");
+        let path = dir.path().join("claude");
+        let script = format!(
+            r#"#!/bin/sh
+trap 'touch "{}"; exit 130' INT
+cat > /dev/null
+echo '{{"type":"assistant","session_id":"s1","message":{{"content":[{{"type":"text","text":"Thinking"}}]}}}}'
+sleep 30 &
+wait
+"#,
+            marker.display()
+        );
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let provider = ClaudeCodeProvider::new(ClaudeCodeConfig {
+            executable_path: path.to_string_lossy().into_owned(),
+            ..ClaudeCodeConfig::default()
+        });
+
+        let control = RequestControl::new();
+        let request = simple_params_to_chat_request("Hello", None, None, None);
+        let mut stream = provider
+            .send_chat_request_stream_with_control(request, None, control.clone())
+            .await
+            .unwrap()
+            .stream;
+        assert_eq!(stream.next().await.unwrap().unwrap().delta, "Thinking");
+
+        control.cancel();
+        let last = stream.next().await.unwrap().unwrap();
+        assert_eq!(last.finish_reason, Some(FinishReason::Cancelled));
+        assert!(stream.next().await.is_none());
+        assert!(marker.exists(), "the CLI should have received SIGINT");
+    }
+}
@@ -0,0 +1,176 @@
+// Request Control
+//
+// Cancellation and timeouts for streaming requests. A `RequestControl` is
+// handed to the provider along with the request; cancelling its token ends
+// the stream with a `Cancelled` finish, and passing its timeout ends it with
+// an error. Either way the provider's stream is dropped, which aborts HTTP
+// requests and kills CLI processes (`kill_on_drop`). Providers with a gentler
+// way to stop (the Claude Code CLI gets SIGINT first) handle it themselves.
+
+use super::types::{ChatChunk, FinishReason, UsageStats};
+use super::StreamChunk;
+use anyhow::{anyhow, Result};
+use futures::{Future, Stream, StreamExt};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Cancellation and timeout for one request
+#[derive(Debug, Clone, Default)]
+pub struct RequestControl {
+    /// Cancel to stop the request
+    pub cancel: CancellationToken,
+    /// Longest the whole request may take, from sending it to the end of the stream
+    pub timeout: Option<Duration>,
+}
+
+impl RequestControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop the request
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Start timing the request
+    pub(crate) fn start(&self) -> Deadline {
+        Deadline {
+            at: self.timeout.map(|t| Instant::now() + t),
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// When a request started with [`RequestControl::start`] times out
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline {
+    at: Option<Instant>,
+    timeout: Option<Duration>,
+}
+
+impl Deadline {
+    /// Resolves once the deadline has passed; never, without a timeout
+    pub(crate) async fn elapsed(self) {
+        match self.at {
+            Some(at) => tokio::time::sleep_until(at).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    pub(crate) fn error(&self) -> anyhow::Error {
+        anyhow!("Request timed out after {:?}", self.timeout.unwrap_or_default())
+    }
+}
+
+/// Why a controlled request stopped early
+pub(crate) enum Interrupted {
+    Cancelled,
+    TimedOut,
+}
+
+/// Run `future` until it completes, the request is cancelled or the deadline passes
+pub(crate) async fn run<F: Future>(control: &RequestControl, deadline: Deadline, future: F) -> Result<F::Output, Interrupted> {
+    tokio::select! {
+        output = future => Ok(output),
+        _ = control.cancel.cancelled() => Err(Interrupted::Cancelled),
+        _ = deadline.elapsed() => Err(Interrupted::TimedOut),
+    }
+}
+
+/// Pass `stream` through until it ends, the request is cancelled or the deadline passes
+///
+/// Cancellation ends the stream with `cancelled()`, a timeout with an error.
+/// The inner stream is dropped as soon as either happens.
+pub(crate) fn guard_stream<T, S>(
+    stream: S,
+    control: RequestControl,
+    deadline: Deadline,
+    cancelled: fn() -> T,
+) -> impl Stream<Item = Result<T>> + Send
+where
+    T: Send + 'static,
+    S: Stream<Item = Result<T>> + Send + Unpin + 'static,
+{
+    async_stream::stream! {
+        let mut stream = stream;
+        loop {
+            match run(&control, deadline, stream.next()).await {
+                Ok(Some(item)) => yield item,
+                Ok(None) => break,
+                Err(Interrupted::Cancelled) => {
+                    drop(stream);
+                    yield Ok(cancelled());
+                    break;
+                }
+                Err(Interrupted::TimedOut) => {
+                    drop(stream);
+                    yield Err(deadline.error());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Final chunk of a cancelled legacy stream
+pub(crate) fn cancelled_stream_chunk() -> StreamChunk {
+    StreamChunk {
+        chunk_type: "result".to_string(),
+        text: None,
+        is_final: true,
+        metadata: Some(serde_json::json!({ "finish_reason": FinishReason::Cancelled })),
+    }
+}
+
+/// Final chunk of a cancelled chat stream
+pub(crate) fn cancelled_chat_chunk() -> ChatChunk {
+    ChatChunk::finish(
+        FinishReason::Cancelled,
+        UsageStats {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow_stream() -> impl Stream<Item = Result<u32>> + Send + Unpin {
+        Box::pin(futures::stream::unfold(0u32, |n| async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Some((Ok(n), n + 1))
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_guard_stream() {
+        // Cancelled mid-stream: the items so far, then the cancellation marker
+        let control = RequestControl::new();
+        let mut stream = Box::pin(guard_stream(slow_stream(), control.clone(), control.start(), || 99));
+        assert_eq!(stream.next().await.unwrap().unwrap(), 0);
+        control.cancel();
+        assert_eq!(stream.next().await.unwrap().unwrap(), 99);
+        assert!(stream.next().await.is_none());
+
+        // Timed out: an error after the items that made it in time
+        let control = RequestControl::new().with_timeout(Duration::from_millis(250));
+        let items: Vec<_> = guard_stream(slow_stream(), control.clone(), control.start(), || 99).collect().await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(*items[1].as_ref().unwrap(), 1);
+        assert!(items[2].as_ref().unwrap_err().to_string().contains("timed out"));
+    }
+}
@@ -1,35 +1,113 @@
 // Provider Manager
 //
 // Manages provider lifecycle, loading configurations from Codex entries
-// and instantiating the appropriate provider implementations.
+// and instantiating the appropriate provider implementations. Loaded
+// providers are supervised: request and health check outcomes feed each
+// provider's status, requests are routed away from unhealthy providers,
+// and unhealthy providers are restarted with backoff (see `supervisor`).
+// Non-streaming chat requests can optionally be answered from a response
+// cache (see `cache`). With a usage tracker attached, each request's tokens
+// and cost are recorded and budgets are enforced before it is sent (see the
+// `usage` module).
 
 use super::{
+    anthropic::{AnthropicConfig, AnthropicProvider},
+    cache::{CacheKey, ResponseCache, ResponseCacheStats},
     claude_code::{ClaudeCodeConfig, ClaudeCodeProvider},
+    control::RequestControl,
     ollama::{OllamaConfig, OllamaProvider},
-    Provider, ProviderResponse, StreamChunk,
+    openai_compatible::{OpenAiCompatibleConfig, OpenAiCompatibleProvider},
+    prompts::PromptLibrary,
+    supervisor::{ProviderHealth, ProviderStatus, SupervisorConfig},
+    types::{ChatChunk, ChatRequest, ChatResponse, ChatRole, FinishReason, ModelInfo, ProviderCapabilities, StreamingResponse},
+    Provider, ProviderResponse, ProviderUsage, StreamChunk,
 };
 use crate::database::Database;
+use crate::observability::BinderyMetrics;
+use crate::secrets::{SecretManager, SecretRef};
+use crate::usage::{HeuristicTokenCounter, ReportedUsage, TokenCounter, UsageRecord, UsageRequest, UsageTracker};
 use anyhow::{anyhow, Context, Result};
-use futures::Stream;
+use chrono::Utc;
+use futures::{Stream, StreamExt};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// Codex templates that define a provider
+const PROVIDER_TEMPLATES: &[&str] = &["claude-code-cli", "ollama", "openai-compatible", "anthropic"];
+
+/// How often the supervision loop looks for due health checks and restarts
+const SUPERVISION_TICK: Duration = Duration::from_secs(1);
+
 /// Provider Manager for managing multiple provider instances
 pub struct ProviderManager {
     database: Arc<Database>,
     providers: Arc<RwLock<HashMap<String, Arc<Box<dyn Provider>>>>>,
+    statuses: Arc<RwLock<HashMap<String, ProviderStatus>>>,
+    supervisor_config: SupervisorConfig,
+    supervision_handle: Mutex<Option<JoinHandle<()>>>,
+    response_cache: Option<Arc<ResponseCache>>,
+    usage_tracker: Option<Arc<UsageTracker>>,
+    prompts: PromptLibrary,
 }
 
 impl ProviderManager {
     /// Create a new ProviderManager
     pub fn new(database: Arc<Database>) -> Self {
         Self {
+            prompts: PromptLibrary::new(Arc::clone(&database)),
             database,
             providers: Arc::new(RwLock::new(HashMap::new())),
+            statuses: Arc::new(RwLock::new(HashMap::new())),
+            supervisor_config: SupervisorConfig::default(),
+            supervision_handle: Mutex::new(None),
+            response_cache: None,
+            usage_tracker: None,
+        }
+    }
+
+    /// Use custom supervision settings
+    pub fn with_supervisor_config(mut self, config: SupervisorConfig) -> Self {
+        self.supervisor_config = config;
+        self
+    }
+
+    /// Answer repeated non-streaming chat requests from `cache`
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Record token usage and cost of every request, and enforce the tracker's budgets
+    pub fn with_usage_tracker(mut self, tracker: Arc<UsageTracker>) -> Self {
+        self.usage_tracker = Some(tracker);
+        self
+    }
+
+    /// The usage tracker, if one is attached
+    pub fn usage_tracker(&self) -> Option<&Arc<UsageTracker>> {
+        self.usage_tracker.as_ref()
+    }
+
+    /// Prompt templates chat requests can select by name
+    pub fn prompts(&self) -> &PromptLibrary {
+        &self.prompts
+    }
+
+    /// Response cache counters, if a cache is configured
+    pub fn response_cache_stats(&self) -> Option<ResponseCacheStats> {
+        self.response_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Drop all cached responses
+    pub fn clear_response_cache(&self) {
+        if let Some(cache) = &self.response_cache {
+            cache.clear();
         }
     }
 
@@ -57,7 +135,7 @@ impl ProviderManager {
 
             if let Some(template_id) = template_id_opt {
                 // Check if this is a provider template
-                if template_id == "claude-code-cli" || template_id == "ollama" {
+                if PROVIDER_TEMPLATES.contains(&template_id) {
                     eprintln!("Debug: Found provider codex with template_id: {}", template_id);
                     if let Some(id) = id_opt {
                         eprintln!("Debug: Attempting to load provider: {}", id);
@@ -123,19 +201,49 @@ impl ProviderManager {
                 let config = self.parse_ollama_config(fields)?;
                 Box::new(OllamaProvider::new(config))
             }
+            "openai-compatible" => {
+                let config = self.parse_openai_compatible_config(fields)?;
+                let api_key = match &config.api_key {
+                    Some(reference) => Some(self.resolve_api_key(codex_id, reference).await?),
+                    None => None,
+                };
+                Box::new(OpenAiCompatibleProvider::new(config, api_key))
+            }
+            "anthropic" => {
+                let config = self.parse_anthropic_config(fields)?;
+                let api_key = self.resolve_api_key(codex_id, &config.api_key).await?;
+                Box::new(AnthropicProvider::new(config, api_key))
+            }
             _ => {
                 return Err(anyhow!("Unknown provider template: {}", template_id));
             }
         };
 
-        // Store provider in cache
-        let mut providers = self.providers.write().await;
-        providers.insert(codex_id.to_string(), Arc::new(provider));
+        self.register_provider(codex_id, provider).await;
 
         info!("Loaded provider {} ({})", codex_id, template_id);
         Ok(())
     }
 
+    /// Add an already-constructed provider under `provider_id`, replacing any with that ID
+    ///
+    /// The provider's supervision history (failures, restarts) is kept across replacements;
+    /// its cached responses are dropped, since its configuration may have changed.
+    pub async fn register_provider(&self, provider_id: &str, provider: Box<dyn Provider>) {
+        let provider_type = provider.provider_type().to_string();
+        if let Some(cache) = &self.response_cache {
+            cache.invalidate_provider(provider_id);
+        }
+        self.providers.write().await.insert(provider_id.to_string(), Arc::new(provider));
+        let mut statuses = self.statuses.write().await;
+        match statuses.get_mut(provider_id) {
+            Some(status) => status.provider_type = provider_type,
+            None => {
+                statuses.insert(provider_id.to_string(), ProviderStatus::new(provider_id, provider_type));
+            }
+        }
+    }
+
     /// Parse ClaudeCodeConfig from Codex fields
     fn parse_claude_code_config(&self, fields: &Value) -> Result<ClaudeCodeConfig> {
         let executable_path = fields
@@ -227,6 +335,77 @@ impl ProviderManager {
         })
     }
 
+    /// Parse OpenAiCompatibleConfig from Codex fields
+    fn parse_openai_compatible_config(&self, fields: &Value) -> Result<OpenAiCompatibleConfig> {
+        let defaults = OpenAiCompatibleConfig::default();
+        let string = |name: &str| fields.get(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        Ok(OpenAiCompatibleConfig {
+            base_url: string("base_url").unwrap_or(defaults.base_url),
+            model: string("model").unwrap_or(defaults.model),
+            api_key: string("api_key").filter(|key| !key.is_empty()).map(SecretRef::parse),
+            temperature: fields
+                .get("temperature")
+                .and_then(|v| v.as_f64())
+                .map(|f| f as f32)
+                .or(defaults.temperature),
+            max_tokens: fields
+                .get("max_tokens")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .or(defaults.max_tokens),
+            system_prompt: string("system_prompt"),
+            context_window: fields
+                .get("context_window")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .or(defaults.context_window),
+            timeout: fields.get("timeout").and_then(|v| v.as_u64()).or(defaults.timeout),
+        })
+    }
+
+    /// Parse AnthropicConfig from Codex fields
+    fn parse_anthropic_config(&self, fields: &Value) -> Result<AnthropicConfig> {
+        let defaults = AnthropicConfig::default();
+        let string = |name: &str| fields.get(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        Ok(AnthropicConfig {
+            base_url: string("base_url").unwrap_or(defaults.base_url),
+            model: string("model").unwrap_or(defaults.model),
+            api_key: string("api_key").filter(|key| !key.is_empty()).map(SecretRef::parse).unwrap_or(defaults.api_key),
+            api_version: string("api_version").unwrap_or(defaults.api_version),
+            temperature: fields.get("temperature").and_then(|v| v.as_f64()).map(|f| f as f32),
+            max_tokens: fields
+                .get("max_tokens")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(defaults.max_tokens),
+            system_prompt: string("system_prompt"),
+            timeout: fields.get("timeout").and_then(|v| v.as_u64()).or(defaults.timeout),
+        })
+    }
+
+    /// Resolve a provider's API key through the secrets module
+    ///
+    /// Keys are configured as `secret://` references (or the older `vault://`).
+    /// A plain value is used as-is, with a warning, since it sits unencrypted
+    /// in the Codex.
+    async fn resolve_api_key(&self, provider_id: &str, api_key: &SecretRef) -> Result<String> {
+        if let SecretRef::Literal(value) = api_key {
+            warn!(
+                "Provider {} has a plaintext API key in its Codex; store it with the secrets module and use a secret:// reference",
+                provider_id
+            );
+            return Ok(value.clone());
+        }
+
+        let secrets = SecretManager::from_env().await.context("Failed to open secret storage")?;
+        api_key
+            .resolve(&secrets)
+            .await
+            .with_context(|| format!("Failed to resolve API key {} for provider {}", api_key, provider_id))
+    }
+
     /// Send a message to a specific provider
     pub async fn send_message(
         &self,
@@ -239,14 +418,37 @@ impl ProviderManager {
     ) -> Result<ProviderResponse> {
         debug!("Sending message to provider: {}", provider_id);
 
-        // Get provider from cache
-        let providers = self.providers.read().await;
-        let provider = providers
-            .get(provider_id)
-            .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))?;
+        let (routed_id, provider) = self.route(provider_id).await?;
+        self.enforce_budgets(&routed_id, session_id).await?;
+        let usage = self.usage_request(&routed_id, provider.as_ref().as_ref(), model, session_id, system_prompt, &[message]);
+        // Sessions belong to the provider that created them
+        let session_id = session_id.filter(|_| routed_id == provider_id);
 
         // Send message to provider with optional model and session_id
-        provider.send_message(message, model, session_id, system_prompt, stream).await
+        let start = Instant::now();
+        let result = provider.send_message(message, model, session_id, system_prompt, stream).await;
+        BinderyMetrics::record_provider_request(provider.provider_type(), "send_message", start.elapsed(), result.is_ok());
+        self.record_outcome(&routed_id, result.as_ref().err().map(|e| e.to_string())).await;
+
+        let mut response = result?;
+        if routed_id != provider_id {
+            response.metadata.insert("routed_from".to_string(), Value::String(provider_id.to_string()));
+            response.metadata.insert("provider_id".to_string(), Value::String(routed_id));
+        }
+
+        let reported = response.usage.as_ref().map(ReportedUsage::from).unwrap_or_default();
+        if let Some(record) = self.record_usage(usage, &response.text, reported).await {
+            let usage = response.usage.get_or_insert(ProviderUsage {
+                input_tokens: record.input_tokens as usize,
+                output_tokens: record.output_tokens as usize,
+                cost_usd: None,
+            });
+            usage.cost_usd = record.cost_usd;
+            if record.estimated {
+                response.metadata.insert("usage_estimated".to_string(), Value::Bool(true));
+            }
+        }
+        Ok(response)
     }
 
     /// Send a message with streaming to a specific provider
@@ -257,21 +459,294 @@ impl ProviderManager {
         model: Option<&str>,
         session_id: Option<&str>,
         system_prompt: Option<&str>,
+    ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+        self.send_message_stream_with_control(provider_id, message, model, session_id, system_prompt, RequestControl::default())
+            .await
+    }
+
+    /// Send a message with streaming to a specific provider, cancellable and with a timeout
+    ///
+    /// Cancelling `control` stops the provider (aborting the HTTP request or
+    /// interrupting the CLI) and ends the stream with a cancelled result chunk.
+    pub async fn send_message_stream_with_control(
+        &self,
+        provider_id: &str,
+        message: &str,
+        model: Option<&str>,
+        session_id: Option<&str>,
+        system_prompt: Option<&str>,
+        control: RequestControl,
     ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
         debug!("Sending message to provider with streaming: {}", provider_id);
 
-        // Get provider from cache
+        let (routed_id, provider) = self.route(provider_id).await?;
+        self.enforce_budgets(&routed_id, session_id).await?;
+        let usage = self.usage_request(&routed_id, provider.as_ref().as_ref(), model, session_id, system_prompt, &[message]);
+        let session_id = session_id.filter(|_| routed_id == provider_id);
+
+        // Send message to provider with optional model and session_id; the
+        // recorded duration covers opening the stream, not draining it
+        let start = Instant::now();
+        let result = provider
+            .send_message_stream_with_control(message, model, session_id, system_prompt, control)
+            .await;
+        BinderyMetrics::record_provider_request(provider.provider_type(), "send_message_stream", start.elapsed(), result.is_ok());
+
+        let stream = match result {
+            Ok(stream) => stream,
+            Err(e) => {
+                self.record_outcome(&routed_id, Some(e.to_string())).await;
+                return Err(e);
+            }
+        };
+
+        let stream = self.supervise_stream(routed_id, stream);
+        Ok(Box::new(self.meter_stream(usage, stream, StreamTally::observe_stream_chunk).boxed()))
+    }
+
+    /// Send a structured chat request (conversation history, tools) to a specific provider
+    ///
+    /// Tool calls the model makes are returned in the response for the caller
+    /// to execute and send back as tool result messages.
+    ///
+    /// With a response cache configured, requests outside a session are
+    /// answered from the cache when possible; a session carries context the
+    /// request doesn't show, so those always go to the provider.
+    ///
+    /// A request naming a prompt template is built from it first.
+    pub async fn send_chat_request(
+        &self,
+        provider_id: &str,
+        request: ChatRequest,
+        session_id: Option<&str>,
+    ) -> Result<ChatResponse> {
+        debug!("Sending chat request to provider: {}", provider_id);
+
+        let request = self.prompts.resolve(request).await?;
+
+        let (routed_id, provider) = self.route(provider_id).await?;
+        validate_chat_request(provider.as_ref().as_ref(), &request)?;
+        let usage_session = session_id;
+        let session_id = session_id.filter(|_| routed_id == provider_id);
+
+        let cache = self.response_cache.as_ref().filter(|_| session_id.is_none());
+        let mut cache_entry = None;
+        if let Some(cache) = cache {
+            let key = CacheKey::new(&routed_id, provider.default_model(), &request);
+            let (cached, embedding) = cache.get(&key).await;
+            if let Some(response) = cached {
+                debug!("Answered chat request for {} from the response cache", routed_id);
+                return Ok(response);
+            }
+            cache_entry = Some((key, embedding));
+        }
+
+        // Cached answers are free, so budgets only apply from here
+        self.enforce_budgets(&routed_id, usage_session).await?;
+        let usage = self.chat_usage_request(&routed_id, provider.as_ref().as_ref(), usage_session, &request);
+
+        let start = Instant::now();
+        let result = provider.send_chat_request(request, session_id).await;
+        BinderyMetrics::record_provider_request(provider.provider_type(), "send_chat_request", start.elapsed(), result.is_ok());
+        self.record_outcome(&routed_id, result.as_ref().err().map(|e| e.to_string())).await;
+
+        if let Ok(response) = &result {
+            self.record_usage(usage, &response.content, ReportedUsage::from(&response.usage)).await;
+        }
+
+        if let (Some(cache), Some((key, embedding)), Ok(response)) = (cache, cache_entry, &result) {
+            if matches!(response.finish_reason, FinishReason::Stop | FinishReason::ToolCalls) {
+                cache.insert(&key, embedding, response.clone());
+            }
+        }
+
+        result
+    }
+
+    /// Send a structured chat request with streaming to a specific provider
+    pub async fn send_chat_request_stream(
+        &self,
+        provider_id: &str,
+        request: ChatRequest,
+        session_id: Option<&str>,
+    ) -> Result<StreamingResponse> {
+        self.send_chat_request_stream_with_control(provider_id, request, session_id, RequestControl::default())
+            .await
+    }
+
+    /// Send a structured chat request with streaming, cancellable and with a timeout
+    ///
+    /// Cancelling `control` ends the stream with a `Cancelled` finish reason.
+    pub async fn send_chat_request_stream_with_control(
+        &self,
+        provider_id: &str,
+        request: ChatRequest,
+        session_id: Option<&str>,
+        control: RequestControl,
+    ) -> Result<StreamingResponse> {
+        debug!("Sending chat request to provider with streaming: {}", provider_id);
+
+        let request = self.prompts.resolve(request).await?;
+
+        let (routed_id, provider) = self.route(provider_id).await?;
+        validate_chat_request(provider.as_ref().as_ref(), &request)?;
+        self.enforce_budgets(&routed_id, session_id).await?;
+        let usage = self.chat_usage_request(&routed_id, provider.as_ref().as_ref(), session_id, &request);
+        let session_id = session_id.filter(|_| routed_id == provider_id);
+
+        let start = Instant::now();
+        let result = provider.send_chat_request_stream_with_control(request, session_id, control).await;
+        BinderyMetrics::record_provider_request(provider.provider_type(), "send_chat_request_stream", start.elapsed(), result.is_ok());
+
+        match result {
+            Ok(response) => {
+                let stream = self.supervise_stream(routed_id, response.stream);
+                Ok(StreamingResponse {
+                    stream: Box::pin(self.meter_stream(usage, stream, StreamTally::observe_chat_chunk)),
+                    metadata: response.metadata,
+                })
+            }
+            Err(e) => {
+                self.record_outcome(&routed_id, Some(e.to_string())).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Pass a response stream through, recording its outcome once it has been drained
+    fn supervise_stream<T, S>(&self, provider_id: String, stream: S) -> impl Stream<Item = Result<T>> + Send
+    where
+        T: Send,
+        S: Stream<Item = Result<T>> + Send + Unpin,
+    {
+        let statuses = Arc::clone(&self.statuses);
+        let config = self.supervisor_config.clone();
+        async_stream::stream! {
+            let mut stream = stream;
+            let mut error = None;
+            while let Some(item) = stream.next().await {
+                if let Err(e) = &item {
+                    error.get_or_insert_with(|| e.to_string());
+                }
+                yield item;
+            }
+            record_outcome(&statuses, &config, &provider_id, error).await;
+        }
+    }
+
+    /// Refuse a request to `provider_id` if a usage budget covering it is used up
+    async fn enforce_budgets(&self, provider_id: &str, session_id: Option<&str>) -> Result<()> {
+        match &self.usage_tracker {
+            Some(tracker) => tracker.enforce(provider_id, session_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Usage details of a request, if usage is being tracked
+    fn usage_request(
+        &self,
+        provider_id: &str,
+        provider: &dyn Provider,
+        model: Option<&str>,
+        session_id: Option<&str>,
+        system_prompt: Option<&str>,
+        messages: &[&str],
+    ) -> Option<UsageRequest> {
+        self.usage_tracker.as_ref()?;
+        let prompt = system_prompt.into_iter().chain(messages.iter().copied()).collect::<Vec<_>>().join("\n");
+        Some(UsageRequest {
+            provider_id: provider_id.to_string(),
+            provider_type: provider.provider_type().to_string(),
+            model: model.or(provider.default_model()).map(str::to_string),
+            session_id: session_id.map(str::to_string),
+            prompt,
+            messages: messages.len() + usize::from(system_prompt.is_some()),
+        })
+    }
+
+    fn chat_usage_request(
+        &self,
+        provider_id: &str,
+        provider: &dyn Provider,
+        session_id: Option<&str>,
+        request: &ChatRequest,
+    ) -> Option<UsageRequest> {
+        let messages: Vec<&str> = request.messages.iter().map(|m| m.content.as_str()).collect();
+        self.usage_request(provider_id, provider, None, session_id, request.system_prompt.as_deref(), &messages)
+    }
+
+    /// Record a completed request; tracking failures are logged, never returned
+    async fn record_usage(&self, request: Option<UsageRequest>, output: &str, reported: ReportedUsage) -> Option<UsageRecord> {
+        record_usage(self.usage_tracker.as_ref()?, &request?, output, reported).await
+    }
+
+    /// Pass a response stream through, recording its usage once it has been drained
+    fn meter_stream<T, S>(
+        &self,
+        request: Option<UsageRequest>,
+        stream: S,
+        observe: fn(&T, &mut StreamTally),
+    ) -> impl Stream<Item = Result<T>> + Send
+    where
+        T: Send,
+        S: Stream<Item = Result<T>> + Send,
+    {
+        let tracker = self.usage_tracker.clone();
+        async_stream::stream! {
+            futures::pin_mut!(stream);
+            let mut tally = StreamTally::default();
+            while let Some(item) = stream.next().await {
+                if let Ok(chunk) = &item {
+                    observe(chunk, &mut tally);
+                }
+                yield item;
+            }
+            if let (Some(tracker), Some(request)) = (tracker, request) {
+                record_usage(&tracker, &request, &tally.output, tally.reported).await;
+            }
+        }
+    }
+
+    /// Pick the provider that should serve a request for `provider_id`
+    ///
+    /// That's the provider itself unless it is unhealthy and fallback routing
+    /// is on, in which case a routable provider is chosen instead — one of the
+    /// same type if possible, preferring those known to be healthy.
+    async fn route(&self, provider_id: &str) -> Result<(String, Arc<Box<dyn Provider>>)> {
         let providers = self.providers.read().await;
-        let provider = providers
+        let requested = providers
             .get(provider_id)
             .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))?;
 
-        // Clone Arc to avoid holding read lock across await
-        let provider = Arc::clone(provider);
-        drop(providers);
+        let statuses = self.statuses.read().await;
+        let routable = |id: &str| statuses.get(id).is_none_or(ProviderStatus::is_routable);
+        if !self.supervisor_config.fallback_routing || routable(provider_id) {
+            return Ok((provider_id.to_string(), Arc::clone(requested)));
+        }
 
-        // Send message to provider with optional model and session_id
-        provider.send_message_stream(message, model, session_id, system_prompt).await
+        let fallback = providers
+            .iter()
+            .filter(|(id, _)| id.as_str() != provider_id && routable(id))
+            .min_by_key(|(id, provider)| {
+                let same_type = provider.provider_type() == requested.provider_type();
+                let healthy = statuses.get(id.as_str()).is_some_and(|s| s.health == ProviderHealth::Healthy);
+                (!same_type, !healthy, id.to_string())
+            });
+
+        match fallback {
+            Some((fallback_id, provider)) => {
+                warn!("Provider {} is unhealthy; routing request to {}", provider_id, fallback_id);
+                Ok((fallback_id.clone(), Arc::clone(provider)))
+            }
+            None => Err(anyhow!(
+                "Provider {} is unhealthy and no healthy provider is available to take over",
+                provider_id
+            )),
+        }
+    }
+
+    async fn record_outcome(&self, provider_id: &str, error: Option<String>) {
+        record_outcome(&self.statuses, &self.supervisor_config, provider_id, error).await;
     }
 
     /// List all loaded providers
@@ -310,16 +785,57 @@ impl ProviderManager {
         })
     }
 
-    /// Health check for a specific provider
-    pub async fn health_check(&self, provider_id: &str) -> Result<bool> {
-        debug!("Performing health check on provider: {}", provider_id);
-
+    /// What a provider supports (streaming, tools, vision, context size)
+    pub async fn capabilities(&self, provider_id: &str) -> Result<ProviderCapabilities> {
         let providers = self.providers.read().await;
         let provider = providers
             .get(provider_id)
             .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))?;
+        Ok(provider.capabilities())
+    }
 
-        provider.health_check().await
+    /// Models a provider can serve, for model pickers
+    pub async fn list_models(&self, provider_id: &str) -> Result<Vec<ModelInfo>> {
+        let provider = {
+            let providers = self.providers.read().await;
+            Arc::clone(
+                providers
+                    .get(provider_id)
+                    .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))?,
+            )
+        };
+        provider.list_models().await
+    }
+
+    /// Health check for a specific provider
+    ///
+    /// The result also updates the provider's supervision status; a check that
+    /// takes longer than the configured timeout counts as unhealthy.
+    pub async fn health_check(&self, provider_id: &str) -> Result<bool> {
+        debug!("Performing health check on provider: {}", provider_id);
+
+        let provider = {
+            let providers = self.providers.read().await;
+            Arc::clone(
+                providers
+                    .get(provider_id)
+                    .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))?,
+            )
+        };
+
+        let error = match tokio::time::timeout(self.supervisor_config.check_timeout, provider.health_check()).await {
+            Ok(Ok(true)) => None,
+            Ok(Ok(false)) => Some("Provider reported unhealthy".to_string()),
+            Ok(Err(e)) => Some(format!("Health check failed: {}", e)),
+            Err(_) => Some("Health check timed out".to_string()),
+        };
+
+        let healthy = error.is_none();
+        if let Some(status) = self.statuses.write().await.get_mut(provider_id) {
+            status.record_check(Utc::now());
+        }
+        self.record_outcome(provider_id, error).await;
+        Ok(healthy)
     }
 
     /// Reload a provider (useful after configuration changes)
@@ -332,6 +848,10 @@ impl ProviderManager {
             providers.remove(provider_id);
         }
 
+        if let Some(status) = self.statuses.write().await.get_mut(provider_id) {
+            status.record_restart();
+        }
+
         // Load fresh instance
         self.load_provider(provider_id).await
     }
@@ -344,9 +864,225 @@ impl ProviderManager {
         providers
             .remove(provider_id)
             .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))?;
+        self.statuses.write().await.remove(provider_id);
+        if let Some(cache) = &self.response_cache {
+            cache.invalidate_provider(provider_id);
+        }
 
         Ok(())
     }
+
+    /// Supervision status of every loaded provider, ordered by ID
+    pub async fn provider_statuses(&self) -> Vec<ProviderStatus> {
+        let mut statuses: Vec<_> = self.statuses.read().await.values().cloned().collect();
+        statuses.sort_by(|a, b| a.provider_id.cmp(&b.provider_id));
+        statuses
+    }
+
+    /// Supervision status of one provider
+    pub async fn provider_status(&self, provider_id: &str) -> Result<ProviderStatus> {
+        self.statuses
+            .read()
+            .await
+            .get(provider_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Provider not found: {}", provider_id))
+    }
+
+    /// Run one supervision pass
+    ///
+    /// Health checks every provider not checked within `health_check_interval`,
+    /// then restarts unhealthy providers whose backoff has elapsed.
+    pub async fn supervise(&self) {
+        let now = Utc::now();
+        let interval = chrono::Duration::from_std(self.supervisor_config.health_check_interval)
+            .unwrap_or(chrono::Duration::MAX);
+
+        let (due_checks, due_restarts): (Vec<_>, Vec<_>) = {
+            let statuses = self.statuses.read().await;
+            let checks = statuses
+                .values()
+                .filter(|s| s.last_check.is_none_or(|at| now - at >= interval))
+                .map(|s| s.provider_id.clone())
+                .collect();
+            let restarts = statuses
+                .values()
+                .filter(|s| s.restart_due(now))
+                .map(|s| s.provider_id.clone())
+                .collect();
+            (checks, restarts)
+        };
+
+        for provider_id in due_checks {
+            if let Err(e) = self.health_check(&provider_id).await {
+                debug!("Skipping health check for {}: {}", provider_id, e);
+            }
+        }
+
+        for provider_id in due_restarts {
+            warn!("Restarting unhealthy provider {}", provider_id);
+            if let Err(e) = self.reload_provider(&provider_id).await {
+                error!("Failed to restart provider {}: {}", provider_id, e);
+                self.record_outcome(&provider_id, Some(format!("Restart failed: {}", e))).await;
+            }
+        }
+    }
+
+    /// Supervise providers in the background until the manager is dropped
+    ///
+    /// Replaces any supervision loop already running.
+    pub async fn start_supervision(self: &Arc<Self>) {
+        let manager = Arc::downgrade(self);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(SUPERVISION_TICK);
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else { break };
+                manager.supervise().await;
+            }
+        });
+
+        if let Some(previous) = self.supervision_handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// Stop the background supervision loop, if running
+    pub async fn stop_supervision(&self) {
+        if let Some(handle) = self.supervision_handle.lock().await.take() {
+            handle.abort();
+        }
+    }
+
+    /// Stop supervision and unload every provider
+    ///
+    /// CLI provider processes are killed once the requests using them end.
+    pub async fn shutdown(&self) {
+        self.stop_supervision().await;
+        let unloaded = std::mem::take(&mut *self.providers.write().await);
+        self.statuses.write().await.clear();
+        info!(providers = unloaded.len(), "Providers unloaded");
+    }
+}
+
+/// Refuse a chat request the provider can't serve, before it is sent
+///
+/// The prompt size is an estimate, so only prompts that clearly overflow the
+/// context window are refused.
+fn validate_chat_request(provider: &dyn Provider, request: &ChatRequest) -> Result<()> {
+    let capabilities = provider.capabilities();
+    let name = provider.display_name();
+
+    if !request.tools.is_empty() && !capabilities.supports_tools {
+        return Err(anyhow!("{} does not support tool calling", name));
+    }
+    let has_system_prompt = request.system_prompt.is_some() || request.messages.iter().any(|m| m.role == ChatRole::System);
+    if has_system_prompt && !capabilities.supports_system_prompt {
+        return Err(anyhow!("{} does not support system prompts", name));
+    }
+
+    let context = capabilities.max_context_length as usize;
+    if let Some(max_tokens) = request.max_tokens {
+        if max_tokens as usize > context {
+            return Err(anyhow!("max_tokens {} exceeds the {}-token context window of {}", max_tokens, context, name));
+        }
+    }
+    let counter = HeuristicTokenCounter::for_provider_type(provider.provider_type());
+    let prompt_tokens = request.system_prompt.as_deref().map_or(0, |s| counter.count(s))
+        + request
+            .messages
+            .iter()
+            .map(|m| counter.count(&m.content) + counter.message_overhead())
+            .sum::<usize>();
+    if prompt_tokens > context {
+        return Err(anyhow!(
+            "Request of about {} tokens exceeds the {}-token context window of {}",
+            prompt_tokens,
+            context,
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Record a completed request with `tracker`, logging failures
+async fn record_usage(
+    tracker: &UsageTracker,
+    request: &UsageRequest,
+    output: &str,
+    reported: ReportedUsage,
+) -> Option<UsageRecord> {
+    match tracker.record(request, output, reported).await {
+        Ok(record) => Some(record),
+        Err(e) => {
+            warn!("Failed to record usage for provider {}: {}", request.provider_id, e);
+            None
+        }
+    }
+}
+
+/// Output text and reported usage collected from a response stream
+#[derive(Default)]
+struct StreamTally {
+    output: String,
+    reported: ReportedUsage,
+}
+
+impl StreamTally {
+    fn observe_stream_chunk(chunk: &StreamChunk, tally: &mut StreamTally) {
+        // Final chunks may repeat the whole reply; only use them if nothing was streamed
+        if let Some(text) = &chunk.text {
+            if !chunk.is_final || tally.output.is_empty() {
+                tally.output.push_str(text);
+            }
+        }
+
+        let Some(metadata) = &chunk.metadata else { return };
+        if let Some(usage) = metadata.get("usage").filter(|u| u.is_object()) {
+            let count = |keys: [&str; 2]| keys.iter().find_map(|k| usage.get(*k)?.as_u64()).unwrap_or(0);
+            tally.reported.tokens = Some((count(["input_tokens", "prompt_tokens"]), count(["output_tokens", "completion_tokens"])));
+        }
+        if let Some(cost) = metadata.get("cost_usd").and_then(Value::as_f64) {
+            tally.reported.cost_usd = Some(cost);
+        }
+    }
+
+    fn observe_chat_chunk(chunk: &ChatChunk, tally: &mut StreamTally) {
+        tally.output.push_str(&chunk.delta);
+        if let Some(usage) = &chunk.usage {
+            tally.reported.tokens = ReportedUsage::from(usage).tokens;
+        }
+    }
+}
+
+/// Fold a request or health check outcome into a provider's status
+async fn record_outcome(
+    statuses: &RwLock<HashMap<String, ProviderStatus>>,
+    config: &SupervisorConfig,
+    provider_id: &str,
+    error: Option<String>,
+) {
+    let mut statuses = statuses.write().await;
+    let Some(status) = statuses.get_mut(provider_id) else {
+        return;
+    };
+
+    let was_routable = status.is_routable();
+    match error {
+        None => status.record_success(Utc::now()),
+        Some(error) => status.record_failure(error, Utc::now(), config),
+    }
+
+    if was_routable && !status.is_routable() {
+        warn!(
+            "Provider {} marked unhealthy after {} consecutive failures: {}",
+            provider_id,
+            status.consecutive_failures,
+            status.last_error.as_deref().unwrap_or("unknown error")
+        );
+    } else if !was_routable && status.is_routable() {
+        info!("Provider {} is healthy again", provider_id);
+    }
 }
 
 /// Provider information for listing
@@ -357,3 +1093,258 @@ pub struct ProviderInfo {
     pub provider_type: String,
     pub display_name: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::cache::ResponseCacheConfig;
+    use crate::providers::supervisor::ProviderHealth;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tempfile::TempDir;
+
+    struct MockProvider {
+        name: &'static str,
+        healthy: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        async fn send_message(
+            &self,
+            _message: &str,
+            _model: Option<&str>,
+            _session_id: Option<&str>,
+            _system_prompt: Option<&str>,
+            _stream: bool,
+        ) -> Result<ProviderResponse> {
+            if !self.healthy.load(Ordering::SeqCst) {
+                return Err(anyhow!("{} crashed", self.name));
+            }
+            Ok(ProviderResponse {
+                text: format!("reply from {}", self.name),
+                session_id: None,
+                usage: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn send_message_stream(
+            &self,
+            _message: &str,
+            _model: Option<&str>,
+            _session_id: Option<&str>,
+            _system_prompt: Option<&str>,
+        ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+            let item = if self.healthy.load(Ordering::SeqCst) {
+                Ok(StreamChunk { chunk_type: "result".to_string(), text: None, is_final: true, metadata: None })
+            } else {
+                Err(anyhow!("{} exited with status 1", self.name))
+            };
+            Ok(Box::new(futures::stream::iter(vec![item])))
+        }
+
+        async fn health_check(&self) -> Result<bool> {
+            Ok(self.healthy.load(Ordering::SeqCst))
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+
+        fn display_name(&self) -> &str {
+            self.name
+        }
+    }
+
+    async fn setup(config: SupervisorConfig) -> (Arc<ProviderManager>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().join("providers.db")).await.unwrap();
+        database.init_schema().await.unwrap();
+        (Arc::new(ProviderManager::new(Arc::new(database)).with_supervisor_config(config)), temp_dir)
+    }
+
+    async fn register(manager: &ProviderManager, name: &'static str) -> Arc<AtomicBool> {
+        let healthy = Arc::new(AtomicBool::new(true));
+        manager.register_provider(name, Box::new(MockProvider { name, healthy: Arc::clone(&healthy) })).await;
+        healthy
+    }
+
+    fn strict() -> SupervisorConfig {
+        SupervisorConfig {
+            failure_threshold: 2,
+            initial_backoff: Duration::from_secs(3600),
+            ..SupervisorConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_route_around_unhealthy_provider() {
+        let (manager, _temp_dir) = setup(strict()).await;
+        let primary = register(&manager, "primary").await;
+        register(&manager, "backup").await;
+
+        primary.store(false, Ordering::SeqCst);
+        assert!(manager.send_message("primary", "hi", None, None, None, false).await.is_err());
+        assert_eq!(manager.provider_status("primary").await.unwrap().health, ProviderHealth::Degraded);
+
+        // A failed stream counts once it has been drained
+        let mut stream = manager.send_message_stream("primary", "hi", None, None, None).await.unwrap();
+        while stream.next().await.is_some() {}
+        let status = manager.provider_status("primary").await.unwrap();
+        assert_eq!(status.health, ProviderHealth::Unhealthy);
+        assert!(status.next_restart_at.is_some());
+
+        let response = manager.send_message("primary", "hi", None, Some("session"), None, false).await.unwrap();
+        assert_eq!(response.text, "reply from backup");
+        assert_eq!(response.metadata["routed_from"], "primary");
+
+        // Nothing left to route to
+        manager.unload_provider("backup").await.unwrap();
+        let err = manager.send_message("primary", "hi", None, None, None, false).await.unwrap_err();
+        assert!(err.to_string().contains("unhealthy"));
+
+        // Recovery is picked up by the next health check
+        primary.store(true, Ordering::SeqCst);
+        assert!(manager.health_check("primary").await.unwrap());
+        let statuses = manager.provider_statuses().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].health, ProviderHealth::Healthy);
+        assert_eq!(statuses[0].total_failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_supervision_restarts_unhealthy_provider() {
+        let (manager, _temp_dir) = setup(SupervisorConfig {
+            failure_threshold: 1,
+            initial_backoff: Duration::ZERO,
+            ..SupervisorConfig::default()
+        })
+        .await;
+
+        // A provider backed by a Codex, so it can be reloaded
+        manager.database.create_codex("local-llm", "Local LLM", "ollama", &serde_json::json!({})).await.unwrap();
+        let healthy = Arc::new(AtomicBool::new(false));
+        manager
+            .register_provider("local-llm", Box::new(MockProvider { name: "local-llm", healthy }))
+            .await;
+
+        // First pass: the health check fails and a restart is scheduled
+        manager.supervise().await;
+        let status = manager.provider_status("local-llm").await.unwrap();
+        assert_eq!(status.health, ProviderHealth::Unhealthy);
+        assert_eq!(status.restarts, 0);
+
+        // Second pass: the restart is due and the provider is rebuilt from its Codex
+        manager.supervise().await;
+        let status = manager.provider_status("local-llm").await.unwrap();
+        assert_eq!(status.restarts, 1);
+        assert_eq!(status.health, ProviderHealth::Unknown);
+        assert_eq!(status.provider_type, "ollama");
+        assert_eq!(manager.get_provider_info("local-llm").await.unwrap().provider_type, "ollama");
+    }
+
+    #[tokio::test]
+    async fn test_response_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().join("providers.db")).await.unwrap();
+        database.init_schema().await.unwrap();
+        let cache = ResponseCache::new(ResponseCacheConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let manager = ProviderManager::new(Arc::new(database)).with_response_cache(cache);
+        let healthy = register(&manager, "primary").await;
+
+        let request = crate::providers::types::simple_params_to_chat_request("hello", None, None, None);
+        let first = manager.send_chat_request("primary", request.clone(), None).await.unwrap();
+        assert_eq!(first.content, "reply from primary");
+
+        // The repeat is answered without the (now failing) provider
+        healthy.store(false, Ordering::SeqCst);
+        let second = manager.send_chat_request("primary", request.clone(), None).await.unwrap();
+        assert_eq!(second.content, first.content);
+        assert_eq!(manager.response_cache_stats().unwrap().hits, 1);
+
+        // Session requests always reach the provider
+        assert!(manager.send_chat_request("primary", request.clone(), Some("s1")).await.is_err());
+
+        // Re-registering a provider drops its cached responses
+        register(&manager, "primary").await;
+        assert_eq!(manager.response_cache_stats().unwrap().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_validated_against_capabilities() {
+        use crate::providers::types::{simple_params_to_chat_request, ToolDefinition};
+
+        let (manager, _temp_dir) = setup(SupervisorConfig::default()).await;
+        register(&manager, "primary").await;
+
+        // The mock keeps the trait defaults: no tools, an 8192-token context
+        let capabilities = manager.capabilities("primary").await.unwrap();
+        assert!(!capabilities.supports_tools);
+        assert!(manager.list_models("primary").await.unwrap().is_empty());
+        assert!(manager.capabilities("missing").await.is_err());
+
+        let mut request = simple_params_to_chat_request("hello", None, None, None);
+        request.tools = vec![ToolDefinition {
+            name: "get_weather".to_string(),
+            description: "Current weather".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let err = manager.send_chat_request("primary", request, None).await.unwrap_err();
+        assert!(err.to_string().contains("tool calling"));
+
+        let request = simple_params_to_chat_request(&"word ".repeat(10_000), None, None, None);
+        let err = manager.send_chat_request("primary", request, None).await.unwrap_err();
+        assert!(err.to_string().contains("context window"));
+
+        let request = simple_params_to_chat_request("hello", None, Some(100_000), None);
+        assert!(manager.send_chat_request("primary", request, None).await.is_err());
+
+        let request = simple_params_to_chat_request("hello", None, Some(1000), None);
+        assert!(manager.send_chat_request("primary", request, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_usage_tracking_and_budgets() {
+        use crate::usage::{Budget, BudgetAction, BudgetPeriod, BudgetScope, UsageConfig, UsageQuery};
+
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Database::new(temp_dir.path().join("providers.db")).await.unwrap());
+        database.init_schema().await.unwrap();
+        let mut config = UsageConfig::default();
+        config.pricing.models.insert("mock-model".to_string(), crate::usage::ModelPrice::new(1000.0, 1000.0));
+        config.budgets.push(Budget {
+            name: "tokens".to_string(),
+            scope: BudgetScope::Provider { provider_id: "primary".to_string() },
+            period: BudgetPeriod::Total,
+            max_tokens: Some(5),
+            max_cost_usd: None,
+            action: BudgetAction::Reject,
+        });
+        let tracker = Arc::new(UsageTracker::new(Arc::clone(&database), config).await.unwrap());
+        let manager = ProviderManager::new(database).with_usage_tracker(tracker);
+        register(&manager, "primary").await;
+
+        // The mock reports no usage, so tokens are estimated and priced
+        let response = manager
+            .send_message("primary", "hello there", Some("mock-model"), Some("s1"), None, false)
+            .await
+            .unwrap();
+        let usage = response.usage.unwrap();
+        assert!(usage.input_tokens > 0 && usage.output_tokens > 0);
+        assert!(usage.cost_usd.unwrap() > 0.0);
+        assert_eq!(response.metadata.get("usage_estimated"), Some(&Value::Bool(true)));
+
+        // The provider's token budget is now used up
+        let err = manager.send_message("primary", "again", None, None, None, false).await.unwrap_err();
+        assert!(err.downcast_ref::<crate::usage::BudgetExceeded>().is_some());
+        // A rejected request is not the provider's fault
+        assert_eq!(manager.provider_status("primary").await.unwrap().total_failures, 0);
+
+        let report = manager.usage_tracker().unwrap().report(&UsageQuery::default()).await.unwrap();
+        assert_eq!(report[0].requests, 1);
+    }
+}
@@ -4,15 +4,31 @@
 // Supports multiple provider types:
 // - Claude Code CLI (stream-json format via stdio)
 // - Ollama (HTTP REST API)
+// - OpenAI-compatible APIs (OpenAI, OpenRouter, vLLM, LM Studio; HTTP + SSE)
+// - Anthropic Messages API (HTTP + SSE)
 //
 // Architecture:
 // - Each provider implements the Provider trait
-// - ProviderManager handles lifecycle (spawn, health, restart)
-// - Providers read configuration from Codex entries
+// - ProviderManager handles lifecycle (spawn, health, restart) and routes
+//   requests away from unhealthy providers
+// - An optional response cache answers repeated chat requests, exactly or
+//   by prompt embedding similarity
+// - Providers read configuration from Codex entries; API keys are
+//   vault references resolved through the secrets module
+// - System prompts and few-shot examples are prompt templates stored as
+//   Codices, selected by name in a chat request
 
+pub mod anthropic;
+pub mod cache;
 pub mod claude_code;
+pub mod control;
 pub mod ollama;
+pub mod openai_compatible;
 pub mod manager;
+pub mod prompts;
+mod rest;
+pub mod supervisor;
+mod tool_emulation;
 pub mod types;
 
 use async_trait::async_trait;
@@ -174,6 +190,67 @@ pub trait Provider: Send + Sync {
         })
     }
 
+    // ==================== Request Control ====================
+
+    /// Send a message with streaming, cancellable and with a timeout
+    ///
+    /// Cancelling `control` ends the stream with a final chunk whose metadata
+    /// has `finish_reason: "cancelled"`; passing its timeout ends the stream
+    /// with an error. Either way the underlying request is dropped, which
+    /// aborts HTTP requests and kills CLI processes.
+    ///
+    /// Default implementation wraps send_message_stream(). Providers with a
+    /// gentler way to stop a request can override.
+    async fn send_message_stream_with_control(
+        &self,
+        message: &str,
+        model: Option<&str>,
+        session_id: Option<&str>,
+        system_prompt: Option<&str>,
+        control: RequestControl,
+    ) -> Result<Box<dyn futures::Stream<Item = Result<StreamChunk, anyhow::Error>> + Unpin + Send>, anyhow::Error> {
+        use futures::StreamExt;
+        let deadline = control.start();
+        let opened = control::run(&control, deadline, self.send_message_stream(message, model, session_id, system_prompt)).await;
+        match opened {
+            Ok(stream) => Ok(Box::new(control::guard_stream(stream?, control, deadline, control::cancelled_stream_chunk).boxed())),
+            Err(control::Interrupted::Cancelled) => Ok(Box::new(futures::stream::iter([Ok(control::cancelled_stream_chunk())]))),
+            Err(control::Interrupted::TimedOut) => Err(deadline.error()),
+        }
+    }
+
+    /// Send a structured chat request with streaming, cancellable and with a timeout
+    ///
+    /// Cancelling `control` ends the stream with a `Cancelled` finish reason;
+    /// see send_message_stream_with_control().
+    async fn send_chat_request_stream_with_control(
+        &self,
+        request: types::ChatRequest,
+        session_id: Option<&str>,
+        control: RequestControl,
+    ) -> Result<types::StreamingResponse, anyhow::Error> {
+        let deadline = control.start();
+        let opened = control::run(&control, deadline, self.send_chat_request_stream(request, session_id)).await;
+        match opened {
+            Ok(response) => {
+                let response = response?;
+                Ok(types::StreamingResponse {
+                    stream: Box::pin(control::guard_stream(response.stream, control, deadline, control::cancelled_chat_chunk)),
+                    metadata: response.metadata,
+                })
+            }
+            Err(control::Interrupted::Cancelled) => Ok(types::StreamingResponse {
+                stream: Box::pin(futures::stream::iter([Ok(control::cancelled_chat_chunk())])),
+                metadata: types::ResponseMetadata {
+                    model: self.default_model().unwrap_or("unknown").to_string(),
+                    provider: self.provider_type().to_string(),
+                    request_id: None,
+                },
+            }),
+            Err(control::Interrupted::TimedOut) => Err(deadline.error()),
+        }
+    }
+
     // ==================== Common Methods ====================
 
     /// Health check - returns true if provider is operational
@@ -185,6 +262,11 @@ pub trait Provider: Send + Sync {
     /// Get provider display name
     fn display_name(&self) -> &str;
 
+    /// Model used when a request doesn't name one, if the provider is configured with one
+    fn default_model(&self) -> Option<&str> {
+        None
+    }
+
     /// Get provider capabilities (new in Phase 17.5 Task 5)
     ///
     /// Returns information about what features this provider supports,
@@ -197,10 +279,18 @@ pub trait Provider: Send + Sync {
             supports_streaming: false,
             supports_tools: false,
             supports_system_prompt: true, // Most providers support this
+            supports_vision: false,
             max_tokens: 4096,
             max_context_length: 8192,
         }
     }
+
+    /// Models this provider can serve, for model pickers
+    ///
+    /// Default implementation lists only the configured default model.
+    async fn list_models(&self) -> Result<Vec<types::ModelInfo>, anyhow::Error> {
+        Ok(self.default_model().map(types::ModelInfo::new).into_iter().collect())
+    }
 }
 
 /// Provider configuration loaded from Codex entry
@@ -208,12 +298,18 @@ pub trait Provider: Send + Sync {
 pub struct ProviderConfig {
     /// Codex ID of the provider configuration
     pub codex_id: Uuid,
-    /// Provider type (claude-code-cli, ollama, etc.)
+    /// Provider type (claude-code-cli, ollama, openai-compatible, anthropic)
     pub provider_type: String,
     /// Provider-specific configuration
     pub config: serde_json::Value,
 }
 
 pub use manager::ProviderManager;
+pub use control::RequestControl;
+pub use prompts::{PromptExample, PromptLibrary, PromptTemplate, PromptVariable};
+pub use cache::{PromptEmbedder, ResponseCache, ResponseCacheConfig, ResponseCacheStats};
+pub use supervisor::{ProviderHealth, ProviderStatus, SupervisorConfig};
 pub use claude_code::ClaudeCodeProvider;
 pub use ollama::OllamaProvider;
+pub use openai_compatible::OpenAiCompatibleProvider;
+pub use anthropic::AnthropicProvider;
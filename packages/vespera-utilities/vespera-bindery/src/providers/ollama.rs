@@ -8,8 +8,17 @@
 // Streaming response format: Newline-delimited JSON
 // Each line: {"model":"...","response":"...", "done":false}
 // Final line: {"done":true}
-
+//
+// Structured chat requests use POST /api/chat instead, which supports native
+// tool calling: tools go in `tools`, and calls come back complete in
+// `message.tool_calls` (without IDs, so IDs are generated here).
+
+use super::types::{
+    ChatChunk, ChatRequest, ChatResponse, ChatRole, FinishReason, ModelInfo, ResponseMetadata, StreamingResponse,
+    ToolCall, UsageStats,
+};
 use super::{Provider, ProviderResponse, ProviderUsage, StreamChunk};
+use crate::observability::trace_headers;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
@@ -97,11 +106,177 @@ impl OllamaProvider {
         }
     }
 
+    /// Build request payload for the /api/chat endpoint
+    fn build_chat_payload(&self, request: &ChatRequest, model: &str, stream: bool) -> OllamaChatRequest {
+        let mut options = HashMap::new();
+        if let Some(temp) = request.temperature.or(self.config.temperature) {
+            options.insert("temperature".to_string(), serde_json::json!(temp));
+        }
+        if let Some(max_tokens) = request.max_tokens.map(|n| n as usize).or(self.config.max_tokens) {
+            options.insert("num_predict".to_string(), serde_json::json!(max_tokens));
+        }
+        if let Some(stop) = &request.stop_sequences {
+            options.insert("stop".to_string(), serde_json::json!(stop));
+        }
+
+        let mut messages = Vec::with_capacity(request.messages.len() + 1);
+        if let Some(system) = request.system_prompt.as_deref().or(self.config.system_prompt.as_deref()) {
+            messages.push(OllamaChatMessage {
+                role: "system".to_string(),
+                content: system.to_string(),
+                tool_calls: Vec::new(),
+                tool_name: None,
+            });
+        }
+        messages.extend(request.messages.iter().map(|message| OllamaChatMessage {
+            role: match message.role {
+                ChatRole::System => "system",
+                ChatRole::User => "user",
+                ChatRole::Assistant => "assistant",
+                ChatRole::Tool => "tool",
+            }
+            .to_string(),
+            content: message.content.clone(),
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(|call| OllamaToolCall {
+                    id: None,
+                    function: OllamaFunctionCall {
+                        name: call.name.clone(),
+                        arguments: call.arguments_json(),
+                    },
+                })
+                .collect(),
+            // Ollama matches tool results to calls by name and order
+            tool_name: message.name.clone().filter(|_| message.role == ChatRole::Tool),
+        }));
+
+        OllamaChatRequest {
+            model: model.to_string(),
+            messages,
+            tools: request
+                .tools
+                .iter()
+                .map(|tool| OllamaTool {
+                    kind: "function",
+                    function: OllamaFunction {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.parameters.clone(),
+                    },
+                })
+                .collect(),
+            stream,
+            options: if options.is_empty() { None } else { Some(options) },
+        }
+    }
+
+    /// Send a chat request to /api/chat and wait for the full response
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let url = format!("{}/api/chat", self.config.base_url);
+        let payload = self.build_chat_payload(request, &self.config.model, false);
+        let response = self
+            .client
+            .post(&url)
+            .headers(trace_headers())
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send chat request to Ollama")?;
+
+        let chat: OllamaChatResponse = response
+            .error_for_status()
+            .context("Ollama rejected chat request")?
+            .json()
+            .await
+            .context("Failed to parse Ollama chat response")?;
+
+        let tool_calls = chat.message.tool_calls.into_iter().map(OllamaToolCall::into_tool_call).collect::<Result<Vec<_>>>()?;
+        Ok(ChatResponse {
+            content: chat.message.content,
+            finish_reason: chat_finish_reason(chat.done_reason.as_deref(), !tool_calls.is_empty()),
+            tool_calls,
+            usage: chat_usage(chat.prompt_eval_count, chat.eval_count),
+        })
+    }
+
+    /// Send a chat request to /api/chat and stream the response
+    async fn chat_stream(&self, request: &ChatRequest) -> Result<StreamingResponse> {
+        let url = format!("{}/api/chat", self.config.base_url);
+        let payload = self.build_chat_payload(request, &self.config.model, true);
+        let response = self
+            .client
+            .post(&url)
+            .headers(trace_headers())
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send streaming chat request to Ollama")?
+            .error_for_status()
+            .context("Ollama rejected chat request")?;
+
+        let mut lines = ndjson_lines(response);
+        let stream = async_stream::stream! {
+            let mut called_tools = false;
+            loop {
+                let line = match lines.next_line().await {
+                    Ok(Some(line)) if line.trim().is_empty() => continue,
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(e) => {
+                        yield Err(e.into());
+                        break;
+                    }
+                };
+
+                let chunk = match serde_json::from_str::<OllamaChatResponse>(&line) {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        yield Err(anyhow!("Failed to parse stream chunk: {}", e));
+                        break;
+                    }
+                };
+                let tool_calls = match chunk.message.tool_calls.into_iter().map(OllamaToolCall::into_tool_call).collect::<Result<Vec<_>>>() {
+                    Ok(tool_calls) => tool_calls,
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                };
+                called_tools |= !tool_calls.is_empty();
+
+                let mut out = ChatChunk::text(chunk.message.content);
+                out.tool_calls = tool_calls;
+                if chunk.done {
+                    out.finish_reason = Some(chat_finish_reason(chunk.done_reason.as_deref(), called_tools));
+                    out.usage = Some(chat_usage(chunk.prompt_eval_count, chunk.eval_count));
+                }
+                if !out.delta.is_empty() || !out.tool_calls.is_empty() || out.is_final() {
+                    yield Ok(out);
+                }
+                if chunk.done {
+                    break;
+                }
+            }
+        };
+
+        Ok(StreamingResponse {
+            stream: Box::pin(stream),
+            metadata: ResponseMetadata {
+                model: self.config.model.clone(),
+                provider: self.provider_type().to_string(),
+                request_id: None,
+            },
+        })
+    }
+
     /// Process non-streaming response from Ollama
     async fn process_response(&self, url: String, payload: OllamaRequest) -> Result<ProviderResponse> {
         let response = self
             .client
             .post(&url)
+            .headers(trace_headers())
             .json(&payload)
             .send()
             .await
@@ -142,18 +317,13 @@ impl OllamaProvider {
         let response = self
             .client
             .post(&url)
+            .headers(trace_headers())
             .json(&payload)
             .send()
             .await
             .context("Failed to send streaming request to Ollama")?;
 
-        use futures::StreamExt as FuturesStreamExt;
-        let stream = response.bytes_stream();
-        let reader = tokio_util::io::StreamReader::new(stream.map(|result| {
-            result.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-        }));
-
-        let mut lines = tokio::io::BufReader::new(reader).lines();
+        let mut lines = ndjson_lines(response);
 
         let stream = async_stream::stream! {
             loop {
@@ -234,13 +404,27 @@ impl Provider for OllamaProvider {
         self.process_stream_response(url, payload).await
     }
 
+    async fn send_chat_request(&self, request: ChatRequest, _session_id: Option<&str>) -> Result<ChatResponse> {
+        info!("Sending chat request to Ollama (model: {}, tools: {})", self.config.model, request.tools.len());
+        self.chat(&request).await
+    }
+
+    async fn send_chat_request_stream(
+        &self,
+        request: ChatRequest,
+        _session_id: Option<&str>,
+    ) -> Result<StreamingResponse> {
+        info!("Sending chat request to Ollama with streaming (model: {}, tools: {})", self.config.model, request.tools.len());
+        self.chat_stream(&request).await
+    }
+
     async fn health_check(&self) -> Result<bool> {
         debug!("Performing health check on Ollama");
 
         // Check if Ollama server is running by hitting /api/tags endpoint
         let url = format!("{}/api/tags", self.config.base_url);
 
-        match self.client.get(&url).send().await {
+        match self.client.get(&url).headers(trace_headers()).send().await {
             Ok(response) => {
                 if response.status().is_success() {
                     info!("Ollama health check passed");
@@ -265,17 +449,82 @@ impl Provider for OllamaProvider {
         "Ollama Local"
     }
 
+    fn default_model(&self) -> Option<&str> {
+        Some(&self.config.model)
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}/api/tags", self.config.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .headers(trace_headers())
+            .send()
+            .await
+            .context("Failed to list Ollama models")?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Ollama returned HTTP {} listing models", response.status()));
+        }
+
+        let tags: OllamaTags = response.json().await.context("Failed to parse Ollama model list")?;
+        Ok(tags
+            .models
+            .into_iter()
+            .map(|model| {
+                let info = ModelInfo::new(model.name);
+                match model.details.and_then(|d| d.parameter_size) {
+                    Some(size) => {
+                        let display_name = format!("{} ({})", info.id, size);
+                        info.with_display_name(display_name)
+                    }
+                    None => info,
+                }
+            })
+            .collect())
+    }
+
     fn capabilities(&self) -> crate::providers::types::ProviderCapabilities {
         crate::providers::types::ProviderCapabilities {
             supports_streaming: true,  // Ollama API supports streaming via newline-delimited JSON
-            supports_tools: false,      // Ollama doesn't support tool calling (model-dependent)
+            supports_tools: true,       // Native via /api/chat; needs a model trained for tools (llama3.1+, qwen2.5, ...)
             supports_system_prompt: true,
+            supports_vision: false,     // Model-dependent (llava, ...), but chat messages are text only
             max_tokens: self.config.max_tokens.unwrap_or(2048) as u32,
             max_context_length: self.config.context_window.unwrap_or(4096) as u32,
         }
     }
 }
 
+/// Read a newline-delimited JSON response body line by line
+fn ndjson_lines(
+    response: reqwest::Response,
+) -> tokio::io::Lines<tokio::io::BufReader<impl tokio::io::AsyncRead + Send + Unpin>> {
+    let reader = tokio_util::io::StreamReader::new(
+        response
+            .bytes_stream()
+            .map(|result| result.map_err(std::io::Error::other)),
+    );
+    tokio::io::BufReader::new(reader).lines()
+}
+
+fn chat_finish_reason(done_reason: Option<&str>, called_tools: bool) -> FinishReason {
+    match done_reason {
+        _ if called_tools => FinishReason::ToolCalls,
+        Some("length") => FinishReason::Length,
+        _ => FinishReason::Stop,
+    }
+}
+
+fn chat_usage(prompt_eval_count: Option<usize>, eval_count: Option<usize>) -> UsageStats {
+    let prompt_tokens = prompt_eval_count.unwrap_or(0) as u32;
+    let completion_tokens = eval_count.unwrap_or(0) as u32;
+    UsageStats {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+    }
+}
+
 // Ollama API request/response types
 
 #[derive(Debug, Clone, Serialize)]
@@ -307,3 +556,254 @@ struct OllamaStreamChunk {
     #[serde(flatten)]
     extra: HashMap<String, serde_json::Value>,
 }
+
+#[derive(Debug, Deserialize)]
+struct OllamaTags {
+    #[serde(default)]
+    models: Vec<OllamaTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTag {
+    name: String,
+    #[serde(default)]
+    details: Option<OllamaTagDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagDetails {
+    #[serde(default)]
+    parameter_size: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaChatMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OllamaTool>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OllamaChatMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OllamaToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OllamaTool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: OllamaFunction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OllamaFunction {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OllamaToolCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    function: OllamaFunctionCall,
+}
+
+impl OllamaToolCall {
+    fn into_tool_call(self) -> Result<ToolCall> {
+        let id = self.id.unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+        ToolCall::from_json(id, self.function.name, self.function.arguments)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OllamaChatResponse {
+    message: OllamaChatMessage,
+    done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<usize>,
+    #[serde(default)]
+    eval_count: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::types::{simple_params_to_chat_request, ChatMessage, ToolDefinition};
+    use axum::{routing::post, Json, Router};
+    use crate::providers::RequestControl;
+    use futures::TryStreamExt;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    async fn chat(Json(body): Json<Value>) -> String {
+        // The tool result must come back with the name of the tool that produced it
+        let last = body["messages"].as_array().unwrap().last().unwrap().clone();
+        let lines = if last["role"] == "tool" {
+            vec![
+                json!({"message": {"role": "assistant", "content": format!("{} says {}", last["tool_name"], last["content"])}, "done": false}),
+                json!({"message": {"role": "assistant", "content": ""}, "done": true, "done_reason": "stop", "prompt_eval_count": 30, "eval_count": 6}),
+            ]
+        } else {
+            assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+            vec![
+                json!({"message": {"role": "assistant", "content": "", "tool_calls": [{"function": {"name": "get_weather", "arguments": {"city": "Oslo"}}}]}, "done": false}),
+                json!({"message": {"role": "assistant", "content": ""}, "done": true, "done_reason": "stop", "prompt_eval_count": 20, "eval_count": 9}),
+            ]
+        };
+        if body["stream"] == json!(true) {
+            lines.iter().map(|line| format!("{}\n", line)).collect()
+        } else {
+            // Non-streaming responses carry everything in one object
+            let mut response = lines[1].clone();
+            response["message"] = lines[0]["message"].clone();
+            response.to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_tool_calls_via_chat_endpoint() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, Router::new().route("/api/chat", post(chat))).await.unwrap() });
+        let provider = OllamaProvider::new(OllamaConfig {
+            base_url: format!("http://{}", addr),
+            model: "llama3.1".to_string(),
+            ..OllamaConfig::default()
+        });
+
+        let mut request = simple_params_to_chat_request("Weather in Oslo?", None, None, None);
+        request.tools = vec![ToolDefinition {
+            name: "get_weather".to_string(),
+            description: "Current weather for a city".to_string(),
+            parameters: json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        }];
+
+        let chunks: Vec<ChatChunk> = provider
+            .send_chat_request_stream(request.clone(), None)
+            .await
+            .unwrap()
+            .stream
+            .try_collect()
+            .await
+            .unwrap();
+        let calls: Vec<ToolCall> = chunks.iter().flat_map(|c| c.tool_calls.clone()).collect();
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].id.starts_with("call_"));
+        assert_eq!(calls[0].arguments["city"], "Oslo");
+        let last = chunks.last().unwrap();
+        assert_eq!(last.finish_reason, Some(FinishReason::ToolCalls));
+        assert_eq!(last.usage.as_ref().unwrap().total_tokens, 29);
+
+        request.messages.push(ChatMessage::assistant_tool_calls("", calls.clone()));
+        request.messages.push(ChatMessage::tool_result(&calls[0], "12C"));
+        let response = provider.send_chat_request(request, None).await.unwrap();
+        assert_eq!(response.content, r#""get_weather" says "12C""#);
+        assert_eq!(response.finish_reason, FinishReason::Stop);
+        assert!(response.tool_calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_models_from_tags() {
+        let tags = json!({"models": [
+            {"name": "llama3.2:latest", "details": {"family": "llama", "parameter_size": "3.2B"}},
+            {"name": "nomic-embed-text:latest"},
+        ]});
+        let app = Router::new().route("/api/tags", axum::routing::get(move || async move { Json(tags) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let provider = OllamaProvider::new(OllamaConfig {
+            base_url: format!("http://{}", addr),
+            ..OllamaConfig::default()
+        });
+
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(
+            models,
+            vec![
+                ModelInfo::new("llama3.2:latest").with_display_name("llama3.2:latest (3.2B)"),
+                ModelInfo::new("nomic-embed-text:latest"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_the_request() {
+        // Sends one chunk, then stalls; notes when the client goes away
+        struct Disconnected(Arc<AtomicBool>);
+        impl Drop for Disconnected {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+        let disconnected = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&disconnected);
+        let app = Router::new().route(
+            "/api/chat",
+            post(move || {
+                let guard = Disconnected(Arc::clone(&flag));
+                async move {
+                    let first = json!({"message": {"role": "assistant", "content": "Hel"}, "done": false});
+                    let body = futures::stream::once(async move { Ok::<_, std::io::Error>(format!("{}\n", first)) })
+                        .chain(futures::stream::once(async move {
+                            let _guard = guard;
+                            std::future::pending::<Result<String, std::io::Error>>().await
+                        }));
+                    axum::body::Body::from_stream(body)
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let provider = OllamaProvider::new(OllamaConfig {
+            base_url: format!("http://{}", addr),
+            ..OllamaConfig::default()
+        });
+
+        let control = RequestControl::new();
+        let request = simple_params_to_chat_request("Hello", None, None, None);
+        let mut stream = provider
+            .send_chat_request_stream_with_control(request, None, control.clone())
+            .await
+            .unwrap()
+            .stream;
+        assert_eq!(stream.next().await.unwrap().unwrap().delta, "Hel");
+
+        control.cancel();
+        let last = tokio::time::timeout(Duration::from_secs(1), stream.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(last.finish_reason, Some(FinishReason::Cancelled));
+        assert!(stream.next().await.is_none());
+
+        // Dropping the response closes the connection
+        for _ in 0..50 {
+            if disconnected.load(Ordering::SeqCst) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("the HTTP request should have been aborted");
+    }
+}
@@ -0,0 +1,742 @@
+// OpenAI-Compatible REST Provider
+//
+// Talks to any server implementing the OpenAI Chat Completions API:
+// OpenAI itself, OpenRouter, vLLM, LM Studio, llama.cpp server, etc.
+//
+// Endpoint: POST {base_url}/chat/completions
+// Protocol: HTTP JSON, Bearer token auth (optional for local servers)
+//
+// Streaming response format: Server-Sent Events
+// Each event: data: {"choices":[{"delta":{"content":"..."},"finish_reason":null}]}
+// Usage (if the server supports stream_options): data: {"choices":[],"usage":{...}}
+// Final event: data: [DONE]
+//
+// Tool calls use the `tools` / `tool_calls` fields; streamed tool call
+// arguments arrive in fragments and are assembled before being surfaced.
+
+use super::rest::{check_status, data_events};
+use super::types::{
+    chat_chunk_to_stream_chunk, chat_response_to_provider_response, simple_params_to_chat_request, ChatChunk,
+    ChatRequest, ChatResponse, ChatRole, FinishReason, ModelInfo, ProviderCapabilities, ResponseMetadata,
+    StreamingResponse, ToolCall, UsageStats,
+};
+use super::{Provider, ProviderResponse, StreamChunk};
+use crate::observability::trace_headers;
+use crate::secrets::SecretRef;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{debug, info, warn};
+
+const API_NAME: &str = "OpenAI-compatible API";
+
+/// OpenAI-compatible provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiCompatibleConfig {
+    /// API base URL including the version prefix (e.g., https://api.openai.com/v1)
+    pub base_url: String,
+    /// Model name (e.g., gpt-4o-mini, meta-llama/llama-3.1-70b-instruct)
+    pub model: String,
+    /// Secret reference for the API key (e.g., secret://openai/api_key); None for servers without auth
+    pub api_key: Option<SecretRef>,
+    /// Temperature (0.0-2.0)
+    pub temperature: Option<f32>,
+    /// Max tokens to generate
+    pub max_tokens: Option<usize>,
+    /// System prompt
+    pub system_prompt: Option<String>,
+    /// Context window size
+    pub context_window: Option<usize>,
+    /// Request timeout in seconds
+    pub timeout: Option<u64>,
+}
+
+impl Default for OpenAiCompatibleConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://api.openai.com/v1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            api_key: None,
+            temperature: Some(0.7),
+            max_tokens: Some(4096),
+            system_prompt: None,
+            context_window: Some(128_000),
+            timeout: Some(120),
+        }
+    }
+}
+
+/// OpenAI-compatible provider implementation
+pub struct OpenAiCompatibleProvider {
+    config: OpenAiCompatibleConfig,
+    /// Resolved API key (never the vault reference)
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OpenAiCompatibleProvider {
+    /// Create a new provider; `api_key` is the already-resolved key, if the server needs one
+    pub fn new(config: OpenAiCompatibleConfig, api_key: Option<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout.unwrap_or(120)))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self { config, api_key, client }
+    }
+
+    fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        self.authorize(self.client.post(format!("{}{}", self.config.base_url.trim_end_matches('/'), path)))
+    }
+
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let builder = builder.headers(trace_headers());
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    /// Build request payload for the Chat Completions API
+    fn build_request_payload(&self, request: &ChatRequest, model: &str, stream: bool) -> OpenAiRequest {
+        let mut messages = Vec::with_capacity(request.messages.len() + 1);
+        if let Some(system) = request.system_prompt.as_deref().or(self.config.system_prompt.as_deref()) {
+            messages.push(OpenAiMessage {
+                role: "system",
+                content: Some(system.to_string()),
+                name: None,
+                tool_call_id: None,
+                tool_calls: Vec::new(),
+            });
+        }
+        messages.extend(request.messages.iter().map(|message| OpenAiMessage {
+            role: match message.role {
+                ChatRole::System => "system",
+                ChatRole::User => "user",
+                ChatRole::Assistant => "assistant",
+                ChatRole::Tool => "tool",
+            },
+            // Assistant messages that only call tools have no content
+            content: (!message.content.is_empty() || message.tool_calls.is_empty()).then(|| message.content.clone()),
+            // Tool results are identified by tool_call_id; `name` isn't allowed on them
+            name: message.name.clone().filter(|_| message.role != ChatRole::Tool),
+            tool_call_id: message.tool_call_id.clone(),
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(|call| OpenAiToolCall {
+                    id: call.id.clone(),
+                    kind: "function".to_string(),
+                    function: OpenAiFunctionCall {
+                        name: call.name.clone(),
+                        arguments: call.arguments_json().to_string(),
+                    },
+                })
+                .collect(),
+        }));
+
+        OpenAiRequest {
+            model: model.to_string(),
+            messages,
+            max_tokens: request.max_tokens.or(self.config.max_tokens.map(|n| n as u32)),
+            temperature: request.temperature.or(self.config.temperature),
+            stop: request.stop_sequences.clone(),
+            tools: request
+                .tools
+                .iter()
+                .map(|tool| OpenAiTool {
+                    kind: "function",
+                    function: OpenAiFunction {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.parameters.clone(),
+                    },
+                })
+                .collect(),
+            stream,
+            stream_options: stream.then_some(StreamOptions { include_usage: true }),
+        }
+    }
+
+    /// Send a chat request and wait for the full response
+    async fn chat(&self, request: &ChatRequest, model: &str) -> Result<ChatResponse> {
+        let payload = self.build_request_payload(request, model, false);
+        let response = self
+            .post("/chat/completions")
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send request to OpenAI-compatible API")?;
+
+        let completion: OpenAiResponse = check_status(response, API_NAME)
+            .await?
+            .json()
+            .await
+            .context("Failed to parse OpenAI-compatible response")?;
+
+        let choice = completion
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("OpenAI-compatible response contained no choices"))?;
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(|call| ToolCall::from_json(call.id, call.function.name, serde_json::Value::String(call.function.arguments)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ChatResponse {
+            content: choice.message.content.unwrap_or_default(),
+            finish_reason: finish_reason(choice.finish_reason.as_deref()),
+            tool_calls,
+            usage: completion.usage.map(OpenAiUsage::into_stats).unwrap_or_else(empty_usage),
+        })
+    }
+
+    /// Send a chat request and stream the response
+    async fn chat_stream(&self, request: &ChatRequest, model: &str) -> Result<StreamingResponse> {
+        let payload = self.build_request_payload(request, model, true);
+        let response = self
+            .post("/chat/completions")
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send streaming request to OpenAI-compatible API")?;
+        let events = data_events(check_status(response, API_NAME).await?);
+
+        let stream = async_stream::stream! {
+            futures::pin_mut!(events);
+            let mut finish: Option<FinishReason> = None;
+            let mut usage: Option<UsageStats> = None;
+            let mut tool_calls: BTreeMap<usize, PartialToolCall> = BTreeMap::new();
+
+            while let Some(event) = events.next().await {
+                let data = match event {
+                    Ok(data) => data,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                if data == "[DONE]" {
+                    break;
+                }
+
+                match serde_json::from_str::<OpenAiStreamChunk>(&data) {
+                    Ok(chunk) => {
+                        if let Some(chunk_usage) = chunk.usage {
+                            usage = Some(chunk_usage.into_stats());
+                        }
+                        for choice in chunk.choices {
+                            if let Some(content) = choice.delta.content.filter(|c| !c.is_empty()) {
+                                yield Ok(ChatChunk::text(content));
+                            }
+                            for fragment in choice.delta.tool_calls {
+                                let call = tool_calls.entry(fragment.index).or_default();
+                                if let Some(id) = fragment.id {
+                                    call.id = id;
+                                }
+                                if let Some(function) = fragment.function {
+                                    if let Some(name) = function.name {
+                                        call.name.push_str(&name);
+                                    }
+                                    if let Some(arguments) = function.arguments {
+                                        call.arguments.push_str(&arguments);
+                                    }
+                                }
+                            }
+                            if let Some(reason) = choice.finish_reason {
+                                finish = Some(finish_reason(Some(&reason)));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        yield Err(anyhow!("Failed to parse stream chunk: {}", e));
+                        return;
+                    }
+                }
+            }
+
+            let tool_calls = match tool_calls
+                .into_values()
+                .map(|call| ToolCall::from_json(call.id, call.name, serde_json::Value::String(call.arguments)))
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(tool_calls) => tool_calls,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+            let mut last = ChatChunk::finish(finish.unwrap_or(FinishReason::Stop), usage.unwrap_or_else(empty_usage));
+            last.tool_calls = tool_calls;
+            yield Ok(last);
+        };
+
+        Ok(StreamingResponse {
+            stream: Box::pin(stream),
+            metadata: ResponseMetadata {
+                model: model.to_string(),
+                provider: self.provider_type().to_string(),
+                request_id: None,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiCompatibleProvider {
+    async fn send_message(
+        &self,
+        message: &str,
+        model: Option<&str>,
+        _session_id: Option<&str>,  // Chat Completions is stateless
+        system_prompt: Option<&str>,
+        stream: bool,
+    ) -> Result<ProviderResponse> {
+        let model_to_use = model.unwrap_or(&self.config.model);
+        info!("Sending message to OpenAI-compatible API (model: {})", model_to_use);
+        if stream {
+            warn!("Streaming requested but send_message doesn't support it, use send_message_stream");
+        }
+
+        let request = simple_params_to_chat_request(message, system_prompt, None, None);
+        let response = self.chat(&request, model_to_use).await?;
+
+        info!("Received response from OpenAI-compatible API: {} characters", response.content.len());
+
+        let mut response = chat_response_to_provider_response(response, None);
+        response.metadata.insert("model".to_string(), serde_json::json!(model_to_use));
+        Ok(response)
+    }
+
+    async fn send_message_stream(
+        &self,
+        message: &str,
+        model: Option<&str>,
+        _session_id: Option<&str>,  // Chat Completions is stateless
+        system_prompt: Option<&str>,
+    ) -> Result<Box<dyn Stream<Item = Result<StreamChunk>> + Unpin + Send>> {
+        let model_to_use = model.unwrap_or(&self.config.model);
+        info!("Sending message to OpenAI-compatible API with streaming (model: {})", model_to_use);
+
+        let request = simple_params_to_chat_request(message, system_prompt, None, None);
+        let response = self.chat_stream(&request, model_to_use).await?;
+        let provider_type = self.provider_type().to_string();
+
+        Ok(Box::new(response.stream.map(move |chunk| {
+            chunk.map(|chunk| chat_chunk_to_stream_chunk(chunk, &provider_type))
+        })))
+    }
+
+    async fn send_chat_request(&self, request: ChatRequest, _session_id: Option<&str>) -> Result<ChatResponse> {
+        self.chat(&request, &self.config.model).await
+    }
+
+    async fn send_chat_request_stream(
+        &self,
+        request: ChatRequest,
+        _session_id: Option<&str>,
+    ) -> Result<StreamingResponse> {
+        self.chat_stream(&request, &self.config.model).await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        debug!("Performing health check on OpenAI-compatible API");
+
+        // Listing models is cheap and checks both reachability and the API key
+        let url = format!("{}/models", self.config.base_url.trim_end_matches('/'));
+
+        match self.authorize(self.client.get(&url)).send().await {
+            Ok(response) if response.status().is_success() => {
+                info!("OpenAI-compatible API health check passed");
+                Ok(true)
+            }
+            Ok(response) => {
+                warn!("OpenAI-compatible API health check failed: HTTP {}", response.status());
+                Ok(false)
+            }
+            Err(e) => {
+                warn!("OpenAI-compatible API health check failed: {}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    fn provider_type(&self) -> &str {
+        "openai-compatible"
+    }
+
+    fn display_name(&self) -> &str {
+        "OpenAI-Compatible API"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        Some(&self.config.model)
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}/models", self.config.base_url.trim_end_matches('/'));
+        let response = self
+            .authorize(self.client.get(&url))
+            .send()
+            .await
+            .context("Failed to list models")?;
+        let models: OpenAiModelList = check_status(response, "OpenAI-compatible API")
+            .await?
+            .json()
+            .await
+            .context("Failed to parse model list")?;
+        Ok(models.data.into_iter().map(|model| ModelInfo::new(model.id)).collect())
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supports_streaming: true,
+            supports_tools: true,
+            supports_system_prompt: true,
+            supports_vision: true,
+            max_tokens: self.config.max_tokens.unwrap_or(4096) as u32,
+            max_context_length: self.config.context_window.unwrap_or(128_000) as u32,
+        }
+    }
+}
+
+fn finish_reason(reason: Option<&str>) -> FinishReason {
+    match reason {
+        Some("length") => FinishReason::Length,
+        Some("tool_calls") | Some("function_call") => FinishReason::ToolCalls,
+        Some("content_filter") => FinishReason::ContentFilter,
+        _ => FinishReason::Stop,
+    }
+}
+
+fn empty_usage() -> UsageStats {
+    UsageStats {
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        total_tokens: 0,
+    }
+}
+
+// Chat Completions API request/response types
+
+#[derive(Debug, Clone, Serialize)]
+struct OpenAiRequest {
+    model: String,
+    messages: Vec<OpenAiMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAiTool>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OpenAiMessage {
+    role: &'static str,
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAiToolCall>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OpenAiTool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: OpenAiFunction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct OpenAiFunction {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenAiToolCall {
+    id: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    function: OpenAiFunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenAiFunctionCall {
+    name: String,
+    /// JSON-encoded arguments object
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiResponse {
+    choices: Vec<OpenAiChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiChoice {
+    message: OpenAiResponseMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiResponseMessage {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAiToolCall>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiStreamChunk {
+    #[serde(default)]
+    choices: Vec<OpenAiStreamChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiStreamChoice {
+    delta: OpenAiDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiDelta {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAiToolCallFragment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiToolCallFragment {
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<OpenAiFunctionFragment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiFunctionFragment {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+/// A streamed tool call being assembled from fragments
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiModelList {
+    #[serde(default)]
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiModel {
+    id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAiUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+    #[serde(default)]
+    total_tokens: u32,
+}
+
+impl OpenAiUsage {
+    fn into_stats(self) -> UsageStats {
+        UsageStats {
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+            total_tokens: self.total_tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::types::ChatMessage;
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use futures::TryStreamExt;
+    use serde_json::{json, Value};
+
+    /// Serve a canned Chat Completions endpoint that echoes what it received in the reply
+    async fn spawn_server() -> String {
+        async fn completions(headers: HeaderMap, Json(body): Json<Value>) -> axum::response::Response {
+            use axum::response::IntoResponse;
+
+            let auth = headers.get("authorization").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+            let system = body["messages"][0]["content"].as_str().unwrap_or("").to_string();
+            let has_tools = body["tools"].as_array().is_some_and(|tools| !tools.is_empty());
+            if has_tools && body["stream"] == json!(true) {
+                // Arguments split across fragments, as real servers send them
+                let sse: String = [
+                    json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": ""}}]}, "finish_reason": null}]}),
+                    json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": "{\"city\":"}}]}, "finish_reason": null}]}),
+                    json!({"choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": "\"Oslo\"}"}}]}, "finish_reason": "tool_calls"}]}),
+                ]
+                .iter()
+                .map(|event| format!("data: {}\n\n", event))
+                .collect();
+                ([("content-type", "text/event-stream")], sse + "data: [DONE]\n\n").into_response()
+            } else if has_tools {
+                // Report back how the previous tool round trip was encoded
+                let history = body["messages"].as_array().unwrap();
+                let echoed = history
+                    .iter()
+                    .filter(|m| m["role"] == "tool" || m.get("tool_calls").is_some())
+                    .map(|m| format!("{}:{}:{}", m["role"], m["tool_call_id"], m["tool_calls"][0]["function"]["arguments"]))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Json(json!({
+                    "choices": [{
+                        "message": {
+                            "role": "assistant",
+                            "content": echoed,
+                            "tool_calls": [{"id": "call_2", "type": "function", "function": {"name": body["tools"][0]["function"]["name"], "arguments": "{\"city\":\"Bergen\"}"}}]
+                        },
+                        "finish_reason": "tool_calls"
+                    }]
+                }))
+                .into_response()
+            } else if body["stream"] == json!(true) {
+                let sse = [
+                    json!({"choices": [{"delta": {"role": "assistant"}, "finish_reason": null}]}),
+                    json!({"choices": [{"delta": {"content": "Hel"}, "finish_reason": null}]}),
+                    json!({"choices": [{"delta": {"content": "lo"}, "finish_reason": "length"}]}),
+                    json!({"choices": [], "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5}}),
+                ]
+                .iter()
+                .map(|event| format!("data: {}\n\n", event))
+                .collect::<String>()
+                    + "data: [DONE]\n\n";
+                ([("content-type", "text/event-stream")], sse).into_response()
+            } else {
+                Json(json!({
+                    "choices": [{"message": {"role": "assistant", "content": format!("{}|{}", auth, system)}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 7, "completion_tokens": 4, "total_tokens": 11}
+                }))
+                .into_response()
+            }
+        }
+
+        let app = Router::new().route("/v1/chat/completions", post(completions));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/v1", addr)
+    }
+
+    #[tokio::test]
+    async fn test_chat_and_stream_against_compatible_server() {
+        let base_url = spawn_server().await;
+        let provider = OpenAiCompatibleProvider::new(
+            OpenAiCompatibleConfig {
+                base_url,
+                system_prompt: Some("be brief".to_string()),
+                ..OpenAiCompatibleConfig::default()
+            },
+            Some("sk-test".to_string()),
+        );
+
+        let response = provider.send_message("hi", None, None, None, false).await.unwrap();
+        assert_eq!(response.text, "Bearer sk-test|be brief");
+        assert_eq!(response.usage.unwrap().output_tokens, 4);
+        assert_eq!(response.metadata["model"], "gpt-4o-mini");
+
+        let chunks: Vec<ChatChunk> = provider
+            .send_chat_request_stream(simple_params_to_chat_request("hi", None, None, None), None)
+            .await
+            .unwrap()
+            .stream
+            .try_collect()
+            .await
+            .unwrap();
+        let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+        assert_eq!(text, "Hello");
+        let last = chunks.last().unwrap();
+        assert_eq!(last.finish_reason, Some(FinishReason::Length));
+        assert_eq!(last.usage.as_ref().unwrap().total_tokens, 5);
+        assert_eq!(chunks.iter().filter(|c| c.is_final()).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_native_tool_calls() {
+        let provider = OpenAiCompatibleProvider::new(
+            OpenAiCompatibleConfig {
+                base_url: spawn_server().await,
+                ..OpenAiCompatibleConfig::default()
+            },
+            None,
+        );
+        let weather = crate::providers::types::ToolDefinition {
+            name: "get_weather".to_string(),
+            description: "Current weather for a city".to_string(),
+            parameters: json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        };
+        let mut request = simple_params_to_chat_request("Weather in Oslo?", None, None, None);
+        request.tools = vec![weather];
+
+        // Streaming: the fragmented call is assembled into the final chunk
+        let chunks: Vec<ChatChunk> = provider
+            .send_chat_request_stream(request.clone(), None)
+            .await
+            .unwrap()
+            .stream
+            .try_collect()
+            .await
+            .unwrap();
+        let last = chunks.last().unwrap();
+        assert_eq!(last.finish_reason, Some(FinishReason::ToolCalls));
+        assert_eq!(last.tool_calls.len(), 1);
+        let call = last.tool_calls[0].clone();
+        assert_eq!((call.id.as_str(), call.name.as_str()), ("call_1", "get_weather"));
+        assert_eq!(call.arguments["city"], "Oslo");
+
+        // Sending the result back round-trips the call in OpenAI's encoding
+        request.messages.push(ChatMessage::assistant_tool_calls("", vec![call.clone()]));
+        request.messages.push(ChatMessage::tool_result(&call, "12C, cloudy"));
+        let response = provider.send_chat_request(request, None).await.unwrap();
+        assert_eq!(response.content, r#""assistant":null:"{\"city\":\"Oslo\"}" "tool":"call_1":null"#);
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+        assert_eq!(response.tool_calls[0].arguments["city"], "Bergen");
+    }
+}
@@ -0,0 +1,339 @@
+// Prompt Templates
+//
+// System prompts and few-shot examples live in Codices (template `prompt`)
+// instead of being hard-coded by each caller. A chat request names the
+// template in `ChatRequest::prompt`, and the ProviderManager builds the
+// request from it before routing:
+// - the rendered system prompt comes first, followed by any system prompt
+//   the request already had
+// - few-shot examples are inserted as user/assistant turns ahead of the
+//   conversation
+// - a user template, when set, wraps the last user message, which is
+//   available to it as `{{input}}`
+//
+// Text is rendered by substituting `{{variable}}` placeholders. Each save
+// creates a new version in its own Codex; requests get the latest version
+// unless they ask for a specific one.
+
+use super::types::{ChatMessage, ChatRequest, ChatRole, PromptRef};
+use crate::database::Database;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Codex template that defines a prompt
+pub const PROMPT_TEMPLATE: &str = "prompt";
+
+/// Variable the user template receives the request's last user message as
+const INPUT_VARIABLE: &str = "input";
+
+/// A versioned prompt template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    /// Assigned when the template is saved
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Wraps the last user message, available as `{{input}}`
+    #[serde(default)]
+    pub user_template: Option<String>,
+    /// Few-shot examples, rendered like the rest of the template
+    #[serde(default)]
+    pub examples: Vec<PromptExample>,
+    /// Variables the template uses
+    #[serde(default)]
+    pub variables: HashMap<String, PromptVariable>,
+}
+
+/// One example exchange shown to the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptExample {
+    pub user: String,
+    pub assistant: String,
+}
+
+/// A variable a template expects
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptVariable {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Used when the request doesn't give a value; without one the variable is required
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl PromptTemplate {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: 0,
+            description: None,
+            system_prompt: None,
+            user_template: None,
+            examples: Vec::new(),
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Build `request` from this template, with `values` for its variables
+    pub fn apply(&self, mut request: ChatRequest, values: &HashMap<String, Value>) -> Result<ChatRequest> {
+        let mut variables: HashMap<String, String> = self
+            .variables
+            .iter()
+            .filter_map(|(name, variable)| Some((name.clone(), variable.default.clone()?)))
+            .collect();
+        variables.extend(values.iter().map(|(name, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (name.clone(), value)
+        }));
+        let fill = |text: &str, variables: &HashMap<String, String>| {
+            render(text, variables).with_context(|| format!("Failed to render prompt '{}' v{}", self.name, self.version))
+        };
+
+        if let Some(system) = &self.system_prompt {
+            let system = fill(system, &variables)?;
+            request.system_prompt = Some(match request.system_prompt.take() {
+                Some(extra) => format!("{}\n\n{}", system, extra),
+                None => system,
+            });
+        }
+
+        if let Some(template) = &self.user_template {
+            let last_user = request.messages.iter().rposition(|m| m.role == ChatRole::User);
+            let input = last_user.map(|i| request.messages[i].content.clone()).unwrap_or_default();
+            let mut with_input = variables.clone();
+            with_input.insert(INPUT_VARIABLE.to_string(), input);
+            let content = fill(template, &with_input)?;
+            match last_user {
+                Some(i) => request.messages[i].content = content,
+                None => request.messages.push(ChatMessage::user(content)),
+            }
+        }
+
+        if !self.examples.is_empty() {
+            let mut examples = Vec::with_capacity(self.examples.len() * 2);
+            for example in &self.examples {
+                examples.push(ChatMessage::user(fill(&example.user, &variables)?));
+                examples.push(ChatMessage::assistant(fill(&example.assistant, &variables)?));
+            }
+            let start = request.messages.iter().position(|m| m.role != ChatRole::System).unwrap_or(request.messages.len());
+            request.messages.splice(start..start, examples);
+        }
+
+        request.prompt = None;
+        Ok(request)
+    }
+
+    fn from_codex(codex: &Value) -> Result<Self> {
+        let fields = codex
+            .get("content")
+            .and_then(|c| c.get("fields"))
+            .ok_or_else(|| anyhow!("Missing fields in prompt codex"))?;
+        serde_json::from_value(fields.clone()).context("Invalid prompt codex")
+    }
+}
+
+/// Substitute `{{variable}}` placeholders in `text`
+///
+/// Fails if a placeholder has no value; `{{{{` produces a literal `{{`.
+pub fn render(text: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut missing = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        if let Some(after_escape) = after.strip_prefix("{{") {
+            output.push_str("{{");
+            rest = after_escape;
+            continue;
+        }
+        let Some(end) = after.find("}}") else {
+            return Err(anyhow!("Unclosed placeholder in prompt template"));
+        };
+        let name = after[..end].trim();
+        match variables.get(name) {
+            Some(value) => output.push_str(value),
+            None => missing.push(name.to_string()),
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    if !missing.is_empty() {
+        return Err(anyhow!("No value for prompt variable(s): {}", missing.join(", ")));
+    }
+    Ok(output)
+}
+
+/// Prompt templates stored as Codices
+pub struct PromptLibrary {
+    database: Arc<Database>,
+}
+
+impl PromptLibrary {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self { database }
+    }
+
+    /// All versions of all templates, by name then version
+    pub async fn list(&self) -> Result<Vec<PromptTemplate>> {
+        let codices = self
+            .database
+            .list_codices_by_template(PROMPT_TEMPLATE)
+            .await
+            .context("Failed to list prompt codices")?;
+        let mut templates = Vec::with_capacity(codices.len());
+        for codex in &codices {
+            match PromptTemplate::from_codex(codex) {
+                Ok(template) => templates.push(template),
+                Err(e) => debug!("Skipping prompt codex {:?}: {:#}", codex.get("id"), e),
+            }
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+        Ok(templates)
+    }
+
+    /// A template by name, at `version` or the latest
+    pub async fn get(&self, name: &str, version: Option<u32>) -> Result<PromptTemplate> {
+        self.list()
+            .await?
+            .into_iter()
+            .filter(|t| t.name == name && version.is_none_or(|v| t.version == v))
+            .max_by_key(|t| t.version)
+            .ok_or_else(|| match version {
+                Some(v) => anyhow!("Prompt not found: {} v{}", name, v),
+                None => anyhow!("Prompt not found: {}", name),
+            })
+    }
+
+    /// Store `template` as the next version of its name
+    pub async fn save(&self, mut template: PromptTemplate) -> Result<PromptTemplate> {
+        if template.name.trim().is_empty() {
+            return Err(anyhow!("Prompt name must not be empty"));
+        }
+        template.version = self
+            .list()
+            .await?
+            .iter()
+            .filter(|t| t.name == template.name)
+            .map(|t| t.version)
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        let id = format!("prompt-{}-v{}", template.name, template.version);
+        let title = format!("{} v{}", template.name, template.version);
+        self.database
+            .create_codex(&id, &title, PROMPT_TEMPLATE, &serde_json::json!({}))
+            .await?;
+        let codex = serde_json::json!({
+            "title": title,
+            "template_id": PROMPT_TEMPLATE,
+            "content": { "fields": template },
+        });
+        self.database.update_codex(&id, &codex).await?;
+
+        debug!("Saved prompt {} v{}", template.name, template.version);
+        Ok(template)
+    }
+
+    /// Build `request` from the template it names, if any
+    pub async fn resolve(&self, request: ChatRequest) -> Result<ChatRequest> {
+        let Some(PromptRef { name, version, variables }) = request.prompt.clone() else {
+            return Ok(request);
+        };
+        self.get(&name, version).await?.apply(request, &variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::types::simple_params_to_chat_request;
+    use tempfile::TempDir;
+
+    fn reviewer() -> PromptTemplate {
+        let mut template = PromptTemplate::new("reviewer");
+        template.system_prompt = Some("You review {{language}} code. Be {{tone}}.".to_string());
+        template.user_template = Some("Review this:\n{{input}}".to_string());
+        template.examples = vec![PromptExample {
+            user: "Review this:\nlet x = 1;".to_string(),
+            assistant: "Looks fine for {{language}}.".to_string(),
+        }];
+        template.variables.insert("language".to_string(), PromptVariable::default());
+        template.variables.insert(
+            "tone".to_string(),
+            PromptVariable { description: None, default: Some("brief".to_string()) },
+        );
+        template
+    }
+
+    #[test]
+    fn test_render() {
+        let variables = HashMap::from([("name".to_string(), "Vespera".to_string())]);
+        assert_eq!(render("Hi {{ name }}, {{{{literal}}", &variables).unwrap(), "Hi Vespera, {{literal}}");
+        let err = render("{{name}} {{missing}}", &variables).unwrap_err();
+        assert!(err.to_string().contains("missing"));
+        assert!(render("{{name", &variables).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let request = simple_params_to_chat_request("fn main() {}", Some("Mention tests."), None, None);
+        let values = HashMap::from([("language".to_string(), Value::from("Rust"))]);
+        let request = reviewer().apply(request, &values).unwrap();
+
+        assert_eq!(request.system_prompt.as_deref(), Some("You review Rust code. Be brief.\n\nMention tests."));
+        let turns: Vec<(ChatRole, &str)> = request.messages.iter().map(|m| (m.role.clone(), m.content.as_str())).collect();
+        assert_eq!(
+            turns,
+            vec![
+                (ChatRole::User, "Review this:\nlet x = 1;"),
+                (ChatRole::Assistant, "Looks fine for Rust."),
+                (ChatRole::User, "Review this:\nfn main() {}"),
+            ]
+        );
+
+        // `language` has no default
+        let request = simple_params_to_chat_request("fn main() {}", None, None, None);
+        assert!(reviewer().apply(request, &HashMap::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_library_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().join("prompts.db")).await.unwrap();
+        database.init_schema().await.unwrap();
+        let library = PromptLibrary::new(Arc::new(database));
+
+        assert_eq!(library.save(reviewer()).await.unwrap().version, 1);
+        let mut second = reviewer();
+        second.system_prompt = Some("You review {{language}} code strictly.".to_string());
+        assert_eq!(library.save(second).await.unwrap().version, 2);
+        assert_eq!(library.list().await.unwrap().len(), 2);
+
+        let mut request = simple_params_to_chat_request("x", None, None, None);
+        request.prompt = Some(PromptRef::new("reviewer").with_variable("language", "Go"));
+        let latest = library.resolve(request.clone()).await.unwrap();
+        assert_eq!(latest.system_prompt.as_deref(), Some("You review Go code strictly."));
+        assert!(latest.prompt.is_none());
+
+        request.prompt = Some(PromptRef::new("reviewer").with_version(1).with_variable("language", "Go"));
+        let first = library.resolve(request).await.unwrap();
+        assert_eq!(first.system_prompt.as_deref(), Some("You review Go code. Be brief."));
+
+        assert!(library.get("reviewer", Some(3)).await.is_err());
+        assert!(library.get("unknown", None).await.is_err());
+    }
+}
@@ -0,0 +1,71 @@
+// Shared HTTP helpers for the REST API providers
+//
+// The OpenAI-compatible and Anthropic APIs both report errors as
+// `{"error": {"message": ...}}` and stream responses as Server-Sent Events:
+// `event:` / `data:` lines, with events separated by a blank line. Only the
+// data payloads matter to the providers, so the reader yields the `data` of
+// each event (multi-line data joined with '\n').
+
+use anyhow::{anyhow, Context, Result};
+use futures::{Stream, StreamExt};
+use tokio::io::AsyncBufReadExt;
+
+/// Pass successful responses through; turn error responses into an error carrying the API's message
+pub(crate) async fn check_status(response: reqwest::Response, api_name: &str) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|value| value.pointer("/error/message").and_then(|m| m.as_str()).map(str::to_string))
+        .unwrap_or(body);
+    Err(anyhow!("{} returned HTTP {}: {}", api_name, status, message))
+}
+
+/// Read the data payload of each event in an SSE response body
+pub(crate) fn data_events(response: reqwest::Response) -> impl Stream<Item = Result<String>> + Send {
+    let reader = tokio_util::io::StreamReader::new(
+        response
+            .bytes_stream()
+            .map(|result| result.map_err(std::io::Error::other)),
+    );
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    async_stream::stream! {
+        let mut data: Option<String> = None;
+        loop {
+            match lines.next_line().await.context("Failed to read event stream") {
+                Ok(Some(line)) => {
+                    if line.is_empty() {
+                        if let Some(event) = data.take() {
+                            yield Ok(event);
+                        }
+                    } else if let Some(value) = line.strip_prefix("data:") {
+                        let value = value.strip_prefix(' ').unwrap_or(value);
+                        match data.as_mut() {
+                            Some(event) => {
+                                event.push('\n');
+                                event.push_str(value);
+                            }
+                            None => data = Some(value.to_string()),
+                        }
+                    }
+                    // `event:`, `id:`, `retry:` and `:` comments carry nothing we need
+                }
+                Ok(None) => {
+                    if let Some(event) = data.take() {
+                        yield Ok(event);
+                    }
+                    break;
+                }
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+    }
+}
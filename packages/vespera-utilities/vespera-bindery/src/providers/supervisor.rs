@@ -0,0 +1,193 @@
+// Provider Supervision
+//
+// Tracks each loaded provider's health from health checks and request
+// outcomes. A provider that fails `failure_threshold` times in a row is
+// marked unhealthy: requests are routed away from it and it is restarted
+// (reloaded from its Codex) after an exponential backoff that resets once
+// it answers successfully again.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Supervision settings for [`ProviderManager`](super::ProviderManager)
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// How often each provider is health checked (skipped if something else checked it recently)
+    pub health_check_interval: Duration,
+    /// How long a health check may take before it counts as a failure
+    pub check_timeout: Duration,
+    /// Consecutive failures before a provider is marked unhealthy
+    pub failure_threshold: u32,
+    /// Delay before the first restart of an unhealthy provider
+    pub initial_backoff: Duration,
+    /// Longest delay between restarts
+    pub max_backoff: Duration,
+    /// Send requests for an unhealthy provider to a healthy one instead
+    pub fallback_routing: bool,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            health_check_interval: Duration::from_secs(60),
+            check_timeout: Duration::from_secs(30),
+            failure_threshold: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            fallback_routing: true,
+        }
+    }
+}
+
+/// Health of a supervised provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderHealth {
+    /// Not checked since it was loaded or restarted
+    Unknown,
+    /// Last check or request succeeded
+    Healthy,
+    /// Failing, but not yet past the failure threshold
+    Degraded,
+    /// Past the failure threshold; requests are routed elsewhere
+    Unhealthy,
+}
+
+/// Supervision state of one provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderStatus {
+    pub provider_id: String,
+    pub provider_type: String,
+    pub health: ProviderHealth,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    /// Restarts since the provider was first loaded
+    pub restarts: u32,
+    pub last_check: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// When the next restart is due, while unhealthy
+    pub next_restart_at: Option<DateTime<Utc>>,
+    /// Restarts since the provider was last healthy; drives the backoff
+    #[serde(skip)]
+    restart_attempts: u32,
+}
+
+impl ProviderStatus {
+    pub fn new(provider_id: impl Into<String>, provider_type: impl Into<String>) -> Self {
+        Self {
+            provider_id: provider_id.into(),
+            provider_type: provider_type.into(),
+            health: ProviderHealth::Unknown,
+            consecutive_failures: 0,
+            total_failures: 0,
+            restarts: 0,
+            last_check: None,
+            last_success: None,
+            last_error: None,
+            next_restart_at: None,
+            restart_attempts: 0,
+        }
+    }
+
+    /// Whether requests may be sent to this provider
+    pub fn is_routable(&self) -> bool {
+        self.health != ProviderHealth::Unhealthy
+    }
+
+    pub(crate) fn record_success(&mut self, now: DateTime<Utc>) {
+        self.health = ProviderHealth::Healthy;
+        self.consecutive_failures = 0;
+        self.restart_attempts = 0;
+        self.last_success = Some(now);
+        self.next_restart_at = None;
+    }
+
+    pub(crate) fn record_failure(&mut self, error: impl Into<String>, now: DateTime<Utc>, config: &SupervisorConfig) {
+        self.consecutive_failures += 1;
+        self.total_failures += 1;
+        self.last_error = Some(error.into());
+
+        if self.consecutive_failures >= config.failure_threshold {
+            self.health = ProviderHealth::Unhealthy;
+            if self.next_restart_at.is_none() {
+                let delay = backoff(config, self.restart_attempts);
+                self.next_restart_at = Some(now + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero()));
+            }
+        } else {
+            self.health = ProviderHealth::Degraded;
+        }
+    }
+
+    pub(crate) fn record_check(&mut self, now: DateTime<Utc>) {
+        self.last_check = Some(now);
+    }
+
+    pub(crate) fn restart_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_restart_at.is_some_and(|at| at <= now)
+    }
+
+    /// The provider was reloaded; it has to prove itself again
+    pub(crate) fn record_restart(&mut self) {
+        self.restarts += 1;
+        self.restart_attempts += 1;
+        self.health = ProviderHealth::Unknown;
+        self.consecutive_failures = 0;
+        self.next_restart_at = None;
+    }
+}
+
+/// Delay before restart number `attempt` (0-based) since the provider was last healthy
+fn backoff(config: &SupervisorConfig, attempt: u32) -> Duration {
+    config
+        .initial_backoff
+        .checked_mul(2u32.saturating_pow(attempt))
+        .unwrap_or(config.max_backoff)
+        .min(config.max_backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions_and_backoff() {
+        let config = SupervisorConfig {
+            failure_threshold: 2,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(3),
+            ..SupervisorConfig::default()
+        };
+        let now = Utc::now();
+        let mut status = ProviderStatus::new("p1", "ollama");
+
+        status.record_failure("timeout", now, &config);
+        assert_eq!(status.health, ProviderHealth::Degraded);
+        assert!(status.is_routable());
+
+        status.record_failure("timeout", now, &config);
+        assert_eq!(status.health, ProviderHealth::Unhealthy);
+        assert!(!status.is_routable());
+        assert!(!status.restart_due(now));
+        assert!(status.restart_due(now + chrono::Duration::seconds(1)));
+
+        // Each restart that doesn't help doubles the wait, up to the cap
+        let mut delays = Vec::new();
+        for _ in 0..3 {
+            status.record_restart();
+            status.record_failure("crash", now, &config);
+            status.record_failure("crash", now, &config);
+            delays.push((status.next_restart_at.unwrap() - now).num_seconds());
+        }
+        assert_eq!(delays, vec![2, 3, 3]);
+        assert_eq!(status.restarts, 3);
+
+        status.record_success(now);
+        assert_eq!(status.health, ProviderHealth::Healthy);
+        assert_eq!(status.next_restart_at, None);
+        status.record_failure("crash", now, &config);
+        status.record_failure("crash", now, &config);
+        assert_eq!((status.next_restart_at.unwrap() - now).num_seconds(), 1);
+    }
+}
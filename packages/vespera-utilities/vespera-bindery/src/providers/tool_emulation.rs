@@ -0,0 +1,241 @@
+// Prompt-Based Tool Calling
+//
+// For providers without a native tool API (the Claude Code CLI runs its own
+// built-in tools and has no way to hand ours back). The tool definitions are
+// described in the system prompt, and the model is asked to answer with
+//
+//     <tool_call>{"name": "...", "arguments": {...}}</tool_call>
+//
+// blocks when it wants to call one. Those blocks are cut out of the reply
+// and surfaced as ToolCalls; results are sent back as <tool_result> blocks.
+
+use super::types::{ChatMessage, ChatRole, ToolCall, ToolDefinition};
+use serde::Deserialize;
+use tracing::warn;
+
+const CALL_OPEN: &str = "<tool_call>";
+const CALL_CLOSE: &str = "</tool_call>";
+
+/// System prompt extended with the tool definitions and calling convention
+pub(crate) fn system_prompt_with_tools(base: Option<&str>, tools: &[ToolDefinition]) -> String {
+    let mut prompt = String::new();
+    if let Some(base) = base.filter(|b| !b.trim().is_empty()) {
+        prompt.push_str(base);
+        prompt.push_str("\n\n");
+    }
+
+    prompt.push_str(
+        "You can call the following tools. To call a tool, reply with one block per call, exactly in this form:\n\
+         <tool_call>{\"name\": \"tool_name\", \"arguments\": {...}}</tool_call>\n\
+         Arguments must be a JSON object matching the tool's parameters. After calling tools, stop and wait: \
+         each result will be sent back as <tool_result id=\"...\" name=\"...\">...</tool_result>. \
+         Only call tools listed here; answer normally when no tool is needed.\n\nTools:\n",
+    );
+    for tool in tools {
+        prompt.push_str(&format!("- {}: {}\n  parameters: {}\n", tool.name, tool.description, tool.parameters));
+    }
+    prompt
+}
+
+/// Prompt for the next turn of a conversation
+///
+/// When resuming a session the provider already has the history, so only the
+/// messages after its last reply are sent. Otherwise the whole conversation
+/// is rendered as a transcript.
+pub(crate) fn render_prompt(messages: &[ChatMessage], resuming: bool) -> String {
+    let start = if resuming {
+        messages
+            .iter()
+            .rposition(|m| m.role == ChatRole::Assistant)
+            .map_or(0, |i| i + 1)
+    } else {
+        0
+    };
+    let turn: Vec<&ChatMessage> = messages[start..].iter().filter(|m| m.role != ChatRole::System).collect();
+
+    // A single user message needs no framing
+    if let [only] = turn.as_slice() {
+        if only.role == ChatRole::User {
+            return only.content.clone();
+        }
+    }
+
+    turn.iter()
+        .map(|message| match message.role {
+            ChatRole::User => format!("User: {}", message.content),
+            ChatRole::Assistant => {
+                let calls: String = message
+                    .tool_calls
+                    .iter()
+                    .map(|call| {
+                        let json = serde_json::json!({ "name": call.name, "arguments": call.arguments_json() });
+                        format!("\n{}{}{}", CALL_OPEN, json, CALL_CLOSE)
+                    })
+                    .collect();
+                format!("Assistant: {}{}", message.content, calls)
+            }
+            ChatRole::Tool => format!(
+                "<tool_result id=\"{}\" name=\"{}\">{}</tool_result>",
+                message.tool_call_id.as_deref().unwrap_or(""),
+                message.name.as_deref().unwrap_or(""),
+                message.content
+            ),
+            ChatRole::System => unreachable!("system messages are filtered out"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Split a reply into its text and the tool calls it contains
+pub(crate) fn extract_tool_calls(text: &str) -> (String, Vec<ToolCall>) {
+    let mut content = String::new();
+    let mut calls = Vec::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find(CALL_OPEN) {
+        content.push_str(&rest[..open]);
+        let after = &rest[open + CALL_OPEN.len()..];
+        let Some(close) = after.find(CALL_CLOSE) else {
+            // Unterminated block: keep it as text rather than guess
+            content.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+
+        match parse_call(&after[..close], calls.len()) {
+            Some(call) => calls.push(call),
+            None => content.push_str(&rest[open..open + CALL_OPEN.len() + close + CALL_CLOSE.len()]),
+        }
+        rest = &after[close + CALL_CLOSE.len()..];
+    }
+    content.push_str(rest);
+
+    (content.trim().to_string(), calls)
+}
+
+fn parse_call(body: &str, index: usize) -> Option<ToolCall> {
+    #[derive(Deserialize)]
+    struct EmulatedCall {
+        name: String,
+        #[serde(default)]
+        arguments: serde_json::Value,
+    }
+
+    let parsed = serde_json::from_str::<EmulatedCall>(body.trim())
+        .map_err(anyhow::Error::from)
+        .and_then(|call| {
+            let id = format!("call_{}_{}", index, uuid::Uuid::new_v4().simple());
+            ToolCall::from_json(id, call.name, call.arguments)
+        });
+    match parsed {
+        Ok(call) => Some(call),
+        Err(e) => {
+            warn!("Ignoring malformed emulated tool call: {}", e);
+            None
+        }
+    }
+}
+
+/// Holds back streamed text that may belong to a tool call block
+///
+/// Text is released as soon as it can't be part of a block; everything from
+/// the first `<tool_call>` on is kept until the stream ends.
+#[derive(Debug, Default)]
+pub(crate) struct ToolCallStreamFilter {
+    pending: String,
+    in_calls: bool,
+}
+
+impl ToolCallStreamFilter {
+    /// Add a text delta; returns the text that is safe to emit now
+    pub(crate) fn push(&mut self, delta: &str) -> String {
+        self.pending.push_str(delta);
+        if self.in_calls {
+            return String::new();
+        }
+
+        if let Some(open) = self.pending.find(CALL_OPEN) {
+            self.in_calls = true;
+            let released: String = self.pending.drain(..open).collect();
+            return released;
+        }
+
+        // Keep a trailing fragment that could still grow into "<tool_call>"
+        let keep = (1..CALL_OPEN.len())
+            .rev()
+            .find(|&n| self.pending.ends_with(&CALL_OPEN[..n]))
+            .unwrap_or(0);
+        let split = self.pending.len() - keep;
+        self.pending.drain(..split).collect()
+    }
+
+    /// End of stream: the remaining text and the tool calls found in it
+    pub(crate) fn finish(self) -> (String, Vec<ToolCall>) {
+        if !self.in_calls {
+            return (self.pending, Vec::new());
+        }
+        let (text, calls) = extract_tool_calls(&self.pending);
+        // Separate text after the calls from what was already emitted
+        let text = if text.is_empty() { text } else { format!("\n{}", text) };
+        (text, calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_and_stream_filter_agree() {
+        let reply = "Let me check.\n<tool_call>{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Oslo\"}}</tool_call>\n\
+                     <tool_call>not json</tool_call>";
+
+        let (text, calls) = extract_tool_calls(reply);
+        assert_eq!(text, "Let me check.\n\n<tool_call>not json</tool_call>");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "get_weather");
+        assert_eq!(calls[0].arguments["city"], json!("Oslo"));
+
+        // Fed in awkward pieces, the filter never leaks part of a block
+        let mut filter = ToolCallStreamFilter::default();
+        let mut emitted = String::new();
+        for piece in ["Let me ch", "eck.\n<tool", "_call>{\"name\": \"get_", "weather\", \"arguments\": {\"city\": \"Oslo\"}}</tool_call>"] {
+            emitted.push_str(&filter.push(piece));
+            assert!(!emitted.contains('<'), "{:?}", emitted);
+        }
+        let (rest, calls) = filter.finish();
+        assert_eq!(emitted, "Let me check.\n");
+        assert_eq!(rest, "");
+        assert_eq!(calls[0].arguments["city"], json!("Oslo"));
+
+        // A lone '<' that turns out not to start a block is released
+        let mut filter = ToolCallStreamFilter::default();
+        assert_eq!(filter.push("a <"), "a ");
+        assert_eq!(filter.push("b"), "<b");
+    }
+
+    #[test]
+    fn test_render_prompt() {
+        let call = ToolCall::from_json("call_1", "get_weather", json!({"city": "Oslo"})).unwrap();
+        let messages = vec![
+            ChatMessage::system("ignored here"),
+            ChatMessage::user("Weather in Oslo?"),
+            ChatMessage::assistant_tool_calls("", vec![call.clone()]),
+            ChatMessage::tool_result(&call, "12C"),
+        ];
+
+        // Resuming: only the new tool result is sent
+        assert_eq!(
+            render_prompt(&messages, true),
+            "<tool_result id=\"call_1\" name=\"get_weather\">12C</tool_result>"
+        );
+
+        // Fresh session: the whole conversation
+        let transcript = render_prompt(&messages, false);
+        assert!(transcript.starts_with("User: Weather in Oslo?\n\nAssistant: \n<tool_call>"));
+        assert!(transcript.ends_with("12C</tool_result>"));
+
+        assert_eq!(render_prompt(&messages[..2], false), "Weather in Oslo?");
+    }
+}
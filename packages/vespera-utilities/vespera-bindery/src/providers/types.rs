@@ -1,37 +1,59 @@
 //! Phase 17 Provider Types
 //!
-//! This module makes the richer PR #85 types available for use in Phase 17 providers,
-//! enabling gradual migration from simple string parameters to structured types.
+//! This module holds the richer PR #85 types (chat requests, streaming chunks,
+//! tool calls, capabilities) for Phase 17 providers. The old `src/llm` module
+//! has been removed; `providers` is the only provider API.
 //!
 //! Migration Strategy:
 //! 1. Phase 17.5 Task 4: Make types available, add optional trait methods
-//! 2. Future tasks: Gradually migrate call sites to use structured types
-//! 3. Eventually: Deprecate simple parameter methods in favor of structured types
+//! 2. Phase 17.5 Task 8: Move the remaining `src/llm` types here and delete it (done)
+//! 3. Future tasks: Gradually migrate call sites to use structured types
+//! 4. Eventually: Deprecate simple parameter methods in favor of structured types
 //!
 //! ## Provider Feature Matrix (Phase 17.5 Tasks 5-6)
 //!
-//! | Provider          | Streaming | Tools | System Prompt | Max Tokens | Context Length |
-//! |-------------------|-----------|-------|---------------|------------|----------------|
-//! | ClaudeCodeProvider| ✅        | ✅    | ✅            | 4096-8192  | 200,000        |
-//! | OllamaProvider    | ✅        | ❌    | ✅            | 2048-4096  | 4,096-8,192    |
+//! | Provider          | Streaming | Tools | System Prompt | Vision | Max Tokens | Context Length |
+//! |-------------------|-----------|-------|---------------|--------|------------|----------------|
+//! | ClaudeCodeProvider| ✅        | ✅    | ✅            | ❌     | 4096-8192  | 200,000        |
+//! | OllamaProvider    | ✅        | ✅    | ✅            | ❌     | 2048-4096  | 4,096-8,192    |
+//! | OpenAiCompatibleProvider | ✅ | ✅    | ✅            | ✅     | 4096       | 128,000        |
+//! | AnthropicProvider | ✅        | ✅    | ✅            | ✅     | 4096       | 200,000        |
 //!
 //! ### Claude Code CLI (ClaudeCodeProvider)
 //! - **Streaming**: Full support via stream-json format
-//! - **Tools**: Full support - extracts tool_use content blocks to ToolCall structs
+//! - **Tools**: Emulated - tools are described in the system prompt and `<tool_call>`
+//!   blocks in the reply become ToolCalls (the CLI's own tool_use blocks are its built-ins)
 //! - **System Prompt**: Supported via --system-prompt flag
 //! - **Max Tokens**: Configurable, defaults to 4096
 //! - **Context**: 200K tokens (Claude Sonnet 4.5)
+//! - **Models**: Static list of current Claude models
 //!
 //! ### Ollama (OllamaProvider)
 //! - **Streaming**: Full support via newline-delimited JSON
-//! - **Tools**: Not supported (model-dependent, most don't have it)
+//! - **Tools**: Native via /api/chat for models trained for tool use
 //! - **System Prompt**: Supported via system field in API
 //! - **Max Tokens**: Configurable, defaults to 2048
 //! - **Context**: Configurable context_window, defaults to 4096
+//! - **Models**: Pulled models from /api/tags
 //!
-//! Use `provider.capabilities()` for runtime feature detection.
+//! ### OpenAI-compatible APIs (OpenAiCompatibleProvider)
+//! - **Streaming**: Full support via Server-Sent Events
+//! - **Tools**: Native `tools` / `tool_calls`, streamed arguments are reassembled
+//! - **System Prompt**: Sent as a leading system message
+//! - **Context**: Configurable context_window, defaults to 128K
+//! - **Models**: From /models
+//!
+//! ### Anthropic Messages API (AnthropicProvider)
+//! - **Streaming**: Full support via Server-Sent Events
+//! - **Tools**: Native `tool_use` / `tool_result` content blocks
+//! - **System Prompt**: Top-level system field (system messages are folded in)
+//! - **Context**: 200K tokens
+//! - **Models**: From /v1/models
+//!
+//! Use `provider.capabilities()` for runtime feature detection and
+//! `provider.list_models()` to populate model pickers.
 
-use super::{ProviderResponse, ProviderUsage};
+use super::{ProviderResponse, ProviderUsage, StreamChunk};
 use futures::Stream;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -60,6 +82,9 @@ pub struct ChatMessage {
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Tools the assistant called in this message
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tool_calls: Vec<ToolCall>,
 }
 
 impl ChatMessage {
@@ -69,6 +94,7 @@ impl ChatMessage {
             content: content.into(),
             name: None,
             tool_call_id: None,
+            tool_calls: Vec::new(),
         }
     }
 
@@ -78,6 +104,7 @@ impl ChatMessage {
             content: content.into(),
             name: None,
             tool_call_id: None,
+            tool_calls: Vec::new(),
         }
     }
 
@@ -87,6 +114,29 @@ impl ChatMessage {
             content: content.into(),
             name: None,
             tool_call_id: None,
+            tool_calls: Vec::new(),
+        }
+    }
+
+    /// Assistant message that called tools, to send back along with their results
+    pub fn assistant_tool_calls(content: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+            name: None,
+            tool_call_id: None,
+            tool_calls,
+        }
+    }
+
+    /// Result of executing the tool call `call`
+    pub fn tool_result(call: &ToolCall, content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Tool,
+            content: content.into(),
+            name: Some(call.name.clone()),
+            tool_call_id: Some(call.id.clone()),
+            tool_calls: Vec::new(),
         }
     }
 }
@@ -105,6 +155,41 @@ pub struct ChatRequest {
     pub temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_sequences: Option<Vec<String>>,
+    /// Stored prompt template to build the request from; resolved by the ProviderManager
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub prompt: Option<PromptRef>,
+}
+
+/// Selects a stored prompt template by name (see `prompts`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRef {
+    pub name: String,
+    /// Version to use; the latest when omitted
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version: Option<u32>,
+    /// Values for the template's variables
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub variables: HashMap<String, serde_json::Value>,
+}
+
+impl PromptRef {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: None,
+            variables: HashMap::new(),
+        }
+    }
+
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn with_variable(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.variables.insert(name.into(), value.into());
+        self
+    }
 }
 
 /// Complete response from an LLM provider (non-streaming)
@@ -134,6 +219,8 @@ pub enum FinishReason {
     ToolCalls,
     ContentFilter,
     Error,
+    /// The caller cancelled the request before it finished
+    Cancelled,
 }
 
 /// Definition of a tool/function the model can call
@@ -152,16 +239,82 @@ pub struct ToolCall {
     pub arguments: HashMap<String, serde_json::Value>,
 }
 
+impl ToolCall {
+    /// Build a tool call from the arguments object a provider returned
+    ///
+    /// Fails if the arguments aren't a JSON object (or a string containing one,
+    /// as the OpenAI API sends them).
+    pub fn from_json(id: impl Into<String>, name: impl Into<String>, arguments: serde_json::Value) -> anyhow::Result<Self> {
+        let name = name.into();
+        let arguments = match arguments {
+            serde_json::Value::String(raw) if raw.trim().is_empty() => serde_json::Value::Object(Default::default()),
+            serde_json::Value::String(raw) => serde_json::from_str(&raw)
+                .map_err(|e| anyhow::anyhow!("Malformed arguments for tool {}: {}", name, e))?,
+            serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+            other => other,
+        };
+        let serde_json::Value::Object(arguments) = arguments else {
+            return Err(anyhow::anyhow!("Arguments for tool {} are not a JSON object", name));
+        };
+
+        Ok(Self {
+            id: id.into(),
+            name,
+            arguments: arguments.into_iter().collect(),
+        })
+    }
+
+    /// Arguments as a JSON object
+    pub fn arguments_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(self.arguments.clone().into_iter().collect())
+    }
+}
+
 /// Provider capabilities
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderCapabilities {
     pub supports_streaming: bool,
     pub supports_tools: bool,
     pub supports_system_prompt: bool,
+    /// Whether the backend accepts images (model-dependent where noted)
+    #[serde(default)]
+    pub supports_vision: bool,
     pub max_tokens: u32,
     pub max_context_length: u32,
 }
 
+/// A model a provider can serve
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Name to pass as the request's model
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub display_name: Option<String>,
+    /// Context window in tokens, when known
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub context_length: Option<u32>,
+}
+
+impl ModelInfo {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            display_name: None,
+            context_length: None,
+        }
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn with_context_length(mut self, context_length: u32) -> Self {
+        self.context_length = Some(context_length);
+        self
+    }
+}
+
 // ============================================================================
 // Streaming Types (formerly from src/llm/streaming.rs)
 // ============================================================================
@@ -267,6 +420,27 @@ pub fn chat_response_to_provider_response(
     }
 }
 
+/// Convert a PR #85 ChatChunk to a Phase 17 StreamChunk tagged with `chunk_type`
+pub fn chat_chunk_to_stream_chunk(chunk: ChatChunk, chunk_type: &str) -> StreamChunk {
+    let is_final = chunk.is_final();
+    let metadata = if is_final || !chunk.tool_calls.is_empty() {
+        Some(serde_json::json!({
+            "finish_reason": chunk.finish_reason,
+            "tool_calls": chunk.tool_calls,
+            "usage": chunk.usage,
+        }))
+    } else {
+        None
+    };
+
+    StreamChunk {
+        chunk_type: chunk_type.to_string(),
+        text: Some(chunk.delta),
+        is_final,
+        metadata,
+    }
+}
+
 /// Convert simple Phase 17 parameters to PR #85 ChatRequest
 pub fn simple_params_to_chat_request(
     message: &str,
@@ -283,6 +457,7 @@ pub fn simple_params_to_chat_request(
         max_tokens,
         temperature,
         stop_sequences: None,
+        prompt: None,
     }
 }
 
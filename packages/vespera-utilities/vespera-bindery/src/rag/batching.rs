@@ -0,0 +1,190 @@
+//! # Embedding Request Batching
+//!
+//! Groups texts into provider-sized embedding requests and paces them, so
+//! indexing thousands of chunks stays within a provider's rate limits.
+//!
+//! A batch closes when it reaches the provider's maximum input count or its
+//! token budget, whichever comes first. Requests then run with bounded
+//! concurrency, optionally spaced out to a requests-per-minute ceiling.
+
+use std::ops::Range;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::EmbeddingModel;
+
+/// Batching and rate limiting for embedding requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingBatchConfig {
+    /// Maximum texts per request (provider default when `None`)
+    pub max_batch_size: Option<usize>,
+
+    /// Maximum estimated tokens per request (provider default when `None`)
+    pub max_batch_tokens: Option<usize>,
+
+    /// Requests allowed in flight at once
+    pub max_concurrent_requests: usize,
+
+    /// Ceiling on requests started per minute (unlimited when `None`)
+    pub requests_per_minute: Option<u32>,
+}
+
+impl Default for EmbeddingBatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: None,
+            max_batch_tokens: None,
+            max_concurrent_requests: 4,
+            requests_per_minute: None,
+        }
+    }
+}
+
+impl EmbeddingBatchConfig {
+    /// Effective `(max_batch_size, max_batch_tokens)` for `model`
+    pub fn limits_for(&self, model: &EmbeddingModel) -> (usize, usize) {
+        let (size, tokens) = provider_limits(model);
+        (
+            self.max_batch_size.unwrap_or(size).max(1),
+            self.max_batch_tokens.unwrap_or(tokens).max(1),
+        )
+    }
+
+    /// Validate the batching configuration
+    pub fn validate(&self) -> Result<(), crate::BinderyError> {
+        if self.max_concurrent_requests == 0 {
+            return Err(crate::BinderyError::ConfigurationError(
+                "max_concurrent_requests must be greater than 0".to_string()
+            ));
+        }
+        if self.max_batch_size == Some(0) || self.max_batch_tokens == Some(0) {
+            return Err(crate::BinderyError::ConfigurationError(
+                "embedding batch limits must be greater than 0".to_string()
+            ));
+        }
+        if self.requests_per_minute == Some(0) {
+            return Err(crate::BinderyError::ConfigurationError(
+                "requests_per_minute must be greater than 0".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Documented per-request limits: `(max inputs, max total tokens)`
+pub fn provider_limits(model: &EmbeddingModel) -> (usize, usize) {
+    match model {
+        // OpenAI accepts 2048 inputs and 300k tokens per request
+        EmbeddingModel::OpenAI(_) => (2048, 300_000),
+        // Cohere embed accepts 96 texts per call
+        EmbeddingModel::Cohere(_) => (96, 96 * 512),
+        // Local models are bounded by memory rather than an API
+        EmbeddingModel::LocalModel(_) => (32, 32 * 512),
+        EmbeddingModel::Mock => (64, 64 * 512),
+    }
+}
+
+/// Rough token count (about four characters per token for English text and code)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4).max(1)
+}
+
+/// Split `texts` into consecutive batches within the size and token limits
+///
+/// A text that alone exceeds the token budget is sent in a batch of its own.
+pub fn plan_batches(texts: &[String], max_batch_size: usize, max_batch_tokens: usize) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+
+    for (i, text) in texts.iter().enumerate() {
+        let text_tokens = estimate_tokens(text);
+        let full = i - start >= max_batch_size || tokens + text_tokens > max_batch_tokens;
+        if i > start && full {
+            batches.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens += text_tokens;
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+
+    batches
+}
+
+/// Spaces request starts at least `interval` apart
+pub struct RequestPacer {
+    interval: Duration,
+    next_slot: tokio::sync::Mutex<Instant>,
+}
+
+impl RequestPacer {
+    /// Pacer admitting `requests_per_minute` requests per minute
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next_slot: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free request slot
+    pub async fn wait(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lengths: &[usize]) -> Vec<String> {
+        lengths.iter().map(|&n| "x".repeat(n)).collect()
+    }
+
+    #[test]
+    fn test_plan_batches_respects_size_and_tokens() {
+        // Size limit only
+        let batches = plan_batches(&texts(&[4; 10]), 4, 1_000);
+        assert_eq!(batches, vec![0..4, 4..8, 8..10]);
+
+        // 40 chars = 10 tokens each, so three fit in a 30 token budget
+        let batches = plan_batches(&texts(&[40; 7]), 100, 30);
+        assert_eq!(batches, vec![0..3, 3..6, 6..7]);
+
+        // An oversized text is isolated rather than dropped
+        let batches = plan_batches(&texts(&[4, 400, 4]), 100, 30);
+        assert_eq!(batches, vec![0..1, 1..2, 2..3]);
+
+        assert!(plan_batches(&[], 4, 100).is_empty());
+    }
+
+    #[test]
+    fn test_provider_limits_and_overrides() {
+        let config = EmbeddingBatchConfig::default();
+        assert_eq!(config.limits_for(&EmbeddingModel::Cohere("embed-english-v3.0".into())).0, 96);
+        assert_eq!(config.limits_for(&EmbeddingModel::OpenAI("text-embedding-3-small".into())).0, 2048);
+
+        let config = EmbeddingBatchConfig { max_batch_size: Some(8), ..Default::default() };
+        assert_eq!(config.limits_for(&EmbeddingModel::OpenAI("text-embedding-3-small".into())), (8, 300_000));
+        assert!(EmbeddingBatchConfig { max_concurrent_requests: 0, ..Default::default() }.validate().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pacer_spaces_requests() {
+        let pacer = RequestPacer::per_minute(60);
+        let start = Instant::now();
+        for _ in 0..3 {
+            pacer.wait().await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,413 @@
+//! # Bindery Indexer
+//!
+//! Makes Codex content and task descriptions searchable alongside project
+//! files. Each record becomes one RAG document tagged with its
+//! [`BinderySource`], so a search hit carries the `CodexId` the app needs to
+//! open it.
+//!
+//! [`BinderyIndexer::start`] keeps the index current by following the
+//! database's change events; a subscriber that falls behind re-syncs
+//! everything with [`BinderyIndexer::index_all`].
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use super::{BinderySource, DocumentType, RAGService};
+use crate::database::{ChangeEvent, Database};
+
+/// Summary of a full Codex/task sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BinderyIndexReport {
+    /// Codices seen in the database
+    pub codices: usize,
+    /// Tasks seen in the database
+    pub tasks: usize,
+    /// Records that were (re-)embedded
+    pub updated: usize,
+    /// Documents removed because their record no longer exists
+    pub removed: usize,
+    /// Records that could not be indexed, with the error
+    pub failed: Vec<(String, String)>,
+}
+
+/// Feeds Codices and tasks from a [`Database`] into a [`RAGService`]
+pub struct BinderyIndexer {
+    rag: Arc<RAGService>,
+    database: Arc<Database>,
+    handle: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl BinderyIndexer {
+    pub fn new(rag: Arc<RAGService>, database: Arc<Database>) -> Self {
+        Self {
+            rag,
+            database,
+            handle: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Index every Codex and task, and drop documents for deleted records
+    pub async fn index_all(&self) -> Result<BinderyIndexReport> {
+        let mut report = BinderyIndexReport::default();
+        let mut present = HashSet::new();
+
+        for codex in self.database.list_codices().await? {
+            report.codices += 1;
+            let id = codex.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            match self.index_codex_value(&codex).await {
+                Ok((source, updated)) => {
+                    present.insert(source);
+                    report.updated += updated as usize;
+                }
+                Err(e) => report.failed.push((id, e.to_string())),
+            }
+        }
+
+        // Walk the task tree from the roots; `list_tasks` only lists one level
+        let mut pending = self.database.list_tasks(None, None).await?;
+        while let Some(summary) = pending.pop() {
+            report.tasks += 1;
+            if summary.child_count > 0 {
+                pending.extend(self.database.list_tasks(None, Some(&summary.id)).await?);
+            }
+            match self.index_task(&summary.id).await {
+                Ok(Some((source, updated))) => {
+                    present.insert(source);
+                    report.updated += updated as usize;
+                }
+                Ok(None) => {}
+                Err(e) => report.failed.push((summary.id, e.to_string())),
+            }
+        }
+
+        for source in self.rag.bindery_sources().await {
+            if !present.contains(&source) && self.rag.remove_bindery_document(source).await? {
+                report.removed += 1;
+            }
+        }
+
+        info!(
+            codices = report.codices,
+            tasks = report.tasks,
+            updated = report.updated,
+            removed = report.removed,
+            failed = report.failed.len(),
+            "Bindery index sync completed"
+        );
+        Ok(report)
+    }
+
+    /// Index one Codex; returns `false` if it does not exist
+    pub async fn index_codex(&self, id: &str) -> Result<bool> {
+        match self.database.get_codex(id).await? {
+            Some(codex) => {
+                self.index_codex_value(&codex).await?;
+                Ok(true)
+            }
+            None => {
+                self.rag.remove_bindery_document(BinderySource::Codex(parse_id(id)?)).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    async fn index_codex_value(&self, codex: &serde_json::Value) -> Result<(BinderySource, bool)> {
+        let id = codex.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+        let source = BinderySource::Codex(parse_id(id)?);
+        let title = codex.get("title").and_then(|v| v.as_str()).unwrap_or("Untitled").to_string();
+
+        let mut tags = vec!["codex".to_string()];
+        if let Some(template_id) = codex.get("template_id").and_then(|v| v.as_str()) {
+            tags.push(template_id.to_string());
+        }
+
+        let content = render_codex(&title, codex);
+        let (_, updated) = self.rag
+            .upsert_bindery_document(source, title, content, DocumentType::Markdown, tags)
+            .await?;
+        Ok((source, updated))
+    }
+
+    /// Index one task; returns `None` if it does not exist
+    pub async fn index_task(&self, id: &str) -> Result<Option<(BinderySource, bool)>> {
+        let source = BinderySource::Task(parse_id(id)?);
+        let Some(task) = self.database.get_task(id).await? else {
+            self.rag.remove_bindery_document(source).await?;
+            return Ok(None);
+        };
+
+        let title = task.get("title").and_then(|v| v.as_str()).unwrap_or("Untitled").to_string();
+        let status = task.get("status").and_then(|v| v.as_str()).unwrap_or_default();
+
+        let mut tags = vec!["task".to_string(), format!("status:{}", status)];
+        tags.extend(string_array(task.get("tags")));
+
+        let content = render_task(&title, &task);
+        let (_, updated) = self.rag
+            .upsert_bindery_document(source, title, content, DocumentType::Markdown, tags)
+            .await?;
+        Ok(Some((source, updated)))
+    }
+
+    /// Apply a single database change to the index
+    pub async fn handle_event(&self, event: &ChangeEvent) -> Result<()> {
+        debug!(?event, "Applying Bindery change to RAG index");
+        match event {
+            ChangeEvent::CodexUpserted { id } => {
+                self.index_codex(id).await?;
+            }
+            ChangeEvent::TaskUpserted { id } => {
+                self.index_task(id).await?;
+            }
+            ChangeEvent::CodexDeleted { id } => {
+                self.rag.remove_bindery_document(BinderySource::Codex(parse_id(id)?)).await?;
+            }
+            ChangeEvent::TaskDeleted { id } => {
+                self.rag.remove_bindery_document(BinderySource::Task(parse_id(id)?)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Follow database changes in the background until [`BinderyIndexer::stop`]
+    ///
+    /// Replaces any subscription that is already running. Call
+    /// [`BinderyIndexer::index_all`] first to pick up records written earlier.
+    pub async fn start(self: &Arc<Self>) {
+        let mut events = self.database.subscribe_changes();
+        let indexer = Arc::downgrade(self);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let received = events.recv().await;
+                // Stop once the indexer itself has been dropped
+                let Some(indexer) = indexer.upgrade() else {
+                    break;
+                };
+
+                match received {
+                    Ok(event) => {
+                        if let Err(e) = indexer.handle_event(&event).await {
+                            error!(?event, error = %e, "Failed to index Bindery change");
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(missed, "Bindery indexer fell behind, re-syncing");
+                        if let Err(e) = indexer.index_all().await {
+                            error!(error = %e, "Bindery re-sync failed");
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        if let Some(previous) = self.handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// Stop following database changes
+    pub async fn stop(&self) {
+        if let Some(handle) = self.handle.lock().await.take() {
+            handle.abort();
+        }
+    }
+}
+
+fn parse_id(id: &str) -> Result<Uuid> {
+    Uuid::parse_str(id).map_err(|e| anyhow::anyhow!("Invalid Codex ID '{}': {}", id, e))
+}
+
+fn string_array(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Markdown text for a Codex: the title, then one section per content field
+fn render_codex(title: &str, codex: &serde_json::Value) -> String {
+    let mut text = format!("# {}\n", title);
+    if let Some(fields) = codex.pointer("/content/fields").and_then(|v| v.as_object()) {
+        for (name, value) in fields {
+            render_field(name, value, &mut text);
+        }
+    }
+    text
+}
+
+fn render_field(name: &str, value: &serde_json::Value, text: &mut String) {
+    match value {
+        serde_json::Value::Null => {}
+        serde_json::Value::String(s) if s.trim().is_empty() => {}
+        serde_json::Value::String(s) => text.push_str(&format!("\n## {}\n\n{}\n", name, s)),
+        serde_json::Value::Object(map) => {
+            for (key, nested) in map {
+                render_field(&format!("{}.{}", name, key), nested, text);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| match item {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+            if !items.is_empty() {
+                text.push_str(&format!("\n## {}\n\n{}\n", name, items.join(", ")));
+            }
+        }
+        other => text.push_str(&format!("\n{}: {}\n", name, other)),
+    }
+}
+
+/// Markdown text for a task: title, description and status line
+fn render_task(title: &str, task: &serde_json::Value) -> String {
+    let mut text = format!("# {}\n", title);
+    if let Some(description) = task.get("description").and_then(|v| v.as_str()) {
+        if !description.trim().is_empty() {
+            text.push_str(&format!("\n{}\n", description));
+        }
+    }
+
+    let status = task.get("status").and_then(|v| v.as_str()).unwrap_or_default();
+    let priority = task.get("priority").and_then(|v| v.as_str()).unwrap_or_default();
+    text.push_str(&format!("\nStatus: {}\nPriority: {}\n", status, priority));
+
+    let tags = string_array(task.get("tags"));
+    if !tags.is_empty() {
+        text.push_str(&format!("Tags: {}\n", tags.join(", ")));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::TaskInput;
+    use crate::rag::{RAGConfig, SearchOptions};
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, Arc<BinderyIndexer>, Arc<Database>) {
+        let temp_dir = TempDir::new().unwrap();
+        let rag = Arc::new(RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap());
+        let database = Database::new(temp_dir.path().join("bindery.db")).await.unwrap();
+        database.init_schema().await.unwrap();
+        let database = Arc::new(database);
+        let indexer = Arc::new(BinderyIndexer::new(rag, Arc::clone(&database)));
+        (temp_dir, indexer, database)
+    }
+
+    #[test]
+    fn test_render_codex_fields() {
+        let codex = serde_json::json!({
+            "content": {"fields": {
+                "body": "The lighthouse keeper's journal",
+                "empty": "",
+                "tags": ["sea", "night"],
+                "stats": {"words": 120},
+            }},
+        });
+        let text = render_codex("Journal", &codex);
+        assert!(text.starts_with("# Journal\n"));
+        assert!(text.contains("## body\n\nThe lighthouse keeper's journal"));
+        assert!(text.contains("## tags\n\nsea, night"));
+        assert!(text.contains("stats.words: 120"));
+        assert!(!text.contains("empty"));
+    }
+
+    #[tokio::test]
+    async fn test_index_all_tags_results_with_codex_id() {
+        let (_temp_dir, indexer, database) = setup().await;
+
+        let codex_id = Uuid::new_v4();
+        database.create_codex(&codex_id.to_string(), "Harbor notes", "note", &serde_json::json!({})).await.unwrap();
+        let mut codex = database.get_codex(&codex_id.to_string()).await.unwrap().unwrap();
+        codex["content"] = serde_json::json!({"fields": {"body": "The quartermaster counts barrels of saltpeter"}});
+        database.update_codex(&codex_id.to_string(), &codex).await.unwrap();
+
+        let task_id = database.create_task(&TaskInput {
+            title: "Restock lanterns".to_string(),
+            description: Some("Order whale oil before the winter storms".to_string()),
+            priority: None,
+            project_id: None,
+            parent_id: None,
+            tags: vec![],
+            labels: serde_json::json!({}),
+            subtasks: vec![],
+        }).await.unwrap();
+
+        let report = indexer.index_all().await.unwrap();
+        assert_eq!((report.codices, report.tasks, report.updated), (1, 1, 2));
+
+        let results = indexer.rag
+            .search_with_options("saltpeter barrels", 5, None, &SearchOptions::lexical())
+            .await
+            .unwrap();
+        assert_eq!(results[0].metadata.bindery_source, Some(BinderySource::Codex(codex_id)));
+        assert_eq!(results[0].metadata.codex_id(), Some(codex_id));
+
+        let results = indexer.rag
+            .search_with_options("whale oil", 5, None, &SearchOptions::lexical())
+            .await
+            .unwrap();
+        assert_eq!(results[0].metadata.codex_id(), Some(Uuid::parse_str(&task_id).unwrap()));
+
+        // Unchanged records are not re-embedded; deleted ones are dropped
+        database.delete_codex(&codex_id.to_string()).await.unwrap();
+        let report = indexer.index_all().await.unwrap();
+        assert_eq!((report.updated, report.removed), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_follows_change_events() {
+        let (_temp_dir, indexer, database) = setup().await;
+        indexer.start().await;
+
+        let parent = database.create_task(&TaskInput {
+            title: "Chart the reef".to_string(),
+            description: None,
+            priority: None,
+            project_id: None,
+            parent_id: None,
+            tags: vec![],
+            labels: serde_json::json!({}),
+            subtasks: vec![TaskInput {
+                title: "Sound the channel depth".to_string(),
+                description: None,
+                priority: None,
+                project_id: None,
+                parent_id: None,
+                tags: vec![],
+                labels: serde_json::json!({}),
+                subtasks: vec![],
+            }],
+        }).await.unwrap();
+
+        let wait_for = |count: usize| {
+            let rag = Arc::clone(&indexer.rag);
+            async move {
+                for _ in 0..100 {
+                    if rag.bindery_sources().await.len() == count {
+                        return true;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                }
+                false
+            }
+        };
+        assert!(wait_for(2).await, "parent and subtask should be indexed");
+
+        database.delete_task(&parent).await.unwrap();
+        assert!(wait_for(0).await, "deleting the parent should drop both documents");
+
+        indexer.stop().await;
+    }
+}
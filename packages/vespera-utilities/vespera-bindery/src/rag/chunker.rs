@@ -2,10 +2,16 @@
 //!
 //! Intelligent document chunking for optimal RAG performance.
 //! Implements multiple strategies based on document type.
+//!
+//! Source files in a language the structural parser understands are chunked
+//! along declaration boundaries by [`DocumentChunker::chunk_source`].
 
 use anyhow::Result;
 use regex::Regex;
 
+use super::code_analyzer::ProgrammingLanguage;
+use super::syntax;
+
 /// Strategies for chunking documents
 #[derive(Debug, Clone, Copy)]
 pub enum ChunkStrategy {
@@ -157,6 +163,79 @@ impl DocumentChunker {
         Ok(chunks)
     }
 
+    /// Chunk source code along declaration boundaries
+    ///
+    /// A function is never split: one longer than `max_chunk_size` becomes a
+    /// chunk of its own. Oversized impls and classes are split between their
+    /// members. Doc comments, attributes and decorators stay with the item
+    /// they annotate. Languages without a structural parser fall back to
+    /// [`ChunkStrategy::Code`].
+    pub fn chunk_source(&self, content: &str, language: ProgrammingLanguage) -> Result<Vec<String>> {
+        let outline = syntax::parse(content, language)?;
+        if outline.symbols.is_empty() {
+            return self.chunk_by_code(content);
+        }
+
+        // Consecutive byte ranges covering the file; items are never divided
+        let mut segments: Vec<std::ops::Range<usize>> = Vec::new();
+        let mut cursor = 0;
+        for item in outline.top_level() {
+            let start = leading_start(content, item.span.start).max(cursor);
+            Self::push_gap(content, cursor..start, &mut segments);
+
+            let members: Vec<_> = outline.children_of(item).collect();
+            if item.span.end - start > self.max_chunk_size && !members.is_empty() {
+                let mut member_cursor = start;
+                for member in members {
+                    let member_start = leading_start(content, member.span.start).max(member_cursor);
+                    if member_start > member_cursor {
+                        segments.push(member_cursor..member_start);
+                    }
+                    segments.push(member_start..member.span.end);
+                    member_cursor = member.span.end;
+                }
+                segments.push(member_cursor..item.span.end);
+            } else {
+                segments.push(start..item.span.end);
+            }
+            cursor = item.span.end;
+        }
+        Self::push_gap(content, cursor..content.len(), &mut segments);
+
+        // Pack segments greedily up to the size limit
+        let mut chunks = Vec::new();
+        let mut current = String::new();
+        for segment in segments {
+            let text = &content[segment];
+            if !current.is_empty() && current.len() + text.len() > self.max_chunk_size {
+                Self::push_chunk(&mut chunks, std::mem::take(&mut current));
+            }
+            current.push_str(text);
+        }
+        Self::push_chunk(&mut chunks, current);
+
+        Ok(chunks)
+    }
+
+    /// Add the code between items, split at blank lines
+    fn push_gap(content: &str, gap: std::ops::Range<usize>, segments: &mut Vec<std::ops::Range<usize>>) {
+        let mut start = gap.start;
+        for (offset, _) in content[gap.clone()].match_indices("\n\n") {
+            segments.push(start..gap.start + offset + 1);
+            start = gap.start + offset + 1;
+        }
+        if start < gap.end {
+            segments.push(start..gap.end);
+        }
+    }
+
+    fn push_chunk(chunks: &mut Vec<String>, chunk: String) {
+        let chunk = chunk.trim_matches('\n');
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk.to_string());
+        }
+    }
+
     /// Fixed-size chunking with overlap
     fn chunk_fixed_size(&self, content: &str) -> Result<Vec<String>> {
         let mut chunks = Vec::new();
@@ -290,6 +369,24 @@ impl DocumentChunker {
     }
 }
 
+/// Start of the line holding `offset`, moved up over attached comments,
+/// attributes and decorators
+fn leading_start(content: &str, offset: usize) -> usize {
+    let mut start = content[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    while start > 0 {
+        let previous_start = content[..start - 1].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let previous = content[previous_start..start - 1].trim_start();
+        let attached = ["///", "//", "#", "@", "/*", "*"]
+            .iter()
+            .any(|prefix| previous.starts_with(prefix));
+        if !attached {
+            break;
+        }
+        start = previous_start;
+    }
+    start
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -321,6 +418,40 @@ mod tests {
         assert_eq!(chunks.len(), 2);
     }
 
+    #[test]
+    fn test_source_chunking_keeps_functions_whole() {
+        let chunker = DocumentChunker::new(120, 0);
+        let long_body = (0..10).map(|i| format!("    let v{} = {};\n", i, i)).collect::<String>();
+        let content = format!(
+            "use std::fmt;\n\n/// Adds one\nfn small(x: i32) -> i32 {{\n    x + 1\n}}\n\n\
+             #[inline]\nfn large() {{\n{}    let s = \"}}\";\n}}\n\nfn tail() {{}}\n",
+            long_body
+        );
+
+        let chunks = chunker.chunk_source(&content, ProgrammingLanguage::Rust).unwrap();
+
+        // `large` exceeds the limit but stays in one piece with its attribute
+        let large = chunks.iter().find(|c| c.contains("fn large")).unwrap();
+        assert!(large.len() > 120);
+        assert!(large.starts_with("#[inline]"));
+        assert!(large.trim_end().ends_with("let s = \"}\";\n}"));
+        assert!(chunks.iter().any(|c| c.contains("/// Adds one\nfn small")));
+        assert!(!large.contains("fn tail"));
+    }
+
+    #[test]
+    fn test_source_chunking_splits_large_classes_between_methods() {
+        let chunker = DocumentChunker::new(80, 0);
+        let content = "class Store:\n    def get(self, key):\n        return self.items[key]\n\n    \
+                       def put(self, key, value):\n        self.items[key] = value\n";
+
+        let chunks = chunker.chunk_source(content, ProgrammingLanguage::Python).unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].starts_with("class Store:") && chunks[0].contains("def get"));
+        assert!(chunks[1].trim_start().starts_with("def put") && chunks[1].contains("= value"));
+    }
+
     #[test]
     fn test_fixed_size_chunking() {
         let chunker = DocumentChunker::new(50, 10);
@@ -731,9 +731,7 @@ impl CircuitBreakerRegistry {
 
     /// Get all breakers and their metrics
     pub async fn get_all_metrics(&self) -> Result<HashMap<String, CircuitBreakerMetrics>> {
-        let breakers = self.breakers.lock().map_err(|_| {
-            anyhow::anyhow!("Failed to acquire breakers lock")
-        })?;
+        let breakers = self.snapshot()?;
 
         let mut metrics = HashMap::new();
         for (name, breaker) in breakers.iter() {
@@ -746,9 +744,7 @@ impl CircuitBreakerRegistry {
 
     /// Reset all circuit breakers
     pub async fn reset_all(&self) -> Result<()> {
-        let breakers = self.breakers.lock().map_err(|_| {
-            anyhow::anyhow!("Failed to acquire breakers lock")
-        })?;
+        let breakers = self.snapshot()?;
 
         for (name, breaker) in breakers.iter() {
             if let Err(e) = breaker.reset().await {
@@ -759,6 +755,14 @@ impl CircuitBreakerRegistry {
         Ok(())
     }
 
+    /// Clone the registered breakers so no lock is held across awaits
+    fn snapshot(&self) -> Result<Vec<(String, Arc<CircuitBreaker>)>> {
+        let breakers = self.breakers.lock().map_err(|_| {
+            anyhow::anyhow!("Failed to acquire breakers lock")
+        })?;
+        Ok(breakers.iter().map(|(name, breaker)| (name.clone(), Arc::clone(breaker))).collect())
+    }
+
     /// Get service names
     pub async fn get_service_names(&self) -> Result<Vec<String>> {
         let breakers = self.breakers.lock().map_err(|_| {
@@ -783,9 +787,7 @@ pub struct CircuitBreakerHealth {
 impl CircuitBreakerRegistry {
     /// Get health status for all circuit breakers
     pub async fn get_health_status(&self) -> Result<Vec<CircuitBreakerHealth>> {
-        let breakers = self.breakers.lock().map_err(|_| {
-            anyhow::anyhow!("Failed to acquire breakers lock")
-        })?;
+        let breakers = self.snapshot()?;
 
         let mut health_status = Vec::new();
         let now = Instant::now();
@@ -2,14 +2,22 @@
 //!
 //! Analyzes source code for structure, dependencies, and potential issues.
 //! Supports multiple programming languages with language-specific analysis.
+//!
+//! Rust, Python and TypeScript/JavaScript go through the structural parser in
+//! [`syntax`](super::syntax), which records every declaration with its line
+//! span and every call site. Those spans back symbol-aware chunking and
+//! [`CodeAnalyzer::locate_symbol`], which answers "does this symbol exist,
+//! and where" with file and line evidence.
 
 use std::path::Path;
 use std::fs;
+use std::ops::Range;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
-use regex::Regex;
 use std::collections::HashMap;
 
+use super::syntax;
+
 /// Analysis result for a code file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeAnalysis {
@@ -22,6 +30,11 @@ pub struct CodeAnalysis {
     pub dependencies: Dependencies,
     pub metrics: CodeMetrics,
     pub issues: Vec<CodeIssue>,
+    /// Every declaration with its span, including impls, traits and type aliases
+    #[serde(default)]
+    pub symbols: Vec<SymbolInfo>,
+    #[serde(default)]
+    pub calls: Vec<CallSite>,
 }
 
 /// Programming languages supported by the analyzer
@@ -102,6 +115,106 @@ pub struct FieldInfo {
     pub is_public: bool,
 }
 
+/// Kind of declaration recorded in [`CodeAnalysis::symbols`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    /// Function declared inside an impl, trait, class or interface
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Class,
+    Interface,
+    TypeAlias,
+    Module,
+}
+
+/// A declaration and where it lives in the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Name of the innermost enclosing declaration (the type for methods)
+    pub parent: Option<String>,
+    /// Declaration header with whitespace collapsed, e.g. `pub fn new(id: u64) -> Self`
+    pub signature: String,
+    pub parameters: Vec<String>,
+    pub return_type: Option<String>,
+    /// Base classes, implemented interfaces, or the trait of a trait impl
+    pub base_types: Vec<String>,
+    /// Fields, interface members or enum variants
+    pub fields: Vec<FieldInfo>,
+    pub is_public: bool,
+    pub is_async: bool,
+    /// First line of the declaration (1-based)
+    pub line_number: usize,
+    /// Last line of the declaration, inclusive
+    pub end_line: usize,
+    /// Byte range of the whole declaration in the source
+    pub span: Range<usize>,
+    /// Byte offset of the name token
+    #[serde(skip)]
+    pub(crate) name_offset: usize,
+    /// Number of enclosing declarations (0 for top-level items)
+    pub depth: usize,
+    pub complexity: usize,
+}
+
+impl SymbolInfo {
+    /// `Parent::name` for nested declarations, `name` otherwise
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}::{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A call expression found in the code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallSite {
+    /// Called function or method name
+    pub name: String,
+    /// Path or receiver before the name (`Type` in `Type::new()`, `self.store` in `self.store.get()`)
+    pub qualifier: Option<String>,
+    /// Called with `.` syntax rather than as a path
+    pub is_method: bool,
+    pub line_number: usize,
+    /// Number of arguments passed, when the argument list is complete
+    #[serde(default)]
+    pub argument_count: Option<usize>,
+}
+
+/// Evidence that a symbol is defined: which file and lines declare it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    pub file_path: String,
+    pub name: String,
+    pub kind: SymbolKind,
+    pub parent: Option<String>,
+    pub signature: String,
+    pub line_number: usize,
+    pub end_line: usize,
+}
+
+impl SymbolLocation {
+    /// Location of `symbol`, declared in `analysis`
+    pub fn new(analysis: &CodeAnalysis, symbol: &SymbolInfo) -> Self {
+        Self {
+            file_path: analysis.file_path.clone(),
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            parent: symbol.parent.clone(),
+            signature: symbol.signature.clone(),
+            line_number: symbol.line_number,
+            end_line: symbol.end_line,
+        }
+    }
+}
+
 /// Dependencies found in the code
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dependencies {
@@ -160,10 +273,14 @@ impl CodeAnalyzer {
     pub fn new() -> Self {
         let mut analyzers: HashMap<ProgrammingLanguage, Box<dyn LanguageAnalyzer>> = HashMap::new();
 
-        analyzers.insert(ProgrammingLanguage::Rust, Box::new(RustAnalyzer));
-        analyzers.insert(ProgrammingLanguage::Python, Box::new(PythonAnalyzer));
-        analyzers.insert(ProgrammingLanguage::JavaScript, Box::new(JavaScriptAnalyzer));
-        analyzers.insert(ProgrammingLanguage::TypeScript, Box::new(TypeScriptAnalyzer));
+        for language in [
+            ProgrammingLanguage::Rust,
+            ProgrammingLanguage::Python,
+            ProgrammingLanguage::JavaScript,
+            ProgrammingLanguage::TypeScript,
+        ] {
+            analyzers.insert(language, Box::new(StructuralAnalyzer { language }));
+        }
 
         Self { analyzers }
     }
@@ -240,6 +357,31 @@ impl CodeAnalyzer {
         issues
     }
 
+    /// Find declarations of `name` across `analyses`
+    ///
+    /// `name` may be qualified with its parent (`Type::method`, `Class.method`);
+    /// only the last qualifier segment is compared. An empty result means the
+    /// symbol is not defined in any analyzed file.
+    pub fn locate_symbol(analyses: &[CodeAnalysis], name: &str) -> Vec<SymbolLocation> {
+        let normalized = name.replace("::", ".");
+        let (parent, name) = match normalized.rsplit_once('.') {
+            Some((qualifier, name)) => (qualifier.rsplit('.').next(), name),
+            None => (None, normalized.as_str()),
+        };
+
+        analyses
+            .iter()
+            .flat_map(|analysis| {
+                analysis
+                    .symbols
+                    .iter()
+                    .filter(|symbol| symbol.name == name)
+                    .filter(|symbol| parent.is_none() || symbol.parent.as_deref() == parent)
+                    .map(|symbol| SymbolLocation::new(analysis, symbol))
+            })
+            .collect()
+    }
+
     fn is_suspicious_import(&self, module: &str) -> bool {
         // Common AI hallucination patterns
         let suspicious_patterns = [
@@ -258,227 +400,164 @@ trait LanguageAnalyzer: Send + Sync {
     fn analyze(&self, content: &str, file_path: String) -> Result<Option<CodeAnalysis>>;
 }
 
-/// Rust language analyzer
-struct RustAnalyzer;
+/// Analyzer for languages with a structural parser (Rust, Python, TypeScript/JavaScript)
+struct StructuralAnalyzer {
+    language: ProgrammingLanguage,
+}
 
-impl LanguageAnalyzer for RustAnalyzer {
+impl LanguageAnalyzer for StructuralAnalyzer {
     fn analyze(&self, content: &str, file_path: String) -> Result<Option<CodeAnalysis>> {
-        let mut imports = Vec::new();
-        let mut functions = Vec::new();
-        let mut structs = Vec::new();
-        let mut metrics = CodeMetrics {
-            total_lines: 0,
-            code_lines: 0,
-            comment_lines: 0,
-            blank_lines: 0,
-            cyclomatic_complexity: 0,
-            function_count: 0,
-            class_count: 0,
+        let outline = syntax::parse(content, self.language)?;
+        let comment_prefixes: &[&str] = match self.language {
+            ProgrammingLanguage::Python => &["#"],
+            _ => &["//", "/*", "*"],
+        };
+        let mut metrics = line_metrics(content, comment_prefixes);
+
+        let to_function = |symbol: &SymbolInfo| FunctionInfo {
+            name: symbol.name.clone(),
+            parameters: symbol.parameters.clone(),
+            return_type: symbol.return_type.clone(),
+            is_async: symbol.is_async,
+            is_public: symbol.is_public,
+            line_number: symbol.line_number,
+            line_count: symbol.end_line - symbol.line_number + 1,
+            complexity: symbol.complexity,
         };
 
-        // Regex patterns for Rust
-        let use_regex = Regex::new(r"^\s*use\s+([^;]+)")?;
-        let fn_regex = Regex::new(r"^\s*(pub\s+)?(async\s+)?fn\s+(\w+)")?;
-        let struct_regex = Regex::new(r"^\s*(pub\s+)?struct\s+(\w+)")?;
-
-        for (line_num, line) in content.lines().enumerate() {
-            metrics.total_lines += 1;
-
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                metrics.blank_lines += 1;
-            } else if trimmed.starts_with("//") {
-                metrics.comment_lines += 1;
-            } else {
-                metrics.code_lines += 1;
-
-                // Check for use statements
-                if let Some(captures) = use_regex.captures(line) {
-                    imports.push(ImportInfo {
-                        module: captures[1].to_string(),
-                        items: Vec::new(),
-                        is_wildcard: captures[1].contains('*'),
-                        line_number: line_num + 1,
-                    });
-                }
-
-                // Check for functions
-                if let Some(captures) = fn_regex.captures(line) {
-                    let is_public = captures.get(1).is_some();
-                    let is_async = captures.get(2).is_some();
-                    let name = captures[3].to_string();
-
-                    functions.push(FunctionInfo {
-                        name,
-                        parameters: Vec::new(), // TODO: Parse function parameters from signature
-                        return_type: None, // TODO: Parse return type from function signature
-                        is_async,
-                        is_public,
-                        line_number: line_num + 1,
-                        line_count: 0, // TODO: Calculate function body line count
-                        complexity: 1, // TODO: Calculate cyclomatic complexity
-                    });
-
-                    metrics.function_count += 1;
-                }
-
-                // Check for structs
-                if let Some(captures) = struct_regex.captures(line) {
-                    let is_public = captures.get(1).is_some();
-                    let name = captures[2].to_string();
-
-                    structs.push(StructInfo {
-                        name,
-                        fields: Vec::new(), // TODO: Parse struct fields from definition
-                        is_public,
-                        line_number: line_num + 1,
-                    });
-                }
-            }
-        }
+        let functions: Vec<FunctionInfo> = outline
+            .symbols
+            .iter()
+            .filter(|symbol| matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method))
+            .map(to_function)
+            .collect();
+
+        let classes: Vec<ClassInfo> = outline
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Class)
+            .map(|class| ClassInfo {
+                name: class.name.clone(),
+                base_classes: class.base_types.clone(),
+                methods: outline
+                    .children_of(class)
+                    .filter(|symbol| symbol.kind == SymbolKind::Method)
+                    .map(to_function)
+                    .collect(),
+                properties: class.fields.iter().map(|field| field.name.clone()).collect(),
+                is_public: class.is_public,
+                line_number: class.line_number,
+            })
+            .collect();
+
+        let structs: Vec<StructInfo> = outline
+            .symbols
+            .iter()
+            .filter(|symbol| matches!(symbol.kind, SymbolKind::Struct | SymbolKind::Enum | SymbolKind::Interface))
+            .map(|symbol| StructInfo {
+                name: symbol.name.clone(),
+                fields: symbol.fields.clone(),
+                is_public: symbol.is_public,
+                line_number: symbol.line_number,
+            })
+            .collect();
+
+        metrics.function_count = functions.len();
+        metrics.class_count = classes.len();
+        metrics.cyclomatic_complexity = functions.iter().map(|function| function.complexity).sum();
 
         Ok(Some(CodeAnalysis {
             file_path,
-            language: ProgrammingLanguage::Rust,
-            imports,
+            language: self.language,
+            dependencies: classify_dependencies(&outline.imports, self.language),
+            imports: outline.imports,
             functions,
-            classes: Vec::new(), // Rust doesn't have classes
+            classes,
             structs,
-            dependencies: Dependencies {
-                internal: Vec::new(),
-                external: Vec::new(),
-                standard: Vec::new(),
-            },
             metrics,
             issues: Vec::new(),
+            symbols: outline.symbols,
+            calls: outline.calls,
         }))
     }
 }
 
-/// Python language analyzer
-struct PythonAnalyzer;
-
-impl LanguageAnalyzer for PythonAnalyzer {
-    fn analyze(&self, content: &str, file_path: String) -> Result<Option<CodeAnalysis>> {
-        let mut imports = Vec::new();
-        let mut functions = Vec::new();
-        let mut classes = Vec::new();
-        let mut metrics = CodeMetrics {
-            total_lines: 0,
-            code_lines: 0,
-            comment_lines: 0,
-            blank_lines: 0,
-            cyclomatic_complexity: 0,
-            function_count: 0,
-            class_count: 0,
-        };
-
-        // Regex patterns for Python
-        let import_regex = Regex::new(r"^\s*(from\s+(\S+)\s+)?import\s+(.+)")?;
-        let def_regex = Regex::new(r"^(\s*)(async\s+)?def\s+(\w+)")?;
-        let class_regex = Regex::new(r"^(\s*)class\s+(\w+)")?;
-
-        for (line_num, line) in content.lines().enumerate() {
-            metrics.total_lines += 1;
-
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                metrics.blank_lines += 1;
-            } else if trimmed.starts_with('#') {
-                metrics.comment_lines += 1;
-            } else {
-                metrics.code_lines += 1;
-
-                // Check for imports
-                if let Some(captures) = import_regex.captures(line) {
-                    let module = if let Some(from_module) = captures.get(2) {
-                        from_module.as_str().to_string()
-                    } else {
-                        captures[3].to_string()
-                    };
-
-                    imports.push(ImportInfo {
-                        module,
-                        items: Vec::new(), // TODO: Parse specific imported items from import statement
-                        is_wildcard: captures[3].contains('*'),
-                        line_number: line_num + 1,
-                    });
-                }
-
-                // Check for function definitions
-                if let Some(captures) = def_regex.captures(line) {
-                    let _indent = captures[1].len();
-                    let is_async = captures.get(2).is_some();
-                    let name = captures[3].to_string();
-
-                    functions.push(FunctionInfo {
-                        name: name.clone(),
-                        parameters: Vec::new(), // TODO: Parse function parameters from signature
-                        return_type: None, // TODO: Parse Python type hints from function signature
-                        is_async,
-                        is_public: !name.starts_with('_'),
-                        line_number: line_num + 1,
-                        line_count: 0, // TODO: Calculate function body line count
-                        complexity: 1, // TODO: Calculate cyclomatic complexity
-                    });
-
-                    metrics.function_count += 1;
-                }
-
-                // Check for class definitions
-                if let Some(captures) = class_regex.captures(line) {
-                    let name = captures[2].to_string();
-
-                    classes.push(ClassInfo {
-                        name: name.clone(),
-                        base_classes: Vec::new(), // TODO: Parse base classes from class inheritance
-                        methods: Vec::new(), // TODO: Parse class methods and their signatures
-                        properties: Vec::new(), // TODO: Parse class properties and attributes
-                        is_public: !name.starts_with('_'),
-                        line_number: line_num + 1,
-                    });
-
-                    metrics.class_count += 1;
-                }
-            }
+/// Line counts by kind; other metrics are filled in by the caller
+fn line_metrics(content: &str, comment_prefixes: &[&str]) -> CodeMetrics {
+    let mut metrics = CodeMetrics {
+        total_lines: 0,
+        code_lines: 0,
+        comment_lines: 0,
+        blank_lines: 0,
+        cyclomatic_complexity: 0,
+        function_count: 0,
+        class_count: 0,
+    };
+
+    for line in content.lines() {
+        metrics.total_lines += 1;
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            metrics.blank_lines += 1;
+        } else if comment_prefixes.iter().any(|prefix| trimmed.starts_with(prefix)) {
+            metrics.comment_lines += 1;
+        } else {
+            metrics.code_lines += 1;
         }
-
-        Ok(Some(CodeAnalysis {
-            file_path,
-            language: ProgrammingLanguage::Python,
-            imports,
-            functions,
-            classes,
-            structs: Vec::new(), // Python doesn't have structs
-            dependencies: Dependencies {
-                internal: Vec::new(),
-                external: Vec::new(),
-                standard: Vec::new(),
-            },
-            metrics,
-            issues: Vec::new(),
-        }))
     }
-}
-
-/// JavaScript language analyzer
-struct JavaScriptAnalyzer;
 
-impl LanguageAnalyzer for JavaScriptAnalyzer {
-    fn analyze(&self, content: &str, file_path: String) -> Result<Option<CodeAnalysis>> {
-        // Similar implementation for JavaScript
-        GenericAnalyzer.analyze(content, file_path)
-    }
+    metrics
 }
 
-/// TypeScript language analyzer
-struct TypeScriptAnalyzer;
+/// Sort imported modules into standard library, project-internal and external
+fn classify_dependencies(imports: &[ImportInfo], language: ProgrammingLanguage) -> Dependencies {
+    const PYTHON_STDLIB: &[&str] = &[
+        "abc", "argparse", "asyncio", "collections", "contextlib", "dataclasses", "datetime", "enum",
+        "functools", "hashlib", "io", "itertools", "json", "logging", "math", "os", "pathlib", "random",
+        "re", "shutil", "subprocess", "sys", "tempfile", "threading", "time", "typing", "unittest", "uuid",
+    ];
+    const NODE_BUILTINS: &[&str] = &[
+        "assert", "buffer", "child_process", "crypto", "events", "fs", "fs/promises", "http", "https",
+        "net", "os", "path", "stream", "url", "util", "worker_threads", "zlib",
+    ];
+
+    let mut dependencies = Dependencies {
+        internal: Vec::new(),
+        external: Vec::new(),
+        standard: Vec::new(),
+    };
+
+    for import in imports {
+        let module = import.module.as_str();
+        let (root, internal) = match language {
+            ProgrammingLanguage::Rust => {
+                let root = module.split("::").next().unwrap_or(module);
+                (root, matches!(root, "crate" | "self" | "super"))
+            }
+            ProgrammingLanguage::Python => (module.split('.').next().unwrap_or(module), module.starts_with('.')),
+            _ => (module.trim_start_matches("node:"), module.starts_with('.') || module.starts_with('/')),
+        };
 
-impl LanguageAnalyzer for TypeScriptAnalyzer {
-    fn analyze(&self, content: &str, file_path: String) -> Result<Option<CodeAnalysis>> {
-        // Similar implementation for TypeScript
-        GenericAnalyzer.analyze(content, file_path)
+        let standard = match language {
+            ProgrammingLanguage::Rust => matches!(root, "std" | "core" | "alloc"),
+            ProgrammingLanguage::Python => PYTHON_STDLIB.contains(&root),
+            _ => module.starts_with("node:") || NODE_BUILTINS.contains(&root),
+        };
+
+        let bucket = if internal {
+            &mut dependencies.internal
+        } else if standard {
+            &mut dependencies.standard
+        } else {
+            &mut dependencies.external
+        };
+        if !bucket.iter().any(|existing| existing == module) {
+            bucket.push(module.to_string());
+        }
     }
+
+    dependencies
 }
 
 /// Generic analyzer for unknown languages
@@ -486,28 +565,7 @@ struct GenericAnalyzer;
 
 impl LanguageAnalyzer for GenericAnalyzer {
     fn analyze(&self, content: &str, file_path: String) -> Result<Option<CodeAnalysis>> {
-        let mut metrics = CodeMetrics {
-            total_lines: 0,
-            code_lines: 0,
-            comment_lines: 0,
-            blank_lines: 0,
-            cyclomatic_complexity: 0,
-            function_count: 0,
-            class_count: 0,
-        };
-
-        for line in content.lines() {
-            metrics.total_lines += 1;
-
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                metrics.blank_lines += 1;
-            } else if trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with("/*") {
-                metrics.comment_lines += 1;
-            } else {
-                metrics.code_lines += 1;
-            }
-        }
+        let metrics = line_metrics(content, &["//", "#", "/*"]);
 
         Ok(Some(CodeAnalysis {
             file_path,
@@ -523,6 +581,8 @@ impl LanguageAnalyzer for GenericAnalyzer {
             },
             metrics,
             issues: Vec::new(),
+            symbols: Vec::new(),
+            calls: Vec::new(),
         }))
     }
 }
@@ -593,6 +653,40 @@ class Point:
         assert_eq!(analysis.classes.len(), 1);
     }
 
+    #[test]
+    fn test_locate_symbol_with_evidence() {
+        let analyzer = CodeAnalyzer::new();
+        let rust = analyzer
+            .analyze_code(
+                "pub struct Cache;\n\nimpl Cache {\n    pub fn get(&self, key: &str) -> Option<String> {\n        None\n    }\n}\n",
+                ProgrammingLanguage::Rust,
+                "src/cache.rs".to_string(),
+            )
+            .unwrap()
+            .unwrap();
+        let typescript = analyzer
+            .analyze_code(
+                "import { Cache } from './cache';\n\nexport function get(key: string): string {\n  return key;\n}\n",
+                ProgrammingLanguage::TypeScript,
+                "src/cache.ts".to_string(),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(typescript.functions.len(), 1);
+        assert_eq!(typescript.dependencies.internal, vec!["./cache"]);
+        assert_eq!(rust.functions[0].line_count, 3);
+
+        let analyses = [rust, typescript];
+        let method = CodeAnalyzer::locate_symbol(&analyses, "Cache::get");
+        assert_eq!(method.len(), 1);
+        assert_eq!((method[0].file_path.as_str(), method[0].line_number, method[0].end_line), ("src/cache.rs", 4, 6));
+        assert_eq!(method[0].signature, "pub fn get(&self, key: &str) -> Option<String>");
+
+        assert_eq!(CodeAnalyzer::locate_symbol(&analyses, "get").len(), 2);
+        assert!(CodeAnalyzer::locate_symbol(&analyses, "Cache::remove").is_empty());
+    }
+
     #[test]
     fn test_hallucination_detection() {
         let analyzer = CodeAnalyzer::new();
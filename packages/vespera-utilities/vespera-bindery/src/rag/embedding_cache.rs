@@ -0,0 +1,284 @@
+//! # Embedding Cache
+//!
+//! Persistent map from (model, content hash) to embedding vector. Re-indexing
+//! an unchanged chunk or repeating a query is answered from the cache instead
+//! of calling the embedding backend again.
+//!
+//! The cache is bounded by entry count; when full, the least recently used
+//! entries are evicted. It is stored under `.vespera/rag/cache` so hits carry
+//! over between runs.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Embedding cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCacheConfig {
+    /// Serve repeated texts from the cache
+    pub enabled: bool,
+
+    /// Maximum number of cached vectors
+    pub max_entries: usize,
+}
+
+impl Default for EmbeddingCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 50_000,
+        }
+    }
+}
+
+/// Cache effectiveness counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingCacheStats {
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// `hits / (hits + misses)`, or 0 before the first lookup
+    pub hit_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    model_id: String,
+    vector: Vec<f32>,
+    /// Logical clock value of the last lookup or insert
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    clock: u64,
+}
+
+/// Size-bounded LRU cache of embedding vectors, persisted with bincode
+pub struct EmbeddingCache {
+    path: PathBuf,
+    config: EmbeddingCacheConfig,
+    state: Mutex<CacheState>,
+    dirty: AtomicBool,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl EmbeddingCache {
+    /// Load the cache stored at `path`, or start an empty one
+    pub fn open(path: &Path, config: EmbeddingCacheConfig) -> Result<Self> {
+        let mut state = CacheState::default();
+        if config.enabled && path.exists() {
+            let entries: HashMap<String, CacheEntry> = bincode::deserialize(&fs::read(path)?)?;
+            state.clock = entries.values().map(|e| e.last_used).max().unwrap_or(0);
+            state.entries = entries;
+        }
+
+        let cache = Self {
+            path: path.to_path_buf(),
+            config,
+            state: Mutex::new(state),
+            dirty: AtomicBool::new(false),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        };
+        // Honour a capacity lowered since the cache was written
+        cache.evict_over_capacity(&mut cache.state.lock().unwrap());
+        Ok(cache)
+    }
+
+    /// Cache file for a project's `.vespera` folder
+    pub fn for_vespera_path(vespera_path: &Path, config: EmbeddingCacheConfig) -> Result<Self> {
+        Self::open(&vespera_path.join("rag/cache/embeddings.bin"), config)
+    }
+
+    fn key(model_id: &str, text: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(text.as_bytes());
+        format!("{}:{:x}", model_id, hasher.finalize())
+    }
+
+    /// Cached vector for `text` under `model_id`
+    pub fn get(&self, model_id: &str, text: &str) -> Option<Vec<f32>> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let key = Self::key(model_id, text);
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+
+        match state.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = clock;
+                self.dirty.store(true, Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.vector.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Remember the vector computed for `text` under `model_id`
+    pub fn insert(&self, model_id: &str, text: &str, vector: Vec<f32>) {
+        if !self.config.enabled || self.config.max_entries == 0 {
+            return;
+        }
+
+        let key = Self::key(model_id, text);
+        let mut state = self.state.lock().unwrap();
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(key, CacheEntry {
+            model_id: model_id.to_string(),
+            vector,
+            last_used,
+        });
+        self.evict_over_capacity(&mut state);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Drop least recently used entries until the cache fits its capacity
+    fn evict_over_capacity(&self, state: &mut CacheState) {
+        let max_entries = self.config.max_entries;
+        if state.entries.len() <= max_entries {
+            return;
+        }
+
+        // Evict down to 90% so a full cache does not re-scan on every insert
+        let target = max_entries - max_entries / 10;
+        let mut by_age: Vec<(u64, String)> = state
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.last_used, key.clone()))
+            .collect();
+        by_age.sort_unstable();
+
+        let excess = state.entries.len() - target;
+        for (_, key) in by_age.into_iter().take(excess) {
+            state.entries.remove(&key);
+        }
+        self.evictions.fetch_add(excess as u64, Ordering::Relaxed);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Remove every entry computed by `model_id`; returns how many were removed
+    pub fn invalidate_model(&self, model_id: &str) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let before = state.entries.len();
+        state.entries.retain(|_, entry| entry.model_id != model_id);
+        let removed = before - state.entries.len();
+        if removed > 0 {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// Write the cache to disk if it changed since the last flush
+    pub fn flush(&self) -> Result<()> {
+        if !self.config.enabled || !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let bytes = bincode::serialize(&self.state.lock().unwrap().entries)?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename so a crash never leaves a truncated cache
+        let tmp_path = self.path.with_extension("bin.tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Current size and hit-rate counters
+    pub fn stats(&self) -> EmbeddingCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+
+        EmbeddingCacheStats {
+            entries: self.state.lock().unwrap().entries.len(),
+            capacity: self.config.max_entries,
+            hits,
+            misses,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(max_entries: usize) -> EmbeddingCacheConfig {
+        EmbeddingCacheConfig { enabled: true, max_entries }
+    }
+
+    #[test]
+    fn test_hits_are_keyed_by_model_and_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = EmbeddingCache::for_vespera_path(temp_dir.path(), config(10)).unwrap();
+
+        assert!(cache.get("mock", "hello").is_none());
+        cache.insert("mock", "hello", vec![1.0, 2.0]);
+        assert_eq!(cache.get("mock", "hello"), Some(vec![1.0, 2.0]));
+        assert!(cache.get("openai:text-embedding-3-small", "hello").is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 1));
+        assert!((stats.hit_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = EmbeddingCache::for_vespera_path(temp_dir.path(), config(10)).unwrap();
+
+        for i in 0..10 {
+            cache.insert("mock", &format!("text {}", i), vec![i as f32]);
+        }
+        // Touch the oldest entry so it survives eviction
+        assert!(cache.get("mock", "text 0").is_some());
+        cache.insert("mock", "text 10", vec![10.0]);
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 9);
+        assert_eq!(stats.evictions, 2);
+        assert!(cache.get("mock", "text 0").is_some());
+        assert!(cache.get("mock", "text 1").is_none());
+        assert!(cache.get("mock", "text 2").is_none());
+        assert!(cache.get("mock", "text 10").is_some());
+    }
+
+    #[test]
+    fn test_persists_across_runs() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let cache = EmbeddingCache::for_vespera_path(temp_dir.path(), config(10)).unwrap();
+            cache.insert("mock", "persisted", vec![0.5; 4]);
+            cache.flush().unwrap();
+        }
+
+        let cache = EmbeddingCache::for_vespera_path(temp_dir.path(), config(10)).unwrap();
+        assert_eq!(cache.get("mock", "persisted"), Some(vec![0.5; 4]));
+        assert_eq!(cache.invalidate_model("mock"), 1);
+        assert!(cache.get("mock", "persisted").is_none());
+    }
+}
@@ -6,12 +6,20 @@
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use anyhow::Result;
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
-use super::DocumentChunk;
+use super::{DocumentChunk, VectorStorage};
+use super::vector_store::HnswVectorStorage;
+use super::lexical::LexicalIndex;
+use super::embedding_cache::{EmbeddingCache, EmbeddingCacheConfig, EmbeddingCacheStats};
+use super::batching::{plan_batches, EmbeddingBatchConfig, RequestPacer};
+use super::circuit_breaker::CircuitBreaker;
 
 #[cfg(any(feature = "embeddings-local", feature = "embeddings-onnx", feature = "embeddings-api"))]
 pub use super::embeddings_impl::{UnifiedEmbedder, EmbeddingConfig, EmbeddingProvider};
@@ -29,6 +37,18 @@ pub enum EmbeddingModel {
     Mock,
 }
 
+impl EmbeddingModel {
+    /// Identifier that keys this model's vectors in the embedding cache
+    pub fn cache_id(&self) -> String {
+        match self {
+            EmbeddingModel::LocalModel(name) => format!("local:{}", name),
+            EmbeddingModel::OpenAI(name) => format!("openai:{}", name),
+            EmbeddingModel::Cohere(name) => format!("cohere:{}", name),
+            EmbeddingModel::Mock => "mock".to_string(),
+        }
+    }
+}
+
 impl Default for EmbeddingModel {
     fn default() -> Self {
         // Default to mock for now, can be changed to LocalModel once integrated
@@ -43,6 +63,11 @@ pub struct EmbeddingStats {
     pub total_documents: usize,
     pub index_size_bytes: u64,
     pub model_info: String,
+    #[serde(default)]
+    pub cache: EmbeddingCacheStats,
+    /// Embedding requests sent to the backend, including retries
+    #[serde(default)]
+    pub backend_requests: u64,
 }
 
 /// A stored embedding with metadata
@@ -56,31 +81,93 @@ struct StoredEmbedding {
     pub created_at: DateTime<Utc>,
 }
 
+/// Embedding backend failure, in a form the circuit breaker can record
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+struct EmbeddingRequestError(String);
+
 /// Service for managing document embeddings
 pub struct EmbeddingService {
     model: EmbeddingModel,
     storage_path: PathBuf,
     embeddings: HashMap<String, StoredEmbedding>,
     document_index: HashMap<Uuid, Vec<String>>, // Document ID -> Chunk IDs
+    vector_store: HnswVectorStorage,
+    lexical_index: LexicalIndex,
+    cache: EmbeddingCache,
+    batch_config: EmbeddingBatchConfig,
+    pacer: Option<Arc<RequestPacer>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    backend_requests: Arc<AtomicU64>,
 }
 
 impl EmbeddingService {
     /// Create a new embedding service
     pub async fn new(model: EmbeddingModel, base_path: &Path) -> Result<Self> {
+        Self::new_with_cache_config(model, base_path, EmbeddingCacheConfig::default()).await
+    }
+
+    /// Create a new embedding service with a custom embedding cache configuration
+    pub async fn new_with_cache_config(
+        model: EmbeddingModel,
+        base_path: &Path,
+        cache_config: EmbeddingCacheConfig,
+    ) -> Result<Self> {
         let storage_path = base_path.join("rag/embeddings");
         fs::create_dir_all(&storage_path)?;
 
         // Load existing embeddings
         let (embeddings, document_index) = Self::load_embeddings(&storage_path)?;
 
+        // Backfill the vector index for embeddings stored before it existed
+        let vector_store = HnswVectorStorage::for_vespera_path(base_path)?;
+        if vector_store.is_empty() && !embeddings.is_empty() {
+            for stored in embeddings.values() {
+                vector_store.store_embedding(&stored.id, &stored.embedding, Self::vector_metadata(stored))?;
+            }
+            vector_store.flush()?;
+        }
+
+        let mut lexical_index = LexicalIndex::open(&base_path.join("rag/indices/lexical.json"))?;
+        if lexical_index.is_empty() && !embeddings.is_empty() {
+            for stored in embeddings.values() {
+                lexical_index.index_chunk(&stored.id, stored.document_id, &stored.content);
+            }
+            lexical_index.save()?;
+        }
+
+        let cache = EmbeddingCache::for_vespera_path(base_path, cache_config)?;
+
         Ok(Self {
             model,
             storage_path,
             embeddings,
             document_index,
+            vector_store,
+            lexical_index,
+            cache,
+            batch_config: EmbeddingBatchConfig::default(),
+            pacer: None,
+            circuit_breaker: None,
+            backend_requests: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// Batch and pace embedding requests according to `config`
+    pub fn with_batch_config(mut self, config: EmbeddingBatchConfig) -> Result<Self> {
+        config.validate()?;
+        self.pacer = config.requests_per_minute.map(|rpm| Arc::new(RequestPacer::per_minute(rpm)));
+        self.batch_config = config;
+        Ok(self)
+    }
+
+    /// Send embedding requests through `breaker`, which retries failures and
+    /// stops calling a backend that keeps failing
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
     /// Load embeddings from storage
     fn load_embeddings(
         storage_path: &Path,
@@ -107,28 +194,142 @@ impl EmbeddingService {
         Ok((embeddings, document_index))
     }
 
+    /// Metadata attached to a chunk's vector for filtered search
+    fn vector_metadata(stored: &StoredEmbedding) -> HashMap<String, serde_json::Value> {
+        let mut metadata = stored.metadata.clone();
+        metadata.insert("document_id".to_string(), serde_json::json!(stored.document_id));
+        metadata
+    }
+
+    /// Write the vector index snapshot and the embedding cache to disk
+    pub fn flush(&self) -> Result<()> {
+        self.vector_store.flush()?;
+        self.cache.flush()
+    }
+
     /// Save embeddings to storage
     async fn save_embeddings(&self) -> Result<()> {
         let index_path = self.storage_path.join("index.json");
         let embeddings: Vec<&StoredEmbedding> = self.embeddings.values().collect();
         let content = serde_json::to_string_pretty(&embeddings)?;
         fs::write(&index_path, content)?;
+        self.cache.flush()?;
         Ok(())
     }
 
-    /// Generate embedding for text
+    /// Embedding for text, served from the cache when this model has seen it before
     async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        match &self.model {
-            EmbeddingModel::Mock => {
-                // Generate mock embeddings based on text hash for consistency
-                let mut mock_embedding = vec![0.0; 384]; // Common embedding size
-                let hash = Self::simple_hash(text);
+        self.embed_batch(&[text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("No embedding generated"))
+    }
 
-                for (i, val) in mock_embedding.iter_mut().enumerate() {
-                    *val = ((hash + i as u64) % 1000) as f32 / 1000.0;
+    /// Embeddings for `texts`, in input order
+    ///
+    /// Cached texts are answered locally; the rest are grouped into
+    /// provider-sized requests that run with bounded concurrency.
+    pub async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let model_id = self.model.cache_id();
+        let mut results: Vec<Option<Vec<f32>>> = texts
+            .iter()
+            .map(|text| self.cache.get(&model_id, text))
+            .collect();
+
+        // Unique uncached texts, each with the positions it fills
+        let mut pending: Vec<String> = Vec::new();
+        let mut slots: Vec<Vec<usize>> = Vec::new();
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for (i, text) in texts.iter().enumerate() {
+            if results[i].is_some() {
+                continue;
+            }
+            let index = *seen.entry(text.as_str()).or_insert_with(|| {
+                pending.push(text.clone());
+                slots.push(Vec::new());
+                pending.len() - 1
+            });
+            slots[index].push(i);
+        }
+
+        if !pending.is_empty() {
+            let (max_batch_size, max_batch_tokens) = self.batch_config.limits_for(&self.model);
+            let batches = plan_batches(&pending, max_batch_size, max_batch_tokens);
+            tracing::debug!(texts = pending.len(), requests = batches.len(), "Embedding uncached texts");
+
+            let responses: Vec<(std::ops::Range<usize>, Vec<Vec<f32>>)> = futures::stream::iter(batches)
+                .map(|range| {
+                    let batch = pending[range.clone()].to_vec();
+                    async move { Ok::<_, anyhow::Error>((range, self.request_embeddings(batch).await?)) }
+                })
+                .buffer_unordered(self.batch_config.max_concurrent_requests.max(1))
+                .try_collect()
+                .await?;
+
+            for (range, vectors) in responses {
+                if vectors.len() != range.len() {
+                    anyhow::bail!("Embedding backend returned {} vectors for {} texts", vectors.len(), range.len());
                 }
+                for (index, vector) in range.zip(vectors) {
+                    self.cache.insert(&model_id, &pending[index], vector.clone());
+                    for &slot in &slots[index] {
+                        results[slot] = Some(vector.clone());
+                    }
+                }
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|vector| vector.ok_or_else(|| anyhow::anyhow!("No embedding generated")))
+            .collect()
+    }
+
+    /// Send one batch to the backend, paced and behind the circuit breaker
+    async fn request_embeddings(&self, batch: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if let Some(pacer) = &self.pacer {
+            pacer.wait().await;
+        }
+
+        let Some(breaker) = &self.circuit_breaker else {
+            self.backend_requests.fetch_add(1, Ordering::Relaxed);
+            return Self::compute_embeddings(&self.model, &batch).await;
+        };
+
+        let model = self.model.clone();
+        let backend_requests = Arc::clone(&self.backend_requests);
+        breaker
+            .execute(move || {
+                let model = model.clone();
+                let batch = batch.clone();
+                backend_requests.fetch_add(1, Ordering::Relaxed);
+                Box::pin(async move {
+                    Self::compute_embeddings(&model, &batch)
+                        .await
+                        .map_err(|e| EmbeddingRequestError(e.to_string()))
+                })
+            })
+            .await
+    }
 
-                Ok(mock_embedding)
+    /// Call the embedding backend for one request, bypassing the cache
+    async fn compute_embeddings(model: &EmbeddingModel, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match model {
+            EmbeddingModel::Mock => {
+                // Generate mock embeddings based on text hash for consistency
+                Ok(texts
+                    .iter()
+                    .map(|text| {
+                        let mut mock_embedding = vec![0.0; 384]; // Common embedding size
+                        let hash = Self::simple_hash(text);
+
+                        for (i, val) in mock_embedding.iter_mut().enumerate() {
+                            *val = ((hash + i as u64) % 1000) as f32 / 1000.0;
+                        }
+
+                        mock_embedding
+                    })
+                    .collect())
             }
             EmbeddingModel::LocalModel(model_name) => {
                 #[cfg(any(feature = "embeddings-local", feature = "embeddings-onnx"))]
@@ -138,11 +339,12 @@ impl EmbeddingService {
                     let config = EmbeddingConfig {
                         provider: EmbeddingProvider::Local,
                         model_name: model_name.clone(),
+                        max_batch_size: texts.len().max(1),
                         ..Default::default()
                     };
 
                     let embedder = UnifiedEmbedder::new(config).await?;
-                    embedder.embed_single(text).await
+                    embedder.embed(texts).await
                 }
 
                 #[cfg(not(any(feature = "embeddings-local", feature = "embeddings-onnx")))]
@@ -156,11 +358,12 @@ impl EmbeddingService {
                     let config = EmbeddingConfig {
                         provider: EmbeddingProvider::OpenAI,
                         model_name: model_name.clone(),
+                        max_batch_size: texts.len().max(1),
                         ..Default::default()
                     };
 
                     let embedder = UnifiedEmbedder::new(config).await?;
-                    embedder.embed_single(text).await
+                    embedder.embed(texts).await
                 }
 
                 #[cfg(not(feature = "embeddings-api"))]
@@ -174,11 +377,12 @@ impl EmbeddingService {
                     let config = EmbeddingConfig {
                         provider: EmbeddingProvider::Cohere,
                         model_name: model_name.clone(),
+                        max_batch_size: texts.len().max(1),
                         ..Default::default()
                     };
 
                     let embedder = UnifiedEmbedder::new(config).await?;
-                    embedder.embed_single(text).await
+                    embedder.embed(texts).await
                 }
 
                 #[cfg(not(feature = "embeddings-api"))]
@@ -194,30 +398,45 @@ impl EmbeddingService {
 
     /// Index a document chunk
     pub async fn index_chunk(&mut self, chunk: &DocumentChunk) -> Result<()> {
-        // Generate embedding
-        let embedding = self.generate_embedding(&chunk.content).await?;
-
-        // Create stored embedding
-        let stored = StoredEmbedding {
-            id: chunk.id.clone(),
-            document_id: chunk.document_id,
-            embedding,
-            content: chunk.content.clone(),
-            metadata: chunk.metadata.clone(),
-            created_at: Utc::now(),
-        };
+        self.index_chunks(std::slice::from_ref(chunk)).await
+    }
 
-        // Store in memory
-        self.embeddings.insert(chunk.id.clone(), stored);
+    /// Index several chunks, embedding them in batched requests
+    pub async fn index_chunks(&mut self, chunks: &[DocumentChunk]) -> Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        // Generate embeddings
+        let texts: Vec<String> = chunks.iter().map(|chunk| chunk.content.clone()).collect();
+        let embeddings = self.embed_batch(&texts).await?;
 
-        // Update document index
-        self.document_index
-            .entry(chunk.document_id)
-            .or_insert_with(Vec::new)
-            .push(chunk.id.clone());
+        for (chunk, embedding) in chunks.iter().zip(embeddings) {
+            // Create stored embedding
+            let stored = StoredEmbedding {
+                id: chunk.id.clone(),
+                document_id: chunk.document_id,
+                embedding,
+                content: chunk.content.clone(),
+                metadata: chunk.metadata.clone(),
+                created_at: Utc::now(),
+            };
+
+            // Store in the vector and lexical indices and in memory
+            self.vector_store.store_embedding(&stored.id, &stored.embedding, Self::vector_metadata(&stored))?;
+            self.lexical_index.index_chunk(&stored.id, stored.document_id, &stored.content);
+            self.embeddings.insert(chunk.id.clone(), stored);
+
+            // Update document index
+            self.document_index
+                .entry(chunk.document_id)
+                .or_insert_with(Vec::new)
+                .push(chunk.id.clone());
+        }
 
         // Save to disk
         self.save_embeddings().await?;
+        self.lexical_index.save()?;
 
         Ok(())
     }
@@ -227,28 +446,58 @@ impl EmbeddingService {
         &self,
         query: &str,
         limit: usize,
+    ) -> Result<Vec<(String, f32, String)>> {
+        self.search_with_filter(query, limit, None).await
+    }
+
+    /// Search for similar content among chunks whose metadata matches `filter`
+    pub async fn search_with_filter(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: Option<HashMap<String, serde_json::Value>>,
     ) -> Result<Vec<(String, f32, String)>> {
         // Generate query embedding
         let query_embedding = self.generate_embedding(query).await?;
 
-        // Calculate similarities
-        let mut similarities: Vec<(String, f32, String)> = self
-            .embeddings
-            .values()
-            .map(|stored| {
-                let similarity = Self::cosine_similarity(&query_embedding, &stored.embedding);
-                (stored.id.clone(), similarity, stored.content.clone())
+        // Results come back sorted by similarity (descending)
+        let results = self.vector_store.search(&query_embedding, limit, filter)?;
+
+        Ok(results
+            .into_iter()
+            .filter_map(|(id, similarity, _)| {
+                self.embeddings
+                    .get(&id)
+                    .map(|stored| (id, similarity, stored.content.clone()))
             })
-            .collect();
+            .collect())
+    }
 
-        // Sort by similarity (descending)
-        similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    /// Keyword (BM25) search, best match first
+    pub async fn lexical_search(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, f32, String)>> {
+        Ok(self
+            .lexical_index
+            .search(query, limit)
+            .into_iter()
+            .filter_map(|(id, score)| {
+                self.embeddings
+                    .get(&id)
+                    .map(|stored| (id, score, stored.content.clone()))
+            })
+            .collect())
+    }
 
-        // Return top results
-        Ok(similarities.into_iter().take(limit).collect())
+    /// Statistics reported by the vector index
+    pub fn vector_store_stats(&self) -> Result<HashMap<String, serde_json::Value>> {
+        self.vector_store.stats()
     }
 
     /// Calculate cosine similarity between two vectors
+    #[cfg(test)]
     fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
         if a.len() != b.len() {
             return 0.0;
@@ -270,9 +519,12 @@ impl EmbeddingService {
         if let Some(chunk_ids) = self.document_index.remove(&document_id) {
             for chunk_id in chunk_ids {
                 self.embeddings.remove(&chunk_id);
+                self.vector_store.delete(&chunk_id)?;
             }
+            self.lexical_index.remove_document(document_id);
 
             self.save_embeddings().await?;
+            self.lexical_index.save()?;
         }
 
         Ok(())
@@ -299,6 +551,8 @@ impl EmbeddingService {
             total_documents: self.document_index.len(),
             index_size_bytes: index_size,
             model_info,
+            cache: self.cache.stats(),
+            backend_requests: self.backend_requests.load(Ordering::Relaxed),
         })
     }
 
@@ -306,7 +560,7 @@ impl EmbeddingService {
     pub async fn health_check(&self) -> Result<()> {
         // Test embedding generation
         let test_text = "Health check test";
-        let _ = self.generate_embedding(test_text).await?;
+        let _ = Self::compute_embeddings(&self.model, &[test_text.to_string()]).await?;
 
         // Check storage is accessible
         if !self.storage_path.exists() {
@@ -327,14 +581,17 @@ impl EmbeddingService {
             .map(|e| (e.id.clone(), e.document_id, e.content.clone(), e.metadata.clone()))
             .collect();
 
-        // Clear existing embeddings
+        // Clear existing embeddings (the new model may use a different dimension)
         self.embeddings.clear();
         self.document_index.clear();
+        self.vector_store.clear()?;
 
         // Re-generate embeddings
+        let texts: Vec<String> = chunks.iter().map(|(_, _, content, _)| content.clone()).collect();
+        let embeddings = self.embed_batch(&texts).await?;
+
         let mut reindexed = 0;
-        for (id, doc_id, content, metadata) in chunks {
-            let embedding = self.generate_embedding(&content).await?;
+        for ((id, doc_id, content, metadata), embedding) in chunks.into_iter().zip(embeddings) {
 
             let stored = StoredEmbedding {
                 id: id.clone(),
@@ -345,6 +602,7 @@ impl EmbeddingService {
                 created_at: Utc::now(),
             };
 
+            self.vector_store.store_embedding(&id, &stored.embedding, Self::vector_metadata(&stored))?;
             self.embeddings.insert(id.clone(), stored);
             self.document_index
                 .entry(doc_id)
@@ -386,6 +644,8 @@ impl EmbeddingService {
         for embedding_value in embeddings {
             let stored: StoredEmbedding = serde_json::from_value(embedding_value.clone())?;
 
+            self.vector_store.store_embedding(&stored.id, &stored.embedding, Self::vector_metadata(&stored))?;
+            self.lexical_index.index_chunk(&stored.id, stored.document_id, &stored.content);
             self.embeddings.insert(stored.id.clone(), stored.clone());
             self.document_index
                 .entry(stored.document_id)
@@ -396,11 +656,21 @@ impl EmbeddingService {
         }
 
         self.save_embeddings().await?;
+        self.lexical_index.save()?;
 
         Ok(imported)
     }
 }
 
+impl Drop for EmbeddingService {
+    fn drop(&mut self) {
+        // Query embeddings are only cached in memory until the next save
+        if let Err(e) = self.cache.flush() {
+            tracing::warn!(error = %e, "Failed to persist embedding cache");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -502,4 +772,98 @@ mod tests {
         service.delete_document(doc_id).await.unwrap();
         assert_eq!(service.embeddings.len(), 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_filtered_search_and_persisted_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let doc_a = Uuid::new_v4();
+        let doc_b = Uuid::new_v4();
+
+        {
+            let mut service = EmbeddingService::new(EmbeddingModel::Mock, temp_dir.path()).await.unwrap();
+            for (i, doc_id) in [doc_a, doc_b].into_iter().enumerate() {
+                let chunk = DocumentChunk {
+                    id: format!("{}_chunk_0", doc_id),
+                    document_id: doc_id,
+                    content: format!("Shared programming content {}", i),
+                    chunk_index: 0,
+                    total_chunks: 1,
+                    start_char: 0,
+                    end_char: 28,
+                    metadata: HashMap::new(),
+                };
+                service.index_chunk(&chunk).await.unwrap();
+            }
+        }
+
+        // Reopening loads the HNSW index from .vespera/rag/indices
+        let service = EmbeddingService::new(EmbeddingModel::Mock, temp_dir.path()).await.unwrap();
+        assert!(temp_dir.path().join("rag/indices").exists());
+
+        let filter: HashMap<String, serde_json::Value> =
+            [("document_id".to_string(), serde_json::json!(doc_b))].into_iter().collect();
+        let results = service.search_with_filter("programming", 5, Some(filter)).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, format!("{}_chunk_0", doc_b));
+
+        let stats = service.vector_store_stats().unwrap();
+        assert_eq!(stats["total_vectors"], serde_json::json!(2));
+    }
+
+    #[tokio::test]
+    async fn test_cache_survives_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let service = EmbeddingService::new(EmbeddingModel::Mock, temp_dir.path()).await.unwrap();
+            service.generate_embedding("unchanged chunk").await.unwrap();
+            assert_eq!(service.cache.stats().misses, 1);
+        }
+
+        let service = EmbeddingService::new(EmbeddingModel::Mock, temp_dir.path()).await.unwrap();
+        service.generate_embedding("unchanged chunk").await.unwrap();
+        let stats = service.get_stats().await.unwrap().cache;
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_index_chunks_in_batches() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut service = EmbeddingService::new(EmbeddingModel::Mock, temp_dir.path())
+            .await
+            .unwrap()
+            .with_batch_config(EmbeddingBatchConfig {
+                max_batch_size: Some(8),
+                max_concurrent_requests: 3,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let doc_id = Uuid::new_v4();
+        let chunks: Vec<DocumentChunk> = (0..100)
+            .map(|i| DocumentChunk {
+                id: format!("{}_chunk_{}", doc_id, i),
+                document_id: doc_id,
+                // Every chunk after the 90th repeats an earlier text
+                content: format!("chunk body {}", i % 90),
+                chunk_index: i,
+                total_chunks: 100,
+                start_char: 0,
+                end_char: 12,
+                metadata: HashMap::new(),
+            })
+            .collect();
+
+        service.index_chunks(&chunks).await.unwrap();
+        let stats = service.get_stats().await.unwrap();
+        assert_eq!(stats.total_embeddings, 100);
+        assert_eq!(stats.backend_requests, 12); // 90 unique texts / 8 per request
+
+        // Vectors line up with their chunks
+        let single = service.generate_embedding("chunk body 42").await.unwrap();
+        assert_eq!(service.embeddings[&format!("{}_chunk_42", doc_id)].embedding, single);
+
+        // Re-indexing unchanged content is served from the cache
+        service.reindex_with_model(EmbeddingModel::Mock).await.unwrap();
+        assert_eq!(service.get_stats().await.unwrap().backend_requests, 12);
+    }
+}
@@ -308,6 +308,7 @@ pub mod onnx {
 pub mod api {
     use super::*;
     use reqwest::Client;
+    use crate::observability::trace_headers;
     use std::env;
 
     #[derive(Serialize)]
@@ -393,6 +394,7 @@ pub mod api {
                 let response = self.client
                     .post(url)
                     .header("Authorization", format!("Bearer {}", self.api_key))
+                    .headers(trace_headers())
                     .json(&request)
                     .send()
                     .await?
@@ -425,6 +427,7 @@ pub mod api {
                 let response = self.client
                     .post(url)
                     .header("Authorization", format!("Bearer {}", self.api_key))
+                    .headers(trace_headers())
                     .json(&request)
                     .send()
                     .await?
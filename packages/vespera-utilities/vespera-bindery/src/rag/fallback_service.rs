@@ -184,6 +184,7 @@ impl FallbackEmbeddingService {
                         updated_at: chrono::Utc::now(),
                         tags: Vec::new(),
                         project_id: None,
+                        bindery_source: None,
                     };
 
                     results.push(SearchResult {
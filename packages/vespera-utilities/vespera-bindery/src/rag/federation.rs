@@ -0,0 +1,143 @@
+//! # Federated Search
+//!
+//! Searches several registered projects at once. Each project is searched
+//! with its own index and its scores are min-max normalized to `[0, 1]`
+//! before merging, so a project whose ranking produces larger raw scores
+//! (a different embedding model, or simply more matching text) does not
+//! crowd out the others.
+//!
+//! Projects marked private in their settings are never searched from
+//! another project, even when requested by ID.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{SearchOptions, SearchResult};
+
+/// Options for [`RAGService::search_federated`](super::RAGService::search_federated)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FederatedSearchOptions {
+    /// Projects to search besides the home project (all registered projects when `None`)
+    pub projects: Option<Vec<Uuid>>,
+
+    /// Results taken from each project before merging (the overall limit when `None`)
+    pub per_project_limit: Option<usize>,
+
+    /// Ranking options used in every project (each project's configured options when `None`)
+    pub search: Option<SearchOptions>,
+}
+
+/// A search hit tagged with the project it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedSearchResult {
+    pub project_id: Uuid,
+    pub project_name: String,
+    /// Score before per-project normalization
+    pub raw_score: f32,
+    /// The hit, with `score` normalized within its project
+    pub result: SearchResult,
+}
+
+/// Per-project slice of [`RAGStats`](super::RAGStats)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub project_id: Uuid,
+    pub name: String,
+    pub total_documents: usize,
+    pub last_indexed: Option<chrono::DateTime<chrono::Utc>>,
+    pub private: bool,
+    /// An index for this project is open for federated search
+    pub federated: bool,
+}
+
+/// Min-max normalize scores in place; equal scores all become 1.0
+pub fn normalize_scores(results: &mut [SearchResult]) {
+    let (min, max) = results.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), result| {
+        (min.min(result.score), max.max(result.score))
+    });
+
+    let range = max - min;
+    for result in results {
+        result.score = if range > f32::EPSILON { (result.score - min) / range } else { 1.0 };
+    }
+}
+
+/// Merge per-project hits by normalized score, keeping the best `limit`
+///
+/// Ties are broken by raw score so the merge is deterministic.
+pub fn merge_results(mut results: Vec<FederatedSearchResult>, limit: usize) -> Vec<FederatedSearchResult> {
+    results.sort_by(|a, b| {
+        b.result
+            .score
+            .total_cmp(&a.result.score)
+            .then(b.raw_score.total_cmp(&a.raw_score))
+    });
+    results.truncate(limit);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::{DocumentMetadata, DocumentType};
+    use chrono::Utc;
+
+    fn hit(score: f32) -> SearchResult {
+        SearchResult {
+            document_id: Uuid::new_v4(),
+            chunk_id: format!("chunk-{}", score),
+            content: String::new(),
+            score,
+            metadata: DocumentMetadata {
+                id: Uuid::new_v4(),
+                title: String::new(),
+                document_type: DocumentType::Text,
+                source_path: None,
+                content_hash: String::new(),
+                indexed_at: Utc::now(),
+                updated_at: Utc::now(),
+                tags: Vec::new(),
+                project_id: None,
+                bindery_source: None,
+            },
+            highlights: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_normalization_puts_projects_on_one_scale() {
+        // Project A scores on a much larger scale than project B
+        let mut a = vec![hit(40.0), hit(30.0), hit(20.0)];
+        let mut b = vec![hit(0.9), hit(0.5)];
+        normalize_scores(&mut a);
+        normalize_scores(&mut b);
+
+        assert_eq!(a.iter().map(|r| r.score).collect::<Vec<_>>(), vec![1.0, 0.5, 0.0]);
+        assert_eq!(b.iter().map(|r| r.score).collect::<Vec<_>>(), vec![1.0, 0.0]);
+
+        let mut single = vec![hit(0.2)];
+        normalize_scores(&mut single);
+        assert_eq!(single[0].score, 1.0);
+    }
+
+    #[test]
+    fn test_merge_interleaves_by_normalized_score() {
+        let tag = |project: u128, raw: f32, score: f32| {
+            let mut result = hit(score);
+            result.score = score;
+            FederatedSearchResult {
+                project_id: Uuid::from_u128(project),
+                project_name: project.to_string(),
+                raw_score: raw,
+                result,
+            }
+        };
+
+        let merged = merge_results(
+            vec![tag(1, 40.0, 1.0), tag(1, 30.0, 0.5), tag(2, 0.9, 1.0), tag(2, 0.7, 0.8)],
+            3,
+        );
+        let order: Vec<(u128, f32)> = merged.iter().map(|r| (r.project_id.as_u128(), r.raw_score)).collect();
+        assert_eq!(order, vec![(1, 40.0), (2, 0.9), (2, 0.7)]);
+    }
+}
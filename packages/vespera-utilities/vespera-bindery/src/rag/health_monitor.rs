@@ -2,18 +2,30 @@
 //!
 //! Provides comprehensive health monitoring for all external services
 //! including circuit breakers, embedding providers, and fallback mechanisms.
+//!
+//! When a service changes status the monitor runs the matching
+//! [`RemediationRule`]s from its config (reloading a provider, resetting
+//! circuit breakers or switching the fallback strategy) and keeps a log of
+//! what it did in the [`SystemHealthReport`].
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use anyhow::Result;
 use serde::{Serialize, Deserialize};
 use tracing::{info, warn, error, debug};
 use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
 use tokio::time::sleep;
 
 use super::{
-    circuit_breaker::{CircuitBreakerHealth},
+    circuit_breaker::{CircuitBreakerHealth, CircuitBreakerRegistry, CircuitState},
+    fallback_service::FallbackStrategy,
 };
+use crate::providers::ProviderManager;
+
+/// Remediation records kept for the health report
+const REMEDIATION_LOG_CAPACITY: usize = 50;
 
 /// Health check configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +42,9 @@ pub struct HealthCheckConfig {
     pub auto_reset_circuit_breakers: bool,
     /// Enable detailed logging of health checks
     pub verbose_logging: bool,
+    /// Actions to run when a service changes status
+    #[serde(default)]
+    pub remediation: Vec<RemediationRule>,
 }
 
 impl Default for HealthCheckConfig {
@@ -41,10 +56,63 @@ impl Default for HealthCheckConfig {
             recovery_threshold: 2,
             auto_reset_circuit_breakers: false,
             verbose_logging: false,
+            remediation: Vec::new(),
         }
     }
 }
 
+/// An action taken automatically when a service enters a status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationRule {
+    /// Service name to match; a trailing `*` matches by prefix (e.g. `provider:*`)
+    pub service: String,
+    /// Status whose entry triggers the action
+    pub on_status: SystemHealthStatus,
+    pub action: RemediationAction,
+    /// Minimum time between two runs of this rule for the same service
+    #[serde(default = "default_remediation_cooldown")]
+    pub cooldown: Duration,
+}
+
+fn default_remediation_cooldown() -> Duration {
+    Duration::from_secs(60)
+}
+
+impl RemediationRule {
+    fn matches(&self, service_name: &str, status: &SystemHealthStatus) -> bool {
+        let service_matches = match self.service.strip_suffix('*') {
+            Some(prefix) => service_name.starts_with(prefix),
+            None => self.service == service_name,
+        };
+        service_matches && self.on_status == *status
+    }
+}
+
+/// Remediation actions the monitor can run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RemediationAction {
+    /// Reload a provider, restarting its process; `None` reloads the provider
+    /// named by a `provider:{id}` service
+    RestartProvider { provider_id: Option<String> },
+    /// Reset a circuit breaker; `None` resets the breaker named by a
+    /// `circuit:{name}` service, or every breaker for other services
+    ResetCircuitBreaker { service_name: Option<String> },
+    /// Replace the shared fallback strategy
+    SwitchFallbackStrategy { strategy: FallbackStrategy },
+}
+
+/// A remediation the monitor ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationRecord {
+    pub timestamp: DateTime<Utc>,
+    pub service_name: String,
+    pub from_status: SystemHealthStatus,
+    pub to_status: SystemHealthStatus,
+    pub action: RemediationAction,
+    pub succeeded: bool,
+    pub error_message: Option<String>,
+}
+
 /// Overall system health status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SystemHealthStatus {
@@ -104,6 +172,12 @@ pub struct SystemHealthReport {
     pub successful_requests: u64,
     pub failed_requests: u64,
     pub system_metrics: SystemMetrics,
+    /// Fallback strategy currently in effect
+    #[serde(default)]
+    pub fallback_strategy: FallbackStrategy,
+    /// Most recent remediations, oldest first
+    #[serde(default)]
+    pub remediations: Vec<RemediationRecord>,
 }
 
 /// System performance metrics
@@ -129,6 +203,12 @@ pub struct HealthMonitor {
     response_times: Vec<u64>,
     circuit_breaker_trips: u64,
     fallback_activations: u64,
+    provider_manager: Option<Arc<ProviderManager>>,
+    circuit_breakers: Option<Arc<CircuitBreakerRegistry>>,
+    fallback_strategy: Arc<RwLock<FallbackStrategy>>,
+    remediations: VecDeque<RemediationRecord>,
+    /// Last run of each (rule index, service) pair, for cooldowns
+    last_remediation: HashMap<(usize, String), Instant>,
 }
 
 impl HealthMonitor {
@@ -146,153 +226,313 @@ impl HealthMonitor {
             response_times: Vec::new(),
             circuit_breaker_trips: 0,
             fallback_activations: 0,
+            provider_manager: None,
+            circuit_breakers: None,
+            fallback_strategy: Arc::new(RwLock::new(FallbackStrategy::default())),
+            remediations: VecDeque::new(),
+            last_remediation: HashMap::new(),
         }
     }
-    
+
+    /// Monitor the providers loaded in `manager` as `provider:{id}` services
+    pub fn with_provider_manager(mut self, manager: Arc<ProviderManager>) -> Self {
+        self.provider_manager = Some(manager);
+        self
+    }
+
+    /// Monitor the breakers in `registry` as `circuit:{name}` services
+    pub fn with_circuit_breakers(mut self, registry: Arc<CircuitBreakerRegistry>) -> Self {
+        self.circuit_breakers = Some(registry);
+        self
+    }
+
+    /// Share the fallback strategy that [`RemediationAction::SwitchFallbackStrategy`] replaces
+    pub fn with_fallback_strategy(mut self, strategy: Arc<RwLock<FallbackStrategy>>) -> Self {
+        self.fallback_strategy = strategy;
+        self
+    }
+
+    /// The fallback strategy in effect, as switched by remediation
+    pub fn fallback_strategy(&self) -> Arc<RwLock<FallbackStrategy>> {
+        Arc::clone(&self.fallback_strategy)
+    }
+
     /// Start the health monitoring loop
-    /// Note: This is a placeholder implementation since ResilientEmbeddingService is disabled
-    #[allow(dead_code)]
     pub async fn start_monitoring(
         &mut self,
     ) -> Result<()> {
         info!("Starting health monitoring loop");
-        
+
         loop {
-            let check_start = Instant::now();
-            
-            // Perform health checks (placeholder implementation)
-            // self.check_resilient_service(resilient_service).await;
-            // self.check_circuit_breakers(resilient_service).await;
-            
-            let check_duration = check_start.elapsed();
-            if self.config.verbose_logging {
-                debug!("Health check completed in {:?}", check_duration);
-            }
-            
-            // Generate health report (placeholder)
-            // let report = self.generate_health_report(resilient_service).await;
-            // self.log_health_summary(&report);
-            
+            self.run_checks().await;
+
             // Sleep until next check
             sleep(self.config.check_interval).await;
         }
     }
-    
-    /// Check health of the resilient embedding service
-    #[allow(dead_code)]
-    async fn check_resilient_service(&mut self) {
-        let service_name = "resilient_embeddings".to_string();
-        let start_time = Instant::now();
-        
+
+    /// Run one round of checks on a shared monitor every `check_interval`
+    ///
+    /// The lock is only held during a round, so [`HealthMonitor::generate_health_report`]
+    /// can be polled in between.
+    pub fn spawn_monitoring(monitor: Arc<tokio::sync::Mutex<Self>>) -> tokio::task::JoinHandle<()> {
+        let monitor = Arc::downgrade(&monitor);
+        tokio::spawn(async move {
+            loop {
+                // Stop once the monitor itself has been dropped
+                let Some(monitor) = monitor.upgrade() else {
+                    break;
+                };
+                let interval = {
+                    let mut monitor = monitor.lock().await;
+                    monitor.run_checks().await;
+                    monitor.config.check_interval
+                };
+                drop(monitor);
+                sleep(interval).await;
+            }
+        })
+    }
+
+    /// Check every attached provider and circuit breaker once
+    pub async fn run_checks(&mut self) -> SystemHealthReport {
+        let check_start = Instant::now();
+
+        self.check_providers().await;
+        self.check_circuit_breakers().await;
+
+        let check_duration = check_start.elapsed();
+        if self.config.verbose_logging {
+            debug!("Health check completed in {:?}", check_duration);
+        }
+
+        let report = self.generate_health_report().await;
+        self.log_health_summary(&report);
+        report
+    }
+
+    /// Check health of every loaded provider
+    async fn check_providers(&mut self) {
+        let Some(manager) = self.provider_manager.clone() else {
+            return;
+        };
+        let provider_ids = match manager.list_providers().await {
+            Ok(ids) => ids,
+            Err(e) => {
+                warn!("Failed to list providers for health check: {}", e);
+                return;
+            }
+        };
+
+        for provider_id in provider_ids {
+            let start_time = Instant::now();
+            let outcome = match tokio::time::timeout(self.config.check_timeout, manager.health_check(&provider_id)).await {
+                Ok(Ok(true)) => Ok(start_time.elapsed()),
+                Ok(Ok(false)) => Err("Provider reported unhealthy".to_string()),
+                Ok(Err(e)) => Err(format!("Health check failed: {}", e)),
+                Err(_) => Err("Health check timed out".to_string()),
+            };
+            self.record_check(&format!("provider:{}", provider_id), outcome).await;
+        }
+    }
+
+    /// Record the outcome of a health check, remediating on status changes
+    ///
+    /// A failure degrades the service and marks it unhealthy after
+    /// `failure_threshold` in a row; a degraded or unhealthy service needs
+    /// `recovery_threshold` successes in a row to become healthy again.
+    /// Returns the previous and new status if the status changed.
+    pub async fn record_check(
+        &mut self,
+        service_name: &str,
+        outcome: std::result::Result<Duration, String>,
+    ) -> Option<(SystemHealthStatus, SystemHealthStatus)> {
         self.total_requests += 1;
-        
-        let health = self.service_health.entry(service_name.clone())
-            .or_insert_with(|| ServiceHealth::new(service_name.clone()));
-        
+
+        let health = self.service_health.entry(service_name.to_string())
+            .or_insert_with(|| ServiceHealth::new(service_name.to_string()));
+        let previous = health.status.clone();
+
         health.total_checks += 1;
         health.last_check = Utc::now();
-        
-        // Perform health check with timeout (placeholder)
-        // let check_result = tokio::time::timeout(
-        //     self.config.check_timeout,
-        //     service.health_check(),
-        // ).await;
-        let check_result: Result<Result<(), anyhow::Error>, anyhow::Error> = Ok(Ok(()));
-        
-        let response_time = start_time.elapsed().as_millis() as u64;
-        health.response_time_ms = Some(response_time);
-        self.response_times.push(response_time);
-        
-        // Keep only last 1000 response times for metrics
-        if self.response_times.len() > 1000 {
-            self.response_times.drain(0..self.response_times.len() - 1000);
-        }
-        
-        match check_result {
-            Ok(Ok(_)) => {
-                // Service health check succeeded
+
+        match outcome {
+            Ok(response_time) => {
+                let response_time = response_time.as_millis() as u64;
+                health.response_time_ms = Some(response_time);
+                self.response_times.push(response_time);
+
+                // Keep only last 1000 response times for metrics
+                if self.response_times.len() > 1000 {
+                    self.response_times.drain(0..self.response_times.len() - 1000);
+                }
+
                 health.consecutive_successes += 1;
                 health.consecutive_failures = 0;
                 health.last_success = Some(Utc::now());
                 health.error_message = None;
                 self.successful_requests += 1;
 
-                // Placeholder: assume healthy for now
-                health.status = SystemHealthStatus::Healthy;
+                let recovering = matches!(health.status, SystemHealthStatus::Degraded | SystemHealthStatus::Unhealthy);
+                if !recovering || health.consecutive_successes >= self.config.recovery_threshold {
+                    health.status = SystemHealthStatus::Healthy;
+                }
 
                 if self.config.verbose_logging {
                     debug!("Service {} health check passed in {}ms", service_name, response_time);
                 }
             }
-            Ok(Err(e)) => {
-                // Service returned an error
-                let error_msg = format!("Health check failed: {}", e);
+            Err(error_message) => {
                 self.failed_requests += 1;
-                // We'll handle the failure after the match
                 health.consecutive_failures += 1;
                 health.consecutive_successes = 0;
-                health.error_message = Some(error_msg);
-                health.status = SystemHealthStatus::Unhealthy;
-            }
-            Err(_) => {
-                // Timeout
-                let error_msg = "Health check timed out".to_string();
-                self.failed_requests += 1;
-                // We'll handle the failure after the match
-                health.consecutive_failures += 1;
-                health.consecutive_successes = 0;
-                health.error_message = Some(error_msg);
-                health.status = SystemHealthStatus::Unhealthy;
+                health.last_failure = Some(Utc::now());
+                health.error_message = Some(error_message.clone());
+
+                if health.consecutive_failures >= self.config.failure_threshold {
+                    health.status = SystemHealthStatus::Unhealthy;
+                    warn!("Service {} marked as unhealthy after {} consecutive failures: {}",
+                          health.service_name, health.consecutive_failures, error_message);
+                } else {
+                    health.status = SystemHealthStatus::Degraded;
+                    if self.config.verbose_logging {
+                        debug!("Service {} degraded (failure {}/{}): {}",
+                              health.service_name, health.consecutive_failures,
+                              self.config.failure_threshold, error_message);
+                    }
+                }
             }
         }
-        
-        // Update success rate
-        health.success_rate = if health.total_checks > 0 {
-            (health.total_checks - health.consecutive_failures as u64) as f64 / health.total_checks as f64
-        } else {
-            0.0
+
+        health.success_rate = (health.total_checks - health.consecutive_failures as u64) as f64 / health.total_checks as f64;
+
+        let current = health.status.clone();
+        self.transition(service_name, previous, current).await
+    }
+
+    /// Check circuit breaker health
+    ///
+    /// Breaker state maps directly to service status: open is unhealthy,
+    /// half-open is degraded and closed is healthy.
+    async fn check_circuit_breakers(&mut self) {
+        let Some(registry) = self.circuit_breakers.clone() else {
+            return;
         };
-        
-        // Update status based on consecutive failures/successes
-        if health.consecutive_failures >= self.config.failure_threshold {
-            health.status = SystemHealthStatus::Unhealthy;
-        } else if health.consecutive_successes >= self.config.recovery_threshold 
-            && health.status == SystemHealthStatus::Unhealthy {
-            health.status = SystemHealthStatus::Healthy;
+        let breakers = match registry.get_health_status().await {
+            Ok(breakers) => breakers,
+            Err(e) => {
+                warn!("Failed to read circuit breaker health: {}", e);
+                return;
+            }
+        };
+
+        for breaker in breakers {
+            let service_name = format!("circuit:{}", breaker.service_name);
+            let status = match breaker.state {
+                CircuitState::Closed => SystemHealthStatus::Healthy,
+                CircuitState::HalfOpen => SystemHealthStatus::Degraded,
+                CircuitState::Open => SystemHealthStatus::Unhealthy,
+            };
+
+            let health = self.service_health.entry(service_name.clone())
+                .or_insert_with(|| ServiceHealth::new(service_name.clone()));
+            let previous = health.status.clone();
+            health.total_checks += 1;
+            health.last_check = Utc::now();
+            health.success_rate = 1.0 - breaker.failure_rate;
+            health.error_message = (status != SystemHealthStatus::Healthy)
+                .then(|| format!("Circuit breaker {}", breaker.state.name()));
+            health.status = status.clone();
+
+            if status == SystemHealthStatus::Unhealthy && previous != SystemHealthStatus::Unhealthy {
+                self.circuit_breaker_trips += 1;
+            }
+            self.transition(&service_name, previous, status).await;
         }
     }
-    
-    /// Handle service failure
-    fn handle_service_failure(&mut self, health: &mut ServiceHealth, error_message: String) {
-        health.consecutive_failures += 1;
-        health.consecutive_successes = 0;
-        health.last_failure = Some(Utc::now());
-        health.error_message = Some(error_message.clone());
-        
-        if health.consecutive_failures >= self.config.failure_threshold {
-            health.status = SystemHealthStatus::Unhealthy;
-            warn!("Service {} marked as unhealthy after {} consecutive failures: {}", 
-                  health.service_name, health.consecutive_failures, error_message);
-        } else {
-            health.status = SystemHealthStatus::Degraded;
-            if self.config.verbose_logging {
-                debug!("Service {} degraded (failure {}/{}): {}", 
-                      health.service_name, health.consecutive_failures, 
-                      self.config.failure_threshold, error_message);
+
+    /// Run the remediation rules for a status change
+    async fn transition(
+        &mut self,
+        service_name: &str,
+        previous: SystemHealthStatus,
+        current: SystemHealthStatus,
+    ) -> Option<(SystemHealthStatus, SystemHealthStatus)> {
+        if previous == current {
+            return None;
+        }
+        info!("Service {} changed status: {:?} -> {:?}", service_name, previous, current);
+
+        let rules: Vec<(usize, RemediationRule)> = self.config.remediation
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.matches(service_name, &current))
+            .map(|(index, rule)| (index, rule.clone()))
+            .collect();
+
+        for (index, rule) in rules {
+            let key = (index, service_name.to_string());
+            if self.last_remediation.get(&key).is_some_and(|last| last.elapsed() < rule.cooldown) {
+                debug!("Skipping remediation for {}: rule {} is cooling down", service_name, index);
+                continue;
             }
+            self.last_remediation.insert(key, Instant::now());
+
+            let result = self.remediate(service_name, &rule.action).await;
+            match &result {
+                Ok(()) => info!("Remediation {:?} for {} succeeded", rule.action, service_name),
+                Err(e) => error!("Remediation {:?} for {} failed: {}", rule.action, service_name, e),
+            }
+
+            if self.remediations.len() == REMEDIATION_LOG_CAPACITY {
+                self.remediations.pop_front();
+            }
+            self.remediations.push_back(RemediationRecord {
+                timestamp: Utc::now(),
+                service_name: service_name.to_string(),
+                from_status: previous.clone(),
+                to_status: current.clone(),
+                action: rule.action,
+                succeeded: result.is_ok(),
+                error_message: result.err().map(|e| e.to_string()),
+            });
         }
+
+        Some((previous, current))
     }
-    
-    /// Check circuit breaker health
-    #[allow(dead_code)]
-    async fn check_circuit_breakers(&mut self) {
-        // Placeholder implementation
-        // let registry = service.get_circuit_breaker_registry();
-        // Implementation when ResilientEmbeddingService is available
+
+    async fn remediate(&mut self, service_name: &str, action: &RemediationAction) -> Result<()> {
+        match action {
+            RemediationAction::RestartProvider { provider_id } => {
+                let manager = self.provider_manager.as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("No provider manager attached"))?;
+                let provider_id = provider_id.as_deref()
+                    .or_else(|| service_name.strip_prefix("provider:"))
+                    .ok_or_else(|| anyhow::anyhow!("No provider to restart for service {}", service_name))?;
+                manager.reload_provider(provider_id).await
+            }
+            RemediationAction::ResetCircuitBreaker { service_name: breaker } => {
+                let registry = self.circuit_breakers.as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("No circuit breaker registry attached"))?;
+                match breaker.as_deref().or_else(|| service_name.strip_prefix("circuit:")) {
+                    Some(name) => registry.get_breaker(name).await?.reset().await,
+                    None => registry.reset_all().await,
+                }
+            }
+            RemediationAction::SwitchFallbackStrategy { strategy } => {
+                *self.fallback_strategy.write().await = strategy.clone();
+                self.fallback_activations += 1;
+                Ok(())
+            }
+        }
     }
-    
-    /// Generate comprehensive health report
-    #[allow(dead_code)]
+
+    /// Recent remediations, oldest first
+    pub fn remediation_history(&self) -> Vec<RemediationRecord> {
+        self.remediations.iter().cloned().collect()
+    }
+
+    /// Aggregated status of every monitored service, for polling
     pub async fn generate_health_report(
         &self,
     ) -> SystemHealthReport {
@@ -319,8 +559,13 @@ impl HealthMonitor {
             }
         }
         
-        // Get circuit breaker health (placeholder)
-        let circuit_breakers = Vec::new();
+        let circuit_breakers = match &self.circuit_breakers {
+            Some(registry) => registry.get_health_status().await.unwrap_or_else(|e| {
+                warn!("Failed to read circuit breaker health: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
         
         // Calculate metrics
         let system_metrics = self.calculate_metrics();
@@ -335,6 +580,8 @@ impl HealthMonitor {
             successful_requests: self.successful_requests,
             failed_requests: self.failed_requests,
             system_metrics,
+            fallback_strategy: self.fallback_strategy.read().await.clone(),
+            remediations: self.remediation_history(),
         }
     }
     
@@ -567,6 +814,88 @@ mod tests {
         assert_eq!(metrics.error_rate, 0.2);
     }
     
+    #[tokio::test]
+    async fn test_status_transitions_follow_thresholds() {
+        let mut monitor = HealthMonitor::new(HealthCheckConfig::default());
+        let ok = || Ok(Duration::from_millis(5));
+        let fail = || Err("connection refused".to_string());
+
+        assert_eq!(monitor.record_check("provider:a", ok()).await, Some((SystemHealthStatus::Unknown, SystemHealthStatus::Healthy)));
+        assert_eq!(monitor.record_check("provider:a", fail()).await, Some((SystemHealthStatus::Healthy, SystemHealthStatus::Degraded)));
+        assert_eq!(monitor.record_check("provider:a", fail()).await, None);
+        assert_eq!(monitor.record_check("provider:a", fail()).await, Some((SystemHealthStatus::Degraded, SystemHealthStatus::Unhealthy)));
+
+        // Recovery needs `recovery_threshold` successes in a row
+        assert_eq!(monitor.record_check("provider:a", ok()).await, None);
+        assert_eq!(monitor.record_check("provider:a", ok()).await, Some((SystemHealthStatus::Unhealthy, SystemHealthStatus::Healthy)));
+
+        let report = monitor.generate_health_report().await;
+        assert_eq!(report.overall_status, SystemHealthStatus::Healthy);
+        assert_eq!(report.services["provider:a"].total_checks, 6);
+    }
+
+    #[tokio::test]
+    async fn test_remediation_runs_on_transition_with_cooldown() {
+        let registry = Arc::new(CircuitBreakerRegistry::new(Default::default()).unwrap());
+        let breaker = registry.get_breaker("embeddings").await.unwrap();
+
+        let config = HealthCheckConfig {
+            failure_threshold: 1,
+            remediation: vec![
+                RemediationRule {
+                    service: "circuit:*".to_string(),
+                    on_status: SystemHealthStatus::Unhealthy,
+                    action: RemediationAction::ResetCircuitBreaker { service_name: None },
+                    cooldown: Duration::ZERO,
+                },
+                RemediationRule {
+                    service: "provider:*".to_string(),
+                    on_status: SystemHealthStatus::Unhealthy,
+                    action: RemediationAction::SwitchFallbackStrategy { strategy: FallbackStrategy::KeywordSearch },
+                    cooldown: Duration::from_secs(3600),
+                },
+            ],
+            ..HealthCheckConfig::default()
+        };
+        let mut monitor = HealthMonitor::new(config).with_circuit_breakers(Arc::clone(&registry));
+
+        // An open breaker is reset as soon as the monitor sees it
+        breaker.force_open().await.unwrap();
+        let report = monitor.run_checks().await;
+        assert_eq!(report.services["circuit:embeddings"].status, SystemHealthStatus::Unhealthy);
+        assert_eq!(breaker.get_state().await, CircuitState::Closed);
+        assert!(report.remediations[0].succeeded);
+        assert_eq!(report.system_metrics.circuit_breaker_trips, 1);
+
+        // The fallback switch fires once, then waits out its cooldown
+        let fail = || Err("timeout".to_string());
+        monitor.record_check("provider:ollama", fail()).await;
+        assert!(matches!(*monitor.fallback_strategy().read().await, FallbackStrategy::KeywordSearch));
+        monitor.record_check("provider:ollama", Ok(Duration::ZERO)).await;
+        monitor.record_check("provider:ollama", Ok(Duration::ZERO)).await;
+        monitor.record_check("provider:ollama", fail()).await;
+
+        let report = monitor.generate_health_report().await;
+        assert_eq!(report.remediations.len(), 2);
+        assert!(matches!(report.fallback_strategy, FallbackStrategy::KeywordSearch));
+
+        // Actions without the handle they need are recorded as failed
+        let mut bare = HealthMonitor::new(HealthCheckConfig {
+            failure_threshold: 1,
+            remediation: vec![RemediationRule {
+                service: "provider:*".to_string(),
+                on_status: SystemHealthStatus::Unhealthy,
+                action: RemediationAction::RestartProvider { provider_id: None },
+                cooldown: Duration::ZERO,
+            }],
+            ..HealthCheckConfig::default()
+        });
+        bare.record_check("provider:ollama", fail()).await;
+        let history = bare.remediation_history();
+        assert!(!history[0].succeeded);
+        assert!(history[0].error_message.is_some());
+    }
+
     #[tokio::test]
     async fn test_external_api_health_check() {
         // Test with a non-existent endpoint
@@ -0,0 +1,495 @@
+//! # Indexing Jobs
+//!
+//! Runs directory indexing as queued background jobs instead of one long
+//! blocking call. [`IndexJobQueue::enqueue_directory`] returns a job ID right
+//! away; a single worker started with [`IndexJobQueue::start`] processes jobs
+//! in order, publishing [`JobEvent`]s and keeping [`JobProgress`] queryable.
+//!
+//! Each job is checkpointed to `.vespera/rag/jobs/{id}.json`: the file list is
+//! taken once, and the files still pending are written back as the job
+//! advances. Jobs that were queued or running when the process stopped are
+//! picked up again by [`IndexJobQueue::open`]. Files are indexed through the
+//! incremental sync, so the few files processed after the last checkpoint
+//! are skipped cheaply on resume rather than re-embedded.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Mutex, Notify};
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+use uuid::Uuid;
+
+use super::RAGService;
+
+/// Files processed between checkpoints
+const CHECKPOINT_INTERVAL: usize = 16;
+
+/// Capacity of the job event channel
+const JOB_EVENT_CAPACITY: usize = 256;
+
+/// Lifecycle of an indexing job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    /// The job could not run at all (e.g. the directory could not be read)
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// Snapshot of an indexing job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub job_id: Uuid,
+    pub dir_path: PathBuf,
+    pub recursive: bool,
+    pub status: JobStatus,
+    /// Files matched when the job started (0 until then)
+    pub files_total: usize,
+    /// Files processed so far, including failures
+    pub files_done: usize,
+    /// Files that could not be indexed, with the error
+    pub failures: Vec<(PathBuf, String)>,
+    /// Why the job failed, for [`JobStatus::Failed`]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Estimated time remaining, from the pace of the current run
+    #[serde(skip)]
+    pub eta: Option<Duration>,
+}
+
+/// Notifications published while jobs run
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    Queued { job_id: Uuid },
+    Started { job_id: Uuid, files_total: usize },
+    /// Published after every processed file
+    Progress(JobProgress),
+    FileFailed { job_id: Uuid, path: PathBuf, error: String },
+    /// The job completed, failed or was cancelled
+    Finished(JobProgress),
+}
+
+/// A job as checkpointed to disk
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexJob {
+    progress: JobProgress,
+    /// Files still to process (`None` until the directory has been scanned)
+    pending: Option<VecDeque<PathBuf>>,
+    #[serde(skip)]
+    cancel_requested: bool,
+    /// Start of the current run and files processed in it, for the ETA
+    #[serde(skip)]
+    run: Option<(Instant, usize)>,
+    /// Span the job runs in, parented to whoever enqueued it so the
+    /// background work stays in the request's trace
+    #[serde(skip, default = "Span::none")]
+    span: Span,
+}
+
+impl IndexJob {
+    fn snapshot(&self) -> JobProgress {
+        let mut progress = self.progress.clone();
+        if let (JobStatus::Running, Some((started, done))) = (progress.status, self.run) {
+            if done > 0 {
+                let remaining = progress.files_total.saturating_sub(progress.files_done);
+                progress.eta = Some(started.elapsed().mul_f64(remaining as f64 / done as f64));
+            }
+        }
+        progress
+    }
+}
+
+/// Background queue of directory indexing jobs for one [`RAGService`]
+pub struct IndexJobQueue {
+    rag: Arc<RAGService>,
+    jobs_dir: PathBuf,
+    jobs: Mutex<HashMap<Uuid, IndexJob>>,
+    queue: Mutex<VecDeque<Uuid>>,
+    wake: Arc<Notify>,
+    events: broadcast::Sender<JobEvent>,
+    handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl IndexJobQueue {
+    /// Open the queue, re-queueing jobs that had not finished
+    pub async fn open(rag: Arc<RAGService>) -> Result<Self> {
+        let jobs_dir = rag.vespera_path.join("rag/jobs");
+        fs::create_dir_all(&jobs_dir)
+            .with_context(|| format!("Failed to create jobs directory at {:?}", jobs_dir))?;
+
+        let mut jobs = HashMap::new();
+        for entry in fs::read_dir(&jobs_dir)?.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            match fs::read_to_string(&path).map_err(anyhow::Error::from)
+                .and_then(|json| serde_json::from_str::<IndexJob>(&json).map_err(anyhow::Error::from))
+            {
+                Ok(mut job) => {
+                    job.span = info_span!(parent: None, "index_job", job_id = %job.progress.job_id, resumed = true);
+                    if job.progress.status == JobStatus::Running {
+                        info!(job_id = %job.progress.job_id, files_done = job.progress.files_done, "Resuming interrupted indexing job");
+                        job.progress.status = JobStatus::Queued;
+                    }
+                    jobs.insert(job.progress.job_id, job);
+                }
+                Err(e) => warn!(path = %path.display(), error = %e, "Skipping unreadable job checkpoint"),
+            }
+        }
+
+        let mut queued: Vec<&IndexJob> = jobs.values().filter(|job| job.progress.status == JobStatus::Queued).collect();
+        queued.sort_by_key(|job| job.progress.created_at);
+        let queue = queued.iter().map(|job| job.progress.job_id).collect();
+
+        Ok(Self {
+            rag,
+            jobs_dir,
+            jobs: Mutex::new(jobs),
+            queue: Mutex::new(queue),
+            wake: Arc::new(Notify::new()),
+            events: broadcast::channel(JOB_EVENT_CAPACITY).0,
+            handle: Mutex::new(None),
+        })
+    }
+
+    /// Queue indexing of the files under `dir_path`
+    pub async fn enqueue_directory(&self, dir_path: &Path, recursive: bool) -> Result<Uuid> {
+        let dir_path = dir_path.canonicalize()
+            .with_context(|| format!("Failed to canonicalize directory: {:?}", dir_path))?;
+
+        let job_id = Uuid::new_v4();
+        let job = IndexJob {
+            progress: JobProgress {
+                job_id,
+                dir_path,
+                recursive,
+                status: JobStatus::Queued,
+                files_total: 0,
+                files_done: 0,
+                failures: Vec::new(),
+                error: None,
+                created_at: Utc::now(),
+                started_at: None,
+                finished_at: None,
+                eta: None,
+            },
+            pending: None,
+            cancel_requested: false,
+            run: None,
+            span: info_span!("index_job", %job_id),
+        };
+        self.checkpoint(&job)?;
+
+        self.jobs.lock().await.insert(job_id, job);
+        self.queue.lock().await.push_back(job_id);
+        let _ = self.events.send(JobEvent::Queued { job_id });
+        self.wake.notify_one();
+
+        Ok(job_id)
+    }
+
+    /// Current state of a job
+    pub async fn progress(&self, job_id: Uuid) -> Option<JobProgress> {
+        self.jobs.lock().await.get(&job_id).map(IndexJob::snapshot)
+    }
+
+    /// All known jobs, oldest first
+    pub async fn jobs(&self) -> Vec<JobProgress> {
+        let mut jobs: Vec<JobProgress> = self.jobs.lock().await.values().map(IndexJob::snapshot).collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs
+    }
+
+    /// Receive job events from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<JobEvent> {
+        self.events.subscribe()
+    }
+
+    /// Cancel a queued or running job; a running job stops after its current file
+    ///
+    /// Returns `false` if the job is unknown or already finished.
+    pub async fn cancel(&self, job_id: Uuid) -> Result<bool> {
+        let mut jobs = self.jobs.lock().await;
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return Ok(false);
+        };
+
+        match job.progress.status {
+            JobStatus::Running => {
+                job.cancel_requested = true;
+                Ok(true)
+            }
+            JobStatus::Queued => {
+                self.queue.lock().await.retain(|id| *id != job_id);
+                job.progress.status = JobStatus::Cancelled;
+                job.progress.finished_at = Some(Utc::now());
+                self.checkpoint(job)?;
+                let _ = self.events.send(JobEvent::Finished(job.snapshot()));
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Process queued jobs in the background until [`IndexJobQueue::stop`]
+    ///
+    /// Replaces any worker that is already running.
+    pub async fn start(self: &Arc<Self>) {
+        let queue = Arc::downgrade(self);
+        let wake = Arc::clone(&self.wake);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                // Stop once the queue itself has been dropped
+                let Some(queue) = queue.upgrade() else {
+                    break;
+                };
+
+                let next = queue.queue.lock().await.pop_front();
+                match next {
+                    Some(job_id) => {
+                        let span = queue.jobs.lock().await.get(&job_id).map(|job| job.span.clone());
+                        queue.run_job(job_id).instrument(span.unwrap_or_else(Span::none)).await
+                    }
+                    None => {
+                        drop(queue);
+                        wake.notified().await;
+                    }
+                }
+            }
+        });
+
+        if let Some(previous) = self.handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// Stop the worker; an interrupted job resumes from its checkpoint on the next [`IndexJobQueue::open`]
+    pub async fn stop(&self) {
+        if let Some(handle) = self.handle.lock().await.take() {
+            handle.abort();
+        }
+    }
+
+    async fn run_job(&self, job_id: Uuid) {
+        let pending = {
+            let mut jobs = self.jobs.lock().await;
+            let Some(job) = jobs.get_mut(&job_id) else {
+                return;
+            };
+            job.progress.status = JobStatus::Running;
+            job.progress.started_at.get_or_insert_with(Utc::now);
+            job.run = Some((Instant::now(), 0));
+            job.pending.clone()
+        };
+
+        if pending.is_none() {
+            let (dir_path, recursive) = match self.jobs.lock().await.get(&job_id) {
+                Some(job) => (job.progress.dir_path.clone(), job.progress.recursive),
+                None => return,
+            };
+            match self.rag.indexable_files(&dir_path, recursive).await {
+                Ok(files) => {
+                    let mut jobs = self.jobs.lock().await;
+                    if let Some(job) = jobs.get_mut(&job_id) {
+                        job.progress.files_total = files.len();
+                        job.pending = Some(files.into());
+                    }
+                }
+                Err(e) => {
+                    error!(job_id = %job_id, error = %e, "Indexing job could not scan its directory");
+                    self.finish(job_id, JobStatus::Failed, Some(e.to_string())).await;
+                    return;
+                }
+            }
+        }
+
+        if let Some(job) = self.jobs.lock().await.get(&job_id) {
+            info!(job_id = %job_id, files_total = job.progress.files_total, files_done = job.progress.files_done, "Indexing job started");
+            self.save(job);
+            let _ = self.events.send(JobEvent::Started { job_id, files_total: job.progress.files_total });
+        }
+
+        loop {
+            let next = {
+                let mut jobs = self.jobs.lock().await;
+                let Some(job) = jobs.get_mut(&job_id) else {
+                    return;
+                };
+                if job.cancel_requested {
+                    drop(jobs);
+                    self.finish(job_id, JobStatus::Cancelled, None).await;
+                    return;
+                }
+                job.pending.as_ref().and_then(|pending| pending.front().cloned())
+            };
+            let Some(path) = next else {
+                break;
+            };
+
+            let failure = self.rag.sync_file(&path).await.err().map(|e| e.to_string());
+
+            let mut jobs = self.jobs.lock().await;
+            let Some(job) = jobs.get_mut(&job_id) else {
+                return;
+            };
+            if let Some(pending) = job.pending.as_mut() {
+                pending.pop_front();
+            }
+            job.progress.files_done += 1;
+            if let Some((_, done)) = job.run.as_mut() {
+                *done += 1;
+            }
+            if let Some(error) = failure {
+                warn!(job_id = %job_id, path = %path.display(), error = %error, "Failed to index file");
+                job.progress.failures.push((path.clone(), error.clone()));
+                let _ = self.events.send(JobEvent::FileFailed { job_id, path, error });
+            }
+            if job.progress.files_done % CHECKPOINT_INTERVAL == 0 {
+                self.save(job);
+            }
+            let _ = self.events.send(JobEvent::Progress(job.snapshot()));
+        }
+
+        self.finish(job_id, JobStatus::Completed, None).await;
+    }
+
+    async fn finish(&self, job_id: Uuid, status: JobStatus, error: Option<String>) {
+        let mut jobs = self.jobs.lock().await;
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return;
+        };
+        job.progress.status = status;
+        job.progress.error = error;
+        job.progress.finished_at = Some(Utc::now());
+        job.run = None;
+        self.save(job);
+
+        info!(
+            job_id = %job_id,
+            status = ?status,
+            files_done = job.progress.files_done,
+            failed = job.progress.failures.len(),
+            "Indexing job finished"
+        );
+        let _ = self.events.send(JobEvent::Finished(job.snapshot()));
+    }
+
+    /// Checkpoint from the worker, where a write failure only costs resume progress
+    fn save(&self, job: &IndexJob) {
+        if let Err(e) = self.checkpoint(job) {
+            warn!(job_id = %job.progress.job_id, error = %e, "Failed to checkpoint indexing job");
+        }
+    }
+
+    fn checkpoint(&self, job: &IndexJob) -> Result<()> {
+        let path = self.jobs_dir.join(format!("{}.json", job.progress.job_id));
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string(job)?)
+            .with_context(|| format!("Failed to write job checkpoint to {:?}", temp_path))?;
+        fs::rename(&temp_path, &path)?;
+        debug!(job_id = %job.progress.job_id, files_done = job.progress.files_done, "Checkpointed indexing job");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::RAGConfig;
+    use tempfile::TempDir;
+
+    async fn wait_finished(events: &mut broadcast::Receiver<JobEvent>, job_id: Uuid) -> JobProgress {
+        tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                if let JobEvent::Finished(progress) = events.recv().await.unwrap() {
+                    if progress.job_id == job_id {
+                        return progress;
+                    }
+                }
+            }
+        })
+        .await
+        .expect("job did not finish")
+    }
+
+    fn write_notes(dir: &Path, count: usize) -> Vec<PathBuf> {
+        (0..count)
+            .map(|i| {
+                let path = dir.join(format!("note{}.md", i));
+                fs::write(&path, format!("# Note {}\n\nLighthouse log entry number {}.", i, i)).unwrap();
+                path.canonicalize().unwrap()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_job_indexes_directory_and_reports_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let rag = Arc::new(RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap());
+        write_notes(temp_dir.path(), 3);
+
+        let queue = Arc::new(IndexJobQueue::open(Arc::clone(&rag)).await.unwrap());
+        let mut events = queue.subscribe();
+        queue.start().await;
+
+        let job_id = queue.enqueue_directory(temp_dir.path(), false).await.unwrap();
+        let finished = wait_finished(&mut events, job_id).await;
+        assert_eq!(finished.status, JobStatus::Completed);
+        assert_eq!((finished.files_done, finished.files_total), (3, 3));
+        assert!(finished.failures.is_empty());
+
+        assert_eq!(queue.progress(job_id).await.unwrap().status, JobStatus::Completed);
+        assert_eq!(rag.get_stats().await.unwrap().total_documents, 3);
+        assert!(!queue.cancel(job_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_job_resumes_from_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let rag = Arc::new(RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap());
+        let files = write_notes(temp_dir.path(), 3);
+
+        // Queue a job without a worker, then fake a crash one file into the run
+        let job_id = {
+            let queue = IndexJobQueue::open(Arc::clone(&rag)).await.unwrap();
+            let job_id = queue.enqueue_directory(temp_dir.path(), false).await.unwrap();
+            let mut jobs = queue.jobs.lock().await;
+            let job = jobs.get_mut(&job_id).unwrap();
+            job.progress.status = JobStatus::Running;
+            job.progress.files_total = 3;
+            job.progress.files_done = 1;
+            job.pending = Some(files[1..].iter().cloned().collect());
+            queue.checkpoint(job).unwrap();
+            job_id
+        };
+
+        let queue = Arc::new(IndexJobQueue::open(Arc::clone(&rag)).await.unwrap());
+        assert_eq!(queue.progress(job_id).await.unwrap().status, JobStatus::Queued);
+
+        let mut events = queue.subscribe();
+        queue.start().await;
+        let finished = wait_finished(&mut events, job_id).await;
+        assert_eq!(finished.status, JobStatus::Completed);
+        assert_eq!(finished.files_done, 3);
+
+        // Only the files pending at the checkpoint were indexed
+        assert_eq!(rag.get_stats().await.unwrap().total_documents, 2);
+    }
+}
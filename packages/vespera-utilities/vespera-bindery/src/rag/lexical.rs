@@ -0,0 +1,282 @@
+//! # Lexical Index
+//!
+//! BM25 keyword index over document chunks. It complements embeddings for
+//! queries that hinge on exact tokens, such as identifiers, error codes and
+//! copied error messages, where vector similarity is unreliable.
+//!
+//! Tokenisation is code-aware: `parseConfigFile` and `parse_config_file`
+//! both index the whole identifier plus its `parse`/`config`/`file` parts.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// BM25 term-frequency saturation
+const K1: f32 = 1.2;
+/// BM25 length normalisation
+const B: f32 = 0.75;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LexicalEntry {
+    document_id: Uuid,
+    term_counts: HashMap<String, u32>,
+    length: u32,
+}
+
+/// Persistent BM25 index keyed by chunk ID
+pub struct LexicalIndex {
+    path: PathBuf,
+    entries: HashMap<String, LexicalEntry>,
+    document_frequency: HashMap<String, u32>,
+    total_length: u64,
+}
+
+impl LexicalIndex {
+    /// Load the index stored at `path`, or start an empty one
+    pub fn open(path: &Path) -> Result<Self> {
+        let entries: HashMap<String, LexicalEntry> = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            HashMap::new()
+        };
+
+        let mut index = Self {
+            path: path.to_path_buf(),
+            entries: HashMap::new(),
+            document_frequency: HashMap::new(),
+            total_length: 0,
+        };
+        for (chunk_id, entry) in entries {
+            index.insert_entry(chunk_id, entry);
+        }
+        Ok(index)
+    }
+
+    /// Persist the index to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// Number of indexed chunks
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no chunks are indexed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Index (or re-index) a chunk's text
+    pub fn index_chunk(&mut self, chunk_id: &str, document_id: Uuid, text: &str) {
+        self.remove_chunk(chunk_id);
+
+        let tokens = tokenize(text);
+        let mut term_counts = HashMap::new();
+        for token in &tokens {
+            *term_counts.entry(token.clone()).or_insert(0) += 1;
+        }
+
+        self.insert_entry(chunk_id.to_string(), LexicalEntry {
+            document_id,
+            term_counts,
+            length: tokens.len() as u32,
+        });
+    }
+
+    fn insert_entry(&mut self, chunk_id: String, entry: LexicalEntry) {
+        for term in entry.term_counts.keys() {
+            *self.document_frequency.entry(term.clone()).or_insert(0) += 1;
+        }
+        self.total_length += entry.length as u64;
+        self.entries.insert(chunk_id, entry);
+    }
+
+    /// Remove a single chunk; returns whether it was indexed
+    pub fn remove_chunk(&mut self, chunk_id: &str) -> bool {
+        let Some(entry) = self.entries.remove(chunk_id) else {
+            return false;
+        };
+
+        for term in entry.term_counts.keys() {
+            if let Some(df) = self.document_frequency.get_mut(term) {
+                *df -= 1;
+                if *df == 0 {
+                    self.document_frequency.remove(term);
+                }
+            }
+        }
+        self.total_length -= entry.length as u64;
+        true
+    }
+
+    /// Remove every chunk of a document
+    pub fn remove_document(&mut self, document_id: Uuid) -> usize {
+        let chunk_ids: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.document_id == document_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for chunk_id in &chunk_ids {
+            self.remove_chunk(chunk_id);
+        }
+        chunk_ids.len()
+    }
+
+    /// Remove everything from the index
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.document_frequency.clear();
+        self.total_length = 0;
+    }
+
+    /// Rank chunks against `query` by BM25, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(String, f32)> {
+        if self.entries.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let query_terms: HashSet<String> = tokenize(query).into_iter().collect();
+        let chunk_count = self.entries.len() as f32;
+        let average_length = (self.total_length as f32 / chunk_count).max(1.0);
+
+        let idf: Vec<(&String, f32)> = query_terms
+            .iter()
+            .filter_map(|term| {
+                let df = *self.document_frequency.get(term)? as f32;
+                Some((term, ((chunk_count - df + 0.5) / (df + 0.5) + 1.0).ln()))
+            })
+            .collect();
+        if idf.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(String, f32)> = self
+            .entries
+            .iter()
+            .filter_map(|(chunk_id, entry)| {
+                let length_norm = 1.0 - B + B * entry.length as f32 / average_length;
+                let score: f32 = idf
+                    .iter()
+                    .filter_map(|(term, idf)| {
+                        let tf = *entry.term_counts.get(*term)? as f32;
+                        Some(idf * tf * (K1 + 1.0) / (tf + K1 * length_norm))
+                    })
+                    .sum();
+                (score > 0.0).then(|| (chunk_id.clone(), score))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored.truncate(limit);
+        scored
+    }
+}
+
+/// Split text into lowercase terms, keeping whole identifiers and their parts
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        let word = word.trim_matches('_');
+        if word.is_empty() {
+            continue;
+        }
+
+        let parts = split_identifier(word);
+        tokens.push(word.to_lowercase());
+        if parts.len() > 1 {
+            tokens.extend(parts.into_iter().map(|p| p.to_lowercase()));
+        }
+    }
+
+    tokens
+}
+
+/// Split `snake_case`, `camelCase` and `PascalCase` identifiers
+fn split_identifier(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+
+    for segment in word.split('_').filter(|s| !s.is_empty()) {
+        let chars: Vec<(usize, char)> = segment.char_indices().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (idx, c) = chars[i];
+            let prev = chars[i - 1].1;
+            let next_is_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+            // fooBar | HTTPServer -> HTTP|Server | v2Api
+            let boundary = (c.is_uppercase() && (prev.is_lowercase() || prev.is_ascii_digit()))
+                || (c.is_uppercase() && prev.is_uppercase() && next_is_lower);
+            if boundary {
+                parts.push(&segment[start..idx]);
+                start = idx;
+            }
+        }
+        parts.push(&segment[start..]);
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tokenize_identifiers() {
+        assert_eq!(
+            tokenize("parseConfigFile(path)"),
+            vec!["parseconfigfile", "parse", "config", "file", "path"]
+        );
+        assert_eq!(tokenize("MAX_RETRY_COUNT"), vec!["max_retry_count", "max", "retry", "count"]);
+        assert_eq!(tokenize("HTTPServer"), vec!["httpserver", "http", "server"]);
+        assert_eq!(tokenize("error E0425: cannot find"), vec!["error", "e0425", "cannot", "find"]);
+    }
+
+    #[test]
+    fn test_bm25_ranks_exact_identifier_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut index = LexicalIndex::open(&temp_dir.path().join("lexical.json")).unwrap();
+        let doc = Uuid::new_v4();
+
+        index.index_chunk("a", doc, "fn load_settings() reads the configuration file");
+        index.index_chunk("b", doc, "fn parse_config_file(path) parses a config file from disk");
+        index.index_chunk("c", doc, "Unrelated prose about gardening and weather");
+
+        let results = index.search("parse_config_file", 10);
+        assert_eq!(results[0].0, "b");
+        assert!(results.iter().all(|(id, _)| id != "c"));
+        assert!(index.search("nonexistent_symbol_xyz", 10).is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_persist() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("lexical.json");
+        let doc_a = Uuid::new_v4();
+        let doc_b = Uuid::new_v4();
+
+        let mut index = LexicalIndex::open(&path).unwrap();
+        index.index_chunk("a0", doc_a, "timeout while connecting");
+        index.index_chunk("a1", doc_a, "connection refused");
+        index.index_chunk("b0", doc_b, "connection pool exhausted");
+        assert_eq!(index.remove_document(doc_a), 2);
+        index.save().unwrap();
+
+        let reopened = LexicalIndex::open(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        let results = reopened.search("connection", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "b0");
+    }
+}
@@ -7,9 +7,11 @@
 //!
 //! The RAG system integrates with the core Bindery functionality through:
 //! - Document chunking and embedding generation
-//! - Vector database for semantic search
-//! - Code analysis for hallucination detection
-//! - Project-aware .vespera folder management
+//! - Background indexing jobs that checkpoint, resume and report progress
+//! - Vector database for semantic search (built-in HNSW index under .vespera)
+//! - Codex and task content indexed alongside files, linked back by `CodexId`
+//! - Code analysis for hallucination detection, with symbol-aware chunking of source files
+//! - Project-aware .vespera folder management, with federated search across projects
 
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
@@ -18,16 +20,27 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use std::time::Duration;
+use crate::types::CodexId;
 
 pub mod service;
 pub mod embeddings;
 pub mod chunker;
 pub mod code_analyzer;
+pub mod syntax;
+pub mod verification;
+pub mod federation;
 pub mod project_manager;
 pub mod circuit_breaker;
 pub mod fallback_service;
 pub mod health_monitor;
 pub mod logging;
+pub mod vector_store;
+pub mod lexical;
+pub mod reranker;
+pub mod bindery_indexer;
+pub mod embedding_cache;
+pub mod batching;
+pub mod jobs;
 // pub mod resilient_embeddings; // Temporarily disabled due to compilation issues
 
 #[cfg(any(feature = "embeddings-local", feature = "embeddings-onnx", feature = "embeddings-api"))]
@@ -36,12 +49,21 @@ pub mod embeddings_impl;
 pub use service::RAGService;
 pub use embeddings::{EmbeddingService, EmbeddingModel};
 pub use chunker::{DocumentChunker, ChunkStrategy};
-pub use code_analyzer::{CodeAnalyzer, CodeAnalysis};
+pub use code_analyzer::{CodeAnalyzer, CodeAnalysis, SymbolInfo, SymbolKind, SymbolLocation, CallSite};
 pub use project_manager::{ProjectManager, ProjectConfig};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerRegistry, CircuitState};
 pub use fallback_service::{FallbackEmbeddingService, FallbackConfig, FallbackStrategy};
-pub use health_monitor::{HealthMonitor, HealthCheckConfig, SystemHealthStatus, SystemHealthReport};
+pub use health_monitor::{HealthMonitor, HealthCheckConfig, SystemHealthStatus, SystemHealthReport, RemediationRule, RemediationAction, RemediationRecord};
 pub use logging::{MetricsCollector, MetricsReport, CircuitBreakerEvent, HealthEvent};
+pub use vector_store::{HnswVectorStorage, HnswConfig};
+pub use lexical::LexicalIndex;
+pub use reranker::{Reranker, RerankConfig, RerankBackend, ProviderReranker};
+pub use bindery_indexer::{BinderyIndexer, BinderyIndexReport};
+pub use embedding_cache::{EmbeddingCache, EmbeddingCacheConfig, EmbeddingCacheStats};
+pub use batching::EmbeddingBatchConfig;
+pub use jobs::{IndexJobQueue, JobEvent, JobProgress, JobStatus};
+pub use federation::{FederatedSearchOptions, FederatedSearchResult, ProjectStats};
+pub use verification::{CodeReferenceReport, UnknownSymbol, SignatureMismatch, MissingFile, ReferenceKind};
 // pub use resilient_embeddings::{ResilientEmbeddingService, ResilientHealthStatus, ResilientMetrics};
 
 /// Configuration for the RAG system
@@ -76,6 +98,22 @@ pub struct RAGConfig {
 
     /// Fallback strategy
     pub fallback_strategy: fallback_service::FallbackStrategy,
+
+    /// Default ranking used by `RAGService::search`
+    #[serde(default)]
+    pub search: SearchOptions,
+
+    /// Optional re-ranking of the top search results
+    #[serde(default)]
+    pub rerank: RerankConfig,
+
+    /// Cache of computed embeddings, keyed by model and content hash
+    #[serde(default)]
+    pub embedding_cache: EmbeddingCacheConfig,
+
+    /// Batching and rate limiting of embedding requests
+    #[serde(default)]
+    pub embedding_batch: EmbeddingBatchConfig,
 }
 
 impl Default for RAGConfig {
@@ -100,10 +138,80 @@ impl Default for RAGConfig {
             enable_circuit_breaker: true,
             fallback_config: fallback_service::FallbackConfig::default(),
             fallback_strategy: fallback_service::FallbackStrategy::default(),
+            search: SearchOptions::default(),
+            rerank: RerankConfig::default(),
+            embedding_cache: EmbeddingCacheConfig::default(),
+            embedding_batch: EmbeddingBatchConfig::default(),
         }
     }
 }
 
+/// How search results are ranked
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Embedding similarity only
+    Vector,
+    /// BM25 keyword ranking only
+    Lexical,
+    /// Both rankings fused with reciprocal rank fusion
+    #[default]
+    Hybrid,
+}
+
+/// Per-query search options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchOptions {
+    /// Ranking mode
+    pub mode: SearchMode,
+
+    /// Weight of the vector ranking in hybrid mode
+    pub vector_weight: f32,
+
+    /// Weight of the lexical ranking in hybrid mode
+    pub lexical_weight: f32,
+
+    /// RRF damping constant; larger values flatten the advantage of top ranks
+    pub rrf_k: f32,
+
+    /// Apply the configured re-ranking stage, if one is enabled
+    #[serde(default = "default_true")]
+    pub rerank: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            mode: SearchMode::Hybrid,
+            vector_weight: 1.0,
+            lexical_weight: 1.0,
+            rrf_k: 60.0,
+            rerank: true,
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Embedding similarity only
+    pub fn vector() -> Self {
+        Self { mode: SearchMode::Vector, ..Self::default() }
+    }
+
+    /// BM25 keyword ranking only
+    pub fn lexical() -> Self {
+        Self { mode: SearchMode::Lexical, ..Self::default() }
+    }
+
+    /// Hybrid ranking with custom weights (e.g. favour lexical for identifiers)
+    pub fn hybrid(vector_weight: f32, lexical_weight: f32) -> Self {
+        Self { vector_weight, lexical_weight, ..Self::default() }
+    }
+}
+
 /// Document metadata for RAG indexing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentMetadata {
@@ -116,6 +224,33 @@ pub struct DocumentMetadata {
     pub updated_at: DateTime<Utc>,
     pub tags: Vec<String>,
     pub project_id: Option<Uuid>,
+    /// Codex or task this document was indexed from, if any
+    #[serde(default)]
+    pub bindery_source: Option<BinderySource>,
+}
+
+impl DocumentMetadata {
+    /// Codex to open when this document is selected in a search result
+    pub fn codex_id(&self) -> Option<CodexId> {
+        self.bindery_source.map(|source| source.codex_id())
+    }
+}
+
+/// Bindery record a document was indexed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum BinderySource {
+    Codex(CodexId),
+    Task(CodexId),
+}
+
+impl BinderySource {
+    /// ID of the underlying Codex (tasks are Codices too)
+    pub fn codex_id(&self) -> CodexId {
+        match self {
+            BinderySource::Codex(id) | BinderySource::Task(id) => *id,
+        }
+    }
 }
 
 /// Types of documents that can be indexed
@@ -141,6 +276,35 @@ impl DocumentType {
             _ => DocumentType::Text,
         }
     }
+
+    /// Lowercase name, as accepted by [`str::parse`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::Text => "text",
+            DocumentType::Code => "code",
+            DocumentType::Markdown => "markdown",
+            DocumentType::Documentation => "documentation",
+            DocumentType::Configuration => "configuration",
+            DocumentType::Data => "data",
+        }
+    }
+}
+
+impl std::str::FromStr for DocumentType {
+    type Err = anyhow::Error;
+
+    /// Parse a document type name, ignoring case (`"markdown"`, `"Code"`, ...)
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" => Ok(DocumentType::Text),
+            "code" => Ok(DocumentType::Code),
+            "markdown" => Ok(DocumentType::Markdown),
+            "documentation" => Ok(DocumentType::Documentation),
+            "configuration" => Ok(DocumentType::Configuration),
+            "data" => Ok(DocumentType::Data),
+            _ => anyhow::bail!("Unknown document type: {}", name),
+        }
+    }
 }
 
 /// A chunk of a document after splitting
@@ -184,6 +348,31 @@ pub struct RAGStats {
     pub index_size_bytes: u64,
     pub last_indexed: Option<DateTime<Utc>>,
     pub projects_tracked: usize,
+    /// Per-project breakdown; other projects' private data is left out
+    #[serde(default)]
+    pub projects: Vec<ProjectStats>,
+}
+
+/// Outcome of an incremental re-index pass over a directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReindexReport {
+    /// Files indexed for the first time
+    pub added: Vec<PathBuf>,
+    /// Files whose content changed and were re-chunked and re-embedded
+    pub updated: Vec<PathBuf>,
+    /// Documents whose source file disappeared; their chunks were removed
+    pub removed: Vec<PathBuf>,
+    /// Files whose content hash matched the index
+    pub unchanged: usize,
+    /// Files that could not be indexed, with the error message
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl ReindexReport {
+    /// Whether the pass changed the index at all
+    pub fn has_changes(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty() || !self.removed.is_empty()
+    }
 }
 
 /// Health status of the RAG system
@@ -35,6 +35,9 @@ pub struct ProjectSettings {
     pub ignore_patterns: Vec<String>,
     pub embedding_model: Option<String>,
     pub chunk_strategy: Option<String>,
+    /// Keep this project's documents out of searches made from other projects
+    #[serde(default)]
+    pub private: bool,
 }
 
 impl Default for ProjectSettings {
@@ -60,6 +63,7 @@ impl Default for ProjectSettings {
             ],
             embedding_model: None,
             chunk_strategy: None,
+            private: false,
         }
     }
 }
@@ -204,10 +208,10 @@ impl ProjectManager {
         let canonical_path = path.canonicalize()?;
 
         // Check exact match first
-        let path_index = self.path_index.read().await;
-        if let Some(id) = path_index.get(&canonical_path) {
+        let indexed = self.path_index.read().await.get(&canonical_path).copied();
+        if let Some(id) = indexed {
             let projects = self.projects.read().await;
-            return Ok(projects.get(id).cloned());
+            return Ok(projects.get(&id).cloned());
         }
 
         // Check if path contains a .vespera folder
@@ -356,6 +360,24 @@ impl ProjectManager {
         }
     }
 
+    /// Whether a project is marked private
+    ///
+    /// The project's own `project.json` is authoritative, so a project made
+    /// private by another process is isolated without re-registering it.
+    /// Unknown projects are treated as private.
+    pub async fn is_private(&self, project_id: Uuid) -> bool {
+        let Some(project) = self.get_project(project_id).await else {
+            return true;
+        };
+
+        let config_path = project.vespera_path.join("project.json");
+        fs::read_to_string(&config_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<ProjectConfig>(&json).ok())
+            .map(|on_disk| on_disk.settings.private)
+            .unwrap_or(project.settings.private)
+    }
+
     /// Get the .vespera folder path for a project
     pub async fn get_vespera_path(&self, project_id: Uuid) -> Option<PathBuf> {
         let projects = self.projects.read().await;
@@ -0,0 +1,281 @@
+//! # Re-ranking
+//!
+//! Optional second stage for [`RAGService::search`](super::RAGService::search):
+//! the top-k first-stage hits are re-scored against the query by a model that
+//! reads query and passage together, which is noticeably more precise than
+//! embedding or keyword similarity alone.
+//!
+//! Two backends are available:
+//! - a local cross-encoder (ONNX, behind the `reranker-onnx` feature)
+//! - an LLM provider from the providers module, prompted to grade passages
+
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::providers::Provider;
+use crate::providers::manager::ProviderManager;
+
+/// Which model re-scores search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RerankBackend {
+    /// Local cross-encoder downloaded from the HuggingFace Hub
+    CrossEncoder {
+        model_name: String,
+    },
+    /// LLM provider loaded by the `ProviderManager`
+    Provider {
+        provider_id: String,
+        model: Option<String>,
+    },
+}
+
+impl Default for RerankBackend {
+    fn default() -> Self {
+        RerankBackend::CrossEncoder {
+            model_name: "cross-encoder/ms-marco-MiniLM-L-6-v2".to_string(),
+        }
+    }
+}
+
+/// Re-ranking stage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankConfig {
+    /// Enable the re-ranking stage
+    pub enabled: bool,
+
+    /// Model used for re-scoring
+    pub backend: RerankBackend,
+
+    /// Number of first-stage results to re-score
+    pub top_k: usize,
+
+    /// Passages are truncated to this many characters before scoring
+    pub max_passage_chars: usize,
+
+    /// Give up on re-ranking (keeping first-stage order) after this long
+    pub timeout_secs: u64,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: RerankBackend::default(),
+            top_k: 20,
+            max_passage_chars: 2000,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// Re-scores passages for relevance to a query
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Relevance score for each passage, in input order; higher is more relevant
+    async fn score(&self, query: &str, passages: &[String]) -> Result<Vec<f32>>;
+
+    /// Short name used in logs
+    fn name(&self) -> &str;
+}
+
+enum ProviderTarget {
+    Managed {
+        manager: Arc<ProviderManager>,
+        provider_id: String,
+    },
+    Direct(Arc<dyn Provider>),
+}
+
+/// Re-ranker that asks an LLM provider to grade each passage from 0 to 10
+pub struct ProviderReranker {
+    target: ProviderTarget,
+    model: Option<String>,
+}
+
+const RERANK_SYSTEM_PROMPT: &str = "You grade how relevant passages are to a search query. \
+Reply with only a JSON array of numbers from 0 (irrelevant) to 10 (directly answers the query), \
+one per passage, in the order given.";
+
+impl ProviderReranker {
+    /// Route re-ranking requests through a provider loaded by `manager`
+    pub fn new(manager: Arc<ProviderManager>, provider_id: impl Into<String>, model: Option<String>) -> Self {
+        Self {
+            target: ProviderTarget::Managed {
+                manager,
+                provider_id: provider_id.into(),
+            },
+            model,
+        }
+    }
+
+    /// Use a provider instance directly
+    pub fn from_provider(provider: Arc<dyn Provider>, model: Option<String>) -> Self {
+        Self {
+            target: ProviderTarget::Direct(provider),
+            model,
+        }
+    }
+
+    fn build_prompt(query: &str, passages: &[String]) -> String {
+        let mut prompt = format!("Query: {}\n\n", query);
+        for (i, passage) in passages.iter().enumerate() {
+            prompt.push_str(&format!("Passage {}:\n{}\n\n", i + 1, passage));
+        }
+        prompt.push_str(&format!("Return a JSON array of {} scores.", passages.len()));
+        prompt
+    }
+
+    /// Pull the score array out of a reply that may wrap it in prose or a code fence
+    fn parse_scores(reply: &str, expected: usize) -> Result<Vec<f32>> {
+        let start = reply.find('[').ok_or_else(|| anyhow::anyhow!("Re-rank reply has no JSON array"))?;
+        let end = reply.rfind(']').ok_or_else(|| anyhow::anyhow!("Re-rank reply has no JSON array"))?;
+        if end < start {
+            anyhow::bail!("Re-rank reply has no JSON array");
+        }
+
+        let scores: Vec<f32> = serde_json::from_str(&reply[start..=end])?;
+        if scores.len() != expected {
+            anyhow::bail!("Re-rank reply scored {} passages, expected {}", scores.len(), expected);
+        }
+        Ok(scores)
+    }
+}
+
+#[async_trait]
+impl Reranker for ProviderReranker {
+    async fn score(&self, query: &str, passages: &[String]) -> Result<Vec<f32>> {
+        if passages.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let prompt = Self::build_prompt(query, passages);
+        let model = self.model.as_deref();
+        let response = match &self.target {
+            ProviderTarget::Managed { manager, provider_id } => {
+                manager
+                    .send_message(provider_id, &prompt, model, None, Some(RERANK_SYSTEM_PROMPT), false)
+                    .await?
+            }
+            ProviderTarget::Direct(provider) => {
+                provider
+                    .send_message(&prompt, model, None, Some(RERANK_SYSTEM_PROMPT), false)
+                    .await?
+            }
+        };
+
+        Self::parse_scores(&response.text, passages.len())
+    }
+
+    fn name(&self) -> &str {
+        "provider"
+    }
+}
+
+#[cfg(feature = "reranker-onnx")]
+pub mod cross_encoder {
+    use super::*;
+    use ort::{Session, SessionBuilder, Value};
+    use tokenizers::Tokenizer;
+    use hf_hub::api::tokio::Api;
+
+    /// Local cross-encoder (e.g. `cross-encoder/ms-marco-MiniLM-L-6-v2`)
+    pub struct CrossEncoderReranker {
+        session: Arc<Session>,
+        tokenizer: Arc<Tokenizer>,
+    }
+
+    impl CrossEncoderReranker {
+        pub async fn new(model_name: &str) -> Result<Self> {
+            // Download model from HuggingFace Hub
+            let api = Api::new()?;
+            let repo = api.model(model_name.to_string());
+            let model_file = repo.get("onnx/model.onnx").await?;
+            let tokenizer_file = repo.get("tokenizer.json").await?;
+
+            let session = SessionBuilder::new()?.with_model_from_file(&model_file)?;
+            let tokenizer = Tokenizer::from_file(&tokenizer_file)
+                .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+
+            Ok(Self {
+                session: Arc::new(session),
+                tokenizer: Arc::new(tokenizer),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Reranker for CrossEncoderReranker {
+        async fn score(&self, query: &str, passages: &[String]) -> Result<Vec<f32>> {
+            if passages.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            // Encode (query, passage) pairs
+            let encodings = passages
+                .iter()
+                .map(|passage| {
+                    self.tokenizer.encode((query, passage.as_str()), true)
+                        .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let max_len = encodings.iter().map(|e| e.len()).max().unwrap_or(0);
+            let batch_size = passages.len();
+
+            let mut input_ids = vec![0i64; batch_size * max_len];
+            let mut attention_mask = vec![0i64; batch_size * max_len];
+            let mut token_type_ids = vec![0i64; batch_size * max_len];
+
+            for (i, encoding) in encodings.iter().enumerate() {
+                for (j, (&id, &type_id)) in encoding.get_ids().iter().zip(encoding.get_type_ids()).enumerate() {
+                    input_ids[i * max_len + j] = id as i64;
+                    attention_mask[i * max_len + j] = 1;
+                    token_type_ids[i * max_len + j] = type_id as i64;
+                }
+            }
+
+            let outputs = self.session.run(vec![
+                Value::from_array(ndarray::Array::from_shape_vec((batch_size, max_len), input_ids)?)?,
+                Value::from_array(ndarray::Array::from_shape_vec((batch_size, max_len), attention_mask)?)?,
+                Value::from_array(ndarray::Array::from_shape_vec((batch_size, max_len), token_type_ids)?)?,
+            ])?;
+
+            // One relevance logit per pair
+            let logits = outputs[0].try_extract::<f32>()?.view().to_owned();
+            Ok(logits.iter().copied().take(batch_size).collect())
+        }
+
+        fn name(&self) -> &str {
+            "cross-encoder"
+        }
+    }
+}
+
+#[cfg(feature = "reranker-onnx")]
+pub use cross_encoder::CrossEncoderReranker;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scores() {
+        assert_eq!(
+            ProviderReranker::parse_scores("Scores:\n```json\n[7, 2.5, 0]\n```", 3).unwrap(),
+            vec![7.0, 2.5, 0.0]
+        );
+        assert!(ProviderReranker::parse_scores("[1, 2]", 3).is_err());
+        assert!(ProviderReranker::parse_scores("no idea", 1).is_err());
+    }
+
+    #[test]
+    fn test_prompt_numbers_passages() {
+        let prompt = ProviderReranker::build_prompt("q", &["first".to_string(), "second".to_string()]);
+        assert!(prompt.contains("Passage 1:\nfirst"));
+        assert!(prompt.contains("Passage 2:\nsecond"));
+        assert!(prompt.ends_with("Return a JSON array of 2 scores."));
+    }
+}
@@ -11,17 +11,29 @@ use uuid::Uuid;
 use chrono::Utc;
 use tokio::sync::RwLock;
 use std::sync::Arc;
+use std::time::Duration;
 use sha2::{Sha256, Digest};
 use tracing::{info, warn, error, debug, trace, instrument};
 
 use super::{
-    RAGConfig, DocumentMetadata, DocumentType, DocumentChunk, SearchResult,
-    RAGStats, RAGHealthStatus, HealthStatus, ComponentHealth,
+    RAGConfig, DocumentMetadata, DocumentType, BinderySource, DocumentChunk, SearchResult,
+    RAGStats, RAGHealthStatus, HealthStatus, ComponentHealth, ReindexReport,
+    SearchMode, SearchOptions,
     ProjectManager,
     DocumentChunker, ChunkStrategy,
     EmbeddingService,
     CodeAnalyzer,
+    CircuitBreaker,
 };
+use super::code_analyzer::{CodeAnalysis, ProgrammingLanguage, SymbolLocation};
+use super::verification::{self, CodeReferenceReport};
+use super::federation::{self, FederatedSearchOptions, FederatedSearchResult, ProjectStats};
+use super::project_manager::ProjectConfig;
+use super::reranker::{Reranker, RerankBackend, ProviderReranker};
+use crate::providers::manager::ProviderManager;
+
+/// Ranked `(chunk_id, score, content)` hits from one retriever
+type RankedChunks = Vec<(String, f32, String)>;
 
 /// Main RAG service integrating all components
 pub struct RAGService {
@@ -33,6 +45,19 @@ pub struct RAGService {
     pub(crate) documents: Arc<RwLock<HashMap<Uuid, DocumentMetadata>>>,
     pub(crate) project_path: PathBuf,
     pub(crate) vespera_path: PathBuf,
+    pub(crate) watch_handle: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    pub(crate) reranker: RwLock<Option<Arc<dyn Reranker>>>,
+    /// Services for other projects, opened by federated search
+    pub(crate) federation: RwLock<HashMap<Uuid, Arc<RAGService>>>,
+}
+
+impl std::fmt::Debug for RAGService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RAGService")
+            .field("project_path", &self.project_path)
+            .field("vespera_path", &self.vespera_path)
+            .finish()
+    }
 }
 
 impl RAGService {
@@ -78,9 +103,21 @@ impl RAGService {
             config.chunk_overlap,
         ));
 
-        let embedding_service = Arc::new(RwLock::new(
-            EmbeddingService::new(config.embedding_model.clone(), &vespera_path).await?
-        ));
+        let mut embedding_service = EmbeddingService::new_with_cache_config(
+            config.embedding_model.clone(),
+            &vespera_path,
+            config.embedding_cache.clone(),
+        ).await?
+        .with_batch_config(config.embedding_batch.clone())?;
+
+        if config.enable_circuit_breaker {
+            let service_name = format!("embeddings:{}", config.embedding_model.cache_id());
+            match CircuitBreaker::new(service_name, config.circuit_breaker_config.clone()) {
+                Ok(breaker) => embedding_service = embedding_service.with_circuit_breaker(Arc::new(breaker)),
+                Err(e) => warn!(error = %e, "Invalid circuit breaker config, embedding requests are unprotected"),
+            }
+        }
+        let embedding_service = Arc::new(RwLock::new(embedding_service));
 
         let code_analyzer = if config.enable_code_analysis {
             Some(Arc::new(CodeAnalyzer::new()))
@@ -91,6 +128,8 @@ impl RAGService {
         // Load existing documents index
         let documents = Arc::new(RwLock::new(Self::load_documents_index(&vespera_path)?));
 
+        let reranker = Self::build_reranker(&config).await;
+
         Ok(Self {
             config,
             project_manager,
@@ -100,9 +139,58 @@ impl RAGService {
             documents,
             project_path: canonical_path,
             vespera_path,
+            watch_handle: Arc::new(tokio::sync::Mutex::new(None)),
+            reranker: RwLock::new(reranker),
+            federation: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Build the configured local re-ranker; provider re-rankers need
+    /// [`RAGService::attach_provider_manager`]
+    async fn build_reranker(config: &RAGConfig) -> Option<Arc<dyn Reranker>> {
+        if !config.rerank.enabled {
+            return None;
+        }
+
+        match &config.rerank.backend {
+            RerankBackend::CrossEncoder { model_name } => {
+                #[cfg(feature = "reranker-onnx")]
+                {
+                    match super::reranker::CrossEncoderReranker::new(model_name).await {
+                        Ok(reranker) => return Some(Arc::new(reranker)),
+                        Err(e) => warn!(model = %model_name, error = %e, "Failed to load cross-encoder, re-ranking disabled"),
+                    }
+                }
+
+                #[cfg(not(feature = "reranker-onnx"))]
+                warn!(model = %model_name, "Cross-encoder re-ranking not compiled in. Enable 'reranker-onnx' feature");
+
+                None
+            }
+            RerankBackend::Provider { provider_id, .. } => {
+                debug!(provider_id = %provider_id, "Provider re-ranking waits for a provider manager");
+                None
+            }
+        }
+    }
+
+    /// Enable provider-based re-ranking when the config selects a provider backend
+    pub async fn attach_provider_manager(&self, manager: Arc<ProviderManager>) {
+        if !self.config.rerank.enabled {
+            return;
+        }
+        if let RerankBackend::Provider { provider_id, model } = &self.config.rerank.backend {
+            info!(provider_id = %provider_id, "Enabling provider re-ranking");
+            let reranker = ProviderReranker::new(manager, provider_id.clone(), model.clone());
+            *self.reranker.write().await = Some(Arc::new(reranker));
+        }
+    }
+
+    /// Replace (or with `None`, remove) the re-ranking stage
+    pub async fn set_reranker(&self, reranker: Option<Arc<dyn Reranker>>) {
+        *self.reranker.write().await = reranker;
+    }
+
     /// Load documents index from storage
     fn load_documents_index(vespera_path: &Path) -> Result<HashMap<Uuid, DocumentMetadata>> {
         let index_path = vespera_path.join("rag/documents/index.json");
@@ -161,7 +249,7 @@ impl RAGService {
         {
             let documents = self.documents.read().await;
             for (_, doc) in documents.iter() {
-                if doc.content_hash == content_hash {
+                if doc.content_hash == content_hash && doc.bindery_source.is_none() {
                     info!(
                         existing_document_id = %doc.id,
                         title = %doc.title,
@@ -189,22 +277,155 @@ impl RAGService {
             updated_at: now,
             tags,
             project_id: Some(project.id),
+            bindery_source: None,
         };
 
-        // Choose chunking strategy based on document type
-        let strategy = match document_type {
+        self.store_document(metadata, &content).await?;
+
+        Ok(document_id)
+    }
+
+    /// Embed, persist and register a document under `metadata.id`
+    async fn store_document(&self, metadata: DocumentMetadata, content: &str) -> Result<()> {
+        // Chunk the document and generate embeddings for each chunk
+        let chunk_count = self.embed_document(&metadata, content).await?;
+
+        // Save document content to disk
+        self.write_document_file(&metadata, content, chunk_count)?;
+
+        // Analyze code if applicable
+        self.analyze_document(&metadata)?;
+
+        // Update documents index
+        {
+            let mut documents = self.documents.write().await;
+            documents.insert(metadata.id, metadata);
+        }
+
+        self.save_documents_index().await
+    }
+
+    /// Index (or refresh) the document for a Codex or task
+    ///
+    /// Unlike [`RAGService::index_document`], records are never deduplicated
+    /// by content: each Codex keeps its own document so search results link
+    /// back to it. Returns the document ID and whether it was (re-)embedded.
+    pub async fn upsert_bindery_document(
+        &self,
+        source: BinderySource,
+        title: String,
+        content: String,
+        document_type: DocumentType,
+        tags: Vec<String>,
+    ) -> Result<(Uuid, bool)> {
+        let content_hash = Self::calculate_content_hash(&content);
+        let now = Utc::now();
+
+        let existing = {
+            let documents = self.documents.read().await;
+            documents.values().find(|doc| doc.bindery_source == Some(source)).cloned()
+        };
+
+        if let Some(mut metadata) = existing {
+            if metadata.content_hash == content_hash && metadata.title == title && metadata.tags == tags {
+                return Ok((metadata.id, false));
+            }
+
+            debug!(document_id = %metadata.id, source = ?source, "Re-embedding changed Bindery record");
+            metadata.title = title;
+            metadata.document_type = document_type;
+            metadata.tags = tags;
+            metadata.content_hash = content_hash;
+            metadata.updated_at = now;
+            let document_id = metadata.id;
+            self.store_document(metadata, &content).await?;
+            return Ok((document_id, true));
+        }
+
+        let project = self.project_manager
+            .get_project_by_path(&self.project_path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Project not found"))?;
+
+        let metadata = DocumentMetadata {
+            id: Uuid::new_v4(),
+            title,
+            document_type,
+            source_path: None,
+            content_hash,
+            indexed_at: now,
+            updated_at: now,
+            tags,
+            project_id: Some(project.id),
+            bindery_source: Some(source),
+        };
+        let document_id = metadata.id;
+        self.store_document(metadata, &content).await?;
+
+        Ok((document_id, true))
+    }
+
+    /// Remove the document indexed from a Codex or task; returns whether one existed
+    pub async fn remove_bindery_document(&self, source: BinderySource) -> Result<bool> {
+        let document_id = {
+            let documents = self.documents.read().await;
+            documents.values().find(|doc| doc.bindery_source == Some(source)).map(|doc| doc.id)
+        };
+
+        match document_id {
+            Some(document_id) => self.delete_document(document_id).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Codices and tasks that currently have an indexed document
+    pub async fn bindery_sources(&self) -> Vec<BinderySource> {
+        self.documents
+            .read()
+            .await
+            .values()
+            .filter_map(|doc| doc.bindery_source)
+            .collect()
+    }
+
+    /// Choose chunking strategy based on document type
+    fn chunk_strategy(document_type: DocumentType) -> ChunkStrategy {
+        match document_type {
             DocumentType::Code => ChunkStrategy::Code,
             DocumentType::Markdown | DocumentType::Documentation => ChunkStrategy::Markdown,
             _ => ChunkStrategy::Paragraph,
-        };
+        }
+    }
 
-        // Chunk the document
-        let chunks = self.chunker.chunk(&content, strategy)?;
+    /// Split a document, chunking source files along declaration boundaries
+    fn chunk_document(&self, metadata: &DocumentMetadata, content: &str) -> Result<Vec<String>> {
+        let language = metadata
+            .source_path
+            .as_deref()
+            .and_then(|path| path.extension())
+            .and_then(|ext| ext.to_str())
+            .map(ProgrammingLanguage::from_extension)
+            .unwrap_or(ProgrammingLanguage::Unknown);
+
+        if metadata.document_type == DocumentType::Code && language != ProgrammingLanguage::Unknown {
+            self.chunker.chunk_source(content, language)
+        } else {
+            self.chunker.chunk(content, Self::chunk_strategy(metadata.document_type))
+        }
+    }
+
+    /// Chunk `content` and replace any existing embeddings of the document
+    async fn embed_document(&self, metadata: &DocumentMetadata, content: &str) -> Result<usize> {
+        let document_id = metadata.id;
+        let chunks = self.chunk_document(metadata, content)?;
 
-        // Generate embeddings for each chunk
         let mut embedding_service = self.embedding_service.write().await;
-        for (i, chunk_content) in chunks.iter().enumerate() {
-            let chunk = DocumentChunk {
+        embedding_service.delete_document(document_id).await?;
+
+        let document_chunks: Vec<DocumentChunk> = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk_content)| DocumentChunk {
                 id: format!("{}_chunk_{}", document_id, i),
                 document_id,
                 content: chunk_content.clone(),
@@ -213,44 +434,94 @@ impl RAGService {
                 start_char: 0, // TODO: Calculate actual character positions in original document
                 end_char: chunk_content.len(),
                 metadata: HashMap::new(),
-            };
+            })
+            .collect();
 
-            // Store chunk and embedding
-            embedding_service.index_chunk(&chunk).await?;
-        }
+        // Store chunks and embeddings, embedded in batched requests
+        embedding_service.index_chunks(&document_chunks).await?;
 
-        // Save document content to disk
-        let doc_path = self.vespera_path.join(format!("rag/documents/{}.json", document_id));
+        Ok(chunks.len())
+    }
+
+    /// Save document content and metadata to disk
+    fn write_document_file(&self, metadata: &DocumentMetadata, content: &str, chunk_count: usize) -> Result<()> {
+        let doc_path = self.vespera_path.join(format!("rag/documents/{}.json", metadata.id));
         let doc_data = serde_json::json!({
             "metadata": metadata,
             "content": content,
-            "chunks": chunks.len(),
+            "chunks": chunk_count,
         });
         fs::write(&doc_path, serde_json::to_string_pretty(&doc_data)?)?;
+        Ok(())
+    }
 
-        // Analyze code if applicable
-        if document_type == DocumentType::Code {
-            if let Some(analyzer) = &self.code_analyzer {
-                if let Some(path) = &source_path {
-                    if let Some(analysis) = analyzer.analyze_file(path)? {
-                        // Store analysis results
-                        let analysis_path = self.vespera_path
-                            .join(format!("rag/documents/{}_analysis.json", document_id));
-                        fs::write(&analysis_path, serde_json::to_string_pretty(&analysis)?)?;
-                    }
-                }
+    /// Run code analysis for code documents that have a source file
+    fn analyze_document(&self, metadata: &DocumentMetadata) -> Result<()> {
+        if metadata.document_type != DocumentType::Code {
+            return Ok(());
+        }
+        if let (Some(analyzer), Some(path)) = (&self.code_analyzer, &metadata.source_path) {
+            if let Some(analysis) = analyzer.analyze_file(path)? {
+                // Store analysis results
+                let analysis_path = self.vespera_path
+                    .join(format!("rag/documents/{}_analysis.json", metadata.id));
+                fs::write(&analysis_path, serde_json::to_string_pretty(&analysis)?)?;
             }
         }
+        Ok(())
+    }
 
-        // Update documents index
-        {
-            let mut documents = self.documents.write().await;
-            documents.insert(document_id, metadata);
+    /// Stored analyses of every indexed code document
+    async fn load_code_analyses(&self) -> Vec<CodeAnalysis> {
+        let code_documents: Vec<Uuid> = self
+            .documents
+            .read()
+            .await
+            .values()
+            .filter(|doc| doc.document_type == DocumentType::Code)
+            .map(|doc| doc.id)
+            .collect();
+
+        let mut analyses = Vec::with_capacity(code_documents.len());
+        for document_id in code_documents {
+            let analysis_path = self.vespera_path
+                .join(format!("rag/documents/{}_analysis.json", document_id));
+            let Ok(json) = fs::read_to_string(&analysis_path) else {
+                continue;
+            };
+            match serde_json::from_str(&json) {
+                Ok(analysis) => analyses.push(analysis),
+                Err(e) => warn!(document_id = %document_id, error = %e, "Skipping unreadable code analysis"),
+            }
         }
+        analyses
+    }
 
-        self.save_documents_index().await?;
+    /// Where `name` (optionally `Parent::name`) is declared in the indexed code
+    pub async fn locate_symbol(&self, name: &str) -> Vec<SymbolLocation> {
+        CodeAnalyzer::locate_symbol(&self.load_code_analyses().await, name)
+    }
 
-        Ok(document_id)
+    /// Check generated code against the indexed project
+    ///
+    /// The snippet may be wrapped in a Markdown code fence, whose language tag
+    /// is used; otherwise the language is guessed. The report lists calls and
+    /// imports that resolve to nothing, calls whose argument count fits no
+    /// definition, and imported modules or file paths that do not exist, with
+    /// file/line evidence for everything that did resolve.
+    pub async fn verify_code_references(&self, snippet: &str) -> Result<CodeReferenceReport> {
+        let (language, code) = verification::detect_language(snippet);
+        if language == ProgrammingLanguage::Unknown {
+            return Err(anyhow::anyhow!("Could not determine the language of the code snippet"));
+        }
+
+        let analyzer = self.code_analyzer.clone().unwrap_or_else(|| Arc::new(CodeAnalyzer::new()));
+        let analysis = analyzer
+            .analyze_code(code, language, "<snippet>".to_string())?
+            .ok_or_else(|| anyhow::anyhow!("Code analysis produced no result"))?;
+        let project = self.load_code_analyses().await;
+
+        Ok(verification::verify_references(&analysis, code, &project, &self.project_path))
     }
 
     /// Index a file from the filesystem
@@ -298,34 +569,43 @@ impl RAGService {
     ) -> Result<Vec<Uuid>> {
         let mut indexed_ids = Vec::new();
 
-        // Get project settings for patterns
+        // Walk directory and index matching files
+        let files = self.indexable_files(dir_path, recursive).await?;
+
+        for path in files {
+            match self.index_file(&path).await {
+                Ok(id) => indexed_ids.push(id),
+                Err(e) => eprintln!("Failed to index {}: {}", path.display(), e),
+            }
+        }
+
+        Ok(indexed_ids)
+    }
+
+    /// Files under `dir_path` matching the project's index and ignore patterns
+    pub(crate) async fn indexable_files(&self, dir_path: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
         let project = self.project_manager
             .get_project_by_path(&self.project_path)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Project not found"))?;
 
-        let patterns = &project.settings.index_patterns;
-        let ignore_patterns = &project.settings.ignore_patterns;
-
-        // Walk directory and index matching files
-        self.index_directory_recursive(
+        let mut files = Vec::new();
+        Self::collect_indexable_files(
             dir_path,
             recursive,
-            patterns,
-            ignore_patterns,
-            &mut indexed_ids,
-        ).await?;
-
-        Ok(indexed_ids)
+            &project.settings.index_patterns,
+            &project.settings.ignore_patterns,
+            &mut files,
+        )?;
+        Ok(files)
     }
 
-    async fn index_directory_recursive(
-        &self,
+    fn collect_indexable_files(
         path: &Path,
         recursive: bool,
         patterns: &[String],
         ignore_patterns: &[String],
-        indexed_ids: &mut Vec<Uuid>,
+        files: &mut Vec<PathBuf>,
     ) -> Result<()> {
         // Check if path should be ignored
         let path_str = path.to_string_lossy();
@@ -339,23 +619,17 @@ impl RAGService {
             // Check if file matches patterns
             for pattern in patterns {
                 if glob::Pattern::new(pattern)?.matches(&path_str) {
-                    match self.index_file(path).await {
-                        Ok(id) => indexed_ids.push(id),
-                        Err(e) => eprintln!("Failed to index {}: {}", path_str, e),
-                    }
+                    files.push(path.to_path_buf());
                     break;
                 }
             }
-        } else if path.is_dir() && recursive {
+        } else if path.is_dir() {
             if let Ok(entries) = fs::read_dir(path) {
                 for entry in entries.flatten() {
-                    Box::pin(self.index_directory_recursive(
-                        &entry.path(),
-                        recursive,
-                        patterns,
-                        ignore_patterns,
-                        indexed_ids,
-                    )).await?;
+                    let entry_path = entry.path();
+                    if entry_path.is_file() || recursive {
+                        Self::collect_indexable_files(&entry_path, recursive, patterns, ignore_patterns, files)?;
+                    }
                 }
             }
         }
@@ -363,23 +637,208 @@ impl RAGService {
         Ok(())
     }
 
-    /// Search for documents using semantic similarity
-    #[instrument(skip(self), fields(
+    /// Bring the index in line with the files under `dir_path`
+    ///
+    /// New files are indexed, files whose content hash changed are re-chunked
+    /// and re-embedded in place (keeping their document ID), and documents
+    /// whose source file no longer exists are deleted along with their chunks.
+    /// Unchanged files are skipped, so repeated calls are cheap.
+    #[instrument(skip(self), fields(dir_path = %dir_path.display()))]
+    pub async fn sync_directory(&self, dir_path: &Path, recursive: bool) -> Result<ReindexReport> {
+        let dir_path = dir_path.canonicalize()
+            .with_context(|| format!("Failed to canonicalize directory: {:?}", dir_path))?;
+
+        let files = self.indexable_files(&dir_path, recursive).await?;
+
+        let mut report = ReindexReport::default();
+
+        // Garbage-collect documents whose source file disappeared
+        let vanished: Vec<(Uuid, PathBuf)> = {
+            let documents = self.documents.read().await;
+            documents
+                .values()
+                .filter_map(|doc| doc.source_path.as_ref().map(|path| (doc.id, path)))
+                .filter(|(_, path)| {
+                    let in_scope = if recursive {
+                        path.starts_with(&dir_path)
+                    } else {
+                        path.parent() == Some(dir_path.as_path())
+                    };
+                    in_scope && !path.exists()
+                })
+                .map(|(id, path)| (id, path.clone()))
+                .collect()
+        };
+        for (document_id, path) in vanished {
+            if self.delete_document(document_id).await? {
+                debug!(document_id = %document_id, path = %path.display(), "Removed document for deleted file");
+                report.removed.push(path);
+            }
+        }
+
+        for path in files {
+            match self.sync_file(&path).await {
+                Ok(SyncOutcome::Added) => report.added.push(path),
+                Ok(SyncOutcome::Updated) => report.updated.push(path),
+                Ok(SyncOutcome::Unchanged) => report.unchanged += 1,
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "Failed to re-index file");
+                    report.failed.push((path, e.to_string()));
+                }
+            }
+        }
+
+        info!(
+            added = report.added.len(),
+            updated = report.updated.len(),
+            removed = report.removed.len(),
+            unchanged = report.unchanged,
+            failed = report.failed.len(),
+            "Incremental re-index completed"
+        );
+
+        Ok(report)
+    }
+
+    /// Index a single file if it is new or its content changed
+    pub(crate) async fn sync_file(&self, file_path: &Path) -> Result<SyncOutcome> {
+        let canonical_path = file_path.canonicalize()?;
+        let content = fs::read_to_string(&canonical_path)
+            .with_context(|| format!("Failed to read file: {:?}", canonical_path))?;
+        let content_hash = Self::calculate_content_hash(&content);
+
+        let existing = {
+            let documents = self.documents.read().await;
+            documents
+                .values()
+                .find(|doc| doc.source_path.as_deref() == Some(canonical_path.as_path()))
+                .cloned()
+        };
+
+        let Some(mut metadata) = existing else {
+            let known_before = self.documents.read().await.len();
+            self.index_file(&canonical_path).await?;
+            // Identical content at another path is deduplicated onto the existing document
+            let added = self.documents.read().await.len() > known_before;
+            return Ok(if added { SyncOutcome::Added } else { SyncOutcome::Unchanged });
+        };
+
+        if metadata.content_hash == content_hash {
+            return Ok(SyncOutcome::Unchanged);
+        }
+
+        debug!(document_id = %metadata.id, path = %canonical_path.display(), "Re-embedding changed file");
+        metadata.content_hash = content_hash;
+        metadata.updated_at = Utc::now();
+
+        self.store_document(metadata, &content).await?;
+
+        Ok(SyncOutcome::Updated)
+    }
+
+    /// Start re-indexing `dir_path` in the background every `interval`
+    ///
+    /// Each tick runs [`RAGService::sync_directory`], so only changed files
+    /// are re-embedded. Replaces any watch that is already running.
+    pub async fn start_watching(self: &Arc<Self>, dir_path: &Path, recursive: bool, interval: Duration) -> Result<()> {
+        if interval.is_zero() {
+            return Err(anyhow::anyhow!(crate::BinderyError::ConfigurationError(
+                "watch interval must be greater than 0".to_string()
+            )));
+        }
+
+        let service = Arc::downgrade(self);
+        let dir_path = dir_path.canonicalize()
+            .with_context(|| format!("Failed to canonicalize directory: {:?}", dir_path))?;
+
+        info!(dir_path = %dir_path.display(), "Watching directory for changes every {:?}", interval);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                // Stop once the service itself has been dropped
+                let Some(service) = service.upgrade() else {
+                    break;
+                };
+
+                match service.sync_directory(&dir_path, recursive).await {
+                    Ok(report) if report.has_changes() => {
+                        debug!(?report, "Watch picked up file changes");
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!(error = %e, "Watch re-index failed"),
+                }
+            }
+        });
+
+        if let Some(previous) = self.watch_handle.lock().await.replace(handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the background watch if it is running
+    pub async fn stop_watching(&self) {
+        if let Some(handle) = self.watch_handle.lock().await.take() {
+            handle.abort();
+            info!("Stopped watching for file changes");
+        }
+    }
+
+    /// Write buffered index state to disk, e.g. before exiting
+    pub async fn flush(&self) -> Result<()> {
+        self.embedding_service.read().await.flush()
+    }
+
+    /// Search for documents using the configured ranking (hybrid by default)
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        filter_types: Option<Vec<DocumentType>>,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_with_options(query, limit, filter_types, &self.config.search).await
+    }
+
+    /// Search for documents with explicit ranking options
+    #[instrument(skip(self, options), fields(
         query = %query,
         limit = limit,
-        filter_types = ?filter_types
+        filter_types = ?filter_types,
+        mode = ?options.mode
     ))]
-    pub async fn search(
+    pub async fn search_with_options(
         &self,
         query: &str,
         limit: usize,
         filter_types: Option<Vec<DocumentType>>,
+        options: &SearchOptions,
     ) -> Result<Vec<SearchResult>> {
-        debug!(query = %query, limit = limit, "Starting semantic search");
+        debug!(query = %query, limit = limit, "Starting search");
 
         let start_time = std::time::Instant::now();
+        // A re-ranker sees at least `top_k` candidates before results are cut back to `limit`
+        let reranker = if options.rerank { self.reranker.read().await.clone() } else { None };
+        let first_stage_limit = if reranker.is_some() {
+            limit.max(self.config.rerank.top_k)
+        } else {
+            limit
+        };
+
         let embedding_service = self.embedding_service.read().await;
-        let raw_results = embedding_service.search(query, limit * 2).await?; // Get more to filter
+        let candidates = first_stage_limit * 2; // Get more to filter
+        let raw_results = match options.mode {
+            SearchMode::Vector => embedding_service.search(query, candidates).await?,
+            SearchMode::Lexical => embedding_service.lexical_search(query, candidates).await?,
+            SearchMode::Hybrid => {
+                let vector = embedding_service.search(query, candidates).await?;
+                let lexical = embedding_service.lexical_search(query, candidates).await?;
+                Self::reciprocal_rank_fusion(
+                    &[(options.vector_weight, vector), (options.lexical_weight, lexical)],
+                    options.rrf_k,
+                )
+            }
+        };
 
         debug!(
             raw_result_count = raw_results.len(),
@@ -420,12 +879,19 @@ impl RAGService {
                         highlights: Vec::new(), // TODO: Implement query term highlighting in search results
                     });
 
-                    if results.len() >= limit {
+                    if results.len() >= first_stage_limit {
                         break;
                     }
                 }
             }
         }
+        drop(documents);
+        drop(embedding_service);
+
+        if let Some(reranker) = reranker {
+            self.rerank(reranker.as_ref(), query, &mut results).await;
+        }
+        results.truncate(limit);
 
         let duration = start_time.elapsed();
         info!(
@@ -437,8 +903,13 @@ impl RAGService {
         );
 
         // Record search metrics for monitoring and performance tracking
+        let search_type = match options.mode {
+            SearchMode::Vector => "semantic",
+            SearchMode::Lexical => "lexical",
+            SearchMode::Hybrid => "hybrid",
+        };
         crate::observability::BinderyMetrics::record_rag_search(
-            "semantic",
+            search_type,
             results.len(),
             duration,
             true // Search completed successfully
@@ -447,6 +918,190 @@ impl RAGService {
         Ok(results)
     }
 
+    /// Re-score the top `top_k` results, keeping first-stage order on failure
+    async fn rerank(&self, reranker: &dyn Reranker, query: &str, results: &mut [SearchResult]) {
+        let head = results.len().min(self.config.rerank.top_k);
+        if head < 2 {
+            return;
+        }
+
+        let passages: Vec<String> = results[..head]
+            .iter()
+            .map(|r| r.content.chars().take(self.config.rerank.max_passage_chars).collect())
+            .collect();
+
+        let timeout = Duration::from_secs(self.config.rerank.timeout_secs);
+        match tokio::time::timeout(timeout, reranker.score(query, &passages)).await {
+            Ok(Ok(scores)) if scores.len() == head => {
+                for (result, score) in results[..head].iter_mut().zip(scores) {
+                    result.score = score;
+                }
+                results[..head].sort_by(|a, b| b.score.total_cmp(&a.score));
+                debug!(reranker = reranker.name(), reranked = head, "Re-ranked search results");
+            }
+            Ok(Ok(scores)) => warn!(
+                reranker = reranker.name(),
+                expected = head,
+                received = scores.len(),
+                "Re-ranker returned the wrong number of scores, keeping first-stage order"
+            ),
+            Ok(Err(e)) => warn!(
+                reranker = reranker.name(),
+                error = %e,
+                "Re-ranking failed, keeping first-stage order"
+            ),
+            Err(_) => warn!(
+                reranker = reranker.name(),
+                timeout_secs = self.config.rerank.timeout_secs,
+                "Re-ranking timed out, keeping first-stage order"
+            ),
+        }
+    }
+
+    /// Fuse ranked lists with weighted reciprocal rank fusion
+    ///
+    /// Each list contributes `weight / (k + rank)` per chunk (rank starting at
+    /// 1), so a chunk ranked well by both retrievers beats one that only a
+    /// single retriever likes, regardless of how their raw scores are scaled.
+    fn reciprocal_rank_fusion(
+        rankings: &[(f32, RankedChunks)],
+        k: f32,
+    ) -> RankedChunks {
+        let mut fused: HashMap<String, (f32, String)> = HashMap::new();
+
+        for (weight, ranking) in rankings {
+            if *weight <= 0.0 {
+                continue;
+            }
+            for (rank, (chunk_id, _, content)) in ranking.iter().enumerate() {
+                let contribution = weight / (k + rank as f32 + 1.0);
+                fused
+                    .entry(chunk_id.clone())
+                    .and_modify(|(score, _)| *score += contribution)
+                    .or_insert_with(|| (contribution, content.clone()));
+            }
+        }
+
+        let mut results: RankedChunks = fused
+            .into_iter()
+            .map(|(chunk_id, (score, content))| (chunk_id, score, content))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results
+    }
+
+    /// ID of the project this service indexes
+    pub async fn project_id(&self) -> Result<Uuid> {
+        self.project_manager
+            .get_project_by_path(&self.project_path)
+            .await?
+            .map(|project| project.id)
+            .ok_or_else(|| anyhow::anyhow!("Project not found"))
+    }
+
+    /// Service for another registered project, opened on first use
+    ///
+    /// Fails for unknown projects and for private ones, which are only
+    /// reachable through their own service.
+    pub async fn federated_service(&self, project_id: Uuid) -> Result<Arc<RAGService>> {
+        if self.project_manager.is_private(project_id).await {
+            self.federation.write().await.remove(&project_id);
+            anyhow::bail!("Project {} is private or not registered", project_id);
+        }
+        if let Some(service) = self.federation.read().await.get(&project_id) {
+            return Ok(service.clone());
+        }
+
+        let project = self.project_manager
+            .get_project(project_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Project not found: {}", project_id))?;
+
+        // Peers keep their own `.vespera` folder even if this service's is overridden
+        let config = RAGConfig {
+            vespera_folder_override: None,
+            ..self.config.clone()
+        };
+        let service = Arc::new(Box::pin(RAGService::new(&project.root_path, config)).await?);
+
+        let mut federation = self.federation.write().await;
+        Ok(federation.entry(project_id).or_insert(service).clone())
+    }
+
+    /// Search this project together with other registered projects
+    ///
+    /// Each project is searched with its own index, its scores are min-max
+    /// normalized, and the hits are merged by normalized score. Private
+    /// projects are skipped when searching all projects and rejected when
+    /// requested explicitly.
+    #[instrument(skip(self, options), fields(query = %query, limit = limit))]
+    pub async fn search_federated(
+        &self,
+        query: &str,
+        limit: usize,
+        filter_types: Option<Vec<DocumentType>>,
+        options: &FederatedSearchOptions,
+    ) -> Result<Vec<FederatedSearchResult>> {
+        let home_id = self.project_id().await?;
+
+        let peer_ids: Vec<Uuid> = match &options.projects {
+            Some(requested) => {
+                for &project_id in requested {
+                    if project_id != home_id && self.project_manager.is_private(project_id).await {
+                        anyhow::bail!("Project {} is private and cannot be searched from another project", project_id);
+                    }
+                }
+                requested.iter().copied().filter(|&id| id != home_id).collect()
+            }
+            None => {
+                let mut peers = Vec::new();
+                for project in self.project_manager.get_all_projects().await {
+                    if project.id != home_id && !self.project_manager.is_private(project.id).await {
+                        peers.push(project.id);
+                    }
+                }
+                peers
+            }
+        };
+
+        let per_project_limit = options.per_project_limit.unwrap_or(limit);
+        let mut merged = Vec::new();
+
+        for project_id in std::iter::once(home_id).chain(peer_ids) {
+            let (project_name, mut results) = if project_id == home_id {
+                let name = self.project_manager.get_project(home_id).await.map(|p| p.name).unwrap_or_default();
+                let search = options.search.as_ref().unwrap_or(&self.config.search);
+                let results = self.search_with_options(query, per_project_limit, filter_types.clone(), search).await?;
+                (name, results)
+            } else {
+                let peer = self.federated_service(project_id).await?;
+                let name = self.project_manager.get_project(project_id).await.map(|p| p.name).unwrap_or_default();
+                let search = options.search.as_ref().unwrap_or(&peer.config.search);
+                match peer.search_with_options(query, per_project_limit, filter_types.clone(), search).await {
+                    Ok(results) => (name, results),
+                    Err(e) => {
+                        warn!(project_id = %project_id, error = %e, "Skipping project in federated search");
+                        continue;
+                    }
+                }
+            };
+
+            // Only documents owned by the searched project may surface under it
+            results.retain(|result| result.metadata.project_id.is_none_or(|owner| owner == project_id));
+            let raw_scores: Vec<f32> = results.iter().map(|result| result.score).collect();
+            federation::normalize_scores(&mut results);
+
+            merged.extend(results.into_iter().zip(raw_scores).map(|(result, raw_score)| FederatedSearchResult {
+                project_id,
+                project_name: project_name.clone(),
+                raw_score,
+                result,
+            }));
+        }
+
+        Ok(federation::merge_results(merged, limit))
+    }
+
     /// Get document by ID
     pub async fn get_document(&self, document_id: Uuid) -> Result<Option<(DocumentMetadata, String)>> {
         let documents = self.documents.read().await;
@@ -516,17 +1171,63 @@ impl RAGService {
 
         // Count projects
         let projects = self.project_manager.get_all_projects().await;
+        let total_documents = documents.len();
+        drop(documents);
+        drop(embedding_service);
 
         Ok(RAGStats {
-            total_documents: documents.len(),
+            total_documents,
             total_chunks: embedding_stats.total_embeddings,
             total_embeddings: embedding_stats.total_embeddings,
             index_size_bytes: index_size,
             last_indexed,
             projects_tracked: projects.len(),
+            projects: self.project_stats(projects).await,
         })
     }
 
+    /// Document counts for this project and every non-private registered project
+    async fn project_stats(&self, projects: Vec<ProjectConfig>) -> Vec<ProjectStats> {
+        let home_id = self.project_id().await.ok();
+        let federation = self.federation.read().await;
+        let mut stats = Vec::new();
+
+        for project in projects {
+            let is_home = Some(project.id) == home_id;
+            let private = self.project_manager.is_private(project.id).await;
+            if private && !is_home {
+                continue;
+            }
+
+            let summarize = |documents: &HashMap<Uuid, DocumentMetadata>| {
+                let owned: Vec<&DocumentMetadata> = documents
+                    .values()
+                    .filter(|doc| doc.project_id.is_none_or(|owner| owner == project.id))
+                    .collect();
+                (owned.len(), owned.iter().map(|doc| doc.indexed_at).max())
+            };
+            let (total_documents, last_indexed) = if is_home {
+                summarize(&*self.documents.read().await)
+            } else if let Some(peer) = federation.get(&project.id) {
+                summarize(&*peer.documents.read().await)
+            } else {
+                summarize(&Self::load_documents_index(&project.vespera_path).unwrap_or_default())
+            };
+
+            stats.push(ProjectStats {
+                project_id: project.id,
+                name: project.name,
+                total_documents,
+                last_indexed,
+                private,
+                federated: federation.contains_key(&project.id),
+            });
+        }
+
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
     fn calculate_directory_size(path: &Path) -> Result<u64> {
         let mut size = 0;
 
@@ -630,37 +1331,9 @@ impl RAGService {
 
                 if let Some(content) = doc_data.get("content").and_then(|v| v.as_str()) {
                     // Re-chunk and re-embed
-                    let documents = self.documents.read().await;
-                    if let Some(metadata) = documents.get(&doc_id) {
-                        let strategy = match metadata.document_type {
-                            DocumentType::Code => ChunkStrategy::Code,
-                            DocumentType::Markdown | DocumentType::Documentation => ChunkStrategy::Markdown,
-                            _ => ChunkStrategy::Paragraph,
-                        };
-
-                        let chunks = self.chunker.chunk(content, strategy)?;
-
-                        let mut embedding_service = self.embedding_service.write().await;
-
-                        // Delete old embeddings
-                        embedding_service.delete_document(doc_id).await?;
-
-                        // Create new embeddings
-                        for (i, chunk_content) in chunks.iter().enumerate() {
-                            let chunk = DocumentChunk {
-                                id: format!("{}_chunk_{}", doc_id, i),
-                                document_id: doc_id,
-                                content: chunk_content.clone(),
-                                chunk_index: i,
-                                total_chunks: chunks.len(),
-                                start_char: 0,
-                                end_char: chunk_content.len(),
-                                metadata: HashMap::new(),
-                            };
-
-                            embedding_service.index_chunk(&chunk).await?;
-                        }
-
+                    let metadata = self.documents.read().await.get(&doc_id).cloned();
+                    if let Some(metadata) = metadata {
+                        self.embed_document(&metadata, content).await?;
                         reindexed += 1;
                     }
                 }
@@ -671,10 +1344,18 @@ impl RAGService {
     }
 }
 
+/// Result of syncing a single file
+pub(crate) enum SyncOutcome {
+    Added,
+    Updated,
+    Unchanged,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
+    use crate::rag::project_manager::ProjectSettings;
 
     #[tokio::test]
     async fn test_rag_service_creation() {
@@ -742,4 +1423,246 @@ mod tests {
         let results = service.search("programming language", 10, None).await.unwrap();
         assert!(!results.is_empty());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_verify_code_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap();
+
+        let source = temp_dir.path().join("src/tokens.rs");
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, "pub fn count_tokens(text: &str) -> usize {\n    text.len() / 4\n}\n").unwrap();
+        service.index_file(&source).await.unwrap();
+
+        let located = service.locate_symbol("count_tokens").await;
+        assert_eq!(located.len(), 1);
+        assert_eq!(located[0].line_number, 1);
+
+        let snippet = "```rust\nuse crate::tokens::count_tokens;\n\nfn budget(text: &str) -> usize {\n    count_tokens(text) + estimate_tokens(text, 4)\n}\n```";
+        let report = service.verify_code_references(snippet).await.unwrap();
+        assert!(report.verified.iter().any(|v| v.name == "count_tokens" && v.location.line_number == 1));
+        assert_eq!(report.unknown_symbols.len(), 1);
+        assert_eq!(report.unknown_symbols[0].name, "estimate_tokens");
+        assert_eq!(report.unknown_symbols[0].suggestions[0].name, "count_tokens");
+
+        assert!(service.verify_code_references("plain prose").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_federated_search_respects_private_projects() {
+        let home_dir = TempDir::new().unwrap();
+        let peer_dir = TempDir::new().unwrap();
+        let home = RAGService::new(home_dir.path(), RAGConfig::default()).await.unwrap();
+
+        let peer_project = home.project_manager.initialize_project(peer_dir.path(), Some("peer".into())).await.unwrap();
+        let peer = home.federated_service(peer_project.id).await.unwrap();
+        assert_eq!(peer.project_id().await.unwrap(), peer_project.id);
+
+        home.index_document("Home notes".into(), "Tokenizer budget notes for the home project.".into(), DocumentType::Text, None, Vec::new())
+            .await.unwrap();
+        peer.index_document("Peer notes".into(), "Tokenizer budget notes kept in the peer project.".into(), DocumentType::Text, None, Vec::new())
+            .await.unwrap();
+
+        let results = home.search_federated("tokenizer budget", 10, None, &FederatedSearchOptions::default()).await.unwrap();
+        assert!(results.iter().any(|r| r.project_id == peer_project.id && r.project_name == "peer"));
+        assert!(results.iter().any(|r| r.project_id != peer_project.id));
+        assert!(results.iter().all(|r| (0.0..=1.0).contains(&r.result.score)));
+
+        let stats = home.get_stats().await.unwrap();
+        let peer_stats = stats.projects.iter().find(|p| p.project_id == peer_project.id).unwrap();
+        assert_eq!(peer_stats.total_documents, 1);
+        assert!(peer_stats.federated);
+
+        // Once private, the peer drops out of federated search and stats
+        let settings = ProjectSettings { private: true, ..ProjectSettings::default() };
+        peer.project_manager.update_project_settings(peer_project.id, settings).await.unwrap();
+
+        let results = home.search_federated("tokenizer budget", 10, None, &FederatedSearchOptions::default()).await.unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.project_id != peer_project.id));
+
+        let explicit = FederatedSearchOptions { projects: Some(vec![peer_project.id]), ..Default::default() };
+        assert!(home.search_federated("tokenizer budget", 10, None, &explicit).await.is_err());
+        assert!(home.federated_service(peer_project.id).await.is_err());
+        assert!(home.get_stats().await.unwrap().projects.iter().all(|p| p.project_id != peer_project.id));
+    }
+
+    #[tokio::test]
+    async fn test_sync_directory_reindexes_only_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap();
+
+        let notes = temp_dir.path().join("notes.txt");
+        let readme = temp_dir.path().join("README.md");
+        fs::write(&notes, "First draft of the notes.").unwrap();
+        fs::write(&readme, "# Project\n\nOverview.").unwrap();
+
+        let report = service.sync_directory(temp_dir.path(), true).await.unwrap();
+        assert_eq!(report.added.len(), 2);
+        assert!(report.failed.is_empty());
+
+        // Nothing changed: nothing is re-embedded
+        let report = service.sync_directory(temp_dir.path(), true).await.unwrap();
+        assert!(!report.has_changes());
+        assert_eq!(report.unchanged, 2);
+
+        // Edit one file and delete the other
+        let notes_id = service.index_file(&notes).await.unwrap();
+        fs::write(&notes, "Second draft with new material.").unwrap();
+        fs::remove_file(&readme).unwrap();
+
+        let report = service.sync_directory(temp_dir.path(), true).await.unwrap();
+        assert_eq!(report.updated, vec![notes.canonicalize().unwrap()]);
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.unchanged, 0);
+
+        // The document keeps its ID and now serves the new content
+        let (_, content) = service.get_document(notes_id).await.unwrap().unwrap();
+        assert_eq!(content, "Second draft with new material.");
+        assert_eq!(service.get_stats().await.unwrap().total_documents, 1);
+
+        let results = service.search("Second draft with new material.", 5, None).await.unwrap();
+        assert!(results.iter().all(|r| r.document_id == notes_id));
+    }
+
+    #[tokio::test]
+    async fn test_watch_picks_up_new_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = Arc::new(RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap());
+
+        assert!(service.start_watching(temp_dir.path(), true, Duration::ZERO).await.is_err());
+        service.start_watching(temp_dir.path(), true, Duration::from_millis(20)).await.unwrap();
+
+        fs::write(temp_dir.path().join("added.md"), "# Added later").unwrap();
+        for _ in 0..100 {
+            if service.get_stats().await.unwrap().total_documents == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        service.stop_watching().await;
+
+        assert_eq!(service.get_stats().await.unwrap().total_documents, 1);
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let hit = |id: &str| (id.to_string(), 0.0, String::new());
+        let vector = vec![hit("a"), hit("b"), hit("c")];
+        let lexical = vec![hit("c"), hit("b"), hit("d")];
+
+        let fused = RAGService::reciprocal_rank_fusion(&[(1.0, vector.clone()), (1.0, lexical.clone())], 60.0);
+        let order: Vec<&str> = fused.iter().map(|r| r.0.as_str()).collect();
+        // b and c appear in both lists, so they outrank single-list hits
+        assert_eq!(order, vec!["c", "b", "a", "d"]);
+
+        // Heavily weighting lexical promotes its top hit
+        let fused = RAGService::reciprocal_rank_fusion(&[(0.2, vector), (1.0, lexical)], 60.0);
+        assert_eq!(fused[0].0, "c");
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_search_finds_exact_identifiers() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap();
+
+        let target = service
+            .index_document(
+                "config.rs".to_string(),
+                "fn parse_config_file(path: &Path) -> Result<Config>".to_string(),
+                DocumentType::Code,
+                None,
+                vec![],
+            )
+            .await
+            .unwrap();
+        for i in 0..5 {
+            service
+                .index_document(
+                    format!("notes-{}", i),
+                    format!("General notes about project planning, iteration {}", i),
+                    DocumentType::Text,
+                    None,
+                    vec![],
+                )
+                .await
+                .unwrap();
+        }
+
+        let lexical = service
+            .search_with_options("parse_config_file", 3, None, &SearchOptions::lexical())
+            .await
+            .unwrap();
+        assert_eq!(lexical.len(), 1);
+        assert_eq!(lexical[0].document_id, target);
+
+        let hybrid = service.search("parse_config_file", 3, None).await.unwrap();
+        assert_eq!(hybrid[0].document_id, target);
+    }
+
+    struct KeywordReranker(&'static str);
+
+    #[async_trait::async_trait]
+    impl Reranker for KeywordReranker {
+        async fn score(&self, _query: &str, passages: &[String]) -> Result<Vec<f32>> {
+            Ok(passages.iter().map(|p| if p.contains(self.0) { 10.0 } else { 0.0 }).collect())
+        }
+
+        fn name(&self) -> &str {
+            "keyword"
+        }
+    }
+
+    struct FailingReranker;
+
+    #[async_trait::async_trait]
+    impl Reranker for FailingReranker {
+        async fn score(&self, _query: &str, _passages: &[String]) -> Result<Vec<f32>> {
+            anyhow::bail!("model unavailable")
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rerank_stage_reorders_and_falls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RAGService::new(temp_dir.path(), RAGConfig::default()).await.unwrap();
+
+        for topic in ["alpha", "beta", "gamma", "delta"] {
+            service
+                .index_document(
+                    topic.to_string(),
+                    format!("Shared search words about {}", topic),
+                    DocumentType::Text,
+                    None,
+                    vec![],
+                )
+                .await
+                .unwrap();
+        }
+
+        let options = SearchOptions::lexical();
+        let baseline = service.search_with_options("shared search words", 2, None, &options).await.unwrap();
+        assert_eq!(baseline.len(), 2);
+
+        // The re-ranker sees top_k candidates, not just `limit`, so it can promote "gamma"
+        service.set_reranker(Some(Arc::new(KeywordReranker("gamma")))).await;
+        let reranked = service.search_with_options("shared search words", 2, None, &options).await.unwrap();
+        assert_eq!(reranked.len(), 2);
+        assert!(reranked[0].content.contains("gamma"));
+        assert_eq!(reranked[0].score, 10.0);
+
+        // Per-query opt-out
+        let opted_out = SearchOptions { rerank: false, ..SearchOptions::lexical() };
+        let plain = service.search_with_options("shared search words", 2, None, &opted_out).await.unwrap();
+        assert_eq!(plain[0].chunk_id, baseline[0].chunk_id);
+
+        // Failures keep first-stage order
+        service.set_reranker(Some(Arc::new(FailingReranker))).await;
+        let fallback = service.search_with_options("shared search words", 2, None, &options).await.unwrap();
+        assert_eq!(fallback[0].chunk_id, baseline[0].chunk_id);
+    }
+}
@@ -0,0 +1,1326 @@
+//! # Structural Source Parser
+//!
+//! Syntax pass shared by the code analyzer and the code chunker. It extracts
+//! declarations (with their full byte and line extent), imports and call
+//! sites for Rust, Python and TypeScript/JavaScript.
+//!
+//! With the `tree-sitter` feature, [`parse`] reads them off tree-sitter syntax
+//! trees (see `grammars`). Without it, [`parse`] falls back to [`scan`], a
+//! lightweight scanner that is not a parser and covers less:
+//!
+//! - declarations are only recognised at the start of a line, so a second item
+//!   on the same line, or an item after an attribute on its line, is missed
+//! - item extents come from bracket matching (Rust, TypeScript/JavaScript) or
+//!   from indentation (Python), so unusual layouts can throw them off
+//! - calls are `name(` patterns, so some non-calls (e.g. `fn(u8)` types) are
+//!   skipped by keyword rather than by grammar
+//!
+//! The scanner blanks out comments and string literals before matching, so
+//! braces, parentheses and keywords are only ever seen in real code. Offsets
+//! into the blanked text are valid offsets into the original source, which is
+//! where signatures are read from.
+//!
+//! Both backends fill in the same [`SymbolInfo`] fields, and complexity is
+//! counted the same way for both, from branch keywords in the symbol's text.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use anyhow::Result;
+use regex::Regex;
+
+use super::code_analyzer::{CallSite, FieldInfo, ImportInfo, ProgrammingLanguage, SymbolInfo, SymbolKind};
+
+#[cfg(feature = "tree-sitter")]
+mod grammars;
+
+/// Declarations, imports and calls found in one source file
+#[derive(Debug, Clone, Default)]
+pub struct SyntaxOutline {
+    /// Declarations in source order
+    pub symbols: Vec<SymbolInfo>,
+    pub imports: Vec<ImportInfo>,
+    pub calls: Vec<CallSite>,
+}
+
+impl SyntaxOutline {
+    /// Top-level declarations (not nested in another declaration)
+    pub fn top_level(&self) -> impl Iterator<Item = &SymbolInfo> {
+        self.symbols.iter().filter(|symbol| symbol.depth == 0)
+    }
+
+    /// Declarations directly nested in `container`
+    pub fn children_of<'a>(&'a self, container: &'a SymbolInfo) -> impl Iterator<Item = &'a SymbolInfo> {
+        self.symbols.iter().filter(move |symbol| {
+            symbol.depth == container.depth + 1
+                && symbol.span.start >= container.span.start
+                && symbol.span.end <= container.span.end
+        })
+    }
+}
+
+/// Parse `content`; languages without a structural parser yield an empty outline
+///
+/// Uses the tree-sitter grammars with the `tree-sitter` feature, [`scan`] otherwise.
+pub fn parse(content: &str, language: ProgrammingLanguage) -> Result<SyntaxOutline> {
+    let source = Source::new(content, language);
+    #[cfg(feature = "tree-sitter")]
+    let outline = grammars::parse(&source)?;
+    #[cfg(not(feature = "tree-sitter"))]
+    let outline = scan_source(&source)?;
+    Ok(finish(outline, &source))
+}
+
+/// Outline `content` with the built-in scanner, whatever the features
+pub fn scan(content: &str, language: ProgrammingLanguage) -> Result<SyntaxOutline> {
+    let source = Source::new(content, language);
+    let outline = scan_source(&source)?;
+    Ok(finish(outline, &source))
+}
+
+fn scan_source(source: &Source) -> Result<SyntaxOutline> {
+    let mut outline = match source.language {
+        ProgrammingLanguage::Rust => parse_rust(source)?,
+        ProgrammingLanguage::Python => parse_python(source)?,
+        ProgrammingLanguage::TypeScript | ProgrammingLanguage::JavaScript => parse_typescript(source)?,
+        _ => return Ok(SyntaxOutline::default()),
+    };
+    let declared: HashSet<usize> = outline.symbols.iter().map(|symbol| symbol.name_offset).collect();
+    outline.calls = find_calls(source, &declared)?;
+    Ok(outline)
+}
+
+/// Order symbols, nest them and measure their complexity
+fn finish(mut outline: SyntaxOutline, source: &Source) -> SyntaxOutline {
+    outline.symbols.sort_by_key(|symbol| (symbol.span.start, std::cmp::Reverse(symbol.span.end)));
+    assign_parents(&mut outline.symbols);
+    for symbol in &mut outline.symbols {
+        symbol.complexity = complexity(&source.masked[symbol.span.clone()], source.language);
+    }
+    outline
+}
+
+/// Source text plus its comment- and string-blanked twin
+struct Source<'a> {
+    text: &'a str,
+    masked: String,
+    line_starts: Vec<usize>,
+    language: ProgrammingLanguage,
+}
+
+impl<'a> Source<'a> {
+    fn new(text: &'a str, language: ProgrammingLanguage) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self {
+            text,
+            masked: mask_source(text, language, false),
+            line_starts,
+            language,
+        }
+    }
+
+    /// 1-based line containing byte `offset`
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Masked lines with their starting byte offsets
+    fn masked_lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.line_starts.iter().map(move |&start| {
+            let end = self.masked[start..].find('\n').map(|i| start + i).unwrap_or(self.masked.len());
+            (start, &self.masked[start..end])
+        })
+    }
+
+    /// Original text in `range` with whitespace collapsed
+    fn collapsed(&self, range: Range<usize>) -> String {
+        self.text[range].split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn symbol(&self, name: &str, kind: SymbolKind, name_offset: usize, header: Range<usize>, span: Range<usize>) -> SymbolInfo {
+        let end = span.end.saturating_sub(1).max(span.start);
+        SymbolInfo {
+            name: name.to_string(),
+            kind,
+            parent: None,
+            signature: self.collapsed(header),
+            parameters: Vec::new(),
+            return_type: None,
+            base_types: Vec::new(),
+            fields: Vec::new(),
+            is_public: false,
+            is_async: false,
+            line_number: self.line_of(span.start),
+            end_line: self.line_of(end),
+            span,
+            name_offset,
+            depth: 0,
+            complexity: 1,
+        }
+    }
+}
+
+/// Replace comments (and, unless `keep_strings`, string literals) with spaces
+///
+/// Newlines and byte offsets are preserved, and multi-byte characters are
+/// blanked whole, so the result is valid UTF-8 of the same length.
+pub fn mask_source(content: &str, language: ProgrammingLanguage, keep_strings: bool) -> String {
+    let bytes = content.as_bytes();
+    let mut out = bytes.to_vec();
+    let blank = |range: Range<usize>, out: &mut Vec<u8>| {
+        for byte in &mut out[range] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    };
+
+    let hash_comments = language == ProgrammingLanguage::Python;
+    let len = bytes.len();
+    let mut i = 0;
+
+    while i < len {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+
+        // Line comments
+        if (!hash_comments && c == b'/' && next == Some(b'/')) || (hash_comments && c == b'#') {
+            let end = content[i..].find('\n').map(|n| i + n).unwrap_or(len);
+            blank(i..end, &mut out);
+            i = end;
+            continue;
+        }
+
+        // Block comments (nested in Rust)
+        if !hash_comments && c == b'/' && next == Some(b'*') {
+            let nested = language == ProgrammingLanguage::Rust;
+            let mut depth = 0;
+            let mut j = i;
+            while j < len {
+                if bytes[j] == b'/' && bytes.get(j + 1) == Some(&b'*') {
+                    depth += 1;
+                    j += 2;
+                } else if bytes[j] == b'*' && bytes.get(j + 1) == Some(&b'/') {
+                    depth -= 1;
+                    j += 2;
+                    if depth == 0 || !nested {
+                        break;
+                    }
+                } else {
+                    j += 1;
+                }
+            }
+            let end = j.min(len);
+            blank(i..end, &mut out);
+            i = end;
+            continue;
+        }
+
+        // Rust raw strings: r"..", r#".."#, br".."
+        if language == ProgrammingLanguage::Rust
+            && (c == b'r' || (c == b'b' && next == Some(b'r')))
+            && (i == 0 || !is_ident_byte(bytes[i - 1]))
+        {
+            let start = if c == b'b' { i + 2 } else { i + 1 };
+            let hashes = bytes[start..].iter().take_while(|&&b| b == b'#').count();
+            if bytes.get(start + hashes) == Some(&b'"') {
+                let terminator = format!("\"{}", "#".repeat(hashes));
+                let body = start + hashes + 1;
+                let end = content[body..].find(&terminator).map(|n| body + n + terminator.len()).unwrap_or(len);
+                if !keep_strings {
+                    blank(i..end, &mut out);
+                }
+                i = end;
+                continue;
+            }
+        }
+
+        // Rust char literals (as opposed to lifetimes)
+        if language == ProgrammingLanguage::Rust && c == b'\'' {
+            let end = if next == Some(b'\\') {
+                content[i + 2..].find('\'').map(|n| i + 2 + n + 1)
+            } else {
+                content[i + 1..].chars().next().and_then(|ch| {
+                    let close = i + 1 + ch.len_utf8();
+                    (bytes.get(close) == Some(&b'\'')).then_some(close + 1)
+                })
+            };
+            if let Some(end) = end {
+                if !keep_strings {
+                    blank(i..end, &mut out);
+                }
+                i = end;
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+
+        // Python triple-quoted strings
+        if hash_comments && (c == b'"' || c == b'\'') && next == Some(c) && bytes.get(i + 2) == Some(&c) {
+            let quote = &content[i..i + 3];
+            let end = content[i + 3..].find(quote).map(|n| i + 3 + n + 3).unwrap_or(len);
+            if !keep_strings {
+                blank(i..end, &mut out);
+            }
+            i = end;
+            continue;
+        }
+
+        // Quoted strings; template literals and Rust strings may span lines
+        let is_quote = c == b'"' || (c == b'\'' && language != ProgrammingLanguage::Rust) || (c == b'`' && !hash_comments);
+        if is_quote {
+            let multiline = c == b'`' || language == ProgrammingLanguage::Rust;
+            let mut j = i + 1;
+            while j < len && bytes[j] != c && (multiline || bytes[j] != b'\n') {
+                j += if bytes[j] == b'\\' { 2 } else { 1 };
+            }
+            let end = (j + 1).min(len);
+            if !keep_strings {
+                blank(i..end, &mut out);
+            }
+            i = end;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    // Only ASCII bytes were replaced, and whole characters at a time
+    String::from_utf8(out).unwrap_or_else(|_| content.to_string())
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+/// Offset of the bracket closing the one at `open`
+fn matching_close(masked: &str, open: usize) -> Option<usize> {
+    let bytes = masked.as_bytes();
+    let (opening, closing) = match bytes.get(open)? {
+        b'(' => (b'(', b')'),
+        b'[' => (b'[', b']'),
+        b'{' => (b'{', b'}'),
+        _ => return None,
+    };
+
+    let mut depth = 0usize;
+    for (i, &byte) in bytes.iter().enumerate().skip(open) {
+        if byte == opening {
+            depth += 1;
+        } else if byte == closing {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// First byte in `targets` at bracket depth 0, scanning from `from`
+///
+/// `<`/`>` count as brackets when `angles` is set, except in `->` and `=>`.
+fn find_at_depth(masked: &str, from: usize, targets: &[u8], angles: bool) -> Option<usize> {
+    let bytes = masked.as_bytes();
+    let mut depth = 0i32;
+    for i in from..bytes.len() {
+        let byte = bytes[i];
+        if depth == 0 && targets.contains(&byte) {
+            return Some(i);
+        }
+        match byte {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'<' if angles => depth += 1,
+            b'>' if angles && i > 0 && bytes[i - 1] != b'-' && bytes[i - 1] != b'=' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return None;
+        }
+    }
+    None
+}
+
+/// Split `text` on `separator` at bracket depth 0, trimming and dropping empty parts
+fn split_top_level(text: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut current = String::new();
+    let mut previous = ' ';
+
+    for c in text.chars() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '>' if previous != '-' && previous != '=' => depth -= 1,
+            _ => {}
+        }
+        if c == separator && depth == 0 {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+        previous = c;
+    }
+    parts.push(current);
+
+    parts
+        .into_iter()
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Parameters between the parentheses at `open`/`close`
+fn parameters(source: &Source, open: usize, close: usize) -> Vec<String> {
+    // Split on the masked text so commas inside string defaults are ignored
+    let masked = &source.masked[open + 1..close];
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0i32;
+    for (i, byte) in masked.bytes().enumerate() {
+        match byte {
+            b'(' | b'[' | b'{' | b'<' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'>' if i == 0 || (masked.as_bytes()[i - 1] != b'-' && masked.as_bytes()[i - 1] != b'=') => depth -= 1,
+            b',' if depth == 0 => {
+                parts.push(open + 1 + start..open + 1 + i);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(open + 1 + start..close);
+
+    parts
+        .into_iter()
+        .map(|range| source.collapsed(range))
+        .filter(|param| !param.is_empty())
+        .collect()
+}
+
+/// Skip a generic parameter list starting at `from`, if any
+fn skip_generics(masked: &str, from: usize) -> usize {
+    let bytes = masked.as_bytes();
+    let mut i = from;
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    if bytes.get(i) != Some(&b'<') {
+        return from;
+    }
+    match find_at_depth(masked, i + 1, b">", true) {
+        Some(close) => close + 1,
+        None => from,
+    }
+}
+
+fn next_non_space(masked: &str, from: usize) -> Option<(usize, u8)> {
+    masked.as_bytes()[from..]
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .map(|i| (from + i, masked.as_bytes()[from + i]))
+}
+
+/// Nest symbols by containment; methods are functions directly inside a type
+fn assign_parents(symbols: &mut [SymbolInfo]) {
+    let spans: Vec<(Range<usize>, String, SymbolKind)> = symbols
+        .iter()
+        .map(|symbol| (symbol.span.clone(), symbol.name.clone(), symbol.kind))
+        .collect();
+
+    for (i, symbol) in symbols.iter_mut().enumerate() {
+        let containers: Vec<usize> = (0..spans.len())
+            .filter(|&j| {
+                j != i
+                    && spans[j].0.start <= symbol.span.start
+                    && symbol.span.end <= spans[j].0.end
+                    && spans[j].0 != symbol.span
+            })
+            .collect();
+        symbol.depth = containers.len();
+
+        // Innermost container is the one that starts last
+        if let Some(&parent) = containers.iter().max_by_key(|&&j| spans[j].0.start) {
+            let (_, parent_name, parent_kind) = &spans[parent];
+            symbol.parent = Some(parent_name.clone());
+            let in_type = matches!(
+                parent_kind,
+                SymbolKind::Impl | SymbolKind::Trait | SymbolKind::Class | SymbolKind::Interface
+            );
+            if symbol.kind == SymbolKind::Function && in_type {
+                symbol.kind = SymbolKind::Method;
+            }
+        }
+    }
+}
+
+fn complexity(masked_body: &str, language: ProgrammingLanguage) -> usize {
+    let keywords: &[&str] = match language {
+        ProgrammingLanguage::Python => &["if", "elif", "for", "while", "except", "and", "or", "case"],
+        ProgrammingLanguage::Rust => &["if", "for", "while", "loop", "match"],
+        _ => &["if", "for", "while", "case", "catch"],
+    };
+    let words = masked_body
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| keywords.contains(word))
+        .count();
+    let operators = if language == ProgrammingLanguage::Python {
+        0
+    } else {
+        masked_body.matches("&&").count() + masked_body.matches("||").count()
+    };
+    1 + words + operators
+}
+
+const CALL_KEYWORDS: &[&str] = &[
+    "if", "while", "for", "match", "return", "loop", "in", "as", "fn", "def", "function", "switch",
+    "catch", "with", "elif", "and", "or", "not", "lambda", "await", "yield", "typeof", "new",
+    "Fn", "FnMut", "FnOnce", "impl", "dyn", "where", "class", "assert", "del", "except", "print!",
+];
+
+/// Call sites: `name(`, `path::name(`, `receiver.name(`
+fn find_calls(source: &Source, declared: &HashSet<usize>) -> Result<Vec<CallSite>> {
+    let call_regex = Regex::new(r"([A-Za-z_$][\w$]*(?:\s*(?:::|\.)\s*[A-Za-z_$][\w$]*)*)\s*(?:::\s*<[^()]*?>\s*)?\(")?;
+    let masked = &source.masked;
+    let mut calls = Vec::new();
+
+    for captures in call_regex.captures_iter(masked) {
+        let path_match = captures.get(1).expect("group 1 always participates");
+        let start = path_match.start();
+        let path: String = path_match.as_str().chars().filter(|c| !c.is_whitespace()).collect();
+
+        // `foo.bar(` directly after another expression, e.g. `x().bar(`
+        let after_dot = masked[..start].trim_end().ends_with('.');
+        let (qualifier, name) = match path.rfind(['.', ':']) {
+            Some(i) => {
+                let qualifier = path[..i].trim_end_matches(':').to_string();
+                (Some(qualifier), path[i + 1..].to_string())
+            }
+            None => (None, path.clone()),
+        };
+
+        let name_offset = start + path_match.as_str().rfind(name.as_str()).unwrap_or(0);
+        if CALL_KEYWORDS.contains(&name.as_str()) || declared.contains(&name_offset) {
+            continue;
+        }
+        // Skip declarations this parser does not record, e.g. closures' `|x|` or `fn(` types
+        let preceding = masked[..start].split_whitespace().next_back().unwrap_or("");
+        if matches!(preceding, "fn" | "def" | "function" | "class" | "struct" | "enum" | "trait" | "interface") {
+            continue;
+        }
+
+        let open = captures.get(0).expect("match").end() - 1;
+        calls.push(CallSite {
+            name,
+            qualifier,
+            is_method: after_dot || path.contains('.'),
+            line_number: source.line_of(start),
+            argument_count: matching_close(masked, open)
+                .map(|close| argument_count(&masked[open + 1..close], &source.text[open + 1..close])),
+        });
+    }
+
+    Ok(calls)
+}
+
+/// Number of top-level arguments in an argument list (trailing comma allowed)
+///
+/// Commas are counted in the masked text; the original decides whether the
+/// list is empty, since a lone string argument is blank once masked.
+fn argument_count(masked: &str, original: &str) -> usize {
+    if original.trim().is_empty() {
+        return 0;
+    }
+    let arguments = masked.trim_end().trim_end_matches(',');
+    let mut depth = 0i32;
+    let mut count = 1;
+    for byte in arguments.bytes() {
+        match byte {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+// ============================================================================
+// Rust
+// ============================================================================
+
+fn parse_rust(source: &Source) -> Result<SyntaxOutline> {
+    let item_regex = Regex::new(
+        r"^\s*((?:pub(?:\s*\([^)]*\))?\s+)?)((?:(?:default|const|async|unsafe|extern)\s+)*)(fn|struct|enum|trait|impl|union|type|mod)\b"
+    )?;
+    let use_regex = Regex::new(r"^\s*(?:pub(?:\s*\([^)]*\))?\s+)?use\s+")?;
+    let name_regex = Regex::new(r"^\s*([A-Za-z_]\w*)")?;
+    let separator_regex = Regex::new(r"\s*(::|\{|\})\s*")?;
+    let masked = source.masked.as_str();
+    let mut outline = SyntaxOutline::default();
+
+    for (line_start, line) in source.masked_lines() {
+        if let Some(m) = use_regex.find(line) {
+            let start = line_start + m.start() + line[m.start()..].find("use").unwrap_or(0);
+            let end = masked[start..].find(';').map(|i| start + i).unwrap_or(masked.len());
+            let path = separator_regex.replace_all(&source.collapsed(start + 3..end), "$1").into_owned();
+            outline.imports.push(rust_import(&path, source.line_of(start)));
+            continue;
+        }
+
+        let Some(captures) = item_regex.captures(line) else {
+            continue;
+        };
+        let whole = captures.get(0).expect("match");
+        let item_start = line_start + whole.start() + (line.len() - line.trim_start().len());
+        let keyword = &captures[3];
+        let after_keyword = line_start + whole.end();
+        let is_public = !captures[1].trim().is_empty();
+        let is_async = captures[2].contains("async");
+
+        if keyword == "impl" {
+            let header_start = skip_generics(masked, after_keyword);
+            let Some(open) = find_at_depth(masked, header_start, b"{;", true) else {
+                continue;
+            };
+            let header = source.collapsed(header_start..open);
+            let (name, base) = match header.split_once(" for ") {
+                Some((trait_name, type_name)) => (type_name.to_string(), Some(trait_name.to_string())),
+                None => (header.clone(), None),
+            };
+            let name = rust_type_name(&name);
+            let end = if masked.as_bytes()[open] == b'{' {
+                matching_close(masked, open).map(|c| c + 1).unwrap_or(masked.len())
+            } else {
+                open + 1
+            };
+            let name_offset = header_start + masked[header_start..open].find(name.as_str()).unwrap_or(0);
+            let mut symbol = source.symbol(&name, SymbolKind::Impl, name_offset, item_start..open, item_start..end);
+            symbol.base_types = base.map(|b| vec![rust_type_name(&b)]).unwrap_or_default();
+            outline.symbols.push(symbol);
+            continue;
+        }
+
+        let Some(name_captures) = name_regex.captures(&masked[after_keyword..]) else {
+            continue;
+        };
+        let name_match = name_captures.get(1).expect("group 1");
+        let name = name_match.as_str().to_string();
+        let name_offset = after_keyword + name_match.start();
+        let name_end = after_keyword + name_match.end();
+
+        let kind = match keyword {
+            "fn" => SymbolKind::Function,
+            "struct" | "union" => SymbolKind::Struct,
+            "enum" => SymbolKind::Enum,
+            "trait" => SymbolKind::Trait,
+            "type" => SymbolKind::TypeAlias,
+            _ => SymbolKind::Module,
+        };
+
+        if kind == SymbolKind::Function {
+            let params_from = skip_generics(masked, name_end);
+            let Some(open_paren) = find_at_depth(masked, params_from, b"(", false) else {
+                continue;
+            };
+            let Some(close_paren) = matching_close(masked, open_paren) else {
+                continue;
+            };
+            let Some(body) = find_at_depth(masked, close_paren + 1, b"{;", true) else {
+                continue;
+            };
+            let end = if masked.as_bytes()[body] == b'{' {
+                matching_close(masked, body).map(|c| c + 1).unwrap_or(masked.len())
+            } else {
+                body + 1
+            };
+
+            let mut symbol = source.symbol(&name, kind, name_offset, item_start..body, item_start..end);
+            symbol.parameters = parameters(source, open_paren, close_paren);
+            let tail = &masked[close_paren + 1..body];
+            if let Some(arrow) = tail.find("->") {
+                let return_end = tail.find(" where ").filter(|&w| w > arrow).unwrap_or(tail.len());
+                let start = close_paren + 1 + arrow + 2;
+                let return_type = source.collapsed(start..close_paren + 1 + return_end);
+                symbol.return_type = (!return_type.is_empty()).then_some(return_type);
+            }
+            symbol.is_public = is_public;
+            symbol.is_async = is_async;
+            outline.symbols.push(symbol);
+            continue;
+        }
+
+        let header_from = skip_generics(masked, name_end);
+        let terminators: &[u8] = if kind == SymbolKind::TypeAlias { b";" } else { b"{;(" };
+        let Some(open) = find_at_depth(masked, header_from, terminators, true) else {
+            continue;
+        };
+        let end = match masked.as_bytes()[open] {
+            b'{' => matching_close(masked, open).map(|c| c + 1).unwrap_or(masked.len()),
+            b'(' => {
+                // Tuple struct: ends at the `;` after the field list
+                let close = matching_close(masked, open).unwrap_or(open);
+                find_at_depth(masked, close + 1, b";", true).map(|s| s + 1).unwrap_or(close + 1)
+            }
+            _ => open + 1,
+        };
+
+        let mut symbol = source.symbol(&name, kind, name_offset, item_start..open, item_start..end);
+        symbol.is_public = is_public;
+        if matches!(kind, SymbolKind::Struct | SymbolKind::Enum) {
+            symbol.fields = rust_fields(source, open, end, kind);
+        }
+        outline.symbols.push(symbol);
+    }
+
+    Ok(outline)
+}
+
+/// `a::b::Type<T>` -> `Type`
+fn rust_type_name(path: &str) -> String {
+    let without_generics = path.split('<').next().unwrap_or(path).trim();
+    let without_refs = without_generics.trim_start_matches(['&', '*']).trim_start_matches("mut ").trim();
+    without_refs.rsplit("::").next().unwrap_or(without_refs).trim().to_string()
+}
+
+fn rust_import(path: &str, line_number: usize) -> ImportInfo {
+    let (module, items) = match path.find('{') {
+        Some(brace) => {
+            let module = path[..brace].trim_end_matches("::").to_string();
+            let inner = path[brace + 1..].trim_end_matches('}');
+            let items = split_top_level(inner, ',')
+                .into_iter()
+                .flat_map(|item| {
+                    // Nested groups (`a::{b, c}`) contribute their leaves
+                    match item.find('{') {
+                        Some(b) => split_top_level(item[b + 1..].trim_end_matches('}'), ',')
+                            .into_iter()
+                            .map(|leaf| import_leaf(&leaf, &module))
+                            .collect::<Vec<_>>(),
+                        None => vec![import_leaf(&item, &module)],
+                    }
+                })
+                .collect();
+            (module, items)
+        }
+        None => {
+            let module = path.split(" as ").next().unwrap_or(path).to_string();
+            let item = import_leaf(path, &module);
+            (module, vec![item])
+        }
+    };
+
+    ImportInfo {
+        is_wildcard: path.contains('*'),
+        module,
+        items,
+        line_number,
+    }
+}
+
+/// Name an import binds locally
+fn import_leaf(item: &str, module: &str) -> String {
+    if let Some((_, alias)) = item.split_once(" as ") {
+        return alias.trim().to_string();
+    }
+    let leaf = item.rsplit("::").next().unwrap_or(item).trim();
+    if leaf == "self" {
+        module.rsplit("::").next().unwrap_or(module).to_string()
+    } else {
+        leaf.to_string()
+    }
+}
+
+fn rust_fields(source: &Source, open: usize, end: usize, kind: SymbolKind) -> Vec<FieldInfo> {
+    let masked = &source.masked;
+    let bytes = masked.as_bytes();
+    if open >= end || !matches!(bytes[open], b'{' | b'(') {
+        return Vec::new();
+    }
+    let close = matching_close(masked, open).unwrap_or(end.saturating_sub(1));
+    let tuple = bytes[open] == b'(';
+    let attribute = Regex::new(r"#\s*\[[^\]]*\]").expect("valid regex");
+    let body = attribute.replace_all(&source.text[open + 1..close], "");
+
+    split_top_level(&body, ',')
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, field)| {
+            let is_public = field.starts_with("pub");
+            let field = field.trim_start_matches("pub").trim();
+            let field = if field.starts_with('(') {
+                field.split_once(')').map(|(_, rest)| rest.trim()).unwrap_or(field)
+            } else {
+                field
+            };
+            if tuple {
+                return Some(FieldInfo { name: index.to_string(), type_name: Some(field.to_string()), is_public });
+            }
+            if kind == SymbolKind::Enum {
+                let name: String = field.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                return (!name.is_empty()).then_some(FieldInfo { name, type_name: None, is_public: true });
+            }
+            let (name, type_name) = field.split_once(':')?;
+            Some(FieldInfo {
+                name: name.trim().to_string(),
+                type_name: Some(type_name.trim().to_string()),
+                is_public,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Python
+// ============================================================================
+
+fn parse_python(source: &Source) -> Result<SyntaxOutline> {
+    let def_regex = Regex::new(r"^(\s*)(async\s+)?def\s+([A-Za-z_]\w*)")?;
+    let class_regex = Regex::new(r"^(\s*)class\s+([A-Za-z_]\w*)")?;
+    let import_regex = Regex::new(r"^\s*import\s+(.+)$")?;
+    let from_regex = Regex::new(r"^\s*from\s+(\S+)\s+import\s+(.+)$")?;
+    let masked = source.masked.as_str();
+    let lines: Vec<(usize, &str)> = source.masked_lines().collect();
+    let mut outline = SyntaxOutline::default();
+
+    for (index, &(line_start, line)) in lines.iter().enumerate() {
+        let line_number = index + 1;
+
+        if let Some(captures) = from_regex.captures(line) {
+            let module = captures[1].to_string();
+            let names_start = line_start + captures.get(2).expect("group 2").start();
+            let names_end = match masked.as_bytes()[names_start] {
+                b'(' => matching_close(masked, names_start).unwrap_or(masked.len()),
+                _ => line_start + line.len(),
+            };
+            let names = source.collapsed(names_start..names_end);
+            let items: Vec<String> = split_top_level(names.trim_start_matches('(').trim_end_matches(')'), ',')
+                .into_iter()
+                .map(|item| item.rsplit(" as ").next().unwrap_or(&item).trim().to_string())
+                .collect();
+            outline.imports.push(ImportInfo {
+                is_wildcard: items.iter().any(|item| item == "*"),
+                module,
+                items,
+                line_number,
+            });
+            continue;
+        }
+
+        if let Some(captures) = import_regex.captures(line) {
+            for module in split_top_level(&captures[1], ',') {
+                let (path, alias) = match module.split_once(" as ") {
+                    Some((path, alias)) => (path.trim().to_string(), alias.trim().to_string()),
+                    None => (module.clone(), module.split('.').next().unwrap_or(&module).to_string()),
+                };
+                outline.imports.push(ImportInfo {
+                    module: path,
+                    items: vec![alias],
+                    is_wildcard: false,
+                    line_number,
+                });
+            }
+            continue;
+        }
+
+        let (indent, name_match, kind, is_async) = if let Some(c) = def_regex.captures(line) {
+            (c[1].len(), c.get(3).expect("group 3"), SymbolKind::Function, c.get(2).is_some())
+        } else if let Some(c) = class_regex.captures(line) {
+            (c[1].len(), c.get(2).expect("group 2"), SymbolKind::Class, false)
+        } else {
+            continue;
+        };
+
+        let name = name_match.as_str().to_string();
+        let name_offset = line_start + name_match.start();
+        let name_end = line_start + name_match.end();
+        let item_start = line_start + indent;
+
+        // The header ends at the `:` that closes the (possibly multi-line) signature
+        let Some(colon) = find_at_depth(masked, name_end, b":", false) else {
+            continue;
+        };
+        let end = python_block_end(source, &lines, colon, indent);
+        let mut symbol = source.symbol(&name, kind, name_offset, item_start..colon, item_start..end);
+        symbol.is_public = !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__"));
+        symbol.is_async = is_async;
+
+        let paren = next_non_space(masked, name_end).filter(|&(_, b)| b == b'(').map(|(i, _)| i);
+        if let Some(open) = paren.filter(|&open| open < colon) {
+            if let Some(close) = matching_close(masked, open) {
+                let params = parameters(source, open, close);
+                if kind == SymbolKind::Class {
+                    symbol.base_types = params;
+                } else {
+                    symbol.parameters = params;
+                    if let Some(arrow) = masked[close..colon].find("->") {
+                        let return_type = source.collapsed(close + arrow + 2..colon);
+                        symbol.return_type = (!return_type.is_empty()).then_some(return_type);
+                    }
+                }
+            }
+        }
+        outline.symbols.push(symbol);
+    }
+
+    Ok(outline)
+}
+
+/// End offset of the indented block whose header ends with the `:` at `colon`
+fn python_block_end(source: &Source, lines: &[(usize, &str)], colon: usize, indent: usize) -> usize {
+    let header_line = source.line_of(colon) - 1;
+    let (header_start, header) = lines[header_line];
+
+    // One-line body: `def f(): return 1`
+    if !header[colon - header_start + 1..].trim().is_empty() {
+        return header_start + header.len();
+    }
+
+    let mut end = header_start + header.len();
+    for &(start, line) in &lines[header_line + 1..] {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_indent = line.len() - line.trim_start().len();
+        if line_indent <= indent {
+            break;
+        }
+        end = start + line.trim_end().len();
+    }
+    end
+}
+
+// ============================================================================
+// TypeScript / JavaScript
+// ============================================================================
+
+fn parse_typescript(source: &Source) -> Result<SyntaxOutline> {
+    let function_regex = Regex::new(
+        r"^\s*((?:export\s+)?(?:default\s+)?(?:declare\s+)?)(async\s+)?function\s*\*?\s*([A-Za-z_$][\w$]*)"
+    )?;
+    let arrow_regex = Regex::new(
+        r"^\s*(export\s+)?(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*(?::[^=]*)?=\s*(async\s+)?(?:function\b|\(|[A-Za-z_$][\w$]*\s*=>)"
+    )?;
+    let type_regex = Regex::new(
+        r"^\s*(export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(class|interface|enum|type)\s+([A-Za-z_$][\w$]*)"
+    )?;
+    let method_regex = Regex::new(
+        r"^\s*((?:(?:public|private|protected|static|async|readonly|override|abstract|get|set)\s+)*)(#?[A-Za-z_$][\w$]*)\s*(?:<[^>]*>)?\s*\("
+    )?;
+    let masked = source.masked.as_str();
+    let mut outline = SyntaxOutline::default();
+    let mut class_bodies: Vec<Range<usize>> = Vec::new();
+
+    for (line_start, line) in source.masked_lines() {
+        let item_start = line_start + (line.len() - line.trim_start().len());
+
+        if let Some(captures) = type_regex.captures(line) {
+            let keyword = &captures[2];
+            let name_match = captures.get(3).expect("group 3");
+            let name = name_match.as_str();
+            let name_end = line_start + name_match.end();
+            let kind = match keyword {
+                "class" => SymbolKind::Class,
+                "interface" => SymbolKind::Interface,
+                "enum" => SymbolKind::Enum,
+                _ => SymbolKind::TypeAlias,
+            };
+
+            let terminators: &[u8] = if kind == SymbolKind::TypeAlias { b";\n" } else { b"{" };
+            let Some(open) = find_at_depth(masked, skip_generics(masked, name_end), terminators, true) else {
+                continue;
+            };
+            let end = if masked.as_bytes()[open] == b'{' {
+                matching_close(masked, open).map(|c| c + 1).unwrap_or(masked.len())
+            } else {
+                open + 1
+            };
+
+            let mut symbol = source.symbol(name, kind, line_start + name_match.start(), item_start..open, item_start..end);
+            symbol.is_public = captures.get(1).is_some();
+            symbol.base_types = typescript_heritage(&source.collapsed(name_end..open));
+            if matches!(kind, SymbolKind::Interface | SymbolKind::Enum) && masked.as_bytes()[open] == b'{' {
+                symbol.fields = typescript_members(source, open, end - 1, kind);
+            }
+            if kind == SymbolKind::Class {
+                class_bodies.push(open + 1..end - 1);
+            }
+            outline.symbols.push(symbol);
+            continue;
+        }
+
+        let (name_match, is_public, is_async) = if let Some(c) = function_regex.captures(line) {
+            (c.get(3).expect("group 3"), c[1].contains("export"), c.get(2).is_some())
+        } else if let Some(c) = arrow_regex.captures(line) {
+            (c.get(2).expect("group 2"), c.get(1).is_some(), c.get(3).is_some())
+        } else if let Some(c) = method_regex.captures(line) {
+            let offset = line_start + c.get(2).expect("group 2").start();
+            // Directly in a class body, not in a nested block such as a method body
+            let in_class = class_bodies.iter().any(|body| {
+                let prefix = &masked[body.start..offset.max(body.start)];
+                body.contains(&offset) && prefix.matches('{').count() == prefix.matches('}').count()
+            });
+            let name = &c[2];
+            if !in_class || CALL_KEYWORDS.contains(&name) {
+                continue;
+            }
+            let modifiers = &c[1];
+            let is_public = !(modifiers.contains("private") || modifiers.contains("protected") || name.starts_with('#'));
+            (c.get(2).expect("group 2"), is_public, modifiers.contains("async"))
+        } else {
+            continue;
+        };
+
+        let name = name_match.as_str();
+        let name_offset = line_start + name_match.start();
+        let Some(open_paren) = find_at_depth(masked, skip_generics(masked, line_start + name_match.end()), b"(", false) else {
+            continue;
+        };
+        let Some(close_paren) = matching_close(masked, open_paren) else {
+            continue;
+        };
+
+        // Body: `{ ... }`, `=> expr`, or `;` for overloads and declarations
+        let Some((body_start, body_byte)) = body_start(masked, close_paren + 1) else {
+            continue;
+        };
+        let end = match body_byte {
+            b'{' => matching_close(masked, body_start).map(|c| c + 1).unwrap_or(masked.len()),
+            _ => find_at_depth(masked, body_start, b";\n", false).map(|e| e + 1).unwrap_or(masked.len()),
+        };
+
+        let mut symbol = source.symbol(name, SymbolKind::Function, name_offset, item_start..body_start, item_start..end);
+        symbol.parameters = parameters(source, open_paren, close_paren);
+        if let Some((colon, b':')) = next_non_space(masked, close_paren + 1) {
+            let return_end = masked[colon..body_start].rfind("=>").map(|a| colon + a).unwrap_or(body_start);
+            let return_type = source.collapsed(colon + 1..return_end);
+            symbol.return_type = (!return_type.is_empty()).then_some(return_type);
+        }
+        symbol.is_public = is_public;
+        symbol.is_async = is_async;
+        outline.symbols.push(symbol);
+    }
+
+    outline.imports = typescript_imports(source)?;
+    Ok(outline)
+}
+
+/// Types after `extends` and `implements` in a declaration header
+fn typescript_heritage(header: &str) -> Vec<String> {
+    let mut base_types = Vec::new();
+    for (clause, next) in [(" extends ", " implements "), (" implements ", " extends ")] {
+        if let Some((_, rest)) = format!(" {}", header).split_once(clause) {
+            let list = rest.split(next).next().unwrap_or(rest);
+            base_types.extend(split_top_level(list, ','));
+        }
+    }
+    base_types
+}
+
+/// Start of a function body after its parameter list: `{`, the expression after `=>`, or `;`
+fn body_start(masked: &str, from: usize) -> Option<(usize, u8)> {
+    let bytes = masked.as_bytes();
+    let mut i = from;
+    let mut depth = 0i32;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if depth == 0 {
+            if byte == b'=' && bytes.get(i + 1) == Some(&b'>') {
+                return next_non_space(masked, i + 2);
+            }
+            if byte == b'{' && !masked[from..i].trim_end().ends_with(':') {
+                return Some((i, byte));
+            }
+            if byte == b';' {
+                return Some((i, byte));
+            }
+        }
+        match byte {
+            b'(' | b'[' | b'{' | b'<' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b'>' if bytes[i - 1] != b'=' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn typescript_members(source: &Source, open: usize, close: usize, kind: SymbolKind) -> Vec<FieldInfo> {
+    let body = source.masked[open + 1..close].replace([';', '\n'], ",");
+    split_top_level(&body, ',')
+        .into_iter()
+        .filter_map(|member| {
+            if kind == SymbolKind::Enum {
+                let name = member.split('=').next()?.trim().to_string();
+                return (!name.is_empty()).then_some(FieldInfo { name, type_name: None, is_public: true });
+            }
+            let (name, type_name) = member.split_once(':')?;
+            let name = name.trim().trim_start_matches("readonly ").trim_end_matches('?').trim();
+            Some(FieldInfo {
+                name: name.to_string(),
+                type_name: Some(type_name.trim().to_string()),
+                is_public: true,
+            })
+        })
+        .collect()
+}
+
+fn typescript_imports(source: &Source) -> Result<Vec<ImportInfo>> {
+    // Module specifiers are string literals, so read them with comments masked only
+    let text = mask_source(source.text, source.language, true);
+    let from_regex = Regex::new(r#"(?m)^\s*import\s+(?:type\s+)?([^;]*?)\s*from\s*['"]([^'"]+)['"]"#)?;
+    let bare_regex = Regex::new(r#"(?m)^\s*import\s*['"]([^'"]+)['"]"#)?;
+    let require_regex = Regex::new(r#"require\(\s*['"]([^'"]+)['"]\s*\)"#)?;
+    let mut imports = Vec::new();
+
+    for captures in from_regex.captures_iter(&text) {
+        let clause = captures[1].split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut items = Vec::new();
+        let mut is_wildcard = false;
+        for part in split_top_level(&clause, ',') {
+            if let Some(named) = part.strip_prefix('{') {
+                items.extend(
+                    split_top_level(named.trim_end_matches('}'), ',')
+                        .into_iter()
+                        .map(|item| item.rsplit(" as ").next().unwrap_or(&item).trim_start_matches("type ").to_string()),
+                );
+            } else if let Some(namespace) = part.strip_prefix("* as ") {
+                is_wildcard = true;
+                items.push(namespace.trim().to_string());
+            } else {
+                items.push(part);
+            }
+        }
+        imports.push(ImportInfo {
+            module: captures[2].to_string(),
+            items,
+            is_wildcard,
+            line_number: source.line_of(captures.get(0).expect("match").start()),
+        });
+    }
+
+    for regex in [&bare_regex, &require_regex] {
+        for captures in regex.captures_iter(&text) {
+            imports.push(ImportInfo {
+                module: captures[1].to_string(),
+                items: Vec::new(),
+                is_wildcard: false,
+                line_number: source.line_of(captures.get(0).expect("match").start()),
+            });
+        }
+    }
+
+    imports.sort_by_key(|import| import.line_number);
+    Ok(imports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Outline tests run against [`scan`] and, with the feature, the tree-sitter backend
+    fn backends() -> Vec<fn(&str, ProgrammingLanguage) -> Result<SyntaxOutline>> {
+        let backends: &[fn(&str, ProgrammingLanguage) -> Result<SyntaxOutline>] = &[
+            scan,
+            #[cfg(feature = "tree-sitter")]
+            parse,
+        ];
+        backends.to_vec()
+    }
+
+    #[test]
+    fn test_masking_hides_comments_and_strings() {
+        let code = "let s = \"a { b\"; // trailing {\nlet c = '{'; /* block\n { */ fn f<'a>() {}";
+        let masked = mask_source(code, ProgrammingLanguage::Rust, false);
+        assert_eq!(masked.len(), code.len());
+        assert_eq!(masked.matches('{').count(), 1);
+        assert!(masked.contains("fn f<'a>() {}"));
+        assert_eq!(masked.lines().count(), code.lines().count());
+    }
+
+    #[test]
+    fn test_rust_outline() {
+        let code = r#"use std::collections::{HashMap, hash_map::Entry};
+use crate::types::CodexId as Id;
+
+/// A point
+pub struct Point {
+    pub x: f64,
+    y: f64,
+}
+
+impl Point {
+    pub fn distance(&self, other: &Point) -> f64 {
+        let text = "fn fake() {";
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+pub async fn load<T: Into<String>>(path: T, retries: u32) -> Result<Point, Error> {
+    if retries > 0 { helper::fetch(path.into()) } else { Point::origin() }
+}
+"#;
+        for parse in backends() {
+            let outline = parse(code, ProgrammingLanguage::Rust).unwrap();
+
+            assert_eq!(outline.imports.len(), 2);
+            assert_eq!(outline.imports[0].module, "std::collections");
+            assert_eq!(outline.imports[0].items, vec!["HashMap", "Entry"]);
+            assert_eq!(outline.imports[1].items, vec!["Id"]);
+
+            let names: Vec<(&str, SymbolKind)> = outline.symbols.iter().map(|s| (s.name.as_str(), s.kind)).collect();
+            assert_eq!(names, vec![
+                ("Point", SymbolKind::Struct),
+                ("Point", SymbolKind::Impl),
+                ("distance", SymbolKind::Method),
+                ("load", SymbolKind::Function),
+            ]);
+
+            let point = &outline.symbols[0];
+            assert_eq!((point.line_number, point.end_line), (5, 8));
+            assert_eq!(point.fields.len(), 2);
+            assert!(point.fields[0].is_public && !point.fields[1].is_public);
+
+            let distance = &outline.symbols[2];
+            assert_eq!(distance.parent.as_deref(), Some("Point"));
+            assert_eq!(distance.parameters, vec!["&self", "other: &Point"]);
+            assert_eq!(distance.return_type.as_deref(), Some("f64"));
+            assert_eq!((distance.line_number, distance.end_line), (11, 14));
+
+            let load = &outline.symbols[3];
+            assert!(load.is_async && load.is_public);
+            assert_eq!(load.parameters, vec!["path: T", "retries: u32"]);
+            assert_eq!(load.return_type.as_deref(), Some("Result<Point, Error>"));
+            assert_eq!(load.complexity, 2);
+
+            let calls: Vec<(Option<&str>, &str)> = outline.calls.iter().map(|c| (c.qualifier.as_deref(), c.name.as_str())).collect();
+            assert!(calls.contains(&(Some("helper"), "fetch")));
+            assert!(calls.contains(&(Some("Point"), "origin")));
+            assert!(!calls.iter().any(|(_, name)| *name == "fake" || *name == "load" || *name == "distance"));
+        }
+    }
+
+    #[test]
+    fn test_python_outline() {
+        let code = r#"import os.path as osp, sys
+from typing import (
+    List,
+    Optional as Opt,
+)
+
+class Store(Base, metaclass=Meta):
+    """Docstring with def fake():"""
+
+    def __init__(self, root: str = "a, b"):
+        self.root = osp.join(root, "data")
+
+    async def load(self, key) -> Opt[str]:
+        if key and self.root:
+            return read(key)
+
+
+def helper(): return 1
+"#;
+        for parse in backends() {
+            let outline = parse(code, ProgrammingLanguage::Python).unwrap();
+
+            assert_eq!(outline.imports.len(), 3);
+            assert_eq!(outline.imports[0].module, "os.path");
+            assert_eq!(outline.imports[0].items, vec!["osp"]);
+            assert_eq!(outline.imports[2].items, vec!["List", "Opt"]);
+
+            let store = &outline.symbols[0];
+            assert_eq!((store.kind, store.line_number, store.end_line), (SymbolKind::Class, 7, 15));
+            assert_eq!(store.base_types, vec!["Base", "metaclass=Meta"]);
+
+            let init = &outline.symbols[1];
+            assert_eq!((init.kind, init.parent.as_deref()), (SymbolKind::Method, Some("Store")));
+            assert_eq!(init.parameters, vec!["self", "root: str = \"a, b\""]);
+
+            let load = &outline.symbols[2];
+            assert!(load.is_async);
+            assert_eq!(load.return_type.as_deref(), Some("Opt[str]"));
+            assert_eq!(load.complexity, 3);
+
+            let helper = &outline.symbols[3];
+            assert_eq!((helper.line_number, helper.end_line, helper.depth), (18, 18, 0));
+
+            assert!(outline.calls.iter().any(|c| c.name == "join" && c.qualifier.as_deref() == Some("osp")));
+            assert!(outline.calls.iter().any(|c| c.name == "read" && c.qualifier.is_none()));
+            assert!(!outline.calls.iter().any(|c| c.name == "fake"));
+        }
+    }
+
+    #[test]
+    fn test_typescript_outline() {
+        let code = r#"import { readFile, writeFile as write } from 'fs/promises';
+import * as path from "path";
+import './polyfill';
+
+export interface Options {
+  root: string;
+  depth?: number;
+}
+
+export class Indexer extends Base implements Disposable {
+  private cache = new Map<string, number>();
+
+  constructor(private readonly options: Options) {
+    super();
+  }
+
+  async index(file: string): Promise<void> {
+    const text = await readFile(path.join(this.options.root, file), 'utf8');
+    this.store(text);
+  }
+}
+
+export const tokenize = (text: string): string[] => text.split(/\s+/);
+
+function internal<T>(value: T): T {
+  return value;
+}
+"#;
+        for parse in backends() {
+            let outline = parse(code, ProgrammingLanguage::TypeScript).unwrap();
+
+            let modules: Vec<&str> = outline.imports.iter().map(|i| i.module.as_str()).collect();
+            assert_eq!(modules, vec!["fs/promises", "path", "./polyfill"]);
+            assert_eq!(outline.imports[0].items, vec!["readFile", "write"]);
+            assert!(outline.imports[1].is_wildcard);
+
+            let names: Vec<(&str, SymbolKind)> = outline.symbols.iter().map(|s| (s.name.as_str(), s.kind)).collect();
+            assert_eq!(names, vec![
+                ("Options", SymbolKind::Interface),
+                ("Indexer", SymbolKind::Class),
+                ("constructor", SymbolKind::Method),
+                ("index", SymbolKind::Method),
+                ("tokenize", SymbolKind::Function),
+                ("internal", SymbolKind::Function),
+            ]);
+
+            let options = &outline.symbols[0];
+            assert_eq!(options.fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["root", "depth"]);
+
+            let indexer = &outline.symbols[1];
+            assert_eq!(indexer.base_types, vec!["Base", "Disposable"]);
+            assert_eq!((indexer.line_number, indexer.end_line), (10, 21));
+
+            let index = &outline.symbols[3];
+            assert!(index.is_async);
+            assert_eq!(index.return_type.as_deref(), Some("Promise<void>"));
+
+            let tokenize = &outline.symbols[4];
+            assert!(tokenize.is_public);
+            assert_eq!(tokenize.end_line, 23);
+            assert_eq!(outline.symbols[5].return_type.as_deref(), Some("T"));
+
+            assert!(outline.calls.iter().any(|c| c.name == "readFile"));
+            assert!(outline.calls.iter().any(|c| c.name == "store" && c.is_method));
+        }
+    }
+}
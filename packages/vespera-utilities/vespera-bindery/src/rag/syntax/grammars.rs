@@ -0,0 +1,689 @@
+//! tree-sitter backend for [`parse`](super::parse)
+//!
+//! Declarations, imports and calls are read off the syntax trees built by the
+//! tree-sitter grammars for Rust, Python, TypeScript and JavaScript, so their
+//! extents are exact whatever the formatting. Macro bodies are token trees to
+//! the Rust grammar; calls inside them are picked out of the tokens.
+
+use anyhow::{anyhow, Result};
+use tree_sitter::{Language, Node, Parser};
+
+use super::{rust_type_name, typescript_heritage, Source, SyntaxOutline};
+use crate::rag::code_analyzer::{CallSite, FieldInfo, ImportInfo, ProgrammingLanguage, SymbolInfo, SymbolKind};
+
+/// Outline `source`; languages without a grammar yield an empty outline
+pub(super) fn parse(source: &Source) -> Result<SyntaxOutline> {
+    let grammar: Language = match source.language {
+        ProgrammingLanguage::Rust => tree_sitter_rust::LANGUAGE.into(),
+        ProgrammingLanguage::Python => tree_sitter_python::LANGUAGE.into(),
+        ProgrammingLanguage::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        ProgrammingLanguage::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+        _ => return Ok(SyntaxOutline::default()),
+    };
+    let mut parser = Parser::new();
+    parser.set_language(&grammar)?;
+    let tree = parser
+        .parse(source.text, None)
+        .ok_or_else(|| anyhow!("tree-sitter gave up parsing {:?} source", source.language))?;
+
+    // Pre-order walk, so imports and calls come out in source order
+    let mut outline = SyntaxOutline::default();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        match source.language {
+            ProgrammingLanguage::Rust => rust_node(source, node, &mut outline),
+            ProgrammingLanguage::Python => python_node(source, node, &mut outline),
+            _ => typescript_node(source, node, &mut outline),
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    Ok(outline)
+}
+
+fn text<'a>(source: &Source<'a>, node: Node) -> &'a str {
+    &source.text[node.byte_range()]
+}
+
+fn field<'t>(node: Node<'t>, name: &str) -> Option<Node<'t>> {
+    node.child_by_field_name(name)
+}
+
+fn child_of_kind<'t>(node: Node<'t>, kind: &str) -> Option<Node<'t>> {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).find(|child| child.kind() == kind);
+    found
+}
+
+/// Named children other than comments and attributes
+fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|child| !child.kind().ends_with("comment") && child.kind() != "attribute_item")
+        .collect()
+}
+
+/// End of a declaration's header: the start of its body, or its end less any `;`
+fn header_end(node: Node, body: Option<Node>) -> usize {
+    match body {
+        Some(body) => body.start_byte(),
+        None => match node.child(node.child_count().saturating_sub(1)) {
+            Some(last) if last.kind() == ";" => last.start_byte(),
+            _ => node.end_byte(),
+        },
+    }
+}
+
+fn parameters(source: &Source, list: Option<Node>) -> Vec<String> {
+    list.map(|list| {
+        named_children(list)
+            .into_iter()
+            .map(|param| source.collapsed(param.byte_range()))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Type text, without the `:` that TypeScript annotations start with
+fn type_text(source: &Source, node: Option<Node>) -> Option<String> {
+    let node = node?;
+    let collapsed = source.collapsed(node.byte_range());
+    let type_text = collapsed.trim_start_matches(':').trim();
+    (!type_text.is_empty()).then(|| type_text.to_string())
+}
+
+/// Receiver or path a call is made through, if it is a plain path
+fn is_path(node: Node) -> bool {
+    match node.kind() {
+        "identifier" | "self" | "this" | "super" | "crate" | "scoped_identifier" => true,
+        "field_expression" => field(node, "value").is_some_and(is_path),
+        "attribute" | "member_expression" => field(node, "object").is_some_and(is_path),
+        _ => false,
+    }
+}
+
+fn qualifier(source: &Source, node: Option<Node>) -> Option<String> {
+    node.filter(|node| is_path(*node))
+        .map(|node| text(source, node).chars().filter(|c| !c.is_whitespace()).collect())
+}
+
+fn call(source: &Source, node: Node, name: Node, qualifier: Option<String>, is_method: bool) -> CallSite {
+    let arguments = field(node, "arguments");
+    CallSite {
+        name: text(source, name).to_string(),
+        qualifier,
+        is_method,
+        line_number: source.line_of(node.start_byte()),
+        argument_count: arguments.map(|arguments| match arguments.kind() {
+            "arguments" | "argument_list" => named_children(arguments).len(),
+            // Bare generator (`f(x for x in y)`) or template (`` tag`...` ``)
+            _ => 1,
+        }),
+    }
+}
+
+// ============================================================================
+// Rust
+// ============================================================================
+
+fn rust_node(source: &Source, node: Node, outline: &mut SyntaxOutline) {
+    match node.kind() {
+        "use_declaration" => {
+            if let Some(argument) = field(node, "argument") {
+                outline.imports.push(rust_import(source, argument, source.line_of(node.start_byte())));
+            }
+        }
+        "function_item" | "function_signature_item" => {
+            let Some(name) = field(node, "name") else { return };
+            let body = field(node, "body");
+            let header = node.start_byte()..header_end(node, body);
+            let mut symbol = source.symbol(text(source, name), SymbolKind::Function, name.start_byte(), header, node.byte_range());
+            symbol.parameters = parameters(source, field(node, "parameters"));
+            symbol.return_type = type_text(source, field(node, "return_type"));
+            symbol.is_public = child_of_kind(node, "visibility_modifier").is_some();
+            symbol.is_async = child_of_kind(node, "function_modifiers")
+                .is_some_and(|modifiers| child_of_kind(modifiers, "async").is_some());
+            outline.symbols.push(symbol);
+        }
+        "struct_item" | "union_item" | "enum_item" | "trait_item" | "type_item" | "mod_item" => {
+            let Some(name) = field(node, "name") else { return };
+            let kind = match node.kind() {
+                "struct_item" | "union_item" => SymbolKind::Struct,
+                "enum_item" => SymbolKind::Enum,
+                "trait_item" => SymbolKind::Trait,
+                "type_item" => SymbolKind::TypeAlias,
+                _ => SymbolKind::Module,
+            };
+            let body = field(node, "body");
+            let header = node.start_byte()..header_end(node, body);
+            let mut symbol = source.symbol(text(source, name), kind, name.start_byte(), header, node.byte_range());
+            symbol.is_public = child_of_kind(node, "visibility_modifier").is_some();
+            if let Some(body) = body.filter(|_| matches!(kind, SymbolKind::Struct | SymbolKind::Enum)) {
+                symbol.fields = rust_fields(source, body);
+            }
+            outline.symbols.push(symbol);
+        }
+        "impl_item" => {
+            let Some(type_node) = field(node, "type") else { return };
+            let name = rust_type_name(&source.collapsed(type_node.byte_range()));
+            let name_offset = type_node.start_byte() + text(source, type_node).find(name.as_str()).unwrap_or(0);
+            let header = node.start_byte()..header_end(node, field(node, "body"));
+            let mut symbol = source.symbol(&name, SymbolKind::Impl, name_offset, header, node.byte_range());
+            symbol.base_types = field(node, "trait")
+                .map(|base| vec![rust_type_name(&source.collapsed(base.byte_range()))])
+                .unwrap_or_default();
+            outline.symbols.push(symbol);
+        }
+        "call_expression" => {
+            let Some(mut function) = field(node, "function") else { return };
+            if function.kind() == "generic_function" {
+                let Some(inner) = field(function, "function") else { return };
+                function = inner;
+            }
+            let site = match function.kind() {
+                "identifier" => call(source, node, function, None, false),
+                "scoped_identifier" => {
+                    let Some(name) = field(function, "name") else { return };
+                    call(source, node, name, qualifier(source, field(function, "path")), false)
+                }
+                "field_expression" => {
+                    let Some(name) = field(function, "field") else { return };
+                    call(source, node, name, qualifier(source, field(function, "value")), true)
+                }
+                // Closures, parenthesized expressions and the like
+                _ => return,
+            };
+            outline.calls.push(site);
+        }
+        "token_tree" => rust_macro_calls(source, node, outline),
+        _ => {}
+    }
+}
+
+fn rust_import(source: &Source, argument: Node, line_number: usize) -> ImportInfo {
+    let path_text = |node: Option<Node>| -> String {
+        node.map(|node| text(source, node).split_whitespace().collect())
+            .unwrap_or_default()
+    };
+    let (module, list) = match argument.kind() {
+        "scoped_use_list" => (path_text(field(argument, "path")), field(argument, "list")),
+        "use_list" => (String::new(), Some(argument)),
+        "use_as_clause" => (path_text(field(argument, "path")), None),
+        _ => (path_text(Some(argument)), None),
+    };
+
+    let mut items = Vec::new();
+    let mut is_wildcard = argument.kind() == "use_wildcard";
+    match list {
+        Some(list) => rust_use_leaves(source, list, &module, &mut items, &mut is_wildcard),
+        None => rust_use_leaves(source, argument, &module, &mut items, &mut is_wildcard),
+    }
+    ImportInfo { module, items, is_wildcard, line_number }
+}
+
+/// Names a use tree binds locally; nested groups contribute their leaves
+fn rust_use_leaves(source: &Source, node: Node, module: &str, items: &mut Vec<String>, is_wildcard: &mut bool) {
+    match node.kind() {
+        "use_list" => {
+            for child in named_children(node) {
+                rust_use_leaves(source, child, module, items, is_wildcard);
+            }
+        }
+        "scoped_use_list" => {
+            if let Some(list) = field(node, "list") {
+                rust_use_leaves(source, list, module, items, is_wildcard);
+            }
+        }
+        "use_as_clause" => {
+            if let Some(alias) = field(node, "alias") {
+                items.push(text(source, alias).to_string());
+            }
+        }
+        "use_wildcard" => {
+            *is_wildcard = true;
+            items.push("*".to_string());
+        }
+        "self" => items.push(module.rsplit("::").next().unwrap_or(module).to_string()),
+        _ => {
+            let path = text(source, node);
+            items.push(path.rsplit("::").next().unwrap_or(path).trim().to_string());
+        }
+    }
+}
+
+fn rust_fields(source: &Source, body: Node) -> Vec<FieldInfo> {
+    match body.kind() {
+        "field_declaration_list" => named_children(body)
+            .into_iter()
+            .filter_map(|declaration| {
+                let name = field(declaration, "name")?;
+                Some(FieldInfo {
+                    name: text(source, name).to_string(),
+                    type_name: type_text(source, field(declaration, "type")),
+                    is_public: child_of_kind(declaration, "visibility_modifier").is_some(),
+                })
+            })
+            .collect(),
+        "ordered_field_declaration_list" => {
+            // `(pub A, B)`: each type is preceded by its own visibility, if any
+            let mut fields = Vec::new();
+            let mut is_public = false;
+            for child in named_children(body) {
+                if child.kind() == "visibility_modifier" {
+                    is_public = true;
+                    continue;
+                }
+                fields.push(FieldInfo {
+                    name: fields.len().to_string(),
+                    type_name: Some(source.collapsed(child.byte_range())),
+                    is_public: std::mem::take(&mut is_public),
+                });
+            }
+            fields
+        }
+        "enum_variant_list" => named_children(body)
+            .into_iter()
+            .filter_map(|variant| field(variant, "name"))
+            .map(|name| FieldInfo { name: text(source, name).to_string(), type_name: None, is_public: true })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Calls in a macro's token tree: `name(`, `path::name(` and `receiver.name(`
+///
+/// Nested token trees are visited by the main walk, so only direct tokens are
+/// looked at here.
+fn rust_macro_calls(source: &Source, token_tree: Node, outline: &mut SyntaxOutline) {
+    let mut cursor = token_tree.walk();
+    let tokens: Vec<Node> = token_tree.children(&mut cursor).collect();
+    let is_segment = |node: Node| matches!(node.kind(), "identifier" | "self" | "super" | "crate");
+
+    for (i, window) in tokens.windows(2).enumerate() {
+        let (name, arguments) = (window[0], window[1]);
+        let opens_call = arguments.kind() == "token_tree" && text(source, arguments).starts_with('(');
+        if name.kind() != "identifier" || !opens_call {
+            continue;
+        }
+        if i > 0 && matches!(tokens[i - 1].kind(), "fn" | "struct" | "enum") {
+            continue;
+        }
+
+        // Walk back over `a::b.` to find where the path starts
+        let mut start = i;
+        while start >= 2 && matches!(tokens[start - 1].kind(), "::" | ".") && is_segment(tokens[start - 2]) {
+            start -= 2;
+        }
+        let is_method = i > 0 && tokens[i - 1].kind() == ".";
+        let qualifier = (start < i).then(|| {
+            source.text[tokens[start].start_byte()..tokens[i - 1].start_byte()]
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect()
+        });
+
+        // Top-level comma-separated groups, less the brackets
+        let mut groups = 0;
+        let mut empty = true;
+        let mut inner = arguments.walk();
+        let inner: Vec<Node> = arguments.children(&mut inner).collect();
+        for token in inner.iter().skip(1).take(inner.len().saturating_sub(2)) {
+            if token.kind() == "," {
+                groups += usize::from(!empty);
+                empty = true;
+            } else if !token.kind().ends_with("comment") {
+                empty = false;
+            }
+        }
+        groups += usize::from(!empty);
+
+        outline.calls.push(CallSite {
+            name: text(source, name).to_string(),
+            qualifier,
+            is_method,
+            line_number: source.line_of(tokens[start].start_byte()),
+            argument_count: Some(groups),
+        });
+    }
+}
+
+// ============================================================================
+// Python
+// ============================================================================
+
+fn python_node(source: &Source, node: Node, outline: &mut SyntaxOutline) {
+    let line_number = source.line_of(node.start_byte());
+    match node.kind() {
+        "import_statement" => {
+            let mut cursor = node.walk();
+            for name in node.children_by_field_name("name", &mut cursor) {
+                let (module, item) = match name.kind() {
+                    "aliased_import" => (
+                        field(name, "name").map(|path| text(source, path)).unwrap_or_default().to_string(),
+                        field(name, "alias").map(|alias| text(source, alias)).unwrap_or_default().to_string(),
+                    ),
+                    _ => {
+                        let path = text(source, name);
+                        (path.to_string(), path.split('.').next().unwrap_or(path).to_string())
+                    }
+                };
+                outline.imports.push(ImportInfo { module, items: vec![item], is_wildcard: false, line_number });
+            }
+        }
+        "import_from_statement" => {
+            let module = field(node, "module_name").map(|module| text(source, module)).unwrap_or_default();
+            let mut cursor = node.walk();
+            let mut items: Vec<String> = node
+                .children_by_field_name("name", &mut cursor)
+                .map(|name| match name.kind() {
+                    "aliased_import" => field(name, "alias").map(|alias| text(source, alias)).unwrap_or_default(),
+                    _ => text(source, name),
+                })
+                .map(str::to_string)
+                .collect();
+            let is_wildcard = child_of_kind(node, "wildcard_import").is_some();
+            if is_wildcard {
+                items.push("*".to_string());
+            }
+            outline.imports.push(ImportInfo { module: module.to_string(), items, is_wildcard, line_number });
+        }
+        "function_definition" | "class_definition" => {
+            let Some(name) = field(node, "name") else { return };
+            let kind = if node.kind() == "class_definition" { SymbolKind::Class } else { SymbolKind::Function };
+            // The header ends at the `:` before the body
+            let body = field(node, "body");
+            let mut cursor = node.walk();
+            let colon = node
+                .children(&mut cursor)
+                .filter(|child| child.kind() == ":" && body.is_none_or(|body| child.end_byte() <= body.start_byte()))
+                .last();
+            let header = node.start_byte()..colon.map(|colon| colon.start_byte()).unwrap_or_else(|| header_end(node, body));
+
+            let name_text = text(source, name);
+            let mut symbol = source.symbol(name_text, kind, name.start_byte(), header, node.byte_range());
+            symbol.is_public = !name_text.starts_with('_') || (name_text.starts_with("__") && name_text.ends_with("__"));
+            if kind == SymbolKind::Class {
+                symbol.base_types = parameters(source, field(node, "superclasses"));
+            } else {
+                symbol.parameters = parameters(source, field(node, "parameters"));
+                symbol.return_type = type_text(source, field(node, "return_type"));
+                symbol.is_async = child_of_kind(node, "async").is_some();
+            }
+            outline.symbols.push(symbol);
+        }
+        "call" => {
+            let Some(function) = field(node, "function") else { return };
+            let site = match function.kind() {
+                "identifier" => call(source, node, function, None, false),
+                "attribute" => {
+                    let Some(name) = field(function, "attribute") else { return };
+                    call(source, node, name, qualifier(source, field(function, "object")), true)
+                }
+                _ => return,
+            };
+            outline.calls.push(site);
+        }
+        _ => {}
+    }
+}
+
+// ============================================================================
+// TypeScript / JavaScript
+// ============================================================================
+
+/// Outermost `export`/`declare` wrapper of a declaration, and whether it is exported
+fn typescript_outer(node: Node) -> (Node, bool) {
+    let mut outer = node;
+    let mut exported = false;
+    while let Some(parent) = outer.parent() {
+        match parent.kind() {
+            "export_statement" => exported = true,
+            "ambient_declaration" => {}
+            _ => break,
+        }
+        outer = parent;
+    }
+    (outer, exported)
+}
+
+/// Function-like declaration spanning `outer`, with `function` giving its signature
+fn typescript_function(source: &Source, name: Node, outer: Node, function: Node) -> SymbolInfo {
+    let body = field(function, "body");
+    let header = outer.start_byte()..header_end(function, body).max(outer.start_byte());
+    let mut symbol = source.symbol(text(source, name), SymbolKind::Function, name.start_byte(), header, outer.byte_range());
+    symbol.parameters = match field(function, "parameters") {
+        Some(list) => parameters(source, Some(list)),
+        // Arrow function with a single bare parameter
+        None => field(function, "parameter").map(|param| vec![text(source, param).to_string()]).unwrap_or_default(),
+    };
+    symbol.return_type = type_text(source, field(function, "return_type"));
+    symbol.is_async = child_of_kind(function, "async").is_some();
+    symbol
+}
+
+fn typescript_node(source: &Source, node: Node, outline: &mut SyntaxOutline) {
+    match node.kind() {
+        "import_statement" => {
+            let Some(module) = field(node, "source") else { return };
+            let mut items = Vec::new();
+            let mut is_wildcard = false;
+            if let Some(clause) = child_of_kind(node, "import_clause") {
+                for part in named_children(clause) {
+                    match part.kind() {
+                        "named_imports" => items.extend(
+                            named_children(part)
+                                .into_iter()
+                                .filter_map(|specifier| field(specifier, "alias").or_else(|| field(specifier, "name")))
+                                .map(|name| text(source, name).to_string()),
+                        ),
+                        "namespace_import" => {
+                            is_wildcard = true;
+                            items.extend(child_of_kind(part, "identifier").map(|name| text(source, name).to_string()));
+                        }
+                        _ => items.push(text(source, part).to_string()),
+                    }
+                }
+            }
+            outline.imports.push(ImportInfo {
+                module: string_contents(source, module),
+                items,
+                is_wildcard,
+                line_number: source.line_of(node.start_byte()),
+            });
+        }
+        "function_declaration" | "generator_function_declaration" | "function_signature" => {
+            let Some(name) = field(node, "name") else { return };
+            let (outer, exported) = typescript_outer(node);
+            let mut symbol = typescript_function(source, name, outer, node);
+            symbol.is_public = exported;
+            outline.symbols.push(symbol);
+        }
+        "lexical_declaration" | "variable_declaration" => {
+            let (outer, exported) = typescript_outer(node);
+            for declarator in named_children(node) {
+                let Some(name) = field(declarator, "name").filter(|name| name.kind() == "identifier") else { continue };
+                let Some(value) = field(declarator, "value") else { continue };
+                if !matches!(value.kind(), "arrow_function" | "function_expression" | "function" | "generator_function") {
+                    continue;
+                }
+                let mut symbol = typescript_function(source, name, outer, value);
+                symbol.is_public = exported;
+                outline.symbols.push(symbol);
+            }
+        }
+        "class_declaration" | "abstract_class_declaration" | "interface_declaration" | "enum_declaration" => {
+            let Some(name) = field(node, "name") else { return };
+            let (outer, exported) = typescript_outer(node);
+            let kind = match node.kind() {
+                "interface_declaration" => SymbolKind::Interface,
+                "enum_declaration" => SymbolKind::Enum,
+                _ => SymbolKind::Class,
+            };
+            let body = field(node, "body");
+            let header = outer.start_byte()..header_end(node, body);
+            let mut symbol = source.symbol(text(source, name), kind, name.start_byte(), header, outer.byte_range());
+            symbol.is_public = exported;
+            let heritage = child_of_kind(node, "class_heritage").or_else(|| child_of_kind(node, "extends_type_clause"));
+            if let Some(heritage) = heritage {
+                symbol.base_types = typescript_heritage(&source.collapsed(heritage.byte_range()));
+            }
+            if let Some(body) = body.filter(|_| kind != SymbolKind::Class) {
+                symbol.fields = typescript_members(source, body);
+            }
+            outline.symbols.push(symbol);
+        }
+        "type_alias_declaration" => {
+            let Some(name) = field(node, "name") else { return };
+            let (outer, exported) = typescript_outer(node);
+            let header = outer.start_byte()..header_end(node, None);
+            let mut symbol = source.symbol(text(source, name), SymbolKind::TypeAlias, name.start_byte(), header, outer.byte_range());
+            symbol.is_public = exported;
+            outline.symbols.push(symbol);
+        }
+        "method_definition" | "method_signature" | "abstract_method_signature" => {
+            if node.parent().is_none_or(|parent| parent.kind() != "class_body") {
+                return;
+            }
+            let Some(name) = field(node, "name") else { return };
+            let mut symbol = typescript_function(source, name, node, node);
+            let hidden = child_of_kind(node, "accessibility_modifier")
+                .is_some_and(|modifier| matches!(text(source, modifier), "private" | "protected"));
+            symbol.is_public = !hidden && !symbol.name.starts_with('#');
+            outline.symbols.push(symbol);
+        }
+        "call_expression" => {
+            let Some(function) = field(node, "function") else { return };
+            let site = match function.kind() {
+                "identifier" | "super" => call(source, node, function, None, false),
+                "member_expression" => {
+                    let Some(name) = field(function, "property") else { return };
+                    call(source, node, name, qualifier(source, field(function, "object")), true)
+                }
+                _ => return,
+            };
+            // `require('x')` is both a call and an import
+            if site.name == "require" {
+                let module = field(node, "arguments")
+                    .and_then(|arguments| named_children(arguments).into_iter().next())
+                    .filter(|argument| argument.kind() == "string");
+                if let Some(module) = module {
+                    outline.imports.push(ImportInfo {
+                        module: string_contents(source, module),
+                        items: Vec::new(),
+                        is_wildcard: false,
+                        line_number: site.line_number,
+                    });
+                }
+            }
+            outline.calls.push(site);
+        }
+        _ => {}
+    }
+}
+
+fn string_contents(source: &Source, string: Node) -> String {
+    child_of_kind(string, "string_fragment")
+        .map(|fragment| text(source, fragment).to_string())
+        .unwrap_or_default()
+}
+
+fn typescript_members(source: &Source, body: Node) -> Vec<FieldInfo> {
+    named_children(body)
+        .into_iter()
+        .filter_map(|member| match member.kind() {
+            "property_signature" => Some(FieldInfo {
+                name: text(source, field(member, "name")?).to_string(),
+                type_name: type_text(source, field(member, "type")),
+                is_public: true,
+            }),
+            "enum_assignment" => Some(FieldInfo {
+                name: text(source, field(member, "name")?).to_string(),
+                type_name: None,
+                is_public: true,
+            }),
+            "property_identifier" | "string" => Some(FieldInfo {
+                name: text(source, member).to_string(),
+                type_name: None,
+                is_public: true,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    #[test]
+    fn test_layouts_the_scanner_misses() {
+        let code = r#"#[inline] pub fn first() -> u8 { 1 } fn second(x: u8) { first(); }
+struct Pair(pub u8, String);
+macro_rules! noop { () => {} }
+fn third() {
+    assert_eq!(store.get(key, 1), helper::fetch(path));
+}
+"#;
+        let outline = parse(code, ProgrammingLanguage::Rust).unwrap();
+
+        let names: Vec<(&str, usize)> = outline.symbols.iter().map(|s| (s.name.as_str(), s.line_number)).collect();
+        assert_eq!(names, vec![("first", 1), ("second", 1), ("Pair", 2), ("third", 4)]);
+        assert!(outline.symbols[0].is_public);
+        assert_eq!(outline.symbols[1].signature, "fn second(x: u8)");
+
+        let pair = &outline.symbols[2];
+        let fields: Vec<(&str, Option<&str>, bool)> =
+            pair.fields.iter().map(|f| (f.name.as_str(), f.type_name.as_deref(), f.is_public)).collect();
+        assert_eq!(fields, vec![("0", Some("u8"), true), ("1", Some("String"), false)]);
+
+        // Calls inside macro arguments come from the token tree
+        let calls: Vec<(Option<&str>, &str, bool, Option<usize>)> = outline
+            .calls
+            .iter()
+            .map(|c| (c.qualifier.as_deref(), c.name.as_str(), c.is_method, c.argument_count))
+            .collect();
+        assert_eq!(calls, vec![
+            (None, "first", false, Some(0)),
+            (Some("store"), "get", true, Some(2)),
+            (Some("helper"), "fetch", false, Some(1)),
+        ]);
+    }
+
+    #[test]
+    fn test_javascript_outline() {
+        let code = r#"const fs = require('fs');
+
+class Walker extends EventEmitter {
+  #seen = new Set();
+
+  async *walk(dir) {
+    for (const entry of fs.readdirSync(dir)) yield entry;
+  }
+
+  #visit(entry) { this.emit('entry', entry); }
+}
+
+module.exports = { Walker, count: (items) => items.length };
+"#;
+        let outline = parse(code, ProgrammingLanguage::JavaScript).unwrap();
+
+        assert_eq!(outline.imports.len(), 1);
+        assert_eq!(outline.imports[0].module, "fs");
+
+        let names: Vec<(&str, SymbolKind, bool)> =
+            outline.symbols.iter().map(|s| (s.name.as_str(), s.kind, s.is_public)).collect();
+        assert_eq!(names, vec![
+            ("Walker", SymbolKind::Class, false),
+            ("walk", SymbolKind::Method, true),
+            ("#visit", SymbolKind::Method, false),
+        ]);
+        assert_eq!(outline.symbols[0].base_types, vec!["EventEmitter"]);
+        assert!(outline.symbols[1].is_async);
+        assert_eq!((outline.symbols[1].line_number, outline.symbols[1].end_line), (6, 8));
+
+        assert!(outline.calls.iter().any(|c| c.name == "readdirSync" && c.qualifier.as_deref() == Some("fs")));
+        assert!(outline.calls.iter().any(|c| c.name == "emit" && c.qualifier.as_deref() == Some("this")));
+    }
+}
@@ -0,0 +1,807 @@
+//! # Vector Store
+//!
+//! Built-in [`VectorStorage`] backend: an in-process HNSW (Hierarchical
+//! Navigable Small World) index persisted under `.vespera/rag/indices`, so
+//! semantic search works without any external vector database.
+//!
+//! Persistence is a bincode snapshot (`hnsw.bin`) plus an append-only
+//! write-ahead log (`hnsw.wal`). Every upsert/delete is appended to the log
+//! and the snapshot is only rewritten every `checkpoint_interval` operations,
+//! which keeps incremental indexing cheap on large projects.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::VectorStorage;
+
+const SNAPSHOT_FILE: &str = "hnsw.bin";
+const WAL_FILE: &str = "hnsw.wal";
+
+/// Tuning parameters for the HNSW index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswConfig {
+    /// Maximum neighbours per node on upper layers (layer 0 allows twice this)
+    pub m: usize,
+
+    /// Candidate list size while inserting
+    pub ef_construction: usize,
+
+    /// Candidate list size while searching (raised to `limit` when smaller)
+    pub ef_search: usize,
+
+    /// Write a fresh snapshot after this many logged operations
+    pub checkpoint_interval: usize,
+
+    /// Rebuild the graph once deleted nodes exceed this fraction of all nodes
+    pub compaction_ratio: f32,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+            checkpoint_interval: 1000,
+            compaction_ratio: 0.5,
+        }
+    }
+}
+
+/// Persistent HNSW-backed vector storage
+///
+/// Vectors are L2-normalised on insert and scored by cosine similarity.
+/// Metadata filters match when every filter key equals the stored value; a
+/// filter value that is an array matches any of its elements.
+pub struct HnswVectorStorage {
+    dir: PathBuf,
+    config: HnswConfig,
+    state: RwLock<StoreState>,
+}
+
+struct StoreState {
+    index: HnswIndex,
+    wal_entries: usize,
+}
+
+/// Operation recorded in the write-ahead log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum WalEntry {
+    Upsert {
+        id: String,
+        embedding: Vec<f32>,
+        metadata: HashMap<String, serde_json::Value>,
+    },
+    Delete {
+        id: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HnswIndex {
+    dimension: Option<usize>,
+    nodes: Vec<Node>,
+    id_to_node: HashMap<String, usize>,
+    entry_point: Option<usize>,
+    max_level: usize,
+    deleted: usize,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    id: String,
+    vector: Vec<f32>,
+    // bincode cannot round-trip `serde_json::Value`, so metadata is stored as JSON text
+    #[serde(with = "metadata_as_json")]
+    metadata: HashMap<String, serde_json::Value>,
+    neighbors: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+mod metadata_as_json {
+    use std::collections::HashMap;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        metadata: &HashMap<String, serde_json::Value>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let json = serde_json::to_string(metadata).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&json)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<String, serde_json::Value>, D::Error> {
+        let json = String::deserialize(deserializer)?;
+        serde_json::from_str(&json).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Node paired with its distance to a query (smaller is closer)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored {
+    dist: f32,
+    node: usize,
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.total_cmp(&other.dist).then(self.node.cmp(&other.node))
+    }
+}
+
+impl HnswVectorStorage {
+    /// Open (or create) the index stored in `dir`
+    pub fn open(dir: &Path, config: HnswConfig) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create vector index directory: {:?}", dir))?;
+
+        let snapshot_path = dir.join(SNAPSHOT_FILE);
+        let mut index = if snapshot_path.exists() {
+            let bytes = fs::read(&snapshot_path)?;
+            bincode::deserialize(&bytes)
+                .with_context(|| format!("Corrupt vector index snapshot: {:?}", snapshot_path))?
+        } else {
+            HnswIndex::default()
+        };
+
+        let wal_entries = Self::replay_wal(&dir.join(WAL_FILE), &mut index, &config)?;
+        debug!(
+            vectors = index.id_to_node.len(),
+            wal_entries,
+            "Opened HNSW vector index"
+        );
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            config,
+            state: RwLock::new(StoreState { index, wal_entries }),
+        })
+    }
+
+    /// Open the index for a project's `.vespera` folder with default settings
+    pub fn for_vespera_path(vespera_path: &Path) -> Result<Self> {
+        Self::open(&vespera_path.join("rag/indices"), HnswConfig::default())
+    }
+
+    fn replay_wal(wal_path: &Path, index: &mut HnswIndex, config: &HnswConfig) -> Result<usize> {
+        if !wal_path.exists() {
+            return Ok(0);
+        }
+
+        let mut replayed = 0;
+        for line in BufReader::new(File::open(wal_path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<WalEntry>(&line) {
+                Ok(WalEntry::Upsert { id, embedding, metadata }) => {
+                    index.upsert(id, &embedding, metadata, config)?;
+                }
+                Ok(WalEntry::Delete { id }) => {
+                    index.delete(&id, config);
+                }
+                Err(e) => {
+                    // A torn final write from a crash; everything before it is intact
+                    warn!(error = %e, "Ignoring unreadable vector index log entry");
+                    break;
+                }
+            }
+            replayed += 1;
+        }
+
+        Ok(replayed)
+    }
+
+    fn append_wal(&self, state: &mut StoreState, entry: &WalEntry) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(WAL_FILE))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        state.wal_entries += 1;
+
+        if state.wal_entries >= self.config.checkpoint_interval {
+            self.write_snapshot(state)?;
+        }
+        Ok(())
+    }
+
+    fn write_snapshot(&self, state: &mut StoreState) -> Result<()> {
+        let snapshot_path = self.dir.join(SNAPSHOT_FILE);
+        let tmp_path = self.dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        fs::write(&tmp_path, bincode::serialize(&state.index)?)?;
+        fs::rename(&tmp_path, &snapshot_path)?;
+
+        // The snapshot now contains everything in the log
+        File::create(self.dir.join(WAL_FILE))?;
+        state.wal_entries = 0;
+        Ok(())
+    }
+
+    fn read_state(&self) -> Result<std::sync::RwLockReadGuard<'_, StoreState>> {
+        self.state.read().map_err(|_| anyhow::anyhow!("Vector index lock poisoned"))
+    }
+
+    fn write_state(&self) -> Result<std::sync::RwLockWriteGuard<'_, StoreState>> {
+        self.state.write().map_err(|_| anyhow::anyhow!("Vector index lock poisoned"))
+    }
+
+    /// Write a snapshot and truncate the log
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self.write_state()?;
+        self.write_snapshot(&mut state)
+    }
+
+    /// Remove every vector (e.g. before re-embedding with a different model)
+    pub fn clear(&self) -> Result<()> {
+        let mut state = self.write_state()?;
+        state.index = HnswIndex::default();
+        self.write_snapshot(&mut state)
+    }
+
+    /// Number of live vectors
+    pub fn len(&self) -> usize {
+        self.read_state().map(|s| s.index.id_to_node.len()).unwrap_or(0)
+    }
+
+    /// Whether the index holds no live vectors
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether a vector with this ID is stored
+    pub fn contains(&self, id: &str) -> bool {
+        self.read_state().map(|s| s.index.id_to_node.contains_key(id)).unwrap_or(false)
+    }
+
+    fn on_disk_size(&self) -> u64 {
+        [SNAPSHOT_FILE, WAL_FILE]
+            .iter()
+            .filter_map(|name| fs::metadata(self.dir.join(name)).ok())
+            .map(|m| m.len())
+            .sum()
+    }
+}
+
+impl VectorStorage for HnswVectorStorage {
+    fn store_embedding(
+        &self,
+        id: &str,
+        embedding: &[f32],
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        let mut state = self.write_state()?;
+        state.index.upsert(id.to_string(), embedding, metadata.clone(), &self.config)?;
+        self.append_wal(&mut state, &WalEntry::Upsert {
+            id: id.to_string(),
+            embedding: embedding.to_vec(),
+            metadata,
+        })
+    }
+
+    fn search(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filter: Option<HashMap<String, serde_json::Value>>,
+    ) -> Result<Vec<(String, f32, HashMap<String, serde_json::Value>)>> {
+        let state = self.read_state()?;
+        let index = &state.index;
+
+        if limit == 0 || index.id_to_node.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(dimension) = index.dimension {
+            if query_embedding.len() != dimension {
+                anyhow::bail!(
+                    "Query has {} dimensions but the index stores {}",
+                    query_embedding.len(),
+                    dimension
+                );
+            }
+        }
+
+        let query = normalize(query_embedding);
+        let matches = |node: &Node| {
+            !node.deleted && filter.as_ref().is_none_or(|f| metadata_matches(&node.metadata, f))
+        };
+
+        // Over-fetch when filtering so selective filters still fill the page
+        let ef = if filter.is_some() {
+            self.config.ef_search.max(limit * 4)
+        } else {
+            self.config.ef_search.max(limit)
+        };
+        let mut hits: Vec<Scored> = index
+            .knn(&query, ef)
+            .into_iter()
+            .filter(|s| matches(&index.nodes[s.node]))
+            .take(limit)
+            .collect();
+
+        // Very selective filters can leave the graph walk short; fall back to an exact scan
+        if hits.len() < limit && filter.is_some() {
+            hits = index
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| matches(node))
+                .map(|(i, node)| Scored { dist: distance(&query, &node.vector), node: i })
+                .collect();
+            hits.sort();
+            hits.truncate(limit);
+        }
+
+        Ok(hits
+            .into_iter()
+            .map(|s| {
+                let node = &index.nodes[s.node];
+                (node.id.clone(), 1.0 - s.dist, node.metadata.clone())
+            })
+            .collect())
+    }
+
+    fn delete(&self, id: &str) -> Result<bool> {
+        let mut state = self.write_state()?;
+        if !state.index.delete(id, &self.config) {
+            return Ok(false);
+        }
+        self.append_wal(&mut state, &WalEntry::Delete { id: id.to_string() })?;
+        Ok(true)
+    }
+
+    fn stats(&self) -> Result<HashMap<String, serde_json::Value>> {
+        let state = self.read_state()?;
+        let index = &state.index;
+
+        let mut stats = HashMap::new();
+        stats.insert("backend".to_string(), serde_json::json!("hnsw"));
+        stats.insert("metric".to_string(), serde_json::json!("cosine"));
+        stats.insert("total_vectors".to_string(), serde_json::json!(index.id_to_node.len()));
+        stats.insert("deleted_vectors".to_string(), serde_json::json!(index.deleted));
+        stats.insert("dimension".to_string(), serde_json::json!(index.dimension));
+        stats.insert("max_level".to_string(), serde_json::json!(index.max_level));
+        stats.insert("m".to_string(), serde_json::json!(self.config.m));
+        stats.insert("ef_construction".to_string(), serde_json::json!(self.config.ef_construction));
+        stats.insert("ef_search".to_string(), serde_json::json!(self.config.ef_search));
+        stats.insert("pending_log_entries".to_string(), serde_json::json!(state.wal_entries));
+        stats.insert("index_size_bytes".to_string(), serde_json::json!(self.on_disk_size()));
+        stats.insert("last_updated".to_string(), serde_json::json!(index.updated_at));
+        stats.insert("path".to_string(), serde_json::json!(self.dir));
+        Ok(stats)
+    }
+}
+
+impl HnswIndex {
+    fn upsert(
+        &mut self,
+        id: String,
+        embedding: &[f32],
+        metadata: HashMap<String, serde_json::Value>,
+        config: &HnswConfig,
+    ) -> Result<()> {
+        if embedding.is_empty() {
+            anyhow::bail!("Cannot store an empty embedding for {}", id);
+        }
+        match self.dimension {
+            Some(dimension) if dimension != embedding.len() => anyhow::bail!(
+                "Embedding for {} has {} dimensions but the index stores {}",
+                id,
+                embedding.len(),
+                dimension
+            ),
+            _ => self.dimension = Some(embedding.len()),
+        }
+
+        let vector = normalize(embedding);
+        self.updated_at = Some(Utc::now());
+
+        if let Some(&existing) = self.id_to_node.get(&id) {
+            // Metadata-only changes keep the node and its links
+            if self.nodes[existing].vector == vector {
+                self.nodes[existing].metadata = metadata;
+                return Ok(());
+            }
+            self.tombstone(existing);
+        }
+
+        let level = random_level(config.m);
+        let idx = self.nodes.len();
+        self.nodes.push(Node {
+            id: id.clone(),
+            vector,
+            metadata,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.id_to_node.insert(id, idx);
+        self.link(idx, config);
+        self.maybe_compact(config);
+        Ok(())
+    }
+
+    fn delete(&mut self, id: &str, config: &HnswConfig) -> bool {
+        let Some(&idx) = self.id_to_node.get(id) else {
+            return false;
+        };
+        self.tombstone(idx);
+        self.updated_at = Some(Utc::now());
+        self.maybe_compact(config);
+        true
+    }
+
+    /// Hide a node from results while keeping it as a routing hop
+    fn tombstone(&mut self, idx: usize) {
+        let node = &mut self.nodes[idx];
+        if !node.deleted {
+            node.deleted = true;
+            self.id_to_node.remove(&node.id);
+            self.deleted += 1;
+        }
+    }
+
+    fn maybe_compact(&mut self, config: &HnswConfig) {
+        let total = self.nodes.len();
+        if self.deleted == 0 || (self.deleted as f32) < total as f32 * config.compaction_ratio {
+            return;
+        }
+
+        debug!(total, deleted = self.deleted, "Compacting HNSW vector index");
+        let live: Vec<Node> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .filter(|n| !n.deleted)
+            .collect();
+
+        self.id_to_node.clear();
+        self.entry_point = None;
+        self.max_level = 0;
+        self.deleted = 0;
+
+        for mut node in live {
+            let level = random_level(config.m);
+            node.neighbors = vec![Vec::new(); level + 1];
+            let idx = self.nodes.len();
+            self.id_to_node.insert(node.id.clone(), idx);
+            self.nodes.push(node);
+            self.link(idx, config);
+        }
+    }
+
+    fn max_connections(layer: usize, config: &HnswConfig) -> usize {
+        if layer == 0 { config.m * 2 } else { config.m }
+    }
+
+    /// Connect a freshly pushed node into the graph
+    fn link(&mut self, idx: usize, config: &HnswConfig) {
+        let level = self.nodes[idx].neighbors.len() - 1;
+        let Some(mut entry) = self.entry_point else {
+            self.entry_point = Some(idx);
+            self.max_level = level;
+            return;
+        };
+
+        let query = self.nodes[idx].vector.clone();
+        let mut layer = self.max_level;
+        while layer > level {
+            entry = self.search_layer(&query, &[entry], 1, layer)[0].node;
+            layer -= 1;
+        }
+
+        let mut entries = vec![entry];
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let found = self.search_layer(&query, &entries, config.ef_construction, layer);
+            let selected: Vec<usize> = found
+                .iter()
+                .map(|s| s.node)
+                .filter(|&n| n != idx)
+                .take(config.m)
+                .collect();
+
+            let max_conn = Self::max_connections(layer, config);
+            for &neighbor in &selected {
+                self.nodes[neighbor].neighbors[layer].push(idx);
+                if self.nodes[neighbor].neighbors[layer].len() > max_conn {
+                    self.prune(neighbor, layer, max_conn);
+                }
+            }
+            self.nodes[idx].neighbors[layer] = selected;
+            entries = found.into_iter().map(|s| s.node).collect();
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry_point = Some(idx);
+        }
+    }
+
+    /// Keep only the closest `max_conn` neighbours of a node on one layer
+    fn prune(&mut self, idx: usize, layer: usize, max_conn: usize) {
+        let vector = &self.nodes[idx].vector;
+        let mut scored: Vec<Scored> = self.nodes[idx].neighbors[layer]
+            .iter()
+            .map(|&n| Scored { dist: distance(vector, &self.nodes[n].vector), node: n })
+            .collect();
+        scored.sort();
+        scored.truncate(max_conn);
+        self.nodes[idx].neighbors[layer] = scored.into_iter().map(|s| s.node).collect();
+    }
+
+    /// Best-first search on one layer, returning up to `ef` nodes closest first
+    fn search_layer(&self, query: &[f32], entries: &[usize], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+
+        for &entry in entries {
+            let scored = Scored { dist: distance(query, &self.nodes[entry].vector), node: entry };
+            candidates.push(Reverse(scored));
+            results.push(scored);
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let furthest = results.peek().map_or(f32::INFINITY, |s| s.dist);
+            if current.dist > furthest && results.len() >= ef {
+                break;
+            }
+
+            let Some(neighbors) = self.nodes[current.node].neighbors.get(layer) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let dist = distance(query, &self.nodes[neighbor].vector);
+                let furthest = results.peek().map_or(f32::INFINITY, |s| s.dist);
+                if results.len() < ef || dist < furthest {
+                    let scored = Scored { dist, node: neighbor };
+                    candidates.push(Reverse(scored));
+                    results.push(scored);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    /// Approximate nearest neighbours (including tombstoned nodes)
+    fn knn(&self, query: &[f32], ef: usize) -> Vec<Scored> {
+        let Some(mut entry) = self.entry_point else {
+            return Vec::new();
+        };
+        for layer in (1..=self.max_level).rev() {
+            entry = self.search_layer(query, &[entry], 1, layer)[0].node;
+        }
+        // Tombstones still occupy candidate slots, so widen the beam to compensate
+        self.search_layer(query, &[entry], ef + self.deleted.min(ef), 0)
+    }
+}
+
+/// Draw a node level from the usual exponentially decaying distribution
+fn random_level(m: usize) -> usize {
+    let ml = 1.0 / (m.max(2) as f64).ln();
+    let uniform: f64 = rand::random::<f64>().max(f64::MIN_POSITIVE);
+    ((-uniform.ln() * ml).floor() as usize).min(16)
+}
+
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        vector.to_vec()
+    } else {
+        vector.iter().map(|x| x / norm).collect()
+    }
+}
+
+/// Cosine distance between two normalised vectors
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>()
+}
+
+fn metadata_matches(
+    metadata: &HashMap<String, serde_json::Value>,
+    filter: &HashMap<String, serde_json::Value>,
+) -> bool {
+    filter.iter().all(|(key, expected)| match (metadata.get(key), expected) {
+        (Some(actual), serde_json::Value::Array(options)) if !actual.is_array() => {
+            options.contains(actual)
+        }
+        (Some(actual), expected) => actual == expected,
+        (None, _) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn meta(pairs: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    fn pseudo_random_vector(seed: u64, dim: usize) -> Vec<f32> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..dim)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                ((state >> 33) as f32 / u32::MAX as f32) - 0.25
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_store_and_search_nearest() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HnswVectorStorage::open(temp_dir.path(), HnswConfig::default()).unwrap();
+
+        store.store_embedding("x", &[1.0, 0.0, 0.0], HashMap::new()).unwrap();
+        store.store_embedding("y", &[0.0, 1.0, 0.0], HashMap::new()).unwrap();
+        store.store_embedding("xy", &[0.7, 0.7, 0.0], HashMap::new()).unwrap();
+
+        let results = store.search(&[0.9, 0.1, 0.0], 2, None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "x");
+        assert_eq!(results[1].0, "xy");
+        assert!(results[0].1 > results[1].1);
+    }
+
+    #[test]
+    fn test_metadata_filtering() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HnswVectorStorage::open(temp_dir.path(), HnswConfig::default()).unwrap();
+
+        store.store_embedding("a", &[1.0, 0.0], meta(&[("kind", "code".into())])).unwrap();
+        store.store_embedding("b", &[0.9, 0.1], meta(&[("kind", "docs".into())])).unwrap();
+        store.store_embedding("c", &[0.0, 1.0], meta(&[("kind", "data".into())])).unwrap();
+
+        let filter = meta(&[("kind", "docs".into())]);
+        let results = store.search(&[1.0, 0.0], 5, Some(filter)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "b");
+
+        let filter = meta(&[("kind", serde_json::json!(["code", "data"]))]);
+        let ids: Vec<String> = store.search(&[1.0, 0.0], 5, Some(filter)).unwrap()
+            .into_iter().map(|r| r.0).collect();
+        assert_eq!(ids, vec!["a".to_string(), "c".to_string()]);
+    }
+
+    #[test]
+    fn test_upsert_replaces_and_delete_hides() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HnswVectorStorage::open(temp_dir.path(), HnswConfig::default()).unwrap();
+
+        store.store_embedding("a", &[1.0, 0.0], meta(&[("v", 1.into())])).unwrap();
+        store.store_embedding("b", &[0.0, 1.0], HashMap::new()).unwrap();
+        store.store_embedding("a", &[0.0, 1.0], meta(&[("v", 2.into())])).unwrap();
+        assert_eq!(store.len(), 2);
+
+        let results = store.search(&[1.0, 0.0], 5, None).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.1 < 0.5));
+        let a = results.iter().find(|r| r.0 == "a").unwrap();
+        assert_eq!(a.2.get("v"), Some(&serde_json::json!(2)));
+
+        assert!(store.delete("a").unwrap());
+        assert!(!store.delete("a").unwrap());
+        let ids: Vec<String> = store.search(&[0.0, 1.0], 5, None).unwrap()
+            .into_iter().map(|r| r.0).collect();
+        assert_eq!(ids, vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_dimension_mismatch_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HnswVectorStorage::open(temp_dir.path(), HnswConfig::default()).unwrap();
+
+        store.store_embedding("a", &[1.0, 0.0, 0.0], HashMap::new()).unwrap();
+        assert!(store.store_embedding("b", &[1.0, 0.0], HashMap::new()).is_err());
+        assert!(store.search(&[1.0, 0.0], 1, None).is_err());
+    }
+
+    #[test]
+    fn test_persistence_through_log_and_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = HnswConfig { checkpoint_interval: 3, ..HnswConfig::default() };
+
+        {
+            let store = HnswVectorStorage::open(temp_dir.path(), config.clone()).unwrap();
+            store.store_embedding("a", &[1.0, 0.0], meta(&[("n", 1.into())])).unwrap();
+            store.store_embedding("b", &[0.0, 1.0], HashMap::new()).unwrap();
+            store.store_embedding("c", &[0.5, 0.5], HashMap::new()).unwrap(); // checkpoint
+            store.delete("b").unwrap(); // only in the log
+        }
+        assert!(temp_dir.path().join(SNAPSHOT_FILE).exists());
+
+        let store = HnswVectorStorage::open(temp_dir.path(), config).unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(store.contains("a"));
+        assert!(!store.contains("b"));
+
+        let results = store.search(&[1.0, 0.0], 1, None).unwrap();
+        assert_eq!(results[0].0, "a");
+        assert_eq!(results[0].2.get("n"), Some(&serde_json::json!(1)));
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats["pending_log_entries"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn test_recall_against_exact_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HnswVectorStorage::open(temp_dir.path(), HnswConfig::default()).unwrap();
+        let dim = 32;
+
+        let vectors: Vec<Vec<f32>> = (0..500).map(|i| pseudo_random_vector(i, dim)).collect();
+        for (i, v) in vectors.iter().enumerate() {
+            store.store_embedding(&i.to_string(), v, HashMap::new()).unwrap();
+        }
+
+        let mut hits = 0;
+        let queries = 20;
+        for q in 0..queries {
+            let query = normalize(&pseudo_random_vector(10_000 + q, dim));
+            let mut exact: Vec<(usize, f32)> = vectors
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i, distance(&query, &normalize(v))))
+                .collect();
+            exact.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let expected: HashSet<String> = exact.iter().take(10).map(|(i, _)| i.to_string()).collect();
+
+            let found = store.search(&query, 10, None).unwrap();
+            hits += found.iter().filter(|r| expected.contains(&r.0)).count();
+        }
+
+        let recall = hits as f32 / (queries as f32 * 10.0);
+        assert!(recall >= 0.9, "recall too low: {}", recall);
+    }
+
+    #[test]
+    fn test_compaction_keeps_live_vectors() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = HnswVectorStorage::open(temp_dir.path(), HnswConfig::default()).unwrap();
+
+        for i in 0..40 {
+            store.store_embedding(&i.to_string(), &pseudo_random_vector(i, 8), HashMap::new()).unwrap();
+        }
+        for i in 0..30 {
+            store.delete(&i.to_string()).unwrap();
+        }
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats["total_vectors"], serde_json::json!(10));
+        assert!(stats["deleted_vectors"].as_u64().unwrap() < 30);
+
+        let results = store.search(&pseudo_random_vector(35, 8), 10, None).unwrap();
+        assert_eq!(results.len(), 10);
+        assert_eq!(results[0].0, "35");
+    }
+}
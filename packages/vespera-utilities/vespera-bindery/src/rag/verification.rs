@@ -0,0 +1,589 @@
+//! # Code Reference Verification
+//!
+//! Checks generated code against the indexed project, to catch hallucinated
+//! APIs before they are trusted:
+//! - calls must resolve to a definition in the snippet, the project, a
+//!   language builtin, or an imported external/standard module
+//! - calls to project functions must match the arity of some definition
+//! - imported project modules, imported items and file path literals must exist
+//!
+//! References that cannot be judged from the project alone (methods on
+//! arbitrary receivers, items of third-party crates) are counted as
+//! unchecked rather than reported.
+
+use std::collections::HashSet;
+use std::path::Path;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::code_analyzer::{CallSite, CodeAnalysis, ProgrammingLanguage, SymbolInfo, SymbolKind, SymbolLocation};
+
+/// Outcome of checking a snippet against the project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodeReferenceReport {
+    pub language: Option<ProgrammingLanguage>,
+    /// References resolved to a project definition, with evidence
+    pub verified: Vec<VerifiedReference>,
+    pub unknown_symbols: Vec<UnknownSymbol>,
+    pub signature_mismatches: Vec<SignatureMismatch>,
+    pub missing_files: Vec<MissingFile>,
+    /// References that could not be judged from the project alone
+    pub unchecked: usize,
+}
+
+impl CodeReferenceReport {
+    /// No unknown symbols, signature mismatches or missing files
+    pub fn is_clean(&self) -> bool {
+        self.unknown_symbols.is_empty() && self.signature_mismatches.is_empty() && self.missing_files.is_empty()
+    }
+}
+
+/// How the snippet referred to something
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    Call,
+    Import,
+    FilePath,
+}
+
+/// A reference backed by a project definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiedReference {
+    pub name: String,
+    /// Line in the snippet
+    pub line_number: usize,
+    pub kind: ReferenceKind,
+    pub location: SymbolLocation,
+}
+
+/// A symbol the snippet uses that nothing defines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnknownSymbol {
+    pub name: String,
+    pub qualifier: Option<String>,
+    pub line_number: usize,
+    pub kind: ReferenceKind,
+    /// Similarly named project definitions
+    pub suggestions: Vec<SymbolLocation>,
+}
+
+/// A call whose argument count matches no definition of the callee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureMismatch {
+    pub name: String,
+    pub line_number: usize,
+    pub argument_count: usize,
+    pub candidates: Vec<SymbolLocation>,
+}
+
+/// A module or file path the snippet references that is not in the project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingFile {
+    pub path: String,
+    pub line_number: usize,
+    pub kind: ReferenceKind,
+}
+
+/// Language of a snippet and its code with any Markdown fence removed
+///
+/// The fence's info string wins; otherwise the language is guessed from
+/// keywords, and `Unknown` is returned when nothing matches.
+pub fn detect_language(snippet: &str) -> (ProgrammingLanguage, &str) {
+    let trimmed = snippet.trim();
+    if let Some(rest) = trimmed.strip_prefix("```") {
+        let (info, body) = rest.split_once('\n').unwrap_or((rest, ""));
+        let body = body.trim_end().strip_suffix("```").unwrap_or(body);
+        let language = match info.trim().to_lowercase().as_str() {
+            "rust" | "rs" => ProgrammingLanguage::Rust,
+            "python" | "py" => ProgrammingLanguage::Python,
+            "typescript" | "ts" | "tsx" => ProgrammingLanguage::TypeScript,
+            "javascript" | "js" | "jsx" => ProgrammingLanguage::JavaScript,
+            _ => guess_language(body),
+        };
+        return (language, body);
+    }
+    (guess_language(snippet), snippet)
+}
+
+fn guess_language(code: &str) -> ProgrammingLanguage {
+    let score = |markers: &[&str]| markers.iter().filter(|marker| code.contains(*marker)).count();
+    let candidates = [
+        (ProgrammingLanguage::Rust, score(&["fn ", "let ", "::", "impl ", "pub ", "-> ", "use "])),
+        (ProgrammingLanguage::Python, score(&["def ", "self.", "import ", "elif ", "None", "):\n"])),
+        (ProgrammingLanguage::TypeScript, score(&["function ", "const ", "=> ", "interface ", "export ", ": string", "this."])),
+    ];
+    candidates
+        .into_iter()
+        .filter(|(_, score)| *score > 0)
+        .max_by_key(|(_, score)| *score)
+        .map(|(language, _)| language)
+        .unwrap_or(ProgrammingLanguage::Unknown)
+}
+
+/// Check `snippet` (analyzed from `code`) against the project's analyses
+///
+/// `project_root` resolves file path literals in the snippet.
+pub fn verify_references(
+    snippet: &CodeAnalysis,
+    code: &str,
+    project: &[CodeAnalysis],
+    project_root: &Path,
+) -> CodeReferenceReport {
+    let language = snippet.language;
+    let mut report = CodeReferenceReport {
+        language: Some(language),
+        ..Default::default()
+    };
+    let files: Vec<String> = project.iter().map(|analysis| analysis.file_path.replace('\\', "/")).collect();
+    let definitions: Vec<(&CodeAnalysis, &SymbolInfo)> = project
+        .iter()
+        .flat_map(|analysis| analysis.symbols.iter().map(move |symbol| (analysis, symbol)))
+        .collect();
+
+    // Names whose calls need no project definition
+    let mut trusted: HashSet<String> = snippet.symbols.iter().map(|symbol| symbol.name.clone()).collect();
+    trusted.extend(local_bindings(code, language));
+    trusted.extend(builtins(language).iter().map(|name| name.to_string()));
+
+    for import in &snippet.imports {
+        let internal = snippet.dependencies.internal.contains(&import.module)
+            || (language == ProgrammingLanguage::Python && is_project_package(&import.module, &files));
+        if !internal {
+            trusted.extend(import.items.iter().cloned());
+            continue;
+        }
+
+        if !module_exists(&import.module, language, &files, false) {
+            report.missing_files.push(MissingFile {
+                path: import.module.clone(),
+                line_number: import.line_number,
+                kind: ReferenceKind::Import,
+            });
+            // Already reported; uses of its items would only repeat the finding
+            trusted.extend(import.items.iter().cloned());
+            continue;
+        }
+
+        for item in &import.items {
+            // Default and namespace imports bind a local name of the importer's choosing
+            let named = language == ProgrammingLanguage::Rust || language == ProgrammingLanguage::Python
+                || code.lines().nth(import.line_number.saturating_sub(1)).is_some_and(|line| line.contains('{'));
+            let submodule = module_exists(&format!("{}{}{}", import.module, separator(language), item), language, &files, true);
+            match definitions.iter().find(|(_, symbol)| &symbol.name == item) {
+                Some((analysis, symbol)) => report.verified.push(VerifiedReference {
+                    name: item.clone(),
+                    line_number: import.line_number,
+                    kind: ReferenceKind::Import,
+                    location: SymbolLocation::new(analysis, symbol),
+                }),
+                None if !named || submodule || item == "*" => report.unchecked += 1,
+                None => {
+                    report.unknown_symbols.push(UnknownSymbol {
+                        name: item.clone(),
+                        qualifier: Some(import.module.clone()),
+                        line_number: import.line_number,
+                        kind: ReferenceKind::Import,
+                        suggestions: suggestions(item, None, &definitions),
+                    });
+                    trusted.insert(item.clone());
+                }
+            }
+        }
+    }
+
+    for call in &snippet.calls {
+        check_call(call, language, &trusted, &definitions, &mut report);
+    }
+
+    report.missing_files.extend(missing_path_literals(code, language, &files, project_root));
+    report
+}
+
+fn separator(language: ProgrammingLanguage) -> &'static str {
+    match language {
+        ProgrammingLanguage::Rust => "::",
+        ProgrammingLanguage::Python => ".",
+        _ => "/",
+    }
+}
+
+fn check_call(
+    call: &CallSite,
+    language: ProgrammingLanguage,
+    trusted: &HashSet<String>,
+    definitions: &[(&CodeAnalysis, &SymbolInfo)],
+    report: &mut CodeReferenceReport,
+) {
+    let qualifier_root = call
+        .qualifier
+        .as_deref()
+        .map(|qualifier| qualifier.split(['.', ':']).next().unwrap_or(qualifier));
+    let qualifier_last = call
+        .qualifier
+        .as_deref()
+        .map(|qualifier| qualifier.rsplit(['.', ':']).next().unwrap_or(qualifier));
+
+    // Which definitions the call could resolve to, or None when it cannot be judged
+    let candidates: Option<Vec<&(&CodeAnalysis, &SymbolInfo)>> = match (qualifier_root, call.is_method) {
+        (None, _) if trusted.contains(&call.name) => return,
+        // Method on an arbitrary expression, e.g. `value.unwrap()`
+        (None, true) => None,
+        (None, false) => Some(definitions.iter().filter(|(_, s)| s.name == call.name && s.depth == 0).collect()),
+        // `self.method()` / `this.method()` / `Self::method()` must exist on some project or snippet type
+        (Some("self" | "this" | "Self"), _) if call.qualifier.as_deref().is_some_and(|q| !q.contains(['.', ':'])) => {
+            if trusted.contains(&call.name) {
+                return;
+            }
+            Some(definitions.iter().filter(|(_, s)| s.name == call.name && s.kind == SymbolKind::Method).collect())
+        }
+        // `crate::path::function()` names a project item directly
+        (Some("crate" | "super"), false) => Some(definitions.iter().filter(|(_, s)| s.name == call.name).collect()),
+        // `Type::function()` on a project type
+        (Some(_), false) if qualifier_last.is_some_and(|ty| definitions.iter().any(|(_, s)| s.name == ty && is_type(s.kind))) => {
+            Some(
+                definitions
+                    .iter()
+                    .filter(|(_, s)| s.name == call.name && s.parent.as_deref() == qualifier_last)
+                    .collect(),
+            )
+        }
+        _ => None,
+    };
+
+    let Some(candidates) = candidates else {
+        report.unchecked += 1;
+        return;
+    };
+
+    if candidates.is_empty() {
+        report.unknown_symbols.push(UnknownSymbol {
+            name: call.name.clone(),
+            qualifier: call.qualifier.clone(),
+            line_number: call.line_number,
+            kind: ReferenceKind::Call,
+            suggestions: suggestions(&call.name, qualifier_last, definitions),
+        });
+        return;
+    }
+
+    let via_path = !call.is_method && call.qualifier.is_some();
+    let matching = candidates.iter().find(|(_, symbol)| {
+        call.argument_count.is_none_or(|count| accepts(symbol, language, count, via_path))
+    });
+    match matching {
+        Some((analysis, symbol)) => report.verified.push(VerifiedReference {
+            name: call.name.clone(),
+            line_number: call.line_number,
+            kind: ReferenceKind::Call,
+            location: SymbolLocation::new(analysis, symbol),
+        }),
+        None => report.signature_mismatches.push(SignatureMismatch {
+            name: call.name.clone(),
+            line_number: call.line_number,
+            argument_count: call.argument_count.unwrap_or_default(),
+            candidates: candidates.iter().map(|(analysis, symbol)| SymbolLocation::new(analysis, symbol)).collect(),
+        }),
+    }
+}
+
+fn is_type(kind: SymbolKind) -> bool {
+    matches!(
+        kind,
+        SymbolKind::Struct | SymbolKind::Enum | SymbolKind::Trait | SymbolKind::Class | SymbolKind::Interface | SymbolKind::TypeAlias
+    )
+}
+
+/// Whether a definition can be called with `arguments` arguments
+fn accepts(symbol: &SymbolInfo, language: ProgrammingLanguage, arguments: usize, via_path: bool) -> bool {
+    // Only functions have a parameter list to check; constructors and types are accepted
+    if !matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method) {
+        return true;
+    }
+
+    let mut params: Vec<&str> = symbol.parameters.iter().map(String::as_str).collect();
+    let has_receiver = match (language, params.first()) {
+        (ProgrammingLanguage::Rust, Some(first)) => {
+            let first = first.trim_start_matches('&').trim_start_matches("mut ").trim_start_matches('\'');
+            first == "self" || first.starts_with("self:") || first.split_whitespace().any(|w| w == "self")
+        }
+        (ProgrammingLanguage::Python, Some(&first)) => symbol.kind == SymbolKind::Method && (first == "self" || first == "cls"),
+        _ => false,
+    };
+    if has_receiver {
+        params.remove(0);
+    }
+
+    let mut required = 0;
+    let mut maximum = Some(0usize);
+    for param in params {
+        let name = param.split([':', '=']).next().unwrap_or(param).trim();
+        if name == "*" || name == "/" {
+            continue;
+        }
+        if name.starts_with('*') || name.starts_with("...") {
+            maximum = None;
+            continue;
+        }
+        maximum = maximum.map(|m| m + 1);
+        if !(param.contains('=') || name.ends_with('?')) {
+            required += 1;
+        }
+    }
+
+    let fits = |count: usize| count >= required && maximum.is_none_or(|max| count <= max);
+    // `Type::method(receiver, ...)` passes the receiver explicitly
+    fits(arguments) || (has_receiver && via_path && arguments > 0 && fits(arguments - 1))
+}
+
+/// Project definitions with a similar name: the same name under another
+/// parent, a name within two edits, or one sharing a word (`count_tokens`
+/// for `estimate_tokens`)
+fn suggestions(name: &str, parent: Option<&str>, definitions: &[(&CodeAnalysis, &SymbolInfo)]) -> Vec<SymbolLocation> {
+    let words = name_words(name);
+    let mut scored: Vec<(usize, SymbolLocation)> = definitions
+        .iter()
+        .filter(|(_, symbol)| symbol.kind != SymbolKind::Impl)
+        .filter_map(|(analysis, symbol)| {
+            let distance = edit_distance(&name.to_lowercase(), &symbol.name.to_lowercase());
+            let shares_word = name_words(&symbol.name).iter().any(|word| words.contains(word));
+            let elsewhere = distance == 0 && parent != symbol.parent.as_deref();
+            (distance <= 2 || shares_word || elsewhere).then(|| (distance, SymbolLocation::new(analysis, symbol)))
+        })
+        .collect();
+    scored.sort_by_key(|(distance, _)| *distance);
+    scored.into_iter().take(5).map(|(_, location)| location).collect()
+}
+
+/// Lowercase words of a snake_case or camelCase name, ignoring short ones
+fn name_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for c in name.chars() {
+        if c == '_' || (c.is_uppercase() && !current.is_empty()) {
+            words.push(std::mem::take(&mut current));
+        }
+        if c != '_' {
+            current.push(c.to_ascii_lowercase());
+        }
+    }
+    words.push(current);
+    words.retain(|word| word.len() >= 4);
+    words
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Variables, parameters and closures bound in the snippet, which may be called
+fn local_bindings(code: &str, language: ProgrammingLanguage) -> Vec<String> {
+    let pattern = match language {
+        ProgrammingLanguage::Rust => r"\blet\s+(?:mut\s+)?([A-Za-z_]\w*)|[(,]\s*(?:mut\s+)?([A-Za-z_]\w*)\s*:",
+        ProgrammingLanguage::Python => r"(?m)^\s*([A-Za-z_]\w*)\s*=[^=]|[(,]\s*([A-Za-z_]\w*)\s*[:,)=]|\bas\s+([A-Za-z_]\w*)",
+        _ => r"\b(?:const|let|var)\s+([A-Za-z_$][\w$]*)|[(,]\s*([A-Za-z_$][\w$]*)\s*[:,)=]",
+    };
+    let Ok(regex) = Regex::new(pattern) else {
+        return Vec::new();
+    };
+    regex
+        .captures_iter(code)
+        .filter_map(|captures| captures.iter().skip(1).flatten().next().map(|m| m.as_str().to_string()))
+        .collect()
+}
+
+fn builtins(language: ProgrammingLanguage) -> &'static [&'static str] {
+    match language {
+        ProgrammingLanguage::Rust => &["Some", "Ok", "Err", "drop", "Box", "Default", "assert", "matches"],
+        ProgrammingLanguage::Python => &[
+            "print", "len", "range", "str", "int", "float", "bool", "list", "dict", "set", "tuple", "open",
+            "isinstance", "issubclass", "super", "enumerate", "zip", "map", "filter", "sorted", "reversed",
+            "min", "max", "sum", "abs", "any", "all", "getattr", "setattr", "hasattr", "type", "repr", "iter",
+            "next", "id", "hash", "round", "format", "input", "vars", "dir", "callable", "property",
+            "staticmethod", "classmethod", "object", "Exception", "ValueError", "TypeError", "KeyError",
+            "RuntimeError", "NotImplementedError",
+        ],
+        _ => &[
+            "parseInt", "parseFloat", "setTimeout", "clearTimeout", "setInterval", "clearInterval", "fetch",
+            "require", "Promise", "Array", "Object", "String", "Number", "Boolean", "Error", "Map", "Set",
+            "Date", "Symbol", "isNaN", "encodeURIComponent", "decodeURIComponent", "structuredClone", "super",
+            "import",
+        ],
+    }
+}
+
+/// Whether an absolute Python import names a package inside the project
+fn is_project_package(module: &str, files: &[String]) -> bool {
+    let root = module.split('.').next().unwrap_or(module);
+    !root.is_empty() && files.iter().any(|file| file.contains(&format!("/{}/", root)) || file.ends_with(&format!("/{}.py", root)))
+}
+
+/// Whether an internal module path resolves to a project file
+///
+/// Rust paths may end in items (`crate::a::Item`), so unless `exact` is set
+/// it is enough for a leading part of the path to be a module.
+fn module_exists(module: &str, language: ProgrammingLanguage, files: &[String], exact: bool) -> bool {
+    let candidates: Vec<String> = match language {
+        ProgrammingLanguage::Rust => {
+            let segments: Vec<&str> = module.split("::").filter(|s| !matches!(*s, "crate" | "self" | "super")).collect();
+            if exact {
+                let path = segments.join("/");
+                vec![format!("/{}.rs", path), format!("/{}/mod.rs", path)]
+            } else {
+                // A single segment may name an item re-exported at the crate root
+                return segments.len() <= 1
+                    || (1..=segments.len()).any(|n| {
+                        let path = segments[..n].join("/");
+                        files.iter().any(|file| file.ends_with(&format!("/{}.rs", path)) || file.contains(&format!("/{}/", path)))
+                    });
+            }
+        }
+        ProgrammingLanguage::Python => {
+            let path = module.trim_start_matches('.').replace('.', "/");
+            if path.is_empty() {
+                return true;
+            }
+            vec![format!("/{}.py", path), format!("/{}/__init__.py", path)]
+        }
+        _ => {
+            let path: Vec<&str> = module.split('/').filter(|s| !matches!(*s, "." | ".." | "")).collect();
+            let path = path.join("/");
+            let path = path.rsplit_once('.').filter(|(_, ext)| ext.len() <= 4).map(|(stem, _)| stem).unwrap_or(&path).to_string();
+            ["ts", "tsx", "js", "jsx", "mjs"]
+                .iter()
+                .flat_map(|ext| [format!("/{}.{}", path, ext), format!("/{}/index.{}", path, ext)])
+                .collect()
+        }
+    };
+    files.iter().any(|file| candidates.iter().any(|candidate| file.ends_with(candidate)))
+}
+
+/// String literals that look like project file paths but do not exist
+fn missing_path_literals(code: &str, language: ProgrammingLanguage, files: &[String], project_root: &Path) -> Vec<MissingFile> {
+    const EXTENSIONS: &[&str] = &[
+        "rs", "py", "ts", "tsx", "js", "jsx", "json", "yaml", "yml", "toml", "md", "txt", "csv", "sql", "html", "css",
+    ];
+    let Ok(literal) = Regex::new(r#"["']((?:\.{1,2}/)?(?:[\w.\-]+/)*[\w\-]+\.([A-Za-z0-9]{1,5}))["']"#) else {
+        return Vec::new();
+    };
+    let import_line = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with("import ") || line.starts_with("export ") || line.contains("require(")
+    };
+
+    let mut missing = Vec::new();
+    for (index, line) in code.lines().enumerate() {
+        if language != ProgrammingLanguage::Rust && import_line(line) {
+            continue;
+        }
+        for captures in literal.captures_iter(line) {
+            let path = &captures[1];
+            let has_directory = path.contains('/');
+            if !EXTENSIONS.contains(&&captures[2]) || (!has_directory && path.matches('.').count() > 1) {
+                continue;
+            }
+            let relative = path.trim_start_matches("./");
+            let suffix = relative.trim_start_matches("../");
+            let exists = project_root.join(relative).exists()
+                || files.iter().any(|file| file.ends_with(&format!("/{}", suffix)));
+            if !exists {
+                missing.push(MissingFile {
+                    path: path.to_string(),
+                    line_number: index + 1,
+                    kind: ReferenceKind::FilePath,
+                });
+            }
+        }
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::CodeAnalyzer;
+
+    fn analyze(code: &str, language: ProgrammingLanguage, path: &str) -> CodeAnalysis {
+        CodeAnalyzer::new().analyze_code(code, language, path.to_string()).unwrap().unwrap()
+    }
+
+    fn project() -> Vec<CodeAnalysis> {
+        vec![
+            analyze(
+                "pub struct Store { root: String }\n\nimpl Store {\n    pub fn open(root: &str) -> Self {\n        Self { root: root.into() }\n    }\n\n    pub fn get(&self, key: &str) -> Option<String> {\n        None\n    }\n}\n",
+                ProgrammingLanguage::Rust,
+                "/repo/src/storage/store.rs",
+            ),
+            analyze("pub fn slugify(title: &str) -> String {\n    title.to_lowercase()\n}\n", ProgrammingLanguage::Rust, "/repo/src/text.rs"),
+        ]
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let (language, code) = detect_language("```python\nprint('hi')\n```");
+        assert_eq!((language, code), (ProgrammingLanguage::Python, "print('hi')\n"));
+        assert_eq!(detect_language("fn main() {\n    let x = 1;\n}").0, ProgrammingLanguage::Rust);
+        assert_eq!(detect_language("export const f = (a: string) => a;").0, ProgrammingLanguage::TypeScript);
+    }
+
+    #[test]
+    fn test_reports_unknown_symbols_and_signatures() {
+        let snippet = r#"use crate::storage::store::Store;
+use crate::text::{slugify, titlecase};
+use std::fs;
+
+fn save(title: &str) {
+    let store = Store::open("/tmp");
+    let value = store.get("k").unwrap_or_default();
+    let slug = slugify(title, true);
+    let missing = Store::connect("db");
+    fs::write(format!("{}.txt", slug), value).ok();
+    normalize_title(title);
+    helper(slug);
+}
+
+fn helper(slug: String) {}
+"#;
+        let analysis = analyze(snippet, ProgrammingLanguage::Rust, "<snippet>");
+        let report = verify_references(&analysis, snippet, &project(), Path::new("/nonexistent"));
+
+        let unknown: Vec<&str> = report.unknown_symbols.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(unknown, vec!["titlecase", "connect", "normalize_title"]);
+        assert_eq!(report.unknown_symbols[1].qualifier.as_deref(), Some("Store"));
+
+        assert_eq!(report.signature_mismatches.len(), 1);
+        let mismatch = &report.signature_mismatches[0];
+        assert_eq!((mismatch.name.as_str(), mismatch.argument_count, mismatch.line_number), ("slugify", 2, 8));
+        assert_eq!(mismatch.candidates[0].file_path, "/repo/src/text.rs");
+
+        let open = report.verified.iter().find(|v| v.name == "open").unwrap();
+        assert_eq!((open.location.file_path.as_str(), open.location.line_number), ("/repo/src/storage/store.rs", 4));
+        assert!(report.missing_files.is_empty());
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_reports_missing_modules_and_paths() {
+        let snippet = "from app.models import User\nfrom app.helpers import slug\n\ndef load():\n    with open(\"config/settings.yaml\") as f:\n        return User(f.read())\n";
+        let project = vec![analyze("class User:\n    def __init__(self, data):\n        self.data = data\n", ProgrammingLanguage::Python, "/repo/app/models.py")];
+        let analysis = analyze(snippet, ProgrammingLanguage::Python, "<snippet>");
+
+        let report = verify_references(&analysis, snippet, &project, Path::new("/nonexistent"));
+
+        let missing: Vec<(&str, ReferenceKind)> = report.missing_files.iter().map(|m| (m.path.as_str(), m.kind)).collect();
+        assert_eq!(missing, vec![("app.helpers", ReferenceKind::Import), ("config/settings.yaml", ReferenceKind::FilePath)]);
+        assert!(report.unknown_symbols.is_empty());
+        assert!(report.signature_mismatches.is_empty());
+        assert!(report.verified.iter().any(|v| v.name == "User" && v.kind == ReferenceKind::Call));
+    }
+}
@@ -0,0 +1,399 @@
+//! Approval gate for destructive operations
+//!
+//! A role lists in [`Role::requires_approval`](super::Role::requires_approval)
+//! the [`GatedAction`]s its users may not carry out alone. When one of them
+//! tries, [`ApprovalGate::authorize`] parks the action as a pending
+//! [`ApprovalRequest`] and fails with [`BinderyError::ApprovalRequired`]. A
+//! different user whose role has the [`ToolGroup::Approval`] capability then
+//! approves or rejects it. Once approved, the requester retries the same
+//! action on the same resource and it goes through, once.
+//!
+//! Callers identify the user, never the role: the role is the one assigned
+//! to the user in the [`RoleManager`] (seeded from
+//! `BinderyConfig::user_roles`), and users without one are refused.
+//!
+//! Each step (request, approval or rejection, execution) is recorded in the
+//! audit log once one is attached with [`ApprovalGate::attach_audit_logger`],
+//! under the request's ID.
+//!
+//! Requests are only kept in memory. After a restart pending and approved
+//! requests are gone and the action has to be requested again; the audit log
+//! still has their history.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::{RoleManager, ToolGroup};
+use crate::errors::{BinderyError, BinderyResult};
+use crate::observability::{create_approval_event, log_security_event, AuditLogger, OperationOutcome, UserContext};
+use crate::UserId;
+
+/// Action a role can require approval for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatedAction {
+    /// Permanently deleting several Codices at once
+    BulkDelete,
+    /// Applying, rolling back or resetting database migrations
+    Migration,
+    /// Executing a task that deploys to production
+    ProductionDeploy,
+}
+
+impl GatedAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GatedAction::BulkDelete => "bulk_delete",
+            GatedAction::Migration => "migration",
+            GatedAction::ProductionDeploy => "production_deploy",
+        }
+    }
+}
+
+/// A user acting in a role
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub user_id: UserId,
+    pub role: String,
+}
+
+impl Actor {
+    pub fn new(user_id: impl Into<UserId>, role: impl Into<String>) -> Self {
+        Self { user_id: user_id.into(), role: role.into() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    /// Approved and carried out
+    Executed,
+}
+
+/// Who approved or rejected a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    pub decided_by: Actor,
+    pub decided_at: DateTime<Utc>,
+    pub comment: Option<String>,
+}
+
+/// A gated action waiting for, or past, its approval
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: Uuid,
+    pub action: GatedAction,
+    /// What the action applies to; a retry must name the same resource
+    pub resource: String,
+    pub description: String,
+    pub requested_by: Actor,
+    pub requested_at: DateTime<Utc>,
+    pub status: ApprovalStatus,
+    pub decision: Option<ApprovalDecision>,
+    pub executed_at: Option<DateTime<Utc>>,
+}
+
+/// Parks gated actions until another user approves them
+#[derive(Debug)]
+pub struct ApprovalGate {
+    role_manager: Arc<RoleManager>,
+    requests: RwLock<HashMap<Uuid, ApprovalRequest>>,
+    audit_logger: OnceLock<Arc<AuditLogger>>,
+}
+
+impl ApprovalGate {
+    pub fn new(role_manager: Arc<RoleManager>) -> Self {
+        Self {
+            role_manager,
+            requests: RwLock::new(HashMap::new()),
+            audit_logger: OnceLock::new(),
+        }
+    }
+
+    /// Record each approval step in `logger`
+    ///
+    /// Returns false, leaving the first in place, if one was already attached.
+    pub fn attach_audit_logger(&self, logger: Arc<AuditLogger>) -> bool {
+        self.audit_logger.set(logger).is_ok()
+    }
+
+    /// The user in `user` acting in their assigned role
+    ///
+    /// Fails with [`BinderyError::PermissionDenied`] for anonymous users and
+    /// users with no role assigned.
+    pub async fn actor(&self, user: &UserContext) -> BinderyResult<Actor> {
+        let user_id = user.user_id.clone()
+            .ok_or_else(|| BinderyError::PermissionDenied("Anonymous users cannot take gated actions".to_string()))?;
+        let role = self.role_manager.user_role(&user_id).await
+            .ok_or_else(|| BinderyError::PermissionDenied(format!("{} has no role assigned", user_id)))?;
+        Ok(Actor::new(user_id, role))
+    }
+
+    /// Whether `actor`'s role requires approval for `action`
+    pub async fn requires_approval(&self, actor: &Actor, action: GatedAction) -> BinderyResult<bool> {
+        let role = self.role_manager.get_role(&actor.role).await
+            .ok_or_else(|| BinderyError::NotFound(format!("Role '{}'", actor.role)))?;
+        Ok(role.requires_approval.contains(&action))
+    }
+
+    /// Let `user` carry out `action` on `resource`, or park it for approval
+    ///
+    /// Succeeds if the user's assigned role does not require approval, or by
+    /// using up an approved request of theirs for the same action and
+    /// resource. Otherwise fails with [`BinderyError::ApprovalRequired`],
+    /// naming the pending request, which is created unless one is already
+    /// waiting.
+    pub async fn authorize(
+        &self,
+        user: &UserContext,
+        action: GatedAction,
+        resource: impl Into<String>,
+        description: impl Into<String>,
+    ) -> BinderyResult<()> {
+        let actor = self.actor(user).await?;
+        if !self.requires_approval(&actor, action).await? {
+            return Ok(());
+        }
+        let resource = resource.into();
+
+        let mut requests = self.requests.write().await;
+        let matching = |status: ApprovalStatus| {
+            requests.values()
+                .filter(|request| request.status == status
+                    && request.action == action
+                    && request.resource == resource
+                    && request.requested_by.user_id == actor.user_id)
+                .min_by_key(|request| request.requested_at)
+                .map(|request| request.id)
+        };
+
+        let approved = matching(ApprovalStatus::Approved);
+        let pending = matching(ApprovalStatus::Pending);
+
+        if let Some(request) = approved.and_then(|id| requests.get_mut(&id)) {
+            request.status = ApprovalStatus::Executed;
+            request.executed_at = Some(Utc::now());
+            let request = request.clone();
+            drop(requests);
+            self.audit(&request, &actor, "execute", None).await;
+            return Ok(());
+        }
+        if let Some(id) = pending {
+            return Err(BinderyError::ApprovalRequired(id));
+        }
+
+        let request = ApprovalRequest {
+            id: Uuid::new_v4(),
+            action,
+            resource,
+            description: description.into(),
+            requested_by: actor.clone(),
+            requested_at: Utc::now(),
+            status: ApprovalStatus::Pending,
+            decision: None,
+            executed_at: None,
+        };
+        requests.insert(request.id, request.clone());
+        drop(requests);
+
+        tracing::info!(request_id = %request.id, action = request.action.as_str(), "Action parked for approval");
+        self.audit(&request, &actor, "request", None).await;
+        Err(BinderyError::ApprovalRequired(request.id))
+    }
+
+    /// Approve a pending request
+    pub async fn approve(&self, id: &Uuid, approver: &UserContext, comment: Option<String>) -> BinderyResult<ApprovalRequest> {
+        let approver = self.actor(approver).await?;
+        self.decide(id, &approver, ApprovalStatus::Approved, comment).await
+    }
+
+    /// Reject a pending request
+    pub async fn reject(&self, id: &Uuid, approver: &UserContext, reason: Option<String>) -> BinderyResult<ApprovalRequest> {
+        let approver = self.actor(approver).await?;
+        self.decide(id, &approver, ApprovalStatus::Rejected, reason).await
+    }
+
+    pub async fn get_request(&self, id: &Uuid) -> Option<ApprovalRequest> {
+        self.requests.read().await.get(id).cloned()
+    }
+
+    /// Requests waiting for a decision, oldest first
+    pub async fn pending_requests(&self) -> Vec<ApprovalRequest> {
+        let mut pending: Vec<_> = self.requests.read().await
+            .values()
+            .filter(|request| request.status == ApprovalStatus::Pending)
+            .cloned()
+            .collect();
+        pending.sort_by_key(|request| request.requested_at);
+        pending
+    }
+
+    async fn decide(
+        &self,
+        id: &Uuid,
+        approver: &Actor,
+        status: ApprovalStatus,
+        comment: Option<String>,
+    ) -> BinderyResult<ApprovalRequest> {
+        let role = self.role_manager.get_role(&approver.role).await
+            .ok_or_else(|| BinderyError::NotFound(format!("Role '{}'", approver.role)))?;
+        if !role.has_capability(&ToolGroup::Approval) {
+            return Err(BinderyError::PermissionDenied(
+                format!("Role '{}' cannot approve actions", approver.role)
+            ));
+        }
+
+        let mut requests = self.requests.write().await;
+        let request = requests.get_mut(id)
+            .ok_or_else(|| BinderyError::NotFound(format!("Approval request {}", id)))?;
+        if request.requested_by.user_id == approver.user_id {
+            return Err(BinderyError::PermissionDenied(
+                format!("{} cannot decide their own approval request", approver.user_id)
+            ));
+        }
+        if request.status != ApprovalStatus::Pending {
+            return Err(BinderyError::InvalidOperation(
+                format!("Approval request {} is already {:?}", id, request.status)
+            ));
+        }
+        request.status = status;
+        request.decision = Some(ApprovalDecision {
+            decided_by: approver.clone(),
+            decided_at: Utc::now(),
+            comment,
+        });
+        let request = request.clone();
+        drop(requests);
+
+        let step = if status == ApprovalStatus::Approved { "approve" } else { "reject" };
+        let comment = request.decision.as_ref().and_then(|decision| decision.comment.as_deref());
+        self.audit(&request, approver, step, comment).await;
+        Ok(request)
+    }
+
+    async fn audit(&self, request: &ApprovalRequest, actor: &Actor, step: &str, comment: Option<&str>) {
+        let Some(logger) = self.audit_logger.get() else {
+            return;
+        };
+        let user_context = UserContext {
+            user_id: Some(actor.user_id.clone()),
+            session_id: None,
+            source_ip: None,
+            user_agent: None,
+        };
+        let outcome = OperationOutcome {
+            success: true,
+            result_code: None,
+            error_message: None,
+            duration_ms: 0,
+            records_affected: None,
+        };
+        let event = create_approval_event(
+            user_context,
+            &request.id.to_string(),
+            request.action.as_str(),
+            &request.resource,
+            &actor.role,
+            step,
+            comment,
+            outcome,
+        );
+        log_security_event(Some(logger), event).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::role_management::Role;
+
+    async fn gate() -> ApprovalGate {
+        let role_manager = Arc::new(RoleManager::default());
+        let mut operator = Role::new("operator".to_string(), "Runs migrations".to_string(), vec![]);
+        operator.requires_approval = vec![GatedAction::Migration];
+        role_manager.add_role(operator).await.unwrap();
+        role_manager.add_role(Role::new("lead".to_string(), "Approves".to_string(), vec![ToolGroup::Approval])).await.unwrap();
+        for (user, role) in [("alice", "operator"), ("carol", "operator"), ("bob", "lead")] {
+            role_manager.assign_user_role(user, role).await.unwrap();
+        }
+        ApprovalGate::new(role_manager)
+    }
+
+    fn user(user_id: &str) -> UserContext {
+        UserContext {
+            user_id: Some(user_id.to_string()),
+            session_id: None,
+            source_ip: None,
+            user_agent: None,
+        }
+    }
+
+    fn approval_id(result: BinderyResult<()>) -> Uuid {
+        match result {
+            Err(BinderyError::ApprovalRequired(id)) => id,
+            other => panic!("expected a pending approval, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approved_action_runs_once() {
+        let gate = gate().await;
+        let alice = user("alice");
+        let bob = user("bob");
+
+        // Not gated for this role
+        gate.authorize(&alice, GatedAction::BulkDelete, "codices", "Delete").await.unwrap();
+
+        let id = approval_id(gate.authorize(&alice, GatedAction::Migration, "migrate_up:3", "Migrate").await);
+        // Retrying while pending points at the same request
+        assert_eq!(approval_id(gate.authorize(&alice, GatedAction::Migration, "migrate_up:3", "Migrate").await), id);
+        assert_eq!(gate.pending_requests().await.len(), 1);
+
+        gate.approve(&id, &bob, Some("ok".to_string())).await.unwrap();
+        // The approval covers only the approved resource
+        approval_id(gate.authorize(&alice, GatedAction::Migration, "reset", "Reset").await);
+        gate.authorize(&alice, GatedAction::Migration, "migrate_up:3", "Migrate").await.unwrap();
+        assert_eq!(gate.get_request(&id).await.unwrap().status, ApprovalStatus::Executed);
+
+        // Used up: the next attempt needs a new approval
+        assert_ne!(approval_id(gate.authorize(&alice, GatedAction::Migration, "migrate_up:3", "Migrate").await), id);
+    }
+
+    #[tokio::test]
+    async fn test_only_other_approvers_decide() {
+        let gate = gate().await;
+        let id = approval_id(gate.authorize(&user("alice"), GatedAction::Migration, "reset", "Reset").await);
+
+        let denied = |result: BinderyResult<ApprovalRequest>| matches!(result, Err(BinderyError::PermissionDenied(_)));
+        assert!(denied(gate.approve(&id, &user("carol"), None).await));
+        assert!(denied(gate.approve(&id, &user("alice"), None).await));
+
+        let request = gate.reject(&id, &user("bob"), Some("not today".to_string())).await.unwrap();
+        assert_eq!(request.status, ApprovalStatus::Rejected);
+        assert_eq!(request.decision.unwrap().decided_by, Actor::new("bob", "lead"));
+        assert!(gate.approve(&id, &user("bob"), None).await.is_err());
+        assert!(gate.pending_requests().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_roles_come_from_assignments() {
+        let gate = gate().await;
+        let denied = |result: BinderyResult<()>| matches!(result, Err(BinderyError::PermissionDenied(_)));
+
+        // Unknown and anonymous users cannot pick a role that skips approval
+        assert!(denied(gate.authorize(&user("mallory"), GatedAction::Migration, "reset", "Reset").await));
+        let anonymous = UserContext { user_id: None, ..user("") };
+        assert!(denied(gate.authorize(&anonymous, GatedAction::Migration, "reset", "Reset").await));
+
+        let id = approval_id(gate.authorize(&user("alice"), GatedAction::Migration, "reset", "Reset").await);
+        assert_eq!(gate.get_request(&id).await.unwrap().requested_by, Actor::new("alice", "operator"));
+        assert!(gate.role_manager.assign_user_role("alice", "missing").await.is_err());
+    }
+}
@@ -6,6 +6,7 @@
 use super::{Role, ToolGroup, FileRestrictions, ExecutionContext, RoleExecutionResult};
 use crate::codex::Codex;
 use crate::errors::{BinderyError, BinderyResult};
+use crate::UserId;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -16,6 +17,8 @@ use std::path::Path;
 #[derive(Debug)]
 pub struct RoleManager {
     roles: Arc<RwLock<HashMap<String, Role>>>,
+    /// The role each user acts in, by user ID
+    user_roles: Arc<RwLock<HashMap<UserId, String>>>,
 }
 
 impl RoleManager {
@@ -23,6 +26,7 @@ impl RoleManager {
     pub async fn new() -> BinderyResult<Self> {
         let manager = Self {
             roles: Arc::new(RwLock::new(HashMap::new())),
+            user_roles: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Load default roles
@@ -35,9 +39,15 @@ impl RoleManager {
     pub fn default() -> Self {
         Self {
             roles: Arc::new(RwLock::new(HashMap::new())),
+            user_roles: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Start with each user in `user_roles` acting in the role given
+    pub fn with_user_roles(self, user_roles: HashMap<UserId, String>) -> Self {
+        Self { user_roles: Arc::new(RwLock::new(user_roles)), ..self }
+    }
+
     /// Load roles from a YAML file
     pub async fn load_roles_from_file<P: AsRef<Path>>(&self, path: P) -> BinderyResult<()> {
         let content = tokio::fs::read_to_string(path).await
@@ -131,6 +141,20 @@ impl RoleManager {
         Ok(roles.remove(name).is_some())
     }
 
+    /// Make `user_id` act in the role `role_name`, which must exist
+    pub async fn assign_user_role(&self, user_id: impl Into<UserId>, role_name: &str) -> BinderyResult<()> {
+        if !self.role_exists(role_name).await {
+            return Err(BinderyError::NotFound(format!("Role '{}'", role_name)));
+        }
+        self.user_roles.write().await.insert(user_id.into(), role_name.to_string());
+        Ok(())
+    }
+
+    /// The role `user_id` acts in, if one was assigned
+    pub async fn user_role(&self, user_id: &str) -> Option<String> {
+        self.user_roles.read().await.get(user_id).cloned()
+    }
+
     // Private helper methods
 
     async fn load_default_roles(&self) -> BinderyResult<()> {
@@ -151,6 +175,7 @@ impl RoleManager {
                 },
                 execution_context: ExecutionContext::default(),
                 metadata: HashMap::new(),
+                requires_approval: Vec::new(),
             },
 
             Role {
@@ -189,6 +214,7 @@ impl RoleManager {
                     ..ExecutionContext::default()
                 },
                 metadata: HashMap::new(),
+                requires_approval: Vec::new(),
             },
 
             Role {
@@ -225,6 +251,7 @@ impl RoleManager {
                     ..ExecutionContext::default()
                 },
                 metadata: HashMap::new(),
+                requires_approval: Vec::new(),
             },
 
             Role {
@@ -266,6 +293,7 @@ impl RoleManager {
                     ..ExecutionContext::default()
                 },
                 metadata: HashMap::new(),
+                requires_approval: Vec::new(),
             },
 
             Role {
@@ -301,6 +329,7 @@ impl RoleManager {
                     ..ExecutionContext::default()
                 },
                 metadata: HashMap::new(),
+                requires_approval: Vec::new(),
             },
         ];
 
@@ -329,6 +358,12 @@ impl RoleManager {
             })
             .unwrap_or_default();
 
+        let requires_approval = match data.get("requires_approval") {
+            Some(actions) => serde_yaml::from_value(actions.clone())
+                .map_err(|e| BinderyError::InvalidInput(format!("Invalid requires_approval for role '{}': {}", name, e)))?,
+            None => Vec::new(),
+        };
+
         Ok(Role {
             name: name.to_string(),
             description,
@@ -336,6 +371,7 @@ impl RoleManager {
             file_restrictions: FileRestrictions::default(), // TODO: Parse from YAML
             execution_context: ExecutionContext::default(),  // TODO: Parse from YAML
             metadata: HashMap::new(),
+            requires_approval,
         })
     }
 
@@ -356,6 +392,7 @@ impl RoleManager {
             "security" => Some(ToolGroup::Security),
             "ai_llm" => Some(ToolGroup::AiLlm),
             "development" => Some(ToolGroup::Development),
+            "approval" => Some(ToolGroup::Approval),
             _ => None,
         }
     }
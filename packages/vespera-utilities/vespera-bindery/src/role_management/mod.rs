@@ -6,10 +6,12 @@
 pub mod manager;
 pub mod definitions;
 pub mod executor;
+pub mod approval;
 
 pub use manager::RoleManager;
 // Note: Role struct is defined below, so we don't import it from definitions
 pub use executor::RoleExecutor;
+pub use approval::{ApprovalGate, ApprovalRequest, ApprovalStatus, Actor, GatedAction};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -33,6 +35,8 @@ pub enum ToolGroup {
     Security,
     AiLlm,
     Development,
+    /// Approving actions that other roles require approval for
+    Approval,
 }
 
 /// Role definition with capabilities and restrictions
@@ -44,6 +48,9 @@ pub struct Role {
     pub file_restrictions: FileRestrictions,
     pub execution_context: ExecutionContext,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Actions this role's users need another user's approval for
+    #[serde(default)]
+    pub requires_approval: Vec<GatedAction>,
 }
 
 /// File access restrictions for roles
@@ -118,6 +125,7 @@ impl Role {
             file_restrictions: FileRestrictions::default(),
             execution_context: ExecutionContext::default(),
             metadata: HashMap::new(),
+            requires_approval: Vec::new(),
         }
     }
 
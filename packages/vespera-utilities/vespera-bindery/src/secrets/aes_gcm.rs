@@ -0,0 +1,374 @@
+// AES-256-GCM encryption backend implementation
+// Vault file encrypted with a key derived from a master passphrase (Argon2id)
+
+use super::{write_atomic, SecretBackend};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use zeroize::Zeroizing;
+
+/// Default location of the vault file, relative to the workspace
+pub const DEFAULT_VAULT_FILE: &str = ".vespera/secrets/secrets.vault";
+
+/// Environment variable holding the vault's master passphrase
+pub const PASSPHRASE_ENV: &str = "VESPERA_VAULT_PASSPHRASE";
+/// Environment variable overriding the vault file location
+pub const VAULT_FILE_ENV: &str = "VESPERA_VAULT_FILE";
+
+/// Vault file format version
+const FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Argon2id cost parameters used to derive the vault key
+///
+/// Defaults follow the OWASP recommendation (19 MiB, 2 passes, 1 lane).
+/// They are stored in the vault file, so changing them only affects vaults
+/// created or re-keyed afterwards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// AES-GCM backend configuration
+pub struct AesGcmConfig {
+    /// Vault file
+    pub path: PathBuf,
+    pub passphrase: Zeroizing<String>,
+    /// Key derivation cost for new vaults
+    pub kdf: KdfParams,
+}
+
+impl AesGcmConfig {
+    /// Configuration from `VESPERA_VAULT_PASSPHRASE`
+    ///
+    /// The file defaults to `.vespera/secrets/secrets.vault`; set
+    /// `VESPERA_VAULT_FILE` to use another.
+    pub fn from_env() -> Result<Self> {
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .map(Zeroizing::new)
+            .map_err(|_| anyhow!("AES-GCM backend needs {} to be set", PASSPHRASE_ENV))?;
+        let path = std::env::var_os(VAULT_FILE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_VAULT_FILE));
+        Ok(Self {
+            path,
+            passphrase,
+            kdf: KdfParams::default(),
+        })
+    }
+}
+
+/// Unencrypted part of the vault file, authenticated along with the secrets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct VaultHeader {
+    version: u32,
+    kdf: KdfParams,
+    /// Base64 Argon2id salt
+    salt: String,
+}
+
+/// On-disk vault file (JSON)
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    #[serde(flatten)]
+    header: VaultHeader,
+    /// Base64 96-bit nonce, fresh for every write
+    nonce: String,
+    /// Base64 ciphertext and GCM tag
+    ciphertext: String,
+}
+
+type Secrets = BTreeMap<String, Zeroizing<String>>;
+
+/// Key derived for a particular salt and cost
+struct DerivedKey {
+    header: VaultHeader,
+    key: Zeroizing<[u8; KEY_LEN]>,
+}
+
+struct State {
+    passphrase: Zeroizing<String>,
+    /// Argon2id is slow on purpose; reuse the key while the salt is unchanged
+    key: Option<DerivedKey>,
+    /// Decrypted secrets and the modification time of the file they came from
+    cache: Option<(Option<SystemTime>, Secrets)>,
+}
+
+/// AES-256-GCM encryption backend
+///
+/// Secrets are kept as a JSON map in a single vault file, encrypted with
+/// AES-256-GCM under a key derived from a master passphrase with Argon2id.
+/// The salt and cost parameters are stored alongside the ciphertext and
+/// authenticated with it, so any modification of the file is detected on
+/// read. Writes replace the file atomically.
+///
+/// The passphrase, derived key and decrypted secrets are zeroized when
+/// dropped. [`AesGcmBackend::rekey`] re-encrypts the vault under a new
+/// passphrase.
+///
+/// # Example
+/// ```rust,no_run
+/// use vespera_bindery::secrets::{AesGcmBackend, AesGcmConfig, SecretBackend};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let backend = AesGcmBackend::new(AesGcmConfig::from_env()?)?;
+/// backend.store_secret("anthropic/api_key", "sk-ant-...").await?;
+/// backend.rekey("a new master passphrase").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AesGcmBackend {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    path: PathBuf,
+    kdf: KdfParams,
+    state: Mutex<State>,
+}
+
+impl AesGcmBackend {
+    /// Create new AES-GCM backend
+    ///
+    /// The vault file is created on the first write.
+    pub fn new(config: AesGcmConfig) -> Result<Self> {
+        if config.passphrase.is_empty() {
+            anyhow::bail!("Vault passphrase must not be empty");
+        }
+        Ok(Self {
+            inner: Arc::new(Inner {
+                path: config.path,
+                kdf: config.kdf,
+                state: Mutex::new(State {
+                    passphrase: config.passphrase,
+                    key: None,
+                    cache: None,
+                }),
+            }),
+        })
+    }
+
+    /// Create AES-GCM backend configured from the environment (see [`AesGcmConfig::from_env`])
+    pub fn from_env() -> Result<Self> {
+        Self::new(AesGcmConfig::from_env()?)
+    }
+
+    /// Re-encrypt the vault under `new_passphrase`, with a fresh salt
+    pub async fn rekey(&self, new_passphrase: &str) -> Result<()> {
+        if new_passphrase.is_empty() {
+            anyhow::bail!("Vault passphrase must not be empty");
+        }
+        let new_passphrase = Zeroizing::new(new_passphrase.to_string());
+        self.with_state(move |inner, state| {
+            let key = inner.derive(&new_passphrase, inner.new_header())?;
+            let secrets = &state.cache.as_ref().expect("cache is loaded").1;
+            inner.save(&key, secrets)?;
+            state.passphrase = new_passphrase;
+            state.key = Some(key);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Run `f` on the blocking pool with the current secrets loaded
+    async fn with_state<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Inner, &mut State) -> Result<T> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            let mut state = inner.state.lock().map_err(|_| anyhow!("Vault state poisoned"))?;
+            let modified = inner.modified();
+            if !matches!(&state.cache, Some((cached, _)) if *cached == modified) {
+                let secrets = inner.load(&mut state)?;
+                state.cache = Some((modified, secrets));
+            }
+            let result = f(&inner, &mut state)?;
+            // A write gives the file a new modification time; the cache
+            // already holds what was written
+            if let Some((stamp, _)) = state.cache.as_mut() {
+                *stamp = inner.modified();
+            }
+            Ok(result)
+        })
+        .await?
+    }
+
+    /// Run `f` on the secrets, writing them back if it returns `true`
+    async fn update<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Secrets) -> bool + Send + 'static,
+    {
+        self.with_state(move |inner, state| {
+            let mut updated = state.cache.as_ref().expect("cache is loaded").1.clone();
+            if !f(&mut updated) {
+                return Ok(());
+            }
+            let key = match state.key.take() {
+                Some(key) => key,
+                None => inner.derive(&state.passphrase, inner.new_header())?,
+            };
+            let saved = inner.save(&key, &updated);
+            state.key = Some(key);
+            saved?;
+            state.cache.as_mut().expect("cache is loaded").1 = updated;
+            Ok(())
+        })
+        .await
+    }
+}
+
+impl Inner {
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Header for a new vault: current cost parameters and a random salt
+    fn new_header(&self) -> VaultHeader {
+        let mut salt = [0u8; SALT_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        VaultHeader {
+            version: FORMAT_VERSION,
+            kdf: self.kdf.clone(),
+            salt: STANDARD.encode(salt),
+        }
+    }
+
+    fn derive(&self, passphrase: &str, header: VaultHeader) -> Result<DerivedKey> {
+        let salt = STANDARD.decode(&header.salt).context("Invalid vault salt")?;
+        let params = argon2::Params::new(
+            header.kdf.memory_kib,
+            header.kdf.iterations,
+            header.kdf.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|e| anyhow!("Invalid vault key derivation parameters: {}", e))?;
+        let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        argon2
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| anyhow!("Failed to derive vault key: {}", e))?;
+        Ok(DerivedKey { header, key })
+    }
+
+    /// Decrypt the vault file; a missing file is an empty vault
+    fn load(&self, state: &mut State) -> Result<Secrets> {
+        let contents = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Secrets::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        };
+        let file: VaultFile = serde_json::from_slice(&contents).context("Vault file is corrupt")?;
+        if file.header.version != FORMAT_VERSION {
+            anyhow::bail!("Unsupported vault file version {}", file.header.version);
+        }
+
+        if !matches!(&state.key, Some(key) if key.header == file.header) {
+            state.key = Some(self.derive(&state.passphrase, file.header.clone())?);
+        }
+        let key = state.key.as_ref().expect("key was just derived");
+
+        let nonce = STANDARD.decode(&file.nonce).context("Vault file is corrupt")?;
+        if nonce.len() != 12 {
+            anyhow::bail!("Vault file is corrupt: bad nonce length");
+        }
+        let ciphertext = STANDARD.decode(&file.ciphertext).context("Vault file is corrupt")?;
+        let aad = serde_json::to_vec(&file.header)?;
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.key.as_ref()))
+                .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+                .map_err(|_| anyhow!("Failed to decrypt vault: wrong passphrase, or the file has been tampered with"))?,
+        );
+
+        let secrets: BTreeMap<String, String> = serde_json::from_slice(&plaintext).context("Vault contents are corrupt")?;
+        Ok(secrets.into_iter().map(|(k, v)| (k, Zeroizing::new(v))).collect())
+    }
+
+    /// Encrypt `secrets` under `key` and atomically replace the vault file
+    fn save(&self, key: &DerivedKey, secrets: &Secrets) -> Result<()> {
+        let plaintext = Zeroizing::new(serde_json::to_vec(
+            &secrets.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect::<BTreeMap<_, _>>(),
+        )?);
+        let aad = serde_json::to_vec(&key.header)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.key.as_ref()))
+            .encrypt(&nonce, Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| anyhow!("Failed to encrypt vault"))?;
+
+        let file = VaultFile {
+            header: key.header.clone(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        write_atomic(&self.path, &serde_json::to_vec_pretty(&file)?)
+    }
+}
+
+#[async_trait]
+impl SecretBackend for AesGcmBackend {
+    async fn store_secret(&self, key: &str, value: &str) -> Result<()> {
+        let key = key.to_string();
+        let value = Zeroizing::new(value.to_string());
+        self.update(move |secrets| {
+            secrets.insert(key, value);
+            true
+        })
+        .await
+    }
+
+    async fn get_secret(&self, key: &str) -> Result<String> {
+        let key = key.to_string();
+        self.with_state(move |_, state| {
+            state
+                .cache
+                .as_ref()
+                .and_then(|(_, secrets)| secrets.get(&key))
+                .map(|value| value.to_string())
+                .ok_or_else(|| anyhow!("Secret not found: {}", key))
+        })
+        .await
+    }
+
+    async fn delete_secret(&self, key: &str) -> Result<()> {
+        let key = key.to_string();
+        self.update(move |secrets| secrets.remove(&key).is_some()).await
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<String>> {
+        self.with_state(|_, state| {
+            Ok(state.cache.as_ref().map(|(_, secrets)| secrets.keys().cloned().collect()).unwrap_or_default())
+        })
+        .await
+    }
+
+    fn backend_name(&self) -> &str {
+        "aes-256-gcm"
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+}
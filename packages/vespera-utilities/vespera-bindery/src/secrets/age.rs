@@ -0,0 +1,284 @@
+// Age encryption backend implementation
+// Encrypted secret file for headless servers without an OS keyring
+
+use super::{write_atomic, SecretBackend};
+use age::secrecy::{ExposeSecret, SecretString};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Default location of the encrypted secret file, relative to the workspace
+pub const DEFAULT_SECRETS_FILE: &str = ".vespera/secrets/secrets.age";
+
+/// Environment variable naming an age identity file
+pub const IDENTITY_ENV: &str = "VESPERA_AGE_IDENTITY";
+/// Environment variable holding the store's passphrase
+pub const PASSPHRASE_ENV: &str = "VESPERA_AGE_PASSPHRASE";
+/// Environment variable overriding the secret file location
+pub const SECRETS_FILE_ENV: &str = "VESPERA_AGE_SECRETS_FILE";
+
+/// How the secret file is encrypted and decrypted
+pub enum AgeKey {
+    /// An age identity file (as written by `age-keygen`); secrets are
+    /// encrypted to its public keys
+    IdentityFile(PathBuf),
+    /// A passphrase, stretched with scrypt
+    Passphrase(SecretString),
+}
+
+/// Age backend configuration
+pub struct AgeConfig {
+    /// Encrypted secret file
+    pub path: PathBuf,
+    pub key: AgeKey,
+}
+
+impl AgeConfig {
+    /// Configuration from `VESPERA_AGE_IDENTITY` or `VESPERA_AGE_PASSPHRASE`
+    ///
+    /// The file defaults to `.vespera/secrets/secrets.age`; set
+    /// `VESPERA_AGE_SECRETS_FILE` to use another.
+    pub fn from_env() -> Result<Self> {
+        let key = if let Some(identity) = std::env::var_os(IDENTITY_ENV) {
+            AgeKey::IdentityFile(PathBuf::from(identity))
+        } else if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+            AgeKey::Passphrase(SecretString::from(passphrase))
+        } else {
+            anyhow::bail!("Age backend needs {} or {} to be set", IDENTITY_ENV, PASSPHRASE_ENV);
+        };
+        let path = std::env::var_os(SECRETS_FILE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SECRETS_FILE));
+        Ok(Self { path, key })
+    }
+}
+
+/// Age encryption backend
+///
+/// All secrets live in one ASCII-armored age file holding a JSON map of
+/// key to value. Every change rewrites the whole file atomically (written to
+/// a temporary file in the same directory, then renamed over it), so a crash
+/// never leaves a half-written store.
+///
+/// Decrypted secrets are kept in memory until the file changes on disk, as
+/// passphrase decryption deliberately takes about a second.
+///
+/// # Example
+/// ```rust,no_run
+/// use vespera_bindery::secrets::{AgeBackend, AgeConfig, AgeKey, SecretBackend};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let backend = AgeBackend::new(AgeConfig {
+///     path: ".vespera/secrets/secrets.age".into(),
+///     key: AgeKey::IdentityFile("/etc/vespera/age-identity.txt".into()),
+/// })?;
+/// backend.store_secret("anthropic/api_key", "sk-ant-...").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AgeBackend {
+    inner: Arc<Inner>,
+}
+
+type Secrets = BTreeMap<String, SecretString>;
+
+/// Decrypted secrets, with the modification time of the file they came from
+type Cache = Option<(Option<SystemTime>, Secrets)>;
+
+struct Inner {
+    path: PathBuf,
+    key: AgeKey,
+    /// scrypt work factor for passphrase encryption; calibrated to ~1s when unset
+    work_factor: Option<u8>,
+    cache: Mutex<Cache>,
+}
+
+impl AgeBackend {
+    /// Create new age backend
+    ///
+    /// The secret file is created on the first write.
+    pub fn new(config: AgeConfig) -> Result<Self> {
+        if let AgeKey::Passphrase(passphrase) = &config.key {
+            if passphrase.expose_secret().is_empty() {
+                anyhow::bail!("Age passphrase must not be empty");
+            }
+        }
+        Ok(Self {
+            inner: Arc::new(Inner {
+                path: config.path,
+                key: config.key,
+                work_factor: None,
+                cache: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// Create age backend configured from the environment (see [`AgeConfig::from_env`])
+    pub fn from_env() -> Result<Self> {
+        Self::new(AgeConfig::from_env()?)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_work_factor(mut self, log_n: u8) -> Self {
+        Arc::get_mut(&mut self.inner).expect("backend not shared yet").work_factor = Some(log_n);
+        self
+    }
+
+    /// Run `f` on the blocking pool with the current secrets
+    async fn with_secrets<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Inner, &mut Secrets) -> Result<T> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            let mut cache = inner.cache.lock().map_err(|_| anyhow!("Age secret cache poisoned"))?;
+            let modified = inner.modified();
+            if !matches!(&*cache, Some((cached, _)) if *cached == modified) {
+                *cache = Some((modified, inner.load()?));
+            }
+            let (stamp, secrets) = cache.as_mut().expect("cache was just filled");
+            let result = f(&inner, secrets)?;
+            // A write gives the file a new modification time; the cache
+            // already holds what was written
+            *stamp = inner.modified();
+            Ok(result)
+        })
+        .await?
+    }
+}
+
+impl Inner {
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Decrypt the secret file; a missing file is an empty store
+    fn load(&self) -> Result<BTreeMap<String, SecretString>> {
+        let encrypted = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        };
+
+        let decryptor = age::Decryptor::new_buffered(age::armor::ArmoredReader::new(&encrypted[..]))
+            .context("Secret file is not a valid age file")?;
+        let mut reader = match &self.key {
+            AgeKey::IdentityFile(path) => {
+                let identities = identity_file(path)?
+                    .into_identities()
+                    .context("Failed to read age identities")?;
+                decryptor.decrypt(identities.iter().map(|i| i.as_ref() as &dyn age::Identity))
+            }
+            AgeKey::Passphrase(passphrase) => {
+                let identity = age::scrypt::Identity::new(passphrase.clone());
+                decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity))
+            }
+        }
+        .context("Failed to decrypt secret file (wrong identity or passphrase?)")?;
+
+        let mut plaintext = String::new();
+        reader.read_to_string(&mut plaintext).context("Failed to decrypt secret file")?;
+        let secrets: BTreeMap<String, String> = serde_json::from_str(&plaintext).context("Secret file is corrupt")?;
+        Ok(secrets.into_iter().map(|(k, v)| (k, SecretString::from(v))).collect())
+    }
+
+    /// Encrypt `secrets` and atomically replace the secret file with them
+    fn save(&self, secrets: &BTreeMap<String, SecretString>) -> Result<()> {
+        let plaintext = SecretString::from(serde_json::to_string(
+            &secrets.iter().map(|(k, v)| (k.as_str(), v.expose_secret())).collect::<BTreeMap<_, _>>(),
+        )?);
+
+        let encryptor = match &self.key {
+            AgeKey::IdentityFile(path) => {
+                let recipients = identity_file(path)?
+                    .to_recipients()
+                    .context("Failed to derive age recipients")?;
+                age::Encryptor::with_recipients(recipients.iter().map(|r| r.as_ref() as &dyn age::Recipient))?
+            }
+            AgeKey::Passphrase(passphrase) => {
+                let mut recipient = age::scrypt::Recipient::new(passphrase.clone());
+                if let Some(log_n) = self.work_factor {
+                    recipient.set_work_factor(log_n);
+                }
+                age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient))?
+            }
+        };
+
+        let mut encrypted = Vec::new();
+        {
+            let armored = age::armor::ArmoredWriter::wrap_output(&mut encrypted, age::armor::Format::AsciiArmor)?;
+            let mut writer = encryptor.wrap_output(armored)?;
+            writer.write_all(plaintext.expose_secret().as_bytes())?;
+            writer.finish()?.finish()?;
+        }
+        write_atomic(&self.path, &encrypted)?;
+        Ok(())
+    }
+}
+
+fn identity_file(path: &Path) -> Result<age::IdentityFile<age::NoCallbacks>> {
+    age::IdentityFile::from_file(path.to_string_lossy().into_owned())
+        .with_context(|| format!("Failed to read age identity file {}", path.display()))
+}
+
+#[async_trait]
+impl SecretBackend for AgeBackend {
+    async fn store_secret(&self, key: &str, value: &str) -> Result<()> {
+        let key = key.to_string();
+        let value = SecretString::from(value.to_string());
+        self.with_secrets(move |inner, secrets| {
+            let mut updated = secrets.clone();
+            updated.insert(key, value);
+            inner.save(&updated)?;
+            *secrets = updated;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_secret(&self, key: &str) -> Result<String> {
+        let key = key.to_string();
+        self.with_secrets(move |_, secrets| {
+            secrets
+                .get(&key)
+                .map(|value| value.expose_secret().to_string())
+                .ok_or_else(|| anyhow!("Secret not found: {}", key))
+        })
+        .await
+    }
+
+    async fn delete_secret(&self, key: &str) -> Result<()> {
+        let key = key.to_string();
+        self.with_secrets(move |inner, secrets| {
+            if !secrets.contains_key(&key) {
+                return Ok(());
+            }
+            let mut updated = secrets.clone();
+            updated.remove(&key);
+            inner.save(&updated)?;
+            *secrets = updated;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<String>> {
+        self.with_secrets(|_, secrets| Ok(secrets.keys().cloned().collect())).await
+    }
+
+    fn backend_name(&self) -> &str {
+        "age-encryption"
+    }
+
+    async fn is_available(&self) -> bool {
+        match &self.inner.key {
+            AgeKey::IdentityFile(path) => identity_file(path).is_ok(),
+            AgeKey::Passphrase(_) => true,
+        }
+    }
+}
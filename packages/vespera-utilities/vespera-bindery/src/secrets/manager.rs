@@ -1,10 +1,37 @@
 // SecretManager facade for backend selection and vault reference resolution
 
-use super::{BackendType, KeyringBackend, SecretBackend};
-use anyhow::Result;
+use super::{secret_key, AesGcmBackend, AgeBackend, BackendType, KeyringBackend, SecretBackend};
+use anyhow::{Context, Result};
+use tracing::{debug, info};
+
+/// Environment variable selecting the backend ("keyring", "age" or "aes-gcm")
+pub const BACKEND_ENV: &str = "VESPERA_SECRET_BACKEND";
+
+/// Order in which [`SecretManager::detect`] probes backends
+pub const DETECTION_ORDER: [BackendType; 3] = [BackendType::Keyring, BackendType::Age, BackendType::AesGcm];
+
+impl BackendType {
+    /// Construct this backend with its default configuration
+    ///
+    /// Age and AES-GCM read their key and file location from the environment.
+    pub fn open(self) -> Result<Box<dyn SecretBackend>> {
+        Ok(match self {
+            BackendType::Keyring => Box::new(KeyringBackend::new("vespera-bindery")?),
+
+            BackendType::Age => Box::new(AgeBackend::from_env()?),
+
+            BackendType::AesGcm => Box::new(AesGcmBackend::from_env()?),
+        })
+    }
+}
 
 /// SecretManager provides a facade for secret storage with backend selection
 ///
+/// Secrets are stored in a primary backend. Reads that miss it fall back to
+/// any further backends in order, so secrets stored before switching
+/// backends stay readable. Individual keys, or key prefixes ending in `/`,
+/// can be pinned to a backend of their own.
+///
 /// # Example
 /// ```rust,no_run
 /// use vespera_bindery::secrets::{SecretManager, BackendType};
@@ -16,12 +43,15 @@ use anyhow::Result;
 /// manager.store_secret("anthropic/api_key", "sk-ant-...").await?;
 ///
 /// // Resolve vault reference (as provider would do)
-/// let api_key = manager.resolve("vault://anthropic/api_key").await?;
+/// let api_key = manager.resolve("secret://anthropic/api_key").await?;
 /// # Ok(())
 /// # }
 /// ```
 pub struct SecretManager {
     backend: Box<dyn SecretBackend>,
+    fallbacks: Vec<Box<dyn SecretBackend>>,
+    /// Key or `prefix/` pinned to a backend
+    overrides: Vec<(String, Box<dyn SecretBackend>)>,
 }
 
 impl SecretManager {
@@ -41,25 +71,139 @@ impl SecretManager {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn new(backend_type: BackendType) -> Result<Self> {
-        let backend: Box<dyn SecretBackend> = match backend_type {
-            BackendType::Keyring => Box::new(KeyringBackend::new("vespera-bindery")?),
+        Ok(Self::with_backend(backend_type.open()?))
+    }
 
-            BackendType::Age => {
-                anyhow::bail!("Age backend not yet implemented (see Issue #86)")
+    /// Create SecretManager using every backend that works on this system
+    ///
+    /// Backends are probed in [`DETECTION_ORDER`] (keyring, age, aes-gcm);
+    /// the first available one becomes the primary backend and the rest are
+    /// read fallbacks. Age and AES-GCM count only when configured through the
+    /// environment, so a headless machine without libsecret uses whichever
+    /// of them is set up.
+    ///
+    /// # Errors
+    /// Returns error if no backend is available
+    pub async fn detect() -> Result<Self> {
+        let mut available = Vec::new();
+        for backend_type in DETECTION_ORDER {
+            match backend_type.open() {
+                Ok(backend) if backend.is_available().await => available.push(backend),
+                Ok(backend) => debug!("Secret backend {} is not available", backend.backend_name()),
+                Err(e) => debug!("Secret backend {:?} is not configured: {:#}", backend_type, e),
             }
+        }
 
-            BackendType::AesGcm => {
-                anyhow::bail!("AES-GCM backend not yet implemented (see Issue #87)")
-            }
+        let mut available = available.into_iter();
+        let Some(backend) = available.next() else {
+            anyhow::bail!(
+                "No secret backend is available: the system keyring is unreachable and neither \
+                 VESPERA_AGE_IDENTITY, VESPERA_AGE_PASSPHRASE nor VESPERA_VAULT_PASSPHRASE is set"
+            );
         };
+        info!("Using secret backend {}", backend.backend_name());
+        Ok(Self {
+            backend,
+            fallbacks: available.collect(),
+            overrides: Vec::new(),
+        })
+    }
 
-        Ok(Self { backend })
+    /// Create SecretManager with the backend named by `VESPERA_SECRET_BACKEND`
+    ///
+    /// Detects the available backends (see [`SecretManager::detect`]) when
+    /// the variable is unset.
+    pub async fn from_env() -> Result<Self> {
+        match std::env::var(BACKEND_ENV) {
+            Ok(name) => Self::new(name.parse().with_context(|| format!("Invalid {}", BACKEND_ENV))?),
+            Err(_) => Self::detect().await,
+        }
+    }
+
+    /// Create SecretManager around an already constructed backend
+    pub fn with_backend(backend: Box<dyn SecretBackend>) -> Self {
+        Self {
+            backend,
+            fallbacks: Vec::new(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Add a backend that reads fall back to when the secret is not found
+    pub fn with_fallback(mut self, backend: Box<dyn SecretBackend>) -> Self {
+        self.fallbacks.push(backend);
+        self
+    }
+
+    /// Keep `key` in `backend` instead of the primary backend
+    ///
+    /// A key ending in `/` applies to every key under that prefix; the
+    /// longest matching override wins.
+    pub fn with_key_backend(mut self, key: impl Into<String>, backend: Box<dyn SecretBackend>) -> Self {
+        self.overrides.push((key.into(), backend));
+        self
+    }
+
+    /// Copy every secret from one backend to another, verifying each copy
+    ///
+    /// The source must support listing, which the system keyring does not;
+    /// use [`SecretManager::migrate_from`] with explicit keys for it.
+    ///
+    /// # Returns
+    /// The keys that were migrated
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use vespera_bindery::secrets::{SecretManager, BackendType};
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let from = BackendType::Age.open()?;
+    /// let to = BackendType::AesGcm.open()?;
+    /// SecretManager::migrate_secrets(from.as_ref(), to.as_ref()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn migrate_secrets(from: &dyn SecretBackend, to: &dyn SecretBackend) -> Result<Vec<String>> {
+        let keys = from
+            .list_secrets()
+            .await
+            .with_context(|| format!("Cannot list secrets in {}", from.backend_name()))?;
+        copy_secrets(from, to, &keys, false).await
+    }
+
+    /// Copy secrets from another backend into the primary one
+    ///
+    /// Keys are given explicitly because the system keyring cannot list its
+    /// entries. Keys missing from `source` are skipped; each copy is read
+    /// back to verify it, and with `delete_source` the secret is then removed
+    /// from `source`.
+    ///
+    /// # Returns
+    /// The keys that were migrated
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use vespera_bindery::secrets::{SecretManager, BackendType, KeyringBackend};
+    /// # async fn example() -> anyhow::Result<()> {
+    /// let keyring = KeyringBackend::new("vespera-bindery")?;
+    /// let manager = SecretManager::new(BackendType::Age)?;
+    /// manager.migrate_from(&keyring, &["anthropic/api_key".to_string()], true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn migrate_from(
+        &self,
+        source: &dyn SecretBackend,
+        keys: &[String],
+        delete_source: bool,
+    ) -> Result<Vec<String>> {
+        copy_secrets(source, self.backend.as_ref(), keys, delete_source).await
     }
 
     /// Resolve vault reference to actual secret value
     ///
     /// # Arguments
-    /// * `reference` - Vault reference in format "vault://provider/key_name"
+    /// * `reference` - Reference in format "secret://provider/key_name"
+    ///   (the older "vault://provider/key_name" is also accepted)
     ///
     /// # Returns
     /// Actual secret value
@@ -73,69 +217,120 @@ impl SecretManager {
     /// # use vespera_bindery::secrets::{SecretManager, BackendType};
     /// # async fn example() -> anyhow::Result<()> {
     /// # let manager = SecretManager::new(BackendType::Keyring)?;
-    /// let api_key = manager.resolve("vault://anthropic/api_key").await?;
+    /// let api_key = manager.resolve("secret://anthropic/api_key").await?;
     /// // api_key = "sk-ant-abc123..."
     /// # Ok(())
     /// # }
     /// ```
     pub async fn resolve(&self, reference: &str) -> Result<String> {
-        // Validate vault:// prefix
-        const VAULT_PREFIX: &str = "vault://";
-
-        if !reference.starts_with(VAULT_PREFIX) {
+        // Validate secret:// (or vault://) prefix
+        let Some(key) = secret_key(reference) else {
             anyhow::bail!(
-                "Invalid vault reference format: '{}'. Expected format: 'vault://provider/key_name'",
+                "Invalid vault reference format: '{}'. Expected format: 'secret://provider/key_name'",
                 reference
             );
-        }
-
-        // Extract the key by removing the vault:// prefix
-        let key = &reference[VAULT_PREFIX.len()..];
+        };
 
         if key.is_empty() {
             anyhow::bail!(
-                "Invalid vault reference: '{}'. Key cannot be empty after 'vault://'",
+                "Invalid vault reference: '{}'. Key cannot be empty after the scheme",
                 reference
             );
         }
 
         // Retrieve the secret using the key
-        self.backend.get_secret(key).await
+        self.get_secret(key).await
     }
 
-    /// Store a secret (delegates to backend)
+    /// Backend pinned to `key`, if any
+    fn override_for(&self, key: &str) -> Option<&dyn SecretBackend> {
+        self.overrides
+            .iter()
+            .filter(|(pattern, _)| pattern == key || (pattern.ends_with('/') && key.starts_with(pattern.as_str())))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, backend)| backend.as_ref())
+    }
+
+    /// Store a secret (in its override backend, else the primary one)
     ///
     /// # Arguments
     /// * `key` - Hierarchical key (e.g., "anthropic/api_key")
     /// * `value` - Secret value
     pub async fn store_secret(&self, key: &str, value: &str) -> Result<()> {
-        self.backend.store_secret(key, value).await
+        self.override_for(key).unwrap_or(self.backend.as_ref()).store_secret(key, value).await
     }
 
-    /// Retrieve a secret (delegates to backend)
+    /// Retrieve a secret
+    ///
+    /// Keys without an override are looked up in the primary backend, then
+    /// in each fallback.
     ///
     /// # Arguments
     /// * `key` - Hierarchical key to retrieve
     pub async fn get_secret(&self, key: &str) -> Result<String> {
-        self.backend.get_secret(key).await
+        if let Some(backend) = self.override_for(key) {
+            return backend.get_secret(key).await;
+        }
+        let primary_error = match self.backend.get_secret(key).await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        for fallback in &self.fallbacks {
+            if let Ok(value) = fallback.get_secret(key).await {
+                debug!("Secret '{}' found in fallback backend {}", key, fallback.backend_name());
+                return Ok(value);
+            }
+        }
+        Err(primary_error)
     }
 
-    /// Delete a secret (delegates to backend)
+    /// Delete a secret
+    ///
+    /// Keys without an override are also deleted from the fallbacks, so an
+    /// older copy can't reappear.
     ///
     /// # Arguments
     /// * `key` - Hierarchical key to delete
     pub async fn delete_secret(&self, key: &str) -> Result<()> {
-        self.backend.delete_secret(key).await
+        if let Some(backend) = self.override_for(key) {
+            return backend.delete_secret(key).await;
+        }
+        self.backend.delete_secret(key).await?;
+        for fallback in &self.fallbacks {
+            fallback.delete_secret(key).await?;
+        }
+        Ok(())
     }
 
-    /// List all secret keys (delegates to backend)
+    /// List all secret keys across backends
     ///
-    /// Note: May not be supported by all backends
+    /// Backends that cannot list (such as the system keyring) are skipped;
+    /// fails only if none of them can.
     pub async fn list_secrets(&self) -> Result<Vec<String>> {
-        self.backend.list_secrets().await
+        let backends = std::iter::once(&self.backend)
+            .chain(&self.fallbacks)
+            .chain(self.overrides.iter().map(|(_, backend)| backend));
+        let mut keys = std::collections::BTreeSet::new();
+        let mut first_error = None;
+        let mut listed = false;
+        for backend in backends {
+            match backend.list_secrets().await {
+                Ok(found) => {
+                    listed = true;
+                    keys.extend(found);
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if !listed => Err(e),
+            _ => Ok(keys.into_iter().collect()),
+        }
     }
 
-    /// Get backend name
+    /// Get primary backend name
     pub fn backend_name(&self) -> &str {
         self.backend.backend_name()
     }
@@ -145,3 +340,47 @@ impl SecretManager {
         self.backend.is_available().await
     }
 }
+
+/// Copy `keys` from `source` to `target`, reading each copy back to verify it
+async fn copy_secrets(
+    source: &dyn SecretBackend,
+    target: &dyn SecretBackend,
+    keys: &[String],
+    delete_source: bool,
+) -> Result<Vec<String>> {
+    let mut migrated = Vec::new();
+    for key in keys {
+        let value = match source.get_secret(key).await {
+            Ok(value) => value,
+            Err(e) => {
+                debug!("Not migrating '{}' from {}: {:#}", key, source.backend_name(), e);
+                continue;
+            }
+        };
+        target
+            .store_secret(key, &value)
+            .await
+            .with_context(|| format!("Failed to store migrated secret '{}'", key))?;
+        let stored = target
+            .get_secret(key)
+            .await
+            .with_context(|| format!("Failed to read back migrated secret '{}'", key))?;
+        if stored != value {
+            anyhow::bail!("Migrated secret '{}' does not match in {}", key, target.backend_name());
+        }
+        if delete_source {
+            source
+                .delete_secret(key)
+                .await
+                .with_context(|| format!("Migrated '{}' but failed to delete it from {}", key, source.backend_name()))?;
+        }
+        migrated.push(key.clone());
+    }
+    info!(
+        "Migrated {} secret(s) from {} to {}",
+        migrated.len(),
+        source.backend_name(),
+        target.backend_name()
+    );
+    Ok(migrated)
+}
@@ -1,19 +1,28 @@
 // Secret storage module with pluggable backends
 // Implements ADR-018: Secret Storage Architecture
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
 use async_trait::async_trait;
 
 // Submodules
+mod aes_gcm;
+mod age;
 mod keyring;
 mod manager;
+mod reference;
 
 #[cfg(test)]
 mod tests;
 
 // Public exports
+pub use aes_gcm::{AesGcmBackend, AesGcmConfig, KdfParams};
+pub use age::{AgeBackend, AgeConfig, AgeKey};
 pub use keyring::KeyringBackend;
 pub use manager::SecretManager;
+pub use reference::{is_secret_reference, redact_field, redact_json, secret_key, SecretRef, REDACTED, SECRET_SCHEME};
 
 // ============================================================================
 // SecretBackend Trait (from ADR-018)
@@ -107,18 +116,56 @@ pub trait SecretBackend: Send + Sync {
 ///
 /// See ADR-018 for implementation priorities:
 /// 1. Keyring (Phase 17.5) - OS-native, most secure
-/// 2. Age (Future) - For headless servers
-/// 3. AesGcm (Future) - For maximum control
+/// 2. Age - For headless servers
+/// 3. AesGcm - For maximum control
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BackendType {
     /// System keyring (Linux: libsecret, macOS: Keychain, Windows: Credential Manager)
     Keyring,
 
-    /// Age encryption (not yet implemented, see Issue #86)
-    #[allow(dead_code)]
+    /// Age-encrypted file, for servers without a keyring (see [`AgeConfig::from_env`])
     Age,
 
-    /// AES-256-GCM encryption (not yet implemented, see Issue #87)
-    #[allow(dead_code)]
+    /// AES-256-GCM vault file keyed by a passphrase (see [`AesGcmConfig::from_env`])
     AesGcm,
 }
+
+impl FromStr for BackendType {
+    type Err = anyhow::Error;
+
+    /// Parse the `secret_backend` setting ("keyring", "age" or "aes-gcm")
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "keyring" => Ok(BackendType::Keyring),
+            "age" => Ok(BackendType::Age),
+            "aes-gcm" | "aesgcm" => Ok(BackendType::AesGcm),
+            other => anyhow::bail!("Unknown secret backend: '{}' (expected keyring, age or aes-gcm)", other),
+        }
+    }
+}
+
+// ============================================================================
+// File Helpers
+// ============================================================================
+
+/// Replace `path` with `contents` atomically
+///
+/// The data is written and synced to a temporary file in the same directory,
+/// which is then renamed over `path`, so readers and crashes only ever see the
+/// old or the new file. The file is readable by its owner only.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    // NamedTempFile is created with owner-only permissions
+    let mut file = tempfile::NamedTempFile::new_in(dir).context("Failed to create temporary secret file")?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+    file.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
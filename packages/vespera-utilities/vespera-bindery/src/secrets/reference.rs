@@ -0,0 +1,162 @@
+// Secret references in configuration
+// Config values written as `secret://provider/key_name` are resolved through
+// SecretManager when used, and never written back out or logged in the clear
+
+use super::SecretManager;
+use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// Scheme of a secret reference
+pub const SECRET_SCHEME: &str = "secret://";
+/// Older scheme for the same references, still accepted
+pub const VAULT_SCHEME: &str = "vault://";
+
+/// Replacement text for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field names whose values are treated as secrets when redacting
+const SENSITIVE_FIELDS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "authorization",
+    "credential",
+    "encryption_key",
+    "passphrase",
+    "password",
+    "secret",
+    "token",
+    "webhook_url",
+];
+
+/// Key named by a `secret://` (or `vault://`) reference
+pub fn secret_key(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(SECRET_SCHEME)
+        .or_else(|| value.strip_prefix(VAULT_SCHEME))
+}
+
+/// Whether `value` is a secret reference rather than a literal
+pub fn is_secret_reference(value: &str) -> bool {
+    secret_key(value).is_some()
+}
+
+/// A configuration value that may refer to a stored secret
+///
+/// Holds only what was configured: a reference is resolved on each call to
+/// [`SecretRef::resolve`], so the secret itself is never kept in (or
+/// serialized from) the configuration. Literal values are hidden from
+/// `Debug` and `Display`.
+///
+/// # Example
+/// ```rust,no_run
+/// use vespera_bindery::secrets::{SecretManager, SecretRef};
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let api_key = SecretRef::parse("secret://anthropic/api_key");
+/// let secrets = SecretManager::from_env().await?;
+/// let value = api_key.resolve(&secrets).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    /// Reference to a secret stored under this key
+    Stored(String),
+    /// Value written directly in the configuration
+    Literal(String),
+}
+
+impl SecretRef {
+    pub fn parse(value: impl Into<String>) -> Self {
+        let value = value.into();
+        match secret_key(&value) {
+            Some(key) => SecretRef::Stored(key.to_string()),
+            None => SecretRef::Literal(value),
+        }
+    }
+
+    /// Key of the referenced secret; None for a literal
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            SecretRef::Stored(key) => Some(key),
+            SecretRef::Literal(_) => None,
+        }
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(self, SecretRef::Literal(_))
+    }
+
+    /// The value to use: the stored secret, or the literal
+    pub async fn resolve(&self, secrets: &SecretManager) -> Result<String> {
+        match self {
+            SecretRef::Stored(key) => secrets.get_secret(key).await,
+            SecretRef::Literal(value) => Ok(value.clone()),
+        }
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretRef::Stored(key) => write!(f, "{}{}", SECRET_SCHEME, key),
+            SecretRef::Literal(_) => f.write_str(REDACTED),
+        }
+    }
+}
+
+impl fmt::Debug for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretRef({})", self)
+    }
+}
+
+impl Serialize for SecretRef {
+    /// Written back exactly as configured; a reference stays a reference
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            SecretRef::Stored(key) => serializer.serialize_str(&format!("{}{}", SECRET_SCHEME, key)),
+            SecretRef::Literal(value) => serializer.serialize_str(value),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Ok(SecretRef::parse(String::deserialize(deserializer)?))
+    }
+}
+
+/// Replace secret values in `value` with [`REDACTED`], in place
+///
+/// Strings under sensitive field names (API keys, tokens, passwords, webhook
+/// URLs, ...) are redacted unless they are secret references, which are safe
+/// to show. Nested objects and arrays are redacted recursively.
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                redact_field(name, field);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Redact `value`, found under the field `name`, in place (see [`redact_json`])
+pub fn redact_field(name: &str, value: &mut Value) {
+    match value {
+        Value::String(s) if is_sensitive(name) && !is_secret_reference(s) => {
+            *value = Value::String(REDACTED.to_string());
+        }
+        _ => redact_json(value),
+    }
+}
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase().replace('-', "_");
+    SENSITIVE_FIELDS.iter().any(|field| name.contains(field))
+}
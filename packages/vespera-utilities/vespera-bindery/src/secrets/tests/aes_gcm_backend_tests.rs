@@ -0,0 +1,118 @@
+// AesGcmBackend tests
+// Cheap Argon2 parameters keep these fast; the file format is unaffected
+
+use crate::secrets::{AesGcmBackend, AesGcmConfig, KdfParams, SecretBackend};
+use anyhow::Result;
+use std::path::Path;
+use tempfile::TempDir;
+use zeroize::Zeroizing;
+
+fn open(path: &Path, passphrase: &str) -> Result<AesGcmBackend> {
+    AesGcmBackend::new(AesGcmConfig {
+        path: path.to_path_buf(),
+        passphrase: Zeroizing::new(passphrase.to_string()),
+        kdf: KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 },
+    })
+}
+
+// ============================================================================
+// Test Suite 1: Basic SecretBackend Operations
+// ============================================================================
+
+#[tokio::test]
+async fn test_store_get_list_delete() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("secrets/secrets.vault");
+    let backend = open(&path, "master passphrase")?;
+    assert_eq!(backend.backend_name(), "aes-256-gcm");
+
+    // A missing file is an empty vault
+    assert!(backend.list_secrets().await?.is_empty());
+
+    backend.store_secret("openai/api_key", "sk-openai").await?;
+    backend.store_secret("anthropic/api_key", "sk-ant-plaintext").await?;
+    assert_eq!(backend.get_secret("anthropic/api_key").await?, "sk-ant-plaintext");
+    assert_eq!(backend.list_secrets().await?, vec!["anthropic/api_key", "openai/api_key"]);
+
+    backend.delete_secret("openai/api_key").await?;
+    backend.delete_secret("openai/api_key").await?;
+    assert!(backend.get_secret("openai/api_key").await.is_err());
+
+    let contents = std::fs::read_to_string(&path)?;
+    assert!(!contents.contains("sk-ant-plaintext"));
+    assert!(contents.contains("\"salt\""));
+
+    // Another instance with the same passphrase reads the vault
+    assert_eq!(open(&path, "master passphrase")?.get_secret("anthropic/api_key").await?, "sk-ant-plaintext");
+    assert!(open(&path, "wrong passphrase")?.get_secret("anthropic/api_key").await.is_err());
+    assert!(open(&path, "").is_err());
+    Ok(())
+}
+
+// ============================================================================
+// Test Suite 2: Tamper Detection
+// ============================================================================
+
+#[tokio::test]
+async fn test_tampering_is_detected() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("secrets.vault");
+    open(&path, "master passphrase")?.store_secret("anthropic/api_key", "sk-ant").await?;
+    let original: serde_json::Value = serde_json::from_slice(&std::fs::read(&path)?)?;
+
+    let tamper = |edit: &dyn Fn(&mut serde_json::Value)| -> Result<()> {
+        let mut file = original.clone();
+        edit(&mut file);
+        std::fs::write(&path, serde_json::to_vec(&file)?)?;
+        Ok(())
+    };
+
+    // Flipped ciphertext bit
+    tamper(&|file| {
+        let mut ciphertext = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            file["ciphertext"].as_str().unwrap(),
+        )
+        .unwrap();
+        ciphertext[0] ^= 1;
+        file["ciphertext"] = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, ciphertext).into();
+    })?;
+    let err = open(&path, "master passphrase")?.get_secret("anthropic/api_key").await.unwrap_err();
+    assert!(err.to_string().contains("tampered"));
+
+    // Swapped nonce
+    tamper(&|file| file["nonce"] = "AAAAAAAAAAAAAAAA".into())?;
+    assert!(open(&path, "master passphrase")?.get_secret("anthropic/api_key").await.is_err());
+
+    // The untouched file still decrypts
+    tamper(&|_| {})?;
+    assert_eq!(open(&path, "master passphrase")?.get_secret("anthropic/api_key").await?, "sk-ant");
+    Ok(())
+}
+
+// ============================================================================
+// Test Suite 3: Re-keying
+// ============================================================================
+
+#[tokio::test]
+async fn test_rekey() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("secrets.vault");
+    let backend = open(&path, "old passphrase")?;
+    backend.store_secret("anthropic/api_key", "sk-ant").await?;
+    let old_salt = serde_json::from_slice::<serde_json::Value>(&std::fs::read(&path)?)?["salt"].clone();
+
+    backend.rekey("new passphrase").await?;
+    let new_salt = serde_json::from_slice::<serde_json::Value>(&std::fs::read(&path)?)?["salt"].clone();
+    assert_ne!(old_salt, new_salt);
+
+    // The backend keeps working under the new passphrase
+    backend.store_secret("openai/api_key", "sk-openai").await?;
+    assert!(open(&path, "old passphrase")?.get_secret("anthropic/api_key").await.is_err());
+    let reopened = open(&path, "new passphrase")?;
+    assert_eq!(reopened.get_secret("anthropic/api_key").await?, "sk-ant");
+    assert_eq!(reopened.get_secret("openai/api_key").await?, "sk-openai");
+
+    assert!(backend.rekey("").await.is_err());
+    Ok(())
+}
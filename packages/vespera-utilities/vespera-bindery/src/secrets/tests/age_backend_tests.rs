@@ -0,0 +1,143 @@
+// AgeBackend tests
+// Unlike the keyring tests these need no OS services, only a temp directory
+
+use crate::secrets::{AgeBackend, AgeConfig, AgeKey, SecretBackend, SecretManager};
+use age::secrecy::{ExposeSecret, SecretString};
+use anyhow::Result;
+use tempfile::TempDir;
+
+/// Write a fresh x25519 identity file, as `age-keygen` would
+fn identity_file(dir: &TempDir) -> std::path::PathBuf {
+    let path = dir.path().join("identity.txt");
+    let identity = age::x25519::Identity::generate();
+    std::fs::write(&path, format!("{}\n", identity.to_string().expose_secret())).unwrap();
+    path
+}
+
+fn identity_backend(dir: &TempDir) -> AgeBackend {
+    AgeBackend::new(AgeConfig {
+        path: dir.path().join("secrets/secrets.age"),
+        key: AgeKey::IdentityFile(identity_file(dir)),
+    })
+    .unwrap()
+}
+
+// ============================================================================
+// Test Suite 1: Basic SecretBackend Operations
+// ============================================================================
+
+#[tokio::test]
+async fn test_store_get_list_delete() -> Result<()> {
+    let dir = TempDir::new()?;
+    let backend = identity_backend(&dir);
+    assert!(backend.is_available().await);
+    assert_eq!(backend.backend_name(), "age-encryption");
+
+    // A missing file is an empty store
+    assert!(backend.list_secrets().await?.is_empty());
+    assert!(backend.get_secret("anthropic/api_key").await.is_err());
+
+    backend.store_secret("openai/api_key", "sk-openai").await?;
+    backend.store_secret("anthropic/api_key", "sk-ant-old").await?;
+    backend.store_secret("anthropic/api_key", "sk-ant-new").await?;
+    assert_eq!(backend.get_secret("anthropic/api_key").await?, "sk-ant-new");
+    assert_eq!(backend.list_secrets().await?, vec!["anthropic/api_key", "openai/api_key"]);
+
+    backend.delete_secret("openai/api_key").await?;
+    // Deleting a missing secret is OK
+    backend.delete_secret("openai/api_key").await?;
+    assert_eq!(backend.list_secrets().await?, vec!["anthropic/api_key"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_is_encrypted_and_replaced_atomically() -> Result<()> {
+    let dir = TempDir::new()?;
+    let backend = identity_backend(&dir);
+    backend.store_secret("anthropic/api_key", "sk-ant-plaintext").await?;
+    backend.store_secret("openai/api_key", "sk-openai").await?;
+
+    let contents = std::fs::read_to_string(dir.path().join("secrets/secrets.age"))?;
+    assert!(contents.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+    assert!(!contents.contains("sk-ant-plaintext"));
+
+    // No temporary files are left beside the store
+    let entries: Vec<_> = std::fs::read_dir(dir.path().join("secrets"))?.collect();
+    assert_eq!(entries.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reopen_and_external_changes() -> Result<()> {
+    let dir = TempDir::new()?;
+    let identity = identity_file(&dir);
+    let open = || {
+        AgeBackend::new(AgeConfig {
+            path: dir.path().join("secrets.age"),
+            key: AgeKey::IdentityFile(identity.clone()),
+        })
+        .unwrap()
+    };
+
+    let first = open();
+    first.store_secret("a", "1").await?;
+    let second = open();
+    assert_eq!(second.get_secret("a").await?, "1");
+
+    // Writes by another instance are picked up
+    second.store_secret("b", "2").await?;
+    assert_eq!(first.get_secret("b").await?, "2");
+
+    // A different identity cannot read the store
+    let other = AgeBackend::new(AgeConfig {
+        path: dir.path().join("secrets.age"),
+        key: AgeKey::IdentityFile(identity_file(&TempDir::new()?)),
+    })?;
+    assert!(other.get_secret("a").await.is_err());
+    Ok(())
+}
+
+// ============================================================================
+// Test Suite 2: Passphrase Mode
+// ============================================================================
+
+#[tokio::test]
+async fn test_passphrase_mode() -> Result<()> {
+    let dir = TempDir::new()?;
+    let open = |passphrase: &str| {
+        AgeBackend::new(AgeConfig {
+            path: dir.path().join("secrets.age"),
+            key: AgeKey::Passphrase(SecretString::from(passphrase.to_string())),
+        })
+        .map(|backend| backend.with_work_factor(2))
+    };
+
+    open("correct horse")?.store_secret("anthropic/api_key", "sk-ant").await?;
+    assert_eq!(open("correct horse")?.get_secret("anthropic/api_key").await?, "sk-ant");
+    assert!(open("wrong")?.get_secret("anthropic/api_key").await.is_err());
+    assert!(open("").is_err());
+    Ok(())
+}
+
+// ============================================================================
+// Test Suite 3: Migration
+// ============================================================================
+
+#[tokio::test]
+async fn test_migrate_between_backends() -> Result<()> {
+    let source_dir = TempDir::new()?;
+    let source = identity_backend(&source_dir);
+    source.store_secret("anthropic/api_key", "sk-ant").await?;
+    source.store_secret("openai/api_key", "sk-openai").await?;
+
+    let target_dir = TempDir::new()?;
+    let manager = SecretManager::with_backend(Box::new(identity_backend(&target_dir)));
+
+    let keys = vec!["anthropic/api_key".to_string(), "missing/api_key".to_string()];
+    let migrated = manager.migrate_from(&source, &keys, true).await?;
+    assert_eq!(migrated, vec!["anthropic/api_key"]);
+
+    assert_eq!(manager.resolve("vault://anthropic/api_key").await?, "sk-ant");
+    assert_eq!(source.list_secrets().await?, vec!["openai/api_key"]);
+    Ok(())
+}
@@ -1,5 +1,8 @@
 // Test module for secret storage system
 // Using TDD approach - tests written before implementation
 
+mod aes_gcm_backend_tests;
+mod age_backend_tests;
 mod keyring_backend_tests;
+mod reference_tests;
 mod secret_manager_tests;
@@ -0,0 +1,43 @@
+// Secret reference and redaction tests
+
+use crate::secrets::{redact_json, SecretRef, REDACTED};
+
+#[test]
+fn test_parse_and_display() {
+    let stored = SecretRef::parse("secret://anthropic/api_key");
+    assert_eq!(stored.key(), Some("anthropic/api_key"));
+    assert_eq!(SecretRef::parse("vault://anthropic/api_key"), stored);
+    assert_eq!(format!("{:?}", stored), "SecretRef(secret://anthropic/api_key)");
+
+    let literal = SecretRef::parse("sk-ant-plaintext");
+    assert!(literal.is_literal());
+    assert_eq!(literal.to_string(), REDACTED);
+    assert!(!format!("{:?}", literal).contains("sk-ant"));
+
+    // References round-trip as references
+    let json = serde_json::to_string(&stored).unwrap();
+    assert_eq!(json, "\"secret://anthropic/api_key\"");
+    assert_eq!(serde_json::from_str::<SecretRef>(&json).unwrap(), stored);
+}
+
+#[test]
+fn test_redact_json() {
+    let mut value = serde_json::json!({
+        "api_key": "sk-ant-plaintext",
+        "other_key": "secret://openai/api_key",
+        "headers": [{ "Authorization": "Bearer abc" }],
+        "provider": { "api_key": "vault://anthropic/api_key", "model": "claude" },
+        "count": 3,
+    });
+    redact_json(&mut value);
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "api_key": REDACTED,
+            "other_key": "secret://openai/api_key",
+            "headers": [{ "Authorization": REDACTED }],
+            "provider": { "api_key": "vault://anthropic/api_key", "model": "claude" },
+            "count": 3,
+        })
+    );
+}
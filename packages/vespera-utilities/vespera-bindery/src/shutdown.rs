@@ -0,0 +1,343 @@
+//! Graceful shutdown across subsystems
+//!
+//! A [`ShutdownCoordinator`] runs the shutdown steps registered by each
+//! subsystem in phase order: first everything stops taking new work, then
+//! in-flight work drains, then pending writes are flushed, and finally
+//! connections and processes are closed. One deadline covers the whole
+//! shutdown; steps still running when it passes are abandoned and reported,
+//! so an embedding application always gets to exit.
+//!
+//! [`CodexManager::shutdown_coordinator`](crate::CodexManager::shutdown_coordinator)
+//! returns a coordinator with the Codex manager's own steps (task executions,
+//! hook scheduler, sync). Applications add the subsystems they own before
+//! running it:
+//!
+//! ```rust,no_run
+//! use vespera_bindery::shutdown::ShutdownPhase;
+//! # use std::sync::Arc;
+//! # async fn example(
+//! #     manager: vespera_bindery::CodexManager,
+//! #     database: Arc<vespera_bindery::Database>,
+//! # ) -> anyhow::Result<()> {
+//! let report = manager
+//!     .shutdown_coordinator()
+//!     .with_step(ShutdownPhase::Close, "database", move || async move {
+//!         database.close().await;
+//!         Ok(())
+//!     })
+//!     .shutdown()
+//!     .await;
+//! report.into_result()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Result};
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Deadline for a whole shutdown unless configured otherwise
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When a shutdown step runs; steps of one phase run concurrently, and each
+/// phase starts once the previous one has finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownPhase {
+    /// Stop accepting new work: schedulers, listeners, supervision loops
+    StopIntake,
+    /// Wait for work already started, such as task executions
+    Drain,
+    /// Write out anything buffered: persistence, audit events, indices
+    Flush,
+    /// Release connections and child processes
+    Close,
+}
+
+type StepFn = Box<dyn FnOnce() -> BoxFuture<'static, Result<()>> + Send>;
+
+struct ShutdownStep {
+    phase: ShutdownPhase,
+    name: String,
+    run: StepFn,
+}
+
+/// Runs registered shutdown steps in phase order within a deadline
+pub struct ShutdownCoordinator {
+    deadline: Duration,
+    steps: Vec<ShutdownStep>,
+}
+
+impl std::fmt::Debug for ShutdownCoordinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShutdownCoordinator")
+            .field("deadline", &self.deadline)
+            .field("steps", &self.steps.iter().map(|step| (step.phase, &step.name)).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new(DEFAULT_SHUTDOWN_TIMEOUT)
+    }
+}
+
+impl ShutdownCoordinator {
+    /// A coordinator that gives up on unfinished steps after `deadline`
+    pub fn new(deadline: Duration) -> Self {
+        Self { deadline, steps: Vec::new() }
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn with_step<F, Fut>(mut self, phase: ShutdownPhase, name: impl Into<String>, step: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.add_step(phase, name, step);
+        self
+    }
+
+    /// Register a step to run in `phase`
+    pub fn add_step<F, Fut>(&mut self, phase: ShutdownPhase, name: impl Into<String>, step: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.steps.push(ShutdownStep {
+            phase,
+            name: name.into(),
+            run: Box::new(move || step().boxed()),
+        });
+    }
+
+    pub fn deadline(&self) -> Duration {
+        self.deadline
+    }
+
+    /// Run every step, phase by phase, and report how each one ended
+    pub async fn shutdown(mut self) -> ShutdownReport {
+        let started = Instant::now();
+        let deadline = started + self.deadline;
+        let mut report = ShutdownReport::default();
+        info!(steps = self.steps.len(), deadline = ?self.deadline, "Shutting down");
+
+        // Stable, so steps of a phase keep their registration order in the report
+        self.steps.sort_by_key(|step| step.phase);
+        let mut steps = self.steps.into_iter().peekable();
+        while let Some(first) = steps.next() {
+            let phase = first.phase;
+            let mut batch = vec![first];
+            while let Some(step) = steps.next_if(|step| step.phase == phase) {
+                batch.push(step);
+            }
+
+            if Instant::now() >= deadline {
+                report.timed_out.extend(batch.into_iter().map(|step| step.name));
+                continue;
+            }
+
+            let outcomes = join_all(batch.into_iter().map(|step| async move {
+                let outcome = tokio::time::timeout_at(deadline, (step.run)()).await;
+                (step.name, outcome)
+            }))
+            .await;
+
+            for (name, outcome) in outcomes {
+                match outcome {
+                    Ok(Ok(())) => report.completed.push(name),
+                    Ok(Err(e)) => {
+                        warn!(step = %name, error = %e, "Shutdown step failed");
+                        report.failed.push((name, e.to_string()));
+                    }
+                    Err(_) => {
+                        warn!(step = %name, "Shutdown step did not finish before the deadline");
+                        report.timed_out.push(name);
+                    }
+                }
+            }
+        }
+
+        report.elapsed = started.elapsed();
+        info!(
+            elapsed = ?report.elapsed,
+            failed = report.failed.len(),
+            timed_out = report.timed_out.len(),
+            "Shutdown finished"
+        );
+        report
+    }
+}
+
+/// Counts in-flight work, such as spawned task executions, so shutdown can
+/// wait for it to finish
+#[derive(Debug, Clone, Default)]
+pub struct WorkTracker {
+    inner: Arc<WorkTrackerInner>,
+}
+
+#[derive(Debug, Default)]
+struct WorkTrackerInner {
+    running: AtomicUsize,
+    idle: Notify,
+}
+
+impl WorkTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `future`, counted as running from now until it completes or is dropped
+    pub fn track<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        self.inner.running.fetch_add(1, Ordering::SeqCst);
+        let guard = WorkGuard(Arc::clone(&self.inner));
+        async move {
+            let _guard = guard;
+            future.await
+        }
+    }
+
+    pub fn running(&self) -> usize {
+        self.inner.running.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no tracked work is running
+    pub async fn wait_idle(&self) {
+        loop {
+            let idle = self.inner.idle.notified();
+            tokio::pin!(idle);
+            // Registered before checking, so a finish in between is not missed
+            idle.as_mut().enable();
+            if self.running() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+struct WorkGuard(Arc<WorkTrackerInner>);
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        if self.0.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// How each shutdown step ended
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub completed: Vec<String>,
+    /// Steps that returned an error, with the error
+    pub failed: Vec<(String, String)>,
+    /// Steps abandoned at the deadline, including those that never started
+    pub timed_out: Vec<String>,
+    pub elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// Whether every step completed
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty() && self.timed_out.is_empty()
+    }
+
+    /// The report, or an error naming the steps that did not complete
+    pub fn into_result(self) -> Result<Self> {
+        if self.is_clean() {
+            return Ok(self);
+        }
+        let mut problems: Vec<String> = self
+            .failed
+            .iter()
+            .map(|(name, error)| format!("{} failed: {}", name, error))
+            .collect();
+        problems.extend(self.timed_out.iter().map(|name| format!("{} timed out", name)));
+        Err(anyhow!("Shutdown incomplete: {}", problems.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_steps_run_in_phase_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        for (phase, name) in [
+            (ShutdownPhase::Close, "database"),
+            (ShutdownPhase::StopIntake, "scheduler"),
+            (ShutdownPhase::Flush, "audit"),
+            (ShutdownPhase::Drain, "tasks"),
+        ] {
+            let order = Arc::clone(&order);
+            coordinator.add_step(phase, name, move || async move {
+                order.lock().unwrap().push(name);
+                Ok(())
+            });
+        }
+
+        let report = coordinator.shutdown().await;
+        assert!(report.is_clean());
+        assert_eq!(*order.lock().unwrap(), ["scheduler", "tasks", "audit", "database"]);
+    }
+
+    #[tokio::test]
+    async fn test_work_tracker_waits_for_running_work() {
+        let tracker = WorkTracker::new();
+        tracker.wait_idle().await;
+
+        let (finish, finished) = tokio::sync::oneshot::channel::<()>();
+        let work = tokio::spawn(tracker.track(async move {
+            let _ = finished.await;
+        }));
+        assert_eq!(tracker.running(), 1);
+
+        let waiting = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.wait_idle().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        finish.send(()).unwrap();
+        work.await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), waiting).await.unwrap().unwrap();
+        assert_eq!(tracker.running(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_abandons_slow_steps() {
+        let report = ShutdownCoordinator::new(Duration::from_millis(50))
+            .with_step(ShutdownPhase::Drain, "stuck", || async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            })
+            .with_step(ShutdownPhase::Drain, "failing", || async { Err(anyhow!("disk full")) })
+            .with_step(ShutdownPhase::Close, "database", || async { Ok(()) })
+            .shutdown()
+            .await;
+
+        assert_eq!(report.timed_out, ["stuck", "database"]);
+        assert_eq!(report.failed, [("failing".to_string(), "disk full".to_string())]);
+        assert!(report.elapsed < Duration::from_secs(5));
+        let error = report.into_result().unwrap_err().to_string();
+        assert!(error.contains("stuck timed out"), "{}", error);
+    }
+}
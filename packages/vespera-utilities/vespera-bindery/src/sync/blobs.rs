@@ -0,0 +1,341 @@
+//! Attachment contents synced between replicas by content hash
+//!
+//! Codices only carry [`Attachment`] records; the bytes live in a
+//! [`BlobStore`], addressed by their SHA-256. A replica that lacks a blob
+//! asks a peer for it in chunks over the sync transport
+//! ([`SyncMessage::BlobRequest`] and [`SyncMessage::BlobChunk`]). Chunks are
+//! appended to a partial file, so an interrupted download resumes where it
+//! stopped, and the blob only becomes visible once its hash checks out.
+//! Identical contents are stored and transferred once.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use super::SyncMessage;
+use crate::crdt::{TemplateValue, VesperaCRDT};
+use crate::task_management::artifacts::artifacts_of;
+use crate::types::{Attachment, ContentHash};
+use crate::{BinderyError, BinderyResult};
+
+/// Codex metadata field listing its [`Attachment`]s
+pub const ATTACHMENTS_FIELD: &str = "attachments";
+
+/// Bytes requested per chunk unless the caller picks another size
+pub const DEFAULT_CHUNK_SIZE: u64 = 256 * 1024;
+
+/// Largest chunk served, whatever was requested
+pub const MAX_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// SHA-256 of `content`, as stored in [`Attachment::content_hash`]
+pub fn content_hash(content: &[u8]) -> ContentHash {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Attachments a Codex refers to: its own and those of its task artifacts
+pub fn attachments_of(crdt: &VesperaCRDT) -> Vec<Attachment> {
+    let mut attachments: Vec<Attachment> = match crdt.get_metadata(ATTACHMENTS_FIELD) {
+        Some(TemplateValue::Structured { value, .. }) => serde_json::from_value(value.clone()).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    attachments.extend(artifacts_of(crdt).into_iter().map(|artifact| artifact.attachment));
+    attachments
+}
+
+/// How much of a blob has arrived
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobProgress {
+    pub content_hash: ContentHash,
+    pub received: u64,
+    pub total: u64,
+}
+
+impl BlobProgress {
+    pub fn is_complete(&self) -> bool {
+        self.received >= self.total
+    }
+}
+
+/// Content-addressed blob storage
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Where the blob with `hash` is stored
+    pub fn path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2.min(hash.len())]).join(hash)
+    }
+
+    fn partial_path(&self, hash: &str) -> PathBuf {
+        self.path(hash).with_extension("partial")
+    }
+
+    /// Store `content`, returning its hash and path
+    ///
+    /// Identical contents are stored once.
+    pub async fn put(&self, content: &[u8]) -> BinderyResult<(ContentHash, PathBuf)> {
+        let hash = content_hash(content);
+        let path = self.path(&hash);
+        if !self.contains(&hash).await {
+            create_parent(&path).await?;
+            // Written aside and renamed so a reader never sees part of a blob
+            let partial = self.partial_path(&hash);
+            tokio::fs::write(&partial, content).await.map_err(io_error)?;
+            tokio::fs::rename(&partial, &path).await.map_err(io_error)?;
+        }
+        Ok((hash, path))
+    }
+
+    pub async fn contains(&self, hash: &str) -> bool {
+        is_valid_hash(hash) && tokio::fs::try_exists(self.path(hash)).await.unwrap_or(false)
+    }
+
+    /// Contents of a blob, checked against its hash
+    pub async fn read(&self, hash: &str) -> BinderyResult<Vec<u8>> {
+        check_hash(hash)?;
+        let content = tokio::fs::read(self.path(hash)).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => BinderyError::NotFound(format!("Blob {}", hash)),
+            _ => io_error(e),
+        })?;
+        if content_hash(&content) != hash {
+            return Err(BinderyError::IoError(format!("Blob {} does not match its hash", hash)));
+        }
+        Ok(content)
+    }
+
+    /// The attachments whose contents aren't stored here yet
+    pub async fn missing<'a>(&self, attachments: &'a [Attachment]) -> Vec<&'a Attachment> {
+        let mut missing = Vec::new();
+        for attachment in attachments {
+            if !self.contains(&attachment.content_hash).await {
+                missing.push(attachment);
+            }
+        }
+        missing
+    }
+
+    /// Bytes of a blob received so far, whole or partial
+    pub async fn received_bytes(&self, hash: &str) -> u64 {
+        if !is_valid_hash(hash) {
+            return 0;
+        }
+        for path in [self.path(hash), self.partial_path(hash)] {
+            if let Ok(metadata) = tokio::fs::metadata(&path).await {
+                return metadata.len();
+            }
+        }
+        0
+    }
+
+    /// The request for the next chunk of `attachment`, or None once it is stored
+    ///
+    /// Resumes after whatever a previous, interrupted download received.
+    pub async fn next_request(&self, attachment: &Attachment, chunk_size: u64) -> Option<SyncMessage> {
+        if self.contains(&attachment.content_hash).await {
+            return None;
+        }
+        Some(SyncMessage::BlobRequest {
+            content_hash: attachment.content_hash.clone(),
+            offset: self.received_bytes(&attachment.content_hash).await,
+            length: chunk_size.clamp(1, MAX_CHUNK_SIZE),
+        })
+    }
+
+    /// Answer a [`SyncMessage::BlobRequest`] with the chunk it asks for
+    pub async fn serve(&self, request: &SyncMessage) -> BinderyResult<SyncMessage> {
+        let SyncMessage::BlobRequest { content_hash, offset, length } = request else {
+            return Err(BinderyError::ProtocolError("Expected a blob request".to_string()));
+        };
+        check_hash(content_hash)?;
+        let mut file = tokio::fs::File::open(self.path(content_hash)).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => BinderyError::NotFound(format!("Blob {}", content_hash)),
+            _ => io_error(e),
+        })?;
+        let total_size = file.metadata().await.map_err(io_error)?.len();
+        let offset = (*offset).min(total_size);
+        let length = (*length).min(MAX_CHUNK_SIZE).min(total_size - offset);
+
+        file.seek(std::io::SeekFrom::Start(offset)).await.map_err(io_error)?;
+        let mut data = vec![0; length as usize];
+        file.read_exact(&mut data).await.map_err(io_error)?;
+        Ok(SyncMessage::BlobChunk { content_hash: content_hash.clone(), offset, total_size, data })
+    }
+
+    /// Store a [`SyncMessage::BlobChunk`]
+    ///
+    /// Chunks must arrive in order; repeats of bytes already received are
+    /// ignored. When the last chunk arrives the whole blob is checked against
+    /// its hash, and discarded if it doesn't match.
+    pub async fn receive_chunk(&self, chunk: &SyncMessage) -> BinderyResult<BlobProgress> {
+        let SyncMessage::BlobChunk { content_hash, offset, total_size, data } = chunk else {
+            return Err(BinderyError::ProtocolError("Expected a blob chunk".to_string()));
+        };
+        check_hash(content_hash)?;
+        let progress = |received| BlobProgress { content_hash: content_hash.clone(), received, total: *total_size };
+        if self.contains(content_hash).await {
+            return Ok(progress(*total_size));
+        }
+
+        let received = self.received_bytes(content_hash).await;
+        if *offset > received {
+            return Err(BinderyError::ProtocolError(format!(
+                "Chunk of blob {} starts at {} but only {} bytes have arrived",
+                content_hash, offset, received
+            )));
+        }
+        let end = offset + data.len() as u64;
+        if end > *total_size {
+            return Err(BinderyError::ProtocolError(format!(
+                "Chunk of blob {} ends past its size of {} bytes",
+                content_hash, total_size
+            )));
+        }
+
+        let partial = self.partial_path(content_hash);
+        // An empty blob still needs its (empty) partial file
+        if end > received || received == 0 {
+            create_parent(&partial).await?;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&partial)
+                .await
+                .map_err(io_error)?;
+            file.write_all(&data[(received - offset) as usize..]).await.map_err(io_error)?;
+            file.flush().await.map_err(io_error)?;
+        }
+        let received = received.max(end);
+        if received < *total_size {
+            return Ok(progress(received));
+        }
+
+        let content = tokio::fs::read(&partial).await.map_err(io_error)?;
+        if self::content_hash(&content) != *content_hash {
+            // Start over rather than keep bytes that can never verify
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(BinderyError::IoError(format!(
+                "Blob {} does not match its hash; discarded",
+                content_hash
+            )));
+        }
+        tokio::fs::rename(&partial, self.path(content_hash)).await.map_err(io_error)?;
+        Ok(progress(received))
+    }
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Hashes come from peers and name files, so only SHA-256 hex is accepted
+fn check_hash(hash: &str) -> BinderyResult<()> {
+    if is_valid_hash(hash) {
+        Ok(())
+    } else {
+        Err(BinderyError::InvalidInput(format!("Invalid content hash '{}'", hash)))
+    }
+}
+
+async fn create_parent(path: &std::path::Path) -> BinderyResult<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+    }
+    Ok(())
+}
+
+fn io_error(error: std::io::Error) -> BinderyError {
+    BinderyError::IoError(error.to_string())
+}
+
+/// Bytes as base64 text in JSON sync messages
+pub(crate) mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        STANDARD.decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::CompressionConfig;
+    use chrono::Utc;
+
+    fn attachment(content: &[u8]) -> Attachment {
+        Attachment {
+            id: uuid::Uuid::new_v4().to_string(),
+            filename: "image.png".to_string(),
+            mime_type: "image/png".to_string(),
+            size: content.len() as u64,
+            content_hash: content_hash(content),
+            storage_location: String::new(),
+            uploaded_at: Utc::now(),
+            uploaded_by: "alice".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunked_transfer_resumes_and_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let sender = BlobStore::new(dir.path().join("sender"));
+        let receiver = BlobStore::new(dir.path().join("receiver"));
+        let content: Vec<u8> = (0..10_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        sender.put(&content).await.unwrap();
+        let image = attachment(&content);
+        assert_eq!(receiver.missing(std::slice::from_ref(&image)).await.len(), 1);
+
+        // Two chunks, then the connection drops
+        for _ in 0..2 {
+            let request = receiver.next_request(&image, 4096).await.unwrap();
+            let chunk = sender.serve(&request).await.unwrap();
+            // Round trip over the wire
+            let chunk = SyncMessage::decode(&chunk.encode(&CompressionConfig::default()).unwrap(), usize::MAX).unwrap();
+            assert!(!receiver.receive_chunk(&chunk).await.unwrap().is_complete());
+        }
+        assert_eq!(receiver.received_bytes(&image.content_hash).await, 8192);
+
+        // A new session resumes at 8192, and a repeated chunk is harmless
+        let mut progress = None;
+        while let Some(request) = receiver.next_request(&image, 4096).await {
+            let chunk = sender.serve(&request).await.unwrap();
+            receiver.receive_chunk(&chunk).await.unwrap();
+            progress = Some(receiver.receive_chunk(&chunk).await.unwrap());
+        }
+        assert!(progress.unwrap().is_complete());
+        assert_eq!(receiver.read(&image.content_hash).await.unwrap(), content);
+        assert!(receiver.missing(std::slice::from_ref(&image)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_and_hostile_chunks_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path());
+        let hash = content_hash(b"expected");
+
+        let corrupt = SyncMessage::BlobChunk { content_hash: hash.clone(), offset: 0, total_size: 8, data: b"tampered".to_vec() };
+        assert!(store.receive_chunk(&corrupt).await.is_err());
+        assert!(!store.contains(&hash).await);
+        assert_eq!(store.received_bytes(&hash).await, 0);
+
+        let gap = SyncMessage::BlobChunk { content_hash: hash.clone(), offset: 4, total_size: 8, data: b"cted".to_vec() };
+        assert!(matches!(store.receive_chunk(&gap).await, Err(BinderyError::ProtocolError(_))));
+
+        let traversal = SyncMessage::BlobRequest { content_hash: "../../etc/passwd".to_string(), offset: 0, length: 10 };
+        assert!(matches!(store.serve(&traversal).await, Err(BinderyError::InvalidInput(_))));
+    }
+}
@@ -1,17 +1,37 @@
 //! Network synchronization for real-time collaboration
+//!
+//! Codices with an access control list ([`crate::codex::CodexAcl`]) are
+//! only pushed to peers allowed to read them, and operations from users
+//! without write access are dropped. Peers are identified by the `peer_id`
+//! of their connection, which is their user ID; operations a connection
+//! sends are only accepted if its peer wrote them.
+//!
+//! Incoming payloads go through [`SyncManager::receive`], which enforces the
+//! size cap and per-connection and per-user rate limits of
+//! `BinderyConfig::sync_limits` (see [`rate_limit`]). Outgoing ones come from
+//! [`SyncManager::outgoing`].
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use crate::{BinderyResult, BinderyConfig, types::CodexId, crdt::VesperaCRDT};
+use std::time::Instant;
+use crate::{BinderyError, BinderyResult, BinderyConfig, types::CodexId, crdt::{CRDTOperation, VesperaCRDT}};
+use crate::codex::acl;
+use crate::observability::{create_codex_access_event, log_security_event, AuditLogger, UserContext};
 
 // Sub-modules for synchronization
+pub mod blobs;
 pub mod protocol;
 pub mod conflict;
 pub mod offline;
+pub mod rate_limit;
 
 // Re-export commonly used types
 pub use protocol::{SyncProtocol, SyncMessage};
 pub use conflict::{ConflictResolver, ConflictResolution};
 pub use offline::{OfflineManager, OfflineQueue};
+pub use rate_limit::{SyncLimits, TokenBucket};
+pub use blobs::{BlobProgress, BlobStore};
 
 /// Manager for real-time synchronization
 #[derive(Debug)]
@@ -21,6 +41,13 @@ pub struct SyncManager {
     registered_codices: std::sync::RwLock<std::collections::HashMap<CodexId, std::sync::Weak<VesperaCRDT>>>,
     /// Active connections that need cleanup
     active_connections: std::sync::RwLock<std::collections::HashMap<String, ConnectionHandle>>,
+    /// Receives refused operations and pushes once attached
+    audit_logger: std::sync::OnceLock<Arc<AuditLogger>>,
+    rejected_operations: AtomicU64,
+    withheld_pushes: AtomicU64,
+    rate_limiter: std::sync::Mutex<rate_limit::RateLimiter>,
+    dropped_payloads: AtomicU64,
+    throttled_operations: AtomicU64,
 }
 
 /// Handle for an active network connection
@@ -36,12 +63,251 @@ pub struct ConnectionHandle {
 impl SyncManager {
     /// Create a new sync manager
     pub fn new(config: BinderyConfig) -> BinderyResult<Self> {
+        config.sync_limits.validate()?;
         Ok(Self {
+            rate_limiter: std::sync::Mutex::new(rate_limit::RateLimiter::new(config.sync_limits.clone())),
+            dropped_payloads: AtomicU64::new(0),
+            throttled_operations: AtomicU64::new(0),
             config,
             registered_codices: std::sync::RwLock::new(std::collections::HashMap::new()),
             active_connections: std::sync::RwLock::new(std::collections::HashMap::new()),
+            audit_logger: std::sync::OnceLock::new(),
+            rejected_operations: AtomicU64::new(0),
+            withheld_pushes: AtomicU64::new(0),
         })
     }
+
+    /// Record refused operations and pushes in `logger`
+    ///
+    /// Returns false, leaving the first in place, if one was already attached.
+    pub fn attach_audit_logger(&self, logger: Arc<AuditLogger>) -> bool {
+        self.audit_logger.set(logger).is_ok()
+    }
+
+    fn registered_codex(&self, codex_id: &CodexId) -> Option<Arc<VesperaCRDT>> {
+        self.registered_codices.read().ok()?.get(codex_id)?.upgrade()
+    }
+
+    fn connection_peer(&self, connection_id: &str) -> Option<String> {
+        self.active_connections.read().ok()?.get(connection_id)?.peer_id.clone()
+    }
+
+    /// The operations from `connection_id` that its peer may apply to
+    /// `codex_id`, dropping the rest
+    ///
+    /// An operation is only accepted from the peer that wrote it: one whose
+    /// `user_id` isn't the connection's `peer_id` is dropped, as is every
+    /// operation from a connection without a peer ID. The rest are checked
+    /// against the Codex's access control list when it is registered here;
+    /// [`CodexManager::apply_remote_operations`](crate::CodexManager::apply_remote_operations)
+    /// checks them against the Codex itself either way. Each dropped
+    /// operation is counted and recorded in the audit log.
+    pub async fn authorize_operations(
+        &self,
+        connection_id: &str,
+        codex_id: &CodexId,
+        operations: Vec<CRDTOperation>,
+    ) -> BinderyResult<Vec<CRDTOperation>> {
+        let operations = self.authenticate_operations(connection_id, &codex_id.to_string(), operations).await?;
+        self.check_codex_acl(codex_id, operations).await
+    }
+
+    /// Drop the operations `connection_id`'s peer didn't write
+    async fn authenticate_operations(
+        &self,
+        connection_id: &str,
+        resource: &str,
+        operations: Vec<CRDTOperation>,
+    ) -> BinderyResult<Vec<CRDTOperation>> {
+        let peer_id = self.connection_peer(connection_id);
+        let (allowed, rejected): (Vec<_>, Vec<_>) = operations
+            .into_iter()
+            .partition(|operation| peer_id.as_deref() == Some(operation.user_id.as_str()));
+
+        for operation in rejected {
+            self.rejected_operations.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                connection_id,
+                peer_id = ?peer_id,
+                user_id = %operation.user_id,
+                "Rejected operation not written by the connection's peer"
+            );
+            let details = HashMap::from([
+                ("operation_id".to_string(), serde_json::Value::String(operation.id.to_string())),
+                ("connection_id".to_string(), serde_json::Value::String(connection_id.to_string())),
+                ("claimed_user_id".to_string(), serde_json::Value::String(operation.user_id.to_string())),
+            ]);
+            self.audit_access(resource, peer_id.clone(), "reject_unauthenticated_operation", details).await;
+        }
+        Ok(allowed)
+    }
+
+    /// The operations a Codex's access control list allows, dropping the rest
+    ///
+    /// Unlike [`authorize_operations`](Self::authorize_operations) this
+    /// doesn't check who sent them, for operations relayed in a replica
+    /// that other users wrote. Each dropped operation is counted and
+    /// recorded in the audit log. Operations for Codices not registered
+    /// here are passed through.
+    pub async fn check_codex_acl(
+        &self,
+        codex_id: &CodexId,
+        operations: Vec<CRDTOperation>,
+    ) -> BinderyResult<Vec<CRDTOperation>> {
+        let Some(crdt) = self.registered_codex(codex_id) else {
+            return Ok(operations);
+        };
+        let (allowed, rejected): (Vec<_>, Vec<_>) = operations
+            .into_iter()
+            .partition(|operation| acl::may_apply(&crdt, operation));
+
+        for operation in rejected {
+            self.rejected_operations.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(%codex_id, user_id = %operation.user_id, "Rejected operation from user without write access");
+            let details = HashMap::from([
+                ("operation_id".to_string(), serde_json::Value::String(operation.id.to_string())),
+                ("required_access".to_string(), serde_json::to_value(acl::required_access(&operation))?),
+            ]);
+            self.audit_access(&codex_id.to_string(), Some(operation.user_id.to_string()), "reject_operation", details).await;
+        }
+        Ok(allowed)
+    }
+
+    /// Refuse a payload from `connection_id` over the configured size cap
+    pub fn check_payload(&self, connection_id: &str, payload_bytes: usize) -> BinderyResult<()> {
+        let max_payload_bytes = self.config.sync_limits.max_payload_bytes;
+        if payload_bytes > max_payload_bytes {
+            self.dropped_payloads.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(connection_id, payload_bytes, max_payload_bytes, "Dropped oversized sync payload");
+            return Err(BinderyError::ProtocolError(format!(
+                "Payload of {} bytes exceeds the limit of {} bytes",
+                payload_bytes, max_payload_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// The operations from `connection_id` within its and their authors'
+    /// rate limits, dropping the rest
+    pub fn throttle_operations(&self, connection_id: &str, operations: Vec<CRDTOperation>) -> Vec<CRDTOperation> {
+        let now = Instant::now();
+        let Ok(mut limiter) = self.rate_limiter.lock() else {
+            return operations;
+        };
+        let before = operations.len();
+        let admitted: Vec<_> = operations
+            .into_iter()
+            .filter(|operation| limiter.admit(connection_id, &operation.user_id, now))
+            .collect();
+        let throttled = before - admitted.len();
+        if throttled > 0 {
+            self.throttled_operations.fetch_add(throttled as u64, Ordering::Relaxed);
+            tracing::warn!(connection_id, throttled, "Throttled operations over the sync rate limit");
+        }
+        admitted
+    }
+
+    /// Decode a payload from `connection_id`, enforcing the size cap, rate
+    /// limits and who may send which operations
+    ///
+    /// The cap applies both to the payload and to what it decompresses to.
+    /// Operations go through [`authorize_operations`](Self::authorize_operations);
+    /// a broadcast names no Codex, so only its author is checked here.
+    /// Returns None when every operation the message carried was dropped.
+    pub async fn receive(&self, connection_id: &str, payload: &[u8]) -> BinderyResult<Option<SyncMessage>> {
+        self.check_payload(connection_id, payload.len())?;
+        let max_payload_bytes = self.config.sync_limits.max_payload_bytes;
+        let message = match SyncMessage::decode(payload, max_payload_bytes)? {
+            SyncMessage::SyncResponse { codex_id, operations } => {
+                let operations = self.authorize_operations(connection_id, &codex_id, operations).await?;
+                let operations = self.throttle_operations(connection_id, operations);
+                if operations.is_empty() {
+                    return Ok(None);
+                }
+                SyncMessage::SyncResponse { codex_id, operations }
+            }
+            SyncMessage::OperationBroadcast { operation } => {
+                let operations = self.authenticate_operations(connection_id, "broadcast", vec![operation]).await?;
+                match self.throttle_operations(connection_id, operations).pop() {
+                    Some(operation) => SyncMessage::OperationBroadcast { operation },
+                    None => return Ok(None),
+                }
+            }
+            message => message,
+        };
+        Ok(Some(message))
+    }
+
+    /// Encode `message` about `codex_id` for each connection allowed to
+    /// receive it, as (connection ID, payload) pairs
+    ///
+    /// The outgoing counterpart of [`receive`](Self::receive): connections
+    /// left out by [`push_targets`](Self::push_targets) get nothing, and the
+    /// message is encoded once with the configured compression.
+    pub async fn outgoing(&self, codex_id: &CodexId, message: &SyncMessage) -> BinderyResult<Vec<(String, Vec<u8>)>> {
+        let targets = self.push_targets(codex_id).await?;
+        if targets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let payload = message.encode(&self.config.effective_compression())?;
+        Ok(targets.into_iter().map(|connection_id| (connection_id, payload.clone())).collect())
+    }
+
+    /// Whether `user_id` may receive `codex_id`
+    pub fn may_read(&self, codex_id: &CodexId, user_id: &str) -> bool {
+        self.registered_codex(codex_id)
+            .is_some_and(|crdt| acl::access_of(&crdt, user_id).can_read())
+    }
+
+    /// The connections `codex_id` may be pushed to
+    ///
+    /// Connections whose peer may not read it are left out, and each is
+    /// counted and recorded in the audit log. A connection without a peer ID
+    /// only receives Codices without an access control list.
+    pub async fn push_targets(&self, codex_id: &CodexId) -> BinderyResult<Vec<String>> {
+        let Some(crdt) = self.registered_codex(codex_id) else {
+            return Ok(Vec::new());
+        };
+        let (allowed, denied): (Vec<_>, Vec<_>) = {
+            let connections = self.active_connections.read()
+                .map_err(|_| crate::BinderyError::InternalError("Lock poisoned".to_string()))?;
+            connections.values()
+                .map(|handle| (handle.connection_id.clone(), handle.peer_id.clone()))
+                .partition(|(_, peer_id)| match peer_id {
+                    Some(peer_id) => acl::access_of(&crdt, peer_id).can_read(),
+                    None => acl::acl_of(&crdt).is_none(),
+                })
+        };
+
+        for (connection_id, peer_id) in denied {
+            self.withheld_pushes.fetch_add(1, Ordering::Relaxed);
+            let details = HashMap::from([
+                ("connection_id".to_string(), serde_json::Value::String(connection_id)),
+            ]);
+            self.audit_access(&codex_id.to_string(), peer_id, "deny_read", details).await;
+        }
+        Ok(allowed.into_iter().map(|(connection_id, _)| connection_id).collect())
+    }
+
+    async fn audit_access(
+        &self,
+        resource: &str,
+        user_id: Option<String>,
+        action: &str,
+        details: HashMap<String, serde_json::Value>,
+    ) {
+        let Some(logger) = self.audit_logger.get() else {
+            return;
+        };
+        let user_context = UserContext {
+            user_id,
+            session_id: None,
+            source_ip: None,
+            user_agent: None,
+        };
+        let event = create_codex_access_event(user_context, resource, action, details);
+        log_security_event(Some(logger), event).await;
+    }
     
     /// Register a Codex for synchronization using weak references to prevent cycles
     pub async fn register_codex(&self, codex_id: CodexId, crdt: Arc<VesperaCRDT>) -> BinderyResult<()> {
@@ -82,6 +348,11 @@ impl SyncManager {
                 is_alive
             });
         }
+
+        // Refilled buckets behave like new ones
+        if let Ok(mut limiter) = self.rate_limiter.lock() {
+            limiter.prune(Instant::now());
+        }
         
         cleaned
     }
@@ -107,6 +378,10 @@ impl SyncManager {
         let mut connections = self.active_connections.write()
             .map_err(|_| crate::BinderyError::InternalError("Lock poisoned".to_string()))?;
         
+        if let Ok(mut limiter) = self.rate_limiter.lock() {
+            limiter.remove_connection(connection_id);
+        }
+
         if let Some(mut handle) = connections.remove(connection_id) {
             // Signal cleanup if channel exists
             if let Some(sender) = handle.cleanup_sender.take() {
@@ -131,6 +406,10 @@ impl SyncManager {
         SyncManagerStats {
             registered_codices: registered_count,
             active_connections,
+            rejected_operations: self.rejected_operations.load(Ordering::Relaxed),
+            withheld_pushes: self.withheld_pushes.load(Ordering::Relaxed),
+            dropped_payloads: self.dropped_payloads.load(Ordering::Relaxed),
+            throttled_operations: self.throttled_operations.load(Ordering::Relaxed),
         }
     }
     
@@ -170,6 +449,15 @@ impl SyncManager {
 pub struct SyncManagerStats {
     pub registered_codices: usize,
     pub active_connections: usize,
+    /// Incoming operations dropped by a Codex's access control list or for
+    /// not being written by the sending peer
+    pub rejected_operations: u64,
+    /// Pushes withheld from peers not allowed to read the Codex
+    pub withheld_pushes: u64,
+    /// Incoming payloads refused for exceeding the size cap
+    pub dropped_payloads: u64,
+    /// Incoming operations dropped by rate limits
+    pub throttled_operations: u64,
 }
 
 /// Implement Drop to ensure cleanup
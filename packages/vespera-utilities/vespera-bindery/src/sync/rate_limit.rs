@@ -0,0 +1,223 @@
+//! Flood protection for collaboration sessions
+//!
+//! Every operation a peer sends costs one token from its connection's
+//! bucket and one from its author's bucket; operations arriving when either
+//! is empty are dropped. Buckets refill continuously at their configured
+//! rate up to their burst size, so short bursts pass and sustained floods
+//! don't. Payloads over [`SyncLimits::max_payload_bytes`] are refused before
+//! they are decoded.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BinderyError, BinderyResult, UserId};
+
+/// Rate and size limits on what peers may send
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncLimits {
+    /// Sustained operations per second from one connection
+    #[serde(default = "default_connection_rate")]
+    pub operations_per_second_per_connection: f64,
+
+    /// Operations one connection may send at once
+    #[serde(default = "default_connection_burst")]
+    pub operation_burst_per_connection: f64,
+
+    /// Sustained operations per second authored by one user, across connections
+    #[serde(default = "default_user_rate")]
+    pub operations_per_second_per_user: f64,
+
+    /// Operations one user may send at once
+    #[serde(default = "default_user_burst")]
+    pub operation_burst_per_user: f64,
+
+    /// Largest sync payload accepted, in bytes
+    #[serde(default = "default_max_payload_bytes")]
+    pub max_payload_bytes: usize,
+}
+
+fn default_connection_rate() -> f64 {
+    200.0
+}
+
+fn default_connection_burst() -> f64 {
+    1000.0
+}
+
+fn default_user_rate() -> f64 {
+    500.0
+}
+
+fn default_user_burst() -> f64 {
+    2000.0
+}
+
+fn default_max_payload_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+impl Default for SyncLimits {
+    fn default() -> Self {
+        Self {
+            operations_per_second_per_connection: default_connection_rate(),
+            operation_burst_per_connection: default_connection_burst(),
+            operations_per_second_per_user: default_user_rate(),
+            operation_burst_per_user: default_user_burst(),
+            max_payload_bytes: default_max_payload_bytes(),
+        }
+    }
+}
+
+impl SyncLimits {
+    pub fn validate(&self) -> BinderyResult<()> {
+        let rates = [
+            ("operations_per_second_per_connection", self.operations_per_second_per_connection),
+            ("operation_burst_per_connection", self.operation_burst_per_connection),
+            ("operations_per_second_per_user", self.operations_per_second_per_user),
+            ("operation_burst_per_user", self.operation_burst_per_user),
+        ];
+        for (name, value) in rates {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(BinderyError::ConfigurationError(format!(
+                    "sync_limits.{} must be a positive number",
+                    name
+                )));
+            }
+        }
+        if self.max_payload_bytes == 0 {
+            return Err(BinderyError::ConfigurationError(
+                "sync_limits.max_payload_bytes must be greater than 0".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A token bucket refilling at `rate` tokens per second up to `capacity`
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket
+    pub fn new(capacity: f64, rate: f64, now: Instant) -> Self {
+        Self { capacity, rate, tokens: capacity, refilled_at: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    /// Whether a token is available at `now`
+    pub fn has_token(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= 1.0
+    }
+
+    /// Take a token if one is available at `now`
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let available = self.has_token(now);
+        if available {
+            self.tokens -= 1.0;
+        }
+        available
+    }
+
+    /// Whether the bucket has refilled completely by `now`
+    pub fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+}
+
+/// Token buckets for each connection and user
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limits: SyncLimits,
+    connections: HashMap<String, TokenBucket>,
+    users: HashMap<UserId, TokenBucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: SyncLimits) -> Self {
+        Self { limits, connections: HashMap::new(), users: HashMap::new() }
+    }
+
+    /// Take one token from both `connection_id`'s and `user_id`'s buckets,
+    /// or from neither if either is empty
+    pub(crate) fn admit(&mut self, connection_id: &str, user_id: &str, now: Instant) -> bool {
+        let limits = &self.limits;
+        let connection = self.connections.entry(connection_id.to_string()).or_insert_with(|| {
+            TokenBucket::new(limits.operation_burst_per_connection, limits.operations_per_second_per_connection, now)
+        });
+        if !connection.has_token(now) {
+            return false;
+        }
+        let user = self.users.entry(user_id.to_string()).or_insert_with(|| {
+            TokenBucket::new(limits.operation_burst_per_user, limits.operations_per_second_per_user, now)
+        });
+        if !user.try_take(now) {
+            return false;
+        }
+        connection.try_take(now)
+    }
+
+    pub(crate) fn remove_connection(&mut self, connection_id: &str) {
+        self.connections.remove(connection_id);
+    }
+
+    /// Forget buckets that have refilled, which behave like new ones
+    pub(crate) fn prune(&mut self, now: Instant) -> usize {
+        let before = self.connections.len() + self.users.len();
+        self.connections.retain(|_, bucket| !bucket.is_full(now));
+        self.users.retain(|_, bucket| !bucket.is_full(now));
+        before - self.connections.len() - self.users.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_bursts_then_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(3.0, 2.0, start);
+        assert!((0..3).all(|_| bucket.try_take(start)));
+        assert!(!bucket.try_take(start));
+
+        // Two tokens a second
+        assert!(bucket.try_take(start + Duration::from_millis(500)));
+        assert!(!bucket.try_take(start + Duration::from_millis(500)));
+        assert!(bucket.is_full(start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_user_limit_spans_connections() {
+        let limits = SyncLimits {
+            operation_burst_per_connection: 5.0,
+            operation_burst_per_user: 3.0,
+            ..SyncLimits::default()
+        };
+        let mut limiter = RateLimiter::new(limits);
+        let now = Instant::now();
+        assert!(limiter.admit("c1", "alice", now));
+        assert!(limiter.admit("c2", "alice", now));
+        assert!(limiter.admit("c1", "alice", now));
+        assert!(!limiter.admit("c2", "alice", now));
+        // A refused user doesn't use up the connection's tokens
+        assert!((0..3).all(|_| limiter.admit("c1", "bob", now)));
+        assert!(!limiter.admit("c1", "carol", now));
+
+        assert_eq!(limiter.prune(now + Duration::from_secs(60)), 4);
+    }
+}
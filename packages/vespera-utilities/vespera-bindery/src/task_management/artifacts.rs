@@ -0,0 +1,197 @@
+//! Artifacts produced by task executions
+//!
+//! A [`TaskExecutor`](super::TaskExecutor) given an [`ArtifactStore`] keeps
+//! what each execution produced: its output transcript, and the files under
+//! its workspace matching the globs the task declares in
+//! [`OUTPUT_GLOBS_FIELD`]. Contents go to the store, addressed by their
+//! SHA-256, and the task Codex lists them in its [`ARTIFACTS_FIELD`] metadata
+//! so they sync with the task and survive the executor. The contents
+//! themselves sync through the store's [`BlobStore`].
+
+use chrono::Utc;
+use globset::{Glob, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::codex::Codex;
+use crate::crdt::{TemplateValue, VesperaCRDT};
+use crate::errors::{BinderyError, BinderyResult};
+use crate::sync::BlobStore;
+use crate::types::Attachment;
+use crate::UserId;
+
+/// Task field listing globs, relative to the workspace, of files an execution produces
+pub const OUTPUT_GLOBS_FIELD: &str = "output_globs";
+
+/// Task metadata field listing the task's [`TaskArtifact`]s
+pub const ARTIFACTS_FIELD: &str = "artifacts";
+
+/// Output files larger than this are not captured
+pub const MAX_ARTIFACT_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// What the execution printed, or its error
+    Transcript,
+    /// A file matching one of the task's output globs
+    Output,
+}
+
+/// Something one execution of a task produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskArtifact {
+    pub execution_id: String,
+    pub kind: ArtifactKind,
+    pub attachment: Attachment,
+}
+
+/// Content-addressed store for artifact contents
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    blobs: BlobStore,
+}
+
+impl ArtifactStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { blobs: BlobStore::new(root) }
+    }
+
+    /// The blobs holding artifact contents, for syncing them to peers
+    pub fn blobs(&self) -> &BlobStore {
+        &self.blobs
+    }
+
+    /// Store `content`, returning its attachment record
+    ///
+    /// Identical contents are stored once.
+    pub async fn put(
+        &self,
+        filename: impl Into<String>,
+        mime_type: impl Into<String>,
+        content: &[u8],
+        uploaded_by: &UserId,
+    ) -> BinderyResult<Attachment> {
+        let (hash, path) = self.blobs.put(content).await?;
+
+        Ok(Attachment {
+            id: uuid::Uuid::new_v4().to_string(),
+            filename: filename.into(),
+            mime_type: mime_type.into(),
+            size: content.len() as u64,
+            content_hash: hash,
+            storage_location: path.to_string_lossy().into_owned(),
+            uploaded_at: Utc::now(),
+            uploaded_by: uploaded_by.clone(),
+        })
+    }
+
+    /// Contents of an attachment, checked against its hash
+    pub async fn read(&self, attachment: &Attachment) -> BinderyResult<Vec<u8>> {
+        self.blobs.read(&attachment.content_hash).await.map_err(|e| match e {
+            BinderyError::NotFound(_) => BinderyError::NotFound(format!("Artifact {}", attachment.filename)),
+            e => e,
+        })
+    }
+}
+
+/// Output globs a task declares, as a list or comma-separated text
+pub fn declared_outputs(task: &Codex) -> Vec<String> {
+    let Some(value) = task.content.template_fields.get(OUTPUT_GLOBS_FIELD) else {
+        return Vec::new();
+    };
+    if let Some(text) = value.as_str() {
+        return text.split(',').map(str::trim).filter(|glob| !glob.is_empty()).map(str::to_string).collect();
+    }
+    value.as_array()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|glob| glob.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Files under `workspace` matching any of `globs`, sorted
+pub fn collect_outputs(workspace: &Path, globs: &[String]) -> BinderyResult<Vec<PathBuf>> {
+    if globs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|e| BinderyError::InvalidInput(format!("Invalid output glob '{}': {}", glob, e)))?);
+    }
+    let set = builder.build().map_err(|e| BinderyError::InvalidInput(e.to_string()))?;
+
+    let mut outputs: Vec<PathBuf> = walkdir::WalkDir::new(workspace)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().strip_prefix(workspace).is_ok_and(|relative| set.is_match(relative)))
+        .map(|entry| entry.into_path())
+        .collect();
+    outputs.sort();
+    Ok(outputs)
+}
+
+/// Artifacts listed on a task Codex
+pub fn artifacts_of(crdt: &VesperaCRDT) -> Vec<TaskArtifact> {
+    match crdt.get_metadata(ARTIFACTS_FIELD) {
+        Some(TemplateValue::Structured { value, .. }) => serde_json::from_value(value.clone()).unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// MIME type for a captured file, by extension
+pub fn mime_type_of(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("txt" | "log") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("json") => "application/json",
+        Some("html" | "htm") => "text/html",
+        Some("csv") => "text/csv",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("svg") => "image/svg+xml",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_store_dedups_and_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(dir.path());
+        let user = "agent".to_string();
+
+        let first = store.put("a.txt", "text/plain", b"hello", &user).await.unwrap();
+        let second = store.put("b.txt", "text/plain", b"hello", &user).await.unwrap();
+        assert_eq!(first.content_hash, second.content_hash);
+        assert_ne!(first.id, second.id);
+        assert_eq!(store.read(&second).await.unwrap(), b"hello");
+
+        std::fs::write(&first.storage_location, b"tampered").unwrap();
+        assert!(matches!(store.read(&first).await, Err(BinderyError::IoError(_))));
+    }
+
+    #[test]
+    fn test_collect_outputs_matches_relative_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("dist/assets")).unwrap();
+        std::fs::write(dir.path().join("dist/app.js"), "").unwrap();
+        std::fs::write(dir.path().join("dist/assets/logo.png"), "").unwrap();
+        std::fs::write(dir.path().join("report.md"), "").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let globs = vec!["dist/**".to_string(), "*.md".to_string()];
+        let outputs: Vec<_> = collect_outputs(dir.path(), &globs).unwrap()
+            .into_iter()
+            .map(|path| path.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        assert_eq!(outputs, vec!["dist/app.js", "dist/assets/logo.png", "report.md"]);
+        assert!(collect_outputs(dir.path(), &[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+//! Provider-suggested task breakdowns
+//!
+//! [`TaskManager::suggest_breakdown`](super::TaskManager::suggest_breakdown)
+//! asks an LLM provider from the providers module to split a task into
+//! subtasks. Nothing is created until the caller passes the suggestion, as is
+//! or edited, to [`TaskManager::accept_breakdown`](super::TaskManager::accept_breakdown),
+//! which goes through the normal task creation path. Breakdowns are off
+//! unless a provider is configured with
+//! [`TaskManager::with_breakdown_provider`](super::TaskManager::with_breakdown_provider).
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::TaskPriority;
+use crate::errors::{BinderyError, BinderyResult};
+use crate::providers::ProviderManager;
+use crate::CodexId;
+
+/// Which provider suggests breakdowns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakdownConfig {
+    /// Provider loaded by the `ProviderManager`
+    pub provider_id: String,
+
+    /// Model override; the provider's default otherwise
+    pub model: Option<String>,
+
+    /// Suggestions are cut to this many subtasks
+    pub max_subtasks: usize,
+}
+
+impl BreakdownConfig {
+    pub fn new(provider_id: impl Into<String>) -> Self {
+        Self {
+            provider_id: provider_id.into(),
+            model: None,
+            max_subtasks: 8,
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_max_subtasks(mut self, max_subtasks: usize) -> Self {
+        self.max_subtasks = max_subtasks;
+        self
+    }
+}
+
+/// One proposed subtask
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubtaskSuggestion {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Indices of the subtasks in the same suggestion this one depends on
+    #[serde(default)]
+    pub depends_on: Vec<usize>,
+}
+
+/// Proposed subtasks for a task, not yet created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownSuggestion {
+    pub task_id: CodexId,
+    pub subtasks: Vec<SubtaskSuggestion>,
+    pub rationale: Option<String>,
+}
+
+/// Shape the provider is asked to reply with
+#[derive(Deserialize)]
+struct BreakdownReply {
+    subtasks: Vec<SubtaskSuggestion>,
+    #[serde(default)]
+    rationale: Option<String>,
+}
+
+const BREAKDOWN_SYSTEM_PROMPT: &str = "You split software tasks into smaller subtasks. \
+Reply with only a JSON object of the form \
+{\"subtasks\": [{\"title\": string, \"description\": string, \"priority\": \"low\"|\"normal\"|\"high\"|\"critical\", \
+\"role\": string or null, \"depends_on\": [indices of earlier subtasks]}], \"rationale\": string}. \
+Subtasks are listed in the order they should be done.";
+
+/// Sends tasks to the configured provider for breakdown
+pub(crate) struct BreakdownProvider {
+    providers: Arc<ProviderManager>,
+    config: BreakdownConfig,
+}
+
+impl std::fmt::Debug for BreakdownProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BreakdownProvider")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl BreakdownProvider {
+    pub(crate) fn new(providers: Arc<ProviderManager>, config: BreakdownConfig) -> Self {
+        Self { providers, config }
+    }
+
+    pub(crate) async fn suggest(
+        &self,
+        task_id: &CodexId,
+        title: &str,
+        description: Option<&str>,
+    ) -> BinderyResult<BreakdownSuggestion> {
+        let prompt = build_prompt(title, description, self.config.max_subtasks);
+        let response = self.providers
+            .send_message(
+                &self.config.provider_id,
+                &prompt,
+                self.config.model.as_deref(),
+                None,
+                Some(BREAKDOWN_SYSTEM_PROMPT),
+                false,
+            )
+            .await
+            .map_err(|e| BinderyError::InternalError(e.to_string()))?;
+        parse_breakdown(task_id, &response.text, self.config.max_subtasks)
+    }
+}
+
+fn build_prompt(title: &str, description: Option<&str>, max_subtasks: usize) -> String {
+    let mut prompt = format!("Task: {}\n\n", title);
+    if let Some(description) = description.filter(|description| !description.trim().is_empty()) {
+        prompt.push_str(&format!("Description:\n{}\n\n", description));
+    }
+    prompt.push_str(&format!("Propose at most {} subtasks.", max_subtasks));
+    prompt
+}
+
+/// Pull the breakdown out of a reply that may wrap it in prose or a code fence
+///
+/// Dependencies that point at the subtask itself, a later subtask, or past the
+/// end of the list are dropped, so the result always orders cleanly.
+fn parse_breakdown(task_id: &CodexId, reply: &str, max_subtasks: usize) -> BinderyResult<BreakdownSuggestion> {
+    let malformed = || BinderyError::InvalidInput("Breakdown reply has no JSON object".to_string());
+    let start = reply.find('{').ok_or_else(malformed)?;
+    let end = reply.rfind('}').ok_or_else(malformed)?;
+    if end < start {
+        return Err(malformed());
+    }
+
+    let parsed: BreakdownReply = serde_json::from_str(&reply[start..=end])
+        .map_err(|e| BinderyError::InvalidInput(format!("Malformed breakdown reply: {}", e)))?;
+
+    // Untitled subtasks are dropped, so indices are remapped onto the kept ones
+    let mut kept = Vec::new();
+    let mut new_index = Vec::with_capacity(parsed.subtasks.len());
+    for subtask in parsed.subtasks {
+        if subtask.title.trim().is_empty() || kept.len() == max_subtasks {
+            new_index.push(None);
+        } else {
+            new_index.push(Some(kept.len()));
+            kept.push(subtask);
+        }
+    }
+
+    for (index, subtask) in kept.iter_mut().enumerate() {
+        let before = subtask.depends_on.len();
+        let mut depends_on: Vec<usize> = subtask.depends_on
+            .iter()
+            .filter_map(|&dependency| new_index.get(dependency).copied().flatten())
+            .filter(|&dependency| dependency < index)
+            .collect();
+        depends_on.sort_unstable();
+        depends_on.dedup();
+        if depends_on.len() != before {
+            tracing::warn!("Dropped invalid dependencies of suggested subtask '{}'", subtask.title);
+        }
+        subtask.depends_on = depends_on;
+    }
+
+    Ok(BreakdownSuggestion {
+        task_id: *task_id,
+        subtasks: kept,
+        rationale: parsed.rationale,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_breakdown_from_fenced_reply() {
+        let task_id = uuid::Uuid::new_v4();
+        let reply = "Here you go:\n```json\n{\"subtasks\": [\
+            {\"title\": \"Write schema\", \"priority\": \"high\"},\
+            {\"title\": \"Migrate data\", \"depends_on\": [0, 1, 5]},\
+            {\"title\": \"  \"},\
+            {\"title\": \"Announce\", \"depends_on\": [1, 2]}\
+            ], \"rationale\": \"schema first\"}\n```";
+
+        let suggestion = parse_breakdown(&task_id, reply, 8).unwrap();
+        assert_eq!(suggestion.task_id, task_id);
+        assert_eq!(suggestion.rationale.as_deref(), Some("schema first"));
+        let titles: Vec<_> = suggestion.subtasks.iter().map(|subtask| subtask.title.as_str()).collect();
+        assert_eq!(titles, vec!["Write schema", "Migrate data", "Announce"]);
+        assert_eq!(suggestion.subtasks[0].priority, Some(TaskPriority::High));
+        assert_eq!(suggestion.subtasks[1].depends_on, vec![0]);
+        assert_eq!(suggestion.subtasks[2].depends_on, vec![1]);
+
+        let capped = parse_breakdown(&task_id, reply, 1).unwrap();
+        assert_eq!(capped.subtasks.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_breakdown_rejects_malformed_reply() {
+        let task_id = uuid::Uuid::new_v4();
+        assert!(matches!(parse_breakdown(&task_id, "no idea", 8), Err(BinderyError::InvalidInput(_))));
+        assert!(matches!(parse_breakdown(&task_id, "{\"steps\": []}", 8), Err(BinderyError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_build_prompt_skips_empty_description() {
+        assert_eq!(build_prompt("Ship it", Some("  "), 3), "Task: Ship it\n\nPropose at most 3 subtasks.");
+        assert!(build_prompt("Ship it", Some("Release 2.0"), 3).contains("Description:\nRelease 2.0"));
+    }
+}
@@ -0,0 +1,327 @@
+//! Project context assembled for task executions
+//!
+//! A [`TaskExecutor`](super::TaskExecutor) given a [`ContextAssembler`]
+//! gathers, before each execution, what the task builds on: summaries of the
+//! tasks it depends on, the Codices it links to, and the top RAG results for
+//! its title and description. Sections are packed in that order, search hits
+//! by score, until the token budget runs out, and the bundle is attached to
+//! the [`ExecutionContext`](super::ExecutionContext).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::crdt::{ReferenceType, TemplateValue, VesperaCRDT};
+use crate::errors::{BinderyError, BinderyResult};
+use crate::rag::batching::estimate_tokens;
+use crate::rag::RAGService;
+use crate::{CodexId, CodexManager};
+
+/// How much context is gathered for an execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextConfig {
+    /// RAG results considered for the bundle
+    pub top_k: usize,
+
+    /// Upper bound on the bundle's estimated token count
+    pub token_budget: usize,
+
+    /// A section cut to fit the budget keeps at least this many tokens, or is left out
+    pub min_section_tokens: usize,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 5,
+            token_budget: 4000,
+            min_section_tokens: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSource {
+    /// A task this task depends on
+    Dependency,
+    /// A Codex this task references
+    LinkedCodex,
+    /// A RAG search result
+    Search,
+}
+
+impl ContextSource {
+    fn heading(&self) -> &'static str {
+        match self {
+            ContextSource::Dependency => "Dependencies",
+            ContextSource::LinkedCodex => "Linked Codices",
+            ContextSource::Search => "Related Project Content",
+        }
+    }
+}
+
+/// One piece of gathered context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextSection {
+    pub source: ContextSource,
+    pub title: String,
+    /// Codex the section came from, if any
+    pub codex_id: Option<CodexId>,
+    pub content: String,
+    pub tokens: usize,
+}
+
+impl ContextSection {
+    fn new(source: ContextSource, title: String, codex_id: Option<CodexId>, content: String) -> Self {
+        let tokens = estimate_tokens(&title) + estimate_tokens(&content);
+        Self { source, title, codex_id, content, tokens }
+    }
+}
+
+/// Context gathered for one execution
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextBundle {
+    pub sections: Vec<ContextSection>,
+    /// Estimated tokens of all sections
+    pub tokens: usize,
+    /// Whether anything was cut or left out to fit the budget
+    pub truncated: bool,
+}
+
+impl ContextBundle {
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// The bundle as Markdown, grouped by source
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        for source in [ContextSource::Dependency, ContextSource::LinkedCodex, ContextSource::Search] {
+            let mut sections = self.sections.iter().filter(|section| section.source == source).peekable();
+            if sections.peek().is_none() {
+                continue;
+            }
+            text.push_str(&format!("## {}\n\n", source.heading()));
+            for section in sections {
+                text.push_str(&format!("### {}\n{}\n\n", section.title, section.content.trim_end()));
+            }
+        }
+        text
+    }
+}
+
+/// Gathers [`ContextBundle`]s for tasks
+#[derive(Clone)]
+pub struct ContextAssembler {
+    codex_manager: Arc<CodexManager>,
+    rag: Option<Arc<RAGService>>,
+    config: ContextConfig,
+}
+
+impl std::fmt::Debug for ContextAssembler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextAssembler")
+            .field("rag", &self.rag.is_some())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl ContextAssembler {
+    pub fn new(codex_manager: Arc<CodexManager>, config: ContextConfig) -> Self {
+        Self {
+            codex_manager,
+            rag: None,
+            config,
+        }
+    }
+
+    /// Include RAG search results for the task
+    pub fn with_rag(mut self, rag: Arc<RAGService>) -> Self {
+        self.rag = Some(rag);
+        self
+    }
+
+    /// Gather context for `task_id` within the configured budget
+    ///
+    /// Dependencies or links that can't be read, and a failed search, are
+    /// logged and skipped rather than failing the execution.
+    pub async fn assemble(&self, task_id: &CodexId) -> BinderyResult<ContextBundle> {
+        let task = self.codex_manager.get_codex(task_id).await
+            .ok_or_else(|| BinderyError::NotFound(format!("Task {}", task_id)))?;
+
+        let mut seen: HashSet<CodexId> = HashSet::from([*task_id]);
+        let mut candidates = Vec::new();
+
+        let mut references: Vec<_> = task.reference_layer.elements().into_iter()
+            .filter(|reference| reference.from_codex_id == *task_id)
+            .filter_map(|reference| match reference.reference_type {
+                ReferenceType::DependsOn => Some((ContextSource::Dependency, reference.to_codex_id)),
+                ReferenceType::References | ReferenceType::Related => Some((ContextSource::LinkedCodex, reference.to_codex_id)),
+                _ => None,
+            })
+            .collect();
+        // Dependencies outrank links when the budget is tight
+        references.sort_by_key(|(source, _)| *source != ContextSource::Dependency);
+
+        for (source, codex_id) in references {
+            if !seen.insert(codex_id) {
+                continue;
+            }
+            match self.codex_manager.get_codex(&codex_id).await {
+                Some(crdt) => candidates.push(ContextSection::new(
+                    source,
+                    crdt.get_title().unwrap_or_else(|| codex_id.to_string()),
+                    Some(codex_id),
+                    summarize(&crdt),
+                )),
+                None => tracing::warn!(task_id = %task_id, codex_id = %codex_id, "Linked Codex not found for execution context"),
+            }
+        }
+
+        if let Some(rag) = &self.rag {
+            let query = search_query(&task);
+            if !query.is_empty() && self.config.top_k > 0 {
+                match rag.search(&query, self.config.top_k, None).await {
+                    Ok(results) => {
+                        for result in results {
+                            let codex_id = result.metadata.bindery_source.as_ref().map(|source| source.codex_id());
+                            if codex_id.is_some_and(|id| seen.contains(&id)) {
+                                continue;
+                            }
+                            candidates.push(ContextSection::new(
+                                ContextSource::Search,
+                                result.metadata.title.clone(),
+                                codex_id,
+                                result.content,
+                            ));
+                        }
+                    }
+                    Err(e) => tracing::warn!(task_id = %task_id, "Context search failed: {}", e),
+                }
+            }
+        }
+
+        Ok(pack(candidates, &self.config))
+    }
+}
+
+/// Title and description of a task, used as its search query
+fn search_query(task: &VesperaCRDT) -> String {
+    let mut query = task.get_title().unwrap_or_default();
+    if let Some(TemplateValue::Text { value, .. }) = task.get_metadata("description") {
+        query.push('\n');
+        query.push_str(value);
+    }
+    query.trim().to_string()
+}
+
+/// Status, description and text fields of a Codex
+fn summarize(crdt: &VesperaCRDT) -> String {
+    let mut summary = String::new();
+    for key in ["status", "priority"] {
+        match crdt.get_metadata(key) {
+            Some(TemplateValue::Text { value, .. }) => summary.push_str(&format!("{}: {}\n", key, value.trim_matches('"'))),
+            Some(TemplateValue::Structured { value, .. }) => summary.push_str(&format!("{}: {}\n", key, value)),
+            _ => {}
+        }
+    }
+    if let Some(TemplateValue::Text { value, .. }) = crdt.get_metadata("description") {
+        summary.push_str(value);
+        summary.push('\n');
+    }
+    let mut fields: Vec<_> = crdt.text_layer.field_ids().into_iter().collect();
+    fields.sort();
+    for field in fields {
+        if let Some(text) = crdt.get_text(field) {
+            let text = text.to_string();
+            if !text.trim().is_empty() {
+                summary.push_str(&text);
+                summary.push('\n');
+            }
+        }
+    }
+    summary
+}
+
+/// Keep `candidates`, in order, while they fit the budget
+///
+/// The first section that doesn't fit is cut to the remaining budget if at
+/// least `min_section_tokens` remain; everything after it is left out.
+fn pack(candidates: Vec<ContextSection>, config: &ContextConfig) -> ContextBundle {
+    let mut bundle = ContextBundle::default();
+    for mut section in candidates {
+        let remaining = config.token_budget.saturating_sub(bundle.tokens);
+        if section.tokens > remaining {
+            bundle.truncated = true;
+            let title_tokens = estimate_tokens(&section.title);
+            if remaining < config.min_section_tokens.max(title_tokens + 1) {
+                break;
+            }
+            let keep_chars = (remaining - title_tokens) * 4;
+            section.content = section.content.chars().take(keep_chars.saturating_sub(1)).collect();
+            section.content.push('…');
+            section.tokens = title_tokens + estimate_tokens(&section.content);
+            bundle.tokens += section.tokens;
+            bundle.sections.push(section);
+            break;
+        }
+        bundle.tokens += section.tokens;
+        bundle.sections.push(section);
+    }
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(source: ContextSource, title: &str, chars: usize) -> ContextSection {
+        ContextSection::new(source, title.to_string(), None, "x".repeat(chars))
+    }
+
+    #[test]
+    fn test_pack_cuts_first_overflow_and_drops_rest() {
+        let config = ContextConfig { top_k: 5, token_budget: 200, min_section_tokens: 16 };
+        let candidates = vec![
+            section(ContextSource::Dependency, "Schema", 400),
+            section(ContextSource::Search, "Guide", 800),
+            section(ContextSource::Search, "Notes", 40),
+        ];
+
+        let bundle = pack(candidates, &config);
+        assert!(bundle.truncated);
+        assert_eq!(bundle.sections.len(), 2);
+        assert!(bundle.tokens <= config.token_budget);
+        assert!(bundle.sections[1].content.ends_with('…'));
+
+        let roomy = ContextConfig { token_budget: 10_000, ..config };
+        let bundle = pack(vec![section(ContextSource::Search, "Notes", 40)], &roomy);
+        assert!(!bundle.truncated);
+        assert_eq!(bundle.sections[0].content.len(), 40);
+    }
+
+    #[test]
+    fn test_pack_leaves_out_sections_too_small_to_cut() {
+        let config = ContextConfig { top_k: 5, token_budget: 110, min_section_tokens: 64 };
+        let bundle = pack(vec![
+            section(ContextSource::Dependency, "Schema", 400),
+            section(ContextSource::Search, "Guide", 800),
+        ], &config);
+        assert!(bundle.truncated);
+        assert_eq!(bundle.sections.len(), 1);
+    }
+
+    #[test]
+    fn test_render_groups_by_source() {
+        let bundle = pack(vec![
+            section(ContextSource::Search, "Guide", 4),
+            section(ContextSource::Dependency, "Schema", 4),
+        ], &ContextConfig::default());
+        let text = bundle.render();
+        assert!(text.find("## Dependencies").unwrap() < text.find("## Related Project Content").unwrap());
+        assert!(text.contains("### Schema\nxxxx\n"));
+        assert!(!text.contains("## Linked Codices"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Semantic duplicate detection for new tasks
+//!
+//! A [`TaskService`](super::TaskService) given a [`RAGService`] with
+//! [`with_duplicate_check`](super::TaskService::with_duplicate_check) compares
+//! each task created through
+//! [`create_task_with_duplicate_check`](super::TaskService::create_task_with_duplicate_check)
+//! with the open tasks the Bindery indexer has embedded. Tasks whose embedding
+//! similarity reaches the threshold come back with the creation result. The
+//! task is still created; acting on the duplicates is left to the caller.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::TaskStatus;
+use crate::errors::{BinderyError, BinderyResult};
+use crate::rag::{BinderySource, RAGService, SearchMode, SearchOptions};
+use crate::{CodexId, CodexManager};
+
+/// When a task counts as a likely duplicate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCheckConfig {
+    /// Minimum embedding similarity, from 0 to 1
+    pub threshold: f32,
+
+    /// Most duplicates reported per task
+    pub limit: usize,
+}
+
+impl Default for DuplicateCheckConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.85,
+            limit: 5,
+        }
+    }
+}
+
+/// An open task similar to one being created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateTask {
+    pub task_id: CodexId,
+    pub title: String,
+    /// Embedding similarity to the new task
+    pub score: f32,
+}
+
+/// A created task and the open tasks it likely duplicates, most similar first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskCreationResult {
+    pub task_id: CodexId,
+    pub duplicates: Vec<DuplicateTask>,
+}
+
+/// Searches indexed tasks for ones similar to a new task
+#[derive(Clone)]
+pub(crate) struct DuplicateDetector {
+    rag: Arc<RAGService>,
+    config: DuplicateCheckConfig,
+}
+
+impl std::fmt::Debug for DuplicateDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DuplicateDetector")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl DuplicateDetector {
+    pub(crate) fn new(rag: Arc<RAGService>, config: DuplicateCheckConfig) -> Self {
+        Self { rag, config }
+    }
+
+    /// Open tasks similar to a task with this title and description
+    pub(crate) async fn find(
+        &self,
+        codex_manager: &CodexManager,
+        title: &str,
+        description: Option<&str>,
+    ) -> BinderyResult<Vec<DuplicateTask>> {
+        if self.config.limit == 0 {
+            return Ok(Vec::new());
+        }
+        let query = match description.filter(|description| !description.trim().is_empty()) {
+            Some(description) => format!("{}\n{}", title, description),
+            None => title.to_string(),
+        };
+        let options = SearchOptions {
+            mode: SearchMode::Vector,
+            rerank: false,
+            ..SearchOptions::default()
+        };
+        // Chunks of other documents share the result list, so ask for more than `limit`
+        let results = self.rag
+            .search_with_options(&query, self.config.limit * 4, None, &options)
+            .await
+            .map_err(|e| BinderyError::RagSearchError(e.to_string()))?;
+
+        let mut candidates = Vec::new();
+        for result in results {
+            let Some(BinderySource::Task(task_id)) = result.metadata.bindery_source else {
+                continue;
+            };
+            // Deleted, trashed and finished tasks aren't worth flagging
+            let Some(crdt) = codex_manager.get_codex(&task_id).await else {
+                continue;
+            };
+            if TaskStatus::of_task(&crdt).is_some_and(|status| !status.is_open()) {
+                continue;
+            }
+            let title = crdt.get_title().unwrap_or_else(|| result.metadata.title.clone());
+            candidates.push(DuplicateTask { task_id, title, score: result.score });
+        }
+
+        Ok(rank_duplicates(candidates, &self.config))
+    }
+}
+
+/// One entry per task with its best score, above the threshold, most similar first
+fn rank_duplicates(candidates: Vec<DuplicateTask>, config: &DuplicateCheckConfig) -> Vec<DuplicateTask> {
+    let mut best: HashMap<CodexId, DuplicateTask> = HashMap::new();
+    for candidate in candidates.into_iter().filter(|candidate| candidate.score >= config.threshold) {
+        match best.get(&candidate.task_id) {
+            Some(existing) if existing.score >= candidate.score => {}
+            _ => {
+                best.insert(candidate.task_id, candidate);
+            }
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateTask> = best.into_values().collect();
+    duplicates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    duplicates.truncate(config.limit);
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(task_id: CodexId, score: f32) -> DuplicateTask {
+        DuplicateTask { task_id, title: "Fix login".to_string(), score }
+    }
+
+    #[test]
+    fn test_rank_duplicates_keeps_best_score_per_task() {
+        let (a, b, c) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        let config = DuplicateCheckConfig { threshold: 0.8, limit: 2 };
+        let ranked = rank_duplicates(vec![
+            candidate(a, 0.82),
+            candidate(b, 0.95),
+            candidate(a, 0.9),
+            candidate(c, 0.5),
+        ], &config);
+        assert_eq!(ranked, vec![candidate(b, 0.95), candidate(a, 0.9)]);
+
+        let config = DuplicateCheckConfig { threshold: 0.8, limit: 1 };
+        assert_eq!(rank_duplicates(vec![candidate(a, 0.82), candidate(b, 0.95)], &config), vec![candidate(b, 0.95)]);
+    }
+}
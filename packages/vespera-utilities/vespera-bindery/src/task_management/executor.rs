@@ -5,6 +5,10 @@
 /// engine that can be used by TaskManager for actual task processing.
 
 use super::{TaskService, TaskExecutionResult, models::ExecutionStatus};
+use super::artifacts::{
+    collect_outputs, declared_outputs, mime_type_of, ArtifactKind, ArtifactStore, TaskArtifact, MAX_ARTIFACT_BYTES,
+};
+use super::context::{ContextAssembler, ContextBundle};
 use crate::role_management::{RoleManager, Role};
 use crate::codex::Codex;
 use crate::CodexId;
@@ -14,6 +18,7 @@ use crate::observability::{
     metrics::BinderyMetrics,
     instrument,
 };
+use std::path::PathBuf;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -28,6 +33,9 @@ use tracing::{info, warn, error, debug};
 pub struct TaskExecutor {
     task_service: Arc<TaskService>,
     role_manager: Arc<RoleManager>,
+    /// Where artifacts are stored, and the workspace output globs are relative to
+    artifact_store: Option<(Arc<ArtifactStore>, PathBuf)>,
+    context_assembler: Option<ContextAssembler>,
 }
 
 /// Execution context for tracking task execution state
@@ -38,6 +46,8 @@ pub struct ExecutionContext {
     pub role_name: String,
     pub started_at: DateTime<Utc>,
     pub timeout_duration: Option<std::time::Duration>,
+    /// Project context gathered for the task; empty without a context assembler
+    pub context: ContextBundle,
 }
 
 impl TaskExecutor {
@@ -46,9 +56,24 @@ impl TaskExecutor {
         Self {
             task_service,
             role_manager,
+            artifact_store: None,
+            context_assembler: None,
         }
     }
 
+    /// Capture each execution's transcript, and files in `workspace` matching
+    /// the task's output globs, into `store`
+    pub fn with_artifact_store(mut self, store: Arc<ArtifactStore>, workspace: impl Into<PathBuf>) -> Self {
+        self.artifact_store = Some((store, workspace.into()));
+        self
+    }
+
+    /// Gather project context for each execution with `assembler`
+    pub fn with_context_assembler(mut self, assembler: ContextAssembler) -> Self {
+        self.context_assembler = Some(assembler);
+        self
+    }
+
     /// Execute a task with role constraints
     ///
     /// This is the main execution method that:
@@ -120,6 +145,7 @@ impl TaskExecutor {
                     started_at,
                     timeout_duration: role.execution_context.max_execution_time
                         .map(|secs| std::time::Duration::from_secs(secs)),
+                    context: self.assemble_context(task_id).await,
                 };
 
                 // Execute with timeout handling
@@ -147,7 +173,8 @@ impl TaskExecutor {
                 };
 
                 // Record execution result
-                let execution_result = self.create_execution_result(&context, result).await;
+                let mut execution_result = self.create_execution_result(&context, result).await;
+                execution_result.artifacts = self.capture_artifacts(&task, &execution_result).await;
                 self.task_service.record_execution(execution_result.clone()).await?;
 
                 info!(
@@ -193,11 +220,13 @@ impl TaskExecutor {
             started_at: Utc::now(),
             timeout_duration: role.execution_context.max_execution_time
                 .map(|secs| std::time::Duration::from_secs(secs)),
+            context: self.assemble_context(task_id).await,
         };
 
         // Execute the task
         let result = self.execute_task_with_role(&task, &role, &context).await;
-        let execution_result = self.create_execution_result(&context, result).await;
+        let mut execution_result = self.create_execution_result(&context, result).await;
+        execution_result.artifacts = self.capture_artifacts(&task, &execution_result).await;
 
         // Record the execution
         self.task_service.record_execution(execution_result.clone()).await?;
@@ -225,8 +254,97 @@ impl TaskExecutor {
         ))
     }
 
+    /// Artifacts captured for a task, oldest first
+    pub async fn artifacts(&self, task_id: &CodexId) -> BinderyResult<Vec<TaskArtifact>> {
+        self.task_service.list_artifacts(task_id).await
+    }
+
+    /// Contents of a captured artifact
+    pub async fn read_artifact(&self, artifact: &TaskArtifact) -> BinderyResult<Vec<u8>> {
+        let (store, _) = self.artifact_store.as_ref()
+            .ok_or_else(|| BinderyError::InvalidOperation("Executor has no artifact store".to_string()))?;
+        store.read(&artifact.attachment).await
+    }
+
     // Private helper methods
 
+    /// Context for an execution, empty if none can be gathered
+    async fn assemble_context(&self, task_id: &CodexId) -> ContextBundle {
+        let Some(assembler) = &self.context_assembler else {
+            return ContextBundle::default();
+        };
+        match assembler.assemble(task_id).await {
+            Ok(bundle) => {
+                debug!(
+                    task_id = %task_id,
+                    sections = bundle.sections.len(),
+                    tokens = bundle.tokens,
+                    truncated = bundle.truncated,
+                    "Assembled execution context"
+                );
+                bundle
+            }
+            Err(e) => {
+                warn!(task_id = %task_id, "Failed to assemble execution context: {}", e);
+                ContextBundle::default()
+            }
+        }
+    }
+
+    /// Store what an execution produced and link it to the task
+    ///
+    /// Failures are logged rather than failing the execution.
+    async fn capture_artifacts(&self, task: &Codex, result: &TaskExecutionResult) -> Vec<TaskArtifact> {
+        let Some((store, workspace)) = &self.artifact_store else {
+            return Vec::new();
+        };
+        let user = self.task_service.acting_user();
+        let artifact = |kind, attachment| TaskArtifact {
+            execution_id: result.execution_id.clone(),
+            kind,
+            attachment,
+        };
+        let mut artifacts = Vec::new();
+
+        let transcript = result.output.as_deref().or(result.error.as_deref()).unwrap_or_default();
+        match store.put("transcript.txt", "text/plain", transcript.as_bytes(), &user).await {
+            Ok(attachment) => artifacts.push(artifact(ArtifactKind::Transcript, attachment)),
+            Err(e) => warn!(task_id = %task.id, "Failed to store execution transcript: {}", e),
+        }
+
+        let globs = declared_outputs(task);
+        let root = workspace.clone();
+        let outputs = tokio::task::spawn_blocking(move || collect_outputs(&root, &globs))
+            .await
+            .map_err(|e| BinderyError::InternalError(e.to_string()))
+            .and_then(|outputs| outputs)
+            .unwrap_or_else(|e| {
+                warn!(task_id = %task.id, "Failed to collect task outputs: {}", e);
+                Vec::new()
+            });
+        for path in outputs {
+            let size = tokio::fs::metadata(&path).await.map(|metadata| metadata.len()).unwrap_or(0);
+            if size > MAX_ARTIFACT_BYTES {
+                warn!(task_id = %task.id, path = %path.display(), size, "Output too large to capture");
+                continue;
+            }
+            let filename = path.strip_prefix(workspace).unwrap_or(&path).to_string_lossy().into_owned();
+            let stored = match tokio::fs::read(&path).await {
+                Ok(content) => store.put(filename, mime_type_of(&path), &content, &user).await,
+                Err(e) => Err(BinderyError::IoError(e.to_string())),
+            };
+            match stored {
+                Ok(attachment) => artifacts.push(artifact(ArtifactKind::Output, attachment)),
+                Err(e) => warn!(task_id = %task.id, path = %path.display(), "Failed to capture output: {}", e),
+            }
+        }
+
+        if let Err(e) = self.task_service.attach_artifacts(&task.id, &artifacts).await {
+            warn!(task_id = %task.id, "Failed to link artifacts to task: {}", e);
+        }
+        artifacts
+    }
+
     /// Extract the role name from a task's metadata
     fn extract_role_from_task(&self, task: &Codex) -> BinderyResult<String> {
         // Check for role in template fields first
@@ -303,6 +421,7 @@ impl TaskExecutor {
                 started_at: context.started_at,
                 completed_at: Some(completed_at),
                 duration_ms: Some(duration_ms),
+                artifacts: Vec::new(),
             },
             Err(error) => TaskExecutionResult {
                 task_id: context.task_id,
@@ -313,6 +432,7 @@ impl TaskExecutor {
                 started_at: context.started_at,
                 completed_at: Some(completed_at),
                 duration_ms: Some(duration_ms),
+                artifacts: Vec::new(),
             },
         }
     }
@@ -8,9 +8,16 @@ use super::{
     TaskService, TaskStatus, TaskPriority, TaskRelation, TaskInput, TaskUpdateInput,
     TaskTree, TaskSummary, TaskDashboard, TaskExecutionResult, ExecutionStatus
 };
+use super::breakdown::{BreakdownConfig, BreakdownProvider, BreakdownSuggestion};
+use super::duplicates::{DuplicateCheckConfig, TaskCreationResult};
+use super::quotas::QuotaEnforcer;
+use crate::codex::Codex;
 use crate::{CodexId, CodexManager};
-use crate::role_management::RoleManager;
+use crate::role_management::{GatedAction, Role, RoleManager, ToolGroup};
+use crate::observability::UserContext;
 use crate::hook_system::HookManager;
+use crate::providers::ProviderManager;
+use crate::rag::RAGService;
 use crate::errors::{BinderyError, BinderyResult};
 use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
@@ -21,10 +28,13 @@ use std::collections::HashMap;
 /// Task manager coordinating task lifecycle with roles and hooks
 #[derive(Debug)]
 pub struct TaskManager {
+    codex_manager: Arc<CodexManager>,
     task_service: Arc<TaskService>,
     role_manager: Arc<RoleManager>,
     hook_manager: Arc<HookManager>,
     active_executions: Arc<RwLock<HashMap<String, TaskExecutionContext>>>,
+    breakdown: Option<BreakdownProvider>,
+    quotas: QuotaEnforcer,
 }
 
 /// Context for tracking active task executions
@@ -45,18 +55,37 @@ impl TaskManager {
         role_manager: Arc<RoleManager>,
         hook_manager: Arc<HookManager>,
     ) -> Self {
-        let task_service = Arc::new(TaskService::new(codex_manager));
-        
+        let task_service = Arc::new(TaskService::new(codex_manager.clone()));
+        let quotas = QuotaEnforcer::new(codex_manager.config().quotas.clone());
+
         Self {
+            codex_manager,
             task_service,
             role_manager,
             hook_manager,
             active_executions: Arc::new(RwLock::new(HashMap::new())),
+            breakdown: None,
+            quotas,
         }
     }
 
+    /// Check tasks created with `create_task_with_duplicate_check` against
+    /// the open tasks indexed in `rag`
+    pub fn with_duplicate_check(mut self, rag: Arc<RAGService>, config: DuplicateCheckConfig) -> Self {
+        self.task_service = Arc::new(TaskService::new(self.codex_manager.clone()).with_duplicate_check(rag, config));
+        self
+    }
+
+    /// Let `suggest_breakdown` ask the provider named in `config`
+    pub fn with_breakdown_provider(mut self, providers: Arc<ProviderManager>, config: BreakdownConfig) -> Self {
+        self.breakdown = Some(BreakdownProvider::new(providers, config));
+        self
+    }
+
     /// Create a new task with hook integration
     pub async fn create_task(&self, input: TaskInput) -> BinderyResult<CodexId> {
+        self.check_task_quota(&input).await?;
+
         // Pre-creation hooks
         self.hook_manager.trigger_pre_task_create(&input).await?;
 
@@ -69,6 +98,15 @@ impl TaskManager {
         Ok(task_id)
     }
 
+    /// Create a task with hook integration, reporting open tasks it likely duplicates
+    pub async fn create_task_with_duplicate_check(&self, input: TaskInput) -> BinderyResult<TaskCreationResult> {
+        self.check_task_quota(&input).await?;
+        self.hook_manager.trigger_pre_task_create(&input).await?;
+        let result = self.task_service.create_task_with_duplicate_check(input.clone()).await?;
+        self.hook_manager.trigger_post_task_create(&result.task_id, &input).await?;
+        Ok(result)
+    }
+
     /// Create a hierarchical task tree
     pub async fn create_task_tree(
         &self,
@@ -184,6 +222,56 @@ impl TaskManager {
         self.task_service.add_task_dependency(task_id, depends_on_task_id, relation).await
     }
 
+    /// Ask the breakdown provider to split a task into subtasks
+    ///
+    /// Nothing is created; pass the suggestion to `accept_breakdown` to keep it.
+    pub async fn suggest_breakdown(&self, task_id: &CodexId) -> BinderyResult<BreakdownSuggestion> {
+        let breakdown = self.breakdown.as_ref()
+            .ok_or_else(|| BinderyError::ConfigurationError("Task breakdown is not configured".to_string()))?;
+        let task = self.codex_manager.get_codex(task_id).await
+            .ok_or_else(|| BinderyError::NotFound(format!("Task {}", task_id)))?;
+
+        let title = task.get_title().unwrap_or_default();
+        let description = match task.get_metadata("description") {
+            Some(crate::crdt::TemplateValue::Text { value, .. }) => Some(value.as_str()),
+            _ => None,
+        };
+        breakdown.suggest(task_id, &title, description).await
+    }
+
+    /// Create the subtasks of a breakdown under its task, with their dependencies
+    ///
+    /// Returns the new task IDs in suggestion order.
+    pub async fn accept_breakdown(&self, suggestion: &BreakdownSuggestion) -> BinderyResult<Vec<CodexId>> {
+        let mut created = Vec::with_capacity(suggestion.subtasks.len());
+        for subtask in &suggestion.subtasks {
+            let input = TaskInput {
+                title: subtask.title.clone(),
+                description: subtask.description.clone(),
+                priority: subtask.priority.clone(),
+                project_id: None,
+                parent_id: Some(suggestion.task_id),
+                assignee: None,
+                due_date: None,
+                role: subtask.role.clone(),
+                tags: Vec::new(),
+                labels: HashMap::new(),
+                subtasks: Vec::new(),
+            };
+            created.push(self.create_task(input).await?);
+        }
+
+        for (subtask, task_id) in suggestion.subtasks.iter().zip(&created) {
+            for &dependency in &subtask.depends_on {
+                let depends_on = created.get(dependency)
+                    .ok_or_else(|| BinderyError::InvalidInput(format!("Subtask dependency {} is out of range", dependency)))?;
+                self.add_task_dependency(task_id, depends_on, None).await?;
+            }
+        }
+
+        Ok(created)
+    }
+
     /// Analyze task dependencies
     pub async fn analyze_task_dependencies(&self, task_id: &CodexId) -> BinderyResult<super::DependencyAnalysis> {
         self.task_service.analyze_task_dependencies(task_id).await
@@ -208,6 +296,31 @@ impl TaskManager {
             return Ok(format!("Would execute task '{}' with role '{}'", task.title, role_name));
         }
 
+        if self.codex_manager.is_shutting_down() {
+            return Err(BinderyError::ExecutionError("Shutting down; not starting new task executions".to_string()));
+        }
+
+        if let Some(role) = self.role_manager.get_role(&role_name).await {
+            if is_production_deploy(&task, &role) {
+                // Approval depends on the role of the user starting it, not
+                // the role it runs in; without a configured user it is refused
+                let user = UserContext {
+                    user_id: self.codex_manager.config().user_id.clone(),
+                    session_id: None,
+                    source_ip: None,
+                    user_agent: None,
+                };
+                self.codex_manager.get_approval_gate().authorize(
+                    &user,
+                    GatedAction::ProductionDeploy,
+                    format!("task:{}", task_id),
+                    format!("Execute deployment task '{}'", task.title),
+                ).await?;
+            }
+        }
+
+        self.quotas.start_execution(&role_name).await?;
+
         // Start execution
         let execution_id = Uuid::new_v4().to_string();
         let execution_context = TaskExecutionContext {
@@ -243,7 +356,8 @@ impl TaskManager {
         let active_executions = self.active_executions.clone();
         let execution_id_for_task = execution_id.clone();
 
-        tokio::spawn(async move {
+        // Tracked so shutdown can wait for the execution to finish
+        crate::observability::spawn_traced(self.codex_manager.execution_tracker().track(async move {
             let result = Self::execute_task_with_role(
                 &role_manager,
                 &task_service,
@@ -256,7 +370,7 @@ impl TaskManager {
             if let Err(e) = result {
                 tracing::error!("Task execution failed: {}", e);
             }
-        });
+        }));
 
         Ok(execution_id)
     }
@@ -270,6 +384,11 @@ impl TaskManager {
         self.execute_task(task_id, false).await
     }
 
+    /// Artifacts captured from a task's executions, oldest first
+    pub async fn get_task_artifacts(&self, task_id: &CodexId) -> BinderyResult<Vec<super::TaskArtifact>> {
+        self.task_service.list_artifacts(task_id).await
+    }
+
     /// Get execution status
     pub async fn get_execution_status(&self, execution_id: &str) -> BinderyResult<Option<serde_json::Value>> {
         let executions = self.active_executions.read().await;
@@ -334,6 +453,7 @@ impl TaskManager {
             started_at: Utc::now(),
             completed_at: Some(Utc::now()),
             duration_ms: None,
+            artifacts: Vec::new(),
         };
 
         self.task_service.record_execution(completion_result).await?;
@@ -393,6 +513,16 @@ impl TaskManager {
 
     // Private helper methods
 
+    /// Refuse `input` if it would take the acting user past their open task quota
+    async fn check_task_quota(&self, input: &TaskInput) -> BinderyResult<()> {
+        if !self.quotas.limits_open_tasks() {
+            return Ok(());
+        }
+        let user = self.task_service.acting_user();
+        let open = self.task_service.count_open_tasks_created_by(&user).await;
+        self.quotas.check_open_tasks(&user, open, count_tasks(input))
+    }
+
     async fn execute_task_with_role(
         role_manager: &Arc<RoleManager>,
         task_service: &Arc<TaskService>,
@@ -418,6 +548,7 @@ impl TaskManager {
             started_at: start_time,
             completed_at: Some(end_time),
             duration_ms: Some((end_time - start_time).num_milliseconds() as u64),
+            artifacts: Vec::new(),
         };
 
         // Record execution result
@@ -444,4 +575,23 @@ impl TaskManager {
 
         result.map(|_| ())
     }
-}
\ No newline at end of file
+}
+
+/// Tasks `input` creates, its subtasks included
+fn count_tasks(input: &TaskInput) -> usize {
+    1 + input.subtasks.iter().map(count_tasks).sum::<usize>()
+}
+
+/// Whether executing `task` with `role` deploys to production
+///
+/// That is when the role can deploy and the task's environment, tags or
+/// labels mention production.
+fn is_production_deploy(task: &Codex, role: &Role) -> bool {
+    role.has_capability(&ToolGroup::Deployment)
+        && ["environment", "tags", "labels"].iter().any(|field| {
+            task.content.template_fields
+                .get(*field)
+                .and_then(|value| serde_json::to_string(value).ok())
+                .is_some_and(|value| value.to_lowercase().contains("production"))
+        })
+}
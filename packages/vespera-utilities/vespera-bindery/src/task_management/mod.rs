@@ -8,10 +8,20 @@ pub mod manager;
 pub mod service; 
 pub mod executor;
 pub mod models;
+pub mod artifacts;
+pub mod breakdown;
+pub mod context;
+pub mod duplicates;
+pub mod quotas;
 
 pub use manager::TaskManager;
 pub use service::TaskService;
 pub use executor::{TaskExecutor, ExecutionContext};
+pub use artifacts::{ArtifactStore, ArtifactKind, TaskArtifact};
+pub use breakdown::{BreakdownConfig, BreakdownSuggestion, SubtaskSuggestion};
+pub use context::{ContextAssembler, ContextBundle, ContextConfig};
+pub use duplicates::{DuplicateCheckConfig, DuplicateTask, TaskCreationResult};
+pub use quotas::QuotaConfig;
 // Re-export specific models to avoid unused import warnings
 pub use models::{
     TaskStatus, TaskPriority, TaskRelation, TaskInput, TaskUpdateInput,
@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crate::crdt::{TemplateValue, VesperaCRDT};
 use crate::CodexId;
 
 /// Task execution status
@@ -26,6 +27,23 @@ pub enum TaskStatus {
     Blocked,
 }
 
+impl TaskStatus {
+    /// Status of a task Codex, however the status field was written
+    pub(crate) fn of_task(crdt: &VesperaCRDT) -> Option<Self> {
+        let status = match crdt.get_metadata("status")? {
+            TemplateValue::Text { value, .. } => value.clone(),
+            TemplateValue::Structured { value, .. } => value.as_str()?.to_string(),
+            _ => return None,
+        };
+        serde_json::from_value(serde_json::Value::String(status.trim_matches('"').to_string())).ok()
+    }
+
+    /// Whether work on the task is still outstanding
+    pub fn is_open(&self) -> bool {
+        !matches!(self, TaskStatus::Done | TaskStatus::Cancelled)
+    }
+}
+
 /// Task priority levels
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -58,8 +76,11 @@ pub struct TaskInput {
     pub role: Option<String>,
     pub project_id: Option<String>,
     pub parent_id: Option<CodexId>,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub labels: HashMap<String, String>,
+    #[serde(default)]
     pub subtasks: Vec<TaskInput>,
 }
 
@@ -116,6 +137,9 @@ pub struct TaskExecutionResult {
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub duration_ms: Option<u64>,
+    /// What the execution produced, when the executor captures artifacts
+    #[serde(default)]
+    pub artifacts: Vec<super::TaskArtifact>,
 }
 
 /// Task dependency analysis
@@ -141,4 +165,28 @@ pub struct TaskDashboard {
     pub overdue_tasks: Vec<TaskSummary>,
     pub completion_rate: f64,
     pub avg_completion_time_hours: Option<f64>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_of_task_reads_written_forms() {
+        let mut crdt = VesperaCRDT::new(uuid::Uuid::new_v4(), "agent".to_string());
+        assert_eq!(TaskStatus::of_task(&crdt), None);
+
+        let value = TemplateValue::Structured {
+            value: serde_json::json!("\"done\""),
+            timestamp: Utc::now(),
+            user_id: "agent".to_string(),
+        };
+        crdt.set_metadata("status".to_string(), value).unwrap();
+        assert_eq!(TaskStatus::of_task(&crdt), Some(TaskStatus::Done));
+        assert!(!TaskStatus::Done.is_open());
+
+        let value = TemplateValue::Text { value: "doing".to_string(), timestamp: Utc::now(), user_id: "agent".to_string() };
+        crdt.set_metadata("status".to_string(), value).unwrap();
+        assert_eq!(TaskStatus::of_task(&crdt), Some(TaskStatus::Doing));
+        assert!(TaskStatus::Doing.is_open());
+    }
+}
@@ -0,0 +1,155 @@
+//! Quotas on task creation, execution and LLM usage
+//!
+//! Set in [`BinderyConfig::quotas`](crate::BinderyConfig::quotas) to keep
+//! autonomous agents from flooding a Bindery. Task quotas are enforced by the
+//! [`TaskManager`](super::TaskManager); the token quota becomes a `reject`
+//! usage [`Budget`] for the provider usage tracker. Every refusal fails with
+//! [`BinderyError::QuotaExceeded`] and counts in
+//! `bindery_quota_rejections_total`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::Mutex;
+
+use crate::errors::{BinderyError, BinderyResult};
+use crate::observability::BinderyMetrics;
+use crate::usage::{Budget, BudgetAction, BudgetPeriod, BudgetScope};
+
+/// Name of the usage budget made from `max_llm_tokens_per_project_per_day`
+pub const LLM_TOKEN_QUOTA: &str = "llm_tokens_per_project_per_day";
+
+/// Limits; unset ones are not enforced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    /// Open tasks (not done or cancelled) a user may have created
+    #[serde(default)]
+    pub max_open_tasks_per_user: Option<usize>,
+
+    /// Executions each role may start in any rolling hour
+    #[serde(default)]
+    pub max_executions_per_hour_per_role: Option<usize>,
+
+    /// Input plus output LLM tokens the project may use per UTC day
+    #[serde(default)]
+    pub max_llm_tokens_per_project_per_day: Option<u64>,
+}
+
+impl QuotaConfig {
+    /// The token quota as a usage budget, if set
+    pub fn token_budget(&self) -> Option<Budget> {
+        self.max_llm_tokens_per_project_per_day.map(|max_tokens| Budget {
+            name: LLM_TOKEN_QUOTA.to_string(),
+            scope: BudgetScope::Project,
+            period: BudgetPeriod::Day,
+            max_tokens: Some(max_tokens),
+            max_cost_usd: None,
+            action: BudgetAction::Reject,
+        })
+    }
+}
+
+/// Checks task quotas, remembering recent executions per role
+#[derive(Debug, Default)]
+pub(crate) struct QuotaEnforcer {
+    config: QuotaConfig,
+    executions: Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>,
+}
+
+impl QuotaEnforcer {
+    pub(crate) fn new(config: QuotaConfig) -> Self {
+        Self {
+            config,
+            executions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether open tasks need counting at all
+    pub(crate) fn limits_open_tasks(&self) -> bool {
+        self.config.max_open_tasks_per_user.is_some()
+    }
+
+    /// Refuse `creating` more tasks if `user` would exceed their open task quota
+    pub(crate) fn check_open_tasks(&self, user: &str, open: usize, creating: usize) -> BinderyResult<()> {
+        let Some(max) = self.config.max_open_tasks_per_user else {
+            return Ok(());
+        };
+        if open + creating > max {
+            BinderyMetrics::record_quota_rejection("open_tasks_per_user");
+            return Err(BinderyError::QuotaExceeded(format!(
+                "user '{}' has {} open tasks; creating {} more would exceed the limit of {}",
+                user, open, creating, max
+            )));
+        }
+        Ok(())
+    }
+
+    /// Count an execution for `role`, refusing it if the role's hourly quota is used up
+    pub(crate) async fn start_execution(&self, role: &str) -> BinderyResult<()> {
+        self.start_execution_at(role, Utc::now()).await
+    }
+
+    async fn start_execution_at(&self, role: &str, now: DateTime<Utc>) -> BinderyResult<()> {
+        let Some(max) = self.config.max_executions_per_hour_per_role else {
+            return Ok(());
+        };
+        let mut executions = self.executions.lock().await;
+        let recent = executions.entry(role.to_string()).or_default();
+        let window_start = now - Duration::hours(1);
+        while recent.front().is_some_and(|started| *started <= window_start) {
+            recent.pop_front();
+        }
+
+        if recent.len() >= max {
+            BinderyMetrics::record_quota_rejection("executions_per_hour_per_role");
+            let retry_at = recent.front().map(|oldest| *oldest + Duration::hours(1)).unwrap_or(now);
+            return Err(BinderyError::QuotaExceeded(format!(
+                "role '{}' has started {} executions in the last hour (limit {}); next slot at {}",
+                role, recent.len(), max, retry_at.to_rfc3339()
+            )));
+        }
+        recent.push_back(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_task_quota() {
+        let enforcer = QuotaEnforcer::new(QuotaConfig { max_open_tasks_per_user: Some(3), ..QuotaConfig::default() });
+        assert!(enforcer.check_open_tasks("agent", 2, 1).is_ok());
+        assert!(matches!(enforcer.check_open_tasks("agent", 2, 2), Err(BinderyError::QuotaExceeded(_))));
+        assert!(QuotaEnforcer::default().check_open_tasks("agent", 100, 1).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execution_quota_rolls_over_hourly_per_role() {
+        let enforcer = QuotaEnforcer::new(QuotaConfig {
+            max_executions_per_hour_per_role: Some(2),
+            ..QuotaConfig::default()
+        });
+        let start = Utc::now();
+        enforcer.start_execution_at("coder", start).await.unwrap();
+        enforcer.start_execution_at("coder", start + Duration::minutes(10)).await.unwrap();
+        let refused = enforcer.start_execution_at("coder", start + Duration::minutes(20)).await;
+        assert!(matches!(refused, Err(BinderyError::QuotaExceeded(_))));
+        enforcer.start_execution_at("reviewer", start + Duration::minutes(20)).await.unwrap();
+
+        enforcer.start_execution_at("coder", start + Duration::minutes(61)).await.unwrap();
+    }
+
+    #[test]
+    fn test_token_quota_becomes_reject_budget() {
+        assert!(QuotaConfig::default().token_budget().is_none());
+        let budget = QuotaConfig { max_llm_tokens_per_project_per_day: Some(50_000), ..QuotaConfig::default() }
+            .token_budget()
+            .unwrap();
+        assert_eq!(budget.scope, BudgetScope::Project);
+        assert_eq!(budget.period, BudgetPeriod::Day);
+        assert_eq!(budget.action, BudgetAction::Reject);
+        assert!(budget.is_exceeded(50_000, 0.0));
+    }
+}
@@ -8,11 +8,14 @@ use super::{
     TaskTree, TaskSummary, TaskDashboard, TaskExecutionResult,
     DependencyAnalysis
 };
+use super::artifacts::{artifacts_of, TaskArtifact, ARTIFACTS_FIELD};
+use super::duplicates::{DuplicateCheckConfig, DuplicateDetector, DuplicateTask, TaskCreationResult};
 use crate::codex::{Codex, CodexManagerExt};
 use crate::CodexId;
 use crate::CodexManager;
 use crate::templates::{TemplateId, TemplateValue};
 use crate::errors::{BinderyError, BinderyResult};
+use crate::rag::RAGService;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -24,6 +27,7 @@ pub struct TaskService {
     codex_manager: Arc<CodexManager>,
     task_template_id: TemplateId,
     execution_history: Arc<RwLock<HashMap<CodexId, Vec<TaskExecutionResult>>>>,
+    duplicate_detector: Option<DuplicateDetector>,
 }
 
 impl TaskService {
@@ -33,9 +37,17 @@ impl TaskService {
             codex_manager,
             task_template_id: TemplateId::new("vespera.templates.hierarchical_task"),
             execution_history: Arc::new(RwLock::new(HashMap::new())),
+            duplicate_detector: None,
         }
     }
 
+    /// Compare tasks created with `create_task_with_duplicate_check` against
+    /// the open tasks indexed in `rag`
+    pub fn with_duplicate_check(mut self, rag: Arc<RAGService>, config: DuplicateCheckConfig) -> Self {
+        self.duplicate_detector = Some(DuplicateDetector::new(rag, config));
+        self
+    }
+
     /// Create a new task as a Codex entry
     pub async fn create_task(&self, input: TaskInput) -> BinderyResult<CodexId> {
         // Create the main task Codex
@@ -56,6 +68,31 @@ impl TaskService {
         Ok(task_id)
     }
 
+    /// Create a task, reporting open tasks it likely duplicates
+    ///
+    /// The check runs before the task is created, so the task never matches
+    /// itself. Without a duplicate check configured, or if the search fails,
+    /// the task is created with no duplicates reported.
+    pub async fn create_task_with_duplicate_check(&self, input: TaskInput) -> BinderyResult<TaskCreationResult> {
+        let duplicates = match self.find_duplicate_tasks(&input.title, input.description.as_deref()).await {
+            Ok(duplicates) => duplicates,
+            Err(e) => {
+                tracing::warn!("Duplicate check for task '{}' failed: {}", input.title, e);
+                Vec::new()
+            }
+        };
+        let task_id = self.create_task(input).await?;
+        Ok(TaskCreationResult { task_id, duplicates })
+    }
+
+    /// Open tasks similar to a task with this title and description, most similar first
+    pub async fn find_duplicate_tasks(&self, title: &str, description: Option<&str>) -> BinderyResult<Vec<DuplicateTask>> {
+        match &self.duplicate_detector {
+            Some(detector) => detector.find(&self.codex_manager, title, description).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Get a task by ID
     pub async fn get_task(&self, task_id: &CodexId) -> BinderyResult<Option<Codex>> {
         self.codex_manager.get_codex_ext(task_id).await
@@ -107,7 +144,7 @@ impl TaskService {
         Ok(())
     }
 
-    /// Delete a task and optionally its subtasks
+    /// Move a task, and optionally its subtasks, to the trash
     pub fn delete_task<'a>(&'a self, task_id: &'a CodexId, delete_subtasks: bool) -> std::pin::Pin<Box<dyn std::future::Future<Output = BinderyResult<()>> + Send + 'a>> {
         Box::pin(async move {
         if delete_subtasks {
@@ -121,8 +158,8 @@ impl TaskService {
         // Remove from execution history
         self.execution_history.write().await.remove(task_id);
 
-        // Delete the Codex entry
-        self.codex_manager.delete_codex_ext(task_id).await?;
+        // Move the Codex to the trash, from where it can be restored
+        self.codex_manager.trash_codex(task_id).await?;
 
         Ok(())
         })
@@ -332,6 +369,53 @@ impl TaskService {
             .unwrap_or_default()
     }
 
+    /// Link artifacts to a task, after those it already lists
+    pub async fn attach_artifacts(&self, task_id: &CodexId, artifacts: &[TaskArtifact]) -> BinderyResult<()> {
+        if artifacts.is_empty() {
+            return Ok(());
+        }
+        let mut all = self.list_artifacts(task_id).await?;
+        all.extend_from_slice(artifacts);
+        let value = serde_json::to_value(&all)
+            .map_err(|e| BinderyError::SerializationError(format!("Failed to serialize task artifacts: {}", e)))?;
+        let field = crate::crdt::TemplateValue::Structured {
+            value,
+            timestamp: Utc::now(),
+            user_id: self.acting_user(),
+        };
+        self.codex_manager.set_codex_fields(task_id, [(ARTIFACTS_FIELD.to_string(), field)]).await
+    }
+
+    /// Artifacts linked to a task, oldest first
+    pub async fn list_artifacts(&self, task_id: &CodexId) -> BinderyResult<Vec<TaskArtifact>> {
+        let crdt = self.codex_manager.get_codex(task_id).await
+            .ok_or_else(|| BinderyError::NotFound(format!("Task {}", task_id)))?;
+        Ok(artifacts_of(&crdt))
+    }
+
+    /// Open tasks created by `user`, trashed ones excluded
+    pub async fn count_open_tasks_created_by(&self, user: &str) -> usize {
+        let task_template = self.task_template_id.to_string();
+        let mut open = 0;
+        for id in self.codex_manager.list_codices().await {
+            let Some(crdt) = self.codex_manager.get_codex(&id).await else {
+                continue;
+            };
+            if crdt.created_by == user
+                && crate::template_of(&crdt).is_some_and(|template| template == task_template)
+                && TaskStatus::of_task(&crdt).is_none_or(|status| status.is_open())
+            {
+                open += 1;
+            }
+        }
+        open
+    }
+
+    /// User that changes made by this service are attributed to
+    pub fn acting_user(&self) -> crate::UserId {
+        self.codex_manager.config().user_id.clone().unwrap_or_else(|| "system".to_string())
+    }
+
     // Private helper methods
 
     async fn initialize_task_content(&self, task_id: &CodexId, input: &TaskInput) -> BinderyResult<()> {
@@ -3,6 +3,7 @@
 /// This module provides template management for Codex types,
 /// supporting dynamic field definitions and validation.
 
+use crate::crdt::VesperaCRDT;
 use crate::errors::{BinderyError, BinderyResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -114,6 +115,39 @@ pub struct FieldDefinition {
     pub ui_config: Option<UiConfig>,
 }
 
+impl FieldDefinition {
+    /// A field with no default, validation or UI configuration
+    pub(crate) fn structural(field_type: FieldType, crdt_layer: CrdtLayer, required: bool) -> Self {
+        Self {
+            field_type,
+            required,
+            default_value: None,
+            validation: None,
+            crdt_layer,
+            ui_config: None,
+        }
+    }
+}
+
+/// Field type to hold a Codex metadata value
+fn field_type_of(value: &crate::crdt::TemplateValue) -> FieldType {
+    use crate::crdt::TemplateValue as CrdtValue;
+    match value {
+        CrdtValue::Text { .. } => FieldType::Text,
+        CrdtValue::RichText { .. } => FieldType::RichText,
+        CrdtValue::Structured { value, .. } => match value {
+            serde_json::Value::Bool(_) => FieldType::Boolean,
+            serde_json::Value::Number(_) => FieldType::Number,
+            serde_json::Value::Array(_) => FieldType::Array,
+            serde_json::Value::String(_) => FieldType::Text,
+            serde_json::Value::Null | serde_json::Value::Object(_) => FieldType::Object,
+        },
+        CrdtValue::Reference { .. } => FieldType::Reference,
+        CrdtValue::List { .. } => FieldType::Array,
+        CrdtValue::Map { .. } => FieldType::Object,
+    }
+}
+
 /// Field types supported by templates
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -233,6 +267,29 @@ impl Template {
         }
     }
 
+    /// Template with the structure of an existing Codex
+    ///
+    /// Each metadata key and text field of the Codex becomes a field, typed
+    /// after its current value. Values are not carried over, and `title` is
+    /// the only required field.
+    pub fn from_codex(id: TemplateId, name: String, crdt: &VesperaCRDT) -> Self {
+        let title = crdt.get_title().unwrap_or_else(|| crdt.codex_id.to_string());
+        let mut template = Self::new(id, name, format!("Saved from \"{}\"", title), "codex".to_string());
+        template.metadata.insert("source_codex_id".to_string(), serde_json::json!(crdt.codex_id));
+
+        for (key, value) in crdt.metadata_layer.snapshot() {
+            if key == "template_id" {
+                continue;
+            }
+            let required = key == "title";
+            template.add_field(key, FieldDefinition::structural(field_type_of(&value), CrdtLayer::Metadata, required));
+        }
+        for field_id in crdt.text_layer.field_ids() {
+            template.add_field(field_id.clone(), FieldDefinition::structural(FieldType::LongText, CrdtLayer::Text, false));
+        }
+        template
+    }
+
     /// Add a field to the template
     pub fn add_field(&mut self, name: String, field_def: FieldDefinition) {
         self.fields.insert(name, field_def);
@@ -673,7 +673,7 @@ async fn test_memory_pressure_chaos() {
 
     let cleanup_start = Instant::now();
     for codex_id in &codex_ids[0..10] {
-        let deleted = manager.delete_codex(codex_id).await.unwrap();
+        let deleted = manager.purge_codex(codex_id).await.unwrap();
         assert!(deleted, "Should be able to delete codices under pressure");
     }
 
@@ -13,7 +13,7 @@ use crate::{
     crdt::TemplateValue as CrdtTemplateValue,
     types::{CodexId, CodexContent, TemplateFieldValue, ContentSection, ContentType, ContentHash, UserId},
     tests::utils::{create_test_manager, TestFixture},
-    CodexManager, BinderyConfig,
+    CodexManager, BinderyConfig, BinderyError,
 };
 
 // Helper functions to create CodexContent instances
@@ -155,14 +155,14 @@ mod codex_manager_tests {
         let result = manager.get_codex_ext(&codex_id).await.expect("Should not error");
         assert!(result.is_none(), "Should return None for non-existent codex");
 
-        // Test update_codex_fields (currently placeholder)
+        // Test update_codex_fields on a codex that does not exist
         let mut fields = HashMap::new();
         fields.insert("title".to_string(), SimpleTemplateValue::Text("Updated Title".to_string()));
 
         let result = manager.update_codex_fields(&codex_id, fields).await;
-        assert!(result.is_ok(), "Should not error on update");
+        assert!(matches!(result, Err(BinderyError::NotFound(_))), "Should not update a missing codex");
 
-        // Test delete_codex_ext (currently placeholder)
+        // Test delete_codex_ext (deleting nothing is not an error)
         let result = manager.delete_codex_ext(&codex_id).await;
         assert!(result.is_ok(), "Should not error on delete");
 
@@ -171,11 +171,11 @@ mod codex_manager_tests {
         let result = manager.list_codices_by_template(&template_id).await.expect("Should not error");
         assert!(result.is_empty(), "Should return empty list");
 
-        // Test add_codex_reference (currently placeholder)
+        // Test add_codex_reference from a codex that does not exist
         let from_id = Uuid::new_v4();
         let to_id = Uuid::new_v4();
         let result = manager.add_codex_reference(&from_id, &to_id, "references", Some("test context".to_string())).await;
-        assert!(result.is_ok(), "Should not error on add reference");
+        assert!(matches!(result, Err(BinderyError::NotFound(_))), "Should not add a reference from a missing codex");
     }
 
     #[tokio::test]
@@ -221,6 +221,110 @@ mod codex_manager_tests {
     }
 }
 
+#[cfg(test)]
+mod duplication_tests {
+    use super::*;
+    use crate::codex::DuplicateOptions;
+    use crate::crdt::{CodexReference, ReferenceType};
+    use crate::templates::{FieldType, Template};
+
+    const TEMPLATE: &str = "test.book";
+
+    async fn manager_with_template() -> CodexManager {
+        let manager = create_test_manager().await.expect("Should create manager");
+        let template = Template::new(
+            TemplateId::new(TEMPLATE),
+            "Book".to_string(),
+            "A book or one of its chapters".to_string(),
+            "book".to_string(),
+        );
+        manager.register_template(template).await.expect("Should register template");
+        manager
+    }
+
+    fn text(value: &str) -> CrdtTemplateValue {
+        CrdtTemplateValue::Text { value: value.to_string(), timestamp: Utc::now(), user_id: "alice".to_string() }
+    }
+
+    async fn title_of(manager: &CodexManager, id: &CodexId) -> Option<String> {
+        manager.get_codex(id).await.and_then(|crdt| crdt.get_title())
+    }
+
+    /// A book with a genre field and one chapter that references it
+    async fn create_book(manager: &CodexManager) -> (CodexId, CodexId) {
+        let book = manager.create_codex("Book", TEMPLATE).await.unwrap();
+        manager.set_codex_fields(&book, [("genre".to_string(), text("fantasy"))]).await.unwrap();
+        let chapter = manager.create_codex("Chapter", TEMPLATE).await.unwrap();
+        manager.reparent_codex(&chapter, Some(book), 0).await.unwrap();
+        manager.add_reference(CodexReference {
+            from_codex_id: chapter,
+            to_codex_id: book,
+            reference_type: ReferenceType::DependsOn,
+            context: None,
+        }).await.unwrap();
+        (book, chapter)
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_codex_copies_fields_and_children() {
+        let manager = manager_with_template().await;
+        let (book, chapter) = create_book(&manager).await;
+
+        let copy = manager.duplicate_codex(&book, DuplicateOptions::default().with_children()).await.unwrap();
+        assert_ne!(copy, book);
+        assert_eq!(title_of(&manager, &copy).await.as_deref(), Some("Book (copy)"));
+        let copied = manager.get_codex(&copy).await.unwrap();
+        assert!(matches!(copied.get_metadata("genre"), Some(CrdtTemplateValue::Text { value, .. }) if value == "fantasy"));
+        assert!(matches!(copied.get_metadata("template_id"), Some(CrdtTemplateValue::Text { value, .. }) if value == TEMPLATE));
+
+        // The chapter is copied too, and its reference follows the copy
+        let children = manager.codex_children(&copy).await.unwrap();
+        assert_eq!(children.len(), 1);
+        assert_ne!(children[0], chapter);
+        assert_eq!(title_of(&manager, &children[0]).await.as_deref(), Some("Chapter"));
+        let chapter_copy = manager.get_codex(&children[0]).await.unwrap();
+        assert!(chapter_copy.reference_layer.iter().any(|reference| reference.to_codex_id == copy));
+
+        // The originals are untouched
+        assert_eq!(manager.codex_children(&book).await.unwrap(), vec![chapter]);
+        assert_eq!(manager.list_codices().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_codex_without_children() {
+        let manager = manager_with_template().await;
+        let (book, _) = create_book(&manager).await;
+
+        let copy = manager.duplicate_codex(&book, DuplicateOptions::default().with_title("Second Edition")).await.unwrap();
+        assert_eq!(title_of(&manager, &copy).await.as_deref(), Some("Second Edition"));
+        assert!(manager.codex_children(&copy).await.unwrap().is_empty());
+        assert_eq!(manager.list_codices().await.len(), 3);
+
+        let missing = manager.duplicate_codex(&Uuid::new_v4(), DuplicateOptions::default()).await;
+        assert!(matches!(missing, Err(BinderyError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_save_as_template_round_trip() {
+        let manager = manager_with_template().await;
+        let (book, _) = create_book(&manager).await;
+
+        let template = manager.save_as_template(&book, "test.saved_book", "Saved Book").await.unwrap();
+        assert_eq!(template.content_type, "book");
+        assert_eq!(template.metadata.get("source_template_id"), Some(&serde_json::json!(TEMPLATE)));
+        assert!(template.fields["title"].required);
+        assert_eq!(template.fields["genre"].field_type, FieldType::Text);
+        assert!(!template.fields["genre"].required);
+        assert!(!template.fields.contains_key("template_id"));
+
+        // Codices can be created from the saved template, but it cannot be saved twice
+        let from_saved = manager.create_codex("Another Book", "test.saved_book").await.unwrap();
+        assert_eq!(title_of(&manager, &from_saved).await.as_deref(), Some("Another Book"));
+        let again = manager.save_as_template(&book, "test.saved_book", "Saved Book").await;
+        assert!(matches!(again, Err(BinderyError::TemplateRegistrationError(_))));
+    }
+}
+
 #[cfg(test)]
 mod codex_format_tests {
     use super::*;
@@ -283,6 +387,41 @@ mod codex_format_tests {
                        "Attachments count should match");
         }
     }
+
+    #[tokio::test]
+    async fn test_serializer_reads_any_codec() {
+        use crate::compression::{CompressionCodec, CompressionConfig};
+        use crate::crdt::VesperaCRDT;
+
+        let mut crdt = VesperaCRDT::new(Uuid::new_v4(), "alice".to_string());
+        crdt.set_title("Compressed Codex").unwrap();
+        for i in 0..50 {
+            crdt.set_metadata(format!("field_{}", i % 5), CrdtTemplateValue::Text {
+                value: "the same value again".to_string(),
+                timestamp: Utc::now(),
+                user_id: "alice".to_string(),
+            }).unwrap();
+        }
+
+        let plain = CodexSerializer::new(CodexFormat::Binary).serialize(&crdt).unwrap();
+        let zstd = CodexSerializer::new(CodexFormat::Binary).with_compression(true);
+        assert_eq!(zstd.codec(), CompressionCodec::Zstd);
+        let lz4 = CodexSerializer::new(CodexFormat::Binary)
+            .with_codec(CompressionConfig::new(CompressionCodec::Lz4));
+
+        let zstd_bytes = zstd.serialize(&crdt).unwrap();
+        let lz4_bytes = lz4.serialize(&crdt).unwrap();
+        assert!(zstd_bytes.len() < plain.len());
+        assert!(lz4_bytes.len() < plain.len());
+
+        // A workspace that switched codecs still reads what it wrote before
+        assert_eq!(zstd.deserialize(&lz4_bytes).unwrap().get_title().unwrap(), "Compressed Codex");
+        assert_eq!(lz4.deserialize(&zstd_bytes).unwrap().operation_log.len(), crdt.operation_log.len());
+
+        // As written before codecs were tagged
+        let legacy = lz4_flex::compress_prepend_size(&plain);
+        assert_eq!(zstd.deserialize(&legacy).unwrap().get_title().unwrap(), "Compressed Codex");
+    }
 }
 
 #[cfg(test)]
@@ -430,14 +569,14 @@ mod error_handling_tests {
         let get_result = result.unwrap();
         assert!(get_result.is_none(), "Should return None for non-existent codex");
 
-        // Test update on non-existent codex (currently placeholder, so won't error)
+        // Test update on non-existent codex
         let fields = HashMap::new();
         let result = manager.update_codex_fields(&invalid_id, fields).await;
-        assert!(result.is_ok(), "Update placeholder should not error");
+        assert!(matches!(result, Err(BinderyError::NotFound(_))), "Update should fail for a missing codex");
 
-        // Test delete on non-existent codex (currently placeholder, so won't error)
+        // Test delete on non-existent codex (nothing to delete, so won't error)
         let result = manager.delete_codex_ext(&invalid_id).await;
-        assert!(result.is_ok(), "Delete placeholder should not error");
+        assert!(result.is_ok(), "Delete should not error");
     }
 }
 
@@ -461,11 +600,11 @@ mod integration_tests {
         let codices = manager.list_codices_by_template(&template_id).await.expect("Should not error");
         assert!(codices.is_empty(), "Should return empty list");
 
-        // 3. Test codex references (placeholder implementation)
+        // 3. Test codex references (the source codex must exist)
         let from_id = Uuid::new_v4();
         let to_id = Uuid::new_v4();
         let result = manager.add_codex_reference(&from_id, &to_id, "references", None).await;
-        assert!(result.is_ok(), "Reference addition should not error");
+        assert!(matches!(result, Err(BinderyError::NotFound(_))), "Reference addition should fail without the codex");
     }
 
     #[tokio::test]
@@ -31,7 +31,7 @@ mod crdt_core_tests {
         assert_eq!(crdt.updated_by, user_id);
         assert!(crdt.operation_log.is_empty());
         assert_eq!(crdt.vector_clock.len(), 1);
-        assert_eq!(crdt.vector_clock.get(&user_id), Some(&0));
+        assert_eq!(crdt.vector_clock.get(user_id.as_str()), Some(&0));
     }
 
     #[tokio::test]
@@ -53,10 +53,10 @@ mod crdt_core_tests {
             user_id.clone(),
         );
 
-        let initial_clock = crdt.vector_clock.get(&user_id).copied().unwrap_or(0);
+        let initial_clock = crdt.vector_clock.get(user_id.as_str()).copied().unwrap_or(0);
         crdt.apply_operation(operation).expect("Operation should succeed");
 
-        let updated_clock = crdt.vector_clock.get(&user_id).copied().unwrap_or(0);
+        let updated_clock = crdt.vector_clock.get(user_id.as_str()).copied().unwrap_or(0);
         assert!(updated_clock > initial_clock, "Vector clock should increment");
     }
 
@@ -149,11 +149,11 @@ mod metadata_layer_tests {
                 key: key.clone(),
                 value: value2.clone(),
             },
-            user_id: user2.clone(),
+            user_id: user2.clone().into(),
             timestamp: Utc::now(),
             vector_clock: {
                 let mut vc = VectorClock::new();
-                vc.insert(user2.clone(), 1);
+                vc.insert(user2.clone().into(), 1);
                 vc
             },
             parents: Vec::new(),
@@ -216,6 +216,57 @@ mod metadata_layer_tests {
             panic!("Expected Reference value");
         }
     }
+
+    #[tokio::test]
+    async fn test_metadata_author_and_history() {
+        let codex_id = Uuid::new_v4();
+        let mut alice = VesperaCRDT::new(codex_id, "alice".to_string());
+        let mut bob = VesperaCRDT::new(codex_id, "bob".to_string());
+        let start = Utc::now();
+        let text = |value: &str| TemplateValue::Text {
+            value: value.to_string(),
+            timestamp: start,
+            user_id: "test".to_string(),
+        };
+
+        // Alice and Bob edit concurrently, each seeing the other's writes only on merge
+        let write = |crdt: &mut VesperaCRDT, user: &str, operation: OperationType, minutes: i64| {
+            let mut op = crdt.create_operation(operation, user.to_string());
+            op.timestamp = start + Duration::minutes(minutes);
+            crdt.apply_operation(op).unwrap();
+        };
+        write(&mut alice, "alice", OperationType::MetadataSet { key: "status".to_string(), value: text("draft") }, 0);
+        write(&mut bob, "bob", OperationType::MetadataSet { key: "status".to_string(), value: text("review") }, 5);
+        write(&mut alice, "alice", OperationType::MetadataDelete { key: "status".to_string() }, 10);
+        write(&mut bob, "bob", OperationType::MetadataSet { key: "status".to_string(), value: text("final") }, 15);
+        alice.merge(&bob).unwrap();
+        bob.merge(&alice).unwrap();
+
+        let current = alice.get_metadata_with_author("status").unwrap();
+        assert_eq!(current.user_id, "bob");
+        assert_eq!(current.timestamp, start + Duration::minutes(15));
+
+        let history = alice.metadata_history("status", 10);
+        let summary: Vec<(&str, bool)> = history.iter().map(|w| (w.user_id.as_str(), w.value.is_some())).collect();
+        assert_eq!(summary, [("bob", true), ("alice", false), ("bob", true), ("alice", true)]);
+        assert_eq!(history[1].timestamp, start + Duration::minutes(10));
+        assert_eq!(alice.metadata_history("status", 2).len(), 2);
+
+        // Both replicas keep the same history whatever order writes arrived in
+        let ids = |crdt: &VesperaCRDT| -> Vec<Uuid> {
+            crdt.metadata_history("status", 10).iter().map(|w| w.operation_id).collect()
+        };
+        assert_eq!(ids(&alice), ids(&bob));
+
+        // Bounded history keeps the newest writes
+        let mut bounded = crate::crdt::LWWMap::<String, String>::new().with_history_limit(2);
+        for (minutes, value) in ["a", "b", "c"].into_iter().enumerate() {
+            let timestamp = start + Duration::minutes(minutes as i64);
+            bounded.set_with_metadata("key".to_string(), value.to_string(), timestamp, "alice".to_string(), Uuid::new_v4());
+        }
+        let kept: Vec<_> = bounded.key_history(&"key".to_string(), 10).iter().map(|w| w.value.clone()).collect();
+        assert_eq!(kept, [Some("c".to_string()), Some("b".to_string())]);
+    }
 }
 
 #[cfg(test)]
@@ -289,6 +340,94 @@ mod reference_layer_tests {
         }
         assert_eq!(found_types.len(), 5, "Should have all unique reference types");
     }
+
+    #[tokio::test]
+    async fn test_bulk_references_and_target_removal() {
+        let codex_id = Uuid::new_v4();
+        let kept = Uuid::new_v4();
+        let deleted = Uuid::new_v4();
+        let mut crdt = VesperaCRDT::new(codex_id, "test_user".to_string());
+        let reference = |to_codex_id, reference_type| CodexReference {
+            from_codex_id: codex_id,
+            to_codex_id,
+            reference_type,
+            context: None,
+        };
+
+        let log_before = crdt.operation_log.len();
+        crdt.add_references(vec![
+            reference(kept, ReferenceType::DependsOn),
+            reference(deleted, ReferenceType::DependsOn),
+            reference(deleted, ReferenceType::Related),
+        ]).expect("Should add references");
+        assert_eq!(crdt.operation_log.len(), log_before + 1, "Bulk add should be one operation");
+        assert_eq!(crdt.get_references().len(), 3);
+        assert_eq!(crdt.references_of_type(&ReferenceType::DependsOn).len(), 2);
+        assert_eq!(crdt.references_to(&deleted).len(), 2);
+
+        // A replica that adds to the deleted target concurrently keeps its reference
+        let mut peer = crdt.clone();
+        peer.set_operation_context(crate::crdt::OperationContext::new("peer".to_string()));
+        peer.add_reference(reference(deleted, ReferenceType::Child)).expect("Should add reference");
+
+        let removed = crdt.remove_references_to(deleted).expect("Should remove references");
+        assert_eq!(removed.len(), 2);
+        assert_eq!(crdt.get_references(), vec![&reference(kept, ReferenceType::DependsOn)]);
+
+        crdt.merge(&peer).expect("Should merge");
+        assert_eq!(crdt.references_to(&deleted), vec![&reference(deleted, ReferenceType::Child)]);
+
+        // The integrity pass catches what the removal had not seen
+        let pruned = crdt.prune_references(|target| *target != deleted).expect("Should prune");
+        assert_eq!(pruned, vec![reference(deleted, ReferenceType::Child)]);
+        assert!(crdt.references_to(&deleted).is_empty());
+        assert!(crdt.remove_references_to(deleted).expect("Should be a no-op").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_rewrites_internal_references() {
+        let user_id = "test_user".to_string();
+        let (parent_id, child_id, other_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut parent = VesperaCRDT::new(parent_id, user_id.clone());
+        parent.set_title("Chapter").expect("Should set title");
+        parent.insert_text("body".to_string(), 0, "Once upon a time".to_string()).expect("Should insert text");
+        parent.tree_layer.insert(Some(parent_id), 0, child_id).expect("Should insert child");
+        parent.tree_layer.insert(Some(parent_id), 1, other_id).expect("Should insert child");
+        let link = |from, to, reference_type| CodexReference {
+            from_codex_id: from,
+            to_codex_id: to,
+            reference_type,
+            context: None,
+        };
+        parent.add_references(vec![
+            link(parent_id, child_id, ReferenceType::Child),
+            link(parent_id, other_id, ReferenceType::Related),
+        ]).expect("Should add references");
+
+        // Copy the parent and one of its children
+        let id_map = HashMap::from([(parent_id, Uuid::new_v4()), (child_id, Uuid::new_v4())]);
+        let copy_id = id_map[&parent_id];
+        let copy = parent.duplicate(copy_id, "copier".to_string(), &id_map).expect("Should duplicate");
+
+        assert_eq!(copy.codex_id, copy_id);
+        assert_eq!(copy.created_by, "copier");
+        assert_eq!(copy.get_title().as_deref(), Some("Chapter"));
+        assert_eq!(copy.get_text("body").map(|text| text.to_string()).as_deref(), Some("Once upon a time"));
+        assert!(copy.references_to(&child_id).is_empty());
+        assert_eq!(copy.references_to(&id_map[&child_id]), vec![&link(copy_id, id_map[&child_id], ReferenceType::Child)]);
+        assert_eq!(copy.references_to(&other_id), vec![&link(copy_id, other_id, ReferenceType::Related)]);
+        assert_eq!(copy.tree_layer.get_children(Some(copy_id)), vec![id_map[&child_id]]);
+        assert!(copy.operation_log.iter().all(|operation| operation.user_id == "copier"));
+
+        // The original is untouched
+        assert_eq!(parent.references_to(&child_id).len(), 1);
+
+        let template = crate::templates::Template::from_codex("chapter".into(), "Chapter".to_string(), &parent);
+        assert!(template.fields["title"].required);
+        assert_eq!(template.fields["body"].crdt_layer, crate::templates::CrdtLayer::Text);
+        assert!(!template.fields.contains_key("template_id"));
+        assert!(template.fields.values().all(|field| field.default_value.is_none()));
+    }
 }
 
 #[cfg(test)]
@@ -331,6 +470,138 @@ mod text_layer_tests {
     }
 }
 
+#[cfg(test)]
+mod tree_layer_tests {
+    use super::*;
+
+    /// A Codex with children `a` and `b`, and a second replica of it
+    fn replicas() -> (VesperaCRDT, VesperaCRDT, CodexId, CodexId) {
+        let root = Uuid::new_v4();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut alice = VesperaCRDT::new(root, "alice".to_string());
+        alice.add_child(root, 0, a).expect("Should add child");
+        alice.add_child(root, 1, b).expect("Should add child");
+        let bob = alice.clone();
+        (alice, bob, a, b)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_moves_into_each_other() {
+        let (mut alice, mut bob, a, b) = replicas();
+        let root = alice.codex_id;
+
+        alice.reparent(a, Some(b), 0).expect("Should move a under b");
+        bob.reparent(b, Some(a), 0).expect("Should move b under a");
+
+        let alice_before = alice.clone();
+        alice.merge(&bob).expect("Should merge");
+        bob.merge(&alice_before).expect("Should merge");
+
+        for replica in [&alice, &bob] {
+            replica.tree_layer.validate().expect("Tree should have no cycle");
+            assert_eq!(replica.subtree(root).len(), 3, "No node should be lost");
+        }
+        // The later move would close a cycle and is dropped everywhere
+        assert_eq!(alice.tree_layer.get_parent(a), Some(b));
+        assert_eq!(alice.tree_layer.get_parent(b), Some(root));
+        assert_eq!(alice.subtree(root), bob.subtree(root));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_inserts_share_one_order() {
+        let (mut alice, mut bob, a, b) = replicas();
+        let root = alice.codex_id;
+        let (c, d) = (Uuid::new_v4(), Uuid::new_v4());
+
+        alice.add_child(root, 0, c).expect("Should add child");
+        bob.add_child(root, 0, d).expect("Should add child");
+        bob.reorder(a, 5).expect("Should reorder");
+
+        let alice_before = alice.clone();
+        alice.merge(&bob).expect("Should merge");
+        bob.merge(&alice_before).expect("Should merge");
+
+        let children = alice.children(root);
+        assert_eq!(children, bob.children(root));
+        assert_eq!(children.len(), 4);
+        assert!(children.contains(&b) && children.contains(&c) && children.contains(&d));
+    }
+
+    #[tokio::test]
+    async fn test_tree_operations_apply_in_any_order() {
+        let (mut alice, _, a, b) = replicas();
+        let root = alice.codex_id;
+        alice.reparent(b, Some(a), 0).expect("Should move b under a");
+        alice.reorder(a, 0).expect("Should reorder");
+        alice.remove_child(a, b).expect("Should detach b");
+        alice.add_child(a, 0, b).expect("Should attach b again");
+
+        let mut replay = VesperaCRDT::new(root, "carol".to_string());
+        for operation in alice.operation_log.iter().rev() {
+            replay.apply_operation(operation.clone()).expect("Should apply");
+            replay.apply_operation(operation.clone()).expect("Should ignore a duplicate");
+        }
+
+        assert_eq!(replay.subtree(root), alice.subtree(root));
+        assert_eq!(alice.subtree(root), vec![root, a, b]);
+    }
+
+    #[cfg(feature = "yjs-compat")]
+    #[tokio::test]
+    async fn test_tree_converges_in_delta_mode() {
+        use crate::crdt::{MemoryConfig, StorageMode};
+
+        let root = Uuid::new_v4();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let config = MemoryConfig {
+            storage_mode: StorageMode::Delta { history_window: 2 },
+            ..MemoryConfig::default()
+        };
+        let mut alice = VesperaCRDT::new_with_memory_config(root, "alice".to_string(), config.clone());
+        let mut bob = VesperaCRDT::new_with_memory_config(root, "bob".to_string(), config.clone());
+        alice.add_child(root, 0, a).expect("Should add child");
+        alice.add_child(root, 1, b).expect("Should add child");
+        bob.merge(&alice).expect("Should merge");
+        assert_eq!(bob.children(root), vec![a, b]);
+
+        // Concurrent moves into each other, each sent as a fragment
+        alice.reparent(a, Some(b), 0).expect("Should move a under b");
+        bob.reparent(b, Some(a), 0).expect("Should move b under a");
+        let alice_before = alice.clone();
+        alice.merge(&bob).expect("Should merge");
+        bob.merge(&alice_before).expect("Should merge");
+
+        for replica in [&alice, &bob] {
+            replica.tree_layer.validate().expect("Tree should have no cycle");
+            assert_eq!(replica.subtree(root), vec![root, b, a]);
+        }
+
+        // A peer further behind than the window gets the whole tree
+        let mut carol = VesperaCRDT::new_with_memory_config(root, "carol".to_string(), config);
+        alice.add_child(root, 0, c).expect("Should add child");
+        alice.reorder(b, 0).expect("Should reorder");
+        alice.reparent(a, Some(c), 0).expect("Should move a under c");
+        carol.merge(&alice).expect("Should merge");
+        bob.merge(&alice).expect("Should merge");
+
+        assert_eq!(alice.subtree(root), vec![root, b, c, a]);
+        assert_eq!(carol.subtree(root), alice.subtree(root));
+        assert_eq!(bob.subtree(root), alice.subtree(root));
+    }
+
+    #[tokio::test]
+    async fn test_reparent_rejects_cycles() {
+        let (mut alice, _, a, b) = replicas();
+        let root = alice.codex_id;
+        alice.reparent(b, Some(a), 0).expect("Should move b under a");
+
+        assert!(alice.reparent(a, Some(b), 0).is_err());
+        assert!(alice.reparent(a, Some(a), 0).is_err());
+        assert!(alice.reorder(Uuid::new_v4(), 0).is_err());
+        assert_eq!(alice.subtree(root), vec![root, a, b]);
+    }
+}
+
 #[cfg(test)]
 mod convergence_tests {
     use super::*;
@@ -374,6 +645,33 @@ mod convergence_tests {
         assert_crdt_convergence(&crdt1, &crdt2);
     }
 
+    #[tokio::test]
+    async fn test_merge_applies_peer_operations_by_reference() {
+        let codex_id = Uuid::new_v4();
+        let mut local = VesperaCRDT::new(codex_id, "user1".to_string());
+        let mut peer = VesperaCRDT::new(codex_id, "user2".to_string());
+        for i in 0..20 {
+            peer.set_metadata(format!("key{}", i % 3), TemplateValue::Text {
+                value: format!("value{}", i),
+                timestamp: Utc::now(),
+                user_id: "user2".to_string(),
+            }).expect("Should set metadata");
+        }
+        let peer_log = peer.operation_log.clone();
+
+        let applied = local.merge(&peer).expect("Should merge");
+        assert_eq!(applied.len(), 20);
+        assert_eq!(peer.operation_log, peer_log, "The peer's log is only read");
+        assert_eq!(local.get_metadata("key2"), peer.get_metadata("key2"));
+
+        // Kept operations are the replica's own copies, with its own shared IDs
+        let kept = local.operation_log.iter().find(|op| op.id == peer_log[0].id).expect("Should be kept");
+        assert!(!kept.user_id.ptr_eq(&peer.operation_log[0].user_id));
+        assert!(local.operation_log.iter().all(|op| op.user_id.ptr_eq(&kept.user_id)));
+
+        assert!(local.merge(&peer).expect("Should merge").is_empty());
+    }
+
     #[tokio::test]
     async fn test_multi_user_convergence() {
         let codex_id = Uuid::new_v4();
@@ -453,6 +751,104 @@ mod convergence_tests {
         assert!(stats.operation_log_size > 0, "Should have operations");
         assert!(stats.metadata_stats.active_entries > 0, "Should have metadata");
     }
+
+    #[tokio::test]
+    async fn test_vector_clock_retirement_keeps_convergence() {
+        use crate::crdt::{MemoryConfig, OperationContext};
+
+        let codex_id = Uuid::new_v4();
+        let config = MemoryConfig { max_vector_clock_entries: 2, ..MemoryConfig::default() };
+        let mut replica_a = VesperaCRDT::new_with_memory_config(codex_id, "user_a".to_string(), config);
+        let reference = |target: u128| CodexReference {
+            from_codex_id: codex_id,
+            to_codex_id: Uuid::from_u128(target),
+            reference_type: ReferenceType::References,
+            context: None,
+        };
+
+        // Guests each add a reference through replica A, then go quiet
+        for guest in 0..3 {
+            replica_a.set_operation_context(OperationContext::new(format!("guest_{}", guest)));
+            replica_a.add_reference(reference(guest)).expect("Should add reference");
+        }
+        replica_a.set_operation_context(OperationContext::new("user_a".to_string()));
+        replica_a.add_reference(reference(10)).expect("Should add reference");
+        let mut replica_b = replica_a.clone();
+
+        // Only guest_0 has been seen by every other client's latest operation,
+        // so only it retires, even though the clock stays past the limit
+        assert_eq!(replica_a.gc_vector_clock(), 1);
+        assert_eq!(replica_a.retired_clients.get("guest_0"), Some(&1));
+        assert!(!replica_a.vector_clock.contains_key("guest_0"));
+        assert!(replica_a.vector_clock.len() > 2);
+
+        // A removal after retirement still observes guest_0's add, while a
+        // concurrent re-add on B survives it
+        replica_a.remove_reference(reference(0)).expect("Should remove reference");
+        assert!(!replica_a.reference_layer.contains(&reference(0)));
+        replica_b.set_operation_context(OperationContext::new("user_b".to_string()));
+        replica_b.add_reference(reference(0)).expect("Should add reference");
+
+        replica_b.merge(&replica_a).expect("Should merge");
+        replica_a.merge(&replica_b).expect("Should merge");
+        assert_eq!(replica_a.reference_layer.snapshot(), replica_b.reference_layer.snapshot());
+        assert!(replica_a.reference_layer.contains(&reference(0)));
+        assert!(!replica_b.vector_clock.contains_key("guest_0"), "Retirement should travel with merge");
+
+        // A retired client that writes again continues its counter
+        replica_b.set_operation_context(OperationContext::new("guest_0".to_string()));
+        replica_b.add_reference(reference(20)).expect("Should add reference");
+        assert_eq!(replica_b.vector_clock.get("guest_0"), Some(&2));
+    }
+
+    #[cfg(feature = "yjs-compat")]
+    #[tokio::test]
+    async fn test_delta_mode_syncs_within_history_window() {
+        use crate::crdt::{MemoryConfig, StorageMode};
+
+        let codex_id = Uuid::new_v4();
+        let config = MemoryConfig {
+            storage_mode: StorageMode::Delta { history_window: 4 },
+            ..MemoryConfig::default()
+        };
+        let mut replica_a = VesperaCRDT::new_with_memory_config(codex_id, "user_a".to_string(), config.clone());
+        let mut replica_b = VesperaCRDT::new_with_memory_config(codex_id, "user_b".to_string(), config);
+        let status = |value: &str| TemplateValue::Text {
+            value: value.to_string(),
+            timestamp: Utc::now(),
+            user_id: "user_a".to_string(),
+        };
+
+        replica_a.set_title("Draft").expect("Should set title");
+        replica_a.insert_text("content".to_string(), 0, "Hello".to_string()).expect("Should insert text");
+        replica_b.merge(&replica_a).expect("Should merge");
+        assert_eq!(replica_b.get_title(), Some("Draft".to_string()));
+
+        // A peer that is only a little behind gets just the new change
+        replica_a.set_metadata("status".to_string(), status("open")).expect("Should set metadata");
+        let delta = replica_a.delta_since(&replica_b.vector_clock).expect("Should build delta");
+        assert_eq!(delta.metadata.snapshot().keys().collect::<Vec<_>>(), vec!["status"]);
+        replica_b.apply_delta(&delta).expect("Should apply delta");
+
+        // Concurrent text edits meet through Yjs
+        replica_a.insert_text("content".to_string(), 0, "Oh, ".to_string()).expect("Should insert text");
+        replica_b.insert_text("content".to_string(), 5, "!".to_string()).expect("Should insert text");
+
+        // Past the window the log is trimmed, and a lagging peer gets the whole state
+        for round in 0..6 {
+            replica_a.set_metadata("status".to_string(), status(&format!("round {}", round))).expect("Should set metadata");
+        }
+        assert!(replica_a.operation_log.len() <= 4);
+        let delta = replica_a.delta_since(&replica_b.vector_clock).expect("Should build delta");
+        assert!(delta.metadata.snapshot().contains_key("title"), "Should fall back to the whole state");
+
+        replica_b.merge(&replica_a).expect("Should merge");
+        replica_a.merge(&replica_b).expect("Should merge");
+        assert_eq!(replica_a.get_text("content").map(|text| text.to_string()), Some("Oh, Hello!".to_string()));
+        assert_eq!(replica_a.text_layer.snapshot(), replica_b.text_layer.snapshot());
+        assert_eq!(replica_a.metadata_layer.snapshot(), replica_b.metadata_layer.snapshot());
+        assert_eq!(replica_a.vector_clock, replica_b.vector_clock);
+    }
 }
 
 #[cfg(test)]
@@ -141,6 +141,7 @@ impl E2EPerformanceHarness {
                 retention_days: Some(1), // Short retention for testing
                 enable_compression: false,
                 batch_size: 100,
+                anomaly: Default::default(),
             };
             Some(Arc::new(AuditLogger::new(audit_config).await?))
         } else {
@@ -10,7 +10,8 @@ use serde_json::json;
 
 use crate::{
     hook_system::{
-        HookManager, HookTrigger, HookAgentInput, TimedAgentInput
+        HookManager, HookTrigger, HookAgentInput, TimedAgentInput,
+        NotificationChannel, NotificationPreferences,
     },
     types::CodexId,
     tests::utils::{TestFixture, PerformanceTest},
@@ -125,6 +126,22 @@ mod hook_manager_tests {
         // assert!(result.is_ok(), "Should register hook agent successfully");
     }
 
+    #[tokio::test]
+    async fn test_codex_manager_hooks_act_on_their_manager() {
+        // Building the manager used to recurse through HookManager::default
+        let manager = CodexManager::new().expect("Should create CodexManager");
+        let hooks = manager.get_hook_manager();
+
+        let preferences = NotificationPreferences::new("alice", NotificationChannel::Log);
+        let id = hooks.set_notification_preferences(&preferences).await.expect("Should store preferences");
+        assert!(manager.get_codex(&id).await.is_some(), "Preferences should be stored in the owning manager");
+        assert_eq!(hooks.notification_preferences("alice").await, Some(preferences.clone()));
+
+        // The hook manager does not keep its manager alive
+        drop(manager);
+        assert!(hooks.set_notification_preferences(&preferences).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_trigger_hook_execution() {
         let mut manager = create_test_hook_manager();
@@ -55,10 +55,10 @@ mod end_to_end_workflow_tests {
         assert_eq!(all_codices.len(), 2);
 
         // 5. Test cleanup
-        let deleted_first = codex_manager.delete_codex(&codex_id).await.expect("Should delete codex");
+        let deleted_first = codex_manager.purge_codex(&codex_id).await.expect("Should delete codex");
         assert!(deleted_first, "Should confirm deletion");
 
-        let deleted_second = codex_manager.delete_codex(&second_codex_id).await.expect("Should delete second codex");
+        let deleted_second = codex_manager.purge_codex(&second_codex_id).await.expect("Should delete second codex");
         assert!(deleted_second, "Should confirm second deletion");
 
         // Verify deletion
@@ -43,6 +43,35 @@ async fn test_operation_log_bounded_growth() {
     println!("✓ Operation log bounded growth test passed. Final size: {}", stats.operation_log_size);
 }
 
+#[test]
+fn test_operation_ids_are_shared() {
+    let codex_id = uuid::Uuid::new_v4();
+    let mut crdt = VesperaCRDT::new(codex_id, "test_user".to_string());
+    for i in 0..200 {
+        crdt.insert_text("content".to_string(), 0, format!("{} ", i)).expect("Failed to insert text");
+    }
+
+    // A peer's copies of the same IDs are swapped for the local ones on merge
+    let mut peer: VesperaCRDT = serde_json::from_str(&serde_json::to_string(&crdt).unwrap()).unwrap();
+    peer.set_operation_context(crate::crdt::OperationContext::new("peer_user".to_string()));
+    peer.insert_text("content".to_string(), 0, "peer ".to_string()).expect("Failed to insert text");
+    crdt.merge(&peer).expect("Failed to merge");
+
+    let first = &crdt.operation_log[0];
+    for operation in &crdt.operation_log {
+        if operation.user_id == first.user_id {
+            assert!(operation.user_id.ptr_eq(&first.user_id));
+        }
+        let OperationType::TextInsert { field_id, .. } = &operation.operation else { panic!("expected a text insert") };
+        let OperationType::TextInsert { field_id: first_field, .. } = &first.operation else { unreachable!() };
+        assert!(field_id.ptr_eq(first_field));
+    }
+
+    let stats = crdt.memory_stats();
+    assert_eq!(stats.interned_ids, 3, "two users and one field");
+    assert!(stats.shared_id_bytes > 200 * ("test_user".len() + "content".len()));
+}
+
 #[tokio::test]
 async fn test_metadata_layer_garbage_collection() {
     let codex_id = uuid::Uuid::new_v4();
@@ -185,7 +214,7 @@ async fn test_codex_manager_memory_management() {
 
     // Delete some Codices (this should trigger Drop implementations)
     for &id in &codex_ids[..5] {
-        let deleted = manager.delete_codex(&id).await.expect("Failed to delete codex");
+        let deleted = manager.purge_codex(&id).await.expect("Failed to delete codex");
         assert!(deleted, "Codex should have been deleted");
     }
 
@@ -183,6 +183,8 @@ async fn test_database_performance_optimizations() {
         acquire_timeout: Duration::from_secs(5),
         idle_timeout: Duration::from_secs(10 * 60),
         test_before_acquire: true,
+        statement_cache_capacity: 100,
+        auto_tune: Default::default(),
     };
 
     // Create temporary database for testing
@@ -104,13 +104,13 @@ fn arb_operation_type() -> impl Strategy<Value = OperationType> {
         // Text operations
         (any::<String>(), any::<usize>(), any::<String>())
             .prop_map(|(field_id, position, content)| OperationType::TextInsert {
-                field_id,
+                field_id: field_id.into(),
                 position: position % 1000, // Bound position to reasonable range
                 content,
             }),
         (any::<String>(), any::<usize>(), 1usize..=100)
             .prop_map(|(field_id, position, length)| OperationType::TextDelete {
-                field_id,
+                field_id: field_id.into(),
                 position: position % 1000,
                 length,
             }),
@@ -153,7 +153,7 @@ fn arb_crdt_operation(codex_id: CodexId) -> impl Strategy<Value = CRDTOperation>
         any::<[u8; 16]>(), // For operation ID
     ).prop_map(move |(operation, user_id, timestamp, id_bytes)| {
         let mut vector_clock = VectorClock::new();
-        vector_clock.insert(user_id.clone(), 1);
+        vector_clock.insert(user_id.clone().into(), 1);
 
         let layer = match &operation {
             OperationType::TextInsert { .. } |
@@ -168,13 +168,17 @@ fn arb_crdt_operation(codex_id: CodexId) -> impl Strategy<Value = CRDTOperation>
             OperationType::MetadataDelete { .. } => CRDTLayer::Metadata,
 
             OperationType::ReferenceAdd { .. } |
-            OperationType::ReferenceRemove { .. } => CRDTLayer::Reference,
+            OperationType::ReferenceRemove { .. } |
+            OperationType::ReferenceAddMany { .. } |
+            OperationType::ReferenceRemoveTarget { .. } => CRDTLayer::Reference,
+
+            OperationType::RetireClients { .. } => CRDTLayer::Clock,
         };
 
         CRDTOperation {
             id: Uuid::from_bytes(id_bytes),
             operation,
-            user_id,
+            user_id: user_id.into(),
             timestamp,
             vector_clock,
             parents: Vec::new(),
@@ -330,7 +334,7 @@ mod property_tests {
                 let _ = crdt.apply_operation(operation);
 
                 // Check that the vector clock for this user is monotonically increasing
-                let current_clock_value = crdt.vector_clock.get(&user_id).copied().unwrap_or(0);
+                let current_clock_value = crdt.vector_clock.get(user_id.as_str()).copied().unwrap_or(0);
                 prop_assert!(
                     current_clock_value > previous_clock_value,
                     "Vector clock should be monotonically increasing: {} <= {}",
@@ -816,7 +820,7 @@ mod invariant_tests {
                     // Verify invariants after each operation
 
                     // 1. Vector clock should never decrease
-                    let current_clock = crdt.vector_clock.get(&user_id).copied().unwrap_or(0);
+                    let current_clock = crdt.vector_clock.get(user_id.as_str()).copied().unwrap_or(0);
                     prop_assert!(current_clock >= 0);
 
                     // 2. Operation log should be bounded
@@ -34,6 +34,10 @@ mod rag_config_tests {
             enable_circuit_breaker: true,
             fallback_config: crate::rag::fallback_service::FallbackConfig::default(),
             fallback_strategy: crate::rag::fallback_service::FallbackStrategy::default(),
+            search: crate::rag::SearchOptions::default(),
+            rerank: crate::rag::RerankConfig::default(),
+            embedding_cache: crate::rag::EmbeddingCacheConfig::default(),
+            embedding_batch: crate::rag::EmbeddingBatchConfig::default(),
         };
 
         assert_eq!(config.max_chunk_size, 512);
@@ -330,6 +334,7 @@ mod project_manager_tests {
             ignore_patterns: vec!["target/".to_string(), ".git/".to_string()],
             embedding_model: Some("all-MiniLM-L6-v2".to_string()),
             chunk_strategy: Some("Semantic".to_string()),
+            private: false,
         };
 
         let config = ProjectConfig {
@@ -360,6 +365,7 @@ mod project_manager_tests {
             ignore_patterns: vec!["target/".to_string()],
             embedding_model: Some("all-MiniLM-L6-v2".to_string()),
             chunk_strategy: Some("Paragraph".to_string()),
+            private: false,
         };
 
         let config = ProjectConfig {
@@ -533,6 +539,10 @@ mod rag_error_handling_tests {
             enable_circuit_breaker: true,
             fallback_config: crate::rag::fallback_service::FallbackConfig::default(),
             fallback_strategy: crate::rag::fallback_service::FallbackStrategy::default(),
+            search: crate::rag::SearchOptions::default(),
+            rerank: crate::rag::RerankConfig::default(),
+            embedding_cache: crate::rag::EmbeddingCacheConfig::default(),
+            embedding_batch: crate::rag::EmbeddingBatchConfig::default(),
         };
 
         // TODO: Test error handling for invalid model
@@ -226,6 +226,7 @@ mod role_tests {
             file_restrictions,
             execution_context: ExecutionContext::default(),
             metadata: HashMap::new(),
+            requires_approval: Vec::new(),
         };
 
         // Test read access
@@ -259,6 +260,7 @@ mod role_tests {
             file_restrictions: FileRestrictions::default(),
             execution_context: ExecutionContext::default(),
             metadata,
+            requires_approval: Vec::new(),
         };
 
         assert_eq!(role.metadata.len(), 3);
@@ -401,6 +403,7 @@ mod glob_pattern_tests {
             file_restrictions,
             execution_context: ExecutionContext::default(),
             metadata: HashMap::new(),
+            requires_approval: Vec::new(),
         }
     }
 
@@ -486,6 +489,7 @@ mod glob_pattern_tests {
             file_restrictions,
             execution_context: ExecutionContext::default(),
             metadata: HashMap::new(),
+            requires_approval: Vec::new(),
         };
 
         // Test read permissions
@@ -564,6 +568,7 @@ mod role_serialization_tests {
                 environment_variables: env_vars,
             },
             metadata,
+            requires_approval: Vec::new(),
         };
 
         // Test serialization
@@ -668,6 +673,7 @@ mod integration_tests {
                 environment_variables: HashMap::new(),
             },
             metadata: HashMap::new(),
+            requires_approval: Vec::new(),
         };
 
         // Test that restricted role cannot access sensitive files
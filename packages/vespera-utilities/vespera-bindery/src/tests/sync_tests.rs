@@ -125,6 +125,145 @@ mod sync_manager_tests {
         assert_eq!(stats_final.active_connections, 0);
         assert_eq!(stats_final.registered_codices, 0);
     }
+
+    #[tokio::test]
+    async fn test_sync_manager_enforces_codex_acl() {
+        use crate::codex::{CodexAcl, ACL_FIELD};
+
+        let sync_manager = SyncManager::new(BinderyConfig { collaboration_enabled: true, ..Default::default() })
+            .expect("Should create sync manager");
+        let codex_id = Uuid::new_v4();
+        let mut crdt = VesperaCRDT::new(codex_id, "alice".to_string());
+        let acl = CodexAcl::new("alice").with_editor("bob").with_reader("carol");
+        crdt.set_metadata(ACL_FIELD.to_string(), acl.to_value("alice").unwrap()).unwrap();
+        let crdt = std::sync::Arc::new(crdt);
+        sync_manager.register_codex(codex_id, crdt.clone()).await.unwrap();
+
+        // Operations written by each user on their own replica
+        let operations: Vec<CRDTOperation> = ["bob", "carol", "mallory"].iter().map(|user| {
+            let mut replica = (*crdt).clone();
+            replica.set_metadata("status".to_string(), TemplateValue::Text {
+                value: format!("set by {}", user),
+                timestamp: Utc::now(),
+                user_id: user.to_string(),
+            }).unwrap();
+            let mut operation = replica.operation_log.last().unwrap().clone();
+            operation.user_id = (*user).into();
+            operation
+        }).collect();
+
+        for (connection, peer) in [("c1", Some("bob")), ("c2", Some("carol")), ("c3", Some("mallory")), ("c4", None)] {
+            sync_manager.register_connection(connection.to_string(), peer.map(str::to_string)).unwrap();
+        }
+        let mut allowed = Vec::new();
+        for (connection, operation) in ["c1", "c2", "c3"].into_iter().zip(operations) {
+            allowed.extend(sync_manager.authorize_operations(connection, &codex_id, vec![operation]).await.unwrap());
+        }
+        assert_eq!(allowed.len(), 1);
+        assert_eq!(allowed[0].user_id, "bob");
+        let message = crate::sync::SyncMessage::SyncResponse { codex_id, operations: crdt.operation_log.clone() };
+        let mut outgoing = sync_manager.outgoing(&codex_id, &message).await.unwrap();
+        outgoing.sort();
+        let targets: Vec<_> = outgoing.iter().map(|(connection, _)| connection.as_str()).collect();
+        assert_eq!(targets, vec!["c1", "c2"]);
+        assert!(matches!(
+            crate::sync::SyncMessage::decode(&outgoing[0].1, usize::MAX).unwrap(),
+            crate::sync::SyncMessage::SyncResponse { operations, .. } if operations.len() == crdt.operation_log.len()
+        ));
+        assert!(sync_manager.may_read(&codex_id, "carol"));
+        assert!(!sync_manager.may_read(&codex_id, "mallory"));
+
+        let stats = sync_manager.get_stats();
+        assert_eq!(stats.rejected_operations, 2);
+        assert_eq!(stats.withheld_pushes, 2);
+    }
+
+    #[tokio::test]
+    async fn test_sync_manager_rejects_operations_claiming_another_author() {
+        use crate::{compression::CompressionConfig, sync::SyncMessage};
+
+        let sync_manager = SyncManager::new(BinderyConfig { collaboration_enabled: true, ..Default::default() })
+            .expect("Should create sync manager");
+        let codex_id = Uuid::new_v4();
+        let mut crdt = VesperaCRDT::new(codex_id, "alice".to_string());
+        crdt.set_title("Plan").unwrap();
+        let crdt = std::sync::Arc::new(crdt);
+        sync_manager.register_codex(codex_id, crdt.clone()).await.unwrap();
+        sync_manager.register_connection("c1".to_string(), Some("mallory".to_string())).unwrap();
+        sync_manager.register_connection("c2".to_string(), None).unwrap();
+
+        // Mallory passes an edit off as the owner's
+        let mut replica = (*crdt).clone();
+        replica.set_title("Taken over").unwrap();
+        let mut forged = replica.operation_log.last().unwrap().clone();
+        forged.user_id = "alice".into();
+
+        let allowed = sync_manager.authorize_operations("c1", &codex_id, vec![forged.clone()]).await.unwrap();
+        assert!(allowed.is_empty());
+        let allowed = sync_manager.authorize_operations("c2", &codex_id, vec![forged.clone()]).await.unwrap();
+        assert!(allowed.is_empty());
+
+        let message = SyncMessage::SyncResponse { codex_id, operations: vec![forged.clone()] };
+        let payload = message.encode(&CompressionConfig::none()).unwrap();
+        assert!(sync_manager.receive("c1", &payload).await.unwrap().is_none());
+        let broadcast = SyncMessage::OperationBroadcast { operation: forged.clone() };
+        let payload = broadcast.encode(&CompressionConfig::none()).unwrap();
+        assert!(sync_manager.receive("c1", &payload).await.unwrap().is_none());
+
+        // Codices not registered here still need the right author
+        let allowed = sync_manager.authorize_operations("c1", &Uuid::new_v4(), vec![forged]).await.unwrap();
+        assert!(allowed.is_empty());
+
+        assert_eq!(sync_manager.get_stats().rejected_operations, 5);
+    }
+
+    #[tokio::test]
+    async fn test_sync_manager_throttles_floods() {
+        use crate::{compression::CompressionConfig, sync::{SyncLimits, SyncMessage}};
+
+        let config = BinderyConfig {
+            collaboration_enabled: true,
+            sync_limits: SyncLimits {
+                operation_burst_per_connection: 5.0,
+                operations_per_second_per_connection: 0.001,
+                max_payload_bytes: 64 * 1024,
+                ..SyncLimits::default()
+            },
+            ..Default::default()
+        };
+        let sync_manager = SyncManager::new(config).expect("Should create sync manager");
+        sync_manager.register_connection("flooder".to_string(), Some("mallory".to_string())).unwrap();
+
+        let codex_id = Uuid::new_v4();
+        let mut crdt = VesperaCRDT::new(codex_id, "mallory".to_string());
+        for i in 0..8 {
+            crdt.set_title(&format!("Spam {}", i)).unwrap();
+        }
+        let message = SyncMessage::SyncResponse { codex_id, operations: crdt.operation_log.clone() };
+        let payload = message.encode(&CompressionConfig::none()).unwrap();
+
+        match sync_manager.receive("flooder", &payload).await.unwrap() {
+            Some(SyncMessage::SyncResponse { operations, .. }) => assert_eq!(operations.len(), 5),
+            other => panic!("unexpected message {:?}", other),
+        }
+        // The bucket is empty until it refills
+        assert!(sync_manager.receive("flooder", &payload).await.unwrap().is_none());
+
+        let oversized = vec![0u8; 64 * 1024 + 1];
+        assert!(sync_manager.receive("flooder", &oversized).await.is_err());
+
+        // Small on the wire, but far over the cap once decompressed
+        let bomb = CompressionConfig::default().compress(&vec![b' '; 16 * 1024 * 1024], "sync").unwrap();
+        assert!(bomb.len() < 64 * 1024);
+        assert!(matches!(
+            sync_manager.receive("flooder", &bomb).await,
+            Err(crate::BinderyError::DecompressionError(_))
+        ));
+
+        let stats = sync_manager.get_stats();
+        assert_eq!(stats.throttled_operations, 3 + 8);
+        assert_eq!(stats.dropped_payloads, 1);
+    }
 }
 
 #[cfg(test)]
@@ -309,7 +448,7 @@ mod offline_handling_tests {
                 },
             },
             OperationType::TextInsert {
-                field_id: "content".to_string(),
+                field_id: "content".into(),
                 position: 0,
                 content: "Offline edit".to_string(),
             },
@@ -188,6 +188,7 @@ mod task_execution_tests {
             started_at,
             completed_at: Some(completed_at),
             duration_ms: Some(duration_ms),
+            artifacts: Vec::new(),
         };
 
         assert_eq!(execution_result.task_id, task_id);
@@ -219,6 +220,7 @@ mod task_execution_tests {
             started_at,
             completed_at: Some(started_at + Duration::minutes(5)),
             duration_ms: Some(5 * 60 * 1000), // 5 minutes
+            artifacts: Vec::new(),
         };
 
         assert_eq!(execution_result.status, ExecutionStatus::Failed);
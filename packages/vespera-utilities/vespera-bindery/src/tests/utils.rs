@@ -49,15 +49,23 @@ pub fn create_test_config() -> BinderyConfig {
         database_path: Some(std::env::temp_dir().join(format!("test_db_{}.sqlite", Uuid::new_v4()))),
         audit_db_path: Some(std::env::temp_dir().join(format!("test_audit_{}.sqlite", Uuid::new_v4()))),
         database_pool: DatabasePoolConfig::default(),
+        backup: None,
+        storage: crate::database::StorageConfig::default(),
         audit_config: None,
         collaboration_enabled: false,
         max_operations_in_memory: 100,
         auto_gc_enabled: true,
         gc_interval_seconds: 60,
         compression_enabled: false, // Disable for faster tests
+        compression: crate::compression::CompressionConfig::default(),
         user_id: Some("test_user".to_string()),
         project_id: Some("test_project".to_string()),
         audit_logging_enabled: false,
+        shutdown_timeout_seconds: 5,
+        trash_retention_days: 30,
+        quotas: crate::task_management::QuotaConfig::default(),
+        sync_limits: crate::sync::SyncLimits::default(),
+        user_roles: HashMap::new(),
     }
 }
 
@@ -92,7 +100,7 @@ impl TestDataGenerator {
                         operations.push((
                             user_id.clone(),
                             OperationType::TextInsert {
-                                field_id: "content".to_string(),
+                                field_id: "content".into(),
                                 position: op_idx,
                                 content: format!("text_{}_{}_{}", user_idx, op_idx, op_idx),
                             }
@@ -223,6 +231,7 @@ pub fn create_mock_role(name: &str, tool_groups: Vec<ToolGroup>) -> Role {
             environment_variables: HashMap::new(),
         },
         metadata: HashMap::new(),
+        requires_approval: Vec::new(),
     }
 }
 
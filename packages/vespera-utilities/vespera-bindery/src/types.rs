@@ -22,8 +22,8 @@ pub type OperationId = Uuid;
 /// Content hash for integrity verification
 pub type ContentHash = String;
 
-/// Vector clock for distributed operations
-pub type VectorClock = HashMap<UserId, u64>;
+/// Vector clock for distributed operations, keyed by user
+pub type VectorClock = HashMap<crate::crdt::Symbol, u64>;
 
 /// Metadata associated with a Codex
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -0,0 +1,133 @@
+//! Usage budgets
+//!
+//! A budget caps the tokens and/or cost spent in a scope (everything, each
+//! session, the project, one provider) over a period (a UTC day, a UTC month,
+//! or all time). Exceeding a `reject` budget stops further requests in its
+//! scope until the period rolls over; a `warn` budget only logs.
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a budget counts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BudgetScope {
+    /// All requests
+    Global,
+    /// Each session separately; requests without a session are not counted
+    PerSession,
+    /// Requests made for the tracker's project
+    Project,
+    /// Requests served by one provider
+    Provider { provider_id: String },
+}
+
+/// Window a budget's usage is summed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Day,
+    Month,
+    Total,
+}
+
+impl BudgetPeriod {
+    /// Start of the period containing `now`, or None for `Total`
+    pub fn start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            BudgetPeriod::Day => now.date_naive().and_hms_opt(0, 0, 0).map(|t| t.and_utc()),
+            BudgetPeriod::Month => Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single(),
+            BudgetPeriod::Total => None,
+        }
+    }
+}
+
+/// What happens once a budget is used up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Refuse further requests in the budget's scope
+    #[default]
+    Reject,
+    /// Log a warning and carry on
+    Warn,
+}
+
+/// A limit on tokens and/or cost
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+    pub name: String,
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    /// Input plus output tokens
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+    #[serde(default)]
+    pub action: BudgetAction,
+}
+
+impl Budget {
+    /// Whether `tokens` and `cost_usd` have reached either limit
+    pub fn is_exceeded(&self, tokens: u64, cost_usd: f64) -> bool {
+        self.max_tokens.is_some_and(|max| tokens >= max) || self.max_cost_usd.is_some_and(|max| cost_usd >= max)
+    }
+}
+
+/// Usage against a budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    /// Session the usage was counted for (`per_session` budgets)
+    pub session_id: Option<String>,
+    pub period_start: Option<DateTime<Utc>>,
+    pub used_tokens: u64,
+    pub used_cost_usd: f64,
+    pub exceeded: bool,
+}
+
+impl BudgetStatus {
+    pub(crate) fn describe(&self) -> String {
+        let mut limits = Vec::new();
+        if let Some(max) = self.budget.max_tokens {
+            limits.push(format!("{} of {} tokens", self.used_tokens, max));
+        }
+        if let Some(max) = self.budget.max_cost_usd {
+            limits.push(format!("${:.4} of ${:.4}", self.used_cost_usd, max));
+        }
+        format!("budget '{}' used {}", self.budget.name, limits.join(", "))
+    }
+}
+
+/// A request was refused because a `reject` budget is used up
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Usage budget exceeded: {detail}")]
+pub struct BudgetExceeded {
+    pub budget: String,
+    pub detail: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods_and_limits() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 14, 15, 9, 26).unwrap();
+        assert_eq!(BudgetPeriod::Day.start(now), Some(Utc.with_ymd_and_hms(2025, 3, 14, 0, 0, 0).unwrap()));
+        assert_eq!(BudgetPeriod::Month.start(now), Some(Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap()));
+        assert_eq!(BudgetPeriod::Total.start(now), None);
+
+        let budget: Budget = serde_json::from_value(serde_json::json!({
+            "name": "daily",
+            "scope": {"type": "global"},
+            "period": "day",
+            "max_cost_usd": 1.0,
+        }))
+        .unwrap();
+        assert_eq!(budget.action, BudgetAction::Reject);
+        assert!(!budget.is_exceeded(1_000_000, 0.5));
+        assert!(budget.is_exceeded(0, 1.0));
+    }
+}
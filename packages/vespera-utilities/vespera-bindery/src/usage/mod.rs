@@ -0,0 +1,41 @@
+//! Token usage and cost budgeting
+//!
+//! Every request served through the [`ProviderManager`](crate::providers::ProviderManager)
+//! can be recorded with its token counts and cost:
+//! - Token counts come from the provider when it reports them, and are
+//!   otherwise counted with a tokenizer for the model or estimated
+//! - Costs come from the provider when it reports them (Claude Code CLI), and
+//!   are otherwise priced from a per-model table
+//! - Records are aggregated per session, project, day, provider or model
+//! - Budgets cap tokens or cost per scope and period, rejecting requests or
+//!   warning once used up
+
+pub mod budget;
+pub mod pricing;
+pub mod tokens;
+pub mod tracker;
+
+use serde::{Deserialize, Serialize};
+
+pub use budget::{Budget, BudgetAction, BudgetExceeded, BudgetPeriod, BudgetScope, BudgetStatus};
+pub use pricing::{ModelPrice, PricingTable};
+pub use tokens::{HeuristicTokenCounter, TokenCounter, TokenCounters};
+#[cfg(feature = "tokenizers")]
+pub use tokens::HfTokenCounter;
+pub use tracker::{ReportedUsage, UsageAggregate, UsageGroupBy, UsageQuery, UsageRecord, UsageRequest, UsageTracker};
+
+/// Usage tracking configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageConfig {
+    /// Project that recorded requests are attributed to
+    #[serde(default)]
+    pub project_id: Option<String>,
+
+    /// Budgets checked before each request
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+
+    /// Price overrides for models the built-in table doesn't know
+    #[serde(default)]
+    pub pricing: PricingTable,
+}
@@ -0,0 +1,107 @@
+//! Model pricing
+//!
+//! Per-token prices used to put a cost on requests whose provider doesn't
+//! report one. The built-in table covers the default models of the bundled
+//! provider templates at list price; configured prices take precedence.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input_per_mtok: f64, output_per_mtok: f64) -> Self {
+        Self { input_per_mtok, output_per_mtok }
+    }
+
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_mtok + output_tokens as f64 * self.output_per_mtok) / 1_000_000.0
+    }
+}
+
+/// List prices of well-known models, matched by prefix
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-4o-mini", ModelPrice::new(0.15, 0.60)),
+    ("gpt-4o", ModelPrice::new(2.50, 10.00)),
+    ("gpt-4.1-mini", ModelPrice::new(0.40, 1.60)),
+    ("gpt-4.1", ModelPrice::new(2.00, 8.00)),
+    ("claude-opus-4", ModelPrice::new(15.00, 75.00)),
+    ("claude-sonnet-4", ModelPrice::new(3.00, 15.00)),
+    ("claude-haiku-4", ModelPrice::new(1.00, 5.00)),
+    ("claude-3-5-haiku", ModelPrice::new(0.80, 4.00)),
+];
+
+/// Provider types that run models locally at no per-token cost
+const FREE_PROVIDER_TYPES: &[&str] = &["ollama"];
+
+/// Model prices: configured overrides first, then the built-in table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricingTable {
+    /// Prices by model name or name prefix
+    #[serde(default)]
+    pub models: HashMap<String, ModelPrice>,
+}
+
+impl PricingTable {
+    /// Price of `model` served by a provider of `provider_type`, if known
+    pub fn price(&self, provider_type: &str, model: Option<&str>) -> Option<ModelPrice> {
+        if let Some(model) = model {
+            // Routers name models "vendor/model"
+            let name = model.rsplit('/').next().unwrap_or(model);
+            let configured = longest_prefix(self.models.iter().map(|(k, v)| (k.as_str(), *v)), model)
+                .or_else(|| longest_prefix(self.models.iter().map(|(k, v)| (k.as_str(), *v)), name));
+            if configured.is_some() {
+                return configured;
+            }
+        }
+
+        if FREE_PROVIDER_TYPES.contains(&provider_type) {
+            return Some(ModelPrice::new(0.0, 0.0));
+        }
+
+        let model = model?;
+        let name = model.rsplit('/').next().unwrap_or(model).replace('.', "-");
+        longest_prefix(BUILTIN_PRICES.iter().copied(), &name)
+    }
+
+    /// Cost of a request, or None when the model's price is unknown
+    pub fn cost(&self, provider_type: &str, model: Option<&str>, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+        self.price(provider_type, model).map(|price| price.cost(input_tokens, output_tokens))
+    }
+}
+
+fn longest_prefix<'a>(prices: impl Iterator<Item = (&'a str, ModelPrice)>, model: &str) -> Option<ModelPrice> {
+    prices
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_lookup() {
+        let mut table = PricingTable::default();
+
+        // Dated and routed names resolve to the family's price; the longest prefix wins
+        assert_eq!(table.price("anthropic", Some("claude-sonnet-4-5-20250929")), Some(ModelPrice::new(3.0, 15.0)));
+        assert_eq!(table.price("openai-compatible", Some("anthropic/claude-sonnet-4.5")), Some(ModelPrice::new(3.0, 15.0)));
+        assert_eq!(table.price("openai-compatible", Some("gpt-4o-mini")), Some(ModelPrice::new(0.15, 0.60)));
+        assert_eq!(table.price("openai-compatible", Some("my-finetune")), None);
+        assert_eq!(table.price("ollama", Some("llama3.2")), Some(ModelPrice::new(0.0, 0.0)));
+
+        // Configured prices win, even for local models
+        table.models.insert("llama3".to_string(), ModelPrice::new(0.1, 0.1));
+        assert_eq!(table.price("ollama", Some("llama3.2")), Some(ModelPrice::new(0.1, 0.1)));
+
+        let cost = table.cost("anthropic", Some("claude-haiku-4-5"), 1_000_000, 100_000).unwrap();
+        assert!((cost - 1.5).abs() < 1e-9);
+    }
+}
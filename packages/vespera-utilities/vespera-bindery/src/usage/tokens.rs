@@ -0,0 +1,136 @@
+//! Token counting
+//!
+//! Providers usually report exact token counts; these counters fill in when
+//! they don't (streams cut short, servers that omit usage) and for estimating
+//! a request before it is sent. Counts from a model's own tokenizer file are
+//! exact; the heuristic counters approximate each vendor's BPE vocabulary
+//! from character and word counts.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Counts tokens in text the way a model's tokenizer would
+pub trait TokenCounter: Send + Sync {
+    fn count(&self, text: &str) -> usize;
+
+    /// Fixed cost of each chat message (role markers, separators)
+    fn message_overhead(&self) -> usize {
+        4
+    }
+
+    /// Whether counts are exact rather than estimated
+    fn is_exact(&self) -> bool {
+        false
+    }
+}
+
+/// Estimates tokens from text length, tuned per tokenizer family
+#[derive(Debug, Clone, Copy)]
+pub struct HeuristicTokenCounter {
+    chars_per_token: f64,
+    tokens_per_word: f64,
+}
+
+impl HeuristicTokenCounter {
+    /// OpenAI's cl100k/o200k vocabularies
+    pub const OPENAI: Self = Self { chars_per_token: 4.0, tokens_per_word: 1.3 };
+    /// Claude's vocabulary, slightly denser than OpenAI's
+    pub const ANTHROPIC: Self = Self { chars_per_token: 3.5, tokens_per_word: 1.4 };
+    /// Llama/Mistral-style SentencePiece vocabularies served by Ollama
+    pub const LLAMA: Self = Self { chars_per_token: 3.8, tokens_per_word: 1.35 };
+
+    /// Counter for a provider type (`claude-code-cli`, `ollama`, ...)
+    pub fn for_provider_type(provider_type: &str) -> Self {
+        match provider_type {
+            "anthropic" | "claude-code-cli" => Self::ANTHROPIC,
+            "ollama" => Self::LLAMA,
+            _ => Self::OPENAI,
+        }
+    }
+}
+
+impl TokenCounter for HeuristicTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        // Character counts underestimate code and punctuation-heavy text,
+        // word counts underestimate long unbroken strings; take the larger
+        let by_chars = text.chars().count() as f64 / self.chars_per_token;
+        let by_words = text.split_whitespace().count() as f64 * self.tokens_per_word;
+        by_chars.max(by_words).ceil() as usize
+    }
+}
+
+/// Exact counts from a HuggingFace `tokenizer.json`
+#[cfg(feature = "tokenizers")]
+pub struct HfTokenCounter {
+    tokenizer: tokenizers::Tokenizer,
+}
+
+#[cfg(feature = "tokenizers")]
+impl HfTokenCounter {
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let tokenizer = tokenizers::Tokenizer::from_file(path).map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
+        Ok(Self { tokenizer })
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl TokenCounter for HfTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        match self.tokenizer.encode(text, false) {
+            Ok(encoding) => encoding.len(),
+            Err(e) => {
+                tracing::warn!("Tokenizer failed, estimating instead: {}", e);
+                HeuristicTokenCounter::LLAMA.count(text)
+            }
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// Token counters by model, falling back to a heuristic for the provider type
+#[derive(Default, Clone)]
+pub struct TokenCounters {
+    by_model: HashMap<String, Arc<dyn TokenCounter>>,
+}
+
+impl TokenCounters {
+    /// Use `counter` for requests to `model`
+    pub fn register(&mut self, model: impl Into<String>, counter: Arc<dyn TokenCounter>) {
+        self.by_model.insert(model.into(), counter);
+    }
+
+    pub fn counter(&self, provider_type: &str, model: Option<&str>) -> Arc<dyn TokenCounter> {
+        model
+            .and_then(|m| self.by_model.get(m))
+            .cloned()
+            .unwrap_or_else(|| Arc::new(HeuristicTokenCounter::for_provider_type(provider_type)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_counts() {
+        let counter = HeuristicTokenCounter::OPENAI;
+        assert_eq!(counter.count(""), 0);
+        // 43 characters / 4 = 10.75; 9 words * 1.3 = 11.7
+        assert_eq!(counter.count("The quick brown fox jumps over the lazy dog"), 12);
+        // One long "word" is counted by length
+        assert_eq!(counter.count(&"a".repeat(400)), 100);
+        // Claude's vocabulary yields more tokens for the same text
+        assert!(HeuristicTokenCounter::for_provider_type("anthropic").count(&"a".repeat(400)) > 100);
+
+        let mut counters = TokenCounters::default();
+        counters.register("tiny", Arc::new(HeuristicTokenCounter { chars_per_token: 1.0, tokens_per_word: 1.0 }));
+        assert_eq!(counters.counter("ollama", Some("tiny")).count("abcd"), 4);
+        assert_eq!(counters.counter("ollama", Some("other")).count("abcd"), 2);
+    }
+}
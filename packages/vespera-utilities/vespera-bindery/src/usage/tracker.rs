@@ -0,0 +1,551 @@
+//! Usage tracker
+//!
+//! Records one row per provider request in the `usage_records` table of the
+//! Bindery database, aggregates them for reports and checks budgets.
+
+use std::sync::Arc;
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row, Sqlite};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::budget::{Budget, BudgetAction, BudgetExceeded, BudgetScope, BudgetStatus};
+use super::tokens::{TokenCounter, TokenCounters};
+use super::UsageConfig;
+use crate::database::Database;
+use crate::providers::types::UsageStats;
+use crate::providers::ProviderUsage;
+
+/// A request about to be (or being) served, before its usage is known
+#[derive(Debug, Clone)]
+pub struct UsageRequest {
+    pub provider_id: String,
+    pub provider_type: String,
+    pub model: Option<String>,
+    pub session_id: Option<String>,
+    /// Prompt text, counted when the provider doesn't report input tokens
+    pub prompt: String,
+    /// Number of chat messages in the prompt
+    pub messages: usize,
+}
+
+/// Usage as reported by the provider
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReportedUsage {
+    /// Input and output tokens
+    pub tokens: Option<(u64, u64)>,
+    pub cost_usd: Option<f64>,
+}
+
+impl From<&ProviderUsage> for ReportedUsage {
+    fn from(usage: &ProviderUsage) -> Self {
+        Self {
+            tokens: Some((usage.input_tokens as u64, usage.output_tokens as u64)),
+            cost_usd: usage.cost_usd,
+        }
+    }
+}
+
+impl From<&UsageStats> for ReportedUsage {
+    fn from(usage: &UsageStats) -> Self {
+        Self {
+            tokens: Some((usage.prompt_tokens as u64, usage.completion_tokens as u64)),
+            cost_usd: None,
+        }
+    }
+}
+
+/// One recorded request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub provider_id: String,
+    pub provider_type: String,
+    pub model: Option<String>,
+    pub session_id: Option<String>,
+    pub project_id: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// None when the model's price is unknown
+    pub cost_usd: Option<f64>,
+    /// Token counts were estimated rather than reported by the provider
+    pub estimated: bool,
+}
+
+impl UsageRecord {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Dimension usage reports are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    #[default]
+    Day,
+    Session,
+    Project,
+    Provider,
+    Model,
+}
+
+impl UsageGroupBy {
+    fn column(&self) -> &'static str {
+        match self {
+            UsageGroupBy::Day => "day",
+            UsageGroupBy::Session => "session_id",
+            UsageGroupBy::Project => "project_id",
+            UsageGroupBy::Provider => "provider_id",
+            UsageGroupBy::Model => "model",
+        }
+    }
+}
+
+/// Filters and grouping for a usage report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageQuery {
+    #[serde(default)]
+    pub group_by: UsageGroupBy,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub provider_id: Option<String>,
+}
+
+/// Usage summed over one group of a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageAggregate {
+    /// Value of the grouping dimension; None for requests without one (e.g. no session)
+    pub key: Option<String>,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    /// Sum over requests with a known price
+    pub cost_usd: f64,
+    /// Requests whose cost is unknown
+    pub unpriced_requests: u64,
+    /// Requests whose token counts were estimated
+    pub estimated_requests: u64,
+}
+
+/// Records provider usage and enforces budgets
+pub struct UsageTracker {
+    database: Arc<Database>,
+    config: UsageConfig,
+    counters: TokenCounters,
+}
+
+impl UsageTracker {
+    /// Create a tracker storing records in `database`
+    pub async fn new(database: Arc<Database>, config: UsageConfig) -> Result<Self> {
+        let tracker = Self {
+            database,
+            config,
+            counters: TokenCounters::default(),
+        };
+        tracker.init_schema().await?;
+        Ok(tracker)
+    }
+
+    /// Count tokens for `model` with `counter` instead of the heuristic for its provider
+    pub fn with_token_counter(mut self, model: impl Into<String>, counter: Arc<dyn TokenCounter>) -> Self {
+        self.counters.register(model, counter);
+        self
+    }
+
+    pub fn config(&self) -> &UsageConfig {
+        &self.config
+    }
+
+    async fn init_schema(&self) -> Result<()> {
+        let pool = self.database.get_pool();
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS usage_records (
+                id TEXT PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                day TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                provider_type TEXT NOT NULL,
+                model TEXT,
+                session_id TEXT,
+                project_id TEXT,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cost_usd REAL,
+                estimated INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_usage_timestamp ON usage_records(timestamp)")
+            .execute(pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_usage_session ON usage_records(session_id)")
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Estimated input tokens of a request, before it is sent
+    pub fn estimate_input_tokens(&self, request: &UsageRequest) -> u64 {
+        let counter = self.counters.counter(&request.provider_type, request.model.as_deref());
+        (counter.count(&request.prompt) + counter.message_overhead() * request.messages.max(1)) as u64
+    }
+
+    /// Refuse the request if a `reject` budget covering it is used up
+    pub async fn enforce(&self, provider_id: &str, session_id: Option<&str>) -> Result<()> {
+        for budget in self.config.budgets.iter().filter(|b| b.action == BudgetAction::Reject) {
+            let Some(status) = self.budget_status(budget, provider_id, session_id).await? else {
+                continue;
+            };
+            if status.exceeded {
+                crate::observability::BinderyMetrics::record_quota_rejection(&budget.name);
+                return Err(BudgetExceeded {
+                    budget: budget.name.clone(),
+                    detail: status.describe(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a completed request
+    ///
+    /// Token counts the provider didn't report are estimated from `prompt`
+    /// and `output`; the cost is priced from the token counts unless the
+    /// provider reported one.
+    pub async fn record(&self, request: &UsageRequest, output: &str, reported: ReportedUsage) -> Result<UsageRecord> {
+        let (input_tokens, output_tokens, estimated) = match reported.tokens {
+            Some((input, output)) if input + output > 0 => (input, output, false),
+            _ => {
+                let counter = self.counters.counter(&request.provider_type, request.model.as_deref());
+                (self.estimate_input_tokens(request), counter.count(output) as u64, true)
+            }
+        };
+        let cost_usd = reported.cost_usd.or_else(|| {
+            self.config
+                .pricing
+                .cost(&request.provider_type, request.model.as_deref(), input_tokens, output_tokens)
+        });
+
+        let record = UsageRecord {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            provider_id: request.provider_id.clone(),
+            provider_type: request.provider_type.clone(),
+            model: request.model.clone(),
+            session_id: request.session_id.clone(),
+            project_id: self.config.project_id.clone(),
+            input_tokens,
+            output_tokens,
+            cost_usd,
+            estimated,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO usage_records
+                (id, timestamp, day, provider_id, provider_type, model, session_id, project_id,
+                 input_tokens, output_tokens, cost_usd, estimated)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&record.id)
+        .bind(format_timestamp(record.timestamp))
+        .bind(record.timestamp.format("%Y-%m-%d").to_string())
+        .bind(&record.provider_id)
+        .bind(&record.provider_type)
+        .bind(&record.model)
+        .bind(&record.session_id)
+        .bind(&record.project_id)
+        .bind(record.input_tokens as i64)
+        .bind(record.output_tokens as i64)
+        .bind(record.cost_usd)
+        .bind(record.estimated)
+        .execute(self.database.get_pool())
+        .await?;
+        debug!(
+            provider = %record.provider_id,
+            input_tokens = record.input_tokens,
+            output_tokens = record.output_tokens,
+            cost_usd = ?record.cost_usd,
+            "Recorded provider usage"
+        );
+
+        self.warn_on_crossed_budgets(&record).await;
+        Ok(record)
+    }
+
+    /// Log budgets that `record` pushed over their limit
+    async fn warn_on_crossed_budgets(&self, record: &UsageRecord) {
+        for budget in &self.config.budgets {
+            let status = match self.budget_status(budget, &record.provider_id, record.session_id.as_deref()).await {
+                Ok(Some(status)) => status,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Failed to check usage budget '{}': {}", budget.name, e);
+                    continue;
+                }
+            };
+            let before_tokens = status.used_tokens.saturating_sub(record.total_tokens());
+            let before_cost = status.used_cost_usd - record.cost_usd.unwrap_or(0.0);
+            if status.exceeded && !budget.is_exceeded(before_tokens, before_cost) {
+                match budget.action {
+                    BudgetAction::Reject => warn!("Usage {}; further requests will be rejected", status.describe()),
+                    BudgetAction::Warn => warn!("Usage {}", status.describe()),
+                }
+            }
+        }
+    }
+
+    /// Usage against every budget; `per_session` budgets are only reported for `session_id`
+    pub async fn budget_statuses(&self, session_id: Option<&str>) -> Result<Vec<BudgetStatus>> {
+        let mut statuses = Vec::new();
+        for budget in &self.config.budgets {
+            let provider_id = match &budget.scope {
+                BudgetScope::Provider { provider_id } => provider_id.as_str(),
+                _ => "",
+            };
+            if let Some(status) = self.budget_status(budget, provider_id, session_id).await? {
+                statuses.push(status);
+            }
+        }
+        Ok(statuses)
+    }
+
+    /// Usage against `budget` for a request to `provider_id`, or None if the budget doesn't cover it
+    async fn budget_status(&self, budget: &Budget, provider_id: &str, session_id: Option<&str>) -> Result<Option<BudgetStatus>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT COALESCE(SUM(input_tokens + output_tokens), 0) AS tokens, COALESCE(SUM(cost_usd), 0.0) AS cost \
+             FROM usage_records WHERE 1 = 1",
+        );
+        let mut counted_session = None;
+        match &budget.scope {
+            BudgetScope::Global => {}
+            BudgetScope::PerSession => {
+                let Some(session_id) = session_id else { return Ok(None) };
+                query.push(" AND session_id = ").push_bind(session_id.to_string());
+                counted_session = Some(session_id.to_string());
+            }
+            BudgetScope::Project => {
+                query.push(" AND project_id IS ").push_bind(self.config.project_id.clone());
+            }
+            BudgetScope::Provider { provider_id: budget_provider } => {
+                if budget_provider != provider_id {
+                    return Ok(None);
+                }
+                query.push(" AND provider_id = ").push_bind(provider_id.to_string());
+            }
+        }
+
+        let period_start = budget.period.start(Utc::now());
+        if let Some(start) = period_start {
+            query.push(" AND timestamp >= ").push_bind(format_timestamp(start));
+        }
+
+        let row = query.build().fetch_one(&self.database.get_read_pool()).await?;
+        let used_tokens = row.try_get::<i64, _>("tokens")? as u64;
+        let used_cost_usd: f64 = row.try_get("cost")?;
+
+        Ok(Some(BudgetStatus {
+            budget: budget.clone(),
+            session_id: counted_session,
+            period_start,
+            used_tokens,
+            used_cost_usd,
+            exceeded: budget.is_exceeded(used_tokens, used_cost_usd),
+        }))
+    }
+
+    /// Usage summed by `query.group_by`, ordered by group key
+    pub async fn report(&self, query: &UsageQuery) -> Result<Vec<UsageAggregate>> {
+        let column = query.group_by.column();
+        let mut sql = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT {column} AS key, COUNT(*) AS requests, \
+             COALESCE(SUM(input_tokens), 0) AS input_tokens, COALESCE(SUM(output_tokens), 0) AS output_tokens, \
+             COALESCE(SUM(cost_usd), 0.0) AS cost, SUM(cost_usd IS NULL) AS unpriced, SUM(estimated) AS estimated \
+             FROM usage_records WHERE 1 = 1"
+        ));
+        if let Some(since) = query.since {
+            sql.push(" AND timestamp >= ").push_bind(format_timestamp(since));
+        }
+        if let Some(until) = query.until {
+            sql.push(" AND timestamp < ").push_bind(format_timestamp(until));
+        }
+        if let Some(session_id) = &query.session_id {
+            sql.push(" AND session_id = ").push_bind(session_id.clone());
+        }
+        if let Some(project_id) = &query.project_id {
+            sql.push(" AND project_id = ").push_bind(project_id.clone());
+        }
+        if let Some(provider_id) = &query.provider_id {
+            sql.push(" AND provider_id = ").push_bind(provider_id.clone());
+        }
+        sql.push(format!(" GROUP BY {column} ORDER BY {column}"));
+
+        let rows = sql.build().fetch_all(&self.database.get_read_pool()).await?;
+        rows.iter()
+            .map(|row| {
+                let input_tokens = row.try_get::<i64, _>("input_tokens")? as u64;
+                let output_tokens = row.try_get::<i64, _>("output_tokens")? as u64;
+                Ok(UsageAggregate {
+                    key: row.try_get("key")?,
+                    requests: row.try_get::<i64, _>("requests")? as u64,
+                    input_tokens,
+                    output_tokens,
+                    total_tokens: input_tokens + output_tokens,
+                    cost_usd: row.try_get("cost")?,
+                    unpriced_requests: row.try_get::<i64, _>("unpriced")? as u64,
+                    estimated_requests: row.try_get::<i64, _>("estimated")? as u64,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Fixed-width RFC 3339, so stored timestamps compare correctly as strings
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::budget::BudgetPeriod;
+    use tempfile::TempDir;
+
+    fn request(provider_id: &str, session_id: Option<&str>, model: &str) -> UsageRequest {
+        UsageRequest {
+            provider_id: provider_id.to_string(),
+            provider_type: "anthropic".to_string(),
+            model: Some(model.to_string()),
+            session_id: session_id.map(str::to_string),
+            prompt: "Summarize the chapter".to_string(),
+            messages: 1,
+        }
+    }
+
+    async fn tracker(budgets: Vec<Budget>) -> (UsageTracker, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().join("usage.db")).await.unwrap();
+        let config = UsageConfig {
+            project_id: Some("atelier".to_string()),
+            budgets,
+            ..UsageConfig::default()
+        };
+        (UsageTracker::new(Arc::new(database), config).await.unwrap(), temp_dir)
+    }
+
+    fn reported(input: u64, output: u64) -> ReportedUsage {
+        ReportedUsage { tokens: Some((input, output)), cost_usd: None }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_report() {
+        let (tracker, _temp_dir) = tracker(Vec::new()).await;
+
+        // Reported tokens are priced from the table
+        let record = tracker
+            .record(&request("claude", Some("s1"), "claude-sonnet-4-5"), "", reported(1_000_000, 0))
+            .await
+            .unwrap();
+        assert!(!record.estimated);
+        assert!((record.cost_usd.unwrap() - 3.0).abs() < 1e-9);
+
+        // Missing usage is estimated; a reported cost is kept as is
+        let record = tracker
+            .record(
+                &request("claude", Some("s2"), "claude-sonnet-4-5"),
+                "A short summary.",
+                ReportedUsage { tokens: None, cost_usd: Some(0.25) },
+            )
+            .await
+            .unwrap();
+        assert!(record.estimated && record.input_tokens > 0 && record.output_tokens > 0);
+        assert_eq!(record.cost_usd, Some(0.25));
+
+        tracker.record(&request("custom", Some("s1"), "my-finetune"), "", reported(10, 5)).await.unwrap();
+
+        let by_session = tracker
+            .report(&UsageQuery { group_by: UsageGroupBy::Session, ..UsageQuery::default() })
+            .await
+            .unwrap();
+        assert_eq!(by_session.len(), 2);
+        assert_eq!(by_session[0].key.as_deref(), Some("s1"));
+        assert_eq!(by_session[0].requests, 2);
+        assert_eq!(by_session[0].total_tokens, 1_000_015);
+        assert_eq!(by_session[0].unpriced_requests, 1);
+        assert_eq!(by_session[1].estimated_requests, 1);
+
+        let by_day = tracker.report(&UsageQuery::default()).await.unwrap();
+        assert_eq!(by_day.len(), 1);
+        assert_eq!(by_day[0].key, Some(Utc::now().format("%Y-%m-%d").to_string()));
+        assert!((by_day[0].cost_usd - 3.25).abs() < 1e-9);
+
+        let filtered = tracker
+            .report(&UsageQuery { provider_id: Some("custom".to_string()), group_by: UsageGroupBy::Project, ..UsageQuery::default() })
+            .await
+            .unwrap();
+        assert_eq!(filtered[0].key.as_deref(), Some("atelier"));
+        assert_eq!(filtered[0].requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_budget_enforcement() {
+        let budgets = vec![
+            Budget {
+                name: "session tokens".to_string(),
+                scope: BudgetScope::PerSession,
+                period: BudgetPeriod::Total,
+                max_tokens: Some(100),
+                max_cost_usd: None,
+                action: BudgetAction::Reject,
+            },
+            Budget {
+                name: "daily spend".to_string(),
+                scope: BudgetScope::Global,
+                period: BudgetPeriod::Day,
+                max_tokens: None,
+                max_cost_usd: Some(0.01),
+                action: BudgetAction::Warn,
+            },
+        ];
+        let (tracker, _temp_dir) = tracker(budgets).await;
+
+        tracker.record(&request("claude", Some("s1"), "claude-haiku-4-5"), "", reported(90, 20)).await.unwrap();
+
+        // s1 is over its token budget; other sessions and session-less requests are not
+        let err = tracker.enforce("claude", Some("s1")).await.unwrap_err();
+        let exceeded = err.downcast_ref::<BudgetExceeded>().unwrap();
+        assert_eq!(exceeded.budget, "session tokens");
+        assert!(exceeded.detail.contains("110 of 100 tokens"));
+        tracker.enforce("claude", Some("s2")).await.unwrap();
+        tracker.enforce("claude", None).await.unwrap();
+
+        // Warn budgets never reject
+        tracker.record(&request("claude", None, "claude-haiku-4-5"), "", reported(10_000, 0)).await.unwrap();
+        tracker.enforce("claude", Some("s2")).await.unwrap();
+
+        let statuses = tracker.budget_statuses(Some("s2")).await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].used_tokens, 0);
+        assert!(!statuses[0].exceeded);
+        assert_eq!(statuses[1].used_tokens, 10_110);
+        assert!(statuses[1].exceeded);
+        assert_eq!(tracker.budget_statuses(None).await.unwrap().len(), 1);
+    }
+}
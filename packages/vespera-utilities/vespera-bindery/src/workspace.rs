@@ -0,0 +1,276 @@
+//! Vespera workspaces
+//!
+//! A workspace is a directory with a `.vespera` folder holding everything
+//! Bindery stores for it: the task database, audit log, Codices, templates,
+//! roles, hooks, the RAG index and logs. [`Workspace`] finds that folder
+//! (walking up from a subdirectory, like git does), creates it when asked,
+//! and hands out a [`BinderyConfig`] pointing at it, so embedders do not each
+//! re-implement the layout.
+//!
+//! ```text
+//! .vespera/
+//! ├── workspace.json   # manifest
+//! ├── bindery.toml     # optional BinderyConfig overrides
+//! ├── tasks.db         # task and Codex index
+//! ├── audit.db
+//! ├── roles.yaml       # optional role definitions
+//! ├── codices/
+//! ├── templates/
+//! ├── hooks/
+//! ├── rag/
+//! └── logs/
+//! ```
+//!
+//! # Example
+//! ```rust,no_run
+//! use vespera_bindery::{workspace::Workspace, CodexManager};
+//!
+//! # fn example() -> anyhow::Result<()> {
+//! let workspace = Workspace::open_or_init(std::env::current_dir()?)?;
+//! let manager = CodexManager::with_config(workspace.config()?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{BinderyConfig, BinderyError, BinderyResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the folder that marks a workspace
+pub const VESPERA_DIR: &str = ".vespera";
+/// Manifest file inside the `.vespera` folder
+pub const MANIFEST_FILE: &str = "workspace.json";
+/// Optional configuration file inside the `.vespera` folder
+pub const CONFIG_FILE: &str = "bindery.toml";
+/// Layout version written to new manifests
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Description of a workspace, stored in `.vespera/workspace.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceManifest {
+    /// Version of the `.vespera` layout
+    pub version: u32,
+    /// Display name, the workspace directory's name by default
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Bindery version that created the workspace
+    pub created_by_version: String,
+}
+
+impl WorkspaceManifest {
+    fn new(root: &Path) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            name: root
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "workspace".to_string()),
+            created_at: Utc::now(),
+            created_by_version: crate::VERSION.to_string(),
+        }
+    }
+}
+
+/// A directory with a `.vespera` folder
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    root: PathBuf,
+    manifest: WorkspaceManifest,
+}
+
+impl Workspace {
+    /// The workspace containing `dir`: the nearest of `dir` and its ancestors
+    /// with a `.vespera` folder, or None if there is none
+    pub fn detect(dir: impl AsRef<Path>) -> BinderyResult<Option<Self>> {
+        let dir = canonicalize(dir.as_ref())?;
+        dir.ancestors()
+            .find(|ancestor| ancestor.join(VESPERA_DIR).is_dir())
+            .map(Self::open)
+            .transpose()
+    }
+
+    /// Open the workspace rooted at `root`
+    ///
+    /// Folders created before manifests existed (by the server or the RAG
+    /// index) are workspaces too; their manifest is filled in from the
+    /// directory until [`init`](Self::init) writes one.
+    pub fn open(root: impl AsRef<Path>) -> BinderyResult<Self> {
+        let root = canonicalize(root.as_ref())?;
+        let vespera_dir = root.join(VESPERA_DIR);
+        if !vespera_dir.is_dir() {
+            return Err(BinderyError::NotFound(format!("No {} folder in {}", VESPERA_DIR, root.display())));
+        }
+
+        let manifest_path = vespera_dir.join(MANIFEST_FILE);
+        let manifest = if manifest_path.exists() {
+            let content = fs::read_to_string(&manifest_path).map_err(|e| io_error(&manifest_path, e))?;
+            serde_json::from_str(&content).map_err(|e| {
+                BinderyError::SerializationError(format!("Invalid {}: {}", manifest_path.display(), e))
+            })?
+        } else {
+            WorkspaceManifest::new(&root)
+        };
+
+        Ok(Self { root, manifest })
+    }
+
+    /// Create the `.vespera` folder in `root` with its subfolders and
+    /// manifest
+    ///
+    /// Safe to run on an existing workspace: only what is missing is created.
+    pub fn init(root: impl AsRef<Path>) -> BinderyResult<Self> {
+        let root = root.as_ref();
+        fs::create_dir_all(root.join(VESPERA_DIR)).map_err(|e| io_error(root, e))?;
+        let workspace = Self::open(root)?;
+
+        for dir in [
+            workspace.codices_dir(),
+            workspace.templates_dir(),
+            workspace.hooks_dir(),
+            workspace.rag_dir(),
+            workspace.logs_dir(),
+        ] {
+            fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+        }
+
+        let manifest_path = workspace.vespera_dir().join(MANIFEST_FILE);
+        if !manifest_path.exists() {
+            let json = serde_json::to_string_pretty(&workspace.manifest)
+                .map_err(|e| BinderyError::SerializationError(e.to_string()))?;
+            fs::write(&manifest_path, json).map_err(|e| io_error(&manifest_path, e))?;
+        }
+
+        Ok(workspace)
+    }
+
+    /// The workspace containing `dir`, or a new one rooted at `dir`
+    pub fn open_or_init(dir: impl AsRef<Path>) -> BinderyResult<Self> {
+        match Self::detect(dir.as_ref())? {
+            Some(workspace) => Ok(workspace),
+            None => Self::init(dir),
+        }
+    }
+
+    /// Configuration for this workspace
+    ///
+    /// `.vespera/bindery.toml` and `VESPERA_*` environment variables apply as
+    /// in [`BinderyConfig::from_file`]; storage and database paths they leave
+    /// unset point into the `.vespera` folder.
+    pub fn config(&self) -> BinderyResult<BinderyConfig> {
+        let config_path = self.config_path();
+        let mut config = if config_path.exists() {
+            BinderyConfig::from_file(&config_path)?
+        } else {
+            BinderyConfig::from_env()?
+        };
+
+        config.storage_path.get_or_insert_with(|| self.codices_dir());
+        config.database_path.get_or_insert_with(|| self.database_path());
+        config.audit_db_path.get_or_insert_with(|| self.audit_db_path());
+        config.project_id.get_or_insert_with(|| self.manifest.name.clone());
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn manifest(&self) -> &WorkspaceManifest {
+        &self.manifest
+    }
+
+    pub fn vespera_dir(&self) -> PathBuf {
+        self.root.join(VESPERA_DIR)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.vespera_dir().join(CONFIG_FILE)
+    }
+
+    /// Task and Codex index
+    pub fn database_path(&self) -> PathBuf {
+        self.vespera_dir().join("tasks.db")
+    }
+
+    pub fn audit_db_path(&self) -> PathBuf {
+        self.vespera_dir().join("audit.db")
+    }
+
+    /// Role definitions, loaded with `RoleManager::load_roles_from_file`
+    pub fn roles_path(&self) -> PathBuf {
+        self.vespera_dir().join("roles.yaml")
+    }
+
+    pub fn codices_dir(&self) -> PathBuf {
+        self.vespera_dir().join("codices")
+    }
+
+    /// Workspace templates, loaded with `TemplateRegistry::load_from_directory`
+    pub fn templates_dir(&self) -> PathBuf {
+        self.vespera_dir().join("templates")
+    }
+
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.vespera_dir().join("hooks")
+    }
+
+    /// RAG documents, embeddings and indices
+    pub fn rag_dir(&self) -> PathBuf {
+        self.vespera_dir().join("rag")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.vespera_dir().join("logs")
+    }
+}
+
+fn canonicalize(path: &Path) -> BinderyResult<PathBuf> {
+    path.canonicalize().map_err(|e| io_error(path, e))
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> BinderyError {
+    BinderyError::IoError(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_and_detect_from_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("chapters").join("act-1");
+        fs::create_dir_all(&nested).unwrap();
+        assert!(Workspace::detect(&nested).unwrap().is_none());
+
+        let workspace = Workspace::init(temp_dir.path()).unwrap();
+        assert!(workspace.vespera_dir().join(MANIFEST_FILE).exists());
+        assert!(workspace.rag_dir().is_dir());
+        assert!(workspace.templates_dir().is_dir());
+
+        let detected = Workspace::detect(&nested).unwrap().unwrap();
+        assert_eq!(detected.root(), workspace.root());
+        assert_eq!(detected.manifest(), workspace.manifest());
+
+        // Running init again keeps the existing manifest
+        let again = Workspace::init(temp_dir.path()).unwrap();
+        assert_eq!(again.manifest().created_at, workspace.manifest().created_at);
+    }
+
+    #[test]
+    fn test_config_points_into_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::open_or_init(temp_dir.path()).unwrap();
+        fs::write(workspace.config_path(), "max_operations_in_memory = 2500\n").unwrap();
+
+        let config = workspace.config().unwrap();
+        assert_eq!(config.max_operations_in_memory, 2500);
+        assert_eq!(config.database_path, Some(workspace.database_path()));
+        assert_eq!(config.storage_path, Some(workspace.codices_dir()));
+        assert_eq!(config.project_id.as_deref(), Some(workspace.manifest().name.as_str()));
+    }
+}
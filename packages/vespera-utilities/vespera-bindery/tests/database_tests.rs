@@ -825,4 +825,439 @@ async fn test_transactional_nested_depth() {
 
     let branch_tasks = db.list_tasks(None, Some(&root_id)).await.expect("Should list branches");
     assert_eq!(branch_tasks.len(), 2, "Should have 2 branch tasks");
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_maintenance_on_file_database() {
+    // WAL checkpointing only reports real frame counts for file-backed databases
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("maintenance.db")).await
+        .expect("Failed to create file database");
+
+    // Generate some WAL traffic
+    sqlx::query("CREATE TABLE IF NOT EXISTS maintenance_probe (id INTEGER PRIMARY KEY, value TEXT)")
+        .execute(db.get_pool()).await.expect("Should create table");
+    for i in 0..50 {
+        sqlx::query("INSERT INTO maintenance_probe (value) VALUES (?1)")
+            .bind(format!("row {}", i))
+            .execute(db.get_pool()).await.expect("Should insert row");
+    }
+
+    let report = db.maintenance().await.expect("Maintenance should run");
+    assert!(report.errors.is_empty(), "Maintenance should not report errors: {:?}", report.errors);
+    assert!(report.analyze_performed, "ANALYZE should always run");
+    assert_eq!(report.integrity_ok, Some(true), "Fresh database should pass integrity check");
+    assert!(report.integrity_errors.is_empty());
+
+    let checkpoint = report.wal_checkpoint.expect("Checkpoint should have run");
+    assert_eq!(checkpoint.mode, WalCheckpointMode::Truncate);
+    assert!(!checkpoint.busy, "Checkpoint should not be blocked");
+    assert_eq!(checkpoint.log_frames, checkpoint.checkpointed_frames);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_maintenance_respects_config() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut db = Database::new(temp_dir.path().join("maintenance.db")).await
+        .expect("Failed to create file database");
+    db.configure_maintenance(MaintenanceConfig {
+        integrity_check_enabled: false,
+        wal_checkpoint_mode: WalCheckpointMode::Passive,
+        ..Default::default()
+    }).expect("Should configure maintenance");
+
+    let report = db.maintenance().await.expect("Maintenance should run");
+    assert!(report.errors.is_empty(), "Maintenance should not report errors: {:?}", report.errors);
+    assert_eq!(report.integrity_ok, None, "Integrity check should be skipped");
+    assert_eq!(report.pages_reclaimed, None, "Default databases don't use incremental auto_vacuum");
+    assert_eq!(report.wal_checkpoint.map(|c| c.mode), Some(WalCheckpointMode::Passive));
+
+    assert!(db.integrity_check().await.expect("Integrity check should run").is_empty());
+}
+
+#[tokio::test]
+async fn test_maintenance_schedule_start_stop() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("maintenance.db")).await
+        .expect("Failed to create file database");
+
+    // No interval configured: schedule is a no-op
+    db.start_maintenance_schedule(None).await.expect("Should accept missing interval");
+
+    assert!(db.start_maintenance_schedule(Some(std::time::Duration::ZERO)).await.is_err(),
+            "Zero interval should be rejected");
+
+    db.start_maintenance_schedule(Some(std::time::Duration::from_millis(20))).await
+        .expect("Should start schedule");
+    tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+    db.stop_maintenance_schedule().await;
+
+    assert!(db.is_pool_healthy().await, "Pool should stay healthy across scheduled maintenance");
+}
+
+async fn count_probe_rows(db: &Database) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM backup_probe")
+        .fetch_one(db.get_pool()).await.expect("Should count rows")
+}
+
+#[tokio::test]
+async fn test_backup_and_restore_roundtrip() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("live.db")).await
+        .expect("Failed to create file database");
+
+    sqlx::query("CREATE TABLE IF NOT EXISTS backup_probe (id INTEGER PRIMARY KEY, value TEXT)")
+        .execute(db.get_pool()).await.expect("Should create table");
+    for i in 0..10 {
+        sqlx::query("INSERT INTO backup_probe (value) VALUES (?1)")
+            .bind(format!("row {}", i))
+            .execute(db.get_pool()).await.expect("Should insert row");
+    }
+
+    let backup_path = temp_dir.path().join("backups").join("snapshot.db");
+    let backup = db.backup_to(&backup_path).await.expect("Backup should succeed");
+    assert_eq!(backup.path, backup_path);
+    assert!(backup.size_bytes > 0, "Backup file should not be empty");
+
+    // Changes after the backup are rolled back by the restore
+    sqlx::query("DELETE FROM backup_probe WHERE id > 3")
+        .execute(db.get_pool()).await.expect("Should delete rows");
+    assert_eq!(count_probe_rows(&db).await, 3);
+
+    db.restore_from(&backup_path).await.expect("Restore should succeed");
+    assert_eq!(count_probe_rows(&db).await, 10, "Restore should bring back deleted rows");
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_restore_rejects_invalid_backup() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("live.db")).await
+        .expect("Failed to create file database");
+
+    let missing = db.restore_from(temp_dir.path().join("missing.db")).await;
+    assert!(missing.is_err(), "Restoring from a missing file should fail");
+
+    let garbage_path = temp_dir.path().join("garbage.db");
+    std::fs::write(&garbage_path, b"definitely not a sqlite database").expect("Should write file");
+    assert!(db.restore_from(&garbage_path).await.is_err(), "Restoring from garbage should fail");
+    assert!(db.is_pool_healthy().await, "Failed restore should leave the database usable");
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_backup_config_validation() {
+    assert!(BackupConfig::new("/var/backups/bindery").validate().is_ok());
+    assert!(BackupConfig::new("relative/backups").validate().is_err());
+    assert!(BackupConfig { retain: 0, ..BackupConfig::new("/var/backups/bindery") }.validate().is_err());
+    assert!(BackupConfig { interval_seconds: 5, ..BackupConfig::new("/var/backups/bindery") }.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_backup_schedule_writes_backup() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("live.db")).await
+        .expect("Failed to create file database");
+    let backup_dir = temp_dir.path().join("scheduled");
+
+    // The first scheduled backup runs immediately
+    db.start_backup_schedule(BackupConfig { retain: 2, ..BackupConfig::new(&backup_dir) }).await
+        .expect("Should start backup schedule");
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    db.stop_backup_schedule().await;
+
+    let backups = db.list_backups(&backup_dir).await.expect("Should list backups");
+    assert_eq!(backups.len(), 1, "Exactly one backup should have been written");
+}
+
+#[tokio::test]
+async fn test_from_config_starts_configured_backups() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let backup_dir = temp_dir.path().join("configured");
+    let config = vespera_bindery::BinderyConfig {
+        database_path: Some(temp_dir.path().join("configured.db")),
+        backup: Some(BackupConfig::new(&backup_dir)),
+        ..Default::default()
+    };
+
+    let db = Database::from_config(&config).await.expect("Should open configured database");
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    db.stop_backup_schedule().await;
+
+    let backups = db.list_backups(&backup_dir).await.expect("Should list backups");
+    assert_eq!(backups.len(), 1, "The configured schedule should have written a backup");
+}
+
+#[tokio::test]
+async fn test_read_pool_is_read_only() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("pools.db")).await
+        .expect("Failed to create file database");
+    db.init_schema().await.expect("Should initialize schema");
+
+    let result = sqlx::query("INSERT INTO tasks (id, title, created_at, updated_at) VALUES ('x', 'x', 'now', 'now')")
+        .execute(&db.get_read_pool()).await;
+    assert!(result.is_err(), "Read pool must reject writes");
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_queries_routed_to_writer_and_reader_pools() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("pools.db")).await
+        .expect("Failed to create file database");
+    db.init_schema().await.expect("Should initialize schema");
+
+    let task_input = create_nested_task_input(0, "routing");
+    let task_id = db.create_task_with_depth(&task_input, 0).await.expect("Should create task");
+
+    // Writes committed on the writer connection are visible to readers
+    let tasks = db.list_tasks(None, None).await.expect("Should list tasks");
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, task_id);
+    db.get_task_dashboard(None).await.expect("Should build dashboard");
+
+    let writer = db.get_pool_metrics_for(PoolRole::Writer).await;
+    let reader = db.get_pool_metrics_for(PoolRole::Reader).await;
+    assert_eq!(writer.total_acquired, 1, "Only the insert should use the writer");
+    assert!(reader.total_acquired >= 2, "Listing and dashboard queries should use the read pool");
+    assert!(writer.total_connections <= 1, "Writer pool holds a single connection");
+    assert!(reader.total_connections <= db.get_pool_config().max_connections);
+
+    let aggregate = db.get_pool_metrics().await;
+    assert_eq!(aggregate.total_acquired, writer.total_acquired + reader.total_acquired);
+    assert!(db.is_pool_healthy().await);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_statement_stats_and_histogram() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("stats.db")).await
+        .expect("Failed to create file database");
+    db.init_schema().await.expect("Should initialize schema");
+
+    for _ in 0..3 {
+        db.list_tasks(None, None).await.expect("Should list tasks");
+    }
+    db.delete_task("missing").await.expect("Delete should succeed");
+
+    let metrics = db.get_query_performance_metrics().await;
+    let delete = metrics.statements.iter()
+        .find(|s| s.sql == "DELETE FROM tasks WHERE id = ?")
+        .expect("Delete statement should be tracked under its normalized SQL");
+    assert_eq!(delete.executions, 1);
+    assert_eq!(delete.errors, 0);
+    assert_eq!(delete.histogram.len(), QUERY_HISTOGRAM_BUCKETS_MS.len() + 1);
+    assert_eq!(delete.histogram.iter().sum::<u64>(), 1);
+
+    let list = metrics.statements.iter()
+        .find(|s| s.sql.starts_with("SELECT t.id, t.title"))
+        .expect("List statement should be tracked");
+    assert_eq!(list.executions, 3);
+    assert!(list.avg_duration_ms() <= list.max_duration_ms);
+
+    // 4 executions of 2 distinct statements: 2 reused a cached statement
+    assert!((metrics.cache_hit_rate - 50.0).abs() < f64::EPSILON);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_slow_query_plan_capture() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut db = Database::new(temp_dir.path().join("plans.db")).await
+        .expect("Failed to create file database");
+    db.init_schema().await.expect("Should initialize schema");
+
+    // A zero threshold makes every query "slow"
+    db.configure_query_metrics(QueryMetricsConfig {
+        slow_query_threshold_ms: 0,
+        capture_query_plans: true,
+        ..Default::default()
+    });
+
+    db.delete_task("missing").await.expect("Delete should succeed");
+
+    let plan = db.explain_query_plan("SELECT id FROM tasks WHERE status = ?").await
+        .expect("Should explain a parameterized query");
+    assert!(plan.iter().any(|line| line.contains("idx_tasks_status")),
+            "Plan should use the status index: {:?}", plan);
+
+    let metrics = db.get_query_performance_metrics().await;
+    let slow = metrics.slow_queries.iter()
+        .find(|q| q.query == "DELETE FROM tasks WHERE id = ?")
+        .expect("Delete should be logged as slow with a zero threshold");
+    assert!(slow.query_plan.is_some(), "Slow queries should carry a captured plan");
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_sqlite_storage_backend_through_trait() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("storage.db");
+
+    let storage = open_storage(&StorageConfig::default(), &db_path, &temp_dir.path().join("migrations")).await
+        .expect("Default storage should open SQLite");
+    assert_eq!(storage.dialect(), SqlDialect::Sqlite);
+
+    // Schema comes from the SQLite database itself
+    let db = Database::new(&db_path).await.expect("Failed to open database");
+    db.init_schema().await.expect("Should initialize schema");
+    db.close().await;
+
+    let task_id = storage.create_task(&create_nested_task_input(1, "Trait")).await
+        .expect("Should create task through the trait");
+    let roots = storage.list_tasks(None, None).await.expect("Should list tasks");
+    assert!(roots.iter().any(|t| t.id == task_id));
+    assert!(storage.update_task(&task_id, None, Some("done")).await.unwrap());
+
+    storage.create_codex("codex-1", "Notes", "vespera.templates.note", &serde_json::json!({})).await
+        .expect("Should create codex through the trait");
+    let codex = storage.get_codex("codex-1").await.unwrap().expect("Codex should exist");
+    assert_eq!(codex["title"], "Notes");
+    assert!(storage.delete_codex("codex-1").await.unwrap());
+    assert!(storage.health_check().await);
+
+    storage.close().await;
+}
+
+#[tokio::test]
+async fn test_storage_config_validation() {
+    assert!(StorageConfig::default().validate().is_ok());
+    assert!(StorageConfig { dialect: SqlDialect::Postgres, url: None }.validate().is_err());
+    assert!(StorageConfig::postgres("mysql://localhost/db").validate().is_err());
+
+    let postgres = StorageConfig::postgres("postgres://localhost/vespera");
+    assert_eq!(postgres.validate().is_ok(), cfg!(feature = "postgres"));
+}
+
+#[tokio::test]
+async fn test_task_time_series() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("series.db")).await
+        .expect("Failed to create file database");
+    db.init_schema().await.expect("Should initialize schema");
+
+    let task = |title: &str, project: &str, tags: &[&str]| TaskInput {
+        title: title.to_string(),
+        description: None,
+        priority: None,
+        project_id: Some(project.to_string()),
+        parent_id: None,
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        labels: serde_json::json!({}),
+        subtasks: vec![],
+    };
+    let a = db.create_task(&task("Schema", "p1", &["backend"])).await.unwrap();
+    let b = db.create_task(&task("Docs", "p1", &[])).await.unwrap();
+    let c = db.create_task(&task("API", "p2", &["backend"])).await.unwrap();
+
+    // Completing stamps completed_at, reopening clears it
+    assert!(db.update_task(&a, None, Some("done")).await.unwrap());
+    assert!(db.update_task(&b, None, Some("done")).await.unwrap());
+    assert!(db.update_task(&c, None, Some("done")).await.unwrap());
+    assert!(db.update_task(&c, None, Some("doing")).await.unwrap());
+    let stamped: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE completed_at IS NOT NULL")
+        .fetch_one(db.get_pool()).await.unwrap();
+    assert_eq!(stamped, 2);
+
+    for (id, created, completed) in [
+        (&a, "2025-12-31T10:00:00+00:00", Some("2026-01-02T10:00:00+00:00")),
+        (&b, "2026-01-01T08:00:00+00:00", Some("2026-01-01T20:00:00+00:00")),
+        (&c, "2026-01-03T00:00:00+00:00", None),
+    ] {
+        sqlx::query("UPDATE tasks SET created_at = ?1, completed_at = ?2 WHERE id = ?3")
+            .bind(created).bind(completed).bind(id)
+            .execute(db.get_pool()).await.unwrap();
+    }
+
+    let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+    let query = TimeSeriesQuery::new(day(1), day(5));
+    let series = db.get_task_time_series(&query).await.unwrap();
+    let counts: Vec<_> = series.days.iter().map(|d| (d.created, d.completed, d.remaining)).collect();
+    assert_eq!(series.days.first().unwrap().day, day(1));
+    assert_eq!(counts, vec![(1, 1, 1), (0, 1, 0), (1, 0, 1), (0, 0, 1), (0, 0, 1)]);
+    assert_eq!(series.cycle_time.samples, 2);
+    assert_eq!(series.cycle_time.p50_hours.map(f64::round), Some(12.0));
+    assert_eq!(series.cycle_time.p95_hours.map(f64::round), Some(48.0));
+
+    let project = db.get_task_time_series(&query.clone().with_project("p1")).await.unwrap();
+    let remaining: Vec<_> = project.days.iter().map(|d| d.remaining).collect();
+    assert_eq!(remaining, vec![1, 0, 0, 0, 0]);
+
+    let label = db.get_task_time_series(&query.clone().with_label("backend")).await.unwrap();
+    let counts: Vec<_> = label.days.iter().map(|d| (d.created, d.completed, d.remaining)).collect();
+    assert_eq!(counts, vec![(0, 0, 1), (0, 1, 0), (1, 0, 1), (0, 0, 1), (0, 0, 1)]);
+    assert_eq!(label.cycle_time.samples, 1);
+
+    assert!(db.get_task_time_series(&TimeSeriesQuery::new(day(5), day(1))).await.is_err());
+
+    let dashboard = db.get_task_dashboard_with_series(None, &query).await.unwrap();
+    assert_eq!(dashboard.average_completion_time.map(f64::round), Some(30.0));
+    assert_eq!(dashboard.time_series.unwrap().days.len(), 5);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_cursor_pagination() {
+    use futures::TryStreamExt;
+    use vespera_bindery::PageRequest;
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let db = Database::new(temp_dir.path().join("pages.db")).await
+        .expect("Failed to create file database");
+    db.init_schema().await.expect("Should initialize schema");
+
+    for i in 0..5 {
+        let id = uuid::Uuid::new_v4().to_string();
+        db.create_codex(&id, &format!("Codex {}", i), "default", &serde_json::json!({})).await.unwrap();
+    }
+    let root = db.create_task(&create_nested_task_input(0, "Root")).await.unwrap();
+    for i in 0..4 {
+        let mut input = create_nested_task_input(0, &format!("Child {}", i));
+        input.parent_id = Some(root.clone());
+        db.create_task(&input).await.unwrap();
+    }
+
+    // Pages never repeat or skip a codex, even when timestamps tie
+    let mut seen = Vec::new();
+    let mut request = PageRequest::first(2);
+    loop {
+        let page = db.list_codices_page(&request).await.unwrap();
+        assert!(page.items.len() <= 2);
+        seen.extend(page.items.iter().map(|codex| codex["id"].as_str().unwrap().to_string()));
+        match page.next_cursor {
+            Some(cursor) => request = PageRequest::after(cursor, 2),
+            None => break,
+        }
+    }
+    let all: Vec<String> = db.list_codices().await.unwrap().iter()
+        .map(|codex| codex["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(seen.len(), 5);
+    assert_eq!(seen.iter().collect::<std::collections::HashSet<_>>(), all.iter().collect());
+
+    let streamed: Vec<_> = db.stream_codices(3).try_collect().await.unwrap();
+    assert_eq!(streamed.len(), 5);
+
+    let first = db.list_tasks_page(Some(&root), &PageRequest::first(3)).await.unwrap();
+    assert_eq!(first.items.len(), 3);
+    let rest = db.list_tasks_page(Some(&root), &PageRequest::after(first.next_cursor.unwrap(), 3)).await.unwrap();
+    assert_eq!(rest.items.len(), 1);
+    assert!(rest.next_cursor.is_none());
+    let roots = db.list_tasks_page(None, &PageRequest::default()).await.unwrap();
+    assert_eq!(roots.items.len(), 1);
+    assert_eq!(roots.items[0].child_count, 4);
+
+    assert!(db.list_codices_page(&PageRequest::after("not a cursor", 2)).await.is_err());
+
+    db.close().await;
+}
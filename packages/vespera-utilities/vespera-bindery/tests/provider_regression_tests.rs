@@ -564,6 +564,7 @@ mod structured_type_tests {
             max_tokens: None,
             temperature: None,
             stop_sequences: None,
+            prompt: None,
         };
 
         let response = provider
@@ -588,6 +589,7 @@ mod structured_type_tests {
             max_tokens: Some(100),
             temperature: Some(0.7),
             stop_sequences: None,
+            prompt: None,
         };
 
         let response = provider
@@ -617,6 +619,7 @@ mod structured_type_tests {
             max_tokens: None,
             temperature: None,
             stop_sequences: None,
+            prompt: None,
         };
 
         let response = provider
@@ -640,6 +643,7 @@ mod structured_type_tests {
             max_tokens: None,
             temperature: None,
             stop_sequences: None,
+            prompt: None,
         };
 
         let streaming_response = provider
@@ -674,6 +678,7 @@ mod structured_type_tests {
             max_tokens: Some(50),
             temperature: Some(0.5),
             stop_sequences: None,
+            prompt: None,
         };
 
         let streaming_response = provider
@@ -707,6 +712,7 @@ mod structured_type_tests {
             max_tokens: None,
             temperature: None,
             stop_sequences: None,
+            prompt: None,
         };
 
         let response = provider
@@ -730,6 +736,7 @@ mod structured_type_tests {
             max_tokens: None,
             temperature: None,
             stop_sequences: None,
+            prompt: None,
         };
 
         let response = provider
@@ -834,9 +841,9 @@ mod capabilities_tests {
         let provider = OllamaProvider::new(config);
         let caps = provider.capabilities();
 
-        // Ollama supports streaming but not tools
+        // Ollama supports streaming, and tools natively via /api/chat
         assert!(caps.supports_streaming, "Ollama should support streaming");
-        assert!(!caps.supports_tools, "Ollama should not support tools");
+        assert!(caps.supports_tools, "Ollama should support tools");
         assert!(caps.supports_system_prompt, "Ollama should support system prompts");
         assert_eq!(caps.max_tokens, 4096); // From config
         assert_eq!(caps.max_context_length, 8192); // From context_window config